@@ -1,6 +1,7 @@
 use crate::hotkey::{AppState, CalibrationMode, SavedCalibration};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleFormat, StreamConfig};
+use flowwisper_core::audio::{AudioDevice, DeviceEnumerator};
 use hound::{SampleFormat as WavSampleFormat, WavSpec, WavWriter};
 use nnnoiseless::DenoiseState;
 use serde::Serialize;
@@ -822,6 +823,28 @@ pub fn list_devices() -> Result<Vec<DeviceSummary>, String> {
     }
 }
 
+/// cpal-backed device enumeration for the core hot-plug watcher. Ids match
+/// [`list_devices`] so they can be handed to the other device commands.
+pub struct CpalDeviceEnumerator;
+
+impl DeviceEnumerator for CpalDeviceEnumerator {
+    fn input_devices(&self) -> anyhow::Result<Vec<AudioDevice>> {
+        let host = cpal::default_host();
+        if host.input_devices()?.next().is_none() {
+            return Ok(Vec::new());
+        }
+        let devices = list_devices().map_err(anyhow::Error::msg)?;
+        Ok(devices
+            .into_iter()
+            .map(|device| AudioDevice {
+                id: device.id,
+                label: device.label,
+                is_default: device.preferred,
+            })
+            .collect())
+    }
+}
+
 pub fn run_device_check(
     app: &AppHandle,
     state: &AppState,
@@ -893,6 +916,17 @@ pub fn prime_waveform_bridge(
     Ok(())
 }
 
+/// 不推送电平事件的短时采集，返回处理后的样本与采样率，供自检使用。
+pub fn capture_probe(
+    device_id: Option<&str>,
+    duration: Duration,
+    frame_window: FrameWindowSetting,
+) -> Result<(Vec<f32>, u32), String> {
+    let (device, _label) = resolve_device(device_id)?;
+    let capture = capture_audio(&device, duration, None, frame_window)?;
+    Ok((capture.samples, capture.sample_rate))
+}
+
 pub fn calibrate_device(
     device_id: Option<&str>,
     state: &AppState,
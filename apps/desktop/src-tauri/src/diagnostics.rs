@@ -0,0 +1,113 @@
+//! 桌面端一键自检：为 core 自检流程提供权限、设备与采集探针。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use flowwisper_core::config::ConfigService;
+use flowwisper_core::orchestrator::{EngineConfig, EngineOrchestrator};
+use flowwisper_core::session::diagnostics::{
+    device_report, export_bundle, DiagnosticSources, DiagnosticsBundle, DiagnosticsError,
+    DiagnosticsRequest,
+};
+use flowwisper_core::session::self_check::{
+    run_self_check, CaptureSample, SelfCheckPlatform, SelfCheckReport, SelfCheckStep,
+    SelfCheckTargets,
+};
+
+use crate::audio::{
+    capture_probe, check_accessibility_permission, list_devices, FrameWindowSetting,
+};
+use crate::history;
+use crate::hotkey::AppState;
+
+pub struct DesktopSelfCheck {
+    microphone_granted: bool,
+    device_id: Option<String>,
+    frame_window: FrameWindowSetting,
+}
+
+impl DesktopSelfCheck {
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            microphone_granted: state.permission_status().microphone,
+            device_id: state.selected_microphone(),
+            frame_window: state.frame_window_mode(),
+        }
+    }
+}
+
+impl SelfCheckPlatform for DesktopSelfCheck {
+    fn check_permissions(&self) -> Result<String> {
+        let accessibility = check_accessibility_permission().map_err(|err| anyhow!(err))?;
+        let mut missing = Vec::new();
+        if !self.microphone_granted {
+            missing.push("microphone");
+        }
+        if !accessibility.granted {
+            missing.push("accessibility");
+        }
+        if missing.is_empty() {
+            Ok("microphone, accessibility granted".to_string())
+        } else {
+            Err(anyhow!("missing permissions: {}", missing.join(", ")))
+        }
+    }
+
+    fn enumerate_devices(&self) -> Result<Vec<String>> {
+        let devices = list_devices().map_err(|err| anyhow!(err))?;
+        Ok(devices.into_iter().map(|device| device.label).collect())
+    }
+
+    fn capture(&self, duration: Duration) -> Result<CaptureSample> {
+        let (samples, sample_rate_hz) =
+            capture_probe(self.device_id.as_deref(), duration, self.frame_window)
+                .map_err(|err| anyhow!(err))?;
+        Ok(CaptureSample {
+            samples,
+            sample_rate_hz,
+        })
+    }
+}
+
+/// 运行完整自检；引擎或数据库初始化失败时将对应项标记为失败而非跳过。
+pub async fn self_check(platform: DesktopSelfCheck) -> SelfCheckReport {
+    let orchestrator = EngineOrchestrator::new(EngineConfig {
+        prefer_cloud: false,
+    });
+    let database = history::sqlite();
+
+    let mut report = run_self_check(SelfCheckTargets {
+        platform: Some(Arc::new(platform)),
+        orchestrator: orchestrator.as_ref().ok(),
+        database: database.as_ref().ok().cloned(),
+    })
+    .await;
+
+    if let Err(err) = &orchestrator {
+        report.mark_failed(SelfCheckStep::EngineWarmup, err.to_string());
+    }
+    if let Err(err) = &database {
+        report.mark_failed(SelfCheckStep::Database, err.message().to_string());
+    }
+    report
+}
+
+/// 导出诊断包。桌面壳不持有会话管理器，诊断包中没有飞行记录，清单会注明。
+pub async fn export_diagnostics(
+    request: DiagnosticsRequest,
+) -> Result<DiagnosticsBundle, DiagnosticsError> {
+    if !request.consent {
+        return Err(DiagnosticsError::ConsentRequired);
+    }
+    let config = ConfigService::load_or_default(ConfigService::default_path()).current();
+    let sources = DiagnosticSources {
+        config: Some(config.as_ref().clone()),
+        device: Some(device_report()),
+        database: history::sqlite().ok(),
+        ..DiagnosticSources::default()
+    };
+    tauri::async_runtime::spawn_blocking(move || export_bundle(&request, &sources))
+        .await
+        .map_err(|err| DiagnosticsError::Other(anyhow!("diagnostics export task failed: {err}")))?
+}
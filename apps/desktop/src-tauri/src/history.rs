@@ -1,83 +1,193 @@
-use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration as StdDuration, SystemTime, UNIX_EPOCH};
 
-use dirs::data_dir;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use flowwisper_core::audio::SessionRecorder;
+use flowwisper_core::error::{ErrorCode, FlowwisperError};
 use flowwisper_core::persistence::sqlite::{
-    EnvKeyResolver, SqliteConfig, SqlitePath, SqlitePersistence,
+    RekeyStage, SecretStoreKeyResolver, SqliteConfig, SqlitePath, SqlitePersistence,
 };
+use flowwisper_core::secrets::{self, SecretStore};
 use flowwisper_core::session::history::{
-    AccuracyUpdate, HistoryActionKind, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery,
+    AccuracyUpdate, ExportRequest, ExportService, ExportSummary, HistoryActionKind, HistoryEntry,
+    HistoryPage, HistoryPostAction, HistoryQuery, ImportSource, ImportSummary,
 };
+use flowwisper_core::session::workspace;
 use once_cell::sync::OnceCell;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tauri::async_runtime;
+use tauri::{async_runtime, AppHandle, Emitter};
 
 static SQLITE: OnceCell<Arc<SqlitePersistence>> = OnceCell::new();
 
-fn resolve_config() -> Result<SqliteConfig, String> {
-    let base_dir = env::var("FLOWWISPER_DATA_DIR")
-        .map(PathBuf::from)
-        .or_else(|_| {
-            data_dir()
-                .map(|dir| dir.join("Flowwisper"))
-                .ok_or_else(|| "无法定位历史数据库目录".to_string())
-        })?;
+/// 与核心服务相同的配置档数据目录及其密钥库。
+fn resolve_data_dir() -> Result<PathBuf, FlowwisperError> {
+    workspace::active_data_dir()
+        .map_err(|err| FlowwisperError::Persistence(format!("无法定位历史数据库目录: {err:#}")))
+}
+
+fn open_secret_store(data_dir: &Path) -> Result<Arc<dyn SecretStore>, FlowwisperError> {
+    let profile = workspace::active_profile()
+        .map_err(|err| FlowwisperError::Persistence(format!("无法确定配置档: {err:#}")))?;
+    secrets::default_store(data_dir, &profile)
+        .map_err(|err| FlowwisperError::Persistence(format!("无法打开密钥库: {err}")))
+}
 
-    fs::create_dir_all(&base_dir).map_err(|err| format!("无法创建数据目录 {base_dir:?}: {err}"))?;
+fn resolve_config() -> Result<SqliteConfig, FlowwisperError> {
+    let base_dir = resolve_data_dir()?;
+
+    fs::create_dir_all(&base_dir).map_err(|err| {
+        FlowwisperError::Persistence(format!("无法创建数据目录 {base_dir:?}: {err}"))
+    })?;
 
     let db_path = base_dir.join("history.db");
+    let store = open_secret_store(&base_dir)?;
+    let profile = workspace::active_profile()
+        .map_err(|err| FlowwisperError::Persistence(format!("无法确定配置档: {err:#}")))?;
     Ok(SqliteConfig {
         path: SqlitePath::File(db_path),
         pool_size: 8,
         busy_timeout: StdDuration::from_millis(250),
-        key_resolver: Arc::new(EnvKeyResolver::default()),
+        key_resolver: Arc::new(SecretStoreKeyResolver::for_profile(store, &profile)),
     })
 }
 
-fn sqlite() -> Result<Arc<SqlitePersistence>, String> {
+pub(crate) fn sqlite() -> Result<Arc<SqlitePersistence>, FlowwisperError> {
     SQLITE
         .get_or_try_init(|| {
             let config = resolve_config()?;
             SqlitePersistence::bootstrap(config)
                 .map(Arc::new)
-                .map_err(|err| err.to_string())
+                .map_err(|err| FlowwisperError::classify(&err, ErrorCode::Persistence))
         })
         .map(|arc| arc.clone())
 }
 
-pub async fn search_history(query: HistoryQuery) -> Result<HistoryPage, String> {
+fn join_failed(err: impl std::fmt::Display) -> FlowwisperError {
+    FlowwisperError::Internal(err.to_string())
+}
+
+fn persistence_failed(err: anyhow::Error) -> FlowwisperError {
+    FlowwisperError::classify(&err, ErrorCode::Persistence)
+}
+
+pub async fn search_history(query: HistoryQuery) -> Result<HistoryPage, FlowwisperError> {
     let sqlite = sqlite()?;
     async_runtime::spawn_blocking(move || sqlite.search_sessions(&query))
         .await
-        .map_err(|err| err.to_string())?
-        .map_err(|err| err.to_string())
+        .map_err(join_failed)?
+        .map_err(persistence_failed)
 }
 
-pub async fn load_history(session_id: String) -> Result<Option<HistoryEntry>, String> {
+pub async fn load_history(session_id: String) -> Result<Option<HistoryEntry>, FlowwisperError> {
     let sqlite = sqlite()?;
     async_runtime::spawn_blocking(move || sqlite.load_session(&session_id))
         .await
-        .map_err(|err| err.to_string())?
-        .map_err(|err| err.to_string())
+        .map_err(join_failed)?
+        .map_err(persistence_failed)
+}
+
+/// 历史记录的会话录音，WAV 以 base64 编码供前端播放。
+#[derive(Debug, Serialize)]
+pub struct HistoryAudio {
+    pub session_id: String,
+    pub sample_rate_hz: u32,
+    pub duration_ms: u64,
+    pub wav_base64: String,
+}
+
+pub async fn load_session_audio(
+    session_id: String,
+) -> Result<Option<HistoryAudio>, FlowwisperError> {
+    let data_dir = resolve_data_dir()?;
+    async_runtime::spawn_blocking(move || {
+        let store = open_secret_store(&data_dir)?;
+        let recorder = SessionRecorder::open(&data_dir, store.as_ref())
+            .map_err(|err| FlowwisperError::Audio(format!("无法打开会话录音: {err:#}")))?;
+        if !recorder.archive_path(&session_id).exists() {
+            return Ok(None);
+        }
+        let audio = recorder
+            .load(&session_id)
+            .map_err(|err| FlowwisperError::Audio(format!("无法读取会话录音: {err:#}")))?;
+        Ok(Some(HistoryAudio {
+            session_id: audio.session_id.clone(),
+            sample_rate_hz: audio.sample_rate_hz,
+            duration_ms: audio.duration().as_millis() as u64,
+            wav_base64: BASE64.encode(audio.to_wav_bytes()),
+        }))
+    })
+    .await
+    .map_err(join_failed)?
 }
 
-pub async fn mark_accuracy(update: AccuracyUpdate) -> Result<(), String> {
+pub async fn export_history(request: ExportRequest) -> Result<ExportSummary, FlowwisperError> {
+    let sqlite = sqlite()?;
+    async_runtime::spawn_blocking(move || {
+        let entries = sqlite.export_sessions(&request.selection)?;
+        ExportService::new(request.format, request.fields)
+            .with_passphrase(request.passphrase)
+            .write(&entries, &request.destination)
+    })
+    .await
+    .map_err(join_failed)?
+    .map_err(persistence_failed)
+}
+
+pub async fn import_history(source: ImportSource) -> Result<ImportSummary, FlowwisperError> {
+    let sqlite = sqlite()?;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+    async_runtime::spawn_blocking(move || sqlite.import_from(&source, now_ms))
+        .await
+        .map_err(join_failed)?
+        .map_err(persistence_failed)
+}
+
+const KEY_ROTATION_EVENT: &str = "history://key-rotation";
+
+/// 以新密钥重新加密历史库，每个阶段向前端发送 `history://key-rotation` 事件。
+pub async fn rotate_key(app: AppHandle, new_key: String) -> Result<(), FlowwisperError> {
+    let sqlite = sqlite()?;
+    async_runtime::spawn_blocking(move || {
+        sqlite.rekey(&new_key, &mut |stage: RekeyStage| {
+            let _ = app.emit(KEY_ROTATION_EVENT, stage);
+        })
+    })
+    .await
+    .map_err(join_failed)?
+    .map_err(persistence_failed)
+}
+
+pub async fn mark_accuracy(update: AccuracyUpdate) -> Result<(), FlowwisperError> {
     let sqlite = sqlite()?;
     async_runtime::spawn_blocking(move || sqlite.update_accuracy(&update))
         .await
-        .map_err(|err| err.to_string())?
-        .map_err(|err| err.to_string())
+        .map_err(join_failed)?
+        .map_err(persistence_failed)
+}
+
+pub async fn set_pinned(session_id: String, pinned: bool) -> Result<(), FlowwisperError> {
+    let sqlite = sqlite()?;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+    async_runtime::spawn_blocking(move || sqlite.set_pinned(&session_id, pinned, now_ms))
+        .await
+        .map_err(join_failed)?
+        .map_err(persistence_failed)
 }
 
 pub async fn append_action(
     session_id: String,
     kind: HistoryActionKind,
     detail: Option<Value>,
-) -> Result<Vec<HistoryPostAction>, String> {
+) -> Result<Vec<HistoryPostAction>, FlowwisperError> {
     let sqlite = sqlite()?;
     let timestamp_ms = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -91,8 +201,8 @@ pub async fn append_action(
 
     async_runtime::spawn_blocking(move || sqlite.append_post_action(&session_id, &action))
         .await
-        .map_err(|err| err.to_string())?
-        .map_err(|err| err.to_string())
+        .map_err(join_failed)?
+        .map_err(persistence_failed)
 }
 
 #[derive(Debug, Deserialize)]
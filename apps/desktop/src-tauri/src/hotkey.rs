@@ -1,11 +1,12 @@
 use crate::audio::FrameWindowSetting;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use flowwisper_core::audio::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+use flowwisper_core::hotkey;
 use rand::{rngs::OsRng, RngCore};
-use ring::{aead, hkdf, hmac};
+use ring::hmac;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    convert::TryInto,
     fs,
     fs::OpenOptions,
     io::{ErrorKind, Write},
@@ -14,15 +15,9 @@ use std::{
     time::{Duration, SystemTime},
 };
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-use std::collections::HashSet;
-
-#[cfg(any(target_os = "macos", target_os = "windows"))]
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 use crate::native_probe;
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-use rdev::{listen, EventType, Key};
-
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
@@ -39,11 +34,20 @@ impl Default for HotkeySource {
     }
 }
 
+/// 主热键之外的命名快捷键，如取消会话、撤销上次插入。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HotkeyActionBinding {
+    pub action: hotkey::HotkeyAction,
+    pub combination: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HotkeyBinding {
     pub combination: String,
     pub source: HotkeySource,
     pub reason: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<HotkeyActionBinding>,
 }
 
 impl Default for HotkeyBinding {
@@ -52,6 +56,7 @@ impl Default for HotkeyBinding {
             combination: "Fn".into(),
             source: HotkeySource::Fn,
             reason: None,
+            actions: Vec::new(),
         }
     }
 }
@@ -73,6 +78,9 @@ pub struct HotkeyConfigPayload {
     pub combination: String,
     pub source: HotkeySource,
     pub reason: Option<String>,
+    /// 为空时不序列化，早期签名的配置仍能通过校验。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<HotkeyActionBinding>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,13 +89,7 @@ pub struct HotkeyConfigEnvelope {
     pub signature: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SampleEnvelope {
-    pub version: u8,
-    pub nonce: String,
-    pub ciphertext: String,
-    pub signature: String,
-}
+pub type SampleEnvelope = SealedEnvelope;
 
 #[derive(Debug, Clone)]
 pub struct SealedSample {
@@ -95,11 +97,7 @@ pub struct SealedSample {
     pub path: PathBuf,
 }
 
-const SAMPLE_ENVELOPE_VERSION: u8 = 1;
 const SAMPLE_ENVELOPE_AAD: &[u8] = b"device-sample";
-const AUDIO_KEY_SALT: &[u8] = b"flowwisper.audio.cache.salt.v1";
-const AUDIO_ENCRYPTION_INFO: &[u8] = b"flowwisper.audio.cache.enc.v1";
-const AUDIO_HMAC_INFO: &[u8] = b"flowwisper.audio.cache.hmac.v1";
 const SAMPLE_RETENTION_SECS: u64 = 60 * 60 * 24; // 24 小时窗口
 const SAMPLE_RETENTION_CAPACITY: usize = 5; // 最近 5 份诊断样本
 
@@ -245,22 +243,6 @@ pub struct OnboardingConfigEnvelope {
     pub signature: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct AudioCacheKeys {
-    encryption: [u8; 32],
-    hmac: [u8; 32],
-}
-
-impl AudioCacheKeys {
-    fn encryption_key(&self) -> &[u8; 32] {
-        &self.encryption
-    }
-
-    fn signing_key(&self) -> &[u8; 32] {
-        &self.hmac
-    }
-}
-
 impl AppState {
     pub fn new(config_path: PathBuf, hmac_key: Vec<u8>, binding: HotkeyBinding) -> Self {
         let probe_log_path = config_path
@@ -300,6 +282,7 @@ impl AppState {
             combination: binding.combination.clone(),
             source: binding.source,
             reason: binding.reason.clone(),
+            actions: binding.actions.clone(),
         };
         let signature = sign_payload(&self.hmac_key, &payload)?;
         let envelope = HotkeyConfigEnvelope { payload, signature };
@@ -650,75 +633,13 @@ impl AppState {
     }
 
     fn seal_sample_payload(&self, wav_bytes: &[u8]) -> Result<SampleEnvelope, String> {
-        let mut nonce = [0u8; 12];
-        OsRng
-            .try_fill_bytes(&mut nonce)
-            .map_err(|err| format!("failed to generate sample nonce: {err}"))?;
-        let key = aead::UnboundKey::new(&aead::AES_256_GCM, self.audio_keys.encryption_key())
-            .map_err(|_| "invalid sample encryption key material".to_string())?;
-        let key = aead::LessSafeKey::new(key);
-        let mut buffer = wav_bytes.to_vec();
-        buffer.reserve(aead::AES_256_GCM.tag_len());
-        key.seal_in_place_append_tag(
-            aead::Nonce::assume_unique_for_key(nonce),
-            aead::Aad::from(SAMPLE_ENVELOPE_AAD),
-            &mut buffer,
-        )
-        .map_err(|_| "failed to seal sample payload".to_string())?;
-        let mut signed = Vec::with_capacity(nonce.len() + buffer.len());
-        signed.extend_from_slice(&nonce);
-        signed.extend_from_slice(&buffer);
-        let signing_key = hmac::Key::new(hmac::HMAC_SHA256, self.audio_keys.signing_key());
-        let signature = hmac::sign(&signing_key, &signed);
-        Ok(SampleEnvelope {
-            version: SAMPLE_ENVELOPE_VERSION,
-            nonce: BASE64.encode(nonce),
-            ciphertext: BASE64.encode(buffer),
-            signature: BASE64.encode(signature.as_ref()),
-        })
+        seal_payload(&self.audio_keys, SAMPLE_ENVELOPE_AAD, wav_bytes)
+            .map_err(|err| err.to_string())
     }
 
     fn open_sample_payload(&self, envelope: SampleEnvelope) -> Result<Vec<u8>, String> {
-        if envelope.version != SAMPLE_ENVELOPE_VERSION {
-            return Err(format!(
-                "unsupported sample envelope version: {}",
-                envelope.version
-            ));
-        }
-        let nonce_bytes = BASE64
-            .decode(envelope.nonce.as_bytes())
-            .map_err(|err| format!("failed to decode sample nonce: {err}"))?;
-        if nonce_bytes.len() != 12 {
-            return Err("invalid sample nonce length".into());
-        }
-        let ciphertext = BASE64
-            .decode(envelope.ciphertext.as_bytes())
-            .map_err(|err| format!("failed to decode sample ciphertext: {err}"))?;
-        let signature = BASE64
-            .decode(envelope.signature.as_bytes())
-            .map_err(|err| format!("failed to decode sample signature: {err}"))?;
-        let mut signed = Vec::with_capacity(nonce_bytes.len() + ciphertext.len());
-        signed.extend_from_slice(&nonce_bytes);
-        signed.extend_from_slice(&ciphertext);
-        let signing_key = hmac::Key::new(hmac::HMAC_SHA256, self.audio_keys.signing_key());
-        hmac::verify(&signing_key, &signed, &signature)
-            .map_err(|_| "sample signature mismatch".to_string())?;
-        let nonce: [u8; 12] = nonce_bytes
-            .as_slice()
-            .try_into()
-            .map_err(|_| "invalid nonce length".to_string())?;
-        let key = aead::UnboundKey::new(&aead::AES_256_GCM, self.audio_keys.encryption_key())
-            .map_err(|_| "invalid sample encryption key material".to_string())?;
-        let key = aead::LessSafeKey::new(key);
-        let mut buffer = ciphertext;
-        let decrypted = key
-            .open_in_place(
-                aead::Nonce::assume_unique_for_key(nonce),
-                aead::Aad::from(SAMPLE_ENVELOPE_AAD),
-                &mut buffer,
-            )
-            .map_err(|_| "failed to decrypt sample payload".to_string())?;
-        Ok(decrypted.to_vec())
+        open_payload(&self.audio_keys, SAMPLE_ENVELOPE_AAD, &envelope)
+            .map_err(|err| err.to_string())
     }
 
     fn cleanup_samples(&self) -> Result<SampleCleanupStats, String> {
@@ -801,29 +722,7 @@ impl AppState {
 }
 
 fn derive_audio_cache_keys(master: &[u8]) -> Result<AudioCacheKeys, String> {
-    if master.len() < 32 {
-        return Err("master key material must be at least 32 bytes".into());
-    }
-    let salt = hkdf::Salt::new(hkdf::HKDF_SHA256, AUDIO_KEY_SALT);
-    let prk = salt.extract(master);
-
-    let mut encryption = [0u8; 32];
-    let mut signing = [0u8; 32];
-
-    prk.expand(&[AUDIO_ENCRYPTION_INFO], hkdf::HKDF_SHA256)
-        .map_err(|_| "failed to derive audio encryption key".to_string())?
-        .fill(&mut encryption)
-        .map_err(|_| "failed to fill audio encryption key".to_string())?;
-
-    prk.expand(&[AUDIO_HMAC_INFO], hkdf::HKDF_SHA256)
-        .map_err(|_| "failed to derive audio signing key".to_string())?
-        .fill(&mut signing)
-        .map_err(|_| "failed to fill audio signing key".to_string())?;
-
-    Ok(AudioCacheKeys {
-        encryption,
-        hmac: signing,
-    })
+    AudioCacheKeys::derive(master).map_err(|err| err.to_string())
 }
 
 fn current_timestamp_ms() -> u128 {
@@ -847,6 +746,7 @@ impl From<HotkeyConfigPayload> for HotkeyBinding {
             combination: value.combination,
             source: value.source,
             reason: value.reason,
+            actions: value.actions,
         }
     }
 }
@@ -1006,23 +906,13 @@ pub fn load_hotkey_config(path: &Path, key: &[u8]) -> Option<HotkeyBinding> {
 pub struct HotkeyCompatibilityLayer;
 
 impl HotkeyCompatibilityLayer {
-    pub const RESERVED: &'static [&'static str] = &[
-        "Ctrl+Alt+Delete",
-        "Ctrl+Shift+Esc",
-        "Alt+F4",
-        "Alt+Tab",
-        "Cmd+Q",
-        "Cmd+Option+Esc",
-        "Cmd+Space",
-        "Win+L",
-        "Win+Space",
-    ];
-
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub const RESERVED: &'static [&'static str] = hotkey::RESERVED;
+
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
     const SLA: Duration = Duration::from_millis(400);
 
     pub fn probe_fn() -> FnProbeResult {
-        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
         {
             if let Ok(value) = std::env::var("FLOWWISPER_SIMULATE_FN") {
                 if value.eq_ignore_ascii_case("supported") {
@@ -1087,7 +977,7 @@ impl HotkeyCompatibilityLayer {
             }
         }
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         {
             FnProbeResult {
                 supported: false,
@@ -1103,53 +993,19 @@ impl HotkeyCompatibilityLayer {
     }
 
     pub fn capture_custom(timeout: Duration) -> Result<String, String> {
-        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
         {
-            use std::sync::mpsc;
-            use std::thread;
-
-            let (tx, rx) = mpsc::channel();
-            thread::spawn(move || {
-                let mut modifiers: HashSet<Key> = HashSet::new();
-                let sender = tx.clone();
-                let _ = std::panic::catch_unwind(move || {
-                    let _ = listen(move |event| match event.event_type {
-                        EventType::KeyPress(key) => {
-                            if Self::is_modifier_key(key) {
-                                modifiers.insert(key);
-                                return;
-                            }
-                            if key == Key::Function {
-                                let _ = sender
-                                    .send(CaptureSignal::Error("Fn 键无法作为备用组合主键".into()));
-                                panic!("stop-listener");
-                            }
-                            if let Some(combo) = Self::build_combination(&modifiers, key) {
-                                let _ = sender.send(CaptureSignal::Captured(combo));
-                            } else {
-                                let _ =
-                                    sender.send(CaptureSignal::Error("未识别的按键组合".into()));
-                            }
-                            panic!("stop-listener");
-                        }
-                        EventType::KeyRelease(key) => {
-                            if Self::is_modifier_key(key) {
-                                modifiers.remove(&key);
-                            }
-                        }
-                        _ => {}
-                    });
-                });
-            });
-
-            match rx.recv_timeout(timeout) {
-                Ok(CaptureSignal::Captured(combo)) => Ok(combo),
-                Ok(CaptureSignal::Error(reason)) => Err(reason),
-                Err(_) => Err("等待组合键超时，请重试".into()),
+            let backend = hotkey::default_backend().map_err(|err| err.to_string())?;
+            let combination = hotkey::capture_combination(backend.as_ref(), timeout)
+                .map_err(|err| err.to_string())?;
+            #[cfg(target_os = "linux")]
+            if let Some(shortcut) = hotkey::EvdevBackend::default().desktop_conflict(&combination) {
+                return Err(format!("{shortcut} 已被桌面环境占用，请换一个组合"));
             }
+            Ok(combination.to_string())
         }
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         {
             let _ = timeout;
             Err("当前平台未提供热键捕获能力".into())
@@ -1160,160 +1016,27 @@ impl HotkeyCompatibilityLayer {
         Self::RESERVED.iter().map(|item| item.to_string()).collect()
     }
 
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    fn is_modifier_key(key: Key) -> bool {
-        matches!(
-            key,
-            Key::ShiftLeft
-                | Key::ShiftRight
-                | Key::ControlLeft
-                | Key::ControlRight
-                | Key::Alt
-                | Key::AltGr
-                | Key::MetaLeft
-                | Key::MetaRight
-        )
-    }
-
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    fn describe_key(key: Key) -> Option<String> {
-        match key {
-            Key::Space => Some("Space".into()),
-            Key::Escape => Some("Esc".into()),
-            Key::Tab => Some("Tab".into()),
-            Key::Return => Some("Enter".into()),
-            Key::Backspace => Some("Backspace".into()),
-            Key::UpArrow => Some("ArrowUp".into()),
-            Key::DownArrow => Some("ArrowDown".into()),
-            Key::LeftArrow => Some("ArrowLeft".into()),
-            Key::RightArrow => Some("ArrowRight".into()),
-            Key::Home => Some("Home".into()),
-            Key::End => Some("End".into()),
-            Key::PageUp => Some("PageUp".into()),
-            Key::PageDown => Some("PageDown".into()),
-            Key::CapsLock => Some("CapsLock".into()),
-            Key::Delete => Some("Delete".into()),
-            Key::Insert => Some("Insert".into()),
-            Key::F1 => Some("F1".into()),
-            Key::F2 => Some("F2".into()),
-            Key::F3 => Some("F3".into()),
-            Key::F4 => Some("F4".into()),
-            Key::F5 => Some("F5".into()),
-            Key::F6 => Some("F6".into()),
-            Key::F7 => Some("F7".into()),
-            Key::F8 => Some("F8".into()),
-            Key::F9 => Some("F9".into()),
-            Key::F10 => Some("F10".into()),
-            Key::F11 => Some("F11".into()),
-            Key::F12 => Some("F12".into()),
-            Key::Num0 => Some("0".into()),
-            Key::Num1 => Some("1".into()),
-            Key::Num2 => Some("2".into()),
-            Key::Num3 => Some("3".into()),
-            Key::Num4 => Some("4".into()),
-            Key::Num5 => Some("5".into()),
-            Key::Num6 => Some("6".into()),
-            Key::Num7 => Some("7".into()),
-            Key::Num8 => Some("8".into()),
-            Key::Num9 => Some("9".into()),
-            Key::KeyA => Some("A".into()),
-            Key::KeyB => Some("B".into()),
-            Key::KeyC => Some("C".into()),
-            Key::KeyD => Some("D".into()),
-            Key::KeyE => Some("E".into()),
-            Key::KeyF => Some("F".into()),
-            Key::KeyG => Some("G".into()),
-            Key::KeyH => Some("H".into()),
-            Key::KeyI => Some("I".into()),
-            Key::KeyJ => Some("J".into()),
-            Key::KeyK => Some("K".into()),
-            Key::KeyL => Some("L".into()),
-            Key::KeyM => Some("M".into()),
-            Key::KeyN => Some("N".into()),
-            Key::KeyO => Some("O".into()),
-            Key::KeyP => Some("P".into()),
-            Key::KeyQ => Some("Q".into()),
-            Key::KeyR => Some("R".into()),
-            Key::KeyS => Some("S".into()),
-            Key::KeyT => Some("T".into()),
-            Key::KeyU => Some("U".into()),
-            Key::KeyV => Some("V".into()),
-            Key::KeyW => Some("W".into()),
-            Key::KeyX => Some("X".into()),
-            Key::KeyY => Some("Y".into()),
-            Key::KeyZ => Some("Z".into()),
-            Key::Minus => Some("-".into()),
-            Key::Equal => Some("=".into()),
-            Key::LeftBracket => Some("[".into()),
-            Key::RightBracket => Some("]".into()),
-            Key::SemiColon => Some(";".into()),
-            Key::Quote => Some("'".into()),
-            Key::Comma => Some(",".into()),
-            Key::Dot => Some(".".into()),
-            Key::Slash => Some("/".into()),
-            Key::BackSlash => Some("\\".into()),
-            Key::BackQuote => Some("`".into()),
-            Key::Function => Some("Fn".into()),
-            Key::PrintScreen => Some("PrintScreen".into()),
-            Key::ScrollLock => Some("ScrollLock".into()),
-            Key::Pause => Some("Pause".into()),
-            Key::NumLock => Some("NumLock".into()),
-            Key::KpDivide => Some("Kp/".into()),
-            Key::KpMultiply => Some("Kp*".into()),
-            Key::KpMinus => Some("Kp-".into()),
-            Key::KpPlus => Some("Kp+".into()),
-            Key::KpReturn => Some("KpEnter".into()),
-            Key::Kp0 => Some("NumPad0".into()),
-            Key::Kp1 => Some("NumPad1".into()),
-            Key::Kp2 => Some("NumPad2".into()),
-            Key::Kp3 => Some("NumPad3".into()),
-            Key::Kp4 => Some("NumPad4".into()),
-            Key::Kp5 => Some("NumPad5".into()),
-            Key::Kp6 => Some("NumPad6".into()),
-            Key::Kp7 => Some("NumPad7".into()),
-            Key::Kp8 => Some("NumPad8".into()),
-            Key::Kp9 => Some("NumPad9".into()),
-            Key::KpDelete => Some("NumPadDel".into()),
-            Key::Unknown(code) => Some(format!("Keycode({code})")),
-            _ => None,
-        }
-    }
-
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    fn build_combination(modifiers: &HashSet<Key>, primary: Key) -> Option<String> {
-        let mut parts = Vec::new();
-        if modifiers.contains(&Key::ControlLeft) || modifiers.contains(&Key::ControlRight) {
-            parts.push("Ctrl".to_string());
-        }
-        if modifiers.contains(&Key::Alt) || modifiers.contains(&Key::AltGr) {
-            parts.push("Alt".to_string());
-        }
-        if modifiers.contains(&Key::ShiftLeft) || modifiers.contains(&Key::ShiftRight) {
-            parts.push("Shift".to_string());
-        }
-        if modifiers.contains(&Key::MetaLeft) || modifiers.contains(&Key::MetaRight) {
-            #[cfg(target_os = "macos")]
-            let label = "Cmd";
-            #[cfg(not(target_os = "macos"))]
-            let label = "Win";
-            parts.push(label.to_string());
-        }
-
-        if let Some(primary_label) = Self::describe_key(primary) {
-            parts.push(primary_label);
-            Some(parts.join("+"))
-        } else {
-            None
+    /// 逐项检查命名快捷键：无法解析、与系统保留组合、主热键或其他命名快捷键重复时返回说明。
+    pub fn action_conflicts(
+        trigger: &str,
+        actions: &[HotkeyActionBinding],
+    ) -> Vec<(hotkey::HotkeyAction, String)> {
+        let mut conflicts = Vec::new();
+        let mut parsed = Vec::new();
+        for binding in actions {
+            match binding.combination.parse::<hotkey::HotkeyCombination>() {
+                Ok(combination) => parsed.push((binding.action, combination)),
+                Err(err) => conflicts.push((binding.action, err.to_string())),
+            }
         }
+        let trigger = trigger
+            .parse::<hotkey::HotkeyCombination>()
+            .unwrap_or_else(|_| hotkey::HotkeyCombination::fn_key());
+        conflicts.extend(hotkey::action_conflicts(&trigger, &parsed));
+        conflicts
     }
 }
 
-#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
-enum CaptureSignal {
-    Captured(String),
-    Error(String),
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1333,6 +1056,7 @@ mod tests {
             combination: "Ctrl+Shift+F".into(),
             source: HotkeySource::Custom,
             reason: Some("fallback".into()),
+            actions: Vec::new(),
         };
         let signature = sign_payload(&key, &payload).expect("signing should succeed");
         let envelope = HotkeyConfigEnvelope {
@@ -1354,6 +1078,7 @@ mod tests {
             combination: "Fn".into(),
             source: HotkeySource::Fn,
             reason: None,
+            actions: Vec::new(),
         };
         let signature = sign_payload(&key, &payload).expect("signing should succeed");
         let mut envelope = HotkeyConfigEnvelope { payload, signature };
@@ -1373,6 +1098,7 @@ mod tests {
             combination: "Ctrl+Alt+Space".into(),
             source: HotkeySource::Custom,
             reason: Some("User opted for fallback".into()),
+            actions: Vec::new(),
         };
 
         state
@@ -1387,6 +1113,58 @@ mod tests {
         assert_eq!(verified.reason, binding.reason);
     }
 
+    #[test]
+    fn action_bindings_are_signed_and_checked_for_conflicts() {
+        let key = sample_key(4);
+        let legacy = HotkeyConfigPayload {
+            combination: "Fn".into(),
+            source: HotkeySource::Fn,
+            reason: None,
+            actions: Vec::new(),
+        };
+        // 没有命名快捷键时序列化结果与旧版一致，旧签名继续有效。
+        let legacy_json = serde_json::to_value(&legacy).expect("serialize");
+        assert!(legacy_json.get("actions").is_none());
+
+        let actions = vec![
+            HotkeyActionBinding {
+                action: hotkey::HotkeyAction::CancelSession,
+                combination: "Ctrl+Esc".into(),
+            },
+            HotkeyActionBinding {
+                action: hotkey::HotkeyAction::UndoPublish,
+                combination: "Ctrl+Alt+Z".into(),
+            },
+        ];
+        let payload = HotkeyConfigPayload {
+            actions: actions.clone(),
+            ..legacy
+        };
+        let signature = sign_payload(&key, &payload).expect("signing should succeed");
+        let mut envelope = HotkeyConfigEnvelope { payload, signature };
+        let binding = verify_envelope(&key, envelope.clone()).expect("verification should pass");
+        assert_eq!(binding.actions, actions);
+        envelope.payload.actions[1].combination = "Ctrl+Z".into();
+        assert!(verify_envelope(&key, envelope).is_err());
+
+        assert!(HotkeyCompatibilityLayer::action_conflicts("Fn", &actions).is_empty());
+        let conflicts = HotkeyCompatibilityLayer::action_conflicts(
+            "Ctrl+Esc",
+            &[
+                actions[0].clone(),
+                HotkeyActionBinding {
+                    action: hotkey::HotkeyAction::RepolishLast,
+                    combination: "Hyper+R".into(),
+                },
+                HotkeyActionBinding {
+                    action: hotkey::HotkeyAction::UndoPublish,
+                    combination: "Alt+F4".into(),
+                },
+            ],
+        );
+        assert_eq!(conflicts.len(), 3);
+    }
+
     #[test]
     fn onboarding_preferences_are_signed_and_verified() {
         let temp = tempdir().expect("tempdir");
@@ -1640,6 +1418,7 @@ mod tests {
             combination: "Fn".into(),
             source: HotkeySource::Fn,
             reason: None,
+            actions: Vec::new(),
         };
         let envelope = HotkeyConfigEnvelope {
             signature: sign_payload(&key, &payload).expect("sign"),
@@ -1685,7 +1464,7 @@ mod tests {
         assert!(conflicts.iter().all(|item| !item.is_empty()));
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     #[test]
     fn probe_fn_reports_platform_gap() {
         let result = HotkeyCompatibilityLayer::probe_fn();
@@ -1696,6 +1475,16 @@ mod tests {
         assert!(result.within_sla.is_none());
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn probe_fn_honours_simulation_on_linux() {
+        std::env::set_var("FLOWWISPER_SIMULATE_FN", "unsupported");
+        let result = HotkeyCompatibilityLayer::probe_fn();
+        std::env::remove_var("FLOWWISPER_SIMULATE_FN");
+        assert!(!result.supported);
+        assert_eq!(result.interface.as_deref(), Some("simulated"));
+    }
+
     #[test]
     fn onboarding_preferences_persist_between_sessions() {
         let temp = tempdir().expect("tempdir");
@@ -6,6 +6,7 @@ use std::{path::PathBuf, time::Duration};
 use tauri::{AppHandle, Manager, State};
 
 mod audio;
+mod diagnostics;
 mod history;
 mod hotkey;
 mod native_probe;
@@ -19,12 +20,17 @@ use audio::{
     request_microphone_permission as request_system_microphone_permission, run_device_check,
     DeviceTestReport, FrameWindowSetting,
 };
+use flowwisper_core::error::FlowwisperError;
+use flowwisper_core::hotkey::HotkeyCombination;
+use flowwisper_core::session::diagnostics::{DiagnosticsBundle, DiagnosticsRequest};
 use flowwisper_core::session::history::{
-    AccuracyUpdate, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery,
+    AccuracyUpdate, ExportRequest, ExportSummary, HistoryEntry, HistoryPage, HistoryPostAction,
+    HistoryQuery, ImportSource, ImportSummary,
 };
+use flowwisper_core::session::self_check::SelfCheckReport;
 use hotkey::{
-    load_hotkey_config, load_or_create_hmac_key, AppState, FnProbeResult, HotkeyBinding,
-    HotkeyCompatibilityLayer, HotkeySource,
+    load_hotkey_config, load_or_create_hmac_key, AppState, FnProbeResult, HotkeyActionBinding,
+    HotkeyBinding, HotkeyCompatibilityLayer, HotkeySource,
 };
 use session::{
     InsertionResult, PublishNotice, PublishingUpdate, SessionRealtimeEvent, SessionStatus,
@@ -130,11 +136,17 @@ fn update_tray_hotkey(app: &AppHandle, binding: &HotkeyBinding) {
 
 impl HotkeyCompatibilityLayer {
     fn detect_conflict(app: &AppHandle, combination: &str) -> Result<Option<String>, String> {
-        if let Some(value) = Self::RESERVED
-            .iter()
-            .copied()
-            .find(|reserved| reserved.eq_ignore_ascii_case(combination))
-        {
+        let reserved = combination
+            .parse::<HotkeyCombination>()
+            .ok()
+            .and_then(|parsed| parsed.conflict())
+            .or_else(|| {
+                Self::RESERVED
+                    .iter()
+                    .copied()
+                    .find(|reserved| reserved.eq_ignore_ascii_case(combination))
+            });
+        if let Some(value) = reserved {
             return Ok(Some(value.to_string()));
         }
 
@@ -145,7 +157,19 @@ impl HotkeyCompatibilityLayer {
             return Ok(None);
         }
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(target_os = "linux")]
+        {
+            let _ = app;
+            let desktop = combination
+                .parse::<HotkeyCombination>()
+                .ok()
+                .and_then(|parsed| {
+                    flowwisper_core::hotkey::EvdevBackend::default().desktop_conflict(&parsed)
+                });
+            Ok(desktop.map(str::to_string))
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         {
             let _ = app;
             let _ = combination;
@@ -222,24 +246,59 @@ fn session_notice_center_history(state: State<AppState>) -> Result<Vec<PublishNo
 }
 
 #[tauri::command]
-async fn session_history_search(query: HistoryQuery) -> Result<HistoryPage, String> {
+async fn session_history_search(query: HistoryQuery) -> Result<HistoryPage, FlowwisperError> {
     history::search_history(query).await
 }
 
 #[tauri::command]
-async fn session_history_entry(session_id: String) -> Result<Option<HistoryEntry>, String> {
+async fn session_history_entry(
+    session_id: String,
+) -> Result<Option<HistoryEntry>, FlowwisperError> {
     history::load_history(session_id).await
 }
 
 #[tauri::command]
-async fn session_history_mark_accuracy(update: AccuracyUpdate) -> Result<(), String> {
+async fn session_history_audio(
+    session_id: String,
+) -> Result<Option<history::HistoryAudio>, FlowwisperError> {
+    history::load_session_audio(session_id).await
+}
+
+#[tauri::command]
+async fn session_history_export(request: ExportRequest) -> Result<ExportSummary, FlowwisperError> {
+    history::export_history(request).await
+}
+
+#[tauri::command]
+async fn session_history_import(source: ImportSource) -> Result<ImportSummary, FlowwisperError> {
+    history::import_history(source).await
+}
+
+#[tauri::command]
+async fn session_history_rotate_key(
+    app: AppHandle,
+    new_key: String,
+) -> Result<(), FlowwisperError> {
+    history::rotate_key(app, new_key).await
+}
+
+#[tauri::command]
+async fn session_history_mark_accuracy(update: AccuracyUpdate) -> Result<(), FlowwisperError> {
     history::mark_accuracy(update).await
 }
 
+#[tauri::command]
+async fn session_history_set_pinned(
+    session_id: String,
+    pinned: bool,
+) -> Result<(), FlowwisperError> {
+    history::set_pinned(session_id, pinned).await
+}
+
 #[tauri::command]
 async fn session_history_append_action(
     request: history::HistoryActionRequest,
-) -> Result<Vec<HistoryPostAction>, String> {
+) -> Result<Vec<HistoryPostAction>, FlowwisperError> {
     history::append_action(request.session_id, request.action, request.detail).await
 }
 
@@ -740,6 +799,19 @@ fn run_audio_diagnostics(
     })
 }
 
+#[tauri::command]
+async fn run_self_check(state: State<'_, AppState>) -> Result<SelfCheckReport, String> {
+    let platform = diagnostics::DesktopSelfCheck::from_state(&state);
+    Ok(diagnostics::self_check(platform).await)
+}
+
+#[tauri::command]
+async fn export_diagnostics(request: DiagnosticsRequest) -> Result<DiagnosticsBundle, String> {
+    diagnostics::export_diagnostics(request)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn load_diagnostic_sample(state: State<AppState>, token: String) -> Result<String, String> {
     let bytes = state
@@ -882,10 +954,20 @@ fn persist_hotkey_binding(
         }
     }
 
+    let actions = binding_guard.binding.actions.clone();
+    if let Some((action, conflict)) =
+        HotkeyCompatibilityLayer::action_conflicts(&request.combination, &actions)
+            .into_iter()
+            .next()
+    {
+        return Err(format!("与“{}”快捷键冲突：{conflict}", action.label()));
+    }
+
     binding_guard.binding = HotkeyBinding {
         combination: request.combination.clone(),
         source: request.source,
         reason: reason.clone(),
+        actions,
     };
 
     let persisted = binding_guard.binding.clone();
@@ -903,6 +985,48 @@ fn persist_hotkey_binding(
     Ok(persisted)
 }
 
+#[tauri::command]
+fn persist_hotkey_actions(
+    app: AppHandle,
+    state: State<AppState>,
+    actions: Vec<HotkeyActionBinding>,
+) -> Result<HotkeyBinding, String> {
+    for binding in &actions {
+        if let Some(conflict) =
+            HotkeyCompatibilityLayer::detect_conflict(&app, &binding.combination)?
+        {
+            return Err(format!(
+                "“{}”与系统快捷键 {conflict} 冲突",
+                binding.action.label()
+            ));
+        }
+    }
+
+    let mut binding_guard = state
+        .hotkey
+        .lock()
+        .map_err(|err| format!("failed to update hotkey binding: {err}"))?;
+    if let Some((action, conflict)) =
+        HotkeyCompatibilityLayer::action_conflicts(&binding_guard.binding.combination, &actions)
+            .into_iter()
+            .next()
+    {
+        return Err(format!("“{}”快捷键无效：{conflict}", action.label()));
+    }
+    binding_guard.binding.actions = actions;
+    let persisted = binding_guard.binding.clone();
+    drop(binding_guard);
+
+    state.persist_binding(&persisted)?;
+    state.session.transition_and_emit(
+        &app,
+        "HotkeyConfigured",
+        format!("Configured {} secondary hotkeys", persisted.actions.len()),
+    )?;
+
+    Ok(persisted)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -920,6 +1044,7 @@ mod tests {
             combination: "Ctrl+Shift+F".into(),
             source: HotkeySource::Custom,
             reason: Some("fallback".into()),
+            actions: Vec::new(),
         };
         let signature = sign_payload(&key, &payload).expect("signing should succeed");
         let envelope = HotkeyConfigEnvelope {
@@ -941,6 +1066,7 @@ mod tests {
             combination: "Fn".into(),
             source: HotkeySource::Fn,
             reason: None,
+            actions: Vec::new(),
         };
         let signature = sign_payload(&key, &payload).expect("signing should succeed");
         let mut envelope = HotkeyConfigEnvelope { payload, signature };
@@ -960,6 +1086,7 @@ mod tests {
             combination: "Ctrl+Alt+Space".into(),
             source: HotkeySource::Custom,
             reason: Some("User opted for fallback".into()),
+            actions: Vec::new(),
         };
 
         state
@@ -1016,6 +1143,7 @@ mod tests {
             combination: "Fn".into(),
             source: HotkeySource::Fn,
             reason: None,
+            actions: Vec::new(),
         };
         let envelope = HotkeyConfigEnvelope {
             signature: sign_payload(&key, &payload).expect("sign"),
@@ -1089,7 +1217,12 @@ fn main() {
             session_notice_center_history,
             session_history_search,
             session_history_entry,
+            session_history_audio,
+            session_history_export,
+            session_history_import,
+            session_history_rotate_key,
             session_history_mark_accuracy,
+            session_history_set_pinned,
             session_history_append_action,
             session_transcript_apply_selection,
             prime_session_preroll,
@@ -1104,6 +1237,8 @@ fn main() {
             permission_status,
             list_audio_inputs,
             run_audio_diagnostics,
+            run_self_check,
+            export_diagnostics,
             load_diagnostic_sample,
             calibrate_noise_floor,
             get_device_calibration,
@@ -1119,7 +1254,8 @@ fn main() {
             record_tutorial_event,
             capture_custom_hotkey,
             get_hotkey_binding,
-            persist_hotkey_binding
+            persist_hotkey_binding,
+            persist_hotkey_actions
         ])
         .setup(|app| {
             let handle = app.handle();
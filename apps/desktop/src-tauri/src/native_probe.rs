@@ -1253,7 +1253,31 @@ mod platform {
     }
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{NativeProbeObservation, ProbeError};
+    use flowwisper_core::hotkey::{EvdevBackend, HotkeyError};
+    use std::time::Duration;
+
+    pub fn probe_fn(timeout: Duration) -> Result<NativeProbeObservation, ProbeError> {
+        match EvdevBackend::default().probe_fn(timeout, timeout) {
+            Ok(probe) => Ok(NativeProbeObservation {
+                supported: probe.supported,
+                latency: probe.latency,
+                raw_latency_ns: probe.latency.map(|latency| latency.as_nanos()),
+                user_reaction: probe.user_reaction,
+                within_sla: probe.within_sla,
+                device_origin: probe.device_origin,
+                interface: probe.interface,
+                reason: probe.reason,
+            }),
+            Err(HotkeyError::Timeout) => Err(ProbeError::Timeout),
+            Err(err) => Err(ProbeError::Io(err.to_string())),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 mod platform {
     use super::{NativeProbeObservation, ProbeError};
     use std::time::Duration;
@@ -1,6 +1,7 @@
+use flowwisper_core::audio::NoiseKind as CoreNoiseKind;
 use flowwisper_core::session::{
-    AutoStopReason as CoreAutoStopReason, SessionEvent as CoreSessionEvent,
-    SessionNoiseWarning as CoreSessionNoiseWarning,
+    AutoStopReason as CoreAutoStopReason, SessionEchoDetected as CoreSessionEchoDetected,
+    SessionEvent as CoreSessionEvent, SessionNoiseWarning as CoreSessionNoiseWarning,
     SessionSilenceCountdown as CoreSessionSilenceCountdown,
     SilenceCancellationReason as CoreSilenceCancellationReason,
     SilenceCountdownState as CoreSilenceCountdownState,
@@ -456,6 +457,17 @@ pub enum SessionSilenceCancellationReason {
 #[serde(rename_all = "camelCase")]
 pub enum SessionAutoStopReason {
     SilenceTimeout,
+    MaxDuration,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionNoiseKind {
+    KeyboardClatter,
+    FanHum,
+    SpeechBabble,
+    #[default]
+    Unknown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -467,6 +479,10 @@ pub enum SessionRealtimeEvent {
         threshold_db: f32,
         level_db: f32,
         persistence_ms: u32,
+        #[serde(default)]
+        kind: SessionNoiseKind,
+        #[serde(default)]
+        hint: Option<String>,
     },
     SilenceCountdown {
         timestamp_ms: u128,
@@ -479,6 +495,18 @@ pub enum SessionRealtimeEvent {
         timestamp_ms: u128,
         reason: SessionAutoStopReason,
     },
+    DurationWarning {
+        timestamp_ms: u128,
+        elapsed_ms: u64,
+        limit_ms: u64,
+    },
+    EchoDetected {
+        timestamp_ms: u128,
+        correlation: f32,
+        delay_ms: u32,
+        persistence_ms: u32,
+        hint: String,
+    },
 }
 
 impl SessionRealtimeEvent {
@@ -526,6 +554,30 @@ impl SessionRealtimeEvent {
                 }
             }
             SessionRealtimeEvent::AutoStop { .. } => {}
+            SessionRealtimeEvent::DurationWarning {
+                elapsed_ms,
+                limit_ms,
+                ..
+            } => {
+                if *limit_ms == 0 {
+                    return Err("duration warning limit must be positive".into());
+                }
+                if elapsed_ms > limit_ms {
+                    return Err("duration warning elapsed exceeds limit".into());
+                }
+            }
+            SessionRealtimeEvent::EchoDetected {
+                correlation,
+                persistence_ms,
+                ..
+            } => {
+                if !correlation.is_finite() || !(0.0..=1.0).contains(correlation) {
+                    return Err("echo correlation must be within 0..=1".into());
+                }
+                if *persistence_ms == 0 {
+                    return Err("echo persistence must be positive".into());
+                }
+            }
         }
 
         Ok(())
@@ -539,6 +591,8 @@ impl SessionRealtimeEvent {
                 threshold_db: payload.threshold_db,
                 level_db: payload.level_db,
                 persistence_ms: payload.persistence_ms,
+                kind: payload.kind.into(),
+                hint: payload.kind.hint().map(str::to_string),
             },
             CoreSessionEvent::SilenceCountdown(payload) => SessionRealtimeEvent::SilenceCountdown {
                 timestamp_ms: current_timestamp_ms(),
@@ -551,6 +605,18 @@ impl SessionRealtimeEvent {
                 timestamp_ms: current_timestamp_ms(),
                 reason: payload.reason.into(),
             },
+            CoreSessionEvent::DurationWarning(payload) => SessionRealtimeEvent::DurationWarning {
+                timestamp_ms: current_timestamp_ms(),
+                elapsed_ms: payload.elapsed_ms,
+                limit_ms: payload.limit_ms,
+            },
+            CoreSessionEvent::EchoDetected(payload) => SessionRealtimeEvent::EchoDetected {
+                timestamp_ms: current_timestamp_ms(),
+                correlation: payload.correlation,
+                delay_ms: payload.delay_ms,
+                persistence_ms: payload.persistence_ms,
+                hint: payload.hint.to_string(),
+            },
         }
     }
 }
@@ -579,10 +645,22 @@ impl From<CoreSilenceCancellationReason> for SessionSilenceCancellationReason {
     }
 }
 
+impl From<CoreNoiseKind> for SessionNoiseKind {
+    fn from(value: CoreNoiseKind) -> Self {
+        match value {
+            CoreNoiseKind::KeyboardClatter => SessionNoiseKind::KeyboardClatter,
+            CoreNoiseKind::FanHum => SessionNoiseKind::FanHum,
+            CoreNoiseKind::SpeechBabble => SessionNoiseKind::SpeechBabble,
+            CoreNoiseKind::Unknown => SessionNoiseKind::Unknown,
+        }
+    }
+}
+
 impl From<CoreAutoStopReason> for SessionAutoStopReason {
     fn from(value: CoreAutoStopReason) -> Self {
         match value {
             CoreAutoStopReason::SilenceTimeout => SessionAutoStopReason::SilenceTimeout,
+            CoreAutoStopReason::MaxDuration => SessionAutoStopReason::MaxDuration,
         }
     }
 }
@@ -593,6 +671,8 @@ pub enum PublishStrategy {
     DirectInsert,
     ClipboardFallback,
     NotifyOnly,
+    SimulatedTyping,
+    WaitForFocus,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -879,6 +959,8 @@ mod tests {
                 threshold_db: 45.0,
                 level_db: 60.0,
                 persistence_ms: 300,
+                kind: SessionNoiseKind::Unknown,
+                hint: None,
             };
             manager
                 .record_session_event(event)
@@ -925,6 +1007,7 @@ mod tests {
             threshold_db: 35.0,
             level_db: 52.0,
             persistence_ms: 250,
+            kind: CoreNoiseKind::KeyboardClatter,
         };
         let countdown = CoreSessionSilenceCountdown {
             total_ms: 5000,
@@ -940,12 +1023,16 @@ mod tests {
                 threshold_db,
                 level_db,
                 persistence_ms,
+                kind,
+                hint,
                 ..
             } => {
                 assert!((baseline_db - 20.0).abs() < f32::EPSILON);
                 assert!((threshold_db - 35.0).abs() < f32::EPSILON);
                 assert!((level_db - 52.0).abs() < f32::EPSILON);
                 assert_eq!(persistence_ms, 250);
+                assert_eq!(kind, SessionNoiseKind::KeyboardClatter);
+                assert_eq!(hint.as_deref(), Some("检测到键盘敲击声"));
             }
             _ => panic!("expected noise warning"),
         }
@@ -974,5 +1061,22 @@ mod tests {
             }
             _ => panic!("expected auto-stop"),
         }
+
+        let echo = SessionRealtimeEvent::from_core_event(CoreSessionEvent::EchoDetected(
+            CoreSessionEchoDetected {
+                correlation: 0.9,
+                delay_ms: 80,
+                persistence_ms: 400,
+                hint: "请佩戴耳机",
+            },
+        ));
+        assert!(echo.validate().is_ok());
+        match echo {
+            SessionRealtimeEvent::EchoDetected { delay_ms, hint, .. } => {
+                assert_eq!(delay_ms, 80);
+                assert_eq!(hint, "请佩戴耳机");
+            }
+            _ => panic!("expected echo detected"),
+        }
     }
 }
@@ -87,10 +87,18 @@ type EnginePreferenceResponse = {
 
 type HotkeySource = "fn" | "custom";
 
+type HotkeyAction = "cancelSession" | "undoPublish" | "repolishLast";
+
+type HotkeyActionBinding = {
+  action: HotkeyAction;
+  combination: string;
+};
+
 type HotkeyBinding = {
   combination: string;
   source: HotkeySource;
   reason?: string | null;
+  actions?: HotkeyActionBinding[];
 };
 
 type FnProbeResult = {
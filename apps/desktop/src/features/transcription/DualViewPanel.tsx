@@ -23,6 +23,7 @@ import {
   MAX_MULTI_SELECT,
 } from "./hooks/useDualViewTranscript";
 import { useSessionEvents } from "./hooks/useSessionEvents";
+import { EchoBanner } from "./EchoBanner";
 import { NoiseBanner } from "./NoiseBanner";
 import { SilenceCountdown } from "./SilenceCountdown";
 
@@ -115,6 +116,8 @@ const LOCALE_MESSAGES: Record<Locale, {
         directInsert: "Direct insert",
         clipboardFallback: "Clipboard fallback",
         notifyOnly: "Notify only",
+        simulatedTyping: "Simulated typing",
+        waitForFocus: "Waiting for target app",
       },
       fallbackLabel: {
         clipboardCopy: "Clipboard copy",
@@ -172,6 +175,8 @@ const LOCALE_MESSAGES: Record<Locale, {
         directInsert: "直接插入",
         clipboardFallback: "剪贴板降级",
         notifyOnly: "仅通知",
+        simulatedTyping: "模拟键入",
+        waitForFocus: "等待目标应用",
       },
       fallbackLabel: {
         clipboardCopy: "剪贴板备份",
@@ -1007,6 +1012,10 @@ export const DualViewPanel = ({
             warning={sessionEvents.noiseWarning}
             onDismiss={sessionEvents.dismissNoiseWarning}
           />
+          <EchoBanner
+            warning={sessionEvents.echoWarning}
+            onDismiss={sessionEvents.dismissEchoWarning}
+          />
           <SilenceCountdown
             countdown={sessionEvents.countdown}
             autoStop={sessionEvents.autoStop}
@@ -0,0 +1,27 @@
+import type { EchoWarningState } from "./hooks/useSessionEvents";
+
+type EchoBannerProps = {
+  warning: EchoWarningState;
+  onDismiss: () => void;
+};
+
+export const EchoBanner = ({ warning, onDismiss }: EchoBannerProps) => {
+  if (!warning.visible) {
+    return null;
+  }
+
+  return (
+    <div className="noise-banner" role="alert" aria-live="assertive">
+      <div className="noise-banner__content">
+        <span className="noise-banner__title">{warning.hint}</span>
+        <span className="noise-banner__meta">
+          The microphone is picking up speaker playback (~{warning.delayMs} ms delay). Use headphones so the
+          computer&apos;s own audio is not transcribed.
+        </span>
+      </div>
+      <button type="button" className="noise-banner__dismiss" onClick={onDismiss}>
+        Dismiss
+      </button>
+    </div>
+  );
+};
@@ -19,7 +19,7 @@ export const NoiseBanner = ({ warning, onDismiss }: NoiseBannerProps) => {
   return (
     <div className="noise-banner" role="alert" aria-live="assertive">
       <div className="noise-banner__content">
-        <span className="noise-banner__title">High background noise detected</span>
+        <span className="noise-banner__title">{warning.hint ?? "High background noise detected"}</span>
         <span className="noise-banner__meta">
           Input level {formatDb(warning.levelDb)} · Threshold {formatDb(warning.thresholdDb)} · Baseline{' '}
           {formatDb(warning.baselineDb)} ({severity})
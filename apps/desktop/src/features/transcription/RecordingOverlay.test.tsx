@@ -1,11 +1,13 @@
 import { fireEvent, render, screen } from "@testing-library/react";
 import { describe, expect, it, vi } from "vitest";
 
+import { EchoBanner } from "./EchoBanner";
 import { NoiseBanner } from "./NoiseBanner";
 import { SilenceCountdown } from "./SilenceCountdown";
 import type {
   AutoStopState,
   CountdownState,
+  EchoWarningState,
   NoiseWarningState,
 } from "./hooks/useSessionEvents";
 
@@ -17,6 +19,8 @@ describe("Recording overlay components", () => {
       thresholdDb: 45.0,
       levelDb: 62.5,
       persistenceMs: 350,
+      kind: "unknown",
+      hint: null,
       triggeredAt: Date.now(),
     };
     const onDismiss = vi.fn();
@@ -29,6 +33,39 @@ describe("Recording overlay components", () => {
     expect(onDismiss).toHaveBeenCalledTimes(1);
   });
 
+  it("names the classified noise source when a hint is available", () => {
+    const warning: NoiseWarningState = {
+      visible: true,
+      baselineDb: 30.0,
+      thresholdDb: 45.0,
+      levelDb: 55.0,
+      persistenceMs: 300,
+      kind: "keyboardClatter",
+      hint: "检测到键盘敲击声",
+      triggeredAt: Date.now(),
+    };
+
+    render(<NoiseBanner warning={warning} onDismiss={vi.fn()} />);
+
+    expect(screen.getByText("检测到键盘敲击声")).toBeInTheDocument();
+  });
+
+  it("suggests headphones when speaker echo is detected", () => {
+    const warning: EchoWarningState = {
+      visible: true,
+      hint: "检测到扬声器回放声，建议佩戴耳机以免转写电脑自身的声音",
+      delayMs: 80,
+      triggeredAt: Date.now(),
+    };
+    const onDismiss = vi.fn();
+
+    render(<EchoBanner warning={warning} onDismiss={onDismiss} />);
+
+    expect(screen.getByText(/建议佩戴耳机/)).toBeInTheDocument();
+    fireEvent.click(screen.getByRole("button", { name: /dismiss/i }));
+    expect(onDismiss).toHaveBeenCalledTimes(1);
+  });
+
   it("does not render the noise banner when hidden", () => {
     const warning: NoiseWarningState = {
       visible: false,
@@ -36,6 +73,8 @@ describe("Recording overlay components", () => {
       thresholdDb: 0,
       levelDb: 0,
       persistenceMs: 0,
+      kind: "unknown",
+      hint: null,
       triggeredAt: 0,
     };
 
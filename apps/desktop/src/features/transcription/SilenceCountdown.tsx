@@ -22,7 +22,9 @@ export const SilenceCountdown = ({ countdown, autoStop, onDismissAutoStop }: Sil
         <div className="silence-countdown__content">
           <span className="silence-countdown__title">Recording ended automatically</span>
           <span className="silence-countdown__meta">
-            We didn't detect speech for {formatSeconds(countdown.totalMs)}. Resume when you're ready.
+            {autoStop.reason === "maxDuration"
+              ? "The session reached its maximum length. Your transcript so far was saved as a draft."
+              : `We didn't detect speech for ${formatSeconds(countdown.totalMs)}. Resume when you're ready.`}
           </span>
         </div>
         <button type="button" className="silence-countdown__action" onClick={onDismissAutoStop}>
@@ -36,7 +36,9 @@ type TranscriptSentence = {
 export type PublishStrategy =
   | "directInsert"
   | "clipboardFallback"
-  | "notifyOnly";
+  | "notifyOnly"
+  | "simulatedTyping"
+  | "waitForFocus";
 
 export type FallbackStrategy = "clipboardCopy" | "notifyOnly";
 
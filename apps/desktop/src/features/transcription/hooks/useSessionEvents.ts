@@ -8,7 +8,10 @@ const NOISE_DISMISS_DELAY_MS = 4000;
 
 type SessionSilenceCountdownState = "started" | "tick" | "canceled" | "completed";
 type SessionSilenceCancellationReason = "speechDetected" | "manualStop";
-type SessionAutoStopReason = "silenceTimeout";
+type SessionAutoStopReason = "silenceTimeout" | "maxDuration";
+export type SessionNoiseKind = "keyboardClatter" | "fanHum" | "speechBabble" | "unknown";
+
+const NOISE_KINDS: SessionNoiseKind[] = ["keyboardClatter", "fanHum", "speechBabble", "unknown"];
 
 type SessionEventPayload =
   | {
@@ -18,6 +21,8 @@ type SessionEventPayload =
       thresholdDb: number;
       levelDb: number;
       persistenceMs: number;
+      kind: SessionNoiseKind;
+      hint: string | null;
     }
   | {
       type: "silenceCountdown";
@@ -31,6 +36,12 @@ type SessionEventPayload =
       type: "autoStop";
       timestampMs: number;
       reason: SessionAutoStopReason;
+    }
+  | {
+      type: "echoDetected";
+      timestampMs: number;
+      delayMs: number;
+      hint: string;
     };
 
 export type NoiseWarningState = {
@@ -39,6 +50,15 @@ export type NoiseWarningState = {
   thresholdDb: number;
   levelDb: number;
   persistenceMs: number;
+  kind: SessionNoiseKind;
+  hint: string | null;
+  triggeredAt: number;
+};
+
+export type EchoWarningState = {
+  visible: boolean;
+  hint: string;
+  delayMs: number;
   triggeredAt: number;
 };
 
@@ -58,6 +78,8 @@ export type AutoStopState = {
 export type SessionEventsState = {
   noiseWarning: NoiseWarningState;
   dismissNoiseWarning: () => void;
+  echoWarning: EchoWarningState;
+  dismissEchoWarning: () => void;
   countdown: CountdownState;
   autoStop: AutoStopState;
   resetAutoStop: () => void;
@@ -69,6 +91,15 @@ const createInitialNoiseState = (): NoiseWarningState => ({
   thresholdDb: 0,
   levelDb: 0,
   persistenceMs: 0,
+  kind: "unknown",
+  hint: null,
+  triggeredAt: 0,
+});
+
+const createInitialEchoState = (): EchoWarningState => ({
+  visible: false,
+  hint: "",
+  delayMs: 0,
   triggeredAt: 0,
 });
 
@@ -95,6 +126,9 @@ const isCountdownEvent = (
 const isAutoStopEvent = (event: SessionEventPayload): event is Extract<SessionEventPayload, { type: "autoStop" }> =>
   event.type === "autoStop";
 
+const isEchoEvent = (event: SessionEventPayload): event is Extract<SessionEventPayload, { type: "echoDetected" }> =>
+  event.type === "echoDetected";
+
 const coerceEventPayload = (payload: unknown): SessionEventPayload | null => {
   if (!payload || typeof payload !== "object") {
     return null;
@@ -112,6 +146,8 @@ const coerceEventPayload = (payload: unknown): SessionEventPayload | null => {
     if ([baseline, threshold, level, persistence, timestamp].some((value) => Number.isNaN(value))) {
       return null;
     }
+    const kind = NOISE_KINDS.find((candidate) => candidate === record["kind"]) ?? "unknown";
+    const hint = typeof record["hint"] === "string" ? record["hint"] : null;
     return {
       type: "noiseWarning",
       timestampMs: timestamp,
@@ -119,6 +155,8 @@ const coerceEventPayload = (payload: unknown): SessionEventPayload | null => {
       thresholdDb: threshold,
       levelDb: level,
       persistenceMs: persistence,
+      kind,
+      hint,
     };
   }
 
@@ -155,13 +193,28 @@ const coerceEventPayload = (payload: unknown): SessionEventPayload | null => {
   if (type === "autoStop") {
     const timestamp = Number(record["timestampMs"]);
     const reason = record["reason"];
-    if (Number.isNaN(timestamp) || reason !== "silenceTimeout") {
+    if (Number.isNaN(timestamp) || (reason !== "silenceTimeout" && reason !== "maxDuration")) {
       return null;
     }
     return {
       type: "autoStop",
       timestampMs: timestamp,
-      reason: "silenceTimeout",
+      reason,
+    };
+  }
+
+  if (type === "echoDetected") {
+    const timestamp = Number(record["timestampMs"]);
+    const delay = Number(record["delayMs"]);
+    const hint = record["hint"];
+    if ([timestamp, delay].some((value) => Number.isNaN(value)) || typeof hint !== "string") {
+      return null;
+    }
+    return {
+      type: "echoDetected",
+      timestampMs: timestamp,
+      delayMs: delay,
+      hint,
     };
   }
 
@@ -170,6 +223,7 @@ const coerceEventPayload = (payload: unknown): SessionEventPayload | null => {
 
 export const useSessionEvents = (): SessionEventsState => {
   const [noiseWarning, setNoiseWarning] = useState<NoiseWarningState>(() => createInitialNoiseState());
+  const [echoWarning, setEchoWarning] = useState<EchoWarningState>(() => createInitialEchoState());
   const [countdown, setCountdown] = useState<CountdownState>(() => createInitialCountdownState());
   const [autoStop, setAutoStop] = useState<AutoStopState>(() => createInitialAutoStopState());
   const lastProcessedTimestamp = useRef<number>(0);
@@ -178,6 +232,10 @@ export const useSessionEvents = (): SessionEventsState => {
     setNoiseWarning((current) => ({ ...current, visible: false }));
   }, []);
 
+  const dismissEchoWarning = useCallback(() => {
+    setEchoWarning((current) => ({ ...current, visible: false }));
+  }, []);
+
   const resetAutoStop = useCallback(() => {
     setAutoStop(createInitialAutoStopState());
   }, []);
@@ -196,6 +254,18 @@ export const useSessionEvents = (): SessionEventsState => {
         thresholdDb: event.thresholdDb,
         levelDb: event.levelDb,
         persistenceMs: event.persistenceMs,
+        kind: event.kind,
+        hint: event.hint,
+        triggeredAt: event.timestampMs,
+      });
+      return;
+    }
+
+    if (isEchoEvent(event)) {
+      setEchoWarning({
+        visible: true,
+        hint: event.hint,
+        delayMs: event.delayMs,
         triggeredAt: event.timestampMs,
       });
       return;
@@ -296,11 +366,13 @@ export const useSessionEvents = (): SessionEventsState => {
     () => ({
       noiseWarning,
       dismissNoiseWarning,
+      echoWarning,
+      dismissEchoWarning,
       countdown,
       autoStop,
       resetAutoStop,
     }),
-    [noiseWarning, dismissNoiseWarning, countdown, autoStop, resetAutoStop],
+    [noiseWarning, dismissNoiseWarning, echoWarning, dismissEchoWarning, countdown, autoStop, resetAutoStop],
   );
 
   return state;
@@ -0,0 +1,17 @@
+import { describe, expect, it } from "vitest";
+
+import { errorMessage, isCoreError } from "./errors";
+
+describe("core errors", () => {
+  it("recognizes structured errors from the core", () => {
+    const err = { code: "persistence", message: "database is locked" };
+    expect(isCoreError(err)).toBe(true);
+    expect(errorMessage(err)).toBe("database is locked");
+  });
+
+  it("falls back to plain errors and strings", () => {
+    expect(isCoreError("boom")).toBe(false);
+    expect(errorMessage(new Error("boom"))).toBe("boom");
+    expect(errorMessage("boom")).toBe("boom");
+  });
+});
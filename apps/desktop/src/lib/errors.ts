@@ -0,0 +1,28 @@
+export type CoreErrorCode =
+  | "audio"
+  | "engine"
+  | "persistence"
+  | "publish"
+  | "permission"
+  | "internal";
+
+export type CoreError = {
+  code: CoreErrorCode;
+  message: string;
+};
+
+export function isCoreError(value: unknown): value is CoreError {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    typeof (value as CoreError).code === "string" &&
+    typeof (value as CoreError).message === "string"
+  );
+}
+
+export function errorMessage(err: unknown): string {
+  if (isCoreError(err) || err instanceof Error) {
+    return err.message;
+  }
+  return String(err);
+}
@@ -11,7 +11,10 @@ export type HistoryActionKind =
   | "reinsert"
   | "export"
   | "save_draft"
-  | "clipboard_backup";
+  | "clipboard_backup"
+  | "open_url"
+  | "webhook"
+  | "plugin";
 
 export type HistoryPostAction = {
   kind: HistoryActionKind;
@@ -0,0 +1,288 @@
+//! 采集链路的自动增益控制（AGC）。
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use super::{AudioPipeline, SAMPLE_RATE_HZ};
+
+const DEFAULT_TARGET_RMS: f32 = 0.2;
+const DEFAULT_MIN_GAIN: f32 = 0.1;
+const DEFAULT_MAX_GAIN: f32 = 10.0;
+const DEFAULT_ATTACK_MS: u64 = 20;
+const DEFAULT_RELEASE_MS: u64 = 400;
+const DEFAULT_GATE_RMS: f32 = 1e-3;
+
+/// AGC 参数。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AgcConfig {
+    /// 期望的输出 RMS（线性幅度）。
+    pub target_rms: f32,
+    /// 允许的最小增益。
+    pub min_gain: f32,
+    /// 允许的最大增益。
+    pub max_gain: f32,
+    /// 输入变响时增益下降的时间常数。
+    pub attack: Duration,
+    /// 输入变轻时增益回升的时间常数。
+    pub release: Duration,
+    /// 低于该 RMS 的帧视为静音，保持当前增益避免放大底噪。
+    pub gate_rms: f32,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        Self {
+            target_rms: DEFAULT_TARGET_RMS,
+            min_gain: DEFAULT_MIN_GAIN,
+            max_gain: DEFAULT_MAX_GAIN,
+            attack: Duration::from_millis(DEFAULT_ATTACK_MS),
+            release: Duration::from_millis(DEFAULT_RELEASE_MS),
+            gate_rms: DEFAULT_GATE_RMS,
+        }
+    }
+}
+
+/// 基于帧 RMS 的单通道 AGC，增益在帧内线性过渡以避免阶跃噪声。
+#[derive(Clone, Debug)]
+pub struct AutomaticGainControl {
+    config: AgcConfig,
+    sample_rate_hz: u32,
+    current_gain: f32,
+}
+
+impl AutomaticGainControl {
+    pub fn new(config: AgcConfig, sample_rate_hz: u32) -> Self {
+        let min_gain = config.min_gain.max(0.0);
+        let max_gain = config.max_gain.max(min_gain);
+        Self {
+            config: AgcConfig {
+                min_gain,
+                max_gain,
+                ..config
+            },
+            sample_rate_hz: sample_rate_hz.max(1),
+            current_gain: 1.0_f32.clamp(min_gain, max_gain),
+        }
+    }
+
+    pub fn config(&self) -> &AgcConfig {
+        &self.config
+    }
+
+    /// 最近一次应用到样本上的增益。
+    pub fn current_gain(&self) -> f32 {
+        self.current_gain
+    }
+
+    pub fn reset(&mut self) {
+        self.current_gain = 1.0_f32.clamp(self.config.min_gain, self.config.max_gain);
+    }
+
+    /// 就地处理一帧样本并返回帧末的增益。
+    pub fn process(&mut self, frame: &mut [f32]) -> f32 {
+        if frame.is_empty() {
+            return self.current_gain;
+        }
+
+        let rms = frame_rms(frame);
+        let start_gain = self.current_gain;
+        let end_gain = if rms < self.config.gate_rms {
+            start_gain
+        } else {
+            let desired =
+                (self.config.target_rms / rms).clamp(self.config.min_gain, self.config.max_gain);
+            let time_constant = if desired < start_gain {
+                self.config.attack
+            } else {
+                self.config.release
+            };
+            let coefficient =
+                smoothing_coefficient(frame.len(), self.sample_rate_hz, time_constant);
+            start_gain + (desired - start_gain) * coefficient
+        };
+
+        let step = (end_gain - start_gain) / frame.len() as f32;
+        for (index, sample) in frame.iter_mut().enumerate() {
+            let gain = start_gain + step * (index + 1) as f32;
+            *sample = (*sample * gain).clamp(-1.0, 1.0);
+        }
+
+        self.current_gain = end_gain;
+        end_gain
+    }
+}
+
+impl AudioPipeline {
+    /// 启用自动增益控制；波形与 PCM 订阅者收到的均为增益后的样本，
+    /// [`AudioPipeline::subscribe_raw_pcm_frames`] 的订阅者除外。
+    pub fn enable_agc(&self, config: AgcConfig) {
+        let agc = AutomaticGainControl::new(config, SAMPLE_RATE_HZ);
+        self.applied_gain
+            .store(agc.current_gain().to_bits(), Ordering::SeqCst);
+        let mut guard = self.agc.lock().expect("agc mutex poisoned");
+        *guard = Some(agc);
+    }
+
+    pub fn disable_agc(&self) {
+        let mut guard = self.agc.lock().expect("agc mutex poisoned");
+        *guard = None;
+        self.applied_gain.store(1.0_f32.to_bits(), Ordering::SeqCst);
+    }
+
+    /// 按配置启用或关闭 AGC；参数未变时保留当前增益。
+    pub fn configure_agc(&self, config: Option<AgcConfig>) {
+        let current = self
+            .agc
+            .lock()
+            .expect("agc mutex poisoned")
+            .as_ref()
+            .map(|agc| *agc.config());
+        match config {
+            Some(config) if current.as_ref() != Some(&config) => self.enable_agc(config),
+            Some(_) => {}
+            None => self.disable_agc(),
+        }
+    }
+
+    pub fn current_gain(&self) -> f32 {
+        f32::from_bits(self.applied_gain.load(Ordering::SeqCst))
+    }
+
+    pub(super) fn apply_gain(&self, samples: &mut [f32]) {
+        let mut guard = self.agc.lock().expect("agc mutex poisoned");
+        if let Some(agc) = guard.as_mut() {
+            let gain = agc.process(samples);
+            self.applied_gain.store(gain.to_bits(), Ordering::SeqCst);
+        }
+    }
+
+    pub(super) fn reset_agc(&self) {
+        let mut guard = self.agc.lock().expect("agc mutex poisoned");
+        if let Some(agc) = guard.as_mut() {
+            agc.reset();
+            self.applied_gain
+                .store(agc.current_gain().to_bits(), Ordering::SeqCst);
+        }
+    }
+}
+
+fn smoothing_coefficient(frame_len: usize, sample_rate_hz: u32, time_constant: Duration) -> f32 {
+    let tau = time_constant.as_secs_f32();
+    if tau <= f32::EPSILON {
+        return 1.0;
+    }
+    let frame_secs = frame_len as f32 / sample_rate_hz as f32;
+    1.0 - (-frame_secs / tau).exp()
+}
+
+fn frame_rms(frame: &[f32]) -> f32 {
+    let energy: f32 = frame.iter().map(|sample| sample * sample).sum();
+    (energy / frame.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{duration_to_samples, MIN_FRAME_MS};
+    use tokio::time::timeout;
+
+    const RATE: u32 = 16_000;
+    const FRAME: usize = 1_600;
+
+    #[test]
+    fn quiet_input_is_boosted_toward_target() {
+        let mut agc = AutomaticGainControl::new(AgcConfig::default(), RATE);
+        let mut frame = vec![0.02_f32; FRAME];
+        for _ in 0..40 {
+            frame.fill(0.02);
+            agc.process(&mut frame);
+        }
+        assert!((agc.current_gain() - 10.0).abs() < 0.1);
+        assert!((frame_rms(&frame) - 0.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn loud_input_attacks_faster_than_release() {
+        let config = AgcConfig::default();
+        let mut agc = AutomaticGainControl::new(config, RATE);
+
+        let mut loud = vec![0.8_f32; FRAME];
+        let gain = agc.process(&mut loud);
+        assert!(gain < 0.35, "attack too slow: {gain}");
+
+        let mut quiet = vec![0.05_f32; FRAME];
+        let released = agc.process(&mut quiet);
+        assert!(released > gain);
+        assert!(released < 2.0, "release too fast: {released}");
+    }
+
+    #[test]
+    fn silence_holds_current_gain() {
+        let mut agc = AutomaticGainControl::new(AgcConfig::default(), RATE);
+        let mut frame = vec![0.0_f32; FRAME];
+        assert_eq!(agc.process(&mut frame), 1.0);
+        assert!(frame.iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn output_is_clamped_to_unit_range() {
+        let config = AgcConfig {
+            min_gain: 4.0,
+            ..AgcConfig::default()
+        };
+        let mut agc = AutomaticGainControl::new(config, RATE);
+        let mut frame = vec![0.9_f32; FRAME];
+        agc.process(&mut frame);
+        assert!(frame.iter().all(|sample| *sample <= 1.0));
+    }
+
+    #[tokio::test]
+    async fn agc_boosts_subscriber_frames_and_reports_gain() {
+        let pipeline = AudioPipeline::new();
+        pipeline.enable_agc(AgcConfig::default());
+        let mut rx = pipeline.subscribe_pcm_frames(8);
+        let mut raw_rx = pipeline.subscribe_raw_pcm_frames(8);
+        let mut waveform_rx = pipeline.subscribe_waveform();
+
+        let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+        for _ in 0..4 {
+            pipeline
+                .push_pcm_frame(vec![0.02_f32; frame_len])
+                .await
+                .expect("push quiet frame");
+        }
+
+        let mut last = None;
+        for _ in 0..4 {
+            last = Some(
+                timeout(Duration::from_millis(200), rx.recv())
+                    .await
+                    .expect("agc frame timed out")
+                    .expect("channel closed unexpectedly"),
+            );
+        }
+        let last = last.expect("received frames");
+        assert!(frame_rms(&last) > 0.02 * 1.5);
+        let raw = timeout(Duration::from_millis(200), raw_rx.recv())
+            .await
+            .expect("raw frame timed out")
+            .expect("raw channel closed unexpectedly");
+        assert!(raw.iter().all(|sample| *sample == 0.02));
+        assert!(pipeline.current_gain() > 1.5);
+
+        let waveform = loop {
+            let frame = timeout(Duration::from_millis(200), waveform_rx.recv())
+                .await
+                .expect("waveform frame timed out")
+                .expect("waveform channel closed unexpectedly");
+            if frame.rms > 0.0 {
+                break frame;
+            }
+        };
+        assert!(waveform.gain > 1.0);
+        assert!(waveform.rms > 0.02);
+
+        pipeline.disable_agc();
+        assert_eq!(pipeline.current_gain(), 1.0);
+    }
+}
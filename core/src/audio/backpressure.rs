@@ -0,0 +1,454 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex as AsyncMutex, Notify};
+use tokio::task;
+use tracing::warn;
+
+use super::spill::{SpillConfig, SpillQueue};
+use super::{
+    duration_to_samples, samples_to_duration, AudioPipeline, DEFAULT_MAX_COALESCED_MS,
+    SAMPLE_RATE_HZ,
+};
+use crate::telemetry::metrics::metrics;
+
+#[derive(Clone)]
+pub(super) struct PcmSubscriber {
+    pub(super) sender: mpsc::Sender<Arc<[f32]>>,
+    pub(super) state: Arc<AsyncMutex<SubscriberState>>,
+    pub(super) max_queue: usize,
+    pub(super) notify: Arc<Notify>,
+    pub(super) lossless: bool,
+    /// Receives frames before automatic gain control.
+    pub(super) raw: bool,
+    pub(super) spill: Option<SpillConfig>,
+    pub(super) coalesce_limit: Arc<AtomicUsize>,
+}
+
+/// How PCM subscribers absorb a consumer that falls behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackpressurePolicy {
+    /// Once a subscriber's queue is full, incoming audio is appended to the
+    /// newest queued frame until that frame reaches this duration. Only then
+    /// are frames dropped (lossy subscribers) or the producer made to wait
+    /// (lossless ones). Zero disables coalescing.
+    pub max_coalesced_frame: Duration,
+}
+
+impl Default for BackpressurePolicy {
+    fn default() -> Self {
+        Self {
+            max_coalesced_frame: Duration::from_millis(DEFAULT_MAX_COALESCED_MS),
+        }
+    }
+}
+
+pub(super) struct SubscriberState {
+    pub(super) queue: VecDeque<Arc<[f32]>>,
+    pub(super) queued_bytes: usize,
+    pub(super) spill: Option<SpillQueue>,
+    pub(super) active: bool,
+}
+
+impl SubscriberState {
+    pub(super) fn push(&mut self, frame: Arc<[f32]>) {
+        self.queued_bytes += frame.len() * std::mem::size_of::<f32>();
+        self.queue.push_back(frame);
+    }
+
+    /// Append `frame` to the newest queued frame when the result stays within
+    /// `limit` samples, so a lagging consumer receives fewer, larger frames.
+    fn coalesce(&mut self, frame: &[f32], limit: usize) -> bool {
+        let Some(tail) = self.queue.back_mut() else {
+            return false;
+        };
+        if tail.len() + frame.len() > limit {
+            return false;
+        }
+        let mut merged = Vec::with_capacity(tail.len() + frame.len());
+        merged.extend_from_slice(tail);
+        merged.extend_from_slice(frame);
+        *tail = merged.into();
+        self.queued_bytes += std::mem::size_of_val(frame);
+        true
+    }
+
+    /// Oldest frame first: the memory queue always precedes anything spilled.
+    pub(super) fn pop(&mut self) -> Option<Arc<[f32]>> {
+        if let Some(frame) = self.queue.pop_front() {
+            self.queued_bytes -= frame.len() * std::mem::size_of::<f32>();
+            return Some(frame);
+        }
+        let spill = self.spill.as_mut()?;
+        match spill.pop() {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!(
+                    target: "audio_pipeline",
+                    %err,
+                    "failed to replay spilled pcm frames; dropping spill file"
+                );
+                self.spill = None;
+                None
+            }
+        }
+    }
+}
+
+impl PcmSubscriber {
+    fn new(
+        sender: mpsc::Sender<Arc<[f32]>>,
+        max_queue: usize,
+        lossless: bool,
+        spill: Option<SpillConfig>,
+        coalesce_limit: Arc<AtomicUsize>,
+    ) -> Self {
+        Self {
+            sender,
+            state: Arc::new(AsyncMutex::new(SubscriberState {
+                queue: VecDeque::new(),
+                queued_bytes: 0,
+                spill: None,
+                active: false,
+            })),
+            max_queue,
+            notify: Arc::new(Notify::new()),
+            lossless,
+            raw: false,
+            spill,
+            coalesce_limit,
+        }
+    }
+
+    pub(super) fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Queue `frame` once there is room. When the queue is full because the
+    /// consumer has stopped draining its channel, the frame is first coalesced
+    /// into the newest queued one; failing that, lossless subscribers wait for
+    /// the consumer while lossy ones drop the oldest frame.
+    async fn admit<'a>(
+        &'a self,
+        mut state: tokio::sync::MutexGuard<'a, SubscriberState>,
+        frame: Arc<[f32]>,
+    ) -> tokio::sync::MutexGuard<'a, SubscriberState> {
+        loop {
+            if self.max_queue == 0 || state.queue.len() < self.max_queue {
+                break;
+            }
+            let lagging = self.sender.capacity() == 0;
+            if lagging && state.coalesce(&frame, self.coalesce_limit.load(Ordering::Relaxed)) {
+                metrics().pcm_frames_coalesced.inc();
+                return state;
+            }
+            if self.lossless {
+                let notify = Arc::clone(&self.notify);
+                drop(state);
+                notify.notified().await;
+                state = self.state.lock().await;
+                continue;
+            }
+            let _ = state.pop();
+            metrics().pcm_frames_dropped.inc();
+            warn!(
+                target: "audio_pipeline",
+                max_queue = self.max_queue,
+                "pcm subscriber queue exceeded capacity; dropping oldest frame"
+            );
+            break;
+        }
+        state.push(frame);
+        state
+    }
+
+    /// Queue `frame` for delivery and return the subscriber's queue depth.
+    pub(super) async fn enqueue(&self, frame: Arc<[f32]>) -> usize {
+        let mut state = self.state.lock().await;
+
+        if let Some(config) = self.spill.as_ref() {
+            if let Some(frame) = state.spill(config, frame) {
+                state.push(frame);
+            }
+        } else {
+            state = self.admit(state, frame).await;
+        }
+        let depth = state.queue.len();
+        if state.active {
+            return depth;
+        }
+
+        state.active = true;
+        let state_arc = Arc::clone(&self.state);
+        let sender = self.sender.clone();
+        let notify = Arc::clone(&self.notify);
+        drop(state);
+
+        task::spawn(async move {
+            loop {
+                let next = {
+                    let mut guard = state_arc.lock().await;
+                    match guard.pop() {
+                        Some(frame) => frame,
+                        None => {
+                            guard.active = false;
+                            notify.notify_waiters();
+                            return;
+                        }
+                    }
+                };
+
+                if sender.send(next).await.is_err() {
+                    let mut guard = state_arc.lock().await;
+                    guard.queue.clear();
+                    guard.queued_bytes = 0;
+                    guard.spill = None;
+                    guard.active = false;
+                    notify.notify_waiters();
+                    warn!(
+                        target: "audio_pipeline",
+                        "pcm subscriber closed before frame delivery"
+                    );
+                    return;
+                }
+
+                notify.notify_waiters();
+            }
+        });
+        depth
+    }
+}
+
+impl AudioPipeline {
+    /// Applies to existing and future PCM subscribers.
+    pub fn set_backpressure_policy(&self, policy: BackpressurePolicy) {
+        let limit = if policy.max_coalesced_frame.is_zero() {
+            0
+        } else {
+            duration_to_samples(policy.max_coalesced_frame, SAMPLE_RATE_HZ)
+        };
+        self.coalesce_limit.store(limit, Ordering::Relaxed);
+    }
+
+    pub fn backpressure_policy(&self) -> BackpressurePolicy {
+        BackpressurePolicy {
+            max_coalesced_frame: samples_to_duration(self.coalesce_limit.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub fn subscribe_pcm_frames(&self, capacity: usize) -> mpsc::Receiver<Arc<[f32]>> {
+        self.subscribe_pcm_frames_with_options(capacity, false, false)
+    }
+
+    pub fn subscribe_lossless_pcm_frames(&self, capacity: usize) -> mpsc::Receiver<Arc<[f32]>> {
+        self.subscribe_pcm_frames_with_options(capacity, true, false)
+    }
+
+    /// Lossless subscription to frames as they were before automatic gain
+    /// control, for archiving what the microphone actually captured.
+    pub fn subscribe_raw_pcm_frames(&self, capacity: usize) -> mpsc::Receiver<Arc<[f32]>> {
+        self.subscribe_pcm_frames_with_options(capacity, true, true)
+    }
+
+    fn subscribe_pcm_frames_with_options(
+        &self,
+        capacity: usize,
+        lossless: bool,
+        raw: bool,
+    ) -> mpsc::Receiver<Arc<[f32]>> {
+        let bounded = capacity.max(1);
+        let max_queue = if lossless {
+            bounded
+        } else {
+            bounded.saturating_mul(4).max(bounded)
+        };
+        let (tx, rx) = mpsc::channel(bounded);
+        let spill = if lossless {
+            self.spill
+                .lock()
+                .expect("spill config mutex poisoned")
+                .clone()
+        } else {
+            None
+        };
+        let mut subscriber = PcmSubscriber::new(
+            tx,
+            max_queue,
+            lossless,
+            spill,
+            Arc::clone(&self.coalesce_limit),
+        );
+        subscriber.raw = raw;
+        let mut guard = self
+            .pcm_subscribers
+            .lock()
+            .expect("pcm subscriber registry poisoned");
+        guard.push(subscriber);
+        rx
+    }
+
+    pub(super) fn collect_subscribers(&self) -> Vec<PcmSubscriber> {
+        let mut guard = self
+            .pcm_subscribers
+            .lock()
+            .expect("pcm subscriber registry poisoned");
+        guard.retain(|subscriber| !subscriber.is_closed());
+        guard.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::MIN_FRAME_MS;
+    use tokio::time::{sleep, timeout};
+
+    #[tokio::test]
+    async fn slow_subscriber_does_not_block_realtime_feed() {
+        let pipeline = AudioPipeline::new();
+        let mut fast = pipeline.subscribe_pcm_frames(4);
+        let slow = pipeline.subscribe_pcm_frames(1);
+
+        let frame = vec![
+            0.05_f32;
+            duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ)
+        ];
+
+        pipeline
+            .push_pcm_frame(frame.clone())
+            .await
+            .expect("first frame should succeed");
+
+        let _ = timeout(Duration::from_millis(100), fast.recv())
+            .await
+            .expect("fast subscriber timed out")
+            .expect("fast channel closed unexpectedly");
+
+        // Intentionally avoid consuming from the slow subscriber so its bounded queue stays full.
+
+        timeout(
+            Duration::from_millis(100),
+            pipeline.push_pcm_frame(frame.clone()),
+        )
+        .await
+        .expect("push_pcm_frame stalled on slow subscriber")
+        .expect("pipeline rejected frame");
+
+        let received = timeout(Duration::from_millis(100), fast.recv())
+            .await
+            .expect("fast subscriber did not receive second frame")
+            .expect("fast channel closed unexpectedly");
+        assert_eq!(received.len(), frame.len());
+
+        drop(slow);
+        sleep(Duration::from_millis(10)).await;
+    }
+
+    #[tokio::test]
+    async fn lagging_subscriber_coalesces_before_dropping() {
+        let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+        let drain = |mut rx: mpsc::Receiver<Arc<[f32]>>| async move {
+            let mut frames = Vec::new();
+            while let Ok(Some(frame)) = timeout(Duration::from_millis(50), rx.recv()).await {
+                frames.push(frame.len());
+            }
+            frames
+        };
+
+        let pipeline = AudioPipeline::new();
+        let rx = pipeline.subscribe_pcm_frames(1);
+        let coalesced = metrics().pcm_frames_coalesced.get();
+        for _ in 0..10 {
+            pipeline.push_pcm_frame(vec![0.1; frame_len]).await.unwrap();
+            // Let delivery fill the channel so the subscriber is visibly lagging.
+            tokio::task::yield_now().await;
+        }
+        assert!(metrics().pcm_queue_depth.get() >= 1);
+        let frames = drain(rx).await;
+        // Every sample arrives, in fewer and larger frames.
+        assert_eq!(frames.iter().sum::<usize>(), 10 * frame_len);
+        assert!(frames.len() < 10);
+        assert!(frames.iter().all(|len| *len <= 10 * frame_len));
+        assert!(metrics().pcm_frames_coalesced.get() > coalesced);
+
+        pipeline.set_backpressure_policy(BackpressurePolicy {
+            max_coalesced_frame: Duration::ZERO,
+        });
+        assert_eq!(
+            pipeline.backpressure_policy().max_coalesced_frame,
+            Duration::ZERO
+        );
+        let rx = pipeline.subscribe_pcm_frames(1);
+        let dropped = metrics().pcm_frames_dropped.get();
+        for _ in 0..10 {
+            pipeline.push_pcm_frame(vec![0.1; frame_len]).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+        let frames = drain(rx).await;
+        assert!(frames.iter().all(|len| *len == frame_len));
+        assert!(frames.len() < 10);
+        assert!(metrics().pcm_frames_dropped.get() > dropped);
+    }
+
+    #[tokio::test]
+    async fn preserves_order_under_backpressure() {
+        let pipeline = AudioPipeline::new();
+        let mut rx = pipeline.subscribe_pcm_frames(2);
+
+        let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+
+        for marker in 0..3 {
+            let frame = vec![marker as f32; frame_len];
+            pipeline
+                .push_pcm_frame(frame)
+                .await
+                .expect("pushes frame under backpressure");
+        }
+
+        for expected in 0..3 {
+            let received = timeout(Duration::from_millis(200), rx.recv())
+                .await
+                .expect("timed out waiting for ordered frame")
+                .expect("channel closed unexpectedly");
+
+            assert_eq!(received.len(), frame_len);
+            assert!(received
+                .iter()
+                .all(|sample| (*sample - expected as f32).abs() < f32::EPSILON));
+        }
+    }
+
+    #[tokio::test]
+    async fn drops_oldest_frame_when_queue_is_full() {
+        let pipeline = AudioPipeline::new();
+        let mut rx = pipeline.subscribe_pcm_frames(1);
+
+        let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+
+        for marker in 0..10 {
+            pipeline
+                .push_pcm_frame(vec![marker as f32; frame_len])
+                .await
+                .expect("push frame while subscriber is stalled");
+        }
+
+        sleep(Duration::from_millis(10)).await;
+
+        let mut seen = Vec::new();
+        while let Ok(Some(frame)) = timeout(Duration::from_millis(500), rx.recv()).await {
+            assert_eq!(frame.len(), frame_len);
+            seen.push(frame[0]);
+        }
+
+        assert!(!seen.is_empty(), "no frames observed after backlog");
+        assert!(seen.len() < 10, "backlog did not shed frames: {:?}", seen);
+        assert!(seen.len() <= 5, "unexpected backlog size: {:?}", seen);
+        assert!(
+            seen.windows(2).all(|w| w[0] <= w[1]),
+            "frames not monotonic: {:?}",
+            seen
+        );
+        assert_eq!(seen.last().copied(), Some(9.0_f32));
+    }
+}
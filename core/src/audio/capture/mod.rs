@@ -0,0 +1,497 @@
+use anyhow::{anyhow, bail, Result};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task;
+use tracing::{info, warn};
+
+use super::{downmix_interleaved, AudioDevice, AudioPipeline, DownmixPolicy, PcmFormat};
+
+#[cfg(feature = "native-capture")]
+mod native;
+#[cfg(feature = "native-capture")]
+pub use native::CpalCaptureBackend;
+
+/// Raw buffers queued between the backend callback and the pipeline before
+/// new ones are dropped.
+const SINK_CAPACITY: usize = 64;
+/// How often [`record_microphone`] drains the sink while it waits.
+const RECORD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What a capture stream records. Carried into transcripts and history so
+/// meeting audio taken from the speakers can be told apart from dictation.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioSource {
+    #[default]
+    Microphone,
+    /// What the system plays back (WASAPI loopback, ScreenCaptureKit audio).
+    SystemLoopback,
+}
+
+impl AudioSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioSource::Microphone => "microphone",
+            AudioSource::SystemLoopback => "system_loopback",
+        }
+    }
+
+    /// Speaker label shown when sources are mixed: the user speaks into the
+    /// microphone, everyone else comes out of the speakers.
+    pub fn speaker_label(&self) -> &'static str {
+        match self {
+            AudioSource::Microphone => "me",
+            AudioSource::SystemLoopback => "others",
+        }
+    }
+}
+
+/// How a capture stream shares the device with other applications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShareMode {
+    /// Mixed with other clients by the OS audio engine.
+    #[default]
+    Shared,
+    /// Exclusive access (WASAPI exclusive mode, hog mode on CoreAudio) for the
+    /// lowest latency. Backends without support fall back to shared mode.
+    Exclusive,
+}
+
+/// Exponential backoff between attempts to reopen a failed stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Consecutive failed attempts before capture gives up; `None` retries
+    /// until the pipeline is stopped.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before reconnect attempt `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureConfig {
+    pub source: AudioSource,
+    /// Device to capture from; `None` uses the system default. A device that
+    /// has disappeared falls back to the default, then to the first device.
+    pub device_id: Option<String>,
+    pub share_mode: ShareMode,
+    pub reconnect: ReconnectPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureEvent {
+    /// Raw PCM in the format the stream was opened with.
+    Data(Bytes),
+    /// The stream failed and will deliver nothing more.
+    Error(String),
+}
+
+/// Handed to [`CaptureBackend::open`]; safe to call from real-time audio
+/// callbacks since it never blocks.
+#[derive(Clone)]
+pub struct CaptureSink {
+    tx: mpsc::Sender<CaptureEvent>,
+}
+
+impl CaptureSink {
+    /// Returns `false` when the buffer was dropped because the pipeline is
+    /// behind or capture has ended.
+    pub fn push(&self, pcm: Bytes) -> bool {
+        self.tx.try_send(CaptureEvent::Data(pcm)).is_ok()
+    }
+
+    pub fn fail(&self, message: impl Into<String>) {
+        let _ = self.tx.try_send(CaptureEvent::Error(message.into()));
+    }
+}
+
+/// Keeps a backend stream alive; dropping it stops capture.
+pub struct CaptureHandle {
+    on_close: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl CaptureHandle {
+    pub fn new(on_close: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            on_close: Some(Box::new(on_close)),
+        }
+    }
+}
+
+impl Drop for CaptureHandle {
+    fn drop(&mut self) {
+        if let Some(close) = self.on_close.take() {
+            close();
+        }
+    }
+}
+
+/// The stream a backend is asked to describe or open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureTarget {
+    pub source: AudioSource,
+    /// An input device for the microphone, a playback device for loopback.
+    pub device: AudioDevice,
+    pub share_mode: ShareMode,
+}
+
+/// A native audio input API. Calls may block and are run off the runtime.
+pub trait CaptureBackend: Send + Sync {
+    fn input_devices(&self) -> Result<Vec<AudioDevice>>;
+
+    /// Playback devices whose output can be recorded. Backends without
+    /// loopback support keep the default, which rejects the source.
+    fn loopback_devices(&self) -> Result<Vec<AudioDevice>> {
+        bail!("system-audio loopback is not supported by this capture backend")
+    }
+
+    /// Formats `target` can deliver, offered to [`PcmFormat::negotiate`].
+    fn supported_formats(&self, target: &CaptureTarget) -> Result<Vec<PcmFormat>>;
+
+    /// Start streaming `target` in `format` into `sink` until the returned
+    /// handle is dropped.
+    fn open(
+        &self,
+        target: &CaptureTarget,
+        format: PcmFormat,
+        sink: CaptureSink,
+    ) -> Result<CaptureHandle>;
+}
+
+/// An open stream and the events it produces.
+pub(crate) struct OpenCapture {
+    /// Only held so the stream closes together with its event queue.
+    _handle: CaptureHandle,
+    events: mpsc::Receiver<CaptureEvent>,
+}
+
+/// The requested device, else the system default, else the first device.
+pub(crate) fn select_device<'a>(
+    devices: &'a [AudioDevice],
+    requested: Option<&str>,
+) -> Option<&'a AudioDevice> {
+    requested
+        .and_then(|id| devices.iter().find(|device| device.id == id))
+        .or_else(|| devices.iter().find(|device| device.is_default))
+        .or_else(|| devices.first())
+}
+
+/// Resolve a device, negotiate its format and open a stream. For the
+/// microphone the pipeline's active device, which tracks hot-plug
+/// migrations, wins over the configured one.
+pub(crate) async fn open_capture(
+    pipeline: &AudioPipeline,
+    backend: &Arc<dyn CaptureBackend>,
+    config: &CaptureConfig,
+) -> Result<OpenCapture> {
+    let source = config.source;
+    let requested = match source {
+        AudioSource::Microphone => pipeline
+            .active_device()
+            .or_else(|| config.device_id.clone()),
+        AudioSource::SystemLoopback => config.device_id.clone(),
+    };
+    let share_mode = config.share_mode;
+    let backend_for_probe = Arc::clone(backend);
+    let (target, formats) = task::spawn_blocking(move || -> Result<_> {
+        let devices = match source {
+            AudioSource::Microphone => backend_for_probe.input_devices()?,
+            AudioSource::SystemLoopback => backend_for_probe.loopback_devices()?,
+        };
+        let device = select_device(&devices, requested.as_deref())
+            .cloned()
+            .ok_or_else(|| anyhow!("no {} device available", source.as_str()))?;
+        if requested.as_deref().is_some_and(|id| id != device.id) {
+            warn!(
+                target: "audio_capture",
+                requested = ?requested,
+                fallback = %device.id,
+                "requested capture device not found"
+            );
+        }
+        let target = CaptureTarget {
+            source,
+            device,
+            share_mode,
+        };
+        let formats = backend_for_probe.supported_formats(&target)?;
+        Ok((target, formats))
+    })
+    .await
+    .map_err(|err| anyhow!("blocking capture probe task failed: {err}"))??;
+
+    let format = pipeline.negotiate_pcm_format(&formats)?;
+    let (tx, events) = mpsc::channel(SINK_CAPACITY);
+    let sink = CaptureSink { tx };
+    let backend_for_open = Arc::clone(backend);
+    let target_for_open = target.clone();
+    let handle =
+        task::spawn_blocking(move || backend_for_open.open(&target_for_open, format, sink))
+            .await
+            .map_err(|err| anyhow!("blocking capture open task failed: {err}"))??;
+
+    if source == AudioSource::Microphone {
+        pipeline.set_active_device(Some(target.device.id.clone()));
+    }
+    info!(
+        target: "audio_capture",
+        source = source.as_str(),
+        device = %target.device.id,
+        encoding = format.encoding.as_str(),
+        channels = format.channels,
+        sample_rate_hz = format.sample_rate_hz,
+        "capture stream opened"
+    );
+    Ok(OpenCapture {
+        _handle: handle,
+        events,
+    })
+}
+
+/// Record `duration` of microphone audio straight from `backend`, outside the
+/// pipeline, and return it as mono samples with their sample rate. Used by
+/// probes such as the self-check. Blocking.
+pub fn record_microphone(
+    backend: &dyn CaptureBackend,
+    device_id: Option<&str>,
+    duration: Duration,
+) -> Result<(Vec<f32>, u32)> {
+    let devices = backend.input_devices()?;
+    let device = select_device(&devices, device_id)
+        .cloned()
+        .ok_or_else(|| anyhow!("no microphone device available"))?;
+    let target = CaptureTarget {
+        source: AudioSource::Microphone,
+        device,
+        share_mode: ShareMode::Shared,
+    };
+    let format = PcmFormat::negotiate(&backend.supported_formats(&target)?)?;
+    let (tx, mut events) = mpsc::channel(SINK_CAPACITY);
+    let handle = backend.open(&target, format, CaptureSink { tx })?;
+
+    let deadline = std::time::Instant::now() + duration;
+    let mut pcm = Vec::new();
+    while std::time::Instant::now() < deadline {
+        match events.try_recv() {
+            Ok(CaptureEvent::Data(bytes)) => pcm.extend_from_slice(&bytes),
+            Ok(CaptureEvent::Error(message)) => bail!("capture stream failed: {message}"),
+            Err(mpsc::error::TryRecvError::Empty) => std::thread::sleep(RECORD_POLL_INTERVAL),
+            Err(mpsc::error::TryRecvError::Disconnected) => break,
+        }
+    }
+    drop(handle);
+
+    let interleaved = format.decode(&pcm)?;
+    let mono = downmix_interleaved(
+        &interleaved,
+        format.channels as usize,
+        DownmixPolicy::Average,
+    )?;
+    Ok((mono, format.sample_rate_hz))
+}
+
+/// Feed the pipeline from `capture`, reopening the stream with backoff when
+/// it fails. Returns once the reconnect budget is exhausted; the pipeline
+/// aborts the task on stop.
+pub(crate) async fn supervise_capture(
+    pipeline: AudioPipeline,
+    backend: Arc<dyn CaptureBackend>,
+    config: CaptureConfig,
+    mut capture: OpenCapture,
+) {
+    loop {
+        let reason = loop {
+            match capture.events.recv().await {
+                Some(CaptureEvent::Data(pcm)) => {
+                    if let Err(err) = pipeline.handle_frame(pcm).await {
+                        warn!(target: "audio_capture", %err, "failed to process captured audio");
+                    }
+                }
+                Some(CaptureEvent::Error(message)) => break message,
+                None => break "capture stream ended".to_string(),
+            }
+        };
+        drop(capture);
+        warn!(target: "audio_capture", %reason, "capture stream lost; reconnecting");
+
+        let mut attempt = 0;
+        capture = loop {
+            attempt += 1;
+            if config
+                .reconnect
+                .max_attempts
+                .is_some_and(|max| attempt > max)
+            {
+                warn!(
+                    target: "audio_capture",
+                    attempts = attempt - 1,
+                    "giving up on audio capture"
+                );
+                return;
+            }
+            tokio::time::sleep(config.reconnect.backoff(attempt)).await;
+            match open_capture(&pipeline, &backend, &config).await {
+                Ok(capture) => break capture,
+                Err(err) => {
+                    warn!(target: "audio_capture", attempt, %err, "capture reconnect failed")
+                }
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::SampleEncoding;
+    use std::sync::Mutex;
+    use tokio::time::timeout;
+
+    fn device(id: &str, is_default: bool) -> AudioDevice {
+        AudioDevice {
+            id: id.into(),
+            label: id.to_uppercase(),
+            is_default,
+        }
+    }
+
+    /// Each open streams one i16 buffer; the first stream then fails.
+    struct FlakyBackend {
+        devices: Vec<AudioDevice>,
+        opened: Mutex<Vec<String>>,
+        handles_closed: Arc<Mutex<usize>>,
+    }
+
+    impl CaptureBackend for FlakyBackend {
+        fn input_devices(&self) -> Result<Vec<AudioDevice>> {
+            Ok(self.devices.clone())
+        }
+
+        fn supported_formats(&self, _: &CaptureTarget) -> Result<Vec<PcmFormat>> {
+            Ok(vec![PcmFormat::new(SampleEncoding::I16, 1, 16_000)])
+        }
+
+        fn open(
+            &self,
+            target: &CaptureTarget,
+            _: PcmFormat,
+            sink: CaptureSink,
+        ) -> Result<CaptureHandle> {
+            let mut opened = self.opened.lock().unwrap();
+            opened.push(target.device.id.clone());
+            let value = 8_192_i16 * opened.len() as i16;
+            let pcm: Vec<u8> = std::iter::repeat_n(value, 1_600)
+                .flat_map(i16::to_le_bytes)
+                .collect();
+            assert!(sink.push(Bytes::from(pcm)));
+            if opened.len() == 1 {
+                sink.fail("device unplugged");
+            }
+            let closed = Arc::clone(&self.handles_closed);
+            Ok(CaptureHandle::new(move || {
+                drop(sink);
+                *closed.lock().unwrap() += 1;
+            }))
+        }
+    }
+
+    #[test]
+    fn selects_requested_then_default_device() {
+        let devices = [device("usb", false), device("builtin", true)];
+        assert_eq!(select_device(&devices, Some("usb")).unwrap().id, "usb");
+        assert_eq!(select_device(&devices, Some("gone")).unwrap().id, "builtin");
+        assert_eq!(select_device(&devices[..1], None).unwrap().id, "usb");
+        assert!(select_device(&[], None).is_none());
+
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(30), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn start_acquires_audio_and_reconnects_after_failure() {
+        let backend = Arc::new(FlakyBackend {
+            devices: vec![device("builtin", true), device("usb", false)],
+            opened: Mutex::new(Vec::new()),
+            handles_closed: Arc::new(Mutex::new(0)),
+        });
+        let pipeline = AudioPipeline::new();
+        let mut rx = pipeline.subscribe_pcm_frames(8);
+        pipeline.set_capture_backend(
+            backend.clone(),
+            CaptureConfig {
+                device_id: Some("usb".into()),
+                reconnect: ReconnectPolicy {
+                    initial_backoff: Duration::from_millis(5),
+                    max_backoff: Duration::from_millis(5),
+                    max_attempts: Some(1),
+                },
+                ..CaptureConfig::default()
+            },
+        );
+        pipeline.start().await.unwrap();
+        assert_eq!(pipeline.active_device().as_deref(), Some("usb"));
+
+        for expected in [0.25, 0.5] {
+            let frame = timeout(Duration::from_secs(1), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(frame.len(), 1_600);
+            assert!((frame[0] - expected).abs() < 1e-6);
+        }
+        assert_eq!(*backend.opened.lock().unwrap(), ["usb", "usb"]);
+
+        pipeline.stop().await.unwrap();
+        assert_eq!(*backend.handles_closed.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn loopback_requires_backend_support() {
+        let backend = Arc::new(FlakyBackend {
+            devices: vec![device("builtin", true)],
+            opened: Mutex::new(Vec::new()),
+            handles_closed: Arc::new(Mutex::new(0)),
+        });
+        let pipeline = AudioPipeline::new();
+        pipeline.set_capture_backend(
+            backend.clone(),
+            CaptureConfig {
+                source: AudioSource::SystemLoopback,
+                ..CaptureConfig::default()
+            },
+        );
+        assert_eq!(pipeline.audio_source(), AudioSource::SystemLoopback);
+
+        let err = pipeline.start().await.unwrap_err();
+        assert!(err.to_string().contains("loopback is not supported"));
+        // Input devices are never tried for loopback, nor remembered as active.
+        assert!(backend.opened.lock().unwrap().is_empty());
+        assert!(pipeline.active_device().is_none());
+    }
+}
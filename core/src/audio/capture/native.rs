@@ -0,0 +1,193 @@
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{BufferSize, SampleFormat, SampleRate, StreamConfig};
+use std::sync::mpsc as std_mpsc;
+use std::thread;
+use tracing::warn;
+
+use super::{AudioSource, CaptureBackend, CaptureHandle, CaptureSink, CaptureTarget, ShareMode};
+use crate::audio::{AudioDevice, PcmFormat, SampleEncoding, SAMPLE_RATE_HZ};
+
+/// Capture through cpal, which drives CoreAudio on macOS and WASAPI on
+/// Windows. Device ids are the names cpal reports. cpal only opens shared-mode
+/// streams, so [`ShareMode::Exclusive`] is served in shared mode with a
+/// warning. System-audio loopback is available on Windows, where WASAPI
+/// records a playback device opened as an input stream; macOS needs a
+/// ScreenCaptureKit backend supplied by the host.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpalCaptureBackend;
+
+impl CpalCaptureBackend {
+    fn find_device(&self, target: &CaptureTarget) -> Result<cpal::Device> {
+        let host = cpal::default_host();
+        let id = target.device.id.as_str();
+        let mut devices = match target.source {
+            AudioSource::Microphone => host.input_devices()?,
+            AudioSource::SystemLoopback => host.output_devices()?,
+        };
+        devices
+            .find(|device| device.name().is_ok_and(|name| name == id))
+            .ok_or_else(|| anyhow!("{} device {id} not found", target.source.as_str()))
+    }
+}
+
+fn describe_devices(
+    devices: impl Iterator<Item = cpal::Device>,
+    default: Option<cpal::Device>,
+) -> Vec<AudioDevice> {
+    let default_name = default.and_then(|device| device.name().ok());
+    devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| AudioDevice {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            label: name.clone(),
+            id: name,
+        })
+        .collect()
+}
+
+fn encoding_of(format: SampleFormat) -> Option<SampleEncoding> {
+    match format {
+        SampleFormat::F32 => Some(SampleEncoding::F32),
+        SampleFormat::I16 => Some(SampleEncoding::I16),
+        _ => None,
+    }
+}
+
+impl CaptureBackend for CpalCaptureBackend {
+    fn input_devices(&self) -> Result<Vec<AudioDevice>> {
+        let host = cpal::default_host();
+        Ok(describe_devices(
+            host.input_devices()?,
+            host.default_input_device(),
+        ))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn loopback_devices(&self) -> Result<Vec<AudioDevice>> {
+        let host = cpal::default_host();
+        Ok(describe_devices(
+            host.output_devices()?,
+            host.default_output_device(),
+        ))
+    }
+
+    fn supported_formats(&self, target: &CaptureTarget) -> Result<Vec<PcmFormat>> {
+        if target.share_mode == ShareMode::Exclusive {
+            warn!(
+                target: "audio_capture",
+                device = %target.device.id,
+                "exclusive mode is not available through cpal; using shared mode"
+            );
+        }
+        let device = self.find_device(target)?;
+        if target.source == AudioSource::SystemLoopback {
+            // Loopback streams run at the playback mix format.
+            let config = device.default_output_config()?;
+            return Ok(encoding_of(config.sample_format())
+                .map(|encoding| PcmFormat::new(encoding, config.channels(), config.sample_rate().0))
+                .into_iter()
+                .collect());
+        }
+        let formats = device
+            .supported_input_configs()?
+            .filter_map(|range| {
+                let encoding = encoding_of(range.sample_format())?;
+                // Prefer the engine rate so the pipeline can skip resampling.
+                let rate = if (range.min_sample_rate().0..=range.max_sample_rate().0)
+                    .contains(&SAMPLE_RATE_HZ)
+                {
+                    SAMPLE_RATE_HZ
+                } else {
+                    range.max_sample_rate().0
+                };
+                Some(PcmFormat::new(encoding, range.channels(), rate))
+            })
+            .collect();
+        Ok(formats)
+    }
+
+    fn open(
+        &self,
+        target: &CaptureTarget,
+        format: PcmFormat,
+        sink: CaptureSink,
+    ) -> Result<CaptureHandle> {
+        let device = self.find_device(target)?;
+        let config = StreamConfig {
+            channels: format.channels,
+            sample_rate: SampleRate(format.sample_rate_hz),
+            buffer_size: BufferSize::Default,
+        };
+        let (ready_tx, ready_rx) = std_mpsc::sync_channel(1);
+        let (stop_tx, stop_rx) = std_mpsc::channel::<()>();
+
+        // cpal streams are not `Send` on every platform, so each one lives on
+        // its own thread until the handle is dropped.
+        thread::Builder::new()
+            .name("flowwisper-capture".into())
+            .spawn(move || {
+                let stream = match build_stream(&device, &config, format.encoding, sink) {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                        return;
+                    }
+                };
+                if let Err(err) = stream.play() {
+                    let _ = ready_tx.send(Err(anyhow!("failed to start capture: {err}")));
+                    return;
+                }
+                let _ = ready_tx.send(Ok(()));
+                // Returns once the handle drops the sender.
+                let _ = stop_rx.recv();
+                drop(stream);
+            })?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| anyhow!("capture thread exited before the stream started"))??;
+        Ok(CaptureHandle::new(move || drop(stop_tx)))
+    }
+}
+
+fn build_stream(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    encoding: SampleEncoding,
+    sink: CaptureSink,
+) -> Result<cpal::Stream> {
+    let error_sink = sink.clone();
+    let on_error = move |err: cpal::StreamError| error_sink.fail(err.to_string());
+    let stream = match encoding {
+        SampleEncoding::F32 => device.build_input_stream(
+            config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let bytes: Vec<u8> = data
+                    .iter()
+                    .flat_map(|sample| sample.to_le_bytes())
+                    .collect();
+                sink.push(Bytes::from(bytes));
+            },
+            on_error,
+            None,
+        )?,
+        SampleEncoding::I16 => device.build_input_stream(
+            config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                let bytes: Vec<u8> = data
+                    .iter()
+                    .flat_map(|sample| sample.to_le_bytes())
+                    .collect();
+                sink.push(Bytes::from(bytes));
+            },
+            on_error,
+            None,
+        )?,
+        SampleEncoding::I24In32 => {
+            return Err(anyhow!("cpal does not deliver 24-bit samples"));
+        }
+    };
+    Ok(stream)
+}
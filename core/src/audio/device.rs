@@ -0,0 +1,162 @@
+use anyhow::Result;
+
+/// An input device as reported by the host audio API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioDevice {
+    pub id: String,
+    pub label: String,
+    pub is_default: bool,
+}
+
+/// Changes in the set of input devices, plus the pipeline's reaction to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioDeviceEvent {
+    Added(AudioDevice),
+    Removed(AudioDevice),
+    /// The system default input changed; `current` is `None` when no device
+    /// is marked as default any more.
+    DefaultChanged {
+        previous: Option<String>,
+        current: Option<AudioDevice>,
+    },
+    /// The active capture device disappeared and the pipeline switched to
+    /// `to`. Hosts should reopen their capture stream on `to`.
+    Migrated {
+        from: String,
+        to: AudioDevice,
+    },
+}
+
+/// Enumerates input devices. Implemented by the host on top of cpal or the
+/// platform APIs; core only diffs the snapshots.
+pub trait DeviceEnumerator: Send + Sync {
+    fn input_devices(&self) -> Result<Vec<AudioDevice>>;
+}
+
+/// Polls a [`DeviceEnumerator`] and reports what changed since the last poll.
+pub struct DeviceWatcher {
+    enumerator: Box<dyn DeviceEnumerator>,
+    known: Option<Vec<AudioDevice>>,
+}
+
+impl DeviceWatcher {
+    pub fn new(enumerator: Box<dyn DeviceEnumerator>) -> Self {
+        Self {
+            enumerator,
+            known: None,
+        }
+    }
+
+    /// Devices seen by the latest poll.
+    pub fn devices(&self) -> &[AudioDevice] {
+        self.known.as_deref().unwrap_or_default()
+    }
+
+    /// The first poll only records the initial snapshot and reports nothing.
+    pub fn poll(&mut self) -> Result<Vec<AudioDeviceEvent>> {
+        let current = self.enumerator.input_devices()?;
+        let events = match &self.known {
+            Some(previous) => diff_devices(previous, &current),
+            None => Vec::new(),
+        };
+        self.known = Some(current);
+        Ok(events)
+    }
+}
+
+/// Device to fall back to once `removed` is gone: the system default if there
+/// is one, otherwise the first remaining device.
+pub(crate) fn fallback_device<'a>(
+    devices: &'a [AudioDevice],
+    removed: &str,
+) -> Option<&'a AudioDevice> {
+    let mut remaining = devices.iter().filter(|device| device.id != removed);
+    let first = remaining.clone().next();
+    remaining.find(|device| device.is_default).or(first)
+}
+
+fn diff_devices(previous: &[AudioDevice], current: &[AudioDevice]) -> Vec<AudioDeviceEvent> {
+    let mut events: Vec<AudioDeviceEvent> = previous
+        .iter()
+        .filter(|device| !current.iter().any(|other| other.id == device.id))
+        .cloned()
+        .map(AudioDeviceEvent::Removed)
+        .collect();
+    events.extend(
+        current
+            .iter()
+            .filter(|device| !previous.iter().any(|other| other.id == device.id))
+            .cloned()
+            .map(AudioDeviceEvent::Added),
+    );
+
+    let default_id =
+        |devices: &[AudioDevice]| devices.iter().find(|d| d.is_default).map(|d| d.id.clone());
+    let previous_default = default_id(previous);
+    if previous_default != default_id(current) {
+        events.push(AudioDeviceEvent::DefaultChanged {
+            previous: previous_default,
+            current: current.iter().find(|device| device.is_default).cloned(),
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn device(id: &str, is_default: bool) -> AudioDevice {
+        AudioDevice {
+            id: id.into(),
+            label: id.to_uppercase(),
+            is_default,
+        }
+    }
+
+    struct Scripted(Arc<Mutex<Vec<AudioDevice>>>);
+
+    impl DeviceEnumerator for Scripted {
+        fn input_devices(&self) -> Result<Vec<AudioDevice>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    #[test]
+    fn reports_hot_plug_and_default_changes() {
+        let devices = Arc::new(Mutex::new(vec![device("builtin", true)]));
+        let mut watcher = DeviceWatcher::new(Box::new(Scripted(Arc::clone(&devices))));
+        assert!(watcher.poll().unwrap().is_empty());
+
+        *devices.lock().unwrap() = vec![device("builtin", false), device("usb", true)];
+        let events = watcher.poll().unwrap();
+        assert_eq!(
+            events,
+            vec![
+                AudioDeviceEvent::Added(device("usb", true)),
+                AudioDeviceEvent::DefaultChanged {
+                    previous: Some("builtin".into()),
+                    current: Some(device("usb", true)),
+                },
+            ]
+        );
+
+        *devices.lock().unwrap() = vec![device("builtin", false)];
+        let events = watcher.poll().unwrap();
+        assert_eq!(events[0], AudioDeviceEvent::Removed(device("usb", true)));
+        assert!(matches!(
+            events[1],
+            AudioDeviceEvent::DefaultChanged { current: None, .. }
+        ));
+        assert_eq!(watcher.devices().len(), 1);
+    }
+
+    #[test]
+    fn falls_back_to_default_then_first_device() {
+        let devices = [device("a", false), device("b", true), device("c", false)];
+        assert_eq!(fallback_device(&devices, "c").unwrap().id, "b");
+        assert_eq!(fallback_device(&devices, "b").unwrap().id, "a");
+        assert!(fallback_device(&devices[..1], "a").is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{bail, Result};
+
+/// Strategy used to fold interleaved multi-channel PCM into the mono stream
+/// consumed by the rest of the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownmixPolicy {
+    /// Average all channels of each sample frame.
+    #[default]
+    Average,
+    /// Keep only the first (left) channel and discard the rest.
+    LeftOnly,
+    /// Keep the channel with the highest RMS within the pushed buffer. Useful
+    /// for interfaces where only one input carries the microphone signal.
+    LoudestChannel,
+}
+
+impl DownmixPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DownmixPolicy::Average => "average",
+            DownmixPolicy::LeftOnly => "left_only",
+            DownmixPolicy::LoudestChannel => "loudest_channel",
+        }
+    }
+}
+
+/// Fold an interleaved buffer with `channels` channels into mono samples.
+pub fn downmix_interleaved(
+    frame: &[f32],
+    channels: usize,
+    policy: DownmixPolicy,
+) -> Result<Vec<f32>> {
+    if channels == 0 {
+        bail!("interleaved frame must have at least one channel");
+    }
+    if !frame.len().is_multiple_of(channels) {
+        bail!(
+            "interleaved frame length {} is not a multiple of {channels} channels",
+            frame.len()
+        );
+    }
+    if channels == 1 {
+        return Ok(frame.to_vec());
+    }
+
+    let mono = match policy {
+        DownmixPolicy::Average => frame
+            .chunks_exact(channels)
+            .map(|samples| samples.iter().sum::<f32>() / channels as f32)
+            .collect(),
+        DownmixPolicy::LeftOnly => extract_channel(frame, channels, 0),
+        DownmixPolicy::LoudestChannel => {
+            extract_channel(frame, channels, loudest_channel(frame, channels))
+        }
+    };
+    Ok(mono)
+}
+
+fn extract_channel(frame: &[f32], channels: usize, channel: usize) -> Vec<f32> {
+    frame
+        .chunks_exact(channels)
+        .map(|samples| samples[channel])
+        .collect()
+}
+
+fn loudest_channel(frame: &[f32], channels: usize) -> usize {
+    let mut energy = vec![0.0_f32; channels];
+    for samples in frame.chunks_exact(channels) {
+        for (total, sample) in energy.iter_mut().zip(samples) {
+            *total += sample * sample;
+        }
+    }
+    energy
+        .iter()
+        .enumerate()
+        .fold((0, f32::MIN), |best, (index, value)| {
+            if *value > best.1 {
+                (index, *value)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interleave(left: &[f32], right: &[f32]) -> Vec<f32> {
+        left.iter().zip(right).flat_map(|(l, r)| [*l, *r]).collect()
+    }
+
+    #[test]
+    fn average_folds_channels_evenly() {
+        let frame = interleave(&[0.2, 0.4, -0.6], &[0.4, 0.0, 0.2]);
+        let mono = downmix_interleaved(&frame, 2, DownmixPolicy::Average).unwrap();
+        let expected = [0.3, 0.2, -0.2];
+        for (sample, expected) in mono.iter().zip(expected) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn left_only_discards_other_channels() {
+        let frame = interleave(&[0.1, 0.2, 0.3], &[0.9, 0.9, 0.9]);
+        let mono = downmix_interleaved(&frame, 2, DownmixPolicy::LeftOnly).unwrap();
+        assert_eq!(mono, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn loudest_channel_picks_highest_energy() {
+        let frame = interleave(&[0.01, -0.01, 0.01], &[0.5, -0.4, 0.3]);
+        let mono = downmix_interleaved(&frame, 2, DownmixPolicy::LoudestChannel).unwrap();
+        assert_eq!(mono, vec![0.5, -0.4, 0.3]);
+    }
+
+    #[test]
+    fn rejects_misaligned_or_channelless_frames() {
+        assert!(downmix_interleaved(&[0.1, 0.2, 0.3], 2, DownmixPolicy::Average).is_err());
+        assert!(downmix_interleaved(&[0.1], 0, DownmixPolicy::Average).is_err());
+        assert_eq!(
+            downmix_interleaved(&[0.1, 0.2], 1, DownmixPolicy::LoudestChannel).unwrap(),
+            vec![0.1, 0.2]
+        );
+    }
+}
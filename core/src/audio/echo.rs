@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+
+/// Captured and reference audio are compared at 16 kHz / 8 = 2 kHz, which
+/// keeps the fundamental and first formants of playback speech and music.
+const DECIMATION: usize = 8;
+/// Length of each correlation window, in decimated samples (200 ms).
+const WINDOW: usize = 400;
+/// Longest speaker-to-microphone delay searched, in decimated samples (300 ms).
+const MAX_LAG: usize = 600;
+/// Reference audio older than this is discarded, in decimated samples. The
+/// extra window covers capture that arrives in the same chunk after a window.
+const REFERENCE_CAPACITY: usize = 2 * WINDOW + MAX_LAG;
+/// Either stream quieter than this (RMS) is not worth correlating.
+const MIN_RMS: f32 = 1e-3;
+const CORRELATION_THRESHOLD: f32 = 0.6;
+/// Consecutive correlated windows required before reporting echo.
+const PERSISTENCE_WINDOWS: u32 = 2;
+/// Windows to stay quiet after a report (~5 s).
+const COOLDOWN_WINDOWS: u32 = 25;
+
+const ECHO_HINT: &str = "检测到扬声器回放声，建议佩戴耳机以免转写电脑自身的声音";
+
+/// The microphone is picking up what the computer is playing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchoDetectedPayload {
+    /// Peak normalised cross-correlation between capture and reference, 0..=1.
+    pub correlation: f32,
+    /// Speaker-to-microphone delay at the correlation peak.
+    pub delay_ms: u32,
+    pub persistence_ms: u32,
+}
+
+impl EchoDetectedPayload {
+    /// User-facing hint to switch to headphones.
+    pub fn hint(&self) -> &'static str {
+        ECHO_HINT
+    }
+}
+
+/// Box-filter decimator carrying partial blocks across calls.
+#[derive(Debug, Default)]
+struct Decimator {
+    sum: f32,
+    count: usize,
+}
+
+impl Decimator {
+    fn push(&mut self, samples: &[f32], out: &mut impl Extend<f32>) {
+        for &sample in samples {
+            self.sum += sample;
+            self.count += 1;
+            if self.count == DECIMATION {
+                out.extend([self.sum / DECIMATION as f32]);
+                self.sum = 0.0;
+                self.count = 0;
+            }
+        }
+    }
+}
+
+/// Correlates captured audio against an optional system-loopback reference.
+/// Both streams are 16 kHz mono and assumed to be fed in real time, so the
+/// newest reference sample lines up with the newest captured sample.
+#[derive(Debug, Default)]
+pub(crate) struct EchoDetector {
+    reference: VecDeque<f32>,
+    reference_decimator: Decimator,
+    capture: Vec<f32>,
+    capture_decimator: Decimator,
+    correlated_windows: u32,
+    cooldown_windows: u32,
+}
+
+impl EchoDetector {
+    pub(crate) fn push_reference(&mut self, samples: &[f32]) {
+        self.reference_decimator.push(samples, &mut self.reference);
+        let excess = self.reference.len().saturating_sub(REFERENCE_CAPACITY);
+        self.reference.drain(..excess);
+    }
+
+    /// Forget capture state between sessions; the reference keeps streaming.
+    pub(crate) fn reset(&mut self) {
+        self.capture.clear();
+        self.capture_decimator = Decimator::default();
+        self.correlated_windows = 0;
+        self.cooldown_windows = 0;
+    }
+
+    pub(crate) fn ingest_capture(&mut self, samples: &[f32]) -> Vec<EchoDetectedPayload> {
+        let mut events = Vec::new();
+        let mut decimated = Vec::with_capacity(samples.len() / DECIMATION + 1);
+        self.capture_decimator.push(samples, &mut decimated);
+
+        for (index, &sample) in decimated.iter().enumerate() {
+            self.capture.push(sample);
+            if self.capture.len() < WINDOW {
+                continue;
+            }
+            let window = std::mem::take(&mut self.capture);
+            // The reference is already level with the end of this chunk.
+            let newer = decimated.len() - index - 1;
+            if let Some(event) = self.evaluate(&window, newer) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn evaluate(&mut self, window: &[f32], newer: usize) -> Option<EchoDetectedPayload> {
+        self.cooldown_windows = self.cooldown_windows.saturating_sub(1);
+        let peak = self.correlate(window, newer);
+        match peak {
+            Some((correlation, _)) if correlation >= CORRELATION_THRESHOLD => {
+                self.correlated_windows += 1;
+            }
+            _ => {
+                self.correlated_windows = 0;
+                return None;
+            }
+        }
+        if self.correlated_windows != PERSISTENCE_WINDOWS || self.cooldown_windows > 0 {
+            return None;
+        }
+        self.cooldown_windows = COOLDOWN_WINDOWS;
+        let (correlation, lag) = peak?;
+        let decimated_ms = |samples: usize| (samples * DECIMATION * 1_000 / 16_000) as u32;
+        Some(EchoDetectedPayload {
+            correlation,
+            delay_ms: decimated_ms(lag),
+            persistence_ms: decimated_ms(WINDOW) * self.correlated_windows,
+        })
+    }
+
+    /// Best normalised correlation and its lag, or `None` when either side is
+    /// too quiet to judge. `newer` reference samples postdate the window.
+    fn correlate(&mut self, window: &[f32], newer: usize) -> Option<(f32, usize)> {
+        let capture_energy: f32 = window.iter().map(|s| s * s).sum();
+        if (capture_energy / window.len() as f32).sqrt() < MIN_RMS {
+            return None;
+        }
+        let reference = self.reference.make_contiguous();
+        let available = reference.len().checked_sub(window.len() + newer)?;
+        let mut best: Option<(f32, usize)> = None;
+        for lag in 0..=available.min(MAX_LAG) {
+            let end = reference.len() - newer - lag;
+            let segment = &reference[end - window.len()..end];
+            let reference_energy: f32 = segment.iter().map(|s| s * s).sum();
+            if (reference_energy / segment.len() as f32).sqrt() < MIN_RMS {
+                continue;
+            }
+            let dot: f32 = window.iter().zip(segment).map(|(a, b)| a * b).sum();
+            let correlation = dot.abs() / (capture_energy * reference_energy).sqrt();
+            if best.is_none_or(|(current, _)| correlation > current) {
+                best = Some((correlation, lag));
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_delayed_playback_in_capture() {
+        let reference = signal(16_000, 7);
+        let delay = 1_280; // 80 ms
+        let mut capture = vec![0.0; delay];
+        capture.extend(reference.iter().map(|sample| sample * 0.3));
+        let noise = signal(capture.len(), 99);
+        for (sample, noise) in capture.iter_mut().zip(noise) {
+            *sample += noise * 0.05;
+        }
+
+        let mut detector = EchoDetector::default();
+        let mut events = Vec::new();
+        for (reference, capture) in reference.chunks(1_600).zip(capture.chunks(1_600)) {
+            detector.push_reference(reference);
+            events.extend(detector.ingest_capture(capture));
+        }
+        assert_eq!(events.len(), 1, "one report per cooldown");
+        assert_eq!(events[0].delay_ms, 80);
+        assert!(events[0].correlation > 0.8);
+        assert!(events[0].hint().contains("耳机"));
+    }
+
+    #[test]
+    fn ignores_unrelated_audio_and_missing_reference() {
+        let mut detector = EchoDetector::default();
+        for chunk in signal(16_000, 3).chunks(1_600) {
+            assert!(detector.ingest_capture(chunk).is_empty());
+        }
+
+        for (reference, capture) in signal(16_000, 5)
+            .chunks(1_600)
+            .zip(signal(16_000, 11).chunks(1_600))
+        {
+            detector.push_reference(reference);
+            assert!(detector.ingest_capture(capture).is_empty());
+        }
+    }
+}
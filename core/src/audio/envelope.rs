@@ -0,0 +1,160 @@
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, hkdf, hmac};
+use serde::{Deserialize, Serialize};
+
+pub const ENVELOPE_VERSION: u8 = 1;
+
+const AUDIO_KEY_SALT: &[u8] = b"flowwisper.audio.cache.salt.v1";
+const AUDIO_ENCRYPTION_INFO: &[u8] = b"flowwisper.audio.cache.enc.v1";
+const AUDIO_HMAC_INFO: &[u8] = b"flowwisper.audio.cache.hmac.v1";
+const NONCE_LEN: usize = 12;
+
+/// Keys used to seal cached audio at rest, derived from the app master key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioCacheKeys {
+    encryption: [u8; 32],
+    signing: [u8; 32],
+}
+
+impl AudioCacheKeys {
+    pub fn derive(master: &[u8]) -> Result<Self> {
+        if master.len() < 32 {
+            bail!("master key material must be at least 32 bytes");
+        }
+        let salt = hkdf::Salt::new(hkdf::HKDF_SHA256, AUDIO_KEY_SALT);
+        let prk = salt.extract(master);
+
+        let mut encryption = [0u8; 32];
+        let mut signing = [0u8; 32];
+
+        prk.expand(&[AUDIO_ENCRYPTION_INFO], hkdf::HKDF_SHA256)
+            .map_err(|_| anyhow!("failed to derive audio encryption key"))?
+            .fill(&mut encryption)
+            .map_err(|_| anyhow!("failed to fill audio encryption key"))?;
+
+        prk.expand(&[AUDIO_HMAC_INFO], hkdf::HKDF_SHA256)
+            .map_err(|_| anyhow!("failed to derive audio signing key"))?
+            .fill(&mut signing)
+            .map_err(|_| anyhow!("failed to fill audio signing key"))?;
+
+        Ok(Self {
+            encryption,
+            signing,
+        })
+    }
+
+    fn cipher(&self) -> Result<aead::LessSafeKey> {
+        let key = aead::UnboundKey::new(&aead::AES_256_GCM, &self.encryption)
+            .map_err(|_| anyhow!("invalid audio encryption key material"))?;
+        Ok(aead::LessSafeKey::new(key))
+    }
+
+    fn signer(&self) -> hmac::Key {
+        hmac::Key::new(hmac::HMAC_SHA256, &self.signing)
+    }
+}
+
+/// AES-256-GCM ciphertext plus an HMAC-SHA256 over nonce and ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedEnvelope {
+    pub version: u8,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub signature: String,
+}
+
+/// Encrypt and sign `plaintext`; `aad` binds the envelope to its context.
+pub fn seal_payload(keys: &AudioCacheKeys, aad: &[u8], plaintext: &[u8]) -> Result<SealedEnvelope> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("failed to generate audio nonce"))?;
+    let mut buffer = plaintext.to_vec();
+    buffer.reserve(aead::AES_256_GCM.tag_len());
+    keys.cipher()?
+        .seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(aad),
+            &mut buffer,
+        )
+        .map_err(|_| anyhow!("failed to seal audio payload"))?;
+    let mut signed = Vec::with_capacity(nonce.len() + buffer.len());
+    signed.extend_from_slice(&nonce);
+    signed.extend_from_slice(&buffer);
+    let signature = hmac::sign(&keys.signer(), &signed);
+    Ok(SealedEnvelope {
+        version: ENVELOPE_VERSION,
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(buffer),
+        signature: BASE64.encode(signature.as_ref()),
+    })
+}
+
+/// Verify and decrypt an envelope produced by [`seal_payload`] with the same `aad`.
+pub fn open_payload(
+    keys: &AudioCacheKeys,
+    aad: &[u8],
+    envelope: &SealedEnvelope,
+) -> Result<Vec<u8>> {
+    if envelope.version != ENVELOPE_VERSION {
+        bail!("unsupported audio envelope version: {}", envelope.version);
+    }
+    let nonce_bytes = BASE64
+        .decode(envelope.nonce.as_bytes())
+        .map_err(|err| anyhow!("failed to decode audio nonce: {err}"))?;
+    let nonce: [u8; NONCE_LEN] = nonce_bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("invalid audio nonce length"))?;
+    let ciphertext = BASE64
+        .decode(envelope.ciphertext.as_bytes())
+        .map_err(|err| anyhow!("failed to decode audio ciphertext: {err}"))?;
+    let signature = BASE64
+        .decode(envelope.signature.as_bytes())
+        .map_err(|err| anyhow!("failed to decode audio signature: {err}"))?;
+    let mut signed = Vec::with_capacity(nonce.len() + ciphertext.len());
+    signed.extend_from_slice(&nonce);
+    signed.extend_from_slice(&ciphertext);
+    hmac::verify(&keys.signer(), &signed, &signature)
+        .map_err(|_| anyhow!("audio signature mismatch"))?;
+    let mut buffer = ciphertext;
+    let decrypted = keys
+        .cipher()?
+        .open_in_place(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(aad),
+            &mut buffer,
+        )
+        .map_err(|_| anyhow!("failed to decrypt audio payload"))?;
+    Ok(decrypted.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn master(seed: u8) -> Vec<u8> {
+        (0..32).map(|index| seed.wrapping_add(index)).collect()
+    }
+
+    #[test]
+    fn round_trips_and_rejects_wrong_context() {
+        let keys = AudioCacheKeys::derive(&master(1)).unwrap();
+        let envelope = seal_payload(&keys, b"ctx", b"pcm bytes").unwrap();
+        assert_eq!(
+            open_payload(&keys, b"ctx", &envelope).unwrap(),
+            b"pcm bytes"
+        );
+        assert!(open_payload(&keys, b"other", &envelope).is_err());
+
+        let other = AudioCacheKeys::derive(&master(2)).unwrap();
+        assert!(open_payload(&other, b"ctx", &envelope).is_err());
+    }
+
+    #[test]
+    fn rejects_short_master_key() {
+        assert!(AudioCacheKeys::derive(&[0u8; 16]).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{bail, Result};
+
+use super::SAMPLE_RATE_HZ;
+
+/// Sample container delivered by a capture backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleEncoding {
+    F32,
+    I16,
+    /// 24-bit signed samples in the low three bytes of a 32-bit container
+    /// (ALSA `S24_LE`-style); the padding byte is ignored.
+    I24In32,
+}
+
+impl SampleEncoding {
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            SampleEncoding::F32 | SampleEncoding::I24In32 => 4,
+            SampleEncoding::I16 => 2,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SampleEncoding::F32 => "f32",
+            SampleEncoding::I16 => "i16",
+            SampleEncoding::I24In32 => "i24_in_32",
+        }
+    }
+
+    /// Higher is preferred during negotiation.
+    fn precision_rank(&self) -> u8 {
+        match self {
+            SampleEncoding::F32 => 2,
+            SampleEncoding::I24In32 => 1,
+            SampleEncoding::I16 => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Wire format of raw PCM handed to [`AudioPipeline::handle_frame`](super::AudioPipeline::handle_frame).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PcmFormat {
+    pub encoding: SampleEncoding,
+    pub endianness: Endianness,
+    /// Interleaved channel count; multi-channel input is downmixed.
+    pub channels: u16,
+    pub sample_rate_hz: u32,
+}
+
+impl Default for PcmFormat {
+    /// Mono little-endian f32 at the engine rate, the format the pipeline
+    /// accepted before negotiation existed.
+    fn default() -> Self {
+        Self {
+            encoding: SampleEncoding::F32,
+            endianness: Endianness::Little,
+            channels: 1,
+            sample_rate_hz: SAMPLE_RATE_HZ,
+        }
+    }
+}
+
+impl PcmFormat {
+    pub fn new(encoding: SampleEncoding, channels: u16, sample_rate_hz: u32) -> Self {
+        Self {
+            encoding,
+            endianness: Endianness::Little,
+            channels,
+            sample_rate_hz,
+        }
+    }
+
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.channels == 0 {
+            bail!("pcm format must have at least one channel");
+        }
+        if self.sample_rate_hz == 0 {
+            bail!("pcm format must have a non-zero sample rate");
+        }
+        Ok(())
+    }
+
+    /// Bytes in one interleaved sample frame (one sample per channel).
+    pub fn frame_bytes(&self) -> usize {
+        self.encoding.bytes_per_sample() * self.channels as usize
+    }
+
+    /// Convert raw bytes to interleaved f32 samples in `[-1.0, 1.0]`.
+    pub fn decode(&self, bytes: &[u8]) -> Result<Vec<f32>> {
+        let width = self.encoding.bytes_per_sample();
+        if !bytes.len().is_multiple_of(self.frame_bytes().max(1)) {
+            bail!(
+                "pcm buffer of {} bytes is not aligned to {}-channel {} frames",
+                bytes.len(),
+                self.channels,
+                self.encoding.as_str()
+            );
+        }
+        let big = self.endianness == Endianness::Big;
+        let samples = bytes
+            .chunks_exact(width)
+            .map(|chunk| match self.encoding {
+                SampleEncoding::F32 => {
+                    let raw = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                    if big {
+                        f32::from_be_bytes(raw)
+                    } else {
+                        f32::from_le_bytes(raw)
+                    }
+                }
+                SampleEncoding::I16 => {
+                    let raw = [chunk[0], chunk[1]];
+                    let value = if big {
+                        i16::from_be_bytes(raw)
+                    } else {
+                        i16::from_le_bytes(raw)
+                    };
+                    value as f32 / 32_768.0
+                }
+                SampleEncoding::I24In32 => {
+                    let raw = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                    let container = if big {
+                        i32::from_be_bytes(raw)
+                    } else {
+                        i32::from_le_bytes(raw)
+                    };
+                    // Sign-extend bit 23 and drop the padding byte.
+                    let value = (container << 8) >> 8;
+                    value as f32 / 8_388_608.0
+                }
+            })
+            .collect();
+        Ok(samples)
+    }
+
+    /// Pick the format the pipeline handles best from what a backend offers:
+    /// the engine sample rate (no resampling) first, then precision, then
+    /// little-endian, then fewer channels to downmix.
+    pub fn negotiate(offered: &[PcmFormat]) -> Result<PcmFormat> {
+        offered
+            .iter()
+            .filter(|format| format.validate().is_ok())
+            .max_by_key(|format| {
+                (
+                    format.sample_rate_hz == SAMPLE_RATE_HZ,
+                    format.encoding.precision_rank(),
+                    format.endianness == Endianness::Little,
+                    std::cmp::Reverse(format.channels),
+                )
+            })
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("capture backend offered no usable pcm format"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_integer_and_float_encodings() {
+        let i16_le = PcmFormat::new(SampleEncoding::I16, 1, 16_000);
+        let bytes: Vec<u8> = [i16::MIN, 0, 16_384]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        assert_eq!(i16_le.decode(&bytes).unwrap(), [-1.0, 0.0, 0.5]);
+
+        let i16_be = i16_le.with_endianness(Endianness::Big);
+        assert_eq!(i16_be.decode(&16_384_i16.to_be_bytes()).unwrap(), [0.5]);
+
+        let i24 = PcmFormat::new(SampleEncoding::I24In32, 1, 16_000);
+        // 0x7F in the padding byte must not leak into the sample.
+        let bytes = [0x00, 0x00, 0xC0, 0x7F, 0x00, 0x00, 0x40, 0x00];
+        assert_eq!(i24.decode(&bytes).unwrap(), [-0.5, 0.5]);
+
+        let f32_be = PcmFormat::default().with_endianness(Endianness::Big);
+        assert_eq!(f32_be.decode(&0.25_f32.to_be_bytes()).unwrap(), [0.25]);
+
+        let stereo = PcmFormat::new(SampleEncoding::I16, 2, 16_000);
+        assert!(stereo.decode(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn negotiation_prefers_engine_rate_then_precision() {
+        let offered = [
+            PcmFormat::new(SampleEncoding::F32, 2, 48_000),
+            PcmFormat::new(SampleEncoding::I16, 2, 16_000),
+            PcmFormat::new(SampleEncoding::I24In32, 1, 16_000).with_endianness(Endianness::Big),
+            PcmFormat::new(SampleEncoding::I24In32, 2, 16_000),
+        ];
+        assert_eq!(
+            PcmFormat::negotiate(&offered).unwrap(),
+            PcmFormat::new(SampleEncoding::I24In32, 2, 16_000)
+        );
+        assert!(PcmFormat::negotiate(&[PcmFormat::new(SampleEncoding::I16, 0, 16_000)]).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{info, warn};
+
+use super::device::{fallback_device, AudioDevice, AudioDeviceEvent, DeviceWatcher};
+use super::{AudioCaptureStage, AudioPipeline};
+
+impl AudioPipeline {
+    /// 记录当前采集所用的输入设备，设备被拔出时据此迁移。
+    pub fn set_active_device(&self, device_id: Option<String>) {
+        *self
+            .active_device
+            .lock()
+            .expect("active device mutex poisoned") = device_id;
+    }
+
+    pub fn active_device(&self) -> Option<String> {
+        self.active_device
+            .lock()
+            .expect("active device mutex poisoned")
+            .clone()
+    }
+
+    pub fn subscribe_device_events(&self) -> broadcast::Receiver<AudioDeviceEvent> {
+        self.device_tx.subscribe()
+    }
+
+    /// 广播设备变化；若当前设备被移除，则切换到备用设备并重新采样噪声基线。
+    /// `devices` 为变化后的设备列表。
+    pub fn handle_device_events(&self, events: Vec<AudioDeviceEvent>, devices: &[AudioDevice]) {
+        for event in events {
+            let removed_active = match &event {
+                AudioDeviceEvent::Removed(device)
+                    if self.active_device().as_deref() == Some(device.id.as_str()) =>
+                {
+                    Some(device.id.clone())
+                }
+                _ => None,
+            };
+            let _ = self.device_tx.send(event);
+            if let Some(removed) = removed_active {
+                self.migrate_device(&removed, devices);
+            }
+        }
+    }
+
+    fn migrate_device(&self, removed: &str, devices: &[AudioDevice]) {
+        let Some(fallback) = fallback_device(devices, removed).cloned() else {
+            warn!(
+                target: "audio_pipeline",
+                device = removed,
+                "active capture device removed and no fallback is available"
+            );
+            self.set_active_device(None);
+            return;
+        };
+
+        self.set_active_device(Some(fallback.id.clone()));
+        if let Some(resampler) = self
+            .resampler
+            .lock()
+            .expect("resampler mutex poisoned")
+            .as_mut()
+        {
+            resampler.reset();
+        }
+        let stage = *self.stage.lock().expect("audio stage mutex poisoned");
+        if !matches!(stage, AudioCaptureStage::Idle) {
+            self.noise_detector
+                .lock()
+                .expect("noise detector mutex poisoned")
+                .resync_baseline();
+        }
+        info!(
+            target: "audio_pipeline",
+            from = removed,
+            to = %fallback.id,
+            "capture device removed; migrated to fallback device"
+        );
+        let _ = self.device_tx.send(AudioDeviceEvent::Migrated {
+            from: removed.to_string(),
+            to: fallback,
+        });
+    }
+
+    /// 周期性轮询设备列表并处理热插拔。
+    pub fn spawn_device_watcher(
+        &self,
+        watcher: DeviceWatcher,
+        poll_interval: Duration,
+    ) -> task::JoinHandle<()> {
+        let pipeline = self.clone();
+        tokio::spawn(async move {
+            let mut watcher = watcher;
+            let mut ticker = interval(poll_interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let polled = task::spawn_blocking(move || {
+                    let events = watcher.poll();
+                    (watcher, events)
+                })
+                .await;
+                let events = match polled {
+                    Ok((returned, events)) => {
+                        watcher = returned;
+                        events
+                    }
+                    Err(err) => {
+                        warn!(target: "audio_pipeline", %err, "device watcher task failed");
+                        return;
+                    }
+                };
+                match events {
+                    Ok(events) if !events.is_empty() => {
+                        pipeline.handle_device_events(events, watcher.devices());
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        warn!(target: "audio_pipeline", %err, "failed to enumerate input devices");
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{DeviceEnumerator, NoiseEvent};
+    use anyhow::Result;
+    use std::sync::{Arc, Mutex};
+    use tokio::time::timeout;
+
+    struct FixedDevices(Arc<Mutex<Vec<AudioDevice>>>);
+
+    impl DeviceEnumerator for FixedDevices {
+        fn input_devices(&self) -> Result<Vec<AudioDevice>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn migrates_to_fallback_when_active_device_is_unplugged() {
+        let device = |id: &str, is_default: bool| AudioDevice {
+            id: id.into(),
+            label: id.into(),
+            is_default,
+        };
+        let devices = Arc::new(Mutex::new(vec![
+            device("builtin", true),
+            device("usb", false),
+        ]));
+        let pipeline = AudioPipeline::new();
+        pipeline.set_active_device(Some("usb".into()));
+        let mut device_rx = pipeline.subscribe_device_events();
+        let mut noise_rx = pipeline.subscribe_noise_events();
+        pipeline.begin_preroll(Some(-40.0));
+        pipeline.begin_recording();
+        let _ = noise_rx.recv().await;
+
+        let watcher = pipeline.spawn_device_watcher(
+            DeviceWatcher::new(Box::new(FixedDevices(Arc::clone(&devices)))),
+            Duration::from_millis(10),
+        );
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        devices.lock().unwrap().retain(|device| device.id != "usb");
+
+        let removed = timeout(Duration::from_millis(500), device_rx.recv())
+            .await
+            .expect("removal timed out")
+            .expect("device channel closed");
+        assert_eq!(removed, AudioDeviceEvent::Removed(device("usb", false)));
+        let migrated = timeout(Duration::from_millis(500), device_rx.recv())
+            .await
+            .expect("migration timed out")
+            .expect("device channel closed");
+        assert_eq!(
+            migrated,
+            AudioDeviceEvent::Migrated {
+                from: "usb".into(),
+                to: device("builtin", true),
+            }
+        );
+        assert_eq!(pipeline.active_device().as_deref(), Some("builtin"));
+        watcher.abort();
+
+        // The baseline is re-sampled from the new device's audio.
+        pipeline
+            .push_pcm_frame(vec![0.1; 8_000])
+            .await
+            .expect("push frame");
+        let baseline = timeout(Duration::from_millis(200), noise_rx.recv())
+            .await
+            .expect("baseline timed out")
+            .expect("noise channel closed");
+        assert!(matches!(
+            baseline,
+            NoiseEvent::BaselineEstablished { level_db } if (level_db + 20.0).abs() < 0.5
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+//! 与桌面端击键事件同步的键盘声抑制。
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const DEFAULT_PRE_MS: u64 = 10;
+const DEFAULT_POST_MS: u64 = 40;
+const DEFAULT_LATENCY_MS: u64 = 30;
+const DEFAULT_ATTENUATION: f32 = 0.1;
+/// 抑制窗口两端的增益过渡长度，避免硬切产生咔哒声。
+const RAMP_MS: u64 = 2;
+
+/// 击键窗口内的处理方式。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeystrokeSuppressionMode {
+    /// 按固定增益衰减（0..=1）。
+    Attenuate(f32),
+    /// 直接静音。
+    Gate,
+}
+
+/// 键盘声抑制参数。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeystrokeSuppressionConfig {
+    pub mode: KeystrokeSuppressionMode,
+    /// 击键时刻之前一并处理的时长。
+    pub pre: Duration,
+    /// 击键时刻之后处理的时长，覆盖按键回弹声。
+    pub post: Duration,
+    /// 采集音频相对击键事件的滞后（设备缓冲 + 传输），用于对齐样本位置。
+    pub latency: Duration,
+}
+
+impl Default for KeystrokeSuppressionConfig {
+    fn default() -> Self {
+        Self {
+            mode: KeystrokeSuppressionMode::Attenuate(DEFAULT_ATTENUATION),
+            pre: Duration::from_millis(DEFAULT_PRE_MS),
+            post: Duration::from_millis(DEFAULT_POST_MS),
+            latency: Duration::from_millis(DEFAULT_LATENCY_MS),
+        }
+    }
+}
+
+/// 按样本位置维护待处理的击键窗口，并在音频流经时就地衰减或静音。
+///
+/// 击键事件按“收到时的样本位置 + 采集滞后 - 事件年龄”换算到音频流上；
+/// 已经流过管线的样本无法回溯处理，因此滞后应覆盖 `pre`。
+#[derive(Clone, Debug)]
+pub struct KeystrokeSuppressor {
+    config: KeystrokeSuppressionConfig,
+    sample_rate_hz: u32,
+    /// 已处理的样本总数。
+    position: u64,
+    /// 按起点排序的 `[start, end)` 窗口。
+    windows: VecDeque<(u64, u64)>,
+}
+
+impl KeystrokeSuppressor {
+    pub fn new(config: KeystrokeSuppressionConfig, sample_rate_hz: u32) -> Self {
+        let mode = match config.mode {
+            KeystrokeSuppressionMode::Attenuate(gain) => {
+                KeystrokeSuppressionMode::Attenuate(gain.clamp(0.0, 1.0))
+            }
+            KeystrokeSuppressionMode::Gate => KeystrokeSuppressionMode::Gate,
+        };
+        Self {
+            config: KeystrokeSuppressionConfig { mode, ..config },
+            sample_rate_hz: sample_rate_hz.max(1),
+            position: 0,
+            windows: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &KeystrokeSuppressionConfig {
+        &self.config
+    }
+
+    /// 登记一次击键，`age` 为击键发生至今的时长。
+    pub fn record_keystroke(&mut self, age: Duration) {
+        let age = self.samples(age);
+        let center = (self.position + self.samples(self.config.latency)).saturating_sub(age);
+        let start = center.saturating_sub(self.samples(self.config.pre));
+        let end = center + self.samples(self.config.post);
+        if end <= self.position {
+            return;
+        }
+
+        // 连续击键的窗口合并，保持有序且互不重叠。
+        if let Some(last) = self.windows.back_mut() {
+            if start <= last.1 {
+                last.0 = last.0.min(start);
+                last.1 = last.1.max(end);
+                return;
+            }
+        }
+        let index = self.windows.partition_point(|window| window.0 <= start);
+        self.windows.insert(index, (start, end));
+    }
+
+    /// 就地处理一段样本，返回被抑制的样本数。
+    pub fn process(&mut self, samples: &mut [f32]) -> usize {
+        let floor = match self.config.mode {
+            KeystrokeSuppressionMode::Attenuate(gain) => gain,
+            KeystrokeSuppressionMode::Gate => 0.0,
+        };
+        let ramp = self.samples(Duration::from_millis(RAMP_MS)).max(1);
+        let mut suppressed = 0;
+
+        for (offset, sample) in samples.iter_mut().enumerate() {
+            let index = self.position + offset as u64;
+            while self.windows.front().is_some_and(|window| window.1 <= index) {
+                self.windows.pop_front();
+            }
+            let Some(&(start, end)) = self.windows.front() else {
+                break;
+            };
+            if index < start {
+                continue;
+            }
+            let edge = (index - start).min(end - 1 - index);
+            let depth = ((edge + 1) as f32 / ramp as f32).min(1.0);
+            *sample *= 1.0 - (1.0 - floor) * depth;
+            suppressed += 1;
+        }
+
+        self.position += samples.len() as u64;
+        suppressed
+    }
+
+    /// 丢弃待处理窗口并将样本位置归零。
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.windows.clear();
+    }
+
+    fn samples(&self, duration: Duration) -> u64 {
+        (duration.as_secs_f64() * self.sample_rate_hz as f64).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: KeystrokeSuppressionMode) -> KeystrokeSuppressionConfig {
+        KeystrokeSuppressionConfig {
+            mode,
+            pre: Duration::from_millis(5),
+            post: Duration::from_millis(20),
+            latency: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn gates_samples_around_the_aligned_keystroke() {
+        let mut suppressor =
+            KeystrokeSuppressor::new(config(KeystrokeSuppressionMode::Gate), 16_000);
+        suppressor.record_keystroke(Duration::ZERO);
+
+        let mut samples = vec![1.0_f32; 3_200];
+        let suppressed = suppressor.process(&mut samples);
+
+        // 50 ms 滞后对应第 800 个样本，窗口为 [720, 1120)。
+        assert_eq!(suppressed, 400);
+        assert_eq!(samples[700], 1.0);
+        assert_eq!(samples[900], 0.0);
+        assert!(samples[720] > 0.0 && samples[720] < 1.0, "edge should ramp");
+        assert_eq!(samples[1_200], 1.0);
+
+        let mut tail = vec![1.0_f32; 1_600];
+        assert_eq!(suppressor.process(&mut tail), 0);
+    }
+
+    #[test]
+    fn attenuates_and_merges_overlapping_keystrokes() {
+        let mut suppressor =
+            KeystrokeSuppressor::new(config(KeystrokeSuppressionMode::Attenuate(0.25)), 16_000);
+        suppressor.record_keystroke(Duration::ZERO);
+        let mut head = vec![1.0_f32; 160];
+        assert_eq!(suppressor.process(&mut head), 0);
+        suppressor.record_keystroke(Duration::ZERO);
+
+        let mut samples = vec![1.0_f32; 3_200];
+        let suppressed = suppressor.process(&mut samples);
+
+        // [720, 1120) 与 [880, 1280) 合并为一个窗口。
+        assert_eq!(suppressed, 560);
+        assert!((samples[1_000] - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ignores_keystrokes_older_than_the_audio_already_processed() {
+        let mut suppressor =
+            KeystrokeSuppressor::new(config(KeystrokeSuppressionMode::Gate), 16_000);
+        let mut samples = vec![1.0_f32; 16_000];
+        suppressor.process(&mut samples);
+
+        suppressor.record_keystroke(Duration::from_millis(500));
+        let mut samples = vec![1.0_f32; 1_600];
+        assert_eq!(suppressor.process(&mut samples), 0);
+    }
+}
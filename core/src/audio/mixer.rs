@@ -0,0 +1,319 @@
+use anyhow::{bail, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::{AudioPipeline, AudioSource, SAMPLE_RATE_HZ};
+
+const DEFAULT_MIX_FRAME: Duration = Duration::from_millis(100);
+/// How far one source may run ahead before the mixer stops waiting for the
+/// others and pads them with silence. WASAPI loopback, for one, delivers
+/// nothing at all while nothing is playing.
+const DEFAULT_MAX_SKEW: Duration = Duration::from_millis(300);
+
+/// One source's share of a [`MixedFrame`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedFrame {
+    pub source: AudioSource,
+    pub samples: Vec<f32>,
+}
+
+/// Time-aligned audio from every source: the summed mix for monitoring and
+/// recording, and the untouched per-source tracks for transcription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixedFrame {
+    pub mix: Vec<f32>,
+    /// Sources that delivered no audio for this span are absent.
+    pub tracks: Vec<TaggedFrame>,
+}
+
+impl MixedFrame {
+    pub fn track(&self, source: AudioSource) -> Option<&TaggedFrame> {
+        self.tracks.iter().find(|track| track.source == source)
+    }
+}
+
+/// Aligns frames arriving independently from several sources into
+/// equal-length [`MixedFrame`]s.
+pub struct SourceMixer {
+    frame_len: usize,
+    max_skew: usize,
+    queues: Vec<(AudioSource, VecDeque<f32>)>,
+}
+
+impl SourceMixer {
+    pub fn new(sources: &[AudioSource], frame: Duration, max_skew: Duration) -> Self {
+        let mut queues: Vec<(AudioSource, VecDeque<f32>)> = Vec::new();
+        for &source in sources {
+            if !queues.iter().any(|(known, _)| *known == source) {
+                queues.push((source, VecDeque::new()));
+            }
+        }
+        Self {
+            frame_len: duration_to_samples(frame).max(1),
+            max_skew: duration_to_samples(max_skew),
+            queues,
+        }
+    }
+
+    /// Queue `samples` for `source` and return every frame that became ready.
+    /// Samples from sources the mixer was not built for are dropped.
+    pub fn push(&mut self, source: AudioSource, samples: &[f32]) -> Vec<MixedFrame> {
+        let Some((_, queue)) = self.queues.iter_mut().find(|(known, _)| *known == source) else {
+            return Vec::new();
+        };
+        queue.extend(samples.iter().copied());
+
+        let mut frames = Vec::new();
+        loop {
+            let ready = self
+                .queues
+                .iter()
+                .all(|(_, queue)| queue.len() >= self.frame_len);
+            let overdue = self
+                .queues
+                .iter()
+                .any(|(_, queue)| queue.len() >= self.frame_len + self.max_skew);
+            if !ready && !overdue {
+                break;
+            }
+            frames.push(self.emit());
+        }
+        frames
+    }
+
+    /// Emit whatever is still queued, padding the last frame with silence.
+    pub fn flush(&mut self) -> Vec<MixedFrame> {
+        let mut frames = Vec::new();
+        while self.queues.iter().any(|(_, queue)| !queue.is_empty()) {
+            frames.push(self.emit());
+        }
+        frames
+    }
+
+    fn emit(&mut self) -> MixedFrame {
+        let frame_len = self.frame_len;
+        let mut mix = vec![0.0_f32; frame_len];
+        let mut tracks = Vec::new();
+        for (source, queue) in &mut self.queues {
+            let take = queue.len().min(frame_len);
+            if take == 0 {
+                continue;
+            }
+            let mut samples: Vec<f32> = queue.drain(..take).collect();
+            samples.resize(frame_len, 0.0);
+            for (mixed, sample) in mix.iter_mut().zip(&samples) {
+                *mixed += sample;
+            }
+            tracks.push(TaggedFrame {
+                source: *source,
+                samples,
+            });
+        }
+        for sample in &mut mix {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+        MixedFrame { mix, tracks }
+    }
+}
+
+/// Runs one [`AudioPipeline`] per source (typically the microphone and system
+/// loopback) side by side and mixes their output.
+#[derive(Clone)]
+pub struct AudioMixer {
+    inputs: Arc<Mutex<Vec<AudioPipeline>>>,
+    frame: Duration,
+    max_skew: Duration,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioMixer {
+    pub fn new() -> Self {
+        Self {
+            inputs: Arc::new(Mutex::new(Vec::new())),
+            frame: DEFAULT_MIX_FRAME,
+            max_skew: DEFAULT_MAX_SKEW,
+        }
+    }
+
+    pub fn with_timing(mut self, frame: Duration, max_skew: Duration) -> Self {
+        self.frame = frame;
+        self.max_skew = max_skew;
+        self
+    }
+
+    /// Add a pipeline under its [`AudioPipeline::audio_source`]; each source
+    /// may be mixed once.
+    pub fn add_source(&self, pipeline: AudioPipeline) -> Result<()> {
+        let source = pipeline.audio_source();
+        let mut inputs = self.inputs.lock().expect("mixer inputs mutex poisoned");
+        if inputs.iter().any(|input| input.audio_source() == source) {
+            bail!("{} is already part of the mix", source.as_str());
+        }
+        inputs.push(pipeline);
+        Ok(())
+    }
+
+    pub fn sources(&self) -> Vec<AudioSource> {
+        self.pipelines()
+            .iter()
+            .map(AudioPipeline::audio_source)
+            .collect()
+    }
+
+    pub fn pipeline(&self, source: AudioSource) -> Option<AudioPipeline> {
+        self.pipelines()
+            .into_iter()
+            .find(|pipeline| pipeline.audio_source() == source)
+    }
+
+    /// Start every source; stops the ones already started if one fails.
+    pub async fn start(&self) -> Result<()> {
+        let pipelines = self.pipelines();
+        for (index, pipeline) in pipelines.iter().enumerate() {
+            if let Err(err) = pipeline.start().await {
+                for started in &pipelines[..index] {
+                    let _ = started.stop().await;
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn stop(&self) -> Result<()> {
+        for pipeline in self.pipelines() {
+            pipeline.stop().await?;
+        }
+        Ok(())
+    }
+
+    /// Mixed frames from the current sources. The channel closes once every
+    /// source pipeline has stopped and the remainder has been flushed.
+    pub fn subscribe(&self, capacity: usize) -> mpsc::Receiver<MixedFrame> {
+        let pipelines = self.pipelines();
+        let sources: Vec<AudioSource> = pipelines.iter().map(|p| p.audio_source()).collect();
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let (tagged_tx, mut tagged_rx) = mpsc::channel(capacity.max(1) * sources.len().max(1));
+        for pipeline in &pipelines {
+            let source = pipeline.audio_source();
+            let mut frames = pipeline.subscribe_lossless_pcm_frames(capacity);
+            let tagged_tx = tagged_tx.clone();
+            tokio::spawn(async move {
+                while let Some(frame) = frames.recv().await {
+                    if tagged_tx.send((source, frame)).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tagged_tx);
+
+        let mut mixer = SourceMixer::new(&sources, self.frame, self.max_skew);
+        tokio::spawn(async move {
+            while let Some((source, frame)) = tagged_rx.recv().await {
+                for mixed in mixer.push(source, &frame) {
+                    if tx.send(mixed).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            for mixed in mixer.flush() {
+                if tx.send(mixed).await.is_err() {
+                    return;
+                }
+            }
+        });
+        rx
+    }
+
+    fn pipelines(&self) -> Vec<AudioPipeline> {
+        self.inputs
+            .lock()
+            .expect("mixer inputs mutex poisoned")
+            .clone()
+    }
+}
+
+fn duration_to_samples(duration: Duration) -> usize {
+    (duration.as_secs_f64() * SAMPLE_RATE_HZ as f64).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::timeout;
+
+    const SOURCES: [AudioSource; 2] = [AudioSource::Microphone, AudioSource::SystemLoopback];
+
+    #[test]
+    fn aligns_sources_and_pads_a_silent_one() {
+        let mut mixer = SourceMixer::new(
+            &SOURCES,
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+        );
+        assert!(mixer.push(AudioSource::Microphone, &[0.25; 160]).is_empty());
+        let frames = mixer.push(AudioSource::SystemLoopback, &[0.5; 160]);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].mix, vec![0.75; 160]);
+        assert_eq!(
+            frames[0]
+                .track(AudioSource::SystemLoopback)
+                .unwrap()
+                .samples,
+            vec![0.5; 160]
+        );
+
+        // Loopback goes quiet; the microphone is held back only up to the skew.
+        assert!(mixer.push(AudioSource::Microphone, &[0.8; 320]).is_empty());
+        let frames = mixer.push(AudioSource::Microphone, &[0.8; 160]);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].tracks.len(), 1);
+        assert!(frames[0].track(AudioSource::SystemLoopback).is_none());
+
+        mixer.push(AudioSource::SystemLoopback, &[0.9; 80]);
+        let rest = mixer.flush();
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0].mix[0], 1.0, "mix is clipped");
+        assert_eq!(rest[0].mix[100], 0.8);
+    }
+
+    #[tokio::test]
+    async fn mixer_tags_frames_from_each_pipeline() {
+        let mixer = AudioMixer::new();
+        let microphone = AudioPipeline::new();
+        let loopback = AudioPipeline::new();
+        loopback.set_audio_source(AudioSource::SystemLoopback);
+        mixer.add_source(microphone.clone()).unwrap();
+        mixer.add_source(loopback.clone()).unwrap();
+        assert!(mixer.add_source(AudioPipeline::new()).is_err());
+        assert_eq!(mixer.sources(), SOURCES);
+
+        let mut rx = mixer.subscribe(8);
+        microphone.push_pcm_frame(vec![0.1; 1_600]).await.unwrap();
+        loopback.push_pcm_frame(vec![0.2; 1_600]).await.unwrap();
+        microphone.flush_pending().await.unwrap();
+        loopback.flush_pending().await.unwrap();
+
+        let frame = timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.tracks.len(), 2);
+        assert!((frame.track(AudioSource::Microphone).unwrap().samples[0] - 0.1).abs() < 1e-6);
+        assert!((frame.mix[0] - 0.3).abs() < 1e-6);
+
+        mixer.stop().await.unwrap();
+        assert!(timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
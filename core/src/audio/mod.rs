@@ -2,26 +2,81 @@
 
 use anyhow::Result;
 use bytes::Bytes;
-use std::collections::VecDeque;
-use std::convert::TryInto;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
     Arc, Mutex,
 };
-use std::time::Duration;
-use tokio::sync::{broadcast, mpsc, Mutex as AsyncMutex, Notify};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tokio::task;
-use tokio::time::{interval, MissedTickBehavior};
 use tracing::{info, warn};
 
-const SAMPLE_RATE_HZ: u32 = 16_000;
+use crate::telemetry::metrics::metrics;
+
+pub(crate) const SAMPLE_RATE_HZ: u32 = 16_000;
 const MIN_FRAME_MS: u64 = 100;
 const MAX_FRAME_MS: u64 = 200;
+const DEFAULT_MAX_COALESCED_MS: u64 = 1_000;
 const VAD_THRESHOLD: f32 = 1e-4;
 const WAVEFORM_FRAME_MS: u64 = 32;
-
+const ENVELOPE_CHANNEL_CAPACITY: usize = 32;
+
+mod agc;
+mod backpressure;
+mod capture;
+mod device;
+mod downmix;
+mod echo;
+mod envelope;
+mod format;
+mod hotplug;
+mod keystroke;
+mod mixer;
+mod mp3;
 mod noise;
-pub use noise::{NoiseDetector, NoiseEvent, SilenceCountdownStatus};
+mod preroll;
+mod recorder;
+mod resample;
+mod shm;
+mod spill;
+mod wav;
+mod waveform;
+pub use agc::{AgcConfig, AutomaticGainControl};
+pub use backpressure::BackpressurePolicy;
+use backpressure::PcmSubscriber;
+#[cfg(feature = "native-capture")]
+pub use capture::CpalCaptureBackend;
+pub use capture::{
+    record_microphone, AudioSource, CaptureBackend, CaptureConfig, CaptureEvent, CaptureHandle,
+    CaptureSink, CaptureTarget, ReconnectPolicy, ShareMode,
+};
+pub use device::{AudioDevice, AudioDeviceEvent, DeviceEnumerator, DeviceWatcher};
+pub use downmix::{downmix_interleaved, DownmixPolicy};
+pub use echo::EchoDetectedPayload;
+use echo::EchoDetector;
+pub use envelope::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+pub use format::{Endianness, PcmFormat, SampleEncoding};
+pub use keystroke::{KeystrokeSuppressionConfig, KeystrokeSuppressionMode, KeystrokeSuppressor};
+pub use mixer::{AudioMixer, MixedFrame, SourceMixer, TaggedFrame};
+pub use mp3::decode_mp3;
+pub use noise::{
+    NoiseDetector, NoiseEvent, NoiseKind, NoiseProfile, SilenceCountdownStatus, SilencePolicy,
+};
+use preroll::PrerollBuffer;
+pub use recorder::{
+    read_archive, RecordedAudio, RecordingSummary, SessionRecorder, RECORDINGS_DIR,
+    RECORDING_KEY_SECRET,
+};
+pub use resample::StreamingResampler;
+pub use shm::{
+    ShmRead, ShmRingReader, ShmRingWriter, SHM_RING_HEADER_LEN, SHM_RING_MAGIC, SHM_RING_VERSION,
+};
+pub use spill::SpillConfig;
+pub use wav::{decode_audio_file, decode_wav, DecodedAudio};
+use waveform::EnvelopeSubscriber;
+pub use waveform::{EnvelopeBucket, WaveformEnvelope, WaveformFrame};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AudioCaptureStage {
@@ -30,167 +85,64 @@ pub enum AudioCaptureStage {
     Recording,
 }
 
+type ConfiguredCapture = (Arc<dyn CaptureBackend>, CaptureConfig);
+
 #[derive(Clone)]
 pub struct AudioPipeline {
     waveform_tx: broadcast::Sender<WaveformFrame>,
     pcm_subscribers: Arc<Mutex<Vec<PcmSubscriber>>>,
-    min_frame_samples: usize,
-    max_frame_samples: usize,
+    min_frame_samples: Arc<AtomicUsize>,
+    max_frame_samples: Arc<AtomicUsize>,
+    frame_window_tx: broadcast::Sender<FrameWindowChanged>,
     pending: Arc<Mutex<VecDeque<f32>>>,
     waveform_frame_samples: usize,
     waveform_pending: Arc<Mutex<VecDeque<f32>>>,
     waveform_started: Arc<AtomicBool>,
+    /// 退出时置位，之后送入的帧直接丢弃。
+    stopped: Arc<AtomicBool>,
+    envelope_subscribers: Arc<Mutex<Vec<EnvelopeSubscriber>>>,
     noise_tx: broadcast::Sender<NoiseEvent>,
     noise_detector: Arc<Mutex<NoiseDetector>>,
+    echo_detector: Arc<Mutex<EchoDetector>>,
     stage: Arc<Mutex<AudioCaptureStage>>,
+    agc: Arc<Mutex<Option<AutomaticGainControl>>>,
+    keystroke_suppressor: Arc<Mutex<Option<KeystrokeSuppressor>>>,
+    applied_gain: Arc<AtomicU32>,
+    downmix_policy: Arc<Mutex<DownmixPolicy>>,
+    /// Wire format [`AudioPipeline::handle_frame`] decodes raw PCM with.
+    pcm_format: Arc<Mutex<PcmFormat>>,
+    resampler: Arc<Mutex<Option<StreamingResampler>>>,
+    preroll: Arc<Mutex<PrerollBuffer>>,
+    spill: Arc<Mutex<Option<SpillConfig>>>,
+    /// Largest frame, in samples, a lagging subscriber's queue may coalesce into.
+    coalesce_limit: Arc<AtomicUsize>,
+    /// Post-gain PCM mirrored into a memory-mapped ring for the desktop shell.
+    shm_ring: Arc<Mutex<Option<ShmRingWriter>>>,
+    active_device: Arc<Mutex<Option<String>>>,
+    /// 各输入设备最近一次的噪声基线（dBFS），用于预录阶段热启动。
+    noise_baselines: Arc<Mutex<HashMap<String, f32>>>,
+    capture_backend: Arc<Mutex<Option<ConfiguredCapture>>>,
+    audio_source: Arc<Mutex<AudioSource>>,
+    capture_task: Arc<Mutex<Option<task::JoinHandle<()>>>>,
+    device_tx: broadcast::Sender<AudioDeviceEvent>,
 }
 
-#[derive(Clone)]
-struct PcmSubscriber {
-    sender: mpsc::Sender<Arc<[f32]>>,
-    state: Arc<AsyncMutex<SubscriberState>>,
-    max_queue: usize,
-    notify: Arc<Notify>,
-    lossless: bool,
-}
-
-struct SubscriberState {
-    queue: VecDeque<Arc<[f32]>>,
-    active: bool,
+/// Emitted when [`AudioPipeline::set_frame_window`] changes the chunk size
+/// delivered to PCM subscribers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameWindowChanged {
+    pub previous: Duration,
+    pub min_frame: Duration,
+    pub max_frame: Duration,
 }
 
-impl PcmSubscriber {
-    fn new(sender: mpsc::Sender<Arc<[f32]>>, max_queue: usize, lossless: bool) -> Self {
-        Self {
-            sender,
-            state: Arc::new(AsyncMutex::new(SubscriberState {
-                queue: VecDeque::new(),
-                active: false,
-            })),
-            max_queue,
-            notify: Arc::new(Notify::new()),
-            lossless,
-        }
-    }
-
-    fn is_closed(&self) -> bool {
-        self.sender.is_closed()
-    }
-
-    async fn enqueue(&self, frame: Arc<[f32]>) {
-        let mut state = self.state.lock().await;
-
-        loop {
-            if self.lossless && self.max_queue > 0 && state.queue.len() >= self.max_queue {
-                let notify = Arc::clone(&self.notify);
-                drop(state);
-                notify.notified().await;
-                state = self.state.lock().await;
-                continue;
-            } else if !self.lossless && self.max_queue > 0 && state.queue.len() >= self.max_queue {
-                let _ = state.queue.pop_front();
-                warn!(
-                    target: "audio_pipeline",
-                    max_queue = self.max_queue,
-                    "pcm subscriber queue exceeded capacity; dropping oldest frame"
-                );
-                break;
-            }
-            break;
-        }
-
-        state.queue.push_back(frame);
-        if state.active {
-            return;
-        }
-
-        state.active = true;
-        let state_arc = Arc::clone(&self.state);
-        let sender = self.sender.clone();
-        let notify = Arc::clone(&self.notify);
-        drop(state);
-
-        task::spawn(async move {
-            loop {
-                let next = {
-                    let mut guard = state_arc.lock().await;
-                    match guard.queue.pop_front() {
-                        Some(frame) => frame,
-                        None => {
-                            guard.active = false;
-                            notify.notify_waiters();
-                            return;
-                        }
-                    }
-                };
-
-                if sender.send(next).await.is_err() {
-                    let mut guard = state_arc.lock().await;
-                    guard.queue.clear();
-                    guard.active = false;
-                    notify.notify_waiters();
-                    warn!(
-                        target: "audio_pipeline",
-                        "pcm subscriber closed before frame delivery"
-                    );
-                    return;
-                }
-
-                notify.notify_waiters();
-            }
-        });
+impl Default for AudioPipeline {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct WaveformFrame {
-    pub rms: f32,
-    pub vad_active: bool,
-}
-
 impl AudioPipeline {
-    fn spawn_waveform_scheduler(&self) {
-        let pending = Arc::clone(&self.waveform_pending);
-        let tx = self.waveform_tx.clone();
-        let frame_samples = self.waveform_frame_samples;
-        let started = Arc::clone(&self.waveform_started);
-
-        task::spawn(async move {
-            let mut ticker = interval(Duration::from_millis(WAVEFORM_FRAME_MS));
-            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
-
-            loop {
-                ticker.tick().await;
-
-                let maybe_chunk = {
-                    let mut guard = pending.lock().expect("waveform accumulator poisoned");
-
-                    if guard.len() >= frame_samples {
-                        let chunk: Vec<f32> = guard.drain(..frame_samples).collect();
-                        Some(chunk)
-                    } else if started.load(Ordering::SeqCst) && !guard.is_empty() {
-                        let mut chunk: Vec<f32> = guard.drain(..).collect();
-                        chunk.resize(frame_samples, 0.0);
-                        Some(chunk)
-                    } else {
-                        None
-                    }
-                };
-
-                if let Some(chunk) = maybe_chunk {
-                    let rms = frame_rms(&chunk);
-                    let vad_active = rms >= VAD_THRESHOLD;
-                    let _ = tx.send(WaveformFrame { rms, vad_active });
-                } else if !started.load(Ordering::SeqCst) {
-                    let _ = tx.send(WaveformFrame {
-                        rms: 0.0,
-                        vad_active: false,
-                    });
-                }
-            }
-        });
-    }
-
     pub fn new() -> Self {
         let (waveform_tx, _) = broadcast::channel(32);
         let pcm_subscribers = Arc::new(Mutex::new(Vec::new()));
@@ -201,20 +153,45 @@ impl AudioPipeline {
         let waveform_frame_samples =
             duration_to_samples(Duration::from_millis(WAVEFORM_FRAME_MS), SAMPLE_RATE_HZ);
         let (noise_tx, _) = broadcast::channel(32);
+        let (frame_window_tx, _) = broadcast::channel(8);
+        let (device_tx, _) = broadcast::channel(16);
         let noise_detector = Arc::new(Mutex::new(NoiseDetector::new(SAMPLE_RATE_HZ)));
         let stage = Arc::new(Mutex::new(AudioCaptureStage::Idle));
         let pipeline = Self {
             waveform_tx,
             pcm_subscribers,
-            min_frame_samples,
-            max_frame_samples,
+            min_frame_samples: Arc::new(AtomicUsize::new(min_frame_samples)),
+            max_frame_samples: Arc::new(AtomicUsize::new(max_frame_samples)),
+            frame_window_tx,
             pending: Arc::new(Mutex::new(VecDeque::new())),
             waveform_frame_samples,
             waveform_pending: Arc::new(Mutex::new(VecDeque::new())),
             waveform_started: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
+            envelope_subscribers: Arc::new(Mutex::new(Vec::new())),
             noise_tx,
             noise_detector,
+            echo_detector: Arc::new(Mutex::new(EchoDetector::default())),
             stage,
+            agc: Arc::new(Mutex::new(None)),
+            keystroke_suppressor: Arc::new(Mutex::new(None)),
+            applied_gain: Arc::new(AtomicU32::new(1.0_f32.to_bits())),
+            downmix_policy: Arc::new(Mutex::new(DownmixPolicy::default())),
+            pcm_format: Arc::new(Mutex::new(PcmFormat::default())),
+            resampler: Arc::new(Mutex::new(None)),
+            preroll: Arc::new(Mutex::new(PrerollBuffer::default())),
+            spill: Arc::new(Mutex::new(None)),
+            coalesce_limit: Arc::new(AtomicUsize::new(duration_to_samples(
+                Duration::from_millis(DEFAULT_MAX_COALESCED_MS),
+                SAMPLE_RATE_HZ,
+            ))),
+            shm_ring: Arc::new(Mutex::new(None)),
+            active_device: Arc::new(Mutex::new(None)),
+            noise_baselines: Arc::new(Mutex::new(HashMap::new())),
+            capture_backend: Arc::new(Mutex::new(None)),
+            audio_source: Arc::new(Mutex::new(AudioSource::default())),
+            capture_task: Arc::new(Mutex::new(None)),
+            device_tx,
         };
 
         pipeline.spawn_waveform_scheduler();
@@ -222,55 +199,208 @@ impl AudioPipeline {
         pipeline
     }
 
-    pub fn subscribe_waveform(&self) -> broadcast::Receiver<WaveformFrame> {
-        self.waveform_tx.subscribe()
+    /// 启用键盘声抑制；之后桌面端通过 [`Self::push_keystroke`] 上报击键时刻。
+    pub fn enable_keystroke_suppression(&self, config: KeystrokeSuppressionConfig) {
+        let mut guard = self
+            .keystroke_suppressor
+            .lock()
+            .expect("keystroke suppressor mutex poisoned");
+        *guard = Some(KeystrokeSuppressor::new(config, SAMPLE_RATE_HZ));
     }
 
-    pub fn subscribe_noise_events(&self) -> broadcast::Receiver<NoiseEvent> {
-        self.noise_tx.subscribe()
+    pub fn disable_keystroke_suppression(&self) {
+        let mut guard = self
+            .keystroke_suppressor
+            .lock()
+            .expect("keystroke suppressor mutex poisoned");
+        *guard = None;
     }
 
-    pub fn subscribe_pcm_frames(&self, capacity: usize) -> mpsc::Receiver<Arc<[f32]>> {
-        self.subscribe_pcm_frames_with_options(capacity, false)
+    /// 上报一次发生于 `at` 的击键，其前后数毫秒的音频将被衰减或静音。
+    /// 未启用键盘声抑制时忽略。
+    pub fn push_keystroke(&self, at: Instant) {
+        let mut guard = self
+            .keystroke_suppressor
+            .lock()
+            .expect("keystroke suppressor mutex poisoned");
+        if let Some(suppressor) = guard.as_mut() {
+            suppressor.record_keystroke(Instant::now().saturating_duration_since(at));
+        }
     }
 
-    pub fn subscribe_lossless_pcm_frames(&self, capacity: usize) -> mpsc::Receiver<Arc<[f32]>> {
-        self.subscribe_pcm_frames_with_options(capacity, true)
+    /// Declare the sample rate of frames passed to [`Self::push_pcm_frame`].
+    /// Frames at any rate other than the engine rate are resampled on the fly.
+    pub fn set_input_sample_rate(&self, sample_rate_hz: u32) {
+        let mut guard = self.resampler.lock().expect("resampler mutex poisoned");
+        let current = guard
+            .as_ref()
+            .map(StreamingResampler::input_rate_hz)
+            .unwrap_or(SAMPLE_RATE_HZ);
+        if current == sample_rate_hz {
+            return;
+        }
+        *guard = if sample_rate_hz == SAMPLE_RATE_HZ {
+            None
+        } else {
+            Some(StreamingResampler::new(sample_rate_hz, SAMPLE_RATE_HZ))
+        };
     }
 
-    fn subscribe_pcm_frames_with_options(
-        &self,
-        capacity: usize,
-        lossless: bool,
-    ) -> mpsc::Receiver<Arc<[f32]>> {
-        let bounded = capacity.max(1);
-        let max_queue = if lossless {
-            bounded
+    pub fn input_sample_rate(&self) -> u32 {
+        self.resampler
+            .lock()
+            .expect("resampler mutex poisoned")
+            .as_ref()
+            .map(StreamingResampler::input_rate_hz)
+            .unwrap_or(SAMPLE_RATE_HZ)
+    }
+
+    /// Retain up to `window` of the most recent audio while not recording so it
+    /// can be prepended to the next session. A zero window disables pre-roll.
+    pub fn set_preroll_window(&self, window: Duration) {
+        let capacity = if window.is_zero() {
+            0
         } else {
-            bounded.saturating_mul(4).max(bounded)
+            duration_to_samples(window, SAMPLE_RATE_HZ)
         };
-        let (tx, rx) = mpsc::channel(bounded);
-        let subscriber = PcmSubscriber::new(tx, max_queue, lossless);
+        self.preroll
+            .lock()
+            .expect("preroll mutex poisoned")
+            .set_capacity(capacity);
+    }
+
+    /// The pre-roll captured when recording began, followed by `current`, the
+    /// first live frame of the session. Drains the pre-roll.
+    pub fn take_preroll(&self, current: Arc<[f32]>) -> Vec<Arc<[f32]>> {
+        self.preroll
+            .lock()
+            .expect("preroll mutex poisoned")
+            .take_through(current)
+    }
+
+    /// Chunk PCM into frames of at most `window`, flushing once half a window
+    /// is buffered (the 200ms/100ms default, or 100ms/50ms for slow hotkeys).
+    pub fn set_frame_window(&self, window: Duration) {
+        let max = duration_to_samples(window, SAMPLE_RATE_HZ);
+        let min = (max / 2).max(1);
+        let previous = self.max_frame_samples.swap(max, Ordering::SeqCst);
+        self.min_frame_samples.store(min, Ordering::SeqCst);
+        if previous == max {
+            return;
+        }
+        let (min_frame, max_frame) = self.frame_window();
+        info!(
+            target: "audio_pipeline",
+            window_ms = max_frame.as_millis() as u64,
+            "frame window changed"
+        );
+        let _ = self.frame_window_tx.send(FrameWindowChanged {
+            previous: samples_to_duration(previous),
+            min_frame,
+            max_frame,
+        });
+    }
+
+    /// Current `(min, max)` duration of frames delivered to PCM subscribers.
+    pub fn frame_window(&self) -> (Duration, Duration) {
+        let (min, max) = self.frame_samples();
+        (samples_to_duration(min), samples_to_duration(max))
+    }
+
+    pub fn subscribe_frame_window(&self) -> broadcast::Receiver<FrameWindowChanged> {
+        self.frame_window_tx.subscribe()
+    }
+
+    fn frame_samples(&self) -> (usize, usize) {
+        (
+            self.min_frame_samples.load(Ordering::SeqCst),
+            self.max_frame_samples.load(Ordering::SeqCst),
+        )
+    }
+
+    /// Mirror post-gain PCM into a shared-memory ring at `path` holding the
+    /// latest `window` of audio; see [`ShmRingWriter`] for the layout. The
+    /// desktop shell maps it with [`ShmRingReader`] instead of receiving
+    /// samples over IPC. Replaces any ring enabled earlier.
+    pub fn enable_shm_ring(&self, path: &Path, window: Duration) -> Result<()> {
+        let capacity = duration_to_samples(window, SAMPLE_RATE_HZ);
+        let writer = ShmRingWriter::create(path, capacity, SAMPLE_RATE_HZ)?;
+        *self.shm_ring.lock().expect("shm ring mutex poisoned") = Some(writer);
+        Ok(())
+    }
+
+    pub fn disable_shm_ring(&self) {
+        *self.shm_ring.lock().expect("shm ring mutex poisoned") = None;
+    }
+
+    pub fn set_downmix_policy(&self, policy: DownmixPolicy) {
         let mut guard = self
-            .pcm_subscribers
+            .downmix_policy
             .lock()
-            .expect("pcm subscriber registry poisoned");
-        guard.push(subscriber);
-        rx
+            .expect("downmix policy mutex poisoned");
+        *guard = policy;
+    }
+
+    /// Decode raw PCM passed to [`AudioPipeline::handle_frame`] as `format`,
+    /// resampling from its rate to the engine rate.
+    pub fn set_pcm_format(&self, format: PcmFormat) -> Result<()> {
+        format.validate()?;
+        self.set_input_sample_rate(format.sample_rate_hz);
+        *self.pcm_format.lock().expect("pcm format mutex poisoned") = format;
+        Ok(())
+    }
+
+    pub fn pcm_format(&self) -> PcmFormat {
+        *self.pcm_format.lock().expect("pcm format mutex poisoned")
+    }
+
+    /// Choose from the formats a capture backend can produce and adopt the
+    /// result; see [`PcmFormat::negotiate`].
+    pub fn negotiate_pcm_format(&self, offered: &[PcmFormat]) -> Result<PcmFormat> {
+        let format = PcmFormat::negotiate(offered)?;
+        self.set_pcm_format(format)?;
+        info!(
+            target: "audio_pipeline",
+            encoding = format.encoding.as_str(),
+            channels = format.channels,
+            sample_rate_hz = format.sample_rate_hz,
+            "negotiated pcm format"
+        );
+        Ok(format)
+    }
+
+    pub fn downmix_policy(&self) -> DownmixPolicy {
+        *self
+            .downmix_policy
+            .lock()
+            .expect("downmix policy mutex poisoned")
+    }
+
+    pub fn subscribe_noise_events(&self) -> broadcast::Receiver<NoiseEvent> {
+        self.noise_tx.subscribe()
     }
 
     pub async fn push_pcm_frame(&self, frame: Vec<f32>) -> Result<()> {
-        if frame.is_empty() {
+        if frame.is_empty() || self.is_stopped() {
             return Ok(());
         }
 
+        let frame = {
+            let mut guard = self.resampler.lock().expect("resampler mutex poisoned");
+            match guard.as_mut() {
+                Some(resampler) => resampler.process(&frame),
+                None => frame,
+            }
+        };
+
+        let (min_frame_samples, max_frame_samples) = self.frame_samples();
         let chunks = {
             let mut guard = self.pending.lock().expect("pcm frame accumulator poisoned");
             guard.extend(frame);
 
             let mut chunks: Vec<Vec<f32>> = Vec::new();
-            while guard.len() >= self.min_frame_samples {
-                let chunk_len = guard.len().min(self.max_frame_samples);
+            while guard.len() >= min_frame_samples {
+                let chunk_len = guard.len().min(max_frame_samples);
                 let chunk: Vec<f32> = guard.drain(0..chunk_len).collect();
                 chunks.push(chunk);
             }
@@ -285,9 +415,29 @@ impl AudioPipeline {
         Ok(())
     }
 
+    /// Push an interleaved multi-channel frame, folding it to mono with the
+    /// configured [`DownmixPolicy`] before it enters the pipeline.
+    pub async fn push_pcm_frame_interleaved(&self, frame: Vec<f32>, channels: u16) -> Result<()> {
+        if channels == 1 {
+            return self.push_pcm_frame(frame).await;
+        }
+        let mono = downmix_interleaved(&frame, channels as usize, self.downmix_policy())?;
+        self.push_pcm_frame(mono).await
+    }
+
     pub async fn flush_pending(&self) -> Result<()> {
+        let resampled_tail = self
+            .resampler
+            .lock()
+            .expect("resampler mutex poisoned")
+            .as_mut()
+            .map(StreamingResampler::flush)
+            .unwrap_or_default();
+
+        let (min_frame_samples, max_frame_samples) = self.frame_samples();
         let chunks = {
             let mut guard = self.pending.lock().expect("pcm frame accumulator poisoned");
+            guard.extend(resampled_tail);
 
             if guard.is_empty() {
                 return Ok(());
@@ -295,16 +445,16 @@ impl AudioPipeline {
 
             let mut chunks: Vec<Vec<f32>> = Vec::new();
 
-            while guard.len() >= self.min_frame_samples {
-                let chunk_len = guard.len().min(self.max_frame_samples);
+            while guard.len() >= min_frame_samples {
+                let chunk_len = guard.len().min(max_frame_samples);
                 let chunk: Vec<f32> = guard.drain(0..chunk_len).collect();
                 chunks.push(chunk);
             }
 
             if !guard.is_empty() {
                 let mut tail: Vec<f32> = guard.drain(..).collect();
-                if tail.len() < self.min_frame_samples {
-                    tail.resize(self.min_frame_samples, 0.0);
+                if tail.len() < min_frame_samples {
+                    tail.resize(min_frame_samples, 0.0);
                 }
                 chunks.push(tail);
             }
@@ -321,49 +471,211 @@ impl AudioPipeline {
         Ok(())
     }
 
+    pub fn noise_profile(&self) -> NoiseProfile {
+        self.noise_detector
+            .lock()
+            .expect("noise detector mutex poisoned")
+            .profile()
+    }
+
+    /// 切换噪声检测档位（对应桌面端校准的强噪声模式）；已锁定的基线按新档位重新采样。
+    pub fn set_noise_profile(&self, profile: NoiseProfile) {
+        let previous = {
+            let mut detector = self
+                .noise_detector
+                .lock()
+                .expect("noise detector mutex poisoned");
+            let previous = detector.profile();
+            detector.set_profile(profile);
+            previous
+        };
+        if previous != profile && profile == NoiseProfile::StrongNoise {
+            metrics().strong_noise_activations.inc();
+        }
+        info!(
+            target: "audio_pipeline",
+            profile = profile.as_str(),
+            "noise profile changed"
+        );
+    }
+
+    pub fn silence_policy(&self) -> SilencePolicy {
+        self.noise_detector
+            .lock()
+            .expect("noise detector mutex poisoned")
+            .silence_policy()
+    }
+
+    /// 设置静音自动停止策略，立即作用于进行中的倒计时。
+    pub fn set_silence_policy(&self, policy: SilencePolicy) {
+        let events = self
+            .noise_detector
+            .lock()
+            .expect("noise detector mutex poisoned")
+            .set_silence_policy(policy);
+        self.dispatch_noise_events(events);
+    }
+
+    /// Capture from a native input API once the pipeline starts, instead of
+    /// relying on frames pushed by the host. Takes effect on the next start.
+    pub fn set_capture_backend(&self, backend: Arc<dyn CaptureBackend>, config: CaptureConfig) {
+        self.set_audio_source(config.source);
+        *self
+            .capture_backend
+            .lock()
+            .expect("capture backend mutex poisoned") = Some((backend, config));
+    }
+
+    /// The backend configured with [`AudioPipeline::set_capture_backend`], if any.
+    pub fn capture_backend(&self) -> Option<(Arc<dyn CaptureBackend>, CaptureConfig)> {
+        self.capture_backend
+            .lock()
+            .expect("capture backend mutex poisoned")
+            .clone()
+    }
+
+    /// Label the audio being fed; set by [`AudioPipeline::set_capture_backend`]
+    /// and by hosts that push loopback audio themselves.
+    pub fn set_audio_source(&self, source: AudioSource) {
+        *self
+            .audio_source
+            .lock()
+            .expect("audio source mutex poisoned") = source;
+    }
+
+    pub fn audio_source(&self) -> AudioSource {
+        *self
+            .audio_source
+            .lock()
+            .expect("audio source mutex poisoned")
+    }
+
+    /// Opens the configured capture backend, failing if no stream can be
+    /// opened; later stream failures are retried per its [`ReconnectPolicy`].
+    /// Without a backend the pipeline only processes pushed frames.
     pub async fn start(&self) -> Result<()> {
-        info!(target: "audio_pipeline", "starting placeholder pipeline");
+        self.stopped.store(false, Ordering::SeqCst);
+        let configured = self
+            .capture_backend
+            .lock()
+            .expect("capture backend mutex poisoned")
+            .clone();
+        let Some((backend, config)) = configured else {
+            info!(target: "audio_pipeline", "starting pipeline for pushed frames");
+            return Ok(());
+        };
+        let running = self
+            .capture_task
+            .lock()
+            .expect("capture task mutex poisoned")
+            .as_ref()
+            .is_some_and(|task| !task.is_finished());
+        if running {
+            return Ok(());
+        }
+
+        let capture = capture::open_capture(self, &backend, &config).await?;
+        let task = task::spawn(capture::supervise_capture(
+            self.clone(),
+            backend,
+            config,
+            capture,
+        ));
+        *self
+            .capture_task
+            .lock()
+            .expect("capture task mutex poisoned") = Some(task);
         Ok(())
     }
 
-    fn collect_subscribers(&self) -> Vec<PcmSubscriber> {
-        let mut guard = self
-            .pcm_subscribers
+    /// 停止接收新帧并冲刷尾部样本；已排队的帧投递完毕后各 PCM 订阅者的通道随之关闭。
+    pub async fn stop(&self) -> Result<()> {
+        if self.stopped.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        info!(target: "audio_pipeline", "stopping pipeline");
+        let capture = self
+            .capture_task
             .lock()
-            .expect("pcm subscriber registry poisoned");
-        guard.retain(|subscriber| !subscriber.is_closed());
-        guard.iter().cloned().collect()
+            .expect("capture task mutex poisoned")
+            .take();
+        if let Some(task) = capture {
+            task.abort();
+            // 等待任务结束，确保采集流已关闭。
+            let _ = task.await;
+        }
+        self.flush_pending().await?;
+        self.pcm_subscribers
+            .lock()
+            .expect("pcm subscriber registry poisoned")
+            .clear();
+        Ok(())
     }
 
-    async fn emit_chunk(&self, chunk: Vec<f32>) {
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    async fn emit_chunk(&self, mut chunk: Vec<f32>) {
         if chunk.is_empty() {
             return;
         }
 
-        self.emit_waveform_samples(&chunk);
+        // 击键抑制先于噪声检测，被抑制的击键不再触发键盘噪声提示。
+        self.suppress_keystrokes(&mut chunk);
+        // 噪声检测基于原始电平，必须在增益之前执行。
         self.process_noise_samples(&chunk);
+        let subscribers = self.collect_subscribers();
+        let raw: Option<Arc<[f32]>> = subscribers
+            .iter()
+            .any(|subscriber| subscriber.raw)
+            .then(|| Arc::from(chunk.as_slice()));
+        self.apply_gain(&mut chunk);
+        self.emit_waveform_samples(&chunk);
+        if let Some(ring) = self
+            .shm_ring
+            .lock()
+            .expect("shm ring mutex poisoned")
+            .as_mut()
+        {
+            ring.write(&chunk);
+        }
 
+        metrics().frames_processed.inc();
         let shared: Arc<[f32]> = chunk.into();
-        let subscribers = self.collect_subscribers();
+        let recording = matches!(
+            *self.stage.lock().expect("audio stage mutex poisoned"),
+            AudioCaptureStage::Recording
+        );
+        if !recording {
+            self.preroll
+                .lock()
+                .expect("preroll mutex poisoned")
+                .push(Arc::clone(&shared));
+        }
 
+        let mut deepest = 0;
         for subscriber in subscribers {
-            subscriber.enqueue(Arc::clone(&shared)).await;
+            let frame = match (&raw, subscriber.raw) {
+                (Some(raw), true) => Arc::clone(raw),
+                _ => Arc::clone(&shared),
+            };
+            deepest = deepest.max(subscriber.enqueue(frame).await);
         }
+        metrics().pcm_queue_depth.set(deepest as u64);
     }
 
-    fn emit_waveform_samples(&self, samples: &[f32]) {
-        if samples.is_empty() {
-            return;
-        }
-
+    fn suppress_keystrokes(&self, samples: &mut [f32]) {
         let mut guard = self
-            .waveform_pending
+            .keystroke_suppressor
             .lock()
-            .expect("waveform accumulator poisoned");
-        guard.extend(samples.iter().copied());
-        drop(guard);
-
-        self.waveform_started.store(true, Ordering::SeqCst);
+            .expect("keystroke suppressor mutex poisoned");
+        if let Some(suppressor) = guard.as_mut() {
+            let suppressed = suppressor.process(samples);
+            metrics()
+                .keystroke_suppressed_samples
+                .add(suppressed as u64);
+        }
     }
 
     fn process_noise_samples(&self, samples: &[f32]) {
@@ -380,7 +692,7 @@ impl AudioPipeline {
             return;
         }
 
-        let events = {
+        let mut events = {
             let mut detector = self
                 .noise_detector
                 .lock()
@@ -388,81 +700,134 @@ impl AudioPipeline {
             detector.ingest(samples, stage)
         };
 
-        self.dispatch_noise_events(events);
-    }
-
-    fn dispatch_noise_events(&self, events: Vec<NoiseEvent>) {
-        for event in events {
-            let _ = self.noise_tx.send(event);
+        if matches!(stage, AudioCaptureStage::Recording) {
+            let mut detector = self
+                .echo_detector
+                .lock()
+                .expect("echo detector mutex poisoned");
+            events.extend(
+                detector
+                    .ingest_capture(samples)
+                    .into_iter()
+                    .map(NoiseEvent::EchoDetected),
+            );
         }
+
+        self.dispatch_noise_events(events);
     }
 
-    fn flush_waveform_tail(&self) {
-        let mut guard = self
-            .waveform_pending
+    /// 送入系统回放（loopback）的 16kHz 单声道参考音频，用于检测麦克风是否录入了扬声器声音。
+    /// 未提供参考音频时不做回声检测。
+    pub fn push_loopback_reference(&self, samples: &[f32]) {
+        self.echo_detector
             .lock()
-            .expect("waveform accumulator poisoned");
+            .expect("echo detector mutex poisoned")
+            .push_reference(samples);
+    }
 
-        if guard.is_empty() {
-            return;
+    fn dispatch_noise_events(&self, events: Vec<NoiseEvent>) {
+        let established = events.iter().rev().find_map(|event| match event {
+            NoiseEvent::BaselineEstablished { level_db } => Some(*level_db),
+            _ => None,
+        });
+        if events
+            .iter()
+            .any(|event| matches!(event, NoiseEvent::NoiseWarning(_)))
+            && self.noise_profile() == NoiseProfile::StrongNoise
+        {
+            metrics().strong_noise_warnings.inc();
         }
-
-        let remainder = guard.len() % self.waveform_frame_samples;
-        if remainder != 0 {
-            let pad = self.waveform_frame_samples - remainder;
-            for _ in 0..pad {
-                guard.push_back(0.0);
-            }
+        if let (Some(level_db), Some(device_id)) = (established, self.active_device()) {
+            self.noise_baselines
+                .lock()
+                .expect("noise baseline mutex poisoned")
+                .insert(device_id, level_db);
         }
-    }
-
-    pub async fn handle_frame(&self, _pcm: Bytes) -> Result<()> {
-        // TODO: 接入 CoreAudio/WASAPI 并执行 VAD、降噪、增益控制。
-        if _pcm.is_empty() {
-            return Ok(());
+        for event in events {
+            let _ = self.noise_tx.send(event);
         }
+    }
 
-        if _pcm.len() % std::mem::size_of::<f32>() != 0 {
-            warn!(
-                target: "audio_pipeline",
-                length = _pcm.len(),
-                "pcm frame is not aligned to f32 samples"
-            );
+    /// 按协商的 [`PcmFormat`] 解析原始 PCM，转换为内部 f32 后送入管线。
+    pub async fn handle_frame(&self, pcm: Bytes) -> Result<()> {
+        if pcm.is_empty() {
             return Ok(());
         }
 
-        let mut frame = Vec::with_capacity(_pcm.len() / std::mem::size_of::<f32>());
-        for chunk in _pcm.chunks_exact(4) {
-            let bytes: [u8; 4] = chunk.try_into().expect("chunk size matches");
-            frame.push(f32::from_le_bytes(bytes));
-        }
+        let format = self.pcm_format();
+        let frame = match format.decode(&pcm) {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!(
+                    target: "audio_pipeline",
+                    length = pcm.len(),
+                    %err,
+                    "dropping misaligned pcm frame"
+                );
+                return Ok(());
+            }
+        };
 
-        self.push_pcm_frame(frame).await?;
-        Ok(())
+        self.push_pcm_frame_interleaved(frame, format.channels)
+            .await
     }
 
+    /// 进入预录阶段。未指定基线时使用当前设备上次记录的基线热启动，
+    /// 后台重新采样仅在漂移超过阈值时重新校准；该设备没有记录时从头采样。
     pub fn begin_preroll(&self, baseline_db: Option<f32>) {
         {
             let mut stage = self.stage.lock().expect("audio stage mutex poisoned");
             *stage = AudioCaptureStage::PreRoll;
         }
 
+        let stored = baseline_db
+            .is_none()
+            .then(|| self.active_device())
+            .flatten()
+            .and_then(|device_id| self.noise_baseline(&device_id));
         let events = {
             let mut detector = self
                 .noise_detector
                 .lock()
                 .expect("noise detector mutex poisoned");
-            detector.enter_preroll(baseline_db)
+            match stored {
+                Some(level_db) => detector.enter_preroll_warm(level_db),
+                None => detector.enter_preroll(baseline_db),
+            }
         };
 
         self.dispatch_noise_events(events);
     }
 
+    pub fn noise_baseline(&self, device_id: &str) -> Option<f32> {
+        self.noise_baselines
+            .lock()
+            .expect("noise baseline mutex poisoned")
+            .get(device_id)
+            .copied()
+    }
+
+    /// 载入持久化的各设备噪声基线，替换已有记录。
+    pub fn set_noise_baselines(&self, baselines: HashMap<String, f32>) {
+        *self
+            .noise_baselines
+            .lock()
+            .expect("noise baseline mutex poisoned") = baselines;
+    }
+
     pub fn begin_recording(&self) {
         {
             let mut stage = self.stage.lock().expect("audio stage mutex poisoned");
             *stage = AudioCaptureStage::Recording;
         }
+        self.preroll
+            .lock()
+            .expect("preroll mutex poisoned")
+            .freeze();
+        self.echo_detector
+            .lock()
+            .expect("echo detector mutex poisoned")
+            .reset();
 
         let mut detector = self
             .noise_detector
@@ -476,6 +841,30 @@ impl AudioPipeline {
             let mut stage = self.stage.lock().expect("audio stage mutex poisoned");
             *stage = AudioCaptureStage::Idle;
         }
+        self.preroll.lock().expect("preroll mutex poisoned").clear();
+        self.echo_detector
+            .lock()
+            .expect("echo detector mutex poisoned")
+            .reset();
+        if let Some(suppressor) = self
+            .keystroke_suppressor
+            .lock()
+            .expect("keystroke suppressor mutex poisoned")
+            .as_mut()
+        {
+            suppressor.reset();
+        }
+
+        self.reset_agc();
+
+        if let Some(resampler) = self
+            .resampler
+            .lock()
+            .expect("resampler mutex poisoned")
+            .as_mut()
+        {
+            resampler.reset();
+        }
 
         let mut detector = self
             .noise_detector
@@ -490,6 +879,15 @@ fn duration_to_samples(duration: Duration, sample_rate_hz: u32) -> usize {
     samples.max(1)
 }
 
+fn samples_to_duration(samples: usize) -> Duration {
+    Duration::from_secs_f64(samples as f64 / SAMPLE_RATE_HZ as f64)
+}
+
+/// Whether `frame` carries voiced audio by the pipeline's VAD threshold.
+pub fn is_speech(frame: &[f32]) -> bool {
+    frame_rms(frame) >= VAD_THRESHOLD
+}
+
 fn frame_rms(frame: &[f32]) -> f32 {
     if frame.is_empty() {
         return 0.0;
@@ -500,238 +898,4 @@ fn frame_rms(frame: &[f32]) -> f32 {
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use tokio::time::{sleep, timeout, Duration};
-
-    #[tokio::test]
-    async fn slow_subscriber_does_not_block_realtime_feed() {
-        let pipeline = AudioPipeline::new();
-        let mut fast = pipeline.subscribe_pcm_frames(4);
-        let slow = pipeline.subscribe_pcm_frames(1);
-
-        let frame = vec![
-            0.05_f32;
-            duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ)
-        ];
-
-        pipeline
-            .push_pcm_frame(frame.clone())
-            .await
-            .expect("first frame should succeed");
-
-        let _ = timeout(Duration::from_millis(100), fast.recv())
-            .await
-            .expect("fast subscriber timed out")
-            .expect("fast channel closed unexpectedly");
-
-        // Intentionally avoid consuming from the slow subscriber so its bounded queue stays full.
-
-        timeout(
-            Duration::from_millis(100),
-            pipeline.push_pcm_frame(frame.clone()),
-        )
-        .await
-        .expect("push_pcm_frame stalled on slow subscriber")
-        .expect("pipeline rejected frame");
-
-        let received = timeout(Duration::from_millis(100), fast.recv())
-            .await
-            .expect("fast subscriber did not receive second frame")
-            .expect("fast channel closed unexpectedly");
-        assert_eq!(received.len(), frame.len());
-
-        drop(slow);
-        sleep(Duration::from_millis(10)).await;
-    }
-
-    #[tokio::test]
-    async fn preserves_order_under_backpressure() {
-        let pipeline = AudioPipeline::new();
-        let mut rx = pipeline.subscribe_pcm_frames(2);
-
-        let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
-
-        for marker in 0..3 {
-            let frame = vec![marker as f32; frame_len];
-            pipeline
-                .push_pcm_frame(frame)
-                .await
-                .expect("pushes frame under backpressure");
-        }
-
-        for expected in 0..3 {
-            let received = timeout(Duration::from_millis(200), rx.recv())
-                .await
-                .expect("timed out waiting for ordered frame")
-                .expect("channel closed unexpectedly");
-
-            assert_eq!(received.len(), frame_len);
-            assert!(received
-                .iter()
-                .all(|sample| (*sample - expected as f32).abs() < f32::EPSILON));
-        }
-    }
-
-    #[tokio::test]
-    async fn flushes_pending_tail_on_request() {
-        let pipeline = AudioPipeline::new();
-        let mut rx = pipeline.subscribe_pcm_frames(4);
-
-        let half_frame =
-            duration_to_samples(Duration::from_millis(MIN_FRAME_MS / 2), SAMPLE_RATE_HZ);
-        pipeline
-            .push_pcm_frame(vec![0.2_f32; half_frame])
-            .await
-            .expect("push half frame");
-
-        // No frame should be emitted before the flush.
-        assert!(timeout(Duration::from_millis(50), rx.recv()).await.is_err());
-
-        pipeline.flush_pending().await.expect("flush pending audio");
-
-        let flushed = timeout(Duration::from_millis(100), rx.recv())
-            .await
-            .expect("flush did not emit frame")
-            .expect("channel closed unexpectedly");
-
-        assert_eq!(
-            flushed.len(),
-            duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ)
-        );
-        assert!(flushed
-            .iter()
-            .take(half_frame)
-            .all(|sample| (*sample - 0.2_f32).abs() < f32::EPSILON));
-        assert!(flushed
-            .iter()
-            .skip(half_frame)
-            .all(|sample| sample.abs() < f32::EPSILON));
-    }
-
-    #[tokio::test]
-    async fn drops_oldest_frame_when_queue_is_full() {
-        let pipeline = AudioPipeline::new();
-        let mut rx = pipeline.subscribe_pcm_frames(1);
-
-        let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
-
-        for marker in 0..10 {
-            pipeline
-                .push_pcm_frame(vec![marker as f32; frame_len])
-                .await
-                .expect("push frame while subscriber is stalled");
-        }
-
-        sleep(Duration::from_millis(10)).await;
-
-        let mut seen = Vec::new();
-        loop {
-            match timeout(Duration::from_millis(500), rx.recv()).await {
-                Ok(Some(frame)) => {
-                    assert_eq!(frame.len(), frame_len);
-                    seen.push(frame[0]);
-                }
-                Ok(None) | Err(_) => break,
-            }
-        }
-
-        assert!(!seen.is_empty(), "no frames observed after backlog");
-        assert!(seen.len() < 10, "backlog did not shed frames: {:?}", seen);
-        assert!(seen.len() <= 5, "unexpected backlog size: {:?}", seen);
-        assert!(
-            seen.windows(2).all(|w| w[0] <= w[1]),
-            "frames not monotonic: {:?}",
-            seen
-        );
-        assert_eq!(seen.last().copied(), Some(9.0_f32));
-    }
-
-    #[tokio::test]
-    async fn waveform_runs_at_target_cadence() {
-        let pipeline = AudioPipeline::new();
-        let mut waveform_rx = pipeline.subscribe_waveform();
-
-        let frame =
-            vec![0.2_f32; duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ)];
-
-        pipeline
-            .push_pcm_frame(frame)
-            .await
-            .expect("pcm frame should enqueue");
-
-        let mut received = 0;
-        while received < 3 {
-            let frame = timeout(Duration::from_millis(150), waveform_rx.recv())
-                .await
-                .expect("waveform frame timed out")
-                .expect("waveform channel closed unexpectedly");
-            assert!(frame.rms > 0.0);
-            assert!(frame.vad_active);
-            received += 1;
-        }
-    }
-
-    #[tokio::test]
-    async fn waveform_flush_emits_tail_frame() {
-        let pipeline = AudioPipeline::new();
-        let mut waveform_rx = pipeline.subscribe_waveform();
-
-        let half_frame =
-            duration_to_samples(Duration::from_millis(MIN_FRAME_MS / 2), SAMPLE_RATE_HZ);
-        pipeline
-            .push_pcm_frame(vec![0.05_f32; half_frame])
-            .await
-            .expect("partial frame should enqueue");
-
-        let preroll = timeout(Duration::from_millis(80), waveform_rx.recv())
-            .await
-            .expect("waveform pre-roll missing")
-            .expect("waveform channel closed unexpectedly");
-        assert_eq!(preroll.rms, 0.0);
-        assert!(!preroll.vad_active);
-
-        pipeline
-            .flush_pending()
-            .await
-            .expect("flush should succeed");
-
-        let frame = timeout(Duration::from_millis(200), waveform_rx.recv())
-            .await
-            .expect("waveform frame not emitted after flush")
-            .expect("waveform channel closed unexpectedly");
-        assert!(frame.rms > 0.0);
-        assert!(frame.vad_active);
-    }
-
-    #[tokio::test]
-    async fn noise_baseline_event_emitted_after_sampling() {
-        let pipeline = AudioPipeline::new();
-        pipeline.begin_preroll(None);
-        let mut noise_rx = pipeline.subscribe_noise_events();
-
-        let frame = vec![0.1_f32; duration_to_samples(Duration::from_millis(500), SAMPLE_RATE_HZ)];
-
-        pipeline
-            .push_pcm_frame(frame)
-            .await
-            .expect("pcm frame should enqueue");
-
-        let event = timeout(Duration::from_millis(200), noise_rx.recv())
-            .await
-            .expect("noise baseline event timed out")
-            .expect("noise channel closed unexpectedly");
-
-        match event {
-            NoiseEvent::BaselineEstablished { level_db } => {
-                assert!((level_db + 20.0).abs() < 1.5);
-            }
-            NoiseEvent::NoiseWarning(_) => {
-                panic!("expected baseline event, received noise warning");
-            }
-            NoiseEvent::SilenceCountdown(_) => {
-                panic!("expected baseline event, received silence countdown");
-            }
-        }
-    }
-}
+mod tests;
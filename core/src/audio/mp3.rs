@@ -0,0 +1,116 @@
+use std::io::{Cursor, ErrorKind};
+
+use anyhow::{Context, Result};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::DecodedAudio;
+
+/// Whether `bytes` start like an MP3 stream: an ID3v2 tag or an MPEG frame sync.
+pub fn looks_like_mp3(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"ID3") || matches!(bytes, [0xFF, second, ..] if second & 0xE0 == 0xE0)
+}
+
+/// Decode an MPEG audio (MP3) stream into interleaved samples. Corrupt frames
+/// are skipped the way players do; a stream without any decodable frame fails.
+pub fn decode_mp3(bytes: &[u8]) -> Result<DecodedAudio> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(bytes.to_vec())), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension("mp3");
+    let mut reader = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .context("not an MP3 stream")?
+        .format;
+    let track = reader
+        .default_track()
+        .context("MP3 stream has no audio track")?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .context("unsupported MP3 codec parameters")?;
+
+    let mut format = None;
+    let mut samples = Vec::new();
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err).context("failed to read MP3 frame"),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(err) => return Err(err).context("failed to decode MP3 frame"),
+        };
+        let spec = *decoded.spec();
+        format.get_or_insert((spec.rate, spec.channels.count() as u16));
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+    }
+
+    let (sample_rate_hz, channels) = format.context("MP3 stream has no decodable frames")?;
+    Ok(DecodedAudio {
+        sample_rate_hz,
+        channels,
+        samples,
+    })
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::audio::DownmixPolicy;
+
+    /// MPEG-1 Layer III, 128 kbps, 44.1 kHz, mono; every frame decodes to 1152 silent samples.
+    const SILENT_FRAME_HEADER: [u8; 4] = [0xFF, 0xFB, 0x90, 0xC0];
+    const SILENT_FRAME_LEN: usize = 417;
+
+    pub(in crate::audio) fn silent_mp3(frames: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(frames * SILENT_FRAME_LEN);
+        for _ in 0..frames {
+            bytes.extend(SILENT_FRAME_HEADER);
+            bytes.resize(
+                bytes.len() + SILENT_FRAME_LEN - SILENT_FRAME_HEADER.len(),
+                0,
+            );
+        }
+        bytes
+    }
+
+    #[test]
+    fn decodes_mp3_frames_and_resamples_to_engine_rate() {
+        let bytes = silent_mp3(40);
+        assert!(looks_like_mp3(&bytes));
+
+        let decoded = decode_mp3(&bytes).expect("decode mp3");
+        assert_eq!((decoded.sample_rate_hz, decoded.channels), (44_100, 1));
+        assert!(decoded.frames() >= 38 * 1_152, "{}", decoded.frames());
+        assert!(decoded.samples.iter().all(|sample| sample.abs() < 1e-3));
+
+        let mono = decoded
+            .to_engine_mono(DownmixPolicy::default())
+            .expect("resample");
+        let expected = decoded.frames() * 16_000 / 44_100;
+        assert!((mono.len() as i64 - expected as i64).abs() <= 2);
+    }
+
+    #[test]
+    fn rejects_streams_without_mp3_frames() {
+        assert!(decode_mp3(b"ID3\x04\0\0\0\0\0\0").is_err());
+        assert!(!looks_like_mp3(b"RIFF\0\0\0\0WAVE"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+/// Cutoff of the one-pole low-pass used to measure hum energy.
+pub(super) const LOW_BAND_CUTOFF_HZ: f32 = 300.0;
+/// Sub-block length used to measure how bursty a window is.
+pub(super) const ENVELOPE_BLOCK: Duration = Duration::from_millis(10);
+
+/// Coarse classification of the noise behind a warning, so calibration hints
+/// can name the likely source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoiseKind {
+    KeyboardClatter,
+    FanHum,
+    SpeechBabble,
+    #[default]
+    Unknown,
+}
+
+impl NoiseKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoiseKind::KeyboardClatter => "keyboardClatter",
+            NoiseKind::FanHum => "fanHum",
+            NoiseKind::SpeechBabble => "speechBabble",
+            NoiseKind::Unknown => "unknown",
+        }
+    }
+
+    /// User-facing calibration hint, or `None` when the source is unclear.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            NoiseKind::KeyboardClatter => Some("检测到键盘敲击声"),
+            NoiseKind::FanHum => Some("检测到风扇或空调的低频嗡嗡声"),
+            NoiseKind::SpeechBabble => Some("检测到背景人声"),
+            NoiseKind::Unknown => None,
+        }
+    }
+}
+
+/// Band-energy features averaged over the windows of one noise spike.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(super) struct SpikeFeatures {
+    /// Zero crossings per sample.
+    zero_crossing_rate: f32,
+    /// Share of energy below [`LOW_BAND_CUTOFF_HZ`].
+    low_band_ratio: f32,
+    /// Peak over RMS.
+    crest_factor: f32,
+    /// Coefficient of variation of the [`ENVELOPE_BLOCK`] RMS envelope.
+    modulation: f32,
+    windows: u32,
+}
+
+impl SpikeFeatures {
+    pub(super) fn measure(window: &[f32], low_pass_alpha: f32, block_samples: usize) -> Self {
+        if window.is_empty() {
+            return Self::default();
+        }
+        let mut energy = 0.0_f32;
+        let mut low_energy = 0.0_f32;
+        let mut peak = 0.0_f32;
+        let mut crossings = 0_usize;
+        let mut low_pass = 0.0_f32;
+        for (index, &sample) in window.iter().enumerate() {
+            energy += sample * sample;
+            peak = peak.max(sample.abs());
+            low_pass += low_pass_alpha * (sample - low_pass);
+            low_energy += low_pass * low_pass;
+            if index > 0 && (window[index - 1] >= 0.0) != (sample >= 0.0) {
+                crossings += 1;
+            }
+        }
+
+        let blocks: Vec<f32> = window
+            .chunks(block_samples.max(1))
+            .map(|block| (block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32).sqrt())
+            .collect();
+        let mean = blocks.iter().sum::<f32>() / blocks.len() as f32;
+        let variance =
+            blocks.iter().map(|rms| (rms - mean).powi(2)).sum::<f32>() / blocks.len() as f32;
+
+        let rms = (energy / window.len() as f32).sqrt();
+        Self {
+            zero_crossing_rate: crossings as f32 / window.len() as f32,
+            low_band_ratio: if energy > 0.0 {
+                (low_energy / energy).min(1.0)
+            } else {
+                0.0
+            },
+            crest_factor: if rms > 0.0 { peak / rms } else { 0.0 },
+            modulation: if mean > 0.0 {
+                variance.sqrt() / mean
+            } else {
+                0.0
+            },
+            windows: 1,
+        }
+    }
+
+    pub(super) fn accumulate(&mut self, other: SpikeFeatures) {
+        self.zero_crossing_rate += other.zero_crossing_rate;
+        self.low_band_ratio += other.low_band_ratio;
+        self.crest_factor += other.crest_factor;
+        self.modulation += other.modulation;
+        self.windows += other.windows;
+    }
+
+    pub(super) fn classify(&self) -> NoiseKind {
+        if self.windows == 0 {
+            return NoiseKind::Unknown;
+        }
+        let count = self.windows as f32;
+        let zcr = self.zero_crossing_rate / count;
+        let low_band = self.low_band_ratio / count;
+        let crest = self.crest_factor / count;
+        let modulation = self.modulation / count;
+
+        // Key presses are short broadband clicks separated by near silence.
+        if crest >= 5.0 && modulation >= 0.8 {
+            NoiseKind::KeyboardClatter
+        // Fans and HVAC are steady and dominated by low-frequency energy.
+        } else if low_band >= 0.6 && modulation < 0.3 {
+            NoiseKind::FanHum
+        // Voices sit in the mid band: too bright for hum, too tonal for hiss.
+        } else if low_band < 0.6 && (0.02..0.3).contains(&zcr) {
+            NoiseKind::SpeechBabble
+        } else {
+            NoiseKind::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::noise::{NoiseDetector, NoiseEvent};
+    use crate::audio::AudioCaptureStage;
+
+    fn classify_spike(signal: impl Fn(usize) -> f32) -> NoiseKind {
+        let mut detector = NoiseDetector::new(16_000);
+        detector.enter_preroll(Some(-60.0));
+        detector.enter_recording();
+        let samples: Vec<f32> = (0..4_800).map(signal).collect();
+        detector
+            .ingest(&samples, AudioCaptureStage::Recording)
+            .into_iter()
+            .find_map(|event| match event {
+                NoiseEvent::NoiseWarning(payload) => Some(payload.kind),
+                _ => None,
+            })
+            .expect("noise warning")
+    }
+
+    #[test]
+    fn classifies_noise_kind_from_band_features() {
+        let tone =
+            |hz: f32, n: usize| (2.0 * std::f32::consts::PI * hz * n as f32 / 16_000.0).sin();
+
+        let hum = classify_spike(|n| 0.3 * tone(120.0, n) + 0.05 * tone(240.0, n));
+        assert_eq!(hum, NoiseKind::FanHum);
+        assert_eq!(hum.hint(), Some("检测到风扇或空调的低频嗡嗡声"));
+
+        // A click every 50 ms that decays within ~2 ms.
+        let clatter = classify_spike(|n| {
+            let offset = n % 800;
+            let noise = ((n.wrapping_mul(1_103_515_245).wrapping_add(12_345) >> 8) % 2_000) as f32
+                / 1_000.0
+                - 1.0;
+            if offset < 32 {
+                0.8 * noise * (-(offset as f32) / 8.0).exp()
+            } else {
+                0.0
+            }
+        });
+        assert_eq!(clatter, NoiseKind::KeyboardClatter);
+        assert_eq!(clatter.hint(), Some("检测到键盘敲击声"));
+
+        let babble = classify_spike(|n| {
+            [
+                (220.0, 0.0),
+                (470.0, 1.3),
+                (830.0, 2.1),
+                (1_330.0, 0.7),
+                (1_900.0, 2.9),
+            ]
+            .iter()
+            .map(|&(hz, phase)| {
+                0.06 * (2.0 * std::f32::consts::PI * hz * n as f32 / 16_000.0 + phase).sin()
+            })
+            .sum()
+        });
+        assert_eq!(babble, NoiseKind::SpeechBabble);
+
+        // Alternating full-scale samples: all high band, no clear source.
+        let hiss = classify_spike(|n| if n % 2 == 0 { 0.2 } else { -0.2 });
+        assert_eq!(hiss, NoiseKind::Unknown);
+        assert_eq!(hiss.hint(), None);
+    }
+}
@@ -1,8 +1,20 @@
 use std::collections::VecDeque;
 use std::time::Duration;
 
+use super::echo::EchoDetectedPayload;
 use super::AudioCaptureStage;
 
+mod classify;
+mod profile;
+mod silence;
+
+pub use classify::NoiseKind;
+use classify::{SpikeFeatures, ENVELOPE_BLOCK, LOW_BAND_CUTOFF_HZ};
+use profile::HighPass;
+pub use profile::NoiseProfile;
+use silence::countdown_windows;
+pub use silence::{SilenceCountdownPayload, SilenceCountdownStatus, SilencePolicy};
+
 /// Event emitted by the [`NoiseDetector`] to describe changes in the
 /// environment noise conditions.
 #[derive(Debug, Clone)]
@@ -15,6 +27,8 @@ pub enum NoiseEvent {
     NoiseWarning(NoiseWarningPayload),
     /// Silence has persisted and a countdown toward auto-stop is underway.
     SilenceCountdown(SilenceCountdownPayload),
+    /// The microphone is picking up the system audio output.
+    EchoDetected(EchoDetectedPayload),
 }
 
 /// Structured payload describing a detected noise warning.
@@ -24,24 +38,12 @@ pub struct NoiseWarningPayload {
     pub threshold_db: f32,
     pub window_db: f32,
     pub persistence_ms: u32,
+    pub kind: NoiseKind,
 }
 
-/// Enumerates the state transitions of a silence countdown timer.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SilenceCountdownStatus {
-    Started,
-    Tick,
-    Canceled,
-    Completed,
-}
-
-/// Structured payload describing the progress of a silence countdown timer.
-#[derive(Debug, Clone)]
-pub struct SilenceCountdownPayload {
-    pub total_ms: u32,
-    pub remaining_ms: u32,
-    pub status: SilenceCountdownStatus,
-}
+/// A warm-started baseline is replaced only when the freshly sampled level
+/// differs by more than this.
+pub const BASELINE_DRIFT_DB: f32 = 6.0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BaselineState {
@@ -53,8 +55,13 @@ enum BaselineState {
 /// Rolling noise and silence detector.
 pub struct NoiseDetector {
     stage: AudioCaptureStage,
+    sample_rate: u32,
+    profile: NoiseProfile,
+    high_pass: Option<HighPass>,
     baseline_state: BaselineState,
     baseline_db: Option<f32>,
+    /// Warm-start baseline still being checked against a fresh sample.
+    verifying_baseline: Option<f32>,
     fallback_samples: usize,
     sampling_remaining: usize,
     sampling_energy: f64,
@@ -62,11 +69,16 @@ pub struct NoiseDetector {
     analysis_window_samples: usize,
     analysis_pending: VecDeque<f32>,
     over_threshold_windows: usize,
+    spike_features: SpikeFeatures,
+    low_pass_alpha: f32,
+    envelope_block_samples: usize,
     spike_active: bool,
     cooldown_windows: usize,
     silence_threshold_offset_db: f32,
-    silence_countdown_ms: u32,
+    silence_policy: SilencePolicy,
     silence_countdown_windows: usize,
+    /// Analysis windows evaluated since recording began.
+    recording_windows: usize,
     silence_windows: usize,
     silence_active: bool,
     silence_completed: bool,
@@ -76,16 +88,15 @@ impl NoiseDetector {
     pub fn new(sample_rate: u32) -> Self {
         let fallback_samples = duration_to_samples(Duration::from_millis(500), sample_rate);
         let analysis_window_samples = duration_to_samples(Duration::from_millis(100), sample_rate);
-        let silence_countdown_ms = 5_000;
-        let silence_countdown_windows = (duration_to_samples(
-            Duration::from_millis(silence_countdown_ms as u64),
-            sample_rate,
-        ) / analysis_window_samples)
-            .max(1);
+        let silence_policy = SilencePolicy::default();
         Self {
             stage: AudioCaptureStage::Idle,
+            sample_rate,
+            profile: NoiseProfile::Standard,
+            high_pass: None,
             baseline_state: BaselineState::Idle,
             baseline_db: None,
+            verifying_baseline: None,
             fallback_samples,
             sampling_remaining: fallback_samples,
             sampling_energy: 0.0,
@@ -93,11 +104,17 @@ impl NoiseDetector {
             analysis_window_samples,
             analysis_pending: VecDeque::new(),
             over_threshold_windows: 0,
+            spike_features: SpikeFeatures::default(),
+            low_pass_alpha: 1.0
+                - (-2.0 * std::f32::consts::PI * LOW_BAND_CUTOFF_HZ / sample_rate.max(1) as f32)
+                    .exp(),
+            envelope_block_samples: duration_to_samples(ENVELOPE_BLOCK, sample_rate),
             spike_active: false,
             cooldown_windows: 0,
             silence_threshold_offset_db: 10.0,
-            silence_countdown_ms,
-            silence_countdown_windows,
+            silence_policy,
+            silence_countdown_windows: countdown_windows(silence_policy.countdown_ms),
+            recording_windows: 0,
             silence_windows: 0,
             silence_active: false,
             silence_completed: false,
@@ -108,11 +125,13 @@ impl NoiseDetector {
         self.stage = AudioCaptureStage::Idle;
         self.baseline_state = BaselineState::Idle;
         self.baseline_db = None;
+        self.verifying_baseline = None;
         self.sampling_remaining = self.fallback_samples;
         self.sampling_energy = 0.0;
         self.sampling_samples = 0;
         self.analysis_pending.clear();
         self.over_threshold_windows = 0;
+        self.spike_features = SpikeFeatures::default();
         self.spike_active = false;
         self.cooldown_windows = 0;
         self.silence_windows = 0;
@@ -122,11 +141,13 @@ impl NoiseDetector {
 
     pub fn enter_preroll(&mut self, baseline_db: Option<f32>) -> Vec<NoiseEvent> {
         self.stage = AudioCaptureStage::PreRoll;
+        self.verifying_baseline = None;
         self.sampling_energy = 0.0;
         self.sampling_samples = 0;
         self.sampling_remaining = self.fallback_samples;
         self.analysis_pending.clear();
         self.over_threshold_windows = 0;
+        self.spike_features = SpikeFeatures::default();
         self.spike_active = false;
         self.cooldown_windows = 0;
         self.silence_windows = 0;
@@ -147,10 +168,40 @@ impl NoiseDetector {
         }
     }
 
+    /// Lock `stored_db`, the last baseline known for this device, right away
+    /// while a fresh baseline is sampled in the background. The stored level
+    /// is kept unless the fresh sample drifts by more than [`BASELINE_DRIFT_DB`],
+    /// in which case the baseline is recalibrated and announced again.
+    pub fn enter_preroll_warm(&mut self, stored_db: f32) -> Vec<NoiseEvent> {
+        let events = self.enter_preroll(Some(stored_db));
+        self.verifying_baseline = Some(stored_db);
+        events
+    }
+
+    /// Discard the locked baseline and sample a new one from the next frames,
+    /// e.g. after the input device changed. The capture stage is kept.
+    pub fn resync_baseline(&mut self) {
+        self.baseline_state = BaselineState::Sampling;
+        self.baseline_db = None;
+        self.verifying_baseline = None;
+        self.sampling_energy = 0.0;
+        self.sampling_samples = 0;
+        self.sampling_remaining = self.fallback_samples;
+        self.analysis_pending.clear();
+        self.over_threshold_windows = 0;
+        self.spike_features = SpikeFeatures::default();
+        self.spike_active = false;
+        self.silence_windows = 0;
+        self.silence_active = false;
+        self.silence_completed = false;
+    }
+
     pub fn enter_recording(&mut self) {
         self.stage = AudioCaptureStage::Recording;
+        self.recording_windows = 0;
         self.analysis_pending.clear();
         self.over_threshold_windows = 0;
+        self.spike_features = SpikeFeatures::default();
         self.spike_active = false;
         self.cooldown_windows = 0;
         self.silence_windows = 0;
@@ -167,6 +218,11 @@ impl NoiseDetector {
             self.stage = stage;
         }
 
+        let filtered = self
+            .high_pass
+            .as_mut()
+            .map(|filter| filter.process(samples));
+        let samples = filtered.as_deref().unwrap_or(samples);
         match stage {
             AudioCaptureStage::PreRoll => self.ingest_preroll(samples),
             AudioCaptureStage::Recording => self.ingest_recording(samples),
@@ -175,7 +231,7 @@ impl NoiseDetector {
     }
 
     fn ingest_preroll(&mut self, samples: &[f32]) -> Vec<NoiseEvent> {
-        if self.baseline_state != BaselineState::Sampling {
+        if self.baseline_state != BaselineState::Sampling && self.verifying_baseline.is_none() {
             return Vec::new();
         }
 
@@ -185,7 +241,7 @@ impl NoiseDetector {
     fn ingest_recording(&mut self, samples: &[f32]) -> Vec<NoiseEvent> {
         let mut events = Vec::new();
 
-        if self.baseline_state == BaselineState::Sampling {
+        if self.baseline_state == BaselineState::Sampling || self.verifying_baseline.is_some() {
             events.extend(self.collect_baseline(samples));
         }
 
@@ -196,12 +252,14 @@ impl NoiseDetector {
         self.analysis_pending.extend(samples.iter().copied());
 
         while self.analysis_pending.len() >= self.analysis_window_samples {
-            let mut energy = 0.0_f64;
-            for _ in 0..self.analysis_window_samples {
-                if let Some(sample) = self.analysis_pending.pop_front() {
-                    energy += f64::from(sample) * f64::from(sample);
-                }
-            }
+            let window: Vec<f32> = self
+                .analysis_pending
+                .drain(..self.analysis_window_samples)
+                .collect();
+            let energy: f64 = window
+                .iter()
+                .map(|&sample| f64::from(sample) * f64::from(sample))
+                .sum();
 
             let rms = if self.analysis_window_samples > 0 {
                 (energy / self.analysis_window_samples as f64).sqrt() as f32
@@ -211,7 +269,7 @@ impl NoiseDetector {
 
             let window_db = amplitude_to_db(rms);
             let baseline_db = self.baseline_db.expect("baseline locked implies value");
-            let threshold = baseline_db + 15.0;
+            let threshold = baseline_db + self.profile.spike_offset_db();
 
             if self.cooldown_windows > 0 {
                 self.cooldown_windows -= 1;
@@ -219,20 +277,29 @@ impl NoiseDetector {
 
             if window_db >= threshold {
                 self.over_threshold_windows += 1;
+                self.spike_features.accumulate(SpikeFeatures::measure(
+                    &window,
+                    self.low_pass_alpha,
+                    self.envelope_block_samples,
+                ));
             } else {
                 self.over_threshold_windows = 0;
+                self.spike_features = SpikeFeatures::default();
                 self.spike_active = false;
             }
 
-            if self.over_threshold_windows >= 3 && !self.spike_active && self.cooldown_windows == 0
+            if self.over_threshold_windows >= self.profile.spike_windows()
+                && !self.spike_active
+                && self.cooldown_windows == 0
             {
                 self.spike_active = true;
-                self.cooldown_windows = 20;
+                self.cooldown_windows = self.profile.cooldown_windows();
                 events.push(NoiseEvent::NoiseWarning(NoiseWarningPayload {
                     baseline_db,
                     threshold_db: threshold,
                     window_db,
                     persistence_ms: (self.over_threshold_windows as u32) * 100,
+                    kind: self.spike_features.classify(),
                 }));
             }
 
@@ -242,59 +309,6 @@ impl NoiseDetector {
         events
     }
 
-    fn evaluate_silence(&mut self, window_db: f32, baseline_db: f32, events: &mut Vec<NoiseEvent>) {
-        let threshold = baseline_db - self.silence_threshold_offset_db;
-
-        if window_db <= threshold {
-            if self.silence_completed {
-                return;
-            }
-
-            self.silence_windows += 1;
-            let countdown_windows = self.silence_countdown_windows.max(1);
-            let elapsed_windows = self.silence_windows.min(countdown_windows);
-            let elapsed_ms = (elapsed_windows as u32) * 100;
-            let remaining_ms = self
-                .silence_countdown_ms
-                .saturating_sub(elapsed_ms)
-                .min(self.silence_countdown_ms);
-
-            let status = if self.silence_windows == 1 {
-                SilenceCountdownStatus::Started
-            } else if remaining_ms == 0 {
-                SilenceCountdownStatus::Completed
-            } else {
-                SilenceCountdownStatus::Tick
-            };
-
-            self.silence_active = true;
-
-            if status == SilenceCountdownStatus::Completed {
-                self.silence_completed = true;
-                self.silence_active = false;
-                self.silence_windows = countdown_windows;
-            }
-
-            events.push(NoiseEvent::SilenceCountdown(SilenceCountdownPayload {
-                total_ms: self.silence_countdown_ms,
-                remaining_ms,
-                status,
-            }));
-        } else if self.silence_windows > 0 || self.silence_active {
-            if !self.silence_completed {
-                events.push(NoiseEvent::SilenceCountdown(SilenceCountdownPayload {
-                    total_ms: self.silence_countdown_ms,
-                    remaining_ms: self.silence_countdown_ms,
-                    status: SilenceCountdownStatus::Canceled,
-                }));
-            }
-
-            self.silence_windows = 0;
-            self.silence_active = false;
-            self.silence_completed = false;
-        }
-    }
-
     fn collect_baseline(&mut self, samples: &[f32]) -> Vec<NoiseEvent> {
         if self.sampling_remaining == 0 {
             return Vec::new();
@@ -322,6 +336,11 @@ impl NoiseDetector {
                 -120.0
             };
 
+            if let Some(stored_db) = self.verifying_baseline.take() {
+                if (level_db - stored_db).abs() <= BASELINE_DRIFT_DB {
+                    return Vec::new();
+                }
+            }
             self.baseline_state = BaselineState::Locked;
             self.baseline_db = Some(level_db);
             vec![NoiseEvent::BaselineEstablished { level_db }]
@@ -360,6 +379,7 @@ mod tests {
             }
             NoiseEvent::NoiseWarning(_) => panic!("unexpected noise warning"),
             NoiseEvent::SilenceCountdown(_) => panic!("unexpected silence countdown"),
+            NoiseEvent::EchoDetected(_) => panic!("unexpected echo event"),
         }
         assert_eq!(detector.baseline_db(), Some(-32.0));
     }
@@ -378,6 +398,7 @@ mod tests {
             NoiseEvent::BaselineEstablished { level_db } => *level_db,
             NoiseEvent::NoiseWarning(_) => panic!("unexpected noise warning"),
             NoiseEvent::SilenceCountdown(_) => panic!("unexpected silence countdown"),
+            NoiseEvent::EchoDetected(_) => panic!("unexpected echo event"),
         };
 
         assert!(
@@ -434,6 +455,9 @@ mod tests {
             NoiseEvent::SilenceCountdown(_) => {
                 panic!("unexpected silence countdown event during noise spike");
             }
+            NoiseEvent::EchoDetected(_) => {
+                panic!("unexpected echo event during noise spike");
+            }
         }
 
         let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
@@ -481,94 +505,37 @@ mod tests {
             NoiseEvent::SilenceCountdown(_) => {
                 panic!("unexpected silence countdown event during noise spike");
             }
-        }
-    }
-
-    #[test]
-    fn silence_countdown_completes_after_5_seconds() {
-        let mut detector = NoiseDetector::new(16_000);
-        detector.enter_preroll(None);
-
-        let baseline_samples = vec![0.05_f32; 8_000];
-        let events = detector.ingest(&baseline_samples, AudioCaptureStage::PreRoll);
-        assert_eq!(events.len(), 1);
-
-        detector.enter_recording();
-
-        let quiet_window = vec![0.005_f32; 1_600];
-        for step in 1..=50 {
-            let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
-            assert_eq!(events.len(), 1, "expected countdown event on step {step}");
-
-            match &events[0] {
-                NoiseEvent::SilenceCountdown(payload) => {
-                    assert_eq!(payload.total_ms, 5_000);
-                    match payload.status {
-                        SilenceCountdownStatus::Started => {
-                            assert_eq!(step, 1);
-                            assert_eq!(payload.remaining_ms, 4_900);
-                        }
-                        SilenceCountdownStatus::Tick => {
-                            assert!(step > 1 && step < 50);
-                            assert!(payload.remaining_ms < 5_000);
-                            assert!(payload.remaining_ms > 0);
-                        }
-                        SilenceCountdownStatus::Completed => {
-                            assert_eq!(step, 50);
-                            assert_eq!(payload.remaining_ms, 0);
-                        }
-                        SilenceCountdownStatus::Canceled => {
-                            panic!("did not expect cancellation during continuous silence");
-                        }
-                    }
-                }
-                _ => panic!("unexpected event emitted during silence countdown"),
+            NoiseEvent::EchoDetected(_) => {
+                panic!("unexpected echo event during noise spike");
             }
         }
-
-        let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
-        assert!(
-            events.is_empty(),
-            "countdown completion should suppress further events"
-        );
     }
 
     #[test]
-    fn silence_countdown_cancels_on_speech_return() {
+    fn warm_start_keeps_stored_baseline_unless_it_drifted() {
         let mut detector = NoiseDetector::new(16_000);
-        detector.enter_preroll(None);
-
-        let baseline_samples = vec![0.05_f32; 8_000];
-        let events = detector.ingest(&baseline_samples, AudioCaptureStage::PreRoll);
+        let events = detector.enter_preroll_warm(-27.0);
+        assert!(matches!(
+            events[..],
+            [NoiseEvent::BaselineEstablished { level_db }] if level_db == -27.0
+        ));
+
+        // 0.05 RMS is about -26 dBFS: within the drift tolerance.
+        let stable = vec![0.05_f32; 8_000];
+        assert!(detector
+            .ingest(&stable, AudioCaptureStage::PreRoll)
+            .is_empty());
+        assert_eq!(detector.baseline_db(), Some(-27.0));
+
+        let events = detector.enter_preroll_warm(-50.0);
         assert_eq!(events.len(), 1);
-
-        detector.enter_recording();
-
-        let quiet_window = vec![0.005_f32; 1_600];
-        for _ in 0..5 {
-            let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
-            assert!(!events.is_empty());
-        }
-
-        let loud_window = vec![0.05_f32; 1_600];
-        let events = detector.ingest(&loud_window, AudioCaptureStage::Recording);
-        assert_eq!(events.len(), 1, "speech return should emit cancellation");
-
-        match &events[0] {
-            NoiseEvent::SilenceCountdown(payload) => {
-                assert_eq!(payload.status, SilenceCountdownStatus::Canceled);
-                assert_eq!(payload.remaining_ms, 5_000);
-            }
-            _ => panic!("expected silence countdown cancellation event"),
-        }
-
-        let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
-        assert_eq!(events.len(), 1, "new silence should restart countdown");
-        match &events[0] {
-            NoiseEvent::SilenceCountdown(payload) => {
-                assert_eq!(payload.status, SilenceCountdownStatus::Started);
+        let events = detector.ingest(&stable, AudioCaptureStage::PreRoll);
+        match events[..] {
+            [NoiseEvent::BaselineEstablished { level_db }] => {
+                assert!((level_db + 26.0).abs() < 0.5);
             }
-            _ => panic!("expected countdown restart"),
+            _ => panic!("expected recalibrated baseline, got {events:?}"),
         }
+        assert!((detector.baseline_db().unwrap() + 26.0).abs() < 0.5);
     }
 }
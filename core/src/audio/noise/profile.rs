@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use super::{BaselineState, NoiseDetector};
+
+/// Detector tuning for the acoustic environment. Strong-noise mode targets
+/// loud rooms such as open offices or cafés, where the standard thresholds
+/// raise warnings constantly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseProfile {
+    #[default]
+    Standard,
+    StrongNoise,
+}
+
+impl NoiseProfile {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoiseProfile::Standard => "standard",
+            NoiseProfile::StrongNoise => "strong_noise",
+        }
+    }
+
+    /// Level above the baseline that counts toward a noise warning.
+    pub(super) fn spike_offset_db(&self) -> f32 {
+        match self {
+            NoiseProfile::Standard => 15.0,
+            NoiseProfile::StrongNoise => 22.0,
+        }
+    }
+
+    /// Consecutive loud windows required before warning.
+    pub(super) fn spike_windows(&self) -> usize {
+        match self {
+            NoiseProfile::Standard => 3,
+            NoiseProfile::StrongNoise => 6,
+        }
+    }
+
+    /// Windows after a warning during which no new warning is raised.
+    pub(super) fn cooldown_windows(&self) -> usize {
+        match self {
+            NoiseProfile::Standard => 20,
+            NoiseProfile::StrongNoise => 40,
+        }
+    }
+
+    /// Cutoff of the high-pass applied before any level is measured.
+    pub(super) fn high_pass_hz(&self) -> Option<f32> {
+        match self {
+            NoiseProfile::Standard => None,
+            NoiseProfile::StrongNoise => Some(300.0),
+        }
+    }
+}
+
+/// First-order high-pass that strips rumble and hum before measurement.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct HighPass {
+    alpha: f32,
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl HighPass {
+    pub(super) fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_hz);
+        let dt = 1.0 / sample_rate.max(1) as f32;
+        Self {
+            alpha: rc / (rc + dt),
+            previous_input: 0.0,
+            previous_output: 0.0,
+        }
+    }
+
+    pub(super) fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        samples
+            .iter()
+            .map(|&sample| {
+                self.previous_output =
+                    self.alpha * (self.previous_output + sample - self.previous_input);
+                self.previous_input = sample;
+                self.previous_output
+            })
+            .collect()
+    }
+}
+
+impl NoiseDetector {
+    pub fn profile(&self) -> NoiseProfile {
+        self.profile
+    }
+
+    /// Switch the detector tuning. Levels are measured differently under each
+    /// profile, so a baseline already locked is sampled again.
+    pub fn set_profile(&mut self, profile: NoiseProfile) {
+        if profile == self.profile {
+            return;
+        }
+        self.profile = profile;
+        self.high_pass = profile
+            .high_pass_hz()
+            .map(|cutoff| HighPass::new(cutoff, self.sample_rate));
+        self.cooldown_windows = 0;
+        if self.baseline_state != BaselineState::Idle {
+            self.resync_baseline();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::noise::NoiseEvent;
+    use crate::audio::AudioCaptureStage;
+
+    fn warnings_for(profile: NoiseProfile, signal: impl Fn(usize) -> f32, len: usize) -> usize {
+        let mut detector = NoiseDetector::new(16_000);
+        detector.set_profile(profile);
+        detector.enter_preroll(Some(-30.0));
+        detector.enter_recording();
+        let samples: Vec<f32> = (0..len).map(signal).collect();
+        detector
+            .ingest(&samples, AudioCaptureStage::Recording)
+            .into_iter()
+            .filter(|event| matches!(event, NoiseEvent::NoiseWarning(_)))
+            .count()
+    }
+
+    #[test]
+    fn strong_noise_profile_filters_hum_and_needs_longer_spikes() {
+        let hum = |n: usize| 0.5 * (2.0 * std::f32::consts::PI * 60.0 * n as f32 / 16_000.0).sin();
+        assert_eq!(warnings_for(NoiseProfile::Standard, hum, 8_000), 1);
+        assert_eq!(warnings_for(NoiseProfile::StrongNoise, hum, 8_000), 0);
+
+        let chatter = |n: usize| {
+            ((n.wrapping_mul(1_103_515_245).wrapping_add(12_345) >> 8) % 2_000) as f32 / 1_000.0
+                - 1.0
+        };
+        // Four loud windows: enough for the standard profile, too short for strong noise.
+        assert_eq!(warnings_for(NoiseProfile::Standard, chatter, 6_400), 1);
+        assert_eq!(warnings_for(NoiseProfile::StrongNoise, chatter, 6_400), 0);
+        assert_eq!(warnings_for(NoiseProfile::StrongNoise, chatter, 12_800), 1);
+    }
+}
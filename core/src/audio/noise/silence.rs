@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+use super::{NoiseDetector, NoiseEvent};
+
+/// Enumerates the state transitions of a silence countdown timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SilenceCountdownStatus {
+    Started,
+    Tick,
+    Canceled,
+    Completed,
+}
+
+/// Structured payload describing the progress of a silence countdown timer.
+#[derive(Debug, Clone)]
+pub struct SilenceCountdownPayload {
+    pub total_ms: u32,
+    pub remaining_ms: u32,
+    pub status: SilenceCountdownStatus,
+}
+
+/// Auto-stop behaviour on sustained silence. Long-form dictation such as
+/// podcast scripts needs longer pauses, or no auto-stop at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SilencePolicy {
+    /// Silence required before the session stops on its own.
+    pub countdown_ms: u32,
+    pub enabled: bool,
+    /// Recording time during which silence never starts a countdown.
+    pub min_session_ms: u32,
+}
+
+impl SilencePolicy {
+    pub const MIN_COUNTDOWN_MS: u32 = 1_000;
+    pub const MAX_COUNTDOWN_MS: u32 = 120_000;
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(Self::MIN_COUNTDOWN_MS..=Self::MAX_COUNTDOWN_MS).contains(&self.countdown_ms) {
+            anyhow::bail!(
+                "silence countdown must be between {} and {} ms, got {}",
+                Self::MIN_COUNTDOWN_MS,
+                Self::MAX_COUNTDOWN_MS,
+                self.countdown_ms
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for SilencePolicy {
+    fn default() -> Self {
+        Self {
+            countdown_ms: 5_000,
+            enabled: true,
+            min_session_ms: 0,
+        }
+    }
+}
+
+/// Analysis windows (100ms each) a countdown of `countdown_ms` spans.
+pub(super) fn countdown_windows(countdown_ms: u32) -> usize {
+    (countdown_ms as usize / 100).max(1)
+}
+
+impl NoiseDetector {
+    pub fn silence_policy(&self) -> SilencePolicy {
+        self.silence_policy
+    }
+
+    /// Apply a new auto-stop policy. A countdown in progress restarts under the
+    /// new duration, and disabling auto-stop cancels it.
+    pub fn set_silence_policy(&mut self, policy: SilencePolicy) -> Vec<NoiseEvent> {
+        let mut events = Vec::new();
+        if self.silence_active {
+            events.push(NoiseEvent::SilenceCountdown(SilenceCountdownPayload {
+                total_ms: self.silence_policy.countdown_ms,
+                remaining_ms: self.silence_policy.countdown_ms,
+                status: SilenceCountdownStatus::Canceled,
+            }));
+        }
+        self.silence_policy = policy;
+        self.silence_countdown_windows = countdown_windows(policy.countdown_ms);
+        self.silence_windows = 0;
+        self.silence_active = false;
+        self.silence_completed = false;
+        events
+    }
+
+    pub(super) fn evaluate_silence(
+        &mut self,
+        window_db: f32,
+        baseline_db: f32,
+        events: &mut Vec<NoiseEvent>,
+    ) {
+        let threshold = baseline_db - self.silence_threshold_offset_db;
+        self.recording_windows += 1;
+        let warmed_up =
+            self.recording_windows as u64 * 100 > u64::from(self.silence_policy.min_session_ms);
+
+        if window_db <= threshold && self.silence_policy.enabled && warmed_up {
+            if self.silence_completed {
+                return;
+            }
+
+            self.silence_windows += 1;
+            let countdown_windows = self.silence_countdown_windows.max(1);
+            let elapsed_windows = self.silence_windows.min(countdown_windows);
+            let elapsed_ms = (elapsed_windows as u32) * 100;
+            let total_ms = self.silence_policy.countdown_ms;
+            let remaining_ms = total_ms.saturating_sub(elapsed_ms).min(total_ms);
+
+            let status = if self.silence_windows == 1 {
+                SilenceCountdownStatus::Started
+            } else if remaining_ms == 0 {
+                SilenceCountdownStatus::Completed
+            } else {
+                SilenceCountdownStatus::Tick
+            };
+
+            self.silence_active = true;
+
+            if status == SilenceCountdownStatus::Completed {
+                self.silence_completed = true;
+                self.silence_active = false;
+                self.silence_windows = countdown_windows;
+            }
+
+            events.push(NoiseEvent::SilenceCountdown(SilenceCountdownPayload {
+                total_ms,
+                remaining_ms,
+                status,
+            }));
+        } else if self.silence_windows > 0 || self.silence_active {
+            if !self.silence_completed {
+                events.push(NoiseEvent::SilenceCountdown(SilenceCountdownPayload {
+                    total_ms: self.silence_policy.countdown_ms,
+                    remaining_ms: self.silence_policy.countdown_ms,
+                    status: SilenceCountdownStatus::Canceled,
+                }));
+            }
+
+            self.silence_windows = 0;
+            self.silence_active = false;
+            self.silence_completed = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioCaptureStage;
+
+    #[test]
+    fn silence_countdown_completes_after_5_seconds() {
+        let mut detector = NoiseDetector::new(16_000);
+        detector.enter_preroll(None);
+
+        let baseline_samples = vec![0.05_f32; 8_000];
+        let events = detector.ingest(&baseline_samples, AudioCaptureStage::PreRoll);
+        assert_eq!(events.len(), 1);
+
+        detector.enter_recording();
+
+        let quiet_window = vec![0.005_f32; 1_600];
+        for step in 1..=50 {
+            let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
+            assert_eq!(events.len(), 1, "expected countdown event on step {step}");
+
+            match &events[0] {
+                NoiseEvent::SilenceCountdown(payload) => {
+                    assert_eq!(payload.total_ms, 5_000);
+                    match payload.status {
+                        SilenceCountdownStatus::Started => {
+                            assert_eq!(step, 1);
+                            assert_eq!(payload.remaining_ms, 4_900);
+                        }
+                        SilenceCountdownStatus::Tick => {
+                            assert!(step > 1 && step < 50);
+                            assert!(payload.remaining_ms < 5_000);
+                            assert!(payload.remaining_ms > 0);
+                        }
+                        SilenceCountdownStatus::Completed => {
+                            assert_eq!(step, 50);
+                            assert_eq!(payload.remaining_ms, 0);
+                        }
+                        SilenceCountdownStatus::Canceled => {
+                            panic!("did not expect cancellation during continuous silence");
+                        }
+                    }
+                }
+                _ => panic!("unexpected event emitted during silence countdown"),
+            }
+        }
+
+        let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
+        assert!(
+            events.is_empty(),
+            "countdown completion should suppress further events"
+        );
+    }
+
+    #[test]
+    fn silence_countdown_cancels_on_speech_return() {
+        let mut detector = NoiseDetector::new(16_000);
+        detector.enter_preroll(None);
+
+        let baseline_samples = vec![0.05_f32; 8_000];
+        let events = detector.ingest(&baseline_samples, AudioCaptureStage::PreRoll);
+        assert_eq!(events.len(), 1);
+
+        detector.enter_recording();
+
+        let quiet_window = vec![0.005_f32; 1_600];
+        for _ in 0..5 {
+            let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
+            assert!(!events.is_empty());
+        }
+
+        let loud_window = vec![0.05_f32; 1_600];
+        let events = detector.ingest(&loud_window, AudioCaptureStage::Recording);
+        assert_eq!(events.len(), 1, "speech return should emit cancellation");
+
+        match &events[0] {
+            NoiseEvent::SilenceCountdown(payload) => {
+                assert_eq!(payload.status, SilenceCountdownStatus::Canceled);
+                assert_eq!(payload.remaining_ms, 5_000);
+            }
+            _ => panic!("expected silence countdown cancellation event"),
+        }
+
+        let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
+        assert_eq!(events.len(), 1, "new silence should restart countdown");
+        match &events[0] {
+            NoiseEvent::SilenceCountdown(payload) => {
+                assert_eq!(payload.status, SilenceCountdownStatus::Started);
+            }
+            _ => panic!("expected countdown restart"),
+        }
+    }
+
+    #[test]
+    fn silence_policy_sets_countdown_grace_period_and_disable_switch() {
+        let mut detector = NoiseDetector::new(16_000);
+        detector.enter_preroll(Some(-26.0));
+        detector.set_silence_policy(SilencePolicy {
+            countdown_ms: 12_000,
+            enabled: true,
+            min_session_ms: 1_000,
+        });
+        detector.enter_recording();
+
+        let quiet_window = vec![0.005_f32; 1_600];
+        for _ in 0..10 {
+            assert!(detector
+                .ingest(&quiet_window, AudioCaptureStage::Recording)
+                .is_empty());
+        }
+        let events = detector.ingest(&quiet_window, AudioCaptureStage::Recording);
+        match &events[..] {
+            [NoiseEvent::SilenceCountdown(payload)] => {
+                assert_eq!(payload.status, SilenceCountdownStatus::Started);
+                assert_eq!(payload.total_ms, 12_000);
+                assert_eq!(payload.remaining_ms, 11_900);
+            }
+            other => panic!("expected countdown start, got {other:?}"),
+        }
+
+        let events = detector.set_silence_policy(SilencePolicy {
+            enabled: false,
+            ..detector.silence_policy()
+        });
+        assert!(matches!(
+            &events[..],
+            [NoiseEvent::SilenceCountdown(payload)]
+                if payload.status == SilenceCountdownStatus::Canceled
+        ));
+        for _ in 0..200 {
+            assert!(detector
+                .ingest(&quiet_window, AudioCaptureStage::Recording)
+                .is_empty());
+        }
+
+        assert!(SilencePolicy {
+            countdown_ms: 500,
+            ..SilencePolicy::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
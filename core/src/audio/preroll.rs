@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Ring of the most recent frames emitted while idle. When recording begins the
+/// ring is frozen so the session can prepend speech that started just before
+/// the hotkey press.
+#[derive(Debug, Default)]
+pub(crate) struct PrerollBuffer {
+    capacity: usize,
+    samples: usize,
+    frames: VecDeque<Arc<[f32]>>,
+    frozen: Vec<Arc<[f32]>>,
+}
+
+impl PrerollBuffer {
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    pub(crate) fn push(&mut self, frame: Arc<[f32]>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.samples += frame.len();
+        self.frames.push_back(frame);
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.samples > self.capacity {
+            match self.frames.pop_front() {
+                Some(frame) => self.samples -= frame.len(),
+                None => break,
+            }
+        }
+    }
+
+    /// Move the retained frames aside for the session that is about to start.
+    pub(crate) fn freeze(&mut self) {
+        self.frozen = self.frames.drain(..).collect();
+        self.samples = 0;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.frames.clear();
+        self.frozen.clear();
+        self.samples = 0;
+    }
+
+    /// Frozen frames followed by `current`. If `current` was already captured by
+    /// the ring it is not repeated, and frames after it are left to the live feed.
+    pub(crate) fn take_through(&mut self, current: Arc<[f32]>) -> Vec<Arc<[f32]>> {
+        let mut frames = std::mem::take(&mut self.frozen);
+        match frames.iter().position(|frame| Arc::ptr_eq(frame, &current)) {
+            Some(index) => frames.truncate(index + 1),
+            None => frames.push(current),
+        }
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(value: f32) -> Arc<[f32]> {
+        Arc::from(vec![value; 100])
+    }
+
+    #[test]
+    fn keeps_most_recent_window_and_skips_duplicates() {
+        let mut buffer = PrerollBuffer::default();
+        buffer.set_capacity(250);
+        let frames: Vec<_> = (0..4).map(|i| frame(i as f32)).collect();
+        for frame in &frames {
+            buffer.push(Arc::clone(frame));
+        }
+        buffer.freeze();
+
+        // The live feed is still behind: frame 2 arrives first.
+        let taken = buffer.take_through(Arc::clone(&frames[2]));
+        assert_eq!(taken.len(), 1);
+        assert!(Arc::ptr_eq(&taken[0], &frames[2]));
+
+        buffer.push(frame(9.0));
+        buffer.freeze();
+        let newer = frame(10.0);
+        let taken = buffer.take_through(Arc::clone(&newer));
+        assert_eq!(taken.len(), 2);
+        assert!(Arc::ptr_eq(&taken[1], &newer));
+        assert_eq!(buffer.take_through(frame(11.0)).len(), 1);
+    }
+}
@@ -0,0 +1,510 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tracing::{info, warn};
+
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use super::envelope::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+use super::{AudioPipeline, SAMPLE_RATE_HZ};
+use crate::secrets::SecretStore;
+
+/// Directory under the data directory that holds session archives.
+pub const RECORDINGS_DIR: &str = "recordings";
+/// Secret store entry holding the recordings master key. Every process that
+/// reads the data directory (core and the desktop shell) derives the archive
+/// keys from it.
+pub const RECORDING_KEY_SECRET: &str = "recordings.key";
+
+const ARCHIVE_VERSION: u8 = 1;
+const ARCHIVE_EXTENSION: &str = "fwa";
+/// Each sealed chunk covers this much audio, bounding memory and crash loss.
+const CHUNK_MS: u64 = 5_000;
+const SUBSCRIBER_CAPACITY: usize = 256;
+/// Grace period for frames still queued in the subscriber when recording stops.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Plain-text first line of an archive; every following line is a
+/// [`SealedEnvelope`] holding 16-bit little-endian PCM, except the last, which
+/// seals the chunk count so a truncated archive is detected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveHeader {
+    version: u8,
+    session_id: String,
+    sample_rate_hz: u32,
+    channels: u16,
+}
+
+/// Outcome of a finished recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingSummary {
+    pub session_id: String,
+    pub path: PathBuf,
+    pub samples: u64,
+    pub chunks: u32,
+}
+
+/// Decrypted session audio loaded back from an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedAudio {
+    pub session_id: String,
+    pub sample_rate_hz: u32,
+    pub samples: Vec<i16>,
+}
+
+impl RecordedAudio {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / self.sample_rate_hz.max(1) as f64)
+    }
+
+    /// Encode as a mono 16-bit PCM WAV file.
+    pub fn to_wav_bytes(&self) -> Vec<u8> {
+        let data_len = (self.samples.len() * 2) as u32;
+        let mut bytes = Vec::with_capacity(44 + data_len as usize);
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate_hz.to_le_bytes());
+        bytes.extend_from_slice(&(self.sample_rate_hz * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+struct ActiveRecording {
+    session_id: String,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<Result<RecordingSummary>>,
+}
+
+/// Tees pipeline PCM into an encrypted per-session archive so the raw audio of
+/// a history entry can be replayed later.
+#[derive(Clone)]
+pub struct SessionRecorder {
+    dir: PathBuf,
+    keys: Arc<AudioCacheKeys>,
+    active: Arc<Mutex<Option<ActiveRecording>>>,
+}
+
+impl SessionRecorder {
+    pub fn new(dir: impl Into<PathBuf>, keys: AudioCacheKeys) -> Self {
+        Self {
+            dir: dir.into(),
+            keys: Arc::new(keys),
+            active: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Recorder for `data_dir`'s archives, keyed from `store`. The master key
+    /// is generated and stored on first use.
+    pub fn open(data_dir: &Path, store: &dyn SecretStore) -> Result<Self> {
+        let master = match store.get(RECORDING_KEY_SECRET)? {
+            Some(master) => master,
+            None => {
+                let mut bytes = [0u8; 32];
+                SystemRandom::new()
+                    .fill(&mut bytes)
+                    .map_err(|_| anyhow!("failed to generate recording key"))?;
+                let master: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+                store.set(RECORDING_KEY_SECRET, &master)?;
+                master
+            }
+        };
+        let keys = AudioCacheKeys::derive(master.as_bytes())?;
+        Ok(Self::new(data_dir.join(RECORDINGS_DIR), keys))
+    }
+
+    pub fn archive_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Archives are named after the SHA-256 of the session id, so distinct ids
+    /// never share a file whatever characters they contain.
+    pub fn archive_path(&self, session_id: &str) -> PathBuf {
+        let digest = digest::digest(&digest::SHA256, session_id.as_bytes());
+        self.dir
+            .join(format!("{}.{ARCHIVE_EXTENSION}", hex(digest.as_ref())))
+    }
+
+    pub async fn active_session(&self) -> Option<String> {
+        self.active
+            .lock()
+            .await
+            .as_ref()
+            .map(|active| active.session_id.clone())
+    }
+
+    /// Start recording `session_id`, finishing any recording still in progress.
+    pub async fn start(&self, pipeline: &AudioPipeline, session_id: &str) -> Result<()> {
+        if let Some(previous) = self.finish().await? {
+            warn!(
+                target: "audio_recorder",
+                session_id = %previous.session_id,
+                "previous recording was still active; finalised before starting a new one"
+            );
+        }
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create recording directory {:?}", self.dir))?;
+        let path = self.archive_path(session_id);
+        let writer = ArchiveWriter::create(&path, session_id, Arc::clone(&self.keys))?;
+        let frames = pipeline.subscribe_raw_pcm_frames(SUBSCRIBER_CAPACITY);
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(record(writer, frames, stop_rx));
+
+        *self.active.lock().await = Some(ActiveRecording {
+            session_id: session_id.to_string(),
+            stop: stop_tx,
+            task,
+        });
+        info!(target: "audio_recorder", session_id, path = %path.display(), "session recording started");
+        Ok(())
+    }
+
+    /// Stop the active recording and seal the remaining audio.
+    pub async fn finish(&self) -> Result<Option<RecordingSummary>> {
+        let Some(active) = self.active.lock().await.take() else {
+            return Ok(None);
+        };
+        let _ = active.stop.send(());
+        let summary = active
+            .task
+            .await
+            .map_err(|err| anyhow!("recording task failed: {err}"))??;
+        info!(
+            target: "audio_recorder",
+            session_id = %summary.session_id,
+            samples = summary.samples,
+            chunks = summary.chunks,
+            "session recording finished"
+        );
+        Ok(Some(summary))
+    }
+
+    /// Decrypt the archive recorded for `session_id`.
+    pub fn load(&self, session_id: &str) -> Result<RecordedAudio> {
+        read_archive(&self.archive_path(session_id), &self.keys)
+    }
+
+    pub fn remove(&self, session_id: &str) -> Result<bool> {
+        match fs::remove_file(self.archive_path(session_id)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(anyhow!("failed to remove session recording: {err}")),
+        }
+    }
+}
+
+async fn record(
+    mut writer: ArchiveWriter,
+    mut frames: mpsc::Receiver<Arc<[f32]>>,
+    mut stop: oneshot::Receiver<()>,
+) -> Result<RecordingSummary> {
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            frame = frames.recv() => match frame {
+                Some(frame) => writer.push(&frame)?,
+                None => break,
+            },
+        }
+    }
+
+    while let Ok(Some(frame)) = timeout(DRAIN_TIMEOUT, frames.recv()).await {
+        writer.push(&frame)?;
+    }
+    writer.finish()
+}
+
+struct ArchiveWriter {
+    session_id: String,
+    path: PathBuf,
+    keys: Arc<AudioCacheKeys>,
+    file: BufWriter<File>,
+    buffer: Vec<i16>,
+    chunk_samples: usize,
+    samples: u64,
+    chunks: u32,
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path, session_id: &str, keys: Arc<AudioCacheKeys>) -> Result<Self> {
+        let mut options = OpenOptions::new();
+        options.create(true).write(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options
+            .open(path)
+            .with_context(|| format!("failed to open session recording {path:?}"))?;
+        let mut file = BufWriter::new(file);
+        let header = ArchiveHeader {
+            version: ARCHIVE_VERSION,
+            session_id: session_id.to_string(),
+            sample_rate_hz: SAMPLE_RATE_HZ,
+            channels: 1,
+        };
+        serde_json::to_writer(&mut file, &header)?;
+        file.write_all(b"\n")?;
+
+        let chunk_samples = (SAMPLE_RATE_HZ as u64 * CHUNK_MS / 1_000) as usize;
+        Ok(Self {
+            session_id: session_id.to_string(),
+            path: path.to_path_buf(),
+            keys,
+            file,
+            buffer: Vec::with_capacity(chunk_samples),
+            chunk_samples,
+            samples: 0,
+            chunks: 0,
+        })
+    }
+
+    fn push(&mut self, frame: &[f32]) -> Result<()> {
+        self.buffer.extend(
+            frame
+                .iter()
+                .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+        );
+        self.samples += frame.len() as u64;
+        while self.buffer.len() >= self.chunk_samples {
+            let chunk: Vec<i16> = self.buffer.drain(..self.chunk_samples).collect();
+            self.write_chunk(&chunk)?;
+        }
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, chunk: &[i16]) -> Result<()> {
+        let pcm: Vec<u8> = chunk
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let aad = chunk_aad(&self.session_id, self.chunks);
+        let envelope = seal_payload(&self.keys, &aad, &pcm)?;
+        serde_json::to_writer(&mut self.file, &envelope)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()?;
+        self.chunks += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<RecordingSummary> {
+        if !self.buffer.is_empty() {
+            let chunk = std::mem::take(&mut self.buffer);
+            self.write_chunk(&chunk)?;
+        }
+        let end = seal_payload(
+            &self.keys,
+            &end_aad(&self.session_id),
+            &self.chunks.to_le_bytes(),
+        )?;
+        serde_json::to_writer(&mut self.file, &end)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()?;
+        Ok(RecordingSummary {
+            session_id: self.session_id,
+            path: self.path,
+            samples: self.samples,
+            chunks: self.chunks,
+        })
+    }
+}
+
+/// Decrypt an archive written by [`SessionRecorder`].
+pub fn read_archive(path: &Path, keys: &AudioCacheKeys) -> Result<RecordedAudio> {
+    let file =
+        File::open(path).with_context(|| format!("failed to open session recording {path:?}"))?;
+    let mut lines = BufReader::new(file).lines();
+    let header_line = lines
+        .next()
+        .ok_or_else(|| anyhow!("session recording is empty"))??;
+    let header: ArchiveHeader =
+        serde_json::from_str(&header_line).context("failed to decode recording header")?;
+    if header.version != ARCHIVE_VERSION {
+        bail!("unsupported recording version: {}", header.version);
+    }
+
+    let mut envelopes = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let envelope: SealedEnvelope = serde_json::from_str(&line)
+            .with_context(|| format!("failed to decode recording chunk {index}"))?;
+        envelopes.push(envelope);
+    }
+    let (end, chunks) = envelopes
+        .split_last()
+        .ok_or_else(|| anyhow!("session recording is truncated: end record missing"))?;
+    let end = open_payload(keys, &end_aad(&header.session_id), end)
+        .context("session recording is truncated: end record missing")?;
+    let expected = <[u8; 4]>::try_from(end.as_slice())
+        .map(u32::from_le_bytes)
+        .map_err(|_| anyhow!("invalid recording end record"))?;
+    if expected as usize != chunks.len() {
+        bail!(
+            "session recording is truncated: expected {expected} chunks, found {}",
+            chunks.len()
+        );
+    }
+
+    let mut samples = Vec::new();
+    for (index, envelope) in chunks.iter().enumerate() {
+        let aad = chunk_aad(&header.session_id, index as u32);
+        let pcm = open_payload(keys, &aad, envelope)
+            .with_context(|| format!("failed to open recording chunk {index}"))?;
+        samples.extend(
+            pcm.chunks_exact(2)
+                .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]])),
+        );
+    }
+
+    Ok(RecordedAudio {
+        session_id: header.session_id,
+        sample_rate_hz: header.sample_rate_hz,
+        samples,
+    })
+}
+
+/// Binds every chunk to its session and position so chunks cannot be
+/// reordered or spliced between archives.
+fn chunk_aad(session_id: &str, index: u32) -> Vec<u8> {
+    format!("session-recording:{session_id}:{index}").into_bytes()
+}
+
+/// Seals the chunk count after the last chunk; an archive cut short loses it.
+fn end_aad(session_id: &str) -> Vec<u8> {
+    format!("session-recording:{session_id}:end").into_bytes()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn keys() -> AudioCacheKeys {
+        AudioCacheKeys::derive(&[7u8; 32]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn records_pipeline_audio_into_encrypted_archive() {
+        let dir = tempdir().unwrap();
+        let recorder = SessionRecorder::new(dir.path(), keys());
+        let pipeline = AudioPipeline::new();
+
+        recorder.start(&pipeline, "session/01").await.unwrap();
+        assert_eq!(
+            recorder.active_session().await.as_deref(),
+            Some("session/01")
+        );
+        // 6 seconds forces at least one full chunk plus a partial tail.
+        for _ in 0..60 {
+            pipeline.push_pcm_frame(vec![0.5_f32; 1_600]).await.unwrap();
+        }
+        let summary = recorder.finish().await.unwrap().expect("active recording");
+
+        assert_eq!(summary.samples, 96_000);
+        assert_eq!(summary.chunks, 2);
+        assert_eq!(summary.path, recorder.archive_path("session/01"));
+        assert_ne!(
+            recorder.archive_path("session/01"),
+            recorder.archive_path("session_01")
+        );
+        assert!(recorder.active_session().await.is_none());
+
+        let audio = recorder.load("session/01").unwrap();
+        assert_eq!(audio.samples.len(), 96_000);
+        assert_eq!(audio.duration(), Duration::from_secs(6));
+        assert!(audio.samples.iter().all(|sample| *sample == i16::MAX / 2));
+        let wav = audio.to_wav_bytes();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), 44 + 96_000 * 2);
+
+        assert!(recorder.remove("session/01").unwrap());
+        assert!(!recorder.remove("session/01").unwrap());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_key_and_reordered_chunks() {
+        let dir = tempdir().unwrap();
+        let recorder = SessionRecorder::new(dir.path(), keys());
+        let pipeline = AudioPipeline::new();
+
+        recorder.start(&pipeline, "reorder").await.unwrap();
+        for marker in 0..100 {
+            pipeline
+                .push_pcm_frame(vec![marker as f32 / 200.0; 1_600])
+                .await
+                .unwrap();
+        }
+        let summary = recorder.finish().await.unwrap().unwrap();
+        assert_eq!(summary.chunks, 2);
+
+        let other = AudioCacheKeys::derive(&[9u8; 32]).unwrap();
+        assert!(read_archive(&summary.path, &other).is_err());
+
+        let raw = fs::read_to_string(&summary.path).unwrap();
+        let mut lines: Vec<&str> = raw.lines().collect();
+        lines.swap(1, 2);
+        fs::write(&summary.path, lines.join("\n")).unwrap();
+        assert!(recorder.load("reorder").is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_archives_missing_the_end_record() {
+        let dir = tempdir().unwrap();
+        let recorder = SessionRecorder::new(dir.path(), keys());
+        let pipeline = AudioPipeline::new();
+
+        recorder.start(&pipeline, "truncated").await.unwrap();
+        for _ in 0..60 {
+            pipeline
+                .push_pcm_frame(vec![0.25_f32; 1_600])
+                .await
+                .unwrap();
+        }
+        let summary = recorder.finish().await.unwrap().unwrap();
+        assert_eq!(summary.chunks, 2);
+        let raw = fs::read_to_string(&summary.path).unwrap();
+        let lines: Vec<&str> = raw.lines().collect();
+        assert_eq!(lines.len(), 4);
+
+        // A dropped chunk no longer matches the sealed count.
+        fs::write(&summary.path, [lines[0], lines[1], lines[3]].join("\n")).unwrap();
+        let err = recorder.load("truncated").unwrap_err();
+        assert!(format!("{err:#}").contains("truncated"), "{err:#}");
+
+        // Cut off before the end record was written.
+        fs::write(&summary.path, lines[..3].join("\n")).unwrap();
+        let err = recorder.load("truncated").unwrap_err();
+        assert!(format!("{err:#}").contains("truncated"), "{err:#}");
+
+        fs::write(&summary.path, raw).unwrap();
+        assert_eq!(recorder.load("truncated").unwrap().samples.len(), 96_000);
+    }
+}
@@ -0,0 +1,253 @@
+use std::f64::consts::PI;
+
+/// Number of input samples on each side of the interpolation point.
+const HALF_TAPS: usize = 32;
+/// Slightly below Nyquist so the windowed-sinc transition band stays clear of
+/// aliasing when downsampling.
+const CUTOFF_RATIO: f64 = 0.9;
+
+/// Streaming polyphase resampler that converts arbitrary device rates into the
+/// engine rate.
+///
+/// Output positions are tracked as an exact rational offset into the input
+/// stream, so chunk boundaries and long sessions never accumulate drift: after
+/// `n` input samples the resampler has produced
+/// `floor(n * output_rate / input_rate)` samples minus the filter look-ahead.
+#[derive(Clone, Debug)]
+pub struct StreamingResampler {
+    input_rate_hz: u32,
+    output_rate_hz: u32,
+    /// Reduced input step per output sample (numerator).
+    step: u64,
+    /// Reduced output rate (denominator); also the number of filter phases.
+    phases: u64,
+    /// Filter taps for every phase, `2 * HALF_TAPS` per phase.
+    kernels: Vec<f32>,
+    /// Input samples retained for the filter window.
+    history: Vec<f32>,
+    /// Absolute input index of `history[0]`.
+    history_start: u64,
+    /// Absolute input index of the next output's left neighbour.
+    center: u64,
+    /// Fractional position of the next output, in units of `1 / phases`.
+    phase: u64,
+}
+
+impl StreamingResampler {
+    pub fn new(input_rate_hz: u32, output_rate_hz: u32) -> Self {
+        let input_rate_hz = input_rate_hz.max(1);
+        let output_rate_hz = output_rate_hz.max(1);
+        let divisor = gcd(input_rate_hz as u64, output_rate_hz as u64);
+        let step = input_rate_hz as u64 / divisor;
+        let phases = output_rate_hz as u64 / divisor;
+        let cutoff = CUTOFF_RATIO * (output_rate_hz as f64 / input_rate_hz as f64).min(1.0);
+
+        Self {
+            input_rate_hz,
+            output_rate_hz,
+            step,
+            phases,
+            kernels: build_kernels(phases, cutoff),
+            // Pre-pad with silence so the first output is centred on sample 0.
+            history: vec![0.0; HALF_TAPS - 1],
+            history_start: 0,
+            center: (HALF_TAPS - 1) as u64,
+            phase: 0,
+        }
+    }
+
+    pub fn input_rate_hz(&self) -> u32 {
+        self.input_rate_hz
+    }
+
+    pub fn output_rate_hz(&self) -> u32 {
+        self.output_rate_hz
+    }
+
+    pub fn is_passthrough(&self) -> bool {
+        self.input_rate_hz == self.output_rate_hz
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.input_rate_hz, self.output_rate_hz);
+    }
+
+    /// Feed a chunk of input samples and return every output sample whose
+    /// filter window is now complete.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+
+        self.history.extend_from_slice(input);
+        let available = self.history_start + self.history.len() as u64;
+        let taps = 2 * HALF_TAPS;
+        let mut output =
+            Vec::with_capacity((input.len() as u64 * self.phases / self.step) as usize + 1);
+
+        while self.center + (HALF_TAPS as u64) < available {
+            let first = (self.center + 1 - HALF_TAPS as u64 - self.history_start) as usize;
+            let window = &self.history[first..first + taps];
+            let offset = self.phase as usize * taps;
+            let kernel = &self.kernels[offset..offset + taps];
+            output.push(window.iter().zip(kernel).map(|(x, h)| x * h).sum());
+
+            self.phase += self.step;
+            self.center += self.phase / self.phases;
+            self.phase %= self.phases;
+        }
+
+        let keep_from = self.center + 1 - HALF_TAPS as u64;
+        if keep_from > self.history_start {
+            let drop = ((keep_from - self.history_start) as usize).min(self.history.len());
+            self.history.drain(..drop);
+            self.history_start += drop as u64;
+        }
+
+        output
+    }
+
+    /// Drain the filter look-ahead by padding with silence, returning the
+    /// remaining samples that correspond to input already pushed.
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.is_passthrough() {
+            return Vec::new();
+        }
+
+        let pushed = self.history_start + self.history.len() as u64 - (HALF_TAPS - 1) as u64;
+        let expected_total = (pushed * self.phases).div_ceil(self.step);
+        let produced = self.produced();
+        let mut output = self.process(&[0.0; HALF_TAPS]);
+        output.truncate(expected_total.saturating_sub(produced) as usize);
+        self.reset();
+        output
+    }
+
+    fn produced(&self) -> u64 {
+        let position = (self.center - (HALF_TAPS - 1) as u64) * self.phases + self.phase;
+        position / self.step
+    }
+}
+
+fn build_kernels(phases: u64, cutoff: f64) -> Vec<f32> {
+    let taps = 2 * HALF_TAPS;
+    let mut kernels = Vec::with_capacity(phases as usize * taps);
+    for phase in 0..phases {
+        let frac = phase as f64 / phases as f64;
+        let start = kernels.len();
+        for tap in 0..taps {
+            // Tap `tap` reads input `center + tap + 1 - HALF_TAPS`.
+            let distance = tap as f64 + 1.0 - HALF_TAPS as f64 - frac;
+            kernels.push((cutoff * sinc(cutoff * distance) * blackman(distance)) as f32);
+        }
+        let sum: f32 = kernels[start..].iter().sum();
+        if sum.abs() > f32::EPSILON {
+            kernels[start..].iter_mut().for_each(|tap| *tap /= sum);
+        }
+    }
+    kernels
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+fn blackman(distance: f64) -> f64 {
+    let width = HALF_TAPS as f64;
+    if distance.abs() >= width {
+        return 0.0;
+    }
+    let n = (distance + width) / (2.0 * width);
+    0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos()
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(rate: u32, freq: f64, start: u64, len: usize) -> Vec<f32> {
+        (0..len as u64)
+            .map(|n| (2.0 * PI * freq * (start + n) as f64 / rate as f64).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn passthrough_when_rates_match() {
+        let mut resampler = StreamingResampler::new(16_000, 16_000);
+        assert_eq!(resampler.process(&[0.1, 0.2, 0.3]), vec![0.1, 0.2, 0.3]);
+        assert!(resampler.flush().is_empty());
+    }
+
+    #[test]
+    fn sample_count_matches_rate_ratio_after_flush() {
+        for input_rate in [8_000, 22_050, 44_100, 48_000] {
+            let mut resampler = StreamingResampler::new(input_rate, 16_000);
+            let input = vec![0.25_f32; input_rate as usize];
+            let mut output = Vec::new();
+            for chunk in input.chunks(997) {
+                output.extend(resampler.process(chunk));
+            }
+            output.extend(resampler.flush());
+            assert_eq!(output.len(), 16_000, "rate {input_rate}");
+            assert!(output[64..output.len() - 64]
+                .iter()
+                .all(|sample| (sample - 0.25).abs() < 1e-3));
+        }
+    }
+
+    #[test]
+    fn long_session_does_not_drift() {
+        let input_rate = 44_100;
+        let freq = 440.0;
+        let mut resampler = StreamingResampler::new(input_rate, 16_000);
+        let mut pushed = 0_u64;
+        let mut produced = 0_u64;
+        let mut tail = Vec::new();
+        // Irregular chunk sizes mimic device callbacks over two minutes.
+        let sizes = [441_usize, 1_024, 512, 4_410, 37];
+        let total = input_rate as u64 * 120;
+        let mut index = 0;
+        while pushed < total {
+            let len = sizes[index % sizes.len()].min((total - pushed) as usize);
+            index += 1;
+            let output = resampler.process(&sine(input_rate, freq, pushed, len));
+            pushed += len as u64;
+            produced += output.len() as u64;
+            tail = output;
+        }
+
+        let expected = pushed * 16_000 / input_rate as u64;
+        assert!(expected - produced <= HALF_TAPS as u64);
+
+        let start = produced - tail.len() as u64;
+        let reference = sine(16_000, freq, start, tail.len());
+        for (sample, expected) in tail.iter().zip(reference) {
+            assert!(
+                (sample - expected).abs() < 0.01,
+                "drifted: {sample} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn downsampling_attenuates_content_above_nyquist() {
+        let mut resampler = StreamingResampler::new(48_000, 16_000);
+        let output = resampler.process(&sine(48_000, 12_000.0, 0, 48_000));
+        let steady = &output[64..];
+        let rms = (steady.iter().map(|s| s * s).sum::<f32>() / steady.len() as f32).sqrt();
+        assert!(rms < 0.01, "aliased energy leaked: {rms}");
+    }
+}
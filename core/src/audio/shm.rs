@@ -0,0 +1,230 @@
+use anyhow::{bail, Context, Result};
+use memmap2::{Mmap, MmapMut};
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const SHM_RING_MAGIC: [u8; 4] = *b"FWPC";
+pub const SHM_RING_VERSION: u32 = 1;
+pub const SHM_RING_HEADER_LEN: usize = 64;
+const CAPACITY_OFFSET: usize = 8;
+const SAMPLE_RATE_OFFSET: usize = 12;
+const WRITE_POS_OFFSET: usize = 16;
+const SAMPLE_BYTES: usize = std::mem::size_of::<f32>();
+
+/// Single-producer PCM ring in a memory-mapped file, so another process can
+/// read engine-rate samples without them being serialized over IPC.
+///
+/// Layout, all fields little-endian:
+///
+/// | offset | size | field                                              |
+/// |--------|------|----------------------------------------------------|
+/// | 0      | 4    | magic `b"FWPC"`                                    |
+/// | 4      | 4    | layout version (`u32`, currently 1)                |
+/// | 8      | 4    | capacity in samples (`u32`)                        |
+/// | 12     | 4    | sample rate in Hz (`u32`)                          |
+/// | 16     | 8    | write position (`u64`, total samples ever written) |
+/// | 24     | 40   | reserved, zero                                     |
+/// | 64     | 4×N  | mono `f32` samples, N = capacity                   |
+///
+/// Sample `p` lives in slot `p % capacity`. The writer copies samples first
+/// and then publishes the new write position with release ordering; readers
+/// load it with acquire ordering, copy the slots they have not seen and
+/// re-check the position to discard any slots overwritten during the copy.
+pub struct ShmRingWriter {
+    map: MmapMut,
+    capacity: usize,
+}
+
+impl ShmRingWriter {
+    /// Create (or truncate) the ring file at `path`.
+    pub fn create(path: &Path, capacity: usize, sample_rate_hz: u32) -> Result<Self> {
+        if capacity == 0 || capacity > u32::MAX as usize {
+            bail!(
+                "shared-memory ring capacity must be between 1 and {}",
+                u32::MAX
+            );
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .with_context(|| format!("failed to create PCM ring at {}", path.display()))?;
+        file.set_len((SHM_RING_HEADER_LEN + capacity * SAMPLE_BYTES) as u64)?;
+        // SAFETY: the file was just sized by us; readers only map it read-only.
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[..4].copy_from_slice(&SHM_RING_MAGIC);
+        map[4..8].copy_from_slice(&SHM_RING_VERSION.to_le_bytes());
+        map[CAPACITY_OFFSET..CAPACITY_OFFSET + 4].copy_from_slice(&(capacity as u32).to_le_bytes());
+        map[SAMPLE_RATE_OFFSET..SAMPLE_RATE_OFFSET + 4]
+            .copy_from_slice(&sample_rate_hz.to_le_bytes());
+        Ok(Self { map, capacity })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Total samples written since the ring was created.
+    pub fn position(&self) -> u64 {
+        write_pos(&self.map).load(Ordering::Acquire)
+    }
+
+    pub fn write(&mut self, samples: &[f32]) {
+        let start = self.position();
+        // Only the newest `capacity` samples can survive in the ring.
+        let skip = samples.len().saturating_sub(self.capacity);
+        for (index, sample) in samples.iter().enumerate().skip(skip) {
+            let slot = ((start + index as u64) % self.capacity as u64) as usize;
+            let offset = SHM_RING_HEADER_LEN + slot * SAMPLE_BYTES;
+            self.map[offset..offset + SAMPLE_BYTES].copy_from_slice(&sample.to_le_bytes());
+        }
+        write_pos(&self.map).store(start + samples.len() as u64, Ordering::Release);
+    }
+}
+
+/// Samples returned by [`ShmRingReader::read`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShmRead {
+    pub samples: Vec<f32>,
+    /// Samples the writer overwrote before this reader got to them.
+    pub lost: u64,
+}
+
+/// Read side of [`ShmRingWriter`], meant for the desktop process.
+pub struct ShmRingReader {
+    map: Mmap,
+    capacity: usize,
+    sample_rate_hz: u32,
+    cursor: u64,
+}
+
+impl ShmRingReader {
+    /// Map an existing ring. Reading starts at the current write position.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open PCM ring at {}", path.display()))?;
+        // SAFETY: the mapping is read-only; concurrent writes by the producer are
+        // detected through the write position and discarded.
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() < SHM_RING_HEADER_LEN || map[..4] != SHM_RING_MAGIC {
+            bail!("{} is not a PCM ring", path.display());
+        }
+        let version = read_u32(&map, 4);
+        if version != SHM_RING_VERSION {
+            bail!("unsupported PCM ring version {version}");
+        }
+        let capacity = read_u32(&map, CAPACITY_OFFSET) as usize;
+        if capacity == 0 || map.len() < SHM_RING_HEADER_LEN + capacity * SAMPLE_BYTES {
+            bail!("PCM ring at {} is truncated", path.display());
+        }
+        let cursor = write_pos(&map).load(Ordering::Acquire);
+        Ok(Self {
+            sample_rate_hz: read_u32(&map, SAMPLE_RATE_OFFSET),
+            map,
+            capacity,
+            cursor,
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn sample_rate_hz(&self) -> u32 {
+        self.sample_rate_hz
+    }
+
+    /// Copy every sample written since the previous call.
+    pub fn read(&mut self) -> ShmRead {
+        let capacity = self.capacity as u64;
+        let end = write_pos(&self.map).load(Ordering::Acquire);
+        if end < self.cursor {
+            // The writer recreated the ring; resynchronise.
+            self.cursor = end;
+        }
+        let start = self.cursor.max(end.saturating_sub(capacity));
+        let mut lost = start - self.cursor;
+        let mut samples: Vec<f32> = (start..end)
+            .map(|position| {
+                let offset = SHM_RING_HEADER_LEN + (position % capacity) as usize * SAMPLE_BYTES;
+                f32::from_le_bytes(
+                    self.map[offset..offset + SAMPLE_BYTES]
+                        .try_into()
+                        .expect("slot is four bytes"),
+                )
+            })
+            .collect();
+
+        // Slots the writer reused while we were copying hold newer samples.
+        let reused = write_pos(&self.map)
+            .load(Ordering::Acquire)
+            .saturating_sub(capacity)
+            .saturating_sub(start)
+            .min(samples.len() as u64);
+        samples.drain(..reused as usize);
+        lost += reused;
+
+        self.cursor = end;
+        ShmRead { samples, lost }
+    }
+}
+
+fn write_pos(map: &[u8]) -> &AtomicU64 {
+    let ptr = map[WRITE_POS_OFFSET..WRITE_POS_OFFSET + 8].as_ptr();
+    debug_assert_eq!(ptr.align_offset(std::mem::align_of::<AtomicU64>()), 0);
+    // SAFETY: mappings are page aligned, so offset 16 is 8-byte aligned, and the
+    // field is only ever accessed atomically.
+    unsafe { &*(ptr as *const AtomicU64) }
+}
+
+fn read_u32(map: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(
+        map[offset..offset + 4]
+            .try_into()
+            .expect("field is four bytes"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_sees_samples_in_write_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pcm.ring");
+        let mut writer = ShmRingWriter::create(&path, 8, 16_000).unwrap();
+        writer.write(&[0.5]);
+        let mut reader = ShmRingReader::open(&path).unwrap();
+        assert_eq!((reader.capacity(), reader.sample_rate_hz()), (8, 16_000));
+        assert!(reader.read().samples.is_empty());
+
+        writer.write(&[0.1, 0.2, 0.3]);
+        writer.write(&[0.4, 0.5, 0.6, 0.7, 0.8]);
+        let read = reader.read();
+        assert_eq!(read.samples, [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]);
+        assert_eq!(read.lost, 0);
+        assert_eq!(writer.position(), 9);
+    }
+
+    #[test]
+    fn overrun_reports_lost_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pcm.ring");
+        let mut writer = ShmRingWriter::create(&path, 4, 16_000).unwrap();
+        let mut reader = ShmRingReader::open(&path).unwrap();
+
+        let samples: Vec<f32> = (0..10).map(|value| value as f32).collect();
+        writer.write(&samples);
+        let read = reader.read();
+        assert_eq!(read.samples, [6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(read.lost, 6);
+
+        drop((writer, reader));
+        std::fs::write(&path, b"not a ring").unwrap();
+        assert!(ShmRingReader::open(&path).is_err());
+    }
+}
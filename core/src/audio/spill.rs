@@ -0,0 +1,251 @@
+use anyhow::{anyhow, bail, Context, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+use super::backpressure::SubscriberState;
+use super::envelope::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+use super::AudioPipeline;
+
+const DEFAULT_BYTE_BUDGET: usize = 4 * 1024 * 1024;
+const SPILL_AAD_PREFIX: &[u8] = b"flowwisper.audio.spill.v1";
+
+/// Where lossless PCM subscribers overflow once their in-memory queue exceeds
+/// `byte_budget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillConfig {
+    pub dir: PathBuf,
+    pub byte_budget: usize,
+}
+
+impl Default for SpillConfig {
+    fn default() -> Self {
+        Self {
+            dir: env::temp_dir().join("flowwisper-spill"),
+            byte_budget: DEFAULT_BYTE_BUDGET,
+        }
+    }
+}
+
+/// Append-only file of sealed frames read back in write order. Each file has
+/// its own random key that never leaves memory, and the file is removed on drop.
+pub(crate) struct SpillQueue {
+    path: PathBuf,
+    keys: AudioCacheKeys,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    written: u64,
+    read: u64,
+}
+
+impl SpillQueue {
+    pub(crate) fn create(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create spill dir {dir:?}"))?;
+        let rng = SystemRandom::new();
+        let mut master = [0u8; 32];
+        let mut name = [0u8; 8];
+        rng.fill(&mut master)
+            .and_then(|_| rng.fill(&mut name))
+            .map_err(|_| anyhow!("failed to generate spill key"))?;
+        let keys = AudioCacheKeys::derive(&master)?;
+        let suffix: String = name.iter().map(|byte| format!("{byte:02x}")).collect();
+        let path = dir.join(format!("pcm-{suffix}.spill"));
+
+        let mut options = OpenOptions::new();
+        options.create_new(true).write(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let writer = options
+            .open(&path)
+            .with_context(|| format!("failed to create spill file {path:?}"))?;
+        let reader =
+            File::open(&path).with_context(|| format!("failed to open spill file {path:?}"))?;
+        Ok(Self {
+            path,
+            keys,
+            writer: BufWriter::new(writer),
+            reader: BufReader::new(reader),
+            written: 0,
+            read: 0,
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.read == self.written
+    }
+
+    pub(crate) fn push(&mut self, frame: &[f32]) -> Result<()> {
+        let pcm: Vec<u8> = frame
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let envelope = seal_payload(&self.keys, &record_aad(self.written), &pcm)?;
+        let record = serde_json::to_vec(&envelope)?;
+        self.writer
+            .write_all(&(record.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&record)?;
+        self.writer.flush()?;
+        self.written += 1;
+        Ok(())
+    }
+
+    pub(crate) fn pop(&mut self) -> Result<Option<Arc<[f32]>>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let mut len = [0u8; 4];
+        self.reader.read_exact(&mut len)?;
+        let mut record = vec![0u8; u32::from_le_bytes(len) as usize];
+        self.reader.read_exact(&mut record)?;
+        let envelope: SealedEnvelope = serde_json::from_slice(&record)?;
+        let pcm = open_payload(&self.keys, &record_aad(self.read), &envelope)?;
+        if pcm.len() % 4 != 0 {
+            bail!("spilled frame is not aligned to f32 samples");
+        }
+        self.read += 1;
+        let frame: Vec<f32> = pcm
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        if self.is_empty() {
+            self.rewind()?;
+        }
+        Ok(Some(frame.into()))
+    }
+
+    /// Reclaim disk space once the reader has caught up with the writer.
+    fn rewind(&mut self) -> Result<()> {
+        self.writer.get_ref().set_len(0)?;
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.reader.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+}
+
+impl Drop for SpillQueue {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn record_aad(index: u64) -> Vec<u8> {
+    let mut aad = SPILL_AAD_PREFIX.to_vec();
+    aad.extend_from_slice(&index.to_le_bytes());
+    aad
+}
+
+impl SubscriberState {
+    /// Write `frame` to disk when the memory budget is exhausted or earlier
+    /// frames are already on disk; returns the frame if it should stay in memory.
+    pub(super) fn spill(&mut self, config: &SpillConfig, frame: Arc<[f32]>) -> Option<Arc<[f32]>> {
+        let spilling = self.spill.as_ref().is_some_and(|spill| !spill.is_empty());
+        let bytes = frame.len() * std::mem::size_of::<f32>();
+        if !spilling && self.queued_bytes + bytes <= config.byte_budget {
+            return Some(frame);
+        }
+        if self.spill.is_none() {
+            match SpillQueue::create(&config.dir) {
+                Ok(spill) => self.spill = Some(spill),
+                Err(err) => {
+                    warn!(target: "audio_pipeline", %err, "failed to create pcm spill file");
+                    return Some(frame);
+                }
+            }
+        }
+        let spill = self.spill.as_mut()?;
+        match spill.push(&frame) {
+            Ok(()) => None,
+            Err(err) => {
+                warn!(target: "audio_pipeline", %err, "failed to spill pcm frame to disk");
+                Some(frame)
+            }
+        }
+    }
+}
+
+impl AudioPipeline {
+    /// Let lossless subscribers created after this call overflow to encrypted
+    /// temp files instead of blocking the feed once `byte_budget` is queued.
+    pub fn enable_spill(&self, config: SpillConfig) {
+        *self.spill.lock().expect("spill config mutex poisoned") = Some(config);
+    }
+
+    pub fn disable_spill(&self) {
+        *self.spill.lock().expect("spill config mutex poisoned") = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{duration_to_samples, MIN_FRAME_MS, SAMPLE_RATE_HZ};
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[test]
+    fn replays_frames_in_order_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = SpillQueue::create(dir.path()).unwrap();
+        let path = queue.path.clone();
+        for value in 0..3 {
+            queue.push(&[value as f32, 0.5]).unwrap();
+        }
+        assert_eq!(&*queue.pop().unwrap().unwrap(), &[0.0, 0.5]);
+        queue.push(&[3.0]).unwrap();
+        for expected in [1.0, 2.0, 3.0] {
+            assert_eq!(queue.pop().unwrap().unwrap()[0], expected);
+        }
+        assert!(queue.pop().unwrap().is_none());
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+
+        // Spilled audio is unreadable as plain PCM.
+        queue.push(&[0.25; 4]).unwrap();
+        let raw = fs::read(&path).unwrap();
+        assert!(!raw
+            .windows(4)
+            .any(|window| window == 0.25_f32.to_le_bytes()));
+
+        drop(queue);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn lossless_subscriber_spills_to_disk_without_blocking() {
+        let dir = tempfile::tempdir().expect("spill dir");
+        let pipeline = AudioPipeline::new();
+        let samples = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+        pipeline.enable_spill(SpillConfig {
+            dir: dir.path().to_path_buf(),
+            byte_budget: samples * std::mem::size_of::<f32>(),
+        });
+        let mut rx = pipeline.subscribe_lossless_pcm_frames(1);
+
+        timeout(Duration::from_millis(500), async {
+            for value in 0..10 {
+                pipeline
+                    .push_pcm_frame(vec![value as f32 / 100.0; samples])
+                    .await
+                    .expect("push frame");
+            }
+        })
+        .await
+        .expect("slow lossless subscriber blocked the feed");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        for value in 0..10 {
+            let frame = timeout(Duration::from_millis(200), rx.recv())
+                .await
+                .expect("spilled frame timed out")
+                .expect("channel closed");
+            assert_eq!(frame.len(), samples);
+            assert_eq!(frame[0], value as f32 / 100.0);
+        }
+    }
+}
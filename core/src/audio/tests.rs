@@ -0,0 +1,430 @@
+use super::*;
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn stop_delivers_tail_then_closes_subscribers() {
+    let pipeline = AudioPipeline::new();
+    let mut rx = pipeline.subscribe_pcm_frames(4);
+    let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+
+    pipeline
+        .push_pcm_frame(vec![0.5; frame_len / 2])
+        .await
+        .unwrap();
+    pipeline.stop().await.unwrap();
+    assert!(pipeline.is_stopped());
+    // Frames pushed after stop are ignored.
+    pipeline.push_pcm_frame(vec![0.5; frame_len]).await.unwrap();
+
+    let mut delivered = Vec::new();
+    while let Some(frame) = timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .expect("channel should close after the tail is delivered")
+    {
+        delivered.push(frame.len());
+    }
+    assert_eq!(delivered, vec![frame_len]);
+
+    pipeline.start().await.unwrap();
+    assert!(!pipeline.is_stopped());
+}
+
+#[tokio::test]
+async fn flushes_pending_tail_on_request() {
+    let pipeline = AudioPipeline::new();
+    let mut rx = pipeline.subscribe_pcm_frames(4);
+
+    let half_frame = duration_to_samples(Duration::from_millis(MIN_FRAME_MS / 2), SAMPLE_RATE_HZ);
+    pipeline
+        .push_pcm_frame(vec![0.2_f32; half_frame])
+        .await
+        .expect("push half frame");
+
+    // No frame should be emitted before the flush.
+    assert!(timeout(Duration::from_millis(50), rx.recv()).await.is_err());
+
+    pipeline.flush_pending().await.expect("flush pending audio");
+
+    let flushed = timeout(Duration::from_millis(100), rx.recv())
+        .await
+        .expect("flush did not emit frame")
+        .expect("channel closed unexpectedly");
+
+    assert_eq!(
+        flushed.len(),
+        duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ)
+    );
+    assert!(flushed
+        .iter()
+        .take(half_frame)
+        .all(|sample| (*sample - 0.2_f32).abs() < f32::EPSILON));
+    assert!(flushed
+        .iter()
+        .skip(half_frame)
+        .all(|sample| sample.abs() < f32::EPSILON));
+}
+
+#[tokio::test]
+async fn waveform_runs_at_target_cadence() {
+    let pipeline = AudioPipeline::new();
+    let mut waveform_rx = pipeline.subscribe_waveform();
+
+    let frame =
+        vec![0.2_f32; duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ)];
+
+    pipeline
+        .push_pcm_frame(frame)
+        .await
+        .expect("pcm frame should enqueue");
+
+    let mut received = 0;
+    while received < 3 {
+        let frame = timeout(Duration::from_millis(150), waveform_rx.recv())
+            .await
+            .expect("waveform frame timed out")
+            .expect("waveform channel closed unexpectedly");
+        assert!(frame.rms > 0.0);
+        assert!(frame.vad_active);
+        received += 1;
+    }
+}
+
+#[tokio::test]
+async fn waveform_flush_emits_tail_frame() {
+    let pipeline = AudioPipeline::new();
+    let mut waveform_rx = pipeline.subscribe_waveform();
+
+    let half_frame = duration_to_samples(Duration::from_millis(MIN_FRAME_MS / 2), SAMPLE_RATE_HZ);
+    pipeline
+        .push_pcm_frame(vec![0.05_f32; half_frame])
+        .await
+        .expect("partial frame should enqueue");
+
+    let preroll = timeout(Duration::from_millis(80), waveform_rx.recv())
+        .await
+        .expect("waveform pre-roll missing")
+        .expect("waveform channel closed unexpectedly");
+    assert_eq!(preroll.rms, 0.0);
+    assert!(!preroll.vad_active);
+
+    pipeline
+        .flush_pending()
+        .await
+        .expect("flush should succeed");
+
+    let frame = timeout(Duration::from_millis(200), waveform_rx.recv())
+        .await
+        .expect("waveform frame not emitted after flush")
+        .expect("waveform channel closed unexpectedly");
+    assert!(frame.rms > 0.0);
+    assert!(frame.vad_active);
+}
+
+#[tokio::test]
+async fn keystroke_suppression_gates_audio_around_reported_keystrokes() {
+    let pipeline = AudioPipeline::new();
+    pipeline.enable_keystroke_suppression(KeystrokeSuppressionConfig {
+        mode: KeystrokeSuppressionMode::Gate,
+        pre: Duration::from_millis(5),
+        post: Duration::from_millis(20),
+        latency: Duration::from_millis(50),
+    });
+    let mut rx = pipeline.subscribe_pcm_frames(8);
+    pipeline.push_keystroke(Instant::now());
+
+    let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+    pipeline
+        .push_pcm_frame(vec![0.5_f32; frame_len])
+        .await
+        .expect("push frame");
+    let frame = timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .expect("frame timed out")
+        .expect("channel closed unexpectedly");
+    assert_eq!(frame[0], 0.5);
+    assert_eq!(frame[900], 0.0, "keystroke window should be gated");
+    assert_eq!(frame[frame.len() - 1], 0.5);
+
+    pipeline.disable_keystroke_suppression();
+    pipeline.push_keystroke(Instant::now());
+    pipeline
+        .push_pcm_frame(vec![0.5_f32; frame_len])
+        .await
+        .expect("push frame");
+    let frame = timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .expect("frame timed out")
+        .expect("channel closed unexpectedly");
+    assert!(frame.iter().all(|&sample| sample == 0.5));
+}
+
+#[tokio::test]
+async fn resamples_device_rate_to_engine_rate() {
+    let pipeline = AudioPipeline::new();
+    pipeline.set_input_sample_rate(48_000);
+    assert_eq!(pipeline.input_sample_rate(), 48_000);
+    let mut rx = pipeline.subscribe_lossless_pcm_frames(32);
+
+    for _ in 0..10 {
+        pipeline
+            .push_pcm_frame(vec![0.1_f32; 4_800])
+            .await
+            .expect("push 48 kHz frame");
+    }
+    pipeline.flush_pending().await.expect("flush resampler");
+
+    let mut received = 0;
+    while let Ok(Some(frame)) = timeout(Duration::from_millis(100), rx.recv()).await {
+        // flush_pending zero-pads the short tail; count only resampled output.
+        received += frame.iter().filter(|sample| **sample != 0.0).count();
+    }
+    assert_eq!(received, SAMPLE_RATE_HZ as usize);
+
+    pipeline.set_input_sample_rate(SAMPLE_RATE_HZ);
+    assert_eq!(pipeline.input_sample_rate(), SAMPLE_RATE_HZ);
+}
+
+#[tokio::test]
+async fn interleaved_stereo_is_downmixed_per_policy() {
+    let pipeline = AudioPipeline::new();
+    let mut rx = pipeline.subscribe_pcm_frames(4);
+    let frame_len = duration_to_samples(Duration::from_millis(MIN_FRAME_MS), SAMPLE_RATE_HZ);
+    let stereo: Vec<f32> = (0..frame_len).flat_map(|_| [0.0_f32, 0.4]).collect();
+
+    pipeline
+        .push_pcm_frame_interleaved(stereo.clone(), 2)
+        .await
+        .expect("push averaged stereo frame");
+    let averaged = timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .expect("averaged frame timed out")
+        .expect("channel closed unexpectedly");
+    assert_eq!(averaged.len(), frame_len);
+    assert!(averaged.iter().all(|sample| (*sample - 0.2).abs() < 1e-6));
+
+    pipeline.set_downmix_policy(DownmixPolicy::LoudestChannel);
+    pipeline
+        .push_pcm_frame_interleaved(stereo, 2)
+        .await
+        .expect("push loudest-channel stereo frame");
+    let loudest = timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .expect("loudest frame timed out")
+        .expect("channel closed unexpectedly");
+    assert!(loudest.iter().all(|sample| (*sample - 0.4).abs() < 1e-6));
+
+    assert!(pipeline
+        .push_pcm_frame_interleaved(vec![0.1; 3], 2)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn noise_baseline_event_emitted_after_sampling() {
+    let pipeline = AudioPipeline::new();
+    pipeline.begin_preroll(None);
+    let mut noise_rx = pipeline.subscribe_noise_events();
+
+    let frame = vec![0.1_f32; duration_to_samples(Duration::from_millis(500), SAMPLE_RATE_HZ)];
+
+    pipeline
+        .push_pcm_frame(frame)
+        .await
+        .expect("pcm frame should enqueue");
+
+    let event = timeout(Duration::from_millis(200), noise_rx.recv())
+        .await
+        .expect("noise baseline event timed out")
+        .expect("noise channel closed unexpectedly");
+
+    match event {
+        NoiseEvent::BaselineEstablished { level_db } => {
+            assert!((level_db + 20.0).abs() < 1.5);
+        }
+        NoiseEvent::NoiseWarning(_) => {
+            panic!("expected baseline event, received noise warning");
+        }
+        NoiseEvent::EchoDetected(_) => {
+            panic!("expected baseline event, received echo event");
+        }
+        NoiseEvent::SilenceCountdown(_) => {
+            panic!("expected baseline event, received silence countdown");
+        }
+    }
+}
+
+#[tokio::test]
+async fn frame_window_resizes_chunks_and_notifies() {
+    let pipeline = AudioPipeline::new();
+    let mut events = pipeline.subscribe_frame_window();
+    let mut rx = pipeline.subscribe_pcm_frames(8);
+
+    pipeline.set_frame_window(Duration::from_millis(100));
+    let changed = events.try_recv().expect("frame window event");
+    assert_eq!(changed.previous, Duration::from_millis(MAX_FRAME_MS));
+    assert_eq!(changed.max_frame, Duration::from_millis(100));
+    assert_eq!(changed.min_frame, Duration::from_millis(50));
+    pipeline.set_frame_window(Duration::from_millis(100));
+    assert!(events.try_recv().is_err());
+
+    pipeline
+        .push_pcm_frame(vec![0.1; 4_000])
+        .await
+        .expect("push frame");
+    let mut sizes = Vec::new();
+    for _ in 0..3 {
+        let frame = timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .expect("frame timed out")
+            .expect("channel closed");
+        sizes.push(frame.len());
+    }
+    // 4000 samples at 16kHz: two full 100ms frames plus a 50ms remainder.
+    assert_eq!(sizes, [1_600, 1_600, 800]);
+}
+
+#[tokio::test]
+async fn handle_frame_decodes_negotiated_integer_pcm() {
+    let pipeline = AudioPipeline::new();
+    let mut rx = pipeline.subscribe_pcm_frames(4);
+    let format = pipeline
+        .negotiate_pcm_format(&[
+            PcmFormat::new(SampleEncoding::F32, 1, 48_000),
+            PcmFormat::new(SampleEncoding::I16, 2, 16_000),
+        ])
+        .unwrap();
+    assert_eq!(format, PcmFormat::new(SampleEncoding::I16, 2, 16_000));
+    assert_eq!(pipeline.pcm_format(), format);
+
+    // 立体声 i16：左声道 0.5、右声道 0.0，平均后为 0.25。
+    let pcm: Vec<u8> = (0..1_600)
+        .flat_map(|_| [16_384_i16, 0])
+        .flat_map(i16::to_le_bytes)
+        .collect();
+    pipeline.handle_frame(Bytes::from(pcm)).await.unwrap();
+    let frame = timeout(Duration::from_millis(100), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.len(), 1_600);
+    assert!(frame.iter().all(|sample| (*sample - 0.25).abs() < 1e-6));
+
+    // 未对齐到整帧的缓冲区被丢弃。
+    pipeline
+        .handle_frame(Bytes::from_static(&[0, 0]))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn shm_ring_mirrors_emitted_frames() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pcm.ring");
+    let pipeline = AudioPipeline::new();
+    pipeline
+        .enable_shm_ring(&path, Duration::from_secs(1))
+        .unwrap();
+    let mut reader = ShmRingReader::open(&path).unwrap();
+    assert_eq!(reader.capacity(), 16_000);
+
+    pipeline.push_pcm_frame(vec![0.25; 1_600]).await.unwrap();
+    let read = reader.read();
+    assert_eq!(read.samples, vec![0.25; 1_600]);
+    assert_eq!(read.lost, 0);
+
+    pipeline.disable_shm_ring();
+    pipeline.push_pcm_frame(vec![0.5; 1_600]).await.unwrap();
+    assert!(reader.read().samples.is_empty());
+}
+
+#[tokio::test]
+async fn waveform_envelope_aggregates_per_bucket() {
+    let pipeline = AudioPipeline::new();
+    let mut envelopes = pipeline.subscribe_waveform_envelope(Duration::from_millis(25));
+    let mut frame = vec![0.0_f32; 1_600];
+    frame[0] = 0.5;
+    frame[400] = -0.25;
+    pipeline.push_pcm_frame(frame).await.expect("push frame");
+
+    let envelope = timeout(Duration::from_millis(100), envelopes.recv())
+        .await
+        .expect("envelope timed out")
+        .expect("channel closed");
+    assert_eq!(envelope.bucket_duration, Duration::from_millis(25));
+    assert_eq!(envelope.buckets.len(), 4);
+    assert_eq!(envelope.buckets[0].max, 0.5);
+    assert_eq!(envelope.buckets[1].min, -0.25);
+    assert_eq!(envelope.buckets[3].rms, 0.0);
+}
+
+#[tokio::test]
+async fn loopback_reference_raises_echo_event() {
+    let pipeline = AudioPipeline::new();
+    let mut noise_rx = pipeline.subscribe_noise_events();
+    pipeline.begin_preroll(Some(-20.0));
+    pipeline.begin_recording();
+
+    let mut state = 17_u32;
+    let playback: Vec<f32> = (0..8_000)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1 << 24) as f32 - 0.5
+        })
+        .collect();
+    for chunk in playback.chunks(1_600) {
+        pipeline.push_loopback_reference(chunk);
+        pipeline
+            .push_pcm_frame(chunk.iter().map(|sample| sample * 0.5).collect())
+            .await
+            .expect("push frame");
+    }
+
+    let echo = timeout(Duration::from_millis(200), async {
+        loop {
+            if let NoiseEvent::EchoDetected(payload) =
+                noise_rx.recv().await.expect("noise channel closed")
+            {
+                break payload;
+            }
+        }
+    })
+    .await
+    .expect("echo event timed out");
+    assert_eq!(echo.delay_ms, 0);
+    assert!(echo.correlation > 0.9);
+}
+
+#[tokio::test]
+async fn preroll_warm_starts_from_the_devices_last_baseline() {
+    let pipeline = AudioPipeline::new();
+    pipeline.set_active_device(Some("usb".into()));
+    let mut noise_rx = pipeline.subscribe_noise_events();
+
+    // No stored baseline yet: nothing is announced until sampling finishes.
+    pipeline.begin_preroll(None);
+    assert!(noise_rx.try_recv().is_err());
+    pipeline
+        .push_pcm_frame(vec![0.1; 8_000])
+        .await
+        .expect("push frame");
+    let sampled = match noise_rx.recv().await.expect("noise channel closed") {
+        NoiseEvent::BaselineEstablished { level_db } => level_db,
+        other => panic!("expected baseline event, got {other:?}"),
+    };
+    assert_eq!(pipeline.noise_baseline("usb"), Some(sampled));
+
+    pipeline.reset_session();
+    pipeline.begin_preroll(None);
+    match noise_rx.try_recv() {
+        Ok(NoiseEvent::BaselineEstablished { level_db }) => assert_eq!(level_db, sampled),
+        other => panic!("expected immediate warm-start baseline, got {other:?}"),
+    }
+    pipeline
+        .push_pcm_frame(vec![0.1; 8_000])
+        .await
+        .expect("push frame");
+    assert!(
+        noise_rx.try_recv().is_err(),
+        "stable level keeps the baseline"
+    );
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use super::mp3::{decode_mp3, looks_like_mp3};
+use super::{downmix_interleaved, DownmixPolicy, StreamingResampler, SAMPLE_RATE_HZ};
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Interleaved samples decoded from an audio file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedAudio {
+    pub sample_rate_hz: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl DecodedAudio {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.frames() as u64 * 1_000 / self.sample_rate_hz.max(1) as u64
+    }
+
+    /// Fold to mono and resample to the engine rate.
+    pub fn to_engine_mono(&self, policy: DownmixPolicy) -> Result<Vec<f32>> {
+        let mono = downmix_interleaved(&self.samples, self.channels as usize, policy)?;
+        if self.sample_rate_hz == SAMPLE_RATE_HZ {
+            return Ok(mono);
+        }
+        let mut resampler = StreamingResampler::new(self.sample_rate_hz, SAMPLE_RATE_HZ);
+        let mut resampled = resampler.process(&mono);
+        resampled.extend(resampler.flush());
+        Ok(resampled)
+    }
+}
+
+/// Decode an audio file: RIFF/WAVE (integer PCM or 32-bit float) or MP3,
+/// detected from the file contents rather than the extension.
+pub fn decode_audio_file(path: &Path) -> Result<DecodedAudio> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {path:?}"))?;
+    let decoded = if looks_like_mp3(&bytes) {
+        decode_mp3(&bytes)
+    } else {
+        decode_wav(&bytes)
+    };
+    decoded.with_context(|| format!("failed to decode {path:?}"))
+}
+
+pub fn decode_wav(bytes: &[u8]) -> Result<DecodedAudio> {
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        bail!("not a RIFF/WAVE file");
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into()?) as usize;
+        let body_start = offset + 8;
+        let body = &bytes[body_start..(body_start + size).min(bytes.len())];
+        match id {
+            b"fmt " => {
+                if body.len() < 16 {
+                    bail!("fmt chunk is truncated");
+                }
+                let mut tag = u16::from_le_bytes([body[0], body[1]]);
+                if tag == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+                    tag = u16::from_le_bytes([body[24], body[25]]);
+                }
+                let channels = u16::from_le_bytes([body[2], body[3]]);
+                let sample_rate = u32::from_le_bytes(body[4..8].try_into()?);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                format = Some((tag, channels, sample_rate, bits));
+            }
+            b"data" => {
+                let Some((tag, channels, sample_rate_hz, bits)) = format else {
+                    bail!("data chunk precedes fmt chunk");
+                };
+                if channels == 0 || sample_rate_hz == 0 {
+                    bail!("invalid channel count or sample rate");
+                }
+                let samples = decode_samples(body, tag, bits)?;
+                return Ok(DecodedAudio {
+                    sample_rate_hz,
+                    channels,
+                    samples,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        offset = body_start + size + (size & 1);
+    }
+    bail!("no data chunk found")
+}
+
+fn decode_samples(body: &[u8], tag: u16, bits: u16) -> Result<Vec<f32>> {
+    let samples = match (tag, bits) {
+        (WAVE_FORMAT_PCM, 8) => body
+            .iter()
+            .map(|&byte| (byte as f32 - 128.0) / 128.0)
+            .collect(),
+        (WAVE_FORMAT_PCM, 16) => body
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0)
+            .collect(),
+        (WAVE_FORMAT_PCM, 24) => body
+            .chunks_exact(3)
+            .map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0)
+            .collect(),
+        (WAVE_FORMAT_PCM, 32) => body
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0)
+            .collect(),
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => body
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        _ => bail!("unsupported WAV encoding (format {tag}, {bits} bits)"),
+    };
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::mp3::tests::silent_mp3;
+    use crate::audio::RecordedAudio;
+
+    #[test]
+    fn decodes_pcm16_and_resamples_stereo_float() {
+        let recorded = RecordedAudio {
+            session_id: "wav".into(),
+            sample_rate_hz: 16_000,
+            samples: vec![0, 16_384, -32_768, 32_767],
+        };
+        let decoded = decode_wav(&recorded.to_wav_bytes()).expect("decode pcm16");
+        assert_eq!((decoded.sample_rate_hz, decoded.channels), (16_000, 1));
+        assert_eq!(&decoded.samples[..3], &[0.0, 0.5, -1.0]);
+
+        let data: Vec<u8> = (0..48_000)
+            .flat_map(|i| {
+                let left = if i % 2 == 0 { 0.5_f32 } else { -0.5 };
+                [left, 0.25_f32]
+            })
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        bytes.extend(b"fmt \x10\0\0\0");
+        bytes.extend(WAVE_FORMAT_IEEE_FLOAT.to_le_bytes());
+        bytes.extend(2_u16.to_le_bytes());
+        bytes.extend(48_000_u32.to_le_bytes());
+        bytes.extend((48_000_u32 * 8).to_le_bytes());
+        bytes.extend(8_u16.to_le_bytes());
+        bytes.extend(32_u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+
+        let decoded = decode_wav(&bytes).expect("decode float");
+        assert_eq!(decoded.duration_ms(), 1_000);
+        let mono = decoded
+            .to_engine_mono(DownmixPolicy::LeftOnly)
+            .expect("downmix");
+        assert!((mono.len() as i64 - 16_000).abs() <= 2);
+    }
+
+    #[test]
+    fn decodes_files_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let mp3 = dir.path().join("memo.wav");
+        fs::write(&mp3, silent_mp3(20)).unwrap();
+        let decoded = decode_audio_file(&mp3).expect("decode mp3 file");
+        assert_eq!(decoded.sample_rate_hz, 44_100);
+
+        let truncated = dir.path().join("memo.mp3");
+        fs::write(&truncated, b"ID3\x04\0\0\0\0\0\0").unwrap();
+        let err = format!("{:#}", decode_audio_file(&truncated).unwrap_err());
+        assert!(err.contains("memo.mp3"), "{err}");
+    }
+}
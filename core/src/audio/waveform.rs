@@ -0,0 +1,228 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{broadcast, mpsc};
+use tokio::task;
+use tokio::time::{interval, MissedTickBehavior};
+
+use super::{
+    duration_to_samples, frame_rms, samples_to_duration, AudioPipeline, ENVELOPE_CHANNEL_CAPACITY,
+    SAMPLE_RATE_HZ, VAD_THRESHOLD, WAVEFORM_FRAME_MS,
+};
+
+/// Peak and RMS level of one UI bucket.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnvelopeBucket {
+    pub min: f32,
+    pub max: f32,
+    pub rms: f32,
+}
+
+/// Buckets completed since the previous message, oldest first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveformEnvelope {
+    pub bucket_duration: Duration,
+    pub buckets: Vec<EnvelopeBucket>,
+}
+
+/// Folds a sample stream into fixed-size buckets, carrying partial buckets
+/// across calls.
+#[derive(Debug)]
+pub(crate) struct EnvelopeBinner {
+    bucket_samples: usize,
+    min: f32,
+    max: f32,
+    energy: f32,
+    count: usize,
+}
+
+impl EnvelopeBinner {
+    pub(crate) fn new(bucket_samples: usize) -> Self {
+        Self {
+            bucket_samples: bucket_samples.max(1),
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            energy: 0.0,
+            count: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, samples: &[f32]) -> Vec<EnvelopeBucket> {
+        let mut buckets = Vec::new();
+        for &sample in samples {
+            self.min = self.min.min(sample);
+            self.max = self.max.max(sample);
+            self.energy += sample * sample;
+            self.count += 1;
+            if self.count == self.bucket_samples {
+                buckets.push(EnvelopeBucket {
+                    min: self.min,
+                    max: self.max,
+                    rms: (self.energy / self.count as f32).sqrt(),
+                });
+                *self = Self::new(self.bucket_samples);
+            }
+        }
+        buckets
+    }
+}
+
+pub(super) struct EnvelopeSubscriber {
+    sender: mpsc::Sender<WaveformEnvelope>,
+    bucket_duration: Duration,
+    binner: EnvelopeBinner,
+}
+
+#[derive(Clone, Debug)]
+pub struct WaveformFrame {
+    pub rms: f32,
+    pub vad_active: bool,
+    /// 生成该帧时 AGC 应用的增益，未启用 AGC 时为 1.0。
+    pub gain: f32,
+}
+
+impl AudioPipeline {
+    pub(super) fn spawn_waveform_scheduler(&self) {
+        let pending = Arc::clone(&self.waveform_pending);
+        let tx = self.waveform_tx.clone();
+        let frame_samples = self.waveform_frame_samples;
+        let started = Arc::clone(&self.waveform_started);
+        let applied_gain = Arc::clone(&self.applied_gain);
+
+        task::spawn(async move {
+            let mut ticker = interval(Duration::from_millis(WAVEFORM_FRAME_MS));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                let maybe_chunk = {
+                    let mut guard = pending.lock().expect("waveform accumulator poisoned");
+
+                    if guard.len() >= frame_samples {
+                        let chunk: Vec<f32> = guard.drain(..frame_samples).collect();
+                        Some(chunk)
+                    } else if started.load(Ordering::SeqCst) && !guard.is_empty() {
+                        let mut chunk: Vec<f32> = guard.drain(..).collect();
+                        chunk.resize(frame_samples, 0.0);
+                        Some(chunk)
+                    } else {
+                        None
+                    }
+                };
+
+                let gain = f32::from_bits(applied_gain.load(Ordering::SeqCst));
+                if let Some(chunk) = maybe_chunk {
+                    let rms = frame_rms(&chunk);
+                    let vad_active = rms >= VAD_THRESHOLD;
+                    let _ = tx.send(WaveformFrame {
+                        rms,
+                        vad_active,
+                        gain,
+                    });
+                } else if !started.load(Ordering::SeqCst) {
+                    let _ = tx.send(WaveformFrame {
+                        rms: 0.0,
+                        vad_active: false,
+                        gain,
+                    });
+                }
+            }
+        });
+    }
+
+    pub fn subscribe_waveform(&self) -> broadcast::Receiver<WaveformFrame> {
+        self.waveform_tx.subscribe()
+    }
+
+    /// Min/max/RMS envelopes with one bucket per `resolution` of audio, so UI
+    /// renderers can draw a pixel per bucket without re-binning raw frames.
+    /// Messages are dropped rather than queued when the receiver falls behind.
+    pub fn subscribe_waveform_envelope(
+        &self,
+        resolution: Duration,
+    ) -> mpsc::Receiver<WaveformEnvelope> {
+        let (sender, rx) = mpsc::channel(ENVELOPE_CHANNEL_CAPACITY);
+        let bucket_samples = duration_to_samples(resolution, SAMPLE_RATE_HZ);
+        self.envelope_subscribers
+            .lock()
+            .expect("envelope subscriber registry poisoned")
+            .push(EnvelopeSubscriber {
+                sender,
+                bucket_duration: samples_to_duration(bucket_samples),
+                binner: EnvelopeBinner::new(bucket_samples),
+            });
+        rx
+    }
+
+    pub(super) fn emit_waveform_samples(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+
+        let mut guard = self
+            .waveform_pending
+            .lock()
+            .expect("waveform accumulator poisoned");
+        guard.extend(samples.iter().copied());
+        drop(guard);
+
+        self.waveform_started.store(true, Ordering::SeqCst);
+        self.emit_envelopes(samples);
+    }
+
+    fn emit_envelopes(&self, samples: &[f32]) {
+        let mut subscribers = self
+            .envelope_subscribers
+            .lock()
+            .expect("envelope subscriber registry poisoned");
+        subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+        for subscriber in subscribers.iter_mut() {
+            let buckets = subscriber.binner.push(samples);
+            if buckets.is_empty() {
+                continue;
+            }
+            let _ = subscriber.sender.try_send(WaveformEnvelope {
+                bucket_duration: subscriber.bucket_duration,
+                buckets,
+            });
+        }
+    }
+
+    pub(super) fn flush_waveform_tail(&self) {
+        let mut guard = self
+            .waveform_pending
+            .lock()
+            .expect("waveform accumulator poisoned");
+
+        if guard.is_empty() {
+            return;
+        }
+
+        let remainder = guard.len() % self.waveform_frame_samples;
+        if remainder != 0 {
+            let pad = self.waveform_frame_samples - remainder;
+            for _ in 0..pad {
+                guard.push_back(0.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bins_across_chunk_boundaries() {
+        let mut binner = EnvelopeBinner::new(4);
+        assert!(binner.push(&[0.5, -0.5]).is_empty());
+        let buckets = binner.push(&[1.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.2]);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!((buckets[0].min, buckets[0].max), (-1.0, 1.0));
+        assert!((buckets[0].rms - (2.5_f32 / 4.0).sqrt()).abs() < 1e-6);
+        assert_eq!(buckets[1].rms, 0.0);
+        assert_eq!(binner.push(&[0.0, 0.0, 0.0])[0].max, 0.2);
+    }
+}
@@ -0,0 +1,327 @@
+//! 性能基准：多订阅者下 `push_pcm_frame` 的吞吐、波形调度的附加开销，以及使用桩引擎的
+//! 端到端首个更新延迟。结果与回归阈值比对后生成 JSON 报告，便于 CI 判定是否退化。
+//! 需启用 `bench` 特性，通过 `flowwisper-core bench [thresholds.json]` 运行。
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::audio::AudioPipeline;
+use crate::orchestrator::{
+    EngineConfig, EngineOrchestrator, RealtimeSessionConfig, TranscriptionUpdate, UpdatePayload,
+};
+use crate::testing::{PcmFixture, ScriptedEngine, ScriptedStep};
+
+/// 单帧 20ms，与常见采集回调的粒度一致。
+const BENCH_FRAME: Duration = Duration::from_millis(20);
+const BENCH_AMPLITUDE: f32 = 0.4;
+const FIRST_UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchConfig {
+    /// 每档订阅者数量各测一次吞吐。
+    pub subscriber_counts: Vec<usize>,
+    /// 吞吐与波形开销测量推送的帧数。
+    pub frames: usize,
+    /// 首个更新延迟的会话次数。
+    pub latency_runs: usize,
+    /// 首个更新延迟测量时的回放倍速，1.0 为实时。
+    pub realtime_factor: f64,
+    /// 桩引擎每次识别的模拟耗时。
+    pub engine_latency: Duration,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            subscriber_counts: vec![1, 4, 16],
+            frames: 2_000,
+            latency_runs: 10,
+            realtime_factor: 1.0,
+            engine_latency: Duration::from_millis(50),
+        }
+    }
+}
+
+/// 回归阈值；JSON 文件中缺省的字段沿用默认值。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchThresholds {
+    /// 每档订阅者数量下的最低吞吐（帧/秒）。
+    pub min_frames_per_sec: f64,
+    /// 波形与包络订阅带来的单帧附加耗时上限（微秒）。
+    pub max_waveform_overhead_us: f64,
+    /// 首个更新延迟 p95 上限（毫秒），默认与首个更新的 SLA 一致。
+    pub max_first_update_p95_ms: f64,
+}
+
+impl Default for BenchThresholds {
+    fn default() -> Self {
+        Self {
+            min_frames_per_sec: 2_000.0,
+            max_waveform_overhead_us: 200.0,
+            max_first_update_p95_ms: 400.0,
+        }
+    }
+}
+
+impl BenchThresholds {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read thresholds from {}", path.display()))?;
+        serde_json::from_str(&raw).context("invalid benchmark thresholds")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchBound {
+    AtLeast,
+    AtMost,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchMetric {
+    pub name: String,
+    pub unit: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub bound: BenchBound,
+    pub passed: bool,
+}
+
+impl BenchMetric {
+    fn new(
+        name: impl Into<String>,
+        unit: &str,
+        value: f64,
+        threshold: f64,
+        bound: BenchBound,
+    ) -> Self {
+        let passed = match bound {
+            BenchBound::AtLeast => value >= threshold,
+            BenchBound::AtMost => value <= threshold,
+        };
+        Self {
+            name: name.into(),
+            unit: unit.into(),
+            value,
+            threshold,
+            bound,
+            passed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub generated_at_ms: u128,
+    pub passed: bool,
+    pub metrics: Vec<BenchMetric>,
+}
+
+impl BenchReport {
+    pub fn metric(&self, name: &str) -> Option<&BenchMetric> {
+        self.metrics.iter().find(|metric| metric.name == name)
+    }
+
+    pub fn regressions(&self) -> Vec<&BenchMetric> {
+        self.metrics
+            .iter()
+            .filter(|metric| !metric.passed)
+            .collect()
+    }
+}
+
+/// 依次执行全部基准并与阈值比对，单项退化不会中断后续测量。
+pub async fn run_benchmarks(
+    config: &BenchConfig,
+    thresholds: &BenchThresholds,
+) -> Result<BenchReport> {
+    let mut metrics = Vec::new();
+
+    for &subscribers in &config.subscriber_counts {
+        let elapsed = measure_push(subscribers, config.frames, false).await?;
+        metrics.push(BenchMetric::new(
+            format!("push_pcm_frame_fps_{subscribers}_subscribers"),
+            "frames_per_sec",
+            config.frames as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            thresholds.min_frames_per_sec,
+            BenchBound::AtLeast,
+        ));
+    }
+
+    let baseline = measure_push(1, config.frames, false).await?;
+    let with_waveform = measure_push(1, config.frames, true).await?;
+    let overhead = with_waveform.saturating_sub(baseline);
+    metrics.push(BenchMetric::new(
+        "waveform_overhead_per_frame",
+        "us",
+        overhead.as_secs_f64() * 1e6 / config.frames.max(1) as f64,
+        thresholds.max_waveform_overhead_us,
+        BenchBound::AtMost,
+    ));
+
+    let mut latencies = Vec::with_capacity(config.latency_runs);
+    for _ in 0..config.latency_runs {
+        latencies.push(measure_first_update(config).await?);
+    }
+    latencies.sort();
+    metrics.push(BenchMetric::new(
+        "first_update_p50",
+        "ms",
+        percentile_ms(&latencies, 0.5),
+        thresholds.max_first_update_p95_ms,
+        BenchBound::AtMost,
+    ));
+    metrics.push(BenchMetric::new(
+        "first_update_p95",
+        "ms",
+        percentile_ms(&latencies, 0.95),
+        thresholds.max_first_update_p95_ms,
+        BenchBound::AtMost,
+    ));
+
+    Ok(BenchReport {
+        generated_at_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis(),
+        passed: metrics.iter().all(|metric| metric.passed),
+        metrics,
+    })
+}
+
+/// 推送 `frames` 帧所用时间；订阅者由独立任务持续消费，测量包含分帧与分发。
+async fn measure_push(subscribers: usize, frames: usize, waveform: bool) -> Result<Duration> {
+    let pipeline = AudioPipeline::new();
+    let mut drains: Vec<JoinHandle<()>> = Vec::new();
+    for _ in 0..subscribers {
+        drains.push(drain(pipeline.subscribe_pcm_frames(64)));
+    }
+    if waveform {
+        drains.push(drain(
+            pipeline.subscribe_waveform_envelope(Duration::from_millis(10)),
+        ));
+        let mut waveform_rx = pipeline.subscribe_waveform();
+        drains.push(tokio::spawn(async move {
+            while waveform_rx.recv().await.is_ok() {}
+        }));
+    }
+
+    let frame = PcmFixture::tone(BENCH_AMPLITUDE, BENCH_FRAME)
+        .frames()
+        .remove(0);
+    let started = Instant::now();
+    for _ in 0..frames {
+        pipeline.push_pcm_frame(frame.clone()).await?;
+    }
+    pipeline.flush_pending().await?;
+    let elapsed = started.elapsed();
+
+    pipeline.stop().await?;
+    for task in drains {
+        task.abort();
+    }
+    Ok(elapsed)
+}
+
+fn drain<T: Send + 'static>(mut rx: mpsc::Receiver<T>) -> JoinHandle<()> {
+    tokio::spawn(async move { while rx.recv().await.is_some() {} })
+}
+
+/// 首条转写的延迟，即编排器自触发解码的帧到下发更新的耗时，与首个更新 SLA 的口径一致。
+async fn measure_first_update(config: &BenchConfig) -> Result<Duration> {
+    let engine = Arc::new(ScriptedEngine::new(vec![ScriptedStep::ok(
+        "benchmark sentence.",
+        config.engine_latency,
+    )]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        engine,
+    );
+    let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    });
+    let fixture = PcmFixture::tone(BENCH_AMPLITUDE, Duration::from_millis(600))
+        .with_frame_duration(Duration::from_millis(100));
+
+    let (played, first) = tokio::join!(
+        fixture.play_into_session(&session, config.realtime_factor),
+        timeout(FIRST_UPDATE_TIMEOUT, first_transcript(&mut rx)),
+    );
+    played?;
+    first
+        .ok()
+        .flatten()
+        .context("no transcript before benchmark timeout")
+}
+
+async fn first_transcript(rx: &mut mpsc::Receiver<TranscriptionUpdate>) -> Option<Duration> {
+    while let Some(update) = rx.recv().await {
+        if matches!(update.payload, UpdatePayload::Transcript(_)) {
+            return Some(update.latency);
+        }
+    }
+    None
+}
+
+fn percentile_ms(sorted: &[Duration], quantile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() as f64 * quantile).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1].as_secs_f64() * 1e3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let samples: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile_ms(&samples, 0.5), 10.0);
+        assert_eq!(percentile_ms(&samples, 0.95), 19.0);
+        assert_eq!(percentile_ms(&[], 0.95), 0.0);
+    }
+
+    #[tokio::test]
+    async fn report_flags_regressions_against_thresholds() {
+        let config = BenchConfig {
+            subscriber_counts: vec![1, 4],
+            frames: 50,
+            latency_runs: 1,
+            realtime_factor: 4.0,
+            engine_latency: Duration::ZERO,
+        };
+        let thresholds = BenchThresholds {
+            min_frames_per_sec: f64::MAX,
+            ..BenchThresholds::default()
+        };
+        let report = run_benchmarks(&config, &thresholds).await.unwrap();
+
+        assert!(!report.passed);
+        let throughput = report.metric("push_pcm_frame_fps_4_subscribers").unwrap();
+        assert!(throughput.value > 0.0 && !throughput.passed);
+        assert!(report.metric("waveform_overhead_per_frame").is_some());
+        let latency = report.metric("first_update_p95").unwrap();
+        assert!(latency.value > 0.0);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["metrics"][0]["bound"], "at_least");
+        let parsed: BenchThresholds =
+            serde_json::from_str(r#"{"max_first_update_p95_ms": 250.0}"#).unwrap();
+        assert_eq!(parsed.max_first_update_p95_ms, 250.0);
+        assert_eq!(parsed.min_frames_per_sec, 2_000.0);
+    }
+}
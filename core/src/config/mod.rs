@@ -0,0 +1,457 @@
+//! 全局配置服务：加载分层 TOML 配置并校验，为各子系统提供类型化访问；
+//! 配置文件变更后自动重新加载，并通过广播通道通知发生变化的配置段。
+//!
+//! 配置文件默认位于 `<系统配置目录>/Flowwisper/config.toml`，可用 `FLOWWISPER_CONFIG` 指定。
+
+mod policy;
+mod schema;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+pub use policy::{
+    PolicyEngine, PolicyError, SignedTenantPolicy, TenantPolicy, POLICY_PATH_ENV,
+    POLICY_PUBLIC_KEY_ENV,
+};
+pub use schema::{
+    BackupSection, BudgetSection, EngineSection, FlowwisperConfig, PolisherSection, PowerSection,
+    RedactionSection, SessionSection, SyncSection, TelemetrySection, CLOUD_ENDPOINT_ENV,
+    HYBRID_ENGINE_ENV, MAX_SESSION_SECS_ENV, MODEL_DIR_ENV, PREFER_CLOUD_ENV, REDACTION_MODE_ENV,
+};
+
+pub const CONFIG_PATH_ENV: &str = "FLOWWISPER_CONFIG";
+pub const CONFIG_FILE: &str = "config.toml";
+/// 默认的文件检查间隔。
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+type EnvLookup = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// 配置中的独立段落，变更通知按段落粒度下发。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    Engine,
+    Polisher,
+    Session,
+    Telemetry,
+    Sync,
+    Backup,
+    Redaction,
+    Budget,
+    Power,
+}
+
+impl ConfigSection {
+    /// 无需重启即可生效的段落；其余段落在下次启动时生效。
+    pub fn is_live(&self) -> bool {
+        matches!(
+            self,
+            ConfigSection::Session
+                | ConfigSection::Redaction
+                | ConfigSection::Budget
+                | ConfigSection::Power
+        )
+    }
+}
+
+/// 重新加载后发生变化的配置。
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    pub config: Arc<FlowwisperConfig>,
+    pub changed: Vec<ConfigSection>,
+}
+
+fn changed_sections(old: &FlowwisperConfig, new: &FlowwisperConfig) -> Vec<ConfigSection> {
+    let mut changed = Vec::new();
+    if old.engine != new.engine {
+        changed.push(ConfigSection::Engine);
+    }
+    if old.polisher != new.polisher {
+        changed.push(ConfigSection::Polisher);
+    }
+    if old.session != new.session {
+        changed.push(ConfigSection::Session);
+    }
+    if old.telemetry != new.telemetry {
+        changed.push(ConfigSection::Telemetry);
+    }
+    if old.sync != new.sync {
+        changed.push(ConfigSection::Sync);
+    }
+    if old.backup != new.backup {
+        changed.push(ConfigSection::Backup);
+    }
+    if old.redaction != new.redaction {
+        changed.push(ConfigSection::Redaction);
+    }
+    if old.budget != new.budget {
+        changed.push(ConfigSection::Budget);
+    }
+    if old.power != new.power {
+        changed.push(ConfigSection::Power);
+    }
+    changed
+}
+
+/// 文件的修改时间与长度，用于判断是否需要重新加载。
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn file_stamp(path: Option<&Path>) -> FileStamp {
+    let metadata = fs::metadata(path?).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+struct Inner {
+    path: Option<PathBuf>,
+    env: EnvLookup,
+    current: RwLock<Arc<FlowwisperConfig>>,
+    stamp: Mutex<FileStamp>,
+    /// 生效的组织策略；重新加载时违反策略的配置被拒绝。
+    policy: RwLock<Option<Arc<TenantPolicy>>>,
+    tx: broadcast::Sender<ConfigChange>,
+}
+
+/// 配置服务，克隆之间共享同一份配置。
+#[derive(Clone)]
+pub struct ConfigService {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for ConfigService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigService")
+            .field("path", &self.inner.path)
+            .field("config", &self.current())
+            .finish()
+    }
+}
+
+impl ConfigService {
+    /// 从 `path` 加载配置并叠加进程环境变量；文件不存在时使用默认值。
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        Self::with_env(path, Arc::new(|name: &str| env::var(name).ok()))
+    }
+
+    /// 从默认位置加载配置。
+    pub fn load_default() -> Result<Self> {
+        Self::load(Self::default_path())
+    }
+
+    /// 与 [`ConfigService::load`] 相同，但配置无效时记录告警并退回默认值；
+    /// 文件修正后仍会被 [`ConfigService::watch`] 重新加载。
+    pub fn load_or_default(path: Option<PathBuf>) -> Self {
+        let env: EnvLookup = Arc::new(|name: &str| env::var(name).ok());
+        Self::with_env(path.clone(), env.clone()).unwrap_or_else(|err| {
+            warn!(target: "config", %err, "invalid configuration, using defaults");
+            let stamp = file_stamp(path.as_deref());
+            Self::build(path, env, FlowwisperConfig::default(), stamp)
+        })
+    }
+
+    /// 不关联文件的固定配置，用于配置无法加载时的降级与测试。
+    pub fn from_config(config: FlowwisperConfig) -> Self {
+        Self::build(None, Arc::new(|_: &str| None), config, None)
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        env::var(CONFIG_PATH_ENV)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("Flowwisper").join(CONFIG_FILE)))
+    }
+
+    fn with_env(path: Option<PathBuf>, env: EnvLookup) -> Result<Self> {
+        let stamp = file_stamp(path.as_deref());
+        let config = read_layers(path.as_deref(), env.as_ref())?;
+        Ok(Self::build(path, env, config, stamp))
+    }
+
+    fn build(
+        path: Option<PathBuf>,
+        env: EnvLookup,
+        config: FlowwisperConfig,
+        stamp: FileStamp,
+    ) -> Self {
+        let (tx, _) = broadcast::channel(16);
+        Self {
+            inner: Arc::new(Inner {
+                path,
+                env,
+                current: RwLock::new(Arc::new(config)),
+                stamp: Mutex::new(stamp),
+                policy: RwLock::new(None),
+                tx,
+            }),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.inner.path.as_deref()
+    }
+
+    pub fn current(&self) -> Arc<FlowwisperConfig> {
+        self.inner
+            .current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigChange> {
+        self.inner.tx.subscribe()
+    }
+
+    /// 之后的每次重新加载都按 `policy` 校验，违反策略的新配置与无效配置一样被拒绝。
+    pub fn enforce_policy(&self, policy: Option<Arc<TenantPolicy>>) {
+        *self
+            .inner
+            .policy
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+    }
+
+    fn check_policy(&self, config: &FlowwisperConfig) -> Result<(), PolicyError> {
+        let policy = self
+            .inner
+            .policy
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let violations = policy.map(|policy| policy.violations(config));
+        match violations {
+            Some(violations) if !violations.is_empty() => Err(PolicyError::Violation(violations)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 重新读取全部配置层；新配置无效或违反组织策略时保留旧配置并返回错误。
+    /// 返回发生变化的段落，有变化时同时广播。
+    pub fn reload(&self) -> Result<Vec<ConfigSection>> {
+        *self
+            .inner
+            .stamp
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = file_stamp(self.path());
+        let config = read_layers(self.path(), self.inner.env.as_ref())?;
+        self.check_policy(&config)?;
+        let mut current = self
+            .inner
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let changed = changed_sections(&current, &config);
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+        let config = Arc::new(config);
+        *current = config.clone();
+        drop(current);
+        info!(target: "config", ?changed, "configuration reloaded");
+        let _ = self.inner.tx.send(ConfigChange {
+            config,
+            changed: changed.clone(),
+        });
+        Ok(changed)
+    }
+
+    /// 文件自上次加载后被修改、创建或删除时重新加载。
+    pub fn reload_if_changed(&self) -> Result<Vec<ConfigSection>> {
+        let stamp = file_stamp(self.path());
+        let unchanged = *self
+            .inner
+            .stamp
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            == stamp;
+        if unchanged {
+            return Ok(Vec::new());
+        }
+        self.reload()
+    }
+
+    /// 按 `interval` 检查配置文件，变更后自动重新加载；无效配置记录告警并保留旧配置。
+    pub fn watch(&self, interval: Duration) -> JoinHandle<()> {
+        let service = self.clone();
+        tokio::spawn(async move {
+            if service.path().is_none() {
+                return;
+            }
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(err) = service.reload_if_changed() {
+                    warn!(target: "config", %err, "ignoring invalid configuration change");
+                }
+            }
+        })
+    }
+}
+
+fn read_layers(
+    path: Option<&Path>,
+    env: &(dyn Fn(&str) -> Option<String> + Send + Sync),
+) -> Result<FlowwisperConfig> {
+    let mut config = match path.filter(|path| path.exists()) {
+        Some(path) => {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("failed to read config {}", path.display()))?;
+            toml::from_str(&raw)
+                .with_context(|| format!("invalid config file {}", path.display()))?
+        }
+        None => FlowwisperConfig::default(),
+    };
+    config.apply_env(env)?;
+    config.validate()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn service(path: &Path, vars: &[(&str, &str)]) -> Result<ConfigService> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        ConfigService::with_env(
+            Some(path.to_path_buf()),
+            Arc::new(move |name: &str| vars.get(name).cloned()),
+        )
+    }
+
+    #[test]
+    fn layers_defaults_file_and_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+
+        let defaults = service(&path, &[]).unwrap().current();
+        assert_eq!(*defaults, FlowwisperConfig::default());
+        assert!(defaults.sync_config().is_none());
+
+        fs::write(
+            &path,
+            r#"
+[engine]
+prefer_cloud = true
+
+[polisher]
+provider = "anthropic"
+timeout_ms = 1500
+
+[session]
+max_session_secs = 0
+checkpoint_secs = 0
+
+[sync]
+folder = "/tmp/flowwisper-sync"
+secret = "from-file"
+"#,
+        )
+        .unwrap();
+        let config = service(
+            &path,
+            &[
+                ("FLOWWISPER_SYNC_SECRET", "from-env"),
+                ("ANTHROPIC_API_KEY", "sk-test"),
+                (PREFER_CLOUD_ENV, "false"),
+            ],
+        )
+        .unwrap()
+        .current();
+        assert!(!config.engine_config().prefer_cloud);
+        assert_eq!(config.max_session_duration(), None);
+        assert_eq!(config.checkpoint_interval(), None);
+        assert_eq!(config.preroll_window(), defaults.preroll_window());
+        let polisher = config.polisher_config().unwrap();
+        assert_eq!(polisher.timeout, Duration::from_millis(1500));
+        assert_eq!(polisher.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(config.sync_config().unwrap().secret, "from-env");
+        assert!(!format!("{config:?}").contains("sk-test"));
+
+        assert!(config.network_monitor_config().is_none());
+        let hybrid = service(
+            &path,
+            &[
+                (HYBRID_ENGINE_ENV, "true"),
+                (CLOUD_ENDPOINT_ENV, "https://asr.example.com/v1"),
+            ],
+        )
+        .unwrap()
+        .current();
+        assert_eq!(
+            hybrid.network_monitor_config().unwrap().endpoint,
+            "https://asr.example.com/v1"
+        );
+        assert!(service(&path, &[(HYBRID_ENGINE_ENV, "true")]).is_err());
+
+        assert!(service(&path, &[(MAX_SESSION_SECS_ENV, "soon")]).is_err());
+        fs::write(&path, "[telemetry]\nendpoint = \"http://insecure\"\n").unwrap();
+        assert!(service(&path, &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn reloads_changed_files_and_keeps_last_good_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, "[session]\nmax_session_secs = 60\n").unwrap();
+        let config = service(&path, &[]).unwrap();
+        let mut changes = config.subscribe();
+        assert!(config.reload_if_changed().unwrap().is_empty());
+
+        fs::write(
+            &path,
+            "[session]\nmax_session_secs = 120\n\n[engine]\nprefer_cloud = false\n",
+        )
+        .unwrap();
+        let watcher = config.watch(Duration::from_millis(10));
+        let change = tokio::time::timeout(Duration::from_secs(2), changes.recv())
+            .await
+            .expect("change should be broadcast")
+            .unwrap();
+        assert_eq!(change.changed, vec![ConfigSection::Session]);
+        assert!(change.changed[0].is_live());
+        assert_eq!(
+            config.current().max_session_duration(),
+            Some(Duration::from_secs(120))
+        );
+
+        fs::write(&path, "[session\n").unwrap();
+        assert!(config.reload().is_err());
+        assert_eq!(config.current().session.max_session_secs, 120);
+        watcher.abort();
+    }
+
+    #[test]
+    fn reload_rejects_configs_that_violate_the_tenant_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, "[engine]\nprefer_cloud = false\n").unwrap();
+        let config = service(&path, &[]).unwrap();
+        config.enforce_policy(Some(Arc::new(TenantPolicy {
+            allowed_engines: Some(vec!["local".into()]),
+            ..TenantPolicy::default()
+        })));
+
+        fs::write(&path, "[engine]\nprefer_cloud = true\n").unwrap();
+        let err = config.reload().unwrap_err();
+        assert!(err.downcast_ref::<PolicyError>().is_some(), "{err}");
+        assert!(!config.current().engine.prefer_cloud);
+
+        config.enforce_policy(None);
+        assert_eq!(config.reload().unwrap(), vec![ConfigSection::Engine]);
+        assert!(config.current().engine.prefer_cloud);
+    }
+}
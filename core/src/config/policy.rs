@@ -0,0 +1,417 @@
+//! 组织（租户）策略：由组织私钥签名的策略文档限定可用引擎、数据保留上限、遥测与云端区域。
+//!
+//! 策略只能收紧本地配置：加载后在编排器的云端开关与遥测上传处强制执行，
+//! 与策略冲突的配置被拒绝而不是静默改写。
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::FlowwisperConfig;
+use crate::orchestrator::{is_local_endpoint, CloudFeature, EngineRoute, LlmProvider};
+use crate::session::history::HISTORY_RETENTION_HOURS;
+
+/// 策略文档路径。
+pub const POLICY_PATH_ENV: &str = "FLOWWISPER_POLICY";
+/// 校验策略签名的 Ed25519 公钥（Base64）。
+pub const POLICY_PUBLIC_KEY_ENV: &str = "FLOWWISPER_POLICY_PUBLIC_KEY";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PolicyError {
+    #[error("策略文档无效: {0}")]
+    Malformed(String),
+    #[error("策略签名校验失败")]
+    InvalidSignature,
+    #[error("策略已于 {0} 过期")]
+    Expired(u64),
+    #[error("配置违反组织策略: {}", .0.join("; "))]
+    Violation(Vec<String>),
+}
+
+/// 组织下发的策略；未设置的限制项表示不限制。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TenantPolicy {
+    pub tenant_id: String,
+    /// 允许的识别引擎（`local`、`cloud`）与润色服务（如 `openai`、`llama_cpp`）。
+    pub allowed_engines: Option<Vec<String>>,
+    /// 数据可保留的最长小时数：超过此时长的本地历史（含置顶条目）被删除，备份保留跨度超出时拒绝配置。
+    pub max_retention_hours: Option<u64>,
+    pub telemetry_opt_out: bool,
+    /// 要求记录外发审计：云端识别、润色、翻译、Webhook 与遥测上传。
+    pub audit_egress: bool,
+    /// 允许处理与存放数据的云端区域，如 `eu-central-1`；限定后云端识别、远程润色与 S3 备份
+    /// 都须声明所在区域。
+    pub cloud_regions: Option<Vec<String>>,
+    /// 过期时间（Unix 毫秒），过期的策略拒绝加载。
+    pub expires_at_ms: Option<u64>,
+}
+
+impl TenantPolicy {
+    /// 本地历史的保留上限，内置保留时长不超过上限时为 `None`。
+    pub fn history_retention_cap(&self) -> Option<Duration> {
+        self.max_retention_hours
+            .filter(|cap| *cap < HISTORY_RETENTION_HOURS as u64)
+            .map(|cap| Duration::from_secs(cap * 3_600))
+    }
+
+    pub fn allows_engine(&self, engine: &str) -> bool {
+        self.allowed_engines.as_ref().is_none_or(|allowed| {
+            allowed
+                .iter()
+                .any(|item| item.trim().eq_ignore_ascii_case(engine))
+        })
+    }
+
+    pub fn allows_cloud(&self) -> bool {
+        self.allows_engine(EngineRoute::Cloud.as_str())
+    }
+
+    pub fn allows_region(&self, region: &str) -> bool {
+        self.cloud_regions.as_ref().is_none_or(|allowed| {
+            allowed
+                .iter()
+                .any(|item| item.trim().eq_ignore_ascii_case(region.trim()))
+        })
+    }
+
+    /// 服务声明的区域是否符合策略；限定区域时未声明视为不符合。
+    fn allows_declared_region(&self, region: Option<&str>) -> bool {
+        self.cloud_regions.is_none() || region.is_some_and(|region| self.allows_region(region))
+    }
+
+    /// 按服务声明的区域，配置中不得使用的云端能力，供编排器在云端出口拦截。
+    pub fn region_blocked_features(&self, config: &FlowwisperConfig) -> Vec<CloudFeature> {
+        let mut blocked = Vec::new();
+        if !self.allows_declared_region(config.engine.cloud_region.as_deref()) {
+            blocked.push(CloudFeature::Transcription);
+        }
+        let remote_polisher = config
+            .polisher_config()
+            .is_some_and(|polisher| !is_local_endpoint(&polisher.endpoint));
+        if remote_polisher && !self.allows_declared_region(config.polisher.region.as_deref()) {
+            blocked.push(CloudFeature::Polish);
+        }
+        blocked
+    }
+
+    /// 列出配置中违反本策略的项，为空表示符合。
+    pub fn violations(&self, config: &FlowwisperConfig) -> Vec<String> {
+        let mut violations = Vec::new();
+        if config.engine.prefer_cloud && !self.allows_cloud() {
+            violations.push("engine.prefer_cloud 要求云端识别，策略未允许".to_string());
+        }
+        if config.engine.hybrid && !self.allows_cloud() {
+            violations.push("engine.hybrid 会切换到云端识别，策略未允许".to_string());
+        }
+        if config.engine.prefer_cloud
+            && self.allows_cloud()
+            && !self.allows_declared_region(config.engine.cloud_region.as_deref())
+        {
+            violations.push("engine.cloud_region 未声明或不在允许的区域内".to_string());
+        }
+        if let Some(provider) = config
+            .polisher
+            .provider
+            .as_deref()
+            .and_then(LlmProvider::parse)
+        {
+            if !self.allows_engine(provider.as_str()) {
+                violations.push(format!(
+                    "polisher.provider {} 不在允许的引擎内",
+                    provider.as_str()
+                ));
+            } else if self
+                .region_blocked_features(config)
+                .contains(&CloudFeature::Polish)
+            {
+                violations.push("polisher.region 未声明或不在允许的区域内".to_string());
+            }
+        }
+        if self.telemetry_opt_out && config.telemetry.endpoint.is_some() {
+            violations.push("策略要求退出遥测，但配置了 telemetry.endpoint".to_string());
+        }
+        if let Some(region) = &config.backup.s3_region {
+            if config.backup.s3_bucket.is_some() && !self.allows_region(region) {
+                violations.push(format!("backup.s3_region {region} 不在允许的区域内"));
+            }
+        }
+        if let Some(cap) = self.max_retention_hours {
+            if let Some(backup) = config.backup_config() {
+                let span_hours = backup.interval.as_secs() * backup.retain as u64 / 3_600;
+                if span_hours > cap {
+                    violations.push(format!(
+                        "备份保留约 {span_hours} 小时（retain × interval_secs），超过上限 {cap} 小时"
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// 带签名的策略文档。`policy` 是组织写出的策略 JSON 原文（Base64），签名直接覆盖这些字节，
+/// 校验通过后才解析，因此任何工具签出的文档、带有新字段的文档都能按原样校验。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTenantPolicy {
+    pub policy: String,
+    pub signature: String,
+}
+
+impl SignedTenantPolicy {
+    /// 签名前的策略原文。
+    pub fn policy_bytes(&self) -> Result<Vec<u8>, PolicyError> {
+        BASE64
+            .decode(self.policy.trim())
+            .map_err(|err| PolicyError::Malformed(format!("策略原文无法解码: {err}")))
+    }
+}
+
+/// 校验并持有当前生效的组织策略。
+pub struct PolicyEngine {
+    public_key: Vec<u8>,
+    current: RwLock<Option<Arc<TenantPolicy>>>,
+}
+
+impl fmt::Debug for PolicyEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolicyEngine")
+            .field("policy", &self.policy())
+            .finish()
+    }
+}
+
+impl PolicyEngine {
+    pub fn new(public_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            public_key: public_key.into(),
+            current: RwLock::new(None),
+        }
+    }
+
+    /// 按环境变量加载策略；未设置策略路径时返回 `None`。
+    pub fn from_env() -> Result<Option<Self>, PolicyError> {
+        let Ok(path) = std::env::var(POLICY_PATH_ENV) else {
+            return Ok(None);
+        };
+        let key = std::env::var(POLICY_PUBLIC_KEY_ENV)
+            .map_err(|_| PolicyError::Malformed(format!("缺少 {POLICY_PUBLIC_KEY_ENV}")))?;
+        let key = BASE64
+            .decode(key.trim())
+            .map_err(|err| PolicyError::Malformed(format!("公钥无法解码: {err}")))?;
+        let engine = Self::new(key);
+        engine.load_file(Path::new(&path))?;
+        Ok(Some(engine))
+    }
+
+    pub fn policy(&self) -> Option<Arc<TenantPolicy>> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 校验签名与有效期后替换当前策略；校验失败时保留原策略。
+    pub fn load(&self, document: SignedTenantPolicy) -> Result<Arc<TenantPolicy>, PolicyError> {
+        let signed = document.policy_bytes()?;
+        let signature = BASE64
+            .decode(document.signature.trim())
+            .map_err(|_| PolicyError::InvalidSignature)?;
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(&signed, &signature)
+            .map_err(|_| PolicyError::InvalidSignature)?;
+        let policy: TenantPolicy = serde_json::from_slice(&signed)
+            .map_err(|err| PolicyError::Malformed(err.to_string()))?;
+        if let Some(expires_at_ms) = policy.expires_at_ms {
+            if now_ms() >= expires_at_ms {
+                return Err(PolicyError::Expired(expires_at_ms));
+            }
+        }
+
+        let policy = Arc::new(policy);
+        *self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::clone(&policy));
+        tracing::info!(
+            target: "policy_engine",
+            tenant = %policy.tenant_id,
+            cloud = policy.allows_cloud(),
+            telemetry_opt_out = policy.telemetry_opt_out,
+            "tenant policy loaded"
+        );
+        Ok(policy)
+    }
+
+    pub fn load_file(&self, path: &Path) -> Result<Arc<TenantPolicy>, PolicyError> {
+        let raw = fs::read(path)
+            .map_err(|err| PolicyError::Malformed(format!("{}: {err}", path.display())))?;
+        let document: SignedTenantPolicy =
+            serde_json::from_slice(&raw).map_err(|err| PolicyError::Malformed(err.to_string()))?;
+        self.load(document)
+    }
+
+    /// 没有生效策略时任何配置都通过。
+    pub fn check_config(&self, config: &FlowwisperConfig) -> Result<(), PolicyError> {
+        let Some(policy) = self.policy() else {
+            return Ok(());
+        };
+        let violations = policy.violations(config);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(PolicyError::Violation(violations))
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn keypair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("generate key");
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("parse key")
+    }
+
+    fn sign_bytes(keypair: &Ed25519KeyPair, raw: &[u8]) -> SignedTenantPolicy {
+        SignedTenantPolicy {
+            policy: BASE64.encode(raw),
+            signature: BASE64.encode(keypair.sign(raw).as_ref()),
+        }
+    }
+
+    fn sign(keypair: &Ed25519KeyPair, policy: TenantPolicy) -> SignedTenantPolicy {
+        sign_bytes(
+            keypair,
+            &serde_json::to_vec(&policy).expect("serialize policy"),
+        )
+    }
+
+    #[test]
+    fn loads_only_signed_unexpired_policies() {
+        let keypair = keypair();
+        let engine = PolicyEngine::new(keypair.public_key().as_ref());
+        let policy = TenantPolicy {
+            tenant_id: "acme".into(),
+            allowed_engines: Some(vec!["local".into()]),
+            ..TenantPolicy::default()
+        };
+
+        let mut tampered = sign(&keypair, policy.clone());
+        tampered.policy = BASE64.encode(br#"{"tenantId":"acme"}"#);
+        assert_eq!(engine.load(tampered), Err(PolicyError::InvalidSignature));
+        let foreign = sign(&self::keypair(), policy.clone());
+        assert_eq!(engine.load(foreign), Err(PolicyError::InvalidSignature));
+        assert!(engine.policy().is_none());
+
+        let loaded = engine.load(sign(&keypair, policy.clone())).expect("load");
+        assert!(!loaded.allows_cloud());
+        assert!(loaded.allows_engine("LOCAL"));
+
+        // 其他工具写出的原文：字段顺序、空白与未知字段都不影响校验。
+        let foreign_tool = br#"{ "allowedEngines": ["local"], "tenantId": "globex",
+            "futureField": {"nested": true} }"#;
+        let loaded = engine
+            .load(sign_bytes(&keypair, foreign_tool))
+            .expect("load raw policy");
+        assert_eq!(loaded.tenant_id, "globex");
+        assert!(!loaded.allows_cloud());
+        engine.load(sign(&keypair, policy.clone())).expect("reload");
+
+        let expired = TenantPolicy {
+            expires_at_ms: Some(1),
+            ..policy
+        };
+        assert_eq!(
+            engine.load(sign(&keypair, expired)),
+            Err(PolicyError::Expired(1))
+        );
+        assert_eq!(
+            engine.policy().expect("previous policy kept").tenant_id,
+            "acme"
+        );
+    }
+
+    #[test]
+    fn rejects_configs_that_exceed_the_policy() {
+        let keypair = keypair();
+        let engine = PolicyEngine::new(keypair.public_key().as_ref());
+        let mut config = FlowwisperConfig::default();
+        config.engine.prefer_cloud = true;
+        config.polisher.provider = Some("openai".into());
+        config.telemetry.endpoint = Some("https://collector.example/v1/events".into());
+        config.backup.s3_bucket = Some("archive".into());
+        config.backup.s3_region = Some("us-east-1".into());
+        assert_eq!(engine.check_config(&config), Ok(()));
+
+        engine
+            .load(sign(
+                &keypair,
+                TenantPolicy {
+                    tenant_id: "acme".into(),
+                    allowed_engines: Some(vec!["local".into(), "llama_cpp".into()]),
+                    max_retention_hours: Some(24),
+                    telemetry_opt_out: true,
+                    audit_egress: true,
+                    cloud_regions: Some(vec!["eu-central-1".into()]),
+                    expires_at_ms: None,
+                },
+            ))
+            .expect("load");
+        match engine.check_config(&config) {
+            Err(PolicyError::Violation(violations)) => assert_eq!(violations.len(), 4),
+            other => panic!("expected violations, got {other:?}"),
+        }
+
+        let mut compliant = FlowwisperConfig::default();
+        compliant.polisher.provider = Some("llama_cpp".into());
+        assert_eq!(engine.check_config(&compliant), Ok(()));
+        assert_eq!(
+            engine.policy().expect("policy").history_retention_cap(),
+            Some(Duration::from_secs(24 * 3_600))
+        );
+
+        // 限定区域后，云端识别与远程润色须声明允许的区域。
+        let mut regional = FlowwisperConfig::default();
+        regional.polisher.provider = Some("llama_cpp".into());
+        regional.polisher.endpoint = Some("https://llm.example/v1/chat/completions".into());
+        let policy = engine.policy().expect("policy");
+        assert_eq!(
+            policy.region_blocked_features(&regional),
+            vec![CloudFeature::Transcription, CloudFeature::Polish]
+        );
+        match engine.check_config(&regional) {
+            Err(PolicyError::Violation(violations)) => assert_eq!(violations.len(), 1),
+            other => panic!("expected region violation, got {other:?}"),
+        }
+        regional.polisher.region = Some("EU-Central-1".into());
+        regional.engine.cloud_region = Some("eu-central-1".into());
+        assert!(policy.region_blocked_features(&regional).is_empty());
+        assert_eq!(engine.check_config(&regional), Ok(()));
+
+        // 默认每天备份、保留 7 份，跨度超过 24 小时上限。
+        compliant.backup.folder = Some("/backups".into());
+        compliant.backup.passphrase = Some("secret".into());
+        match engine.check_config(&compliant) {
+            Err(PolicyError::Violation(violations)) => assert_eq!(violations.len(), 1),
+            other => panic!("expected retention violation, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,646 @@
+//! 配置结构与分层规则：内置默认值 → 用户 TOML 文件 → 环境变量。
+//!
+//! 文件中缺失的字段取默认值；环境变量沿用各子系统原有的变量名，优先级最高。
+
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AgcConfig;
+use crate::orchestrator::budget::DEFAULT_WARN_RATIO;
+use crate::orchestrator::{
+    Accelerator, BudgetCaps, EngineConfig, EngineTuning, HardwareProfile, LlmPolisherConfig,
+    LlmProvider, NetworkMonitorConfig, Quantization, RedactionMode, RedactionPattern, Redactor,
+    TuningOverride,
+};
+use crate::persistence::backup::{
+    BackupConfig, BackupTarget, S3Settings, BACKUP_FOLDER_ENV, BACKUP_PASSPHRASE_ENV,
+    BACKUP_S3_SECRET_KEY_ENV, DEFAULT_BACKUP_INTERVAL_SECS, DEFAULT_BACKUP_RETAIN,
+};
+use crate::persistence::sync::{
+    SyncConfig, SyncTarget, DEFAULT_SYNC_INTERVAL_SECS, SYNC_FOLDER_ENV, SYNC_SECRET_ENV,
+    SYNC_WEBDAV_PASSWORD_ENV, SYNC_WEBDAV_URL_ENV, SYNC_WEBDAV_USER_ENV,
+};
+use crate::power::GovernorConfig;
+use crate::session::{DEFAULT_CHECKPOINT_SECS, DEFAULT_MAX_SESSION_SECS, DEFAULT_PREROLL_MS};
+use crate::telemetry::uploader::{TelemetryUploadConfig, TELEMETRY_ENDPOINT_ENV};
+
+/// 脱敏后敏感字段的占位值。
+const REDACTED: &str = "<redacted>";
+/// 预录窗口上限，过长会让每次按键都补发大量旧音频。
+const MAX_PREROLL_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowwisperConfig {
+    pub engine: EngineSection,
+    pub polisher: PolisherSection,
+    pub session: SessionSection,
+    pub telemetry: TelemetrySection,
+    pub sync: SyncSection,
+    pub backup: BackupSection,
+    pub redaction: RedactionSection,
+    pub budget: BudgetSection,
+    pub power: PowerSection,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineSection {
+    pub prefer_cloud: bool,
+    /// 混合模式：按到 `cloud_endpoint` 的网络质量在本地与云端引擎间切换。
+    pub hybrid: bool,
+    /// 云端识别服务地址，混合模式据此探测时延与丢包。
+    pub cloud_endpoint: Option<String>,
+    /// 云端识别服务处理数据的区域，如 `eu-central-1`；组织策略限定区域时必须填写。
+    pub cloud_region: Option<String>,
+    /// 本地模型目录；为空时使用数据目录下的默认位置。
+    pub model_dir: Option<PathBuf>,
+    /// 以下覆盖启动时按硬件自动选择的本地引擎配置。`cpu`、`metal`、`cuda` 或 `directml`。
+    pub accelerator: Option<String>,
+    pub threads: Option<usize>,
+    /// `f16`、`q8_0` 或 `q5_1`。
+    pub quantization: Option<String>,
+    /// 卸载到 GPU 的层数，0 表示纯 CPU。
+    pub gpu_layers: Option<u32>,
+}
+
+/// 大模型润色器；未配置 `provider` 时使用内置润色器。
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolisherSection {
+    pub provider: Option<String>,
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+    pub api_key: Option<String>,
+    pub timeout_ms: Option<u64>,
+    /// 远程润色端点处理数据的区域；组织策略限定区域时必须填写，本机端点无需填写。
+    pub region: Option<String>,
+}
+
+impl fmt::Debug for PolisherSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolisherSection")
+            .field("provider", &self.provider)
+            .field("endpoint", &self.endpoint)
+            .field("model", &self.model)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("timeout_ms", &self.timeout_ms)
+            .field("region", &self.region)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionSection {
+    /// 单次会话的最长录音秒数，0 表示不限制。
+    pub max_session_secs: u64,
+    pub preroll_ms: u64,
+    /// 进行中会话写入检查点的间隔秒数，0 表示不写入。
+    pub checkpoint_secs: u64,
+    /// 加密保存每次会话的原始音频，供历史记录回放；默认关闭。
+    pub record_audio: bool,
+    /// 对送往识别引擎的音频启用自动增益控制；默认关闭。
+    pub auto_gain: bool,
+}
+
+impl Default for SessionSection {
+    fn default() -> Self {
+        Self {
+            max_session_secs: DEFAULT_MAX_SESSION_SECS,
+            preroll_ms: DEFAULT_PREROLL_MS,
+            checkpoint_secs: DEFAULT_CHECKPOINT_SECS,
+            record_audio: false,
+            auto_gain: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySection {
+    /// 上报地址，仅接受 HTTPS；为空时遥测只保存在本地。
+    pub endpoint: Option<String>,
+    pub batch_size: usize,
+    pub interval_secs: u64,
+    pub offline_queue_cap: i64,
+}
+
+impl Default for TelemetrySection {
+    fn default() -> Self {
+        let defaults = TelemetryUploadConfig::default();
+        Self {
+            endpoint: None,
+            batch_size: defaults.batch_size,
+            interval_secs: defaults.interval.as_secs(),
+            offline_queue_cap: defaults.offline_queue_cap,
+        }
+    }
+}
+
+/// 多设备历史同步；`folder` 优先于 WebDAV，两者都需要 `secret`。
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncSection {
+    pub folder: Option<PathBuf>,
+    pub webdav_url: Option<String>,
+    pub webdav_user: Option<String>,
+    pub webdav_password: Option<String>,
+    pub secret: Option<String>,
+    pub interval_secs: u64,
+}
+
+impl Default for SyncSection {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            webdav_url: None,
+            webdav_user: None,
+            webdav_password: None,
+            secret: None,
+            interval_secs: DEFAULT_SYNC_INTERVAL_SECS,
+        }
+    }
+}
+
+impl fmt::Debug for SyncSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |value: &Option<String>| value.as_ref().map(|_| "<redacted>");
+        f.debug_struct("SyncSection")
+            .field("folder", &self.folder)
+            .field("webdav_url", &self.webdav_url)
+            .field("webdav_user", &self.webdav_user)
+            .field("webdav_password", &redacted(&self.webdav_password))
+            .field("secret", &redacted(&self.secret))
+            .field("interval_secs", &self.interval_secs)
+            .finish()
+    }
+}
+
+/// 历史库加密备份；目标按文件夹、WebDAV、S3 的顺序选取，均需要 `passphrase`。
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSection {
+    pub folder: Option<PathBuf>,
+    pub webdav_url: Option<String>,
+    pub webdav_user: Option<String>,
+    pub webdav_password: Option<String>,
+    pub s3_endpoint: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_region: Option<String>,
+    pub s3_prefix: String,
+    pub s3_access_key: Option<String>,
+    pub s3_secret_key: Option<String>,
+    pub passphrase: Option<String>,
+    pub interval_secs: u64,
+    /// 目标上保留的最近备份份数。
+    pub retain: usize,
+}
+
+impl Default for BackupSection {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            webdav_url: None,
+            webdav_user: None,
+            webdav_password: None,
+            s3_endpoint: None,
+            s3_bucket: None,
+            s3_region: None,
+            s3_prefix: String::new(),
+            s3_access_key: None,
+            s3_secret_key: None,
+            passphrase: None,
+            interval_secs: DEFAULT_BACKUP_INTERVAL_SECS,
+            retain: DEFAULT_BACKUP_RETAIN,
+        }
+    }
+}
+
+impl fmt::Debug for BackupSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |value: &Option<String>| value.as_ref().map(|_| "<redacted>");
+        f.debug_struct("BackupSection")
+            .field("folder", &self.folder)
+            .field("webdav_url", &self.webdav_url)
+            .field("webdav_user", &self.webdav_user)
+            .field("webdav_password", &redacted(&self.webdav_password))
+            .field("s3_endpoint", &self.s3_endpoint)
+            .field("s3_bucket", &self.s3_bucket)
+            .field("s3_region", &self.s3_region)
+            .field("s3_prefix", &self.s3_prefix)
+            .field("s3_access_key", &self.s3_access_key)
+            .field("s3_secret_key", &redacted(&self.s3_secret_key))
+            .field("passphrase", &redacted(&self.passphrase))
+            .field("interval_secs", &self.interval_secs)
+            .field("retain", &self.retain)
+            .finish()
+    }
+}
+
+/// 敏感信息脱敏；未设置 `mode` 时不识别。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSection {
+    /// `mask`、`strip` 或 `annotate`。
+    pub mode: Option<String>,
+    /// 追加在内置规则之后的自定义规则。
+    pub patterns: Vec<RedactionPattern>,
+}
+
+/// 云端用量上限；超出后当期改用本地处理，未设置的项不限制。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetSection {
+    pub daily_cloud_secs: Option<u64>,
+    pub monthly_cloud_secs: Option<u64>,
+    pub daily_polish_tokens: Option<u64>,
+    pub monthly_polish_tokens: Option<u64>,
+    /// 用量达到上限的该比例时提醒一次。
+    pub warn_ratio: f64,
+}
+
+impl Default for BudgetSection {
+    fn default() -> Self {
+        Self {
+            daily_cloud_secs: None,
+            monthly_cloud_secs: None,
+            daily_polish_tokens: None,
+            monthly_polish_tokens: None,
+            warn_ratio: DEFAULT_WARN_RATIO,
+        }
+    }
+}
+
+/// 电源调节：用电池或设备过热时降低本地识别强度。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerSection {
+    pub governor: bool,
+    /// 电量低于该百分比时进入最省电档。
+    pub low_battery_percent: u8,
+    /// 降档期间新会话优先使用云端识别。
+    pub prefer_cloud_on_battery: bool,
+}
+
+impl Default for PowerSection {
+    fn default() -> Self {
+        let defaults = GovernorConfig::default();
+        Self {
+            governor: defaults.enabled,
+            low_battery_percent: defaults.low_battery_percent,
+            prefer_cloud_on_battery: defaults.prefer_cloud_on_battery,
+        }
+    }
+}
+
+/// 设置后覆盖引擎的云端优先开关。
+pub const PREFER_CLOUD_ENV: &str = "FLOWWISPER_PREFER_CLOUD";
+pub const HYBRID_ENGINE_ENV: &str = "FLOWWISPER_HYBRID_ENGINE";
+pub const CLOUD_ENDPOINT_ENV: &str = "FLOWWISPER_CLOUD_ENDPOINT";
+pub const MODEL_DIR_ENV: &str = "FLOWWISPER_MODEL_DIR";
+pub const MAX_SESSION_SECS_ENV: &str = "FLOWWISPER_MAX_SESSION_SECS";
+const POLISHER_PROVIDER_ENV: &str = "FLOWWISPER_POLISHER_PROVIDER";
+const POLISHER_ENDPOINT_ENV: &str = "FLOWWISPER_POLISHER_ENDPOINT";
+const POLISHER_MODEL_ENV: &str = "FLOWWISPER_POLISHER_MODEL";
+const POLISHER_API_KEY_ENV: &str = "FLOWWISPER_POLISHER_API_KEY";
+const POLISHER_TIMEOUT_ENV: &str = "FLOWWISPER_POLISHER_TIMEOUT_MS";
+pub const REDACTION_MODE_ENV: &str = "FLOWWISPER_REDACTION_MODE";
+
+fn parse_env<T: std::str::FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("invalid value {value:?} for {name}"))
+}
+
+impl FlowwisperConfig {
+    /// 用环境变量覆盖已加载的配置；空值视为未设置。
+    pub fn apply_env(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> Result<()> {
+        let read = |name: &str| lookup(name).filter(|value| !value.trim().is_empty());
+        if let Some(value) = read(PREFER_CLOUD_ENV) {
+            self.engine.prefer_cloud = parse_env(PREFER_CLOUD_ENV, &value)?;
+        }
+        if let Some(value) = read(HYBRID_ENGINE_ENV) {
+            self.engine.hybrid = parse_env(HYBRID_ENGINE_ENV, &value)?;
+        }
+        if let Some(value) = read(CLOUD_ENDPOINT_ENV) {
+            self.engine.cloud_endpoint = Some(value.trim().to_string());
+        }
+        if let Some(value) = read(MODEL_DIR_ENV) {
+            self.engine.model_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = read(POLISHER_PROVIDER_ENV) {
+            self.polisher.provider = Some(value);
+        }
+        if let Some(value) = read(POLISHER_ENDPOINT_ENV) {
+            self.polisher.endpoint = Some(value);
+        }
+        if let Some(value) = read(POLISHER_MODEL_ENV) {
+            self.polisher.model = Some(value);
+        }
+        if let Some(value) = read(POLISHER_API_KEY_ENV) {
+            self.polisher.api_key = Some(value);
+        }
+        if let Some(value) = read(POLISHER_TIMEOUT_ENV) {
+            self.polisher.timeout_ms = Some(parse_env(POLISHER_TIMEOUT_ENV, &value)?);
+        }
+        if self.polisher.api_key.is_none() {
+            // 与 `LlmPolisherConfig::from_env` 一致，回退到服务商的标准变量。
+            self.polisher.api_key = self
+                .polisher
+                .provider
+                .as_deref()
+                .and_then(LlmProvider::parse)
+                .and_then(|provider| provider.api_key_env())
+                .and_then(read);
+        }
+        if let Some(value) = read(MAX_SESSION_SECS_ENV) {
+            self.session.max_session_secs = parse_env(MAX_SESSION_SECS_ENV, &value)?;
+        }
+        if let Some(value) = read(TELEMETRY_ENDPOINT_ENV) {
+            self.telemetry.endpoint = Some(value.trim().to_string());
+        }
+        if let Some(value) = read(SYNC_FOLDER_ENV) {
+            self.sync.folder = Some(PathBuf::from(value.trim()));
+        }
+        if let Some(value) = read(SYNC_WEBDAV_URL_ENV) {
+            self.sync.webdav_url = Some(value.trim().to_string());
+        }
+        if let Some(value) = read(SYNC_WEBDAV_USER_ENV) {
+            self.sync.webdav_user = Some(value.trim().to_string());
+        }
+        if let Some(value) = lookup(SYNC_WEBDAV_PASSWORD_ENV) {
+            self.sync.webdav_password = Some(value);
+        }
+        if let Some(value) = read(SYNC_SECRET_ENV) {
+            self.sync.secret = Some(value.trim().to_string());
+        }
+        if let Some(value) = read(BACKUP_FOLDER_ENV) {
+            self.backup.folder = Some(PathBuf::from(value.trim()));
+        }
+        if let Some(value) = lookup(BACKUP_PASSPHRASE_ENV) {
+            self.backup.passphrase = Some(value);
+        }
+        if let Some(value) = read(BACKUP_S3_SECRET_KEY_ENV) {
+            self.backup.s3_secret_key = Some(value.trim().to_string());
+        }
+        if let Some(value) = read(REDACTION_MODE_ENV) {
+            self.redaction.mode = Some(value.trim().to_string());
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(accelerator) = &self.engine.accelerator {
+            if accelerator != "cpu" && Accelerator::parse(accelerator).is_none() {
+                return Err(anyhow!("unknown engine accelerator {accelerator:?}"));
+            }
+        }
+        if self.engine.hybrid && self.engine.cloud_endpoint.is_none() {
+            return Err(anyhow!("engine.hybrid requires engine.cloud_endpoint"));
+        }
+        if self.engine.threads == Some(0) {
+            return Err(anyhow!("engine.threads must be positive"));
+        }
+        if let Some(quantization) = &self.engine.quantization {
+            if Quantization::parse(quantization).is_none() {
+                return Err(anyhow!("unknown engine quantization {quantization:?}"));
+            }
+        }
+        if let Some(provider) = &self.polisher.provider {
+            if LlmProvider::parse(provider).is_none() {
+                return Err(anyhow!("unknown polisher provider {provider:?}"));
+            }
+        }
+        if self.polisher.timeout_ms == Some(0) {
+            return Err(anyhow!("polisher.timeout_ms must be positive"));
+        }
+        if self.session.preroll_ms > MAX_PREROLL_MS {
+            return Err(anyhow!(
+                "session.preroll_ms must not exceed {MAX_PREROLL_MS}"
+            ));
+        }
+        if let Some(endpoint) = &self.telemetry.endpoint {
+            if !endpoint.starts_with("https://") {
+                return Err(anyhow!("telemetry.endpoint must use https"));
+            }
+        }
+        if self.telemetry.batch_size == 0 || self.telemetry.interval_secs == 0 {
+            return Err(anyhow!(
+                "telemetry.batch_size and telemetry.interval_secs must be positive"
+            ));
+        }
+        if self.telemetry.offline_queue_cap < 0 {
+            return Err(anyhow!("telemetry.offline_queue_cap must not be negative"));
+        }
+        let sync_target = self.sync.folder.is_some() || self.sync.webdav_url.is_some();
+        if sync_target && self.sync.secret.is_none() {
+            return Err(anyhow!("sync target configured without sync.secret"));
+        }
+        if self.sync.interval_secs == 0 {
+            return Err(anyhow!("sync.interval_secs must be positive"));
+        }
+        let backup = &self.backup;
+        let backup_target =
+            backup.folder.is_some() || backup.webdav_url.is_some() || backup.s3_bucket.is_some();
+        if backup_target && backup.passphrase.is_none() {
+            return Err(anyhow!(
+                "backup target configured without backup.passphrase"
+            ));
+        }
+        if backup.s3_bucket.is_some()
+            && (backup.s3_endpoint.is_none()
+                || backup.s3_region.is_none()
+                || backup.s3_access_key.is_none()
+                || backup.s3_secret_key.is_none())
+        {
+            return Err(anyhow!(
+                "backup.s3_bucket requires s3_endpoint, s3_region, s3_access_key and s3_secret_key"
+            ));
+        }
+        if backup.interval_secs == 0 || backup.retain == 0 {
+            return Err(anyhow!(
+                "backup.interval_secs and backup.retain must be positive"
+            ));
+        }
+        if let Some(mode) = &self.redaction.mode {
+            let mode = RedactionMode::parse(mode)
+                .ok_or_else(|| anyhow!("unknown redaction mode {mode:?}"))?;
+            Redactor::new(mode, &self.redaction.patterns)?;
+        }
+        if !(self.budget.warn_ratio > 0.0 && self.budget.warn_ratio <= 1.0) {
+            return Err(anyhow!("budget.warn_ratio must be in (0, 1]"));
+        }
+        if self.power.low_battery_percent > 100 {
+            return Err(anyhow!("power.low_battery_percent must be at most 100"));
+        }
+        Ok(())
+    }
+
+    pub fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            prefer_cloud: self.engine.prefer_cloud,
+        }
+    }
+
+    /// 混合模式的网络监测配置；未启用混合模式时为空。
+    pub fn network_monitor_config(&self) -> Option<NetworkMonitorConfig> {
+        if !self.engine.hybrid {
+            return None;
+        }
+        Some(NetworkMonitorConfig {
+            endpoint: self.engine.cloud_endpoint.clone()?,
+            ..NetworkMonitorConfig::default()
+        })
+    }
+
+    /// 在自动探测的结果上叠加 `[engine]` 中的手动覆盖。
+    pub fn engine_tuning(&self, hardware: &HardwareProfile) -> EngineTuning {
+        let engine = &self.engine;
+        EngineTuning::select(hardware).with_override(&TuningOverride {
+            // `cpu` 解析为无加速器，即强制纯 CPU。
+            accelerator: engine.accelerator.as_deref().map(Accelerator::parse),
+            threads: engine.threads,
+            quantization: engine.quantization.as_deref().and_then(Quantization::parse),
+            gpu_layers: engine.gpu_layers,
+        })
+    }
+
+    pub fn polisher_config(&self) -> Option<LlmPolisherConfig> {
+        let provider = LlmProvider::parse(self.polisher.provider.as_deref()?)?;
+        let mut config = LlmPolisherConfig::new(provider);
+        if let Some(endpoint) = &self.polisher.endpoint {
+            config.endpoint = endpoint.clone();
+        }
+        if let Some(model) = &self.polisher.model {
+            config.model = model.clone();
+        }
+        config.api_key = self.polisher.api_key.clone();
+        if let Some(timeout_ms) = self.polisher.timeout_ms {
+            config.timeout = Duration::from_millis(timeout_ms);
+        }
+        Some(config)
+    }
+
+    pub fn max_session_duration(&self) -> Option<Duration> {
+        (self.session.max_session_secs > 0)
+            .then(|| Duration::from_secs(self.session.max_session_secs))
+    }
+
+    pub fn checkpoint_interval(&self) -> Option<Duration> {
+        (self.session.checkpoint_secs > 0)
+            .then(|| Duration::from_secs(self.session.checkpoint_secs))
+    }
+
+    pub fn preroll_window(&self) -> Duration {
+        Duration::from_millis(self.session.preroll_ms)
+    }
+
+    pub fn agc_config(&self) -> Option<AgcConfig> {
+        self.session.auto_gain.then(AgcConfig::default)
+    }
+
+    pub fn telemetry_upload_config(&self) -> TelemetryUploadConfig {
+        TelemetryUploadConfig {
+            endpoint: self.telemetry.endpoint.clone(),
+            batch_size: self.telemetry.batch_size,
+            interval: Duration::from_secs(self.telemetry.interval_secs),
+            offline_queue_cap: self.telemetry.offline_queue_cap,
+            ..TelemetryUploadConfig::default()
+        }
+    }
+
+    /// 未设置脱敏模式或配置无效时返回 `None`。
+    pub fn redactor(&self) -> Option<Redactor> {
+        let mode = RedactionMode::parse(self.redaction.mode.as_deref()?)?;
+        Redactor::new(mode, &self.redaction.patterns).ok()
+    }
+
+    pub fn budget_caps(&self) -> BudgetCaps {
+        BudgetCaps {
+            daily_cloud_secs: self.budget.daily_cloud_secs,
+            monthly_cloud_secs: self.budget.monthly_cloud_secs,
+            daily_polish_tokens: self.budget.daily_polish_tokens,
+            monthly_polish_tokens: self.budget.monthly_polish_tokens,
+            warn_ratio: self.budget.warn_ratio,
+        }
+    }
+
+    /// 把密钥、口令等敏感值替换为占位符的副本，用于诊断包等需要交给他人的场景。
+    pub fn redacted(&self) -> Self {
+        let mask = |value: &mut Option<String>| {
+            if value.is_some() {
+                *value = Some(REDACTED.to_string());
+            }
+        };
+        let mut config = self.clone();
+        mask(&mut config.polisher.api_key);
+        mask(&mut config.sync.webdav_password);
+        mask(&mut config.sync.secret);
+        mask(&mut config.backup.webdav_password);
+        mask(&mut config.backup.s3_access_key);
+        mask(&mut config.backup.s3_secret_key);
+        mask(&mut config.backup.passphrase);
+        config
+    }
+
+    pub fn governor_config(&self) -> GovernorConfig {
+        GovernorConfig {
+            enabled: self.power.governor,
+            low_battery_percent: self.power.low_battery_percent,
+            prefer_cloud_on_battery: self.power.prefer_cloud_on_battery,
+        }
+    }
+
+    /// 未配置同步目标时返回 `None`。
+    pub fn sync_config(&self) -> Option<SyncConfig> {
+        let target = match (&self.sync.folder, &self.sync.webdav_url) {
+            (Some(folder), _) => SyncTarget::Folder(folder.clone()),
+            (None, Some(url)) => SyncTarget::WebDav {
+                url: url.clone(),
+                username: self.sync.webdav_user.clone(),
+                password: self.sync.webdav_password.clone(),
+            },
+            (None, None) => return None,
+        };
+        Some(SyncConfig {
+            target,
+            secret: self.sync.secret.clone()?,
+            interval: Duration::from_secs(self.sync.interval_secs),
+        })
+    }
+
+    /// 未配置备份目标时返回 `None`。
+    pub fn backup_config(&self) -> Option<BackupConfig> {
+        let backup = &self.backup;
+        let target = if let Some(folder) = &backup.folder {
+            BackupTarget::Folder(folder.clone())
+        } else if let Some(url) = &backup.webdav_url {
+            BackupTarget::WebDav {
+                url: url.clone(),
+                username: backup.webdav_user.clone(),
+                password: backup.webdav_password.clone(),
+            }
+        } else {
+            BackupTarget::S3(S3Settings {
+                endpoint: backup.s3_endpoint.clone()?,
+                bucket: backup.s3_bucket.clone()?,
+                region: backup.s3_region.clone()?,
+                prefix: backup.s3_prefix.clone(),
+                access_key: backup.s3_access_key.clone()?,
+                secret_key: backup.s3_secret_key.clone()?,
+            })
+        };
+        Some(BackupConfig {
+            target,
+            passphrase: backup.passphrase.clone()?,
+            interval: Duration::from_secs(backup.interval_secs),
+            retain: backup.retain,
+        })
+    }
+}
@@ -0,0 +1,259 @@
+//! 跨模块的错误分类。对外接口返回 `FlowwisperError`，序列化为 `{ "code", "message" }`，
+//! 前端与遥测按 `code` 分支，无需匹配错误文本。
+
+use std::io;
+
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::config::PolicyError;
+use crate::hotkey::HotkeyError;
+use crate::plugins::PluginError;
+use crate::session::clipboard::ClipboardError;
+use crate::session::publisher::{
+    AutomationError, PublisherError, PublisherFailure, PublisherFailureCode,
+};
+
+pub type FlowwisperResult<T> = std::result::Result<T, FlowwisperError>;
+
+/// 下发给前端与遥测的错误类别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Audio,
+    Engine,
+    Persistence,
+    Publish,
+    Permission,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Audio => "audio",
+            ErrorCode::Engine => "engine",
+            ErrorCode::Persistence => "persistence",
+            ErrorCode::Publish => "publish",
+            ErrorCode::Permission => "permission",
+            ErrorCode::Internal => "internal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FlowwisperError {
+    #[error("audio error: {0}")]
+    Audio(String),
+    #[error("engine error: {0}")]
+    Engine(String),
+    #[error("persistence error: {0}")]
+    Persistence(String),
+    #[error("publish error: {0}")]
+    Publish(String),
+    #[error("permission denied: {0}")]
+    Permission(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl FlowwisperError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match code {
+            ErrorCode::Audio => Self::Audio(message),
+            ErrorCode::Engine => Self::Engine(message),
+            ErrorCode::Persistence => Self::Persistence(message),
+            ErrorCode::Publish => Self::Publish(message),
+            ErrorCode::Permission => Self::Permission(message),
+            ErrorCode::Internal => Self::Internal(message),
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Audio(_) => ErrorCode::Audio,
+            Self::Engine(_) => ErrorCode::Engine,
+            Self::Persistence(_) => ErrorCode::Persistence,
+            Self::Publish(_) => ErrorCode::Publish,
+            Self::Permission(_) => ErrorCode::Permission,
+            Self::Internal(_) => ErrorCode::Internal,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Audio(message)
+            | Self::Engine(message)
+            | Self::Persistence(message)
+            | Self::Publish(message)
+            | Self::Permission(message)
+            | Self::Internal(message) => message,
+        }
+    }
+
+    /// 按错误链中最先出现的已知错误类型归类；都不认识时归为 `fallback`。
+    /// 消息保留完整的上下文链。
+    pub fn classify(err: &anyhow::Error, fallback: ErrorCode) -> Self {
+        let code = err.chain().find_map(code_of).unwrap_or(fallback);
+        Self::new(code, format!("{err:#}"))
+    }
+}
+
+/// 单个错误的类别，不认识的类型返回 `None`。
+fn code_of(err: &(dyn std::error::Error + 'static)) -> Option<ErrorCode> {
+    if let Some(err) = err.downcast_ref::<FlowwisperError>() {
+        return Some(err.code());
+    }
+    if let Some(err) = err.downcast_ref::<PublisherError>() {
+        return Some(publisher_error_code(err));
+    }
+    if let Some(err) = err.downcast_ref::<AutomationError>() {
+        return Some(automation_error_code(err));
+    }
+    if err.is::<ClipboardError>() {
+        return Some(ErrorCode::Publish);
+    }
+    if let Some(err) = err.downcast_ref::<HotkeyError>() {
+        return Some(match err {
+            HotkeyError::PermissionDenied(_) => ErrorCode::Permission,
+            _ => ErrorCode::Internal,
+        });
+    }
+    if let Some(err) = err.downcast_ref::<PolicyError>() {
+        return Some(match err {
+            PolicyError::Violation(_) => ErrorCode::Permission,
+            _ => ErrorCode::Internal,
+        });
+    }
+    if err.is::<PluginError>() {
+        return Some(ErrorCode::Engine);
+    }
+    if err.is::<rusqlite::Error>() || err.is::<r2d2::Error>() {
+        return Some(ErrorCode::Persistence);
+    }
+    if let Some(err) = err.downcast_ref::<io::Error>() {
+        if err.kind() == io::ErrorKind::PermissionDenied {
+            return Some(ErrorCode::Permission);
+        }
+    }
+    None
+}
+
+fn automation_error_code(err: &AutomationError) -> ErrorCode {
+    match err {
+        AutomationError::PermissionDenied => ErrorCode::Permission,
+        _ => ErrorCode::Publish,
+    }
+}
+
+fn publisher_error_code(err: &PublisherError) -> ErrorCode {
+    match err {
+        PublisherError::FocusInspectionFailed(inner)
+        | PublisherError::InsertionFailed(inner)
+        | PublisherError::UndoFailed(inner) => automation_error_code(inner),
+        _ => ErrorCode::Publish,
+    }
+}
+
+impl From<anyhow::Error> for FlowwisperError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::classify(&err, ErrorCode::Internal)
+    }
+}
+
+impl From<PublisherError> for FlowwisperError {
+    fn from(err: PublisherError) -> Self {
+        Self::new(publisher_error_code(&err), err.to_string())
+    }
+}
+
+impl From<AutomationError> for FlowwisperError {
+    fn from(err: AutomationError) -> Self {
+        Self::new(automation_error_code(&err), err.to_string())
+    }
+}
+
+impl From<ClipboardError> for FlowwisperError {
+    fn from(err: ClipboardError) -> Self {
+        Self::Publish(err.to_string())
+    }
+}
+
+impl From<PluginError> for FlowwisperError {
+    fn from(err: PluginError) -> Self {
+        Self::Engine(err.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for FlowwisperError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Persistence(err.to_string())
+    }
+}
+
+impl From<&PublisherFailure> for FlowwisperError {
+    fn from(failure: &PublisherFailure) -> Self {
+        match failure.code {
+            PublisherFailureCode::PermissionDenied => Self::Permission(failure.message.clone()),
+            _ => Self::Publish(failure.message.clone()),
+        }
+    }
+}
+
+impl Serialize for FlowwisperError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FlowwisperError", 2)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+    use serde_json::json;
+
+    #[test]
+    fn classifies_error_chains_by_source_type() {
+        let denied = anyhow!(PublisherError::InsertionFailed(
+            AutomationError::PermissionDenied
+        ))
+        .context("publish failed");
+        let error = FlowwisperError::from(denied);
+        assert_eq!(error.code(), ErrorCode::Permission);
+        assert_eq!(
+            error.message(),
+            "publish failed: insertion failed: accessibility permission denied"
+        );
+
+        let sqlite: anyhow::Result<()> =
+            Err(rusqlite::Error::InvalidQuery).context("failed to read history");
+        assert_eq!(
+            FlowwisperError::from(sqlite.unwrap_err()).code(),
+            ErrorCode::Persistence
+        );
+
+        let nested = anyhow!(FlowwisperError::Audio("device lost".into())).context("start");
+        assert_eq!(FlowwisperError::from(nested).code(), ErrorCode::Audio);
+
+        let untyped = anyhow!("model missing");
+        assert_eq!(
+            FlowwisperError::classify(&untyped, ErrorCode::Engine),
+            FlowwisperError::Engine("model missing".into())
+        );
+        assert_eq!(FlowwisperError::from(untyped).code(), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn serializes_code_and_message() {
+        let error = FlowwisperError::Persistence("database is locked".into());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "code": "persistence", "message": "database is locked" })
+        );
+        assert_eq!(error.to_string(), "persistence error: database is locked");
+    }
+}
@@ -0,0 +1,105 @@
+//! 听写主热键之外的命名快捷键：取消会话、撤销上次插入、重新润色上一句。
+
+use std::sync::mpsc;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    HoldToTalk, HoldToTalkEvent, HotkeyBackend, HotkeyCombination, HotkeyError, HotkeyListener,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyAction {
+    /// 结束当前录音并丢弃草稿。
+    CancelSession,
+    /// 撤销最近一次发布的插入。
+    UndoPublish,
+    /// 重新润色当前会话的最后一句。
+    RepolishLast,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 3] = [
+        HotkeyAction::CancelSession,
+        HotkeyAction::UndoPublish,
+        HotkeyAction::RepolishLast,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HotkeyAction::CancelSession => "cancelSession",
+            HotkeyAction::UndoPublish => "undoPublish",
+            HotkeyAction::RepolishLast => "repolishLast",
+        }
+    }
+
+    /// 冲突提示等界面文案使用的名称。
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotkeyAction::CancelSession => "取消会话",
+            HotkeyAction::UndoPublish => "撤销上次插入",
+            HotkeyAction::RepolishLast => "重新润色上一句",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "cancelsession" | "cancel_session" | "cancel" => Some(HotkeyAction::CancelSession),
+            "undopublish" | "undo_publish" | "undo" => Some(HotkeyAction::UndoPublish),
+            "repolishlast" | "repolish_last" | "repolish" => Some(HotkeyAction::RepolishLast),
+            _ => None,
+        }
+    }
+}
+
+/// 检查一组命名绑定：与系统保留组合、主热键或其他绑定重复的项按绑定顺序返回说明。
+pub fn action_conflicts(
+    trigger: &HotkeyCombination,
+    bindings: &[(HotkeyAction, HotkeyCombination)],
+) -> Vec<(HotkeyAction, String)> {
+    let mut conflicts = Vec::new();
+    for (index, (action, combination)) in bindings.iter().enumerate() {
+        let reason = if let Some(reserved) = combination.conflict() {
+            Some(format!("{reserved} 为系统保留组合"))
+        } else if combination == trigger {
+            Some(format!("{combination} 已用作听写热键"))
+        } else {
+            bindings[..index]
+                .iter()
+                .find(|(_, other)| other == combination)
+                .map(|(other, _)| format!("{combination} 已绑定到“{}”", other.label()))
+        };
+        if let Some(reason) = reason {
+            conflicts.push((*action, reason));
+        }
+    }
+    conflicts
+}
+
+/// 同时监听多个命名绑定，组合按下时把对应动作写入 `sink`，直到返回的句柄被释放。
+pub fn spawn_actions(
+    backend: &dyn HotkeyBackend,
+    bindings: Vec<(HotkeyAction, HotkeyCombination)>,
+    sink: mpsc::Sender<HotkeyAction>,
+) -> Result<HotkeyListener, HotkeyError> {
+    let (tx, rx) = mpsc::channel();
+    let listener = backend.listen(tx)?;
+    thread::spawn(move || {
+        let mut detectors: Vec<(HotkeyAction, HoldToTalk)> = bindings
+            .into_iter()
+            .map(|(action, combination)| (action, HoldToTalk::new(combination)))
+            .collect();
+        for event in rx {
+            for (action, detector) in &mut detectors {
+                if let Some(HoldToTalkEvent::Pressed { .. }) = detector.feed(&event) {
+                    if sink.send(*action).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+    Ok(listener)
+}
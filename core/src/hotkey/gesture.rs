@@ -0,0 +1,295 @@
+//! 同一绑定上的手势识别：单击切换录音、双击重开会话、长按即按住说话。
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    spawn_hold_to_talk, HoldToTalkEvent, HotkeyBackend, HotkeyCombination, HotkeyError,
+    HotkeyListener,
+};
+
+const DEFAULT_HOLD_THRESHOLD_MS: u64 = 300;
+const DEFAULT_DOUBLE_TAP_WINDOW_MS: u64 = 300;
+
+/// 手势判定阈值。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GestureConfig {
+    /// 按住超过该时长视为长按。
+    pub hold_threshold_ms: u64,
+    /// 单击松开后在该时长内再次按下视为双击；单击因此延迟同样时长确认。
+    pub double_tap_window_ms: u64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            hold_threshold_ms: DEFAULT_HOLD_THRESHOLD_MS,
+            double_tap_window_ms: DEFAULT_DOUBLE_TAP_WINDOW_MS,
+        }
+    }
+}
+
+impl GestureConfig {
+    fn hold_threshold(&self) -> Duration {
+        Duration::from_millis(self.hold_threshold_ms)
+    }
+
+    fn double_tap_window(&self) -> Duration {
+        Duration::from_millis(self.double_tap_window_ms)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum HotkeyGesture {
+    /// 单击：切换录音。
+    Tap,
+    /// 双击：丢弃当前草稿并开始新会话。
+    DoubleTap,
+    /// 长按开始：按住说话。
+    HoldStarted,
+    /// 长按松开。
+    HoldEnded { held_ms: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GestureState {
+    Idle,
+    /// 按下未松开；`after_tap` 表示这是单击后的第二次按下。
+    Pressing {
+        since: Instant,
+        after_tap: bool,
+    },
+    Holding {
+        since: Instant,
+    },
+    /// 单击已松开，等待可能的第二次按下。
+    TapPending {
+        released: Instant,
+    },
+}
+
+/// 把按下、抬起与时间推进转换为手势。时间推进由调用方按 [`Self::deadline`] 驱动。
+#[derive(Debug)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    state: GestureState,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            state: GestureState::Idle,
+        }
+    }
+
+    pub fn config(&self) -> GestureConfig {
+        self.config
+    }
+
+    /// 下一次需要调用 [`Self::poll`] 的时刻；空闲时为 `None`。
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.state {
+            GestureState::Pressing { since, .. } => Some(since + self.config.hold_threshold()),
+            GestureState::TapPending { released } => {
+                Some(released + self.config.double_tap_window())
+            }
+            GestureState::Idle | GestureState::Holding { .. } => None,
+        }
+    }
+
+    pub fn feed(&mut self, event: &HoldToTalkEvent) -> Vec<HotkeyGesture> {
+        match *event {
+            HoldToTalkEvent::Pressed { at } => self.press(at),
+            HoldToTalkEvent::Released { at, .. } => self.release(at),
+        }
+    }
+
+    /// 按时间推进确认长按与单击。
+    pub fn poll(&mut self, now: Instant) -> Vec<HotkeyGesture> {
+        match self.state {
+            GestureState::Pressing { since, after_tap }
+                if now.saturating_duration_since(since) >= self.config.hold_threshold() =>
+            {
+                self.state = GestureState::Holding { since };
+                // 单击后再长按：先确认那次单击。
+                if after_tap {
+                    vec![HotkeyGesture::Tap, HotkeyGesture::HoldStarted]
+                } else {
+                    vec![HotkeyGesture::HoldStarted]
+                }
+            }
+            GestureState::TapPending { released }
+                if now.saturating_duration_since(released) >= self.config.double_tap_window() =>
+            {
+                self.state = GestureState::Idle;
+                vec![HotkeyGesture::Tap]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn press(&mut self, at: Instant) -> Vec<HotkeyGesture> {
+        // 事件可能先于到期的轮询到达，先结算过期状态。
+        let gestures = self.poll(at);
+        match self.state {
+            GestureState::Idle => {
+                self.state = GestureState::Pressing {
+                    since: at,
+                    after_tap: false,
+                };
+            }
+            GestureState::TapPending { .. } => {
+                self.state = GestureState::Pressing {
+                    since: at,
+                    after_tap: true,
+                };
+            }
+            GestureState::Pressing { .. } | GestureState::Holding { .. } => {}
+        }
+        gestures
+    }
+
+    fn release(&mut self, at: Instant) -> Vec<HotkeyGesture> {
+        let mut gestures = self.poll(at);
+        match self.state {
+            GestureState::Pressing { after_tap, .. } => {
+                if after_tap {
+                    self.state = GestureState::Idle;
+                    gestures.push(HotkeyGesture::DoubleTap);
+                } else {
+                    self.state = GestureState::TapPending { released: at };
+                }
+            }
+            GestureState::Holding { since } => {
+                self.state = GestureState::Idle;
+                gestures.push(HotkeyGesture::HoldEnded {
+                    held_ms: at.saturating_duration_since(since).as_millis() as u64,
+                });
+            }
+            GestureState::Idle | GestureState::TapPending { .. } => {}
+        }
+        gestures
+    }
+}
+
+/// 监听 `binding` 并把手势写入 `sink`，直到返回的句柄被释放。
+pub fn spawn_gestures(
+    backend: &dyn HotkeyBackend,
+    binding: HotkeyCombination,
+    config: GestureConfig,
+    sink: mpsc::Sender<HotkeyGesture>,
+) -> Result<HotkeyListener, HotkeyError> {
+    let (tx, rx) = mpsc::channel();
+    let listener = spawn_hold_to_talk(backend, binding, tx)?;
+    thread::spawn(move || {
+        let mut recognizer = GestureRecognizer::new(config);
+        loop {
+            let gestures = match recognizer.deadline() {
+                Some(deadline) => {
+                    let wait = deadline.saturating_duration_since(Instant::now());
+                    match rx.recv_timeout(wait) {
+                        Ok(event) => recognizer.feed(&event),
+                        Err(mpsc::RecvTimeoutError::Timeout) => recognizer.poll(Instant::now()),
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match rx.recv() {
+                    Ok(event) => recognizer.feed(&event),
+                    Err(_) => break,
+                },
+            };
+            for gesture in gestures {
+                if sink.send(gesture).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressed(at: Instant) -> HoldToTalkEvent {
+        HoldToTalkEvent::Pressed { at }
+    }
+
+    fn released(at: Instant) -> HoldToTalkEvent {
+        HoldToTalkEvent::Released {
+            at,
+            held: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn distinguishes_tap_double_tap_and_hold() {
+        let start = Instant::now();
+        let ms = |value: u64| start + Duration::from_millis(value);
+        let mut recognizer = GestureRecognizer::new(GestureConfig::default());
+
+        // 单击在双击窗口过后确认。
+        assert!(recognizer.feed(&pressed(ms(0))).is_empty());
+        assert!(recognizer.feed(&released(ms(80))).is_empty());
+        assert_eq!(recognizer.deadline(), Some(ms(380)));
+        assert!(recognizer.poll(ms(200)).is_empty());
+        assert_eq!(recognizer.poll(ms(380)), vec![HotkeyGesture::Tap]);
+
+        // 双击。
+        recognizer.feed(&pressed(ms(1_000)));
+        recognizer.feed(&released(ms(1_060)));
+        recognizer.feed(&pressed(ms(1_200)));
+        assert_eq!(
+            recognizer.feed(&released(ms(1_260))),
+            vec![HotkeyGesture::DoubleTap]
+        );
+        assert_eq!(recognizer.deadline(), None);
+
+        // 长按。
+        recognizer.feed(&pressed(ms(2_000)));
+        assert_eq!(recognizer.poll(ms(2_300)), vec![HotkeyGesture::HoldStarted]);
+        assert_eq!(
+            recognizer.feed(&released(ms(3_000))),
+            vec![HotkeyGesture::HoldEnded { held_ms: 1_000 }]
+        );
+    }
+
+    #[test]
+    fn late_events_settle_expired_states_first() {
+        let start = Instant::now();
+        let ms = |value: u64| start + Duration::from_millis(value);
+        let mut recognizer = GestureRecognizer::new(GestureConfig {
+            hold_threshold_ms: 200,
+            double_tap_window_ms: 250,
+        });
+
+        // 没有轮询时，窗口外的第二次按下先确认上一次单击。
+        recognizer.feed(&pressed(ms(0)));
+        recognizer.feed(&released(ms(50)));
+        assert_eq!(recognizer.feed(&pressed(ms(400))), vec![HotkeyGesture::Tap]);
+        // 松开时已超过长按阈值。
+        assert_eq!(
+            recognizer.feed(&released(ms(700))),
+            vec![
+                HotkeyGesture::HoldStarted,
+                HotkeyGesture::HoldEnded { held_ms: 300 }
+            ]
+        );
+
+        // 单击后接长按：单击随长按开始一并确认。
+        recognizer.feed(&pressed(ms(1_000)));
+        recognizer.feed(&released(ms(1_050)));
+        recognizer.feed(&pressed(ms(1_100)));
+        assert_eq!(
+            recognizer.poll(ms(1_300)),
+            vec![HotkeyGesture::Tap, HotkeyGesture::HoldStarted]
+        );
+    }
+}
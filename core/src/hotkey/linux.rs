@@ -0,0 +1,578 @@
+//! Linux evdev 后端：直接读取 `/dev/input/event*`，需要当前用户在 `input` 组中。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::mem::size_of;
+use std::ops::ControlFlow;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{
+    FnProbe, HotkeyBackend, HotkeyCombination, HotkeyError, HotkeyKey, HotkeyListener, KeyEvent,
+    Modifier,
+};
+
+/// `O_NONBLOCK`，设备读取不阻塞以便监听线程及时响应停止。
+const O_NONBLOCK: i32 = 0o4000;
+const EV_KEY: u16 = 0x01;
+/// `struct input_event`：`timeval`（两个 long）后接 type、code 与 value。
+const INPUT_EVENT_LEN: usize = 2 * size_of::<usize>() + 8;
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+/// 键盘必备的 `KEY_Q`，用于在 sysfs 能力位图中区分键盘与鼠标、电源键等设备。
+const KEY_Q: u32 = 16;
+const KEY_FN: u16 = 0x1d0;
+
+#[derive(Debug, Clone)]
+pub struct EvdevBackend {
+    devices_dir: PathBuf,
+    sysfs_dir: PathBuf,
+}
+
+impl Default for EvdevBackend {
+    fn default() -> Self {
+        Self {
+            devices_dir: PathBuf::from("/dev/input"),
+            sysfs_dir: PathBuf::from("/sys/class/input"),
+        }
+    }
+}
+
+impl EvdevBackend {
+    /// 列出所有键盘设备节点。
+    pub fn keyboards(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.devices_dir) else {
+            return Vec::new();
+        };
+        let mut keyboards: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("event") && self.is_keyboard(name))
+            })
+            .collect();
+        keyboards.sort();
+        keyboards
+    }
+
+    fn is_keyboard(&self, node: &str) -> bool {
+        fs::read_to_string(self.sysfs_dir.join(node).join("device/capabilities/key"))
+            .map(|bitmap| has_capability(&bitmap, KEY_Q))
+            .unwrap_or(false)
+    }
+
+    fn open(path: &Path) -> std::io::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .custom_flags(O_NONBLOCK)
+            .open(path)
+    }
+}
+
+impl EvdevBackend {
+    fn open_keyboards(&self) -> Result<Vec<Keyboard>, HotkeyError> {
+        let keyboards = self.keyboards();
+        if keyboards.is_empty() {
+            return Err(HotkeyError::Unsupported("未找到可用的键盘输入设备".into()));
+        }
+
+        let mut devices = Vec::new();
+        let mut denied = false;
+        for path in &keyboards {
+            match Self::open(path) {
+                Ok(file) => devices.push(Keyboard {
+                    name: self.device_name(path),
+                    file,
+                }),
+                Err(err) if err.kind() == ErrorKind::PermissionDenied => denied = true,
+                Err(_) => {}
+            }
+        }
+        if devices.is_empty() {
+            return Err(if denied {
+                HotkeyError::PermissionDenied(
+                    "无权读取 /dev/input，请将当前用户加入 input 组".into(),
+                )
+            } else {
+                HotkeyError::Backend("无法打开键盘输入设备".into())
+            });
+        }
+        Ok(devices)
+    }
+
+    fn device_name(&self, path: &Path) -> Option<String> {
+        let node = path.file_name()?.to_str()?;
+        fs::read_to_string(self.sysfs_dir.join(node).join("device/name"))
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// 等待用户按下 Fn，按内核事件时间戳计算驱动到用户态的延迟。
+    /// 多数 Linux 笔记本的 Fn 由键盘固件处理，不会产生 `KEY_FN`；
+    /// 此时先按下的其他按键会被报告为不支持。
+    pub fn probe_fn(&self, timeout: Duration, sla: Duration) -> Result<FnProbe, HotkeyError> {
+        let mut devices = self.open_keyboards()?;
+        let started = Instant::now();
+        let mut buffer = [0u8; INPUT_EVENT_LEN * 64];
+        let mut outcome = None;
+        while outcome.is_none() {
+            if started.elapsed() >= timeout {
+                return Err(HotkeyError::Timeout);
+            }
+            let idle = poll_keyboards(&mut devices, &mut buffer, |keyboard, raw| {
+                if !raw.pressed() {
+                    return ControlFlow::Continue(());
+                }
+                let reaction = started.elapsed();
+                let origin = keyboard.name.clone();
+                outcome = Some(if raw.code == KEY_FN {
+                    let latency = SystemTime::now()
+                        .duration_since(raw.timestamp)
+                        .unwrap_or_default();
+                    let within_sla = latency <= sla;
+                    FnProbe {
+                        supported: true,
+                        latency: Some(latency),
+                        user_reaction: Some(reaction),
+                        within_sla: Some(within_sla),
+                        interface: "evdev",
+                        device_origin: origin,
+                        reason: (!within_sla).then(|| {
+                            format!(
+                                "Fn 驱动回调耗时 {}ms，超出 {}ms SLA",
+                                latency.as_millis(),
+                                sla.as_millis()
+                            )
+                        }),
+                    }
+                } else {
+                    FnProbe {
+                        supported: false,
+                        latency: None,
+                        user_reaction: Some(reaction),
+                        within_sla: None,
+                        interface: "evdev",
+                        device_origin: origin,
+                        reason: Some(format!(
+                            "收到 {} 而非 Fn，Fn 可能由键盘固件处理，请录制备用组合",
+                            key_for_code(raw.code).label()
+                        )),
+                    }
+                });
+                ControlFlow::Break(())
+            });
+            if devices.is_empty() {
+                return Err(HotkeyError::Backend("键盘输入设备已断开".into()));
+            }
+            if idle && outcome.is_none() {
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+        Ok(outcome.expect("probe outcome"))
+    }
+
+    /// 与当前桌面环境（`XDG_CURRENT_DESKTOP`）默认快捷键冲突时返回冲突项。
+    pub fn desktop_conflict(&self, combination: &HotkeyCombination) -> Option<&'static str> {
+        let desktop = std::env::var("XDG_CURRENT_DESKTOP").ok()?;
+        desktop_conflict(&desktop, combination)
+    }
+}
+
+impl HotkeyBackend for EvdevBackend {
+    fn name(&self) -> &'static str {
+        "evdev"
+    }
+
+    fn listen(&self, sink: mpsc::Sender<KeyEvent>) -> Result<HotkeyListener, HotkeyError> {
+        let mut devices = self.open_keyboards()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            let mut buffer = [0u8; INPUT_EVENT_LEN * 64];
+            let mut closed = false;
+            while !closed && !flag.load(Ordering::SeqCst) && !devices.is_empty() {
+                let idle = poll_keyboards(&mut devices, &mut buffer, |_, raw| {
+                    // 自动重复不是新的按下。
+                    if raw.value == 2 {
+                        return ControlFlow::Continue(());
+                    }
+                    let event = KeyEvent {
+                        key: key_for_code(raw.code),
+                        pressed: raw.pressed(),
+                        at: Instant::now(),
+                    };
+                    if sink.send(event).is_err() {
+                        closed = true;
+                        return ControlFlow::Break(());
+                    }
+                    ControlFlow::Continue(())
+                });
+                if idle {
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        });
+        Ok(HotkeyListener::new(stop, thread, None))
+    }
+}
+
+struct Keyboard {
+    name: Option<String>,
+    file: File,
+}
+
+/// 一条 `EV_KEY` 事件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RawKey {
+    code: u16,
+    /// 0 抬起，1 按下，2 自动重复。
+    value: i32,
+    timestamp: SystemTime,
+}
+
+impl RawKey {
+    fn pressed(&self) -> bool {
+        self.value == 1
+    }
+}
+
+/// 非阻塞地读取所有设备一轮，逐条回调按键事件；断开的设备被移除。
+/// 没有读到任何数据时返回 `true`。
+fn poll_keyboards(
+    devices: &mut Vec<Keyboard>,
+    buffer: &mut [u8],
+    mut on_key: impl FnMut(&Keyboard, RawKey) -> ControlFlow<()>,
+) -> bool {
+    let mut idle = true;
+    let mut index = 0;
+    while index < devices.len() {
+        match devices[index].file.read(buffer) {
+            Ok(0) => {
+                devices.swap_remove(index);
+                continue;
+            }
+            Ok(read) => {
+                idle = false;
+                for chunk in buffer[..read].chunks_exact(INPUT_EVENT_LEN) {
+                    if let Some(raw) = decode_event(chunk) {
+                        if on_key(&devices[index], raw).is_break() {
+                            return false;
+                        }
+                    }
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+            // 设备被拔出。
+            Err(_) => {
+                devices.swap_remove(index);
+                continue;
+            }
+        }
+        index += 1;
+    }
+    idle
+}
+
+/// sysfs 能力位图由空格分隔的十六进制 long 组成，高位在前。
+fn has_capability(bitmap: &str, bit: u32) -> bool {
+    let word_bits = usize::BITS;
+    let words: Vec<&str> = bitmap.split_whitespace().collect();
+    let index = (bit / word_bits) as usize;
+    let Some(word) = words.len().checked_sub(index + 1).map(|i| words[i]) else {
+        return false;
+    };
+    usize::from_str_radix(word, 16)
+        .map(|value| value & (1 << (bit % word_bits)) != 0)
+        .unwrap_or(false)
+}
+
+/// 解析一条 `input_event`，非按键事件返回 `None`。
+fn decode_event(raw: &[u8]) -> Option<RawKey> {
+    let long = size_of::<usize>();
+    let read_long = |offset: usize| -> Option<u64> {
+        let bytes = raw.get(offset..offset + long)?;
+        Some(if long == 8 {
+            u64::from_ne_bytes(bytes.try_into().ok()?)
+        } else {
+            u32::from_ne_bytes(bytes.try_into().ok()?) as u64
+        })
+    };
+    let offset = INPUT_EVENT_LEN - 8;
+    let kind = u16::from_ne_bytes(raw.get(offset..offset + 2)?.try_into().ok()?);
+    if kind != EV_KEY {
+        return None;
+    }
+    let code = u16::from_ne_bytes(raw.get(offset + 2..offset + 4)?.try_into().ok()?);
+    let value = i32::from_ne_bytes(raw.get(offset + 4..offset + 8)?.try_into().ok()?);
+    let timestamp =
+        UNIX_EPOCH + Duration::from_secs(read_long(0)?) + Duration::from_micros(read_long(long)?);
+    Some(RawKey {
+        code,
+        value,
+        timestamp,
+    })
+}
+
+/// 常见桌面环境默认占用的全局快捷键。
+fn desktop_shortcuts(desktop: &str) -> &'static [&'static str] {
+    const GNOME: &[&str] = &[
+        "Super+Space",
+        "Super+L",
+        "Super+A",
+        "Super+V",
+        "Alt+F2",
+        "Ctrl+Alt+T",
+        "Alt+Tab",
+        "Super+Tab",
+        "Ctrl+Alt+Delete",
+        "Super+Up",
+        "Super+Down",
+    ];
+    const KDE: &[&str] = &[
+        "Alt+Space",
+        "Alt+F2",
+        "Super+L",
+        "Super+E",
+        "Super+V",
+        "Ctrl+Alt+L",
+        "Ctrl+Alt+T",
+        "Ctrl+F1",
+        "Ctrl+F2",
+        "Ctrl+F3",
+        "Ctrl+F4",
+        "Ctrl+Alt+Delete",
+        "Alt+Tab",
+    ];
+    const XFCE: &[&str] = &[
+        "Alt+F1",
+        "Alt+F2",
+        "Alt+F3",
+        "Ctrl+Alt+L",
+        "Ctrl+Alt+T",
+        "Ctrl+Alt+Delete",
+        "Ctrl+Esc",
+        "Super+E",
+        "Alt+Tab",
+    ];
+    // `XDG_CURRENT_DESKTOP` 可能是冒号分隔的列表，如 `ubuntu:GNOME`。
+    for name in desktop.split(':') {
+        match name.to_ascii_lowercase().as_str() {
+            "gnome" | "ubuntu" | "unity" | "pop" | "budgie" | "cinnamon" => return GNOME,
+            "kde" | "plasma" => return KDE,
+            "xfce" | "lxqt" | "mate" => return XFCE,
+            _ => {}
+        }
+    }
+    &[]
+}
+
+fn desktop_conflict(desktop: &str, combination: &HotkeyCombination) -> Option<&'static str> {
+    desktop_shortcuts(desktop)
+        .iter()
+        .copied()
+        .find(|shortcut| shortcut.parse::<HotkeyCombination>().ok().as_ref() == Some(combination))
+}
+
+fn key_for_code(code: u16) -> HotkeyKey {
+    const LETTER_ROWS: [(u16, &str); 3] = [(16, "QWERTYUIOP"), (30, "ASDFGHJKL"), (44, "ZXCVBNM")];
+    for (start, letters) in LETTER_ROWS {
+        if let Some(letter) = code
+            .checked_sub(start)
+            .and_then(|index| letters.chars().nth(index as usize))
+        {
+            return HotkeyKey::named(letter.to_string());
+        }
+    }
+    let label = match code {
+        KEY_FN => return HotkeyKey::Fn,
+        29 | 97 => return HotkeyKey::Modifier(Modifier::Ctrl),
+        42 | 54 => return HotkeyKey::Modifier(Modifier::Shift),
+        56 | 100 => return HotkeyKey::Modifier(Modifier::Alt),
+        125 | 126 => return HotkeyKey::Modifier(Modifier::Meta),
+        2..=10 => return HotkeyKey::named((code - 1).to_string()),
+        11 => "0",
+        59..=68 => return HotkeyKey::named(format!("F{}", code - 58)),
+        87 => "F11",
+        88 => "F12",
+        1 => "Esc",
+        12 => "-",
+        13 => "=",
+        14 => "Backspace",
+        15 => "Tab",
+        26 => "[",
+        27 => "]",
+        28 => "Enter",
+        39 => ";",
+        40 => "'",
+        41 => "`",
+        43 => "\\",
+        51 => ",",
+        52 => ".",
+        53 => "/",
+        55 => "Kp*",
+        57 => "Space",
+        58 => "CapsLock",
+        69 => "NumLock",
+        70 => "ScrollLock",
+        71 => "NumPad7",
+        72 => "NumPad8",
+        73 => "NumPad9",
+        74 => "Kp-",
+        75 => "NumPad4",
+        76 => "NumPad5",
+        77 => "NumPad6",
+        78 => "Kp+",
+        79 => "NumPad1",
+        80 => "NumPad2",
+        81 => "NumPad3",
+        82 => "NumPad0",
+        83 => "NumPadDel",
+        96 => "KpEnter",
+        98 => "Kp/",
+        99 => "PrintScreen",
+        102 => "Home",
+        103 => "ArrowUp",
+        104 => "PageUp",
+        105 => "ArrowLeft",
+        106 => "ArrowRight",
+        107 => "End",
+        108 => "ArrowDown",
+        109 => "PageDown",
+        110 => "Insert",
+        111 => "Delete",
+        119 => "Pause",
+        _ => return HotkeyKey::named(format!("Keycode({code})")),
+    };
+    HotkeyKey::named(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_event(kind: u16, code: u16, value: i32, timestamp: SystemTime) -> Vec<u8> {
+        let since_epoch = timestamp.duration_since(UNIX_EPOCH).expect("after epoch");
+        let mut raw = Vec::new();
+        raw.extend_from_slice(&(since_epoch.as_secs() as usize).to_ne_bytes());
+        raw.extend_from_slice(&(since_epoch.subsec_micros() as usize).to_ne_bytes());
+        raw.extend_from_slice(&kind.to_ne_bytes());
+        raw.extend_from_slice(&code.to_ne_bytes());
+        raw.extend_from_slice(&value.to_ne_bytes());
+        raw
+    }
+
+    /// 以普通文件模拟 `/dev/input/event0` 与对应的 sysfs 节点。
+    fn fake_keyboard(root: &Path, events: &[Vec<u8>]) -> EvdevBackend {
+        let devices_dir = root.join("dev");
+        let device_dir = root.join("sys/event0/device");
+        fs::create_dir_all(&devices_dir).expect("dev dir");
+        fs::create_dir_all(device_dir.join("capabilities")).expect("sysfs dir");
+        fs::write(
+            device_dir.join("capabilities/key"),
+            format!("{:x}\n", 1usize << KEY_Q),
+        )
+        .expect("capabilities");
+        fs::write(device_dir.join("name"), "Test Keyboard\n").expect("name");
+        fs::write(devices_dir.join("event0"), events.concat()).expect("events");
+        EvdevBackend {
+            devices_dir,
+            sysfs_dir: root.join("sys"),
+        }
+    }
+
+    #[test]
+    fn decodes_key_events_with_kernel_timestamps() {
+        let at = UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456);
+        let press = decode_event(&raw_event(EV_KEY, 30, 1, at)).expect("press");
+        assert_eq!(press.code, 30);
+        assert!(press.pressed());
+        assert_eq!(press.timestamp, at);
+        let repeat = decode_event(&raw_event(EV_KEY, 30, 2, at)).expect("repeat");
+        assert!(!repeat.pressed());
+        assert!(decode_event(&raw_event(0x02, 0, 1, at)).is_none());
+
+        assert_eq!(key_for_code(KEY_FN), HotkeyKey::Fn);
+        assert_eq!(key_for_code(29), HotkeyKey::Modifier(Modifier::Ctrl));
+        assert_eq!(key_for_code(2), HotkeyKey::named("1"));
+        assert_eq!(key_for_code(50), HotkeyKey::named("M"));
+        assert_eq!(key_for_code(63), HotkeyKey::named("F5"));
+        assert_eq!(key_for_code(240), HotkeyKey::named("Keycode(240)"));
+    }
+
+    #[test]
+    fn probes_fn_and_reports_other_keys_as_unsupported() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let pressed_at = SystemTime::now() - Duration::from_millis(5);
+        let backend = fake_keyboard(dir.path(), &[raw_event(EV_KEY, KEY_FN, 1, pressed_at)]);
+        let probe = backend
+            .probe_fn(Duration::from_secs(1), Duration::from_millis(400))
+            .expect("probe");
+        assert!(probe.supported);
+        assert_eq!(probe.interface, "evdev");
+        assert_eq!(probe.device_origin.as_deref(), Some("Test Keyboard"));
+        assert!(probe.latency.expect("latency") >= Duration::from_millis(5));
+        assert_eq!(probe.within_sla, Some(true));
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let backend = fake_keyboard(
+            dir.path(),
+            &[
+                raw_event(EV_KEY, 30, 0, pressed_at),
+                raw_event(EV_KEY, 30, 1, pressed_at),
+            ],
+        );
+        let probe = backend
+            .probe_fn(Duration::from_secs(1), Duration::from_millis(400))
+            .expect("probe");
+        assert!(!probe.supported);
+        assert!(probe.reason.expect("reason").contains("收到 A 而非 Fn"));
+    }
+
+    #[test]
+    fn captures_combination_from_evdev_events() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let at = SystemTime::now();
+        let backend = fake_keyboard(
+            dir.path(),
+            &[
+                raw_event(EV_KEY, 29, 1, at),
+                raw_event(EV_KEY, 57, 1, at),
+                raw_event(EV_KEY, 57, 2, at),
+            ],
+        );
+        let combination =
+            super::super::capture_combination(&backend, Duration::from_secs(1)).expect("capture");
+        assert_eq!(combination.to_string(), "Ctrl+Space");
+    }
+
+    #[test]
+    fn detects_conflicts_with_desktop_shortcuts() {
+        let super_space: HotkeyCombination = "Super+Space".parse().expect("parse");
+        assert_eq!(
+            desktop_conflict("ubuntu:GNOME", &super_space),
+            Some("Super+Space")
+        );
+        assert_eq!(desktop_conflict("KDE", &super_space), None);
+        let alt_space: HotkeyCombination = "Alt+Space".parse().expect("parse");
+        assert_eq!(desktop_conflict("KDE", &alt_space), Some("Alt+Space"));
+        assert_eq!(desktop_conflict("sway", &alt_space), None);
+    }
+
+    #[test]
+    fn detects_keyboards_from_sysfs_capabilities() {
+        let bits = usize::BITS as usize / 4;
+        assert!(has_capability(&format!("{:x}", 1usize << KEY_Q), KEY_Q));
+        assert!(!has_capability("0", KEY_Q));
+        assert!(has_capability(
+            &format!("1 {:0bits$x}", 1usize << KEY_Q),
+            KEY_Q
+        ));
+        assert!(!has_capability("", KEY_Q));
+    }
+}
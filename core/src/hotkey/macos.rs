@@ -0,0 +1,277 @@
+//! macOS 后端：基于 CGEventTap 的只读监听，需要“辅助功能”或“输入监控”授权。
+
+use std::ffi::c_void;
+use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+
+use super::{HotkeyBackend, HotkeyError, HotkeyKey, HotkeyListener, KeyEvent, Modifier};
+
+type CFMachPortRef = *mut c_void;
+type CFRunLoopSourceRef = *mut c_void;
+type CFRunLoopRef = *mut c_void;
+type CFStringRef = *const c_void;
+type CGEventRef = *mut c_void;
+type CGEventTapProxy = *mut c_void;
+type CGEventTapCallBack =
+    unsafe extern "C" fn(CGEventTapProxy, u32, CGEventRef, *mut c_void) -> CGEventRef;
+
+const K_CG_SESSION_EVENT_TAP: u32 = 1;
+const K_CG_HEAD_INSERT_EVENT_TAP: u32 = 0;
+const K_CG_EVENT_TAP_OPTION_LISTEN_ONLY: u32 = 1;
+const K_CG_EVENT_KEY_DOWN: u32 = 10;
+const K_CG_EVENT_KEY_UP: u32 = 11;
+const K_CG_EVENT_FLAGS_CHANGED: u32 = 12;
+const K_CG_EVENT_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFF_FFFE;
+const K_CG_KEYBOARD_EVENT_KEYCODE: u32 = 9;
+const K_CG_KEYBOARD_EVENT_AUTOREPEAT: u32 = 8;
+
+const FLAG_SHIFT: u64 = 0x0002_0000;
+const FLAG_CONTROL: u64 = 0x0004_0000;
+const FLAG_ALTERNATE: u64 = 0x0008_0000;
+const FLAG_COMMAND: u64 = 0x0010_0000;
+const FLAG_SECONDARY_FN: u64 = 0x0080_0000;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn CGEventTapCreate(
+        tap: u32,
+        place: u32,
+        options: u32,
+        events_of_interest: u64,
+        callback: CGEventTapCallBack,
+        user_info: *mut c_void,
+    ) -> CFMachPortRef;
+    fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+    fn CGEventGetIntegerValueField(event: CGEventRef, field: u32) -> i64;
+    fn CGEventGetFlags(event: CGEventRef) -> u64;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopCommonModes: CFStringRef;
+    fn CFMachPortCreateRunLoopSource(
+        allocator: *const c_void,
+        port: CFMachPortRef,
+        order: isize,
+    ) -> CFRunLoopSourceRef;
+    fn CFRunLoopGetCurrent() -> CFRunLoopRef;
+    fn CFRunLoopAddSource(run_loop: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRunLoopRun();
+    fn CFRunLoopStop(run_loop: CFRunLoopRef);
+    fn CFRelease(value: *const c_void);
+}
+
+struct TapContext {
+    sink: mpsc::Sender<KeyEvent>,
+    tap: CFMachPortRef,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventTapBackend;
+
+impl HotkeyBackend for EventTapBackend {
+    fn name(&self) -> &'static str {
+        "CGEventTap"
+    }
+
+    fn listen(&self, sink: mpsc::Sender<KeyEvent>) -> Result<HotkeyListener, HotkeyError> {
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<usize, HotkeyError>>();
+        let thread = thread::spawn(move || unsafe {
+            let context = Box::into_raw(Box::new(TapContext {
+                sink,
+                tap: std::ptr::null_mut(),
+            }));
+            let mask = (1 << K_CG_EVENT_KEY_DOWN)
+                | (1 << K_CG_EVENT_KEY_UP)
+                | (1 << K_CG_EVENT_FLAGS_CHANGED);
+            let tap = CGEventTapCreate(
+                K_CG_SESSION_EVENT_TAP,
+                K_CG_HEAD_INSERT_EVENT_TAP,
+                K_CG_EVENT_TAP_OPTION_LISTEN_ONLY,
+                mask,
+                tap_callback,
+                context.cast(),
+            );
+            if tap.is_null() {
+                drop(Box::from_raw(context));
+                let _ = ready_tx.send(Err(HotkeyError::PermissionDenied(
+                    "无法创建 CGEventTap，请在“隐私与安全性”中授予辅助功能权限".into(),
+                )));
+                return;
+            }
+            (*context).tap = tap;
+
+            let source = CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0);
+            let run_loop = CFRunLoopGetCurrent();
+            CFRunLoopAddSource(run_loop, source, kCFRunLoopCommonModes);
+            CGEventTapEnable(tap, true);
+            let _ = ready_tx.send(Ok(run_loop as usize));
+
+            CFRunLoopRun();
+
+            CGEventTapEnable(tap, false);
+            CFRelease(source);
+            CFRelease(tap);
+            drop(Box::from_raw(context));
+        });
+
+        let run_loop = ready_rx
+            .recv()
+            .map_err(|_| HotkeyError::Backend("CGEventTap 线程意外退出".into()))??;
+        let stop = Arc::new(AtomicBool::new(false));
+        Ok(HotkeyListener::new(
+            stop,
+            thread,
+            Some(Box::new(move || unsafe {
+                CFRunLoopStop(run_loop as CFRunLoopRef);
+            })),
+        ))
+    }
+}
+
+unsafe extern "C" fn tap_callback(
+    _proxy: CGEventTapProxy,
+    event_type: u32,
+    event: CGEventRef,
+    user_info: *mut c_void,
+) -> CGEventRef {
+    let context = &*(user_info as *const TapContext);
+    if event_type == K_CG_EVENT_TAP_DISABLED_BY_TIMEOUT {
+        // 系统在回调过慢时会停用监听，重新启用即可。
+        CGEventTapEnable(context.tap, true);
+        return event;
+    }
+
+    let code = CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_KEYCODE) as u16;
+    let pressed = match event_type {
+        K_CG_EVENT_KEY_DOWN => {
+            if CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_AUTOREPEAT) != 0 {
+                return event;
+            }
+            true
+        }
+        K_CG_EVENT_KEY_UP => false,
+        K_CG_EVENT_FLAGS_CHANGED => {
+            let Some(flag) = modifier_flag(code) else {
+                return event;
+            };
+            CGEventGetFlags(event) & flag != 0
+        }
+        _ => return event,
+    };
+    let _ = context.sink.send(KeyEvent {
+        key: key_for_code(code),
+        pressed,
+        at: Instant::now(),
+    });
+    event
+}
+
+/// 修饰键按下与否由 FlagsChanged 事件中对应的标志位表示。
+fn modifier_flag(code: u16) -> Option<u64> {
+    match code {
+        56 | 60 => Some(FLAG_SHIFT),
+        59 | 62 => Some(FLAG_CONTROL),
+        58 | 61 => Some(FLAG_ALTERNATE),
+        54 | 55 => Some(FLAG_COMMAND),
+        63 => Some(FLAG_SECONDARY_FN),
+        _ => None,
+    }
+}
+
+fn key_for_code(code: u16) -> HotkeyKey {
+    let label = match code {
+        63 => return HotkeyKey::Fn,
+        56 | 60 => return HotkeyKey::Modifier(Modifier::Shift),
+        59 | 62 => return HotkeyKey::Modifier(Modifier::Ctrl),
+        58 | 61 => return HotkeyKey::Modifier(Modifier::Alt),
+        54 | 55 => return HotkeyKey::Modifier(Modifier::Meta),
+        0 => "A",
+        1 => "S",
+        2 => "D",
+        3 => "F",
+        4 => "H",
+        5 => "G",
+        6 => "Z",
+        7 => "X",
+        8 => "C",
+        9 => "V",
+        11 => "B",
+        12 => "Q",
+        13 => "W",
+        14 => "E",
+        15 => "R",
+        16 => "Y",
+        17 => "T",
+        18 => "1",
+        19 => "2",
+        20 => "3",
+        21 => "4",
+        22 => "6",
+        23 => "5",
+        24 => "=",
+        25 => "9",
+        26 => "7",
+        27 => "-",
+        28 => "8",
+        29 => "0",
+        30 => "]",
+        31 => "O",
+        32 => "U",
+        33 => "[",
+        34 => "I",
+        35 => "P",
+        36 => "Enter",
+        37 => "L",
+        38 => "J",
+        39 => "'",
+        40 => "K",
+        41 => ";",
+        42 => "\\",
+        43 => ",",
+        44 => "/",
+        45 => "N",
+        46 => "M",
+        47 => ".",
+        48 => "Tab",
+        49 => "Space",
+        50 => "`",
+        51 => "Backspace",
+        53 => "Esc",
+        57 => "CapsLock",
+        65 => "NumPadDel",
+        67 => "Kp*",
+        69 => "Kp+",
+        75 => "Kp/",
+        76 => "KpEnter",
+        78 => "Kp-",
+        82..=89 => return HotkeyKey::named(format!("NumPad{}", code - 82)),
+        91 => "NumPad8",
+        92 => "NumPad9",
+        96 => "F5",
+        97 => "F6",
+        98 => "F7",
+        99 => "F3",
+        100 => "F8",
+        101 => "F9",
+        103 => "F11",
+        109 => "F10",
+        111 => "F12",
+        115 => "Home",
+        116 => "PageUp",
+        117 => "Delete",
+        118 => "F4",
+        119 => "End",
+        120 => "F2",
+        121 => "PageDown",
+        122 => "F1",
+        123 => "ArrowLeft",
+        124 => "ArrowRight",
+        125 => "ArrowDown",
+        126 => "ArrowUp",
+        _ => return HotkeyKey::named(format!("Keycode({code})")),
+    };
+    HotkeyKey::named(label)
+}
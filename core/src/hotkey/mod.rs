@@ -0,0 +1,656 @@
+//! 全局热键捕获。
+//!
+//! 平台后端（macOS CGEventTap、Windows 低级键盘钩子、Linux evdev）只负责把系统按键
+//! 翻译成 [`KeyEvent`]；组合键录制、按住说话与手势判定、冲突检测在此统一实现，
+//! 桌面端、CLI 与后续外壳共用同一套逻辑。
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+mod actions;
+mod gesture;
+#[cfg(all(target_os = "linux", feature = "linux-hotkey"))]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+pub use actions::{action_conflicts, spawn_actions, HotkeyAction};
+pub use gesture::{spawn_gestures, GestureConfig, GestureRecognizer, HotkeyGesture};
+#[cfg(all(target_os = "linux", feature = "linux-hotkey"))]
+pub use linux::EvdevBackend;
+#[cfg(target_os = "macos")]
+pub use macos::EventTapBackend;
+#[cfg(target_os = "windows")]
+pub use windows::LowLevelHookBackend;
+
+/// 系统保留、不允许绑定为听写热键的组合。
+pub const RESERVED: &[&str] = &[
+    "Ctrl+Alt+Delete",
+    "Ctrl+Shift+Esc",
+    "Alt+F4",
+    "Alt+Tab",
+    "Cmd+Q",
+    "Cmd+Option+Esc",
+    "Cmd+Space",
+    "Win+L",
+    "Win+Space",
+];
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum HotkeyError {
+    #[error("hotkey capture is not supported: {0}")]
+    Unsupported(String),
+    #[error("hotkey capture permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("hotkey backend failed: {0}")]
+    Backend(String),
+    #[error("等待组合键超时，请重试")]
+    Timeout,
+    #[error("{0}")]
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    /// macOS 上的 Cmd，Windows/Linux 上的 Win/Super。
+    Meta,
+}
+
+impl Modifier {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Alt => "Alt",
+            Modifier::Shift => "Shift",
+            Modifier::Meta if cfg!(target_os = "macos") => "Cmd",
+            Modifier::Meta => "Win",
+        }
+    }
+
+    fn parse(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Some(Modifier::Ctrl),
+            "alt" | "option" => Some(Modifier::Alt),
+            "shift" => Some(Modifier::Shift),
+            "cmd" | "command" | "win" | "super" | "meta" => Some(Modifier::Meta),
+            _ => None,
+        }
+    }
+}
+
+/// 平台无关的按键。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyKey {
+    Fn,
+    Modifier(Modifier),
+    /// 其他按键，使用与桌面端一致的标签（如 `A`、`F5`、`Space`、`Keycode(42)`）。
+    Named(String),
+}
+
+impl HotkeyKey {
+    pub fn named(label: impl Into<String>) -> Self {
+        HotkeyKey::Named(label.into())
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            HotkeyKey::Fn => "Fn",
+            HotkeyKey::Modifier(modifier) => modifier.label(),
+            HotkeyKey::Named(label) => label,
+        }
+    }
+}
+
+/// 后端上报的一次按下或抬起。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: HotkeyKey,
+    pub pressed: bool,
+    pub at: Instant,
+}
+
+impl KeyEvent {
+    pub fn press(key: HotkeyKey) -> Self {
+        Self {
+            key,
+            pressed: true,
+            at: Instant::now(),
+        }
+    }
+
+    pub fn release(key: HotkeyKey) -> Self {
+        Self {
+            key,
+            pressed: false,
+            at: Instant::now(),
+        }
+    }
+}
+
+/// 一次 Fn 探测的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnProbe {
+    pub supported: bool,
+    /// 驱动产生事件到用户态收到的延迟。
+    pub latency: Option<Duration>,
+    /// 开始探测到用户按下按键的时长。
+    pub user_reaction: Option<Duration>,
+    pub within_sla: Option<bool>,
+    pub interface: &'static str,
+    pub device_origin: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// 修饰键加一个主键，或单独的 Fn。文本形式为 `Ctrl+Alt+Shift+Cmd+X`。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HotkeyCombination {
+    pub modifiers: BTreeSet<Modifier>,
+    pub key: HotkeyKey,
+}
+
+impl HotkeyCombination {
+    pub fn fn_key() -> Self {
+        Self {
+            modifiers: BTreeSet::new(),
+            key: HotkeyKey::Fn,
+        }
+    }
+
+    /// 与系统保留组合冲突时返回冲突项。
+    pub fn conflict(&self) -> Option<&'static str> {
+        RESERVED
+            .iter()
+            .copied()
+            .find(|reserved| reserved.parse::<HotkeyCombination>().ok().as_ref() == Some(self))
+    }
+}
+
+impl fmt::Display for HotkeyCombination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier.label())?;
+        }
+        f.write_str(self.key.label())
+    }
+}
+
+impl FromStr for HotkeyCombination {
+    type Err = HotkeyError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = value.split('+').map(str::trim).collect();
+        // `Kp+` 之类以加号结尾的标签会被拆出空串。
+        if parts.len() > 1 && parts.last() == Some(&"") {
+            parts.pop();
+            let last = parts.pop().unwrap_or_default();
+            parts.push(&value[value.len() - last.len() - 1..]);
+        }
+        let Some((primary, modifiers)) = parts.split_last() else {
+            return Err(HotkeyError::Rejected(format!("无法解析组合键: {value}")));
+        };
+        let modifiers = modifiers
+            .iter()
+            .map(|label| {
+                Modifier::parse(label)
+                    .ok_or_else(|| HotkeyError::Rejected(format!("未知的修饰键: {label}")))
+            })
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        let key = match *primary {
+            "" => return Err(HotkeyError::Rejected(format!("无法解析组合键: {value}"))),
+            label if label.eq_ignore_ascii_case("fn") => HotkeyKey::Fn,
+            label => match Modifier::parse(label) {
+                Some(modifier) => HotkeyKey::Modifier(modifier),
+                None if label.eq_ignore_ascii_case("esc") => HotkeyKey::named("Esc"),
+                None if label.chars().count() == 1 => HotkeyKey::named(label.to_uppercase()),
+                None => HotkeyKey::named(label),
+            },
+        };
+        Ok(Self { modifiers, key })
+    }
+}
+
+/// 平台键盘监听后端。
+pub trait HotkeyBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// 开始监听全局按键，事件写入 `sink` 直到返回的 [`HotkeyListener`] 被释放。
+    fn listen(&self, sink: mpsc::Sender<KeyEvent>) -> Result<HotkeyListener, HotkeyError>;
+}
+
+/// 监听线程的句柄，释放时通知后端退出。
+pub struct HotkeyListener {
+    stop: Arc<AtomicBool>,
+    on_stop: Option<Box<dyn FnOnce() + Send>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl HotkeyListener {
+    /// `on_stop` 用于唤醒阻塞在系统消息循环中的线程。
+    pub fn new(
+        stop: Arc<AtomicBool>,
+        thread: thread::JoinHandle<()>,
+        on_stop: Option<Box<dyn FnOnce() + Send>>,
+    ) -> Self {
+        Self {
+            stop,
+            on_stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(on_stop) = self.on_stop.take() {
+            on_stop();
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 当前平台的默认后端。
+pub fn default_backend() -> Result<Box<dyn HotkeyBackend>, HotkeyError> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Box::new(EventTapBackend))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Ok(Box::new(LowLevelHookBackend))
+    }
+    #[cfg(all(target_os = "linux", feature = "linux-hotkey"))]
+    {
+        Ok(Box::new(EvdevBackend::default()))
+    }
+    #[cfg(all(target_os = "linux", not(feature = "linux-hotkey")))]
+    {
+        Err(HotkeyError::Unsupported(
+            "未启用 linux-hotkey 特性，无法读取 evdev 键盘事件".into(),
+        ))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Err(HotkeyError::Unsupported(
+            "当前平台未提供热键捕获能力".into(),
+        ))
+    }
+}
+
+/// 根据按键序列录制一个备用组合：按住修饰键后按下主键即完成。
+#[derive(Debug, Default)]
+pub struct CombinationRecorder {
+    modifiers: BTreeSet<Modifier>,
+}
+
+impl CombinationRecorder {
+    /// 返回 `None` 表示仍在等待主键。
+    pub fn feed(&mut self, event: &KeyEvent) -> Option<Result<HotkeyCombination, HotkeyError>> {
+        match (&event.key, event.pressed) {
+            (HotkeyKey::Modifier(modifier), true) => {
+                self.modifiers.insert(*modifier);
+                None
+            }
+            (HotkeyKey::Modifier(modifier), false) => {
+                self.modifiers.remove(modifier);
+                None
+            }
+            (HotkeyKey::Fn, true) => Some(Err(HotkeyError::Rejected(
+                "Fn 键无法作为备用组合主键".into(),
+            ))),
+            (key, true) => {
+                let combination = HotkeyCombination {
+                    modifiers: self.modifiers.clone(),
+                    key: key.clone(),
+                };
+                Some(match combination.conflict() {
+                    Some(reserved) => Err(HotkeyError::Rejected(format!(
+                        "{reserved} 为系统保留组合，请换一个"
+                    ))),
+                    None => Ok(combination),
+                })
+            }
+            (_, false) => None,
+        }
+    }
+}
+
+/// 通过 `backend` 录制一个备用组合，超时返回 [`HotkeyError::Timeout`]。
+pub fn capture_combination(
+    backend: &dyn HotkeyBackend,
+    timeout: Duration,
+) -> Result<HotkeyCombination, HotkeyError> {
+    let (tx, rx) = mpsc::channel();
+    let _listener = backend.listen(tx)?;
+    let deadline = Instant::now() + timeout;
+    let mut recorder = CombinationRecorder::default();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let event = rx.recv_timeout(remaining).map_err(|err| match err {
+            mpsc::RecvTimeoutError::Timeout => HotkeyError::Timeout,
+            mpsc::RecvTimeoutError::Disconnected => {
+                HotkeyError::Backend(format!("{} 监听已退出", backend.name()))
+            }
+        })?;
+        if let Some(result) = recorder.feed(&event) {
+            return result;
+        }
+    }
+}
+
+/// 按住说话的状态变化。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HoldToTalkEvent {
+    Pressed { at: Instant },
+    Released { at: Instant, held: Duration },
+}
+
+/// 根据绑定组合判定按住说话：组合完整按下时开始，主键或任一修饰键抬起时结束。
+#[derive(Debug)]
+pub struct HoldToTalk {
+    binding: HotkeyCombination,
+    modifiers: BTreeSet<Modifier>,
+    pressed_at: Option<Instant>,
+}
+
+impl HoldToTalk {
+    pub fn new(binding: HotkeyCombination) -> Self {
+        Self {
+            binding,
+            modifiers: BTreeSet::new(),
+            pressed_at: None,
+        }
+    }
+
+    pub fn binding(&self) -> &HotkeyCombination {
+        &self.binding
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.pressed_at.is_some()
+    }
+
+    pub fn feed(&mut self, event: &KeyEvent) -> Option<HoldToTalkEvent> {
+        if let HotkeyKey::Modifier(modifier) = &event.key {
+            if event.pressed {
+                self.modifiers.insert(*modifier);
+            } else {
+                self.modifiers.remove(modifier);
+            }
+        }
+
+        match self.pressed_at {
+            None if event.pressed
+                && event.key == self.binding.key
+                && self.modifiers_match(&event.key) =>
+            {
+                self.pressed_at = Some(event.at);
+                Some(HoldToTalkEvent::Pressed { at: event.at })
+            }
+            Some(started)
+                if !event.pressed
+                    && (event.key == self.binding.key
+                        || matches!(&event.key, HotkeyKey::Modifier(modifier)
+                            if self.binding.modifiers.contains(modifier))) =>
+            {
+                self.pressed_at = None;
+                Some(HoldToTalkEvent::Released {
+                    at: event.at,
+                    held: event.at.saturating_duration_since(started),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn modifiers_match(&self, key: &HotkeyKey) -> bool {
+        // 绑定本身是修饰键时，按下的那个键不计入其余修饰键。
+        let mut held = self.modifiers.clone();
+        if let HotkeyKey::Modifier(modifier) = key {
+            held.remove(modifier);
+        }
+        held == self.binding.modifiers
+    }
+}
+
+/// 把按键流转换为按住说话事件，在独立线程中运行直到监听结束。
+pub fn spawn_hold_to_talk(
+    backend: &dyn HotkeyBackend,
+    binding: HotkeyCombination,
+    sink: mpsc::Sender<HoldToTalkEvent>,
+) -> Result<HotkeyListener, HotkeyError> {
+    let (tx, rx) = mpsc::channel();
+    let listener = backend.listen(tx)?;
+    thread::spawn(move || {
+        let mut detector = HoldToTalk::new(binding);
+        for event in rx {
+            if let Some(change) = detector.feed(&event) {
+                if sink.send(change).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按给定序列回放事件的测试后端。
+    struct ScriptedBackend(Vec<KeyEvent>);
+
+    impl HotkeyBackend for ScriptedBackend {
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+
+        fn listen(&self, sink: mpsc::Sender<KeyEvent>) -> Result<HotkeyListener, HotkeyError> {
+            let events = self.0.clone();
+            let stop = Arc::new(AtomicBool::new(false));
+            let flag = Arc::clone(&stop);
+            let thread = thread::spawn(move || {
+                for event in events {
+                    if flag.load(Ordering::SeqCst) || sink.send(event).is_err() {
+                        return;
+                    }
+                }
+                while !flag.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            });
+            Ok(HotkeyListener::new(stop, thread, None))
+        }
+    }
+
+    fn ctrl() -> HotkeyKey {
+        HotkeyKey::Modifier(Modifier::Ctrl)
+    }
+
+    #[test]
+    fn combinations_round_trip_through_text() {
+        let combination: HotkeyCombination = "Shift+ctrl+space".parse().expect("parse");
+        assert_eq!(combination.key, HotkeyKey::named("space"));
+        assert_eq!(
+            combination.modifiers,
+            BTreeSet::from([Modifier::Ctrl, Modifier::Shift])
+        );
+        assert_eq!(combination.to_string(), "Ctrl+Shift+space");
+        assert_eq!(
+            "Alt+Kp+".parse::<HotkeyCombination>().expect("parse").key,
+            HotkeyKey::named("Kp+")
+        );
+        assert_eq!(
+            "Fn".parse::<HotkeyCombination>().expect("parse"),
+            HotkeyCombination::fn_key()
+        );
+        assert!("Hyper+A".parse::<HotkeyCombination>().is_err());
+        assert_eq!(
+            "Alt+F4"
+                .parse::<HotkeyCombination>()
+                .expect("parse")
+                .conflict(),
+            Some("Alt+F4")
+        );
+    }
+
+    #[test]
+    fn captures_modifier_combination_and_rejects_reserved_ones() {
+        let backend = ScriptedBackend(vec![
+            KeyEvent::press(ctrl()),
+            KeyEvent::press(HotkeyKey::Modifier(Modifier::Alt)),
+            KeyEvent::press(HotkeyKey::named("K")),
+        ]);
+        let combination = capture_combination(&backend, Duration::from_secs(1)).expect("captured");
+        assert_eq!(combination.to_string(), "Ctrl+Alt+K");
+
+        let backend = ScriptedBackend(vec![
+            KeyEvent::press(HotkeyKey::Modifier(Modifier::Alt)),
+            KeyEvent::press(HotkeyKey::named("F4")),
+        ]);
+        assert!(matches!(
+            capture_combination(&backend, Duration::from_secs(1)),
+            Err(HotkeyError::Rejected(_))
+        ));
+
+        let backend = ScriptedBackend(vec![KeyEvent::press(HotkeyKey::Fn)]);
+        assert!(matches!(
+            capture_combination(&backend, Duration::from_secs(1)),
+            Err(HotkeyError::Rejected(_))
+        ));
+
+        let backend = ScriptedBackend(vec![KeyEvent::press(ctrl())]);
+        assert_eq!(
+            capture_combination(&backend, Duration::from_millis(20)),
+            Err(HotkeyError::Timeout)
+        );
+    }
+
+    #[test]
+    fn hold_to_talk_tracks_press_and_release_of_the_binding() {
+        let (tx, rx) = mpsc::channel();
+        let backend = ScriptedBackend(vec![
+            KeyEvent::press(HotkeyKey::Fn),
+            KeyEvent::release(HotkeyKey::Fn),
+            KeyEvent::press(HotkeyKey::named("A")),
+            KeyEvent::press(ctrl()),
+            KeyEvent::press(HotkeyKey::Fn),
+            KeyEvent::release(HotkeyKey::Fn),
+        ]);
+        let listener =
+            spawn_hold_to_talk(&backend, HotkeyCombination::fn_key(), tx).expect("listen");
+
+        let first = rx.recv_timeout(Duration::from_secs(1)).expect("pressed");
+        assert!(matches!(first, HoldToTalkEvent::Pressed { .. }));
+        let second = rx.recv_timeout(Duration::from_secs(1)).expect("released");
+        assert!(matches!(second, HoldToTalkEvent::Released { .. }));
+        // 带着 Ctrl 按下 Fn 不是绑定组合。
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        listener.stop();
+
+        let mut detector = HoldToTalk::new("Ctrl+Space".parse().expect("parse"));
+        assert_eq!(detector.feed(&KeyEvent::press(ctrl())), None);
+        assert!(matches!(
+            detector.feed(&KeyEvent::press(HotkeyKey::named("Space"))),
+            Some(HoldToTalkEvent::Pressed { .. })
+        ));
+        assert!(detector.is_held());
+        assert!(matches!(
+            detector.feed(&KeyEvent::release(ctrl())),
+            Some(HoldToTalkEvent::Released { .. })
+        ));
+        assert!(!detector.is_held());
+    }
+
+    #[test]
+    fn named_bindings_dispatch_actions_and_report_conflicts() {
+        let (tx, rx) = mpsc::channel();
+        let backend = ScriptedBackend(vec![
+            KeyEvent::press(ctrl()),
+            KeyEvent::press(HotkeyKey::named("Esc")),
+            KeyEvent::release(HotkeyKey::named("Esc")),
+            KeyEvent::press(HotkeyKey::named("Z")),
+            KeyEvent::release(HotkeyKey::named("Z")),
+            KeyEvent::release(ctrl()),
+            KeyEvent::press(HotkeyKey::named("Z")),
+        ]);
+        let listener = spawn_actions(
+            &backend,
+            vec![
+                (
+                    HotkeyAction::CancelSession,
+                    "Ctrl+Esc".parse().expect("parse"),
+                ),
+                (HotkeyAction::UndoPublish, "Ctrl+Z".parse().expect("parse")),
+            ],
+            tx,
+        )
+        .expect("listen");
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)),
+            Ok(HotkeyAction::CancelSession)
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)),
+            Ok(HotkeyAction::UndoPublish)
+        );
+        // 没有 Ctrl 时单按 Z 不触发。
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        listener.stop();
+
+        let trigger = HotkeyCombination::fn_key();
+        let conflicts = action_conflicts(
+            &trigger,
+            &[
+                (
+                    HotkeyAction::CancelSession,
+                    "Alt+F4".parse().expect("parse"),
+                ),
+                (HotkeyAction::UndoPublish, "Fn".parse().expect("parse")),
+                (HotkeyAction::RepolishLast, "Ctrl+R".parse().expect("parse")),
+                (
+                    HotkeyAction::CancelSession,
+                    "Ctrl+R".parse().expect("parse"),
+                ),
+            ],
+        );
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|(action, _)| *action)
+                .collect::<Vec<_>>(),
+            vec![
+                HotkeyAction::CancelSession,
+                HotkeyAction::UndoPublish,
+                HotkeyAction::CancelSession,
+            ]
+        );
+        assert_eq!(HotkeyAction::parse("undo"), Some(HotkeyAction::UndoPublish));
+    }
+}
@@ -0,0 +1,187 @@
+//! Windows 后端：`WH_KEYBOARD_LL` 低级键盘钩子，在专用线程的消息循环中运行。
+
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+
+use super::{HotkeyBackend, HotkeyError, HotkeyKey, HotkeyListener, KeyEvent, Modifier};
+
+type HookProc = unsafe extern "system" fn(i32, usize, isize) -> isize;
+
+const WH_KEYBOARD_LL: i32 = 13;
+const HC_ACTION: i32 = 0;
+const WM_KEYDOWN: usize = 0x0100;
+const WM_KEYUP: usize = 0x0101;
+const WM_SYSKEYDOWN: usize = 0x0104;
+const WM_SYSKEYUP: usize = 0x0105;
+const WM_QUIT: u32 = 0x0012;
+
+#[repr(C)]
+#[allow(dead_code)]
+struct KbdLlHookStruct {
+    vk_code: u32,
+    scan_code: u32,
+    flags: u32,
+    time: u32,
+    extra_info: usize,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct Msg {
+    hwnd: isize,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    time: u32,
+    pt_x: i32,
+    pt_y: i32,
+}
+
+#[link(name = "user32")]
+extern "system" {
+    fn SetWindowsHookExW(id: i32, hook: HookProc, module: isize, thread_id: u32) -> isize;
+    fn UnhookWindowsHookEx(hook: isize) -> i32;
+    fn CallNextHookEx(hook: isize, code: i32, wparam: usize, lparam: isize) -> isize;
+    fn GetMessageW(msg: *mut Msg, hwnd: isize, filter_min: u32, filter_max: u32) -> i32;
+    fn PostThreadMessageW(thread_id: u32, msg: u32, wparam: usize, lparam: isize) -> i32;
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetCurrentThreadId() -> u32;
+    fn GetModuleHandleW(name: *const u16) -> isize;
+    fn GetLastError() -> u32;
+}
+
+thread_local! {
+    /// 钩子回调没有用户数据参数，只能通过安装线程的线程局部变量取得事件出口。
+    static SINK: RefCell<Option<mpsc::Sender<KeyEvent>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LowLevelHookBackend;
+
+impl HotkeyBackend for LowLevelHookBackend {
+    fn name(&self) -> &'static str {
+        "SetWindowsHookEx"
+    }
+
+    fn listen(&self, sink: mpsc::Sender<KeyEvent>) -> Result<HotkeyListener, HotkeyError> {
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<u32, HotkeyError>>();
+        let thread = thread::spawn(move || unsafe {
+            SINK.with(|slot| *slot.borrow_mut() = Some(sink));
+            let module = GetModuleHandleW(ptr::null());
+            let hook = SetWindowsHookExW(WH_KEYBOARD_LL, hook_proc, module, 0);
+            if hook == 0 {
+                let _ = ready_tx.send(Err(HotkeyError::Backend(format!(
+                    "SetWindowsHookEx 失败: {}",
+                    GetLastError()
+                ))));
+                return;
+            }
+            let _ = ready_tx.send(Ok(GetCurrentThreadId()));
+
+            let mut msg: Msg = std::mem::zeroed();
+            while GetMessageW(&mut msg, 0, 0, 0) > 0 {}
+
+            UnhookWindowsHookEx(hook);
+            SINK.with(|slot| slot.borrow_mut().take());
+        });
+
+        let thread_id = ready_rx
+            .recv()
+            .map_err(|_| HotkeyError::Backend("键盘钩子线程意外退出".into()))??;
+        let stop = Arc::new(AtomicBool::new(false));
+        Ok(HotkeyListener::new(
+            stop,
+            thread,
+            Some(Box::new(move || unsafe {
+                PostThreadMessageW(thread_id, WM_QUIT, 0, 0);
+            })),
+        ))
+    }
+}
+
+unsafe extern "system" fn hook_proc(code: i32, wparam: usize, lparam: isize) -> isize {
+    if code == HC_ACTION {
+        let kb = &*(lparam as *const KbdLlHookStruct);
+        let pressed = match wparam {
+            WM_KEYDOWN | WM_SYSKEYDOWN => Some(true),
+            WM_KEYUP | WM_SYSKEYUP => Some(false),
+            _ => None,
+        };
+        if let Some(pressed) = pressed {
+            SINK.with(|slot| {
+                if let Some(sink) = slot.borrow().as_ref() {
+                    let _ = sink.send(KeyEvent {
+                        key: key_for_vk(kb),
+                        pressed,
+                        at: Instant::now(),
+                    });
+                }
+            });
+        }
+    }
+    CallNextHookEx(0, code, wparam, lparam)
+}
+
+fn key_for_vk(kb: &KbdLlHookStruct) -> HotkeyKey {
+    let vk = kb.vk_code;
+    // 多数笔记本的 Fn 不产生虚拟键码，少数驱动上报 0xFF。
+    if vk == 0xFF || (vk == 0 && kb.scan_code == 0) {
+        return HotkeyKey::Fn;
+    }
+    let label = match vk {
+        0x10 | 0xA0 | 0xA1 => return HotkeyKey::Modifier(Modifier::Shift),
+        0x11 | 0xA2 | 0xA3 => return HotkeyKey::Modifier(Modifier::Ctrl),
+        0x12 | 0xA4 | 0xA5 => return HotkeyKey::Modifier(Modifier::Alt),
+        0x5B | 0x5C => return HotkeyKey::Modifier(Modifier::Meta),
+        0x30..=0x39 | 0x41..=0x5A => {
+            return HotkeyKey::named(char::from(vk as u8).to_string());
+        }
+        0x60..=0x69 => return HotkeyKey::named(format!("NumPad{}", vk - 0x60)),
+        0x70..=0x7B => return HotkeyKey::named(format!("F{}", vk - 0x6F)),
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0D => "Enter",
+        0x13 => "Pause",
+        0x14 => "CapsLock",
+        0x1B => "Esc",
+        0x20 => "Space",
+        0x21 => "PageUp",
+        0x22 => "PageDown",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "ArrowLeft",
+        0x26 => "ArrowUp",
+        0x27 => "ArrowRight",
+        0x28 => "ArrowDown",
+        0x2C => "PrintScreen",
+        0x2D => "Insert",
+        0x2E => "Delete",
+        0x6A => "Kp*",
+        0x6B => "Kp+",
+        0x6D => "Kp-",
+        0x6E => "NumPadDel",
+        0x6F => "Kp/",
+        0x90 => "NumLock",
+        0x91 => "ScrollLock",
+        0xBA => ";",
+        0xBB => "=",
+        0xBC => ",",
+        0xBD => "-",
+        0xBE => ".",
+        0xBF => "/",
+        0xC0 => "`",
+        0xDB => "[",
+        0xDC => "\\",
+        0xDD => "]",
+        0xDE => "'",
+        _ => return HotkeyKey::named(format!("Keycode({vk})")),
+    };
+    HotkeyKey::named(label)
+}
@@ -4,7 +4,18 @@
 //! including audio processing, session management, persistence, and telemetry.
 
 pub mod audio;
+#[cfg(any(test, feature = "bench"))]
+pub mod bench;
+pub mod config;
+pub mod error;
+pub mod hotkey;
+pub mod models;
 pub mod orchestrator;
 pub mod persistence;
+pub mod plugins;
+pub mod power;
+pub mod secrets;
 pub mod session;
 pub mod telemetry;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
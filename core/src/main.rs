@@ -1,17 +1,281 @@
-mod audio;
-mod orchestrator;
-mod persistence;
-mod session;
-mod telemetry;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use anyhow::Result;
-use session::SessionManager;
-use telemetry::init_tracing;
+use anyhow::{Context, Result};
+use flowwisper_core::audio::{decode_audio_file, DownmixPolicy};
+use flowwisper_core::config::PolicyEngine;
+use flowwisper_core::hotkey::{
+    action_conflicts, default_backend, spawn_actions, HotkeyAction, HotkeyCombination,
+    HotkeyListener,
+};
+use flowwisper_core::models::ModelManager;
+use flowwisper_core::orchestrator::{EngineConfig, EngineOrchestrator, RealtimeSessionConfig};
+use flowwisper_core::persistence::audit::EgressQuery;
+use flowwisper_core::session::capture::CaptureMode;
+use flowwisper_core::session::self_check::{SelfCheckOutcome, SelfCheckPlatform};
+use flowwisper_core::session::workspace::Workspace;
+use flowwisper_core::session::SessionManager;
+use flowwisper_core::telemetry::init_tracing;
+use flowwisper_core::telemetry::sealed::{decrypt_log, TelemetryLogKey};
+use serde_json::json;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("telemetry") {
+        return telemetry(std::env::args().skip(2).collect());
+    }
     init_tracing();
 
+    if std::env::args().nth(1).as_deref() == Some("transcribe") {
+        let path = std::env::args()
+            .nth(2)
+            .map(PathBuf::from)
+            .context("usage: flowwisper-core transcribe <file.wav|mp3>")?;
+        return transcribe(path).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("profiles") {
+        return profiles(std::env::args().skip(2).collect());
+    }
+    if std::env::args().nth(1).as_deref() == Some("models") {
+        return models(std::env::args().skip(2).collect()).await;
+    }
+    #[cfg(feature = "bench")]
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        return bench(std::env::args().nth(2).map(PathBuf::from)).await;
+    }
+
     let manager = SessionManager::new()?;
-    manager.run().await
+    manager.crash_guard().install_panic_hook();
+    // 设置 `FLOWWISPER_POLICY` 后加载组织策略，当前配置与策略冲突时拒绝启动。
+    if let Some(policy) = PolicyEngine::from_env()? {
+        manager.apply_tenant_policy(&policy).await?;
+    }
+    match std::env::args().nth(1).as_deref() {
+        // 失败以状态 1 退出，有跳过项（例如没有采集后端）以状态 2 退出。
+        Some("self-check") => {
+            let report = manager.self_check(self_check_probe()).await;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            match report.outcome {
+                SelfCheckOutcome::Passed => Ok(()),
+                SelfCheckOutcome::Incomplete => std::process::exit(2),
+                SelfCheckOutcome::Failed => std::process::exit(1),
+            }
+        }
+        // `audit` 列出最近的外发审计记录，`audit verify` 校验审计链。
+        Some("audit") if std::env::args().nth(2).as_deref() == Some("verify") => {
+            let verification = manager.verify_egress_log().await?;
+            println!("{}", serde_json::to_string_pretty(&verification)?);
+            if !verification.is_intact() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some("audit") => {
+            let records = manager.egress_log(EgressQuery::default()).await?;
+            println!("{}", serde_json::to_string_pretty(&records)?);
+            Ok(())
+        }
+        _ => {
+            manager.run().await?;
+            let _hotkey = listen_hotkey(&manager)?;
+            let (_actions, mut actions) = listen_hotkey_actions()?;
+            let shutdown = tokio::signal::ctrl_c();
+            tokio::pin!(shutdown);
+            loop {
+                tokio::select! {
+                    signal = &mut shutdown => {
+                        signal.context("failed to listen for shutdown signal")?;
+                        break;
+                    }
+                    Some(action) = actions.recv() => {
+                        if let Err(err) = manager.dispatch_action(action).await {
+                            tracing::warn!(action = action.as_str(), %err, "hotkey action failed");
+                        }
+                    }
+                }
+            }
+            manager.shutdown().await
+        }
+    }
+}
+
+/// CLI 自检的设备与采集探针：启用 `native-capture` 时直接打开 cpal 默认输入设备，
+/// 否则交给会话管理器使用音频管线配置的采集后端。
+fn self_check_probe() -> Option<Arc<dyn SelfCheckPlatform>> {
+    #[cfg(feature = "native-capture")]
+    {
+        use flowwisper_core::audio::CpalCaptureBackend;
+        use flowwisper_core::session::self_check::CaptureBackendProbe;
+
+        Some(Arc::new(CaptureBackendProbe::new(
+            Arc::new(CpalCaptureBackend),
+            None,
+        )))
+    }
+    #[cfg(not(feature = "native-capture"))]
+    None
+}
+
+/// 设置 `FLOWWISPER_HOTKEY`（如 `Fn`、`Ctrl+Alt+Space`）后由 core 监听全局热键，按住说话。
+fn listen_hotkey(manager: &SessionManager) -> Result<Option<HotkeyListener>> {
+    let Ok(binding) = std::env::var("FLOWWISPER_HOTKEY") else {
+        return Ok(None);
+    };
+    let binding: HotkeyCombination = binding.parse()?;
+    let backend = default_backend()?;
+    manager.set_capture_mode(CaptureMode::HoldToTalk);
+    let listener = manager.listen_hotkey(backend.as_ref(), binding)?;
+    Ok(Some(listener))
+}
+
+/// 设置 `FLOWWISPER_HOTKEY_ACTIONS`（如 `cancelSession=Ctrl+Esc;undoPublish=Ctrl+Alt+Z`）后
+/// 监听命名快捷键，按下的动作经返回的通道交给会话执行。
+fn listen_hotkey_actions() -> Result<(
+    Option<HotkeyListener>,
+    tokio::sync::mpsc::UnboundedReceiver<HotkeyAction>,
+)> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let Ok(spec) = std::env::var("FLOWWISPER_HOTKEY_ACTIONS") else {
+        return Ok((None, rx));
+    };
+    let mut bindings = Vec::new();
+    for entry in spec.split(';').filter(|entry| !entry.trim().is_empty()) {
+        let (action, combination) = entry
+            .split_once('=')
+            .with_context(|| format!("invalid hotkey action binding: {entry}"))?;
+        let action = HotkeyAction::parse(action)
+            .with_context(|| format!("unknown hotkey action: {action}"))?;
+        bindings.push((action, combination.trim().parse::<HotkeyCombination>()?));
+    }
+    let trigger = match std::env::var("FLOWWISPER_HOTKEY") {
+        Ok(binding) => binding.parse()?,
+        Err(_) => HotkeyCombination::fn_key(),
+    };
+    if let Some((action, reason)) = action_conflicts(&trigger, &bindings).into_iter().next() {
+        anyhow::bail!("{}: {reason}", action.label());
+    }
+
+    let (action_tx, action_rx) = std::sync::mpsc::channel();
+    let backend = default_backend()?;
+    let listener = spawn_actions(backend.as_ref(), bindings, action_tx)?;
+    std::thread::spawn(move || {
+        for action in action_rx {
+            if tx.send(action).is_err() {
+                break;
+            }
+        }
+    });
+    Ok((Some(listener), rx))
+}
+
+/// `telemetry decrypt <file>`：用当前配置档的日志密钥（或 `FLOWWISPER_TELEMETRY_KEY`）
+/// 解密遥测日志并输出到标准输出。
+fn telemetry(args: Vec<String>) -> Result<()> {
+    let usage = "usage: flowwisper-core telemetry decrypt <file>";
+    let path = match args.first().map(String::as_str) {
+        Some("decrypt") => args.get(1).context(usage)?,
+        _ => anyhow::bail!(usage),
+    };
+    let key = TelemetryLogKey::for_active_profile()?;
+    let file = std::fs::File::open(path).with_context(|| format!("failed to open {path}"))?;
+    decrypt_log(
+        &key,
+        std::io::BufReader::new(file),
+        std::io::stdout().lock(),
+    )?;
+    Ok(())
+}
+
+/// 列出、创建或切换配置档；切换在下次启动时生效。
+fn profiles(args: Vec<String>) -> Result<()> {
+    let workspace = Workspace::from_env()?;
+    let usage = "usage: flowwisper-core profiles [list | create <name> | switch <name>]";
+    let output = match args.first().map(String::as_str) {
+        None | Some("list") => serde_json::to_value(workspace.list_profiles()?)?,
+        Some("create") => {
+            serde_json::to_value(workspace.create_profile(args.get(1).context(usage)?)?)?
+        }
+        Some("switch") => {
+            serde_json::to_value(workspace.switch_profile(args.get(1).context(usage)?)?)?
+        }
+        Some(_) => anyhow::bail!(usage),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// 列出、下载、校验、选用或删除本地模型；下载进度输出到 stderr。
+async fn models(args: Vec<String>) -> Result<()> {
+    let dir = ModelManager::default_dir().context("failed to determine model directory")?;
+    let models = ModelManager::new(dir);
+    let usage =
+        "usage: flowwisper-core models [list | download <id> | verify <id> | use <id> | remove <id>]";
+    let id = || args.get(1).map(String::as_str).context(usage);
+    let output = match args.first().map(String::as_str) {
+        None | Some("list") => serde_json::to_value(models.list())?,
+        Some("download") => {
+            let mut events = models.subscribe();
+            let progress = tokio::spawn(async move {
+                while let Ok(event) = events.recv().await {
+                    if let Ok(line) = serde_json::to_string(&event) {
+                        eprintln!("{line}");
+                    }
+                }
+            });
+            let path = models.download(id()?).await;
+            progress.abort();
+            json!({ "path": path? })
+        }
+        Some("verify") => json!({ "intact": models.verify(id()?)? }),
+        Some("use") => {
+            models.set_active(id()?)?;
+            json!({ "active": id()? })
+        }
+        Some("remove") => json!({ "removed": models.remove(id()?)? }),
+        Some(_) => anyhow::bail!(usage),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// 运行性能基准并输出 JSON 报告；任一指标超出阈值时以非零状态退出。
+#[cfg(feature = "bench")]
+async fn bench(thresholds: Option<PathBuf>) -> Result<()> {
+    use flowwisper_core::bench::{run_benchmarks, BenchConfig, BenchThresholds};
+
+    let thresholds = match thresholds {
+        Some(path) => BenchThresholds::load(&path)?,
+        None => BenchThresholds::default(),
+    };
+    let report = run_benchmarks(&BenchConfig::default(), &thresholds).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.passed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// 离线转写音频文件，结果与元数据以 JSON 输出到 stdout。
+async fn transcribe(path: PathBuf) -> Result<()> {
+    let audio = decode_audio_file(&path)?;
+    let samples = audio.to_engine_mono(DownmixPolicy::default())?;
+    let orchestrator = EngineOrchestrator::new(EngineConfig {
+        prefer_cloud: false,
+    })?;
+    let transcript = orchestrator
+        .transcribe_batch(&samples, RealtimeSessionConfig::default())
+        .await;
+
+    let report = json!({
+        "file": path,
+        "durationMs": audio.duration_ms(),
+        "sampleRateHz": audio.sample_rate_hz,
+        "channels": audio.channels,
+        "text": transcript.text(),
+        "sentences": transcript.sentences,
+        "notices": transcript.notices,
+        "elapsedMs": transcript.elapsed_ms,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
 }
@@ -0,0 +1,89 @@
+//! 内置的本地模型目录。未附带校验和的条目在首次下载时记录哈希，之后以此校验；
+//! 数据目录下的 `catalog.json` 可补充校验和或新增条目。
+
+use serde::{Deserialize, Serialize};
+
+const WHISPER_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelEngine {
+    Whisper,
+}
+
+impl ModelEngine {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelEngine::Whisper => "whisper",
+        }
+    }
+}
+
+/// 识别质量档位，越高越慢、占用越大。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelQuality {
+    Fast,
+    Balanced,
+    Accurate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub id: String,
+    pub engine: ModelEngine,
+    pub file_name: String,
+    pub url: String,
+    /// 目录标注的大小，用于展示与下载前的空间提示。
+    pub size_bytes: u64,
+    /// 单语种模型的语言（ISO 639-1）；`None` 表示多语种。
+    pub language: Option<String>,
+    pub quality: ModelQuality,
+    /// 小写十六进制的 SHA-256；为空时信任首次下载的结果。
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+fn whisper(id: &str, size_bytes: u64, language: Option<&str>, quality: ModelQuality) -> ModelInfo {
+    let file_name = format!("ggml-{id}.bin");
+    ModelInfo {
+        id: id.to_string(),
+        engine: ModelEngine::Whisper,
+        url: format!("{WHISPER_BASE_URL}/{file_name}"),
+        file_name,
+        size_bytes,
+        language: language.map(str::to_string),
+        quality,
+        sha256: None,
+    }
+}
+
+pub fn builtin_catalog() -> Vec<ModelInfo> {
+    vec![
+        whisper("tiny", 77_691_713, None, ModelQuality::Fast),
+        whisper("tiny.en", 77_704_715, Some("en"), ModelQuality::Fast),
+        whisper("base", 147_951_465, None, ModelQuality::Balanced),
+        whisper("base.en", 147_964_211, Some("en"), ModelQuality::Balanced),
+        whisper("small", 487_601_967, None, ModelQuality::Balanced),
+        whisper("small.en", 487_614_201, Some("en"), ModelQuality::Balanced),
+        whisper("medium", 1_533_763_059, None, ModelQuality::Accurate),
+        whisper(
+            "large-v3-turbo",
+            1_624_555_275,
+            None,
+            ModelQuality::Accurate,
+        ),
+    ]
+}
+
+/// 以 `overrides` 中的同名条目替换内置条目，其余追加在后。
+pub(super) fn merge_catalog(mut base: Vec<ModelInfo>, overrides: Vec<ModelInfo>) -> Vec<ModelInfo> {
+    for entry in overrides {
+        match base.iter_mut().find(|model| model.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => base.push(entry),
+        }
+    }
+    base
+}
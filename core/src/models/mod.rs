@@ -0,0 +1,557 @@
+//! 本地模型管理：列出可用模型，断点续传下载并做 SHA-256 校验，记录每个引擎当前使用的模型。
+
+mod catalog;
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ring::digest::{Context, SHA256};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+pub use catalog::{builtin_catalog, ModelEngine, ModelInfo, ModelQuality};
+
+use crate::config::MODEL_DIR_ENV;
+use crate::session::workspace;
+
+const CATALOG_FILE: &str = "catalog.json";
+const STATE_FILE: &str = "state.json";
+const PARTIAL_EXTENSION: &str = "part";
+/// 进度事件的最小间隔字节数，避免大模型下载时刷屏。
+const PROGRESS_STEP_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ModelError {
+    #[error("unknown model {0}")]
+    UnknownModel(String),
+    #[error("model {0} is not installed")]
+    NotInstalled(String),
+    #[error("model {0} is already downloading")]
+    Busy(String),
+    #[error("model {id} failed verification: expected sha256 {expected}, got {actual}")]
+    ChecksumMismatch {
+        id: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("model download failed: {0}")]
+    Http(String),
+    #[error("model state is corrupt: {0}")]
+    State(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// 下载进度，供引导页展示。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ModelEvent {
+    #[serde(rename_all = "camelCase")]
+    Started { model_id: String, resumed_from: u64 },
+    #[serde(rename_all = "camelCase")]
+    Progress {
+        model_id: String,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Verifying { model_id: String },
+    #[serde(rename_all = "camelCase")]
+    Installed { model_id: String, path: PathBuf },
+    #[serde(rename_all = "camelCase")]
+    Failed { model_id: String, error: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStatus {
+    #[serde(flatten)]
+    pub info: ModelInfo,
+    pub installed: bool,
+    pub active: bool,
+    /// 目录中的校验和与已安装文件不同，重新下载即可更新。
+    pub update_available: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstalledModel {
+    sha256: String,
+    installed_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ModelState {
+    active: BTreeMap<ModelEngine, String>,
+    installed: BTreeMap<String, InstalledModel>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    catalog: Vec<ModelInfo>,
+    state: Mutex<ModelState>,
+    downloading: Mutex<HashSet<String>>,
+    events: broadcast::Sender<ModelEvent>,
+}
+
+/// 克隆得到的句柄共享同一份状态与事件通道。
+#[derive(Clone)]
+pub struct ModelManager {
+    inner: Arc<Inner>,
+}
+
+impl ModelManager {
+    /// 打开模型目录。目录清单或状态文件损坏时记录告警并按空处理，已下载的文件不受影响。
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let overrides = read_json::<Vec<ModelInfo>>(&dir.join(CATALOG_FILE))
+            .unwrap_or_else(|err| {
+                warn!(target: "model_manager", %err, "ignoring model catalog overrides");
+                None
+            })
+            .unwrap_or_default();
+        let state = read_json::<ModelState>(&dir.join(STATE_FILE))
+            .unwrap_or_else(|err| {
+                warn!(target: "model_manager", %err, "resetting model state");
+                None
+            })
+            .unwrap_or_default();
+        let (events, _) = broadcast::channel(64);
+        Self {
+            inner: Arc::new(Inner {
+                dir,
+                catalog: catalog::merge_catalog(builtin_catalog(), overrides),
+                state: Mutex::new(state),
+                downloading: Mutex::new(HashSet::new()),
+                events,
+            }),
+        }
+    }
+
+    /// `FLOWWISPER_MODEL_DIR` 优先，否则为当前配置档数据目录下的 `models`。
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Ok(dir) = std::env::var(MODEL_DIR_ENV) {
+            if !dir.is_empty() {
+                return Some(PathBuf::from(dir));
+            }
+        }
+        workspace::active_data_dir()
+            .ok()
+            .map(|dir| dir.join("models"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.inner.dir
+    }
+
+    pub fn catalog(&self) -> &[ModelInfo] {
+        &self.inner.catalog
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ModelEvent> {
+        self.inner.events.subscribe()
+    }
+
+    pub fn list(&self) -> Vec<ModelStatus> {
+        let state = self.lock_state();
+        self.inner
+            .catalog
+            .iter()
+            .map(|info| {
+                let installed = state
+                    .installed
+                    .get(&info.id)
+                    .filter(|_| self.path_for(info).is_file());
+                ModelStatus {
+                    installed: installed.is_some(),
+                    active: state.active.get(&info.engine) == Some(&info.id),
+                    update_available: match (installed, &info.sha256) {
+                        (Some(installed), Some(expected)) => {
+                            !installed.sha256.eq_ignore_ascii_case(expected)
+                        }
+                        _ => false,
+                    },
+                    info: info.clone(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn model(&self, id: &str) -> Result<&ModelInfo, ModelError> {
+        self.inner
+            .catalog
+            .iter()
+            .find(|model| model.id == id)
+            .ok_or_else(|| ModelError::UnknownModel(id.to_string()))
+    }
+
+    pub fn path_for(&self, info: &ModelInfo) -> PathBuf {
+        self.inner.dir.join(&info.file_name)
+    }
+
+    /// 引擎当前选用且已安装的模型文件。
+    pub fn active_path(&self, engine: ModelEngine) -> Option<PathBuf> {
+        let id = self.lock_state().active.get(&engine).cloned()?;
+        let path = self.path_for(self.model(&id).ok()?);
+        path.is_file().then_some(path)
+    }
+
+    pub fn set_active(&self, id: &str) -> Result<(), ModelError> {
+        let info = self.model(id)?;
+        let mut state = self.lock_state();
+        if !state.installed.contains_key(id) || !self.path_for(info).is_file() {
+            return Err(ModelError::NotInstalled(id.to_string()));
+        }
+        state.active.insert(info.engine, id.to_string());
+        self.save_state(&state)
+    }
+
+    /// 下载（或续传）并校验模型；已安装的同一模型会被重新下载，用于更新。
+    pub async fn download(&self, id: &str) -> Result<PathBuf, ModelError> {
+        let info = self.model(id)?.clone();
+        if !self
+            .inner
+            .downloading
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(info.id.clone())
+        {
+            return Err(ModelError::Busy(info.id));
+        }
+        let manager = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let result = manager.fetch(&info);
+            if let Err(err) = &result {
+                manager.emit(ModelEvent::Failed {
+                    model_id: info.id.clone(),
+                    error: err.to_string(),
+                });
+            }
+            result
+        })
+        .await
+        .unwrap_or_else(|err| Err(ModelError::Http(format!("download task failed: {err}"))));
+        self.inner
+            .downloading
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(id);
+        result
+    }
+
+    /// 重新计算已安装文件的哈希，与安装时记录的值比较。
+    pub fn verify(&self, id: &str) -> Result<bool, ModelError> {
+        let info = self.model(id)?;
+        let expected = self
+            .lock_state()
+            .installed
+            .get(id)
+            .map(|installed| installed.sha256.clone())
+            .ok_or_else(|| ModelError::NotInstalled(id.to_string()))?;
+        Ok(sha256_file(&self.path_for(info))?.eq_ignore_ascii_case(&expected))
+    }
+
+    /// 删除模型文件；正在使用的模型同时取消选中。
+    pub fn remove(&self, id: &str) -> Result<bool, ModelError> {
+        let info = self.model(id)?;
+        let mut state = self.lock_state();
+        let removed = state.installed.remove(id).is_some();
+        state.active.retain(|_, active| active != id);
+        match fs::remove_file(self.path_for(info)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        self.save_state(&state)?;
+        Ok(removed)
+    }
+
+    fn fetch(&self, info: &ModelInfo) -> Result<PathBuf, ModelError> {
+        fs::create_dir_all(&self.inner.dir)?;
+        let target = self.path_for(info);
+        let partial = target.with_extension(PARTIAL_EXTENSION);
+        let offset = fs::metadata(&partial).map(|meta| meta.len()).unwrap_or(0);
+        info!(
+            target: "model_manager",
+            model = %info.id,
+            url = %info.url,
+            resumed_from = offset,
+            "downloading model"
+        );
+
+        let mut request = ureq::get(&info.url);
+        if offset > 0 {
+            request = request.set("Range", &format!("bytes={offset}-"));
+        }
+        match request.call() {
+            Ok(response) => {
+                let resumed = offset > 0 && response.status() == 206;
+                let start = if resumed { offset } else { 0 };
+                self.emit(ModelEvent::Started {
+                    model_id: info.id.clone(),
+                    resumed_from: start,
+                });
+                let total = response
+                    .header("Content-Length")
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .map(|remaining| start + remaining);
+                let file = if resumed {
+                    OpenOptions::new().append(true).open(&partial)?
+                } else {
+                    File::create(&partial)?
+                };
+                self.copy_body(info, response.into_reader(), file, start, total)?;
+            }
+            // 续传起点已在文件末尾：上次下载完整但未来得及校验。
+            Err(ureq::Error::Status(416, _)) if offset > 0 => {}
+            Err(err) => return Err(ModelError::Http(err.to_string())),
+        }
+
+        self.emit(ModelEvent::Verifying {
+            model_id: info.id.clone(),
+        });
+        let actual = sha256_file(&partial)?;
+        if let Some(expected) = &info.sha256 {
+            if !actual.eq_ignore_ascii_case(expected) {
+                // 续传可能拼接了不同版本的内容，丢弃后需从头下载。
+                let _ = fs::remove_file(&partial);
+                return Err(ModelError::ChecksumMismatch {
+                    id: info.id.clone(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        fs::rename(&partial, &target)?;
+
+        let mut state = self.lock_state();
+        state.installed.insert(
+            info.id.clone(),
+            InstalledModel {
+                sha256: actual,
+                installed_at_ms: now_ms(),
+            },
+        );
+        state
+            .active
+            .entry(info.engine)
+            .or_insert_with(|| info.id.clone());
+        self.save_state(&state)?;
+        drop(state);
+
+        info!(target: "model_manager", model = %info.id, "model installed");
+        self.emit(ModelEvent::Installed {
+            model_id: info.id.clone(),
+            path: target.clone(),
+        });
+        Ok(target)
+    }
+
+    fn copy_body(
+        &self,
+        info: &ModelInfo,
+        mut reader: impl Read,
+        mut file: File,
+        mut downloaded: u64,
+        total: Option<u64>,
+    ) -> Result<(), ModelError> {
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut reported = downloaded;
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(ModelError::Http(err.to_string())),
+            };
+            file.write_all(&buffer[..read])?;
+            downloaded += read as u64;
+            if downloaded - reported >= PROGRESS_STEP_BYTES {
+                reported = downloaded;
+                self.emit(ModelEvent::Progress {
+                    model_id: info.id.clone(),
+                    downloaded_bytes: downloaded,
+                    total_bytes: total,
+                });
+            }
+        }
+        file.sync_all()?;
+        self.emit(ModelEvent::Progress {
+            model_id: info.id.clone(),
+            downloaded_bytes: downloaded,
+            total_bytes: total,
+        });
+        Ok(())
+    }
+
+    fn emit(&self, event: ModelEvent) {
+        let _ = self.inner.events.send(event);
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ModelState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn save_state(&self, state: &ModelState) -> Result<(), ModelError> {
+        fs::create_dir_all(&self.inner.dir)?;
+        let bytes =
+            serde_json::to_vec_pretty(state).map_err(|err| ModelError::State(err.to_string()))?;
+        let path = self.inner.dir.join(STATE_FILE);
+        let staging = path.with_extension("tmp");
+        fs::write(&staging, bytes)?;
+        fs::rename(&staging, &path)?;
+        Ok(())
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>, ModelError> {
+    match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|err| ModelError::State(format!("{}: {err}", path.display()))),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut context = Context::new(&SHA256);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// 按 `Range` 请求头返回 `body` 的剩余部分，处理 `requests` 个请求后退出。
+    fn serve(body: Vec<u8>, requests: usize) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind");
+        let address = listener.local_addr().expect("addr");
+        let handle = thread::spawn(move || {
+            let mut ranges = Vec::new();
+            for _ in 0..requests {
+                let (mut stream, _) = listener.accept().expect("accept");
+                let mut request = [0u8; 1024];
+                let read = stream.read(&mut request).expect("read request");
+                let request = String::from_utf8_lossy(&request[..read]).to_ascii_lowercase();
+                let start = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("range: bytes="))
+                    .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+                ranges.push(start.map(|start| start.to_string()).unwrap_or_default());
+                let (status, body) = match start {
+                    Some(start) => ("206 Partial Content", &body[start..]),
+                    None => ("200 OK", &body[..]),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).expect("write head");
+                stream.write_all(body).expect("write body");
+            }
+            ranges
+        });
+        (format!("http://{address}"), handle)
+    }
+
+    fn test_model(url: &str, sha256: Option<String>) -> ModelInfo {
+        ModelInfo {
+            id: "test".into(),
+            engine: ModelEngine::Whisper,
+            file_name: "ggml-test.bin".into(),
+            url: format!("{url}/ggml-test.bin"),
+            size_bytes: 10,
+            language: Some("en".into()),
+            quality: ModelQuality::Fast,
+            sha256,
+        }
+    }
+
+    #[tokio::test]
+    async fn resumes_partial_download_and_verifies_checksum() {
+        let body = b"0123456789".to_vec();
+        let digest: String = ring::digest::digest(&SHA256, &body)
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let (url, server) = serve(body.clone(), 2);
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut catalog = vec![
+            test_model(&url, Some(digest.clone())),
+            test_model(&url, Some("00".repeat(32))),
+        ];
+        catalog[1].id = "tampered".into();
+        catalog[1].file_name = "ggml-tampered.bin".into();
+        fs::write(
+            dir.path().join(CATALOG_FILE),
+            serde_json::to_vec(&catalog).unwrap(),
+        )
+        .unwrap();
+        fs::write(dir.path().join("ggml-test.part"), &body[..4]).unwrap();
+
+        let manager = ModelManager::new(dir.path());
+        let mut events = manager.subscribe();
+        let path = manager.download("test").await.expect("download");
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert!(!dir.path().join("ggml-test.part").exists());
+        assert_eq!(
+            events.recv().await.unwrap(),
+            ModelEvent::Started {
+                model_id: "test".into(),
+                resumed_from: 4,
+            }
+        );
+
+        let err = manager.download("tampered").await.unwrap_err();
+        assert!(matches!(err, ModelError::ChecksumMismatch { .. }));
+        assert!(!dir.path().join("ggml-tampered.part").exists());
+        assert_eq!(server.join().unwrap(), vec!["4".to_string(), String::new()]);
+
+        // 首个安装的模型自动成为该引擎的当前模型，状态重开后仍在。
+        let reopened = ModelManager::new(dir.path());
+        assert_eq!(reopened.active_path(ModelEngine::Whisper), Some(path));
+        assert!(reopened.verify("test").unwrap());
+        let status = reopened.list();
+        let test = status.iter().find(|model| model.info.id == "test").unwrap();
+        assert!(test.installed && test.active && !test.update_available);
+        assert!(matches!(
+            reopened.set_active("tampered"),
+            Err(ModelError::NotInstalled(_))
+        ));
+    }
+}
@@ -0,0 +1,282 @@
+//! 双引擎对比模式：本地与云端并行识别同一帧，按置信度、延迟与一致性裁决胜出方。
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::stabilizer::tokenize;
+use super::vocabulary::ScoredToken;
+use super::TranscriptSource;
+
+/// 等待另一引擎结果的最大帧数，超出后丢弃未配对的结果。
+const MAX_PENDING_FRAMES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbitrationConfig {
+    pub confidence_weight: f32,
+    pub latency_weight: f32,
+    /// 延迟达到该值时延迟得分为 0。
+    pub latency_budget: Duration,
+    /// 两个结果的一致度达到该值时视为一致，直接取置信度更高者。
+    pub agreement_threshold: f32,
+}
+
+impl Default for ArbitrationConfig {
+    fn default() -> Self {
+        Self {
+            confidence_weight: 0.7,
+            latency_weight: 0.3,
+            latency_budget: Duration::from_millis(1_500),
+            agreement_threshold: 0.9,
+        }
+    }
+}
+
+/// 某个引擎对一帧给出的结果。
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineCandidate {
+    pub source: TranscriptSource,
+    pub text: String,
+    /// 词级置信度的均值；引擎不提供时为空。
+    pub confidence: Option<f32>,
+    pub latency: Duration,
+    /// 该帧结果对应下发的句子。
+    pub sentence_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArbitrationReason {
+    /// 两个结果一致，取置信度更高者，持平时取本地。
+    Agreement,
+    /// 结果不一致，按加权得分裁决。
+    Score,
+    /// 只有一方产出文本。
+    SoleResult,
+}
+
+impl ArbitrationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArbitrationReason::Agreement => "agreement",
+            ArbitrationReason::Score => "score",
+            ArbitrationReason::SoleResult => "sole_result",
+        }
+    }
+}
+
+/// 一帧的裁决结果，两个候选均保留供双视图展示。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbitrationDecision {
+    pub frame_index: usize,
+    pub winner: TranscriptSource,
+    pub reason: ArbitrationReason,
+    pub agreement: f32,
+    pub local: EngineCandidate,
+    pub cloud: EngineCandidate,
+    pub local_score: f32,
+    pub cloud_score: f32,
+}
+
+/// 按帧配对两个引擎的结果。
+#[derive(Debug)]
+pub struct Arbiter {
+    config: ArbitrationConfig,
+    pending: BTreeMap<usize, (Option<EngineCandidate>, Option<EngineCandidate>)>,
+}
+
+impl Arbiter {
+    pub fn new(config: ArbitrationConfig) -> Self {
+        Self {
+            config,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// 记录一个候选；同一帧的两个结果都到齐时返回裁决。两者均为空文本时不裁决。
+    pub fn record(
+        &mut self,
+        frame_index: usize,
+        candidate: EngineCandidate,
+    ) -> Option<ArbitrationDecision> {
+        let oldest = frame_index.saturating_sub(MAX_PENDING_FRAMES);
+        self.pending = self.pending.split_off(&oldest);
+
+        let slot = self.pending.entry(frame_index).or_default();
+        match candidate.source {
+            TranscriptSource::Cloud => slot.1 = Some(candidate),
+            _ => slot.0 = Some(candidate),
+        }
+        if slot.0.is_none() || slot.1.is_none() {
+            return None;
+        }
+        let (local, cloud) = self.pending.remove(&frame_index)?;
+        let (local, cloud) = (local?, cloud?);
+        if local.text.trim().is_empty() && cloud.text.trim().is_empty() {
+            return None;
+        }
+        Some(self.decide(frame_index, local, cloud))
+    }
+
+    fn score(&self, candidate: &EngineCandidate) -> f32 {
+        let confidence = candidate.confidence.unwrap_or(0.5);
+        let budget = self.config.latency_budget.as_secs_f32().max(f32::EPSILON);
+        let latency = 1.0 - (candidate.latency.as_secs_f32() / budget).min(1.0);
+        self.config.confidence_weight * confidence + self.config.latency_weight * latency
+    }
+
+    fn decide(
+        &self,
+        frame_index: usize,
+        local: EngineCandidate,
+        cloud: EngineCandidate,
+    ) -> ArbitrationDecision {
+        let agreement = agreement(&local.text, &cloud.text);
+        let local_score = self.score(&local);
+        let cloud_score = self.score(&cloud);
+        let (winner, reason) = if cloud.text.trim().is_empty() {
+            (TranscriptSource::Local, ArbitrationReason::SoleResult)
+        } else if local.text.trim().is_empty() {
+            (TranscriptSource::Cloud, ArbitrationReason::SoleResult)
+        } else if agreement >= self.config.agreement_threshold {
+            let local_confidence = local.confidence.unwrap_or(0.0);
+            let cloud_confidence = cloud.confidence.unwrap_or(0.0);
+            let winner = if cloud_confidence > local_confidence {
+                TranscriptSource::Cloud
+            } else {
+                TranscriptSource::Local
+            };
+            (winner, ArbitrationReason::Agreement)
+        } else if cloud_score > local_score {
+            (TranscriptSource::Cloud, ArbitrationReason::Score)
+        } else {
+            (TranscriptSource::Local, ArbitrationReason::Score)
+        };
+        ArbitrationDecision {
+            frame_index,
+            winner,
+            reason,
+            agreement,
+            local,
+            cloud,
+            local_score,
+            cloud_score,
+        }
+    }
+}
+
+/// 词级置信度的均值；没有任何词带置信度时为空。
+pub fn mean_confidence(tokens: &[ScoredToken]) -> Option<f32> {
+    let scores: Vec<f32> = tokens.iter().filter_map(|token| token.confidence).collect();
+    (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32)
+}
+
+/// 以忽略大小写与标点的词元序列计算一致度：2·LCS / (m + n)。
+pub fn agreement(left: &str, right: &str) -> f32 {
+    let normalize = |text: &str| -> Vec<String> {
+        tokenize(text)
+            .into_iter()
+            .map(|token| {
+                token
+                    .chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect::<String>()
+            })
+            .filter(|token| !token.is_empty())
+            .collect()
+    };
+    let (left, right) = (normalize(left), normalize(right));
+    if left.is_empty() && right.is_empty() {
+        return 1.0;
+    }
+    let mut row = vec![0usize; right.len() + 1];
+    for token in &left {
+        let mut diagonal = 0;
+        for (index, other) in right.iter().enumerate() {
+            let above = row[index + 1];
+            row[index + 1] = if token == other {
+                diagonal + 1
+            } else {
+                above.max(row[index])
+            };
+            diagonal = above;
+        }
+    }
+    2.0 * row[right.len()] as f32 / (left.len() + right.len()) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(
+        source: TranscriptSource,
+        text: &str,
+        confidence: Option<f32>,
+        latency_ms: u64,
+    ) -> EngineCandidate {
+        EngineCandidate {
+            source,
+            text: text.into(),
+            confidence,
+            latency: Duration::from_millis(latency_ms),
+            sentence_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn measures_agreement_ignoring_case_and_punctuation() {
+        assert_eq!(agreement("Ship it today.", "ship it today"), 1.0);
+        assert!((agreement("ship it today", "ship it tomorrow") - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(agreement("", ""), 1.0);
+        assert_eq!(agreement("你好世界", "你好"), 2.0 * 2.0 / 6.0);
+    }
+
+    #[test]
+    fn pairs_results_by_frame_and_picks_winner() {
+        let mut arbiter = Arbiter::new(ArbitrationConfig::default());
+        assert!(arbiter
+            .record(
+                1,
+                candidate(TranscriptSource::Local, "ship to git hub", Some(0.4), 120)
+            )
+            .is_none());
+        let decision = arbiter
+            .record(
+                1,
+                candidate(TranscriptSource::Cloud, "ship to GitHub", Some(0.9), 600),
+            )
+            .expect("both results arrived");
+        assert_eq!(decision.winner, TranscriptSource::Cloud);
+        assert_eq!(decision.reason, ArbitrationReason::Score);
+
+        arbiter.record(
+            2,
+            candidate(TranscriptSource::Cloud, "hello", Some(0.9), 800),
+        );
+        let decision = arbiter
+            .record(
+                2,
+                candidate(TranscriptSource::Local, "Hello.", Some(0.9), 80),
+            )
+            .unwrap();
+        assert_eq!(decision.winner, TranscriptSource::Local);
+        assert_eq!(decision.reason, ArbitrationReason::Agreement);
+
+        arbiter.record(3, candidate(TranscriptSource::Local, "", None, 50));
+        let decision = arbiter
+            .record(
+                3,
+                candidate(TranscriptSource::Cloud, "late words", None, 900),
+            )
+            .unwrap();
+        assert_eq!(decision.winner, TranscriptSource::Cloud);
+        assert_eq!(decision.reason, ArbitrationReason::SoleResult);
+
+        // 长期未配对的结果会被淘汰。
+        arbiter.record(4, candidate(TranscriptSource::Local, "lost", None, 50));
+        arbiter.record(200, candidate(TranscriptSource::Local, "new", None, 50));
+        assert!(arbiter
+            .record(4, candidate(TranscriptSource::Cloud, "lost", None, 50))
+            .is_none());
+    }
+}
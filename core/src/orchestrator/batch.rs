@@ -0,0 +1,168 @@
+//! 离线批量转写：把整段音频按实时会话的帧窗口送入编排器，汇总原始稿与润色稿。
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::time::timeout;
+
+use super::{
+    EngineOrchestrator, RealtimeSessionConfig, TranscriptSource, TranscriptionUpdate, UpdatePayload,
+};
+
+/// 送完音频后等待剩余结果的额外时长。
+const DRAIN_GRACE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSentence {
+    pub sentence_id: u64,
+    pub raw: Option<String>,
+    pub polished: Option<String>,
+}
+
+impl BatchSentence {
+    /// 优先润色稿。
+    pub fn text(&self) -> &str {
+        self.polished
+            .as_deref()
+            .or(self.raw.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchTranscript {
+    pub sentences: Vec<BatchSentence>,
+    pub notices: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+impl BatchTranscript {
+    pub fn text(&self) -> String {
+        self.sentences
+            .iter()
+            .map(BatchSentence::text)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl EngineOrchestrator {
+    /// 以实时会话转写一段 16kHz 单声道音频。会话按音频时长节奏处理，
+    /// 送完后在润色截止时间内无新结果即视为结束。
+    pub async fn transcribe_batch(
+        &self,
+        samples: &[f32],
+        config: RealtimeSessionConfig,
+    ) -> BatchTranscript {
+        let started = Instant::now();
+        let frame_samples = ((config.max_frame_duration.as_secs_f64()
+            * config.sample_rate_hz as f64) as usize)
+            .max(1);
+        let idle = config.polish_emit_deadline + DRAIN_GRACE;
+        let (handle, mut updates) = self.start_realtime_session(config);
+
+        let pushed = Arc::new(AtomicBool::new(false));
+        let collector_pushed = Arc::clone(&pushed);
+        let collector = tokio::spawn(async move {
+            let mut sentences: BTreeMap<u64, BatchSentence> = BTreeMap::new();
+            let mut notices = Vec::new();
+            loop {
+                match timeout(idle, updates.recv()).await {
+                    Ok(Some(update)) => record_update(update, &mut sentences, &mut notices),
+                    Ok(None) => break,
+                    Err(_) if collector_pushed.load(Ordering::SeqCst) => break,
+                    Err(_) => continue,
+                }
+            }
+            (sentences, notices)
+        });
+
+        for frame in samples.chunks(frame_samples) {
+            if handle.push_frame(frame.to_vec()).await.is_err() {
+                break;
+            }
+        }
+        pushed.store(true, Ordering::SeqCst);
+
+        let (sentences, notices) = collector.await.unwrap_or_default();
+        drop(handle);
+        BatchTranscript {
+            sentences: sentences.into_values().collect(),
+            notices,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+fn record_update(
+    update: TranscriptionUpdate,
+    sentences: &mut BTreeMap<u64, BatchSentence>,
+    notices: &mut Vec<String>,
+) {
+    match update.payload {
+        UpdatePayload::Transcript(payload) => {
+            let sentence = sentences
+                .entry(payload.sentence_id)
+                .or_insert_with(|| BatchSentence {
+                    sentence_id: payload.sentence_id,
+                    ..BatchSentence::default()
+                });
+            match payload.source {
+                TranscriptSource::Polished => sentence.polished = Some(payload.text),
+                TranscriptSource::Local | TranscriptSource::Cloud => {
+                    sentence.raw = Some(payload.text)
+                }
+            }
+        }
+        UpdatePayload::Notice(notice) => notices.push(notice.message),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, SpeechEngine};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    struct ScriptedEngine(Mutex<Vec<&'static str>>);
+
+    #[async_trait]
+    impl SpeechEngine for ScriptedEngine {
+        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
+            Ok(self.0.lock().unwrap().pop().unwrap_or_default().to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn collects_raw_and_polished_sentences() {
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ScriptedEngine(Mutex::new(vec!["uh see you at two."]))),
+        );
+        let transcript = orchestrator
+            .transcribe_batch(
+                &[0.2; 3_200],
+                RealtimeSessionConfig {
+                    polish_emit_deadline: Duration::from_millis(200),
+                    ..RealtimeSessionConfig::default()
+                },
+            )
+            .await;
+
+        assert_eq!(transcript.sentences.len(), 1);
+        let sentence = &transcript.sentences[0];
+        assert_eq!(sentence.raw.as_deref(), Some("uh see you at two."));
+        assert_eq!(transcript.text(), "See you at two.");
+    }
+}
@@ -0,0 +1,564 @@
+//! 云端用量预算：按 UTC 自然日与自然月累计云端识别时长与大模型 token，
+//! 接近上限时提醒一次，超出后由 `CloudGate` 改用本地处理。
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::offline::CloudFeature;
+use super::{
+    LanguageGuess, PhraseHint, PolishProfile, ScoredTranscript, SentencePolisher, SpeechEngine,
+    Translator,
+};
+use crate::power::PerformanceLevel;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1_000;
+/// 用量达到上限的该比例时提醒。
+pub const DEFAULT_WARN_RATIO: f64 = 0.8;
+
+/// 计量的云端资源。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetMeter {
+    /// 发往云端识别的音频秒数。
+    CloudSeconds,
+    /// 云端润色与翻译的输入输出 token（估算）。
+    PolishTokens,
+}
+
+impl BudgetMeter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetMeter::CloudSeconds => "cloud_seconds",
+            BudgetMeter::PolishTokens => "polish_tokens",
+        }
+    }
+
+    pub fn for_feature(feature: CloudFeature) -> Self {
+        match feature {
+            CloudFeature::Transcription => BudgetMeter::CloudSeconds,
+            CloudFeature::Polish | CloudFeature::Translation => BudgetMeter::PolishTokens,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            BudgetMeter::CloudSeconds => "云端识别时长",
+            BudgetMeter::PolishTokens => "云端润色用量",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Day,
+    Month,
+}
+
+impl BudgetPeriod {
+    fn label(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Day => "今日",
+            BudgetPeriod::Month => "本月",
+        }
+    }
+}
+
+/// 用户设置的上限；未设置的项不限制。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetCaps {
+    pub daily_cloud_secs: Option<u64>,
+    pub monthly_cloud_secs: Option<u64>,
+    pub daily_polish_tokens: Option<u64>,
+    pub monthly_polish_tokens: Option<u64>,
+    pub warn_ratio: f64,
+}
+
+impl Default for BudgetCaps {
+    fn default() -> Self {
+        Self {
+            daily_cloud_secs: None,
+            monthly_cloud_secs: None,
+            daily_polish_tokens: None,
+            monthly_polish_tokens: None,
+            warn_ratio: DEFAULT_WARN_RATIO,
+        }
+    }
+}
+
+impl BudgetCaps {
+    /// 以计量单位表示的上限：识别为毫秒，润色为 token。
+    fn limit(&self, meter: BudgetMeter, period: BudgetPeriod) -> Option<u64> {
+        match (meter, period) {
+            (BudgetMeter::CloudSeconds, BudgetPeriod::Day) => self.daily_cloud_secs,
+            (BudgetMeter::CloudSeconds, BudgetPeriod::Month) => self.monthly_cloud_secs,
+            (BudgetMeter::PolishTokens, BudgetPeriod::Day) => self.daily_polish_tokens,
+            (BudgetMeter::PolishTokens, BudgetPeriod::Month) => self.monthly_polish_tokens,
+        }
+        .map(|cap| match meter {
+            BudgetMeter::CloudSeconds => cap.saturating_mul(1_000),
+            BudgetMeter::PolishTokens => cap,
+        })
+    }
+}
+
+/// 当前日、月的累计用量；跨日或跨月时对应计数清零。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BudgetUsage {
+    /// 自 Unix 纪元起的 UTC 日序号。
+    pub day: i64,
+    /// `年 * 12 + 月 - 1`。
+    pub month: i64,
+    pub daily_cloud_ms: u64,
+    pub monthly_cloud_ms: u64,
+    pub daily_polish_tokens: u64,
+    pub monthly_polish_tokens: u64,
+}
+
+impl BudgetUsage {
+    fn roll(&mut self, now_ms: i64) -> Vec<BudgetPeriod> {
+        let day = now_ms.div_euclid(DAY_MS);
+        let month = month_index(day);
+        let mut rolled = Vec::new();
+        if self.day != day {
+            self.day = day;
+            self.daily_cloud_ms = 0;
+            self.daily_polish_tokens = 0;
+            rolled.push(BudgetPeriod::Day);
+        }
+        if self.month != month {
+            self.month = month;
+            self.monthly_cloud_ms = 0;
+            self.monthly_polish_tokens = 0;
+            rolled.push(BudgetPeriod::Month);
+        }
+        rolled
+    }
+
+    fn used(&self, meter: BudgetMeter, period: BudgetPeriod) -> u64 {
+        match (meter, period) {
+            (BudgetMeter::CloudSeconds, BudgetPeriod::Day) => self.daily_cloud_ms,
+            (BudgetMeter::CloudSeconds, BudgetPeriod::Month) => self.monthly_cloud_ms,
+            (BudgetMeter::PolishTokens, BudgetPeriod::Day) => self.daily_polish_tokens,
+            (BudgetMeter::PolishTokens, BudgetPeriod::Month) => self.monthly_polish_tokens,
+        }
+    }
+
+    fn add(&mut self, meter: BudgetMeter, amount: u64) {
+        let (daily, monthly) = match meter {
+            BudgetMeter::CloudSeconds => (&mut self.daily_cloud_ms, &mut self.monthly_cloud_ms),
+            BudgetMeter::PolishTokens => (
+                &mut self.daily_polish_tokens,
+                &mut self.monthly_polish_tokens,
+            ),
+        };
+        *daily = daily.saturating_add(amount);
+        *monthly = monthly.saturating_add(amount);
+    }
+}
+
+/// 日序号所在的年月（公历），算法见 Howard Hinnant 的 `civil_from_days`。
+fn month_index(day: i64) -> i64 {
+    let z = day + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    year * 12 + month - 1
+}
+
+/// 单项用量，识别时长以秒计。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetLine {
+    pub meter: BudgetMeter,
+    pub period: BudgetPeriod,
+    pub used: f64,
+    pub cap: Option<u64>,
+    pub exceeded: bool,
+}
+
+/// 供统计接口展示的预算状态。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetReport {
+    pub caps: BudgetCaps,
+    pub lines: Vec<BudgetLine>,
+}
+
+#[derive(Default)]
+struct BudgetState {
+    usage: BudgetUsage,
+    warned: HashSet<(BudgetMeter, BudgetPeriod)>,
+    dirty: bool,
+}
+
+/// 进程内共享的预算计数器，编排器在放行云端请求前查询、请求完成后计入。
+#[derive(Default)]
+pub struct CloudBudget {
+    caps: RwLock<BudgetCaps>,
+    state: Mutex<BudgetState>,
+}
+
+impl std::fmt::Debug for CloudBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CloudBudget")
+            .field("caps", &self.caps())
+            .field("usage", &self.usage())
+            .finish()
+    }
+}
+
+const METERS: [BudgetMeter; 2] = [BudgetMeter::CloudSeconds, BudgetMeter::PolishTokens];
+const PERIODS: [BudgetPeriod; 2] = [BudgetPeriod::Day, BudgetPeriod::Month];
+
+impl CloudBudget {
+    pub fn new(caps: BudgetCaps) -> Self {
+        Self {
+            caps: RwLock::new(caps),
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn caps(&self) -> BudgetCaps {
+        self.caps
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 更新上限后重新判断是否需要提醒。
+    pub fn set_caps(&self, caps: BudgetCaps) {
+        *self
+            .caps
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = caps;
+        self.lock().warned.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BudgetState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn rolled(&self, now_ms: i64) -> std::sync::MutexGuard<'_, BudgetState> {
+        let mut state = self.lock();
+        for period in state.usage.roll(now_ms) {
+            state.warned.retain(|(_, warned)| *warned != period);
+        }
+        state
+    }
+
+    /// 载入上次保存的用量；已跨日或跨月的部分会被清零。
+    pub fn restore(&self, usage: BudgetUsage) {
+        let mut state = self.lock();
+        state.usage = usage;
+        state.warned.clear();
+        drop(state);
+        drop(self.rolled(now_ms()));
+    }
+
+    pub fn usage(&self) -> BudgetUsage {
+        self.rolled(now_ms()).usage.clone()
+    }
+
+    /// 自上次取出后有新用量时返回当前用量，供宿主落盘。
+    pub fn take_dirty(&self) -> Option<BudgetUsage> {
+        let mut state = self.rolled(now_ms());
+        std::mem::take(&mut state.dirty).then(|| state.usage.clone())
+    }
+
+    /// 计入用量：识别以毫秒计，润色以 token 计。
+    pub fn record(&self, meter: BudgetMeter, amount: u64) {
+        self.record_at(meter, amount, now_ms());
+    }
+
+    fn record_at(&self, meter: BudgetMeter, amount: u64, now_ms: i64) {
+        if amount == 0 {
+            return;
+        }
+        let mut state = self.rolled(now_ms);
+        state.usage.add(meter, amount);
+        state.dirty = true;
+    }
+
+    /// 已超出上限的周期；未超出时为 `None`。
+    pub fn exceeded(&self, meter: BudgetMeter) -> Option<BudgetPeriod> {
+        self.exceeded_at(meter, now_ms())
+    }
+
+    fn exceeded_at(&self, meter: BudgetMeter, now_ms: i64) -> Option<BudgetPeriod> {
+        let caps = self.caps();
+        let state = self.rolled(now_ms);
+        PERIODS.into_iter().find(|period| {
+            caps.limit(meter, *period)
+                .is_some_and(|limit| state.usage.used(meter, *period) >= limit)
+        })
+    }
+
+    /// 用量首次越过提醒比例时返回提醒文案，同一周期内只提醒一次。
+    pub fn take_warning(&self, meter: BudgetMeter) -> Option<String> {
+        self.take_warning_at(meter, now_ms())
+    }
+
+    fn take_warning_at(&self, meter: BudgetMeter, now_ms: i64) -> Option<String> {
+        let caps = self.caps();
+        let mut state = self.rolled(now_ms);
+        for period in PERIODS {
+            let Some(limit) = caps.limit(meter, period).filter(|limit| *limit > 0) else {
+                continue;
+            };
+            let ratio = state.usage.used(meter, period) as f64 / limit as f64;
+            if ratio >= caps.warn_ratio && ratio < 1.0 && state.warned.insert((meter, period)) {
+                return Some(format!(
+                    "{}{}已用 {:.0}%，达到上限后将改用本地处理",
+                    period.label(),
+                    meter.label(),
+                    ratio * 100.0
+                ));
+            }
+        }
+        None
+    }
+
+    pub fn report(&self) -> BudgetReport {
+        let caps = self.caps();
+        let state = self.rolled(now_ms());
+        let lines = METERS
+            .into_iter()
+            .flat_map(|meter| PERIODS.into_iter().map(move |period| (meter, period)))
+            .map(|(meter, period)| {
+                let used = state.usage.used(meter, period);
+                let limit = caps.limit(meter, period);
+                BudgetLine {
+                    meter,
+                    period,
+                    used: match meter {
+                        BudgetMeter::CloudSeconds => used as f64 / 1_000.0,
+                        BudgetMeter::PolishTokens => used as f64,
+                    },
+                    cap: match meter {
+                        BudgetMeter::CloudSeconds => limit.map(|limit| limit / 1_000),
+                        BudgetMeter::PolishTokens => limit,
+                    },
+                    exceeded: limit.is_some_and(|limit| used >= limit),
+                }
+            })
+            .collect();
+        BudgetReport { caps, lines }
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// 粗略估算 token：约四个字符一个 token，中日文约一字一个。
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (wide, narrow) =
+        text.chars()
+            .filter(|ch| !ch.is_whitespace())
+            .fold((0u64, 0u64), |(wide, narrow), ch| {
+                if (ch as u32) >= 0x2E80 {
+                    (wide + 1, narrow)
+                } else {
+                    (wide, narrow + 1)
+                }
+            });
+    wide + narrow.div_ceil(4)
+}
+
+/// 计入云端识别时长的引擎包装。
+pub(crate) struct MeteredEngine {
+    inner: Arc<dyn SpeechEngine>,
+    budget: Arc<CloudBudget>,
+    sample_rate_hz: u32,
+}
+
+impl MeteredEngine {
+    pub(crate) fn new(
+        inner: Arc<dyn SpeechEngine>,
+        budget: Arc<CloudBudget>,
+        sample_rate_hz: u32,
+    ) -> Self {
+        Self {
+            inner,
+            budget,
+            sample_rate_hz: sample_rate_hz.max(1),
+        }
+    }
+
+    fn record(&self, samples: &[f32]) {
+        let ms = samples.len() as u64 * 1_000 / u64::from(self.sample_rate_hz);
+        self.budget.record(BudgetMeter::CloudSeconds, ms);
+    }
+}
+
+#[async_trait]
+impl SpeechEngine for MeteredEngine {
+    async fn transcribe(&self, frame: &[f32]) -> Result<String> {
+        self.record(frame);
+        self.inner.transcribe(frame).await
+    }
+
+    async fn transcribe_scored(
+        &self,
+        frame: &[f32],
+        hints: &[PhraseHint],
+    ) -> Result<ScoredTranscript> {
+        self.record(frame);
+        self.inner.transcribe_scored(frame, hints).await
+    }
+
+    async fn detect_language(&self, samples: &[f32]) -> Result<Option<LanguageGuess>> {
+        self.record(samples);
+        self.inner.detect_language(samples).await
+    }
+
+    async fn set_language(&self, language: &str) -> Result<()> {
+        self.inner.set_language(language).await
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+
+    fn set_performance(&self, level: PerformanceLevel) {
+        self.inner.set_performance(level);
+    }
+}
+
+/// 计入输入与输出 token 的云端润色器包装；请求失败时只计输入。
+pub(crate) struct MeteredPolisher {
+    inner: Arc<dyn SentencePolisher>,
+    budget: Arc<CloudBudget>,
+}
+
+impl MeteredPolisher {
+    pub(crate) fn new(inner: Arc<dyn SentencePolisher>, budget: Arc<CloudBudget>) -> Self {
+        Self { inner, budget }
+    }
+
+    fn record(&self, sentence: &str, result: &Result<String>) {
+        let output = result.as_deref().map(estimate_tokens).unwrap_or_default();
+        self.budget.record(
+            BudgetMeter::PolishTokens,
+            estimate_tokens(sentence) + output,
+        );
+    }
+}
+
+#[async_trait]
+impl SentencePolisher for MeteredPolisher {
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        let result = self.inner.polish(sentence).await;
+        self.record(sentence, &result);
+        result
+    }
+
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        let result = self.inner.polish_streaming(sentence, partial).await;
+        self.record(sentence, &result);
+        result
+    }
+
+    async fn polish_with_profile(
+        &self,
+        sentence: &str,
+        profile: PolishProfile,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        let result = self
+            .inner
+            .polish_with_profile(sentence, profile, partial)
+            .await;
+        self.record(sentence, &result);
+        result
+    }
+}
+
+pub(crate) struct MeteredTranslator {
+    inner: Arc<dyn Translator>,
+    budget: Arc<CloudBudget>,
+}
+
+impl MeteredTranslator {
+    pub(crate) fn new(inner: Arc<dyn Translator>, budget: Arc<CloudBudget>) -> Self {
+        Self { inner, budget }
+    }
+}
+
+#[async_trait]
+impl Translator for MeteredTranslator {
+    async fn translate(&self, text: &str, source: Option<&str>, target: &str) -> Result<String> {
+        let result = self.inner.translate(text, source, target).await;
+        let output = result.as_deref().map(estimate_tokens).unwrap_or_default();
+        self.budget
+            .record(BudgetMeter::PolishTokens, estimate_tokens(text) + output);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-01-31 12:00 UTC。
+    const JAN_31: i64 = 20_484 * DAY_MS + DAY_MS / 2;
+
+    #[test]
+    fn caps_warn_once_then_block_and_reset_on_rollover() {
+        let budget = CloudBudget::new(BudgetCaps {
+            daily_cloud_secs: Some(100),
+            monthly_polish_tokens: Some(1_000),
+            ..BudgetCaps::default()
+        });
+        let meter = BudgetMeter::CloudSeconds;
+        budget.record_at(meter, 79_000, JAN_31);
+        assert_eq!(budget.take_warning_at(meter, JAN_31), None);
+        budget.record_at(meter, 2_000, JAN_31);
+        let warning = budget.take_warning_at(meter, JAN_31).expect("warning");
+        assert!(warning.contains("81%"), "{warning}");
+        assert_eq!(budget.take_warning_at(meter, JAN_31), None);
+        assert_eq!(budget.exceeded_at(meter, JAN_31), None);
+
+        budget.record_at(meter, 19_000, JAN_31);
+        assert_eq!(budget.exceeded_at(meter, JAN_31), Some(BudgetPeriod::Day));
+        assert_eq!(budget.exceeded_at(BudgetMeter::PolishTokens, JAN_31), None);
+
+        // 次日跨月：日计数与月计数都清零。
+        let feb_1 = JAN_31 + DAY_MS;
+        budget.record_at(BudgetMeter::PolishTokens, 0, feb_1);
+        assert_eq!(budget.exceeded_at(meter, feb_1), None);
+        let usage = budget.rolled(feb_1).usage.clone();
+        assert_eq!(usage.daily_cloud_ms, 0);
+        assert_eq!(usage.monthly_cloud_ms, 0);
+        assert_eq!(usage.month, 2026 * 12 + 1);
+    }
+
+    #[test]
+    fn token_estimate_counts_cjk_per_character() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello world"), 3);
+        assert_eq!(estimate_tokens("你好世界"), 4);
+    }
+}
@@ -0,0 +1,219 @@
+//! 听写过程中的语音编辑指令识别。
+
+use serde::{Deserialize, Serialize};
+
+/// 由语音指令转换而来的编辑动作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionCommand {
+    DeleteLastSentence,
+    DeleteLastWord,
+    NewLine,
+    NewParagraph,
+    Undo,
+}
+
+impl SessionCommand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionCommand::DeleteLastSentence => "delete_last_sentence",
+            SessionCommand::DeleteLastWord => "delete_last_word",
+            SessionCommand::NewLine => "new_line",
+            SessionCommand::NewParagraph => "new_paragraph",
+            SessionCommand::Undo => "undo",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPhrase {
+    pub phrase: String,
+    pub command: SessionCommand,
+}
+
+impl CommandPhrase {
+    pub fn new(phrase: impl Into<String>, command: SessionCommand) -> Self {
+        Self {
+            phrase: phrase.into(),
+            command,
+        }
+    }
+}
+
+/// 指令语法。为抑制误触发，只有整句恰好等于某条指令短语（可带礼貌用语、
+/// 可要求唤醒前缀）时才视为指令，句中出现的指令短语仍按正文输出。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandGrammar {
+    pub phrases: Vec<CommandPhrase>,
+    /// 设置后，指令必须以该前缀开头，例如 "flow"。
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 可出现在指令前后的礼貌用语或口头禅，例如 "please"。
+    #[serde(default)]
+    pub fillers: Vec<String>,
+}
+
+impl Default for CommandGrammar {
+    fn default() -> Self {
+        use SessionCommand::*;
+        let phrases = [
+            ("delete last sentence", DeleteLastSentence),
+            ("delete that sentence", DeleteLastSentence),
+            ("删除上一句", DeleteLastSentence),
+            ("delete last word", DeleteLastWord),
+            ("删除上一个词", DeleteLastWord),
+            ("new line", NewLine),
+            ("换行", NewLine),
+            ("new paragraph", NewParagraph),
+            ("另起一段", NewParagraph),
+            ("undo that", Undo),
+            ("scratch that", Undo),
+            ("撤销", Undo),
+        ]
+        .into_iter()
+        .map(|(phrase, command)| CommandPhrase::new(phrase, command))
+        .collect();
+
+        Self {
+            phrases,
+            prefix: None,
+            fillers: ["please", "okay", "ok", "um", "uh", "请", "麻烦"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+impl CommandGrammar {
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// 将一句转写识别为指令；不是指令时返回 `None`。
+    pub fn recognize(&self, utterance: &str) -> Option<SessionCommand> {
+        let mut words = normalize_words(utterance);
+        if let Some(prefix) = &self.prefix {
+            let prefix = normalize_words(prefix);
+            if prefix.is_empty() || !words.starts_with(&prefix) {
+                return None;
+            }
+            words.drain(..prefix.len());
+        }
+
+        let fillers: Vec<Vec<String>> = self.fillers.iter().map(|f| normalize_words(f)).collect();
+        strip_fillers(&mut words, &fillers);
+        if words.is_empty() {
+            return None;
+        }
+
+        let spoken = words.concat();
+        self.phrases
+            .iter()
+            .find(|phrase| normalize_words(&phrase.phrase).concat() == spoken)
+            .map(|phrase| phrase.command)
+    }
+}
+
+/// 小写、去除标点后按空白切词。中文短语没有空格，整体作为一个词。
+fn normalize_words(text: &str) -> Vec<String> {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+fn strip_fillers(words: &mut Vec<String>, fillers: &[Vec<String>]) {
+    loop {
+        let before = words.len();
+        for filler in fillers.iter().filter(|filler| !filler.is_empty()) {
+            if words.starts_with(filler) {
+                words.drain(..filler.len());
+            }
+            if words.ends_with(filler) {
+                words.truncate(words.len() - filler.len());
+            }
+            // 中文口头禅与指令之间通常没有空格。
+            if let [single] = filler.as_slice() {
+                if single.is_ascii() {
+                    continue;
+                }
+                if let Some(first) = words.first_mut() {
+                    if first.len() > single.len() && first.starts_with(single.as_str()) {
+                        first.drain(..single.len());
+                    }
+                }
+            }
+        }
+        if words.len() == before {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_whole_utterance_commands() {
+        let grammar = CommandGrammar::default();
+        assert_eq!(
+            grammar.recognize("Delete last sentence."),
+            Some(SessionCommand::DeleteLastSentence)
+        );
+        assert_eq!(
+            grammar.recognize("New paragraph, please"),
+            Some(SessionCommand::NewParagraph)
+        );
+        assert_eq!(
+            grammar.recognize("Scratch that!"),
+            Some(SessionCommand::Undo)
+        );
+        assert_eq!(
+            grammar.recognize("请另起一段。"),
+            Some(SessionCommand::NewParagraph)
+        );
+        assert_eq!(grammar.recognize("newline"), Some(SessionCommand::NewLine));
+    }
+
+    #[test]
+    fn suppresses_commands_embedded_in_dictation() {
+        let grammar = CommandGrammar::default();
+        for sentence in [
+            "We should delete last sentence from the draft.",
+            "Start a new paragraph about pricing.",
+            "I can't undo that decision.",
+            "please",
+            "",
+            "我们需要换行符。",
+        ] {
+            assert_eq!(grammar.recognize(sentence), None, "{sentence:?}");
+        }
+    }
+
+    #[test]
+    fn honours_configured_prefix_and_custom_phrases() {
+        let mut grammar = CommandGrammar::default().with_prefix("flow");
+        grammar
+            .phrases
+            .push(CommandPhrase::new("strike that", SessionCommand::Undo));
+
+        assert_eq!(grammar.recognize("new line"), None);
+        assert_eq!(
+            grammar.recognize("Flow, new line."),
+            Some(SessionCommand::NewLine)
+        );
+        assert_eq!(
+            grammar.recognize("flow strike that"),
+            Some(SessionCommand::Undo)
+        );
+        assert_eq!(grammar.recognize("flow"), None);
+    }
+}
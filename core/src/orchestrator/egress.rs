@@ -0,0 +1,171 @@
+//! 云端出口的外发审计：包装云端引擎、润色器与翻译器，每次把音频或文本交出前记录一次。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use super::{
+    LanguageGuess, PhraseHint, PolishProfile, ScoredTranscript, SentencePolisher, SpeechEngine,
+    Translator,
+};
+use crate::persistence::audit::{EgressChannel, EgressRecorder};
+use crate::power::PerformanceLevel;
+
+/// 云端引擎没有公开的地址，审计记录中以此标识。
+pub(crate) const CLOUD_ENGINE_DESTINATION: &str = "cloud_engine";
+
+/// 音频帧按小端 `f32` 字节计算摘要。
+fn frame_bytes(frame: &[f32]) -> Vec<u8> {
+    frame
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect()
+}
+
+pub(crate) struct AuditedEngine {
+    inner: Arc<dyn SpeechEngine>,
+    egress: EgressRecorder,
+}
+
+impl AuditedEngine {
+    pub(crate) fn new(inner: Arc<dyn SpeechEngine>, egress: EgressRecorder) -> Self {
+        Self { inner, egress }
+    }
+
+    fn record(&self, frame: &[f32]) {
+        if self.egress.is_enabled() {
+            self.egress.record(
+                EgressChannel::CloudTranscription,
+                CLOUD_ENGINE_DESTINATION,
+                &frame_bytes(frame),
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechEngine for AuditedEngine {
+    async fn transcribe(&self, frame: &[f32]) -> Result<String> {
+        self.record(frame);
+        self.inner.transcribe(frame).await
+    }
+
+    async fn transcribe_scored(
+        &self,
+        frame: &[f32],
+        hints: &[PhraseHint],
+    ) -> Result<ScoredTranscript> {
+        self.record(frame);
+        self.inner.transcribe_scored(frame, hints).await
+    }
+
+    async fn detect_language(&self, samples: &[f32]) -> Result<Option<LanguageGuess>> {
+        self.record(samples);
+        self.inner.detect_language(samples).await
+    }
+
+    async fn set_language(&self, language: &str) -> Result<()> {
+        self.inner.set_language(language).await
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+
+    fn set_performance(&self, level: PerformanceLevel) {
+        self.inner.set_performance(level);
+    }
+}
+
+pub(crate) struct AuditedPolisher {
+    inner: Arc<dyn SentencePolisher>,
+    egress: EgressRecorder,
+    destination: String,
+}
+
+impl AuditedPolisher {
+    pub(crate) fn new(
+        inner: Arc<dyn SentencePolisher>,
+        egress: EgressRecorder,
+        destination: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner,
+            egress,
+            destination: destination.into(),
+        }
+    }
+
+    fn record(&self, sentence: &str) {
+        if !sentence.trim().is_empty() {
+            self.egress.record(
+                EgressChannel::CloudPolish,
+                &self.destination,
+                sentence.as_bytes(),
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl SentencePolisher for AuditedPolisher {
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        self.record(sentence);
+        self.inner.polish(sentence).await
+    }
+
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.record(sentence);
+        self.inner.polish_streaming(sentence, partial).await
+    }
+
+    async fn polish_with_profile(
+        &self,
+        sentence: &str,
+        profile: PolishProfile,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.record(sentence);
+        self.inner
+            .polish_with_profile(sentence, profile, partial)
+            .await
+    }
+}
+
+pub(crate) struct AuditedTranslator {
+    inner: Arc<dyn Translator>,
+    egress: EgressRecorder,
+    destination: String,
+}
+
+impl AuditedTranslator {
+    pub(crate) fn new(
+        inner: Arc<dyn Translator>,
+        egress: EgressRecorder,
+        destination: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner,
+            egress,
+            destination: destination.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for AuditedTranslator {
+    async fn translate(&self, text: &str, source: Option<&str>, target: &str) -> Result<String> {
+        self.egress.record(
+            EgressChannel::CloudTranslation,
+            &self.destination,
+            text.as_bytes(),
+        );
+        self.inner.translate(text, source, target).await
+    }
+}
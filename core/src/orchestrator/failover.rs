@@ -0,0 +1,469 @@
+//! 引擎热切换：缓存最近的语音帧，本地引擎故障时重放给备用引擎，并去除与已下发句子重复的部分。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
+use tracing::warn;
+
+use super::stabilizer::tokenize;
+use super::{
+    segment_languages, transcribe_frame, NoticeLevel, PunctuationRestorer, SentenceStore,
+    SessionNotice, SpeechEngine, TranscriptPayload, TranscriptSource, TranscriptionUpdate,
+    UpdatePayload, VocabularyPass,
+};
+use crate::audio::AudioSource;
+
+/// 已下发文本保留的最大字符数，用于与重放结果对齐去重。
+const EMITTED_TAIL_CHARS: usize = 512;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverConfig {
+    /// 缓存的最近语音时长，切换时重放给备用引擎。
+    pub replay_window: Duration,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            replay_window: Duration::from_secs(3),
+        }
+    }
+}
+
+/// 主引擎尚未完整确认的语音帧，超出窗口的旧帧被丢弃。
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    capacity: usize,
+    samples: usize,
+    frames: VecDeque<(usize, Arc<[f32]>)>,
+    emitted_tail: String,
+}
+
+impl ReplayBuffer {
+    pub fn new(config: &FailoverConfig, sample_rate_hz: u32) -> Self {
+        let capacity = (config.replay_window.as_secs_f64() * sample_rate_hz as f64) as usize;
+        Self {
+            capacity: capacity.max(1),
+            samples: 0,
+            frames: VecDeque::new(),
+            emitted_tail: String::new(),
+        }
+    }
+
+    pub fn push(&mut self, frame_index: usize, frame: Arc<[f32]>) {
+        self.samples += frame.len();
+        self.frames.push_back((frame_index, frame));
+        while self.samples > self.capacity && self.frames.len() > 1 {
+            if let Some((_, dropped)) = self.frames.pop_front() {
+                self.samples -= dropped.len();
+            }
+        }
+    }
+
+    /// 主引擎已把 `frame_index`（含）之前的语音完整落为句子，这些帧无需重放。
+    pub fn commit(&mut self, frame_index: usize) {
+        while self
+            .frames
+            .front()
+            .is_some_and(|(index, _)| *index <= frame_index)
+        {
+            if let Some((_, frame)) = self.frames.pop_front() {
+                self.samples -= frame.len();
+            }
+        }
+    }
+
+    /// 记录主引擎已下发的句子，供重放结果去重。
+    pub fn record_emitted(&mut self, sentence: &str) {
+        if !self.emitted_tail.is_empty() && !sentence.starts_with(char::is_whitespace) {
+            self.emitted_tail.push(' ');
+        }
+        self.emitted_tail.push_str(sentence);
+        let excess = self
+            .emitted_tail
+            .chars()
+            .count()
+            .saturating_sub(EMITTED_TAIL_CHARS);
+        if excess > 0 {
+            let cut = self
+                .emitted_tail
+                .char_indices()
+                .nth(excess)
+                .map_or(self.emitted_tail.len(), |(index, _)| index);
+            self.emitted_tail.drain(..cut);
+        }
+    }
+
+    pub fn emitted_tail(&self) -> &str {
+        &self.emitted_tail
+    }
+
+    /// `first..=last` 区间内的帧；区间开头已被淘汰时返回 `None`。
+    pub fn frames_between(&self, first: usize, last: usize) -> Option<Vec<Arc<[f32]>>> {
+        let oldest = self.frames.front().map(|(index, _)| *index)?;
+        if oldest > first {
+            return None;
+        }
+        Some(
+            self.frames
+                .iter()
+                .filter(|(index, _)| (first..=last).contains(index))
+                .map(|(_, frame)| Arc::clone(frame))
+                .collect(),
+        )
+    }
+
+    /// 取出全部待重放的帧。
+    pub fn drain(&mut self) -> Vec<(usize, Arc<[f32]>)> {
+        self.samples = 0;
+        self.frames.drain(..).collect()
+    }
+}
+
+/// 拼接逐帧识别结果，拉丁文字之间补空格。
+pub fn join_segments<'a>(segments: impl IntoIterator<Item = &'a str>) -> String {
+    let mut joined = String::new();
+    for segment in segments
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        let needs_space = joined
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c.is_ascii_punctuation())
+            && segment.starts_with(|c: char| c.is_ascii_alphanumeric());
+        if needs_space {
+            joined.push(' ');
+        }
+        joined.push_str(segment);
+    }
+    joined
+}
+
+/// 去除重放文本开头与已下发文本结尾重合的词元（忽略大小写与标点），返回剩余部分。
+pub fn reconcile_replay(emitted: &str, replayed: &str) -> String {
+    let normalize = |token: &str| -> String {
+        token
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let emitted: Vec<String> = tokenize(emitted)
+        .into_iter()
+        .map(normalize)
+        .filter(|token| !token.is_empty())
+        .collect();
+    let replayed_tokens = tokenize(replayed);
+    // 只统计有字词内容的词元，标点附着在前一个词元上一并移除。
+    let words: Vec<(usize, String)> = replayed_tokens
+        .iter()
+        .enumerate()
+        .map(|(index, token)| (index, normalize(token)))
+        .filter(|(_, token)| !token.is_empty())
+        .collect();
+
+    let max = emitted.len().min(words.len());
+    let overlap = (1..=max)
+        .rev()
+        .find(|&len| {
+            emitted[emitted.len() - len..]
+                .iter()
+                .zip(&words[..len])
+                .all(|(left, (_, right))| left == right)
+        })
+        .unwrap_or(0);
+    if overlap == 0 {
+        return replayed.trim().to_string();
+    }
+
+    let mut skip = words[overlap - 1].0 + 1;
+    while replayed_tokens
+        .get(skip)
+        .is_some_and(|token| normalize(token).is_empty())
+    {
+        skip += 1;
+    }
+    replayed_tokens[skip..].concat().trim().to_string()
+}
+
+/// 热切换状态：缓存待重放的语音帧，切换后由备用引擎接管。
+pub(super) struct FailoverState {
+    standby: Arc<dyn SpeechEngine>,
+    replay: StdMutex<ReplayBuffer>,
+    active: AtomicBool,
+    /// 重放期间持有，接管后的逐帧识别等待重放完成以保持句子顺序。
+    replaying: Mutex<()>,
+    audio_source: AudioSource,
+}
+
+impl FailoverState {
+    pub(super) fn new(
+        standby: Arc<dyn SpeechEngine>,
+        replay: ReplayBuffer,
+        audio_source: AudioSource,
+    ) -> Self {
+        Self {
+            standby,
+            replay: StdMutex::new(replay),
+            active: AtomicBool::new(false),
+            replaying: Mutex::new(()),
+            audio_source,
+        }
+    }
+
+    pub(super) fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub(super) fn replay(&self) -> std::sync::MutexGuard<'_, ReplayBuffer> {
+        self.replay
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 记录本地引擎下发的句子；未成句部分为空时该帧之前的语音无需重放。
+    pub(super) fn record_local(&self, frame_index: usize, sentences: &[String], settled: bool) {
+        let mut replay = self.replay();
+        for sentence in sentences {
+            replay.record_emitted(sentence);
+        }
+        if settled {
+            replay.commit(frame_index);
+        }
+    }
+
+    pub(super) async fn settled(&self) {
+        drop(self.replaying.lock().await);
+    }
+
+    /// 切换到备用引擎：重放缓存的语音，去除与已下发句子重复的部分后作为主结果下发。
+    pub(super) async fn take_over(
+        &self,
+        vocabulary: Option<&VocabularyPass>,
+        punctuation: Option<&(Arc<dyn PunctuationRestorer>, String)>,
+        sentences: &Mutex<SentenceStore>,
+        tx: &mpsc::Sender<TranscriptionUpdate>,
+        frame_index: usize,
+        frame_started: Instant,
+    ) {
+        let _replaying = self.replaying.lock().await;
+        let first_switch = !self.active.swap(true, Ordering::SeqCst);
+        let frames = self.replay().drain();
+        if first_switch {
+            warn!(
+                target: "engine_orchestrator",
+                frame_index,
+                replayed_frames = frames.len(),
+                "local engine failed, switching to standby engine"
+            );
+            let notice = TranscriptionUpdate {
+                payload: UpdatePayload::Notice(SessionNotice {
+                    level: NoticeLevel::Warn,
+                    message: "本地识别异常，已切换云端引擎并补录未完成的语音".to_string(),
+                }),
+                latency: frame_started.elapsed(),
+                frame_index,
+                is_first: false,
+            };
+            if let Err(err) = tx.send(notice).await {
+                warn!(
+                    target: "engine_orchestrator",
+                    %err,
+                    "failed to deliver failover notice"
+                );
+            }
+        }
+
+        let mut segments = Vec::with_capacity(frames.len());
+        for (index, frame) in &frames {
+            match transcribe_frame(self.standby.as_ref(), frame.as_ref(), vocabulary).await {
+                Ok(text) => segments.push(text),
+                Err(err) => warn!(
+                    target: "engine_orchestrator",
+                    %err,
+                    frame_index = *index,
+                    "standby engine failed to transcribe replayed frame"
+                ),
+            }
+        }
+        let replayed = join_segments(segments.iter().map(String::as_str));
+        let emitted_tail = self.replay().emitted_tail().to_string();
+        let text = reconcile_replay(&emitted_tail, &replayed);
+        if text.is_empty() {
+            return;
+        }
+        let text = match punctuation {
+            Some((restorer, language)) => restorer.restore(&text, language),
+            None => text,
+        };
+        self.replay().record_emitted(&text);
+
+        let sentence_id = {
+            let mut store = sentences.lock().await;
+            store.register_raw_sentence(text.clone(), TranscriptSource::Cloud, frame_index)
+        };
+        let segments = segment_languages(&text, punctuation.map(|(_, language)| language.as_str()));
+        let update = TranscriptionUpdate {
+            payload: UpdatePayload::Transcript(TranscriptPayload {
+                sentence_id,
+                text,
+                source: TranscriptSource::Cloud,
+                is_primary: true,
+                within_sla: false,
+                segments,
+                translation: None,
+                audio_source: self.audio_source,
+            }),
+            latency: frame_started.elapsed(),
+            frame_index,
+            is_first: false,
+        };
+        if let Err(err) = tx.send(update).await {
+            warn!(
+                target: "engine_orchestrator",
+                %err,
+                "failed to deliver replayed transcription"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::tests::MockSpeechEngine;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator, RealtimeSessionConfig};
+    use anyhow::{anyhow, Result};
+    use async_trait::async_trait;
+    use tokio::time::{sleep, timeout};
+
+    #[test]
+    fn keeps_uncommitted_frames_within_window() {
+        let config = FailoverConfig {
+            replay_window: Duration::from_millis(300),
+        };
+        let mut buffer = ReplayBuffer::new(&config, 1_000);
+        for index in 1..=4 {
+            buffer.push(index, Arc::from(vec![0.1_f32; 100]));
+        }
+        buffer.commit(2);
+        let frames: Vec<usize> = buffer.drain().into_iter().map(|(index, _)| index).collect();
+        assert_eq!(frames, [3, 4]);
+        assert!(buffer.drain().is_empty());
+    }
+
+    #[test]
+    fn drops_replayed_words_already_emitted() {
+        assert_eq!(
+            reconcile_replay("Hello world. Ship the", "ship the build today."),
+            "build today."
+        );
+        assert_eq!(
+            reconcile_replay("Hello world.", "Hello world. Ship it."),
+            "Ship it."
+        );
+        assert_eq!(reconcile_replay("", "new words"), "new words");
+        assert_eq!(reconcile_replay("done.", "done."), "");
+        assert_eq!(
+            join_segments(["ship the", " build", "今天", "发布。"]),
+            "ship the build今天发布。"
+        );
+    }
+
+    /// 依次返回给定结果，用尽后持续报错。
+    struct ExhaustibleSpeechEngine {
+        segments: StdMutex<VecDeque<&'static str>>,
+    }
+
+    #[async_trait]
+    impl SpeechEngine for ExhaustibleSpeechEngine {
+        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
+            sleep(Duration::from_millis(10)).await;
+            self.segments
+                .lock()
+                .expect("segments lock poisoned")
+                .pop_front()
+                .map(String::from)
+                .ok_or_else(|| anyhow!("local model crashed"))
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_standby_and_replays_unfinished_sentence() {
+        let local_engine = Arc::new(ExhaustibleSpeechEngine {
+            segments: StdMutex::new(VecDeque::from(["hello world.", "ship the"])),
+        });
+        let cloud_engine = Arc::new(MockSpeechEngine::new(
+            vec!["ship the", "build today.", "next frame."],
+            Duration::from_millis(10),
+        ));
+        let orchestrator = EngineOrchestrator::with_engines(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            local_engine,
+            Some(cloud_engine),
+        );
+        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
+            enable_polisher: false,
+            failover: Some(FailoverConfig::default()),
+            ..RealtimeSessionConfig::default()
+        });
+
+        let mut transcripts = Vec::new();
+        let mut notices = Vec::new();
+        for _ in 0..3 {
+            session
+                .push_frame(vec![0.3_f32; 1_600])
+                .await
+                .expect("frame should enqueue");
+        }
+        while transcripts.len() < 2 {
+            let update = timeout(Duration::from_millis(800), rx.recv())
+                .await
+                .expect("failover timed out")
+                .expect("channel closed unexpectedly");
+            match update.payload {
+                UpdatePayload::Transcript(payload) => transcripts.push(payload),
+                UpdatePayload::Notice(notice) => notices.push(notice),
+                _ => {}
+            }
+        }
+        assert_eq!(transcripts[0].text, "hello world.");
+        assert_eq!(transcripts[0].source, TranscriptSource::Local);
+        // 切换前未成句的语音由备用引擎补录，不丢字也不重复。
+        assert_eq!(transcripts[1].text, "ship the build today.");
+        assert_eq!(transcripts[1].source, TranscriptSource::Cloud);
+        assert!(transcripts[1].is_primary);
+        assert!(
+            notices
+                .iter()
+                .any(|notice| notice.level == NoticeLevel::Warn
+                    && notice.message.contains("切换云端"))
+        );
+
+        // 之后的帧直接交给备用引擎。
+        session
+            .push_frame(vec![0.3_f32; 1_600])
+            .await
+            .expect("frame should enqueue");
+        let next = loop {
+            let update = timeout(Duration::from_millis(800), rx.recv())
+                .await
+                .expect("standby transcript timed out")
+                .expect("channel closed unexpectedly");
+            if let UpdatePayload::Transcript(payload) = update.payload {
+                break payload;
+            }
+        };
+        assert_eq!(next.text, "next frame.");
+        assert_eq!(next.source, TranscriptSource::Cloud);
+        assert!(next.is_primary);
+    }
+}
@@ -0,0 +1,304 @@
+//! 启动时探测硬件能力，为本地引擎选择线程数、量化精度与 GPU 卸载层数。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// 表示"全部层"的卸载层数，与 llama.cpp 系工具的约定一致。
+pub const ALL_GPU_LAYERS: u32 = 999;
+/// 使用 GPU 时 CPU 只负责调度与前后处理，线程过多反而争抢。
+const GPU_HOST_THREADS: usize = 4;
+/// 纯 CPU 推理的线程上限，再多收益很小且会拖慢前台应用。
+const MAX_CPU_THREADS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Accelerator {
+    Metal,
+    Cuda,
+    DirectMl,
+}
+
+impl Accelerator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Accelerator::Metal => "metal",
+            Accelerator::Cuda => "cuda",
+            Accelerator::DirectMl => "directml",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "metal" => Some(Accelerator::Metal),
+            "cuda" => Some(Accelerator::Cuda),
+            "directml" | "dml" => Some(Accelerator::DirectMl),
+            _ => None,
+        }
+    }
+}
+
+/// CPU 支持的最高向量指令集。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimdLevel {
+    Scalar,
+    Sse42,
+    Avx,
+    Avx2,
+    Avx512,
+    Neon,
+}
+
+impl SimdLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SimdLevel::Scalar => "scalar",
+            SimdLevel::Sse42 => "sse4.2",
+            SimdLevel::Avx => "avx",
+            SimdLevel::Avx2 => "avx2",
+            SimdLevel::Avx512 => "avx512",
+            SimdLevel::Neon => "neon",
+        }
+    }
+
+    /// 整数点积足够快，8 位量化不会比半精度慢。
+    fn fast_int8(&self) -> bool {
+        matches!(self, SimdLevel::Avx2 | SimdLevel::Avx512 | SimdLevel::Neon)
+    }
+
+    fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if std::arch::is_x86_feature_detected!("avx512f") {
+                return SimdLevel::Avx512;
+            }
+            if std::arch::is_x86_feature_detected!("avx2") {
+                return SimdLevel::Avx2;
+            }
+            if std::arch::is_x86_feature_detected!("avx") {
+                return SimdLevel::Avx;
+            }
+            if std::arch::is_x86_feature_detected!("sse4.2") {
+                return SimdLevel::Sse42;
+            }
+            SimdLevel::Scalar
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            SimdLevel::Neon
+        }
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        {
+            SimdLevel::Scalar
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareProfile {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub logical_cores: usize,
+    pub simd: SimdLevel,
+    /// 按优先级排列的可用加速器。
+    pub accelerators: Vec<Accelerator>,
+}
+
+impl HardwareProfile {
+    /// 只检查系统库与驱动是否存在，不加载它们，启动时调用开销很小。
+    pub fn probe() -> Self {
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            logical_cores: std::thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1),
+            simd: SimdLevel::detect(),
+            accelerators: detect_accelerators(),
+        }
+    }
+}
+
+fn any_exists(paths: &[&str]) -> bool {
+    paths.iter().any(|path| Path::new(path).exists())
+}
+
+fn detect_accelerators() -> Vec<Accelerator> {
+    let mut found = Vec::new();
+    // Apple 芯片的 Mac 都支持 Metal；Intel Mac 的集成显卡跑推理通常不如 CPU。
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        found.push(Accelerator::Metal);
+    }
+    let cuda = if cfg!(target_os = "windows") {
+        any_exists(&[r"C:\Windows\System32\nvcuda.dll"])
+    } else if cfg!(target_os = "linux") {
+        any_exists(&[
+            "/proc/driver/nvidia/version",
+            "/usr/lib/x86_64-linux-gnu/libcuda.so.1",
+            "/usr/lib64/libcuda.so.1",
+            "/usr/lib/wsl/lib/libcuda.so.1",
+        ])
+    } else {
+        false
+    };
+    if cuda {
+        found.push(Accelerator::Cuda);
+    }
+    if cfg!(target_os = "windows") && any_exists(&[r"C:\Windows\System32\DirectML.dll"]) {
+        found.push(Accelerator::DirectMl);
+    }
+    found
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quantization {
+    F16,
+    Q8_0,
+    Q5_1,
+}
+
+impl Quantization {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Quantization::F16 => "f16",
+            Quantization::Q8_0 => "q8_0",
+            Quantization::Q5_1 => "q5_1",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "f16" => Some(Quantization::F16),
+            "q8_0" | "q8" => Some(Quantization::Q8_0),
+            "q5_1" | "q5" => Some(Quantization::Q5_1),
+            _ => None,
+        }
+    }
+}
+
+/// 配置文件中的手动覆盖项，未设置的沿用自动选择。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TuningOverride {
+    /// `Some(None)` 表示强制纯 CPU。
+    pub accelerator: Option<Option<Accelerator>>,
+    pub threads: Option<usize>,
+    pub quantization: Option<Quantization>,
+    pub gpu_layers: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineTuning {
+    pub accelerator: Option<Accelerator>,
+    pub threads: usize,
+    /// 推荐的模型量化精度；Whisper 的精度由所选模型文件决定，供模型选择参考。
+    pub quantization: Quantization,
+    /// 卸载到 GPU 的层数，0 表示纯 CPU；Whisper 只区分是否为 0。
+    pub gpu_layers: u32,
+    /// 至少有一项来自配置覆盖。
+    pub overridden: bool,
+}
+
+impl EngineTuning {
+    pub fn select(profile: &HardwareProfile) -> Self {
+        let accelerator = profile.accelerators.first().copied();
+        let cores = profile.logical_cores.max(1);
+        let (threads, quantization, gpu_layers) = match accelerator {
+            Some(_) => (
+                cores.min(GPU_HOST_THREADS),
+                Quantization::F16,
+                ALL_GPU_LAYERS,
+            ),
+            None => {
+                // 留一个核给音频采集与界面。
+                let threads = cores.saturating_sub(1).clamp(1, MAX_CPU_THREADS);
+                let quantization = if profile.simd.fast_int8() {
+                    Quantization::Q8_0
+                } else {
+                    Quantization::Q5_1
+                };
+                (threads, quantization, 0)
+            }
+        };
+        Self {
+            accelerator,
+            threads,
+            quantization,
+            gpu_layers,
+            overridden: false,
+        }
+    }
+
+    pub fn with_override(mut self, overrides: &TuningOverride) -> Self {
+        if let Some(accelerator) = overrides.accelerator {
+            self.accelerator = accelerator;
+            self.gpu_layers = if accelerator.is_some() {
+                ALL_GPU_LAYERS
+            } else {
+                0
+            };
+            self.overridden = true;
+        }
+        if let Some(threads) = overrides.threads {
+            self.threads = threads.max(1);
+            self.overridden = true;
+        }
+        if let Some(quantization) = overrides.quantization {
+            self.quantization = quantization;
+            self.overridden = true;
+        }
+        if let Some(gpu_layers) = overrides.gpu_layers {
+            self.gpu_layers = gpu_layers;
+            if gpu_layers == 0 {
+                self.accelerator = None;
+            }
+            self.overridden = true;
+        }
+        self
+    }
+
+    pub fn uses_gpu(&self) -> bool {
+        self.accelerator.is_some() && self.gpu_layers > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(simd: SimdLevel, accelerators: Vec<Accelerator>) -> HardwareProfile {
+        HardwareProfile {
+            os: "linux",
+            arch: "x86_64",
+            logical_cores: 16,
+            simd,
+            accelerators,
+        }
+    }
+
+    #[test]
+    fn selects_gpu_offload_or_cpu_quantization_and_honours_overrides() {
+        let gpu = EngineTuning::select(&profile(SimdLevel::Avx2, vec![Accelerator::Cuda]));
+        assert_eq!(gpu.accelerator, Some(Accelerator::Cuda));
+        assert_eq!((gpu.threads, gpu.gpu_layers), (4, ALL_GPU_LAYERS));
+        assert_eq!(gpu.quantization, Quantization::F16);
+
+        let cpu = EngineTuning::select(&profile(SimdLevel::Avx2, Vec::new()));
+        assert!(!cpu.uses_gpu());
+        assert_eq!((cpu.threads, cpu.quantization), (8, Quantization::Q8_0));
+        let old_cpu = EngineTuning::select(&profile(SimdLevel::Sse42, Vec::new()));
+        assert_eq!(old_cpu.quantization, Quantization::Q5_1);
+
+        let forced_cpu = gpu.clone().with_override(&TuningOverride {
+            gpu_layers: Some(0),
+            threads: Some(12),
+            ..TuningOverride::default()
+        });
+        assert!(!forced_cpu.uses_gpu() && forced_cpu.overridden);
+        assert_eq!(forced_cpu.threads, 12);
+        assert!(!gpu.with_override(&TuningOverride::default()).overridden);
+    }
+}
@@ -0,0 +1,557 @@
+//! 语种识别：采样会话早期的语音帧选定引擎语言，并在说话人切换语言时重新配置引擎。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn, Instrument};
+
+use super::{
+    CloudFeature, LanguageChangedPayload, RealtimeWorker, TranscriptionUpdate, UpdatePayload,
+};
+
+/// 引擎对一段音频给出的语种判断，`language` 为 ISO 639-1 代码。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageGuess {
+    pub language: String,
+    pub confidence: f32,
+}
+
+impl LanguageGuess {
+    pub fn new(language: impl Into<String>, confidence: f32) -> Self {
+        Self {
+            language: language.into(),
+            confidence,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageIdConfig {
+    /// 首次识别前累积的语音时长。
+    pub initial_window: Duration,
+    /// 选定语言后，每累积该时长的语音重新识别一次。
+    pub redetect_window: Duration,
+    /// 首次选定语言所需的最低置信度。
+    pub min_confidence: f32,
+    /// 判定为切换语言所需的最低置信度。
+    pub switch_confidence: f32,
+    /// 连续多少次识别到同一新语言才切换，抑制夹杂外语单词导致的抖动。
+    pub switch_confirmations: usize,
+    /// 允许的候选语言；为空时不限制。
+    pub candidates: Vec<String>,
+}
+
+impl Default for LanguageIdConfig {
+    fn default() -> Self {
+        Self {
+            initial_window: Duration::from_secs(2),
+            redetect_window: Duration::from_secs(4),
+            min_confidence: 0.5,
+            switch_confidence: 0.7,
+            switch_confirmations: 2,
+            candidates: Vec::new(),
+        }
+    }
+}
+
+/// 一次语言切换；`previous` 为空表示会话首次选定语言。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageSwitch {
+    pub previous: Option<String>,
+    pub language: String,
+    pub confidence: f32,
+}
+
+/// 会话内的语种识别状态：累积语音样本、限制并发识别，并对切换做防抖。
+#[derive(Debug)]
+pub struct LanguageTracker {
+    config: LanguageIdConfig,
+    sample_rate_hz: u32,
+    buffer: Vec<f32>,
+    current: Option<String>,
+    pending: Option<(String, usize)>,
+    in_flight: bool,
+}
+
+impl LanguageTracker {
+    pub fn new(config: LanguageIdConfig, sample_rate_hz: u32) -> Self {
+        Self {
+            config,
+            sample_rate_hz,
+            buffer: Vec::new(),
+            current: None,
+            pending: None,
+            in_flight: false,
+        }
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    fn window_samples(&self) -> usize {
+        let window = if self.current.is_none() {
+            self.config.initial_window
+        } else {
+            self.config.redetect_window
+        };
+        ((window.as_secs_f64() * self.sample_rate_hz as f64) as usize).max(1)
+    }
+
+    /// 累积一帧语音；窗口已满且没有进行中的识别时返回待识别样本。
+    pub fn push(&mut self, frame: &[f32]) -> Option<Vec<f32>> {
+        if self.in_flight {
+            return None;
+        }
+        self.buffer.extend_from_slice(frame);
+        if self.buffer.len() < self.window_samples() {
+            return None;
+        }
+        self.in_flight = true;
+        Some(std::mem::take(&mut self.buffer))
+    }
+
+    /// 记录一次识别结果（识别失败或引擎不支持时为 `None`），需要切换语言时返回切换信息。
+    pub fn observe(&mut self, guess: Option<LanguageGuess>) -> Option<LanguageSwitch> {
+        self.in_flight = false;
+        let guess = guess.filter(|guess| self.is_candidate(&guess.language))?;
+        let language = guess.language.to_ascii_lowercase();
+
+        let Some(current) = self.current.clone() else {
+            if guess.confidence < self.config.min_confidence {
+                return None;
+            }
+            self.current = Some(language.clone());
+            return Some(LanguageSwitch {
+                previous: None,
+                language,
+                confidence: guess.confidence,
+            });
+        };
+
+        if language == current || guess.confidence < self.config.switch_confidence {
+            self.pending = None;
+            return None;
+        }
+
+        let confirmations = match self.pending.take() {
+            Some((pending, count)) if pending == language => count + 1,
+            _ => 1,
+        };
+        if confirmations < self.config.switch_confirmations.max(1) {
+            self.pending = Some((language, confirmations));
+            return None;
+        }
+
+        self.current = Some(language.clone());
+        Some(LanguageSwitch {
+            previous: Some(current),
+            language,
+            confidence: guess.confidence,
+        })
+    }
+
+    fn is_candidate(&self, language: &str) -> bool {
+        self.config.candidates.is_empty()
+            || self.config.candidates.iter().any(|candidate| {
+                candidate
+                    .split(['-', '_'])
+                    .next()
+                    .is_some_and(|primary| primary.eq_ignore_ascii_case(language))
+            })
+    }
+}
+
+/// 混合语言文本中的一段同语种文本；按顺序拼接各段即得到原文。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageSegment {
+    pub text: String,
+    /// ISO 639-1 代码；无法判断时为 `und`。
+    pub language: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Script {
+    Han,
+    Kana,
+    Hangul,
+    Latin,
+    Other,
+    /// 数字、空白与标点，归入相邻的段。
+    Neutral,
+}
+
+pub(crate) fn script_of(c: char) -> Script {
+    match c as u32 {
+        0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Script::Kana,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F => Script::Han,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        _ if c.is_ascii_alphabetic() => Script::Latin,
+        0x00C0..=0x024F if c.is_alphabetic() => Script::Latin,
+        _ if c.is_alphabetic() => Script::Other,
+        _ => Script::Neutral,
+    }
+}
+
+/// 按书写系统把文本切分为语种段：汉字在含假名的文本中视为日语，拉丁字母沿用
+/// `default_language`（非 CJK 时）否则视为英语，其他文字沿用 `default_language`。
+pub fn segment_languages(text: &str, default_language: Option<&str>) -> Vec<LanguageSegment> {
+    let default_primary = default_language
+        .and_then(|language| language.split(['-', '_']).next())
+        .map(str::to_ascii_lowercase)
+        .filter(|language| !language.is_empty());
+    let has_kana = text.chars().any(|c| script_of(c) == Script::Kana);
+    let language_of = |script: Script| -> Option<String> {
+        let language = match script {
+            Script::Neutral => return None,
+            Script::Han if has_kana => "ja".to_string(),
+            Script::Han => "zh".to_string(),
+            Script::Kana => "ja".to_string(),
+            Script::Hangul => "ko".to_string(),
+            Script::Latin => match default_primary.as_deref() {
+                Some("zh" | "ja" | "ko") | None => "en".to_string(),
+                Some(language) => language.to_string(),
+            },
+            Script::Other => default_primary.clone().unwrap_or_else(|| "und".into()),
+        };
+        Some(language)
+    };
+
+    let mut segments: Vec<LanguageSegment> = Vec::new();
+    let mut leading = String::new();
+    for c in text.chars() {
+        match language_of(script_of(c)) {
+            None => match segments.last_mut() {
+                Some(segment) => segment.text.push(c),
+                None => leading.push(c),
+            },
+            Some(language) => match segments.last_mut() {
+                Some(segment) if segment.language == language => segment.text.push(c),
+                _ => {
+                    // 语种切换处的空白归属后一段，便于渲染时在段首处理间距。
+                    let mut carried = std::mem::take(&mut leading);
+                    if let Some(previous) = segments.last_mut() {
+                        let kept = previous.text.trim_end().len();
+                        carried = previous.text.split_off(kept);
+                    }
+                    carried.push(c);
+                    segments.push(LanguageSegment {
+                        text: carried,
+                        language,
+                    });
+                }
+            },
+        }
+    }
+    if !leading.is_empty() {
+        segments.push(LanguageSegment {
+            text: leading,
+            language: default_primary.unwrap_or_else(|| "und".into()),
+        });
+    }
+    segments
+}
+
+impl RealtimeWorker {
+    pub(super) fn detected_language(&self) -> Option<String> {
+        self.language.as_ref().and_then(|tracker| {
+            tracker
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .current()
+                .map(String::from)
+        })
+    }
+
+    /// 语种切分的默认语言：优先使用识别结果，其次为标点恢复语言。
+    pub(super) fn segment_language(&self) -> Option<String> {
+        self.detected_language()
+            .or_else(|| self.config.punctuation_language.clone())
+    }
+
+    /// 累积语音帧，窗口满时在后台识别语种；确认切换后重新配置引擎并下发
+    /// `UpdatePayload::LanguageChanged`。
+    pub(super) fn spawn_language_detection(&self, frame: &[f32], frame_index: usize) {
+        let Some(tracker) = self.language.clone() else {
+            return;
+        };
+        let Some(window) = tracker
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(frame)
+        else {
+            return;
+        };
+
+        let local_engine = Arc::clone(&self.local_engine);
+        let cloud_engine = self
+            .cloud_engine
+            .clone()
+            .filter(|_| self.cloud_gate.allows(CloudFeature::Transcription));
+        let tx = self.updates_tx.clone();
+        let started_at = self.started_at;
+        tokio::spawn(
+            async move {
+                let guess = match local_engine.detect_language(&window).await {
+                    Ok(Some(guess)) => Some(guess),
+                    Ok(None) => match &cloud_engine {
+                        Some(cloud) => cloud.detect_language(&window).await.unwrap_or_else(|err| {
+                            warn!(target: "engine_orchestrator", %err, "cloud language detection failed");
+                            None
+                        }),
+                        None => None,
+                    },
+                    Err(err) => {
+                        warn!(target: "engine_orchestrator", %err, "language detection failed");
+                        None
+                    }
+                };
+                let switch = tracker
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .observe(guess);
+                let Some(switch) = switch else {
+                    return;
+                };
+
+                for engine in std::iter::once(&local_engine).chain(cloud_engine.as_ref()) {
+                    if let Err(err) = engine.set_language(&switch.language).await {
+                        warn!(
+                            target: "engine_orchestrator",
+                            %err,
+                            language = %switch.language,
+                            "failed to reconfigure engine language"
+                        );
+                    }
+                }
+                info!(
+                    target: "engine_orchestrator",
+                    previous = ?switch.previous,
+                    language = %switch.language,
+                    confidence = switch.confidence,
+                    "session language changed"
+                );
+                let _ = tx
+                    .send(TranscriptionUpdate {
+                        payload: UpdatePayload::LanguageChanged(LanguageChangedPayload {
+                            previous: switch.previous,
+                            language: switch.language,
+                            confidence: switch.confidence,
+                        }),
+                        latency: started_at.elapsed(),
+                        frame_index,
+                        is_first: false,
+                    })
+                    .await;
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{
+        EngineConfig, EngineOrchestrator, RealtimeSessionConfig, SpeechEngine,
+    };
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use tokio::time::timeout;
+
+    fn tracker() -> LanguageTracker {
+        LanguageTracker::new(
+            LanguageIdConfig {
+                initial_window: Duration::from_millis(200),
+                redetect_window: Duration::from_millis(400),
+                ..LanguageIdConfig::default()
+            },
+            1_000,
+        )
+    }
+
+    #[test]
+    fn samples_initial_window_before_detecting() {
+        let mut tracker = tracker();
+        assert!(tracker.push(&[0.1; 100]).is_none());
+        let window = tracker.push(&[0.1; 100]).expect("initial window ready");
+        assert_eq!(window.len(), 200);
+        // 识别进行中时不再产出窗口。
+        assert!(tracker.push(&[0.1; 400]).is_none());
+
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("zh", 0.3)))
+            .is_none());
+        assert_eq!(tracker.current(), None);
+        assert!(tracker.push(&[0.1; 200]).is_some());
+        let switch = tracker
+            .observe(Some(LanguageGuess::new("ZH", 0.9)))
+            .expect("language selected");
+        assert_eq!(switch.previous, None);
+        assert_eq!(switch.language, "zh");
+        assert_eq!(tracker.current(), Some("zh"));
+        // 选定后使用更长的重检窗口。
+        assert!(tracker.push(&[0.1; 200]).is_none());
+        assert!(tracker.push(&[0.1; 200]).is_some());
+    }
+
+    #[test]
+    fn switches_after_consecutive_confident_detections() {
+        let mut tracker = tracker();
+        tracker.push(&[0.1; 200]);
+        tracker.observe(Some(LanguageGuess::new("en", 0.9)));
+
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("zh", 0.9)))
+            .is_none());
+        // 回到原语言会清空待确认状态。
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("en", 0.9)))
+            .is_none());
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("zh", 0.9)))
+            .is_none());
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("zh", 0.6)))
+            .is_none());
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("zh", 0.8)))
+            .is_none());
+        let switch = tracker
+            .observe(Some(LanguageGuess::new("zh", 0.95)))
+            .expect("switch confirmed");
+        assert_eq!(switch.previous.as_deref(), Some("en"));
+        assert_eq!(switch.language, "zh");
+        assert!(tracker.observe(None).is_none());
+    }
+
+    #[test]
+    fn ignores_languages_outside_candidates() {
+        let mut tracker = LanguageTracker::new(
+            LanguageIdConfig {
+                candidates: vec!["en-US".into(), "zh-CN".into()],
+                ..LanguageIdConfig::default()
+            },
+            16_000,
+        );
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("ja", 0.99)))
+            .is_none());
+        assert!(tracker
+            .observe(Some(LanguageGuess::new("zh", 0.8)))
+            .is_some());
+    }
+
+    #[test]
+    fn segments_mixed_chinese_and_english() {
+        let text = "我们用 Rust 重写了 audio pipeline，延迟降到 80ms。";
+        let segments = segment_languages(text, Some("zh-CN"));
+        let languages: Vec<&str> = segments.iter().map(|s| s.language.as_str()).collect();
+        assert_eq!(languages, ["zh", "en", "zh", "en", "zh", "en"]);
+        assert_eq!(segments[1].text, " Rust");
+        assert_eq!(segments[3].text, " audio pipeline，");
+        assert_eq!(
+            segments.iter().map(|s| s.text.as_str()).collect::<String>(),
+            text
+        );
+
+        let japanese = segment_languages("今日はDeployします", None);
+        assert!(japanese
+            .iter()
+            .all(|s| s.language == "ja" || s.language == "en"));
+        assert_eq!(japanese[0].text, "今日は");
+
+        let french = segment_languages("Bonjour 世界", Some("fr-FR"));
+        assert_eq!(french[0].language, "fr");
+        assert_eq!(segment_languages("42 !", Some("en"))[0].language, "en");
+        assert!(segment_languages("", None).is_empty());
+    }
+
+    struct MultilingualEngine {
+        guesses: Mutex<VecDeque<LanguageGuess>>,
+        languages: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SpeechEngine for MultilingualEngine {
+        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
+            Ok(String::new())
+        }
+
+        async fn detect_language(&self, _samples: &[f32]) -> Result<Option<LanguageGuess>> {
+            Ok(self
+                .guesses
+                .lock()
+                .expect("guesses lock poisoned")
+                .pop_front())
+        }
+
+        async fn set_language(&self, language: &str) -> Result<()> {
+            self.languages
+                .lock()
+                .expect("languages lock poisoned")
+                .push(language.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_language_and_follows_switches() {
+        let engine = Arc::new(MultilingualEngine {
+            guesses: Mutex::new(
+                [("en", 0.9), ("zh", 0.85), ("zh", 0.9)]
+                    .into_iter()
+                    .map(|(language, confidence)| LanguageGuess::new(language, confidence))
+                    .collect(),
+            ),
+            languages: Mutex::new(Vec::new()),
+        });
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            engine.clone(),
+        );
+        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
+            enable_polisher: false,
+            language_id: Some(LanguageIdConfig {
+                initial_window: Duration::from_millis(100),
+                redetect_window: Duration::from_millis(100),
+                ..LanguageIdConfig::default()
+            }),
+            ..RealtimeSessionConfig::default()
+        });
+
+        let mut changes = Vec::new();
+        for _ in 0..12 {
+            session
+                .push_frame(vec![0.5_f32; 1_600])
+                .await
+                .expect("frame should enqueue");
+            while let Ok(Some(update)) = timeout(Duration::from_millis(50), rx.recv()).await {
+                if let UpdatePayload::LanguageChanged(change) = update.payload {
+                    changes.push((change.previous, change.language));
+                }
+            }
+            if changes.len() == 2 {
+                break;
+            }
+        }
+
+        assert_eq!(
+            changes,
+            vec![
+                (None, "en".to_string()),
+                (Some("en".to_string()), "zh".to_string())
+            ]
+        );
+        assert_eq!(
+            *engine.languages.lock().expect("languages lock poisoned"),
+            vec!["en".to_string(), "zh".to_string()]
+        );
+    }
+}
@@ -0,0 +1,537 @@
+//! 会议模式：把多来源会话的转写整理为带说话方的发言并按话题切分，会后由大模型生成摘要与
+//! 待办事项；未配置大模型或调用失败时退回抽取式摘要。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::{
+    is_local_endpoint, LlmPolisher, LlmPolisherConfig, TranscriptSource, TranscriptionUpdate,
+    UpdatePayload,
+};
+use crate::audio::AudioSource;
+use crate::persistence::audit::{EgressChannel, EgressRecorder};
+
+const SUMMARY_SYSTEM_PROMPT: &str = "You write meeting notes from a transcript whose lines \
+are prefixed with the speaker (\"me\" or \"others\") and grouped by topic. Reply with JSON \
+only: {\"summary\": string, \"actionItems\": [{\"owner\": string or null, \"task\": string}]}. \
+Keep the summary under 120 words and in the language of the meeting.";
+/// 抽取式摘要识别待办的提示语，按小写匹配。
+const ACTION_CUES: &[&str] = &[
+    "action item",
+    "follow up",
+    "i will ",
+    "i'll ",
+    "we will ",
+    "we'll ",
+    "need to ",
+    "let's ",
+    "todo",
+    "待办",
+    "我来",
+    "负责",
+    "需要",
+];
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "that", "this", "with", "you", "are", "was", "but", "not", "have", "has",
+    "our", "your", "can", "will", "just", "what", "about", "from", "they", "them", "then", "there",
+    "here", "let's", "it's", "i'll", "we'll", "yes", "yeah", "okay", "sure", "sounds", "good",
+    "think", "also", "like", "need", "want", "some", "into", "would", "could", "should", "been",
+    "were", "when", "how", "all", "any", "one", "out",
+];
+const TOPIC_TITLE_WORDS: usize = 3;
+
+/// 一句带说话方的发言。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingUtterance {
+    pub speaker: AudioSource,
+    /// 距会议开始的毫秒数。
+    pub offset_ms: u64,
+    pub text: String,
+}
+
+impl MeetingUtterance {
+    pub fn labeled(&self) -> String {
+        format!("{}: {}", self.speaker.speaker_label(), self.text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingTopic {
+    pub title: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub utterances: Vec<MeetingUtterance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItem {
+    #[serde(default)]
+    pub owner: Option<String>,
+    pub task: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingSummary {
+    pub summary: String,
+    #[serde(default)]
+    pub action_items: Vec<ActionItem>,
+}
+
+/// 会议纪要，作为结构化字段保存在历史记录上。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingNotes {
+    pub topics: Vec<MeetingTopic>,
+    pub summary: String,
+    #[serde(default)]
+    pub action_items: Vec<ActionItem>,
+}
+
+impl MeetingNotes {
+    /// 逐行带说话方的完整记录。
+    pub fn transcript(&self) -> String {
+        self.topics
+            .iter()
+            .flat_map(|topic| topic.utterances.iter().map(MeetingUtterance::labeled))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 按停顿与词汇衔接切分话题：发言间隔超过 `pause`，或当前话题近几句与后几句的
+/// 词汇重合度低于 `similarity_threshold` 时开启新话题。
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicSegmenter {
+    pub pause: Duration,
+    /// 话题至少包含的发言数，也是比较词汇重合度的窗口大小。
+    pub min_utterances: usize,
+    pub similarity_threshold: f32,
+}
+
+impl Default for TopicSegmenter {
+    fn default() -> Self {
+        Self {
+            pause: Duration::from_secs(30),
+            min_utterances: 3,
+            similarity_threshold: 0.1,
+        }
+    }
+}
+
+impl TopicSegmenter {
+    pub fn segment(&self, utterances: &[MeetingUtterance]) -> Vec<MeetingTopic> {
+        let window = self.min_utterances.max(1);
+        let mut boundaries = vec![0];
+        for index in 1..utterances.len() {
+            let start = *boundaries.last().unwrap_or(&0);
+            if index - start < window {
+                continue;
+            }
+            let gap = utterances[index]
+                .offset_ms
+                .saturating_sub(utterances[index - 1].offset_ms);
+            let before = keywords(&utterances[index - window..index]);
+            let after = keywords(&utterances[index..(index + window).min(utterances.len())]);
+            if gap >= self.pause.as_millis() as u64
+                || jaccard(&before, &after) < self.similarity_threshold
+            {
+                boundaries.push(index);
+            }
+        }
+
+        let mut topics = Vec::new();
+        for (position, &start) in boundaries.iter().enumerate() {
+            let end = boundaries
+                .get(position + 1)
+                .copied()
+                .unwrap_or(utterances.len());
+            let slice = &utterances[start..end];
+            if slice.is_empty() {
+                continue;
+            }
+            topics.push(MeetingTopic {
+                title: topic_title(slice).unwrap_or_else(|| format!("Topic {}", topics.len() + 1)),
+                start_ms: slice[0].offset_ms,
+                end_ms: slice[slice.len() - 1].offset_ms,
+                utterances: slice.to_vec(),
+            });
+        }
+        topics
+    }
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '\'')) {
+        let word = word.trim_matches('\'').to_lowercase();
+        if word.chars().any(is_cjk) {
+            // 中文不分词，按字计入。
+            tokens.extend(word.chars().filter(|c| is_cjk(*c)).map(String::from));
+        } else if word.chars().count() >= 3 && !STOPWORDS.contains(&word.as_str()) {
+            tokens.push(word);
+        }
+    }
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    ('\u{4e00}'..='\u{9fff}').contains(&c)
+}
+
+fn keywords(utterances: &[MeetingUtterance]) -> HashSet<String> {
+    utterances
+        .iter()
+        .flat_map(|utterance| tokens(&utterance.text))
+        .collect()
+}
+
+fn jaccard(left: &HashSet<String>, right: &HashSet<String>) -> f32 {
+    let union = left.union(right).count();
+    if union == 0 {
+        return 1.0;
+    }
+    left.intersection(right).count() as f32 / union as f32
+}
+
+/// 话题内出现最多的关键词，次数相同时先出现者优先。
+fn topic_title(utterances: &[MeetingUtterance]) -> Option<String> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for (order, token) in utterances
+        .iter()
+        .flat_map(|utterance| tokens(&utterance.text))
+        .enumerate()
+    {
+        counts.entry(token).or_insert((0, order)).0 += 1;
+    }
+    let mut ranked: Vec<(String, (usize, usize))> = counts.into_iter().collect();
+    ranked.sort_by(|(_, (a_count, a_order)), (_, (b_count, b_order))| {
+        b_count.cmp(a_count).then(a_order.cmp(b_order))
+    });
+    let words: Vec<String> = ranked
+        .into_iter()
+        .take(TOPIC_TITLE_WORDS)
+        .map(|(word, _)| word)
+        .collect();
+    (!words.is_empty()).then(|| words.join(", "))
+}
+
+/// 汇集多来源会话的更新：同一来源的同一句以润色稿优先，其次以最后一次原文为准，
+/// 时间取该句首次出现的时刻。
+#[derive(Debug)]
+pub struct MeetingTranscriptBuilder {
+    started_at: Instant,
+    sentences: BTreeMap<(AudioSource, u64), (u64, bool, String)>,
+}
+
+impl Default for MeetingTranscriptBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MeetingTranscriptBuilder {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            sentences: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, update: &TranscriptionUpdate) {
+        let offset_ms = self.started_at.elapsed().as_millis() as u64;
+        let UpdatePayload::Transcript(payload) = &update.payload else {
+            return;
+        };
+        if payload.text.trim().is_empty() {
+            return;
+        }
+        let polished = payload.source == TranscriptSource::Polished;
+        let entry = self
+            .sentences
+            .entry((payload.audio_source, payload.sentence_id))
+            .or_insert((offset_ms, polished, String::new()));
+        if polished || !entry.1 {
+            entry.1 = polished;
+            entry.2 = payload.text.trim().to_string();
+        }
+    }
+
+    pub fn utterances(&self) -> Vec<MeetingUtterance> {
+        let mut utterances: Vec<MeetingUtterance> = self
+            .sentences
+            .iter()
+            .map(|((speaker, _), (offset_ms, _, text))| MeetingUtterance {
+                speaker: *speaker,
+                offset_ms: *offset_ms,
+                text: text.clone(),
+            })
+            .collect();
+        utterances.sort_by_key(|utterance| utterance.offset_ms);
+        utterances
+    }
+}
+
+#[async_trait]
+pub trait MeetingSummarizer: Send + Sync {
+    async fn summarize(&self, topics: &[MeetingTopic]) -> Result<MeetingSummary>;
+}
+
+/// 不依赖大模型的摘要：每个话题取首句，含待办提示语的发言记为待办，负责人为说话方。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractiveSummarizer;
+
+impl ExtractiveSummarizer {
+    fn summarize_now(topics: &[MeetingTopic]) -> MeetingSummary {
+        let summary = topics
+            .iter()
+            .filter_map(|topic| {
+                let first = topic.utterances.first()?;
+                Some(format!("{}: {}", topic.title, first.text))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let action_items = topics
+            .iter()
+            .flat_map(|topic| &topic.utterances)
+            .filter(|utterance| {
+                let lower = format!("{} ", utterance.text.to_lowercase());
+                ACTION_CUES.iter().any(|cue| lower.contains(cue))
+            })
+            .map(|utterance| ActionItem {
+                owner: Some(utterance.speaker.speaker_label().to_string()),
+                task: utterance.text.clone(),
+            })
+            .collect();
+        MeetingSummary {
+            summary,
+            action_items,
+        }
+    }
+}
+
+#[async_trait]
+impl MeetingSummarizer for ExtractiveSummarizer {
+    async fn summarize(&self, topics: &[MeetingTopic]) -> Result<MeetingSummary> {
+        Ok(Self::summarize_now(topics))
+    }
+}
+
+/// 复用润色器的大模型配置生成摘要，要求模型以 JSON 返回。
+pub struct LlmMeetingSummarizer {
+    config: LlmPolisherConfig,
+    egress: EgressRecorder,
+}
+
+impl LlmMeetingSummarizer {
+    pub fn new(mut config: LlmPolisherConfig) -> Self {
+        config.stream = false;
+        config.system_prompt = SUMMARY_SYSTEM_PROMPT.to_string();
+        Self {
+            config,
+            egress: EgressRecorder::default(),
+        }
+    }
+
+    /// 发往非本机端点的会议全文记入外发审计。
+    pub fn with_egress(mut self, egress: EgressRecorder) -> Self {
+        self.egress = egress;
+        self
+    }
+}
+
+#[async_trait]
+impl MeetingSummarizer for LlmMeetingSummarizer {
+    async fn summarize(&self, topics: &[MeetingTopic]) -> Result<MeetingSummary> {
+        let prompt = topics
+            .iter()
+            .map(|topic| {
+                let lines = topic
+                    .utterances
+                    .iter()
+                    .map(MeetingUtterance::labeled)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("## {}\n{lines}", topic.title)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if !is_local_endpoint(&self.config.endpoint) {
+            self.egress.record(
+                EgressChannel::MeetingSummary,
+                &self.config.endpoint,
+                prompt.as_bytes(),
+            );
+        }
+        let polisher = LlmPolisher::new(self.config.clone());
+        let task = tokio::task::spawn_blocking(move || {
+            let (partial, _) = mpsc::unbounded_channel();
+            polisher.complete_blocking(&prompt, &partial)
+        });
+        let reply = tokio::time::timeout(self.config.timeout, task)
+            .await
+            .map_err(|_| anyhow!("summary timed out after {:?}", self.config.timeout))?
+            .map_err(|err| anyhow!("summary task failed: {err}"))??;
+        parse_summary(&reply)
+    }
+}
+
+/// 模型常把 JSON 包在代码块里，取首个 `{` 到末个 `}` 之间的内容解析。
+fn parse_summary(reply: &str) -> Result<MeetingSummary> {
+    let start = reply.find('{');
+    let end = reply.rfind('}');
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(anyhow!("summary reply carried no JSON object"));
+    };
+    let value: JsonValue = serde_json::from_str(&reply[start..=end])
+        .map_err(|err| anyhow!("failed to parse summary reply: {err}"))?;
+    let summary: MeetingSummary = serde_json::from_value(value)
+        .map_err(|err| anyhow!("summary reply has an unexpected shape: {err}"))?;
+    if summary.summary.trim().is_empty() {
+        return Err(anyhow!("summary reply was empty"));
+    }
+    Ok(summary)
+}
+
+/// 切分话题并生成纪要；摘要器失败时记录告警并改用抽取式摘要。
+pub async fn build_meeting_notes(
+    utterances: &[MeetingUtterance],
+    segmenter: &TopicSegmenter,
+    summarizer: &dyn MeetingSummarizer,
+) -> MeetingNotes {
+    let topics = segmenter.segment(utterances);
+    let summary = if topics.is_empty() {
+        MeetingSummary::default()
+    } else {
+        match summarizer.summarize(&topics).await {
+            Ok(summary) => summary,
+            Err(err) => {
+                warn!(
+                    target: "engine_orchestrator",
+                    %err,
+                    "meeting summary failed, using extractive summary"
+                );
+                ExtractiveSummarizer::summarize_now(&topics)
+            }
+        }
+    };
+    MeetingNotes {
+        topics,
+        summary: summary.summary,
+        action_items: summary.action_items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::LlmProvider;
+
+    fn say(speaker: AudioSource, offset_ms: u64, text: &str) -> MeetingUtterance {
+        MeetingUtterance {
+            speaker,
+            offset_ms,
+            text: text.into(),
+        }
+    }
+
+    fn meeting() -> Vec<MeetingUtterance> {
+        use AudioSource::{Microphone as Me, SystemLoopback as Others};
+        vec![
+            say(
+                Others,
+                0,
+                "The budget review shows marketing spend is over plan.",
+            ),
+            say(Me, 4_000, "Marketing budget needs a cap next quarter."),
+            say(
+                Others,
+                8_000,
+                "Agreed, the budget cap should be ten percent.",
+            ),
+            say(Me, 12_000, "I'll draft the budget memo."),
+            say(
+                Others,
+                20_000,
+                "Next, hiring for the backend team is behind.",
+            ),
+            say(
+                Me,
+                24_000,
+                "Backend hiring needs two more interviews weekly.",
+            ),
+            say(
+                Others,
+                28_000,
+                "We need to post the backend hiring role today.",
+            ),
+        ]
+    }
+
+    #[test]
+    fn segments_topics_by_vocabulary_shift() {
+        let topics = TopicSegmenter::default().segment(&meeting());
+        assert_eq!(topics.len(), 2);
+        assert_eq!(topics[0].utterances.len(), 4);
+        assert!(topics[0].title.starts_with("budget"));
+        assert!(topics[1].title.contains("backend"));
+        assert_eq!((topics[1].start_ms, topics[1].end_ms), (20_000, 28_000));
+
+        // A long pause splits even without a vocabulary change.
+        let mut paused = meeting()[..4].to_vec();
+        paused.push(say(
+            AudioSource::Microphone,
+            13_000,
+            "The marketing budget cap is settled.",
+        ));
+        assert_eq!(TopicSegmenter::default().segment(&paused).len(), 1);
+        paused[4].offset_ms = 200_000;
+        assert_eq!(TopicSegmenter::default().segment(&paused).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_extractive_summary_when_llm_fails() {
+        let mut config = LlmPolisherConfig::new(LlmProvider::LlamaCpp);
+        config.endpoint = "http://127.0.0.1:9/v1/chat/completions".into();
+        let notes = build_meeting_notes(
+            &meeting(),
+            &TopicSegmenter::default(),
+            &LlmMeetingSummarizer::new(config),
+        )
+        .await;
+
+        assert_eq!(notes.topics.len(), 2);
+        assert!(notes.summary.contains("marketing spend is over plan"));
+        assert_eq!(
+            notes.action_items,
+            [
+                ActionItem {
+                    owner: Some("me".into()),
+                    task: "I'll draft the budget memo.".into(),
+                },
+                ActionItem {
+                    owner: Some("others".into()),
+                    task: "We need to post the backend hiring role today.".into(),
+                },
+            ]
+        );
+        assert!(notes.transcript().starts_with("others: The budget review"));
+
+        let parsed = parse_summary(
+            "```json\n{\"summary\": \"Capped budget.\", \"actionItems\": [{\"task\": \"Memo\"}]}\n```",
+        )
+        .unwrap();
+        assert_eq!(parsed.summary, "Capped budget.");
+        assert_eq!(parsed.action_items[0].owner, None);
+    }
+}
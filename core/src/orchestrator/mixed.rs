@@ -0,0 +1,215 @@
+//! 多来源会话：麦克风与系统回放等来源各开一路实时会话独立识别，更新按到达顺序交织到
+//! 同一通道，以 `TranscriptPayload::audio_source` 区分“我”与“对方”。
+
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use super::{
+    EngineOrchestrator, RealtimeSessionConfig, RealtimeSessionHandle, TranscriptionUpdate,
+};
+use crate::audio::{AudioSource, MixedFrame};
+
+/// 多来源会话的句柄；丢弃后各路会话随之结束。句 ID 在各来源内独立编号，
+/// 需与 `audio_source` 一起标识一句。
+pub struct MixedSessionHandle {
+    sessions: Vec<(AudioSource, RealtimeSessionHandle)>,
+    forwarders: Vec<JoinHandle<()>>,
+}
+
+impl MixedSessionHandle {
+    pub fn sources(&self) -> Vec<AudioSource> {
+        self.sessions.iter().map(|(source, _)| *source).collect()
+    }
+
+    pub fn session(&self, source: AudioSource) -> Option<&RealtimeSessionHandle> {
+        self.sessions
+            .iter()
+            .find(|(known, _)| *known == source)
+            .map(|(_, handle)| handle)
+    }
+
+    /// 把混音帧中各来源的音轨送入对应会话，未参与会话的来源被忽略。
+    pub async fn push_mixed_frame(
+        &self,
+        frame: MixedFrame,
+    ) -> Result<(), mpsc::error::SendError<Arc<[f32]>>> {
+        for track in frame.tracks {
+            if let Some(handle) = self.session(track.source) {
+                handle.push_frame(track.samples).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 持续把 `frames` 中的混音帧分发给各路会话，直到混音通道关闭或会话结束。
+    pub fn spawn_feeder(&self, mut frames: mpsc::Receiver<MixedFrame>) -> JoinHandle<()> {
+        let senders: Vec<(AudioSource, mpsc::Sender<Arc<[f32]>>)> = self
+            .sessions
+            .iter()
+            .map(|(source, handle)| (*source, handle.frame_sender()))
+            .collect();
+        tokio::spawn(async move {
+            while let Some(frame) = frames.recv().await {
+                for track in frame.tracks {
+                    let Some((_, sender)) =
+                        senders.iter().find(|(source, _)| *source == track.source)
+                    else {
+                        continue;
+                    };
+                    if sender.send(track.samples.into()).await.is_err() {
+                        warn!(
+                            target: "engine_orchestrator",
+                            source = track.source.as_str(),
+                            "mixed session closed before capture ended"
+                        );
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Drop for MixedSessionHandle {
+    fn drop(&mut self) {
+        for forwarder in &self.forwarders {
+            forwarder.abort();
+        }
+    }
+}
+
+impl EngineOrchestrator {
+    /// 为每个来源各开一路实时会话，配置中的 `audio_source` 按来源覆盖。
+    pub fn start_mixed_session(
+        &self,
+        config: RealtimeSessionConfig,
+        sources: &[AudioSource],
+    ) -> (MixedSessionHandle, mpsc::Receiver<TranscriptionUpdate>) {
+        let (tx, rx) = mpsc::channel(config.buffer_capacity * sources.len().max(1));
+        let mut sessions: Vec<(AudioSource, RealtimeSessionHandle)> = Vec::new();
+        let mut forwarders = Vec::new();
+        for &source in sources {
+            if sessions.iter().any(|(known, _)| *known == source) {
+                continue;
+            }
+            let (handle, mut updates) = self.start_realtime_session(RealtimeSessionConfig {
+                audio_source: source,
+                ..config.clone()
+            });
+            let tx = tx.clone();
+            forwarders.push(tokio::spawn(async move {
+                while let Some(update) = updates.recv().await {
+                    if tx.send(update).await.is_err() {
+                        break;
+                    }
+                }
+            }));
+            sessions.push((source, handle));
+        }
+        (
+            MixedSessionHandle {
+                sessions,
+                forwarders,
+            },
+            rx,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{SourceMixer, TaggedFrame};
+    use crate::orchestrator::{EngineConfig, SpeechEngine, UpdatePayload};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    /// 以音量区分来源：会议另一端的声音更响。
+    struct LoudnessEngine;
+
+    #[async_trait]
+    impl SpeechEngine for LoudnessEngine {
+        async fn transcribe(&self, frame: &[f32]) -> Result<String> {
+            let peak = frame.iter().fold(0.0_f32, |peak, sample| peak.max(*sample));
+            Ok(if peak > 0.3 {
+                "Let's review the roadmap.".into()
+            } else {
+                "Sounds good to me.".into()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn interleaves_updates_labeled_by_source() {
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(LoudnessEngine),
+        );
+        let sources = [AudioSource::Microphone, AudioSource::SystemLoopback];
+        let (handle, mut rx) = orchestrator.start_mixed_session(
+            RealtimeSessionConfig {
+                enable_polisher: false,
+                ..RealtimeSessionConfig::default()
+            },
+            &sources,
+        );
+        assert_eq!(handle.sources(), sources);
+
+        let mut mixer = SourceMixer::new(
+            &sources,
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+        );
+        let (tx, frames) = mpsc::channel(16);
+        let feeder = handle.spawn_feeder(frames);
+        for _ in 0..4 {
+            mixer.push(AudioSource::Microphone, &[0.2; 1_600]);
+            for frame in mixer.push(AudioSource::SystemLoopback, &[0.5; 1_600]) {
+                tx.send(frame).await.unwrap();
+            }
+        }
+        handle
+            .push_mixed_frame(MixedFrame {
+                mix: vec![0.0; 1_600],
+                tracks: vec![TaggedFrame {
+                    source: AudioSource::Microphone,
+                    samples: vec![0.2; 1_600],
+                }],
+            })
+            .await
+            .unwrap();
+
+        let mut heard = Vec::new();
+        timeout(Duration::from_secs(5), async {
+            while heard.len() < 2 {
+                let update = rx.recv().await.expect("updates open");
+                if let UpdatePayload::Transcript(payload) = update.payload {
+                    let entry = (payload.audio_source.speaker_label(), payload.text);
+                    if !heard.contains(&entry) {
+                        heard.push(entry);
+                    }
+                }
+            }
+        })
+        .await
+        .expect("both sources transcribed");
+        heard.sort();
+        assert_eq!(
+            heard,
+            [
+                ("me", "Sounds good to me.".to_string()),
+                ("others", "Let's review the roadmap.".to_string()),
+            ]
+        );
+
+        drop(tx);
+        feeder.await.unwrap();
+    }
+}
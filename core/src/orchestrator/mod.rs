@@ -3,20 +3,110 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 use std::sync::{
     atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex as StdMutex,
 };
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tokio::sync::{mpsc, Mutex, Notify};
 use tokio::task::JoinHandle;
-use tokio::time::{sleep, sleep_until, timeout, Instant as TokioInstant};
-use tracing::{error, info, warn};
+use tokio::time::{sleep, sleep_until, timeout, Instant};
+use tracing::{error, info, info_span, warn, Instrument};
 
+use crate::audio::AudioSource;
+use crate::persistence::audit::EgressRecorder;
+use crate::power::{GovernorDecision, PerformanceGovernor, PerformanceLevel};
+use crate::secrets::SecretStore;
 use crate::telemetry::events::{
-    record_dual_view_latency, record_dual_view_revert, DualViewSelectionLog,
+    record_dual_view_arbitration, record_dual_view_latency, record_dual_view_revert,
+    DualViewArbitrationEvent, DualViewSelectionLog,
 };
+use crate::telemetry::metrics::metrics;
+
+pub mod arbitration;
+pub mod batch;
+pub mod budget;
+pub mod commands;
+mod egress;
+pub mod failover;
+pub mod hardware;
+pub mod language;
+pub mod meeting;
+pub mod mixed;
+pub mod network;
+pub mod offline;
+pub mod polisher;
+pub mod profile;
+pub mod punctuation;
+pub mod quality;
+pub mod redaction;
+mod retranscribe;
+mod sentences;
+pub mod sla;
+pub mod stabilizer;
+pub mod translation;
+pub mod vocabulary;
+pub mod warmup;
+
+pub use arbitration::{
+    Arbiter, ArbitrationConfig, ArbitrationDecision, ArbitrationReason, EngineCandidate,
+};
+pub use batch::{BatchSentence, BatchTranscript};
+pub use budget::{
+    estimate_tokens, BudgetCaps, BudgetLine, BudgetMeter, BudgetPeriod, BudgetReport, BudgetUsage,
+    CloudBudget,
+};
+use budget::{MeteredEngine, MeteredPolisher, MeteredTranslator};
+pub use commands::{CommandGrammar, CommandPhrase, SessionCommand};
+use egress::{AuditedEngine, AuditedPolisher, AuditedTranslator};
+use failover::FailoverState;
+pub use failover::{reconcile_replay, FailoverConfig, ReplayBuffer};
+pub use hardware::{
+    Accelerator, EngineTuning, HardwareProfile, Quantization, SimdLevel, TuningOverride,
+};
+pub use language::{
+    segment_languages, LanguageGuess, LanguageIdConfig, LanguageSegment, LanguageSwitch,
+    LanguageTracker,
+};
+pub use meeting::{
+    build_meeting_notes, ActionItem, ExtractiveSummarizer, LlmMeetingSummarizer, MeetingNotes,
+    MeetingSummarizer, MeetingSummary, MeetingTopic, MeetingTranscriptBuilder, MeetingUtterance,
+    TopicSegmenter,
+};
+pub use mixed::MixedSessionHandle;
+pub use network::{
+    score_network, EngineRoute, NetworkMonitor, NetworkMonitorConfig, NetworkQuality, RouteSwitch,
+};
+use offline::CloudGate;
+pub use offline::{is_local_endpoint, CloudBlock, CloudFeature, OfflineGuard};
+pub use polisher::{LlmPolisher, LlmPolisherConfig, LlmProvider, PolisherSelection};
+pub use profile::{resolve_profile, PolishProfile, PolishProfileBinding};
+pub use punctuation::{
+    ModelPunctuationRestorer, PunctuationModel, PunctuationRestorer, PunctuationTag,
+    RulePunctuationRestorer,
+};
+pub use quality::{ConfidenceTracker, QualityConfig, QualityFlag, SentenceConfidence};
+pub use redaction::{
+    PiiKind, RedactionMode, RedactionPattern, RedactionSpan, Redactor, StrippedText,
+};
+use redaction::{RedactingPolisher, PLACEHOLDER_PROMPT};
+use retranscribe::RETRANSCRIBE_WINDOW;
+use sentences::{SentenceBuffer, SentenceStore};
+pub use sla::{
+    LatencyCalibrator, LatencySamples, SlaCalibration, CALIBRATION_SESSIONS,
+    DEFAULT_FIRST_UPDATE_DEADLINE,
+};
+pub use stabilizer::{PartialStabilizer, StabilizerConfig, TranscriptDelta};
+pub use translation::{
+    nllb_code, LlmTranslator, Locale, NllbConfig, NllbTranslator, TranslatedText, Translator,
+    TranslatorSelection,
+};
+pub use vocabulary::{
+    PhraseHint, ScoredToken, ScoredTranscript, Vocabulary, VocabularyCorrector, VocabularyKind,
+    VocabularyTerm,
+};
+use warmup::WarmupTracker;
+pub use warmup::{EngineWarmupStatus, WarmupState, CLOUD_KEEPALIVE_INTERVAL};
 
 const SILENCE_RMS_THRESHOLD: f32 = 1e-4;
 const SPEECH_RMS_THRESHOLD: f32 = 5e-4;
@@ -29,11 +119,117 @@ pub struct EngineConfig {
 #[async_trait]
 pub trait SpeechEngine: Send + Sync {
     async fn transcribe(&self, frame: &[f32]) -> Result<String>;
+
+    /// 带短语增强提示的转写，并返回逐词置信度。默认忽略提示、置信度未知。
+    async fn transcribe_scored(
+        &self,
+        frame: &[f32],
+        hints: &[PhraseHint],
+    ) -> Result<ScoredTranscript> {
+        let _ = hints;
+        self.transcribe(frame).await.map(ScoredTranscript::unscored)
+    }
+
+    /// 识别一段语音的语种；不支持语种识别的引擎返回 `None`。
+    async fn detect_language(&self, samples: &[f32]) -> Result<Option<LanguageGuess>> {
+        let _ = samples;
+        Ok(None)
+    }
+
+    /// 切换后续解码使用的语言（ISO 639-1）。默认忽略。
+    async fn set_language(&self, language: &str) -> Result<()> {
+        let _ = language;
+        Ok(())
+    }
+
+    /// 预热：本地引擎预载权重并跑通一次推理，云端引擎建立或保活连接。默认无需预热。
+    async fn warmup(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 按电源与散热状态调整推理强度，例如减少线程数。默认忽略。
+    fn set_performance(&self, level: PerformanceLevel) {
+        let _ = level;
+    }
 }
 
 #[async_trait]
 pub trait SentencePolisher: Send + Sync {
     async fn polish(&self, sentence: &str) -> Result<String>;
+
+    /// 流式润色：生成过程中通过 `partial` 发送累计的润色片段。默认一次性返回。
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        let _ = partial;
+        self.polish(sentence).await
+    }
+
+    /// 按风格预设润色。默认先做基础润色，再套用预设的规则化改写。
+    async fn polish_with_profile(
+        &self,
+        sentence: &str,
+        profile: PolishProfile,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.polish_streaming(sentence, partial)
+            .await
+            .map(|polished| profile.apply(&polished))
+    }
+}
+
+/// 润色前对句子做的改写，例如用户注册的自动化脚本。
+pub trait PrePolishHook: Send + Sync + std::fmt::Debug {
+    fn apply(&self, sentence: &str) -> String;
+}
+
+/// 在进入润色器前先执行改写钩子。
+struct HookedPolisher {
+    inner: Arc<dyn SentencePolisher>,
+    hook: Arc<dyn PrePolishHook>,
+}
+
+#[async_trait]
+impl SentencePolisher for HookedPolisher {
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        self.inner.polish(&self.hook.apply(sentence)).await
+    }
+
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.inner
+            .polish_streaming(&self.hook.apply(sentence), partial)
+            .await
+    }
+}
+
+/// 将会话的风格预设固定到润色器上。
+struct ProfiledPolisher {
+    inner: Arc<dyn SentencePolisher>,
+    profile: PolishProfile,
+}
+
+#[async_trait]
+impl SentencePolisher for ProfiledPolisher {
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        let (partial, _) = mpsc::unbounded_channel();
+        self.polish_streaming(sentence, &partial).await
+    }
+
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.inner
+            .polish_with_profile(sentence, self.profile, partial)
+            .await
+    }
 }
 
 #[derive(Debug, Default)]
@@ -121,22 +317,42 @@ impl SentencePolisher for LightweightSentencePolisher {
     }
 }
 
+/// 各组件都以 `Arc` 共享，克隆得到的句柄共用同一组引擎、预算与预热状态。
+#[derive(Clone)]
 pub struct EngineOrchestrator {
     config: EngineConfig,
     local_engine: Arc<dyn SpeechEngine>,
     cloud_engine: Option<Arc<dyn SpeechEngine>>,
     polisher: Arc<dyn SentencePolisher>,
+    punctuation: Arc<dyn PunctuationRestorer>,
+    translator: Option<Arc<dyn Translator>>,
+    offline_guard: Arc<OfflineGuard>,
+    network: Option<Arc<NetworkMonitor>>,
+    secrets: Option<Arc<dyn SecretStore>>,
+    budget: Arc<CloudBudget>,
+    warmup: Arc<WarmupTracker>,
+    tuning: Option<EngineTuning>,
+    governor: Option<Arc<PerformanceGovernor>>,
+    latency: Option<Arc<LatencyCalibrator>>,
 }
 
 impl EngineOrchestrator {
+    /// 按探测到的硬件自动配置本地引擎。
     pub fn new(config: EngineConfig) -> Result<Self> {
-        let local_engine = Self::build_local_engine()?;
-        Ok(Self::with_components(
+        Self::tuned(config, EngineTuning::select(&HardwareProfile::probe()))
+    }
+
+    /// 按给定的线程数与 GPU 卸载配置构建本地引擎，用于应用配置中的手动覆盖。
+    pub fn tuned(config: EngineConfig, tuning: EngineTuning) -> Result<Self> {
+        let local_engine = Self::build_local_engine(&tuning)?;
+        let mut orchestrator = Self::with_components(
             config,
             local_engine,
             None,
-            Arc::new(LightweightSentencePolisher::default()),
-        ))
+            Arc::new(LightweightSentencePolisher),
+        );
+        orchestrator.tuning = Some(tuning);
+        Ok(orchestrator)
     }
 
     pub fn with_engine(config: EngineConfig, local_engine: Arc<dyn SpeechEngine>) -> Self {
@@ -144,7 +360,7 @@ impl EngineOrchestrator {
             config,
             local_engine,
             None,
-            Arc::new(LightweightSentencePolisher::default()),
+            Arc::new(LightweightSentencePolisher),
         )
     }
 
@@ -157,7 +373,7 @@ impl EngineOrchestrator {
             config,
             local_engine,
             cloud_engine,
-            Arc::new(LightweightSentencePolisher::default()),
+            Arc::new(LightweightSentencePolisher),
         )
     }
 
@@ -172,81 +388,276 @@ impl EngineOrchestrator {
             local_engine,
             cloud_engine,
             polisher,
+            punctuation: Arc::new(RulePunctuationRestorer),
+            translator: None,
+            offline_guard: Arc::new(OfflineGuard::new()),
+            network: None,
+            secrets: None,
+            budget: Arc::new(CloudBudget::default()),
+            warmup: Arc::new(WarmupTracker::default()),
+            tuning: None,
+            governor: None,
+            latency: None,
         }
     }
 
-    pub async fn warmup(&self) -> Result<()> {
+    /// 本地引擎采用的硬件配置；直接注入引擎构建时为 `None`。
+    pub fn tuning(&self) -> Option<&EngineTuning> {
+        self.tuning.as_ref()
+    }
+
+    /// 替换默认的规则标点恢复器，例如接入模型推理。
+    pub fn with_punctuation_restorer(mut self, restorer: Arc<dyn PunctuationRestorer>) -> Self {
+        self.punctuation = restorer;
+        self
+    }
+
+    /// 注入会话默认的翻译器，供 `TranslatorSelection::Default` 使用。
+    pub fn with_translator(mut self, translator: Arc<dyn Translator>) -> Self {
+        self.translator = Some(translator);
+        self
+    }
+
+    /// 共享宿主的离线与隐私开关，会话中途更新同样生效。
+    pub fn with_offline_guard(mut self, guard: Arc<OfflineGuard>) -> Self {
+        self.offline_guard = guard;
+        self
+    }
+
+    pub fn offline_guard(&self) -> Arc<OfflineGuard> {
+        Arc::clone(&self.offline_guard)
+    }
+
+    /// 混合引擎模式：按网络评分逐帧决定本地或云端为主引擎，本地为主时不再请求云端。
+    pub fn with_network_monitor(mut self, monitor: Arc<NetworkMonitor>) -> Self {
+        self.network = Some(monitor);
+        self
+    }
+
+    /// 会话的大模型润色与翻译未显式提供 API Key 时，按服务商从该密钥库读取。
+    pub fn with_secret_store(mut self, store: Arc<dyn SecretStore>) -> Self {
+        self.secrets = Some(store);
+        self
+    }
+
+    /// 共享宿主的云端用量预算，超出上限的能力改用本地处理。
+    pub fn with_budget(mut self, budget: Arc<CloudBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn budget(&self) -> Arc<CloudBudget> {
+        Arc::clone(&self.budget)
+    }
+
+    /// 接入电源调节器：用电池或过热时降低本地推理强度，并按配置优先使用云端。
+    pub fn with_governor(mut self, governor: Arc<PerformanceGovernor>) -> Self {
+        self.governor = Some(governor);
+        self
+    }
+
+    pub fn governor(&self) -> Option<Arc<PerformanceGovernor>> {
+        self.governor.clone()
+    }
+
+    /// 重新采样电源状态并把新档位下发给本地引擎；档位未变化时返回 `None`。
+    pub fn govern(&self) -> Option<GovernorDecision> {
+        let decision = self.governor.as_ref()?.evaluate()?;
         info!(
             target: "engine_orchestrator",
-            prefer_cloud = self.config.prefer_cloud,
-            "warming up engines"
+            level = decision.level.as_str(),
+            on_battery = decision.state.on_battery,
+            prefer_cloud = decision.prefer_cloud,
+            "performance level changed"
         );
-        Ok(())
+        self.local_engine.set_performance(decision.level);
+        Some(decision)
+    }
+
+    /// 按本机实测延迟校准首个更新期限：每次会话记录一个样本，样本足够后替代会话配置中的期限，
+    /// 增量节奏检查同比例放宽。
+    pub fn with_latency_calibrator(mut self, calibrator: Arc<LatencyCalibrator>) -> Self {
+        self.latency = Some(calibrator);
+        self
+    }
+
+    pub fn latency_calibrator(&self) -> Option<Arc<LatencyCalibrator>> {
+        self.latency.clone()
+    }
+
+    /// 会话未显式指定时的云端优先设置，电源调节器降档时可临时改为云端优先。
+    fn default_prefer_cloud(&self) -> bool {
+        self.config.prefer_cloud
+            || self
+                .governor
+                .as_ref()
+                .is_some_and(|governor| governor.prefers_cloud())
+    }
+
+    pub fn secret_store(&self) -> Option<Arc<dyn SecretStore>> {
+        self.secrets.clone()
+    }
+
+    /// 为大模型配置补齐密钥库中的 API Key。
+    pub fn resolve_llm_config(&self, config: &mut LlmPolisherConfig) {
+        if let Some(store) = &self.secrets {
+            config.resolve_api_key(store.as_ref());
+        }
     }
 
     pub fn start_realtime_session(
         &self,
-        config: RealtimeSessionConfig,
+        mut config: RealtimeSessionConfig,
     ) -> (RealtimeSessionHandle, mpsc::Receiver<TranscriptionUpdate>) {
+        if let PolisherSelection::Llm(llm) = &mut config.polisher {
+            self.resolve_llm_config(llm);
+        }
+        if let TranslatorSelection::Llm(llm) = &mut config.translator {
+            self.resolve_llm_config(llm);
+        }
+        // 所有实时任务都挂在该 span 下，调用方所在的会话 span 会成为其父级。
+        let span = info_span!(
+            target: "engine_orchestrator",
+            "realtime_session",
+            session_id = config.session_id.as_deref().unwrap_or_default(),
+        );
+        let _entered = span.enter();
         let (tx, rx) = mpsc::channel(config.buffer_capacity);
         let (frame_tx, frame_rx) = mpsc::channel(config.buffer_capacity);
         let (command_tx, command_rx) = mpsc::channel(config.buffer_capacity);
         let first_update_flag = Arc::new(AtomicBool::new(false));
         let first_local_update_flag = Arc::new(AtomicBool::new(false));
         let local_progress = Arc::new(LocalProgress::new());
+        local_progress.set_frame_window(config.min_frame_duration, config.max_frame_duration);
         let local_update_notify = Arc::new(Notify::new());
-        let local_serial = Arc::new(Mutex::new(LocalDecoderState::new(config.raw_emit_window)));
+        let local_serial = Arc::new(Mutex::new(LocalDecoderState::new(
+            config.raw_emit_window,
+            config.stabilizer.clone(),
+            config.quality.clone(),
+        )));
         let sentences = Arc::new(Mutex::new(SentenceStore::default()));
         let started_at = Instant::now();
         let monitor_progress = local_progress.clone();
         let monitor_tx = tx.clone();
-        let deadline = config.first_update_deadline;
-        let cadence = if config.max_frame_duration.is_zero() {
-            config.min_frame_duration
-        } else {
-            config.max_frame_duration.max(config.min_frame_duration)
+        let calibrator = self.latency.clone();
+        let (deadline, cadence_slack) = match &calibrator {
+            Some(calibrator) => (
+                calibrator.first_update_deadline(config.first_update_deadline),
+                calibrator.calibration().cadence_slack,
+            ),
+            None => (config.first_update_deadline, 1.0),
         };
 
-        let monitor: JoinHandle<()> = tokio::spawn(async move {
-            let poll_interval = Duration::from_millis(25);
-            let mut first_window = true;
-            let mut last_seen_frame = 0_u64;
-            let mut violation_active = false;
+        let monitor: JoinHandle<()> = tokio::spawn(
+            async move {
+                let poll_interval = Duration::from_millis(25);
+                let mut first_window = true;
+                let mut last_seen_frame = 0_u64;
+                let mut violation_active = false;
 
-            loop {
-                let wait = if first_window { poll_interval } else { cadence };
-                sleep(wait).await;
+                loop {
+                    let wait = if first_window {
+                        poll_interval
+                    } else {
+                        monitor_progress.cadence()
+                    };
+                    sleep(wait).await;
 
-                if monitor_tx.is_closed() {
-                    break;
-                }
+                    if monitor_tx.is_closed() {
+                        break;
+                    }
+
+                    let current_frame = monitor_progress.last_frame();
+                    let degraded = monitor_progress.is_degraded();
+
+                    if first_window {
+                        if current_frame > 0 {
+                            // 语音先于首个更新被检测到时，两者之差即本机的首个更新延迟。
+                            let speech_ms = monitor_progress.speech_started_ms();
+                            let update_ms = monitor_progress.first_update_ms();
+                            if let Some(calibrator) = &calibrator {
+                                if speech_ms > 0 && update_ms > speech_ms {
+                                    calibrator.record(Duration::from_millis(update_ms - speech_ms));
+                                }
+                            }
+                            last_seen_frame = current_frame;
+                            violation_active = false;
+                            first_window = false;
+                            continue;
+                        }
+
+                        let speech_started_ms = monitor_progress.speech_started_ms();
+                        if speech_started_ms == 0 {
+                            continue;
+                        }
+
+                        let elapsed_since_speech = started_at
+                            .elapsed()
+                            .saturating_sub(Duration::from_millis(speech_started_ms));
 
-                let current_frame = monitor_progress.last_frame();
-                let degraded = monitor_progress.is_degraded();
+                        if elapsed_since_speech >= deadline && !violation_active {
+                            warn!(
+                                target: "engine_orchestrator",
+                                elapsed = ?elapsed_since_speech,
+                                "first transcription update missed {:?} deadline",
+                                deadline
+                            );
+
+                            monitor_progress.mark_degraded(started_at);
+
+                            let notice = TranscriptionUpdate {
+                                payload: UpdatePayload::Notice(SessionNotice {
+                                    level: NoticeLevel::Warn,
+                                    message: "本地解码延迟异常，已保留回退提示".to_string(),
+                                }),
+                                latency: elapsed_since_speech,
+                                frame_index: 0,
+                                is_first: false,
+                            };
+
+                            if let Err(err) = monitor_tx.send(notice).await {
+                                warn!(
+                                    target: "engine_orchestrator",
+                                    %err,
+                                    "failed to deliver deadline fallback notice"
+                                );
+                            }
+                            violation_active = true;
+                        } else if elapsed_since_speech < deadline {
+                            violation_active = false;
+                        }
 
-                if first_window {
-                    if current_frame > 0 {
+                        continue;
+                    }
+
+                    if current_frame > last_seen_frame {
                         last_seen_frame = current_frame;
                         violation_active = false;
-                        first_window = false;
                         continue;
                     }
 
-                    let speech_started_ms = monitor_progress.speech_started_ms();
-                    if speech_started_ms == 0 {
+                    if degraded {
+                        violation_active = false;
                         continue;
                     }
 
-                    let elapsed_since_speech = started_at
-                        .elapsed()
-                        .saturating_sub(Duration::from_millis(speech_started_ms));
+                    let elapsed_ms = duration_to_ms(started_at.elapsed());
+                    let last_update_ms = monitor_progress.last_update_ms();
+                    let since_ms = elapsed_ms.saturating_sub(last_update_ms);
+                    let cadence_ms =
+                        duration_to_ms(monitor_progress.cadence().mul_f64(cadence_slack));
 
-                    if elapsed_since_speech >= deadline && !violation_active {
+                    if !monitor_progress.is_speech_active() {
+                        violation_active = false;
+                        continue;
+                    }
+
+                    if since_ms >= cadence_ms && !violation_active {
                         warn!(
                             target: "engine_orchestrator",
-                            elapsed = ?elapsed_since_speech,
-                            "first transcription update missed {:?} deadline",
-                            deadline
+                            elapsed_ms,
+                            last_update_ms,
+                            "local transcription cadence degraded"
                         );
 
                         monitor_progress.mark_degraded(started_at);
@@ -254,10 +665,10 @@ impl EngineOrchestrator {
                         let notice = TranscriptionUpdate {
                             payload: UpdatePayload::Notice(SessionNotice {
                                 level: NoticeLevel::Warn,
-                                message: "本地解码延迟异常，已保留回退提示".to_string(),
+                                message: "本地解码增量延迟，已保留回退提示".to_string(),
                             }),
-                            latency: elapsed_since_speech,
-                            frame_index: 0,
+                            latency: Duration::from_millis(since_ms),
+                            frame_index: last_seen_frame as usize,
                             is_first: false,
                         };
 
@@ -265,80 +676,44 @@ impl EngineOrchestrator {
                             warn!(
                                 target: "engine_orchestrator",
                                 %err,
-                                "failed to deliver deadline fallback notice"
+                                "failed to deliver rolling cadence notice"
                             );
                         }
                         violation_active = true;
-                    } else if elapsed_since_speech < deadline {
+                    } else if since_ms < cadence_ms {
                         violation_active = false;
                     }
-
-                    continue;
-                }
-
-                if current_frame > last_seen_frame {
-                    last_seen_frame = current_frame;
-                    violation_active = false;
-                    continue;
-                }
-
-                if degraded {
-                    violation_active = false;
-                    continue;
-                }
-
-                let elapsed_ms = duration_to_ms(started_at.elapsed());
-                let last_update_ms = monitor_progress.last_update_ms();
-                let since_ms = elapsed_ms.saturating_sub(last_update_ms);
-                let cadence_ms = duration_to_ms(cadence);
-
-                if !monitor_progress.is_speech_active() {
-                    violation_active = false;
-                    continue;
                 }
+            }
+            .in_current_span(),
+        );
 
-                if since_ms >= cadence_ms && !violation_active {
-                    warn!(
-                        target: "engine_orchestrator",
-                        elapsed_ms,
-                        last_update_ms,
-                        "local transcription cadence degraded"
-                    );
-
-                    monitor_progress.mark_degraded(started_at);
-
-                    let notice = TranscriptionUpdate {
-                        payload: UpdatePayload::Notice(SessionNotice {
-                            level: NoticeLevel::Warn,
-                            message: "本地解码增量延迟，已保留回退提示".to_string(),
-                        }),
-                        latency: Duration::from_millis(since_ms),
-                        frame_index: last_seen_frame as usize,
-                        is_first: false,
-                    };
-
-                    if let Err(err) = monitor_tx.send(notice).await {
-                        warn!(
-                            target: "engine_orchestrator",
-                            %err,
-                            "failed to deliver rolling cadence notice"
-                        );
-                    }
-                    violation_active = true;
-                } else if since_ms < cadence_ms {
-                    violation_active = false;
-                }
+        let cloud_engine = self.cloud_engine.clone().map(|engine| {
+            let mut engine: Arc<dyn SpeechEngine> = Arc::new(MeteredEngine::new(
+                engine,
+                Arc::clone(&self.budget),
+                config.sample_rate_hz,
+            ));
+            if config.egress.is_attached() {
+                engine = Arc::new(AuditedEngine::new(engine, config.egress.clone()));
             }
+            engine
         });
-
         let worker = RealtimeWorker::new(
             config.clone(),
             frame_rx,
             command_rx,
             tx.clone(),
             Arc::clone(&self.local_engine),
-            self.cloud_engine.clone(),
+            cloud_engine,
             Arc::clone(&self.polisher),
+            Arc::clone(&self.punctuation),
+            self.translator.clone(),
+            Arc::new(CloudGate::new(
+                Arc::clone(&self.offline_guard),
+                Arc::clone(&self.budget),
+            )),
+            self.network.clone(),
             first_update_flag.clone(),
             first_local_update_flag.clone(),
             local_progress.clone(),
@@ -346,19 +721,18 @@ impl EngineOrchestrator {
             Arc::clone(&local_serial),
             Arc::clone(&sentences),
             started_at,
-            self.config.prefer_cloud,
+            config
+                .prefer_cloud
+                .unwrap_or_else(|| self.default_prefer_cloud()),
         );
 
         let handle = RealtimeSessionHandle {
             config,
             frame_tx,
             command_tx,
+            #[cfg(test)]
             updates_tx: tx,
-            first_update_flag,
-            first_local_update_flag,
             local_progress,
-            local_update_notify,
-            started_at,
             monitor: Some(monitor),
             worker: Some(worker.spawn()),
         };
@@ -366,10 +740,10 @@ impl EngineOrchestrator {
         (handle, rx)
     }
 
-    fn build_local_engine() -> Result<Arc<dyn SpeechEngine>> {
+    fn build_local_engine(tuning: &EngineTuning) -> Result<Arc<dyn SpeechEngine>> {
         #[cfg(feature = "local-asr")]
         {
-            return match WhisperLocalEngine::from_env() {
+            return match WhisperLocalEngine::from_env(tuning) {
                 Ok(engine) => Ok(Arc::new(engine)),
                 Err(err) => {
                     if std::env::var("WHISPER_ALLOW_FALLBACK").is_ok() {
@@ -393,7 +767,8 @@ impl EngineOrchestrator {
 
         #[cfg(not(feature = "local-asr"))]
         {
-            return Ok(Arc::new(FallbackSpeechEngine::default()));
+            let _ = tuning;
+            Ok(Arc::new(FallbackSpeechEngine::default()))
         }
     }
 }
@@ -408,6 +783,43 @@ pub struct RealtimeSessionConfig {
     pub raw_emit_window: Duration,
     pub polish_emit_deadline: Duration,
     pub enable_polisher: bool,
+    /// 覆盖编排器的云端优先设置；为空时沿用 `EngineConfig::prefer_cloud`。
+    pub prefer_cloud: Option<bool>,
+    /// 用户词表：下发给支持短语增强的引擎，并用于纠正低置信度词。
+    pub vocabulary: Option<Arc<Vocabulary>>,
+    /// 语音编辑指令语法；命中的整句以 `UpdatePayload::Command` 下发，不作为正文输出。
+    pub command_grammar: Option<Arc<CommandGrammar>>,
+    /// 原始稿的标点恢复语言（BCP 47）；为空时不做恢复。
+    pub punctuation_language: Option<String>,
+    /// 会话使用的润色器，默认为编排器内置润色器。
+    pub polisher: PolisherSelection,
+    /// 润色风格预设；为空时只做基础润色。
+    pub polish_profile: Option<PolishProfile>,
+    /// 润色前的句子改写钩子。
+    pub pre_polish: Option<Arc<dyn PrePolishHook>>,
+    /// 语种自动识别；启用后识别结果同时作为标点恢复的语言。
+    pub language_id: Option<LanguageIdConfig>,
+    /// 翻译目标语言；设置后润色稿附带译文下发。
+    pub translate_to: Option<Locale>,
+    pub translator: TranslatorSelection,
+    /// 未成句部分的稳定化；启用后以 `UpdatePayload::TranscriptDelta` 下发中间结果。
+    pub stabilizer: Option<StabilizerConfig>,
+    /// 按句汇总词级置信度，低于阈值的句子以 `UpdatePayload::QualityFlag` 下发。
+    pub quality: Option<QualityConfig>,
+    /// 双引擎对比模式：本地与云端并行识别，逐帧裁决并下发 `UpdatePayload::Arbitration`。
+    /// 仅在配置了云端引擎时生效。
+    pub arbitration: Option<ArbitrationConfig>,
+    /// 本地引擎热切换：云端引擎转为备用，本地故障时重放最近的语音并接管后续帧。
+    /// 仅在配置了云端引擎时生效，启用后不再进行双引擎对比。
+    pub failover: Option<FailoverConfig>,
+    /// 会话音频的采集来源，随每条转写下发。
+    pub audio_source: AudioSource,
+    /// 敏感信息识别；除仅标注模式外，发往云端润色前剔除命中内容并在结果中还原。
+    pub redaction: Option<Arc<Redactor>>,
+    /// 外发审计；连接后云端识别、润色与翻译的每次请求都会记录。
+    pub egress: EgressRecorder,
+    /// 所属会话，写入 `realtime_session` span 供链路追踪关联。
+    pub session_id: Option<String>,
 }
 
 impl Default for RealtimeSessionConfig {
@@ -416,11 +828,29 @@ impl Default for RealtimeSessionConfig {
             sample_rate_hz: 16_000,
             min_frame_duration: Duration::from_millis(100),
             max_frame_duration: Duration::from_millis(200),
-            first_update_deadline: Duration::from_millis(400),
+            first_update_deadline: DEFAULT_FIRST_UPDATE_DEADLINE,
             buffer_capacity: 32,
             raw_emit_window: Duration::from_millis(200),
             polish_emit_deadline: Duration::from_millis(2_500),
             enable_polisher: true,
+            prefer_cloud: None,
+            vocabulary: None,
+            command_grammar: None,
+            punctuation_language: None,
+            polisher: PolisherSelection::Default,
+            polish_profile: None,
+            pre_polish: None,
+            language_id: None,
+            translate_to: None,
+            translator: TranslatorSelection::Default,
+            stabilizer: None,
+            quality: None,
+            arbitration: None,
+            failover: None,
+            audio_source: AudioSource::Microphone,
+            redaction: None,
+            egress: EgressRecorder::default(),
+            session_id: None,
         }
     }
 }
@@ -430,6 +860,12 @@ pub enum UpdatePayload {
     Transcript(TranscriptPayload),
     Notice(SessionNotice),
     Selection(TranscriptSelectionPayload),
+    Command(SessionCommandPayload),
+    PolishDelta(PolishDeltaPayload),
+    LanguageChanged(LanguageChangedPayload),
+    TranscriptDelta(TranscriptDelta),
+    QualityFlag(QualityFlag),
+    Arbitration(ArbitrationDecision),
 }
 
 #[derive(Debug, Clone)]
@@ -439,6 +875,35 @@ pub struct TranscriptPayload {
     pub source: TranscriptSource,
     pub is_primary: bool,
     pub within_sla: bool,
+    /// 按语种切分的文本段，供按语种润色与混排渲染使用。
+    pub segments: Vec<LanguageSegment>,
+    /// 润色稿的译文，仅在启用翻译且翻译成功时出现。
+    pub translation: Option<TranslatedText>,
+    /// 该句音频的采集来源（麦克风或系统回放）。
+    pub audio_source: AudioSource,
+}
+
+/// 流式润色过程中的累计片段；最终结果仍以 `TranscriptSource::Polished` 下发。
+#[derive(Debug, Clone)]
+pub struct PolishDeltaPayload {
+    pub sentence_id: u64,
+    pub text: String,
+}
+
+/// 会话选定或切换了识别语言，引擎已按新语言重新配置。
+#[derive(Debug, Clone)]
+pub struct LanguageChangedPayload {
+    /// 为空表示会话首次选定语言。
+    pub previous: Option<String>,
+    pub language: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionCommandPayload {
+    pub command: SessionCommand,
+    /// 触发指令的原始语句。
+    pub utterance: String,
 }
 
 #[derive(Debug, Clone)]
@@ -468,6 +933,13 @@ fn variant_label(variant: SentenceVariant) -> &'static str {
 #[derive(Debug, Clone)]
 pub enum TranscriptCommand {
     ApplySelection(Vec<SentenceSelection>),
+    /// 用指定引擎重新识别单句缓存的语音，结果以同一句 ID 下发替换原文。
+    Retranscribe {
+        sentence_id: u64,
+        engine: EngineRoute,
+    },
+    /// 重新润色最近一句的原文，结果以同一句 ID 的润色稿下发。
+    RepolishLast,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -478,7 +950,7 @@ pub enum TranscriptSource {
 }
 
 impl TranscriptSource {
-    fn as_str(&self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             TranscriptSource::Local => "local",
             TranscriptSource::Cloud => "cloud",
@@ -508,11 +980,35 @@ pub struct TranscriptionUpdate {
     pub is_first: bool,
 }
 
+#[derive(Clone)]
+pub struct FrameWindowControl(Arc<LocalProgress>);
+
+impl FrameWindowControl {
+    pub fn set(&self, min_frame: Duration, max_frame: Duration) {
+        info!(
+            target: "engine_orchestrator",
+            min_ms = duration_to_ms(min_frame),
+            max_ms = duration_to_ms(max_frame),
+            "frame window updated"
+        );
+        self.0.set_frame_window(min_frame, max_frame);
+    }
+
+    /// 节奏监测等待新结果的间隔。
+    pub fn cadence(&self) -> Duration {
+        self.0.cadence()
+    }
+}
+
 #[derive(Default)]
 struct LocalProgress {
     last_frame: AtomicU64,
+    min_frame_ms: AtomicU64,
+    max_frame_ms: AtomicU64,
     degraded: AtomicBool,
     last_update_ms: AtomicU64,
+    /// 首个本地更新相对会话开始的毫秒数，未产生更新时为 0。
+    first_update_ms: AtomicU64,
     speech_started_ms: AtomicU64,
     speech_active: AtomicBool,
 }
@@ -520,269 +1016,106 @@ struct LocalProgress {
 #[derive(Debug)]
 struct LocalDecoderState {
     sentence_buffer: SentenceBuffer,
+    stabilizer: Option<PartialStabilizer>,
+    quality: Option<ConfidenceTracker>,
 }
 
 impl LocalDecoderState {
-    fn new(window: Duration) -> Self {
+    fn new(
+        window: Duration,
+        stabilizer: Option<StabilizerConfig>,
+        quality: Option<QualityConfig>,
+    ) -> Self {
         Self {
             sentence_buffer: SentenceBuffer::new(window),
+            stabilizer: stabilizer.map(PartialStabilizer::new),
+            quality: quality.map(ConfidenceTracker::new),
         }
     }
 }
 
-#[derive(Debug)]
-struct SentenceBuffer {
-    pending: String,
-    pending_since: Option<Instant>,
-    window: Duration,
-}
-
-impl SentenceBuffer {
-    fn new(window: Duration) -> Self {
-        Self {
-            pending: String::new(),
-            pending_since: None,
-            window,
-        }
+impl LocalProgress {
+    fn new() -> Self {
+        Self::default()
     }
 
-    fn ingest(&mut self, delta: &str, now: Instant) -> Vec<String> {
-        let mut ready = Vec::new();
-        let has_content = !delta.trim().is_empty();
-
-        if has_content {
-            let trimmed_start = if self.pending.is_empty() {
-                delta.trim_start_matches(char::is_whitespace)
-            } else {
-                delta
-            };
-
-            if !self.pending.is_empty() && needs_injected_space(&self.pending, trimmed_start) {
-                self.pending.push(' ');
-            }
-
-            self.pending.push_str(trimmed_start);
+    fn record_success(&self, frame_index: usize, started_at: Instant) {
+        let new_index = frame_index as u64;
+        let mut current = self.last_frame.load(Ordering::SeqCst);
 
-            if self.pending_since.is_none() && !self.pending.is_empty() {
-                self.pending_since = Some(now);
+        loop {
+            if current >= new_index {
+                self.mark_speech_detected(started_at);
+                return;
             }
 
-            ready.extend(self.take_completed_sentences(now));
-        }
-
-        if ready.is_empty() {
-            if let Some(since) = self.pending_since {
-                if now.saturating_duration_since(since) >= self.window && !self.pending.is_empty() {
-                    ready.push(self.pending.trim().to_string());
-                    self.pending.clear();
-                    self.pending_since = None;
+            match self.last_frame.compare_exchange(
+                current,
+                new_index,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => {
+                    let now_ms = duration_to_ms(started_at.elapsed()).max(1);
+                    self.degraded.store(false, Ordering::SeqCst);
+                    self.last_update_ms.store(now_ms, Ordering::SeqCst);
+                    let _ = self.first_update_ms.compare_exchange(
+                        0,
+                        now_ms,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    );
+                    self.mark_speech_detected(started_at);
+                    return;
                 }
+                Err(actual) => current = actual,
             }
         }
-
-        ready
     }
 
-    fn take_completed_sentences(&mut self, now: Instant) -> Vec<String> {
-        let mut ready = Vec::new();
-
-        loop {
-            let Some(boundary) = find_sentence_boundary(&self.pending) else {
-                break;
-            };
-
-            let chunk = self.pending[..boundary].trim().to_string();
-            if !chunk.is_empty() {
-                ready.push(chunk);
-            }
-
-            let remainder = self.pending[boundary..]
-                .trim_start_matches(char::is_whitespace)
-                .to_string();
-            self.pending = remainder;
+    fn mark_degraded(&self, started_at: Instant) {
+        self.degraded.store(true, Ordering::SeqCst);
+        self.last_update_ms
+            .store(duration_to_ms(started_at.elapsed()), Ordering::SeqCst);
+    }
 
-            if self.pending.is_empty() {
-                self.pending_since = None;
-            } else {
-                self.pending_since = Some(now);
-            }
+    fn record_frame_energy(&self, started_at: Instant, rms: f32) {
+        if rms >= SPEECH_RMS_THRESHOLD {
+            self.speech_active.store(true, Ordering::SeqCst);
+            self.mark_speech_detected(started_at);
+        } else if rms <= SILENCE_RMS_THRESHOLD {
+            self.speech_active.store(false, Ordering::SeqCst);
         }
+    }
 
-        ready
+    fn last_frame(&self) -> u64 {
+        self.last_frame.load(Ordering::SeqCst)
     }
-}
 
-fn find_sentence_boundary(pending: &str) -> Option<usize> {
-    let mut chars = pending.char_indices();
-    while let Some((idx, ch)) = chars.next() {
-        if !is_sentence_boundary(ch) {
-            continue;
-        }
+    fn set_frame_window(&self, min_frame: Duration, max_frame: Duration) {
+        self.min_frame_ms
+            .store(duration_to_ms(min_frame), Ordering::SeqCst);
+        self.max_frame_ms
+            .store(duration_to_ms(max_frame), Ordering::SeqCst);
+    }
 
-        let mut boundary = idx + ch.len_utf8();
-        while let Some(next) = pending[boundary..].chars().next() {
-            if next == ch && is_sentence_boundary(next) {
-                boundary += next.len_utf8();
-            } else {
-                break;
-            }
-        }
+    fn min_frame(&self) -> Duration {
+        Duration::from_millis(self.min_frame_ms.load(Ordering::SeqCst))
+    }
 
-        return Some(boundary);
+    fn max_frame(&self) -> Duration {
+        Duration::from_millis(self.max_frame_ms.load(Ordering::SeqCst))
     }
-    None
-}
 
-fn is_sentence_boundary(ch: char) -> bool {
-    matches!(
-        ch,
-        '.' | '!' | '?' | '\n' | '\r' | '。' | '！' | '？' | '…' | ';' | '；'
-    )
-}
-
-fn needs_injected_space(existing: &str, addition: &str) -> bool {
-    let last = existing.chars().rev().find(|c| !c.is_whitespace());
-    let first = addition.chars().find(|c| !c.is_whitespace());
-
-    match (last, first) {
-        (Some(l), Some(f)) => {
-            !l.is_whitespace()
-                && !f.is_whitespace()
-                && !is_sentence_boundary(l)
-                && !is_sentence_boundary(f)
-                && !matches!(f, ',' | '，' | ':' | '：')
-        }
-        _ => false,
-    }
-}
-
-#[derive(Debug, Default)]
-struct SentenceStore {
-    next_sentence_id: u64,
-    records: BTreeMap<u64, SentenceRecord>,
-}
-
-#[derive(Debug)]
-struct SentenceRecord {
-    raw_text: String,
-    raw_source: TranscriptSource,
-    polished_text: Option<String>,
-    polished_within_sla: Option<bool>,
-    active_variant: SentenceVariant,
-    user_override: bool,
-}
-
-impl SentenceStore {
-    fn register_raw_sentence(&mut self, text: String, source: TranscriptSource) -> u64 {
-        self.next_sentence_id = self.next_sentence_id.saturating_add(1);
-        let sentence_id = self.next_sentence_id;
-        let record = SentenceRecord {
-            raw_text: text,
-            raw_source: source,
-            polished_text: None,
-            polished_within_sla: None,
-            active_variant: SentenceVariant::Raw,
-            user_override: false,
-        };
-        self.records.insert(sentence_id, record);
-        sentence_id
-    }
-
-    fn record_polished(
-        &mut self,
-        sentence_id: u64,
-        text: String,
-        within_sla: bool,
-    ) -> Option<SentenceVariant> {
-        if let Some(record) = self.records.get_mut(&sentence_id) {
-            record.polished_text = Some(text);
-            record.polished_within_sla = Some(within_sla);
-            if !record.user_override {
-                record.active_variant = SentenceVariant::Polished;
-            }
-            return Some(record.active_variant);
-        }
-        None
-    }
-
-    fn apply_selection(&mut self, selections: &[SentenceSelection]) -> Vec<SentenceSelection> {
-        let mut applied = Vec::new();
-
-        for selection in selections {
-            if let Some(record) = self.records.get_mut(&selection.sentence_id) {
-                match selection.active_variant {
-                    SentenceVariant::Raw => {
-                        record.active_variant = SentenceVariant::Raw;
-                        record.user_override = true;
-                        applied.push(*selection);
-                    }
-                    SentenceVariant::Polished => {
-                        if record.polished_text.is_some() {
-                            record.active_variant = SentenceVariant::Polished;
-                            record.user_override = false;
-                            applied.push(*selection);
-                        }
-                    }
-                }
-            }
-        }
-
-        applied
-    }
-}
-
-impl LocalProgress {
-    fn new() -> Self {
-        Self::default()
-    }
-
-    fn record_success(&self, frame_index: usize, started_at: Instant) {
-        let new_index = frame_index as u64;
-        let mut current = self.last_frame.load(Ordering::SeqCst);
-
-        loop {
-            if current >= new_index {
-                self.mark_speech_detected(started_at);
-                return;
-            }
-
-            match self.last_frame.compare_exchange(
-                current,
-                new_index,
-                Ordering::SeqCst,
-                Ordering::SeqCst,
-            ) {
-                Ok(_) => {
-                    self.degraded.store(false, Ordering::SeqCst);
-                    self.last_update_ms
-                        .store(duration_to_ms(started_at.elapsed()), Ordering::SeqCst);
-                    self.mark_speech_detected(started_at);
-                    return;
-                }
-                Err(actual) => current = actual,
-            }
-        }
-    }
-
-    fn mark_degraded(&self, started_at: Instant) {
-        self.degraded.store(true, Ordering::SeqCst);
-        self.last_update_ms
-            .store(duration_to_ms(started_at.elapsed()), Ordering::SeqCst);
-    }
-
-    fn record_frame_energy(&self, started_at: Instant, rms: f32) {
-        if rms >= SPEECH_RMS_THRESHOLD {
-            self.speech_active.store(true, Ordering::SeqCst);
-            self.mark_speech_detected(started_at);
-        } else if rms <= SILENCE_RMS_THRESHOLD {
-            self.speech_active.store(false, Ordering::SeqCst);
+    fn cadence(&self) -> Duration {
+        let (min, max) = (self.min_frame(), self.max_frame());
+        if max.is_zero() {
+            min
+        } else {
+            max.max(min)
         }
     }
 
-    fn last_frame(&self) -> u64 {
-        self.last_frame.load(Ordering::SeqCst)
-    }
-
     fn is_degraded(&self) -> bool {
         self.degraded.load(Ordering::SeqCst)
     }
@@ -801,6 +1134,10 @@ impl LocalProgress {
         );
     }
 
+    fn first_update_ms(&self) -> u64 {
+        self.first_update_ms.load(Ordering::SeqCst)
+    }
+
     fn speech_started_ms(&self) -> u64 {
         self.speech_started_ms.load(Ordering::SeqCst)
     }
@@ -818,17 +1155,20 @@ pub struct RealtimeSessionHandle {
     config: RealtimeSessionConfig,
     frame_tx: mpsc::Sender<Arc<[f32]>>,
     command_tx: mpsc::Sender<TranscriptCommand>,
+    /// 测试用于预先塞满下发通道。
+    #[cfg(test)]
     updates_tx: mpsc::Sender<TranscriptionUpdate>,
-    first_update_flag: Arc<AtomicBool>,
-    first_local_update_flag: Arc<AtomicBool>,
     local_progress: Arc<LocalProgress>,
-    local_update_notify: Arc<Notify>,
-    started_at: Instant,
     monitor: Option<JoinHandle<()>>,
     worker: Option<JoinHandle<()>>,
 }
 
 impl RealtimeSessionHandle {
+    /// 会话实际生效的配置，已合并预设与应用偏好。
+    pub fn config(&self) -> &RealtimeSessionConfig {
+        &self.config
+    }
+
     pub async fn push_frame(
         &self,
         frame: Vec<f32>,
@@ -841,14 +1181,16 @@ impl RealtimeSessionHandle {
         let frame_duration =
             Duration::from_secs_f64(frame.len() as f64 / self.config.sample_rate_hz as f64);
 
-        if frame_duration < self.config.min_frame_duration
-            || frame_duration > self.config.max_frame_duration
-        {
+        let (min, max) = (
+            self.local_progress.min_frame(),
+            self.local_progress.max_frame(),
+        );
+        if frame_duration < min || frame_duration > max {
             warn!(
                 target: "engine_orchestrator",
                 ?frame_duration,
-                min = ?self.config.min_frame_duration,
-                max = ?self.config.max_frame_duration,
+                ?min,
+                ?max,
                 "audio frame duration out of expected bounds"
             );
         }
@@ -871,6 +1213,23 @@ impl RealtimeSessionHandle {
         self.frame_tx.clone()
     }
 
+    /// 采集端调整帧窗口后同步给会话：帧长校验、解码节奏与节奏监测均按新窗口执行。
+    pub fn set_frame_window(&self, min_frame: Duration, max_frame: Duration) {
+        self.frame_window().set(min_frame, max_frame);
+    }
+
+    /// 可在会话句柄之外持有的帧窗口控制。
+    pub fn frame_window(&self) -> FrameWindowControl {
+        FrameWindowControl(self.local_progress.clone())
+    }
+
+    /// 重新润色最近一句；会话未启用润色时同样执行，没有句子时下发提示。
+    pub async fn repolish_last_sentence(
+        &self,
+    ) -> Result<(), mpsc::error::SendError<TranscriptCommand>> {
+        self.command_tx.send(TranscriptCommand::RepolishLast).await
+    }
+
     pub async fn apply_sentence_selections(
         &self,
         selections: Vec<SentenceSelection>,
@@ -883,6 +1242,11 @@ impl RealtimeSessionHandle {
             .send(TranscriptCommand::ApplySelection(selections))
             .await
     }
+
+    /// 会话命令入口，供不持有句柄的一方（如全局快捷键）下发命令。
+    pub fn command_sender(&self) -> mpsc::Sender<TranscriptCommand> {
+        self.command_tx.clone()
+    }
 }
 
 impl Drop for RealtimeSessionHandle {
@@ -906,6 +1270,7 @@ struct RealtimeWorker {
     local_engine: Arc<dyn SpeechEngine>,
     cloud_engine: Option<Arc<dyn SpeechEngine>>,
     polisher: Arc<dyn SentencePolisher>,
+    punctuation: Arc<dyn PunctuationRestorer>,
     first_update_flag: Arc<AtomicBool>,
     first_local_update_flag: Arc<AtomicBool>,
     local_progress: Arc<LocalProgress>,
@@ -914,6 +1279,248 @@ struct RealtimeWorker {
     sentences: Arc<Mutex<SentenceStore>>,
     started_at: Instant,
     prefer_cloud: bool,
+    vocabulary: Option<Arc<VocabularyPass>>,
+    language: Option<Arc<StdMutex<LanguageTracker>>>,
+    translation: Option<Arc<TranslationStage>>,
+    arbiter: Option<Arc<StdMutex<Arbiter>>>,
+    failover: Option<Arc<FailoverState>>,
+    cloud_gate: Arc<CloudGate>,
+    /// 润色走云端时的本地替代，云端被禁用时使用。
+    local_polisher: Option<Arc<dyn SentencePolisher>>,
+    network: Option<Arc<NetworkMonitor>>,
+    /// 最近的语音帧，供按句重新识别。
+    audio_history: Arc<StdMutex<ReplayBuffer>>,
+}
+
+/// 会话内固定的词表提示与纠正器。
+struct VocabularyPass {
+    hints: Vec<PhraseHint>,
+    corrector: VocabularyCorrector,
+}
+
+impl VocabularyPass {
+    fn from_config(config: &RealtimeSessionConfig) -> Option<Arc<Self>> {
+        let vocabulary = config
+            .vocabulary
+            .as_ref()
+            .filter(|vocab| !vocab.is_empty())?;
+        Some(Arc::new(Self {
+            hints: vocabulary.phrase_hints(),
+            corrector: VocabularyCorrector::new(vocabulary),
+        }))
+    }
+}
+
+/// 会话内固定的翻译目标与翻译器。
+struct TranslationStage {
+    translator: Arc<dyn Translator>,
+    target: Locale,
+    /// 译文需发往远端服务；注入的翻译器按远端处理。
+    remote: bool,
+}
+
+impl TranslationStage {
+    fn from_config(
+        config: &RealtimeSessionConfig,
+        injected: Option<Arc<dyn Translator>>,
+        budget: &Arc<CloudBudget>,
+    ) -> Option<Arc<Self>> {
+        let target = config.translate_to.clone()?;
+        let remote = match &config.translator {
+            TranslatorSelection::Default => true,
+            TranslatorSelection::Llm(llm) => !is_local_endpoint(&llm.endpoint),
+            TranslatorSelection::Nllb(nllb) => !is_local_endpoint(&nllb.endpoint),
+        };
+        let translator: Option<Arc<dyn Translator>> = match &config.translator {
+            TranslatorSelection::Default => injected,
+            TranslatorSelection::Llm(llm) => Some(Arc::new(LlmTranslator::new(llm.clone()))),
+            TranslatorSelection::Nllb(nllb) => Some(Arc::new(NllbTranslator::new(nllb.clone()))),
+        };
+        let translator = translator.map(|translator| -> Arc<dyn Translator> {
+            if !remote {
+                return translator;
+            }
+            let translator = Arc::new(MeteredTranslator::new(translator, Arc::clone(budget)));
+            if !config.egress.is_attached() {
+                return translator;
+            }
+            let destination = match &config.translator {
+                TranslatorSelection::Default => "translator",
+                TranslatorSelection::Llm(llm) => llm.endpoint.as_str(),
+                TranslatorSelection::Nllb(nllb) => nllb.endpoint.as_str(),
+            };
+            Arc::new(AuditedTranslator::new(
+                translator,
+                config.egress.clone(),
+                destination,
+            ))
+        });
+        if translator.is_none() {
+            warn!(
+                target: "engine_orchestrator",
+                %target,
+                "translation requested but no translator is configured"
+            );
+        }
+        translator.map(|translator| {
+            Arc::new(Self {
+                translator,
+                target,
+                remote,
+            })
+        })
+    }
+
+    /// 翻译失败时记录告警并返回 `None`，不影响原文下发。
+    async fn translate(&self, text: &str, source: Option<&str>) -> Option<TranslatedText> {
+        if text.trim().is_empty() || translation::same_language(source, &self.target) {
+            return None;
+        }
+        match self.translator.translate(text, source, &self.target).await {
+            Ok(translated) => Some(TranslatedText {
+                locale: self.target.clone(),
+                text: translated,
+            }),
+            Err(err) => {
+                warn!(
+                    target: "engine_orchestrator",
+                    %err,
+                    target_locale = %self.target,
+                    "failed to translate polished sentence"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// 对比模式下提交一方的结果；同一帧两个结果到齐后下发裁决并记录遥测。
+async fn submit_arbitration(
+    arbiter: Option<&StdMutex<Arbiter>>,
+    frame_index: usize,
+    candidate: Option<EngineCandidate>,
+    tx: &mpsc::Sender<TranscriptionUpdate>,
+) {
+    let (Some(arbiter), Some(candidate)) = (arbiter, candidate) else {
+        return;
+    };
+    let decision = arbiter
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .record(frame_index, candidate);
+    let Some(decision) = decision else {
+        return;
+    };
+    record_dual_view_arbitration(DualViewArbitrationEvent {
+        frame_index,
+        winner: decision.winner.as_str(),
+        reason: decision.reason.as_str(),
+        agreement: decision.agreement,
+        local_score: decision.local_score,
+        cloud_score: decision.cloud_score,
+        local_latency_ms: duration_to_ms(decision.local.latency),
+        cloud_latency_ms: duration_to_ms(decision.cloud.latency),
+    });
+    let update = TranscriptionUpdate {
+        latency: decision.local.latency.max(decision.cloud.latency),
+        payload: UpdatePayload::Arbitration(decision),
+        frame_index,
+        is_first: false,
+    };
+    if let Err(err) = tx.send(update).await {
+        warn!(
+            target: "engine_orchestrator",
+            %err,
+            "failed to deliver engine arbitration"
+        );
+    }
+}
+
+/// 下发未成句部分的增量，并记录词元从出现到稳定的耗时。
+async fn send_partial_delta(
+    tx: &mpsc::Sender<TranscriptionUpdate>,
+    partial: Option<(TranscriptDelta, Vec<Duration>)>,
+    latency: Duration,
+    frame_index: usize,
+) {
+    let Some((delta, stabilized)) = partial else {
+        return;
+    };
+    for elapsed in stabilized {
+        metrics().partial_stabilization_latency.observe(elapsed);
+    }
+    let update = TranscriptionUpdate {
+        payload: UpdatePayload::TranscriptDelta(delta),
+        latency,
+        frame_index,
+        is_first: false,
+    };
+    if let Err(err) = tx.send(update).await {
+        warn!(
+            target: "engine_orchestrator",
+            %err,
+            "failed to deliver partial transcript delta"
+        );
+    }
+}
+
+/// 调用润色器，并把流式片段转发为 `UpdatePayload::PolishDelta`；返回前确保片段均已送出。
+async fn polish_streaming(
+    polisher: &dyn SentencePolisher,
+    sentence: &str,
+    sentence_id: u64,
+    frame_index: usize,
+    tx: &mpsc::Sender<TranscriptionUpdate>,
+    polish_started: Instant,
+) -> Result<String> {
+    let (partial_tx, mut partial_rx) = mpsc::unbounded_channel::<String>();
+    let forward_tx = tx.clone();
+    let forwarder = tokio::spawn(async move {
+        while let Some(text) = partial_rx.recv().await {
+            let update = TranscriptionUpdate {
+                payload: UpdatePayload::PolishDelta(PolishDeltaPayload { sentence_id, text }),
+                latency: polish_started.elapsed(),
+                frame_index,
+                is_first: false,
+            };
+            if forward_tx.send(update).await.is_err() {
+                break;
+            }
+        }
+    });
+    let result = polisher.polish_streaming(sentence, &partial_tx).await;
+    drop(partial_tx);
+    let _ = forwarder.await;
+    result
+}
+
+async fn transcribe_frame(
+    engine: &dyn SpeechEngine,
+    frame: &[f32],
+    vocabulary: Option<&VocabularyPass>,
+) -> Result<String> {
+    match vocabulary {
+        Some(pass) => engine
+            .transcribe_scored(frame, &pass.hints)
+            .await
+            .map(|scored| pass.corrector.correct(&scored)),
+        None => engine.transcribe(frame).await,
+    }
+}
+
+/// 与 `transcribe_frame` 相同，同时保留词级置信度供质量评估。
+async fn transcribe_frame_scored(
+    engine: &dyn SpeechEngine,
+    frame: &[f32],
+    vocabulary: Option<&VocabularyPass>,
+) -> Result<(String, Vec<ScoredToken>)> {
+    let hints = vocabulary.map(|pass| pass.hints.as_slice()).unwrap_or(&[]);
+    let scored = engine.transcribe_scored(frame, hints).await?;
+    let text = match vocabulary {
+        Some(pass) => pass.corrector.correct(&scored),
+        None => scored.text,
+    };
+    Ok((text, scored.tokens))
 }
 
 struct CloudCircuit {
@@ -958,8 +1565,8 @@ impl CloudCircuit {
         let backoff_ms = duration_to_ms(backoff);
         let next_retry = elapsed_ms.saturating_add(backoff_ms);
         self.next_retry_ms.store(next_retry, Ordering::SeqCst);
-        let was_enabled = self.enabled.swap(false, Ordering::SeqCst);
-        was_enabled
+
+        self.enabled.swap(false, Ordering::SeqCst)
     }
 }
 
@@ -981,6 +1588,7 @@ fn frame_rms(frame: &[f32]) -> f32 {
     (energy / frame.len() as f32).sqrt()
 }
 
+#[cfg_attr(not(feature = "local-asr"), allow(dead_code))]
 fn suffix_prefix_overlap(existing: &str, new_text: &str) -> usize {
     let max = existing.len().min(new_text.len());
     for overlap in (1..=max).rev() {
@@ -992,6 +1600,7 @@ fn suffix_prefix_overlap(existing: &str, new_text: &str) -> usize {
 }
 
 impl RealtimeWorker {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         config: RealtimeSessionConfig,
         frame_rx: mpsc::Receiver<Arc<[f32]>>,
@@ -1000,6 +1609,10 @@ impl RealtimeWorker {
         local_engine: Arc<dyn SpeechEngine>,
         cloud_engine: Option<Arc<dyn SpeechEngine>>,
         polisher: Arc<dyn SentencePolisher>,
+        punctuation: Arc<dyn PunctuationRestorer>,
+        translator: Option<Arc<dyn Translator>>,
+        cloud_gate: Arc<CloudGate>,
+        network: Option<Arc<NetworkMonitor>>,
         first_update_flag: Arc<AtomicBool>,
         first_local_update_flag: Arc<AtomicBool>,
         local_progress: Arc<LocalProgress>,
@@ -1009,6 +1622,77 @@ impl RealtimeWorker {
         started_at: Instant,
         prefer_cloud: bool,
     ) -> Self {
+        let vocabulary = VocabularyPass::from_config(&config);
+        let translation = TranslationStage::from_config(&config, translator, cloud_gate.budget());
+        let failover = config.failover.as_ref().and_then(|failover| {
+            cloud_engine.clone().map(|standby| {
+                Arc::new(FailoverState::new(
+                    standby,
+                    ReplayBuffer::new(failover, config.sample_rate_hz),
+                    config.audio_source,
+                ))
+            })
+        });
+        let arbiter = config
+            .arbitration
+            .clone()
+            .filter(|_| cloud_engine.is_some() && failover.is_none())
+            .map(|arbitration| Arc::new(StdMutex::new(Arbiter::new(arbitration))));
+        let language = config.language_id.clone().map(|language_id| {
+            Arc::new(StdMutex::new(LanguageTracker::new(
+                language_id,
+                config.sample_rate_hz,
+            )))
+        });
+        let with_profile = |polisher: Arc<dyn SentencePolisher>| -> Arc<dyn SentencePolisher> {
+            let polisher: Arc<dyn SentencePolisher> = match config.polish_profile {
+                Some(profile) => Arc::new(ProfiledPolisher {
+                    inner: polisher,
+                    profile,
+                }),
+                None => polisher,
+            };
+            match &config.pre_polish {
+                Some(hook) => Arc::new(HookedPolisher {
+                    inner: polisher,
+                    hook: Arc::clone(hook),
+                }),
+                None => polisher,
+            }
+        };
+        let (polisher, local_polisher) = match &config.polisher {
+            PolisherSelection::Default => (with_profile(polisher), None),
+            PolisherSelection::Llm(llm) => {
+                let remote = !is_local_endpoint(&llm.endpoint);
+                let local = remote.then(|| with_profile(Arc::new(LightweightSentencePolisher)));
+                let redactor = config
+                    .redaction
+                    .as_ref()
+                    .filter(|redactor| remote && redactor.mode().strips_cloud());
+                let mut llm = llm.clone();
+                if redactor.is_some() {
+                    llm.system_prompt = format!("{}\n{PLACEHOLDER_PROMPT}", llm.system_prompt);
+                }
+                let endpoint = llm.endpoint.clone();
+                let mut llm: Arc<dyn SentencePolisher> = Arc::new(LlmPolisher::new(llm));
+                if remote {
+                    llm = Arc::new(MeteredPolisher::new(llm, Arc::clone(cloud_gate.budget())));
+                }
+                if remote && config.egress.is_attached() {
+                    llm = Arc::new(AuditedPolisher::new(llm, config.egress.clone(), endpoint));
+                }
+                if let Some(redactor) = redactor {
+                    llm = Arc::new(RedactingPolisher::new(llm, Arc::clone(redactor)));
+                }
+                (with_profile(llm), local)
+            }
+        };
+        let audio_history = Arc::new(StdMutex::new(ReplayBuffer::new(
+            &FailoverConfig {
+                replay_window: RETRANSCRIBE_WINDOW,
+            },
+            config.sample_rate_hz,
+        )));
         Self {
             config,
             frame_rx,
@@ -1017,6 +1701,7 @@ impl RealtimeWorker {
             local_engine,
             cloud_engine,
             polisher,
+            punctuation,
             first_update_flag,
             first_local_update_flag,
             local_progress,
@@ -1025,24 +1710,81 @@ impl RealtimeWorker {
             sentences,
             started_at,
             prefer_cloud,
+            vocabulary,
+            language,
+            translation,
+            arbiter,
+            failover,
+            cloud_gate,
+            local_polisher,
+            network,
+            audio_history,
+        }
+    }
+
+    /// 混合模式下的主引擎；未启用、无云端引擎或云端被禁用时为 `None`。
+    fn hybrid_route(&self) -> Option<EngineRoute> {
+        let monitor = self
+            .network
+            .as_ref()
+            .filter(|_| self.cloud_engine.is_some())?;
+        if !self.cloud_gate.allows(CloudFeature::Transcription) {
+            return Some(EngineRoute::Local);
         }
+        Some(monitor.route())
     }
 
-    fn spawn(self) -> JoinHandle<()> {
-        tokio::spawn(async move {
-            self.run().await;
+    fn punctuation_stage(&self) -> Option<(Arc<dyn PunctuationRestorer>, String)> {
+        self.config.punctuation_language.clone().map(|language| {
+            let language = self.detected_language().unwrap_or(language);
+            (Arc::clone(&self.punctuation), language)
         })
     }
 
+    /// 混合模式主引擎变化时提示界面。
+    async fn announce_route(&self, route: Option<EngineRoute>, frame_index: usize) {
+        let message = match route {
+            Some(EngineRoute::Cloud) => "网络状况良好，已切换为云端识别",
+            Some(EngineRoute::Local) => "网络状况不佳，已切换为本地识别",
+            None => return,
+        };
+        let notice = TranscriptionUpdate {
+            payload: UpdatePayload::Notice(SessionNotice {
+                level: NoticeLevel::Info,
+                message: message.to_string(),
+            }),
+            latency: self.started_at.elapsed(),
+            frame_index,
+            is_first: false,
+        };
+        if let Err(err) = self.updates_tx.send(notice).await {
+            warn!(
+                target: "engine_orchestrator",
+                %err,
+                "failed to deliver engine route notice"
+            );
+        }
+    }
+
+    fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(
+            async move {
+                self.run().await;
+            }
+            .in_current_span(),
+        )
+    }
+
     async fn run(mut self) {
         let mut frame_index: usize = 0;
         let cloud_circuit = self
             .cloud_engine
             .as_ref()
             .map(|_| Arc::new(CloudCircuit::new()));
-        let mut next_schedule = TokioInstant::now();
+        let mut next_schedule = Instant::now();
         let mut frame_closed = false;
         let mut command_closed = false;
+        let mut last_route = self.hybrid_route();
 
         loop {
             if frame_closed && command_closed {
@@ -1072,34 +1814,70 @@ impl RealtimeWorker {
                                 frame.len() as f64 / self.config.sample_rate_hz as f64,
                             );
 
-                            let pacing_step = frame_duration.max(self.config.min_frame_duration);
-                            let now = TokioInstant::now();
+                            let pacing_step =
+                                frame_duration.max(self.local_progress.min_frame());
+                            let now = Instant::now();
                             if now < next_schedule {
                                 sleep_until(next_schedule).await;
                             }
-                            next_schedule = TokioInstant::now() + pacing_step;
+                            next_schedule = Instant::now() + pacing_step;
 
                             let frame_started = Instant::now();
+                            self.audio_history
+                                .lock()
+                                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                .push(frame_index, frame.clone());
                             let rms = frame_rms(frame.as_ref());
                             self.local_progress
                                 .record_frame_energy(self.started_at, rms);
+                            if rms >= SPEECH_RMS_THRESHOLD {
+                                self.spawn_language_detection(frame.as_ref(), frame_index);
+                            }
 
-                            self.spawn_local_task(
-                                frame.clone(),
-                                frame_index,
-                                frame_started,
-                                cloud_circuit.as_ref().map(Arc::clone),
-                            );
+                            let route = self.hybrid_route();
+                            if route != last_route {
+                                self.announce_route(route, frame_index).await;
+                                last_route = route;
+                            }
+                            let cloud_primary = route == Some(EngineRoute::Cloud);
+
+                            // 热切换模式下云端引擎仅作备用，切换后由其接管全部帧。
+                            let standby_active = self.failover.as_ref().map(|failover| failover.is_active());
+                            if standby_active != Some(true) {
+                                if let Some(failover) = &self.failover {
+                                    failover.replay().push(frame_index, frame.clone());
+                                }
+                                self.spawn_local_task(
+                                    frame.clone(),
+                                    frame_index,
+                                    frame_started,
+                                    cloud_primary,
+                                    cloud_circuit.as_ref().map(Arc::clone),
+                                );
+                            }
 
                             if let (Some(cloud_engine), Some(circuit)) =
                                 (self.cloud_engine.clone(), cloud_circuit.as_ref())
                             {
                                 let now = Instant::now();
-                                if circuit.allow_attempt(self.started_at, now) {
+                                if standby_active != Some(false)
+                                    && route != Some(EngineRoute::Local)
+                                    && self
+                                        .cloud_gate
+                                        .admit(
+                                            CloudFeature::Transcription,
+                                            &self.updates_tx,
+                                            frame_index,
+                                            Duration::ZERO,
+                                        )
+                                        .await
+                                    && circuit.allow_attempt(self.started_at, now)
+                                {
                                     self.spawn_cloud_task(
                                         frame.clone(),
                                         frame_index,
                                         frame_started,
+                                        cloud_primary,
                                         cloud_engine,
                                         Arc::clone(circuit),
                                     );
@@ -1124,6 +1902,7 @@ impl RealtimeWorker {
         frame: Arc<[f32]>,
         frame_index: usize,
         frame_started: Instant,
+        cloud_primary: bool,
         _cloud_state: Option<Arc<CloudCircuit>>,
     ) {
         let engine = Arc::clone(&self.local_engine);
@@ -1134,69 +1913,222 @@ impl RealtimeWorker {
         let local_notify = self.local_update_notify.clone();
         let local_serial = self.local_serial.clone();
         let sentences_store = self.sentences.clone();
+        let vocabulary = self.vocabulary.clone();
+        let command_grammar = self.config.command_grammar.clone();
+        let punctuation = self.punctuation_stage();
+        let segment_language = self.segment_language();
+        let audio_source = self.config.audio_source;
+        let translation = self.translation.clone();
         let started_at = self.started_at;
         let polisher = Arc::clone(&self.polisher);
         let polish_deadline = self.config.polish_emit_deadline;
         let polisher_enabled = self.config.enable_polisher;
+        let arbiter = self.arbiter.clone();
+        let failover = self.failover.clone();
+        let cloud_gate = Arc::clone(&self.cloud_gate);
+        let local_polisher = self.local_polisher.clone();
+        let want_scores = self.config.quality.is_some() || arbiter.is_some();
+
+        tokio::spawn(
+            async move {
+                let mut guard = local_serial.lock().await;
+                let transcribed = if want_scores {
+                    transcribe_frame_scored(engine.as_ref(), frame.as_ref(), vocabulary.as_deref())
+                        .await
+                } else {
+                    transcribe_frame(engine.as_ref(), frame.as_ref(), vocabulary.as_deref())
+                        .await
+                        .map(|text| (text, Vec::new()))
+                };
+                match transcribed {
+                    Ok((text, tokens)) => {
+                        let now = Instant::now();
+                        let mut local_candidate = arbiter.as_ref().map(|_| EngineCandidate {
+                            source: TranscriptSource::Local,
+                            text: text.clone(),
+                            confidence: arbitration::mean_confidence(&tokens),
+                            latency: frame_started.elapsed(),
+                            sentence_ids: Vec::new(),
+                        });
+                        let state = &mut *guard;
+                        let mut sentences = state.sentence_buffer.ingest(&text, now);
+                        let assessments: Vec<Option<SentenceConfidence>> =
+                            match state.quality.as_mut() {
+                                Some(tracker) => {
+                                    tracker.push(tokens);
+                                    sentences
+                                        .iter()
+                                        .map(|sentence| tracker.assess(sentence))
+                                        .collect()
+                                }
+                                None => vec![None; sentences.len()],
+                            };
+                        let partial = state.stabilizer.as_mut().and_then(|stabilizer| {
+                            if !sentences.is_empty() {
+                                stabilizer.reset();
+                            }
+                            stabilizer.update(state.sentence_buffer.pending(), now)
+                        });
+                        if let Some(failover) = &failover {
+                            failover.record_local(
+                                frame_index,
+                                &sentences,
+                                state.sentence_buffer.pending().is_empty(),
+                            );
+                        }
+                        drop(guard);
+                        if let Some((restorer, language)) = &punctuation {
+                            for sentence in sentences.iter_mut() {
+                                *sentence = restorer.restore(sentence, language);
+                            }
+                        }
 
-        tokio::spawn(async move {
-            let mut guard = local_serial.lock().await;
-            match engine.transcribe(frame.as_ref()).await {
-                Ok(text) => {
-                    let now = Instant::now();
-                    let sentences = guard.sentence_buffer.ingest(&text, now);
-                    drop(guard);
-
-                    if sentences.is_empty() {
-                        return;
-                    }
+                        if sentences.is_empty() {
+                            send_partial_delta(&tx, partial, frame_started.elapsed(), frame_index)
+                                .await;
+                            submit_arbitration(
+                                arbiter.as_deref(),
+                                frame_index,
+                                local_candidate,
+                                &tx,
+                            )
+                            .await;
+                            return;
+                        }
 
-                    let claimed_first = first_flag
-                        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-                        .is_ok();
-                    let was_first_local = !first_local_flag.load(Ordering::SeqCst);
-                    let is_primary = !local_progress.is_degraded();
-                    let mut emitted = false;
-                    let mut first_emit = true;
+                        let claimed_first = first_flag
+                            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                            .is_ok();
+                        if claimed_first {
+                            metrics().first_update_latency.observe(started_at.elapsed());
+                        }
+                        let was_first_local = !first_local_flag.load(Ordering::SeqCst);
+                        let is_primary = !cloud_primary && !local_progress.is_degraded();
+                        let mut emitted = false;
+                        let mut first_emit = true;
+
+                        for (chunk, assessment) in sentences.into_iter().zip(assessments) {
+                            if let Some(command) = command_grammar
+                                .as_ref()
+                                .and_then(|grammar| grammar.recognize(&chunk))
+                            {
+                                let update = TranscriptionUpdate {
+                                    payload: UpdatePayload::Command(SessionCommandPayload {
+                                        command,
+                                        utterance: chunk,
+                                    }),
+                                    latency: frame_started.elapsed(),
+                                    frame_index,
+                                    is_first: false,
+                                };
+                                match tx.send(update).await {
+                                    Ok(_) => emitted = true,
+                                    Err(err) => warn!(
+                                        target: "engine_orchestrator",
+                                        %err,
+                                        "failed to deliver voice command"
+                                    ),
+                                }
+                                continue;
+                            }
 
-                    for chunk in sentences {
-                        let sentence_id = {
-                            let mut store = sentences_store.lock().await;
-                            store.register_raw_sentence(chunk.clone(), TranscriptSource::Local)
-                        };
-                        let polished_seed = chunk.clone();
-                        let latency = frame_started.elapsed();
-                        let update = TranscriptionUpdate {
-                            payload: UpdatePayload::Transcript(TranscriptPayload {
+                            let sentence_id = {
+                                let mut store = sentences_store.lock().await;
+                                store.register_raw_sentence(
+                                    chunk.clone(),
+                                    TranscriptSource::Local,
+                                    frame_index,
+                                )
+                            };
+                            if let Some(candidate) = local_candidate.as_mut() {
+                                candidate.sentence_ids.push(sentence_id);
+                            }
+                            let polished_seed = chunk.clone();
+                            let latency = frame_started.elapsed();
+                            let segments = segment_languages(&chunk, segment_language.as_deref());
+                            let quality_flag = assessment.map(|assessment| QualityFlag {
                                 sentence_id,
-                                text: chunk,
-                                source: TranscriptSource::Local,
-                                is_primary,
-                                within_sla: true,
-                            }),
-                            latency,
-                            frame_index,
-                            is_first: claimed_first && first_emit,
-                        };
-
-                        match tx.send(update).await {
-                            Ok(_) => {
-                                emitted = true;
-                                record_dual_view_latency(
+                                text: chunk.clone(),
+                                confidence: assessment.confidence,
+                                low_confidence_words: assessment.low_confidence_words,
+                            });
+                            let update = TranscriptionUpdate {
+                                payload: UpdatePayload::Transcript(TranscriptPayload {
                                     sentence_id,
-                                    variant_label(SentenceVariant::Raw),
-                                    TranscriptSource::Local.as_str(),
+                                    text: chunk,
+                                    source: TranscriptSource::Local,
                                     is_primary,
-                                    latency,
-                                    true,
-                                );
-                                if polisher_enabled {
-                                    let polish_tx = tx.clone();
-                                    let polisher = Arc::clone(&polisher);
-                                    let sentences_store = sentences_store.clone();
-                                    tokio::spawn(async move {
+                                    within_sla: true,
+                                    segments,
+                                    translation: None,
+                                    audio_source,
+                                }),
+                                latency,
+                                frame_index,
+                                is_first: claimed_first && first_emit,
+                            };
+
+                            match tx.send(update).await {
+                                Ok(_) => {
+                                    emitted = true;
+                                    record_dual_view_latency(
+                                        sentence_id,
+                                        variant_label(SentenceVariant::Raw),
+                                        TranscriptSource::Local.as_str(),
+                                        is_primary,
+                                        latency,
+                                        true,
+                                    );
+                                    if let Some(flag) = quality_flag {
+                                        let update = TranscriptionUpdate {
+                                            payload: UpdatePayload::QualityFlag(flag),
+                                            latency,
+                                            frame_index,
+                                            is_first: false,
+                                        };
+                                        if let Err(err) = tx.send(update).await {
+                                            warn!(
+                                                target: "engine_orchestrator",
+                                                %err,
+                                                "failed to deliver quality flag"
+                                            );
+                                        }
+                                    }
+                                    if polisher_enabled {
+                                        let polish_tx = tx.clone();
+                                        let polisher = Arc::clone(&polisher);
+                                        let sentences_store = sentences_store.clone();
+                                        let segment_language = segment_language.clone();
+                                        let translation = translation.clone();
+                                        let cloud_gate = Arc::clone(&cloud_gate);
+                                        let local_polisher = local_polisher.clone();
+                                        tokio::spawn(async move {
+                                        let polisher = match &local_polisher {
+                                            Some(local)
+                                                if !cloud_gate
+                                                    .admit(
+                                                        CloudFeature::Polish,
+                                                        &polish_tx,
+                                                        frame_index,
+                                                        latency,
+                                                    )
+                                                    .await =>
+                                            {
+                                                Arc::clone(local)
+                                            }
+                                            _ => polisher,
+                                        };
                                         let polish_started = Instant::now();
-                                        match polisher.polish(&polished_seed).await {
+                                        match polish_streaming(
+                                            polisher.as_ref(),
+                                            &polished_seed,
+                                            sentence_id,
+                                            frame_index,
+                                            &polish_tx,
+                                            polish_started,
+                                        )
+                                        .await
+                                        {
                                             Ok(polished) => {
                                                 let elapsed = polish_started.elapsed();
                                                 let within_sla = elapsed <= polish_deadline;
@@ -1218,6 +2150,31 @@ impl RealtimeWorker {
                                                     );
                                                 }
 
+                                                let segments = segment_languages(
+                                                    &polished,
+                                                    segment_language.as_deref(),
+                                                );
+                                                let translation = match &translation {
+                                                    Some(stage)
+                                                        if !stage.remote
+                                                            || cloud_gate
+                                                                .admit(
+                                                                    CloudFeature::Translation,
+                                                                    &polish_tx,
+                                                                    frame_index,
+                                                                    elapsed,
+                                                                )
+                                                                .await =>
+                                                    {
+                                                        stage
+                                                            .translate(
+                                                                &polished,
+                                                                segment_language.as_deref(),
+                                                            )
+                                                            .await
+                                                    }
+                                                    _ => None,
+                                                };
                                                 let update = TranscriptionUpdate {
                                                     payload: UpdatePayload::Transcript(
                                                         TranscriptPayload {
@@ -1226,6 +2183,9 @@ impl RealtimeWorker {
                                                             source: TranscriptSource::Polished,
                                                             is_primary,
                                                             within_sla,
+                                                            segments,
+                                                            translation,
+                                                            audio_source,
                                                         },
                                                     ),
                                                     latency: elapsed,
@@ -1282,106 +2242,132 @@ impl RealtimeWorker {
                                                 }
                                             }
                                         }
-                                    });
+                                    }.in_current_span());
+                                    }
                                 }
-                            }
-                            Err(err) => {
-                                if claimed_first {
-                                    first_flag.store(false, Ordering::SeqCst);
+                                Err(err) => {
+                                    if claimed_first {
+                                        first_flag.store(false, Ordering::SeqCst);
+                                    }
+                                    if was_first_local {
+                                        first_local_flag.store(false, Ordering::SeqCst);
+                                    }
+                                    local_progress.mark_degraded(started_at);
+                                    local_notify.notify_waiters();
+                                    warn!(
+                                        target: "engine_orchestrator",
+                                        %err,
+                                        "failed to deliver local transcription update"
+                                    );
+
+                                    let notice_message = if frame_index == 1 {
+                                        "本地解码延迟异常，已保留回退提示"
+                                    } else {
+                                        "本地解码增量延迟，已保留回退提示"
+                                    };
+
+                                    let notice = TranscriptionUpdate {
+                                        payload: UpdatePayload::Notice(SessionNotice {
+                                            level: NoticeLevel::Warn,
+                                            message: notice_message.to_string(),
+                                        }),
+                                        latency: frame_started.elapsed(),
+                                        frame_index,
+                                        is_first: false,
+                                    };
+
+                                    if let Err(err) = tx.send(notice).await {
+                                        warn!(
+                                            target: "engine_orchestrator",
+                                            %err,
+                                            "failed to deliver local backpressure notice"
+                                        );
+                                    }
+                                    return;
                                 }
-                                if was_first_local {
-                                    first_local_flag.store(false, Ordering::SeqCst);
-                                }
-                                local_progress.mark_degraded(started_at);
-                                local_notify.notify_waiters();
-                                warn!(
-                                    target: "engine_orchestrator",
-                                    %err,
-                                    "failed to deliver local transcription update"
-                                );
+                            }
 
-                                let notice_message = if frame_index == 1 {
-                                    "本地解码延迟异常，已保留回退提示"
-                                } else {
-                                    "本地解码增量延迟，已保留回退提示"
-                                };
+                            first_emit = false;
+                        }
 
-                                let notice = TranscriptionUpdate {
-                                    payload: UpdatePayload::Notice(SessionNotice {
-                                        level: NoticeLevel::Warn,
-                                        message: notice_message.to_string(),
-                                    }),
-                                    latency: frame_started.elapsed(),
-                                    frame_index,
-                                    is_first: false,
-                                };
+                        send_partial_delta(&tx, partial, frame_started.elapsed(), frame_index)
+                            .await;
+                        submit_arbitration(arbiter.as_deref(), frame_index, local_candidate, &tx)
+                            .await;
 
-                                if let Err(err) = tx.send(notice).await {
-                                    warn!(
-                                        target: "engine_orchestrator",
-                                        %err,
-                                        "failed to deliver local backpressure notice"
-                                    );
-                                }
-                                return;
+                        if emitted {
+                            if was_first_local {
+                                let _ = first_local_flag.compare_exchange(
+                                    false,
+                                    true,
+                                    Ordering::SeqCst,
+                                    Ordering::SeqCst,
+                                );
                             }
+                            local_progress.record_success(frame_index, started_at);
+                            local_notify.notify_waiters();
                         }
-
-                        first_emit = false;
                     }
+                    Err(err) => {
+                        drop(guard);
+                        error!(
+                            target: "engine_orchestrator",
+                            %err,
+                            frame_index,
+                            "local speech engine failed to transcribe frame"
+                        );
 
-                    if emitted {
-                        if was_first_local {
-                            let _ = first_local_flag.compare_exchange(
-                                false,
-                                true,
-                                Ordering::SeqCst,
-                                Ordering::SeqCst,
-                            );
-                        }
-                        local_progress.record_success(frame_index, started_at);
+                        local_progress.mark_degraded(started_at);
                         local_notify.notify_waiters();
-                    }
-                }
-                Err(err) => {
-                    drop(guard);
-                    error!(
-                        target: "engine_orchestrator",
-                        %err,
-                        frame_index,
-                        "local speech engine failed to transcribe frame"
-                    );
 
-                    local_progress.mark_degraded(started_at);
-                    local_notify.notify_waiters();
+                        if let Some(failover) = failover
+                            .as_ref()
+                            .filter(|_| cloud_gate.allows(CloudFeature::Transcription))
+                        {
+                            failover
+                                .take_over(
+                                    vocabulary.as_deref(),
+                                    punctuation.as_ref(),
+                                    &sentences_store,
+                                    &tx,
+                                    frame_index,
+                                    frame_started,
+                                )
+                                .await;
+                            return;
+                        }
 
-                    let notice = TranscriptionUpdate {
-                        payload: UpdatePayload::Notice(SessionNotice {
-                            level: NoticeLevel::Error,
-                            message: "本地识别异常，已切换云端回退".to_string(),
-                        }),
-                        latency: frame_started.elapsed(),
-                        frame_index,
-                        is_first: false,
-                    };
+                        let notice = TranscriptionUpdate {
+                            payload: UpdatePayload::Notice(SessionNotice {
+                                level: NoticeLevel::Error,
+                                message: "本地识别异常，已切换云端回退".to_string(),
+                            }),
+                            latency: frame_started.elapsed(),
+                            frame_index,
+                            is_first: false,
+                        };
 
-                    if let Err(err) = tx.send(notice).await {
-                        warn!(
-                            target: "engine_orchestrator",
-                            %err,
-                            "failed to deliver local fallback notice"
-                        );
+                        if let Err(err) = tx.send(notice).await {
+                            warn!(
+                                target: "engine_orchestrator",
+                                %err,
+                                "failed to deliver local fallback notice"
+                            );
+                        }
                     }
                 }
             }
-        });
+            .in_current_span(),
+        );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn spawn_cloud_task(
         &self,
         frame: Arc<[f32]>,
         frame_index: usize,
         frame_started: Instant,
+        cloud_primary: bool,
         engine: Arc<dyn SpeechEngine>,
         cloud_state: Arc<CloudCircuit>,
     ) {
@@ -1393,164 +2379,225 @@ impl RealtimeWorker {
         let started_at = self.started_at;
         let prefer_cloud = self.prefer_cloud;
         let local_deadline = self.config.first_update_deadline;
-        let cadence = if self.config.max_frame_duration.is_zero() {
-            self.config.min_frame_duration
-        } else {
-            self.config
-                .max_frame_duration
-                .max(self.config.min_frame_duration)
-        };
+        let cadence = self.local_progress.cadence();
         let sentences_store = self.sentences.clone();
+        let vocabulary = self.vocabulary.clone();
+        let command_grammar = self.config.command_grammar.clone();
+        let punctuation = self.punctuation_stage();
+        let segment_language = self.segment_language();
+        let audio_source = self.config.audio_source;
+        let arbiter = self.arbiter.clone();
+        let failover = self.failover.clone();
+
+        tokio::spawn(
+            async move {
+                if let Some(failover) = &failover {
+                    failover.settled().await;
+                }
+                let mut timed_out = false;
 
-        tokio::spawn(async move {
-            let mut timed_out = false;
+                if !cloud_primary
+                    && local_progress.last_frame() < frame_index as u64
+                    && !local_progress.is_degraded()
+                {
+                    let gate_deadline = if frame_index == 1 {
+                        local_deadline
+                    } else {
+                        cadence
+                    };
 
-            if local_progress.last_frame() < frame_index as u64 && !local_progress.is_degraded() {
-                let gate_deadline = if frame_index == 1 {
-                    local_deadline
-                } else {
-                    cadence
-                };
+                    loop {
+                        if local_progress.last_frame() >= frame_index as u64
+                            || local_progress.is_degraded()
+                        {
+                            break;
+                        }
 
-                loop {
-                    if local_progress.last_frame() >= frame_index as u64
-                        || local_progress.is_degraded()
-                    {
-                        break;
-                    }
+                        let elapsed = frame_started.elapsed();
+                        if elapsed >= gate_deadline {
+                            timed_out = true;
+                            break;
+                        }
 
-                    let elapsed = frame_started.elapsed();
-                    if elapsed >= gate_deadline {
-                        timed_out = true;
-                        break;
+                        let remaining = gate_deadline - elapsed;
+                        let _ = timeout(remaining, local_notify.notified()).await;
                     }
-
-                    let remaining = gate_deadline - elapsed;
-                    let _ = timeout(remaining, local_notify.notified()).await;
                 }
-            }
 
-            if timed_out
-                && (!local_progress.has_speech_started() || !local_progress.is_speech_active())
-            {
-                timed_out = false;
-            }
+                if timed_out
+                    && (!local_progress.has_speech_started() || !local_progress.is_speech_active())
+                {
+                    timed_out = false;
+                }
 
-            if timed_out && !local_progress.is_degraded() {
-                local_progress.mark_degraded(started_at);
-                local_notify.notify_waiters();
+                if timed_out && !local_progress.is_degraded() {
+                    local_progress.mark_degraded(started_at);
+                    local_notify.notify_waiters();
 
-                let notice_message = if frame_index == 1 {
-                    "本地解码延迟异常，已保留回退提示"
-                } else {
-                    "本地解码增量延迟，已保留回退提示"
-                };
+                    let notice_message = if frame_index == 1 {
+                        "本地解码延迟异常，已保留回退提示"
+                    } else {
+                        "本地解码增量延迟，已保留回退提示"
+                    };
 
-                let notice = TranscriptionUpdate {
-                    payload: UpdatePayload::Notice(SessionNotice {
-                        level: NoticeLevel::Warn,
-                        message: notice_message.to_string(),
-                    }),
-                    latency: frame_started.elapsed(),
-                    frame_index,
-                    is_first: false,
-                };
+                    let notice = TranscriptionUpdate {
+                        payload: UpdatePayload::Notice(SessionNotice {
+                            level: NoticeLevel::Warn,
+                            message: notice_message.to_string(),
+                        }),
+                        latency: frame_started.elapsed(),
+                        frame_index,
+                        is_first: false,
+                    };
 
-                if let Err(err) = tx.send(notice).await {
-                    warn!(
-                        target: "engine_orchestrator",
-                        %err,
-                        "failed to deliver cadence fallback notice"
-                    );
+                    if let Err(err) = tx.send(notice).await {
+                        warn!(
+                            target: "engine_orchestrator",
+                            %err,
+                            "failed to deliver cadence fallback notice"
+                        );
+                    }
                 }
-            }
 
-            match engine.transcribe(frame.as_ref()).await {
-                Ok(text) if !text.is_empty() => {
-                    cloud_state.mark_success();
-                    let is_first = if prefer_cloud {
-                        if first_local_flag.load(Ordering::SeqCst) {
-                            !first_flag.swap(true, Ordering::SeqCst)
+                let transcribed = if arbiter.is_some() {
+                    transcribe_frame_scored(engine.as_ref(), frame.as_ref(), vocabulary.as_deref())
+                        .await
+                } else {
+                    transcribe_frame(engine.as_ref(), frame.as_ref(), vocabulary.as_deref())
+                        .await
+                        .map(|text| (text, Vec::new()))
+                };
+                match transcribed {
+                    // 语音指令由本地链路下发，云端结果不再作为正文输出。
+                    Ok((text, _))
+                        if command_grammar
+                            .as_ref()
+                            .is_some_and(|grammar| grammar.recognize(&text).is_some()) =>
+                    {
+                        cloud_state.mark_success();
+                    }
+                    Ok((text, tokens)) if !text.is_empty() => {
+                        cloud_state.mark_success();
+                        let text = match &punctuation {
+                            Some((restorer, language)) => restorer.restore(&text, language),
+                            None => text,
+                        };
+                        let is_first = if prefer_cloud {
+                            if first_local_flag.load(Ordering::SeqCst) {
+                                !first_flag.swap(true, Ordering::SeqCst)
+                            } else {
+                                let _ = first_flag.swap(true, Ordering::SeqCst);
+                                false
+                            }
                         } else {
-                            let _ = first_flag.swap(true, Ordering::SeqCst);
+                            first_flag.store(true, Ordering::SeqCst);
                             false
+                        };
+                        if is_first {
+                            metrics().first_update_latency.observe(started_at.elapsed());
                         }
-                    } else {
-                        first_flag.store(true, Ordering::SeqCst);
-                        false
-                    };
-                    let sentence_id = {
-                        let mut store = sentences_store.lock().await;
-                        store.register_raw_sentence(text.clone(), TranscriptSource::Cloud)
-                    };
-                    let latency = frame_started.elapsed();
-                    let is_primary = local_progress.is_degraded();
-                    let update = TranscriptionUpdate {
-                        payload: UpdatePayload::Transcript(TranscriptPayload {
-                            sentence_id,
-                            text,
+                        let sentence_id = {
+                            let mut store = sentences_store.lock().await;
+                            store.register_raw_sentence(
+                                text.clone(),
+                                TranscriptSource::Cloud,
+                                frame_index,
+                            )
+                        };
+                        let latency = frame_started.elapsed();
+                        let cloud_candidate = arbiter.as_ref().map(|_| EngineCandidate {
                             source: TranscriptSource::Cloud,
-                            is_primary,
-                            within_sla: true,
-                        }),
-                        latency,
-                        frame_index,
-                        is_first,
-                    };
-
-                    match tx.send(update).await {
-                        Ok(_) => {
-                            record_dual_view_latency(
+                            text: text.clone(),
+                            confidence: arbitration::mean_confidence(&tokens),
+                            latency,
+                            sentence_ids: vec![sentence_id],
+                        });
+                        let is_primary = cloud_primary || local_progress.is_degraded();
+                        let segments = segment_languages(&text, segment_language.as_deref());
+                        let update = TranscriptionUpdate {
+                            payload: UpdatePayload::Transcript(TranscriptPayload {
                                 sentence_id,
-                                variant_label(SentenceVariant::Raw),
-                                TranscriptSource::Cloud.as_str(),
+                                text,
+                                source: TranscriptSource::Cloud,
                                 is_primary,
-                                latency,
-                                true,
-                            );
-                        }
-                        Err(err) => {
-                            warn!(
-                                target: "engine_orchestrator",
-                                %err,
-                                "failed to deliver cloud transcription update"
-                            );
+                                within_sla: true,
+                                segments,
+                                translation: None,
+                                audio_source,
+                            }),
+                            latency,
+                            frame_index,
+                            is_first,
+                        };
+
+                        match tx.send(update).await {
+                            Ok(_) => {
+                                record_dual_view_latency(
+                                    sentence_id,
+                                    variant_label(SentenceVariant::Raw),
+                                    TranscriptSource::Cloud.as_str(),
+                                    is_primary,
+                                    latency,
+                                    true,
+                                );
+                            }
+                            Err(err) => {
+                                warn!(
+                                    target: "engine_orchestrator",
+                                    %err,
+                                    "failed to deliver cloud transcription update"
+                                );
+                            }
                         }
+                        submit_arbitration(arbiter.as_deref(), frame_index, cloud_candidate, &tx)
+                            .await;
                     }
-                }
-                Ok(_) => {}
-                Err(err) => {
-                    warn!(
-                        target: "engine_orchestrator",
-                        %err,
-                        frame_index,
-                        "cloud speech engine failed to transcribe frame"
-                    );
-
-                    let tripped = cloud_state.trip(started_at, Instant::now(), CLOUD_RETRY_BACKOFF);
-
-                    if tripped {
-                        let notice = TranscriptionUpdate {
-                            payload: UpdatePayload::Notice(SessionNotice {
-                                level: NoticeLevel::Warn,
-                                message: "云端识别异常，已回退至本地结果".to_string(),
-                            }),
+                    Ok(_) => {
+                        let cloud_candidate = arbiter.as_ref().map(|_| EngineCandidate {
+                            source: TranscriptSource::Cloud,
+                            text: String::new(),
+                            confidence: None,
                             latency: frame_started.elapsed(),
+                            sentence_ids: Vec::new(),
+                        });
+                        submit_arbitration(arbiter.as_deref(), frame_index, cloud_candidate, &tx)
+                            .await;
+                    }
+                    Err(err) => {
+                        warn!(
+                            target: "engine_orchestrator",
+                            %err,
                             frame_index,
-                            is_first: false,
-                        };
+                            "cloud speech engine failed to transcribe frame"
+                        );
 
-                        if let Err(err) = tx.send(notice).await {
-                            warn!(
-                                target: "engine_orchestrator",
-                                %err,
-                                "failed to deliver cloud fallback notice"
-                            );
+                        let tripped =
+                            cloud_state.trip(started_at, Instant::now(), CLOUD_RETRY_BACKOFF);
+
+                        if tripped {
+                            let notice = TranscriptionUpdate {
+                                payload: UpdatePayload::Notice(SessionNotice {
+                                    level: NoticeLevel::Warn,
+                                    message: "云端识别异常，已回退至本地结果".to_string(),
+                                }),
+                                latency: frame_started.elapsed(),
+                                frame_index,
+                                is_first: false,
+                            };
+
+                            if let Err(err) = tx.send(notice).await {
+                                warn!(
+                                    target: "engine_orchestrator",
+                                    %err,
+                                    "failed to deliver cloud fallback notice"
+                                );
+                            }
                         }
                     }
                 }
             }
-        });
+            .in_current_span(),
+        );
     }
 
     async fn handle_command(&self, command: TranscriptCommand) {
@@ -1603,6 +2650,11 @@ impl RealtimeWorker {
                     );
                 }
             }
+            TranscriptCommand::Retranscribe {
+                sentence_id,
+                engine,
+            } => self.spawn_retranscription(sentence_id, engine),
+            TranscriptCommand::RepolishLast => self.spawn_repolish_last(),
         }
     }
 }
@@ -1631,2125 +2683,9 @@ impl SpeechEngine for FallbackSpeechEngine {
 }
 
 #[cfg(feature = "local-asr")]
-mod whisper {
-    use super::*;
-    use anyhow::{anyhow, Context, Result as AnyhowResult};
-    use dirs::data_dir;
-    use std::fs::{self, File};
-    use std::io::{BufWriter, Write};
-    use std::mem::transmute;
-    use std::path::{Path, PathBuf};
-    use std::sync::{Arc, Mutex};
-    use tracing::{info, warn};
-    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperState};
-
-    const DEFAULT_MODEL_URL: &str =
-        "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin";
-    const DEFAULT_MODEL_FILENAME: &str = "ggml-base.en.bin";
-
-    pub struct WhisperLocalEngine {
-        _context: Arc<WhisperContext>,
-        streaming: Arc<Mutex<StreamingState>>,
-    }
-
-    impl WhisperLocalEngine {
-        pub fn from_env() -> Result<Self> {
-            let model_path = resolve_or_fetch_model()?;
-            Self::from_model_path(model_path)
-        }
-
-        pub fn from_model_path<P: AsRef<Path>>(path: P) -> Result<Self> {
-            let path_ref = path.as_ref();
-            let path_str = path_ref
-                .to_str()
-                .ok_or_else(|| anyhow!("模型路径不是有效的 UTF-8"))?;
-            let context = Arc::new(WhisperContext::new(path_str)?);
-            let state = unsafe {
-                transmute::<WhisperState<'_>, WhisperState<'static>>(context.create_state()?)
-            };
-            Ok(Self {
-                _context: Arc::clone(&context),
-                streaming: Arc::new(Mutex::new(StreamingState::new(state))),
-            })
-        }
-    }
-
-    fn resolve_or_fetch_model() -> AnyhowResult<PathBuf> {
-        if let Ok(path) = std::env::var("WHISPER_MODEL_PATH") {
-            let path_buf = PathBuf::from(path);
-            if path_buf.is_file() {
-                return Ok(path_buf);
-            }
-            return Err(anyhow!(
-                "WHISPER_MODEL_PATH points to a missing file: {}",
-                path_buf.display()
-            ));
-        }
-
-        if !auto_download_enabled() {
-            warn!(
-                target: "engine_orchestrator",
-                "WHISPER_MODEL_PATH missing and auto-download disabled"
-            );
-            return Err(anyhow!(
-                "WHISPER_MODEL_PATH is not set and auto-download is disabled"
-            ));
-        }
-
-        let cache_path = default_model_path()?;
-        if cache_path.is_file() {
-            std::env::set_var("WHISPER_MODEL_PATH", &cache_path);
-            return Ok(cache_path);
-        }
-
-        let url = std::env::var("WHISPER_MODEL_URL").unwrap_or_else(|_| DEFAULT_MODEL_URL.into());
-        download_model(&cache_path, &url)?;
-        std::env::set_var("WHISPER_MODEL_PATH", &cache_path);
-        Ok(cache_path)
-    }
-
-    fn default_model_path() -> AnyhowResult<PathBuf> {
-        if let Ok(dir) = std::env::var("FLOWWISPER_MODEL_DIR") {
-            let path = PathBuf::from(dir).join(DEFAULT_MODEL_FILENAME);
-            ensure_parent_dir(&path)?;
-            return Ok(path);
-        }
-
-        let base_dir = data_dir()
-            .map(|dir| dir.join("Flowwisper").join("models"))
-            .ok_or_else(|| anyhow!("failed to determine default model cache directory"))?;
-        let path = base_dir.join(DEFAULT_MODEL_FILENAME);
-        ensure_parent_dir(&path)?;
-        Ok(path)
-    }
-
-    fn ensure_parent_dir(path: &Path) -> AnyhowResult<()> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).with_context(|| {
-                format!(
-                    "failed to create whisper model directory: {}",
-                    parent.display()
-                )
-            })?;
-        }
-        Ok(())
-    }
-
-    fn download_model(path: &Path, url: &str) -> AnyhowResult<()> {
-        info!(
-            target: "engine_orchestrator",
-            %url,
-            path = %path.display(),
-            "downloading whisper model"
-        );
-
-        let temp_path = path.with_extension("download");
-        let response = ureq::get(url)
-            .call()
-            .map_err(|err| anyhow!("failed to download whisper model: {err}"))?;
-
-        if !(200..300).contains(&response.status()) {
-            return Err(anyhow!(
-                "failed to download whisper model: received HTTP status {}",
-                response.status()
-            ));
-        }
-
-        let mut reader = response.into_reader();
-        let file = File::create(&temp_path).with_context(|| {
-            format!(
-                "failed to create temporary whisper model file: {}",
-                temp_path.display()
-            )
-        })?;
-        let mut writer = BufWriter::new(file);
-
-        std::io::copy(&mut reader, &mut writer)
-            .with_context(|| format!("failed to write whisper model to {}", temp_path.display()))?;
-        writer
-            .flush()
-            .context("failed to flush whisper model to disk")?;
-
-        fs::rename(&temp_path, path).with_context(|| {
-            format!(
-                "failed to finalize whisper model download to {}",
-                path.display()
-            )
-        })?;
-
-        info!(
-            target: "engine_orchestrator",
-            path = %path.display(),
-            "whisper model ready"
-        );
-
-        Ok(())
-    }
-
-    fn auto_download_enabled() -> bool {
-        if let Ok(value) = std::env::var("WHISPER_DISABLE_AUTO_DOWNLOAD") {
-            return !matches!(
-                value.trim().to_ascii_lowercase().as_str(),
-                "1" | "true" | "yes" | "on"
-            );
-        }
-
-        if let Ok(value) = std::env::var("WHISPER_AUTO_DOWNLOAD") {
-            return matches!(
-                value.trim().to_ascii_lowercase().as_str(),
-                "1" | "true" | "yes" | "on"
-            );
-        }
-
-        true
-    }
-
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use std::io::{Read, Write};
-        use std::net::TcpListener;
-        use std::sync::{Mutex, OnceLock};
-        use std::thread;
-        use tempfile::tempdir;
-
-        fn env_guard() -> &'static Mutex<()> {
-            static GUARD: OnceLock<Mutex<()>> = OnceLock::new();
-            GUARD.get_or_init(|| Mutex::new(()))
-        }
-
-        fn reset_env() {
-            std::env::remove_var("FLOWWISPER_MODEL_DIR");
-            std::env::remove_var("WHISPER_MODEL_PATH");
-            std::env::remove_var("WHISPER_MODEL_URL");
-            std::env::remove_var("WHISPER_AUTO_DOWNLOAD");
-            std::env::remove_var("WHISPER_DISABLE_AUTO_DOWNLOAD");
-        }
-
-        #[test]
-        fn downloads_model_when_missing() {
-            let _lock = env_guard().lock().expect("env guard poisoned");
-            reset_env();
-
-            let directory = tempdir().expect("tempdir should create model cache");
-            std::env::set_var("FLOWWISPER_MODEL_DIR", directory.path());
-
-            let payload = b"fake-whisper-model".to_vec();
-            let expected = payload.clone();
-
-            let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind ephemeral port");
-            let address = listener.local_addr().expect("local addr available");
-            let handle = thread::spawn(move || {
-                if let Ok((mut stream, _)) = listener.accept() {
-                    let mut buffer = [0_u8; 512];
-                    let _ = stream.read(&mut buffer);
-                    let response = format!(
-                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
-                        payload.len()
-                    );
-                    stream
-                        .write_all(response.as_bytes())
-                        .expect("response headers written");
-                    stream.write_all(&payload).expect("response body written");
-                }
-            });
-
-            std::env::set_var(
-                "WHISPER_MODEL_URL",
-                format!("http://{address}/{}", DEFAULT_MODEL_FILENAME),
-            );
-
-            let model_path = resolve_or_fetch_model().expect("model should download");
-            handle.join().expect("http server thread joined");
-
-            assert_eq!(std::fs::read(&model_path).expect("read model"), expected);
-            assert_eq!(
-                std::env::var("WHISPER_MODEL_PATH").expect("model path exported"),
-                model_path.to_string_lossy()
-            );
-
-            reset_env();
-        }
-
-        #[test]
-        fn reuses_cached_model_when_present() {
-            let _lock = env_guard().lock().expect("env guard poisoned");
-            reset_env();
-
-            let directory = tempdir().expect("tempdir should create model cache");
-            let cached_path = directory.path().join(DEFAULT_MODEL_FILENAME);
-            std::fs::write(&cached_path, b"existing-model").expect("write cached whisper model");
-
-            std::env::set_var("FLOWWISPER_MODEL_DIR", directory.path());
-            std::env::set_var("WHISPER_MODEL_URL", "http://127.0.0.1:1/never-used");
-
-            let model_path = resolve_or_fetch_model().expect("should reuse cached model");
-
-            assert_eq!(model_path, cached_path);
-            assert_eq!(
-                std::fs::read(&model_path).expect("read cached model"),
-                b"existing-model"
-            );
-            assert_eq!(
-                std::env::var("WHISPER_MODEL_PATH").expect("model path exported"),
-                model_path.to_string_lossy()
-            );
-
-            reset_env();
-        }
-    }
-
-    struct StreamingState {
-        state: WhisperState<'static>,
-        tail: Vec<f32>,
-        pending: Vec<f32>,
-        emitted: String,
-        lookback_samples: usize,
-        sample_rate: usize,
-        min_stride_samples: usize,
-        max_stride_samples: usize,
-    }
-
-    impl StreamingState {
-        fn new(state: WhisperState<'static>) -> Self {
-            const SAMPLE_RATE: usize = 16_000;
-            const LOOKBACK_MS: usize = 240;
-            const MIN_STRIDE_MS: usize = 80;
-            const MAX_STRIDE_MS: usize = 200;
-            let lookback_samples = (SAMPLE_RATE * LOOKBACK_MS + 999) / 1_000;
-            let min_stride_samples = (SAMPLE_RATE * MIN_STRIDE_MS + 999) / 1_000;
-            let max_stride_samples = (SAMPLE_RATE * MAX_STRIDE_MS + 999) / 1_000;
-            Self {
-                state,
-                tail: Vec::with_capacity(lookback_samples),
-                pending: Vec::with_capacity(max_stride_samples),
-                emitted: String::new(),
-                lookback_samples,
-                sample_rate: SAMPLE_RATE,
-                min_stride_samples,
-                max_stride_samples,
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SpeechEngine for WhisperLocalEngine {
-        async fn transcribe(&self, frame: &[f32]) -> Result<String> {
-            if frame.is_empty() {
-                return Ok(String::new());
-            }
-
-            let pcm: Vec<f32> = frame.to_vec();
-            let speechy = frame_rms(frame) >= SPEECH_RMS_THRESHOLD;
-            let streaming = Arc::clone(&self.streaming);
-
-            tokio::task::spawn_blocking(move || {
-                let mut guard = streaming
-                    .lock()
-                    .expect("whisper streaming state lock poisoned");
-
-                guard.pending.extend_from_slice(&pcm);
-
-                let should_decode = if speechy {
-                    guard.pending.len() >= guard.min_stride_samples
-                } else {
-                    guard.pending.len() >= guard.max_stride_samples
-                };
-
-                if !should_decode {
-                    return Ok(String::new());
-                }
-
-                let mut decode_window = Vec::with_capacity(guard.tail.len() + guard.pending.len());
-                decode_window.extend_from_slice(&guard.tail);
-                decode_window.extend_from_slice(&guard.pending);
-
-                if decode_window.is_empty() {
-                    return Ok(String::new());
-                }
-
-                let mut params = FullParams::new(SamplingStrategy::default());
-                params.set_translate(false);
-                params.set_single_segment(true);
-                params.set_temperature(0.0);
-                params.set_no_context(false);
-                params.set_print_realtime(false);
-                params.set_print_progress(false);
-
-                let duration_ms = ((decode_window.len() * 1_000) / guard.sample_rate).max(1) as i32;
-                params.set_duration_ms(duration_ms);
-
-                guard.state.full(params, &decode_window)?;
-                guard.pending.clear();
-
-                let tail_len = guard.lookback_samples.min(decode_window.len());
-                guard.tail = decode_window[decode_window.len() - tail_len..].to_vec();
-
-                let mut transcript = String::new();
-                let segments = guard.state.full_n_segments()? as usize;
-                for segment in 0..segments {
-                    let text = guard.state.full_get_segment_text(segment as i32)?;
-                    let trimmed = text.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-                    if !transcript.is_empty() {
-                        transcript.push(' ');
-                    }
-                    transcript.push_str(trimmed);
-                }
-
-                let transcript = transcript.trim().to_string();
-                if transcript.is_empty() {
-                    return Ok(String::new());
-                }
-
-                let overlap = suffix_prefix_overlap(&guard.emitted, &transcript);
-                let mut delta = transcript[overlap..].trim_start().to_string();
-
-                if delta.is_empty() && guard.emitted.contains(&transcript) {
-                    return Ok(String::new());
-                }
-
-                if !delta.is_empty() {
-                    if !guard.emitted.is_empty()
-                        && !guard.emitted.ends_with(' ')
-                        && !delta.starts_with(' ')
-                    {
-                        guard.emitted.push(' ');
-                    }
-                    guard.emitted.push_str(&delta);
-                } else {
-                    guard.emitted = transcript.clone();
-                    delta = transcript;
-                }
-
-                Ok(delta)
-            })
-            .await?
-        }
-    }
-}
-
-#[cfg(feature = "local-asr")]
-use whisper::WhisperLocalEngine;
+mod whisper;
+#[cfg(feature = "local-asr")]
+use whisper::WhisperLocalEngine;
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use anyhow::{anyhow, Result};
-    use std::collections::VecDeque;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::{Mutex, OnceLock};
-    use tokio::time::timeout;
-
-    fn env_guard() -> &'static Mutex<()> {
-        static GUARD: OnceLock<Mutex<()>> = OnceLock::new();
-        GUARD.get_or_init(|| Mutex::new(()))
-    }
-
-    #[tokio::test]
-    async fn lightweight_polisher_applies_light_edits() {
-        let polisher = LightweightSentencePolisher::default();
-        let polished = polisher
-            .polish("  uh i think i'm heading over around two  ")
-            .await
-            .expect("polish succeeds");
-        assert_eq!(polished, "I think I'm heading over around two.");
-    }
-
-    struct MockSpeechEngine {
-        segments: Mutex<VecDeque<String>>,
-        delay: Duration,
-    }
-
-    impl MockSpeechEngine {
-        fn new(segments: Vec<&str>, delay: Duration) -> Self {
-            Self {
-                segments: Mutex::new(segments.into_iter().map(String::from).collect()),
-                delay,
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SpeechEngine for MockSpeechEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
-            sleep(self.delay).await;
-            Ok(self
-                .segments
-                .lock()
-                .expect("segments lock poisoned")
-                .pop_front()
-                .unwrap_or_default())
-        }
-    }
-
-    struct WindowSpeechEngine {
-        segments: Mutex<VecDeque<&'static str>>,
-        delay: Duration,
-    }
-
-    impl WindowSpeechEngine {
-        fn new(segments: Vec<&'static str>, delay: Duration) -> Self {
-            Self {
-                segments: Mutex::new(segments.into_iter().collect()),
-                delay,
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SpeechEngine for WindowSpeechEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
-            sleep(self.delay).await;
-            Ok(self
-                .segments
-                .lock()
-                .expect("segments lock poisoned")
-                .pop_front()
-                .unwrap_or_default()
-                .to_string())
-        }
-    }
-
-    struct SequencedSpeechEngine {
-        segments: Mutex<VecDeque<(&'static str, Duration)>>,
-    }
-
-    impl SequencedSpeechEngine {
-        fn new(segments: Vec<(&'static str, Duration)>) -> Self {
-            Self {
-                segments: Mutex::new(segments.into_iter().collect()),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SpeechEngine for SequencedSpeechEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
-            let next = {
-                let mut guard = self
-                    .segments
-                    .lock()
-                    .expect("sequenced segments lock poisoned");
-                guard.pop_front()
-            };
-
-            if let Some((text, delay)) = next {
-                sleep(delay).await;
-                Ok(text.to_string())
-            } else {
-                Ok(String::new())
-            }
-        }
-    }
-
-    struct SequencedPolisher {
-        outputs: Mutex<VecDeque<(&'static str, Duration)>>,
-    }
-
-    impl SequencedPolisher {
-        fn new(outputs: Vec<(&'static str, Duration)>) -> Self {
-            Self {
-                outputs: Mutex::new(outputs.into_iter().collect()),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SentencePolisher for SequencedPolisher {
-        async fn polish(&self, sentence: &str) -> Result<String> {
-            let next = {
-                let mut guard = self
-                    .outputs
-                    .lock()
-                    .expect("sequenced polisher lock poisoned");
-                guard.pop_front()
-            };
-
-            if let Some((text, delay)) = next {
-                if !delay.is_zero() {
-                    sleep(delay).await;
-                }
-                Ok(text.to_string())
-            } else {
-                Ok(sentence.to_string())
-            }
-        }
-    }
-
-    struct SlowPolisher {
-        delay: Duration,
-    }
-
-    #[async_trait]
-    impl SentencePolisher for SlowPolisher {
-        async fn polish(&self, sentence: &str) -> Result<String> {
-            sleep(self.delay).await;
-            Ok(sentence.to_string())
-        }
-    }
-
-    struct FailingPolisher;
-
-    #[async_trait]
-    impl SentencePolisher for FailingPolisher {
-        async fn polish(&self, _sentence: &str) -> Result<String> {
-            Err(anyhow!("polish failed"))
-        }
-    }
-
-    struct SlowSecondLocalEngine {
-        calls: AtomicUsize,
-    }
-
-    impl SlowSecondLocalEngine {
-        fn new() -> Self {
-            Self {
-                calls: AtomicUsize::new(0),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SpeechEngine for SlowSecondLocalEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
-            let call = self.calls.fetch_add(1, Ordering::SeqCst);
-            if call == 0 {
-                sleep(Duration::from_millis(40)).await;
-                Ok("local-fast.".to_string())
-            } else {
-                sleep(Duration::from_millis(480)).await;
-                Ok("local-slow.".to_string())
-            }
-        }
-    }
-
-    #[test]
-    fn fails_when_whisper_env_missing_without_fallback() {
-        let _lock = env_guard().lock().expect("env guard poisoned");
-        std::env::remove_var("WHISPER_MODEL_PATH");
-        std::env::remove_var("WHISPER_ALLOW_FALLBACK");
-        std::env::set_var("WHISPER_DISABLE_AUTO_DOWNLOAD", "1");
-
-        let result = EngineOrchestrator::new(EngineConfig {
-            prefer_cloud: false,
-        });
-
-        assert!(
-            result.is_err(),
-            "expected whisper init failure without fallback"
-        );
-        std::env::remove_var("WHISPER_DISABLE_AUTO_DOWNLOAD");
-    }
-
-    #[test]
-    fn allows_fallback_when_explicitly_opted_in() {
-        let _lock = env_guard().lock().expect("env guard poisoned");
-        std::env::remove_var("WHISPER_MODEL_PATH");
-        std::env::set_var("WHISPER_ALLOW_FALLBACK", "1");
-        std::env::set_var("WHISPER_DISABLE_AUTO_DOWNLOAD", "1");
-
-        let orchestrator = EngineOrchestrator::new(EngineConfig {
-            prefer_cloud: false,
-        })
-        .expect("fallback should be allowed when explicitly opted in");
-
-        std::env::remove_var("WHISPER_ALLOW_FALLBACK");
-        std::env::remove_var("WHISPER_DISABLE_AUTO_DOWNLOAD");
-        drop(orchestrator);
-    }
-
-    #[tokio::test]
-    async fn emits_first_transcription_within_deadline() {
-        let engine = Arc::new(MockSpeechEngine::new(
-            vec!["hello.", "world."],
-            Duration::from_millis(50),
-        ));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            engine,
-        );
-
-        let (session, mut rx) =
-            orchestrator.start_realtime_session(RealtimeSessionConfig::default());
-
-        // 100ms frame at 16kHz sample rate.
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        let update = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("transcription timed out")
-            .expect("channel closed unexpectedly");
-
-        let payload = match update.payload {
-            UpdatePayload::Transcript(payload) => payload,
-            _ => panic!("expected transcript payload"),
-        };
-        assert_eq!(payload.text, "hello.");
-        assert_eq!(payload.source, TranscriptSource::Local);
-        assert!(payload.is_primary);
-        assert!(payload.within_sla);
-        assert!(payload.sentence_id > 0);
-        let sentence_id = payload.sentence_id;
-        assert!(update.is_first);
-        assert_eq!(update.frame_index, 1);
-        assert!(update.latency <= Duration::from_millis(400));
-
-        let polished = timeout(Duration::from_millis(500), rx.recv())
-            .await
-            .expect("polished transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match polished.payload {
-            UpdatePayload::Transcript(polished_payload) => {
-                assert_eq!(polished_payload.text, "Hello.");
-                assert_eq!(polished_payload.source, TranscriptSource::Polished);
-                assert!(polished_payload.is_primary);
-                assert!(polished_payload.within_sla);
-                assert_eq!(polished_payload.sentence_id, sentence_id);
-            }
-            _ => panic!("expected polished transcript payload"),
-        }
-        assert!(!polished.is_first);
-        assert!(polished.latency <= Duration::from_millis(500));
-    }
-
-    #[tokio::test]
-    async fn polished_transcript_marks_deadline_breach() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["hello."],
-            Duration::from_millis(20),
-        ));
-        let polisher: Arc<dyn SentencePolisher> = Arc::new(SlowPolisher {
-            delay: Duration::from_millis(150),
-        });
-
-        let orchestrator = EngineOrchestrator::with_components(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            None,
-            polisher,
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.polish_emit_deadline = Duration::from_millis(50);
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        // Drain the raw transcript first.
-        let _ = timeout(Duration::from_millis(200), rx.recv())
-            .await
-            .expect("raw transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        let polished = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("polished transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match polished.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.source, TranscriptSource::Polished);
-                assert_eq!(payload.text, "hello.");
-                assert!(!payload.within_sla);
-            }
-            _ => panic!("expected polished transcript payload"),
-        }
-        assert!(polished.latency >= Duration::from_millis(150));
-        assert!(!polished.is_first);
-    }
-
-    #[tokio::test]
-    async fn polisher_failure_emits_notice() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["hi."],
-            Duration::from_millis(20),
-        ));
-        let polisher: Arc<dyn SentencePolisher> = Arc::new(FailingPolisher);
-
-        let orchestrator = EngineOrchestrator::with_components(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            None,
-            polisher,
-        );
-
-        let (session, mut rx) =
-            orchestrator.start_realtime_session(RealtimeSessionConfig::default());
-
-        let frame = vec![0.4_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        // Drain the raw transcript.
-        let _ = timeout(Duration::from_millis(200), rx.recv())
-            .await
-            .expect("raw transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        let notice = timeout(Duration::from_millis(300), rx.recv())
-            .await
-            .expect("polisher notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Error);
-                assert!(session_notice.message.contains("润色生成失败"));
-            }
-            _ => panic!("expected polishing failure notice"),
-        }
-    }
-
-    #[tokio::test]
-    async fn emits_selection_updates_on_revert_commands() {
-        let engine = Arc::new(MockSpeechEngine::new(
-            vec!["hello."],
-            Duration::from_millis(40),
-        ));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            engine,
-        );
-
-        let (session, mut rx) =
-            orchestrator.start_realtime_session(RealtimeSessionConfig::default());
-
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        let local = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        let sentence_id = match local.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(payload.is_primary);
-                assert!(payload.sentence_id > 0);
-                payload.sentence_id
-            }
-            other => panic!("expected local transcript, got {other:?}"),
-        };
-
-        let polished = timeout(Duration::from_millis(700), rx.recv())
-            .await
-            .expect("polished transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match polished.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.source, TranscriptSource::Polished);
-                assert_eq!(payload.sentence_id, sentence_id);
-            }
-            other => panic!("expected polished transcript, got {other:?}"),
-        }
-
-        session
-            .apply_sentence_selections(vec![SentenceSelection {
-                sentence_id,
-                active_variant: SentenceVariant::Raw,
-            }])
-            .await
-            .expect("revert command should be delivered");
-
-        let selection = timeout(Duration::from_millis(500), rx.recv())
-            .await
-            .expect("selection update timed out")
-            .expect("channel closed unexpectedly");
-
-        match selection.payload {
-            UpdatePayload::Selection(payload) => {
-                assert_eq!(payload.selections.len(), 1);
-                assert_eq!(payload.selections[0].sentence_id, sentence_id);
-                assert_eq!(payload.selections[0].active_variant, SentenceVariant::Raw);
-            }
-            other => panic!("expected selection update, got {other:?}"),
-        }
-
-        session
-            .apply_sentence_selections(vec![SentenceSelection {
-                sentence_id,
-                active_variant: SentenceVariant::Polished,
-            }])
-            .await
-            .expect("restore command should be delivered");
-
-        let restore = timeout(Duration::from_millis(500), rx.recv())
-            .await
-            .expect("restore update timed out")
-            .expect("channel closed unexpectedly");
-
-        match restore.payload {
-            UpdatePayload::Selection(payload) => {
-                assert_eq!(payload.selections.len(), 1);
-                assert_eq!(payload.selections[0].sentence_id, sentence_id);
-                assert_eq!(
-                    payload.selections[0].active_variant,
-                    SentenceVariant::Polished
-                );
-            }
-            other => panic!("expected selection update, got {other:?}"),
-        }
-    }
-
-    #[tokio::test]
-    async fn acknowledges_multi_sentence_revert_commands() {
-        let local_engine = Arc::new(SequencedSpeechEngine::new(vec![
-            ("first.", Duration::from_millis(35)),
-            ("second.", Duration::from_millis(35)),
-        ]));
-        let polisher: Arc<dyn SentencePolisher> = Arc::new(SequencedPolisher::new(vec![
-            ("First polished.", Duration::from_millis(25)),
-            ("Second polished.", Duration::from_millis(25)),
-        ]));
-
-        let orchestrator = EngineOrchestrator::with_components(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            None,
-            polisher,
-        );
-
-        let (session, mut rx) =
-            orchestrator.start_realtime_session(RealtimeSessionConfig::default());
-
-        let frame = vec![0.4_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-        session
-            .push_frame(frame)
-            .await
-            .expect("second frame should enqueue");
-
-        let mut sentence_ids = Vec::new();
-        let mut polished_count = 0_u32;
-
-        while sentence_ids.len() < 2 || polished_count < 2 {
-            let update = timeout(Duration::from_millis(800), rx.recv())
-                .await
-                .expect("update timed out")
-                .expect("channel closed unexpectedly");
-
-            match update.payload {
-                UpdatePayload::Transcript(payload) => match payload.source {
-                    TranscriptSource::Local => {
-                        sentence_ids.push(payload.sentence_id);
-                    }
-                    TranscriptSource::Polished => {
-                        polished_count += 1;
-                    }
-                    TranscriptSource::Cloud => {}
-                },
-                UpdatePayload::Notice(_) => {}
-                UpdatePayload::Selection(_) => {
-                    panic!("unexpected selection payload before revert command");
-                }
-            }
-        }
-
-        let selections: Vec<SentenceSelection> = sentence_ids
-            .iter()
-            .map(|&sentence_id| SentenceSelection {
-                sentence_id,
-                active_variant: SentenceVariant::Raw,
-            })
-            .collect();
-
-        session
-            .apply_sentence_selections(selections.clone())
-            .await
-            .expect("revert command should be delivered");
-
-        let acknowledgement = timeout(Duration::from_millis(500), rx.recv())
-            .await
-            .expect("selection acknowledgement timed out")
-            .expect("channel closed unexpectedly");
-
-        match acknowledgement.payload {
-            UpdatePayload::Selection(payload) => {
-                assert_eq!(payload.selections.len(), selections.len());
-                for (expected, actual) in selections.iter().zip(payload.selections.iter()) {
-                    assert_eq!(expected.sentence_id, actual.sentence_id);
-                    assert_eq!(expected.active_variant, actual.active_variant);
-                }
-            }
-            other => panic!("expected selection acknowledgement, got {other:?}"),
-        }
-        assert_eq!(acknowledgement.latency, Duration::from_millis(0));
-        assert!(!acknowledgement.is_first);
-    }
-
-    #[tokio::test]
-    async fn ignores_unknown_sentence_selection() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["only."],
-            Duration::from_millis(35),
-        ));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        session
-            .push_frame(vec![0.4_f32; 1_600])
-            .await
-            .expect("frame should enqueue");
-
-        let update = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        let sentence_id = match update.payload {
-            UpdatePayload::Transcript(payload) => payload.sentence_id,
-            other => panic!("expected transcript payload, got {other:?}"),
-        };
-
-        session
-            .apply_sentence_selections(vec![SentenceSelection {
-                sentence_id: sentence_id + 42,
-                active_variant: SentenceVariant::Polished,
-            }])
-            .await
-            .expect("selection command should be accepted");
-
-        match timeout(Duration::from_millis(250), rx.recv()).await {
-            Err(_) => {}
-            Ok(Some(update)) => match update.payload {
-                UpdatePayload::Selection(payload) => {
-                    panic!("unexpected selection acknowledgement: {payload:?}")
-                }
-                _ => {}
-            },
-            Ok(None) => panic!("update channel closed unexpectedly"),
-        }
-    }
-
-    struct FailingSpeechEngine;
-
-    #[async_trait]
-    impl SpeechEngine for FailingSpeechEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
-            sleep(Duration::from_millis(20)).await;
-            Err(anyhow!("cloud unavailable"))
-        }
-    }
-
-    #[tokio::test]
-    async fn emits_deadline_notice_when_local_is_late() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-slow."],
-            Duration::from_millis(600),
-        ));
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-fast."],
-            Duration::from_millis(40),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.3_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("frame should enqueue");
-
-        let notice = timeout(Duration::from_millis(450), rx.recv())
-            .await
-            .expect("deadline notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("本地解码"));
-            }
-            _ => panic!("expected deadline notice"),
-        }
-
-        let (cloud_payload, cloud_is_first) = loop {
-            let update = timeout(Duration::from_millis(700), rx.recv())
-                .await
-                .expect("cloud transcript timed out")
-                .expect("channel closed unexpectedly");
-
-            match update.payload {
-                UpdatePayload::Transcript(payload) => break (payload, update.is_first),
-                UpdatePayload::Notice(session_notice) => {
-                    assert_eq!(session_notice.level, NoticeLevel::Warn);
-                    assert!(session_notice.message.contains("本地解码"));
-                }
-                UpdatePayload::Selection(_) => {
-                    panic!("unexpected selection update while waiting for cloud transcript");
-                }
-            }
-        };
-
-        assert_eq!(cloud_payload.text, "cloud-fast.");
-        assert_eq!(cloud_payload.source, TranscriptSource::Cloud);
-        assert!(cloud_payload.is_primary);
-        assert!(!cloud_is_first);
-
-        let local = timeout(Duration::from_millis(1_100), rx.recv())
-            .await
-            .expect("local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match local.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-slow.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(!payload.is_primary);
-                assert!(!local.is_first);
-            }
-            UpdatePayload::Notice(_) => panic!("expected local transcript"),
-            UpdatePayload::Selection(_) => {
-                panic!("unexpected selection update for local transcript")
-            }
-        }
-    }
-
-    #[tokio::test]
-    async fn deadline_notice_latency_reflects_monitor() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-slow."],
-            Duration::from_millis(650),
-        ));
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-fast."],
-            Duration::from_millis(40),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        config.first_update_deadline = Duration::from_millis(420);
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        session
-            .push_frame(vec![0.4_f32; 1_600])
-            .await
-            .expect("frame should enqueue");
-
-        let notice = timeout(Duration::from_millis(520), rx.recv())
-            .await
-            .expect("deadline notice timed out")
-            .expect("channel closed unexpectedly");
-
-        assert!(notice.latency >= Duration::from_millis(380));
-        assert!(notice.latency <= Duration::from_millis(520));
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("本地解码"));
-            }
-            _ => panic!("expected deadline notice"),
-        }
-
-        drop(session);
-    }
-
-    #[tokio::test]
-    async fn local_recovers_primary_after_cloud_fallback() {
-        let local_engine = Arc::new(SequencedSpeechEngine::new(vec![
-            ("local-slow.", Duration::from_millis(650)),
-            ("local-recovered.", Duration::from_millis(60)),
-        ]));
-        let cloud_engine = Arc::new(SequencedSpeechEngine::new(vec![
-            ("cloud-fallback.", Duration::from_millis(40)),
-            ("cloud-secondary.", Duration::from_millis(120)),
-        ]));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.35_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-
-        let notice = timeout(Duration::from_millis(500), rx.recv())
-            .await
-            .expect("deadline notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("本地解码"));
-            }
-            other => panic!("expected degradation notice, got {other:?}"),
-        }
-
-        let cloud_fallback = loop {
-            let update = timeout(Duration::from_millis(700), rx.recv())
-                .await
-                .expect("cloud fallback timed out")
-                .expect("channel closed unexpectedly");
-
-            match update.payload {
-                UpdatePayload::Transcript(payload) if payload.source == TranscriptSource::Cloud => {
-                    break payload
-                }
-                UpdatePayload::Notice(_) => continue,
-                UpdatePayload::Transcript(_) => continue,
-                UpdatePayload::Selection(_) => {
-                    panic!("unexpected selection before fallback transcript")
-                }
-            }
-        };
-
-        assert_eq!(cloud_fallback.text, "cloud-fallback.");
-        assert!(cloud_fallback.is_primary);
-
-        let delayed_local = loop {
-            let update = timeout(Duration::from_millis(1_100), rx.recv())
-                .await
-                .expect("delayed local timed out")
-                .expect("channel closed unexpectedly");
-
-            match update.payload {
-                UpdatePayload::Transcript(payload) if payload.source == TranscriptSource::Local => {
-                    break payload
-                }
-                UpdatePayload::Notice(_) => continue,
-                UpdatePayload::Selection(_) => {
-                    panic!("unexpected selection before local recovery")
-                }
-                UpdatePayload::Transcript(_) => continue,
-            }
-        };
-
-        assert_eq!(delayed_local.text, "local-slow.");
-        assert!(!delayed_local.is_primary);
-
-        session
-            .push_frame(frame)
-            .await
-            .expect("second frame should enqueue");
-
-        let recovered = loop {
-            let update = timeout(Duration::from_millis(500), rx.recv())
-                .await
-                .expect("recovered local timed out")
-                .expect("channel closed unexpectedly");
-
-            match update.payload {
-                UpdatePayload::Transcript(payload)
-                    if payload.source == TranscriptSource::Local && update.frame_index == 2 =>
-                {
-                    break payload
-                }
-                UpdatePayload::Notice(_) => continue,
-                UpdatePayload::Selection(_) => {
-                    panic!("unexpected selection during recovery")
-                }
-                UpdatePayload::Transcript(_) => continue,
-            }
-        };
-
-        assert_eq!(recovered.text, "local-recovered.");
-        assert!(recovered.is_primary);
-
-        let trailing_cloud = loop {
-            let update = timeout(Duration::from_millis(800), rx.recv())
-                .await
-                .expect("cloud secondary timed out")
-                .expect("channel closed unexpectedly");
-
-            match update.payload {
-                UpdatePayload::Transcript(payload) if payload.source == TranscriptSource::Cloud => {
-                    break payload
-                }
-                UpdatePayload::Notice(_) => continue,
-                UpdatePayload::Selection(_) => {
-                    panic!("unexpected selection while waiting for trailing cloud")
-                }
-                UpdatePayload::Transcript(_) => continue,
-            }
-        };
-
-        assert_eq!(trailing_cloud.text, "cloud-secondary.");
-        assert!(!trailing_cloud.is_primary);
-    }
-
-    #[tokio::test]
-    async fn silence_does_not_trigger_cadence_notice() {
-        let engine = Arc::new(MockSpeechEngine::new(
-            vec!["hello."],
-            Duration::from_millis(40),
-        ));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            engine,
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let speech_frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(speech_frame)
-            .await
-            .expect("speech frame should enqueue");
-
-        let update = timeout(Duration::from_millis(200), rx.recv())
-            .await
-            .expect("local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match update.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "hello.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected transcript payload"),
-        }
-
-        for _ in 0..5 {
-            session
-                .push_frame(vec![0.0_f32; 1_600])
-                .await
-                .expect("silent frame should enqueue");
-        }
-
-        assert!(
-            timeout(Duration::from_millis(350), rx.recv())
-                .await
-                .is_err(),
-            "silence triggered unexpected cadence notice",
-        );
-    }
-
-    #[tokio::test]
-    async fn emits_notice_when_local_engine_fails() {
-        let local_engine = Arc::new(FailingSpeechEngine);
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-ok."],
-            Duration::from_millis(40),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.3_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        let notice = timeout(Duration::from_millis(300), rx.recv())
-            .await
-            .expect("local failure notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Error);
-                assert!(session_notice.message.contains("本地识别异常"));
-            }
-            _ => panic!("expected local failure notice"),
-        }
-
-        let cloud = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("cloud fallback timed out")
-            .expect("channel closed unexpectedly");
-
-        match cloud.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-ok.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(payload.is_primary);
-                assert!(!cloud.is_first);
-            }
-            _ => panic!("expected cloud transcript"),
-        }
-
-        let deadline_notice = timeout(Duration::from_millis(900), rx.recv())
-            .await
-            .expect("deadline notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match deadline_notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("本地解码"));
-            }
-            _ => panic!("expected deadline degradation notice"),
-        }
-    }
-
-    #[tokio::test]
-    async fn cloud_waits_for_local_each_frame() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-one.", "local-two."],
-            Duration::from_millis(120),
-        ));
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-one.", "cloud-two."],
-            Duration::from_millis(30),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.4_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-
-        let first = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("first local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match first.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-one.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(first.is_first);
-            }
-            _ => panic!("expected first local transcript"),
-        }
-
-        let second = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match second.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-one.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(!second.is_first);
-            }
-            _ => panic!("expected first cloud transcript"),
-        }
-
-        session
-            .push_frame(frame)
-            .await
-            .expect("second frame should enqueue");
-
-        let third = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("second local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match third.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-two.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert_eq!(third.frame_index, 2);
-            }
-            _ => panic!("expected second local transcript"),
-        }
-
-        let fourth = timeout(Duration::from_millis(800), rx.recv())
-            .await
-            .expect("second cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match fourth.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-two.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert_eq!(fourth.frame_index, 2);
-                assert!(!fourth.is_first);
-            }
-            _ => panic!("expected second cloud transcript"),
-        }
-    }
-
-    #[tokio::test]
-    async fn cloud_does_not_preempt_when_updates_channel_full() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-backpressure."],
-            Duration::from_millis(120),
-        ));
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-fast."],
-            Duration::from_millis(20),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
-            buffer_capacity: 1,
-            enable_polisher: false,
-            ..RealtimeSessionConfig::default()
-        });
-
-        let updates_tx = session.updates_tx.clone();
-        updates_tx
-            .try_send(TranscriptionUpdate {
-                payload: UpdatePayload::Notice(SessionNotice {
-                    level: NoticeLevel::Info,
-                    message: "prefill".to_string(),
-                }),
-                latency: Duration::from_millis(0),
-                frame_index: 0,
-                is_first: false,
-            })
-            .expect("prefill updates channel");
-
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        sleep(Duration::from_millis(150)).await;
-        let progress = session.local_progress.clone();
-        assert_eq!(progress.last_frame(), 0);
-
-        let _ = timeout(Duration::from_millis(50), rx.recv())
-            .await
-            .expect("prefill frame not drained")
-            .expect("channel closed unexpectedly");
-
-        let first = timeout(Duration::from_millis(250), rx.recv())
-            .await
-            .expect("first transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match first.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-backpressure.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(first.is_first);
-            }
-            _ => panic!("expected first local transcript"),
-        }
-
-        let second = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match second.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-fast.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(!second.is_first);
-            }
-            _ => panic!("expected cloud transcript after local"),
-        }
-    }
-
-    #[tokio::test]
-    async fn cadence_timeout_emits_notice_before_cloud_promotion() {
-        let local_engine = Arc::new(SlowSecondLocalEngine::new());
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-fast.", "cloud-follow."],
-            Duration::from_millis(50),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-
-        let first = timeout(Duration::from_millis(250), rx.recv())
-            .await
-            .expect("first local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match first.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-fast.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(first.is_first);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected first local transcript"),
-        }
-
-        let second = timeout(Duration::from_millis(500), rx.recv())
-            .await
-            .expect("first cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match second.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-fast.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(!second.is_first);
-                assert!(!payload.is_primary);
-            }
-            _ => panic!("expected first cloud transcript"),
-        }
-
-        session
-            .push_frame(frame)
-            .await
-            .expect("second frame should enqueue");
-
-        let notice = timeout(Duration::from_millis(350), rx.recv())
-            .await
-            .expect("cadence notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("本地解码"));
-            }
-            _ => panic!("expected cadence fallback notice"),
-        }
-
-        let cloud = timeout(Duration::from_millis(650), rx.recv())
-            .await
-            .expect("fallback cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match cloud.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-follow.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(payload.is_primary);
-                assert!(!cloud.is_first);
-            }
-            _ => panic!("expected fallback cloud transcript"),
-        }
-
-        let local = timeout(Duration::from_millis(950), rx.recv())
-            .await
-            .expect("delayed local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match local.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-slow.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(!payload.is_primary);
-                assert!(!local.is_first);
-            }
-            _ => panic!("expected delayed local transcript"),
-        }
-    }
-
-    #[tokio::test]
-    async fn flushes_partial_sentence_when_window_elapses() {
-        let local_engine = Arc::new(WindowSpeechEngine::new(
-            vec!["hello", "world"],
-            Duration::from_millis(250),
-        ));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-
-        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
-            min_frame_duration: Duration::from_millis(50),
-            max_frame_duration: Duration::from_millis(50),
-            first_update_deadline: Duration::from_secs(5),
-            raw_emit_window: Duration::from_millis(200),
-            enable_polisher: false,
-            ..RealtimeSessionConfig::default()
-        });
-
-        let frame = vec![0.4_f32; 800];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-        session
-            .push_frame(frame)
-            .await
-            .expect("second frame should enqueue");
-
-        let update = timeout(Duration::from_millis(900), rx.recv())
-            .await
-            .expect("windowed transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match update.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "hello world");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected transcript payload after window flush"),
-        }
-        assert!(update.is_first);
-        assert!(update.latency >= Duration::from_millis(200));
-    }
-
-    #[tokio::test]
-    async fn cloud_preferred_sessions_emit_local_first() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-one."],
-            Duration::from_millis(150),
-        ));
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-one."],
-            Duration::from_millis(40),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig { prefer_cloud: true },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.4_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-
-        let first = timeout(Duration::from_millis(450), rx.recv())
-            .await
-            .expect("first local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match first.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-one.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(first.is_first);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected first local transcript"),
-        }
-
-        let second = timeout(Duration::from_millis(700), rx.recv())
-            .await
-            .expect("cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match second.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-one.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(!second.is_first);
-                assert!(!payload.is_primary);
-            }
-            _ => panic!("expected cloud transcript"),
-        }
-    }
-
-    #[tokio::test]
-    async fn cloud_preferred_waits_for_whisper_each_frame() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-one.", "local-two."],
-            Duration::from_millis(120),
-        ));
-        let cloud_engine = Arc::new(MockSpeechEngine::new(
-            vec!["cloud-one.", "cloud-two."],
-            Duration::from_millis(30),
-        ));
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig { prefer_cloud: true },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.4_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("first frame should enqueue");
-
-        let first = timeout(Duration::from_millis(450), rx.recv())
-            .await
-            .expect("first local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match first.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-one.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(first.is_first);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected first local transcript"),
-        }
-
-        let second = timeout(Duration::from_millis(700), rx.recv())
-            .await
-            .expect("cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match second.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-one.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert!(!second.is_first);
-                assert!(!payload.is_primary);
-            }
-            _ => panic!("expected first cloud transcript"),
-        }
-
-        session
-            .push_frame(frame)
-            .await
-            .expect("second frame should enqueue");
-
-        let third = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("second local transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match third.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-two.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert_eq!(third.frame_index, 2);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected second local transcript"),
-        }
-
-        let fourth = timeout(Duration::from_millis(800), rx.recv())
-            .await
-            .expect("second cloud transcript timed out")
-            .expect("channel closed unexpectedly");
-
-        match fourth.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "cloud-two.");
-                assert_eq!(payload.source, TranscriptSource::Cloud);
-                assert_eq!(fourth.frame_index, 2);
-                assert!(!fourth.is_first);
-                assert!(!payload.is_primary);
-            }
-            _ => panic!("expected second cloud transcript"),
-        }
-    }
-
-    struct FlakyCloudEngine {
-        attempts: AtomicUsize,
-    }
-
-    impl FlakyCloudEngine {
-        fn new() -> Self {
-            Self {
-                attempts: AtomicUsize::new(0),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl SpeechEngine for FlakyCloudEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
-            let call = self.attempts.fetch_add(1, Ordering::SeqCst);
-            if call == 0 {
-                sleep(Duration::from_millis(40)).await;
-                Err(anyhow!("transient cloud outage"))
-            } else {
-                sleep(Duration::from_millis(60)).await;
-                Ok("cloud-online.".to_string())
-            }
-        }
-    }
-
-    #[tokio::test]
-    async fn retries_cloud_after_backoff() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-one.", "local-two."],
-            Duration::from_millis(15),
-        ));
-        let cloud_engine = Arc::new(FlakyCloudEngine::new());
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig { prefer_cloud: true },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.4_f32; 1_600];
-        session
-            .push_frame(frame.clone())
-            .await
-            .expect("frame should enqueue");
-
-        let first = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("first update timed out")
-            .expect("channel closed unexpectedly");
-
-        match first.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-one.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-                assert!(payload.is_primary);
-            }
-            _ => panic!("expected first local transcript"),
-        }
-
-        let notice = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("fallback notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("云端识别异常"));
-            }
-            _ => panic!("expected fallback notice"),
-        }
-
-        sleep(CLOUD_RETRY_BACKOFF + Duration::from_millis(200)).await;
-
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        let mut cloud_transcript = None;
-        for _ in 0..4 {
-            let update = timeout(Duration::from_millis(800), rx.recv())
-                .await
-                .expect("update timed out")
-                .expect("channel closed unexpectedly");
-
-            if let UpdatePayload::Transcript(payload) = &update.payload {
-                if payload.source == TranscriptSource::Cloud {
-                    cloud_transcript = Some((payload.clone(), update.is_first));
-                    break;
-                }
-            }
-        }
-
-        let (cloud_payload, is_first) =
-            cloud_transcript.expect("cloud transcript not received after retry");
-        assert_eq!(cloud_payload.text, "cloud-online.");
-        assert!(!cloud_payload.is_primary);
-        assert!(!is_first);
-    }
-    #[tokio::test]
-    async fn emits_fallback_notice_when_cloud_engine_fails() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["fallback."],
-            Duration::from_millis(10),
-        ));
-        let cloud_engine = Arc::new(FailingSpeechEngine);
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig { prefer_cloud: true },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        let first = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("transcription timed out")
-            .expect("channel closed unexpectedly");
-
-        let transcript = match first.payload {
-            UpdatePayload::Transcript(payload) => payload,
-            UpdatePayload::Notice(_) => panic!("expected transcript before notice"),
-            UpdatePayload::Selection(_) => panic!("unexpected selection before notice"),
-        };
-        assert_eq!(transcript.text, "fallback.");
-        assert_eq!(transcript.source, TranscriptSource::Local);
-        assert!(transcript.is_primary);
-
-        let notice = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("云端识别异常"));
-            }
-            UpdatePayload::Transcript(_) => panic!("expected fallback notice"),
-            UpdatePayload::Selection(_) => {
-                panic!("unexpected selection instead of fallback notice")
-            }
-        }
-    }
-
-    #[tokio::test]
-    async fn cloud_path_runs_when_local_is_primary() {
-        let local_engine = Arc::new(MockSpeechEngine::new(
-            vec!["local-first."],
-            Duration::from_millis(5),
-        ));
-        let cloud_engine = Arc::new(FailingSpeechEngine);
-
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (session, mut rx) = orchestrator.start_realtime_session(config);
-
-        let frame = vec![0.5_f32; 1_600];
-        session
-            .push_frame(frame)
-            .await
-            .expect("frame should enqueue");
-
-        let first = timeout(Duration::from_millis(400), rx.recv())
-            .await
-            .expect("transcription timed out")
-            .expect("channel closed unexpectedly");
-
-        let transcript = match first.payload {
-            UpdatePayload::Transcript(payload) => payload,
-            UpdatePayload::Notice(_) => panic!("expected transcript before notice"),
-            UpdatePayload::Selection(_) => panic!("unexpected selection before notice"),
-        };
-        assert_eq!(transcript.text, "local-first.");
-        assert_eq!(transcript.source, TranscriptSource::Local);
-        assert!(transcript.is_primary);
-
-        let notice = timeout(Duration::from_millis(600), rx.recv())
-            .await
-            .expect("notice timed out")
-            .expect("channel closed unexpectedly");
-
-        match notice.payload {
-            UpdatePayload::Notice(session_notice) => {
-                assert_eq!(session_notice.level, NoticeLevel::Warn);
-                assert!(session_notice.message.contains("云端识别异常"));
-            }
-            UpdatePayload::Transcript(_) => panic!("expected fallback notice"),
-            UpdatePayload::Selection(_) => {
-                panic!("unexpected selection instead of fallback notice")
-            }
-        }
-    }
-}
+mod tests;
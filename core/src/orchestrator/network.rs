@@ -0,0 +1,310 @@
+//! 网络质量监测：定期测量到云端端点的往返时延与丢包，按评分在本地与云端引擎间切换，
+//! 以上下两个阈值和最短驻留时间抑制抖动。
+
+use std::collections::VecDeque;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::info;
+
+use super::offline::endpoint_address;
+use crate::session::shutdown::CancellationToken;
+use crate::telemetry::events::{record_engine_route_switch, EngineRouteSwitchEvent};
+
+/// 混合模式下当前的主引擎。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineRoute {
+    Local,
+    Cloud,
+}
+
+impl EngineRoute {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EngineRoute::Local => "local",
+            EngineRoute::Cloud => "cloud",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkMonitorConfig {
+    pub endpoint: String,
+    pub probe_interval: Duration,
+    pub probe_timeout: Duration,
+    /// 参与评分的最近探测次数。
+    pub window: usize,
+    /// 时延不高于该值时时延得分为 1。
+    pub good_rtt: Duration,
+    /// 时延不低于该值时时延得分为 0。
+    pub poor_rtt: Duration,
+    /// 评分达到该值时切换到云端。
+    pub cloud_threshold: f32,
+    /// 评分低于该值时退回本地。
+    pub local_threshold: f32,
+    /// 两次切换之间的最短间隔。
+    pub min_dwell: Duration,
+}
+
+impl Default for NetworkMonitorConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            probe_interval: Duration::from_secs(2),
+            probe_timeout: Duration::from_millis(800),
+            window: 10,
+            good_rtt: Duration::from_millis(120),
+            poor_rtt: Duration::from_millis(600),
+            cloud_threshold: 0.7,
+            local_threshold: 0.4,
+            min_dwell: Duration::from_secs(10),
+        }
+    }
+}
+
+/// 最近窗口内的网络质量。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkQuality {
+    /// 成功探测的平均时延；全部丢失时为空。
+    pub rtt: Option<Duration>,
+    pub packet_loss: f32,
+    pub score: f32,
+}
+
+/// 一次主引擎切换。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteSwitch {
+    pub from: EngineRoute,
+    pub to: EngineRoute,
+    pub quality: NetworkQuality,
+}
+
+/// 按时延与丢包计算 0 到 1 的网络评分；`None` 表示一次丢失的探测。
+pub fn score_network(
+    samples: &[Option<Duration>],
+    config: &NetworkMonitorConfig,
+) -> NetworkQuality {
+    if samples.is_empty() {
+        return NetworkQuality {
+            rtt: None,
+            packet_loss: 0.0,
+            score: 0.0,
+        };
+    }
+    let received: Vec<Duration> = samples.iter().flatten().copied().collect();
+    let packet_loss = 1.0 - received.len() as f32 / samples.len() as f32;
+    let rtt =
+        (!received.is_empty()).then(|| received.iter().sum::<Duration>() / received.len() as u32);
+    let rtt_score = rtt.map_or(0.0, |rtt| {
+        let good = config.good_rtt.as_secs_f32();
+        let span = (config.poor_rtt.as_secs_f32() - good).max(f32::EPSILON);
+        1.0 - ((rtt.as_secs_f32() - good) / span).clamp(0.0, 1.0)
+    });
+    NetworkQuality {
+        rtt,
+        packet_loss,
+        score: rtt_score * (1.0 - packet_loss).powi(2),
+    }
+}
+
+#[derive(Debug)]
+struct MonitorState {
+    samples: VecDeque<Option<Duration>>,
+    route: EngineRoute,
+    last_switch: Option<Instant>,
+}
+
+/// 混合引擎模式的路由依据，可被多个会话共享。
+#[derive(Debug)]
+pub struct NetworkMonitor {
+    config: NetworkMonitorConfig,
+    state: Mutex<MonitorState>,
+}
+
+impl NetworkMonitor {
+    pub fn new(config: NetworkMonitorConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(MonitorState {
+                samples: VecDeque::new(),
+                route: EngineRoute::Local,
+                last_switch: None,
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MonitorState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn route(&self) -> EngineRoute {
+        self.state().route
+    }
+
+    pub fn quality(&self) -> NetworkQuality {
+        let samples: Vec<_> = self.state().samples.iter().copied().collect();
+        score_network(&samples, &self.config)
+    }
+
+    /// 记录一次探测结果，必要时切换主引擎并上报遥测。
+    pub fn record(&self, rtt: Option<Duration>, now: Instant) -> Option<RouteSwitch> {
+        let mut state = self.state();
+        state.samples.push_back(rtt);
+        while state.samples.len() > self.config.window.max(1) {
+            state.samples.pop_front();
+        }
+        // 样本不足时不做判断，避免单次探测决定路由。
+        if state.samples.len() < self.config.window.clamp(1, 3) {
+            return None;
+        }
+        let samples: Vec<_> = state.samples.iter().copied().collect();
+        let quality = score_network(&samples, &self.config);
+        let target = match state.route {
+            EngineRoute::Local if quality.score >= self.config.cloud_threshold => {
+                EngineRoute::Cloud
+            }
+            EngineRoute::Cloud if quality.score < self.config.local_threshold => EngineRoute::Local,
+            _ => return None,
+        };
+        let dwelling = state
+            .last_switch
+            .is_some_and(|last| now.saturating_duration_since(last) < self.config.min_dwell);
+        if dwelling {
+            return None;
+        }
+
+        let switch = RouteSwitch {
+            from: state.route,
+            to: target,
+            quality,
+        };
+        state.route = target;
+        state.last_switch = Some(now);
+        drop(state);
+
+        info!(
+            target: "network_monitor",
+            from = switch.from.as_str(),
+            to = switch.to.as_str(),
+            score = quality.score,
+            "engine route switched"
+        );
+        record_engine_route_switch(EngineRouteSwitchEvent {
+            from: switch.from.as_str(),
+            to: switch.to.as_str(),
+            score: quality.score,
+            rtt_ms: quality.rtt.map(|rtt| rtt.as_millis() as u64),
+            packet_loss: quality.packet_loss,
+        });
+        Some(switch)
+    }
+
+    /// 以 TCP 建连耗时近似往返时延；连接失败或超时记为丢包。
+    pub fn probe(&self) -> Option<Duration> {
+        let addrs = endpoint_address(&self.config.endpoint)?
+            .to_socket_addrs()
+            .ok()?;
+        for addr in addrs {
+            let started = Instant::now();
+            if TcpStream::connect_timeout(&addr, self.config.probe_timeout).is_ok() {
+                return Some(started.elapsed());
+            }
+        }
+        None
+    }
+
+    /// 按探测间隔在后台持续测量，`shutdown` 取消后停止。
+    pub fn spawn(self: &Arc<Self>, shutdown: &CancellationToken) -> JoinHandle<()> {
+        let monitor = Arc::clone(self);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown
+                .run_until_cancelled(async move {
+                    let mut ticker = tokio::time::interval(monitor.config.probe_interval);
+                    loop {
+                        ticker.tick().await;
+                        let probing = Arc::clone(&monitor);
+                        let rtt = tokio::task::spawn_blocking(move || probing.probe())
+                            .await
+                            .unwrap_or(None);
+                        monitor.record(rtt, Instant::now());
+                    }
+                })
+                .await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(value: u64) -> Option<Duration> {
+        Some(Duration::from_millis(value))
+    }
+
+    #[test]
+    fn scores_latency_and_loss() {
+        let config = NetworkMonitorConfig::default();
+        assert_eq!(score_network(&[ms(80), ms(100)], &config).score, 1.0);
+        let lossy = score_network(&[ms(100), None], &config);
+        assert_eq!(lossy.packet_loss, 0.5);
+        assert!((lossy.score - 0.25).abs() < 1e-6);
+        let slow = score_network(&[ms(360)], &config);
+        assert!((slow.score - 0.5).abs() < 1e-3);
+        assert_eq!(score_network(&[None, None], &config).score, 0.0);
+    }
+
+    #[test]
+    fn switches_with_hysteresis_and_dwell() {
+        let monitor = NetworkMonitor::new(NetworkMonitorConfig {
+            window: 3,
+            min_dwell: Duration::from_secs(5),
+            ..NetworkMonitorConfig::default()
+        });
+        let start = Instant::now();
+        assert_eq!(monitor.record(ms(50), start), None);
+        assert_eq!(monitor.record(ms(50), start), None);
+        let switch = monitor.record(ms(50), start).expect("good network");
+        assert_eq!(
+            (switch.from, switch.to),
+            (EngineRoute::Local, EngineRoute::Cloud)
+        );
+
+        // 时延轻微上升时仍保持云端。
+        monitor.record(ms(300), start + Duration::from_secs(6));
+        assert_eq!(monitor.route(), EngineRoute::Cloud);
+        // 驻留时间内不回切。
+        assert_eq!(monitor.record(None, start + Duration::from_secs(1)), None);
+        let switch = monitor
+            .record(None, start + Duration::from_secs(7))
+            .expect("degraded network");
+        assert_eq!(switch.to, EngineRoute::Local);
+        assert_eq!(monitor.route(), EngineRoute::Local);
+    }
+
+    #[tokio::test]
+    async fn background_probing_stops_on_shutdown() {
+        let monitor = Arc::new(NetworkMonitor::new(NetworkMonitorConfig {
+            endpoint: "http://127.0.0.1:9".into(),
+            probe_interval: Duration::from_millis(10),
+            probe_timeout: Duration::from_millis(10),
+            ..NetworkMonitorConfig::default()
+        }));
+        let shutdown = CancellationToken::new();
+        let task = monitor.spawn(&shutdown);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(monitor.quality().packet_loss > 0.0);
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("probing stops after shutdown")
+            .unwrap();
+    }
+}
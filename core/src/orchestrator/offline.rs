@@ -0,0 +1,449 @@
+//! 离线与隐私约束：跟踪网络连通性、用户“仅本地”选择与组织策略，决定能否使用云端能力。
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use super::budget::{BudgetMeter, CloudBudget};
+use super::{NoticeLevel, SessionNotice, TranscriptionUpdate, UpdatePayload};
+
+/// 需要把音频或文本发往云端的能力。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloudFeature {
+    Transcription,
+    Polish,
+    Translation,
+}
+
+impl CloudFeature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloudFeature::Transcription => "transcription",
+            CloudFeature::Polish => "polish",
+            CloudFeature::Translation => "translation",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            CloudFeature::Transcription => "云端识别",
+            CloudFeature::Polish => "云端润色",
+            CloudFeature::Translation => "云端翻译",
+        }
+    }
+}
+
+/// 云端能力被禁用的原因，按优先级排列。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloudBlock {
+    /// 组织策略禁止使用云端。
+    TenantPolicy,
+    /// 用户选择了仅本地模式。
+    LocalOnly,
+    /// 网络不可用。
+    Offline,
+    /// 已达到用户设置的云端用量上限。
+    BudgetExceeded,
+}
+
+impl CloudBlock {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloudBlock::TenantPolicy => "tenant_policy",
+            CloudBlock::LocalOnly => "local_only",
+            CloudBlock::Offline => "offline",
+            CloudBlock::BudgetExceeded => "budget_exceeded",
+        }
+    }
+
+    /// 出于隐私原因而非网络故障。
+    pub fn is_privacy(&self) -> bool {
+        !matches!(self, CloudBlock::Offline | CloudBlock::BudgetExceeded)
+    }
+
+    /// 面向用户的跳过说明。
+    pub fn notice(&self, feature: CloudFeature) -> String {
+        let reason = match self {
+            CloudBlock::TenantPolicy => "组织策略禁止云端处理",
+            CloudBlock::LocalOnly => "已启用仅本地模式",
+            CloudBlock::Offline => "网络不可用",
+            CloudBlock::BudgetExceeded => "已达到云端用量上限",
+        };
+        format!("{reason}，已跳过{}", feature.label())
+    }
+}
+
+/// 进程内共享的云端访问开关，可在会话进行中随时更新。
+#[derive(Debug)]
+pub struct OfflineGuard {
+    local_only: AtomicBool,
+    tenant_forbids_cloud: AtomicBool,
+    /// 服务所在区域不在组织策略允许范围内的能力。
+    tenant_blocked_features: StdMutex<HashSet<CloudFeature>>,
+    online: AtomicBool,
+}
+
+impl Default for OfflineGuard {
+    fn default() -> Self {
+        Self {
+            local_only: AtomicBool::new(false),
+            tenant_forbids_cloud: AtomicBool::new(false),
+            tenant_blocked_features: StdMutex::new(HashSet::new()),
+            online: AtomicBool::new(true),
+        }
+    }
+}
+
+impl OfflineGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_local_only(&self, local_only: bool) {
+        self.local_only.store(local_only, Ordering::SeqCst);
+    }
+
+    pub fn set_tenant_forbids_cloud(&self, forbidden: bool) {
+        self.tenant_forbids_cloud.store(forbidden, Ordering::SeqCst);
+    }
+
+    /// 按组织策略单独禁止的云端能力，例如服务区域不在允许范围内的云端识别或润色。
+    pub fn set_tenant_blocked_features(&self, features: impl IntoIterator<Item = CloudFeature>) {
+        *self
+            .tenant_blocked_features
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = features.into_iter().collect();
+    }
+
+    pub fn set_online(&self, online: bool) {
+        let previous = self.online.swap(online, Ordering::SeqCst);
+        if previous != online {
+            info!(target: "offline_guard", online, "connectivity changed");
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// 当前禁止使用云端的原因；允许时为 `None`。
+    pub fn cloud_block(&self) -> Option<CloudBlock> {
+        if self.tenant_forbids_cloud.load(Ordering::SeqCst) {
+            Some(CloudBlock::TenantPolicy)
+        } else if self.local_only.load(Ordering::SeqCst) {
+            Some(CloudBlock::LocalOnly)
+        } else if !self.is_online() {
+            Some(CloudBlock::Offline)
+        } else {
+            None
+        }
+    }
+
+    pub fn allows_cloud(&self) -> bool {
+        self.cloud_block().is_none()
+    }
+
+    /// 指定能力当前被禁止的原因：组织策略单独禁止该能力时优先，其余同 [`Self::cloud_block`]。
+    pub fn feature_block(&self, feature: CloudFeature) -> Option<CloudBlock> {
+        let blocked = self
+            .tenant_blocked_features
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(&feature);
+        if blocked {
+            Some(CloudBlock::TenantPolicy)
+        } else {
+            self.cloud_block()
+        }
+    }
+
+    /// 以 TCP 建连探测 `endpoint` 是否可达，并更新连通状态。
+    pub fn probe(&self, endpoint: &str, timeout: Duration) -> bool {
+        let reachable = endpoint_address(endpoint)
+            .and_then(|address| address.to_socket_addrs().ok())
+            .is_some_and(|mut addrs| {
+                addrs.any(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok())
+            });
+        self.set_online(reachable);
+        reachable
+    }
+
+    /// 按固定间隔在后台探测连通性。
+    pub fn spawn_monitor(
+        self: &Arc<Self>,
+        endpoint: String,
+        interval: Duration,
+        timeout: Duration,
+    ) -> JoinHandle<()> {
+        let guard = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let guard = Arc::clone(&guard);
+                let endpoint = endpoint.clone();
+                let _ = tokio::task::spawn_blocking(move || guard.probe(&endpoint, timeout)).await;
+            }
+        })
+    }
+}
+
+/// 从 URL 中取出 `host:port`，缺省端口按协议推断。
+pub(super) fn endpoint_address(endpoint: &str) -> Option<String> {
+    let (scheme, rest) = endpoint.split_once("://").unwrap_or(("https", endpoint));
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    if authority.is_empty() {
+        return None;
+    }
+    let has_port = match authority.rfind(']') {
+        Some(bracket) => authority[bracket..].contains(':'),
+        None => authority.contains(':'),
+    };
+    if has_port {
+        return Some(authority.to_string());
+    }
+    let port = if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("ws") {
+        80
+    } else {
+        443
+    };
+    Some(format!("{authority}:{port}"))
+}
+
+/// 端点指向本机时不涉及数据外发。
+pub fn is_local_endpoint(endpoint: &str) -> bool {
+    let Some(address) = endpoint_address(endpoint) else {
+        return false;
+    };
+    let host = match address.rfind(':') {
+        Some(index) => &address[..index],
+        None => address.as_str(),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback() || ip.is_unspecified())
+}
+
+/// 会话内的云端访问检查：每种能力被禁用时只提示一次，恢复后重新计。
+pub(super) struct CloudGate {
+    guard: Arc<OfflineGuard>,
+    budget: Arc<CloudBudget>,
+    notified: StdMutex<HashMap<CloudFeature, CloudBlock>>,
+}
+
+impl CloudGate {
+    pub(super) fn new(guard: Arc<OfflineGuard>, budget: Arc<CloudBudget>) -> Self {
+        Self {
+            guard,
+            budget,
+            notified: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    pub(super) fn budget(&self) -> &Arc<CloudBudget> {
+        &self.budget
+    }
+
+    /// 开关优先，其次为该能力的用量预算。
+    pub(super) fn block(&self, feature: CloudFeature) -> Option<CloudBlock> {
+        self.guard.feature_block(feature).or_else(|| {
+            self.budget
+                .exceeded(BudgetMeter::for_feature(feature))
+                .map(|_| CloudBlock::BudgetExceeded)
+        })
+    }
+
+    pub(super) fn allows(&self, feature: CloudFeature) -> bool {
+        self.block(feature).is_none()
+    }
+
+    /// 允许使用云端时返回 `true`，用量接近上限时提醒一次；否则在原因变化时下发说明。
+    pub(super) async fn admit(
+        &self,
+        feature: CloudFeature,
+        tx: &mpsc::Sender<TranscriptionUpdate>,
+        frame_index: usize,
+        latency: Duration,
+    ) -> bool {
+        let block = self.block(feature);
+        let fresh = {
+            let mut notified = self
+                .notified
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            match block {
+                None => {
+                    notified.remove(&feature);
+                    None
+                }
+                Some(block) => Some(notified.insert(feature, block) != Some(block)),
+            }
+        };
+        let (level, message) = match (block, fresh) {
+            (None, _) => {
+                let Some(warning) = self.budget.take_warning(BudgetMeter::for_feature(feature))
+                else {
+                    return true;
+                };
+                (NoticeLevel::Warn, warning)
+            }
+            (Some(block), Some(true)) => {
+                info!(
+                    target: "engine_orchestrator",
+                    feature = feature.as_str(),
+                    reason = block.as_str(),
+                    "cloud feature skipped"
+                );
+                let level = if block.is_privacy() {
+                    NoticeLevel::Info
+                } else {
+                    NoticeLevel::Warn
+                };
+                (level, block.notice(feature))
+            }
+            (Some(_), _) => return false,
+        };
+        let notice = TranscriptionUpdate {
+            payload: UpdatePayload::Notice(SessionNotice { level, message }),
+            latency,
+            frame_index,
+            is_first: false,
+        };
+        if let Err(err) = tx.send(notice).await {
+            warn!(
+                target: "engine_orchestrator",
+                %err,
+                "failed to deliver cloud gate notice"
+            );
+        }
+        block.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::tests::{BracketTranslator, MockSpeechEngine};
+    use crate::orchestrator::{
+        EngineConfig, EngineOrchestrator, RealtimeSessionConfig, TranscriptSource,
+    };
+    use std::net::TcpListener;
+    use tokio::time::timeout;
+
+    #[test]
+    fn reports_blocks_by_priority() {
+        let guard = OfflineGuard::new();
+        assert_eq!(guard.cloud_block(), None);
+        guard.set_online(false);
+        assert_eq!(guard.cloud_block(), Some(CloudBlock::Offline));
+        guard.set_local_only(true);
+        assert_eq!(guard.cloud_block(), Some(CloudBlock::LocalOnly));
+        guard.set_tenant_forbids_cloud(true);
+        assert_eq!(guard.cloud_block(), Some(CloudBlock::TenantPolicy));
+
+        let regional = OfflineGuard::new();
+        regional.set_tenant_blocked_features([CloudFeature::Polish]);
+        assert_eq!(regional.cloud_block(), None);
+        assert_eq!(
+            regional.feature_block(CloudFeature::Polish),
+            Some(CloudBlock::TenantPolicy)
+        );
+        assert_eq!(regional.feature_block(CloudFeature::Transcription), None);
+        assert!(CloudBlock::TenantPolicy.is_privacy());
+        assert_eq!(
+            CloudBlock::LocalOnly.notice(CloudFeature::Polish),
+            "已启用仅本地模式，已跳过云端润色"
+        );
+    }
+
+    #[test]
+    fn classifies_local_endpoints() {
+        assert!(is_local_endpoint(
+            "http://127.0.0.1:8080/v1/chat/completions"
+        ));
+        assert!(is_local_endpoint("http://localhost:7860/translate"));
+        assert!(is_local_endpoint("http://[::1]:9000"));
+        assert!(!is_local_endpoint(
+            "https://api.openai.com/v1/chat/completions"
+        ));
+        assert_eq!(
+            endpoint_address("https://user@api.example.com/v1?x=1").as_deref(),
+            Some("api.example.com:443")
+        );
+    }
+
+    #[test]
+    fn probes_connectivity() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind listener");
+        let endpoint = format!("http://{}/health", listener.local_addr().unwrap());
+        let guard = OfflineGuard::new();
+        assert!(guard.probe(&endpoint, Duration::from_millis(200)));
+        drop(listener);
+        assert!(!guard.probe(&endpoint, Duration::from_millis(200)));
+        assert_eq!(guard.cloud_block(), Some(CloudBlock::Offline));
+    }
+
+    #[tokio::test]
+    async fn local_only_mode_keeps_audio_and_text_on_device() {
+        let guard = Arc::new(OfflineGuard::new());
+        guard.set_local_only(true);
+        let orchestrator = EngineOrchestrator::with_engines(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(MockSpeechEngine::new(
+                vec!["ship it friday."],
+                Duration::from_millis(10),
+            )),
+            Some(Arc::new(MockSpeechEngine::new(
+                vec!["cloud copy."],
+                Duration::from_millis(10),
+            ))),
+        )
+        .with_translator(Arc::new(BracketTranslator))
+        .with_offline_guard(Arc::clone(&guard));
+        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
+            punctuation_language: Some("en-US".into()),
+            translate_to: Some("zh-CN".into()),
+            ..RealtimeSessionConfig::default()
+        });
+        session
+            .push_frame(vec![0.5_f32; 1_600])
+            .await
+            .expect("frame should enqueue");
+
+        let mut notices = Vec::new();
+        let mut sources = Vec::new();
+        let polished = loop {
+            let update = timeout(Duration::from_millis(800), rx.recv())
+                .await
+                .expect("update timed out")
+                .expect("channel closed unexpectedly");
+            match update.payload {
+                UpdatePayload::Transcript(payload)
+                    if payload.source == TranscriptSource::Polished =>
+                {
+                    break payload
+                }
+                UpdatePayload::Transcript(payload) => sources.push(payload.source),
+                UpdatePayload::Notice(notice) => notices.push(notice),
+                _ => {}
+            }
+        };
+        assert_eq!(sources, [TranscriptSource::Local]);
+        assert_eq!(polished.translation, None);
+        let messages: Vec<&str> = notices.iter().map(|n| n.message.as_str()).collect();
+        assert!(messages.contains(&"已启用仅本地模式，已跳过云端识别"));
+        assert!(messages.contains(&"已启用仅本地模式，已跳过云端翻译"));
+        assert!(notices.iter().all(|n| n.level == NoticeLevel::Info));
+    }
+}
@@ -0,0 +1,486 @@
+//! 基于大模型的润色器：支持 OpenAI、Anthropic 与本地 llama.cpp 服务，
+//! 可流式输出润色片段，失败或超时时回退为原始稿。
+
+use std::env;
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value as JsonValue};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::{PolishProfile, SentencePolisher};
+use crate::secrets::SecretStore;
+
+const DEFAULT_SYSTEM_PROMPT: &str = "You polish dictated text. Fix punctuation, casing, \
+grammar and filler words while keeping the speaker's wording and language. \
+Reply with the polished text only.";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const MAX_OUTPUT_TOKENS: u32 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProvider {
+    OpenAi,
+    Anthropic,
+    /// llama.cpp `server` 的 OpenAI 兼容接口。
+    LlamaCpp,
+}
+
+impl LlmProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "openai",
+            LlmProvider::Anthropic => "anthropic",
+            LlmProvider::LlamaCpp => "llama_cpp",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openai" => Some(LlmProvider::OpenAi),
+            "anthropic" => Some(LlmProvider::Anthropic),
+            "llama_cpp" | "llama.cpp" | "llamacpp" => Some(LlmProvider::LlamaCpp),
+            _ => None,
+        }
+    }
+
+    fn default_endpoint(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "https://api.openai.com/v1/chat/completions",
+            LlmProvider::Anthropic => "https://api.anthropic.com/v1/messages",
+            LlmProvider::LlamaCpp => "http://127.0.0.1:8080/v1/chat/completions",
+        }
+    }
+
+    fn default_model(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "gpt-4o-mini",
+            LlmProvider::Anthropic => "claude-3-5-haiku-latest",
+            LlmProvider::LlamaCpp => "local",
+        }
+    }
+
+    /// 云端服务的网络往返更长；本地服务超时应尽早回退。
+    fn default_timeout(&self) -> Duration {
+        match self {
+            LlmProvider::OpenAi | LlmProvider::Anthropic => Duration::from_millis(4_000),
+            LlmProvider::LlamaCpp => Duration::from_millis(2_000),
+        }
+    }
+
+    /// 密钥库中该服务商 API Key 的条目名；本地服务无需密钥。
+    pub fn secret_name(&self) -> Option<&'static str> {
+        match self {
+            LlmProvider::OpenAi => Some("openai.api_key"),
+            LlmProvider::Anthropic => Some("anthropic.api_key"),
+            LlmProvider::LlamaCpp => None,
+        }
+    }
+
+    pub(crate) fn api_key_env(&self) -> Option<&'static str> {
+        match self {
+            LlmProvider::OpenAi => Some("OPENAI_API_KEY"),
+            LlmProvider::Anthropic => Some("ANTHROPIC_API_KEY"),
+            LlmProvider::LlamaCpp => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct LlmPolisherConfig {
+    pub provider: LlmProvider,
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    pub timeout: Duration,
+    pub stream: bool,
+    pub system_prompt: String,
+}
+
+impl fmt::Debug for LlmPolisherConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LlmPolisherConfig")
+            .field("provider", &self.provider)
+            .field("endpoint", &self.endpoint)
+            .field("model", &self.model)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("timeout", &self.timeout)
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+impl LlmPolisherConfig {
+    /// 使用服务商默认的端点、模型与超时。
+    pub fn new(provider: LlmProvider) -> Self {
+        Self {
+            provider,
+            endpoint: provider.default_endpoint().to_string(),
+            model: provider.default_model().to_string(),
+            api_key: None,
+            timeout: provider.default_timeout(),
+            stream: true,
+            system_prompt: DEFAULT_SYSTEM_PROMPT.to_string(),
+        }
+    }
+
+    /// 读取 `FLOWWISPER_POLISHER_PROVIDER` 及对应的 `_ENDPOINT`、`_MODEL`、`_API_KEY`、
+    /// `_TIMEOUT_MS`；未配置服务商时返回 `None`。API Key 缺省时回退到服务商的标准变量。
+    pub fn from_env() -> Option<Self> {
+        let provider = LlmProvider::parse(&env::var("FLOWWISPER_POLISHER_PROVIDER").ok()?)?;
+        let mut config = Self::new(provider);
+        if let Ok(endpoint) = env::var("FLOWWISPER_POLISHER_ENDPOINT") {
+            config.endpoint = endpoint;
+        }
+        if let Ok(model) = env::var("FLOWWISPER_POLISHER_MODEL") {
+            config.model = model;
+        }
+        config.api_key = env::var("FLOWWISPER_POLISHER_API_KEY")
+            .ok()
+            .or_else(|| provider.api_key_env().and_then(|name| env::var(name).ok()));
+        if let Some(timeout_ms) = env::var("FLOWWISPER_POLISHER_TIMEOUT_MS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+        {
+            config.timeout = Duration::from_millis(timeout_ms);
+        }
+        Some(config)
+    }
+
+    /// 未显式配置 API Key 时从密钥库读取；读取失败只记录告警，请求按无密钥发出。
+    pub fn resolve_api_key(&mut self, store: &dyn SecretStore) {
+        if self.api_key.is_some() {
+            return;
+        }
+        let Some(name) = self.provider.secret_name() else {
+            return;
+        };
+        match store.get(name) {
+            Ok(key) => self.api_key = key,
+            Err(err) => warn!(
+                target: "engine_orchestrator",
+                %err,
+                backend = store.backend(),
+                secret = name,
+                "failed to read api key from secret store"
+            ),
+        }
+    }
+
+    fn request_body(&self, sentence: &str) -> JsonValue {
+        match self.provider {
+            LlmProvider::Anthropic => json!({
+                "model": self.model,
+                "max_tokens": MAX_OUTPUT_TOKENS,
+                "system": self.system_prompt,
+                "messages": [{ "role": "user", "content": sentence }],
+                "temperature": 0,
+                "stream": self.stream,
+            }),
+            LlmProvider::OpenAi | LlmProvider::LlamaCpp => json!({
+                "model": self.model,
+                "messages": [
+                    { "role": "system", "content": self.system_prompt },
+                    { "role": "user", "content": sentence },
+                ],
+                "temperature": 0,
+                "stream": self.stream,
+            }),
+        }
+    }
+}
+
+/// 会话使用的润色器。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum PolisherSelection {
+    /// 编排器内置的润色器。
+    #[default]
+    Default,
+    Llm(LlmPolisherConfig),
+}
+
+pub struct LlmPolisher {
+    config: LlmPolisherConfig,
+}
+
+impl LlmPolisher {
+    pub fn new(config: LlmPolisherConfig) -> Self {
+        Self { config }
+    }
+
+    fn request(&self) -> ureq::Request {
+        let request = ureq::post(&self.config.endpoint)
+            .timeout(self.config.timeout)
+            .set("Content-Type", "application/json");
+        match (self.config.provider, &self.config.api_key) {
+            (LlmProvider::Anthropic, Some(key)) => request
+                .set("x-api-key", key)
+                .set("anthropic-version", ANTHROPIC_VERSION),
+            (LlmProvider::Anthropic, None) => request.set("anthropic-version", ANTHROPIC_VERSION),
+            (_, Some(key)) => request.set("Authorization", &format!("Bearer {key}")),
+            (_, None) => request,
+        }
+    }
+
+    /// 阻塞执行一次请求；流式模式下每收到一段增量即通过 `partial` 发送累计文本。
+    pub(super) fn complete_blocking(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        let body = self.config.request_body(sentence).to_string();
+        let response = self.request().send_string(&body).map_err(|err| {
+            anyhow!(
+                "{} polish request failed: {err}",
+                self.config.provider.as_str()
+            )
+        })?;
+
+        if !self.config.stream {
+            let body: JsonValue = serde_json::from_str(
+                &response
+                    .into_string()
+                    .map_err(|err| anyhow!("failed to read polish response: {err}"))?,
+            )
+            .map_err(|err| anyhow!("failed to parse polish response: {err}"))?;
+            return extract_completion(self.config.provider, &body)
+                .ok_or_else(|| anyhow!("polish response carried no text"));
+        }
+
+        let mut polished = String::new();
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = line.map_err(|err| anyhow!("failed to read polish stream: {err}"))?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+            let Ok(event) = serde_json::from_str::<JsonValue>(data) else {
+                continue;
+            };
+            if let Some(delta) = extract_stream_delta(self.config.provider, &event) {
+                if delta.is_empty() {
+                    continue;
+                }
+                polished.push_str(&delta);
+                let _ = partial.send(polished.trim().to_string());
+            }
+        }
+        Ok(polished)
+    }
+}
+
+fn extract_completion(provider: LlmProvider, body: &JsonValue) -> Option<String> {
+    let text = match provider {
+        LlmProvider::Anthropic => body["content"]
+            .as_array()?
+            .iter()
+            .filter_map(|block| block["text"].as_str())
+            .collect::<String>(),
+        LlmProvider::OpenAi | LlmProvider::LlamaCpp => body["choices"][0]["message"]["content"]
+            .as_str()?
+            .to_string(),
+    };
+    Some(text)
+}
+
+fn extract_stream_delta(provider: LlmProvider, event: &JsonValue) -> Option<String> {
+    match provider {
+        LlmProvider::Anthropic => (event["type"] == "content_block_delta")
+            .then(|| event["delta"]["text"].as_str().map(String::from))
+            .flatten(),
+        LlmProvider::OpenAi | LlmProvider::LlamaCpp => event["choices"][0]["delta"]["content"]
+            .as_str()
+            .map(String::from),
+    }
+}
+
+#[async_trait]
+impl SentencePolisher for LlmPolisher {
+    /// 风格要求追加到系统提示词，由模型直接按预设改写。
+    async fn polish_with_profile(
+        &self,
+        sentence: &str,
+        profile: PolishProfile,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        let mut config = self.config.clone();
+        config.system_prompt = format!("{}\n{}", config.system_prompt, profile.instructions());
+        Self::new(config).polish_streaming(sentence, partial).await
+    }
+
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        let (partial, _) = mpsc::unbounded_channel();
+        self.polish_streaming(sentence, &partial).await
+    }
+
+    /// 请求失败、超时或返回空文本时记录告警并回退为原始稿，不向上游报错。
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        if sentence.trim().is_empty() {
+            return Ok(String::new());
+        }
+        let polisher = Self::new(self.config.clone());
+        let owned = sentence.to_string();
+        let partial = partial.clone();
+        let task =
+            tokio::task::spawn_blocking(move || polisher.complete_blocking(&owned, &partial));
+
+        let result = match tokio::time::timeout(self.config.timeout, task).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(err)) => Err(anyhow!("polish task failed: {err}")),
+            Err(_) => Err(anyhow!("polish timed out after {:?}", self.config.timeout)),
+        };
+
+        match result {
+            Ok(polished) if !polished.trim().is_empty() => Ok(polished.trim().to_string()),
+            Ok(_) => {
+                warn!(
+                    target: "engine_orchestrator",
+                    provider = self.config.provider.as_str(),
+                    "polish provider returned empty text, keeping raw transcript"
+                );
+                Ok(sentence.to_string())
+            }
+            Err(err) => {
+                warn!(
+                    target: "engine_orchestrator",
+                    provider = self.config.provider.as_str(),
+                    %err,
+                    "polish provider failed, keeping raw transcript"
+                );
+                Ok(sentence.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// 单次应答的本地 HTTP 服务，返回请求体。
+    fn serve_once(
+        content_type: &'static str,
+        body: String,
+    ) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(split) = text.find("\r\n\r\n") {
+                    let length = text[..split]
+                        .lines()
+                        .find_map(|line| {
+                            line.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|value| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= split + 4 + length {
+                        break;
+                    }
+                }
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+        (format!("http://{addr}/v1/chat/completions"), handle)
+    }
+
+    #[tokio::test]
+    async fn streams_openai_compatible_deltas() {
+        let sse = [
+            r#"data: {"choices":[{"delta":{"content":"Hello,"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":" world."}}]}"#,
+            "data: [DONE]",
+        ]
+        .join("\n\n");
+        let (endpoint, server) = serve_once("text/event-stream", sse);
+        let mut config = LlmPolisherConfig::new(LlmProvider::LlamaCpp);
+        config.endpoint = endpoint;
+        let polisher = LlmPolisher::new(config);
+
+        let (partial_tx, mut partial_rx) = mpsc::unbounded_channel();
+        let polished = polisher
+            .polish_streaming("hello world", &partial_tx)
+            .await
+            .unwrap();
+        assert_eq!(polished, "Hello, world.");
+        assert_eq!(partial_rx.recv().await.as_deref(), Some("Hello,"));
+        assert_eq!(partial_rx.recv().await.as_deref(), Some("Hello, world."));
+
+        let request = server.join().unwrap();
+        assert!(request.contains(r#""stream":true"#));
+        assert!(!request.to_ascii_lowercase().contains("authorization"));
+    }
+
+    #[test]
+    fn api_key_resolves_from_secret_store_unless_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::secrets::EncryptedFileStore::open(dir.path()).unwrap();
+        store.set("openai.api_key", "sk-stored").unwrap();
+
+        let mut config = LlmPolisherConfig::new(LlmProvider::OpenAi);
+        config.resolve_api_key(&store);
+        assert_eq!(config.api_key.as_deref(), Some("sk-stored"));
+
+        config.api_key = Some("sk-explicit".into());
+        config.resolve_api_key(&store);
+        assert_eq!(config.api_key.as_deref(), Some("sk-explicit"));
+
+        let mut local = LlmPolisherConfig::new(LlmProvider::LlamaCpp);
+        local.resolve_api_key(&store);
+        assert_eq!(local.api_key, None);
+    }
+
+    #[tokio::test]
+    async fn parses_anthropic_response_and_falls_back_on_failure() {
+        let (endpoint, server) = serve_once(
+            "application/json",
+            r#"{"content":[{"type":"text","text":"Ship it today."}]}"#.to_string(),
+        );
+        let mut config = LlmPolisherConfig::new(LlmProvider::Anthropic);
+        config.endpoint = endpoint;
+        config.stream = false;
+        config.api_key = Some("test-key".into());
+        assert!(!format!("{config:?}").contains("test-key"));
+        let (partial_tx, _partial_rx) = mpsc::unbounded_channel();
+        let polished = LlmPolisher::new(config.clone())
+            .polish_with_profile("ship it today", PolishProfile::Email, &partial_tx)
+            .await
+            .unwrap();
+        assert_eq!(polished, "Ship it today.");
+        let request = server.join().unwrap().to_ascii_lowercase();
+        assert!(request.contains("polished email prose"));
+        assert!(request.contains("x-api-key: test-key"));
+        assert!(request.contains("anthropic-version"));
+
+        // 端口已关闭：请求失败后保留原始稿。
+        let fallback = LlmPolisher::new(config)
+            .polish("ship it today")
+            .await
+            .unwrap();
+        assert_eq!(fallback, "ship it today");
+    }
+}
@@ -0,0 +1,217 @@
+//! 润色风格预设：让润色稿匹配目标场景（正式、随意、要点、邮件、代码注释）。
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PolishProfile {
+    Formal,
+    Casual,
+    BulletNotes,
+    Email,
+    CodeComment,
+}
+
+impl PolishProfile {
+    pub const ALL: [PolishProfile; 5] = [
+        PolishProfile::Formal,
+        PolishProfile::Casual,
+        PolishProfile::BulletNotes,
+        PolishProfile::Email,
+        PolishProfile::CodeComment,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolishProfile::Formal => "formal",
+            PolishProfile::Casual => "casual",
+            PolishProfile::BulletNotes => "bullet_notes",
+            PolishProfile::Email => "email",
+            PolishProfile::CodeComment => "code_comment",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.as_str() == value)
+    }
+
+    /// 追加到大模型系统提示词中的风格要求。
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            PolishProfile::Formal => {
+                "Use a formal, professional tone and expand contractions."
+            }
+            PolishProfile::Casual => {
+                "Keep a relaxed, conversational tone; contractions are fine."
+            }
+            PolishProfile::BulletNotes => {
+                "Condense the text into terse note bullets, one per line, each starting with \"- \"."
+            }
+            PolishProfile::Email => {
+                "Write it as polished email prose with complete, courteous sentences."
+            }
+            PolishProfile::CodeComment => {
+                "Write it as a concise code comment: imperative mood, no greetings, no trailing filler."
+            }
+        }
+    }
+
+    /// 不依赖大模型的规则化改写，供内置润色器在基础润色之后调用。
+    pub fn apply(&self, polished: &str) -> String {
+        let text = polished.trim();
+        if text.is_empty() {
+            return String::new();
+        }
+        match self {
+            PolishProfile::Formal | PolishProfile::Email => expand_contractions(text),
+            PolishProfile::Casual => text.to_string(),
+            PolishProfile::BulletNotes => {
+                let note = text.trim_end_matches(['.', '。']);
+                format!("- {note}")
+            }
+            PolishProfile::CodeComment => {
+                let comment = text.trim_end_matches(['.', '。', '!', '！']);
+                format!("// {comment}")
+            }
+        }
+    }
+}
+
+const CONTRACTIONS: &[(&str, &str)] = &[
+    ("can't", "cannot"),
+    ("won't", "will not"),
+    ("don't", "do not"),
+    ("doesn't", "does not"),
+    ("didn't", "did not"),
+    ("isn't", "is not"),
+    ("aren't", "are not"),
+    ("wasn't", "was not"),
+    ("couldn't", "could not"),
+    ("shouldn't", "should not"),
+    ("wouldn't", "would not"),
+    ("i'm", "I am"),
+    ("i've", "I have"),
+    ("i'll", "I will"),
+    ("i'd", "I would"),
+    ("it's", "it is"),
+    ("we're", "we are"),
+    ("they're", "they are"),
+    ("you're", "you are"),
+    ("let's", "let us"),
+];
+
+fn expand_contractions(text: &str) -> String {
+    text.split(' ')
+        .map(|word| {
+            let end = word
+                .char_indices()
+                .rev()
+                .find(|(_, c)| c.is_alphanumeric())
+                .map(|(index, c)| index + c.len_utf8())
+                .unwrap_or(0);
+            let (core, trailing) = word.split_at(end);
+            let lower = core.to_lowercase();
+            match CONTRACTIONS.iter().find(|(short, _)| *short == lower) {
+                Some((_, long)) => {
+                    let mut expanded = (*long).to_string();
+                    if core.starts_with(char::is_uppercase) && !long.starts_with('I') {
+                        expanded = capitalize(&expanded);
+                    }
+                    format!("{expanded}{trailing}")
+                }
+                None => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// 风格预设的作用范围：`app_identifier` 为空时为全局默认。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolishProfileBinding {
+    #[serde(default)]
+    pub app_identifier: Option<String>,
+    pub profile: PolishProfile,
+}
+
+/// 目标应用的专属预设优先于全局默认。
+pub fn resolve_profile(
+    bindings: &[PolishProfileBinding],
+    app_identifier: Option<&str>,
+) -> Option<PolishProfile> {
+    let scoped = app_identifier.and_then(|app| {
+        bindings.iter().find(|binding| {
+            binding
+                .app_identifier
+                .as_deref()
+                .is_some_and(|scope| scope.eq_ignore_ascii_case(app))
+        })
+    });
+    scoped
+        .or_else(|| {
+            bindings
+                .iter()
+                .find(|binding| binding.app_identifier.is_none())
+        })
+        .map(|binding| binding.profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_rule_based_styles() {
+        assert_eq!(
+            PolishProfile::Formal.apply("I'm sure we can't ship it's late."),
+            "I am sure we cannot ship it is late."
+        );
+        assert_eq!(PolishProfile::Email.apply("Don't worry."), "Do not worry.");
+        assert_eq!(
+            PolishProfile::BulletNotes.apply("Ship the beta on Friday."),
+            "- Ship the beta on Friday"
+        );
+        assert_eq!(
+            PolishProfile::CodeComment.apply("Retry the upload twice."),
+            "// Retry the upload twice"
+        );
+        assert_eq!(PolishProfile::Casual.apply(" hey there. "), "hey there.");
+        for profile in PolishProfile::ALL {
+            assert_eq!(PolishProfile::parse(profile.as_str()), Some(profile));
+        }
+    }
+
+    #[test]
+    fn app_bindings_override_global_default() {
+        let bindings = vec![
+            PolishProfileBinding {
+                app_identifier: None,
+                profile: PolishProfile::Casual,
+            },
+            PolishProfileBinding {
+                app_identifier: Some("com.microsoft.VSCode".into()),
+                profile: PolishProfile::CodeComment,
+            },
+        ];
+        assert_eq!(
+            resolve_profile(&bindings, Some("com.microsoft.vscode")),
+            Some(PolishProfile::CodeComment)
+        );
+        assert_eq!(
+            resolve_profile(&bindings, Some("com.apple.mail")),
+            Some(PolishProfile::Casual)
+        );
+        assert_eq!(resolve_profile(&bindings[1..], None), None);
+    }
+}
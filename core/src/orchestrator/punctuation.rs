@@ -0,0 +1,287 @@
+//! 标点与大小写恢复：位于语音引擎与润色器之间，让双视图中的原始稿在润色前即可阅读。
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use tracing::warn;
+
+const SENTENCE_MARKS: &[char] = &[
+    '.', ',', '!', '?', ';', ':', '。', '，', '！', '？', '；', '：', '…',
+];
+
+const EN_QUESTION_STARTERS: &[&str] = &[
+    "what", "why", "how", "who", "whom", "whose", "where", "when", "which", "is", "are", "am",
+    "was", "were", "can", "could", "would", "should", "will", "shall", "do", "does", "did", "have",
+    "has", "may", "might",
+];
+const EN_COMMA_BEFORE: &[&str] = &["but", "so", "because", "although", "however"];
+const ZH_QUESTION_ENDINGS: &[char] = &['吗', '呢'];
+const ZH_COMMA_BEFORE: &[&str] = &["但是", "所以", "因为", "不过", "然后"];
+
+/// 将无标点、全小写的原始转写恢复为可读文本。
+pub trait PunctuationRestorer: Send + Sync {
+    /// `language` 为 BCP 47 语言标签，例如 `en-US`、`zh-CN`。
+    fn restore(&self, text: &str, language: &str) -> String;
+}
+
+/// 只处理明显未经标点化的文本：已含标点或大写字母的引擎输出原样保留。
+pub fn needs_restoration(text: &str) -> bool {
+    let trimmed = text.trim();
+    !trimmed.is_empty()
+        && !trimmed
+            .chars()
+            .any(|c| SENTENCE_MARKS.contains(&c) || c.is_uppercase())
+}
+
+fn primary_language(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn is_cjk(language: &str) -> bool {
+    matches!(primary_language(language).as_str(), "zh" | "ja")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// 基于规则的恢复器：英文按疑问词与连词补问号、逗号并修正大小写，
+/// 中文按语气词与连词补全角标点，其余语言仅补句首大写与句号。
+#[derive(Debug, Default, Clone)]
+pub struct RulePunctuationRestorer;
+
+impl RulePunctuationRestorer {
+    fn restore_english(words: &[&str]) -> String {
+        let mut output: Vec<String> = Vec::with_capacity(words.len());
+        for (index, word) in words.iter().enumerate() {
+            let mut token = match *word {
+                "i" | "i'm" | "i'd" | "i've" | "i'll" => capitalize(word),
+                _ => word.to_string(),
+            };
+            if index == 0 {
+                token = capitalize(&token);
+            } else if EN_COMMA_BEFORE.contains(word) {
+                if let Some(previous) = output.last_mut() {
+                    previous.push(',');
+                }
+            }
+            output.push(token);
+        }
+        let mut text = output.join(" ");
+        let is_question = words
+            .first()
+            .is_some_and(|first| EN_QUESTION_STARTERS.contains(first));
+        text.push(if is_question { '?' } else { '.' });
+        text
+    }
+
+    fn restore_cjk(text: &str) -> String {
+        let mut output = String::with_capacity(text.len() + 8);
+        let compact: String = text.split_whitespace().collect();
+        let mut rest = compact.as_str();
+        while !rest.is_empty() {
+            if !output.is_empty() {
+                if let Some(conjunction) = ZH_COMMA_BEFORE
+                    .iter()
+                    .find(|conjunction| rest.starts_with(**conjunction))
+                {
+                    output.push('，');
+                    output.push_str(conjunction);
+                    rest = &rest[conjunction.len()..];
+                    continue;
+                }
+            }
+            let mut chars = rest.chars();
+            if let Some(c) = chars.next() {
+                output.push(c);
+            }
+            rest = chars.as_str();
+        }
+        let is_question = output
+            .chars()
+            .last()
+            .is_some_and(|c| ZH_QUESTION_ENDINGS.contains(&c));
+        output.push(if is_question { '？' } else { '。' });
+        output
+    }
+}
+
+impl PunctuationRestorer for RulePunctuationRestorer {
+    fn restore(&self, text: &str, language: &str) -> String {
+        if !needs_restoration(text) {
+            return text.to_string();
+        }
+        if is_cjk(language) {
+            return Self::restore_cjk(text);
+        }
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if primary_language(language) == "en" {
+            return Self::restore_english(&words);
+        }
+        let mut restored = capitalize(&words.join(" "));
+        restored.push('.');
+        restored
+    }
+}
+
+/// 模型为每个词预测的后随标点。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PunctuationTag {
+    None,
+    Comma,
+    Period,
+    Question,
+}
+
+/// 逐词标点预测模型，例如以 ONNX Runtime 加载的 token-classification 模型。
+pub trait PunctuationModel: Send + Sync {
+    /// 模型支持的语言（主语言子标签，如 `en`）。
+    fn supports(&self, language: &str) -> bool;
+    /// 返回与 `words` 等长的标签序列。
+    fn predict(&self, words: &[&str]) -> Result<Vec<PunctuationTag>>;
+}
+
+/// 优先使用模型预测，模型不支持该语言或推理失败时回退到规则。
+pub struct ModelPunctuationRestorer {
+    model: Arc<dyn PunctuationModel>,
+    fallback: RulePunctuationRestorer,
+}
+
+impl ModelPunctuationRestorer {
+    pub fn new(model: Arc<dyn PunctuationModel>) -> Self {
+        Self {
+            model,
+            fallback: RulePunctuationRestorer,
+        }
+    }
+
+    fn apply(words: &[&str], tags: &[PunctuationTag], cjk: bool) -> Result<String> {
+        if tags.len() != words.len() {
+            return Err(anyhow!(
+                "punctuation model returned {} tags for {} words",
+                tags.len(),
+                words.len()
+            ));
+        }
+        let mut output = String::new();
+        let mut capitalize_next = !cjk;
+        for (word, tag) in words.iter().zip(tags) {
+            if !output.is_empty() && !cjk {
+                output.push(' ');
+            }
+            if capitalize_next {
+                output.push_str(&capitalize(word));
+            } else {
+                output.push_str(word);
+            }
+            capitalize_next =
+                !cjk && matches!(tag, PunctuationTag::Period | PunctuationTag::Question);
+            match (tag, cjk) {
+                (PunctuationTag::None, _) => {}
+                (PunctuationTag::Comma, false) => output.push(','),
+                (PunctuationTag::Comma, true) => output.push('，'),
+                (PunctuationTag::Period, false) => output.push('.'),
+                (PunctuationTag::Period, true) => output.push('。'),
+                (PunctuationTag::Question, false) => output.push('?'),
+                (PunctuationTag::Question, true) => output.push('？'),
+            }
+        }
+        if output.ends_with([',', '，']) {
+            output.pop();
+        }
+        if !output.ends_with(SENTENCE_MARKS) {
+            output.push(if cjk { '。' } else { '.' });
+        }
+        Ok(output)
+    }
+}
+
+impl PunctuationRestorer for ModelPunctuationRestorer {
+    fn restore(&self, text: &str, language: &str) -> String {
+        if !needs_restoration(text) || !self.model.supports(&primary_language(language)) {
+            return self.fallback.restore(text, language);
+        }
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match self
+            .model
+            .predict(&words)
+            .and_then(|tags| Self::apply(&words, &tags, is_cjk(language)))
+        {
+            Ok(restored) => restored,
+            Err(err) => {
+                warn!(
+                    target: "engine_orchestrator",
+                    %err,
+                    "punctuation model failed, falling back to rules"
+                );
+                self.fallback.restore(text, language)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_restore_english_and_chinese() {
+        let restorer = RulePunctuationRestorer;
+        assert_eq!(
+            restorer.restore("i think we should ship it but not today", "en-US"),
+            "I think we should ship it, but not today."
+        );
+        assert_eq!(
+            restorer.restore("can you send the report", "en"),
+            "Can you send the report?"
+        );
+        assert_eq!(
+            restorer.restore("我们明天开会但是地点还没定", "zh-CN"),
+            "我们明天开会，但是地点还没定。"
+        );
+        assert_eq!(restorer.restore("你吃饭了吗", "zh"), "你吃饭了吗？");
+        assert_eq!(restorer.restore("hola a todos", "es"), "Hola a todos.");
+        // 已有标点或大写的文本保持原样。
+        assert_eq!(restorer.restore("Already done.", "en"), "Already done.");
+        assert_eq!(restorer.restore("  ", "en"), "  ");
+    }
+
+    struct FixedModel(Result<Vec<PunctuationTag>, &'static str>);
+
+    impl PunctuationModel for FixedModel {
+        fn supports(&self, language: &str) -> bool {
+            language == "en"
+        }
+
+        fn predict(&self, _words: &[&str]) -> Result<Vec<PunctuationTag>> {
+            self.0.clone().map_err(|err| anyhow!(err))
+        }
+    }
+
+    #[test]
+    fn model_restorer_applies_tags_and_falls_back() {
+        use PunctuationTag::*;
+        let restorer = ModelPunctuationRestorer::new(Arc::new(FixedModel(Ok(vec![
+            None, Period, None, Question,
+        ]))));
+        assert_eq!(
+            restorer.restore("hello there how come", "en-GB"),
+            "Hello there. How come?"
+        );
+        // 不支持的语言走规则。
+        assert_eq!(restorer.restore("你好", "zh"), "你好。");
+
+        let broken = ModelPunctuationRestorer::new(Arc::new(FixedModel(Err("boom"))));
+        assert_eq!(broken.restore("so it goes", "en"), "So it goes.");
+        let mismatched = ModelPunctuationRestorer::new(Arc::new(FixedModel(Ok(vec![None]))));
+        assert_eq!(mismatched.restore("so it goes", "en"), "So it goes.");
+    }
+}
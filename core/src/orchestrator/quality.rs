@@ -0,0 +1,136 @@
+//! 按句汇总词级置信度，标记识别质量可疑的句子。
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::vocabulary::ScoredToken;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityConfig {
+    /// 句子平均置信度低于该值时下发 `QualityFlag`。
+    pub sentence_threshold: f32,
+    /// 低于该值的词列入可疑词。
+    pub word_threshold: f32,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            sentence_threshold: 0.6,
+            word_threshold: 0.5,
+        }
+    }
+}
+
+/// 置信度偏低的句子，随历史记录保存供准确性标注预填。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityFlag {
+    pub sentence_id: u64,
+    pub text: String,
+    pub confidence: f32,
+    #[serde(default)]
+    pub low_confidence_words: Vec<String>,
+}
+
+/// 一句话的置信度汇总。
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentenceConfidence {
+    pub confidence: f32,
+    pub low_confidence_words: Vec<String>,
+}
+
+/// 缓存尚未成句的词级置信度，句子落定时按字符数近似对齐取出。
+#[derive(Debug)]
+pub struct ConfidenceTracker {
+    config: QualityConfig,
+    pending: VecDeque<ScoredToken>,
+}
+
+impl ConfidenceTracker {
+    pub fn new(config: QualityConfig) -> Self {
+        Self {
+            config,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, tokens: Vec<ScoredToken>) {
+        self.pending.extend(tokens);
+    }
+
+    /// 取出覆盖 `sentence` 的词元并汇总；句子未低于阈值或没有可用置信度时返回 `None`。
+    pub fn assess(&mut self, sentence: &str) -> Option<SentenceConfidence> {
+        let mut remaining = visible_len(sentence);
+        let mut tokens = Vec::new();
+        while remaining > 0 {
+            let Some(token) = self.pending.pop_front() else {
+                break;
+            };
+            remaining = remaining.saturating_sub(visible_len(&token.text));
+            tokens.push(token);
+        }
+
+        let scores: Vec<f32> = tokens.iter().filter_map(|token| token.confidence).collect();
+        if scores.is_empty() {
+            return None;
+        }
+        let confidence = scores.iter().sum::<f32>() / scores.len() as f32;
+        if confidence >= self.config.sentence_threshold {
+            return None;
+        }
+        let low_confidence_words = tokens
+            .into_iter()
+            .filter(|token| {
+                token
+                    .confidence
+                    .is_some_and(|score| score < self.config.word_threshold)
+            })
+            .map(|token| token.text.trim().to_string())
+            .filter(|word| !word.is_empty())
+            .collect();
+        Some(SentenceConfidence {
+            confidence,
+            low_confidence_words,
+        })
+    }
+}
+
+fn visible_len(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(text: &str, confidence: Option<f32>) -> ScoredToken {
+        ScoredToken {
+            text: text.into(),
+            confidence,
+        }
+    }
+
+    #[test]
+    fn flags_sentences_below_threshold() {
+        let mut tracker = ConfidenceTracker::new(QualityConfig::default());
+        tracker.push(vec![
+            token("ship", Some(0.9)),
+            token("it.", Some(0.95)),
+            token("deploy", Some(0.3)),
+            token("the", Some(0.7)),
+        ]);
+        tracker.push(vec![token("kubelet.", Some(0.4))]);
+
+        assert_eq!(tracker.assess("Ship it."), None);
+        let flagged = tracker
+            .assess("Deploy the kubelet.")
+            .expect("low confidence sentence");
+        assert!((flagged.confidence - 0.4667).abs() < 1e-3);
+        assert_eq!(flagged.low_confidence_words, ["deploy", "kubelet."]);
+
+        tracker.push(vec![token("unscored", None)]);
+        assert_eq!(tracker.assess("unscored"), None);
+    }
+}
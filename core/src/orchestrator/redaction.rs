@@ -0,0 +1,454 @@
+//! 敏感信息识别与脱敏：邮箱、电话、类似银行卡号的数字串以及自定义规则。
+//!
+//! 同一个 [`Redactor`] 同时用于发往云端润色前的剔除与写入历史前的遮盖。
+
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::{PolishProfile, SentencePolisher};
+
+/// 云端润色时追加到系统提示词，要求模型保留占位符。
+pub(crate) const PLACEHOLDER_PROMPT: &str =
+    "Keep bracketed placeholders such as [EMAIL_1] exactly as written.";
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9\-]+(?:\.[A-Za-z0-9\-]+)*\.[A-Za-z]{2,}";
+const CARD_PATTERN: &str = r"\d(?:[ \-]?\d){12,18}";
+const PHONE_PATTERN: &str = r"\+?\(?\d(?:[ \-().]{0,2}\d){6,14}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    /// 历史中以占位符遮盖，发往云端润色前同样剔除。
+    Mask,
+    /// 仅在发往云端润色前剔除，润色结果中还原；本地历史保留原文。
+    Strip,
+    /// 不改动文本，只在历史元数据中标注命中位置。
+    Annotate,
+}
+
+impl RedactionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RedactionMode::Mask => "mask",
+            RedactionMode::Strip => "strip",
+            RedactionMode::Annotate => "annotate",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "mask" => Some(RedactionMode::Mask),
+            "strip" => Some(RedactionMode::Strip),
+            "annotate" => Some(RedactionMode::Annotate),
+            _ => None,
+        }
+    }
+
+    /// 发往云端润色前是否剔除敏感信息。
+    pub fn strips_cloud(&self) -> bool {
+        !matches!(self, RedactionMode::Annotate)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    CardNumber,
+    /// 用户自定义规则，携带规则名称。
+    Custom(String),
+}
+
+impl PiiKind {
+    /// 占位符中使用的大写标签，如 `EMAIL`。
+    pub fn tag(&self) -> String {
+        match self {
+            PiiKind::Email => "EMAIL".to_string(),
+            PiiKind::Phone => "PHONE".to_string(),
+            PiiKind::CardNumber => "CARD".to_string(),
+            PiiKind::Custom(name) => name.trim().to_ascii_uppercase().replace([' ', '-'], "_"),
+        }
+    }
+}
+
+/// 自定义识别规则。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionPattern {
+    pub name: String,
+    /// 正则表达式。
+    pub pattern: String,
+}
+
+/// 一处命中；`start`、`end` 为 UTF-8 字节偏移。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionSpan {
+    pub kind: PiiKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 剔除敏感信息后的文本，保留占位符与原文的对应关系以便还原。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrippedText {
+    pub text: String,
+    replacements: Vec<(String, String)>,
+}
+
+impl StrippedText {
+    pub fn is_redacted(&self) -> bool {
+        !self.replacements.is_empty()
+    }
+
+    /// 把占位符还原为原文；任一占位符丢失时返回 `None`。
+    pub fn restore(&self, polished: &str) -> Option<String> {
+        self.replacements
+            .iter()
+            .all(|(placeholder, _)| polished.contains(placeholder.as_str()))
+            .then(|| self.restore_partial(polished))
+    }
+
+    /// 还原已出现的占位符，用于流式片段。
+    pub fn restore_partial(&self, polished: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(polished.to_string(), |text, (placeholder, original)| {
+                text.replace(placeholder.as_str(), original)
+            })
+    }
+}
+
+pub struct Redactor {
+    mode: RedactionMode,
+    rules: Vec<(PiiKind, Regex)>,
+}
+
+impl fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redactor")
+            .field("mode", &self.mode)
+            .field(
+                "rules",
+                &self.rules.iter().map(|(kind, _)| kind).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Redactor {
+    /// 内置规则在前，自定义规则按给定顺序排在其后；规则无法编译时返回错误。
+    pub fn new(mode: RedactionMode, custom: &[RedactionPattern]) -> Result<Self> {
+        let builtin = |pattern: &str| Regex::new(pattern).expect("builtin pattern compiles");
+        let mut rules = vec![
+            (PiiKind::Email, builtin(EMAIL_PATTERN)),
+            (PiiKind::CardNumber, builtin(CARD_PATTERN)),
+            (PiiKind::Phone, builtin(PHONE_PATTERN)),
+        ];
+        for custom in custom {
+            if custom.name.trim().is_empty() {
+                return Err(anyhow!("redaction pattern name must not be empty"));
+            }
+            let regex = Regex::new(&custom.pattern)
+                .map_err(|err| anyhow!("invalid redaction pattern {:?}: {err}", custom.pattern))?;
+            rules.push((PiiKind::Custom(custom.name.trim().to_string()), regex));
+        }
+        Ok(Self { mode, rules })
+    }
+
+    pub fn mode(&self) -> RedactionMode {
+        self.mode
+    }
+
+    /// 按位置排序且互不重叠的命中；同一位置先匹配的规则优先。
+    pub fn detect(&self, text: &str) -> Vec<RedactionSpan> {
+        let mut candidates = Vec::new();
+        for (priority, (kind, regex)) in self.rules.iter().enumerate() {
+            for found in regex.find_iter(text) {
+                if accepts(kind, text, found.start(), found.end()) {
+                    candidates.push((found.start(), priority, found.end(), kind));
+                }
+            }
+        }
+        candidates.sort_by_key(|(start, priority, _, _)| (*start, *priority));
+
+        let mut spans: Vec<RedactionSpan> = Vec::new();
+        for (start, _, end, kind) in candidates {
+            if spans.last().is_some_and(|last| start < last.end) {
+                continue;
+            }
+            spans.push(RedactionSpan {
+                kind: kind.clone(),
+                start,
+                end,
+            });
+        }
+        spans
+    }
+
+    /// 以 `[EMAIL]` 等类型占位符遮盖命中内容。
+    pub fn mask(&self, text: &str) -> String {
+        self.replace(text, |span, _| format!("[{}]", span.kind.tag()))
+            .text
+    }
+
+    /// 以编号占位符（如 `[EMAIL_1]`）替换命中内容，可通过 [`StrippedText::restore`] 还原。
+    pub fn strip(&self, text: &str) -> StrippedText {
+        self.replace(text, |span, index| {
+            format!("[{}_{}]", span.kind.tag(), index + 1)
+        })
+    }
+
+    fn replace(
+        &self,
+        text: &str,
+        placeholder: impl Fn(&RedactionSpan, usize) -> String,
+    ) -> StrippedText {
+        let mut output = String::with_capacity(text.len());
+        let mut replacements = Vec::new();
+        let mut cursor = 0;
+        for (index, span) in self.detect(text).iter().enumerate() {
+            let marker = placeholder(span, index);
+            output.push_str(&text[cursor..span.start]);
+            output.push_str(&marker);
+            replacements.push((marker, text[span.start..span.end].to_string()));
+            cursor = span.end;
+        }
+        output.push_str(&text[cursor..]);
+        StrippedText {
+            text: output,
+            replacements,
+        }
+    }
+}
+
+/// 数字类命中不能是更长数字串的一部分；银行卡号还需通过 Luhn 校验。
+fn accepts(kind: &PiiKind, text: &str, start: usize, end: usize) -> bool {
+    let numeric = matches!(kind, PiiKind::Phone | PiiKind::CardNumber);
+    if numeric {
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        if before.is_some_and(|c| c.is_ascii_digit()) || after.is_some_and(|c| c.is_ascii_digit()) {
+            return false;
+        }
+    }
+    match kind {
+        PiiKind::CardNumber => luhn_valid(&text[start..end]),
+        _ => true,
+    }
+}
+
+fn luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                *digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// 发往云端润色前剔除敏感信息，并在结果中还原；占位符丢失时保留原句。
+pub(crate) struct RedactingPolisher {
+    inner: Arc<dyn SentencePolisher>,
+    redactor: Arc<Redactor>,
+}
+
+impl RedactingPolisher {
+    pub(crate) fn new(inner: Arc<dyn SentencePolisher>, redactor: Arc<Redactor>) -> Self {
+        Self { inner, redactor }
+    }
+
+    async fn polish_redacted(
+        &self,
+        sentence: &str,
+        profile: Option<PolishProfile>,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        let stripped = self.redactor.strip(sentence);
+        if !stripped.is_redacted() {
+            return match profile {
+                Some(profile) => {
+                    self.inner
+                        .polish_with_profile(sentence, profile, partial)
+                        .await
+                }
+                None => self.inner.polish_streaming(sentence, partial).await,
+            };
+        }
+
+        let (inner_tx, mut inner_rx) = mpsc::unbounded_channel::<String>();
+        let forward = {
+            let stripped = stripped.clone();
+            let partial = partial.clone();
+            tokio::spawn(async move {
+                while let Some(text) = inner_rx.recv().await {
+                    let _ = partial.send(stripped.restore_partial(&text));
+                }
+            })
+        };
+        let polished = match profile {
+            Some(profile) => {
+                self.inner
+                    .polish_with_profile(&stripped.text, profile, &inner_tx)
+                    .await
+            }
+            None => self.inner.polish_streaming(&stripped.text, &inner_tx).await,
+        };
+        drop(inner_tx);
+        let _ = forward.await;
+
+        let polished = polished?;
+        Ok(stripped.restore(&polished).unwrap_or_else(|| {
+            warn!(
+                target: "engine_orchestrator",
+                "polisher dropped redaction placeholders, keeping raw transcript"
+            );
+            sentence.to_string()
+        }))
+    }
+}
+
+#[async_trait]
+impl SentencePolisher for RedactingPolisher {
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        let (partial, _) = mpsc::unbounded_channel();
+        self.polish_redacted(sentence, None, &partial).await
+    }
+
+    async fn polish_streaming(
+        &self,
+        sentence: &str,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.polish_redacted(sentence, None, partial).await
+    }
+
+    async fn polish_with_profile(
+        &self,
+        sentence: &str,
+        profile: PolishProfile,
+        partial: &mpsc::UnboundedSender<String>,
+    ) -> Result<String> {
+        self.polish_redacted(sentence, Some(profile), partial).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+
+    fn redactor(mode: RedactionMode) -> Redactor {
+        Redactor::new(
+            mode,
+            &[RedactionPattern {
+                name: "employee id".into(),
+                pattern: r"EMP-\d{4}".into(),
+            }],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn detects_builtin_and_custom_patterns() {
+        let redactor = redactor(RedactionMode::Mask);
+        let text = "邮件发到 ann.lee@example.co.uk，电话 +86 138-0013-8000，\
+                    卡号 4111 1111 1111 1111，工号 EMP-0042，订单 4111111111111112 共 3 件";
+        let kinds: Vec<PiiKind> = redactor.detect(text).into_iter().map(|s| s.kind).collect();
+        // 未通过 Luhn 校验、又超过电话号码长度的数字串不算命中。
+        assert_eq!(
+            kinds,
+            vec![
+                PiiKind::Email,
+                PiiKind::Phone,
+                PiiKind::CardNumber,
+                PiiKind::Custom("employee id".into()),
+            ]
+        );
+        assert_eq!(
+            redactor.mask(text),
+            "邮件发到 [EMAIL]，电话 [PHONE]，卡号 [CARD]，工号 [EMPLOYEE_ID]，订单 4111111111111112 共 3 件"
+        );
+        assert!(Redactor::new(
+            RedactionMode::Mask,
+            &[RedactionPattern {
+                name: "bad".into(),
+                pattern: "(".into(),
+            }],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn stripped_text_restores_placeholders() {
+        let redactor = redactor(RedactionMode::Strip);
+        let stripped = redactor.strip("mail bob@example.com or bob@example.com");
+        assert_eq!(stripped.text, "mail [EMAIL_1] or [EMAIL_2]");
+        assert_eq!(
+            stripped.restore("Mail [EMAIL_1] or [EMAIL_2].").as_deref(),
+            Some("Mail bob@example.com or bob@example.com.")
+        );
+        assert_eq!(stripped.restore("Mail [EMAIL_1]."), None);
+    }
+
+    struct RecordingPolisher {
+        seen: StdMutex<Vec<String>>,
+        reply: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl SentencePolisher for RecordingPolisher {
+        async fn polish(&self, sentence: &str) -> Result<String> {
+            self.seen.lock().unwrap().push(sentence.to_string());
+            Ok(self
+                .reply
+                .map(String::from)
+                .unwrap_or_else(|| sentence.to_uppercase()))
+        }
+    }
+
+    #[tokio::test]
+    async fn redacting_polisher_hides_pii_from_inner_polisher() {
+        let inner = Arc::new(RecordingPolisher {
+            seen: StdMutex::new(Vec::new()),
+            reply: None,
+        });
+        let polisher =
+            RedactingPolisher::new(inner.clone(), Arc::new(redactor(RedactionMode::Strip)));
+        let polished = polisher.polish("call 555-123-4567 today").await.unwrap();
+        assert_eq!(inner.seen.lock().unwrap()[0], "call [PHONE_1] today");
+        assert_eq!(polished, "CALL 555-123-4567 TODAY");
+
+        let lossy = RedactingPolisher::new(
+            Arc::new(RecordingPolisher {
+                seen: StdMutex::new(Vec::new()),
+                reply: Some("call today"),
+            }),
+            Arc::new(redactor(RedactionMode::Strip)),
+        );
+        assert_eq!(
+            lossy.polish("call 555-123-4567 today").await.unwrap(),
+            "call 555-123-4567 today"
+        );
+    }
+}
@@ -0,0 +1,443 @@
+//! 按句重新识别与重新润色：重放该句缓存的语音，结果以同一句 ID 下发。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{info, warn, Instrument};
+
+use super::{
+    segment_languages, transcribe_frame, CloudFeature, EngineRoute, NoticeLevel,
+    RealtimeSessionHandle, RealtimeWorker, SentenceStore, SentenceVariant, SessionNotice,
+    TranscriptCommand, TranscriptPayload, TranscriptSource, TranscriptionUpdate, UpdatePayload,
+};
+
+/// 为按句重新识别保留的最近语音时长。
+pub(super) const RETRANSCRIBE_WINDOW: Duration = Duration::from_secs(120);
+
+impl RealtimeSessionHandle {
+    /// 用 `engine` 重新识别单句：重放该句缓存的语音，结果以同一句 ID 的原文更新下发，
+    /// 启用润色时随后下发新的润色稿。语音已超出缓存窗口或引擎不可用时下发提示。
+    pub async fn retranscribe_sentence(
+        &self,
+        sentence_id: u64,
+        engine: EngineRoute,
+    ) -> Result<(), mpsc::error::SendError<TranscriptCommand>> {
+        self.command_tx
+            .send(TranscriptCommand::Retranscribe {
+                sentence_id,
+                engine,
+            })
+            .await
+    }
+}
+
+impl SentenceStore {
+    /// 以重新识别的结果替换原文；旧的润色稿作废，界面回到原文直到新的润色稿下发。
+    fn replace_raw(&mut self, sentence_id: u64, text: String, source: TranscriptSource) -> bool {
+        match self.records.get_mut(&sentence_id) {
+            Some(record) => {
+                record.raw_text = text;
+                record.raw_source = source;
+                record.polished_text = None;
+                record.polished_within_sla = None;
+                record.active_variant = SentenceVariant::Raw;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl RealtimeWorker {
+    /// 在后台重新润色最近一句的原文。
+    pub(super) fn spawn_repolish_last(&self) {
+        let tx = self.updates_tx.clone();
+        let sentences_store = self.sentences.clone();
+        let segment_language = self.segment_language();
+        let audio_source = self.config.audio_source;
+        let polisher = Arc::clone(&self.polisher);
+        let local_polisher = self.local_polisher.clone();
+        let polish_deadline = self.config.polish_emit_deadline;
+        let cloud_gate = Arc::clone(&self.cloud_gate);
+
+        tokio::spawn(
+            async move {
+                let started = Instant::now();
+                let last = sentences_store
+                    .lock()
+                    .await
+                    .records
+                    .iter()
+                    .next_back()
+                    .map(|(id, record)| (*id, record.raw_text.clone(), record.frames.1));
+                let Some((sentence_id, raw_text, frame_index)) = last else {
+                    let notice = TranscriptionUpdate {
+                        payload: UpdatePayload::Notice(SessionNotice {
+                            level: NoticeLevel::Warn,
+                            message: "还没有可重新润色的句子".to_string(),
+                        }),
+                        latency: started.elapsed(),
+                        frame_index: 0,
+                        is_first: false,
+                    };
+                    if let Err(err) = tx.send(notice).await {
+                        warn!(
+                            target: "engine_orchestrator",
+                            %err,
+                            "failed to deliver repolish notice"
+                        );
+                    }
+                    return;
+                };
+
+                let polisher = match &local_polisher {
+                    Some(local)
+                        if !cloud_gate
+                            .admit(CloudFeature::Polish, &tx, frame_index, started.elapsed())
+                            .await =>
+                    {
+                        Arc::clone(local)
+                    }
+                    _ => polisher,
+                };
+                match polisher.polish(&raw_text).await {
+                    Ok(polished) => {
+                        let within_sla = started.elapsed() <= polish_deadline;
+                        sentences_store.lock().await.record_polished(
+                            sentence_id,
+                            polished.clone(),
+                            within_sla,
+                        );
+                        info!(
+                            target: "engine_orchestrator",
+                            sentence_id,
+                            "sentence repolished"
+                        );
+                        let update = TranscriptionUpdate {
+                            payload: UpdatePayload::Transcript(TranscriptPayload {
+                                sentence_id,
+                                segments: segment_languages(&polished, segment_language.as_deref()),
+                                text: polished,
+                                source: TranscriptSource::Polished,
+                                is_primary: true,
+                                within_sla,
+                                translation: None,
+                                audio_source,
+                            }),
+                            latency: started.elapsed(),
+                            frame_index,
+                            is_first: false,
+                        };
+                        if let Err(err) = tx.send(update).await {
+                            warn!(
+                                target: "engine_orchestrator",
+                                %err,
+                                "failed to deliver repolished sentence"
+                            );
+                        }
+                    }
+                    Err(err) => warn!(
+                        target: "engine_orchestrator",
+                        %err,
+                        sentence_id,
+                        "failed to repolish sentence"
+                    ),
+                }
+            }
+            .in_current_span(),
+        );
+    }
+
+    /// 在后台重新识别单句，不阻塞实时帧的处理。
+    pub(super) fn spawn_retranscription(&self, sentence_id: u64, route: EngineRoute) {
+        let engine = match route {
+            EngineRoute::Local => Some(Arc::clone(&self.local_engine)),
+            EngineRoute::Cloud => self.cloud_engine.clone(),
+        };
+        let source = match route {
+            EngineRoute::Local => TranscriptSource::Local,
+            EngineRoute::Cloud => TranscriptSource::Cloud,
+        };
+        let tx = self.updates_tx.clone();
+        let sentences_store = self.sentences.clone();
+        let audio_history = Arc::clone(&self.audio_history);
+        let vocabulary = self.vocabulary.clone();
+        let punctuation = self.punctuation_stage();
+        let segment_language = self.segment_language();
+        let audio_source = self.config.audio_source;
+        let polisher = Arc::clone(&self.polisher);
+        let local_polisher = self.local_polisher.clone();
+        let polisher_enabled = self.config.enable_polisher;
+        let polish_deadline = self.config.polish_emit_deadline;
+        let cloud_gate = Arc::clone(&self.cloud_gate);
+
+        tokio::spawn(
+            async move {
+                let started = Instant::now();
+                let notify = |message: String| {
+                    let tx = tx.clone();
+                    async move {
+                        let notice = TranscriptionUpdate {
+                            payload: UpdatePayload::Notice(SessionNotice {
+                                level: NoticeLevel::Warn,
+                                message,
+                            }),
+                            latency: started.elapsed(),
+                            frame_index: 0,
+                            is_first: false,
+                        };
+                        if let Err(err) = tx.send(notice).await {
+                            warn!(
+                                target: "engine_orchestrator",
+                                %err,
+                                "failed to deliver retranscription notice"
+                            );
+                        }
+                    }
+                };
+
+                let frames = sentences_store
+                    .lock()
+                    .await
+                    .records
+                    .get(&sentence_id)
+                    .map(|record| record.frames);
+                let Some((first, last)) = frames else {
+                    notify(format!("未找到句子 {sentence_id}，无法重新识别")).await;
+                    return;
+                };
+                let audio = audio_history
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .frames_between(first, last);
+                let Some(audio) = audio.filter(|frames| !frames.is_empty()) else {
+                    notify("该句语音已超出缓存时长，无法重新识别".to_string()).await;
+                    return;
+                };
+                let Some(engine) = engine else {
+                    notify("云端引擎未配置，无法重新识别".to_string()).await;
+                    return;
+                };
+                if route == EngineRoute::Cloud
+                    && !cloud_gate
+                        .admit(CloudFeature::Transcription, &tx, 0, started.elapsed())
+                        .await
+                {
+                    return;
+                }
+
+                let samples: Vec<f32> = audio
+                    .iter()
+                    .flat_map(|frame| frame.iter().copied())
+                    .collect();
+                let text = match transcribe_frame(engine.as_ref(), &samples, vocabulary.as_deref())
+                    .await
+                {
+                    Ok(text) if !text.trim().is_empty() => text.trim().to_string(),
+                    Ok(_) => {
+                        notify("重新识别没有得到文本，已保留原句".to_string()).await;
+                        return;
+                    }
+                    Err(err) => {
+                        warn!(
+                            target: "engine_orchestrator",
+                            %err,
+                            sentence_id,
+                            "sentence retranscription failed"
+                        );
+                        notify("重新识别失败，已保留原句".to_string()).await;
+                        return;
+                    }
+                };
+                let text = match &punctuation {
+                    Some((restorer, language)) => restorer.restore(&text, language),
+                    None => text,
+                };
+                if !sentences_store
+                    .lock()
+                    .await
+                    .replace_raw(sentence_id, text.clone(), source)
+                {
+                    return;
+                }
+                info!(
+                    target: "engine_orchestrator",
+                    sentence_id,
+                    engine = route.as_str(),
+                    frames = audio.len(),
+                    "sentence retranscribed"
+                );
+                let update = TranscriptionUpdate {
+                    payload: UpdatePayload::Transcript(TranscriptPayload {
+                        sentence_id,
+                        text: text.clone(),
+                        source,
+                        is_primary: true,
+                        within_sla: true,
+                        segments: segment_languages(&text, segment_language.as_deref()),
+                        translation: None,
+                        audio_source,
+                    }),
+                    latency: started.elapsed(),
+                    frame_index: last,
+                    is_first: false,
+                };
+                if tx.send(update).await.is_err() || !polisher_enabled {
+                    return;
+                }
+
+                let polisher = match &local_polisher {
+                    Some(local)
+                        if !cloud_gate
+                            .admit(CloudFeature::Polish, &tx, last, started.elapsed())
+                            .await =>
+                    {
+                        Arc::clone(local)
+                    }
+                    _ => polisher,
+                };
+                let polish_started = Instant::now();
+                match polisher.polish(&text).await {
+                    Ok(polished) => {
+                        let within_sla = polish_started.elapsed() <= polish_deadline;
+                        sentences_store.lock().await.record_polished(
+                            sentence_id,
+                            polished.clone(),
+                            within_sla,
+                        );
+                        let update = TranscriptionUpdate {
+                            payload: UpdatePayload::Transcript(TranscriptPayload {
+                                sentence_id,
+                                segments: segment_languages(&polished, segment_language.as_deref()),
+                                text: polished,
+                                source: TranscriptSource::Polished,
+                                is_primary: true,
+                                within_sla,
+                                translation: None,
+                                audio_source,
+                            }),
+                            latency: started.elapsed(),
+                            frame_index: last,
+                            is_first: false,
+                        };
+                        if let Err(err) = tx.send(update).await {
+                            warn!(
+                                target: "engine_orchestrator",
+                                %err,
+                                "failed to deliver retranscribed polish"
+                            );
+                        }
+                    }
+                    Err(err) => warn!(
+                        target: "engine_orchestrator",
+                        %err,
+                        sentence_id,
+                        "failed to polish retranscribed sentence"
+                    ),
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::tests::MockSpeechEngine;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator, RealtimeSessionConfig};
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn retranscribes_single_sentence_from_buffered_audio() {
+        let local_engine = Arc::new(MockSpeechEngine::new(
+            vec!["helo wrld.", "hello world."],
+            Duration::from_millis(30),
+        ));
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            local_engine,
+        );
+        let config = RealtimeSessionConfig {
+            enable_polisher: false,
+            ..RealtimeSessionConfig::default()
+        };
+        let (session, mut rx) = orchestrator.start_realtime_session(config);
+
+        session
+            .push_frame(vec![0.4_f32; 1_600])
+            .await
+            .expect("frame should enqueue");
+
+        let first = timeout(Duration::from_millis(400), rx.recv())
+            .await
+            .expect("transcript timed out")
+            .expect("channel closed unexpectedly");
+        let sentence_id = match first.payload {
+            UpdatePayload::Transcript(payload) => {
+                assert_eq!(payload.text, "helo wrld.");
+                payload.sentence_id
+            }
+            other => panic!("expected transcript payload, got {other:?}"),
+        };
+
+        session
+            .retranscribe_sentence(sentence_id, EngineRoute::Local)
+            .await
+            .expect("retranscribe command should be accepted");
+        let replacement = timeout(Duration::from_millis(400), rx.recv())
+            .await
+            .expect("replacement timed out")
+            .expect("channel closed unexpectedly");
+        match replacement.payload {
+            UpdatePayload::Transcript(payload) => {
+                assert_eq!(payload.sentence_id, sentence_id);
+                assert_eq!(payload.text, "hello world.");
+                assert_eq!(payload.source, TranscriptSource::Local);
+                assert!(payload.is_primary);
+            }
+            other => panic!("expected replacement transcript, got {other:?}"),
+        }
+
+        // 没有云端引擎、或句子不存在时只下发提示。
+        session
+            .retranscribe_sentence(sentence_id, EngineRoute::Cloud)
+            .await
+            .expect("retranscribe command should be accepted");
+        session
+            .retranscribe_sentence(sentence_id + 42, EngineRoute::Local)
+            .await
+            .expect("retranscribe command should be accepted");
+        for _ in 0..2 {
+            let notice = timeout(Duration::from_millis(400), rx.recv())
+                .await
+                .expect("notice timed out")
+                .expect("channel closed unexpectedly");
+            match notice.payload {
+                UpdatePayload::Notice(notice) => assert_eq!(notice.level, NoticeLevel::Warn),
+                other => panic!("expected notice, got {other:?}"),
+            }
+        }
+
+        // 重新润色最近一句，即使会话未启用润色。
+        session
+            .repolish_last_sentence()
+            .await
+            .expect("repolish command should be accepted");
+        let repolished = timeout(Duration::from_millis(400), rx.recv())
+            .await
+            .expect("repolish timed out")
+            .expect("channel closed unexpectedly");
+        match repolished.payload {
+            UpdatePayload::Transcript(payload) => {
+                assert_eq!(payload.sentence_id, sentence_id);
+                assert_eq!(payload.source, TranscriptSource::Polished);
+            }
+            other => panic!("expected repolished transcript, got {other:?}"),
+        }
+    }
+}
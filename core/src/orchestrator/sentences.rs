@@ -0,0 +1,233 @@
+//! 实时识别的句子切分与登记：按标点或等待窗口成句，并记录每句的原文、润色稿与当前展示版本。
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::{SentenceSelection, SentenceVariant, TranscriptSource};
+
+#[derive(Debug)]
+pub(super) struct SentenceBuffer {
+    pending: String,
+    pending_since: Option<Instant>,
+    window: Duration,
+}
+
+impl SentenceBuffer {
+    pub(super) fn new(window: Duration) -> Self {
+        Self {
+            pending: String::new(),
+            pending_since: None,
+            window,
+        }
+    }
+
+    pub(super) fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    pub(super) fn ingest(&mut self, delta: &str, now: Instant) -> Vec<String> {
+        let mut ready = Vec::new();
+        let has_content = !delta.trim().is_empty();
+
+        if has_content {
+            let trimmed_start = if self.pending.is_empty() {
+                delta.trim_start_matches(char::is_whitespace)
+            } else {
+                delta
+            };
+
+            if !self.pending.is_empty() && needs_injected_space(&self.pending, trimmed_start) {
+                self.pending.push(' ');
+            }
+
+            self.pending.push_str(trimmed_start);
+
+            if self.pending_since.is_none() && !self.pending.is_empty() {
+                self.pending_since = Some(now);
+            }
+
+            ready.extend(self.take_completed_sentences(now));
+        }
+
+        if ready.is_empty() {
+            if let Some(since) = self.pending_since {
+                if now.saturating_duration_since(since) >= self.window && !self.pending.is_empty() {
+                    ready.push(self.pending.trim().to_string());
+                    self.pending.clear();
+                    self.pending_since = None;
+                }
+            }
+        }
+
+        ready
+    }
+
+    pub(super) fn take_completed_sentences(&mut self, now: Instant) -> Vec<String> {
+        let mut ready = Vec::new();
+
+        while let Some(boundary) = find_sentence_boundary(&self.pending) {
+            let chunk = self.pending[..boundary].trim().to_string();
+            if !chunk.is_empty() {
+                ready.push(chunk);
+            }
+
+            let remainder = self.pending[boundary..]
+                .trim_start_matches(char::is_whitespace)
+                .to_string();
+            self.pending = remainder;
+
+            if self.pending.is_empty() {
+                self.pending_since = None;
+            } else {
+                self.pending_since = Some(now);
+            }
+        }
+
+        ready
+    }
+}
+
+fn find_sentence_boundary(pending: &str) -> Option<usize> {
+    let chars = pending.char_indices();
+    for (idx, ch) in chars {
+        if !is_sentence_boundary(ch) {
+            continue;
+        }
+
+        let mut boundary = idx + ch.len_utf8();
+        while let Some(next) = pending[boundary..].chars().next() {
+            if next == ch && is_sentence_boundary(next) {
+                boundary += next.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        return Some(boundary);
+    }
+    None
+}
+
+fn is_sentence_boundary(ch: char) -> bool {
+    matches!(
+        ch,
+        '.' | '!' | '?' | '\n' | '\r' | '。' | '！' | '？' | '…' | ';' | '；'
+    )
+}
+
+fn needs_injected_space(existing: &str, addition: &str) -> bool {
+    let last = existing.chars().rev().find(|c| !c.is_whitespace());
+    let first = addition.chars().find(|c| !c.is_whitespace());
+
+    match (last, first) {
+        (Some(l), Some(f)) => {
+            !l.is_whitespace()
+                && !f.is_whitespace()
+                && !is_sentence_boundary(l)
+                && !is_sentence_boundary(f)
+                && !matches!(f, ',' | '，' | ':' | '：')
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct SentenceStore {
+    next_sentence_id: u64,
+    pub(super) records: BTreeMap<u64, SentenceRecord>,
+    /// 各引擎上一句结束时所在的帧，下一句的语音从此处开始。
+    local_boundary: usize,
+    cloud_boundary: usize,
+}
+
+#[derive(Debug)]
+pub(super) struct SentenceRecord {
+    pub(super) raw_text: String,
+    pub(super) raw_source: TranscriptSource,
+    /// 该句语音所在的帧区间（含两端）。
+    pub(super) frames: (usize, usize),
+    pub(super) polished_text: Option<String>,
+    pub(super) polished_within_sla: Option<bool>,
+    pub(super) active_variant: SentenceVariant,
+    pub(super) user_override: bool,
+}
+
+impl SentenceStore {
+    /// 登记在 `frame_index` 帧成句的原文。本地引擎按流式缓冲成句，句首可能落在上一句结束的帧内；
+    /// 云端逐帧识别，句子只覆盖上一句之后的帧。
+    pub(super) fn register_raw_sentence(
+        &mut self,
+        text: String,
+        source: TranscriptSource,
+        frame_index: usize,
+    ) -> u64 {
+        self.next_sentence_id = self.next_sentence_id.saturating_add(1);
+        let sentence_id = self.next_sentence_id;
+        let first_frame = match source {
+            TranscriptSource::Local => self.local_boundary.max(1),
+            _ => (self.cloud_boundary + 1).min(frame_index),
+        };
+        match source {
+            TranscriptSource::Local => self.local_boundary = frame_index,
+            _ => self.cloud_boundary = frame_index,
+        }
+        let record = SentenceRecord {
+            raw_text: text,
+            raw_source: source,
+            frames: (first_frame.min(frame_index), frame_index),
+            polished_text: None,
+            polished_within_sla: None,
+            active_variant: SentenceVariant::Raw,
+            user_override: false,
+        };
+        self.records.insert(sentence_id, record);
+        sentence_id
+    }
+
+    pub(super) fn record_polished(
+        &mut self,
+        sentence_id: u64,
+        text: String,
+        within_sla: bool,
+    ) -> Option<SentenceVariant> {
+        if let Some(record) = self.records.get_mut(&sentence_id) {
+            record.polished_text = Some(text);
+            record.polished_within_sla = Some(within_sla);
+            if !record.user_override {
+                record.active_variant = SentenceVariant::Polished;
+            }
+            return Some(record.active_variant);
+        }
+        None
+    }
+
+    pub(super) fn apply_selection(
+        &mut self,
+        selections: &[SentenceSelection],
+    ) -> Vec<SentenceSelection> {
+        let mut applied = Vec::new();
+
+        for selection in selections {
+            if let Some(record) = self.records.get_mut(&selection.sentence_id) {
+                match selection.active_variant {
+                    SentenceVariant::Raw => {
+                        record.active_variant = SentenceVariant::Raw;
+                        record.user_override = true;
+                        applied.push(*selection);
+                    }
+                    SentenceVariant::Polished => {
+                        if record.polished_text.is_some() {
+                            record.active_variant = SentenceVariant::Polished;
+                            record.user_override = false;
+                            applied.push(*selection);
+                        }
+                    }
+                }
+            }
+        }
+
+        applied
+    }
+}
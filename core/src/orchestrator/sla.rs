@@ -0,0 +1,191 @@
+//! 按本机实测延迟校准实时会话的首个更新期限：慢机器放宽以免反复提示降级，快机器收紧。
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+/// 未校准时的首个更新期限。
+pub const DEFAULT_FIRST_UPDATE_DEADLINE: Duration = Duration::from_millis(400);
+/// 采满该数量的会话后才启用校准值。
+pub const CALIBRATION_SESSIONS: usize = 5;
+/// 只保留最近的样本，升级硬件或模型后能逐步跟上。
+const MAX_SAMPLES: usize = 50;
+const MIN_DEADLINE: Duration = Duration::from_millis(250);
+const MAX_DEADLINE: Duration = Duration::from_millis(1_200);
+/// 期限取 p90 再留出的余量。
+const HEADROOM: f64 = 1.25;
+/// 增量节奏检查的放宽倍数上限。
+const MAX_CADENCE_SLACK: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaCalibration {
+    pub first_update_deadline: Duration,
+    /// 增量节奏检查的放宽倍数，不小于 1；只放宽不收紧，节奏本身已由帧长决定。
+    pub cadence_slack: f64,
+    pub samples: usize,
+    pub p90_ms: Option<u64>,
+    pub calibrated: bool,
+}
+
+impl Default for SlaCalibration {
+    fn default() -> Self {
+        Self {
+            first_update_deadline: DEFAULT_FIRST_UPDATE_DEADLINE,
+            cadence_slack: 1.0,
+            samples: 0,
+            p90_ms: None,
+            calibrated: false,
+        }
+    }
+}
+
+/// 持久化的原始样本（毫秒），按时间先后排列。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySamples {
+    pub first_update_ms: Vec<u64>,
+}
+
+pub struct LatencyCalibrator {
+    samples: Mutex<VecDeque<u64>>,
+    state: watch::Sender<SlaCalibration>,
+}
+
+impl Default for LatencyCalibrator {
+    fn default() -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::new()),
+            state: watch::channel(SlaCalibration::default()).0,
+        }
+    }
+}
+
+impl LatencyCalibrator {
+    pub fn calibration(&self) -> SlaCalibration {
+        *self.state.borrow()
+    }
+
+    /// 每次会话记录或恢复样本后推送最新的校准结果。
+    pub fn subscribe(&self) -> watch::Receiver<SlaCalibration> {
+        self.state.subscribe()
+    }
+
+    /// 记录一次会话从检测到语音到首个本地更新的耗时。
+    pub fn record(&self, first_update: Duration) {
+        let mut samples = self
+            .samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        samples.push_back(first_update.as_millis().min(u64::MAX as u128) as u64);
+        while samples.len() > MAX_SAMPLES {
+            samples.pop_front();
+        }
+        let calibration = calibrate(&samples);
+        drop(samples);
+        self.state.send_replace(calibration);
+    }
+
+    pub fn samples(&self) -> LatencySamples {
+        LatencySamples {
+            first_update_ms: self
+                .samples
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// 用持久化的样本替换当前样本，启动时调用。
+    pub fn restore(&self, stored: LatencySamples) {
+        let mut samples = self
+            .samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let skip = stored.first_update_ms.len().saturating_sub(MAX_SAMPLES);
+        *samples = stored.first_update_ms.into_iter().skip(skip).collect();
+        let calibration = calibrate(&samples);
+        drop(samples);
+        self.state.send_replace(calibration);
+    }
+
+    /// 会话使用的首个更新期限；样本不足时沿用会话配置。
+    pub fn first_update_deadline(&self, configured: Duration) -> Duration {
+        let calibration = self.calibration();
+        if calibration.calibrated {
+            calibration.first_update_deadline
+        } else {
+            configured
+        }
+    }
+}
+
+fn calibrate(samples: &VecDeque<u64>) -> SlaCalibration {
+    if samples.is_empty() {
+        return SlaCalibration::default();
+    }
+    let mut sorted = samples.iter().copied().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    let rank = (sorted.len() * 9).div_ceil(10).max(1) - 1;
+    let p90_ms = sorted[rank];
+    if samples.len() < CALIBRATION_SESSIONS {
+        return SlaCalibration {
+            samples: samples.len(),
+            p90_ms: Some(p90_ms),
+            ..SlaCalibration::default()
+        };
+    }
+    let deadline = Duration::from_millis(p90_ms)
+        .mul_f64(HEADROOM)
+        .clamp(MIN_DEADLINE, MAX_DEADLINE);
+    let cadence_slack = (deadline.as_secs_f64() / DEFAULT_FIRST_UPDATE_DEADLINE.as_secs_f64())
+        .clamp(1.0, MAX_CADENCE_SLACK);
+    SlaCalibration {
+        first_update_deadline: deadline,
+        cadence_slack,
+        samples: samples.len(),
+        p90_ms: Some(p90_ms),
+        calibrated: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibrates_after_enough_sessions_within_bounds() {
+        let calibrator = LatencyCalibrator::default();
+        for _ in 0..CALIBRATION_SESSIONS - 1 {
+            calibrator.record(Duration::from_millis(700));
+        }
+        assert!(!calibrator.calibration().calibrated);
+        assert_eq!(
+            calibrator.first_update_deadline(DEFAULT_FIRST_UPDATE_DEADLINE),
+            DEFAULT_FIRST_UPDATE_DEADLINE
+        );
+
+        calibrator.record(Duration::from_millis(700));
+        let slow = calibrator.calibration();
+        assert!(slow.calibrated);
+        assert_eq!(slow.first_update_deadline, Duration::from_millis(875));
+        assert!(slow.cadence_slack > 2.0);
+
+        calibrator.restore(LatencySamples {
+            first_update_ms: vec![60; 8],
+        });
+        let fast = calibrator.calibration();
+        assert_eq!(fast.first_update_deadline, MIN_DEADLINE);
+        assert_eq!(fast.cadence_slack, 1.0);
+
+        calibrator.restore(LatencySamples {
+            first_update_ms: vec![5_000; 8],
+        });
+        assert_eq!(calibrator.calibration().first_update_deadline, MAX_DEADLINE);
+        assert_eq!(calibrator.samples().first_update_ms.len(), 8);
+    }
+}
@@ -0,0 +1,206 @@
+//! 未成句部分的稳定化：逐词比对相邻两次中间结果，标记稳定前缀，
+//! 让界面只追加变化部分而不是整体替换。
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::language::{script_of, Script};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StabilizerConfig {
+    /// 词元在同一位置连续出现多少次后视为稳定；至少为 1。
+    pub confirmations: usize,
+}
+
+impl Default for StabilizerConfig {
+    fn default() -> Self {
+        Self { confirmations: 2 }
+    }
+}
+
+/// 中间结果的增量：保留已渲染文本的前 `stable_prefix_len` 个字符，其后替换为 `appended`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptDelta {
+    /// 以 Unicode 字符计。
+    pub stable_prefix_len: usize,
+    pub appended: String,
+}
+
+#[derive(Debug, Clone)]
+struct TokenState {
+    text: String,
+    seen: usize,
+    first_seen: Instant,
+    reported: bool,
+}
+
+/// 会话内未成句文本的稳定器；句子落定后调用 `reset`。
+#[derive(Debug)]
+pub struct PartialStabilizer {
+    config: StabilizerConfig,
+    tokens: Vec<TokenState>,
+    stable: usize,
+    /// 重置前已有文本时，下一次更新需下发清空增量。
+    cleared: bool,
+}
+
+impl PartialStabilizer {
+    pub fn new(config: StabilizerConfig) -> Self {
+        Self {
+            config,
+            tokens: Vec::new(),
+            stable: 0,
+            cleared: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.cleared = !self.tokens.is_empty();
+        self.tokens.clear();
+        self.stable = 0;
+    }
+
+    /// 比对新的中间结果；文本与稳定前缀均未变化时返回 `None`。
+    /// 同时返回本次新近稳定的词元从首次出现到稳定所用的时间。
+    pub fn update(
+        &mut self,
+        partial: &str,
+        now: Instant,
+    ) -> Option<(TranscriptDelta, Vec<Duration>)> {
+        let incoming = tokenize(partial);
+        let common = self
+            .tokens
+            .iter()
+            .zip(&incoming)
+            .take_while(|(old, new)| old.text == **new)
+            .count();
+        let unchanged = common == self.tokens.len() && common == incoming.len();
+
+        let mut tokens = Vec::with_capacity(incoming.len());
+        for (index, text) in incoming.into_iter().enumerate() {
+            if index < common {
+                let mut token = self.tokens[index].clone();
+                token.seen += 1;
+                tokens.push(token);
+            } else {
+                tokens.push(TokenState {
+                    text: text.to_string(),
+                    seen: 1,
+                    first_seen: now,
+                    reported: false,
+                });
+            }
+        }
+
+        let confirmations = self.config.confirmations.max(1);
+        let stable = tokens
+            .iter()
+            .take_while(|token| token.seen >= confirmations)
+            .count()
+            .min(common);
+        let mut stabilized = Vec::new();
+        for token in tokens.iter_mut().take(stable) {
+            if !token.reported {
+                token.reported = true;
+                stabilized.push(now.saturating_duration_since(token.first_seen));
+            }
+        }
+
+        let changed = !unchanged || stable != self.stable || std::mem::take(&mut self.cleared);
+        self.tokens = tokens;
+        self.stable = stable;
+        if !changed {
+            return None;
+        }
+
+        let stable_prefix_len = self.tokens[..stable]
+            .iter()
+            .map(|token| token.text.chars().count())
+            .sum();
+        let appended = self.tokens[stable..]
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect();
+        Some((
+            TranscriptDelta {
+                stable_prefix_len,
+                appended,
+            },
+            stabilized,
+        ))
+    }
+}
+
+/// 切分为词元，拼接后与原文一致：空白归入后一个词元，汉字与假名逐字成词。
+pub(crate) fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut has_body = false;
+    let mut body_is_word = false;
+    for (index, c) in text.char_indices() {
+        if c.is_whitespace() {
+            if has_body {
+                tokens.push(&text[start..index]);
+                start = index;
+                has_body = false;
+            }
+            continue;
+        }
+        let ideographic = matches!(script_of(c), Script::Han | Script::Kana);
+        let word_char = !ideographic && (c.is_alphanumeric() || c == '\'');
+        if has_body && !(word_char && body_is_word) {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        has_body = true;
+        body_is_word = word_char;
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_words_and_ideographs() {
+        assert_eq!(
+            tokenize("we can't 发布 v2, ok"),
+            ["we", " can't", " 发", "布", " v2", ",", " ok"]
+        );
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn marks_prefix_stable_after_repeated_partials() {
+        let start = Instant::now();
+        let mut stabilizer = PartialStabilizer::new(StabilizerConfig::default());
+
+        let (delta, stabilized) = stabilizer.update("ship the", start).unwrap();
+        assert_eq!(delta.stable_prefix_len, 0);
+        assert_eq!(delta.appended, "ship the");
+        assert!(stabilized.is_empty());
+
+        let later = start + Duration::from_millis(120);
+        let (delta, stabilized) = stabilizer.update("ship the build", later).unwrap();
+        assert_eq!(delta.stable_prefix_len, "ship the".len());
+        assert_eq!(delta.appended, " build");
+        assert_eq!(stabilized, [Duration::from_millis(120); 2]);
+
+        // 修订最后一个词时只替换不稳定的尾部。
+        let (delta, _) = stabilizer.update("ship the beta", later).unwrap();
+        assert_eq!(delta.stable_prefix_len, "ship the".len());
+        assert_eq!(delta.appended, " beta");
+        assert!(stabilizer.update("ship the beta", later).is_some());
+        assert!(stabilizer.update("ship the beta", later).is_none());
+
+        stabilizer.reset();
+        let (delta, _) = stabilizer.update("", later).unwrap();
+        assert_eq!((delta.stable_prefix_len, delta.appended.as_str()), (0, ""));
+        assert!(stabilizer.update("", later).is_none());
+    }
+}
@@ -0,0 +1,214 @@
+//! 翻译输出：把润色后的句子译为目标语言，支持云端大模型与本地 NLLB 服务。
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value as JsonValue};
+use tokio::sync::mpsc;
+
+use super::{LlmPolisher, LlmPolisherConfig};
+
+/// BCP 47 语言标签，例如 `en-US`、`zh-CN`。
+pub type Locale = String;
+
+const TRANSLATION_PROMPT: &str = "You translate dictated text. Preserve meaning, names, \
+numbers and formatting. Reply with the translation only.";
+
+/// 与原文并列下发的译文。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslatedText {
+    pub locale: Locale,
+    pub text: String,
+}
+
+#[async_trait]
+pub trait Translator: Send + Sync {
+    /// `source` 为原文语言（可能未知），`target` 为目标语言标签。
+    async fn translate(&self, text: &str, source: Option<&str>, target: &str) -> Result<String>;
+}
+
+/// 本地 NLLB 推理服务（如 CTranslate2 封装），接收 FLORES-200 语言代码。
+#[derive(Debug, Clone, PartialEq)]
+pub struct NllbConfig {
+    pub endpoint: String,
+    pub timeout: Duration,
+}
+
+impl Default for NllbConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:7860/translate".to_string(),
+            timeout: Duration::from_millis(3_000),
+        }
+    }
+}
+
+/// 会话使用的翻译器。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum TranslatorSelection {
+    /// 编排器注入的翻译器，见 `EngineOrchestrator::with_translator`。
+    #[default]
+    Default,
+    Llm(LlmPolisherConfig),
+    Nllb(NllbConfig),
+}
+
+fn primary_subtag(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// 原文与目标语言相同时无需翻译。
+pub fn same_language(source: Option<&str>, target: &str) -> bool {
+    source.is_some_and(|source| primary_subtag(source) == primary_subtag(target))
+}
+
+/// 将 BCP 47 标签映射为 NLLB 使用的 FLORES-200 代码。
+pub fn nllb_code(locale: &str) -> Option<&'static str> {
+    let lower = locale.to_ascii_lowercase();
+    let code = match primary_subtag(locale).as_str() {
+        "zh" if lower.contains("hant") || lower.ends_with("-tw") || lower.ends_with("-hk") => {
+            "zho_Hant"
+        }
+        "zh" => "zho_Hans",
+        "en" => "eng_Latn",
+        "ja" => "jpn_Jpan",
+        "ko" => "kor_Hang",
+        "fr" => "fra_Latn",
+        "de" => "deu_Latn",
+        "es" => "spa_Latn",
+        "it" => "ita_Latn",
+        "pt" => "por_Latn",
+        "ru" => "rus_Cyrl",
+        "ar" => "arb_Arab",
+        "hi" => "hin_Deva",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// 复用大模型润色器的请求通道，以翻译提示词非流式调用。
+pub struct LlmTranslator {
+    config: LlmPolisherConfig,
+}
+
+impl LlmTranslator {
+    pub fn new(config: LlmPolisherConfig) -> Self {
+        Self { config }
+    }
+
+    fn polisher_for(&self, source: Option<&str>, target: &str) -> LlmPolisher {
+        let mut config = self.config.clone();
+        config.stream = false;
+        config.system_prompt = match source {
+            Some(source) => format!("{TRANSLATION_PROMPT}\nTranslate from {source} into {target}."),
+            None => format!("{TRANSLATION_PROMPT}\nTranslate into {target}."),
+        };
+        LlmPolisher::new(config)
+    }
+}
+
+#[async_trait]
+impl Translator for LlmTranslator {
+    async fn translate(&self, text: &str, source: Option<&str>, target: &str) -> Result<String> {
+        let polisher = self.polisher_for(source, target);
+        let owned = text.to_string();
+        let task = tokio::task::spawn_blocking(move || {
+            let (partial, _) = mpsc::unbounded_channel();
+            polisher.complete_blocking(&owned, &partial)
+        });
+        let translated = match tokio::time::timeout(self.config.timeout, task).await {
+            Ok(Ok(result)) => result?,
+            Ok(Err(err)) => return Err(anyhow!("translation task failed: {err}")),
+            Err(_) => {
+                return Err(anyhow!(
+                    "translation timed out after {:?}",
+                    self.config.timeout
+                ))
+            }
+        };
+        let translated = translated.trim();
+        if translated.is_empty() {
+            return Err(anyhow!("translation provider returned empty text"));
+        }
+        Ok(translated.to_string())
+    }
+}
+
+pub struct NllbTranslator {
+    config: NllbConfig,
+}
+
+impl NllbTranslator {
+    pub fn new(config: NllbConfig) -> Self {
+        Self { config }
+    }
+
+    fn request_body(text: &str, source: Option<&str>, target: &str) -> Result<JsonValue> {
+        let source =
+            source.ok_or_else(|| anyhow!("NLLB translation requires a known source language"))?;
+        let source_code =
+            nllb_code(source).ok_or_else(|| anyhow!("NLLB does not support {source}"))?;
+        let target_code =
+            nllb_code(target).ok_or_else(|| anyhow!("NLLB does not support {target}"))?;
+        Ok(json!({
+            "text": text,
+            "source_lang": source_code,
+            "target_lang": target_code,
+        }))
+    }
+
+    fn translate_blocking(config: &NllbConfig, body: &JsonValue) -> Result<String> {
+        let response = ureq::post(&config.endpoint)
+            .timeout(config.timeout)
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map_err(|err| anyhow!("NLLB translation request failed: {err}"))?;
+        let body: JsonValue = serde_json::from_str(
+            &response
+                .into_string()
+                .map_err(|err| anyhow!("failed to read NLLB response: {err}"))?,
+        )
+        .map_err(|err| anyhow!("failed to parse NLLB response: {err}"))?;
+        body["translation"]
+            .as_str()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .ok_or_else(|| anyhow!("NLLB response carried no translation"))
+    }
+}
+
+#[async_trait]
+impl Translator for NllbTranslator {
+    async fn translate(&self, text: &str, source: Option<&str>, target: &str) -> Result<String> {
+        let body = Self::request_body(text, source, target)?;
+        let config = self.config.clone();
+        tokio::task::spawn_blocking(move || Self::translate_blocking(&config, &body))
+            .await
+            .map_err(|err| anyhow!("translation task failed: {err}"))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_locales_to_nllb_codes() {
+        assert_eq!(nllb_code("zh-CN"), Some("zho_Hans"));
+        assert_eq!(nllb_code("zh-Hant-TW"), Some("zho_Hant"));
+        assert_eq!(nllb_code("en_US"), Some("eng_Latn"));
+        assert_eq!(nllb_code("tlh"), None);
+        assert!(same_language(Some("en"), "en-GB"));
+        assert!(!same_language(None, "en"));
+
+        let body = NllbTranslator::request_body("你好", Some("zh"), "en-US").unwrap();
+        assert_eq!(body["source_lang"], "zho_Hans");
+        assert_eq!(body["target_lang"], "eng_Latn");
+        assert!(NllbTranslator::request_body("你好", None, "en").is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! 自定义词表：向支持短语增强的引擎下发提示词，并对低置信度词做模糊纠正。
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// 纠正时向后合并的最大词数，用于把 "git hub" 这类被拆开的词还原为 "GitHub"。
+const MAX_WINDOW_WORDS: usize = 3;
+/// 规范化后短于该长度的词条只接受精确匹配，避免误改常用短词。
+const MIN_FUZZY_LEN: usize = 5;
+/// 每多少个字符允许一次编辑。
+const CHARS_PER_EDIT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VocabularyKind {
+    Term,
+    Acronym,
+    Name,
+}
+
+impl VocabularyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VocabularyKind::Term => "term",
+            VocabularyKind::Acronym => "acronym",
+            VocabularyKind::Name => "name",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "term" => Some(VocabularyKind::Term),
+            "acronym" => Some(VocabularyKind::Acronym),
+            "name" => Some(VocabularyKind::Name),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VocabularyTerm {
+    pub term: String,
+    pub kind: VocabularyKind,
+    /// 短语增强权重，1.0 为默认值。
+    pub boost: f32,
+    #[serde(default)]
+    pub created_at_ms: i64,
+}
+
+impl VocabularyTerm {
+    pub fn new(term: impl Into<String>, kind: VocabularyKind) -> Self {
+        Self {
+            term: term.into().trim().to_string(),
+            kind,
+            boost: 1.0,
+            created_at_ms: 0,
+        }
+    }
+}
+
+/// 下发给引擎的短语增强提示。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhraseHint {
+    pub phrase: String,
+    pub boost: f32,
+}
+
+/// 引擎输出的单个词及其置信度；不提供置信度的引擎为 `None`。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredToken {
+    pub text: String,
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScoredTranscript {
+    pub text: String,
+    pub tokens: Vec<ScoredToken>,
+}
+
+impl ScoredTranscript {
+    /// 按空白切分文本，所有词的置信度均未知。
+    pub fn unscored(text: String) -> Self {
+        let tokens = text
+            .split_whitespace()
+            .map(|token| ScoredToken {
+                text: token.to_string(),
+                confidence: None,
+            })
+            .collect();
+        Self { text, tokens }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vocabulary {
+    terms: Vec<VocabularyTerm>,
+}
+
+impl Vocabulary {
+    /// 丢弃空词条，并按规范化形式去重（保留先出现的写法）。
+    pub fn new(terms: Vec<VocabularyTerm>) -> Self {
+        let mut seen = HashSet::new();
+        let terms = terms
+            .into_iter()
+            .filter(|term| {
+                let key = normalize(&term.term);
+                !key.is_empty() && seen.insert(key)
+            })
+            .collect();
+        Self { terms }
+    }
+
+    pub fn terms(&self) -> &[VocabularyTerm] {
+        &self.terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// 按权重从高到低排列的提示词。
+    pub fn phrase_hints(&self) -> Vec<PhraseHint> {
+        let mut hints: Vec<PhraseHint> = self
+            .terms
+            .iter()
+            .map(|term| PhraseHint {
+                phrase: term.term.clone(),
+                boost: term.boost,
+            })
+            .collect();
+        hints.sort_by(|a, b| b.boost.total_cmp(&a.boost));
+        hints
+    }
+}
+
+/// 将低置信度（或置信度未知）的词与词表做模糊匹配并替换为词表中的写法。
+#[derive(Debug, Clone)]
+pub struct VocabularyCorrector {
+    entries: Vec<(String, String)>,
+    min_confidence: f32,
+}
+
+impl VocabularyCorrector {
+    pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.6;
+
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let entries = vocabulary
+            .terms()
+            .iter()
+            .map(|term| (normalize(&term.term), term.term.clone()))
+            .collect();
+        Self {
+            entries,
+            min_confidence: Self::DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    pub fn correct(&self, transcript: &ScoredTranscript) -> String {
+        if self.entries.is_empty() || transcript.tokens.is_empty() {
+            return transcript.text.clone();
+        }
+
+        let tokens = &transcript.tokens;
+        let mut output: Vec<String> = Vec::with_capacity(tokens.len());
+        let mut changed = false;
+        let mut index = 0;
+        while index < tokens.len() {
+            match self.best_match(&tokens[index..]) {
+                Some((width, replacement)) => {
+                    let original = tokens[index..index + width]
+                        .iter()
+                        .map(|token| token.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    changed |= original != replacement;
+                    output.push(replacement);
+                    index += width;
+                }
+                None => {
+                    output.push(tokens[index].text.clone());
+                    index += 1;
+                }
+            }
+        }
+
+        if changed {
+            output.join(" ")
+        } else {
+            transcript.text.clone()
+        }
+    }
+
+    fn is_uncertain(&self, token: &ScoredToken) -> bool {
+        token
+            .confidence
+            .map(|confidence| confidence < self.min_confidence)
+            .unwrap_or(true)
+    }
+
+    /// 返回匹配的词数及替换文本，优先更长的窗口和更小的编辑距离。
+    fn best_match(&self, tokens: &[ScoredToken]) -> Option<(usize, String)> {
+        for width in (1..=MAX_WINDOW_WORDS.min(tokens.len())).rev() {
+            let window = &tokens[..width];
+            if !window.iter().any(|token| self.is_uncertain(token)) {
+                continue;
+            }
+
+            let first = &window[0].text;
+            let last = &window[width - 1].text;
+            let prefix: String = first.chars().take_while(|c| !c.is_alphanumeric()).collect();
+            let suffix: String = {
+                let mut tail: Vec<char> = last
+                    .chars()
+                    .rev()
+                    .take_while(|c| !c.is_alphanumeric())
+                    .collect();
+                tail.reverse();
+                tail.into_iter().collect()
+            };
+            let candidate: String = window.iter().map(|token| normalize(&token.text)).collect();
+            if candidate.is_empty() {
+                continue;
+            }
+
+            let best = self
+                .entries
+                .iter()
+                .filter_map(|(normalized, term)| {
+                    let allowed = if normalized.chars().count() < MIN_FUZZY_LEN {
+                        0
+                    } else {
+                        normalized.chars().count() / CHARS_PER_EDIT
+                    };
+                    let distance = levenshtein(&candidate, normalized);
+                    (distance <= allowed).then_some((distance, term))
+                })
+                .min_by_key(|(distance, _)| *distance);
+
+            if let Some((_, term)) = best {
+                return Some((width, format!("{prefix}{term}{suffix}")));
+            }
+        }
+        None
+    }
+}
+
+/// 小写并去掉非字母数字字符。
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary() -> Vocabulary {
+        Vocabulary::new(vec![
+            VocabularyTerm::new("Flowwisper", VocabularyKind::Name),
+            VocabularyTerm::new("GitHub", VocabularyKind::Name),
+            VocabularyTerm::new("SQLCipher", VocabularyKind::Term),
+            VocabularyTerm::new("Rust", VocabularyKind::Term),
+            VocabularyTerm::new("github", VocabularyKind::Name),
+        ])
+    }
+
+    #[test]
+    fn corrects_uncertain_tokens_against_vocabulary() {
+        let vocabulary = vocabulary();
+        assert_eq!(vocabulary.terms().len(), 4);
+        let corrector = VocabularyCorrector::new(&vocabulary);
+
+        let corrected = corrector.correct(&ScoredTranscript::unscored(
+            "open flow whisper and push to git hub, then sqlcypher.".into(),
+        ));
+        assert_eq!(
+            corrected,
+            "open Flowwisper and push to GitHub, then SQLCipher."
+        );
+
+        // 短词只接受精确匹配。
+        let untouched = "just trust it".to_string();
+        assert_eq!(
+            corrector.correct(&ScoredTranscript::unscored(untouched.clone())),
+            untouched
+        );
+    }
+
+    #[test]
+    fn keeps_confident_tokens() {
+        let corrector = VocabularyCorrector::new(&vocabulary());
+        let transcript = ScoredTranscript {
+            text: "flow whisper".into(),
+            tokens: vec![
+                ScoredToken {
+                    text: "flow".into(),
+                    confidence: Some(0.95),
+                },
+                ScoredToken {
+                    text: "whisper".into(),
+                    confidence: Some(0.9),
+                },
+            ],
+        };
+        assert_eq!(corrector.correct(&transcript), "flow whisper");
+        let strict = corrector.with_min_confidence(0.92);
+        assert_eq!(strict.correct(&transcript), "Flowwisper");
+    }
+}
@@ -0,0 +1,327 @@
+//! 引擎预热状态：本地模型预载与云端待命连接，供界面在用户开口前显示"引擎就绪"。
+
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use super::{CloudFeature, EngineOrchestrator};
+
+/// 云端待命连接的默认保活间隔，应短于服务端的空闲断开时间。
+pub const CLOUD_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(45);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupState {
+    Cold,
+    Warming,
+    Ready,
+    Failed,
+    /// 未配置该引擎，或离线、隐私开关禁止连接云端。
+    Skipped,
+}
+
+impl WarmupState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarmupState::Cold => "cold",
+            WarmupState::Warming => "warming",
+            WarmupState::Ready => "ready",
+            WarmupState::Failed => "failed",
+            WarmupState::Skipped => "skipped",
+        }
+    }
+
+    /// 处于该状态的引擎需要（再次）预热。
+    fn needs_warmup(&self) -> bool {
+        matches!(self, WarmupState::Cold | WarmupState::Failed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineWarmupStatus {
+    pub local: WarmupState,
+    pub cloud: WarmupState,
+    /// 最近一次预热失败的原因。
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub updated_at: SystemTime,
+}
+
+impl Default for EngineWarmupStatus {
+    fn default() -> Self {
+        Self {
+            local: WarmupState::Cold,
+            cloud: WarmupState::Cold,
+            error: None,
+            updated_at: SystemTime::now(),
+        }
+    }
+}
+
+impl EngineWarmupStatus {
+    /// 本地引擎可用即视为就绪，云端只是加速项。
+    pub fn is_ready(&self) -> bool {
+        self.local == WarmupState::Ready
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WarmupTarget {
+    Local,
+    Cloud,
+}
+
+/// 以 watch 通道广播预热状态，订阅方只关心最新值。
+#[derive(Debug)]
+pub(crate) struct WarmupTracker {
+    tx: watch::Sender<EngineWarmupStatus>,
+    cloud_warmed_at: std::sync::Mutex<Option<Instant>>,
+}
+
+impl Default for WarmupTracker {
+    fn default() -> Self {
+        let (tx, _) = watch::channel(EngineWarmupStatus::default());
+        Self {
+            tx,
+            cloud_warmed_at: std::sync::Mutex::new(None),
+        }
+    }
+}
+
+impl WarmupTracker {
+    pub(crate) fn status(&self) -> EngineWarmupStatus {
+        self.tx.borrow().clone()
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<EngineWarmupStatus> {
+        self.tx.subscribe()
+    }
+
+    pub(crate) fn state(&self, target: WarmupTarget) -> WarmupState {
+        let status = self.tx.borrow();
+        match target {
+            WarmupTarget::Local => status.local,
+            WarmupTarget::Cloud => status.cloud,
+        }
+    }
+
+    pub(crate) fn needs_warmup(&self, target: WarmupTarget) -> bool {
+        self.state(target).needs_warmup()
+    }
+
+    /// 云端连接距上次预热超过 `interval` 时需要保活；从未连上过的也算。
+    pub(crate) fn cloud_stale(&self, interval: Duration) -> bool {
+        self.cloud_warmed_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_none_or(|at| at.elapsed() >= interval)
+    }
+
+    /// 状态未变时不广播，避免保活在界面上反复闪烁。
+    pub(crate) fn set(&self, target: WarmupTarget, state: WarmupState, error: Option<String>) {
+        if target == WarmupTarget::Cloud {
+            let mut warmed_at = self
+                .cloud_warmed_at
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            *warmed_at = (state == WarmupState::Ready).then(Instant::now);
+        }
+        self.tx.send_if_modified(|status| {
+            let slot = match target {
+                WarmupTarget::Local => &mut status.local,
+                WarmupTarget::Cloud => &mut status.cloud,
+            };
+            if *slot == state && (error.is_none() || status.error == error) {
+                return false;
+            }
+            *slot = state;
+            if error.is_some() || state == WarmupState::Ready {
+                status.error = error;
+            }
+            status.updated_at = SystemTime::now();
+            true
+        });
+    }
+}
+
+impl EngineOrchestrator {
+    /// 预热本地与云端引擎。本地失败时返回错误；云端失败只记录状态，会话照常回落本地。
+    pub async fn warmup(&self) -> Result<()> {
+        info!(
+            target: "engine_orchestrator",
+            prefer_cloud = self.config.prefer_cloud,
+            "warming up engines"
+        );
+        let local = self.warm_local().await;
+        self.warm_cloud().await;
+        local
+    }
+
+    /// 仅预热尚未就绪的引擎，供按下热键、预录开始时调用；已就绪时立即返回。
+    pub async fn ensure_warm(&self) -> Result<()> {
+        let local = if self.warmup.needs_warmup(WarmupTarget::Local) {
+            self.warm_local().await
+        } else {
+            Ok(())
+        };
+        if self.warmup.needs_warmup(WarmupTarget::Cloud) {
+            self.warm_cloud().await;
+        }
+        local
+    }
+
+    /// 云端待命连接超过 `interval` 未使用时重新预热，由宿主定时调用。
+    pub async fn keep_cloud_warm(&self, interval: Duration) {
+        if self.warmup.state(WarmupTarget::Cloud) == WarmupState::Warming
+            || !self.warmup.cloud_stale(interval)
+        {
+            return;
+        }
+        self.warm_cloud().await;
+    }
+
+    pub fn warmup_status(&self) -> EngineWarmupStatus {
+        self.warmup.status()
+    }
+
+    pub fn subscribe_warmup(&self) -> tokio::sync::watch::Receiver<EngineWarmupStatus> {
+        self.warmup.subscribe()
+    }
+
+    async fn warm_local(&self) -> Result<()> {
+        self.warmup
+            .set(WarmupTarget::Local, WarmupState::Warming, None);
+        let started = Instant::now();
+        match self.local_engine.warmup().await {
+            Ok(()) => {
+                info!(
+                    target: "engine_orchestrator",
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "local engine ready"
+                );
+                self.warmup
+                    .set(WarmupTarget::Local, WarmupState::Ready, None);
+                Ok(())
+            }
+            Err(err) => {
+                warn!(target: "engine_orchestrator", %err, "local engine warmup failed");
+                self.warmup.set(
+                    WarmupTarget::Local,
+                    WarmupState::Failed,
+                    Some(err.to_string()),
+                );
+                Err(err)
+            }
+        }
+    }
+
+    async fn warm_cloud(&self) {
+        let Some(cloud) = &self.cloud_engine else {
+            self.warmup
+                .set(WarmupTarget::Cloud, WarmupState::Skipped, None);
+            return;
+        };
+        if let Some(block) = self
+            .offline_guard
+            .feature_block(CloudFeature::Transcription)
+        {
+            info!(
+                target: "engine_orchestrator",
+                reason = block.as_str(),
+                "cloud engine warmup skipped"
+            );
+            self.warmup
+                .set(WarmupTarget::Cloud, WarmupState::Skipped, None);
+            return;
+        }
+        self.warmup
+            .set(WarmupTarget::Cloud, WarmupState::Warming, None);
+        match cloud.warmup().await {
+            Ok(()) => self
+                .warmup
+                .set(WarmupTarget::Cloud, WarmupState::Ready, None),
+            Err(err) => {
+                warn!(target: "engine_orchestrator", %err, "cloud engine warmup failed");
+                self.warmup.set(
+                    WarmupTarget::Cloud,
+                    WarmupState::Failed,
+                    Some(err.to_string()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, SpeechEngine};
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct WarmupEngine {
+        warmups: AtomicUsize,
+        fail: bool,
+    }
+
+    impl WarmupEngine {
+        fn new(fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                warmups: AtomicUsize::new(0),
+                fail,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl SpeechEngine for WarmupEngine {
+        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
+            Ok(String::new())
+        }
+
+        async fn warmup(&self) -> Result<()> {
+            self.warmups.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(anyhow!("connection refused"));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn warmup_reports_engine_states_and_skips_ready_engines() {
+        let local = WarmupEngine::new(false);
+        let cloud = WarmupEngine::new(true);
+        let orchestrator = EngineOrchestrator::with_engines(
+            EngineConfig { prefer_cloud: true },
+            local.clone(),
+            Some(cloud.clone()),
+        );
+        let status_rx = orchestrator.subscribe_warmup();
+        assert_eq!(orchestrator.warmup_status().local, WarmupState::Cold);
+
+        orchestrator.warmup().await.expect("local warmup succeeds");
+        assert!(status_rx.has_changed().unwrap());
+        let status = orchestrator.warmup_status();
+        assert!(status.is_ready());
+        assert_eq!(status.cloud, WarmupState::Failed);
+        assert_eq!(status.error.as_deref(), Some("connection refused"));
+
+        // 按下热键时只补热失败的云端，本地不再重复预热。
+        orchestrator.ensure_warm().await.expect("local stays ready");
+        assert_eq!(local.warmups.load(Ordering::SeqCst), 1);
+        assert_eq!(cloud.warmups.load(Ordering::SeqCst), 2);
+
+        orchestrator.offline_guard().set_local_only(true);
+        orchestrator.keep_cloud_warm(Duration::ZERO).await;
+        assert_eq!(orchestrator.warmup_status().cloud, WarmupState::Skipped);
+        assert_eq!(cloud.warmups.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,545 @@
+//! 基于 whisper.cpp 的本地识别引擎，仅在启用 `local-asr` 特性时编译。
+
+use super::*;
+use anyhow::{anyhow, Context, Result as AnyhowResult};
+use dirs::data_dir;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::mem::transmute;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicI32;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+use crate::models::{ModelEngine, ModelManager};
+
+const DEFAULT_MODEL_URL: &str =
+    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin";
+const DEFAULT_MODEL_FILENAME: &str = "ggml-base.en.bin";
+/// Whisper 提示词上限为文本上下文窗口的一半。
+const MAX_PROMPT_TOKENS: usize = 224;
+
+pub struct WhisperLocalEngine {
+    context: Arc<WhisperContext>,
+    streaming: Arc<Mutex<StreamingState>>,
+    /// 全速档的线程数；`threads` 随电源调节器的档位缩减。
+    base_threads: i32,
+    threads: AtomicI32,
+}
+
+/// 预热解码的静音长度：16 kHz 下 1 秒，Whisper 最短的有效输入。
+const WARMUP_SAMPLES: usize = 16_000;
+
+impl WhisperLocalEngine {
+    pub fn from_env(tuning: &EngineTuning) -> Result<Self> {
+        let model_path = resolve_or_fetch_model()?;
+        Self::with_tuning(model_path, tuning)
+    }
+
+    pub fn from_model_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_tuning(path, &EngineTuning::select(&HardwareProfile::probe()))
+    }
+
+    /// whisper.cpp 不支持按层卸载，卸载层数非 0 即整模型上 GPU；未编译 GPU 后端时忽略。
+    pub fn with_tuning<P: AsRef<Path>>(path: P, tuning: &EngineTuning) -> Result<Self> {
+        let path_ref = path.as_ref();
+        let path_str = path_ref
+            .to_str()
+            .ok_or_else(|| anyhow!("模型路径不是有效的 UTF-8"))?;
+        let mut params = WhisperContextParameters::default();
+        params.use_gpu(tuning.uses_gpu());
+        let context = Arc::new(WhisperContext::new_with_params(path_str, params)?);
+        let state = unsafe {
+            transmute::<WhisperState<'_>, WhisperState<'static>>(context.create_state()?)
+        };
+        Ok(Self {
+            context: Arc::clone(&context),
+            streaming: Arc::new(Mutex::new(StreamingState::new(state))),
+            base_threads: tuning.threads.clamp(1, i32::MAX as usize) as i32,
+            threads: AtomicI32::new(tuning.threads.clamp(1, i32::MAX as usize) as i32),
+        })
+    }
+}
+
+fn resolve_or_fetch_model() -> AnyhowResult<PathBuf> {
+    if let Ok(path) = std::env::var("WHISPER_MODEL_PATH") {
+        let path_buf = PathBuf::from(path);
+        if path_buf.is_file() {
+            return Ok(path_buf);
+        }
+        return Err(anyhow!(
+            "WHISPER_MODEL_PATH points to a missing file: {}",
+            path_buf.display()
+        ));
+    }
+
+    // 模型管理器中选定的模型优先于默认下载。
+    if let Some(path) = ModelManager::default_dir()
+        .map(ModelManager::new)
+        .and_then(|models| models.active_path(ModelEngine::Whisper))
+    {
+        std::env::set_var("WHISPER_MODEL_PATH", &path);
+        return Ok(path);
+    }
+
+    if !auto_download_enabled() {
+        warn!(
+            target: "engine_orchestrator",
+            "WHISPER_MODEL_PATH missing and auto-download disabled"
+        );
+        return Err(anyhow!(
+            "WHISPER_MODEL_PATH is not set and auto-download is disabled"
+        ));
+    }
+
+    let cache_path = default_model_path()?;
+    if cache_path.is_file() {
+        std::env::set_var("WHISPER_MODEL_PATH", &cache_path);
+        return Ok(cache_path);
+    }
+
+    let url = std::env::var("WHISPER_MODEL_URL").unwrap_or_else(|_| DEFAULT_MODEL_URL.into());
+    download_model(&cache_path, &url)?;
+    std::env::set_var("WHISPER_MODEL_PATH", &cache_path);
+    Ok(cache_path)
+}
+
+fn default_model_path() -> AnyhowResult<PathBuf> {
+    if let Ok(dir) = std::env::var("FLOWWISPER_MODEL_DIR") {
+        let path = PathBuf::from(dir).join(DEFAULT_MODEL_FILENAME);
+        ensure_parent_dir(&path)?;
+        return Ok(path);
+    }
+
+    let base_dir = data_dir()
+        .map(|dir| dir.join("Flowwisper").join("models"))
+        .ok_or_else(|| anyhow!("failed to determine default model cache directory"))?;
+    let path = base_dir.join(DEFAULT_MODEL_FILENAME);
+    ensure_parent_dir(&path)?;
+    Ok(path)
+}
+
+fn ensure_parent_dir(path: &Path) -> AnyhowResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "failed to create whisper model directory: {}",
+                parent.display()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn download_model(path: &Path, url: &str) -> AnyhowResult<()> {
+    info!(
+        target: "engine_orchestrator",
+        %url,
+        path = %path.display(),
+        "downloading whisper model"
+    );
+
+    let temp_path = path.with_extension("download");
+    let response = ureq::get(url)
+        .call()
+        .map_err(|err| anyhow!("failed to download whisper model: {err}"))?;
+
+    if !(200..300).contains(&response.status()) {
+        return Err(anyhow!(
+            "failed to download whisper model: received HTTP status {}",
+            response.status()
+        ));
+    }
+
+    let mut reader = response.into_reader();
+    let file = File::create(&temp_path).with_context(|| {
+        format!(
+            "failed to create temporary whisper model file: {}",
+            temp_path.display()
+        )
+    })?;
+    let mut writer = BufWriter::new(file);
+
+    std::io::copy(&mut reader, &mut writer)
+        .with_context(|| format!("failed to write whisper model to {}", temp_path.display()))?;
+    writer
+        .flush()
+        .context("failed to flush whisper model to disk")?;
+
+    fs::rename(&temp_path, path).with_context(|| {
+        format!(
+            "failed to finalize whisper model download to {}",
+            path.display()
+        )
+    })?;
+
+    info!(
+        target: "engine_orchestrator",
+        path = %path.display(),
+        "whisper model ready"
+    );
+
+    Ok(())
+}
+
+fn auto_download_enabled() -> bool {
+    if let Ok(value) = std::env::var("WHISPER_DISABLE_AUTO_DOWNLOAD") {
+        return !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        );
+    }
+
+    if let Ok(value) = std::env::var("WHISPER_AUTO_DOWNLOAD") {
+        return matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        );
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Mutex, OnceLock};
+    use std::thread;
+    use tempfile::tempdir;
+
+    fn env_guard() -> &'static Mutex<()> {
+        static GUARD: OnceLock<Mutex<()>> = OnceLock::new();
+        GUARD.get_or_init(|| Mutex::new(()))
+    }
+
+    fn reset_env() {
+        std::env::remove_var("FLOWWISPER_MODEL_DIR");
+        std::env::remove_var("WHISPER_MODEL_PATH");
+        std::env::remove_var("WHISPER_MODEL_URL");
+        std::env::remove_var("WHISPER_AUTO_DOWNLOAD");
+        std::env::remove_var("WHISPER_DISABLE_AUTO_DOWNLOAD");
+    }
+
+    #[test]
+    fn downloads_model_when_missing() {
+        let _lock = env_guard().lock().expect("env guard poisoned");
+        reset_env();
+
+        let directory = tempdir().expect("tempdir should create model cache");
+        std::env::set_var("FLOWWISPER_MODEL_DIR", directory.path());
+
+        let payload = b"fake-whisper-model".to_vec();
+        let expected = payload.clone();
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind ephemeral port");
+        let address = listener.local_addr().expect("local addr available");
+        let handle = thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buffer = [0_u8; 512];
+                let _ = stream.read(&mut buffer);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    payload.len()
+                );
+                stream
+                    .write_all(response.as_bytes())
+                    .expect("response headers written");
+                stream.write_all(&payload).expect("response body written");
+            }
+        });
+
+        std::env::set_var(
+            "WHISPER_MODEL_URL",
+            format!("http://{address}/{}", DEFAULT_MODEL_FILENAME),
+        );
+
+        let model_path = resolve_or_fetch_model().expect("model should download");
+        handle.join().expect("http server thread joined");
+
+        assert_eq!(std::fs::read(&model_path).expect("read model"), expected);
+        assert_eq!(
+            std::env::var("WHISPER_MODEL_PATH").expect("model path exported"),
+            model_path.to_string_lossy()
+        );
+
+        reset_env();
+    }
+
+    #[test]
+    fn reuses_cached_model_when_present() {
+        let _lock = env_guard().lock().expect("env guard poisoned");
+        reset_env();
+
+        let directory = tempdir().expect("tempdir should create model cache");
+        let cached_path = directory.path().join(DEFAULT_MODEL_FILENAME);
+        std::fs::write(&cached_path, b"existing-model").expect("write cached whisper model");
+
+        std::env::set_var("FLOWWISPER_MODEL_DIR", directory.path());
+        std::env::set_var("WHISPER_MODEL_URL", "http://127.0.0.1:1/never-used");
+
+        let model_path = resolve_or_fetch_model().expect("should reuse cached model");
+
+        assert_eq!(model_path, cached_path);
+        assert_eq!(
+            std::fs::read(&model_path).expect("read cached model"),
+            b"existing-model"
+        );
+        assert_eq!(
+            std::env::var("WHISPER_MODEL_PATH").expect("model path exported"),
+            model_path.to_string_lossy()
+        );
+
+        reset_env();
+    }
+}
+
+struct StreamingState {
+    state: WhisperState<'static>,
+    tail: Vec<f32>,
+    pending: Vec<f32>,
+    emitted: String,
+    prompt: String,
+    prompt_tokens: Vec<std::os::raw::c_int>,
+    language: Option<String>,
+    lookback_samples: usize,
+    sample_rate: usize,
+    min_stride_samples: usize,
+    max_stride_samples: usize,
+}
+
+impl StreamingState {
+    fn new(state: WhisperState<'static>) -> Self {
+        const SAMPLE_RATE: usize = 16_000;
+        const LOOKBACK_MS: usize = 240;
+        const MIN_STRIDE_MS: usize = 80;
+        const MAX_STRIDE_MS: usize = 200;
+        let lookback_samples = (SAMPLE_RATE * LOOKBACK_MS + 999) / 1_000;
+        let min_stride_samples = (SAMPLE_RATE * MIN_STRIDE_MS + 999) / 1_000;
+        let max_stride_samples = (SAMPLE_RATE * MAX_STRIDE_MS + 999) / 1_000;
+        Self {
+            state,
+            tail: Vec::with_capacity(lookback_samples),
+            pending: Vec::with_capacity(max_stride_samples),
+            emitted: String::new(),
+            prompt: String::new(),
+            prompt_tokens: Vec::new(),
+            language: None,
+            lookback_samples,
+            sample_rate: SAMPLE_RATE,
+            min_stride_samples,
+            max_stride_samples,
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechEngine for WhisperLocalEngine {
+    async fn transcribe(&self, frame: &[f32]) -> Result<String> {
+        self.decode(frame, &[]).await
+    }
+
+    /// 词表以初始提示词的形式注入解码器；Whisper 不区分权重，按权重顺序截断。
+    async fn transcribe_scored(
+        &self,
+        frame: &[f32],
+        hints: &[PhraseHint],
+    ) -> Result<ScoredTranscript> {
+        self.decode(frame, hints)
+            .await
+            .map(ScoredTranscript::unscored)
+    }
+
+    /// 使用 Whisper 内置的语种识别；仅多语种模型有意义，`.en` 模型总是返回英语。
+    async fn detect_language(&self, samples: &[f32]) -> Result<Option<LanguageGuess>> {
+        if samples.is_empty() {
+            return Ok(None);
+        }
+        let pcm = samples.to_vec();
+        let streaming = Arc::clone(&self.streaming);
+        let threads = self.threads.load(Ordering::Relaxed) as usize;
+        tokio::task::spawn_blocking(move || {
+            let mut guard = streaming
+                .lock()
+                .expect("whisper streaming state lock poisoned");
+            guard.state.pcm_to_mel(&pcm, threads)?;
+            let probabilities = guard.state.lang_detect(0, threads)?;
+            let best = probabilities
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+            Ok(best.and_then(|(id, probability)| {
+                whisper_rs::get_lang_str(id as i32)
+                    .map(|language| LanguageGuess::new(language, *probability))
+            }))
+        })
+        .await?
+    }
+
+    async fn set_language(&self, language: &str) -> Result<()> {
+        let mut guard = self
+            .streaming
+            .lock()
+            .expect("whisper streaming state lock poisoned");
+        if guard.language.as_deref() != Some(language) {
+            guard.language = Some(language.to_string());
+            // 新语言与旧上下文无关，清空去重缓存与回看窗口。
+            guard.emitted.clear();
+            guard.tail.clear();
+        }
+        Ok(())
+    }
+
+    /// 用独立的解码状态跑一段静音，完成权重分页与计算图分配，不影响流式上下文。
+    async fn warmup(&self) -> Result<()> {
+        let context = Arc::clone(&self.context);
+        let threads = self.threads.load(Ordering::Relaxed);
+        tokio::task::spawn_blocking(move || {
+            let mut state = context.create_state()?;
+            let mut params = FullParams::new(SamplingStrategy::default());
+            params.set_n_threads(threads);
+            params.set_single_segment(true);
+            params.set_no_context(true);
+            params.set_print_realtime(false);
+            params.set_print_progress(false);
+            let silence = vec![0.0_f32; WARMUP_SAMPLES];
+            state.full(params, &silence)?;
+            Ok(())
+        })
+        .await?
+    }
+
+    fn set_performance(&self, level: PerformanceLevel) {
+        let threads = level.scale_threads(self.base_threads as usize) as i32;
+        self.threads.store(threads, Ordering::Relaxed);
+    }
+}
+
+impl WhisperLocalEngine {
+    async fn decode(&self, frame: &[f32], hints: &[PhraseHint]) -> Result<String> {
+        if frame.is_empty() {
+            return Ok(String::new());
+        }
+
+        let pcm: Vec<f32> = frame.to_vec();
+        let speechy = frame_rms(frame) >= SPEECH_RMS_THRESHOLD;
+        let streaming = Arc::clone(&self.streaming);
+        let context = Arc::clone(&self.context);
+        let threads = self.threads.load(Ordering::Relaxed);
+        let prompt = hints
+            .iter()
+            .map(|hint| hint.phrase.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        tokio::task::spawn_blocking(move || {
+            let mut guard = streaming
+                .lock()
+                .expect("whisper streaming state lock poisoned");
+
+            if guard.prompt != prompt {
+                guard.prompt_tokens = if prompt.is_empty() {
+                    Vec::new()
+                } else {
+                    // 词元数不超过字节数，先完整切分再按权重顺序截断。
+                    let mut tokens = context.tokenize(&prompt, prompt.len() + 1)?;
+                    tokens.truncate(MAX_PROMPT_TOKENS);
+                    tokens
+                };
+                guard.prompt = prompt;
+            }
+            let prompt_tokens = guard.prompt_tokens.clone();
+            let language = guard.language.clone();
+
+            guard.pending.extend_from_slice(&pcm);
+
+            let should_decode = if speechy {
+                guard.pending.len() >= guard.min_stride_samples
+            } else {
+                guard.pending.len() >= guard.max_stride_samples
+            };
+
+            if !should_decode {
+                return Ok(String::new());
+            }
+
+            let mut decode_window = Vec::with_capacity(guard.tail.len() + guard.pending.len());
+            decode_window.extend_from_slice(&guard.tail);
+            decode_window.extend_from_slice(&guard.pending);
+
+            if decode_window.is_empty() {
+                return Ok(String::new());
+            }
+
+            let mut params = FullParams::new(SamplingStrategy::default());
+            params.set_n_threads(threads);
+            params.set_translate(false);
+            params.set_single_segment(true);
+            params.set_temperature(0.0);
+            params.set_no_context(false);
+            params.set_print_realtime(false);
+            params.set_print_progress(false);
+            if !prompt_tokens.is_empty() {
+                params.set_tokens(&prompt_tokens);
+            }
+            if language.is_some() {
+                params.set_language(language.as_deref());
+            }
+
+            let duration_ms = ((decode_window.len() * 1_000) / guard.sample_rate).max(1) as i32;
+            params.set_duration_ms(duration_ms);
+
+            guard.state.full(params, &decode_window)?;
+            guard.pending.clear();
+
+            let tail_len = guard.lookback_samples.min(decode_window.len());
+            guard.tail = decode_window[decode_window.len() - tail_len..].to_vec();
+
+            let mut transcript = String::new();
+            let segments = guard.state.full_n_segments()? as usize;
+            for segment in 0..segments {
+                let text = guard.state.full_get_segment_text(segment as i32)?;
+                let trimmed = text.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if !transcript.is_empty() {
+                    transcript.push(' ');
+                }
+                transcript.push_str(trimmed);
+            }
+
+            let transcript = transcript.trim().to_string();
+            if transcript.is_empty() {
+                return Ok(String::new());
+            }
+
+            let overlap = suffix_prefix_overlap(&guard.emitted, &transcript);
+            let mut delta = transcript[overlap..].trim_start().to_string();
+
+            if delta.is_empty() && guard.emitted.contains(&transcript) {
+                return Ok(String::new());
+            }
+
+            if !delta.is_empty() {
+                if !guard.emitted.is_empty()
+                    && !guard.emitted.ends_with(' ')
+                    && !delta.starts_with(' ')
+                {
+                    guard.emitted.push(' ');
+                }
+                guard.emitted.push_str(&delta);
+            } else {
+                guard.emitted = transcript.clone();
+                delta = transcript;
+            }
+
+            Ok(delta)
+        })
+        .await?
+    }
+}
@@ -0,0 +1,598 @@
+//! 数据外发审计：转写文本或音频每次离开本机（云端识别、云端润色与翻译、Webhook 与历史动作、
+//! 同步与备份上传、会议摘要、遥测上传）都追加一条记录，保存负载摘要与目的地，供合规审查。
+//!
+//! 记录只追加不修改：每条记录的 HMAC 覆盖上一条的 HMAC，链头另存于 `user_settings`，
+//! 中间记录被改动、删除或末尾被截断都会在 [`EgressLog::verify`] 中暴露。
+//! HMAC 密钥保存在密钥库而非数据库中，能改写数据库的人无法重算整条链。
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{digest, hmac};
+use rusqlite::{params, OptionalExtension, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::persistence::sqlite::SqlitePersistence;
+use crate::secrets::SecretStore;
+
+/// 密钥库中保存链式 HMAC 密钥的条目。
+pub const KEY_SECRET: &str = "egress_audit.key";
+/// 早期版本在 `user_settings` 中保存链密钥的键，首次读取时迁入密钥库。
+const LEGACY_KEY_SETTING: &str = "egress_audit_key";
+/// `user_settings` 中保存链头（最后一条的序号与 HMAC）的键。
+const HEAD_SETTING: &str = "egress_audit_head";
+const DEFAULT_QUERY_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressChannel {
+    CloudTranscription,
+    CloudPolish,
+    CloudTranslation,
+    Webhook,
+    HistorySync,
+    Backup,
+    MeetingSummary,
+    TelemetryUpload,
+}
+
+impl EgressChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EgressChannel::CloudTranscription => "cloud_transcription",
+            EgressChannel::CloudPolish => "cloud_polish",
+            EgressChannel::CloudTranslation => "cloud_translation",
+            EgressChannel::Webhook => "webhook",
+            EgressChannel::HistorySync => "history_sync",
+            EgressChannel::Backup => "backup",
+            EgressChannel::MeetingSummary => "meeting_summary",
+            EgressChannel::TelemetryUpload => "telemetry_upload",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "cloud_transcription" => Some(EgressChannel::CloudTranscription),
+            "cloud_polish" => Some(EgressChannel::CloudPolish),
+            "cloud_translation" => Some(EgressChannel::CloudTranslation),
+            "webhook" => Some(EgressChannel::Webhook),
+            "history_sync" => Some(EgressChannel::HistorySync),
+            "backup" => Some(EgressChannel::Backup),
+            "meeting_summary" => Some(EgressChannel::MeetingSummary),
+            "telemetry_upload" => Some(EgressChannel::TelemetryUpload),
+            _ => None,
+        }
+    }
+}
+
+/// 一次外发；负载只保存 SHA-256 摘要与字节数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressEvent {
+    pub channel: EgressChannel,
+    pub destination: String,
+    pub session_id: Option<String>,
+    pub payload_sha256: String,
+    pub payload_bytes: u64,
+    pub recorded_at_ms: i64,
+}
+
+impl EgressEvent {
+    pub fn new(channel: EgressChannel, destination: impl Into<String>, payload: &[u8]) -> Self {
+        Self {
+            channel,
+            destination: destination.into(),
+            session_id: None,
+            payload_sha256: hex(digest::digest(&digest::SHA256, payload).as_ref()),
+            payload_bytes: payload.len() as u64,
+            recorded_at_ms: now_ms(),
+        }
+    }
+
+    pub fn with_session(mut self, session_id: Option<String>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressRecord {
+    pub seq: i64,
+    pub recorded_at_ms: i64,
+    pub channel: EgressChannel,
+    pub destination: String,
+    pub session_id: Option<String>,
+    pub payload_sha256: String,
+    pub payload_bytes: u64,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EgressQuery {
+    pub channel: Option<EgressChannel>,
+    pub session_id: Option<String>,
+    pub since_ms: Option<i64>,
+    pub until_ms: Option<i64>,
+    /// 按时间倒序返回的最大条数，默认 200。
+    pub limit: Option<usize>,
+}
+
+/// 链校验结果；`broken_at` 为第一条校验失败的序号。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressVerification {
+    pub checked: usize,
+    pub broken_at: Option<i64>,
+}
+
+impl EgressVerification {
+    pub fn is_intact(&self) -> bool {
+        self.broken_at.is_none()
+    }
+}
+
+/// 数据库中的外发审计表。
+pub struct EgressLog {
+    sqlite: Arc<SqlitePersistence>,
+    secrets: Arc<dyn SecretStore>,
+    key: Mutex<Option<hmac::Key>>,
+}
+
+impl fmt::Debug for EgressLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EgressLog").finish_non_exhaustive()
+    }
+}
+
+impl EgressLog {
+    pub fn new(sqlite: Arc<SqlitePersistence>, secrets: Arc<dyn SecretStore>) -> Self {
+        Self {
+            sqlite,
+            secrets,
+            key: Mutex::new(None),
+        }
+    }
+
+    /// 读取链密钥，首次使用时生成并存入密钥库；数据库中遗留的旧密钥迁入密钥库后删除。
+    fn key(&self) -> Result<hmac::Key> {
+        let mut cached = self
+            .key
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(key) = cached.as_ref() {
+            return Ok(key.clone());
+        }
+        let encoded = match self.secrets.get(KEY_SECRET)? {
+            Some(encoded) => encoded,
+            None => {
+                let encoded = match self.sqlite.load_user_setting(LEGACY_KEY_SETTING)? {
+                    Some(legacy) => legacy,
+                    None => {
+                        let mut bytes = [0u8; 32];
+                        SystemRandom::new()
+                            .fill(&mut bytes)
+                            .map_err(|_| anyhow!("failed to generate egress audit key"))?;
+                        BASE64.encode(bytes)
+                    }
+                };
+                self.secrets.set(KEY_SECRET, &encoded)?;
+                self.sqlite.connection()?.execute(
+                    "DELETE FROM user_settings WHERE key = ?1",
+                    params![LEGACY_KEY_SETTING],
+                )?;
+                encoded
+            }
+        };
+        let secret = BASE64
+            .decode(encoded.trim())
+            .context("egress audit key is corrupted")?;
+        let key = hmac::Key::new(hmac::HMAC_SHA256, &secret);
+        *cached = Some(key.clone());
+        Ok(key)
+    }
+
+    /// 追加一条记录并前移链头。
+    pub fn append(&self, event: &EgressEvent) -> Result<EgressRecord> {
+        let key = self.key()?;
+        let mut conn = self.sqlite.connection()?;
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("failed to open transaction for egress audit")?;
+        let previous: Option<(i64, String)> = tx
+            .query_row(
+                "SELECT seq, mac FROM egress_audit ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (seq, prev_mac) = match previous {
+            Some((seq, mac)) => (seq + 1, mac),
+            None => (1, String::new()),
+        };
+        let mut record = EgressRecord {
+            seq,
+            recorded_at_ms: event.recorded_at_ms,
+            channel: event.channel,
+            destination: event.destination.clone(),
+            session_id: event.session_id.clone(),
+            payload_sha256: event.payload_sha256.clone(),
+            payload_bytes: event.payload_bytes,
+            mac: String::new(),
+        };
+        record.mac = chain_mac(&key, &prev_mac, &record);
+        tx.execute(
+            "INSERT INTO egress_audit(seq, recorded_at_ms, channel, destination, session_id,
+                payload_sha256, payload_bytes, mac)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                record.seq,
+                record.recorded_at_ms,
+                record.channel.as_str(),
+                record.destination,
+                record.session_id,
+                record.payload_sha256,
+                record.payload_bytes as i64,
+                record.mac,
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO user_settings(key, value, updated_at_ms) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at_ms = excluded.updated_at_ms",
+            params![
+                HEAD_SETTING,
+                format!("{}:{}", record.seq, record.mac),
+                record.recorded_at_ms
+            ],
+        )?;
+        tx.commit()
+            .context("failed to commit egress audit record")?;
+        Ok(record)
+    }
+
+    /// 按条件倒序查询。
+    pub fn query(&self, query: &EgressQuery) -> Result<Vec<EgressRecord>> {
+        let conn = self.sqlite.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT seq, recorded_at_ms, channel, destination, session_id, payload_sha256,
+                payload_bytes, mac
+             FROM egress_audit
+             WHERE (?1 IS NULL OR channel = ?1)
+               AND (?2 IS NULL OR session_id = ?2)
+               AND (?3 IS NULL OR recorded_at_ms >= ?3)
+               AND (?4 IS NULL OR recorded_at_ms <= ?4)
+             ORDER BY seq DESC LIMIT ?5",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                query.channel.map(|channel| channel.as_str()),
+                query.session_id,
+                query.since_ms,
+                query.until_ms,
+                query.limit.unwrap_or(DEFAULT_QUERY_LIMIT) as i64,
+            ],
+            read_record,
+        )?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read egress audit log")
+    }
+
+    /// 从头重算整条链，并与保存的链头比对以发现末尾截断。
+    pub fn verify(&self) -> Result<EgressVerification> {
+        let key = self.key()?;
+        let conn = self.sqlite.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT seq, recorded_at_ms, channel, destination, session_id, payload_sha256,
+                payload_bytes, mac
+             FROM egress_audit ORDER BY seq ASC",
+        )?;
+        let records = stmt
+            .query_map([], read_record)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read egress audit log")?;
+
+        let mut prev_mac = String::new();
+        let mut expected_seq = 1;
+        for (index, record) in records.iter().enumerate() {
+            if record.seq != expected_seq || chain_mac(&key, &prev_mac, record) != record.mac {
+                return Ok(EgressVerification {
+                    checked: index,
+                    broken_at: Some(record.seq),
+                });
+            }
+            prev_mac = record.mac.clone();
+            expected_seq += 1;
+        }
+        let head = self.sqlite.load_user_setting(HEAD_SETTING)?;
+        let tail = records
+            .last()
+            .map(|record| format!("{}:{}", record.seq, record.mac));
+        Ok(EgressVerification {
+            checked: records.len(),
+            broken_at: (head != tail).then_some(expected_seq),
+        })
+    }
+}
+
+fn read_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<EgressRecord> {
+    let channel: String = row.get(2)?;
+    Ok(EgressRecord {
+        seq: row.get(0)?,
+        recorded_at_ms: row.get(1)?,
+        channel: EgressChannel::parse(&channel).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                2,
+                rusqlite::types::Type::Text,
+                format!("unknown egress channel {channel:?}").into(),
+            )
+        })?,
+        destination: row.get(3)?,
+        session_id: row.get(4)?,
+        payload_sha256: row.get(5)?,
+        payload_bytes: row.get::<_, i64>(6)? as u64,
+        mac: row.get(7)?,
+    })
+}
+
+fn chain_mac(key: &hmac::Key, prev_mac: &str, record: &EgressRecord) -> String {
+    let canonical = format!(
+        "{prev_mac}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+        record.seq,
+        record.recorded_at_ms,
+        record.channel.as_str(),
+        record.destination,
+        record.session_id.as_deref().unwrap_or(""),
+        record.payload_sha256,
+        record.payload_bytes,
+    );
+    hex(hmac::sign(key, canonical.as_bytes()).as_ref())
+}
+
+/// 记录外发的句柄，可在各出口间共享。未开启审计时记录被忽略；
+/// 写入在后台线程完成，不阻塞外发本身。
+#[derive(Clone, Default)]
+pub struct EgressRecorder {
+    sink: Option<Arc<EgressSink>>,
+    session_id: Option<String>,
+}
+
+struct EgressSink {
+    enabled: AtomicBool,
+    tx: mpsc::Sender<EgressEvent>,
+}
+
+impl fmt::Debug for EgressRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EgressRecorder")
+            .field("enabled", &self.is_enabled())
+            .field("session_id", &self.session_id)
+            .finish()
+    }
+}
+
+impl EgressRecorder {
+    /// 启动写入线程；返回的句柄默认关闭，需调用 [`Self::set_enabled`] 开启。
+    pub fn spawn(log: Arc<EgressLog>) -> Self {
+        let (tx, rx) = mpsc::channel::<EgressEvent>();
+        thread::spawn(move || {
+            for event in rx {
+                if let Err(err) = log.append(&event) {
+                    warn!(
+                        target: "egress_audit",
+                        %err,
+                        channel = event.channel.as_str(),
+                        "failed to append egress audit record"
+                    );
+                }
+            }
+        });
+        Self {
+            sink: Some(Arc::new(EgressSink {
+                enabled: AtomicBool::new(false),
+                tx,
+            })),
+            session_id: None,
+        }
+    }
+
+    /// 是否连接了审计表；未连接的句柄无法开启。
+    pub fn is_attached(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        if let Some(sink) = &self.sink {
+            sink.enabled.store(enabled, Ordering::SeqCst);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sink
+            .as_ref()
+            .is_some_and(|sink| sink.enabled.load(Ordering::SeqCst))
+    }
+
+    /// 为之后的记录附加会话 ID。
+    pub fn for_session(&self, session_id: &str) -> Self {
+        Self {
+            sink: self.sink.clone(),
+            session_id: (!session_id.is_empty()).then(|| session_id.to_string()),
+        }
+    }
+
+    pub fn record(&self, channel: EgressChannel, destination: &str, payload: &[u8]) {
+        let Some(sink) = self.sink.as_ref().filter(|_| self.is_enabled()) else {
+            return;
+        };
+        let event =
+            EgressEvent::new(channel, destination, payload).with_session(self.session_id.clone());
+        if sink.tx.send(event).is_err() {
+            warn!(target: "egress_audit", "egress audit writer stopped");
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::{SqliteConfig, SqlitePath};
+    use crate::secrets::EncryptedFileStore;
+    use tempfile::TempDir;
+
+    fn secrets(dir: &TempDir) -> Arc<dyn SecretStore> {
+        Arc::new(EncryptedFileStore::open(&dir.path().join("secrets")).unwrap())
+    }
+
+    /// 内存库的连接池中每条连接各自独立，审计需要跨连接读写，因此使用文件库。
+    fn log() -> (TempDir, Arc<SqlitePersistence>, EgressLog) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = SqliteConfig::memory();
+        config.path = SqlitePath::File(dir.path().join("history.db"));
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(config).unwrap());
+        let log = EgressLog::new(Arc::clone(&sqlite), secrets(&dir));
+        (dir, sqlite, log)
+    }
+
+    #[test]
+    fn chain_key_lives_in_the_secret_store() {
+        let (dir, sqlite, log) = log();
+        let first = log
+            .append(&EgressEvent::new(EgressChannel::Backup, "s3://b", b"x"))
+            .unwrap();
+        assert!(sqlite
+            .load_user_setting(LEGACY_KEY_SETTING)
+            .unwrap()
+            .is_none());
+        assert!(secrets(&dir).get(KEY_SECRET).unwrap().is_some());
+
+        // 新建的日志实例从密钥库取回同一密钥，链仍可校验。
+        let reopened = EgressLog::new(Arc::clone(&sqlite), secrets(&dir));
+        assert_eq!(reopened.verify().unwrap().checked, 1);
+        assert_eq!(
+            reopened.query(&EgressQuery::default()).unwrap(),
+            vec![first]
+        );
+    }
+
+    #[test]
+    fn legacy_key_in_user_settings_moves_to_the_secret_store() {
+        let (dir, sqlite, _) = log();
+        let legacy = BASE64.encode([7u8; 32]);
+        sqlite
+            .store_user_setting(LEGACY_KEY_SETTING, &legacy, 1)
+            .unwrap();
+
+        let log = EgressLog::new(Arc::clone(&sqlite), secrets(&dir));
+        log.append(&EgressEvent::new(
+            EgressChannel::Webhook,
+            "https://hook",
+            b"{}",
+        ))
+        .unwrap();
+        assert_eq!(secrets(&dir).get(KEY_SECRET).unwrap(), Some(legacy));
+        assert!(sqlite
+            .load_user_setting(LEGACY_KEY_SETTING)
+            .unwrap()
+            .is_none());
+        assert!(log.verify().unwrap().is_intact());
+    }
+
+    #[test]
+    fn appends_chained_records_and_filters_queries() {
+        let (_dir, _, log) = log();
+        let polish = EgressEvent::new(EgressChannel::CloudPolish, "https://llm", b"hello")
+            .with_session(Some("s-1".into()));
+        let first = log.append(&polish).unwrap();
+        let second = log
+            .append(&EgressEvent::new(
+                EgressChannel::Webhook,
+                "https://hook",
+                b"{}",
+            ))
+            .unwrap();
+        assert_eq!((first.seq, second.seq), (1, 2));
+        assert_ne!(first.mac, second.mac);
+        assert_eq!(first.payload_bytes, 5);
+        assert_eq!(
+            first.payload_sha256,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        let all = log.query(&EgressQuery::default()).unwrap();
+        assert_eq!(all, vec![second.clone(), first.clone()]);
+        let polish_only = log
+            .query(&EgressQuery {
+                channel: Some(EgressChannel::CloudPolish),
+                session_id: Some("s-1".into()),
+                ..EgressQuery::default()
+            })
+            .unwrap();
+        assert_eq!(polish_only, vec![first]);
+        assert!(log.verify().unwrap().is_intact());
+    }
+
+    #[test]
+    fn verification_detects_tampering_and_truncation() {
+        let (_dir, sqlite, log) = log();
+        for index in 0..3u8 {
+            log.append(&EgressEvent::new(
+                EgressChannel::TelemetryUpload,
+                "https://collector",
+                &[index],
+            ))
+            .unwrap();
+        }
+        let conn = sqlite.connection().unwrap();
+        // 表本身拒绝改写。
+        assert!(conn
+            .execute(
+                "UPDATE egress_audit SET destination = 'x' WHERE seq = 2",
+                []
+            )
+            .is_err());
+        assert!(conn
+            .execute("DELETE FROM egress_audit WHERE seq = 3", [])
+            .is_err());
+
+        // 绕过触发器截断末尾后，链头不再匹配。
+        conn.execute_batch(
+            "DROP TRIGGER egress_audit_no_delete;
+             DELETE FROM egress_audit WHERE seq = 3;",
+        )
+        .unwrap();
+        assert_eq!(
+            log.verify().unwrap(),
+            EgressVerification {
+                checked: 2,
+                broken_at: Some(3),
+            }
+        );
+
+        conn.execute_batch(
+            "DROP TRIGGER egress_audit_no_update;
+             UPDATE egress_audit SET payload_bytes = 9 WHERE seq = 2;",
+        )
+        .unwrap();
+        assert_eq!(log.verify().unwrap().broken_at, Some(2));
+    }
+}
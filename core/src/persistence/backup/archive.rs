@@ -0,0 +1,236 @@
+use std::num::NonZeroU32;
+
+use anyhow::{anyhow, bail, Context, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, pbkdf2};
+
+pub const BACKUP_EXTENSION: &str = "fwbak";
+/// PBKDF2-HMAC-SHA256 rounds used for every archive. The count is written to the
+/// header, but readers only accept this value: it is parsed before the archive is
+/// authenticated, so a crafted header must not pick the work factor.
+pub const PBKDF2_ITERATIONS: u32 = 100_000;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = 6 + 4 + SALT_LEN + NONCE_LEN;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Passphrase-sealed container format shared by backups and history archives;
+/// each artifact has its own magic so one is never mistaken for the other.
+pub(crate) struct Envelope {
+    magic: &'static [u8; 6],
+    kind: &'static str,
+}
+
+pub(crate) const BACKUP_ENVELOPE: Envelope = Envelope::new(b"FWBAK\x01", "backup");
+
+/// Object name for a backup taken at `created_at_ms`. Names sort by age.
+pub fn archive_name(created_at_ms: i64) -> String {
+    format!("history-{created_at_ms:013}.{BACKUP_EXTENSION}")
+}
+
+/// Creation time encoded in an archive name.
+pub fn parse_archive_name(name: &str) -> Option<i64> {
+    name.strip_prefix("history-")?
+        .strip_suffix(&format!(".{BACKUP_EXTENSION}"))?
+        .parse()
+        .ok()
+}
+
+/// Stretches `passphrase` into a 256-bit key with PBKDF2-HMAC-SHA256. Shared by every
+/// passphrase-protected artifact (backups, sync change sets, history archives).
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<[u8; 32]> {
+    let iterations =
+        NonZeroU32::new(iterations).ok_or_else(|| anyhow!("invalid PBKDF2 iteration count"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+fn cipher(passphrase: &str, salt: &[u8], iterations: u32) -> Result<aead::LessSafeKey> {
+    let key = derive_key(passphrase, salt, iterations)?;
+    let key = aead::UnboundKey::new(&aead::AES_256_GCM, &key)
+        .map_err(|_| anyhow!("invalid archive key material"))?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
+impl Envelope {
+    pub(crate) const fn new(magic: &'static [u8; 6], kind: &'static str) -> Self {
+        Self { magic, kind }
+    }
+
+    /// Encrypts `plaintext` with a key derived from `passphrase`.
+    ///
+    /// Layout: magic, PBKDF2 iteration count (big endian), salt, nonce, then the
+    /// AES-256-GCM ciphertext and tag. The header is bound as associated data.
+    pub(crate) fn seal(&self, passphrase: &str, plaintext: Vec<u8>) -> Result<Vec<u8>> {
+        let kind = self.kind;
+        if passphrase.len() < MIN_PASSPHRASE_LEN {
+            bail!("{kind} passphrase must be at least {MIN_PASSPHRASE_LEN} bytes");
+        }
+        let rng = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut salt)
+            .and_then(|_| rng.fill(&mut nonce))
+            .map_err(|_| anyhow!("failed to generate {kind} salt"))?;
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(self.magic);
+        header.extend_from_slice(&PBKDF2_ITERATIONS.to_be_bytes());
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+
+        let mut buffer = plaintext;
+        cipher(passphrase, &salt, PBKDF2_ITERATIONS)?
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(header.as_slice()),
+                &mut buffer,
+            )
+            .map_err(|_| anyhow!("failed to encrypt {kind}"))?;
+        header.extend_from_slice(&buffer);
+        Ok(header)
+    }
+
+    /// Decrypts a container produced by [`Envelope::seal`] with the same magic.
+    pub(crate) fn open(&self, passphrase: &str, archive: &[u8]) -> Result<Vec<u8>> {
+        let kind = self.kind;
+        if archive.len() < HEADER_LEN || !archive.starts_with(self.magic) {
+            bail!("not a Flowwisper {kind} archive");
+        }
+        let (header, ciphertext) = archive.split_at(HEADER_LEN);
+        let magic_len = self.magic.len();
+        let mut iterations = [0u8; 4];
+        iterations.copy_from_slice(&header[magic_len..magic_len + 4]);
+        let salt = &header[magic_len + 4..magic_len + 4 + SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&header[HEADER_LEN - NONCE_LEN..]);
+
+        let iterations = u32::from_be_bytes(iterations);
+        if iterations != PBKDF2_ITERATIONS {
+            bail!("unsupported {kind} key derivation ({iterations} PBKDF2 rounds)");
+        }
+
+        let mut buffer = ciphertext.to_vec();
+        let plaintext = cipher(passphrase, salt, iterations)?
+            .open_in_place(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(header),
+                &mut buffer,
+            )
+            .map_err(|_| {
+                anyhow!("failed to decrypt {kind} (wrong passphrase or corrupted archive)")
+            })?;
+        let len = plaintext.len();
+        buffer.truncate(len);
+        Ok(buffer)
+    }
+}
+
+/// Encrypts a database snapshot with a key derived from `passphrase`.
+pub fn seal(passphrase: &str, snapshot: Vec<u8>) -> Result<Vec<u8>> {
+    BACKUP_ENVELOPE.seal(passphrase, snapshot)
+}
+
+/// Decrypts an archive produced by [`seal`].
+pub fn open(passphrase: &str, archive: &[u8]) -> Result<Vec<u8>> {
+    BACKUP_ENVELOPE.open(passphrase, archive)
+}
+
+/// Decrypted contents of a history backup.
+pub struct BackupPayload {
+    /// SQLCipher key the snapshot was written with; `None` for a plaintext database.
+    pub database_key: Option<String>,
+    pub snapshot: Vec<u8>,
+}
+
+/// Encrypts a database snapshot together with the SQLCipher key it was written
+/// with, so the passphrase alone is enough to restore it.
+///
+/// Payload layout: key length (big endian `u32`), key bytes, then the snapshot.
+pub fn seal_backup(
+    passphrase: &str,
+    database_key: Option<&str>,
+    snapshot: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let key = database_key.unwrap_or_default().as_bytes();
+    let key_len = u32::try_from(key.len()).context("SQLCipher key too long for backup")?;
+    let mut payload = Vec::with_capacity(4 + key.len() + snapshot.len());
+    payload.extend_from_slice(&key_len.to_be_bytes());
+    payload.extend_from_slice(key);
+    payload.extend_from_slice(&snapshot);
+    BACKUP_ENVELOPE.seal(passphrase, payload)
+}
+
+/// Decrypts an archive produced by [`seal_backup`].
+pub fn open_backup(passphrase: &str, archive: &[u8]) -> Result<BackupPayload> {
+    let mut payload = BACKUP_ENVELOPE.open(passphrase, archive)?;
+    let mut key_len = [0u8; 4];
+    key_len.copy_from_slice(
+        payload
+            .get(..4)
+            .ok_or_else(|| anyhow!("backup archive is missing its database key"))?,
+    );
+    let key_end = 4 + u32::from_be_bytes(key_len) as usize;
+    if payload.len() < key_end {
+        bail!("backup archive database key is truncated");
+    }
+    let snapshot = payload.split_off(key_end);
+    let key = String::from_utf8(payload.split_off(4))
+        .context("backup archive database key is not valid UTF-8")?;
+    Ok(BackupPayload {
+        database_key: (!key.is_empty()).then_some(key),
+        snapshot,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_and_opens_archives() {
+        let archive = seal("correct horse", b"SQLite format 3\0".to_vec()).unwrap();
+        assert_eq!(
+            open("correct horse", &archive).unwrap(),
+            b"SQLite format 3\0"
+        );
+        assert!(open("wrong horse!", &archive).is_err());
+
+        let mut tampered = archive.clone();
+        tampered[BACKUP_ENVELOPE.magic.len() + 5] ^= 1;
+        assert!(open("correct horse", &tampered).is_err());
+        assert!(seal("short", Vec::new()).is_err());
+
+        // The iteration count is read before authentication and must not be attacker chosen.
+        let mut expensive = archive.clone();
+        let magic_len = BACKUP_ENVELOPE.magic.len();
+        expensive[magic_len..magic_len + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = open("correct horse", &expensive).unwrap_err();
+        assert!(err.to_string().contains("PBKDF2 rounds"), "{err}");
+
+        let backup = seal_backup("correct horse", Some("db-secret"), b"snapshot".to_vec()).unwrap();
+        let payload = open_backup("correct horse", &backup).unwrap();
+        assert_eq!(payload.database_key.as_deref(), Some("db-secret"));
+        assert_eq!(payload.snapshot, b"snapshot");
+        let plaintext = seal_backup("correct horse", None, b"snapshot".to_vec()).unwrap();
+        assert_eq!(
+            open_backup("correct horse", &plaintext)
+                .unwrap()
+                .database_key,
+            None
+        );
+
+        let name = archive_name(1_700_000_000_000);
+        assert_eq!(name, "history-1700000000000.fwbak");
+        assert_eq!(parse_archive_name(&name), Some(1_700_000_000_000));
+        assert_eq!(parse_archive_name("notes.txt"), None);
+    }
+}
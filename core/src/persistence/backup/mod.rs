@@ -0,0 +1,506 @@
+//! 历史库加密备份：定期用 SQLite 备份 API 生成 `history.db` 快照，以用户口令派生的密钥加密后
+//! 上传到文件夹、WebDAV 或 S3，上传后回读校验，并只保留最近 N 份。
+
+pub mod archive;
+pub mod storage;
+
+pub use archive::{archive_name, parse_archive_name, BACKUP_EXTENSION};
+pub use storage::{BackupStorage, FolderStorage, S3Settings, S3Storage, WebDavStorage};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::persistence::audit::EgressRecorder;
+use crate::persistence::sqlite::SqlitePersistence;
+use crate::telemetry::events::record_session_history_backup;
+
+pub const BACKUP_FOLDER_ENV: &str = "FLOWWISPER_BACKUP_FOLDER";
+pub const BACKUP_PASSPHRASE_ENV: &str = "FLOWWISPER_BACKUP_PASSPHRASE";
+pub const BACKUP_S3_SECRET_KEY_ENV: &str = "FLOWWISPER_BACKUP_S3_SECRET_KEY";
+
+pub(crate) const DEFAULT_BACKUP_INTERVAL_SECS: u64 = 24 * 60 * 60;
+pub(crate) const DEFAULT_BACKUP_RETAIN: usize = 7;
+/// Suffix of a verified snapshot waiting to replace `history.db` on the next start.
+const STAGED_RESTORE_SUFFIX: &str = "restore";
+
+/// Where encrypted backups are stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupTarget {
+    Folder(PathBuf),
+    WebDav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    S3(S3Settings),
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct BackupConfig {
+    pub target: BackupTarget,
+    /// Passphrase the archive key is derived from. Losing it makes every
+    /// backup unrecoverable.
+    pub passphrase: String,
+    pub interval: Duration,
+    /// Number of most recent backups kept on the target.
+    pub retain: usize,
+}
+
+impl std::fmt::Debug for BackupConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackupConfig")
+            .field("target", &self.target)
+            .field("passphrase", &"<redacted>")
+            .field("interval", &self.interval)
+            .field("retain", &self.retain)
+            .finish()
+    }
+}
+
+impl BackupConfig {
+    /// Storage for the configured target; uploads leaving the device are recorded
+    /// through `egress`.
+    pub fn storage(&self, egress: &EgressRecorder) -> Arc<dyn BackupStorage> {
+        match &self.target {
+            BackupTarget::Folder(path) => Arc::new(FolderStorage::new(path.clone())),
+            BackupTarget::WebDav {
+                url,
+                username,
+                password,
+            } => Arc::new(
+                WebDavStorage::new(url, username.as_deref(), password.as_deref())
+                    .with_egress(egress.clone()),
+            ),
+            BackupTarget::S3(settings) => {
+                Arc::new(S3Storage::new(settings.clone()).with_egress(egress.clone()))
+            }
+        }
+    }
+}
+
+/// Outcome of one [`BackupService::backup_once`] run.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupReport {
+    pub name: String,
+    /// Size of the encrypted archive.
+    pub bytes: usize,
+    pub sessions: usize,
+    /// Older archives removed to honour the retention count.
+    pub pruned: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub name: String,
+    pub created_at_ms: i64,
+}
+
+/// Temporary file removed when dropped.
+struct ScratchFile(PathBuf);
+
+impl ScratchFile {
+    fn new(label: &str) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        Self(std::env::temp_dir().join(format!(
+            "flowwisper-{label}-{}-{}-{}.db",
+            std::process::id(),
+            now_ms(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        )))
+    }
+}
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Backs up the history database to a [`BackupStorage`].
+///
+/// Each archive seals the snapshot's SQLCipher key alongside it, so the backup
+/// passphrase alone restores it even after the key was rotated or on a fresh
+/// install; restores are re-encrypted with the key the database uses then.
+#[derive(Clone)]
+pub struct BackupService {
+    sqlite: Arc<SqlitePersistence>,
+    storage: Arc<dyn BackupStorage>,
+    passphrase: Arc<str>,
+    interval: Duration,
+    retain: usize,
+    started: Arc<AtomicBool>,
+}
+
+impl BackupService {
+    pub fn new(
+        sqlite: Arc<SqlitePersistence>,
+        storage: Arc<dyn BackupStorage>,
+        passphrase: &str,
+        retain: usize,
+    ) -> Self {
+        Self {
+            sqlite,
+            storage,
+            passphrase: Arc::from(passphrase),
+            interval: Duration::from_secs(DEFAULT_BACKUP_INTERVAL_SECS),
+            retain: retain.max(1),
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn from_config(
+        sqlite: Arc<SqlitePersistence>,
+        config: &BackupConfig,
+        egress: &EgressRecorder,
+    ) -> Self {
+        let mut service = Self::new(
+            sqlite,
+            config.storage(egress),
+            &config.passphrase,
+            config.retain,
+        );
+        service.interval = config.interval;
+        service
+    }
+
+    /// Snapshot, encrypt, upload, verify the uploaded copy, then prune old
+    /// archives. Blocking.
+    pub fn backup_once(&self, now_ms: i64) -> Result<BackupReport> {
+        let key = self.sqlite.key_material()?;
+        let snapshot = ScratchFile::new("backup");
+        self.sqlite.backup_to(&snapshot.0)?;
+        let sessions = SqlitePersistence::verify_snapshot(&snapshot.0, key.as_deref())?;
+        let plaintext = fs::read(&snapshot.0).context("failed to read database snapshot")?;
+        let sealed = archive::seal_backup(&self.passphrase, key.as_deref(), plaintext)?;
+
+        let name = archive_name(now_ms);
+        self.storage.put(&name, &sealed)?;
+        match self.verify_remote(&name) {
+            Ok(restored) if restored == sessions => {}
+            Ok(restored) => {
+                let _ = self.storage.delete(&name);
+                return Err(anyhow!(
+                    "backup {name} verification found {restored} sessions, expected {sessions}"
+                ));
+            }
+            Err(err) => {
+                let _ = self.storage.delete(&name);
+                return Err(err.context(format!("backup {name} failed verification")));
+            }
+        }
+
+        let pruned = self.prune()?;
+        Ok(BackupReport {
+            name,
+            bytes: sealed.len(),
+            sessions,
+            pruned,
+        })
+    }
+
+    /// Download and decrypt `name`, returning the verified snapshot file and the
+    /// SQLCipher key it opens with.
+    fn fetch_verified(&self, name: &str) -> Result<(ScratchFile, Option<String>, usize)> {
+        let sealed = self.storage.get(name)?;
+        let payload = archive::open_backup(&self.passphrase, &sealed)?;
+        let scratch = ScratchFile::new("restore");
+        fs::write(&scratch.0, payload.snapshot).context("failed to write restored snapshot")?;
+        let sessions =
+            SqlitePersistence::verify_snapshot(&scratch.0, payload.database_key.as_deref())?;
+        Ok((scratch, payload.database_key, sessions))
+    }
+
+    fn verify_remote(&self, name: &str) -> Result<usize> {
+        self.fetch_verified(name).map(|(_, _, sessions)| sessions)
+    }
+
+    fn prune(&self) -> Result<usize> {
+        let names = self.storage.list()?;
+        let excess = names.len().saturating_sub(self.retain);
+        for name in &names[..excess] {
+            self.storage.delete(name)?;
+        }
+        Ok(excess)
+    }
+
+    /// Backups on the target, newest first.
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        let mut backups: Vec<BackupInfo> = self
+            .storage
+            .list()?
+            .into_iter()
+            .filter_map(|name| {
+                Some(BackupInfo {
+                    created_at_ms: parse_archive_name(&name)?,
+                    name,
+                })
+            })
+            .collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at_ms));
+        Ok(backups)
+    }
+
+    /// Download, decrypt and verify `name`, then write it to `dest` encrypted
+    /// with the live database's current SQLCipher key. Returns the restored
+    /// session count. Blocking.
+    pub fn restore_to(&self, name: &str, dest: &Path) -> Result<usize> {
+        let (scratch, archived_key, sessions) = self.fetch_verified(name)?;
+        let key = self.sqlite.key_material()?;
+        let partial = dest.with_extension("partial");
+        let _ = fs::remove_file(&partial);
+        SqlitePersistence::export_snapshot(
+            &scratch.0,
+            archived_key.as_deref(),
+            &partial,
+            key.as_deref(),
+        )
+        .and_then(|_| SqlitePersistence::verify_snapshot(&partial, key.as_deref()))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&partial);
+        })?;
+        fs::rename(&partial, dest)
+            .with_context(|| format!("failed to move restored backup to {}", dest.display()))?;
+        Ok(sessions)
+    }
+
+    /// Restore `name` over the live database on the next start; see
+    /// [`apply_staged_restore`]. Blocking.
+    pub fn stage_restore(&self, name: &str) -> Result<usize> {
+        let db_path = self
+            .sqlite
+            .database_path()
+            .ok_or_else(|| anyhow!("restore requires a file-backed database"))?;
+        self.restore_to(name, &staged_restore_path(db_path))
+    }
+
+    /// Start the periodic backup loop; later calls are no-ops.
+    pub fn spawn(&self) -> Option<JoinHandle<()>> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let service = self.clone();
+        Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(service.interval).await;
+                let worker = service.clone();
+                let result = tokio::task::spawn_blocking(move || worker.backup_once(now_ms()))
+                    .await
+                    .map_err(|err| anyhow!("history backup task failed: {err}"))
+                    .and_then(|result| result);
+                match result {
+                    Ok(report) => record_session_history_backup(
+                        report.bytes,
+                        report.sessions,
+                        report.pruned,
+                        None,
+                    ),
+                    Err(err) => record_session_history_backup(0, 0, 0, Some(&err)),
+                }
+            }
+        }))
+    }
+}
+
+fn staged_restore_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{STAGED_RESTORE_SUFFIX}"));
+    db_path.with_file_name(name)
+}
+
+/// Swap a staged restore in for `db_path` before the database is opened. The
+/// replaced database is kept beside it as `<name>.pre-restore`. Returns whether
+/// a restore was applied.
+pub fn apply_staged_restore(db_path: &Path) -> Result<bool> {
+    let staged = staged_restore_path(db_path);
+    if !staged.exists() {
+        return Ok(false);
+    }
+    if db_path.exists() {
+        let mut previous = db_path.file_name().unwrap_or_default().to_os_string();
+        previous.push(".pre-restore");
+        fs::rename(db_path, db_path.with_file_name(previous))
+            .context("failed to set aside the current database")?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_os_string();
+        sidecar.push(suffix);
+        let _ = fs::remove_file(PathBuf::from(sidecar));
+    }
+    fs::rename(&staged, db_path).context("failed to move restored database into place")?;
+    Ok(true)
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::{SecretStoreKeyResolver, SqliteConfig, SqlitePath};
+    use crate::secrets::EncryptedFileStore;
+    use crate::session::history::SessionSnapshot;
+
+    fn database(path: &Path) -> Arc<SqlitePersistence> {
+        Arc::new(
+            SqlitePersistence::bootstrap(SqliteConfig {
+                path: SqlitePath::File(path.to_path_buf()),
+                ..SqliteConfig::memory()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn keyed_database(dir: &Path, name: &str, key: &str) -> Arc<SqlitePersistence> {
+        let store = EncryptedFileStore::open(&dir.join(format!("{name}-secrets"))).unwrap();
+        let config = SqliteConfig {
+            path: SqlitePath::File(dir.join(format!("{name}.db"))),
+            key_resolver: Arc::new(SecretStoreKeyResolver::new(Arc::new(store))),
+            ..SqliteConfig::memory()
+        };
+        config.key_resolver.store_key(key).unwrap();
+        Arc::new(SqlitePersistence::bootstrap(config).unwrap())
+    }
+
+    fn snapshot(session_id: &str) -> SessionSnapshot {
+        SessionSnapshot {
+            session_id: session_id.into(),
+            started_at_ms: 0,
+            completed_at_ms: 0,
+            locale: None,
+            app_identifier: None,
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: "raw".into(),
+            polished_transcript: "polished".into(),
+            metadata: serde_json::json!({}),
+            post_actions: vec![],
+            language_segments: vec![],
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn backs_up_prunes_and_restores() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite = database(&dir.path().join("history.db"));
+        sqlite.insert_session(&snapshot("backup-1")).unwrap();
+        let target = dir.path().join("backups");
+        let service = BackupService::new(
+            sqlite.clone(),
+            Arc::new(FolderStorage::new(&target)),
+            "correct horse battery",
+            2,
+        );
+
+        let first = service.backup_once(1_000).unwrap();
+        assert_eq!(first.sessions, 1);
+        sqlite.insert_session(&snapshot("backup-2")).unwrap();
+        service.backup_once(2_000).unwrap();
+        let third = service.backup_once(3_000).unwrap();
+        assert_eq!(third.sessions, 2);
+        assert_eq!(third.pruned, 1);
+        let names: Vec<_> = service
+            .list_backups()
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.created_at_ms)
+            .collect();
+        assert_eq!(names, vec![3_000, 2_000]);
+        let raw = fs::read(target.join(&third.name)).unwrap();
+        assert!(!raw.windows(6).any(|window| window == b"SQLite"));
+
+        let restored = dir.path().join("restored.db");
+        assert_eq!(service.restore_to(&third.name, &restored).unwrap(), 2);
+        assert_eq!(
+            database(&restored)
+                .load_session("backup-2")
+                .unwrap()
+                .unwrap()
+                .session_id,
+            "backup-2"
+        );
+
+        let wrong = BackupService::new(
+            sqlite.clone(),
+            Arc::new(FolderStorage::new(&target)),
+            "wrong passphrase",
+            2,
+        );
+        assert!(wrong.restore_to(&third.name, &restored).is_err());
+
+        assert!(service.restore_to(&first.name, &restored).is_err());
+        sqlite.insert_session(&snapshot("backup-3")).unwrap();
+        assert_eq!(service.stage_restore(&third.name).unwrap(), 2);
+        let db_path = sqlite.database_path().unwrap().to_path_buf();
+        drop(wrong);
+        drop(service);
+        drop(sqlite);
+        assert!(apply_staged_restore(&db_path).unwrap());
+        assert!(!apply_staged_restore(&db_path).unwrap());
+        let reopened = database(&db_path);
+        assert!(reopened.load_session("backup-2").unwrap().is_some());
+        assert!(reopened.load_session("backup-3").unwrap().is_none());
+        assert!(dir.path().join("history.db.pre-restore").exists());
+    }
+
+    #[test]
+    fn restores_with_the_current_key_after_rotation_and_on_a_new_install() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("backups");
+        let sqlite = keyed_database(dir.path(), "history", "old-secret");
+        sqlite.insert_session(&snapshot("keyed-1")).unwrap();
+        let service = BackupService::new(
+            sqlite.clone(),
+            Arc::new(FolderStorage::new(&target)),
+            "correct horse battery",
+            2,
+        );
+        let report = service.backup_once(1_000).unwrap();
+
+        sqlite.rekey("new-secret", &mut |_| {}).unwrap();
+        let restored = dir.path().join("restored.db");
+        assert_eq!(service.restore_to(&report.name, &restored).unwrap(), 1);
+        assert_eq!(
+            SqlitePersistence::verify_snapshot(&restored, Some("new-secret")).unwrap(),
+            1
+        );
+        assert!(SqlitePersistence::verify_snapshot(&restored, Some("old-secret")).is_err());
+
+        let fresh = keyed_database(dir.path(), "fresh", "fresh-secret");
+        let fresh_service = BackupService::new(
+            fresh,
+            Arc::new(FolderStorage::new(&target)),
+            "correct horse battery",
+            2,
+        );
+        let reinstalled = dir.path().join("reinstalled.db");
+        assert_eq!(
+            fresh_service
+                .restore_to(&report.name, &reinstalled)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            SqlitePersistence::verify_snapshot(&reinstalled, Some("fresh-secret")).unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::{anyhow, Result};
+
+use super::{now_timestamp_ms, PersistenceHandle};
+use crate::session::recovery::RecoverySnapshot;
+
+impl PersistenceHandle {
+    pub async fn save_checkpoint(&self, mut snapshot: RecoverySnapshot) -> Result<()> {
+        if let Some(redactor) = self.masking_redactor() {
+            for sentence in &mut snapshot.sentences {
+                sentence.raw = redactor.mask(&sentence.raw);
+                sentence.polished = sentence.polished.as_deref().map(|text| redactor.mask(text));
+            }
+        }
+        let sqlite = self.sqlite.clone();
+        let updated_at_ms = now_timestamp_ms() as i64;
+        tokio::task::spawn_blocking(move || sqlite.upsert_checkpoint(&snapshot, updated_at_ms))
+            .await
+            .map_err(|err| anyhow!("blocking checkpoint task failed: {err}"))?
+    }
+
+    pub async fn remove_checkpoint(&self, session_id: String) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.delete_checkpoint(&session_id))
+            .await
+            .map_err(|err| anyhow!("blocking checkpoint task failed: {err}"))?
+    }
+
+    pub async fn list_checkpoints(&self) -> Result<Vec<RecoverySnapshot>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_checkpoints())
+            .await
+            .map_err(|err| anyhow!("blocking checkpoint task failed: {err}"))?
+    }
+}
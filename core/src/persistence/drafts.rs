@@ -0,0 +1,457 @@
+use std::collections::VecDeque;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use super::{now_timestamp_ms, PersistenceActor, PersistenceCommand, PersistenceHandle};
+
+const DEFAULT_DRAFT_TITLE: &str = "Polished transcript";
+const DEFAULT_DRAFT_TAG: &str = "transcript";
+pub(super) const MAX_DRAFT_HISTORY: usize = 240;
+pub(super) const MAX_NOTICE_HISTORY: usize = 240;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DraftSaveRequest {
+    pub draft_id: String,
+    pub session_id: String,
+    pub content: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DraftRecord {
+    pub draft_id: String,
+    pub session_id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub content: String,
+    pub created_at_ms: u128,
+    pub updated_at_ms: u128,
+}
+
+impl DraftRecord {
+    pub fn from_request(request: DraftSaveRequest) -> Self {
+        let timestamp_ms = now_timestamp_ms();
+        let title = request
+            .title
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_DRAFT_TITLE.to_string());
+        let tags = request
+            .tags
+            .unwrap_or_else(|| vec![DEFAULT_DRAFT_TAG.to_string()]);
+
+        Self {
+            draft_id: request.draft_id,
+            session_id: request.session_id,
+            title,
+            tags,
+            content: request.content,
+            created_at_ms: timestamp_ms,
+            updated_at_ms: timestamp_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NoticeSaveRequest {
+    pub notice_id: String,
+    pub session_id: String,
+    pub action: String,
+    pub result: String,
+    pub level: String,
+    pub message: String,
+    #[serde(default)]
+    pub undo_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NoticeRecord {
+    pub notice_id: String,
+    pub session_id: String,
+    pub action: String,
+    pub result: String,
+    pub level: String,
+    pub message: String,
+    pub undo_token: Option<String>,
+    pub timestamp_ms: u128,
+}
+
+impl NoticeRecord {
+    pub fn from_request(request: NoticeSaveRequest) -> Self {
+        Self {
+            notice_id: request.notice_id,
+            session_id: request.session_id,
+            action: request.action,
+            result: request.result,
+            level: request.level,
+            message: request.message,
+            undo_token: request.undo_token,
+            timestamp_ms: now_timestamp_ms(),
+        }
+    }
+}
+
+impl PersistenceHandle {
+    pub async fn save_draft(&self, mut request: DraftSaveRequest) -> Result<DraftRecord> {
+        if let Some(redactor) = self.masking_redactor() {
+            request.content = redactor.mask(&request.content);
+        }
+        let record = DraftRecord::from_request(request);
+        let (tx, rx) = oneshot::channel();
+        self.tx
+            .send(PersistenceCommand::StoreDraft {
+                record,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|err| anyhow!("failed to queue draft save: {err}"))?;
+        rx.await
+            .map_err(|err| anyhow!("draft save channel dropped: {err}"))?
+    }
+
+    pub async fn save_notice(&self, request: NoticeSaveRequest) -> Result<NoticeRecord> {
+        let record = NoticeRecord::from_request(request);
+        let (tx, rx) = oneshot::channel();
+        self.tx
+            .send(PersistenceCommand::StoreNotice {
+                record,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|err| anyhow!("failed to queue notice save: {err}"))?;
+        rx.await
+            .map_err(|err| anyhow!("notice save channel dropped: {err}"))?
+    }
+
+    pub async fn list_drafts(&self, limit: usize) -> Result<Vec<DraftRecord>> {
+        let (tx, rx) = oneshot::channel();
+        self.tx
+            .send(PersistenceCommand::ListDrafts {
+                limit,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|err| anyhow!("failed to queue draft list request: {err}"))?;
+        rx.await
+            .map_err(|err| anyhow!("draft list channel dropped: {err}"))?
+    }
+
+    pub async fn list_notices(&self, limit: usize) -> Result<Vec<NoticeRecord>> {
+        let (tx, rx) = oneshot::channel();
+        self.tx
+            .send(PersistenceCommand::ListNotices {
+                limit,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|err| anyhow!("failed to queue notice list request: {err}"))?;
+        rx.await
+            .map_err(|err| anyhow!("notice list channel dropped: {err}"))?
+    }
+}
+
+impl PersistenceActor {
+    pub(super) fn store_draft(&mut self, record: DraftRecord) -> Result<DraftRecord> {
+        info!(
+            target: "persistence",
+            draft_id = %record.draft_id,
+            session_id = %record.session_id,
+            "persisting transcript draft"
+        );
+        self.sqlite.upsert_draft(&record)?;
+        let mut record = record;
+        let cached = self
+            .drafts
+            .iter()
+            .position(|existing| existing.draft_id == record.draft_id)
+            .and_then(|index| self.drafts.remove(index));
+        match cached {
+            Some(previous) => record.created_at_ms = previous.created_at_ms,
+            // 已被挤出缓存的草稿以库中保留的创建时间为准。
+            None => {
+                if let Some(stored) = self.sqlite.load_draft(&record.draft_id)? {
+                    record.created_at_ms = stored.created_at_ms;
+                }
+            }
+        }
+        Self::push_with_limit(&mut self.drafts, record.clone(), MAX_DRAFT_HISTORY);
+        Ok(record)
+    }
+
+    pub(super) fn store_notice(&mut self, record: NoticeRecord) -> Result<NoticeRecord> {
+        info!(
+            target: "persistence",
+            notice_id = %record.notice_id,
+            session_id = %record.session_id,
+            action = %record.action,
+            result = %record.result,
+            "persisting publish notice"
+        );
+        self.sqlite.insert_notice(&record)?;
+        Self::push_with_limit(&mut self.notices, record.clone(), MAX_NOTICE_HISTORY);
+        Ok(record)
+    }
+
+    pub(super) fn collect_drafts(&self, limit: usize) -> Vec<DraftRecord> {
+        if limit > self.drafts.len() && self.drafts.len() >= MAX_DRAFT_HISTORY {
+            match self.sqlite.list_drafts(limit) {
+                Ok(records) => return records,
+                Err(err) => {
+                    warn!(target: "persistence", %err, "failed to read drafts, serving cache")
+                }
+            }
+        }
+        let effective_limit = limit.min(self.drafts.len());
+        self.drafts
+            .iter()
+            .rev() // Reverse iterator (newest first)
+            .take(effective_limit) // Take the newest N items
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev() // Reverse again (oldest first among the taken items)
+            .collect()
+    }
+
+    pub(super) fn collect_notices(&self, limit: usize) -> Vec<NoticeRecord> {
+        if limit > self.notices.len() && self.notices.len() >= MAX_NOTICE_HISTORY {
+            match self.sqlite.list_notices(limit) {
+                Ok(records) => return records,
+                Err(err) => {
+                    warn!(target: "persistence", %err, "failed to read notices, serving cache")
+                }
+            }
+        }
+        let effective_limit = limit.min(self.notices.len());
+        self.notices
+            .iter()
+            .rev() // Reverse iterator (newest first)
+            .take(effective_limit) // Take the newest N items
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev() // Reverse again (oldest first among the taken items)
+            .collect()
+    }
+
+    fn push_with_limit<T>(deque: &mut VecDeque<T>, item: T, limit: usize) {
+        if deque.len() >= limit {
+            deque.pop_front();
+        }
+        deque.push_back(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::{SqliteConfig, SqlitePersistence};
+    use crate::session::history::HISTORY_RETENTION_MS;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn saves_draft_with_defaults_and_retrieves_history() {
+        let (tx, rx) = mpsc::channel(4);
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+
+        let request = DraftSaveRequest {
+            draft_id: "draft-1".into(),
+            session_id: "session-1".into(),
+            content: "Hello".into(),
+            title: None,
+            tags: None,
+        };
+
+        let record = handle
+            .save_draft(request)
+            .await
+            .expect("draft save should succeed");
+
+        assert_eq!(record.title, DEFAULT_DRAFT_TITLE);
+        assert_eq!(record.tags, vec![DEFAULT_DRAFT_TAG.to_string()]);
+
+        let history = handle
+            .list_drafts(10)
+            .await
+            .expect("draft history should be returned");
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].draft_id, "draft-1");
+    }
+
+    #[tokio::test]
+    async fn evicted_drafts_keep_creation_time_and_expire_with_history() {
+        let (tx, rx) = mpsc::channel(4);
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+        let request = |draft_id: String| DraftSaveRequest {
+            draft_id,
+            session_id: "session".into(),
+            content: "draft content".into(),
+            title: None,
+            tags: None,
+        };
+
+        let original = handle
+            .save_draft(request("draft-first".into()))
+            .await
+            .expect("draft save should succeed");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        for idx in 0..MAX_DRAFT_HISTORY {
+            handle
+                .save_draft(request(format!("draft-{idx}")))
+                .await
+                .expect("draft save should succeed");
+        }
+        let revised = handle
+            .save_draft(request("draft-first".into()))
+            .await
+            .expect("draft save should succeed");
+        assert_eq!(revised.created_at_ms, original.created_at_ms);
+        assert!(revised.updated_at_ms > original.created_at_ms);
+
+        let later = now_timestamp_ms() as i64 + HISTORY_RETENTION_MS;
+        handle.cleanup_expired(later).await.expect("cleanup runs");
+        assert!(handle.list_drafts(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn respects_draft_list_limit_and_order() {
+        let (tx, rx) = mpsc::channel(4);
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+
+        for idx in 0..5 {
+            let request = DraftSaveRequest {
+                draft_id: format!("draft-{idx}"),
+                session_id: "session".into(),
+                content: format!("draft content {idx}"),
+                title: Some(format!("Custom {idx}")),
+                tags: Some(vec!["transcript".into(), idx.to_string()]),
+            };
+
+            handle
+                .save_draft(request)
+                .await
+                .expect("draft save should succeed");
+        }
+
+        let history = handle
+            .list_drafts(3)
+            .await
+            .expect("draft list should be returned");
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].draft_id, "draft-2");
+        assert_eq!(history[1].draft_id, "draft-3");
+        assert_eq!(history[2].draft_id, "draft-4");
+        assert_eq!(history[2].title, "Custom 4");
+        assert_eq!(
+            history[2].tags,
+            vec!["transcript".to_string(), "4".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn stores_notices_and_limits_history() {
+        let (tx, rx) = mpsc::channel(4);
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+
+        for idx in 0..(MAX_NOTICE_HISTORY + 5) {
+            let request = NoticeSaveRequest {
+                notice_id: format!("notice-{idx}"),
+                session_id: "session".into(),
+                action: "copy".into(),
+                result: if idx % 2 == 0 {
+                    "success".into()
+                } else {
+                    "failure".into()
+                },
+                level: "warn".into(),
+                message: format!("notice #{idx}"),
+                undo_token: None,
+            };
+
+            handle
+                .save_notice(request)
+                .await
+                .expect("notice save should succeed");
+        }
+
+        let history = handle
+            .list_notices(50)
+            .await
+            .expect("notice history should be returned");
+        assert_eq!(history.len(), 50);
+        assert_eq!(history.first().unwrap().notice_id, "notice-195");
+        assert_eq!(
+            history.last().unwrap().notice_id,
+            format!("notice-{}", MAX_NOTICE_HISTORY + 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn drafts_and_notices_survive_actor_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = SqliteConfig::memory();
+        config.path = crate::persistence::sqlite::SqlitePath::File(dir.path().join("history.db"));
+
+        {
+            let (tx, rx) = mpsc::channel(4);
+            let sqlite = Arc::new(SqlitePersistence::bootstrap(config.clone()).unwrap());
+            let handle = PersistenceHandle::new(tx, sqlite.clone());
+            tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+
+            for content in ["first", "revised"] {
+                handle
+                    .save_draft(DraftSaveRequest {
+                        draft_id: "draft-1".into(),
+                        session_id: "session-1".into(),
+                        content: content.into(),
+                        title: None,
+                        tags: None,
+                    })
+                    .await
+                    .expect("draft save should succeed");
+            }
+            handle
+                .save_notice(NoticeSaveRequest {
+                    notice_id: "notice-1".into(),
+                    session_id: "session-1".into(),
+                    action: "insert".into(),
+                    result: "failure".into(),
+                    level: "warn".into(),
+                    message: "focus lost".into(),
+                    undo_token: Some("undo-1".into()),
+                })
+                .await
+                .expect("notice save should succeed");
+        }
+
+        let (tx, rx) = mpsc::channel(4);
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(config).unwrap());
+        let handle = PersistenceHandle::new(tx, sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+
+        let drafts = handle.list_drafts(10).await.unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].content, "revised");
+        assert_eq!(drafts[0].tags, vec![DEFAULT_DRAFT_TAG.to_string()]);
+
+        let notices = handle.list_notices(10).await.unwrap();
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].undo_token.as_deref(), Some("undo-1"));
+    }
+}
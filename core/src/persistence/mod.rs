@@ -1,32 +1,59 @@
 //! 本地持久化层脚手架，负责编排 SQLCipher 数据库操作与回退逻辑。
 
+pub mod audit;
+pub mod backup;
 pub mod sqlite;
-
-use crate::persistence::sqlite::SqlitePersistence;
+pub mod sync;
+
+mod checkpoints;
+mod drafts;
+mod redaction;
+
+pub use drafts::{DraftRecord, DraftSaveRequest, NoticeRecord, NoticeSaveRequest};
+use drafts::{MAX_DRAFT_HISTORY, MAX_NOTICE_HISTORY};
+use redaction::redact_snapshot;
+
+use crate::audio::{RecordedAudio, SessionRecorder, SilencePolicy};
+use crate::orchestrator::budget::BudgetUsage;
+use crate::orchestrator::profile::{PolishProfile, PolishProfileBinding};
+use crate::orchestrator::redaction::Redactor;
+use crate::orchestrator::sla::LatencySamples;
+use crate::orchestrator::vocabulary::{Vocabulary, VocabularyTerm};
+use crate::persistence::sqlite::{RekeyStage, SqlitePersistence};
+use crate::session::app_profile::AppProfile;
+use crate::session::corrections::CorrectionPair;
 use crate::session::history::{
-    AccuracyUpdate, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery, SessionSnapshot,
+    AccuracyUpdate, ExportSelection, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery,
+    ImportSource, ImportSummary, SessionSnapshot, HISTORY_RETENTION_MS,
 };
+use crate::session::preset::SessionPreset;
+use crate::session::publisher::FieldRole;
+use crate::session::replacement::ReplacementRule;
+use crate::session::retry_queue::PublishRetryEntry;
 use crate::telemetry::events::{
     record_session_history_accuracy, record_session_history_action, record_session_history_cleanup,
-    record_session_history_persist_failure, record_session_history_persisted,
+    record_session_history_key_rotation, record_session_history_persist_failure,
+    record_session_history_persisted,
 };
 use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use std::collections::VecDeque;
+use std::future::Future;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, timeout};
-use tracing::{info, warn};
+use tracing::warn;
 
-const DEFAULT_DRAFT_TITLE: &str = "Polished transcript";
-const DEFAULT_DRAFT_TAG: &str = "transcript";
-const MAX_DRAFT_HISTORY: usize = 240;
-const MAX_NOTICE_HISTORY: usize = 240;
 const PERSISTENCE_TIMEOUT_MS: u64 = 200;
 const PERSISTENCE_RETRIES: u8 = 3;
+/// `user_settings` key of the silence auto-stop policy.
+const SILENCE_POLICY_SETTING: &str = "silence_policy";
+/// `user_settings` key of the cloud usage counted against the budget caps.
+const CLOUD_BUDGET_SETTING: &str = "cloud_budget_usage";
+const LATENCY_SAMPLES_SETTING: &str = "latency_calibration";
 
 fn now_timestamp_ms() -> u128 {
     SystemTime::now()
@@ -35,94 +62,10 @@ fn now_timestamp_ms() -> u128 {
         .unwrap_or(0)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct DraftSaveRequest {
-    pub draft_id: String,
-    pub session_id: String,
-    pub content: String,
-    #[serde(default)]
-    pub title: Option<String>,
-    #[serde(default)]
-    pub tags: Option<Vec<String>>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct DraftRecord {
-    pub draft_id: String,
-    pub session_id: String,
-    pub title: String,
-    pub tags: Vec<String>,
-    pub content: String,
-    pub created_at_ms: u128,
-    pub updated_at_ms: u128,
-}
-
-impl DraftRecord {
-    pub fn from_request(request: DraftSaveRequest) -> Self {
-        let timestamp_ms = now_timestamp_ms();
-        let title = request
-            .title
-            .filter(|value| !value.trim().is_empty())
-            .unwrap_or_else(|| DEFAULT_DRAFT_TITLE.to_string());
-        let tags = request
-            .tags
-            .unwrap_or_else(|| vec![DEFAULT_DRAFT_TAG.to_string()]);
-
-        Self {
-            draft_id: request.draft_id,
-            session_id: request.session_id,
-            title,
-            tags,
-            content: request.content,
-            created_at_ms: timestamp_ms,
-            updated_at_ms: timestamp_ms,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct NoticeSaveRequest {
-    pub notice_id: String,
-    pub session_id: String,
-    pub action: String,
-    pub result: String,
-    pub level: String,
-    pub message: String,
-    #[serde(default)]
-    pub undo_token: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct NoticeRecord {
-    pub notice_id: String,
-    pub session_id: String,
-    pub action: String,
-    pub result: String,
-    pub level: String,
-    pub message: String,
-    pub undo_token: Option<String>,
-    pub timestamp_ms: u128,
-}
-
-impl NoticeRecord {
-    pub fn from_request(request: NoticeSaveRequest) -> Self {
-        Self {
-            notice_id: request.notice_id,
-            session_id: request.session_id,
-            action: request.action,
-            result: request.result,
-            level: request.level,
-            message: request.message,
-            undo_token: request.undo_token,
-            timestamp_ms: now_timestamp_ms(),
-        }
-    }
-}
-
 #[derive(Debug)]
 pub enum PersistenceCommand {
     PersistSession {
-        snapshot: SessionSnapshot,
+        snapshot: Box<SessionSnapshot>,
         respond_to: oneshot::Sender<Result<()>>,
     },
     SearchHistory {
@@ -140,7 +83,10 @@ pub enum PersistenceCommand {
     },
     CleanupExpired {
         now_ms: i64,
-        respond_to: oneshot::Sender<Result<usize>>,
+        /// Organization retention cap; sessions older than this go even when pinned.
+        max_age_ms: Option<i64>,
+        /// Receives the ids of the removed sessions.
+        respond_to: oneshot::Sender<Result<Vec<String>>>,
     },
     EnqueueTelemetry {
         session_id: String,
@@ -163,28 +109,53 @@ pub enum PersistenceCommand {
         limit: usize,
         respond_to: oneshot::Sender<Result<Vec<NoticeRecord>>>,
     },
+    /// 等待此前排队的命令全部落盘。
+    Flush { respond_to: oneshot::Sender<()> },
 }
 
 #[derive(Clone)]
 pub struct PersistenceHandle {
     tx: mpsc::Sender<PersistenceCommand>,
     sqlite: Arc<SqlitePersistence>,
+    recordings: Arc<RwLock<Option<SessionRecorder>>>,
+    redactor: Arc<RwLock<Option<Arc<Redactor>>>>,
 }
 
 impl PersistenceHandle {
     pub fn new(tx: mpsc::Sender<PersistenceCommand>, sqlite: Arc<SqlitePersistence>) -> Self {
-        Self { tx, sqlite }
+        Self {
+            tx,
+            sqlite,
+            recordings: Arc::new(RwLock::new(None)),
+            redactor: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 关联会话录音归档，使历史记录可以回放原始音频。
+    pub fn attach_recordings(&self, recorder: Option<SessionRecorder>) {
+        let mut guard = self
+            .recordings
+            .write()
+            .expect("recordings registry poisoned");
+        *guard = recorder;
     }
 
     pub fn database_path(&self) -> Option<PathBuf> {
         self.sqlite.database_path().map(|path| path.to_path_buf())
     }
 
-    pub async fn persist_session(&self, snapshot: SessionSnapshot) -> Result<()> {
+    pub fn sqlite(&self) -> Arc<SqlitePersistence> {
+        Arc::clone(&self.sqlite)
+    }
+
+    pub async fn persist_session(&self, mut snapshot: SessionSnapshot) -> Result<()> {
+        if let Some(redactor) = self.redactor() {
+            redact_snapshot(&redactor, &mut snapshot);
+        }
         let (tx, rx) = oneshot::channel();
         self.tx
             .send(PersistenceCommand::PersistSession {
-                snapshot,
+                snapshot: Box::new(snapshot),
                 respond_to: tx,
             })
             .await
@@ -213,6 +184,344 @@ impl PersistenceHandle {
             .map_err(|err| anyhow!("blocking load task failed: {err}"))?
     }
 
+    /// 读取导出所需的历史记录，按完成时间升序排列。
+    pub async fn export_sessions(&self, selection: ExportSelection) -> Result<Vec<HistoryEntry>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.export_sessions(&selection))
+            .await
+            .map_err(|err| anyhow!("blocking export load task failed: {err}"))?
+    }
+
+    /// 校验并合并导入的历史记录（导出归档或其他设备的 `history.db`），按会话 ID 去重。
+    pub async fn import_history(&self, source: ImportSource) -> Result<ImportSummary> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.import_from(&source, now_timestamp_ms() as i64))
+            .await
+            .map_err(|err| anyhow!("blocking import task failed: {err}"))?
+    }
+
+    /// 置顶或取消置顶历史记录；置顶的记录不参与保留期清理。
+    pub async fn set_pinned(&self, session_id: String, pinned: bool) -> Result<()> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || {
+            sqlite.set_pinned(&session_id, pinned, now_timestamp_ms() as i64)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking pin task failed: {err}"))?
+    }
+
+    /// 新增或更新词表条目；同一词条（忽略大小写）保留最初的创建时间。
+    pub async fn upsert_vocabulary_term(&self, mut term: VocabularyTerm) -> Result<()> {
+        term.term = term.term.trim().to_string();
+        if term.term.is_empty() {
+            return Err(anyhow!("vocabulary term must not be empty"));
+        }
+        if term.created_at_ms == 0 {
+            term.created_at_ms = now_timestamp_ms() as i64;
+        }
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.upsert_vocabulary_term(&term))
+            .await
+            .map_err(|err| anyhow!("blocking vocabulary task failed: {err}"))?
+    }
+
+    pub async fn remove_vocabulary_term(&self, term: String) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.delete_vocabulary_term(term.trim()))
+            .await
+            .map_err(|err| anyhow!("blocking vocabulary task failed: {err}"))?
+    }
+
+    /// 保存替换规则；无法编译的规则会被拒绝。
+    pub async fn upsert_replacement_rule(&self, mut rule: ReplacementRule) -> Result<()> {
+        if rule.rule_id.trim().is_empty() {
+            return Err(anyhow!("replacement rule id must not be empty"));
+        }
+        rule.validate()?;
+        let now = now_timestamp_ms() as i64;
+        if rule.created_at_ms == 0 {
+            rule.created_at_ms = now;
+        }
+        rule.updated_at_ms = now;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.upsert_replacement_rule(&rule))
+            .await
+            .map_err(|err| anyhow!("blocking replacement rule task failed: {err}"))?
+    }
+
+    pub async fn remove_replacement_rule(&self, rule_id: String) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.delete_replacement_rule(&rule_id))
+            .await
+            .map_err(|err| anyhow!("blocking replacement rule task failed: {err}"))?
+    }
+
+    pub async fn list_replacement_rules(&self) -> Result<Vec<ReplacementRule>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_replacement_rules())
+            .await
+            .map_err(|err| anyhow!("blocking replacement rule task failed: {err}"))?
+    }
+
+    /// 将修正短语对计入个人纠错语料。
+    pub async fn record_corrections(&self, pairs: Vec<(String, String)>) -> Result<()> {
+        if pairs.is_empty() {
+            return Ok(());
+        }
+        let now = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.record_corrections(&pairs, now))
+            .await
+            .map_err(|err| anyhow!("blocking corrections task failed: {err}"))?
+    }
+
+    pub async fn list_corrections(&self) -> Result<Vec<CorrectionPair>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_corrections())
+            .await
+            .map_err(|err| anyhow!("blocking corrections task failed: {err}"))?
+    }
+
+    pub async fn remove_correction(&self, original: String, corrected: String) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.delete_correction(&original, &corrected))
+            .await
+            .map_err(|err| anyhow!("blocking corrections task failed: {err}"))?
+    }
+
+    pub async fn store_noise_baseline(&self, device_id: String, level_db: f32) -> Result<()> {
+        let now = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.store_noise_baseline(&device_id, level_db, now))
+            .await
+            .map_err(|err| anyhow!("blocking noise baseline task failed: {err}"))?
+    }
+
+    pub async fn list_noise_baselines(&self) -> Result<Vec<(String, f32)>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_noise_baselines())
+            .await
+            .map_err(|err| anyhow!("blocking noise baseline task failed: {err}"))?
+    }
+
+    /// 读取用户保存的静音自动停止策略；未保存或无法解析时返回 `None`。
+    pub async fn load_silence_policy(&self) -> Result<Option<SilencePolicy>> {
+        let sqlite = self.sqlite.clone();
+        let stored =
+            tokio::task::spawn_blocking(move || sqlite.load_user_setting(SILENCE_POLICY_SETTING))
+                .await
+                .map_err(|err| anyhow!("blocking user setting task failed: {err}"))??;
+        Ok(stored.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    pub async fn store_silence_policy(&self, policy: SilencePolicy) -> Result<()> {
+        let value = serde_json::to_string(&policy)?;
+        let now = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || {
+            sqlite.store_user_setting(SILENCE_POLICY_SETTING, &value, now)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking user setting task failed: {err}"))?
+    }
+
+    /// 读取上次保存的云端用量；未保存或无法解析时返回 `None`。
+    pub async fn load_cloud_budget_usage(&self) -> Result<Option<BudgetUsage>> {
+        let sqlite = self.sqlite.clone();
+        let stored =
+            tokio::task::spawn_blocking(move || sqlite.load_user_setting(CLOUD_BUDGET_SETTING))
+                .await
+                .map_err(|err| anyhow!("blocking user setting task failed: {err}"))??;
+        Ok(stored.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    pub async fn store_cloud_budget_usage(&self, usage: BudgetUsage) -> Result<()> {
+        let value = serde_json::to_string(&usage)?;
+        let now = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || {
+            sqlite.store_user_setting(CLOUD_BUDGET_SETTING, &value, now)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking user setting task failed: {err}"))?
+    }
+
+    pub async fn load_latency_samples(&self) -> Result<Option<LatencySamples>> {
+        let sqlite = self.sqlite.clone();
+        let stored =
+            tokio::task::spawn_blocking(move || sqlite.load_user_setting(LATENCY_SAMPLES_SETTING))
+                .await
+                .map_err(|err| anyhow!("blocking user setting task failed: {err}"))??;
+        Ok(stored.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    pub async fn store_latency_samples(&self, samples: LatencySamples) -> Result<()> {
+        let value = serde_json::to_string(&samples)?;
+        let now = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || {
+            sqlite.store_user_setting(LATENCY_SAMPLES_SETTING, &value, now)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking user setting task failed: {err}"))?
+    }
+
+    /// 为应用绑定润色风格；`app_identifier` 为空时设置全局默认。
+    pub async fn set_polish_profile(
+        &self,
+        app_identifier: Option<String>,
+        profile: PolishProfile,
+    ) -> Result<()> {
+        let now = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || {
+            sqlite.set_polish_profile(app_identifier.as_deref(), profile, now)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking polish profile task failed: {err}"))?
+    }
+
+    pub async fn clear_polish_profile(&self, app_identifier: Option<String>) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.clear_polish_profile(app_identifier.as_deref()))
+            .await
+            .map_err(|err| anyhow!("blocking polish profile task failed: {err}"))?
+    }
+
+    pub async fn list_polish_profiles(&self) -> Result<Vec<PolishProfileBinding>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_polish_profiles())
+            .await
+            .map_err(|err| anyhow!("blocking polish profile task failed: {err}"))?
+    }
+
+    pub async fn upsert_app_profile(&self, mut profile: AppProfile) -> Result<()> {
+        profile.validate()?;
+        profile.app_identifier = profile.app_identifier.trim().to_string();
+        profile.updated_at_ms = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.upsert_app_profile(&profile))
+            .await
+            .map_err(|err| anyhow!("blocking app profile task failed: {err}"))?
+    }
+
+    pub async fn remove_app_profile(
+        &self,
+        app_identifier: String,
+        field_role: Option<FieldRole>,
+    ) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || {
+            sqlite.delete_app_profile(app_identifier.trim(), field_role)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking app profile task failed: {err}"))?
+    }
+
+    pub async fn list_app_profiles(&self) -> Result<Vec<AppProfile>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_app_profiles())
+            .await
+            .map_err(|err| anyhow!("blocking app profile task failed: {err}"))?
+    }
+
+    pub async fn upsert_session_preset(&self, mut preset: SessionPreset) -> Result<()> {
+        preset.validate()?;
+        preset.name = preset.name.trim().to_string();
+        preset.updated_at_ms = now_timestamp_ms() as i64;
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.upsert_session_preset(&preset))
+            .await
+            .map_err(|err| anyhow!("blocking session preset task failed: {err}"))?
+    }
+
+    pub async fn remove_session_preset(&self, name: String) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.delete_session_preset(name.trim()))
+            .await
+            .map_err(|err| anyhow!("blocking session preset task failed: {err}"))?
+    }
+
+    pub async fn list_session_presets(&self) -> Result<Vec<SessionPreset>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_session_presets())
+            .await
+            .map_err(|err| anyhow!("blocking session preset task failed: {err}"))?
+    }
+
+    pub async fn save_publish_retry(&self, entry: PublishRetryEntry) -> Result<()> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.upsert_publish_retry(&entry))
+            .await
+            .map_err(|err| anyhow!("blocking publish retry task failed: {err}"))?
+    }
+
+    pub async fn remove_publish_retry(&self, retry_id: String) -> Result<bool> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.delete_publish_retry(&retry_id))
+            .await
+            .map_err(|err| anyhow!("blocking publish retry task failed: {err}"))?
+    }
+
+    pub async fn list_publish_retries(&self) -> Result<Vec<PublishRetryEntry>> {
+        let sqlite = self.sqlite.clone();
+        tokio::task::spawn_blocking(move || sqlite.list_publish_retries())
+            .await
+            .map_err(|err| anyhow!("blocking publish retry task failed: {err}"))?
+    }
+
+    pub async fn load_vocabulary(&self) -> Result<Vocabulary> {
+        let sqlite = self.sqlite.clone();
+        let terms = tokio::task::spawn_blocking(move || sqlite.list_vocabulary())
+            .await
+            .map_err(|err| anyhow!("blocking vocabulary task failed: {err}"))??;
+        Ok(Vocabulary::new(terms))
+    }
+
+    /// 使用新密钥原地重新加密历史数据库，并通过遥测事件与可选通道汇报进度。
+    pub async fn rotate_key(
+        &self,
+        new_key: String,
+        progress: Option<mpsc::UnboundedSender<RekeyStage>>,
+    ) -> Result<()> {
+        let sqlite = self.sqlite.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            sqlite.rekey(&new_key, &mut |stage| {
+                record_session_history_key_rotation(stage.as_str(), None);
+                if let Some(progress) = &progress {
+                    let _ = progress.send(stage);
+                }
+            })
+        })
+        .await
+        .map_err(|err| anyhow!("blocking rekey task failed: {err}"))
+        .and_then(|result| result);
+        if let Err(err) = &result {
+            record_session_history_key_rotation("failed", Some(err));
+        }
+        result
+    }
+
+    /// 读取并解密会话录音；未启用录音或该会话没有归档时返回 `None`。
+    pub async fn load_session_audio(&self, session_id: String) -> Result<Option<RecordedAudio>> {
+        let recorder = self
+            .recordings
+            .read()
+            .expect("recordings registry poisoned")
+            .clone();
+        let Some(recorder) = recorder else {
+            return Ok(None);
+        };
+        tokio::task::spawn_blocking(move || {
+            if !recorder.archive_path(&session_id).exists() {
+                return Ok(None);
+            }
+            recorder.load(&session_id).map(Some)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking audio load task failed: {err}"))?
+    }
+
     pub async fn update_accuracy(&self, update: AccuracyUpdate) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.tx
@@ -261,77 +570,63 @@ impl PersistenceHandle {
     }
 
     pub async fn cleanup_expired(&self, now_ms: i64) -> Result<usize> {
-        let (tx, rx) = oneshot::channel();
-        self.tx
-            .send(PersistenceCommand::CleanupExpired {
-                now_ms,
-                respond_to: tx,
-            })
-            .await
-            .map_err(|err| anyhow!("failed to queue cleanup job: {err}"))?;
-        rx.await
-            .map_err(|err| anyhow!("cleanup channel dropped: {err}"))?
+        self.cleanup_history(now_ms, None).await
     }
 
-    pub async fn save_draft(&self, request: DraftSaveRequest) -> Result<DraftRecord> {
-        let record = DraftRecord::from_request(request);
-        let (tx, rx) = oneshot::channel();
-        self.tx
-            .send(PersistenceCommand::StoreDraft {
-                record,
-                respond_to: tx,
-            })
-            .await
-            .map_err(|err| anyhow!("failed to queue draft save: {err}"))?;
-        rx.await
-            .map_err(|err| anyhow!("draft save channel dropped: {err}"))?
+    /// Like [`Self::cleanup_expired`], but also removes everything older than
+    /// `max_age_ms`, pinned entries included.
+    pub async fn cleanup_with_retention_cap(&self, now_ms: i64, max_age_ms: i64) -> Result<usize> {
+        self.cleanup_history(now_ms, Some(max_age_ms)).await
     }
 
-    pub async fn save_notice(&self, request: NoticeSaveRequest) -> Result<NoticeRecord> {
-        let record = NoticeRecord::from_request(request);
+    /// Removes expired sessions and the recordings archived for them.
+    async fn cleanup_history(&self, now_ms: i64, max_age_ms: Option<i64>) -> Result<usize> {
         let (tx, rx) = oneshot::channel();
         self.tx
-            .send(PersistenceCommand::StoreNotice {
-                record,
+            .send(PersistenceCommand::CleanupExpired {
+                now_ms,
+                max_age_ms,
                 respond_to: tx,
             })
             .await
-            .map_err(|err| anyhow!("failed to queue notice save: {err}"))?;
-        rx.await
-            .map_err(|err| anyhow!("notice save channel dropped: {err}"))?
-    }
-
-    pub async fn list_drafts(&self, limit: usize) -> Result<Vec<DraftRecord>> {
-        let (tx, rx) = oneshot::channel();
-        self.tx
-            .send(PersistenceCommand::ListDrafts {
-                limit,
-                respond_to: tx,
+            .map_err(|err| anyhow!("failed to queue cleanup job: {err}"))?;
+        let removed = rx
+            .await
+            .map_err(|err| anyhow!("cleanup channel dropped: {err}"))??;
+        let recorder = self
+            .recordings
+            .read()
+            .expect("recordings registry poisoned")
+            .clone();
+        if let Some(recorder) = recorder {
+            let ids = removed.clone();
+            tokio::task::spawn_blocking(move || {
+                for session_id in &ids {
+                    if let Err(err) = recorder.remove(session_id) {
+                        warn!(
+                            target: "persistence",
+                            %err,
+                            %session_id,
+                            "failed to remove expired session recording"
+                        );
+                    }
+                }
             })
             .await
-            .map_err(|err| anyhow!("failed to queue draft list request: {err}"))?;
-        rx.await
-            .map_err(|err| anyhow!("draft list channel dropped: {err}"))?
+            .map_err(|err| anyhow!("blocking recording cleanup task failed: {err}"))?;
+        }
+        Ok(removed.len())
     }
 
-    pub async fn list_notices(&self, limit: usize) -> Result<Vec<NoticeRecord>> {
+    /// 等待此前提交给持久化队列的命令全部完成，退出前调用以免丢失写入。
+    pub async fn flush(&self) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.tx
-            .send(PersistenceCommand::ListNotices {
-                limit,
-                respond_to: tx,
-            })
+            .send(PersistenceCommand::Flush { respond_to: tx })
             .await
-            .map_err(|err| anyhow!("failed to queue notice list request: {err}"))?;
+            .map_err(|err| anyhow!("failed to queue persistence flush: {err}"))?;
         rx.await
-            .map_err(|err| anyhow!("notice list channel dropped: {err}"))?
-    }
-}
-
-#[cfg(test)]
-impl PersistenceHandle {
-    pub fn sqlite(&self) -> Arc<SqlitePersistence> {
-        Arc::clone(&self.sqlite)
+            .map_err(|err| anyhow!("persistence flush channel dropped: {err}"))
     }
 }
 
@@ -340,15 +635,29 @@ pub struct PersistenceActor {
     drafts: VecDeque<DraftRecord>,
     notices: VecDeque<NoticeRecord>,
     sqlite: Arc<SqlitePersistence>,
+    /// 尚未完成的后台写入与查询。
+    pending: Vec<JoinHandle<()>>,
 }
 
 impl PersistenceActor {
     pub fn new(sqlite: Arc<SqlitePersistence>, rx: mpsc::Receiver<PersistenceCommand>) -> Self {
+        let mut drafts = VecDeque::with_capacity(MAX_DRAFT_HISTORY);
+        let mut notices = VecDeque::with_capacity(MAX_NOTICE_HISTORY);
+        // 预热内存缓存，使重启后的草稿与通知列表无需回查数据库。
+        match sqlite.list_drafts(MAX_DRAFT_HISTORY) {
+            Ok(records) => drafts.extend(records),
+            Err(err) => warn!(target: "persistence", %err, "failed to warm draft cache"),
+        }
+        match sqlite.list_notices(MAX_NOTICE_HISTORY) {
+            Ok(records) => notices.extend(records),
+            Err(err) => warn!(target: "persistence", %err, "failed to warm notice cache"),
+        }
         Self {
             rx,
-            drafts: VecDeque::with_capacity(MAX_DRAFT_HISTORY),
-            notices: VecDeque::with_capacity(MAX_NOTICE_HISTORY),
+            drafts,
+            notices,
             sqlite,
+            pending: Vec::new(),
         }
     }
 
@@ -359,18 +668,18 @@ impl PersistenceActor {
                     snapshot,
                     respond_to,
                 } => {
-                    self.handle_persist_session(snapshot, respond_to);
+                    self.handle_persist_session(*snapshot, respond_to);
                 }
                 PersistenceCommand::SearchHistory { query, respond_to } => {
                     let sqlite = self.sqlite.clone();
-                    tokio::spawn(async move {
+                    self.track(async move {
                         let result = run_blocking(move || sqlite.search_sessions(&query)).await;
                         let _ = respond_to.send(result);
                     });
                 }
                 PersistenceCommand::UpdateAccuracy { update, respond_to } => {
                     let sqlite = self.sqlite.clone();
-                    tokio::spawn(async move {
+                    self.track(async move {
                         let session_id = update.session_id.clone();
                         let flag = update.flag.clone();
                         let remarks = update.remarks.clone();
@@ -408,7 +717,7 @@ impl PersistenceActor {
                     respond_to,
                 } => {
                     let sqlite = self.sqlite.clone();
-                    tokio::spawn(async move {
+                    self.track(async move {
                         let kind = action.kind.clone();
                         let session_id_for_blocking = session_id.clone();
                         let action_for_blocking = action.clone();
@@ -417,19 +726,41 @@ impl PersistenceActor {
                                 .append_post_action(&session_id_for_blocking, &action_for_blocking)
                         })
                         .await;
-                        if let Ok(_) = &result {
+                        if result.is_ok() {
                             record_session_history_action(&session_id, kind.as_str());
                         }
                         let _ = respond_to.send(result);
                     });
                 }
-                PersistenceCommand::CleanupExpired { now_ms, respond_to } => {
+                PersistenceCommand::CleanupExpired {
+                    now_ms,
+                    max_age_ms,
+                    respond_to,
+                } => {
+                    let max_age_ms = max_age_ms
+                        .map_or(HISTORY_RETENTION_MS, |cap| cap.min(HISTORY_RETENTION_MS));
+                    // Drafts expire by the same age; shift "now" so the draft cleanup,
+                    // which counts the full retention window, honours a shorter cap.
+                    let draft_now_ms = now_ms + (HISTORY_RETENTION_MS - max_age_ms);
+                    let draft_cutoff = now_ms - max_age_ms;
+                    self.drafts
+                        .retain(|draft| draft.updated_at_ms as i64 > draft_cutoff);
                     let sqlite = self.sqlite.clone();
-                    tokio::spawn(async move {
+                    self.track(async move {
                         let started = Instant::now();
-                        let result = run_blocking(move || sqlite.cleanup_expired(now_ms)).await;
-                        if let Ok(count) = &result {
-                            record_session_history_cleanup(*count, started.elapsed());
+                        let result = run_blocking(move || {
+                            if let Err(err) = sqlite.cleanup_expired_drafts(draft_now_ms) {
+                                warn!(target: "persistence", %err, "failed to prune expired drafts");
+                            }
+                            let mut removed = sqlite.cleanup_expired(now_ms)?;
+                            if max_age_ms < HISTORY_RETENTION_MS {
+                                removed.extend(sqlite.purge_sessions_before(now_ms - max_age_ms)?);
+                            }
+                            Ok(removed)
+                        })
+                        .await;
+                        if let Ok(removed) = &result {
+                            record_session_history_cleanup(removed.len(), started.elapsed());
                         }
                         let _ = respond_to.send(result);
                     });
@@ -468,18 +799,28 @@ impl PersistenceActor {
                     let result = Ok(self.collect_notices(limit));
                     let _ = respond_to.send(result);
                 }
+                PersistenceCommand::Flush { respond_to } => {
+                    // 只等待此前已受理的写入，不阻塞之后的命令。
+                    let pending = std::mem::take(&mut self.pending);
+                    tokio::spawn(async move {
+                        for task in pending {
+                            let _ = task.await;
+                        }
+                        let _ = respond_to.send(());
+                    });
+                }
             }
         }
         Ok(())
     }
 
     fn handle_persist_session(
-        &self,
+        &mut self,
         snapshot: SessionSnapshot,
         respond_to: oneshot::Sender<Result<()>>,
     ) {
         let sqlite = self.sqlite.clone();
-        tokio::spawn(async move {
+        self.track(async move {
             let mut attempt: u8 = 0;
             let started = Instant::now();
             let mut last_error: Option<anyhow::Error> = None;
@@ -534,61 +875,13 @@ impl PersistenceActor {
         });
     }
 
-    fn store_draft(&mut self, record: DraftRecord) -> Result<DraftRecord> {
-        info!(
-            target: "persistence",
-            draft_id = %record.draft_id,
-            session_id = %record.session_id,
-            "persisting transcript draft"
-        );
-        Self::push_with_limit(&mut self.drafts, record.clone(), MAX_DRAFT_HISTORY);
-        Ok(record)
-    }
-
-    fn store_notice(&mut self, record: NoticeRecord) -> Result<NoticeRecord> {
-        info!(
-            target: "persistence",
-            notice_id = %record.notice_id,
-            session_id = %record.session_id,
-            action = %record.action,
-            result = %record.result,
-            "persisting publish notice"
-        );
-        Self::push_with_limit(&mut self.notices, record.clone(), MAX_NOTICE_HISTORY);
-        Ok(record)
-    }
-
-    fn collect_drafts(&self, limit: usize) -> Vec<DraftRecord> {
-        let effective_limit = limit.min(self.drafts.len());
-        self.drafts
-            .iter()
-            .rev() // Reverse iterator (newest first)
-            .take(effective_limit) // Take the newest N items
-            .cloned()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev() // Reverse again (oldest first among the taken items)
-            .collect()
-    }
-
-    fn collect_notices(&self, limit: usize) -> Vec<NoticeRecord> {
-        let effective_limit = limit.min(self.notices.len());
-        self.notices
-            .iter()
-            .rev() // Reverse iterator (newest first)
-            .take(effective_limit) // Take the newest N items
-            .cloned()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev() // Reverse again (oldest first among the taken items)
-            .collect()
-    }
-
-    fn push_with_limit<T>(deque: &mut VecDeque<T>, item: T, limit: usize) {
-        if deque.len() >= limit {
-            deque.pop_front();
-        }
-        deque.push_back(item);
+    /// 在后台执行命令，并登记句柄以便 `Flush` 等待其完成。
+    fn track<F>(&mut self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.pending.retain(|handle| !handle.is_finished());
+        self.pending.push(tokio::spawn(task));
     }
 }
 
@@ -609,111 +902,112 @@ mod legacy_tests {
     use tokio::sync::mpsc;
 
     #[tokio::test]
-    async fn saves_draft_with_defaults_and_retrieves_history() {
+    async fn retention_cap_removes_pinned_sessions_past_the_cap() {
+        use crate::persistence::sqlite::tests::snapshot;
+
         let (tx, rx) = mpsc::channel(4);
         let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
-        let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
-        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
-
-        let request = DraftSaveRequest {
-            draft_id: "draft-1".into(),
-            session_id: "session-1".into(),
-            content: "Hello".into(),
-            title: None,
-            tags: None,
-        };
-
-        let record = handle
-            .save_draft(request)
-            .await
-            .expect("draft save should succeed");
-
-        assert_eq!(record.title, DEFAULT_DRAFT_TITLE);
-        assert_eq!(record.tags, vec![DEFAULT_DRAFT_TAG.to_string()]);
-
-        let history = handle
-            .list_drafts(10)
-            .await
-            .expect("draft history should be returned");
-        assert_eq!(history.len(), 1);
-        assert_eq!(history[0].draft_id, "draft-1");
+        let handle = PersistenceHandle::new(tx, sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite.clone(), rx).run());
+
+        let now_ms = now_timestamp_ms() as i64;
+        let cap_ms = 24 * 3_600 * 1_000;
+        sqlite
+            .insert_session(&snapshot("old", now_ms - 2 * cap_ms, "old", "Old."))
+            .unwrap();
+        sqlite
+            .insert_session(&snapshot("recent", now_ms - 1_000, "new", "New."))
+            .unwrap();
+        sqlite.set_pinned("old", true, now_ms).unwrap();
+
+        assert_eq!(handle.cleanup_expired(now_ms).await.unwrap(), 0);
+        assert_eq!(
+            handle
+                .cleanup_with_retention_cap(now_ms, cap_ms)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(sqlite.load_session("old").unwrap().is_none());
+        assert!(sqlite.load_session("recent").unwrap().is_some());
     }
 
     #[tokio::test]
-    async fn respects_draft_list_limit_and_order() {
+    async fn cleanup_removes_recordings_of_expired_sessions() {
+        use crate::audio::{AudioCacheKeys, AudioPipeline};
+        use crate::persistence::sqlite::tests::snapshot;
+
         let (tx, rx) = mpsc::channel(4);
         let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
-        let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
-        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
-
-        for idx in 0..5 {
-            let request = DraftSaveRequest {
-                draft_id: format!("draft-{idx}"),
-                session_id: "session".into(),
-                content: format!("draft content {idx}"),
-                title: Some(format!("Custom {idx}")),
-                tags: Some(vec!["transcript".into(), idx.to_string()]),
-            };
-
-            handle
-                .save_draft(request)
+        let handle = PersistenceHandle::new(tx, sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite.clone(), rx).run());
+
+        let dir = tempfile::tempdir().unwrap();
+        let recorder =
+            SessionRecorder::new(dir.path(), AudioCacheKeys::derive(&[3u8; 32]).unwrap());
+        let pipeline = AudioPipeline::new();
+        for session_id in ["expired", "kept"] {
+            recorder.start(&pipeline, session_id).await.unwrap();
+            pipeline
+                .push_pcm_frame(vec![0.25_f32; 1_600])
                 .await
-                .expect("draft save should succeed");
+                .unwrap();
+            recorder.finish().await.unwrap().expect("active recording");
         }
-
-        let history = handle
-            .list_drafts(3)
-            .await
-            .expect("draft list should be returned");
-
-        assert_eq!(history.len(), 3);
-        assert_eq!(history[0].draft_id, "draft-2");
-        assert_eq!(history[1].draft_id, "draft-3");
-        assert_eq!(history[2].draft_id, "draft-4");
-        assert_eq!(history[2].title, "Custom 4");
-        assert_eq!(
-            history[2].tags,
-            vec!["transcript".to_string(), "4".to_string()]
-        );
+        handle.attach_recordings(Some(recorder.clone()));
+
+        let now_ms = now_timestamp_ms() as i64;
+        sqlite
+            .insert_session(&snapshot("expired", now_ms - 1_000, "old", "Old."))
+            .unwrap();
+        sqlite
+            .insert_session(&snapshot("kept", now_ms - 1_000, "new", "New."))
+            .unwrap();
+        sqlite.set_pinned("kept", true, now_ms).unwrap();
+
+        let later = now_ms + HISTORY_RETENTION_MS;
+        assert_eq!(handle.cleanup_expired(later).await.unwrap(), 1);
+        assert!(!recorder.archive_path("expired").exists());
+        assert!(recorder.archive_path("kept").exists());
+        assert!(recorder.load("kept").is_ok());
     }
 
     #[tokio::test]
-    async fn stores_notices_and_limits_history() {
+    async fn flush_waits_for_queued_session_writes() {
         let (tx, rx) = mpsc::channel(4);
         let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
         let handle = PersistenceHandle::new(tx.clone(), sqlite.clone());
-        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
-
-        for idx in 0..(MAX_NOTICE_HISTORY + 5) {
-            let request = NoticeSaveRequest {
-                notice_id: format!("notice-{idx}"),
-                session_id: "session".into(),
-                action: "copy".into(),
-                result: if idx % 2 == 0 {
-                    "success".into()
-                } else {
-                    "failure".into()
-                },
-                level: "warn".into(),
-                message: format!("notice #{idx}"),
-                undo_token: None,
-            };
-
-            handle
-                .save_notice(request)
-                .await
-                .expect("notice save should succeed");
-        }
+        tokio::spawn(PersistenceActor::new(sqlite.clone(), rx).run());
+
+        // 提交后不等待结果，模拟退出时仍在队列中的写入。
+        let (respond_to, _) = oneshot::channel();
+        tx.send(PersistenceCommand::PersistSession {
+            snapshot: Box::new(SessionSnapshot {
+                session_id: "flush-1".into(),
+                started_at_ms: 0,
+                completed_at_ms: 0,
+                locale: None,
+                app_identifier: None,
+                app_version: None,
+                confidence_score: None,
+                raw_transcript: "raw".into(),
+                polished_transcript: "polished".into(),
+                metadata: json!({}),
+                post_actions: vec![],
+                language_segments: vec![],
+                translated_transcript: None,
+                translation_locale: None,
+                quality_flags: Vec::new(),
+                speed: None,
+                meeting: None,
+                tags: Vec::new(),
+            }),
+            respond_to,
+        })
+        .await
+        .unwrap();
 
-        let history = handle
-            .list_notices(50)
-            .await
-            .expect("notice history should be returned");
-        assert_eq!(history.len(), 50);
-        assert_eq!(history.first().unwrap().notice_id, "notice-195");
-        assert_eq!(
-            history.last().unwrap().notice_id,
-            format!("notice-{}", MAX_NOTICE_HISTORY + 4)
-        );
+        handle.flush().await.unwrap();
+        assert!(sqlite.load_session("flush-1").unwrap().is_some());
     }
 }
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use serde_json::json;
+
+use super::PersistenceHandle;
+use crate::orchestrator::redaction::{RedactionMode, Redactor};
+use crate::session::history::SessionSnapshot;
+
+/// 遮盖模式改写全部转写文本并记录命中数；标注模式保留原文，在元数据中记录润色稿的命中位置。
+pub(super) fn redact_snapshot(redactor: &Redactor, snapshot: &mut SessionSnapshot) {
+    let annotation = match redactor.mode() {
+        RedactionMode::Strip => return,
+        RedactionMode::Annotate => {
+            let spans = redactor.detect(&snapshot.polished_transcript);
+            if spans.is_empty() {
+                return;
+            }
+            json!({ "mode": RedactionMode::Annotate.as_str(), "spans": spans })
+        }
+        RedactionMode::Mask => {
+            let count = redactor.detect(&snapshot.raw_transcript).len()
+                + redactor.detect(&snapshot.polished_transcript).len();
+            if count == 0 {
+                return;
+            }
+            snapshot.raw_transcript = redactor.mask(&snapshot.raw_transcript);
+            snapshot.polished_transcript = redactor.mask(&snapshot.polished_transcript);
+            snapshot.translated_transcript = snapshot
+                .translated_transcript
+                .as_deref()
+                .map(|text| redactor.mask(text));
+            for flag in &mut snapshot.quality_flags {
+                flag.text = redactor.mask(&flag.text);
+            }
+            // 按语种切分的文本段在写入时由遮盖后的润色稿重新生成。
+            snapshot.language_segments.clear();
+            json!({ "mode": RedactionMode::Mask.as_str(), "count": count })
+        }
+    };
+    if snapshot.metadata.is_null() {
+        snapshot.metadata = json!({});
+    }
+    if let Some(metadata) = snapshot.metadata.as_object_mut() {
+        metadata.insert("redaction".into(), annotation);
+    }
+}
+
+impl PersistenceHandle {
+    /// 设置写入前的敏感信息处理：遮盖模式改写会话、草稿与检查点文本，标注模式只记录命中位置。
+    pub fn set_redactor(&self, redactor: Option<Arc<Redactor>>) {
+        *self.redactor.write().expect("redactor registry poisoned") = redactor;
+    }
+
+    pub fn redactor(&self) -> Option<Arc<Redactor>> {
+        self.redactor
+            .read()
+            .expect("redactor registry poisoned")
+            .clone()
+    }
+
+    /// 遮盖模式下的脱敏器；其他模式不改写落盘文本。
+    pub(super) fn masking_redactor(&self) -> Option<Arc<Redactor>> {
+        self.redactor()
+            .filter(|redactor| redactor.mode() == RedactionMode::Mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::{SqliteConfig, SqlitePersistence};
+    use crate::persistence::{DraftSaveRequest, PersistenceActor};
+    use serde_json::Value as JsonValue;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn redactor_masks_or_annotates_persisted_text() {
+        let (tx, rx) = mpsc::channel(4);
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        let handle = PersistenceHandle::new(tx, sqlite.clone());
+        tokio::spawn(PersistenceActor::new(sqlite, rx).run());
+        let snapshot = |session_id: &str| SessionSnapshot {
+            session_id: session_id.into(),
+            started_at_ms: 0,
+            completed_at_ms: 0,
+            locale: None,
+            app_identifier: None,
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: "mail ann@example.com".into(),
+            polished_transcript: "Mail ann@example.com.".into(),
+            metadata: JsonValue::Null,
+            post_actions: vec![],
+            language_segments: vec![],
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+        };
+
+        handle.set_redactor(Some(Arc::new(
+            Redactor::new(RedactionMode::Mask, &[]).unwrap(),
+        )));
+        handle.persist_session(snapshot("masked")).await.unwrap();
+        let entry = handle.load_session("masked".into()).await.unwrap().unwrap();
+        assert_eq!(entry.raw_transcript, "mail [EMAIL]");
+        assert_eq!(entry.polished_transcript, "Mail [EMAIL].");
+        assert_eq!(entry.metadata["redaction"]["count"], 2);
+        let draft = handle
+            .save_draft(DraftSaveRequest {
+                draft_id: "draft-1".into(),
+                session_id: "masked".into(),
+                content: "call +1 415 555 0100".into(),
+                title: None,
+                tags: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(draft.content, "call [PHONE]");
+
+        handle.set_redactor(Some(Arc::new(
+            Redactor::new(RedactionMode::Annotate, &[]).unwrap(),
+        )));
+        handle.persist_session(snapshot("annotated")).await.unwrap();
+        let entry = handle
+            .load_session("annotated".into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.polished_transcript, "Mail ann@example.com.");
+        assert_eq!(
+            entry.metadata["redaction"]["spans"],
+            json!([{ "kind": "email", "start": 5, "end": 20 }])
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::AtomicI64;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Context, Result};
 use r2d2::{Pool, PooledConnection};
@@ -9,25 +10,27 @@ use rusqlite::types::Value;
 use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Row};
 use serde_json::Value as JsonValue;
 
+use crate::orchestrator::language::segment_languages;
 use crate::session::history::{
-    AccuracyFlag, AccuracyUpdate, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery,
+    AccuracyFlag, AccuracyUpdate, ExportSelection, HistoryEntry, HistoryPostAction,
     SessionSnapshot, HISTORY_PREVIEW_LIMIT,
 };
 
-/// Provides SQLCipher key material for the local database.
-pub trait KeyResolver: Send + Sync {
-    fn resolve_key(&self) -> Result<Option<String>>;
-}
-
-/// Key resolver that reads the key material from the `FLOWWISPER_SQLCIPHER_KEY` env variable.
-#[derive(Default)]
-pub struct EnvKeyResolver;
-
-impl KeyResolver for EnvKeyResolver {
-    fn resolve_key(&self) -> Result<Option<String>> {
-        Ok(std::env::var("FLOWWISPER_SQLCIPHER_KEY").ok())
-    }
-}
+mod checkpoints;
+mod drafts;
+mod import;
+mod keys;
+mod migrations;
+mod preferences;
+mod retention;
+mod retry_queue;
+mod search;
+mod snapshot;
+mod sync;
+mod telemetry_queue;
+
+pub use keys::{EnvKeyResolver, KeyResolver, RekeyStage, SecretStoreKeyResolver};
+pub use telemetry_queue::QueuedTelemetry;
 
 /// Storage location configuration for the SQLCipher database.
 #[derive(Debug, Clone)]
@@ -75,7 +78,7 @@ impl SqliteConfig {
             path: SqlitePath::Memory,
             pool_size: 4,
             busy_timeout: Duration::from_millis(250),
-            key_resolver: Arc::new(EnvKeyResolver::default()),
+            key_resolver: Arc::new(EnvKeyResolver),
         }
     }
 }
@@ -83,8 +86,11 @@ impl SqliteConfig {
 /// Handle that manages SQLCipher backed persistence.
 #[derive(Clone)]
 pub struct SqlitePersistence {
-    pool: Pool<SqliteConnectionManager>,
+    pool: Arc<RwLock<Pool<SqliteConnectionManager>>>,
     db_path: Option<PathBuf>,
+    config: SqliteConfig,
+    /// Undelivered telemetry rows kept after each enqueue; lowered while uploads are offline.
+    telemetry_queue_cap: Arc<AtomicI64>,
 }
 
 pub(crate) const MAX_TELEMETRY_QUEUE: i64 = 300;
@@ -92,18 +98,9 @@ pub(crate) const MAX_TELEMETRY_QUEUE: i64 = 300;
 impl SqlitePersistence {
     /// Bootstraps a SQLCipher connection pool and runs the database migrations.
     pub fn bootstrap(config: SqliteConfig) -> Result<Self> {
+        Self::resume_interrupted_rekey(&config)?;
         let key_material = config.key_resolver.resolve_key()?;
-        let key_for_init = key_material.clone();
-        let busy_timeout = config.busy_timeout;
-        let manager = config.path.to_manager().with_init(move |conn| {
-            Self::configure_connection(conn, busy_timeout, key_for_init.as_deref())
-        });
-
-        let pool = Pool::builder()
-            .max_size(config.pool_size)
-            .connection_timeout(Duration::from_secs(5))
-            .build(manager)
-            .context("failed to create SQLCipher connection pool")?;
+        let pool = Self::build_pool(&config, key_material.clone())?;
 
         {
             let mut conn = pool
@@ -114,15 +111,38 @@ impl SqlitePersistence {
         }
 
         Ok(Self {
-            pool,
+            pool: Arc::new(RwLock::new(pool)),
             db_path: config.path.as_path().map(Path::to_path_buf),
+            config,
+            telemetry_queue_cap: Arc::new(AtomicI64::new(MAX_TELEMETRY_QUEUE)),
         })
     }
 
+    fn build_pool(
+        config: &SqliteConfig,
+        key: Option<String>,
+    ) -> Result<Pool<SqliteConnectionManager>> {
+        let busy_timeout = config.busy_timeout;
+        let manager = config
+            .path
+            .to_manager()
+            .with_init(move |conn| Self::configure_connection(conn, busy_timeout, key.as_deref()));
+
+        Pool::builder()
+            .max_size(config.pool_size)
+            .connection_timeout(Duration::from_secs(5))
+            .build(manager)
+            .context("failed to create SQLCipher connection pool")
+    }
+
     /// Provides access to a pooled connection for custom commands.
     pub fn connection(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
-        self.pool
-            .get()
+        let pool = self
+            .pool
+            .read()
+            .map_err(|_| anyhow!("SQLCipher connection pool poisoned"))?
+            .clone();
+        pool.get()
             .map_err(|err| anyhow!("failed to obtain SQLCipher connection: {err}"))
     }
 
@@ -131,13 +151,14 @@ impl SqlitePersistence {
         busy_timeout: Duration,
         key: Option<&str>,
     ) -> rusqlite::Result<()> {
+        // SQLCipher requires the key before any statement touches the database file.
+        if let Some(value) = key {
+            conn.pragma_update(None, "key", value)?;
+        }
         conn.busy_timeout(busy_timeout)?;
         conn.execute_batch("PRAGMA journal_mode=WAL;")?;
         conn.execute_batch("PRAGMA foreign_keys=ON;")?;
         conn.execute_batch("PRAGMA synchronous=NORMAL;")?;
-        if let Some(value) = key {
-            conn.pragma_update(None, "key", value)?;
-        }
         Ok(())
     }
 
@@ -158,75 +179,6 @@ impl SqlitePersistence {
         Ok(())
     }
 
-    fn run_migrations(conn: &mut Connection) -> Result<()> {
-        conn.execute_batch(
-            r#"
-            CREATE TABLE IF NOT EXISTS sessions (
-                session_id TEXT PRIMARY KEY,
-                started_at_ms INTEGER NOT NULL,
-                completed_at_ms INTEGER NOT NULL,
-                duration_ms INTEGER NOT NULL,
-                locale TEXT,
-                app_identifier TEXT,
-                app_version TEXT,
-                raw_transcript TEXT NOT NULL,
-                polished_transcript TEXT NOT NULL,
-                confidence_score REAL,
-                accuracy_flag TEXT,
-                accuracy_remarks TEXT,
-                post_actions TEXT NOT NULL DEFAULT '[]',
-                expires_at_ms INTEGER NOT NULL,
-                metadata TEXT NOT NULL DEFAULT '{}'
-            );
-
-            CREATE TABLE IF NOT EXISTS telemetry_queue (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id TEXT NOT NULL,
-                event_type TEXT NOT NULL,
-                payload TEXT NOT NULL,
-                created_at_ms INTEGER NOT NULL,
-                delivered INTEGER NOT NULL DEFAULT 0
-            );
-
-            CREATE VIRTUAL TABLE IF NOT EXISTS session_index USING fts5(
-                session_id UNINDEXED,
-                raw_transcript,
-                polished_transcript,
-                app_identifier,
-                content='sessions',
-                content_rowid='rowid',
-                tokenize='unicode61 remove_diacritics 2'
-            );
-
-            CREATE TRIGGER IF NOT EXISTS sessions_ai AFTER INSERT ON sessions BEGIN
-                INSERT INTO session_index(rowid, session_id, raw_transcript, polished_transcript, app_identifier)
-                VALUES (new.rowid, new.session_id, new.raw_transcript, new.polished_transcript, new.app_identifier);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS sessions_ad AFTER DELETE ON sessions BEGIN
-                INSERT INTO session_index(session_index, rowid, session_id, raw_transcript, polished_transcript, app_identifier)
-                VALUES('delete', old.rowid, old.session_id, old.raw_transcript, old.polished_transcript, old.app_identifier);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS sessions_au AFTER UPDATE ON sessions BEGIN
-                INSERT INTO session_index(session_index, rowid, session_id, raw_transcript, polished_transcript, app_identifier)
-                VALUES('delete', old.rowid, old.session_id, old.raw_transcript, old.polished_transcript, old.app_identifier);
-                INSERT INTO session_index(rowid, session_id, raw_transcript, polished_transcript, app_identifier)
-                VALUES (new.rowid, new.session_id, new.raw_transcript, new.polished_transcript, new.app_identifier);
-            END;
-            "#,
-        )
-        .context("failed to run SQLCipher migrations")?;
-
-        // Verify that FTS5 is operational.
-        conn.prepare("SELECT count(*) FROM session_index")
-            .context("FTS5 session_index missing after migration")?
-            .query_row([], |row| row.get::<_, i64>(0))
-            .context("failed to read session_index after migration")?;
-
-        Ok(())
-    }
-
     pub fn insert_session(&self, snapshot: &SessionSnapshot) -> Result<()> {
         let mut conn = self.connection()?;
         let tx = conn
@@ -241,6 +193,33 @@ impl SqlitePersistence {
             serde_json::to_string(&snapshot.metadata)
                 .context("failed to serialize session metadata")?
         };
+        let language_segments = if snapshot.language_segments.is_empty() {
+            let transcript = if snapshot.polished_transcript.trim().is_empty() {
+                &snapshot.raw_transcript
+            } else {
+                &snapshot.polished_transcript
+            };
+            segment_languages(transcript, snapshot.locale.as_deref())
+        } else {
+            snapshot.language_segments.clone()
+        };
+        let language_segments = serde_json::to_string(&language_segments)
+            .context("failed to serialize language segments")?;
+        let quality_flags = serde_json::to_string(&snapshot.quality_flags)
+            .context("failed to serialize quality flags")?;
+        let speed = snapshot
+            .speed
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("failed to serialize dictation speed")?;
+        let meeting = snapshot
+            .meeting
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("failed to serialize meeting notes")?;
+        let tags = serde_json::to_string(&snapshot.tags).context("failed to serialize tags")?;
 
         tx.execute(
             "INSERT INTO sessions (
@@ -258,8 +237,17 @@ impl SqlitePersistence {
                 accuracy_remarks,
                 post_actions,
                 expires_at_ms,
-                metadata
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                metadata,
+                language_segments,
+                translated_transcript,
+                translation_locale,
+                quality_flags,
+                speed,
+                meeting,
+                tags,
+                updated_at_ms
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                ?19, ?20, ?21, ?22, ?23)
             ON CONFLICT(session_id) DO UPDATE SET
                 started_at_ms=excluded.started_at_ms,
                 completed_at_ms=excluded.completed_at_ms,
@@ -273,6 +261,14 @@ impl SqlitePersistence {
                 post_actions=excluded.post_actions,
                 expires_at_ms=excluded.expires_at_ms,
                 metadata=excluded.metadata,
+                language_segments=excluded.language_segments,
+                translated_transcript=excluded.translated_transcript,
+                translation_locale=excluded.translation_locale,
+                quality_flags=excluded.quality_flags,
+                speed=excluded.speed,
+                meeting=excluded.meeting,
+                tags=excluded.tags,
+                updated_at_ms=excluded.updated_at_ms,
                 accuracy_flag=COALESCE(sessions.accuracy_flag, excluded.accuracy_flag),
                 accuracy_remarks=COALESCE(sessions.accuracy_remarks, excluded.accuracy_remarks)
             ",
@@ -292,6 +288,14 @@ impl SqlitePersistence {
                 post_actions,
                 snapshot.expires_at_ms(),
                 metadata,
+                language_segments,
+                snapshot.translated_transcript.as_deref(),
+                snapshot.translation_locale.as_deref(),
+                quality_flags,
+                speed,
+                meeting,
+                tags,
+                now_ms(),
             ],
         )
         .context("failed to insert session record")?;
@@ -305,99 +309,53 @@ impl SqlitePersistence {
         let mut stmt = conn.prepare(
             "SELECT session_id, started_at_ms, completed_at_ms, duration_ms, locale,
                 app_identifier, app_version, raw_transcript, polished_transcript,
-                confidence_score, accuracy_flag, accuracy_remarks, post_actions, metadata
+                confidence_score, accuracy_flag, accuracy_remarks, post_actions, metadata, pinned,
+                language_segments, translated_transcript, translation_locale, quality_flags,
+                speed, meeting, tags
             FROM sessions WHERE session_id = ?1",
         )?;
 
         let entry = stmt
-            .query_row(params![session_id], |row| Self::read_history_entry(row))
+            .query_row(params![session_id], Self::read_history_entry)
             .optional()?;
         Ok(entry)
     }
 
-    pub fn search_sessions(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+    /// Loads the entries selected for export, oldest first.
+    pub fn export_sessions(&self, selection: &ExportSelection) -> Result<Vec<HistoryEntry>> {
         let conn = self.connection()?;
-        let mut filters = Vec::new();
-        let mut values: Vec<Value> = Vec::new();
-
-        if let Some(keyword) = query
-            .keyword
-            .as_ref()
-            .and_then(|value| Some(value.trim().to_string()))
-            .filter(|value| !value.is_empty())
-        {
-            filters.push(
-                "rowid IN (SELECT rowid FROM session_index WHERE session_index MATCH ?)"
-                    .to_string(),
-            );
-            values.push(Value::Text(format!("{}*", keyword)));
-        }
-
-        if let Some(locale) = query
-            .locale
-            .as_ref()
-            .and_then(|value| Some(value.trim().to_string()))
-            .filter(|value| !value.is_empty())
-        {
-            filters.push("locale = ?".to_string());
-            values.push(Value::Text(locale));
-        }
-
-        if let Some(app) = query
-            .app_identifier
-            .as_ref()
-            .and_then(|value| Some(value.trim().to_string()))
-            .filter(|value| !value.is_empty())
-        {
-            filters.push("app_identifier = ?".to_string());
-            values.push(Value::Text(app));
-        }
-
-        let mut base_query = "SELECT session_id, started_at_ms, completed_at_ms, duration_ms, \
-            locale, app_identifier, app_version, raw_transcript, polished_transcript, \
-            confidence_score, accuracy_flag, accuracy_remarks, post_actions, metadata \
-            FROM sessions"
-            .to_string();
-
-        if !filters.is_empty() {
-            base_query.push_str(" WHERE ");
-            base_query.push_str(&filters.join(" AND "));
-        }
-
-        base_query.push_str(" ORDER BY completed_at_ms DESC LIMIT ? OFFSET ?");
-
-        let mut page_values = values.clone();
-        page_values.push(Value::Integer(query.limit as i64));
-        page_values.push(Value::Integer(query.offset as i64));
+        let (filter, values) = match selection {
+            ExportSelection::Sessions { session_ids } => {
+                if session_ids.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let placeholders = vec!["?"; session_ids.len()].join(", ");
+                let values = session_ids
+                    .iter()
+                    .map(|id| Value::Text(id.clone()))
+                    .collect::<Vec<_>>();
+                (format!("session_id IN ({placeholders})"), values)
+            }
+            ExportSelection::DateRange { from_ms, to_ms } => (
+                "completed_at_ms >= ? AND completed_at_ms < ?".to_string(),
+                vec![Value::Integer(*from_ms), Value::Integer(*to_ms)],
+            ),
+        };
 
-        let mut stmt = conn.prepare(&base_query)?;
-        let mut rows = stmt.query(rusqlite::params_from_iter(page_values.iter()))?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT session_id, started_at_ms, completed_at_ms, duration_ms, locale,
+                app_identifier, app_version, raw_transcript, polished_transcript,
+                confidence_score, accuracy_flag, accuracy_remarks, post_actions, metadata, pinned,
+                language_segments, translated_transcript, translation_locale, quality_flags,
+                speed, meeting, tags
+            FROM sessions WHERE {filter} ORDER BY completed_at_ms ASC"
+        ))?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(values.iter()))?;
         let mut entries = Vec::new();
         while let Some(row) = rows.next()? {
             entries.push(Self::read_history_entry(row)?);
         }
-
-        let mut count_sql = "SELECT COUNT(*) FROM sessions".to_string();
-        if !filters.is_empty() {
-            count_sql.push_str(" WHERE ");
-            count_sql.push_str(&filters.join(" AND "));
-        }
-
-        let total: i64 = conn
-            .prepare(&count_sql)?
-            .query_row(rusqlite::params_from_iter(values.iter()), |row| row.get(0))?;
-
-        let next_offset = if (query.offset + entries.len()) < total as usize {
-            Some(query.offset + entries.len())
-        } else {
-            None
-        };
-
-        Ok(HistoryPage {
-            total: Some(total),
-            next_offset,
-            entries,
-        })
+        Ok(entries)
     }
 
     pub fn update_accuracy(&self, update: &AccuracyUpdate) -> Result<()> {
@@ -407,8 +365,16 @@ impl SqlitePersistence {
             .context("failed to open transaction for accuracy update")?;
 
         let affected = tx.execute(
-            "UPDATE sessions SET accuracy_flag = ?2, accuracy_remarks = ?3 WHERE session_id = ?1",
-            params![update.session_id, update.flag.as_str(), update.remarks],
+            "UPDATE sessions SET accuracy_flag = ?2, accuracy_remarks = ?3,
+                polished_transcript = COALESCE(?4, polished_transcript), updated_at_ms = ?5
+             WHERE session_id = ?1",
+            params![
+                update.session_id,
+                update.flag.as_str(),
+                update.remarks,
+                update.corrected_transcript,
+                now_ms(),
+            ],
         )?;
 
         if affected == 0 {
@@ -446,8 +412,8 @@ impl SqlitePersistence {
         let encoded = serde_json::to_string(&actions).context("failed to encode post actions")?;
 
         let updated = tx.execute(
-            "UPDATE sessions SET post_actions = ?2 WHERE session_id = ?1",
-            params![session_id, encoded],
+            "UPDATE sessions SET post_actions = ?2, updated_at_ms = ?3 WHERE session_id = ?1",
+            params![session_id, encoded, now_ms()],
         )?;
 
         if updated == 0 {
@@ -459,29 +425,6 @@ impl SqlitePersistence {
         Ok(actions)
     }
 
-    pub fn enqueue_telemetry(
-        &self,
-        session_id: &str,
-        event_type: &str,
-        payload: JsonValue,
-    ) -> Result<()> {
-        let conn = self.connection()?;
-        let encoded = serde_json::to_string(&payload)
-            .context("failed to encode telemetry payload for queue")?;
-        conn.execute(
-            "INSERT INTO telemetry_queue(session_id, event_type, payload, created_at_ms)
-             VALUES (?1, ?2, ?3, strftime('%s','now') * 1000)",
-            params![session_id, event_type, encoded],
-        )?;
-        conn.execute(
-            "DELETE FROM telemetry_queue WHERE id NOT IN (
-                SELECT id FROM telemetry_queue ORDER BY id DESC LIMIT ?1
-            )",
-            params![MAX_TELEMETRY_QUEUE],
-        )?;
-        Ok(())
-    }
-
     fn read_history_entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
         let raw_transcript: String = row.get("raw_transcript")?;
         let polished_transcript: String = row.get("polished_transcript")?;
@@ -509,7 +452,30 @@ impl SqlitePersistence {
         let metadata = row
             .get::<_, Option<String>>("metadata")?
             .and_then(|json| serde_json::from_str(&json).ok())
-            .unwrap_or_else(|| JsonValue::default());
+            .unwrap_or_else(JsonValue::default);
+
+        let language_segments = row
+            .get::<_, Option<String>>("language_segments")?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        let quality_flags = row
+            .get::<_, Option<String>>("quality_flags")?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        let speed = row
+            .get::<_, Option<String>>("speed")?
+            .and_then(|json| serde_json::from_str(&json).ok());
+
+        let meeting = row
+            .get::<_, Option<String>>("meeting")?
+            .and_then(|json| serde_json::from_str(&json).ok());
+
+        let tags = row
+            .get::<_, Option<String>>("tags")?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
 
         let confidence_score = row
             .get::<_, Option<f64>>("confidence_score")?
@@ -531,27 +497,209 @@ impl SqlitePersistence {
             post_actions,
             metadata,
             confidence_score,
+            pinned: row.get("pinned")?,
+            language_segments,
+            translated_transcript: row.get("translated_transcript")?,
+            translation_locale: row.get("translation_locale")?,
+            quality_flags,
+            speed,
+            meeting,
+            tags,
+            search_hit: None,
         })
     }
 
-    /// Deletes expired sessions according to the configured TTL.
-    pub fn cleanup_expired(&self, now_ms: i64) -> Result<usize> {
+    /// 对在用的历史库执行 `PRAGMA integrity_check`，完好时返回 `["ok"]`，否则返回各项问题。
+    pub fn integrity_check(&self) -> Result<Vec<String>> {
         let conn = self.connection()?;
-        let affected = conn.execute(
-            "DELETE FROM sessions WHERE expires_at_ms <= ?1",
-            params![now_ms],
-        )?;
-        Ok(affected)
+        let mut statement = conn
+            .prepare("PRAGMA integrity_check")
+            .context("failed to prepare integrity check")?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .context("failed to run integrity check")?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read integrity check results")
     }
 
     pub fn database_path(&self) -> Option<&Path> {
         self.db_path.as_deref()
     }
+
+    /// Key material the database is currently opened with.
+    pub(crate) fn key_material(&self) -> Result<Option<String>> {
+        self.config.key_resolver.resolve_key()
+    }
+}
+
+/// Wall-clock time stamped on local session edits.
+pub(super) fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
-impl SqlitePersistence {
-    pub fn run_migrations_for_tests(conn: &mut Connection) -> Result<()> {
-        Self::run_migrations(conn)
+pub(crate) mod tests {
+    use super::*;
+    use crate::orchestrator::{LanguageSegment, QualityFlag};
+    use crate::session::history::{DictationSpeed, HistoryQuery};
+
+    pub(crate) fn snapshot(
+        session_id: &str,
+        completed_at_ms: i64,
+        raw: &str,
+        polished: &str,
+    ) -> SessionSnapshot {
+        SessionSnapshot {
+            session_id: session_id.into(),
+            started_at_ms: completed_at_ms - 1_000,
+            completed_at_ms,
+            locale: Some("en-US".into()),
+            app_identifier: Some("com.example.notes".into()),
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: raw.into(),
+            polished_transcript: polished.into(),
+            metadata: JsonValue::Null,
+            post_actions: Vec::new(),
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+        }
+    }
+
+    pub(super) fn keyword_query(keyword: &str) -> HistoryQuery {
+        HistoryQuery {
+            keyword: Some(keyword.into()),
+            limit: 10,
+            ..HistoryQuery::default()
+        }
+    }
+
+    #[test]
+    fn export_selects_sessions_by_id_or_date_range() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        for (id, completed) in [("s-1", 1_000), ("s-2", 2_000), ("s-3", 3_000)] {
+            sqlite
+                .insert_session(&snapshot(id, completed, "raw", "polished"))
+                .unwrap();
+        }
+
+        let ids = |entries: Vec<HistoryEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| entry.session_id)
+                .collect::<Vec<_>>()
+        };
+
+        let selected = sqlite
+            .export_sessions(&ExportSelection::Sessions {
+                session_ids: vec!["s-3".into(), "s-1".into(), "missing".into()],
+            })
+            .unwrap();
+        assert_eq!(ids(selected), vec!["s-1", "s-3"]);
+
+        let ranged = sqlite
+            .export_sessions(&ExportSelection::DateRange {
+                from_ms: 2_000,
+                to_ms: 3_000,
+            })
+            .unwrap();
+        assert_eq!(ids(ranged), vec!["s-2"]);
+    }
+
+    #[test]
+    fn stores_language_segments_and_translation_with_sessions() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut mixed = snapshot("s-1", 1_000, "", "部署 the build 完成");
+        mixed.locale = Some("zh-CN".into());
+        sqlite.insert_session(&mixed).unwrap();
+
+        let stored = sqlite.load_session("s-1").unwrap().unwrap();
+        let languages: Vec<&str> = stored
+            .language_segments
+            .iter()
+            .map(|segment| segment.language.as_str())
+            .collect();
+        assert_eq!(languages, ["zh", "en", "zh"]);
+
+        let mut tagged = snapshot("s-2", 2_000, "bonjour", "Bonjour.");
+        tagged.language_segments = vec![LanguageSegment {
+            text: "Bonjour.".into(),
+            language: "fr".into(),
+        }];
+        tagged.translated_transcript = Some("Hello.".into());
+        tagged.translation_locale = Some("en".into());
+        sqlite.insert_session(&tagged).unwrap();
+        let results = sqlite.search_sessions(&keyword_query("bonjour")).unwrap();
+        let entry = &results.entries[0];
+        assert_eq!(entry.language_segments, tagged.language_segments);
+        assert_eq!(entry.translated_transcript.as_deref(), Some("Hello."));
+        assert_eq!(entry.translation_locale.as_deref(), Some("en"));
+        assert_eq!(stored.translated_transcript, None);
+    }
+
+    #[test]
+    fn quality_flags_seed_accuracy_draft() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut flagged = snapshot("s-1", 1_000, "deploy the kubelet", "Deploy the kubelet.");
+        flagged.quality_flags = vec![QualityFlag {
+            sentence_id: 1,
+            text: "deploy the kubelet".into(),
+            confidence: 0.42,
+            low_confidence_words: vec!["kubelet".into()],
+        }];
+        sqlite.insert_session(&flagged).unwrap();
+        sqlite
+            .insert_session(&snapshot("s-2", 2_000, "ship it", "Ship it."))
+            .unwrap();
+
+        let entry = sqlite.load_session("s-1").unwrap().unwrap();
+        assert_eq!(entry.quality_flags, flagged.quality_flags);
+        let draft = entry.accuracy_draft();
+        assert_eq!(draft.flag, AccuracyFlag::InaccurateRaw);
+        assert_eq!(draft.remarks.as_deref(), Some("deploy the kubelet"));
+
+        let clean = sqlite
+            .load_session("s-2")
+            .unwrap()
+            .unwrap()
+            .accuracy_draft();
+        assert_eq!((clean.flag, clean.remarks), (AccuracyFlag::Unknown, None));
+    }
+
+    #[test]
+    fn stores_dictation_speed_with_sessions() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut timed = snapshot("s-1", 1_000, "one two three", "One two three.");
+        timed.speed = Some(DictationSpeed::new(3, Duration::from_millis(2_400)));
+        sqlite.insert_session(&timed).unwrap();
+        let mut brief = snapshot("s-2", 2_000, "ok", "OK.");
+        brief.speed = Some(DictationSpeed::new(1, Duration::from_millis(600)));
+        sqlite.insert_session(&brief).unwrap();
+        sqlite
+            .insert_session(&snapshot("s-3", 3_000, "untimed", "Untimed."))
+            .unwrap();
+
+        let entry = sqlite.load_session("s-1").unwrap().unwrap();
+        let speed = entry.speed.expect("speed stored");
+        assert_eq!(speed.speech_duration_ms, 2_400);
+        assert_eq!(speed.words_per_minute, Some(75.0));
+        let brief = sqlite.load_session("s-2").unwrap().unwrap();
+        assert_eq!(brief.speed.unwrap().words_per_minute, None);
+        assert_eq!(sqlite.load_session("s-3").unwrap().unwrap().speed, None);
+
+        let copy = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        copy.upsert_history_entry(&entry, 0, 0).unwrap();
+        assert_eq!(
+            copy.load_session("s-1").unwrap().unwrap().speed,
+            Some(speed)
+        );
     }
 }
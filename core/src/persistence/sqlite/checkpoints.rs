@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use rusqlite::params;
+use rusqlite::types::Type;
+
+use super::SqlitePersistence;
+use crate::session::recovery::RecoverySnapshot;
+
+impl SqlitePersistence {
+    /// Replaces the checkpoint of an in-flight session with its latest partial transcript.
+    pub fn upsert_checkpoint(&self, snapshot: &RecoverySnapshot, updated_at_ms: i64) -> Result<()> {
+        let conn = self.connection()?;
+        let encoded =
+            serde_json::to_string(snapshot).context("failed to encode session checkpoint")?;
+        conn.execute(
+            "INSERT INTO session_checkpoints(session_id, snapshot, frame_cursor, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(session_id) DO UPDATE SET
+                snapshot = excluded.snapshot,
+                frame_cursor = excluded.frame_cursor,
+                updated_at_ms = excluded.updated_at_ms",
+            params![
+                snapshot.session_id,
+                encoded,
+                snapshot.frame_cursor as i64,
+                updated_at_ms,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_checkpoint(&self, session_id: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM session_checkpoints WHERE session_id = ?1",
+            params![session_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Checkpoints left behind by sessions that never finished, oldest first.
+    pub fn list_checkpoints(&self) -> Result<Vec<RecoverySnapshot>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT snapshot, updated_at_ms FROM session_checkpoints
+             ORDER BY updated_at_ms ASC, session_id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let snapshot: String = row.get(0)?;
+            let mut snapshot: RecoverySnapshot =
+                serde_json::from_str(&snapshot).map_err(|err| {
+                    rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(err))
+                })?;
+            snapshot.captured_at_ms = Some(row.get(1)?);
+            Ok(snapshot)
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read session checkpoints")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::SqliteConfig;
+    use crate::session::recovery::CrashGuard;
+
+    #[test]
+    fn session_checkpoints_keep_latest_partial_transcript() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let guard = CrashGuard::new(dir.path());
+        guard.begin("session-checkpoint");
+        guard.record_transcript(1, "first line", false);
+        guard.record_frame(10);
+        sqlite
+            .upsert_checkpoint(&guard.in_flight().unwrap(), 100)
+            .unwrap();
+        guard.record_transcript(2, "second line", false);
+        guard.record_frame(25);
+        sqlite
+            .upsert_checkpoint(&guard.in_flight().unwrap(), 200)
+            .unwrap();
+
+        let checkpoints = sqlite.list_checkpoints().unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].session_id, "session-checkpoint");
+        assert_eq!(checkpoints[0].raw_transcript(), "first line second line");
+        assert_eq!(checkpoints[0].frame_cursor, 25);
+        assert_eq!(checkpoints[0].captured_at_ms, Some(200));
+        assert!(sqlite.delete_checkpoint("session-checkpoint").unwrap());
+        assert!(sqlite.list_checkpoints().unwrap().is_empty());
+    }
+}
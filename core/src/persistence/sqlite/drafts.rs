@@ -0,0 +1,178 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::SqlitePersistence;
+use crate::persistence::{DraftRecord, NoticeRecord};
+use crate::session::history::HISTORY_RETENTION_MS;
+
+/// Publish notices kept on disk; older rows are pruned on insert.
+pub(crate) const MAX_PERSISTED_NOTICES: i64 = 2_000;
+
+impl SqlitePersistence {
+    /// Insert or replace a draft, keeping the original creation time.
+    pub fn upsert_draft(&self, record: &DraftRecord) -> Result<()> {
+        let conn = self.connection()?;
+        let tags = serde_json::to_string(&record.tags).context("failed to encode draft tags")?;
+        conn.execute(
+            "INSERT INTO drafts(draft_id, session_id, title, tags, content, created_at_ms, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(draft_id) DO UPDATE SET
+                session_id = excluded.session_id,
+                title = excluded.title,
+                tags = excluded.tags,
+                content = excluded.content,
+                updated_at_ms = excluded.updated_at_ms",
+            params![
+                record.draft_id,
+                record.session_id,
+                record.title,
+                tags,
+                record.content,
+                record.created_at_ms as i64,
+                record.updated_at_ms as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Most recently updated drafts, returned oldest first.
+    pub fn list_drafts(&self, limit: usize) -> Result<Vec<DraftRecord>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT draft_id, session_id, title, tags, content, created_at_ms, updated_at_ms
+             FROM (
+                SELECT *, rowid AS row_order FROM drafts
+                ORDER BY updated_at_ms DESC, row_order DESC LIMIT ?1
+             ) ORDER BY updated_at_ms ASC, row_order ASC",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], Self::read_draft)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read drafts")
+    }
+
+    pub fn load_draft(&self, draft_id: &str) -> Result<Option<DraftRecord>> {
+        let conn = self.connection()?;
+        conn.query_row(
+            "SELECT draft_id, session_id, title, tags, content, created_at_ms, updated_at_ms
+             FROM drafts WHERE draft_id = ?1",
+            params![draft_id],
+            Self::read_draft,
+        )
+        .optional()
+        .context("failed to read draft")
+    }
+
+    fn read_draft(row: &Row) -> rusqlite::Result<DraftRecord> {
+        let tags: String = row.get(3)?;
+        Ok(DraftRecord {
+            draft_id: row.get(0)?,
+            session_id: row.get(1)?,
+            title: row.get(2)?,
+            tags: serde_json::from_str(&tags).unwrap_or_default(),
+            content: row.get(4)?,
+            created_at_ms: row.get::<_, i64>(5)?.max(0) as u128,
+            updated_at_ms: row.get::<_, i64>(6)?.max(0) as u128,
+        })
+    }
+
+    pub fn insert_notice(&self, record: &NoticeRecord) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO notices(notice_id, session_id, action, result, level, message, undo_token, timestamp_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                record.notice_id,
+                record.session_id,
+                record.action,
+                record.result,
+                record.level,
+                record.message,
+                record.undo_token,
+                record.timestamp_ms as i64,
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM notices WHERE seq NOT IN (
+                SELECT seq FROM notices ORDER BY seq DESC LIMIT ?1
+            )",
+            params![MAX_PERSISTED_NOTICES],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent notices, returned oldest first.
+    pub fn list_notices(&self, limit: usize) -> Result<Vec<NoticeRecord>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT notice_id, session_id, action, result, level, message, undo_token, timestamp_ms
+             FROM (SELECT * FROM notices ORDER BY seq DESC LIMIT ?1) ORDER BY seq ASC",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(NoticeRecord {
+                notice_id: row.get(0)?,
+                session_id: row.get(1)?,
+                action: row.get(2)?,
+                result: row.get(3)?,
+                level: row.get(4)?,
+                message: row.get(5)?,
+                undo_token: row.get(6)?,
+                timestamp_ms: row.get::<_, i64>(7)?.max(0) as u128,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read notices")
+    }
+
+    pub fn delete_draft(&self, draft_id: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute("DELETE FROM drafts WHERE draft_id = ?1", params![draft_id])?;
+        Ok(removed > 0)
+    }
+
+    /// Deletes drafts not updated within the history retention window. `now_ms`
+    /// follows [`SqlitePersistence::cleanup_expired`], so a shortened retention
+    /// cap applies to drafts too.
+    pub fn cleanup_expired_drafts(&self, now_ms: i64) -> Result<usize> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM drafts WHERE updated_at_ms <= ?1",
+            params![now_ms - HISTORY_RETENTION_MS],
+        )?;
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::SqliteConfig;
+
+    fn draft(draft_id: &str, created_at_ms: u128, updated_at_ms: u128) -> DraftRecord {
+        DraftRecord {
+            draft_id: draft_id.into(),
+            session_id: "session-drafts".into(),
+            title: "Polished transcript".into(),
+            tags: vec!["transcript".into()],
+            content: format!("{draft_id} content"),
+            created_at_ms,
+            updated_at_ms,
+        }
+    }
+
+    #[test]
+    fn upsert_keeps_creation_time_and_expired_drafts_are_pruned() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        sqlite.upsert_draft(&draft("stale", 1_000, 1_000)).unwrap();
+        sqlite.upsert_draft(&draft("fresh", 2_000, 2_000)).unwrap();
+        sqlite.upsert_draft(&draft("fresh", 9_000, 9_000)).unwrap();
+
+        let fresh = sqlite.load_draft("fresh").unwrap().unwrap();
+        assert_eq!((fresh.created_at_ms, fresh.updated_at_ms), (2_000, 9_000));
+
+        let now_ms = 5_000 + HISTORY_RETENTION_MS;
+        assert_eq!(sqlite.cleanup_expired_drafts(now_ms).unwrap(), 1);
+        assert!(sqlite.load_draft("stale").unwrap().is_none());
+        assert_eq!(sqlite.cleanup_expired_drafts(now_ms).unwrap(), 0);
+        assert_eq!(sqlite.list_drafts(10).unwrap(), vec![fresh]);
+    }
+}
@@ -0,0 +1,336 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use tracing::warn;
+
+use super::SqlitePersistence;
+use crate::session::history::import::{
+    merge_post_actions, validate_entry, HistoryArchive, ImportSource,
+};
+use crate::session::history::{
+    HistoryEntry, HistoryPostAction, ImportSummary, HISTORY_RETENTION_MS,
+};
+
+impl SqlitePersistence {
+    /// Reads `source` and merges its entries with [`SqlitePersistence::import_entries`].
+    /// Shared by `SessionManager` and the desktop shell so both validate and decrypt
+    /// imports the same way.
+    pub fn import_from(&self, source: &ImportSource, now_ms: i64) -> Result<ImportSummary> {
+        let entries = match source {
+            ImportSource::Archive { path, passphrase } => {
+                HistoryArchive::read(path, passphrase)?.entries
+            }
+            ImportSource::Database { path, key } => {
+                Self::read_foreign_history(path, key.as_deref())?
+            }
+        };
+        self.import_entries(&entries, now_ms)
+    }
+
+    /// Reads every session from another Flowwisper `history.db` without
+    /// modifying it. `key` is that database's SQLCipher key, if any.
+    pub fn read_foreign_history(path: &Path, key: Option<&str>) -> Result<Vec<HistoryEntry>> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_FULL_MUTEX,
+        )
+        .with_context(|| format!("failed to open history database {}", path.display()))?;
+        if let Some(value) = key {
+            conn.pragma_update(None, "key", value)
+                .context("failed to apply history database key")?;
+        }
+
+        // Databases from older releases predate the pinned column.
+        let pinned = if Self::has_column(&conn, "sessions", "pinned")
+            .context("not a readable Flowwisper history database (wrong key?)")?
+        {
+            "pinned"
+        } else {
+            "0 AS pinned"
+        };
+        let language_segments = if Self::has_column(&conn, "sessions", "language_segments")? {
+            "language_segments"
+        } else {
+            "'[]' AS language_segments"
+        };
+        let translation = if Self::has_column(&conn, "sessions", "translated_transcript")? {
+            "translated_transcript, translation_locale"
+        } else {
+            "NULL AS translated_transcript, NULL AS translation_locale"
+        };
+        let quality_flags = if Self::has_column(&conn, "sessions", "quality_flags")? {
+            "quality_flags"
+        } else {
+            "'[]' AS quality_flags"
+        };
+        let speed = if Self::has_column(&conn, "sessions", "speed")? {
+            "speed"
+        } else {
+            "NULL AS speed"
+        };
+        let meeting = if Self::has_column(&conn, "sessions", "meeting")? {
+            "meeting"
+        } else {
+            "NULL AS meeting"
+        };
+        let tags = if Self::has_column(&conn, "sessions", "tags")? {
+            "tags"
+        } else {
+            "'[]' AS tags"
+        };
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT session_id, started_at_ms, completed_at_ms, duration_ms, locale,
+                    app_identifier, app_version, raw_transcript, polished_transcript,
+                    confidence_score, accuracy_flag, accuracy_remarks, post_actions, metadata,
+                    {pinned}, {language_segments}, {translation},
+                    {quality_flags}, {speed}, {meeting}, {tags}
+                FROM sessions ORDER BY completed_at_ms ASC"
+            ))
+            .context("not a readable Flowwisper history database (wrong key?)")?;
+        let mut rows = stmt.query([])?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows.next()? {
+            entries.push(Self::read_history_entry(row)?);
+        }
+        Ok(entries)
+    }
+
+    /// Merges imported entries into the local store in a single transaction.
+    ///
+    /// Unknown sessions are inserted with their accuracy marks and post actions
+    /// intact; their retention window restarts at `now_ms` so migrated history
+    /// is not purged on the next cleanup. Sessions that already exist keep their
+    /// local content and only gain post actions missing locally.
+    pub fn import_entries(&self, entries: &[HistoryEntry], now_ms: i64) -> Result<ImportSummary> {
+        let mut conn = self.connection()?;
+        let tx = conn
+            .transaction()
+            .context("failed to open transaction for history import")?;
+        let mut summary = ImportSummary::default();
+
+        for entry in entries {
+            if let Err(err) = validate_entry(entry) {
+                warn!(target: "persistence", %err, "skipping invalid history entry");
+                summary.rejected += 1;
+                continue;
+            }
+
+            let existing: Option<(String, bool)> = tx
+                .query_row(
+                    "SELECT post_actions, pinned FROM sessions WHERE session_id = ?1",
+                    params![entry.session_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+
+            if let Some((existing, local_pinned)) = existing {
+                let local: Vec<HistoryPostAction> =
+                    serde_json::from_str(&existing).unwrap_or_default();
+                let merged = merge_post_actions(&local, &entry.post_actions);
+                let pin = entry.pinned && !local_pinned;
+                if merged.len() == local.len() && !pin {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                let encoded =
+                    serde_json::to_string(&merged).context("failed to encode post actions")?;
+                tx.execute(
+                    "UPDATE sessions SET post_actions = ?2, pinned = pinned OR ?3,
+                        updated_at_ms = ?4
+                     WHERE session_id = ?1",
+                    params![entry.session_id, encoded, pin, now_ms],
+                )?;
+                summary.merged += 1;
+                continue;
+            }
+
+            let post_actions = serde_json::to_string(&entry.post_actions)
+                .context("failed to serialize post actions")?;
+            let metadata = if entry.metadata.is_null() {
+                "{}".to_string()
+            } else {
+                serde_json::to_string(&entry.metadata)
+                    .context("failed to serialize session metadata")?
+            };
+            let language_segments = serde_json::to_string(&entry.language_segments)
+                .context("failed to serialize language segments")?;
+            let quality_flags = serde_json::to_string(&entry.quality_flags)
+                .context("failed to serialize quality flags")?;
+            let speed = entry
+                .speed
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .context("failed to serialize dictation speed")?;
+            let meeting = entry
+                .meeting
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .context("failed to serialize meeting notes")?;
+            let tags = serde_json::to_string(&entry.tags).context("failed to serialize tags")?;
+            let expires_at_ms =
+                (entry.completed_at_ms.max(now_ms)).saturating_add(HISTORY_RETENTION_MS);
+            tx.execute(
+                "INSERT INTO sessions (
+                    session_id, started_at_ms, completed_at_ms, duration_ms, locale,
+                    app_identifier, app_version, raw_transcript, polished_transcript,
+                    confidence_score, accuracy_flag, accuracy_remarks, post_actions,
+                    expires_at_ms, metadata, pinned, language_segments,
+                    translated_transcript, translation_locale, quality_flags, speed, meeting, tags,
+                    updated_at_ms
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                    ?18, ?19, ?20, ?21, ?22, ?23, ?24)",
+                params![
+                    entry.session_id,
+                    entry.started_at_ms,
+                    entry.completed_at_ms,
+                    entry.duration_ms,
+                    entry.locale.as_deref(),
+                    entry.app_identifier.as_deref(),
+                    entry.app_version.as_deref(),
+                    entry.raw_transcript,
+                    entry.polished_transcript,
+                    entry.confidence_score,
+                    entry.accuracy_flag.as_str(),
+                    entry.accuracy_remarks.as_deref(),
+                    post_actions,
+                    expires_at_ms,
+                    metadata,
+                    entry.pinned,
+                    language_segments,
+                    entry.translated_transcript.as_deref(),
+                    entry.translation_locale.as_deref(),
+                    quality_flags,
+                    speed,
+                    meeting,
+                    tags,
+                    now_ms,
+                ],
+            )
+            .context("failed to insert imported session")?;
+            summary.inserted += 1;
+        }
+
+        tx.commit()
+            .context("failed to commit history import transaction")?;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::tests::snapshot;
+    use crate::persistence::sqlite::{SqliteConfig, SqlitePath};
+    use crate::session::history::ExportSelection;
+
+    #[test]
+    fn import_merges_archive_entries_and_foreign_databases() {
+        let source = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        source
+            .insert_session(&snapshot("s-1", 1_000, "raw one", "One."))
+            .unwrap();
+        source
+            .append_post_action("s-1", &HistoryPostAction::clipboard_backup(1_500))
+            .unwrap();
+        source
+            .insert_session(&snapshot("s-2", 2_000, "raw two", "Two."))
+            .unwrap();
+        let mut exported = source
+            .export_sessions(&ExportSelection::DateRange {
+                from_ms: 0,
+                to_ms: i64::MAX,
+            })
+            .unwrap();
+        let mut invalid = exported[0].clone();
+        invalid.session_id = "  ".into();
+        exported.push(invalid);
+
+        let local = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        local
+            .insert_session(&snapshot("s-1", 1_000, "raw one", "One."))
+            .unwrap();
+
+        let summary = local.import_entries(&exported, 10_000).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                inserted: 1,
+                merged: 1,
+                unchanged: 0,
+                rejected: 1,
+            }
+        );
+        let merged = local.load_session("s-1").unwrap().unwrap();
+        assert_eq!(merged.post_actions.len(), 1);
+
+        let again = local.import_entries(&exported[..2], 10_000).unwrap();
+        assert_eq!(again.unchanged, 2);
+
+        // Imported sessions restart their retention window at import time.
+        let s2_natural_expiry = 2_000 + HISTORY_RETENTION_MS;
+        assert_eq!(local.cleanup_expired(s2_natural_expiry).unwrap().len(), 1);
+        assert!(local.load_session("s-2").unwrap().is_some());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.db");
+        let other = SqlitePersistence::bootstrap(SqliteConfig {
+            path: SqlitePath::File(path.clone()),
+            ..SqliteConfig::memory()
+        })
+        .unwrap();
+        other
+            .insert_session(&snapshot("s-3", 3_000, "raw three", "Three."))
+            .unwrap();
+        drop(other);
+        let foreign = SqlitePersistence::read_foreign_history(&path, None).unwrap();
+        assert_eq!(foreign.len(), 1);
+        let imported = local
+            .import_from(&ImportSource::Database { path, key: None }, 10_000)
+            .unwrap();
+        assert_eq!(imported.inserted, 1);
+        assert!(
+            SqlitePersistence::read_foreign_history(&dir.path().join("missing.db"), None).is_err()
+        );
+    }
+
+    #[test]
+    fn imports_sealed_archives_and_rejects_wrong_passphrases() {
+        let source = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        source
+            .insert_session(&snapshot("s-1", 1_000, "raw one", "One."))
+            .unwrap();
+        let entries = source
+            .export_sessions(&ExportSelection::DateRange {
+                from_ms: 0,
+                to_ms: i64::MAX,
+            })
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.fwh");
+        std::fs::write(
+            &path,
+            HistoryArchive::new(5_000, entries)
+                .seal("correct horse")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let local = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let wrong = ImportSource::Archive {
+            path: path.clone(),
+            passphrase: "wrong horse!".into(),
+        };
+        assert!(local.import_from(&wrong, 10_000).is_err());
+        assert!(local.load_session("s-1").unwrap().is_none());
+
+        let source = ImportSource::Archive {
+            path,
+            passphrase: "correct horse".into(),
+        };
+        assert_eq!(local.import_from(&source, 10_000).unwrap().inserted, 1);
+    }
+}
@@ -0,0 +1,454 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use tracing::warn;
+
+use super::{SqliteConfig, SqlitePath, SqlitePersistence};
+use crate::secrets::SecretStore;
+use crate::session::workspace::DEFAULT_PROFILE;
+
+const SQLCIPHER_KEY_ENV: &str = "FLOWWISPER_SQLCIPHER_KEY";
+/// Secret store entry holding the current SQLCipher key of the default profile.
+pub const SQLCIPHER_KEY_SECRET: &str = "sqlcipher.key";
+/// Secret store entry holding the key the default profile's database used before the last
+/// rotation.
+pub const SQLCIPHER_PREVIOUS_KEY_SECRET: &str = "sqlcipher.previous_key";
+
+/// Secret name for `base` in `profile`. Platform stores share one service across profiles,
+/// so every profile other than the default gets its own `<base>.<profile>` entry; the
+/// default profile keeps the bare name used before profiles existed.
+fn profile_secret_name(base: &str, profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        base.to_string()
+    } else {
+        format!("{base}.{profile}")
+    }
+}
+
+/// Provides SQLCipher key material for the local database.
+pub trait KeyResolver: Send + Sync {
+    fn resolve_key(&self) -> Result<Option<String>>;
+
+    /// Key that was current before the last [`KeyResolver::store_key`], used to finish a
+    /// rotation that was interrupted before the database was re-encrypted.
+    fn previous_key(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Persists rotated key material so later bootstraps open the re-encrypted database.
+    /// Resolvers that cannot persist keys across restarts must keep the default, which
+    /// makes [`SqlitePersistence::rekey`] refuse to run.
+    fn store_key(&self, _key: &str) -> Result<()> {
+        Err(anyhow!("key resolver cannot store rotated key material"))
+    }
+}
+
+/// Key resolver that reads the key material from the `FLOWWISPER_SQLCIPHER_KEY` env variable.
+///
+/// The environment belongs to the launcher, so this resolver cannot persist rotated keys.
+#[derive(Default)]
+pub struct EnvKeyResolver;
+
+impl KeyResolver for EnvKeyResolver {
+    fn resolve_key(&self) -> Result<Option<String>> {
+        Ok(std::env::var(SQLCIPHER_KEY_ENV).ok())
+    }
+}
+
+/// Key resolver backed by the platform secret store. A key in the store takes precedence
+/// over `FLOWWISPER_SQLCIPHER_KEY`, so a database keyed from the environment keeps opening
+/// until its first rotation moves the key into the store.
+pub struct SecretStoreKeyResolver {
+    store: Arc<dyn SecretStore>,
+    key_secret: String,
+    previous_key_secret: String,
+}
+
+impl SecretStoreKeyResolver {
+    /// Resolver for the default profile's database.
+    pub fn new(store: Arc<dyn SecretStore>) -> Self {
+        Self::for_profile(store, DEFAULT_PROFILE)
+    }
+
+    /// Resolver whose key entries belong to `profile`, so rotating one profile's database
+    /// never touches the key another profile's database is encrypted with.
+    pub fn for_profile(store: Arc<dyn SecretStore>, profile: &str) -> Self {
+        Self {
+            store,
+            key_secret: profile_secret_name(SQLCIPHER_KEY_SECRET, profile),
+            previous_key_secret: profile_secret_name(SQLCIPHER_PREVIOUS_KEY_SECRET, profile),
+        }
+    }
+}
+
+impl KeyResolver for SecretStoreKeyResolver {
+    fn resolve_key(&self) -> Result<Option<String>> {
+        match self.store.get(&self.key_secret)? {
+            Some(key) => Ok(Some(key)),
+            None => EnvKeyResolver.resolve_key(),
+        }
+    }
+
+    fn previous_key(&self) -> Result<Option<String>> {
+        Ok(self.store.get(&self.previous_key_secret)?)
+    }
+
+    fn store_key(&self, key: &str) -> Result<()> {
+        if let Some(current) = self.resolve_key()? {
+            self.store.set(&self.previous_key_secret, &current)?;
+        }
+        self.store.set(&self.key_secret, key)?;
+        Ok(())
+    }
+}
+
+/// Progress reported while [`SqlitePersistence::rekey`] rotates the database key.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RekeyStage {
+    Started,
+    KeyStored,
+    Reencrypted,
+    Verified,
+    Completed,
+}
+
+impl RekeyStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RekeyStage::Started => "started",
+            RekeyStage::KeyStored => "key_stored",
+            RekeyStage::Reencrypted => "reencrypted",
+            RekeyStage::Verified => "verified",
+            RekeyStage::Completed => "completed",
+        }
+    }
+}
+
+impl SqlitePersistence {
+    /// Re-encrypts the database in place with `new_key` and swaps the connection pool over
+    /// to the new key.
+    ///
+    /// The new key is handed to the configured [`KeyResolver`] before the database is
+    /// touched, so a restart never finds a re-encrypted database without its key; a crash
+    /// between the two steps is finished on the next [`SqlitePersistence::bootstrap`].
+    /// Connections checked out before the rotation keep the old key and fail on their next
+    /// read, so callers should quiesce writers first.
+    pub fn rekey(&self, new_key: &str, progress: &mut dyn FnMut(RekeyStage)) -> Result<()> {
+        if new_key.trim().is_empty() {
+            return Err(anyhow!("refusing to rotate to an empty SQLCipher key"));
+        }
+        if self.db_path.is_none() {
+            return Err(anyhow!("key rotation requires a file-backed database"));
+        }
+        let Some(current_key) = self.config.key_resolver.resolve_key()? else {
+            return Err(anyhow!(
+                "database is not encrypted; key rotation requires existing SQLCipher key material"
+            ));
+        };
+
+        let mut pool = self
+            .pool
+            .write()
+            .map_err(|_| anyhow!("SQLCipher connection pool poisoned"))?;
+        progress(RekeyStage::Started);
+
+        self.config
+            .key_resolver
+            .store_key(new_key)
+            .context("failed to store rotated SQLCipher key; database left unchanged")?;
+        progress(RekeyStage::KeyStored);
+
+        let mut reencrypted = false;
+        let rotated = Self::rekey_with(&pool, new_key)
+            .inspect(|_| {
+                reencrypted = true;
+                progress(RekeyStage::Reencrypted);
+            })
+            .and_then(|_| Self::build_pool(&self.config, Some(new_key.to_string())))
+            .and_then(|rotated| Self::verify_readable(&rotated).map(|_| rotated));
+        let rotated = match rotated {
+            Ok(rotated) => rotated,
+            Err(err) => {
+                warn!(
+                    target: "persistence",
+                    %err,
+                    "SQLCipher rekey failed, restoring previous key"
+                );
+                if reencrypted {
+                    Self::build_pool(&self.config, Some(new_key.to_string()))
+                        .and_then(|rotated| Self::rekey_with(&rotated, &current_key))
+                        .context("failed to restore previous SQLCipher key after rekey failure")?;
+                }
+                self.config
+                    .key_resolver
+                    .store_key(&current_key)
+                    .context("failed to restore previous SQLCipher key in the key store")?;
+                return Err(err);
+            }
+        };
+        progress(RekeyStage::Verified);
+
+        *pool = rotated;
+        progress(RekeyStage::Completed);
+        Ok(())
+    }
+
+    /// Finishes a rotation that stored the new key but crashed before re-encrypting: if
+    /// the database only opens with the resolver's previous key, it is rekeyed to the
+    /// current one.
+    pub(super) fn resume_interrupted_rekey(config: &SqliteConfig) -> Result<()> {
+        let SqlitePath::File(path) = &config.path else {
+            return Ok(());
+        };
+        if !path.exists() {
+            return Ok(());
+        }
+        let Some(current) = config.key_resolver.resolve_key()? else {
+            return Ok(());
+        };
+        if Self::opens_with(path, &current) {
+            return Ok(());
+        }
+        let Some(previous) = config.key_resolver.previous_key()? else {
+            return Ok(());
+        };
+        if !Self::opens_with(path, &previous) {
+            return Ok(());
+        }
+
+        warn!(
+            target: "persistence",
+            "database still uses the previous SQLCipher key, finishing interrupted rotation"
+        );
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open {} to finish rekey", path.display()))?;
+        conn.pragma_update(None, "key", &previous)
+            .context("failed to apply previous SQLCipher key")?;
+        conn.pragma_update(None, "rekey", &current)
+            .context("SQLCipher rekey failed")?;
+        Ok(())
+    }
+
+    fn opens_with(path: &Path, key: &str) -> bool {
+        let Ok(conn) = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+            return false;
+        };
+        conn.pragma_update(None, "key", key).is_ok()
+            && conn
+                .query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+                    row.get::<_, i64>(0)
+                })
+                .is_ok()
+    }
+
+    fn rekey_with(pool: &Pool<SqliteConnectionManager>, key: &str) -> Result<()> {
+        let conn = pool
+            .get()
+            .context("failed to acquire SQLCipher connection for rekey")?;
+        conn.pragma_update(None, "rekey", key)
+            .context("SQLCipher rekey failed")?;
+        Ok(())
+    }
+
+    fn verify_readable(pool: &Pool<SqliteConnectionManager>) -> Result<()> {
+        let conn = pool
+            .get()
+            .context("failed to open database with rotated SQLCipher key")?;
+        conn.query_row("SELECT count(*) FROM sessions", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .context("database unreadable with rotated SQLCipher key")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::tests::snapshot;
+    use crate::secrets::EncryptedFileStore;
+    use std::sync::Mutex;
+
+    fn secret_config(dir: &Path) -> SqliteConfig {
+        let store = EncryptedFileStore::open(&dir.join("secrets")).unwrap();
+        SqliteConfig {
+            path: SqlitePath::File(dir.join("history.db")),
+            key_resolver: Arc::new(SecretStoreKeyResolver::new(Arc::new(store))),
+            ..SqliteConfig::memory()
+        }
+    }
+
+    #[test]
+    fn rotated_key_survives_restart_through_secret_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = secret_config(dir.path());
+        config.key_resolver.store_key("old-secret").unwrap();
+        let sqlite = SqlitePersistence::bootstrap(config).unwrap();
+        sqlite.store_user_setting("theme", "dark", 1).unwrap();
+
+        sqlite.rekey("new-secret", &mut |_| {}).unwrap();
+        drop(sqlite);
+
+        let reopened = SqlitePersistence::bootstrap(secret_config(dir.path())).unwrap();
+        assert_eq!(
+            reopened.load_user_setting("theme").unwrap().as_deref(),
+            Some("dark")
+        );
+        assert!(!SqlitePersistence::opens_with(
+            &dir.path().join("history.db"),
+            "old-secret"
+        ));
+    }
+
+    #[test]
+    fn bootstrap_finishes_rotation_interrupted_after_key_was_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = secret_config(dir.path());
+        config.key_resolver.store_key("old-secret").unwrap();
+        let sqlite = SqlitePersistence::bootstrap(config.clone()).unwrap();
+        sqlite.store_user_setting("theme", "dark", 1).unwrap();
+        drop(sqlite);
+        // Crash after the new key reached the store but before PRAGMA rekey ran.
+        config.key_resolver.store_key("new-secret").unwrap();
+
+        let reopened = SqlitePersistence::bootstrap(secret_config(dir.path())).unwrap();
+        assert_eq!(
+            reopened.load_user_setting("theme").unwrap().as_deref(),
+            Some("dark")
+        );
+        assert!(SqlitePersistence::opens_with(
+            &dir.path().join("history.db"),
+            "new-secret"
+        ));
+    }
+
+    #[test]
+    fn rotating_one_profile_keeps_the_other_profile_readable() {
+        let dir = tempfile::tempdir().unwrap();
+        // Platform stores are shared by every profile on the machine.
+        let store: Arc<dyn SecretStore> =
+            Arc::new(EncryptedFileStore::open(&dir.path().join("secrets")).unwrap());
+        let profile_config = |profile: &str| SqliteConfig {
+            path: SqlitePath::File(dir.path().join(format!("{profile}.db"))),
+            key_resolver: Arc::new(SecretStoreKeyResolver::for_profile(store.clone(), profile)),
+            ..SqliteConfig::memory()
+        };
+
+        let work = profile_config("work");
+        work.key_resolver.store_key("work-secret").unwrap();
+        let work = SqlitePersistence::bootstrap(work).unwrap();
+        let personal = profile_config(DEFAULT_PROFILE);
+        personal.key_resolver.store_key("personal-secret").unwrap();
+        let personal = SqlitePersistence::bootstrap(personal).unwrap();
+        personal.store_user_setting("theme", "dark", 1).unwrap();
+        drop(personal);
+
+        work.rekey("work-rotated", &mut |_| {}).unwrap();
+        work.rekey("work-rotated-again", &mut |_| {}).unwrap();
+        drop(work);
+
+        let reopened = SqlitePersistence::bootstrap(profile_config(DEFAULT_PROFILE)).unwrap();
+        assert_eq!(
+            reopened.load_user_setting("theme").unwrap().as_deref(),
+            Some("dark")
+        );
+        assert!(SqlitePersistence::opens_with(
+            &dir.path().join(format!("{DEFAULT_PROFILE}.db")),
+            "personal-secret"
+        ));
+        assert!(SqlitePersistence::bootstrap(profile_config("work")).is_ok());
+    }
+
+    #[test]
+    fn rekey_refuses_resolvers_that_cannot_persist_keys() {
+        struct FixedKey;
+        impl KeyResolver for FixedKey {
+            fn resolve_key(&self) -> Result<Option<String>> {
+                Ok(Some("fixed-secret".into()))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig {
+            path: SqlitePath::File(path.clone()),
+            key_resolver: Arc::new(FixedKey),
+            ..SqliteConfig::memory()
+        })
+        .unwrap();
+
+        let mut stages = Vec::new();
+        assert!(sqlite
+            .rekey("new-secret", &mut |stage| stages.push(stage))
+            .is_err());
+        assert_eq!(stages, vec![RekeyStage::Started]);
+        assert!(SqlitePersistence::opens_with(&path, "fixed-secret"));
+    }
+
+    struct RotatingKeyResolver(Mutex<Option<String>>);
+
+    impl KeyResolver for RotatingKeyResolver {
+        fn resolve_key(&self) -> Result<Option<String>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn store_key(&self, key: &str) -> Result<()> {
+            *self.0.lock().unwrap() = Some(key.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rekey_reencrypts_database_and_stores_new_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        let resolver = Arc::new(RotatingKeyResolver(Mutex::new(Some("old-secret".into()))));
+        let config = SqliteConfig {
+            path: SqlitePath::File(path.clone()),
+            key_resolver: resolver.clone(),
+            ..SqliteConfig::memory()
+        };
+        let sqlite = SqlitePersistence::bootstrap(config).unwrap();
+        sqlite
+            .insert_session(&snapshot("s-1", 1_000, "rotate me", "Rotate me."))
+            .unwrap();
+
+        let mut stages = Vec::new();
+        sqlite
+            .rekey("new-secret", &mut |stage| stages.push(stage))
+            .unwrap();
+        assert_eq!(
+            stages,
+            vec![
+                RekeyStage::Started,
+                RekeyStage::KeyStored,
+                RekeyStage::Reencrypted,
+                RekeyStage::Verified,
+                RekeyStage::Completed,
+            ]
+        );
+        assert_eq!(
+            resolver.resolve_key().unwrap().as_deref(),
+            Some("new-secret")
+        );
+        assert!(sqlite.load_session("s-1").unwrap().is_some());
+
+        let read_with = |key: &str| {
+            let conn =
+                Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY).unwrap();
+            conn.pragma_update(None, "key", key).unwrap();
+            conn.query_row("SELECT count(*) FROM sessions", [], |row| {
+                row.get::<_, i64>(0)
+            })
+        };
+        assert!(read_with("old-secret").is_err());
+        assert_eq!(read_with("new-secret").unwrap(), 1);
+        assert!(sqlite.rekey("  ", &mut |_| {}).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+
+use super::SqlitePersistence;
+
+impl SqlitePersistence {
+    pub(super) fn run_migrations(conn: &mut Connection) -> Result<()> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                session_id TEXT PRIMARY KEY,
+                started_at_ms INTEGER NOT NULL,
+                completed_at_ms INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                locale TEXT,
+                app_identifier TEXT,
+                app_version TEXT,
+                raw_transcript TEXT NOT NULL,
+                polished_transcript TEXT NOT NULL,
+                confidence_score REAL,
+                accuracy_flag TEXT,
+                accuracy_remarks TEXT,
+                post_actions TEXT NOT NULL DEFAULT '[]',
+                expires_at_ms INTEGER NOT NULL,
+                metadata TEXT NOT NULL DEFAULT '{}',
+                pinned INTEGER NOT NULL DEFAULT 0,
+                language_segments TEXT NOT NULL DEFAULT '[]',
+                translated_transcript TEXT,
+                translation_locale TEXT,
+                quality_flags TEXT NOT NULL DEFAULT '[]',
+                speed TEXT,
+                meeting TEXT,
+                tags TEXT NOT NULL DEFAULT '[]'
+            );
+
+            CREATE TABLE IF NOT EXISTS telemetry_queue (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                created_at_ms INTEGER NOT NULL,
+                delivered INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS drafts (
+                draft_id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                tags TEXT NOT NULL DEFAULT '[]',
+                content TEXT NOT NULL,
+                created_at_ms INTEGER NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS drafts_updated_idx ON drafts(updated_at_ms);
+
+            CREATE TABLE IF NOT EXISTS notices (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                notice_id TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                result TEXT NOT NULL,
+                level TEXT NOT NULL,
+                message TEXT NOT NULL,
+                undo_token TEXT,
+                timestamp_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS publish_retry_queue (
+                retry_id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                transcript TEXT NOT NULL,
+                app_identifier TEXT,
+                window_title TEXT,
+                insertion TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                snapshot TEXT NOT NULL,
+                created_at_ms INTEGER NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS session_checkpoints (
+                session_id TEXT PRIMARY KEY,
+                snapshot TEXT NOT NULL,
+                frame_cursor INTEGER NOT NULL DEFAULT 0,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS vocabulary (
+                term TEXT PRIMARY KEY COLLATE NOCASE,
+                kind TEXT NOT NULL,
+                boost REAL NOT NULL DEFAULT 1.0,
+                created_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS replacement_rules (
+                rule_id TEXT PRIMARY KEY,
+                pattern TEXT NOT NULL,
+                replacement TEXT NOT NULL,
+                is_regex INTEGER NOT NULL DEFAULT 0,
+                app_identifier TEXT,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at_ms INTEGER NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS corrections (
+                original TEXT NOT NULL,
+                corrected TEXT NOT NULL,
+                occurrences INTEGER NOT NULL DEFAULT 1,
+                first_seen_ms INTEGER NOT NULL,
+                last_seen_ms INTEGER NOT NULL,
+                PRIMARY KEY (original, corrected)
+            );
+
+            CREATE TABLE IF NOT EXISTS polish_profiles (
+                app_identifier TEXT PRIMARY KEY COLLATE NOCASE,
+                profile TEXT NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS app_profiles (
+                app_identifier TEXT NOT NULL COLLATE NOCASE,
+                field_role TEXT NOT NULL DEFAULT '',
+                fallback TEXT,
+                polish_profile TEXT,
+                vocabulary TEXT NOT NULL DEFAULT '[]',
+                insertion TEXT,
+                updated_at_ms INTEGER NOT NULL,
+                output_format TEXT,
+                PRIMARY KEY (app_identifier, field_role)
+            );
+
+            CREATE TABLE IF NOT EXISTS session_presets (
+                name TEXT PRIMARY KEY COLLATE NOCASE,
+                engine TEXT,
+                polish_profile TEXT,
+                language TEXT,
+                target_app TEXT,
+                fallback TEXT,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS user_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS noise_baselines (
+                device_id TEXT PRIMARY KEY,
+                level_db REAL NOT NULL,
+                updated_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS sync_rows (
+                kind TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                clock TEXT NOT NULL DEFAULT '{}',
+                content_hash TEXT NOT NULL,
+                updated_at_ms INTEGER NOT NULL,
+                deleted INTEGER NOT NULL DEFAULT 0,
+                dirty INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (kind, row_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS sync_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS diagnostics_probe (
+                id INTEGER PRIMARY KEY,
+                token TEXT NOT NULL,
+                written_at_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS egress_audit (
+                seq INTEGER PRIMARY KEY,
+                recorded_at_ms INTEGER NOT NULL,
+                channel TEXT NOT NULL,
+                destination TEXT NOT NULL,
+                session_id TEXT,
+                payload_sha256 TEXT NOT NULL,
+                payload_bytes INTEGER NOT NULL,
+                mac TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_egress_audit_recorded_at ON egress_audit(recorded_at_ms);
+
+            CREATE TRIGGER IF NOT EXISTS egress_audit_no_update BEFORE UPDATE ON egress_audit BEGIN
+                SELECT RAISE(ABORT, 'egress audit log is append-only');
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS egress_audit_no_delete BEFORE DELETE ON egress_audit BEGIN
+                SELECT RAISE(ABORT, 'egress audit log is append-only');
+            END;
+
+            CREATE VIRTUAL TABLE IF NOT EXISTS session_index USING fts5(
+                session_id UNINDEXED,
+                raw_transcript,
+                polished_transcript,
+                app_identifier,
+                content='sessions',
+                content_rowid='rowid',
+                tokenize='unicode61 remove_diacritics 2'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS sessions_ai AFTER INSERT ON sessions BEGIN
+                INSERT INTO session_index(rowid, session_id, raw_transcript, polished_transcript, app_identifier)
+                VALUES (new.rowid, new.session_id, new.raw_transcript, new.polished_transcript, new.app_identifier);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS sessions_ad AFTER DELETE ON sessions BEGIN
+                INSERT INTO session_index(session_index, rowid, session_id, raw_transcript, polished_transcript, app_identifier)
+                VALUES('delete', old.rowid, old.session_id, old.raw_transcript, old.polished_transcript, old.app_identifier);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS sessions_au AFTER UPDATE ON sessions BEGIN
+                INSERT INTO session_index(session_index, rowid, session_id, raw_transcript, polished_transcript, app_identifier)
+                VALUES('delete', old.rowid, old.session_id, old.raw_transcript, old.polished_transcript, old.app_identifier);
+                INSERT INTO session_index(rowid, session_id, raw_transcript, polished_transcript, app_identifier)
+                VALUES (new.rowid, new.session_id, new.raw_transcript, new.polished_transcript, new.app_identifier);
+            END;
+            "#,
+        )
+        .context("failed to run SQLCipher migrations")?;
+
+        // Columns added after the initial schema shipped.
+        if !Self::has_column(conn, "sessions", "pinned")? {
+            conn.execute_batch(
+                "ALTER TABLE sessions ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;",
+            )
+            .context("failed to add sessions.pinned column")?;
+        }
+        if !Self::has_column(conn, "sessions", "language_segments")? {
+            conn.execute_batch(
+                "ALTER TABLE sessions ADD COLUMN language_segments TEXT NOT NULL DEFAULT '[]';",
+            )
+            .context("failed to add sessions.language_segments column")?;
+        }
+        if !Self::has_column(conn, "sessions", "translated_transcript")? {
+            conn.execute_batch(
+                "ALTER TABLE sessions ADD COLUMN translated_transcript TEXT;
+                ALTER TABLE sessions ADD COLUMN translation_locale TEXT;",
+            )
+            .context("failed to add sessions translation columns")?;
+        }
+        if !Self::has_column(conn, "sessions", "quality_flags")? {
+            conn.execute_batch(
+                "ALTER TABLE sessions ADD COLUMN quality_flags TEXT NOT NULL DEFAULT '[]';",
+            )
+            .context("failed to add sessions.quality_flags column")?;
+        }
+        if !Self::has_column(conn, "sessions", "speed")? {
+            conn.execute_batch("ALTER TABLE sessions ADD COLUMN speed TEXT;")
+                .context("failed to add sessions.speed column")?;
+        }
+        if !Self::has_column(conn, "sessions", "meeting")? {
+            conn.execute_batch("ALTER TABLE sessions ADD COLUMN meeting TEXT;")
+                .context("failed to add sessions.meeting column")?;
+        }
+        if !Self::has_column(conn, "sessions", "tags")? {
+            conn.execute_batch("ALTER TABLE sessions ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';")
+                .context("failed to add sessions.tags column")?;
+        }
+        if !Self::has_column(conn, "sessions", "updated_at_ms")? {
+            conn.execute_batch(
+                "ALTER TABLE sessions ADD COLUMN updated_at_ms INTEGER NOT NULL DEFAULT 0;
+                UPDATE sessions SET updated_at_ms = completed_at_ms;",
+            )
+            .context("failed to add sessions.updated_at_ms column")?;
+        }
+        if !Self::has_column(conn, "app_profiles", "field_role")? {
+            // The primary key gains the field role, which SQLite can only do by rebuilding.
+            conn.execute_batch(
+                "BEGIN;
+                ALTER TABLE app_profiles RENAME TO app_profiles_legacy;
+                CREATE TABLE app_profiles (
+                    app_identifier TEXT NOT NULL COLLATE NOCASE,
+                    field_role TEXT NOT NULL DEFAULT '',
+                    fallback TEXT,
+                    polish_profile TEXT,
+                    vocabulary TEXT NOT NULL DEFAULT '[]',
+                    insertion TEXT,
+                    updated_at_ms INTEGER NOT NULL,
+                    PRIMARY KEY (app_identifier, field_role)
+                );
+                INSERT INTO app_profiles(app_identifier, fallback, polish_profile, vocabulary,
+                    insertion, updated_at_ms)
+                SELECT app_identifier, fallback, polish_profile, vocabulary, insertion, updated_at_ms
+                FROM app_profiles_legacy;
+                DROP TABLE app_profiles_legacy;
+                COMMIT;",
+            )
+            .context("failed to key app profiles by field role")?;
+        }
+        if !Self::has_column(conn, "app_profiles", "output_format")? {
+            conn.execute_batch("ALTER TABLE app_profiles ADD COLUMN output_format TEXT;")
+                .context("failed to add app_profiles.output_format column")?;
+        }
+
+        // Delivered telemetry used to be flagged rather than deleted.
+        conn.execute_batch("DELETE FROM telemetry_queue WHERE delivered = 1;")
+            .context("failed to purge delivered telemetry")?;
+
+        // Verify that FTS5 is operational.
+        conn.prepare("SELECT count(*) FROM session_index")
+            .context("FTS5 session_index missing after migration")?
+            .query_row([], |row| row.get::<_, i64>(0))
+            .context("failed to read session_index after migration")?;
+
+        Ok(())
+    }
+
+    pub(super) fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        for name in names {
+            if name? == column {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+impl SqlitePersistence {
+    pub fn run_migrations_for_tests(conn: &mut Connection) -> Result<()> {
+        Self::run_migrations(conn)
+    }
+}
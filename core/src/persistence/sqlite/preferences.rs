@@ -0,0 +1,586 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension};
+
+use super::SqlitePersistence;
+use crate::orchestrator::profile::{PolishProfile, PolishProfileBinding};
+use crate::orchestrator::vocabulary::{VocabularyKind, VocabularyTerm};
+use crate::session::app_profile::AppProfile;
+use crate::session::corrections::CorrectionPair;
+use crate::session::preset::{EngineChoice, SessionPreset};
+use crate::session::publisher::{FallbackStrategy, FieldRole, InsertionMethod, OutputFormat};
+use crate::session::replacement::ReplacementRule;
+
+impl SqlitePersistence {
+    /// Insert or update a vocabulary term. Terms are unique ignoring case; the
+    /// latest spelling wins.
+    pub fn upsert_vocabulary_term(&self, term: &VocabularyTerm) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO vocabulary(term, kind, boost, created_at_ms)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(term) DO UPDATE SET
+                term = excluded.term,
+                kind = excluded.kind,
+                boost = excluded.boost",
+            params![
+                term.term,
+                term.kind.as_str(),
+                term.boost as f64,
+                term.created_at_ms
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_vocabulary_term(&self, term: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute("DELETE FROM vocabulary WHERE term = ?1", params![term])?;
+        Ok(removed > 0)
+    }
+
+    /// All vocabulary terms in insertion order.
+    pub fn list_vocabulary(&self) -> Result<Vec<VocabularyTerm>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT term, kind, boost, created_at_ms FROM vocabulary
+             ORDER BY created_at_ms ASC, rowid ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let kind: String = row.get(1)?;
+            Ok(VocabularyTerm {
+                term: row.get(0)?,
+                kind: VocabularyKind::parse(&kind).unwrap_or(VocabularyKind::Term),
+                boost: row.get::<_, f64>(2)? as f32,
+                created_at_ms: row.get(3)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read vocabulary")
+    }
+
+    /// Insert or update a replacement rule, keeping the original creation time.
+    pub fn upsert_replacement_rule(&self, rule: &ReplacementRule) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO replacement_rules(rule_id, pattern, replacement, is_regex, app_identifier,
+                enabled, created_at_ms, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(rule_id) DO UPDATE SET
+                pattern = excluded.pattern,
+                replacement = excluded.replacement,
+                is_regex = excluded.is_regex,
+                app_identifier = excluded.app_identifier,
+                enabled = excluded.enabled,
+                updated_at_ms = excluded.updated_at_ms",
+            params![
+                rule.rule_id,
+                rule.pattern,
+                rule.replacement,
+                rule.is_regex,
+                rule.app_identifier,
+                rule.enabled,
+                rule.created_at_ms,
+                rule.updated_at_ms,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_replacement_rule(&self, rule_id: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM replacement_rules WHERE rule_id = ?1",
+            params![rule_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// All replacement rules in creation order.
+    pub fn list_replacement_rules(&self) -> Result<Vec<ReplacementRule>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT rule_id, pattern, replacement, is_regex, app_identifier, enabled,
+                created_at_ms, updated_at_ms
+             FROM replacement_rules ORDER BY created_at_ms ASC, rowid ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ReplacementRule {
+                rule_id: row.get(0)?,
+                pattern: row.get(1)?,
+                replacement: row.get(2)?,
+                is_regex: row.get(3)?,
+                app_identifier: row.get(4)?,
+                enabled: row.get(5)?,
+                created_at_ms: row.get(6)?,
+                updated_at_ms: row.get(7)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read replacement rules")
+    }
+
+    /// Adds correction pairs to the corpus, counting repeats of a known pair.
+    pub fn record_corrections(&self, pairs: &[(String, String)], now_ms: i64) -> Result<()> {
+        let mut conn = self.connection()?;
+        let tx = conn
+            .transaction()
+            .context("failed to open transaction for corrections")?;
+        for (original, corrected) in pairs {
+            tx.execute(
+                "INSERT INTO corrections(original, corrected, occurrences, first_seen_ms,
+                    last_seen_ms)
+                 VALUES (?1, ?2, 1, ?3, ?3)
+                 ON CONFLICT(original, corrected) DO UPDATE SET
+                    occurrences = occurrences + 1,
+                    last_seen_ms = excluded.last_seen_ms",
+                params![original, corrected, now_ms],
+            )?;
+        }
+        tx.commit().context("failed to commit corrections")?;
+        Ok(())
+    }
+
+    /// The correction corpus, most frequent pairs first.
+    pub fn list_corrections(&self) -> Result<Vec<CorrectionPair>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT original, corrected, occurrences, first_seen_ms, last_seen_ms
+             FROM corrections ORDER BY occurrences DESC, last_seen_ms DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(CorrectionPair {
+                original: row.get(0)?,
+                corrected: row.get(1)?,
+                occurrences: row.get(2)?,
+                first_seen_ms: row.get(3)?,
+                last_seen_ms: row.get(4)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read corrections")
+    }
+
+    pub fn delete_correction(&self, original: &str, corrected: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM corrections WHERE original = ?1 AND corrected = ?2",
+            params![original, corrected],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// JSON value of a per-user preference, if one was stored.
+    pub fn load_user_setting(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.connection()?;
+        conn.query_row(
+            "SELECT value FROM user_settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("failed to read user setting")
+    }
+
+    pub fn store_user_setting(&self, key: &str, value: &str, now_ms: i64) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO user_settings(key, value, updated_at_ms)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at_ms = excluded.updated_at_ms",
+            params![key, value, now_ms],
+        )?;
+        Ok(())
+    }
+
+    /// Records the last noise baseline measured on an input device.
+    pub fn store_noise_baseline(&self, device_id: &str, level_db: f32, now_ms: i64) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO noise_baselines(device_id, level_db, updated_at_ms)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(device_id) DO UPDATE SET
+                level_db = excluded.level_db,
+                updated_at_ms = excluded.updated_at_ms",
+            params![device_id, f64::from(level_db), now_ms],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_noise_baselines(&self) -> Result<Vec<(String, f32)>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare("SELECT device_id, level_db FROM noise_baselines")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)? as f32))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read noise baselines")
+    }
+
+    /// Binds a polish profile to an app, or sets the global default when
+    /// `app_identifier` is `None` (stored as an empty identifier).
+    pub fn set_polish_profile(
+        &self,
+        app_identifier: Option<&str>,
+        profile: PolishProfile,
+        now_ms: i64,
+    ) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO polish_profiles(app_identifier, profile, updated_at_ms)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(app_identifier) DO UPDATE SET
+                profile = excluded.profile,
+                updated_at_ms = excluded.updated_at_ms",
+            params![app_identifier.unwrap_or_default(), profile.as_str(), now_ms],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear_polish_profile(&self, app_identifier: Option<&str>) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM polish_profiles WHERE app_identifier = ?1",
+            params![app_identifier.unwrap_or_default()],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// All profile bindings; rows naming an unknown profile are skipped.
+    pub fn list_polish_profiles(&self) -> Result<Vec<PolishProfileBinding>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT app_identifier, profile FROM polish_profiles ORDER BY app_identifier ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let rows = rows
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read polish profiles")?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(app_identifier, profile)| {
+                Some(PolishProfileBinding {
+                    app_identifier: (!app_identifier.is_empty()).then_some(app_identifier),
+                    profile: PolishProfile::parse(&profile)?,
+                })
+            })
+            .collect())
+    }
+
+    pub fn upsert_app_profile(&self, profile: &AppProfile) -> Result<()> {
+        let conn = self.connection()?;
+        let vocabulary = serde_json::to_string(&profile.vocabulary)
+            .context("failed to encode app profile vocabulary")?;
+        conn.execute(
+            "INSERT INTO app_profiles(app_identifier, field_role, fallback, polish_profile,
+                vocabulary, insertion, updated_at_ms, output_format)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(app_identifier, field_role) DO UPDATE SET
+                fallback = excluded.fallback,
+                polish_profile = excluded.polish_profile,
+                vocabulary = excluded.vocabulary,
+                insertion = excluded.insertion,
+                updated_at_ms = excluded.updated_at_ms,
+                output_format = excluded.output_format",
+            params![
+                profile.app_identifier,
+                profile.field_role.as_ref().map_or("", FieldRole::as_str),
+                profile.fallback.as_ref().map(FallbackStrategy::as_str),
+                profile.polish_profile.as_ref().map(PolishProfile::as_str),
+                vocabulary,
+                profile.insertion.as_ref().map(InsertionMethod::as_str),
+                profile.updated_at_ms,
+                profile.output_format.as_ref().map(OutputFormat::as_str),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_app_profile(
+        &self,
+        app_identifier: &str,
+        field_role: Option<FieldRole>,
+    ) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM app_profiles WHERE app_identifier = ?1 AND field_role = ?2",
+            params![
+                app_identifier,
+                field_role.as_ref().map_or("", FieldRole::as_str)
+            ],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// All app profiles ordered by identifier. Unknown enum values read back as
+    /// unset so a newer schema never blocks older builds.
+    pub fn list_app_profiles(&self) -> Result<Vec<AppProfile>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT app_identifier, fallback, polish_profile, vocabulary, insertion, updated_at_ms,
+                field_role, output_format
+             FROM app_profiles ORDER BY app_identifier ASC, field_role ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let fallback: Option<String> = row.get(1)?;
+            let polish_profile: Option<String> = row.get(2)?;
+            let vocabulary: String = row.get(3)?;
+            let insertion: Option<String> = row.get(4)?;
+            let field_role: String = row.get(6)?;
+            let output_format: Option<String> = row.get(7)?;
+            Ok(AppProfile {
+                app_identifier: row.get(0)?,
+                field_role: FieldRole::parse(&field_role),
+                fallback: fallback.as_deref().and_then(FallbackStrategy::parse),
+                polish_profile: polish_profile.as_deref().and_then(PolishProfile::parse),
+                vocabulary: serde_json::from_str(&vocabulary).unwrap_or_default(),
+                insertion: insertion.as_deref().and_then(InsertionMethod::parse),
+                output_format: output_format.as_deref().and_then(OutputFormat::parse),
+                updated_at_ms: row.get(5)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read app profiles")
+    }
+
+    pub fn upsert_session_preset(&self, preset: &SessionPreset) -> Result<()> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO session_presets(name, engine, polish_profile, language, target_app,
+                fallback, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(name) DO UPDATE SET
+                engine = excluded.engine,
+                polish_profile = excluded.polish_profile,
+                language = excluded.language,
+                target_app = excluded.target_app,
+                fallback = excluded.fallback,
+                updated_at_ms = excluded.updated_at_ms",
+            params![
+                preset.name,
+                preset.engine.as_ref().map(EngineChoice::as_str),
+                preset.polish_profile.as_ref().map(PolishProfile::as_str),
+                preset.language,
+                preset.target_app,
+                preset.fallback.as_ref().map(FallbackStrategy::as_str),
+                preset.updated_at_ms,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_session_preset(&self, name: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute("DELETE FROM session_presets WHERE name = ?1", [name])?;
+        Ok(removed > 0)
+    }
+
+    /// All session presets ordered by name, matched case-insensitively.
+    pub fn list_session_presets(&self) -> Result<Vec<SessionPreset>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT name, engine, polish_profile, language, target_app, fallback, updated_at_ms
+             FROM session_presets ORDER BY name COLLATE NOCASE ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let engine: Option<String> = row.get(1)?;
+            let polish_profile: Option<String> = row.get(2)?;
+            let fallback: Option<String> = row.get(5)?;
+            Ok(SessionPreset {
+                name: row.get(0)?,
+                engine: engine.as_deref().and_then(EngineChoice::parse),
+                polish_profile: polish_profile.as_deref().and_then(PolishProfile::parse),
+                language: row.get(3)?,
+                target_app: row.get(4)?,
+                fallback: fallback.as_deref().and_then(FallbackStrategy::parse),
+                updated_at_ms: row.get(6)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read session presets")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::SqliteConfig;
+
+    #[test]
+    fn vocabulary_terms_upsert_case_insensitively() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut github = VocabularyTerm::new("github", VocabularyKind::Name);
+        github.created_at_ms = 1;
+        sqlite.upsert_vocabulary_term(&github).unwrap();
+        let mut api = VocabularyTerm::new("API", VocabularyKind::Acronym);
+        api.created_at_ms = 2;
+        api.boost = 2.5;
+        sqlite.upsert_vocabulary_term(&api).unwrap();
+
+        github.term = "GitHub".into();
+        github.created_at_ms = 3;
+        sqlite.upsert_vocabulary_term(&github).unwrap();
+
+        let terms = sqlite.list_vocabulary().unwrap();
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].term, "GitHub");
+        assert_eq!(terms[0].created_at_ms, 1);
+        assert_eq!(terms[1].kind, VocabularyKind::Acronym);
+        assert_eq!(terms[1].boost, 2.5);
+
+        assert!(sqlite.delete_vocabulary_term("api").unwrap());
+        assert!(!sqlite.delete_vocabulary_term("api").unwrap());
+        assert_eq!(sqlite.list_vocabulary().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn replacement_rules_round_trip_and_keep_creation_time() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut rule = ReplacementRule {
+            rule_id: "email".into(),
+            pattern: "my email".into(),
+            replacement: "me@example.com".into(),
+            is_regex: false,
+            app_identifier: None,
+            enabled: true,
+            created_at_ms: 10,
+            updated_at_ms: 10,
+        };
+        sqlite.upsert_replacement_rule(&rule).unwrap();
+
+        rule.app_identifier = Some("com.apple.mail".into());
+        rule.created_at_ms = 20;
+        rule.updated_at_ms = 20;
+        sqlite.upsert_replacement_rule(&rule).unwrap();
+
+        let rules = sqlite.list_replacement_rules().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].app_identifier.as_deref(), Some("com.apple.mail"));
+        assert_eq!(rules[0].created_at_ms, 10);
+        assert_eq!(rules[0].updated_at_ms, 20);
+
+        assert!(sqlite.delete_replacement_rule("email").unwrap());
+        assert!(sqlite.list_replacement_rules().unwrap().is_empty());
+    }
+
+    #[test]
+    fn polish_profiles_bind_per_app_and_global() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        sqlite
+            .set_polish_profile(None, PolishProfile::Casual, 1)
+            .unwrap();
+        sqlite
+            .set_polish_profile(Some("com.apple.mail"), PolishProfile::Formal, 2)
+            .unwrap();
+        sqlite
+            .set_polish_profile(Some("COM.APPLE.MAIL"), PolishProfile::Email, 3)
+            .unwrap();
+
+        let bindings = sqlite.list_polish_profiles().unwrap();
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].app_identifier, None);
+        assert_eq!(bindings[0].profile, PolishProfile::Casual);
+        assert_eq!(bindings[1].profile, PolishProfile::Email);
+
+        assert!(sqlite.clear_polish_profile(None).unwrap());
+        assert!(!sqlite.clear_polish_profile(None).unwrap());
+        assert_eq!(sqlite.list_polish_profiles().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn user_settings_upsert_by_key() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        assert_eq!(sqlite.load_user_setting("silence_policy").unwrap(), None);
+        sqlite
+            .store_user_setting("silence_policy", r#"{"enabled":true}"#, 1)
+            .unwrap();
+        sqlite
+            .store_user_setting("silence_policy", r#"{"enabled":false}"#, 2)
+            .unwrap();
+        assert_eq!(
+            sqlite
+                .load_user_setting("silence_policy")
+                .unwrap()
+                .as_deref(),
+            Some(r#"{"enabled":false}"#)
+        );
+    }
+
+    #[test]
+    fn noise_baselines_keep_latest_level_per_device() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        sqlite.store_noise_baseline("usb", -48.0, 1).unwrap();
+        sqlite.store_noise_baseline("builtin", -40.5, 2).unwrap();
+        sqlite.store_noise_baseline("usb", -45.0, 3).unwrap();
+
+        let mut baselines = sqlite.list_noise_baselines().unwrap();
+        baselines.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            baselines,
+            vec![("builtin".to_string(), -40.5), ("usb".to_string(), -45.0)]
+        );
+    }
+
+    #[test]
+    fn app_profiles_round_trip() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut profile = AppProfile::new("com.apple.mail");
+        profile.fallback = Some(FallbackStrategy::NotifyOnly);
+        profile.polish_profile = Some(PolishProfile::Email);
+        profile.vocabulary = vec!["Flowwisper".into()];
+        profile.insertion = Some(InsertionMethod::ClipboardPaste);
+        profile.output_format = Some(OutputFormat::Html);
+        profile.updated_at_ms = 5;
+        sqlite.upsert_app_profile(&profile).unwrap();
+        sqlite
+            .upsert_app_profile(&AppProfile::new("COM.APPLE.MAIL"))
+            .unwrap();
+
+        let profiles = sqlite.list_app_profiles().unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].fallback, None);
+
+        sqlite.upsert_app_profile(&profile).unwrap();
+        assert_eq!(sqlite.list_app_profiles().unwrap(), vec![profile]);
+        let mut compose = AppProfile::new("com.apple.mail");
+        compose.field_role = Some(FieldRole::SearchBox);
+        sqlite.upsert_app_profile(&compose).unwrap();
+        assert_eq!(sqlite.list_app_profiles().unwrap().len(), 2);
+        assert!(sqlite.delete_app_profile("com.apple.mail", None).unwrap());
+        assert_eq!(sqlite.list_app_profiles().unwrap(), vec![compose]);
+        assert!(sqlite
+            .delete_app_profile("com.apple.mail", Some(FieldRole::SearchBox))
+            .unwrap());
+        assert!(sqlite.list_app_profiles().unwrap().is_empty());
+    }
+
+    #[test]
+    fn session_presets_round_trip() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut meeting = SessionPreset::new("Meeting notes");
+        meeting.engine = Some(EngineChoice::Cloud);
+        meeting.polish_profile = Some(PolishProfile::BulletNotes);
+        meeting.language = Some("en-US".into());
+        meeting.target_app = Some("md.obsidian".into());
+        meeting.fallback = Some(FallbackStrategy::ClipboardCopy);
+        meeting.updated_at_ms = 7;
+        sqlite.upsert_session_preset(&meeting).unwrap();
+        sqlite
+            .upsert_session_preset(&SessionPreset::new("code comments"))
+            .unwrap();
+        sqlite
+            .upsert_session_preset(&SessionPreset::new("CODE COMMENTS"))
+            .unwrap();
+
+        let presets = sqlite.list_session_presets().unwrap();
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "code comments");
+        assert_eq!(presets[1], meeting);
+        assert!(sqlite.delete_session_preset("meeting NOTES").unwrap());
+        assert!(!sqlite.delete_session_preset("meeting notes").unwrap());
+        assert_eq!(sqlite.list_session_presets().unwrap().len(), 1);
+    }
+}
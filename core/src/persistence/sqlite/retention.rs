@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Context, Result};
+use rusqlite::params;
+
+use super::SqlitePersistence;
+use crate::session::history::HISTORY_RETENTION_MS;
+
+impl SqlitePersistence {
+    /// Pins or unpins a session. Unpinning restarts the retention window so an
+    /// entry kept past its original expiry is not purged on the next cleanup.
+    pub fn set_pinned(&self, session_id: &str, pinned: bool, now_ms: i64) -> Result<()> {
+        let conn = self.connection()?;
+        let updated = conn.execute(
+            "UPDATE sessions SET pinned = ?2,
+                expires_at_ms = CASE WHEN ?2 THEN expires_at_ms
+                    ELSE MAX(expires_at_ms, ?3) END,
+                updated_at_ms = ?4
+             WHERE session_id = ?1",
+            params![
+                session_id,
+                pinned,
+                now_ms.saturating_add(HISTORY_RETENTION_MS),
+                now_ms
+            ],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("history entry {session_id} not found"));
+        }
+        Ok(())
+    }
+
+    /// Deletes expired sessions according to the configured TTL and returns
+    /// their ids. Pinned sessions are never removed.
+    pub fn cleanup_expired(&self, now_ms: i64) -> Result<Vec<String>> {
+        self.delete_sessions(
+            "DELETE FROM sessions WHERE expires_at_ms <= ?1 AND pinned = 0 RETURNING session_id",
+            now_ms,
+        )
+    }
+
+    /// Deletes every session captured at or before `cutoff_ms`, pinned or not, and
+    /// returns their ids. Used to enforce an organization retention cap, which users
+    /// must not be able to exceed.
+    pub fn purge_sessions_before(&self, cutoff_ms: i64) -> Result<Vec<String>> {
+        self.delete_sessions(
+            "DELETE FROM sessions WHERE completed_at_ms <= ?1 RETURNING session_id",
+            cutoff_ms,
+        )
+    }
+
+    fn delete_sessions(&self, sql: &str, bound_ms: i64) -> Result<Vec<String>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(sql)?;
+        let ids = stmt
+            .query_map(params![bound_ms], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .context("failed to delete expired sessions")?;
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::tests::snapshot;
+    use crate::persistence::sqlite::SqliteConfig;
+    use crate::session::history::HistoryQuery;
+
+    #[test]
+    fn pinned_entries_survive_cleanup_and_filter_searches() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        sqlite
+            .insert_session(&snapshot("s-1", 1_000, "keep this", "Keep this."))
+            .unwrap();
+        sqlite
+            .insert_session(&snapshot("s-2", 2_000, "drop this", "Drop this."))
+            .unwrap();
+        sqlite.set_pinned("s-1", true, 2_000).unwrap();
+        assert!(sqlite.set_pinned("missing", true, 2_000).is_err());
+
+        let pinned = sqlite
+            .search_sessions(&HistoryQuery {
+                limit: 10,
+                pinned_only: true,
+                ..HistoryQuery::default()
+            })
+            .unwrap();
+        assert_eq!(pinned.entries.len(), 1);
+        assert!(pinned.entries[0].pinned);
+
+        let far_future = 10_000 + HISTORY_RETENTION_MS;
+        assert_eq!(sqlite.cleanup_expired(far_future).unwrap().len(), 1);
+        assert!(sqlite.load_session("s-1").unwrap().is_some());
+        assert!(sqlite.load_session("s-2").unwrap().is_none());
+
+        sqlite.set_pinned("s-1", false, far_future).unwrap();
+        assert!(sqlite.cleanup_expired(far_future).unwrap().is_empty());
+        assert!(!sqlite.load_session("s-1").unwrap().unwrap().pinned);
+
+        // A retention cap applies to pinned entries too.
+        sqlite.set_pinned("s-1", true, far_future).unwrap();
+        assert_eq!(sqlite.purge_sessions_before(1_000).unwrap().len(), 1);
+        assert!(sqlite.load_session("s-1").unwrap().is_none());
+    }
+}
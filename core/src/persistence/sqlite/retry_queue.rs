@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use rusqlite::params;
+use rusqlite::types::Type;
+
+use super::SqlitePersistence;
+use crate::session::publisher::InsertionMethod;
+use crate::session::retry_queue::PublishRetryEntry;
+
+impl SqlitePersistence {
+    pub fn upsert_publish_retry(&self, entry: &PublishRetryEntry) -> Result<()> {
+        let conn = self.connection()?;
+        let snapshot = serde_json::to_string(&entry.snapshot)
+            .context("failed to encode publish retry snapshot")?;
+        conn.execute(
+            "INSERT INTO publish_retry_queue(retry_id, session_id, transcript, app_identifier,
+                window_title, insertion, attempts, last_error, snapshot, created_at_ms, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(retry_id) DO UPDATE SET
+                attempts = excluded.attempts,
+                last_error = excluded.last_error,
+                updated_at_ms = excluded.updated_at_ms",
+            params![
+                entry.retry_id,
+                entry.session_id,
+                entry.transcript,
+                entry.app_identifier,
+                entry.window_title,
+                entry.insertion.as_str(),
+                entry.attempts,
+                entry.last_error,
+                snapshot,
+                entry.created_at_ms,
+                entry.updated_at_ms,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_publish_retry(&self, retry_id: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM publish_retry_queue WHERE retry_id = ?1",
+            params![retry_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Pending publish retries, oldest first.
+    pub fn list_publish_retries(&self) -> Result<Vec<PublishRetryEntry>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT retry_id, session_id, transcript, app_identifier, window_title, insertion,
+                attempts, last_error, snapshot, created_at_ms, updated_at_ms
+             FROM publish_retry_queue ORDER BY created_at_ms ASC, retry_id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let insertion: String = row.get(5)?;
+            let snapshot: String = row.get(8)?;
+            let snapshot = serde_json::from_str(&snapshot).map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(8, Type::Text, Box::new(err))
+            })?;
+            Ok(PublishRetryEntry {
+                retry_id: row.get(0)?,
+                session_id: row.get(1)?,
+                transcript: row.get(2)?,
+                app_identifier: row.get(3)?,
+                window_title: row.get(4)?,
+                insertion: InsertionMethod::parse(&insertion).unwrap_or_default(),
+                attempts: row.get(6)?,
+                last_error: row.get(7)?,
+                snapshot,
+                created_at_ms: row.get(9)?,
+                updated_at_ms: row.get(10)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read publish retry queue")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::tests::snapshot;
+    use crate::persistence::sqlite::SqliteConfig;
+
+    #[test]
+    fn publish_retries_round_trip_and_update_attempts() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        let mut entry = PublishRetryEntry {
+            retry_id: "retry-1".into(),
+            session_id: "session-retry".into(),
+            transcript: "see you at two".into(),
+            app_identifier: Some("com.apple.mail".into()),
+            window_title: Some("Reply".into()),
+            insertion: InsertionMethod::Keystrokes,
+            attempts: 0,
+            last_error: Some("focus lost".into()),
+            snapshot: snapshot("session-retry", 5_000, "raw", "see you at two"),
+            created_at_ms: 10,
+            updated_at_ms: 10,
+        };
+        sqlite.upsert_publish_retry(&entry).unwrap();
+        entry.attempts = 2;
+        entry.last_error = Some("operation timed out".into());
+        entry.updated_at_ms = 20;
+        sqlite.upsert_publish_retry(&entry).unwrap();
+
+        assert_eq!(sqlite.list_publish_retries().unwrap(), vec![entry]);
+        assert!(sqlite.delete_publish_retry("retry-1").unwrap());
+        assert!(!sqlite.delete_publish_retry("retry-1").unwrap());
+        assert!(sqlite.list_publish_retries().unwrap().is_empty());
+    }
+}
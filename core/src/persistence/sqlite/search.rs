@@ -0,0 +1,268 @@
+use anyhow::Result;
+use rusqlite::types::Value;
+use rusqlite::Row;
+
+use super::SqlitePersistence;
+use crate::session::history::{
+    HighlightRange, HistoryMatchField, HistoryPage, HistoryQuery, HistorySearchHit,
+};
+
+/// Tokens of context returned around each search match.
+const SEARCH_SNIPPET_TOKENS: usize = 16;
+const SNIPPET_OPEN: char = '\u{2}';
+const SNIPPET_CLOSE: char = '\u{3}';
+
+impl SqlitePersistence {
+    pub fn search_sessions(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let conn = self.connection()?;
+        let mut filters = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        let match_expr = query
+            .keyword
+            .as_deref()
+            .and_then(Self::fts_match_expression);
+        if let Some(expr) = &match_expr {
+            filters.push("session_index MATCH ?".to_string());
+            values.push(Value::Text(expr.clone()));
+        }
+
+        if let Some(locale) = query
+            .locale
+            .as_ref()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+        {
+            filters.push("s.locale = ?".to_string());
+            values.push(Value::Text(locale));
+        }
+
+        if let Some(app) = query
+            .app_identifier
+            .as_ref()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+        {
+            filters.push("s.app_identifier = ?".to_string());
+            values.push(Value::Text(app));
+        }
+
+        if query.pinned_only {
+            filters.push("s.pinned = 1".to_string());
+        }
+
+        if let Some(tag) = query
+            .tag
+            .as_ref()
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty())
+        {
+            filters.push(
+                "EXISTS (SELECT 1 FROM json_each(s.tags) WHERE lower(json_each.value) = ?)"
+                    .to_string(),
+            );
+            values.push(Value::Text(tag));
+        }
+
+        let from_clause = if match_expr.is_some() {
+            " FROM sessions s JOIN session_index ON session_index.rowid = s.rowid"
+        } else {
+            " FROM sessions s"
+        };
+        let where_clause = if filters.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", filters.join(" AND "))
+        };
+
+        let mut base_query = "SELECT s.session_id, s.started_at_ms, s.completed_at_ms, \
+            s.duration_ms, s.locale, s.app_identifier, s.app_version, s.raw_transcript, \
+            s.polished_transcript, s.confidence_score, s.accuracy_flag, s.accuracy_remarks, \
+            s.post_actions, s.metadata, s.pinned, s.language_segments, \
+            s.translated_transcript, s.translation_locale, s.quality_flags, s.speed, s.meeting, \
+            s.tags"
+            .to_string();
+        if match_expr.is_some() {
+            // Only the transcript columns contribute to relevance.
+            base_query.push_str(&format!(
+                ", bm25(session_index, 0.0, 1.0, 1.0, 0.0) AS rank, \
+                snippet(session_index, 1, char(2), char(3), '…', {n}) AS raw_snippet, \
+                snippet(session_index, 2, char(2), char(3), '…', {n}) AS polished_snippet",
+                n = SEARCH_SNIPPET_TOKENS
+            ));
+        }
+        base_query.push_str(from_clause);
+        base_query.push_str(&where_clause);
+        if match_expr.is_some() {
+            base_query.push_str(" ORDER BY rank ASC, s.completed_at_ms DESC LIMIT ? OFFSET ?");
+        } else {
+            base_query.push_str(" ORDER BY s.completed_at_ms DESC LIMIT ? OFFSET ?");
+        }
+
+        let mut page_values = values.clone();
+        page_values.push(Value::Integer(query.limit as i64));
+        page_values.push(Value::Integer(query.offset as i64));
+
+        let mut stmt = conn.prepare(&base_query)?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(page_values.iter()))?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut entry = Self::read_history_entry(row)?;
+            if match_expr.is_some() {
+                entry.search_hit = Some(Self::read_search_hit(row)?);
+            }
+            entries.push(entry);
+        }
+
+        let count_sql = format!("SELECT COUNT(*){from_clause}{where_clause}");
+        let total: i64 = conn
+            .prepare(&count_sql)?
+            .query_row(rusqlite::params_from_iter(values.iter()), |row| row.get(0))?;
+
+        let next_offset = if (query.offset + entries.len()) < total as usize {
+            Some(query.offset + entries.len())
+        } else {
+            None
+        };
+
+        Ok(HistoryPage {
+            total: Some(total),
+            next_offset,
+            entries,
+        })
+    }
+
+    /// Turns free-form input into a prefix query over the transcript columns.
+    /// Every term is quoted so FTS5 operators and stray quotes cannot break the
+    /// query syntax.
+    fn fts_match_expression(keyword: &str) -> Option<String> {
+        let terms: Vec<String> = keyword
+            .split_whitespace()
+            .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+            .collect();
+        if terms.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{{raw_transcript polished_transcript}} : ({})",
+            terms.join(" AND ")
+        ))
+    }
+
+    fn read_search_hit(row: &Row) -> rusqlite::Result<HistorySearchHit> {
+        let rank: f64 = row.get("rank")?;
+        let polished: Option<String> = row.get("polished_snippet")?;
+        let raw: Option<String> = row.get("raw_snippet")?;
+
+        let (field, marked) = match polished {
+            Some(snippet) if snippet.contains(SNIPPET_OPEN) => {
+                (HistoryMatchField::Polished, snippet)
+            }
+            _ => (HistoryMatchField::Raw, raw.unwrap_or_default()),
+        };
+        let (snippet, highlights) = Self::parse_snippet(&marked);
+
+        Ok(HistorySearchHit {
+            field,
+            snippet,
+            highlights,
+            rank,
+        })
+    }
+
+    /// Strips the `snippet()` markers and returns highlight ranges in chars.
+    fn parse_snippet(marked: &str) -> (String, Vec<HighlightRange>) {
+        let mut snippet = String::with_capacity(marked.len());
+        let mut highlights = Vec::new();
+        let mut open: Option<usize> = None;
+        let mut offset = 0;
+        for ch in marked.chars() {
+            match ch {
+                SNIPPET_OPEN => open = Some(offset),
+                SNIPPET_CLOSE => {
+                    if let Some(start) = open.take() {
+                        highlights.push(HighlightRange { start, end: offset });
+                    }
+                }
+                _ => {
+                    snippet.push(ch);
+                    offset += 1;
+                }
+            }
+        }
+        (snippet, highlights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::tests::{keyword_query, snapshot};
+    use crate::persistence::sqlite::SqliteConfig;
+
+    #[test]
+    fn keyword_search_ranks_matches_and_highlights_snippets() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        sqlite
+            .insert_session(&snapshot(
+                "s-1",
+                1_000,
+                "we talked about the budget once",
+                "We talked about the budget once.",
+            ))
+            .unwrap();
+        sqlite
+            .insert_session(&snapshot("s-2", 2_000, "budget budget budget review", ""))
+            .unwrap();
+        sqlite
+            .insert_session(&snapshot("s-3", 3_000, "lunch plans", "Lunch plans."))
+            .unwrap();
+
+        let page = sqlite.search_sessions(&keyword_query("budg")).unwrap();
+        assert_eq!(page.total, Some(2));
+        let ids: Vec<_> = page
+            .entries
+            .iter()
+            .map(|entry| entry.session_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["s-2", "s-1"]);
+
+        let top = page.entries[0].search_hit.as_ref().unwrap();
+        assert_eq!(top.field, HistoryMatchField::Raw);
+        assert_eq!(top.snippet, "budget budget budget review");
+        assert_eq!(top.highlights.len(), 3);
+        assert_eq!(top.highlights[0], HighlightRange { start: 0, end: 6 });
+
+        let second = page.entries[1].search_hit.as_ref().unwrap();
+        assert_eq!(second.field, HistoryMatchField::Polished);
+        let range = second.highlights[0];
+        let highlighted: String = second
+            .snippet
+            .chars()
+            .skip(range.start)
+            .take(range.end - range.start)
+            .collect();
+        assert_eq!(highlighted, "budget");
+        assert!(top.rank <= second.rank);
+    }
+
+    #[test]
+    fn keyword_search_tolerates_fts_syntax_and_skips_app_identifier() {
+        let sqlite = SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap();
+        sqlite
+            .insert_session(&snapshot("s-1", 1_000, "say \"hello\" AND bye", ""))
+            .unwrap();
+
+        let page = sqlite
+            .search_sessions(&keyword_query("\"hello AND"))
+            .unwrap();
+        assert_eq!(page.total, Some(1));
+
+        let page = sqlite.search_sessions(&keyword_query("notes")).unwrap();
+        assert_eq!(page.total, Some(0));
+
+        let page = sqlite.search_sessions(&keyword_query("   ")).unwrap();
+        assert_eq!(page.total, Some(1));
+        assert!(page.entries[0].search_hit.is_none());
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OpenFlags};
+
+use super::SqlitePersistence;
+
+impl SqlitePersistence {
+    /// Writes, reads back and removes a probe row to verify the database is usable.
+    pub fn probe(&self) -> Result<Duration> {
+        let started = Instant::now();
+        let token = format!(
+            "probe-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_nanos())
+                .unwrap_or(0)
+        );
+
+        let mut conn = self.connection()?;
+        let tx = conn
+            .transaction()
+            .context("failed to open transaction for database probe")?;
+        tx.execute(
+            "INSERT OR REPLACE INTO diagnostics_probe(id, token, written_at_ms)
+             VALUES (1, ?1, strftime('%s','now') * 1000)",
+            params![token],
+        )
+        .context("failed to write database probe")?;
+        let stored: String = tx
+            .query_row(
+                "SELECT token FROM diagnostics_probe WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .context("failed to read database probe")?;
+        if stored != token {
+            return Err(anyhow!("database probe read back unexpected value"));
+        }
+        tx.execute("DELETE FROM diagnostics_probe WHERE id = 1", [])
+            .context("failed to clear database probe")?;
+        tx.commit().context("failed to commit database probe")?;
+
+        Ok(started.elapsed())
+    }
+
+    /// Copies a consistent snapshot of the database to `dest` with the SQLite
+    /// online backup API. Writers are not blocked while the copy runs, and the
+    /// snapshot keeps the database's SQLCipher key.
+    pub fn backup_to(&self, dest: &Path) -> Result<()> {
+        let source = self.connection()?;
+        let mut target = Connection::open(dest)
+            .with_context(|| format!("failed to create snapshot {}", dest.display()))?;
+        if let Some(key) = self.key_material()? {
+            target
+                .pragma_update(None, "key", key)
+                .context("failed to apply SQLCipher key to snapshot")?;
+        }
+        rusqlite::backup::Backup::new(&source, &mut target)
+            .context("failed to start database backup")?
+            .run_to_completion(256, Duration::from_millis(5), None)
+            .context("database backup failed")?;
+        Ok(())
+    }
+
+    /// Writes a copy of the snapshot at `source` (opened with `source_key`) to a new
+    /// database at `dest` encrypted with `dest_key`, or plaintext when it is `None`.
+    /// Uses `sqlcipher_export`, so it converts between keyed and plaintext files too.
+    pub fn export_snapshot(
+        source: &Path,
+        source_key: Option<&str>,
+        dest: &Path,
+        dest_key: Option<&str>,
+    ) -> Result<()> {
+        let conn = Connection::open(source)
+            .with_context(|| format!("failed to open snapshot {}", source.display()))?;
+        if let Some(key) = source_key {
+            conn.pragma_update(None, "key", key)
+                .context("failed to apply snapshot key")?;
+        }
+        conn.execute(
+            "ATTACH DATABASE ?1 AS export KEY ?2",
+            params![dest.to_string_lossy(), dest_key.unwrap_or_default()],
+        )
+        .with_context(|| format!("failed to create {}", dest.display()))?;
+        conn.query_row("SELECT sqlcipher_export('export')", [], |_| Ok(()))
+            .context("failed to re-encrypt snapshot")?;
+        conn.execute("DETACH DATABASE export", [])
+            .context("failed to finish snapshot export")?;
+        Ok(())
+    }
+
+    /// Checks that a snapshot written by [`SqlitePersistence::backup_to`] opens
+    /// with `key` and passes `PRAGMA integrity_check`. Returns its session count.
+    pub fn verify_snapshot(path: &Path, key: Option<&str>) -> Result<usize> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_FULL_MUTEX,
+        )
+        .with_context(|| format!("failed to open snapshot {}", path.display()))?;
+        if let Some(value) = key {
+            conn.pragma_update(None, "key", value)
+                .context("failed to apply snapshot key")?;
+        }
+        let status: String = conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .context("snapshot is not a readable database (wrong key?)")?;
+        if status != "ok" {
+            return Err(anyhow!("snapshot failed integrity check: {status}"));
+        }
+        let sessions: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .context("snapshot has no history sessions table")?;
+        Ok(sessions as usize)
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension};
+
+use super::SqlitePersistence;
+use crate::session::history::{HistoryEntry, HISTORY_RETENTION_MS};
+
+impl SqlitePersistence {
+    /// Writes a complete history entry, replacing any local copy. Used when a
+    /// synced change wins reconciliation; `updated_at_ms` is the winning edit's time.
+    pub fn upsert_history_entry(
+        &self,
+        entry: &HistoryEntry,
+        updated_at_ms: i64,
+        now_ms: i64,
+    ) -> Result<()> {
+        let conn = self.connection()?;
+        let post_actions = serde_json::to_string(&entry.post_actions)
+            .context("failed to serialize post actions")?;
+        let metadata = if entry.metadata.is_null() {
+            "{}".to_string()
+        } else {
+            serde_json::to_string(&entry.metadata)
+                .context("failed to serialize session metadata")?
+        };
+        let language_segments = serde_json::to_string(&entry.language_segments)
+            .context("failed to serialize language segments")?;
+        let quality_flags = serde_json::to_string(&entry.quality_flags)
+            .context("failed to serialize quality flags")?;
+        let speed = entry
+            .speed
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("failed to serialize dictation speed")?;
+        let meeting = entry
+            .meeting
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("failed to serialize meeting notes")?;
+        let tags = serde_json::to_string(&entry.tags).context("failed to serialize tags")?;
+        let expires_at_ms =
+            (entry.completed_at_ms.max(now_ms)).saturating_add(HISTORY_RETENTION_MS);
+        conn.execute(
+            "INSERT INTO sessions (
+                session_id, started_at_ms, completed_at_ms, duration_ms, locale,
+                app_identifier, app_version, raw_transcript, polished_transcript,
+                confidence_score, accuracy_flag, accuracy_remarks, post_actions,
+                expires_at_ms, metadata, pinned, language_segments,
+                translated_transcript, translation_locale, quality_flags, speed, meeting, tags,
+                updated_at_ms
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                ?18, ?19, ?20, ?21, ?22, ?23, ?24)
+            ON CONFLICT(session_id) DO UPDATE SET
+                started_at_ms=excluded.started_at_ms,
+                completed_at_ms=excluded.completed_at_ms,
+                duration_ms=excluded.duration_ms,
+                locale=excluded.locale,
+                app_identifier=excluded.app_identifier,
+                app_version=excluded.app_version,
+                raw_transcript=excluded.raw_transcript,
+                polished_transcript=excluded.polished_transcript,
+                confidence_score=excluded.confidence_score,
+                accuracy_flag=excluded.accuracy_flag,
+                accuracy_remarks=excluded.accuracy_remarks,
+                post_actions=excluded.post_actions,
+                expires_at_ms=MAX(sessions.expires_at_ms, excluded.expires_at_ms),
+                metadata=excluded.metadata,
+                pinned=excluded.pinned,
+                language_segments=excluded.language_segments,
+                translated_transcript=excluded.translated_transcript,
+                translation_locale=excluded.translation_locale,
+                quality_flags=excluded.quality_flags,
+                speed=excluded.speed,
+                meeting=excluded.meeting,
+                tags=excluded.tags,
+                updated_at_ms=excluded.updated_at_ms",
+            params![
+                entry.session_id,
+                entry.started_at_ms,
+                entry.completed_at_ms,
+                entry.duration_ms,
+                entry.locale.as_deref(),
+                entry.app_identifier.as_deref(),
+                entry.app_version.as_deref(),
+                entry.raw_transcript,
+                entry.polished_transcript,
+                entry.confidence_score,
+                entry.accuracy_flag.as_str(),
+                entry.accuracy_remarks.as_deref(),
+                post_actions,
+                expires_at_ms,
+                metadata,
+                entry.pinned,
+                language_segments,
+                entry.translated_transcript.as_deref(),
+                entry.translation_locale.as_deref(),
+                quality_flags,
+                speed,
+                meeting,
+                tags,
+                updated_at_ms,
+            ],
+        )
+        .context("failed to upsert history entry")?;
+        Ok(())
+    }
+
+    /// When a session was last changed locally or by sync; `None` once it is gone.
+    pub fn session_updated_at(&self, session_id: &str) -> Result<Option<i64>> {
+        let conn = self.connection()?;
+        conn.query_row(
+            "SELECT updated_at_ms FROM sessions WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("failed to read session edit time")
+    }
+
+    pub fn delete_session(&self, session_id: &str) -> Result<bool> {
+        let conn = self.connection()?;
+        let removed = conn.execute(
+            "DELETE FROM sessions WHERE session_id = ?1",
+            params![session_id],
+        )?;
+        Ok(removed > 0)
+    }
+}
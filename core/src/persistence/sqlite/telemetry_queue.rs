@@ -0,0 +1,101 @@
+use std::sync::atomic::Ordering;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::{SqlitePersistence, MAX_TELEMETRY_QUEUE};
+
+/// Telemetry row waiting in `telemetry_queue` for upload.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedTelemetry {
+    pub id: i64,
+    pub session_id: String,
+    pub event_type: String,
+    pub payload: JsonValue,
+    pub created_at_ms: i64,
+}
+
+impl SqlitePersistence {
+    /// Limits how many undelivered telemetry rows `enqueue_telemetry` keeps, clamped to
+    /// `0..=MAX_TELEMETRY_QUEUE`. A cap of zero drops new events outright.
+    pub fn set_telemetry_queue_cap(&self, cap: i64) {
+        self.telemetry_queue_cap
+            .store(cap.clamp(0, MAX_TELEMETRY_QUEUE), Ordering::SeqCst);
+    }
+
+    pub fn enqueue_telemetry(
+        &self,
+        session_id: &str,
+        event_type: &str,
+        payload: JsonValue,
+    ) -> Result<()> {
+        let cap = self.telemetry_queue_cap.load(Ordering::SeqCst);
+        if cap == 0 {
+            return Ok(());
+        }
+        let conn = self.connection()?;
+        let encoded = serde_json::to_string(&payload)
+            .context("failed to encode telemetry payload for queue")?;
+        conn.execute(
+            "INSERT INTO telemetry_queue(session_id, event_type, payload, created_at_ms)
+             VALUES (?1, ?2, ?3, strftime('%s','now') * 1000)",
+            params![session_id, event_type, encoded],
+        )?;
+        Self::prune_pending_telemetry(&conn, cap)?;
+        Ok(())
+    }
+
+    /// Oldest undelivered telemetry rows, up to `limit`.
+    pub fn pending_telemetry(&self, limit: usize) -> Result<Vec<QueuedTelemetry>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, session_id, event_type, payload, created_at_ms FROM telemetry_queue
+             WHERE delivered = 0 ORDER BY id ASC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            let payload: String = row.get(3)?;
+            Ok(QueuedTelemetry {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                event_type: row.get(2)?,
+                payload: serde_json::from_str(&payload).unwrap_or(JsonValue::Null),
+                created_at_ms: row.get(4)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read pending telemetry")
+    }
+
+    /// Deletes rows the uploader has handed off; the queue only ever holds pending events.
+    pub fn remove_delivered_telemetry(&self, ids: &[i64]) -> Result<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let conn = self.connection()?;
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let removed = conn.execute(
+            &format!("DELETE FROM telemetry_queue WHERE id IN ({placeholders})"),
+            rusqlite::params_from_iter(ids.iter()),
+        )?;
+        Ok(removed)
+    }
+
+    /// Drops the oldest undelivered rows so at most `max_pending` remain.
+    pub fn cap_pending_telemetry(&self, max_pending: i64) -> Result<usize> {
+        let conn = self.connection()?;
+        Self::prune_pending_telemetry(&conn, max_pending)
+    }
+
+    fn prune_pending_telemetry(conn: &Connection, max_pending: i64) -> Result<usize> {
+        let removed = conn.execute(
+            "DELETE FROM telemetry_queue WHERE delivered = 0 AND id NOT IN (
+                SELECT id FROM telemetry_queue WHERE delivered = 0 ORDER BY id DESC LIMIT ?1
+            )",
+            params![max_pending.max(0)],
+        )?;
+        Ok(removed)
+    }
+}
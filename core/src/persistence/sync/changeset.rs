@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::aead;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::clock::VersionVector;
+use crate::persistence::backup::archive::{derive_key, PBKDF2_ITERATIONS};
+
+pub const CHANGESET_VERSION: u8 = 2;
+pub const CHANGESET_EXTENSION: &str = "fwsync";
+pub const SYNC_SALT_LEN: usize = 16;
+
+const NONCE_LEN: usize = 12;
+const MIN_SECRET_LEN: usize = 8;
+/// Per-device keys kept by a [`SyncKeyring`]; salts come from untrusted change sets,
+/// so the cache must not grow with every salt a peer sends.
+const MAX_CACHED_KEYS: usize = 32;
+
+/// Kind of row carried in a change set.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum RowKind {
+    Session,
+    Draft,
+}
+
+impl RowKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RowKind::Session => "session",
+            RowKind::Draft => "draft",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "session" => Some(RowKind::Session),
+            "draft" => Some(RowKind::Draft),
+            _ => None,
+        }
+    }
+}
+
+/// A single row at a given version. Deletions carry no payload.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RowChange {
+    pub kind: RowKind,
+    pub row_id: String,
+    pub clock: VersionVector,
+    /// Wall-clock time of the change, used to break concurrent edits.
+    pub updated_at_ms: i64,
+    pub deleted: bool,
+    #[serde(default)]
+    pub payload: Option<JsonValue>,
+}
+
+/// Every local change one device pushed in a single sync round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSet {
+    pub device_id: String,
+    pub sequence: u64,
+    pub created_at_ms: i64,
+    pub changes: Vec<RowChange>,
+}
+
+impl ChangeSet {
+    /// Object name used on the sync target, e.g. `a1b2c3.000000000007.fwsync`.
+    pub fn file_name(&self) -> String {
+        format!(
+            "{}.{:012}.{CHANGESET_EXTENSION}",
+            self.device_id, self.sequence
+        )
+    }
+
+    /// Split a change-set object name into its device id and sequence.
+    pub fn parse_file_name(name: &str) -> Option<(String, u64)> {
+        let stem = name.strip_suffix(&format!(".{CHANGESET_EXTENSION}"))?;
+        let (device_id, sequence) = stem.rsplit_once('.')?;
+        if device_id.is_empty() {
+            return None;
+        }
+        Some((device_id.to_string(), sequence.parse().ok()?))
+    }
+}
+
+/// AES-256-GCM key derived from the sync secret and one device's salt.
+#[derive(Clone)]
+pub struct SyncKey([u8; 32]);
+
+impl SyncKey {
+    /// Stretch the user's sync secret with PBKDF2 over `salt`.
+    pub fn derive(secret: &str, salt: &[u8], iterations: u32) -> Result<Self> {
+        if secret.len() < MIN_SECRET_LEN {
+            bail!("sync secret must be at least {MIN_SECRET_LEN} bytes");
+        }
+        Ok(Self(derive_key(secret, salt, iterations)?))
+    }
+
+    fn cipher(&self) -> Result<aead::LessSafeKey> {
+        let key = aead::UnboundKey::new(&aead::AES_256_GCM, &self.0)
+            .map_err(|_| anyhow!("invalid sync key material"))?;
+        Ok(aead::LessSafeKey::new(key))
+    }
+}
+
+/// Derived keys cached by salt.
+type KeyCache = HashMap<Vec<u8>, SyncKey>;
+
+/// The sync secret every device shares, with the keys derived from it so far.
+///
+/// Each device seals its change sets under its own random salt, so peers
+/// derive one key per device they hear from rather than one per change set.
+#[derive(Clone)]
+pub struct SyncKeyring {
+    secret: Arc<str>,
+    keys: Arc<Mutex<KeyCache>>,
+}
+
+impl SyncKeyring {
+    pub fn new(secret: &str) -> Result<Self> {
+        if secret.len() < MIN_SECRET_LEN {
+            bail!("sync secret must be at least {MIN_SECRET_LEN} bytes");
+        }
+        Ok(Self {
+            secret: Arc::from(secret),
+            keys: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Key for one device's salt, derived with [`PBKDF2_ITERATIONS`] rounds.
+    pub fn key(&self, salt: &[u8]) -> Result<SyncKey> {
+        if salt.len() != SYNC_SALT_LEN {
+            bail!("sync salt must be {SYNC_SALT_LEN} bytes");
+        }
+        if let Some(key) = self.lock().get(salt) {
+            return Ok(key.clone());
+        }
+        let key = SyncKey::derive(&self.secret, salt, PBKDF2_ITERATIONS)?;
+        let mut keys = self.lock();
+        if keys.len() >= MAX_CACHED_KEYS {
+            keys.clear();
+        }
+        keys.insert(salt.to_vec(), key.clone());
+        Ok(key)
+    }
+
+    #[cfg(test)]
+    fn cached_keys(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, KeyCache> {
+        self.keys
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// On-the-wire form of a change set: AES-256-GCM over the JSON body under a
+/// PBKDF2 key salted per device, with the header bound as associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedChangeSet {
+    pub version: u8,
+    pub device_id: String,
+    pub sequence: u64,
+    pub salt: String,
+    pub iterations: u32,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn associated_data(device_id: &str, sequence: u64, salt: &str, iterations: u32) -> Vec<u8> {
+    format!("{device_id}:{sequence}:{salt}:{iterations}").into_bytes()
+}
+
+impl EncryptedChangeSet {
+    pub fn seal(keyring: &SyncKeyring, salt: &[u8], changes: &ChangeSet) -> Result<Self> {
+        let iterations = PBKDF2_ITERATIONS;
+        let key = keyring.key(salt)?;
+        let salt = BASE64.encode(salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("failed to generate sync nonce"))?;
+        let mut buffer = serde_json::to_vec(changes)
+            .map_err(|err| anyhow!("failed to encode change set: {err}"))?;
+        let aad = associated_data(&changes.device_id, changes.sequence, &salt, iterations);
+        key.cipher()?
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(aad.as_slice()),
+                &mut buffer,
+            )
+            .map_err(|_| anyhow!("failed to seal change set"))?;
+        Ok(Self {
+            version: CHANGESET_VERSION,
+            device_id: changes.device_id.clone(),
+            sequence: changes.sequence,
+            salt,
+            iterations,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(buffer),
+        })
+    }
+
+    pub fn open(&self, keyring: &SyncKeyring) -> Result<ChangeSet> {
+        if self.version != CHANGESET_VERSION {
+            bail!("unsupported change set version: {}", self.version);
+        }
+        let salt = BASE64
+            .decode(self.salt.as_bytes())
+            .map_err(|err| anyhow!("failed to decode sync salt: {err}"))?;
+        // Header fields are unauthenticated until the tag is checked, so the work
+        // factor is fixed rather than taken from the file.
+        if self.iterations != PBKDF2_ITERATIONS {
+            bail!(
+                "unsupported change set key derivation ({} PBKDF2 rounds)",
+                self.iterations
+            );
+        }
+        let key = keyring.key(&salt)?;
+        let nonce: [u8; NONCE_LEN] = BASE64
+            .decode(self.nonce.as_bytes())
+            .map_err(|err| anyhow!("failed to decode sync nonce: {err}"))?
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("invalid sync nonce length"))?;
+        let mut buffer = BASE64
+            .decode(self.ciphertext.as_bytes())
+            .map_err(|err| anyhow!("failed to decode change set: {err}"))?;
+        let aad = associated_data(&self.device_id, self.sequence, &self.salt, self.iterations);
+        let plaintext = key
+            .cipher()?
+            .open_in_place(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(aad.as_slice()),
+                &mut buffer,
+            )
+            .map_err(|_| anyhow!("failed to decrypt change set (wrong sync secret?)"))?;
+        let changes: ChangeSet = serde_json::from_slice(plaintext)
+            .map_err(|err| anyhow!("failed to parse change set: {err}"))?;
+        if changes.device_id != self.device_id || changes.sequence != self.sequence {
+            bail!("change set header does not match its contents");
+        }
+        Ok(changes)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|err| anyhow!("failed to encode change set: {err}"))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|err| anyhow!("failed to parse change set: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_and_rejects_wrong_secret_or_tampered_header() {
+        let mut clock = VersionVector::default();
+        clock.bump("laptop");
+        let changes = ChangeSet {
+            device_id: "laptop".into(),
+            sequence: 3,
+            created_at_ms: 1_000,
+            changes: vec![RowChange {
+                kind: RowKind::Draft,
+                row_id: "draft-1".into(),
+                clock,
+                updated_at_ms: 1_000,
+                deleted: true,
+                payload: None,
+            }],
+        };
+        let keyring = SyncKeyring::new("correct horse battery").unwrap();
+        let sealed = EncryptedChangeSet::seal(&keyring, b"laptop salt 0001", &changes).unwrap();
+        assert_eq!(sealed.iterations, PBKDF2_ITERATIONS);
+        assert_eq!(sealed.open(&keyring).unwrap(), changes);
+
+        let other = SyncKeyring::new("wrong horse battery").unwrap();
+        assert!(sealed.open(&other).is_err());
+
+        let mut tampered = sealed.clone();
+        tampered.sequence = 4;
+        assert!(tampered.open(&keyring).is_err());
+
+        let mut resalted = sealed.clone();
+        resalted.salt = BASE64.encode(b"other salt 00001");
+        assert!(resalted.open(&keyring).is_err());
+
+        let mut expensive = sealed.clone();
+        expensive.iterations = u32::MAX;
+        assert!(expensive.open(&keyring).is_err());
+        let mut short_salt = sealed.clone();
+        short_salt.salt = BASE64.encode(b"short");
+        assert!(short_salt.open(&keyring).is_err());
+        assert!(SyncKeyring::new("short").is_err());
+
+        assert_eq!(
+            ChangeSet::parse_file_name(&changes.file_name()),
+            Some(("laptop".to_string(), 3))
+        );
+        assert_eq!(ChangeSet::parse_file_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn keyring_cache_stays_bounded() {
+        let keyring = SyncKeyring::new("correct horse battery").unwrap();
+        for idx in 0..=MAX_CACHED_KEYS as u8 {
+            keyring.key(&[idx; SYNC_SALT_LEN]).unwrap();
+        }
+        assert!(keyring.cached_keys() <= MAX_CACHED_KEYS);
+    }
+}
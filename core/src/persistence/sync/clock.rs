@@ -0,0 +1,83 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Per-row version vector: one counter per device that changed the row.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct VersionVector(BTreeMap<String, u64>);
+
+/// How two version vectors relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockOrdering {
+    Equal,
+    /// `self` happened before the other vector.
+    Before,
+    /// `self` supersedes the other vector.
+    After,
+    /// Both sides changed the row independently.
+    Concurrent,
+}
+
+impl VersionVector {
+    pub fn get(&self, device_id: &str) -> u64 {
+        self.0.get(device_id).copied().unwrap_or(0)
+    }
+
+    /// Record a local change made on `device_id`.
+    pub fn bump(&mut self, device_id: &str) {
+        *self.0.entry(device_id.to_string()).or_insert(0) += 1;
+    }
+
+    /// Pointwise maximum of both vectors.
+    pub fn merge(&mut self, other: &VersionVector) {
+        for (device, counter) in &other.0 {
+            let entry = self.0.entry(device.clone()).or_insert(0);
+            *entry = (*entry).max(*counter);
+        }
+    }
+
+    pub fn compare(&self, other: &VersionVector) -> ClockOrdering {
+        let mut ordering = Ordering::Equal;
+        for device in self.0.keys().chain(other.0.keys()) {
+            match (ordering, self.get(device).cmp(&other.get(device))) {
+                (_, Ordering::Equal) => {}
+                (Ordering::Equal, next) => ordering = next,
+                (current, next) if current != next => return ClockOrdering::Concurrent,
+                _ => {}
+            }
+        }
+        match ordering {
+            Ordering::Equal => ClockOrdering::Equal,
+            Ordering::Less => ClockOrdering::Before,
+            Ordering::Greater => ClockOrdering::After,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_and_detects_concurrent_changes() {
+        let mut base = VersionVector::default();
+        base.bump("laptop");
+
+        let mut newer = base.clone();
+        newer.bump("laptop");
+        assert_eq!(newer.compare(&base), ClockOrdering::After);
+        assert_eq!(base.compare(&newer), ClockOrdering::Before);
+        assert_eq!(base.compare(&base.clone()), ClockOrdering::Equal);
+
+        let mut desktop = base.clone();
+        desktop.bump("desktop");
+        assert_eq!(newer.compare(&desktop), ClockOrdering::Concurrent);
+
+        newer.merge(&desktop);
+        assert_eq!(newer.get("laptop"), 2);
+        assert_eq!(newer.get("desktop"), 1);
+        assert_eq!(newer.compare(&desktop), ClockOrdering::After);
+    }
+}
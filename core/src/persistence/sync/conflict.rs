@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+
+use super::{
+    content_hash, load_tracked_row, session_payload, store_tracked, ClockOrdering, RowChange,
+    RowKind, SyncEngine, SyncReport, TrackedRow,
+};
+use crate::persistence::DraftRecord;
+use crate::session::history::HistoryEntry;
+
+impl SyncEngine {
+    pub(super) fn apply(
+        &self,
+        device_id: &str,
+        change: &RowChange,
+        now_ms: i64,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let key = (change.kind, change.row_id.clone());
+        let local = {
+            let conn = self.sqlite.connection()?;
+            load_tracked_row(&conn, &key)?
+        };
+        let remote_hash = change
+            .payload
+            .as_ref()
+            .map(content_hash)
+            .unwrap_or_default();
+
+        let remote_wins = match &local {
+            None => true,
+            Some(local) => match change.clock.compare(&local.clock) {
+                ClockOrdering::After => true,
+                ClockOrdering::Before | ClockOrdering::Equal => false,
+                ClockOrdering::Concurrent => {
+                    report.conflicts += 1;
+                    // Later edit wins; the content hash breaks exact ties the
+                    // same way on every device.
+                    (change.updated_at_ms, &remote_hash)
+                        > (local.updated_at_ms, &local.content_hash)
+                }
+            },
+        };
+
+        let mut clock = local
+            .as_ref()
+            .map(|row| row.clock.clone())
+            .unwrap_or_default();
+        clock.merge(&change.clock);
+
+        let conn = self.sqlite.connection()?;
+        if !remote_wins {
+            if let Some(mut local) = local {
+                if change.clock.compare(&local.clock) == ClockOrdering::Concurrent {
+                    // Keep the local edit but make it supersede the remote one.
+                    clock.bump(device_id);
+                    local.clock = clock;
+                    local.dirty = true;
+                    store_tracked(&conn, &key, &local)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let content_hash = self.write_row(change, now_ms)?.unwrap_or(remote_hash);
+        store_tracked(
+            &conn,
+            &key,
+            &TrackedRow {
+                clock,
+                content_hash,
+                updated_at_ms: change.updated_at_ms,
+                deleted: change.deleted,
+                dirty: false,
+            },
+        )?;
+        report.applied += 1;
+        Ok(())
+    }
+
+    /// Write a winning remote row. Returns the hash of the stored row as read
+    /// back, so local normalisation is not mistaken for a new edit.
+    fn write_row(&self, change: &RowChange, now_ms: i64) -> Result<Option<String>> {
+        match (change.kind, &change.payload) {
+            (RowKind::Session, Some(payload)) if !change.deleted => {
+                let entry: HistoryEntry = serde_json::from_value(payload.clone())
+                    .context("invalid synced history entry")?;
+                self.sqlite
+                    .upsert_history_entry(&entry, change.updated_at_ms, now_ms)?;
+                self.sqlite
+                    .load_session(&entry.session_id)?
+                    .map(|stored| session_payload(&stored).map(|value| content_hash(&value)))
+                    .transpose()
+            }
+            (RowKind::Draft, Some(payload)) if !change.deleted => {
+                let draft: DraftRecord =
+                    serde_json::from_value(payload.clone()).context("invalid synced draft")?;
+                self.sqlite.upsert_draft(&draft)?;
+                self.sqlite
+                    .load_draft(&draft.draft_id)?
+                    .map(|stored| {
+                        serde_json::to_value(&stored)
+                            .map(|value| content_hash(&value))
+                            .context("failed to encode draft")
+                    })
+                    .transpose()
+            }
+            (RowKind::Session, _) => {
+                self.sqlite.delete_session(&change.row_id)?;
+                Ok(None)
+            }
+            (RowKind::Draft, _) => {
+                self.sqlite.delete_draft(&change.row_id)?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::persistence::sync::tests::{device, draft, session};
+
+    #[test]
+    fn newer_local_pin_survives_a_concurrent_remote_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("cloud");
+        let laptop = device(dir.path(), "laptop", &shared);
+        let desktop = device(dir.path(), "desktop", &shared);
+
+        laptop.sqlite.insert_session(&session("s-1")).unwrap();
+        laptop.sync_once(100).unwrap();
+        desktop.sync_once(110).unwrap();
+
+        // The desktop purges the entry after it was captured, then the laptop pins
+        // it before seeing the tombstone. The pin is the later edit, so it wins.
+        desktop.sqlite.delete_session("s-1").unwrap();
+        desktop.sync_once(5_000).unwrap();
+        laptop.sqlite.set_pinned("s-1", true, 6_000).unwrap();
+        assert_eq!(
+            laptop.sqlite.session_updated_at("s-1").unwrap(),
+            Some(6_000)
+        );
+        let report = laptop.sync_once(6_010).unwrap();
+        assert_eq!(report.conflicts, 1);
+        desktop.sync_once(6_020).unwrap();
+        for engine in [&laptop, &desktop] {
+            let stored = engine.sqlite.load_session("s-1").unwrap().unwrap();
+            assert!(stored.pinned);
+            assert_eq!(
+                engine.sqlite.session_updated_at("s-1").unwrap(),
+                Some(6_000)
+            );
+        }
+    }
+
+    #[test]
+    fn older_edit_synced_last_loses_the_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("cloud");
+        let laptop = device(dir.path(), "laptop", &shared);
+        let desktop = device(dir.path(), "desktop", &shared);
+
+        laptop.sqlite.upsert_draft(&draft("first", 10)).unwrap();
+        laptop.sync_once(100).unwrap();
+        desktop.sync_once(110).unwrap();
+
+        // The desktop edit is older but is only synced after the laptop's.
+        desktop
+            .sqlite
+            .upsert_draft(&draft("older desktop edit", 20))
+            .unwrap();
+        laptop
+            .sqlite
+            .upsert_draft(&draft("newer laptop edit", 30))
+            .unwrap();
+        laptop.sync_once(200).unwrap();
+        let report = desktop.sync_once(900).unwrap();
+        assert_eq!(report.conflicts, 1);
+        laptop.sync_once(1_000).unwrap();
+        for engine in [&laptop, &desktop] {
+            let stored = engine.sqlite.load_draft("draft-1").unwrap().unwrap();
+            assert_eq!(stored.content, "newer laptop edit");
+        }
+    }
+}
@@ -0,0 +1,721 @@
+//! 多设备历史同步：逐行维护版本向量，生成加密变更集，经云同步文件夹或 WebDAV 交换，
+//! 并发修改按时间戳裁决。
+
+pub mod changeset;
+pub mod clock;
+pub mod transport;
+
+mod conflict;
+
+pub use changeset::{ChangeSet, EncryptedChangeSet, RowChange, RowKind, SyncKey, SyncKeyring};
+pub use clock::{ClockOrdering, VersionVector};
+pub use transport::{
+    FolderTransport, SyncConfig, SyncTarget, SyncTransport, WebDavTransport, SYNC_FOLDER_ENV,
+    SYNC_SECRET_ENV, SYNC_WEBDAV_PASSWORD_ENV, SYNC_WEBDAV_URL_ENV, SYNC_WEBDAV_USER_ENV,
+};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::persistence::audit::EgressRecorder;
+use crate::persistence::sqlite::SqlitePersistence;
+use crate::session::history::HistoryEntry;
+use crate::telemetry::events::record_session_history_sync;
+
+pub(crate) const DEFAULT_SYNC_INTERVAL_SECS: u64 = 5 * 60;
+const META_DEVICE_ID: &str = "device_id";
+const META_SEQUENCE: &str = "local_sequence";
+const META_SALT: &str = "salt";
+const META_PENDING_SEEDED: &str = "pending_seeded";
+
+/// Triggers queueing every written or deleted row in `sync_pending`, so a
+/// round only reads rows changed since the previous one. Re-queueing deletes
+/// first, which moves a row edited mid-round past the rowid the round clears
+/// up to. (`INSERT OR REPLACE` would not do: an upsert's conflict clause
+/// overrides the one inside its triggers.)
+const CHANGE_TRACKING_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS sync_pending (
+        kind TEXT NOT NULL,
+        row_id TEXT NOT NULL,
+        PRIMARY KEY (kind, row_id)
+    );
+    CREATE TRIGGER IF NOT EXISTS sync_sessions_ai AFTER INSERT ON sessions BEGIN
+        DELETE FROM sync_pending WHERE kind = 'session' AND row_id = new.session_id;
+        INSERT INTO sync_pending(kind, row_id) VALUES ('session', new.session_id);
+    END;
+    CREATE TRIGGER IF NOT EXISTS sync_sessions_au AFTER UPDATE ON sessions BEGIN
+        DELETE FROM sync_pending WHERE kind = 'session' AND row_id = new.session_id;
+        INSERT INTO sync_pending(kind, row_id) VALUES ('session', new.session_id);
+    END;
+    CREATE TRIGGER IF NOT EXISTS sync_sessions_ad AFTER DELETE ON sessions BEGIN
+        DELETE FROM sync_pending WHERE kind = 'session' AND row_id = old.session_id;
+        INSERT INTO sync_pending(kind, row_id) VALUES ('session', old.session_id);
+    END;
+    CREATE TRIGGER IF NOT EXISTS sync_drafts_ai AFTER INSERT ON drafts BEGIN
+        DELETE FROM sync_pending WHERE kind = 'draft' AND row_id = new.draft_id;
+        INSERT INTO sync_pending(kind, row_id) VALUES ('draft', new.draft_id);
+    END;
+    CREATE TRIGGER IF NOT EXISTS sync_drafts_au AFTER UPDATE ON drafts BEGIN
+        DELETE FROM sync_pending WHERE kind = 'draft' AND row_id = new.draft_id;
+        INSERT INTO sync_pending(kind, row_id) VALUES ('draft', new.draft_id);
+    END;
+    CREATE TRIGGER IF NOT EXISTS sync_drafts_ad AFTER DELETE ON drafts BEGIN
+        DELETE FROM sync_pending WHERE kind = 'draft' AND row_id = old.draft_id;
+        INSERT INTO sync_pending(kind, row_id) VALUES ('draft', old.draft_id);
+    END;
+"#;
+
+/// Outcome of one [`SyncEngine::sync_once`] round.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// Local rows pushed in this round's change set.
+    pub pushed: usize,
+    /// Remote change sets downloaded.
+    pub pulled: usize,
+    /// Remote row changes written locally.
+    pub applied: usize,
+    /// Concurrent edits resolved by timestamp.
+    pub conflicts: usize,
+    /// Remote change sets that could not be decoded or decrypted and were
+    /// skipped so later ones still apply.
+    pub skipped: usize,
+}
+
+/// Sync bookkeeping for one row, stored in `sync_rows`.
+#[derive(Debug, Clone)]
+struct TrackedRow {
+    clock: VersionVector,
+    content_hash: String,
+    /// When the row itself was last edited, compared across devices to break
+    /// concurrent edits.
+    updated_at_ms: i64,
+    deleted: bool,
+    dirty: bool,
+}
+
+type RowKey = (RowKind, String);
+
+/// Reconciles history sessions and drafts with other devices.
+///
+/// Rows changed directly in SQLite are queued by triggers and confirmed by
+/// comparing content hashes, so existing write paths need no sync hooks.
+/// Drafts applied from other devices bypass the persistence actor's in-memory
+/// cache and show up there after the next restart.
+#[derive(Clone)]
+pub struct SyncEngine {
+    sqlite: Arc<SqlitePersistence>,
+    transport: Arc<dyn SyncTransport>,
+    keyring: SyncKeyring,
+    interval: Duration,
+    started: Arc<AtomicBool>,
+}
+
+impl SyncEngine {
+    pub fn new(
+        sqlite: Arc<SqlitePersistence>,
+        transport: Arc<dyn SyncTransport>,
+        keyring: SyncKeyring,
+    ) -> Self {
+        Self {
+            sqlite,
+            transport,
+            keyring,
+            interval: Duration::from_secs(DEFAULT_SYNC_INTERVAL_SECS),
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn from_config(
+        sqlite: Arc<SqlitePersistence>,
+        config: &SyncConfig,
+        egress: &EgressRecorder,
+    ) -> Result<Self> {
+        let keyring = SyncKeyring::new(&config.secret)?;
+        let mut engine = Self::new(sqlite, config.transport(egress), keyring);
+        engine.interval = config.interval;
+        Ok(engine)
+    }
+
+    /// Stable identifier of this installation, generated on first use.
+    pub fn device_id(&self) -> Result<String> {
+        let conn = self.sqlite.connection()?;
+        if let Some(existing) = meta_get(&conn, META_DEVICE_ID)? {
+            return Ok(existing);
+        }
+        let mut bytes = [0u8; 8];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| anyhow!("failed to generate sync device id"))?;
+        let device_id = hex(&bytes);
+        meta_set(&conn, META_DEVICE_ID, &device_id)?;
+        Ok(device_id)
+    }
+
+    /// Push local changes, then pull and apply every unseen remote change set.
+    /// Blocking.
+    pub fn sync_once(&self, now_ms: i64) -> Result<SyncReport> {
+        let device_id = self.device_id()?;
+        self.ensure_change_tracking()?;
+        let mut report = SyncReport::default();
+
+        self.track_local_changes(&device_id, now_ms)?;
+        report.pushed = self.push(&device_id, now_ms)?;
+        self.pull(&device_id, now_ms, &mut report)?;
+        // Rows that won a conflict locally were re-marked dirty; send them now
+        // so peers converge in the same round.
+        if report.conflicts > 0 {
+            report.pushed += self.push(&device_id, now_ms)?;
+        }
+        Ok(report)
+    }
+
+    /// Salt this device seals its change sets with, generated on first use.
+    fn local_salt(&self, conn: &Connection) -> Result<Vec<u8>> {
+        if let Some(salt) = meta_get(conn, META_SALT)?.and_then(|value| unhex(&value)) {
+            return Ok(salt);
+        }
+        let mut salt = vec![0u8; changeset::SYNC_SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| anyhow!("failed to generate sync salt"))?;
+        meta_set(conn, META_SALT, &hex(&salt))?;
+        Ok(salt)
+    }
+
+    /// Install the change-tracking triggers. The first time, queue every
+    /// existing row so history written before sync was enabled is pushed.
+    fn ensure_change_tracking(&self) -> Result<()> {
+        let mut conn = self.sqlite.connection()?;
+        let tx = conn.transaction()?;
+        tx.execute_batch(CHANGE_TRACKING_SQL)
+            .context("failed to install sync change tracking")?;
+        if meta_get(&tx, META_PENDING_SEEDED)?.is_none() {
+            tx.execute_batch(
+                "INSERT OR REPLACE INTO sync_pending(kind, row_id)
+                    SELECT 'session', session_id FROM sessions;
+                 INSERT OR REPLACE INTO sync_pending(kind, row_id)
+                    SELECT 'draft', draft_id FROM drafts;",
+            )
+            .context("failed to queue existing rows for sync")?;
+            meta_set(&tx, META_PENDING_SEEDED, "1")?;
+        }
+        tx.commit()
+            .context("failed to commit sync change tracking")?;
+        Ok(())
+    }
+
+    /// Start the periodic sync loop; later calls are no-ops.
+    pub fn spawn(&self) -> Option<JoinHandle<()>> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let engine = self.clone();
+        Some(tokio::spawn(async move {
+            loop {
+                let worker = engine.clone();
+                let result = tokio::task::spawn_blocking(move || worker.sync_once(now_ms()))
+                    .await
+                    .map_err(|err| anyhow!("history sync task failed: {err}"))
+                    .and_then(|result| result);
+                match result {
+                    Ok(report) => record_session_history_sync(
+                        report.pushed,
+                        report.pulled,
+                        report.applied,
+                        report.conflicts,
+                        report.skipped,
+                        None,
+                    ),
+                    Err(err) => record_session_history_sync(0, 0, 0, 0, 0, Some(&err)),
+                }
+                tokio::time::sleep(engine.interval).await;
+            }
+        }))
+    }
+
+    /// Current payload of a synced row and when it was last edited, or
+    /// `None` once the row is gone.
+    fn load_row(&self, key: &RowKey) -> Result<Option<(JsonValue, i64)>> {
+        match key.0 {
+            RowKind::Session => {
+                let Some(entry) = self.sqlite.load_session(&key.1)? else {
+                    return Ok(None);
+                };
+                let edited_at_ms = self
+                    .sqlite
+                    .session_updated_at(&key.1)?
+                    .unwrap_or(entry.completed_at_ms);
+                Ok(Some((session_payload(&entry)?, edited_at_ms)))
+            }
+            RowKind::Draft => self
+                .sqlite
+                .load_draft(&key.1)?
+                .map(|draft| {
+                    let value = serde_json::to_value(&draft).context("failed to encode draft")?;
+                    Ok((value, draft.updated_at_ms as i64))
+                })
+                .transpose(),
+        }
+    }
+
+    /// Bump the local clock of every queued row whose content changed or
+    /// disappeared since the last round and mark it for the next push.
+    fn track_local_changes(&self, device_id: &str, now_ms: i64) -> Result<()> {
+        let (pending, cursor) = {
+            let conn = self.sqlite.connection()?;
+            load_pending(&conn)?
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut current = Vec::with_capacity(pending.len());
+        for key in pending {
+            let row = self.load_row(&key)?;
+            current.push((key, row));
+        }
+
+        let mut conn = self.sqlite.connection()?;
+        let tx = conn.transaction()?;
+        for (key, row) in current {
+            let previous = load_tracked_row(&tx, &key)?;
+            let Some((value, edited_at_ms)) = row else {
+                // Gone locally: tombstone it if peers know about it.
+                if let Some(mut previous) = previous.filter(|row| !row.deleted) {
+                    previous.clock.bump(device_id);
+                    previous.content_hash.clear();
+                    previous.updated_at_ms = now_ms;
+                    previous.deleted = true;
+                    previous.dirty = true;
+                    store_tracked(&tx, &key, &previous)?;
+                }
+                continue;
+            };
+            let hash = content_hash(&value);
+            let unchanged = previous
+                .as_ref()
+                .is_some_and(|row| !row.deleted && row.content_hash == hash);
+            if unchanged {
+                continue;
+            }
+            let mut clock = previous.map(|row| row.clock).unwrap_or_default();
+            clock.bump(device_id);
+            store_tracked(
+                &tx,
+                &key,
+                &TrackedRow {
+                    clock,
+                    content_hash: hash,
+                    updated_at_ms: edited_at_ms,
+                    deleted: false,
+                    dirty: true,
+                },
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM sync_pending WHERE rowid <= ?1",
+            params![cursor],
+        )
+        .context("failed to clear queued sync rows")?;
+        tx.commit()
+            .context("failed to commit sync change tracking")?;
+        Ok(())
+    }
+
+    fn push(&self, device_id: &str, now_ms: i64) -> Result<usize> {
+        let conn = self.sqlite.connection()?;
+        let dirty = load_dirty(&conn)?;
+        let mut changes = Vec::with_capacity(dirty.len());
+        let mut sent = Vec::with_capacity(dirty.len());
+        for ((kind, row_id), row) in dirty {
+            let key = (kind, row_id);
+            let payload = if row.deleted {
+                None
+            } else {
+                // A row deleted since it was tracked goes out as a tombstone
+                // next round instead.
+                match self.load_row(&key)? {
+                    Some((value, _)) => Some(value),
+                    None => continue,
+                }
+            };
+            changes.push(RowChange {
+                kind: key.0,
+                row_id: key.1.clone(),
+                clock: row.clock,
+                updated_at_ms: row.updated_at_ms,
+                deleted: row.deleted,
+                payload,
+            });
+            sent.push(key);
+        }
+        if changes.is_empty() {
+            return Ok(0);
+        }
+
+        let sequence = meta_get(&conn, META_SEQUENCE)?
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(0)
+            + 1;
+        let change_set = ChangeSet {
+            device_id: device_id.to_string(),
+            sequence,
+            created_at_ms: now_ms,
+            changes,
+        };
+        let salt = self.local_salt(&conn)?;
+        let sealed = EncryptedChangeSet::seal(&self.keyring, &salt, &change_set)?;
+        self.transport
+            .put(&change_set.file_name(), &sealed.to_bytes()?)?;
+
+        meta_set(&conn, META_SEQUENCE, &sequence.to_string())?;
+        for (kind, row_id) in &sent {
+            conn.execute(
+                "UPDATE sync_rows SET dirty = 0 WHERE kind = ?1 AND row_id = ?2",
+                params![kind.as_str(), row_id],
+            )?;
+        }
+        Ok(sent.len())
+    }
+
+    fn pull(&self, device_id: &str, now_ms: i64, report: &mut SyncReport) -> Result<()> {
+        let mut pending: Vec<(String, u64, String)> = self
+            .transport
+            .list()?
+            .into_iter()
+            .filter_map(|name| {
+                let (device, sequence) = ChangeSet::parse_file_name(&name)?;
+                (device != device_id).then_some((device, sequence, name))
+            })
+            .collect();
+        pending.sort();
+
+        for (device, sequence, name) in pending {
+            let conn = self.sqlite.connection()?;
+            let cursor_key = peer_cursor_key(&device);
+            let seen = meta_get(&conn, &cursor_key)?
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(0);
+            if sequence <= seen {
+                continue;
+            }
+            drop(conn);
+
+            // Transport failures abort the round and are retried; a change set
+            // that downloads but cannot be read never will be, so skip it.
+            let bytes = self.transport.get(&name)?;
+            let opened = EncryptedChangeSet::from_bytes(&bytes)
+                .and_then(|sealed| sealed.open(&self.keyring));
+            let change_set = match opened {
+                Ok(change_set) => change_set,
+                Err(err) => {
+                    warn!(
+                        target: "persistence::sync",
+                        change_set = %name,
+                        error = %err,
+                        "skipping unreadable change set"
+                    );
+                    report.skipped += 1;
+                    let conn = self.sqlite.connection()?;
+                    meta_set(&conn, &cursor_key, &sequence.to_string())?;
+                    continue;
+                }
+            };
+            report.pulled += 1;
+            for change in &change_set.changes {
+                self.apply(device_id, change, now_ms, report)?;
+            }
+            let conn = self.sqlite.connection()?;
+            meta_set(&conn, &cursor_key, &sequence.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+fn session_payload(entry: &HistoryEntry) -> Result<JsonValue> {
+    serde_json::to_value(entry).context("failed to encode history entry")
+}
+
+fn content_hash(value: &JsonValue) -> String {
+    hex(digest::digest(&digest::SHA256, value.to_string().as_bytes()).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn peer_cursor_key(device_id: &str) -> String {
+    format!("peer:{device_id}")
+}
+
+fn meta_get(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM sync_meta WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("failed to read sync metadata")
+}
+
+fn meta_set(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO sync_meta(key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .context("failed to write sync metadata")?;
+    Ok(())
+}
+
+fn read_tracked(row: &rusqlite::Row) -> rusqlite::Result<(String, String, TrackedRow)> {
+    let clock: String = row.get(2)?;
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        TrackedRow {
+            clock: serde_json::from_str(&clock).unwrap_or_default(),
+            content_hash: row.get(3)?,
+            updated_at_ms: row.get(4)?,
+            deleted: row.get(5)?,
+            dirty: row.get(6)?,
+        },
+    ))
+}
+
+const TRACKED_COLUMNS: &str = "kind, row_id, clock, content_hash, updated_at_ms, deleted, dirty";
+
+/// Rows queued by the change-tracking triggers, with the highest rowid read.
+fn load_pending(conn: &Connection) -> Result<(Vec<RowKey>, i64)> {
+    let mut stmt = conn.prepare("SELECT rowid, kind, row_id FROM sync_pending ORDER BY rowid")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut pending = Vec::new();
+    let mut cursor = 0;
+    for row in rows {
+        let (rowid, kind, row_id) = row?;
+        cursor = cursor.max(rowid);
+        if let Some(kind) = RowKind::parse(&kind) {
+            pending.push((kind, row_id));
+        }
+    }
+    Ok((pending, cursor))
+}
+
+fn load_dirty(conn: &Connection) -> Result<HashMap<RowKey, TrackedRow>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {TRACKED_COLUMNS} FROM sync_rows WHERE dirty = 1"
+    ))?;
+    let rows = stmt.query_map([], read_tracked)?;
+    let mut tracked = HashMap::new();
+    for row in rows {
+        let (kind, row_id, row) = row?;
+        if let Some(kind) = RowKind::parse(&kind) {
+            tracked.insert((kind, row_id), row);
+        }
+    }
+    Ok(tracked)
+}
+
+fn load_tracked_row(conn: &Connection, key: &RowKey) -> Result<Option<TrackedRow>> {
+    conn.query_row(
+        &format!("SELECT {TRACKED_COLUMNS} FROM sync_rows WHERE kind = ?1 AND row_id = ?2"),
+        params![key.0.as_str(), key.1],
+        read_tracked,
+    )
+    .optional()
+    .map(|row| row.map(|(_, _, row)| row))
+    .context("failed to read sync row state")
+}
+
+fn store_tracked(conn: &Connection, key: &RowKey, row: &TrackedRow) -> Result<()> {
+    let clock = serde_json::to_string(&row.clock).context("failed to encode version vector")?;
+    conn.execute(
+        "INSERT INTO sync_rows(kind, row_id, clock, content_hash, updated_at_ms, deleted, dirty)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(kind, row_id) DO UPDATE SET
+            clock = excluded.clock,
+            content_hash = excluded.content_hash,
+            updated_at_ms = excluded.updated_at_ms,
+            deleted = excluded.deleted,
+            dirty = excluded.dirty",
+        params![
+            key.0.as_str(),
+            key.1,
+            clock,
+            row.content_hash,
+            row.updated_at_ms,
+            row.deleted,
+            row.dirty,
+        ],
+    )
+    .context("failed to write sync row state")?;
+    Ok(())
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::{SqliteConfig, SqlitePath};
+    use crate::persistence::DraftRecord;
+    use crate::session::history::SessionSnapshot;
+
+    pub(super) fn device(
+        dir: &std::path::Path,
+        name: &str,
+        shared: &std::path::Path,
+    ) -> SyncEngine {
+        let config = SqliteConfig {
+            path: SqlitePath::File(dir.join(format!("{name}.db"))),
+            ..SqliteConfig::memory()
+        };
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(config).unwrap());
+        SyncEngine::new(
+            sqlite,
+            Arc::new(FolderTransport::new(shared)),
+            SyncKeyring::new("shared sync secret").unwrap(),
+        )
+    }
+
+    pub(super) fn draft(content: &str, updated_at_ms: u128) -> DraftRecord {
+        DraftRecord {
+            draft_id: "draft-1".into(),
+            session_id: "s-1".into(),
+            title: "Notes".into(),
+            tags: vec!["transcript".into()],
+            content: content.into(),
+            created_at_ms: 1,
+            updated_at_ms,
+        }
+    }
+
+    pub(super) fn session(session_id: &str) -> SessionSnapshot {
+        SessionSnapshot {
+            session_id: session_id.into(),
+            started_at_ms: 1_000,
+            completed_at_ms: 2_000,
+            locale: Some("en-US".into()),
+            app_identifier: None,
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: "hello sync".into(),
+            polished_transcript: "Hello, sync.".into(),
+            metadata: JsonValue::Null,
+            post_actions: Vec::new(),
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn converges_two_devices_and_resolves_conflicts_by_timestamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("cloud");
+        let laptop = device(dir.path(), "laptop", &shared);
+        let desktop = device(dir.path(), "desktop", &shared);
+
+        laptop.sqlite.insert_session(&session("s-1")).unwrap();
+        laptop.sqlite.upsert_draft(&draft("first", 10)).unwrap();
+
+        let report = laptop.sync_once(100).unwrap();
+        assert_eq!(report.pushed, 2);
+        let report = desktop.sync_once(110).unwrap();
+        assert_eq!((report.pulled, report.applied), (1, 2));
+        assert!(desktop.sqlite.load_session("s-1").unwrap().is_some());
+
+        // Nothing changed, so nothing is echoed back.
+        assert_eq!(desktop.sync_once(120).unwrap().pushed, 0);
+        assert_eq!(laptop.sync_once(130).unwrap(), SyncReport::default());
+
+        // Both devices edit the draft; the later edit wins everywhere.
+        laptop
+            .sqlite
+            .upsert_draft(&draft("laptop edit", 20))
+            .unwrap();
+        desktop
+            .sqlite
+            .upsert_draft(&draft("desktop edit", 30))
+            .unwrap();
+        laptop.sync_once(200).unwrap();
+        let report = desktop.sync_once(300).unwrap();
+        assert_eq!(report.conflicts, 1);
+        laptop.sync_once(400).unwrap();
+        for engine in [&laptop, &desktop] {
+            let stored = engine.sqlite.load_draft("draft-1").unwrap().unwrap();
+            assert_eq!(stored.content, "desktop edit");
+        }
+
+        // Deletions propagate as tombstones.
+        desktop.sqlite.delete_session("s-1").unwrap();
+        desktop.sync_once(500).unwrap();
+        laptop.sync_once(600).unwrap();
+        assert!(laptop.sqlite.load_session("s-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn skips_unreadable_change_sets_and_keeps_syncing() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("cloud");
+        let laptop = device(dir.path(), "laptop", &shared);
+        let desktop = device(dir.path(), "desktop", &shared);
+
+        laptop.sqlite.upsert_draft(&draft("first", 10)).unwrap();
+        laptop.sync_once(100).unwrap();
+        // Change sets this device cannot read: sealed under another secret,
+        // and not a change set at all.
+        let stranger = SyncKeyring::new("another sync secret").unwrap();
+        let bogus = ChangeSet {
+            device_id: "0badc0de".into(),
+            sequence: 1,
+            created_at_ms: 50,
+            changes: Vec::new(),
+        };
+        let sealed = EncryptedChangeSet::seal(&stranger, b"stranger salt 01", &bogus).unwrap();
+        std::fs::write(shared.join(bogus.file_name()), sealed.to_bytes().unwrap()).unwrap();
+        std::fs::write(shared.join("deadbeef.000000000001.fwsync"), b"not json").unwrap();
+
+        let report = desktop.sync_once(110).unwrap();
+        assert_eq!((report.skipped, report.pulled), (2, 1));
+        assert!(desktop.sqlite.load_draft("draft-1").unwrap().is_some());
+        // Cursors moved past the bad change sets, so they are not retried.
+        assert_eq!(desktop.sync_once(120).unwrap().skipped, 0);
+    }
+}
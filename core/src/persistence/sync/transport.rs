@@ -0,0 +1,279 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use tracing::warn;
+
+use super::changeset::CHANGESET_EXTENSION;
+use super::DEFAULT_SYNC_INTERVAL_SECS;
+use crate::persistence::audit::{EgressChannel, EgressRecorder};
+
+pub const SYNC_FOLDER_ENV: &str = "FLOWWISPER_SYNC_FOLDER";
+pub const SYNC_WEBDAV_URL_ENV: &str = "FLOWWISPER_SYNC_WEBDAV_URL";
+pub const SYNC_WEBDAV_USER_ENV: &str = "FLOWWISPER_SYNC_WEBDAV_USER";
+pub const SYNC_WEBDAV_PASSWORD_ENV: &str = "FLOWWISPER_SYNC_WEBDAV_PASSWORD";
+pub const SYNC_SECRET_ENV: &str = "FLOWWISPER_SYNC_SECRET";
+
+/// Where change sets are exchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncTarget {
+    Folder(PathBuf),
+    WebDav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncConfig {
+    pub target: SyncTarget,
+    /// Shared secret every device uses to derive the change-set key.
+    pub secret: String,
+    pub interval: Duration,
+}
+
+impl SyncConfig {
+    /// 读取同步目标与密钥；未配置目标或密钥时返回 `None`，同步保持关闭。
+    pub fn from_env() -> Option<Self> {
+        let read = |key: &str| {
+            env::var(key)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let target = match (read(SYNC_FOLDER_ENV), read(SYNC_WEBDAV_URL_ENV)) {
+            (Some(folder), _) => SyncTarget::Folder(PathBuf::from(folder)),
+            (None, Some(url)) => SyncTarget::WebDav {
+                url,
+                username: read(SYNC_WEBDAV_USER_ENV),
+                password: env::var(SYNC_WEBDAV_PASSWORD_ENV).ok(),
+            },
+            (None, None) => return None,
+        };
+        let Some(secret) = read(SYNC_SECRET_ENV) else {
+            warn!(
+                target: "persistence::sync",
+                "sync target configured without {SYNC_SECRET_ENV}; history sync disabled"
+            );
+            return None;
+        };
+        Some(Self {
+            target,
+            secret,
+            interval: Duration::from_secs(DEFAULT_SYNC_INTERVAL_SECS),
+        })
+    }
+
+    /// Transport for the configured target; uploads leaving the device are recorded
+    /// through `egress`.
+    pub fn transport(&self, egress: &EgressRecorder) -> Arc<dyn SyncTransport> {
+        match &self.target {
+            SyncTarget::Folder(path) => Arc::new(FolderTransport::new(path.clone())),
+            SyncTarget::WebDav {
+                url,
+                username,
+                password,
+            } => Arc::new(
+                WebDavTransport::new(url, username.as_deref(), password.as_deref())
+                    .with_egress(egress.clone()),
+            ),
+        }
+    }
+}
+
+/// Object store holding change sets. Implementations only need flat
+/// list/get/put semantics; names are unique per device and sequence, so
+/// objects are never overwritten.
+pub trait SyncTransport: Send + Sync {
+    /// Names of every change-set object on the target.
+    fn list(&self) -> Result<Vec<String>>;
+    fn get(&self, name: &str) -> Result<Vec<u8>>;
+    fn put(&self, name: &str, bytes: &[u8]) -> Result<()>;
+}
+
+fn is_changeset(name: &str) -> bool {
+    name.ends_with(&format!(".{CHANGESET_EXTENSION}"))
+}
+
+/// A folder the user already syncs with a cloud drive (iCloud, Dropbox, OneDrive).
+pub struct FolderTransport {
+    root: PathBuf,
+}
+
+impl FolderTransport {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl SyncTransport for FolderTransport {
+    fn list(&self) -> Result<Vec<String>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("failed to list sync folder {}", self.root.display()))?
+        {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if is_changeset(&name) {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>> {
+        fs::read(self.root.join(name)).with_context(|| format!("failed to read change set {name}"))
+    }
+
+    fn put(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("failed to create sync folder {}", self.root.display()))?;
+        // Write beside the target and rename so cloud clients never pick up a
+        // partially written change set.
+        let partial = self.root.join(format!("{name}.partial"));
+        fs::write(&partial, bytes).with_context(|| format!("failed to write change set {name}"))?;
+        fs::rename(&partial, self.root.join(name))
+            .with_context(|| format!("failed to publish change set {name}"))?;
+        Ok(())
+    }
+}
+
+/// A WebDAV collection (Nextcloud, ownCloud, NAS shares).
+pub struct WebDavTransport {
+    base_url: String,
+    authorization: Option<String>,
+    timeout: Duration,
+    egress: EgressRecorder,
+}
+
+impl WebDavTransport {
+    pub fn new(base_url: &str, username: Option<&str>, password: Option<&str>) -> Self {
+        let authorization = username.map(|user| {
+            let credentials = format!("{user}:{}", password.unwrap_or_default());
+            format!("Basic {}", BASE64.encode(credentials))
+        });
+        Self {
+            base_url: format!("{}/", base_url.trim_end_matches('/')),
+            authorization,
+            timeout: Duration::from_secs(30),
+            egress: EgressRecorder::default(),
+        }
+    }
+
+    /// Records every uploaded change set in the egress audit log.
+    pub fn with_egress(mut self, egress: EgressRecorder) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    fn request(&self, method: &str, name: &str) -> ureq::Request {
+        let request =
+            ureq::request(method, &format!("{}{name}", self.base_url)).timeout(self.timeout);
+        match &self.authorization {
+            Some(value) => request.set("Authorization", value),
+            None => request,
+        }
+    }
+
+    /// Pull the final path segment out of every `<href>` in a PROPFIND reply.
+    fn parse_listing(body: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        let lower = body.to_ascii_lowercase();
+        let mut cursor = 0;
+        while let Some(start) = lower[cursor..].find("href>") {
+            let value_start = cursor + start + "href>".len();
+            let Some(end) = lower[value_start..].find("</") else {
+                break;
+            };
+            let href = body[value_start..value_start + end].trim();
+            if let Some(name) = href.trim_end_matches('/').rsplit('/').next() {
+                if is_changeset(name) {
+                    names.push(name.to_string());
+                }
+            }
+            cursor = value_start + end;
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl SyncTransport for WebDavTransport {
+    fn list(&self) -> Result<Vec<String>> {
+        let response = self
+            .request("PROPFIND", "")
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml")
+            .send_string(
+                r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/></d:prop></d:propfind>"#,
+            );
+        let body = match response {
+            Ok(response) => response
+                .into_string()
+                .map_err(|err| anyhow!("failed to read WebDAV listing: {err}"))?,
+            // The collection has not been created yet.
+            Err(ureq::Error::Status(404, _)) => return Ok(Vec::new()),
+            Err(err) => return Err(anyhow!("WebDAV listing failed: {err}")),
+        };
+        Ok(Self::parse_listing(&body))
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>> {
+        let response = self
+            .request("GET", name)
+            .call()
+            .map_err(|err| anyhow!("WebDAV download of {name} failed: {err}"))?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(|err| anyhow!("failed to read WebDAV object {name}: {err}"))?;
+        Ok(bytes)
+    }
+
+    fn put(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        self.egress.record(
+            EgressChannel::HistorySync,
+            &format!("{}{name}", self.base_url),
+            bytes,
+        );
+        self.request("PUT", name)
+            .set("Content-Type", "application/octet-stream")
+            .send_bytes(bytes)
+            .map_err(|err| anyhow!("WebDAV upload of {name} failed: {err}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_propfind_listing() {
+        let body = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:">
+              <d:response><d:href>/dav/flowwisper/</d:href></d:response>
+              <d:response><d:href>/dav/flowwisper/abc.000000000001.fwsync</d:href></d:response>
+              <D:response><D:href>/dav/flowwisper/def.000000000002.fwsync</D:href></D:response>
+              <d:response><d:href>/dav/flowwisper/readme.txt</d:href></d:response>
+            </d:multistatus>"#;
+        assert_eq!(
+            WebDavTransport::parse_listing(body),
+            vec![
+                "abc.000000000001.fwsync".to_string(),
+                "def.000000000002.fwsync".to_string()
+            ]
+        );
+    }
+}
@@ -258,6 +258,7 @@ fn search_applies_keyword_and_filters() {
         app_identifier: Some("com.example.filtered".into()),
         limit: 10,
         offset: 0,
+        pinned_only: false,
     };
 
     let page = persistence.search_sessions(&query).expect("search succeeds");
@@ -0,0 +1,120 @@
+//! 把插件能力接入现有扩展点：润色器、发布器与历史后续动作。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::process::PluginProcess;
+use crate::orchestrator::SentencePolisher;
+use crate::session::history::actions::ActionPlugin;
+use crate::session::history::HistoryEntry;
+use crate::session::publisher::{
+    PublishOutcome, PublishRequest, PublishStrategy, PublisherError, PublisherFailure,
+    PublisherFailureCode, SessionPublisher,
+};
+
+/// `polisher/polish {sentence}` → `{text}`。
+#[derive(Debug, Clone)]
+pub struct PluginPolisher {
+    process: Arc<PluginProcess>,
+}
+
+impl PluginPolisher {
+    pub fn new(process: Arc<PluginProcess>) -> Self {
+        Self { process }
+    }
+}
+
+#[async_trait]
+impl SentencePolisher for PluginPolisher {
+    async fn polish(&self, sentence: &str) -> Result<String> {
+        let result = self
+            .process
+            .call("polisher/polish", json!({ "sentence": sentence }))
+            .await?;
+        Ok(result
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or(sentence)
+            .to_string())
+    }
+}
+
+/// `publisher/publish {transcript, html, app, windowTitle}` → `{status, message}`。
+///
+/// 插件失败或返回 `failed` 时产出失败结果，会话据此走既有的剪贴板降级。
+#[derive(Debug, Clone)]
+pub struct PluginPublisher {
+    process: Arc<PluginProcess>,
+}
+
+impl PluginPublisher {
+    pub fn new(process: Arc<PluginProcess>) -> Self {
+        Self { process }
+    }
+}
+
+#[async_trait]
+impl SessionPublisher for PluginPublisher {
+    async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
+        request.validate()?;
+        let params = json!({
+            "transcript": request.transcript,
+            "html": request.html,
+            "app": request.focus.app_identifier,
+            "windowTitle": request.focus.window_title,
+        });
+        let failed = |message: String| {
+            PublishOutcome::failed(
+                1,
+                PublishStrategy::DirectInsert,
+                None,
+                PublisherFailure::new(PublisherFailureCode::Unknown, message),
+            )
+        };
+        match self.process.call("publisher/publish", params).await {
+            Ok(result) => match result.get("status").and_then(Value::as_str) {
+                Some("failed") => Ok(failed(
+                    result
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("plugin rejected the transcript")
+                        .to_string(),
+                )),
+                _ => Ok(PublishOutcome::completed()),
+            },
+            Err(err) => Ok(failed(err.to_string())),
+        }
+    }
+}
+
+/// 以插件 id 注册的历史动作：`action/execute {entry, params}`，结果原样保存。
+#[derive(Debug, Clone)]
+pub struct PluginAction {
+    process: Arc<PluginProcess>,
+}
+
+impl PluginAction {
+    pub fn new(process: Arc<PluginProcess>) -> Self {
+        Self { process }
+    }
+}
+
+#[async_trait]
+impl ActionPlugin for PluginAction {
+    fn id(&self) -> &str {
+        &self.process.manifest().id
+    }
+
+    async fn execute(&self, entry: &HistoryEntry, params: &Value) -> Result<Value> {
+        Ok(self
+            .process
+            .call(
+                "action/execute",
+                json!({ "entry": entry, "params": params }),
+            )
+            .await?)
+    }
+}
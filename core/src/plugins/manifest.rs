@@ -0,0 +1,138 @@
+//! 插件清单：每个插件目录下的 `plugin.json` 声明可执行文件、能力与沙箱范围。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+
+/// 插件可以提供的扩展点。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginCapability {
+    /// 实现 `polisher/polish`，替代内置润色器。
+    Polisher,
+    /// 实现 `publisher/publish`，把润色稿送往自定义目标。
+    Publisher,
+    /// 实现 `action/execute`，作为历史记录的后续动作。
+    PostAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    /// 可执行文件；相对路径按插件目录解析，裸命令名按 `PATH` 查找。
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub capabilities: Vec<PluginCapability>,
+    /// 单次调用的超时，超时后进程会被终止并在下次调用时重启。
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 除插件自身数据目录外，允许通过 `fs/*` 访问的目录；不限制进程的直接文件访问。
+    #[serde(default)]
+    pub allowed_paths: Vec<PathBuf>,
+    /// 清单所在目录，加载时填充。
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_TIMEOUT_MS
+}
+
+impl PluginManifest {
+    /// 读取 `dir/plugin.json` 并校验。
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read plugin manifest {}", path.display()))?;
+        let mut manifest: PluginManifest = serde_json::from_str(&raw)
+            .with_context(|| format!("invalid plugin manifest {}", path.display()))?;
+        manifest.dir = dir.to_path_buf();
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let id_ok = !self.id.is_empty()
+            && self
+                .id
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+            && !self.id.starts_with('.');
+        if !id_ok {
+            return Err(anyhow!("invalid plugin id {:?}", self.id));
+        }
+        if self.command.trim().is_empty() {
+            return Err(anyhow!("plugin {} does not declare a command", self.id));
+        }
+        if self.capabilities.is_empty() {
+            return Err(anyhow!(
+                "plugin {} does not declare any capability",
+                self.id
+            ));
+        }
+        if self.timeout_ms == 0 {
+            return Err(anyhow!("plugin {} timeout must be positive", self.id));
+        }
+        Ok(())
+    }
+
+    pub fn has_capability(&self, capability: PluginCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+
+    /// 解析实际启动的可执行文件路径。
+    pub fn executable(&self) -> PathBuf {
+        let command = Path::new(&self.command);
+        if command.is_absolute() || command.components().count() == 1 {
+            command.to_path_buf()
+        } else {
+            self.dir.join(command)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_and_validates_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            r#"{"id":"md-table","name":"Tables","command":"./bin/run","capabilities":["polisher","post_action"]}"#,
+        )
+        .unwrap();
+        let manifest = PluginManifest::load(dir.path()).unwrap();
+        assert_eq!(
+            manifest.timeout(),
+            Duration::from_millis(DEFAULT_TIMEOUT_MS)
+        );
+        assert!(manifest.has_capability(PluginCapability::PostAction));
+        assert!(!manifest.has_capability(PluginCapability::Publisher));
+        assert_eq!(manifest.executable(), dir.path().join("bin/run"));
+
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            r#"{"id":"../escape","name":"Bad","command":"run","capabilities":["polisher"]}"#,
+        )
+        .unwrap();
+        assert!(PluginManifest::load(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! 第三方插件：按清单启动外部可执行文件，通过标准输入输出上的 JSON-RPC 提供
+//! 自定义润色器、发布器与历史后续动作。
+//!
+//! 插件目录结构为 `<plugins>/<id>/plugin.json`，运行数据位于 `<data>/<id>`。
+//! 宿主的 `fs/*` 方法只允许访问数据目录与清单声明的目录。
+//!
+//! 这不是操作系统级隔离：插件进程以当前用户身份运行，只清空了环境变量并以数据
+//! 目录为工作目录，仍可直接读写该用户可访问的任何文件、访问网络或启动其他进程。
+//! 只应安装可信的插件。
+
+mod adapters;
+mod manifest;
+mod process;
+mod rpc;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use tracing::warn;
+
+pub use adapters::{PluginAction, PluginPolisher, PluginPublisher};
+pub use manifest::{PluginCapability, PluginManifest, MANIFEST_FILE};
+pub use process::{PluginProcess, Sandbox};
+pub use rpc::{HostHandler, RpcClient};
+
+use crate::orchestrator::SentencePolisher;
+use crate::session::history::actions::ActionRegistry;
+use crate::session::publisher::SessionPublisher;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PluginError {
+    #[error("plugin call {method} timed out after {timeout_ms}ms")]
+    Timeout { method: String, timeout_ms: u64 },
+    #[error("plugin returned error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("plugin connection closed")]
+    Closed,
+    #[error("plugin {0} crashed too many times")]
+    Crashed(String),
+    #[error("plugin io error: {0}")]
+    Io(String),
+    #[error("sandbox violation: {0}")]
+    Sandbox(String),
+}
+
+/// 已加载插件的注册表，克隆之间共享。
+#[derive(Debug, Clone)]
+pub struct PluginHost {
+    data_root: PathBuf,
+    plugins: Arc<RwLock<HashMap<String, Arc<PluginProcess>>>>,
+}
+
+impl PluginHost {
+    /// `data_root` 下为每个插件分配独立的数据目录。
+    pub fn new(data_root: PathBuf) -> Self {
+        Self {
+            data_root,
+            plugins: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 加载 `dir` 下每个子目录中的清单，无效清单记录告警后跳过；返回成功加载的插件 id。
+    pub fn load_dir(&self, dir: &Path) -> Result<Vec<String>> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut loaded = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.join(MANIFEST_FILE).is_file() {
+                continue;
+            }
+            match PluginManifest::load(&path).and_then(|manifest| self.register(manifest)) {
+                Ok(id) => loaded.push(id),
+                Err(err) => {
+                    warn!(target: "plugins", %err, path = %path.display(), "skipping plugin")
+                }
+            }
+        }
+        loaded.sort();
+        Ok(loaded)
+    }
+
+    /// 注册插件，同 id 的旧插件会被替换；进程在首次调用时才启动。
+    pub fn register(&self, manifest: PluginManifest) -> Result<String> {
+        manifest.validate()?;
+        let id = manifest.id.clone();
+        let process = Arc::new(PluginProcess::new(manifest, self.data_root.join(&id)));
+        self.plugins
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(id.clone(), process);
+        Ok(id)
+    }
+
+    pub fn manifests(&self) -> Vec<PluginManifest> {
+        let mut manifests: Vec<_> = self
+            .plugins
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .map(|process| process.manifest().clone())
+            .collect();
+        manifests.sort_by(|a, b| a.id.cmp(&b.id));
+        manifests
+    }
+
+    fn process(&self, id: &str, capability: PluginCapability) -> Result<Arc<PluginProcess>> {
+        let process = self
+            .plugins
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(id)
+            .cloned()
+            .ok_or_else(|| anyhow!("plugin {id} is not loaded"))?;
+        if !process.manifest().has_capability(capability) {
+            return Err(anyhow!("plugin {id} does not provide {capability:?}"));
+        }
+        Ok(process)
+    }
+
+    pub fn polisher(&self, id: &str) -> Result<Arc<dyn SentencePolisher>> {
+        Ok(Arc::new(PluginPolisher::new(
+            self.process(id, PluginCapability::Polisher)?,
+        )))
+    }
+
+    pub fn publisher(&self, id: &str) -> Result<Arc<dyn SessionPublisher>> {
+        Ok(Arc::new(PluginPublisher::new(
+            self.process(id, PluginCapability::Publisher)?,
+        )))
+    }
+
+    /// 把声明了 `post_action` 的插件注册为历史动作，返回注册数量。
+    pub fn register_actions(&self, registry: &ActionRegistry) -> usize {
+        let plugins = self
+            .plugins
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut count = 0;
+        for process in plugins.values() {
+            if process
+                .manifest()
+                .has_capability(PluginCapability::PostAction)
+            {
+                registry.register(Arc::new(PluginAction::new(process.clone())));
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 停止全部插件进程。
+    pub async fn shutdown(&self) {
+        let processes: Vec<_> = self
+            .plugins
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        for process in processes {
+            process.stop().await;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::session::history::HistoryEntry;
+
+    /// 按行读取请求，根据方法名回写固定结果；`id` 从请求中截取。
+    const SCRIPT: &str = r#"#!/bin/sh
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{}}\n' "$id" ;;
+    *'"method":"polisher/polish"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"text":"polished"}}\n' "$id" ;;
+    *'"method":"publisher/publish"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"status":"failed","message":"offline"}}\n' "$id" ;;
+    *'"method":"action/execute"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"ok":true}}\n' "$id" ;;
+    *) exit 1 ;;
+  esac
+done
+"#;
+
+    #[tokio::test]
+    async fn launches_plugins_and_restarts_after_crash() {
+        let plugins = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        let dir = plugins.path().join("echo");
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("run.sh");
+        fs::write(&script, SCRIPT).unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            r#"{"id":"echo","name":"Echo","command":"./run.sh","capabilities":["polisher","publisher","post_action"],"timeoutMs":2000}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(plugins.path().join("broken")).unwrap();
+        fs::write(plugins.path().join("broken").join(MANIFEST_FILE), "{").unwrap();
+
+        let host = PluginHost::new(data.path().to_path_buf());
+        assert_eq!(host.load_dir(plugins.path()).unwrap(), vec!["echo"]);
+
+        let polisher = host.polisher("echo").unwrap();
+        assert_eq!(polisher.polish("raw").await.unwrap(), "polished");
+
+        let process = host.process("echo", PluginCapability::Polisher).unwrap();
+        // 未知方法让脚本退出，下次调用应自动重启。
+        assert!(process
+            .call("unknown", serde_json::Value::Null)
+            .await
+            .is_err());
+        assert_eq!(polisher.polish("again").await.unwrap(), "polished");
+
+        let registry = ActionRegistry::default();
+        assert_eq!(host.register_actions(&registry), 1);
+        let entry = HistoryEntry {
+            session_id: "plugin-entry".into(),
+            started_at_ms: 0,
+            completed_at_ms: 0,
+            duration_ms: 0,
+            locale: None,
+            app_identifier: None,
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: "hello".into(),
+            polished_transcript: "Hello.".into(),
+            preview: String::new(),
+            accuracy_flag: Default::default(),
+            accuracy_remarks: None,
+            post_actions: Vec::new(),
+            metadata: serde_json::Value::Null,
+            pinned: false,
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+            search_hit: None,
+        };
+        let action = registry.get("echo").unwrap();
+        assert_eq!(
+            action
+                .execute(&entry, &serde_json::Value::Null)
+                .await
+                .unwrap(),
+            serde_json::json!({ "ok": true })
+        );
+        let outcome = host
+            .publisher("echo")
+            .unwrap()
+            .publish(crate::session::publisher::PublishRequest {
+                transcript: "hello".into(),
+                focus: Default::default(),
+                fallback: crate::session::publisher::FallbackStrategy::ClipboardCopy,
+                insertion: Default::default(),
+                strategy: None,
+                html: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(outcome.failure.unwrap().message, "offline");
+        host.shutdown().await;
+        assert!(data.path().join("echo").is_dir());
+    }
+}
@@ -0,0 +1,345 @@
+//! 插件进程的生命周期：按需启动、崩溃后有限次重启、超时终止，以及 `fs/*` 方法的目录白名单。
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use super::manifest::PluginManifest;
+use super::rpc::{HostHandler, RpcClient, INVALID_PARAMS, METHOD_NOT_FOUND};
+use super::PluginError;
+
+/// 进程异常退出后允许的最大重启次数。
+const MAX_RESTARTS: u32 = 3;
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+/// `fs/read` 与 `fs/write` 单次允许的最大字节数。
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// `fs/*` 调用的错误码，位于 JSON-RPC 的服务端自定义区间。
+const SANDBOX_DENIED: i64 = -32001;
+const IO_FAILED: i64 = -32002;
+
+/// 插件通过 `fs/*` 访问文件时的目录白名单。
+///
+/// 只约束经由 JSON-RPC 的文件访问，不限制插件进程自身的系统调用；插件直接
+/// 打开文件或联网不受此白名单影响。
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    data_dir: PathBuf,
+    roots: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    pub fn new(data_dir: PathBuf, allowed: &[PathBuf]) -> Self {
+        let roots = std::iter::once(data_dir.clone())
+            .chain(allowed.iter().cloned())
+            .filter_map(|root| root.canonicalize().ok())
+            .collect();
+        Self { data_dir, roots }
+    }
+
+    /// 解析插件给出的路径；相对路径基于插件数据目录，符号链接解析后仍须落在白名单内。
+    pub fn resolve(&self, requested: &str) -> Result<PathBuf, PluginError> {
+        let requested = Path::new(requested);
+        if requested
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+        {
+            return Err(PluginError::Sandbox(format!(
+                "{} must not contain `..`",
+                requested.display()
+            )));
+        }
+        let path = if requested.is_absolute() {
+            requested.to_path_buf()
+        } else {
+            self.data_dir.join(requested)
+        };
+        // 待写入的文件可能尚不存在，此时校验其父目录。
+        let resolved = match path.canonicalize() {
+            Ok(resolved) => resolved,
+            Err(_) => {
+                let parent = path.parent().and_then(|parent| parent.canonicalize().ok());
+                match (parent, path.file_name()) {
+                    (Some(parent), Some(name)) => parent.join(name),
+                    _ => {
+                        return Err(PluginError::Sandbox(format!(
+                            "{} does not exist",
+                            path.display()
+                        )))
+                    }
+                }
+            }
+        };
+        if self.roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(PluginError::Sandbox(format!(
+                "{} is outside the plugin sandbox",
+                path.display()
+            )))
+        }
+    }
+}
+
+/// 宿主为插件提供的方法：`host/log`、`fs/read`、`fs/write`。
+struct PluginHostHandler {
+    plugin: String,
+    sandbox: Sandbox,
+}
+
+#[async_trait]
+impl HostHandler for PluginHostHandler {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, (i64, String)> {
+        let path = || {
+            params
+                .get("path")
+                .and_then(Value::as_str)
+                .ok_or((INVALID_PARAMS, "missing `path`".to_string()))
+        };
+        let sandbox_error = |err: PluginError| match err {
+            PluginError::Sandbox(message) => (SANDBOX_DENIED, message),
+            other => (IO_FAILED, other.to_string()),
+        };
+        match method {
+            "host/log" => {
+                let message = params.get("message").and_then(Value::as_str).unwrap_or("");
+                match params.get("level").and_then(Value::as_str) {
+                    Some("error" | "warn") => {
+                        warn!(target: "plugins", plugin = %self.plugin, "{message}")
+                    }
+                    Some("debug") => debug!(target: "plugins", plugin = %self.plugin, "{message}"),
+                    _ => info!(target: "plugins", plugin = %self.plugin, "{message}"),
+                }
+                Ok(Value::Null)
+            }
+            "fs/read" => {
+                let resolved = self.sandbox.resolve(path()?).map_err(sandbox_error)?;
+                let size = fs::metadata(&resolved)
+                    .map_err(|err| (IO_FAILED, err.to_string()))?
+                    .len();
+                if size > MAX_FILE_BYTES {
+                    return Err((IO_FAILED, format!("file exceeds {MAX_FILE_BYTES} bytes")));
+                }
+                let contents =
+                    fs::read_to_string(&resolved).map_err(|err| (IO_FAILED, err.to_string()))?;
+                Ok(json!({ "contents": contents }))
+            }
+            "fs/write" => {
+                let contents = params
+                    .get("contents")
+                    .and_then(Value::as_str)
+                    .ok_or((INVALID_PARAMS, "missing `contents`".to_string()))?;
+                if contents.len() as u64 > MAX_FILE_BYTES {
+                    return Err((IO_FAILED, format!("file exceeds {MAX_FILE_BYTES} bytes")));
+                }
+                let resolved = self.sandbox.resolve(path()?).map_err(sandbox_error)?;
+                fs::write(&resolved, contents).map_err(|err| (IO_FAILED, err.to_string()))?;
+                Ok(json!({ "bytes": contents.len() }))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unknown host method {method}"))),
+        }
+    }
+}
+
+struct Running {
+    child: Child,
+    rpc: Arc<RpcClient>,
+}
+
+/// 单个插件的进程，调用之间复用。
+pub struct PluginProcess {
+    manifest: PluginManifest,
+    data_dir: PathBuf,
+    running: Mutex<Option<Running>>,
+    restarts: AtomicU32,
+}
+
+impl std::fmt::Debug for PluginProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginProcess")
+            .field("id", &self.manifest.id)
+            .field("restarts", &self.restarts.load(Ordering::SeqCst))
+            .finish_non_exhaustive()
+    }
+}
+
+impl PluginProcess {
+    pub fn new(manifest: PluginManifest, data_dir: PathBuf) -> Self {
+        Self {
+            manifest,
+            data_dir,
+            running: Mutex::new(None),
+            restarts: AtomicU32::new(0),
+        }
+    }
+
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    /// 调用插件方法；进程未启动或已退出时先（重新）启动，超时则终止进程。
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, PluginError> {
+        let rpc = self.ensure_running().await?;
+        let result = rpc.request(method, params, self.manifest.timeout()).await;
+        if let Err(PluginError::Timeout { .. }) = &result {
+            warn!(target: "plugins", plugin = %self.manifest.id, method, "plugin timed out, killing process");
+            if let Some(mut running) = self.running.lock().await.take() {
+                let _ = running.child.kill().await;
+            }
+        }
+        result
+    }
+
+    async fn ensure_running(&self) -> Result<Arc<RpcClient>, PluginError> {
+        let mut running = self.running.lock().await;
+        if let Some(current) = running.as_mut() {
+            let exited = matches!(current.child.try_wait(), Ok(Some(_)));
+            if !exited && !current.rpc.is_closed() {
+                return Ok(current.rpc.clone());
+            }
+            warn!(target: "plugins", plugin = %self.manifest.id, "plugin process exited unexpectedly");
+            let _ = current.child.kill().await;
+            *running = None;
+            if self.restarts.fetch_add(1, Ordering::SeqCst) >= MAX_RESTARTS {
+                return Err(PluginError::Crashed(self.manifest.id.clone()));
+            }
+        } else if self.restarts.load(Ordering::SeqCst) > MAX_RESTARTS {
+            return Err(PluginError::Crashed(self.manifest.id.clone()));
+        }
+
+        let started = self.start().await?;
+        let rpc = started.rpc.clone();
+        *running = Some(started);
+        Ok(rpc)
+    }
+
+    async fn start(&self) -> Result<Running, PluginError> {
+        fs::create_dir_all(&self.data_dir).map_err(|err| PluginError::Io(err.to_string()))?;
+        let mut command = Command::new(self.manifest.executable());
+        command
+            .args(&self.manifest.args)
+            .current_dir(&self.data_dir)
+            .env_clear()
+            .env("HOME", &self.data_dir)
+            .env("FLOWWISPER_PLUGIN_DATA", &self.data_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(path) = std::env::var_os("PATH") {
+            command.env("PATH", path);
+        }
+        let mut child = command.spawn().map_err(|err| {
+            PluginError::Io(format!(
+                "failed to launch plugin {}: {err}",
+                self.manifest.id
+            ))
+        })?;
+
+        let stdin = child.stdin.take().ok_or(PluginError::Closed)?;
+        let stdout = child.stdout.take().ok_or(PluginError::Closed)?;
+        if let Some(stderr) = child.stderr.take() {
+            let plugin = self.manifest.id.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!(target: "plugins", %plugin, "{line}");
+                }
+            });
+        }
+
+        let handler = PluginHostHandler {
+            plugin: self.manifest.id.clone(),
+            sandbox: Sandbox::new(self.data_dir.clone(), &self.manifest.allowed_paths),
+        };
+        let rpc = Arc::new(RpcClient::spawn(
+            stdout,
+            stdin,
+            Arc::new(handler),
+            self.manifest.id.clone(),
+        ));
+        rpc.request(
+            "initialize",
+            json!({
+                "hostVersion": env!("CARGO_PKG_VERSION"),
+                "capabilities": self.manifest.capabilities,
+                "dataDir": self.data_dir,
+            }),
+            self.manifest.timeout(),
+        )
+        .await?;
+        info!(target: "plugins", plugin = %self.manifest.id, "plugin started");
+        Ok(Running { child, rpc })
+    }
+
+    /// 通知插件退出，宽限期后仍未退出则强制终止。
+    pub async fn stop(&self) {
+        let Some(mut running) = self.running.lock().await.take() else {
+            return;
+        };
+        let _ = running.rpc.notify("shutdown", Value::Null).await;
+        if tokio::time::timeout(SHUTDOWN_GRACE, running.child.wait())
+            .await
+            .is_err()
+        {
+            let _ = running.child.kill().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sandbox_confines_file_access() {
+        let data = tempfile::tempdir().unwrap();
+        let shared = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(shared.path().join("notes.txt"), "shared").unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        let handler = PluginHostHandler {
+            plugin: "test".into(),
+            sandbox: Sandbox::new(data.path().to_path_buf(), &[shared.path().to_path_buf()]),
+        };
+        handler
+            .handle(
+                "fs/write",
+                json!({ "path": "state.json", "contents": "{}" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(data.path().join("state.json")).unwrap(),
+            "{}"
+        );
+        let shared_path = shared.path().join("notes.txt");
+        let read = handler
+            .handle("fs/read", json!({ "path": shared_path }))
+            .await
+            .unwrap();
+        assert_eq!(read["contents"], "shared");
+
+        let secret = outside.path().join("secret.txt");
+        let denied = handler
+            .handle("fs/read", json!({ "path": secret }))
+            .await
+            .unwrap_err();
+        assert_eq!(denied.0, SANDBOX_DENIED);
+        let escape = handler
+            .handle("fs/read", json!({ "path": "../secret.txt" }))
+            .await
+            .unwrap_err();
+        assert_eq!(escape.0, SANDBOX_DENIED);
+    }
+}
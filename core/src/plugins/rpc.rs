@@ -0,0 +1,293 @@
+//! 插件通信使用的 JSON-RPC 2.0，每行一条消息。
+//!
+//! 插件也可以向宿主发起请求（如 `fs/read`），由 [`HostHandler`] 处理后回写响应。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{oneshot, Mutex};
+use tracing::{debug, warn};
+
+use super::PluginError;
+
+/// JSON-RPC 标准错误码：方法不存在。
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC 标准错误码：参数无效。
+pub const INVALID_PARAMS: i64 = -32602;
+
+type Pending = Arc<StdMutex<HashMap<u64, oneshot::Sender<Result<Value, PluginError>>>>>;
+type Writer = Arc<Mutex<Box<dyn AsyncWrite + Send + Unpin>>>;
+
+/// 处理插件发往宿主的请求与通知。
+#[async_trait]
+pub trait HostHandler: Send + Sync {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, (i64, String)>;
+}
+
+/// 与单个插件进程的 RPC 连接。
+pub struct RpcClient {
+    writer: Writer,
+    pending: Pending,
+    next_id: AtomicU64,
+    closed: Arc<AtomicBool>,
+}
+
+impl std::fmt::Debug for RpcClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcClient")
+            .field("closed", &self.is_closed())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RpcClient {
+    /// 绑定插件的输出与输入流，并启动读取任务。
+    pub fn spawn(
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        handler: Arc<dyn HostHandler>,
+        label: String,
+    ) -> Self {
+        let writer: Writer = Arc::new(Mutex::new(Box::new(writer)));
+        let pending: Pending = Arc::new(StdMutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        tokio::spawn(read_loop(
+            BufReader::new(reader),
+            writer.clone(),
+            pending.clone(),
+            closed.clone(),
+            handler,
+            label,
+        ));
+        Self {
+            writer,
+            pending,
+            next_id: AtomicU64::new(1),
+            closed,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// 发送请求并在 `timeout` 内等待响应。
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, PluginError> {
+        if self.is_closed() {
+            return Err(PluginError::Closed);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(id, tx);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(err) = write_message(&self.writer, &message).await {
+            self.forget(id);
+            return Err(err);
+        }
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(PluginError::Closed),
+            Err(_) => {
+                self.forget(id);
+                Err(PluginError::Timeout {
+                    method: method.to_string(),
+                    timeout_ms: timeout.as_millis() as u64,
+                })
+            }
+        }
+    }
+
+    /// 发送无需响应的通知。
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), PluginError> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        write_message(&self.writer, &message).await
+    }
+
+    fn forget(&self, id: u64) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&id);
+    }
+}
+
+async fn write_message(writer: &Writer, message: &Value) -> Result<(), PluginError> {
+    let mut line = message.to_string();
+    line.push('\n');
+    let mut writer = writer.lock().await;
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|err| PluginError::Io(err.to_string()))?;
+    writer
+        .flush()
+        .await
+        .map_err(|err| PluginError::Io(err.to_string()))
+}
+
+async fn read_loop(
+    reader: BufReader<impl AsyncRead + Unpin>,
+    writer: Writer,
+    pending: Pending,
+    closed: Arc<AtomicBool>,
+    handler: Arc<dyn HostHandler>,
+    label: String,
+) {
+    let mut lines = reader.lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) => {
+                warn!(target: "plugins", plugin = %label, %err, "failed to read plugin output");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!(target: "plugins", plugin = %label, %err, "ignoring malformed plugin message");
+                continue;
+            }
+        };
+
+        if let Some(method) = message.get("method").and_then(Value::as_str) {
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+            let result = handler.handle(method, params).await;
+            // 没有 id 的是通知，不回写响应。
+            if let Some(id) = message.get("id").cloned() {
+                let response = match result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+                if let Err(err) = write_message(&writer, &response).await {
+                    warn!(target: "plugins", plugin = %label, %err, "failed to answer plugin request");
+                }
+            }
+            continue;
+        }
+
+        let Some(id) = message.get("id").and_then(Value::as_u64) else {
+            debug!(target: "plugins", plugin = %label, "ignoring plugin message without id");
+            continue;
+        };
+        let Some(sender) = pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&id)
+        else {
+            continue;
+        };
+        let result = match message.get("error") {
+            Some(error) => Err(PluginError::Rpc {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or(0),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("plugin error")
+                    .to_string(),
+            }),
+            None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        let _ = sender.send(result);
+    }
+
+    closed.store(true, Ordering::SeqCst);
+    // 丢弃发送端即可让等待中的请求以 `Closed` 结束。
+    pending
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoHost;
+
+    #[async_trait]
+    impl HostHandler for EchoHost {
+        async fn handle(&self, method: &str, params: Value) -> Result<Value, (i64, String)> {
+            match method {
+                "host/echo" => Ok(params),
+                _ => Err((METHOD_NOT_FOUND, format!("unknown method {method}"))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn correlates_responses_and_answers_plugin_requests() {
+        let (host_side, plugin_side) = tokio::io::duplex(4096);
+        let (host_read, host_write) = tokio::io::split(host_side);
+        let (plugin_read, mut plugin_write) = tokio::io::split(plugin_side);
+        let client = RpcClient::spawn(host_read, host_write, Arc::new(EchoHost), "test".into());
+
+        let plugin = tokio::spawn(async move {
+            let mut lines = BufReader::new(plugin_read).lines();
+            let request: Value =
+                serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+            let ask = json!({ "jsonrpc": "2.0", "id": 99, "method": "host/echo", "params": [1] });
+            plugin_write
+                .write_all(format!("{ask}\n").as_bytes())
+                .await
+                .unwrap();
+            let echoed: Value =
+                serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+            let reply = json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "method": request["method"], "echoed": echoed["result"] },
+            });
+            plugin_write
+                .write_all(format!("{reply}\n").as_bytes())
+                .await
+                .unwrap();
+            // 第二个请求不回应，验证超时。
+            lines.next_line().await.unwrap();
+            plugin_write
+        });
+
+        let result = client
+            .request("polisher/polish", json!({}), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(result["method"], "polisher/polish");
+        assert_eq!(result["echoed"], json!([1]));
+
+        let timeout = client
+            .request("slow", json!({}), Duration::from_millis(20))
+            .await;
+        assert!(matches!(timeout, Err(PluginError::Timeout { .. })));
+
+        drop(plugin.await.unwrap());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(client.is_closed());
+        assert!(matches!(
+            client
+                .request("after", json!({}), Duration::from_millis(20))
+                .await,
+            Err(PluginError::Closed)
+        ));
+    }
+}
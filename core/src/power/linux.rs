@@ -0,0 +1,101 @@
+//! Linux：读取 sysfs 中的电源与温度传感器。
+
+use std::fs;
+use std::path::Path;
+
+use super::{PowerState, ThermalPressure};
+
+/// 任一温区达到该温度（摄氏度）视为发热或过热。
+const ELEVATED_CELSIUS: i64 = 80;
+const CRITICAL_CELSIUS: i64 = 92;
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+/// `class_root` 通常为 `/sys/class`。
+pub(super) fn sample(class_root: &Path) -> PowerState {
+    let mut state = PowerState::default();
+    let mut mains_online = None;
+    if let Ok(entries) = fs::read_dir(class_root.join("power_supply")) {
+        for entry in entries.flatten() {
+            let supply = entry.path();
+            match read_trimmed(&supply.join("type")).as_deref() {
+                Some("Mains") | Some("USB") => {
+                    let online = read_trimmed(&supply.join("online")).as_deref() == Some("1");
+                    mains_online = Some(mains_online.unwrap_or(false) || online);
+                }
+                Some("Battery") => {
+                    if read_trimmed(&supply.join("status")).as_deref() == Some("Discharging") {
+                        state.on_battery = true;
+                    }
+                    state.battery_percent = read_trimmed(&supply.join("capacity"))
+                        .and_then(|value| value.parse::<u8>().ok())
+                        .or(state.battery_percent);
+                }
+                _ => {}
+            }
+        }
+    }
+    // 部分机器电池状态滞后，以交流适配器为准。
+    if mains_online == Some(true) {
+        state.on_battery = false;
+    }
+
+    let hottest = fs::read_dir(class_root.join("thermal"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("thermal_zone")
+        })
+        .filter_map(|entry| {
+            read_trimmed(&entry.path().join("temp"))?
+                .parse::<i64>()
+                .ok()
+        })
+        .max()
+        .map(|millidegrees| millidegrees / 1000);
+    state.thermal = match hottest {
+        Some(celsius) if celsius >= CRITICAL_CELSIUS => ThermalPressure::Critical,
+        Some(celsius) if celsius >= ELEVATED_CELSIUS => ThermalPressure::Elevated,
+        _ => ThermalPressure::Nominal,
+    };
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, value: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value).unwrap();
+    }
+
+    #[test]
+    fn reads_battery_and_hottest_thermal_zone_from_sysfs() {
+        let root = tempfile::tempdir().unwrap();
+        write(root.path(), "power_supply/AC/type", "Mains\n");
+        write(root.path(), "power_supply/AC/online", "0\n");
+        write(root.path(), "power_supply/BAT0/type", "Battery\n");
+        write(root.path(), "power_supply/BAT0/status", "Discharging\n");
+        write(root.path(), "power_supply/BAT0/capacity", "42\n");
+        write(root.path(), "thermal/thermal_zone0/temp", "45000\n");
+        write(root.path(), "thermal/thermal_zone1/temp", "84000\n");
+
+        let state = sample(root.path());
+        assert!(state.on_battery);
+        assert_eq!(state.battery_percent, Some(42));
+        assert_eq!(state.thermal, ThermalPressure::Elevated);
+
+        write(root.path(), "power_supply/AC/online", "1\n");
+        assert!(!sample(root.path()).on_battery);
+    }
+}
@@ -0,0 +1,38 @@
+//! macOS：解析 `pmset` 的电池与 CPU 限速输出。
+
+use std::process::Command;
+
+use super::{PowerState, ThermalPressure};
+
+fn pmset(args: &[&str]) -> Option<String> {
+    let output = Command::new("pmset").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub(super) fn sample() -> PowerState {
+    let mut state = PowerState::default();
+    if let Some(battery) = pmset(&["-g", "batt"]) {
+        state.on_battery = battery.contains("'Battery Power'");
+        state.battery_percent = battery
+            .split_whitespace()
+            .find_map(|word| word.trim_end_matches(';').strip_suffix('%'))
+            .and_then(|percent| percent.parse().ok());
+    }
+    // 系统因温度限速时 CPU_Speed_Limit 低于 100。
+    if let Some(thermal) = pmset(&["-g", "therm"]) {
+        let limit = thermal
+            .lines()
+            .find(|line| line.contains("CPU_Speed_Limit"))
+            .and_then(|line| line.split('=').nth(1))
+            .and_then(|value| value.trim().parse::<u32>().ok());
+        state.thermal = match limit {
+            Some(limit) if limit <= 50 => ThermalPressure::Critical,
+            Some(limit) if limit < 100 => ThermalPressure::Elevated,
+            _ => ThermalPressure::Nominal,
+        };
+    }
+    state
+}
@@ -0,0 +1,273 @@
+//! 电源与散热感知的性能调节：用电池或机器过热时降低本地推理强度，必要时优先使用云端。
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+
+/// 默认的低电量阈值（百分比），低于该值进入最省电档。
+pub const DEFAULT_LOW_BATTERY_PERCENT: u8 = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalPressure {
+    #[default]
+    Nominal,
+    Elevated,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    pub on_battery: bool,
+    /// 无电池或无法读取时为 `None`。
+    pub battery_percent: Option<u8>,
+    pub thermal: ThermalPressure,
+}
+
+/// 读取当前的电源与散热状态；平台不支持的项按交流供电、散热正常处理。
+pub trait PowerProbe: Send + Sync {
+    fn sample(&self) -> PowerState;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemPowerProbe;
+
+impl PowerProbe for SystemPowerProbe {
+    fn sample(&self) -> PowerState {
+        #[cfg(target_os = "linux")]
+        {
+            linux::sample(std::path::Path::new("/sys/class"))
+        }
+        #[cfg(target_os = "macos")]
+        {
+            macos::sample()
+        }
+        #[cfg(target_os = "windows")]
+        {
+            windows::sample()
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            PowerState::default()
+        }
+    }
+}
+
+/// 本地引擎的推理强度。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceLevel {
+    #[default]
+    Full,
+    Reduced,
+    Minimal,
+}
+
+impl PerformanceLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PerformanceLevel::Full => "full",
+            PerformanceLevel::Reduced => "reduced",
+            PerformanceLevel::Minimal => "minimal",
+        }
+    }
+
+    /// 按档位缩减线程数，至少保留一个线程。
+    pub fn scale_threads(&self, threads: usize) -> usize {
+        let scaled = match self {
+            PerformanceLevel::Full => threads,
+            PerformanceLevel::Reduced => threads / 2,
+            PerformanceLevel::Minimal => threads / 4,
+        };
+        scaled.max(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GovernorConfig {
+    pub enabled: bool,
+    pub low_battery_percent: u8,
+    /// 降档期间优先使用云端识别；云端被禁用或超出预算时仍走本地。
+    pub prefer_cloud_on_battery: bool,
+}
+
+impl Default for GovernorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            low_battery_percent: DEFAULT_LOW_BATTERY_PERCENT,
+            prefer_cloud_on_battery: true,
+        }
+    }
+}
+
+/// 档位变化；`reason` 可直接展示给用户。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GovernorDecision {
+    pub level: PerformanceLevel,
+    pub prefer_cloud: bool,
+    pub state: PowerState,
+    pub reason: String,
+}
+
+pub struct PerformanceGovernor {
+    probe: Arc<dyn PowerProbe>,
+    config: RwLock<GovernorConfig>,
+    current: Mutex<(PerformanceLevel, PowerState)>,
+}
+
+impl PerformanceGovernor {
+    pub fn new(probe: Arc<dyn PowerProbe>, config: GovernorConfig) -> Self {
+        Self {
+            probe,
+            config: RwLock::new(config),
+            current: Mutex::new((PerformanceLevel::Full, PowerState::default())),
+        }
+    }
+
+    pub fn system(config: GovernorConfig) -> Self {
+        Self::new(Arc::new(SystemPowerProbe), config)
+    }
+
+    pub fn set_config(&self, config: GovernorConfig) {
+        *self
+            .config
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+    }
+
+    fn config(&self) -> GovernorConfig {
+        *self
+            .config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn level(&self) -> PerformanceLevel {
+        self.current
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .0
+    }
+
+    pub fn power_state(&self) -> PowerState {
+        self.current
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .1
+    }
+
+    pub fn prefers_cloud(&self) -> bool {
+        self.config().prefer_cloud_on_battery && self.level() != PerformanceLevel::Full
+    }
+
+    /// 重新采样；档位变化时返回决策，否则返回 `None`。
+    pub fn evaluate(&self) -> Option<GovernorDecision> {
+        let config = self.config();
+        let state = self.probe.sample();
+        let (level, reason) = decide(&config, &state);
+        let mut current = self
+            .current
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let changed = current.0 != level;
+        *current = (level, state);
+        drop(current);
+        changed.then(|| GovernorDecision {
+            level,
+            prefer_cloud: config.prefer_cloud_on_battery && level != PerformanceLevel::Full,
+            state,
+            reason: reason.to_string(),
+        })
+    }
+}
+
+fn decide(config: &GovernorConfig, state: &PowerState) -> (PerformanceLevel, &'static str) {
+    if !config.enabled {
+        return (PerformanceLevel::Full, "已关闭省电调节，本地识别恢复全速");
+    }
+    let low_battery = state.on_battery
+        && state
+            .battery_percent
+            .is_some_and(|percent| percent <= config.low_battery_percent);
+    match (state.thermal, state.on_battery) {
+        (ThermalPressure::Critical, _) => (
+            PerformanceLevel::Minimal,
+            "设备温度过高，已降低本地识别强度",
+        ),
+        _ if low_battery => (
+            PerformanceLevel::Minimal,
+            "电量不足，已降低本地识别强度以延长续航",
+        ),
+        (ThermalPressure::Elevated, _) => (
+            PerformanceLevel::Reduced,
+            "设备发热，已适度降低本地识别强度",
+        ),
+        (_, true) => (
+            PerformanceLevel::Reduced,
+            "正在使用电池供电，已适度降低本地识别强度",
+        ),
+        _ => (PerformanceLevel::Full, "已接通电源，本地识别恢复全速"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ScriptedProbe(Mutex<PowerState>);
+
+    impl PowerProbe for ScriptedProbe {
+        fn sample(&self) -> PowerState {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn steps_down_on_battery_and_heat_and_recovers_on_mains() {
+        let probe = Arc::new(ScriptedProbe(Mutex::new(PowerState::default())));
+        let governor = PerformanceGovernor::new(probe.clone(), GovernorConfig::default());
+        assert_eq!(governor.evaluate(), None);
+
+        let set = |state: PowerState| *probe.0.lock().unwrap() = state;
+        set(PowerState {
+            on_battery: true,
+            battery_percent: Some(80),
+            thermal: ThermalPressure::Nominal,
+        });
+        let decision = governor.evaluate().expect("battery steps down");
+        assert_eq!(decision.level, PerformanceLevel::Reduced);
+        assert!(decision.prefer_cloud && governor.prefers_cloud());
+        assert_eq!(governor.evaluate(), None);
+
+        set(PowerState {
+            on_battery: true,
+            battery_percent: Some(15),
+            thermal: ThermalPressure::Nominal,
+        });
+        assert_eq!(
+            governor.evaluate().map(|decision| decision.level),
+            Some(PerformanceLevel::Minimal)
+        );
+        assert_eq!(PerformanceLevel::Minimal.scale_threads(8), 2);
+
+        governor.set_config(GovernorConfig {
+            enabled: false,
+            ..GovernorConfig::default()
+        });
+        assert_eq!(
+            governor.evaluate().map(|decision| decision.level),
+            Some(PerformanceLevel::Full)
+        );
+        assert!(!governor.prefers_cloud());
+    }
+}
@@ -0,0 +1,35 @@
+//! Windows：`GetSystemPowerStatus` 提供交流电与电量；温度没有通用的用户态接口，按正常处理。
+
+use super::PowerState;
+
+const AC_LINE_OFFLINE: u8 = 0;
+const UNKNOWN_PERCENT: u8 = 255;
+
+#[repr(C)]
+#[derive(Default)]
+struct SystemPowerStatus {
+    ac_line_status: u8,
+    battery_flag: u8,
+    battery_life_percent: u8,
+    system_status_flag: u8,
+    battery_life_time: u32,
+    battery_full_life_time: u32,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+}
+
+pub(super) fn sample() -> PowerState {
+    let mut status = SystemPowerStatus::default();
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return PowerState::default();
+    }
+    PowerState {
+        on_battery: status.ac_line_status == AC_LINE_OFFLINE,
+        battery_percent: (status.battery_life_percent != UNKNOWN_PERCENT)
+            .then_some(status.battery_life_percent),
+        ..PowerState::default()
+    }
+}
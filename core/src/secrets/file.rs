@@ -0,0 +1,186 @@
+//! 加密文件后端：每个条目单独以 AES-256-GCM 封装，条目名作为附加数据。
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{SecretError, SecretStore};
+use crate::audio::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+
+/// 加密文件的主密钥，至少 32 字节；未设置时使用数据目录下随机生成的密钥文件。
+pub const SECRETS_KEY_ENV: &str = "FLOWWISPER_SECRETS_KEY";
+const STORE_FILE: &str = "secrets.json";
+const KEY_FILE: &str = "secrets.key";
+const AAD_PREFIX: &[u8] = b"flowwisper.secrets.v1:";
+
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    keys: AudioCacheKeys,
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileStore {
+    pub fn open(dir: &Path) -> Result<Self, SecretError> {
+        fs::create_dir_all(dir)?;
+        let master = match env::var(SECRETS_KEY_ENV) {
+            Ok(value) if !value.is_empty() => value.into_bytes(),
+            _ => load_or_create_key(&dir.join(KEY_FILE))?,
+        };
+        let keys =
+            AudioCacheKeys::derive(&master).map_err(|err| SecretError::Backend(err.to_string()))?;
+        Ok(Self {
+            path: dir.join(STORE_FILE),
+            keys,
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn aad(name: &str) -> Vec<u8> {
+        [AAD_PREFIX, name.as_bytes()].concat()
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn backend(&self) -> &'static str {
+        "encrypted-file"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, SecretError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let entries: BTreeMap<String, SealedEnvelope> = read_map(&self.path)?;
+        let Some(envelope) = entries.get(name) else {
+            return Ok(None);
+        };
+        let plaintext = open_payload(&self.keys, &Self::aad(name), envelope)
+            .map_err(|err| SecretError::Backend(format!("failed to open {name}: {err}")))?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| SecretError::Backend(format!("{name} is not valid UTF-8")))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut entries: BTreeMap<String, SealedEnvelope> = read_map(&self.path)?;
+        let envelope = seal_payload(&self.keys, &Self::aad(name), value.as_bytes())
+            .map_err(|err| SecretError::Backend(err.to_string()))?;
+        entries.insert(name.to_string(), envelope);
+        write_map(&self.path, &entries)
+    }
+
+    fn delete(&self, name: &str) -> Result<bool, SecretError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut entries: BTreeMap<String, SealedEnvelope> = read_map(&self.path)?;
+        if entries.remove(name).is_none() {
+            return Ok(false);
+        }
+        write_map(&self.path, &entries)?;
+        Ok(true)
+    }
+}
+
+fn load_or_create_key(path: &Path) -> Result<Vec<u8>, SecretError> {
+    match fs::read(path) {
+        Ok(key) => return Ok(key),
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+    let mut key = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| SecretError::Backend("failed to generate secrets key".into()))?;
+    write_private(path, &key)?;
+    Ok(key.to_vec())
+}
+
+/// 读取条目表；文件不存在时为空表。
+pub(super) fn read_map<T: DeserializeOwned>(
+    path: &Path,
+) -> Result<BTreeMap<String, T>, SecretError> {
+    match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|err| SecretError::Backend(format!("corrupt secrets file: {err}"))),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// 先写临时文件再替换，避免中途失败留下半个条目表。
+pub(super) fn write_map<T: Serialize>(
+    path: &Path,
+    entries: &BTreeMap<String, T>,
+) -> Result<(), SecretError> {
+    let bytes =
+        serde_json::to_vec_pretty(entries).map_err(|err| SecretError::Backend(err.to_string()))?;
+    let staging = path.with_extension("tmp");
+    write_private(&staging, &bytes)?;
+    fs::rename(&staging, path)?;
+    Ok(())
+}
+
+fn write_private(path: &Path, bytes: &[u8]) -> Result<(), SecretError> {
+    let mut options = OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_file_round_trips_without_plaintext_on_disk() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = EncryptedFileStore::open(dir.path()).expect("open");
+        assert_eq!(store.get("openai.api_key").unwrap(), None);
+
+        store.set("openai.api_key", "sk-live-123").unwrap();
+        store.set("anthropic.api_key", "ak-456").unwrap();
+        let on_disk = fs::read_to_string(dir.path().join(STORE_FILE)).unwrap();
+        assert!(!on_disk.contains("sk-live-123"));
+
+        let reopened = EncryptedFileStore::open(dir.path()).expect("reopen");
+        assert_eq!(
+            reopened.get("openai.api_key").unwrap().as_deref(),
+            Some("sk-live-123")
+        );
+        assert!(reopened.delete("openai.api_key").unwrap());
+        assert!(!reopened.delete("openai.api_key").unwrap());
+        assert_eq!(reopened.get("openai.api_key").unwrap(), None);
+        assert_eq!(
+            reopened.get("anthropic.api_key").unwrap().as_deref(),
+            Some("ak-456")
+        );
+
+        // 条目不能被挪到另一个名称下解密。
+        let mut entries: BTreeMap<String, SealedEnvelope> =
+            read_map(&dir.path().join(STORE_FILE)).unwrap();
+        let moved = entries.remove("anthropic.api_key").unwrap();
+        entries.insert("openai.api_key".into(), moved);
+        write_map(&dir.path().join(STORE_FILE), &entries).unwrap();
+        assert!(reopened.get("openai.api_key").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Linux 后端：通过 `secret-tool` 访问 libsecret（GNOME Keyring、KWallet 等），密钥经标准输入传递。
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+use super::{SecretError, SecretStore};
+
+const SECRET_TOOL: &str = "secret-tool";
+
+#[derive(Debug, Clone)]
+pub struct LibsecretStore {
+    service: String,
+}
+
+impl LibsecretStore {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// 已安装 `secret-tool` 且会话总线上有可用的密钥服务。
+    pub fn is_available() -> bool {
+        Command::new(SECRET_TOOL)
+            .args(["search", "service", super::SERVICE, "account", "probe"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    fn command(&self, action: &str, name: &str) -> Command {
+        let mut command = Command::new(SECRET_TOOL);
+        command.arg(action);
+        if action == "store" {
+            command.arg(format!("--label=Flowwisper {name}"));
+        }
+        command.args(["service", &self.service, "account", name]);
+        command
+    }
+}
+
+fn spawn_error(err: std::io::Error) -> SecretError {
+    if err.kind() == ErrorKind::NotFound {
+        SecretError::Unavailable(format!("{SECRET_TOOL} is not installed"))
+    } else {
+        err.into()
+    }
+}
+
+impl SecretStore for LibsecretStore {
+    fn backend(&self) -> &'static str {
+        "libsecret"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, SecretError> {
+        let output = self
+            .command("lookup", name)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(spawn_error)?;
+        // 找不到条目时 `secret-tool lookup` 以非零状态退出且没有输出。
+        if !output.status.success() {
+            if output.stderr.is_empty() {
+                return Ok(None);
+            }
+            return Err(SecretError::Backend(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        String::from_utf8(output.stdout)
+            .map(Some)
+            .map_err(|_| SecretError::Backend(format!("{name} is not valid UTF-8")))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretError> {
+        let mut child = self
+            .command("store", name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(value.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(SecretError::Backend(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<bool, SecretError> {
+        if self.get(name)?.is_none() {
+            return Ok(false);
+        }
+        let output = self
+            .command("clear", name)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(spawn_error)?;
+        if !output.status.success() {
+            return Err(SecretError::Backend(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,178 @@
+//! macOS 后端：登录钥匙串中的通用密码条目，服务名固定、账户名为密钥名称。
+
+use std::ffi::c_void;
+use std::ptr;
+
+use super::{SecretError, SecretStore};
+
+type OsStatus = i32;
+type SecKeychainItemRef = *mut c_void;
+
+const ERR_SEC_SUCCESS: OsStatus = 0;
+const ERR_SEC_ITEM_NOT_FOUND: OsStatus = -25300;
+
+#[link(name = "Security", kind = "framework")]
+extern "C" {
+    fn SecKeychainFindGenericPassword(
+        keychain: *const c_void,
+        service_len: u32,
+        service: *const u8,
+        account_len: u32,
+        account: *const u8,
+        password_len: *mut u32,
+        password: *mut *mut c_void,
+        item: *mut SecKeychainItemRef,
+    ) -> OsStatus;
+    fn SecKeychainAddGenericPassword(
+        keychain: *const c_void,
+        service_len: u32,
+        service: *const u8,
+        account_len: u32,
+        account: *const u8,
+        password_len: u32,
+        password: *const c_void,
+        item: *mut SecKeychainItemRef,
+    ) -> OsStatus;
+    fn SecKeychainItemModifyAttributesAndData(
+        item: SecKeychainItemRef,
+        attributes: *const c_void,
+        length: u32,
+        data: *const c_void,
+    ) -> OsStatus;
+    fn SecKeychainItemDelete(item: SecKeychainItemRef) -> OsStatus;
+    fn SecKeychainItemFreeContent(attributes: *mut c_void, data: *mut c_void) -> OsStatus;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: *const c_void);
+}
+
+#[derive(Debug, Clone)]
+pub struct KeychainStore {
+    service: String,
+}
+
+impl KeychainStore {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// 查找条目；`with_data` 为假时只取条目引用。调用方负责释放返回的条目。
+    fn find(
+        &self,
+        name: &str,
+        with_data: bool,
+    ) -> Result<Option<(SecKeychainItemRef, Option<Vec<u8>>)>, SecretError> {
+        let mut len = 0u32;
+        let mut data: *mut c_void = ptr::null_mut();
+        let mut item: SecKeychainItemRef = ptr::null_mut();
+        let status = unsafe {
+            SecKeychainFindGenericPassword(
+                ptr::null(),
+                self.service.len() as u32,
+                self.service.as_ptr(),
+                name.len() as u32,
+                name.as_ptr(),
+                if with_data {
+                    &mut len as *mut u32
+                } else {
+                    ptr::null_mut()
+                },
+                if with_data {
+                    &mut data as *mut *mut c_void
+                } else {
+                    ptr::null_mut()
+                },
+                &mut item,
+            )
+        };
+        match status {
+            ERR_SEC_SUCCESS => {}
+            ERR_SEC_ITEM_NOT_FOUND => return Ok(None),
+            status => return Err(keychain_error("find", status)),
+        }
+        let bytes = (!data.is_null()).then(|| unsafe {
+            let bytes = std::slice::from_raw_parts(data as *const u8, len as usize).to_vec();
+            SecKeychainItemFreeContent(ptr::null_mut(), data);
+            bytes
+        });
+        Ok(Some((item, bytes)))
+    }
+}
+
+fn keychain_error(action: &str, status: OsStatus) -> SecretError {
+    SecretError::Backend(format!("keychain {action} failed with status {status}"))
+}
+
+fn release(item: SecKeychainItemRef) {
+    if !item.is_null() {
+        unsafe { CFRelease(item) };
+    }
+}
+
+impl SecretStore for KeychainStore {
+    fn backend(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, SecretError> {
+        let Some((item, data)) = self.find(name, true)? else {
+            return Ok(None);
+        };
+        release(item);
+        data.map(|bytes| {
+            String::from_utf8(bytes)
+                .map_err(|_| SecretError::Backend(format!("{name} is not valid UTF-8")))
+        })
+        .transpose()
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretError> {
+        if let Some((item, _)) = self.find(name, false)? {
+            let status = unsafe {
+                SecKeychainItemModifyAttributesAndData(
+                    item,
+                    ptr::null(),
+                    value.len() as u32,
+                    value.as_ptr() as *const c_void,
+                )
+            };
+            release(item);
+            return match status {
+                ERR_SEC_SUCCESS => Ok(()),
+                status => Err(keychain_error("update", status)),
+            };
+        }
+        let status = unsafe {
+            SecKeychainAddGenericPassword(
+                ptr::null(),
+                self.service.len() as u32,
+                self.service.as_ptr(),
+                name.len() as u32,
+                name.as_ptr(),
+                value.len() as u32,
+                value.as_ptr() as *const c_void,
+                ptr::null_mut(),
+            )
+        };
+        match status {
+            ERR_SEC_SUCCESS => Ok(()),
+            status => Err(keychain_error("add", status)),
+        }
+    }
+
+    fn delete(&self, name: &str) -> Result<bool, SecretError> {
+        let Some((item, _)) = self.find(name, false)? else {
+            return Ok(false);
+        };
+        let status = unsafe { SecKeychainItemDelete(item) };
+        release(item);
+        match status {
+            ERR_SEC_SUCCESS => Ok(true),
+            status => Err(keychain_error("delete", status)),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! 云端服务密钥的安全存储。
+//!
+//! 平台后端（macOS 钥匙串、Windows DPAPI、Linux libsecret）优先；平台后端不可用时
+//! 退回到数据目录下的加密文件。编排器在会话开始时按服务商从这里取 API Key，
+//! 不再要求把密钥放在环境变量里。每个工作区配置档使用独立的服务名，互不读取对方的条目。
+
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::session::workspace::DEFAULT_PROFILE;
+
+mod file;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+pub use file::{EncryptedFileStore, SECRETS_KEY_ENV};
+#[cfg(target_os = "linux")]
+pub use linux::LibsecretStore;
+#[cfg(target_os = "macos")]
+pub use macos::KeychainStore;
+#[cfg(target_os = "windows")]
+pub use windows::DpapiStore;
+
+/// 默认配置档在平台密钥库中的服务名。
+pub const SERVICE: &str = "flowwisper";
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("secret store is unavailable: {0}")]
+    Unavailable(String),
+    #[error("secret store failed: {0}")]
+    Backend(String),
+    #[error("secret store I/O failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// 以名称存取的密钥；名称如 `openai.api_key`，见 `LlmProvider::secret_name`。
+pub trait SecretStore: Send + Sync {
+    /// 后端名称，用于日志与自检。
+    fn backend(&self) -> &'static str;
+
+    fn get(&self, name: &str) -> Result<Option<String>, SecretError>;
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretError>;
+
+    /// 删除条目，返回条目此前是否存在。
+    fn delete(&self, name: &str) -> Result<bool, SecretError>;
+}
+
+/// 配置档在平台密钥库中的服务名：默认配置档沿用 [`SERVICE`]，其余为 `flowwisper.<配置档>`。
+pub fn service_name(profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        SERVICE.to_string()
+    } else {
+        format!("{SERVICE}.{profile}")
+    }
+}
+
+/// 配置档 `profile` 可用的密钥库；平台后端不可用时使用其数据目录 `dir` 下的加密文件。
+pub fn default_store(dir: &Path, profile: &str) -> Result<Arc<dyn SecretStore>, SecretError> {
+    if let Some(store) = platform_store(dir, profile) {
+        return Ok(store);
+    }
+    Ok(Arc::new(EncryptedFileStore::open(dir)?))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_store(_dir: &Path, profile: &str) -> Option<Arc<dyn SecretStore>> {
+    Some(Arc::new(KeychainStore::new(service_name(profile))))
+}
+
+/// DPAPI 条目保存在数据目录下，已随配置档隔离。
+#[cfg(target_os = "windows")]
+fn platform_store(dir: &Path, _profile: &str) -> Option<Arc<dyn SecretStore>> {
+    Some(Arc::new(DpapiStore::new(dir)))
+}
+
+#[cfg(target_os = "linux")]
+fn platform_store(_dir: &Path, profile: &str) -> Option<Arc<dyn SecretStore>> {
+    LibsecretStore::is_available()
+        .then(|| Arc::new(LibsecretStore::new(service_name(profile))) as Arc<dyn SecretStore>)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn platform_store(_dir: &Path, _profile: &str) -> Option<Arc<dyn SecretStore>> {
+    None
+}
@@ -0,0 +1,189 @@
+//! Windows 后端：DPAPI 以当前用户身份加密，密文保存在数据目录下，条目名作为附加熵。
+
+use std::collections::BTreeMap;
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+use super::file::{read_map, write_map};
+use super::{SecretError, SecretStore};
+
+const STORE_FILE: &str = "secrets.dpapi.json";
+const CRYPTPROTECT_UI_FORBIDDEN: u32 = 0x1;
+
+#[repr(C)]
+struct DataBlob {
+    len: u32,
+    data: *mut u8,
+}
+
+impl DataBlob {
+    fn borrowed(bytes: &[u8]) -> Self {
+        Self {
+            len: bytes.len() as u32,
+            data: bytes.as_ptr() as *mut u8,
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            len: 0,
+            data: ptr::null_mut(),
+        }
+    }
+
+    /// 取出系统分配的输出缓冲区并释放。
+    fn take(self) -> Vec<u8> {
+        if self.data.is_null() {
+            return Vec::new();
+        }
+        unsafe {
+            let bytes = std::slice::from_raw_parts(self.data, self.len as usize).to_vec();
+            LocalFree(self.data as *mut c_void);
+            bytes
+        }
+    }
+}
+
+#[link(name = "crypt32")]
+extern "system" {
+    fn CryptProtectData(
+        data_in: *const DataBlob,
+        description: *const u16,
+        entropy: *const DataBlob,
+        reserved: *mut c_void,
+        prompt: *const c_void,
+        flags: u32,
+        data_out: *mut DataBlob,
+    ) -> i32;
+    fn CryptUnprotectData(
+        data_in: *const DataBlob,
+        description: *mut *mut u16,
+        entropy: *const DataBlob,
+        reserved: *mut c_void,
+        prompt: *const c_void,
+        flags: u32,
+        data_out: *mut DataBlob,
+    ) -> i32;
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn LocalFree(memory: *mut c_void) -> *mut c_void;
+    fn GetLastError() -> u32;
+}
+
+pub struct DpapiStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl DpapiStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(STORE_FILE),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+fn protect(name: &str, plaintext: &[u8]) -> Result<Vec<u8>, SecretError> {
+    let input = DataBlob::borrowed(plaintext);
+    let entropy = DataBlob::borrowed(name.as_bytes());
+    let mut output = DataBlob::empty();
+    let ok = unsafe {
+        CryptProtectData(
+            &input,
+            ptr::null(),
+            &entropy,
+            ptr::null_mut(),
+            ptr::null(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    };
+    if ok == 0 {
+        let code = unsafe { GetLastError() };
+        return Err(SecretError::Backend(format!(
+            "CryptProtectData failed with error {code}"
+        )));
+    }
+    Ok(output.take())
+}
+
+fn unprotect(name: &str, ciphertext: &[u8]) -> Result<Vec<u8>, SecretError> {
+    let input = DataBlob::borrowed(ciphertext);
+    let entropy = DataBlob::borrowed(name.as_bytes());
+    let mut output = DataBlob::empty();
+    let ok = unsafe {
+        CryptUnprotectData(
+            &input,
+            ptr::null_mut(),
+            &entropy,
+            ptr::null_mut(),
+            ptr::null(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    };
+    if ok == 0 {
+        let code = unsafe { GetLastError() };
+        return Err(SecretError::Backend(format!(
+            "CryptUnprotectData failed with error {code}"
+        )));
+    }
+    Ok(output.take())
+}
+
+impl SecretStore for DpapiStore {
+    fn backend(&self) -> &'static str {
+        "dpapi"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, SecretError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let entries: BTreeMap<String, String> = read_map(&self.path)?;
+        let Some(encoded) = entries.get(name) else {
+            return Ok(None);
+        };
+        let ciphertext = BASE64
+            .decode(encoded)
+            .map_err(|err| SecretError::Backend(format!("corrupt entry {name}: {err}")))?;
+        String::from_utf8(unprotect(name, &ciphertext)?)
+            .map(Some)
+            .map_err(|_| SecretError::Backend(format!("{name} is not valid UTF-8")))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut entries: BTreeMap<String, String> = read_map(&self.path)?;
+        let ciphertext = protect(name, value.as_bytes())?;
+        entries.insert(name.to_string(), BASE64.encode(ciphertext));
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_map(&self.path, &entries)
+    }
+
+    fn delete(&self, name: &str) -> Result<bool, SecretError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut entries: BTreeMap<String, String> = read_map(&self.path)?;
+        if entries.remove(name).is_none() {
+            return Ok(false);
+        }
+        write_map(&self.path, &entries)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,168 @@
+//! 发布前的手动改稿：转写结束后、发布之前，界面可按句提交用户的修改，发布时以改后的文本为准，
+//! 修改记录写入历史元数据。
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+
+use crate::session::recovery::RecoverySnapshot;
+
+/// 历史元数据中保存修改记录的键。
+pub const AMENDMENT_METADATA_KEY: &str = "transcriptEdits";
+
+/// 对单句的修改；`text` 为空表示删除该句。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentenceEdit {
+    pub sentence_id: u64,
+    pub text: String,
+}
+
+/// 一次会话累计的修改，同一句以最后一次提交为准。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptAmendment {
+    pub session_id: String,
+    /// 句子 ID 到（原文, 改后文本）。
+    edits: BTreeMap<u64, (String, String)>,
+    /// 应用修改后的全文。
+    pub text: String,
+    pub amended_at_ms: i64,
+}
+
+impl TranscriptAmendment {
+    /// 在进行中会话的句子上叠加 `edits`；`previous` 为此前提交过的修改。
+    pub fn apply(
+        snapshot: &RecoverySnapshot,
+        previous: Option<&TranscriptAmendment>,
+        edits: &[SentenceEdit],
+        now_ms: i64,
+    ) -> Result<Self> {
+        if edits.is_empty() {
+            return Err(anyhow!("no transcript edits submitted"));
+        }
+        let mut merged = previous
+            .map(|amendment| amendment.edits.clone())
+            .unwrap_or_default();
+        for edit in edits {
+            let sentence = snapshot
+                .sentences
+                .iter()
+                .find(|sentence| sentence.sentence_id == edit.sentence_id)
+                .ok_or_else(|| anyhow!("sentence {} not found in session", edit.sentence_id))?;
+            let original = sentence
+                .polished
+                .clone()
+                .unwrap_or_else(|| sentence.raw.clone());
+            merged.insert(edit.sentence_id, (original, edit.text.trim().to_string()));
+        }
+
+        let text = snapshot
+            .sentences
+            .iter()
+            .map(|sentence| match merged.get(&sentence.sentence_id) {
+                Some((_, amended)) => amended.as_str(),
+                None => sentence.polished.as_deref().unwrap_or(&sentence.raw),
+            })
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(Self {
+            session_id: snapshot.session_id.clone(),
+            edits: merged,
+            text,
+            amended_at_ms: now_ms,
+        })
+    }
+
+    /// 实际改动过的句数（改回原文的不计）。
+    pub fn edited_sentences(&self) -> usize {
+        self.edits
+            .values()
+            .filter(|(original, amended)| original != amended)
+            .count()
+    }
+
+    /// 写入历史元数据，保留宿主已有的字段。
+    pub fn annotate(&self, metadata: &mut JsonValue) {
+        let edits: Vec<JsonValue> = self
+            .edits
+            .iter()
+            .filter(|(_, (original, amended))| original != amended)
+            .map(|(sentence_id, (original, amended))| {
+                json!({
+                    "sentenceId": sentence_id,
+                    "original": original,
+                    "amended": amended,
+                })
+            })
+            .collect();
+        if !metadata.is_object() {
+            *metadata = json!({});
+        }
+        metadata[AMENDMENT_METADATA_KEY] = json!({
+            "amendedAtMs": self.amended_at_ms,
+            "edits": edits,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::recovery::RecoveredSentence;
+
+    fn snapshot() -> RecoverySnapshot {
+        let sentence = |sentence_id, raw: &str, polished: Option<&str>| RecoveredSentence {
+            sentence_id,
+            raw: raw.into(),
+            polished: polished.map(Into::into),
+        };
+        RecoverySnapshot {
+            session_id: "session-amend".into(),
+            started_at_ms: 0,
+            captured_at_ms: None,
+            reason: None,
+            sentences: vec![
+                sentence(1, "hello team", Some("Hello team.")),
+                sentence(2, "ship on friday", Some("Ship on Friday.")),
+                sentence(3, "um", None),
+            ],
+            frame_cursor: 0,
+        }
+    }
+
+    #[test]
+    fn merges_sentence_edits_into_transcript() {
+        let snapshot = snapshot();
+        let edit = |sentence_id, text: &str| SentenceEdit {
+            sentence_id,
+            text: text.into(),
+        };
+        let first =
+            TranscriptAmendment::apply(&snapshot, None, &[edit(2, "Ship on Monday.")], 10).unwrap();
+        assert_eq!(first.text, "Hello team. Ship on Monday. um");
+
+        let second = TranscriptAmendment::apply(
+            &snapshot,
+            Some(&first),
+            &[edit(3, ""), edit(1, "Hello team.")],
+            20,
+        )
+        .unwrap();
+        assert_eq!(second.text, "Hello team. Ship on Monday.");
+        assert_eq!(second.edited_sentences(), 2);
+
+        let mut metadata = json!({ "origin": "desktop" });
+        second.annotate(&mut metadata);
+        assert_eq!(metadata["origin"], "desktop");
+        let edits = &metadata[AMENDMENT_METADATA_KEY]["edits"];
+        assert_eq!(edits.as_array().unwrap().len(), 2);
+        assert_eq!(edits[0]["original"], "Ship on Friday.");
+        assert_eq!(edits[0]["amended"], "Ship on Monday.");
+
+        assert!(TranscriptAmendment::apply(&snapshot, None, &[edit(9, "x")], 0).is_err());
+        assert!(TranscriptAmendment::apply(&snapshot, None, &[], 0).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! 使用统计：基于历史会话汇总每日听写字数、平均会话时长、常用目标应用、准确性标记比例
+//! 与语速（WPM）走势，供桌面端仪表盘展示。
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::orchestrator::stabilizer::tokenize;
+use crate::orchestrator::BudgetReport;
+use crate::session::history::export::format_timestamp;
+use crate::session::history::{AccuracyFlag, HistoryEntry, MIN_SPEED_SAMPLE_MS};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1_000;
+/// 常用应用榜单的长度。
+const TOP_APPS: usize = 5;
+
+/// 统计区间 `[from_ms, to_ms)`；按天分组时使用宿主提供的本地时区偏移。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRange {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+impl UsageRange {
+    /// 截至 `now_ms` 所在本地日（含）的最近 `days` 天。
+    pub fn last_days(days: u32, now_ms: i64, utc_offset_minutes: i32) -> Self {
+        let offset_ms = i64::from(utc_offset_minutes) * 60_000;
+        let today = (now_ms + offset_ms).div_euclid(DAY_MS) * DAY_MS - offset_ms;
+        Self {
+            from_ms: today - i64::from(days.saturating_sub(1)) * DAY_MS,
+            to_ms: today + DAY_MS,
+            utc_offset_minutes,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.from_ms >= self.to_ms {
+            return Err(anyhow!("usage range must end after it starts"));
+        }
+        if self.utc_offset_minutes.abs() > 14 * 60 {
+            return Err(anyhow!("utc offset out of range"));
+        }
+        Ok(())
+    }
+
+    fn offset_ms(&self) -> i64 {
+        i64::from(self.utc_offset_minutes) * 60_000
+    }
+
+    fn day_start(&self, timestamp_ms: i64) -> i64 {
+        let offset_ms = self.offset_ms();
+        (timestamp_ms + offset_ms).div_euclid(DAY_MS) * DAY_MS - offset_ms
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// 本地日期，`YYYY-MM-DD`。
+    pub date: String,
+    pub day_start_ms: i64,
+    pub sessions: usize,
+    pub words: usize,
+    pub duration_ms: i64,
+    /// 当天的平均语速；没有可计算的会话时为空。
+    pub words_per_minute: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUsage {
+    pub app_identifier: String,
+    pub sessions: usize,
+    pub words: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccuracyBreakdown {
+    pub accurate: usize,
+    pub inaccurate_raw: usize,
+    pub inaccurate_polished: usize,
+    pub unknown: usize,
+    /// 已标记会话中“准确”的比例；尚无标记时为空。
+    pub accurate_ratio: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    pub range: UsageRange,
+    pub total_sessions: usize,
+    pub total_words: usize,
+    pub total_duration_ms: i64,
+    pub average_session_ms: Option<i64>,
+    pub average_words_per_minute: Option<f64>,
+    /// 区间内每一天，包括没有会话的日子。
+    pub daily: Vec<DailyUsage>,
+    pub top_apps: Vec<AppUsage>,
+    pub accuracy: AccuracyBreakdown,
+    /// 当前日、月的云端用量与上限，由会话管理器填入。
+    pub cloud_budget: Option<BudgetReport>,
+}
+
+/// 字数：汉字与假名逐字计数，其他文字按词计数，标点不计。
+pub fn count_words(text: &str) -> usize {
+    tokenize(text)
+        .into_iter()
+        .filter(|token| token.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+fn entry_words(entry: &HistoryEntry) -> usize {
+    if entry.polished_transcript.trim().is_empty() {
+        count_words(&entry.raw_transcript)
+    } else {
+        count_words(&entry.polished_transcript)
+    }
+}
+
+/// 语速的分子分母，只累计时长足够的会话，避免误触产生的离群值。
+#[derive(Default)]
+struct Pace {
+    words: usize,
+    duration_ms: i64,
+}
+
+impl Pace {
+    fn add(&mut self, words: usize, duration_ms: i64) {
+        if duration_ms >= MIN_SPEED_SAMPLE_MS {
+            self.words += words;
+            self.duration_ms += duration_ms;
+        }
+    }
+
+    fn words_per_minute(&self) -> Option<f64> {
+        (self.duration_ms > 0).then(|| self.words as f64 * 60_000.0 / self.duration_ms as f64)
+    }
+}
+
+/// 语速样本：优先使用 VAD 测得的有声时长，旧会话退回整段会话时长。
+fn pace_sample(entry: &HistoryEntry, words: usize, duration_ms: i64) -> (usize, i64) {
+    match &entry.speed {
+        Some(speed) => (speed.words, speed.speech_duration_ms),
+        None => (words, duration_ms),
+    }
+}
+
+fn ratio(part: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| part as f64 / total as f64)
+}
+
+/// 汇总区间内完成的会话；区间外的条目会被忽略。
+pub fn compute_usage(range: UsageRange, entries: &[HistoryEntry]) -> UsageStats {
+    let mut days: BTreeMap<i64, (DailyUsage, Pace)> = BTreeMap::new();
+    let mut day = range.day_start(range.from_ms);
+    while day < range.to_ms {
+        let usage = DailyUsage {
+            date: format_timestamp(day + range.offset_ms())[..10].to_string(),
+            day_start_ms: day,
+            ..DailyUsage::default()
+        };
+        days.insert(day, (usage, Pace::default()));
+        day += DAY_MS;
+    }
+
+    let mut apps: HashMap<String, AppUsage> = HashMap::new();
+    let mut accuracy = AccuracyBreakdown::default();
+    let mut pace = Pace::default();
+    let mut total_sessions = 0;
+    let mut total_words = 0;
+    let mut total_duration_ms = 0;
+
+    for entry in entries.iter().filter(|entry| {
+        entry.completed_at_ms >= range.from_ms && entry.completed_at_ms < range.to_ms
+    }) {
+        let words = entry_words(entry);
+        let duration_ms = entry.duration_ms.max(0);
+        total_sessions += 1;
+        total_words += words;
+        total_duration_ms += duration_ms;
+        let (pace_words, pace_ms) = pace_sample(entry, words, duration_ms);
+        pace.add(pace_words, pace_ms);
+
+        if let Some((usage, day_pace)) = days.get_mut(&range.day_start(entry.completed_at_ms)) {
+            usage.sessions += 1;
+            usage.words += words;
+            usage.duration_ms += duration_ms;
+            day_pace.add(pace_words, pace_ms);
+        }
+        if let Some(app) = entry
+            .app_identifier
+            .as_deref()
+            .filter(|app| !app.is_empty())
+        {
+            let usage = apps.entry(app.to_string()).or_insert_with(|| AppUsage {
+                app_identifier: app.to_string(),
+                sessions: 0,
+                words: 0,
+            });
+            usage.sessions += 1;
+            usage.words += words;
+        }
+        match entry.accuracy_flag {
+            AccuracyFlag::Accurate => accuracy.accurate += 1,
+            AccuracyFlag::InaccurateRaw => accuracy.inaccurate_raw += 1,
+            AccuracyFlag::InaccuratePolished => accuracy.inaccurate_polished += 1,
+            AccuracyFlag::Unknown => accuracy.unknown += 1,
+        }
+    }
+
+    accuracy.accurate_ratio = ratio(
+        accuracy.accurate,
+        accuracy.accurate + accuracy.inaccurate_raw + accuracy.inaccurate_polished,
+    );
+    let mut top_apps: Vec<AppUsage> = apps.into_values().collect();
+    top_apps.sort_by(|a, b| {
+        b.sessions
+            .cmp(&a.sessions)
+            .then(b.words.cmp(&a.words))
+            .then(a.app_identifier.cmp(&b.app_identifier))
+    });
+    top_apps.truncate(TOP_APPS);
+
+    UsageStats {
+        range,
+        total_sessions,
+        total_words,
+        total_duration_ms,
+        average_session_ms: (total_sessions > 0).then(|| total_duration_ms / total_sessions as i64),
+        average_words_per_minute: pace.words_per_minute(),
+        daily: days
+            .into_values()
+            .map(|(mut usage, pace)| {
+                usage.words_per_minute = pace.words_per_minute();
+                usage
+            })
+            .collect(),
+        top_apps,
+        cloud_budget: None,
+        accuracy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::history::DictationSpeed;
+    use std::time::Duration;
+
+    fn entry(completed_at_ms: i64, duration_ms: i64, app: &str, text: &str) -> HistoryEntry {
+        HistoryEntry {
+            session_id: format!("usage-{completed_at_ms}"),
+            started_at_ms: completed_at_ms - duration_ms,
+            completed_at_ms,
+            duration_ms,
+            locale: None,
+            app_identifier: Some(app.into()),
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: text.into(),
+            polished_transcript: text.into(),
+            preview: text.into(),
+            accuracy_flag: AccuracyFlag::Unknown,
+            accuracy_remarks: None,
+            post_actions: Vec::new(),
+            metadata: serde_json::json!({}),
+            pinned: false,
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+            search_hit: None,
+        }
+    }
+
+    #[test]
+    fn counts_words_across_scripts() {
+        assert_eq!(count_words("Ship the v2 build, today!"), 5);
+        assert_eq!(count_words("发布新版本 ok"), 6);
+        assert_eq!(count_words(" … "), 0);
+    }
+
+    #[test]
+    fn aggregates_usage_per_local_day() {
+        // 2024-01-01T00:00:00Z, viewed from UTC+8.
+        let day0 = 1_704_067_200_000 - 8 * 60 * 60 * 1_000;
+        let range = UsageRange::last_days(3, day0 + 2 * DAY_MS + 1_000, 480);
+        assert_eq!(range.from_ms, day0);
+        range.validate().unwrap();
+
+        let mut flagged = entry(day0 + 1_000, 60_000, "com.slack", "one two three four");
+        flagged.accuracy_flag = AccuracyFlag::Accurate;
+        // Speech time measured by the VAD takes precedence over session length.
+        flagged.speed = Some(DictationSpeed::new(4, Duration::from_secs(30)));
+        let mut wrong = entry(day0 + 2_000, 30_000, "com.slack", "one two");
+        wrong.accuracy_flag = AccuracyFlag::InaccurateRaw;
+        let entries = vec![
+            flagged,
+            wrong,
+            entry(day0 + 2 * DAY_MS, 500, "com.notion", "hi"),
+            entry(day0 - 1, 60_000, "com.slack", "outside the range"),
+        ];
+        let stats = compute_usage(range, &entries);
+
+        assert_eq!(stats.total_sessions, 3);
+        assert_eq!(stats.total_words, 7);
+        assert_eq!(stats.average_session_ms, Some(30_166));
+        assert_eq!(stats.average_words_per_minute, Some(6.0));
+        let dates: Vec<_> = stats.daily.iter().map(|day| day.date.as_str()).collect();
+        assert_eq!(dates, ["2024-01-01", "2024-01-02", "2024-01-03"]);
+        assert_eq!(stats.daily[0].words, 6);
+        assert_eq!(stats.daily[1].sessions, 0);
+        assert_eq!(stats.daily[2].words_per_minute, None);
+        assert_eq!(stats.top_apps[0].app_identifier, "com.slack");
+        assert_eq!(stats.top_apps[0].sessions, 2);
+        assert_eq!(stats.accuracy.accurate_ratio, Some(0.5));
+        assert_eq!(stats.accuracy.unknown, 1);
+    }
+}
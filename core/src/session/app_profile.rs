@@ -0,0 +1,177 @@
+//! 按应用配置的润色与发布偏好，以 `FocusWindowContext::app_identifier` 为键，
+//! 可进一步限定到某类输入框。
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::orchestrator::{PolishProfile, Vocabulary};
+use crate::session::publisher::{
+    FallbackStrategy, FieldRole, FocusWindowContext, InsertionMethod, OutputFormat, PublishRequest,
+};
+
+/// 单个应用的偏好；为空的字段沿用会话或请求自带的设置。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppProfile {
+    pub app_identifier: String,
+    /// 仅对该类输入框生效；为空时适用于应用内所有输入框。
+    #[serde(default)]
+    pub field_role: Option<FieldRole>,
+    #[serde(default)]
+    pub fallback: Option<FallbackStrategy>,
+    #[serde(default)]
+    pub polish_profile: Option<PolishProfile>,
+    /// 在该应用中启用的词表子集；为空时使用完整词表。
+    #[serde(default)]
+    pub vocabulary: Vec<String>,
+    #[serde(default)]
+    pub insertion: Option<InsertionMethod>,
+    /// 插入时的标记格式；为空时按焦点推断。
+    #[serde(default)]
+    pub output_format: Option<OutputFormat>,
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+impl AppProfile {
+    pub fn new(app_identifier: impl Into<String>) -> Self {
+        Self {
+            app_identifier: app_identifier.into().trim().to_string(),
+            field_role: None,
+            fallback: None,
+            polish_profile: None,
+            vocabulary: Vec::new(),
+            insertion: None,
+            output_format: None,
+            updated_at_ms: 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.app_identifier.trim().is_empty() {
+            return Err(anyhow!("app profile identifier must not be empty"));
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, focus: &FocusWindowContext) -> bool {
+        let field_matches = self.field_role.is_none() || self.field_role == focus.field_role;
+        field_matches
+            && focus
+                .app_identifier
+                .as_deref()
+                .is_some_and(|app| app.eq_ignore_ascii_case(&self.app_identifier))
+    }
+
+    /// 用配置的回退策略与插入方式覆盖请求中的默认值；输出格式由会话在替换规则之后应用。
+    pub fn apply_to_request(&self, request: &mut PublishRequest) {
+        if let Some(fallback) = &self.fallback {
+            request.fallback = fallback.clone();
+        }
+        if let Some(insertion) = self.insertion {
+            request.insertion = insertion;
+        }
+    }
+
+    /// 从完整词表中挑出该应用启用的词条；未配置子集时原样返回。
+    pub fn vocabulary_subset(&self, vocabulary: &Vocabulary) -> Vocabulary {
+        if self.vocabulary.is_empty() {
+            return vocabulary.clone();
+        }
+        Vocabulary::new(
+            vocabulary
+                .terms()
+                .iter()
+                .filter(|term| {
+                    self.vocabulary
+                        .iter()
+                        .any(|wanted| wanted.trim().eq_ignore_ascii_case(&term.term))
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+/// 按焦点查找应用偏好，应用标识忽略大小写；限定输入框类型的偏好优先于应用级偏好。
+pub fn resolve_app_profile<'a>(
+    profiles: &'a [AppProfile],
+    focus: &FocusWindowContext,
+) -> Option<&'a AppProfile> {
+    let mut matching = profiles.iter().filter(|profile| profile.matches(focus));
+    let first = matching.next()?;
+    if first.field_role.is_some() {
+        return Some(first);
+    }
+    matching
+        .find(|profile| profile.field_role.is_some())
+        .or(Some(first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{VocabularyKind, VocabularyTerm};
+
+    #[test]
+    fn overrides_request_and_filters_vocabulary() {
+        let mut profile = AppProfile::new("com.microsoft.VSCode");
+        profile.fallback = Some(FallbackStrategy::NotifyOnly);
+        profile.insertion = Some(InsertionMethod::Keystrokes);
+        profile.vocabulary = vec!["tokio".into()];
+
+        let profiles = vec![profile];
+        let focus = FocusWindowContext::from_app_identifier("com.microsoft.vscode");
+        let resolved = resolve_app_profile(&profiles, &focus).expect("profile matches");
+        assert!(resolve_app_profile(&profiles, &FocusWindowContext::default()).is_none());
+
+        let mut request = PublishRequest {
+            transcript: "hello".into(),
+            focus,
+            fallback: FallbackStrategy::ClipboardCopy,
+            insertion: InsertionMethod::Auto,
+            strategy: None,
+            html: None,
+        };
+        resolved.apply_to_request(&mut request);
+        assert_eq!(request.fallback, FallbackStrategy::NotifyOnly);
+        assert_eq!(request.insertion, InsertionMethod::Keystrokes);
+
+        let vocabulary = Vocabulary::new(vec![
+            VocabularyTerm::new("Tokio", VocabularyKind::Term),
+            VocabularyTerm::new("Flowwisper", VocabularyKind::Name),
+        ]);
+        let subset = resolved.vocabulary_subset(&vocabulary);
+        assert_eq!(subset.terms().len(), 1);
+        assert_eq!(subset.terms()[0].term, "Tokio");
+        assert_eq!(
+            AppProfile::new("other").vocabulary_subset(&vocabulary),
+            vocabulary
+        );
+        assert!(AppProfile::new("  ").validate().is_err());
+    }
+
+    #[test]
+    fn field_specific_profiles_take_precedence() {
+        let general = AppProfile::new("com.apple.Safari");
+        let mut search = AppProfile::new("com.apple.Safari");
+        search.field_role = Some(FieldRole::SearchBox);
+        search.insertion = Some(InsertionMethod::Keystrokes);
+        let profiles = vec![general, search];
+
+        let mut focus = FocusWindowContext::from_app_identifier("com.apple.safari");
+        assert_eq!(
+            resolve_app_profile(&profiles, &focus).unwrap().field_role,
+            None
+        );
+        focus.field_role = Some(FieldRole::SearchBox);
+        assert_eq!(
+            resolve_app_profile(&profiles, &focus).unwrap().insertion,
+            Some(InsertionMethod::Keystrokes)
+        );
+        assert!(!profiles[1].matches(&FocusWindowContext {
+            field_role: Some(FieldRole::TextField),
+            ..FocusWindowContext::from_app_identifier("com.apple.Safari")
+        }));
+    }
+}
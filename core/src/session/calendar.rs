@@ -0,0 +1,689 @@
+//! 日历集成：会话开始时查找正在进行的日历事件，把会议标题与参会人写入历史元数据与标签，
+//! 便于按会议检索历史。提供 ICS、Microsoft Graph 与 Google Calendar 三种数据源。
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use super::history::export::format_timestamp;
+use super::history::SessionSnapshot;
+use super::SessionManager;
+
+/// 历史记录元数据中保存日历事件的字段。
+pub const CALENDAR_METADATA_KEY: &str = "calendarEvent";
+/// 会议标题标签前缀，例如 `meeting:Weekly sync`。
+pub const MEETING_TAG_PREFIX: &str = "meeting:";
+/// 参会人标签前缀，值为邮箱，缺失时为显示名。
+pub const ATTENDEE_TAG_PREFIX: &str = "attendee:";
+/// 会前提前开始录音也视为该会议。
+pub const EARLY_JOIN_MS: i64 = 5 * 60 * 1_000;
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 日历事件的参会人。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarAttendee {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+impl CalendarAttendee {
+    fn label(&self) -> Option<&str> {
+        self.email
+            .as_deref()
+            .or(self.name.as_deref())
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+    }
+}
+
+/// 会话开始时匹配到的日历事件。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    pub title: String,
+    pub starts_at_ms: i64,
+    pub ends_at_ms: i64,
+    #[serde(default)]
+    pub attendees: Vec<CalendarAttendee>,
+    #[serde(default)]
+    pub location: Option<String>,
+    /// 提供该事件的数据源名称。
+    #[serde(default)]
+    pub provider: String,
+}
+
+impl CalendarEvent {
+    /// 事件覆盖 `at_ms`，或将在 [`EARLY_JOIN_MS`] 内开始。
+    pub fn is_current(&self, at_ms: i64) -> bool {
+        self.starts_at_ms - EARLY_JOIN_MS <= at_ms && at_ms < self.ends_at_ms
+    }
+
+    /// 会议标题与去重后的参会人标签。
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = Vec::new();
+        let title = self.title.trim();
+        if !title.is_empty() {
+            tags.push(format!("{MEETING_TAG_PREFIX}{title}"));
+        }
+        for label in self.attendees.iter().filter_map(CalendarAttendee::label) {
+            let tag = format!("{ATTENDEE_TAG_PREFIX}{}", label.to_lowercase());
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// 在保留既有字段的前提下写入元数据；元数据不是对象时改为对象。
+    pub fn annotate(&self, metadata: &mut Value) {
+        if !metadata.is_object() {
+            *metadata = json!({});
+        }
+        metadata[CALENDAR_METADATA_KEY] = json!(self);
+    }
+}
+
+/// 日历数据源；查询会阻塞，调用方在阻塞线程池中执行。
+pub trait CalendarProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// 与 `[from_ms, to_ms)` 相交的事件。
+    fn events_between(&self, from_ms: i64, to_ms: i64) -> Result<Vec<CalendarEvent>>;
+
+    /// `at_ms` 时正在进行（或即将开始）的事件；有多个时取最晚开始且非全天的一个。
+    fn current_event(&self, at_ms: i64) -> Result<Option<CalendarEvent>> {
+        let events = self.events_between(at_ms - EARLY_JOIN_MS, at_ms + EARLY_JOIN_MS)?;
+        Ok(pick_current(events, at_ms))
+    }
+}
+
+fn pick_current(events: Vec<CalendarEvent>, at_ms: i64) -> Option<CalendarEvent> {
+    const DAY_MS: i64 = 24 * 60 * 60 * 1_000;
+    events
+        .into_iter()
+        .filter(|event| event.is_current(at_ms))
+        .max_by_key(|event| {
+            (
+                event.ends_at_ms - event.starts_at_ms < DAY_MS,
+                event.starts_at_ms,
+            )
+        })
+}
+
+/// ICS 日历来源：本地文件或订阅地址。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcsSource {
+    File(PathBuf),
+    Url(String),
+}
+
+/// 读取 iCalendar 文件中的 `VEVENT`。带 `TZID` 的时间按 UTC 解释。
+#[derive(Debug, Clone)]
+pub struct IcsCalendarProvider {
+    source: IcsSource,
+}
+
+impl IcsCalendarProvider {
+    pub fn new(source: IcsSource) -> Self {
+        Self { source }
+    }
+
+    fn load(&self) -> Result<String> {
+        match &self.source {
+            IcsSource::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read calendar {}", path.display())),
+            IcsSource::Url(url) => ureq::get(url)
+                .timeout(LOOKUP_TIMEOUT)
+                .call()
+                .map_err(|err| anyhow!("failed to fetch calendar {url}: {err}"))?
+                .into_string()
+                .context("failed to read calendar response"),
+        }
+    }
+}
+
+impl CalendarProvider for IcsCalendarProvider {
+    fn name(&self) -> &'static str {
+        "ics"
+    }
+
+    fn events_between(&self, from_ms: i64, to_ms: i64) -> Result<Vec<CalendarEvent>> {
+        Ok(parse_ics(&self.load()?)
+            .into_iter()
+            .filter(|event| event.starts_at_ms < to_ms && event.ends_at_ms > from_ms)
+            .collect())
+    }
+}
+
+/// 解析 iCalendar 文本；缺少开始时间的事件被跳过，缺少结束时间时按一小时计。
+pub fn parse_ics(text: &str) -> Vec<CalendarEvent> {
+    // 以空白开头的行是上一行的折行。
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines
+                .last_mut()
+                .unwrap()
+                .push_str(rest.trim_end_matches('\r')),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<IcsEvent> = None;
+    for line in &lines {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = head.split(';');
+        let name = params.next().unwrap_or_default().to_ascii_uppercase();
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(IcsEvent::default()),
+            ("END", "VEVENT") => {
+                if let Some(event) = current.take().and_then(IcsEvent::finish) {
+                    events.push(event);
+                }
+            }
+            _ => {
+                let Some(event) = current.as_mut() else {
+                    continue;
+                };
+                match name.as_str() {
+                    "SUMMARY" => event.title = unescape_ics(value),
+                    "LOCATION" => event.location = Some(unescape_ics(value)),
+                    "DTSTART" => event.starts_at_ms = parse_ics_time(value),
+                    "DTEND" => event.ends_at_ms = parse_ics_time(value),
+                    "ATTENDEE" => {
+                        let name = params.find_map(|param| {
+                            param
+                                .strip_prefix("CN=")
+                                .map(|cn| cn.trim_matches('"').to_string())
+                        });
+                        let email = value
+                            .strip_prefix("mailto:")
+                            .or_else(|| value.strip_prefix("MAILTO:"))
+                            .map(str::to_string);
+                        event.attendees.push(CalendarAttendee { name, email });
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    events
+}
+
+#[derive(Default)]
+struct IcsEvent {
+    title: String,
+    location: Option<String>,
+    starts_at_ms: Option<i64>,
+    ends_at_ms: Option<i64>,
+    attendees: Vec<CalendarAttendee>,
+}
+
+impl IcsEvent {
+    fn finish(self) -> Option<CalendarEvent> {
+        let starts_at_ms = self.starts_at_ms?;
+        Some(CalendarEvent {
+            title: self.title,
+            starts_at_ms,
+            ends_at_ms: self.ends_at_ms.unwrap_or(starts_at_ms + 60 * 60 * 1_000),
+            attendees: self.attendees,
+            location: self.location,
+            provider: "ics".into(),
+        })
+    }
+}
+
+fn unescape_ics(value: &str) -> String {
+    value
+        .replace("\\n", "\n")
+        .replace("\\N", "\n")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// `20261017T090000Z`、`20261017T090000` 或全天的 `20261017`。
+fn parse_ics_time(value: &str) -> Option<i64> {
+    let value = value.trim().trim_end_matches('Z');
+    let (date, time) = value.split_once('T').unwrap_or((value, "000000"));
+    if date.len() != 8 || time.len() < 6 {
+        return None;
+    }
+    let field = |text: &str| text.parse::<i64>().ok();
+    civil_to_ms(
+        field(&date[..4])?,
+        field(&date[4..6])?,
+        field(&date[6..8])?,
+        field(&time[..2])? * 3_600 + field(&time[2..4])? * 60 + field(&time[4..6])?,
+    )
+}
+
+/// 解析 RFC 3339 时间或 `YYYY-MM-DD` 日期；无时区后缀时按 UTC 解释。
+pub(crate) fn parse_rfc3339_ms(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (date, rest) = value.split_once('T').unwrap_or((value, ""));
+    let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if rest.is_empty() {
+        return civil_to_ms(year, month, day, 0);
+    }
+
+    let (clock, offset_secs) = if let Some(clock) = rest.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else if let Some(index) = rest.rfind(['+', '-']) {
+        let (clock, offset) = rest.split_at(index);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let offset = hours.parse::<i64>().ok()? * 3_600 + minutes.parse::<i64>().ok()? * 60;
+        (clock, sign * offset)
+    } else {
+        (rest, 0)
+    };
+    let (clock, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+    let mut fields = clock.split(':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute) = (fields.next()??, fields.next()??);
+    let second = fields.next().flatten().unwrap_or(0);
+    let millis = format!("{fraction:0<3}")[..3].parse::<i64>().unwrap_or(0);
+    let ms = civil_to_ms(year, month, day, hour * 3_600 + minute * 60 + second)?;
+    Some(ms + millis - offset_secs * 1_000)
+}
+
+/// 公历日期转纪元毫秒（days-from-civil 算法）。
+fn civil_to_ms(year: i64, month: i64, day: i64, secs_of_day: i64) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some((days * 86_400 + secs_of_day) * 1_000)
+}
+
+/// 以宿主提供的 OAuth 访问令牌调用云端日历；令牌过期后由宿主通过
+/// `set_access_token` 替换。
+#[derive(Debug)]
+struct BearerToken(RwLock<String>);
+
+impl BearerToken {
+    fn new(token: String) -> Self {
+        Self(RwLock::new(token))
+    }
+
+    fn set(&self, token: String) {
+        *self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = token;
+    }
+
+    fn header(&self) -> String {
+        format!(
+            "Bearer {}",
+            self.0
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+        )
+    }
+}
+
+fn get_json(request: ureq::Request, token: &BearerToken, provider: &str) -> Result<Value> {
+    let body = request
+        .timeout(LOOKUP_TIMEOUT)
+        .set("Authorization", &token.header())
+        .call()
+        .map_err(|err| anyhow!("{provider} calendar request failed: {err}"))?
+        .into_string()
+        .with_context(|| format!("failed to read {provider} calendar response"))?;
+    serde_json::from_str(&body).with_context(|| format!("invalid {provider} calendar response"))
+}
+
+fn non_empty(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// Microsoft Graph 日历视图（`/me/calendarView`）。
+#[derive(Debug)]
+pub struct GraphCalendarProvider {
+    base_url: String,
+    token: BearerToken,
+}
+
+impl GraphCalendarProvider {
+    pub const DEFAULT_BASE_URL: &'static str = "https://graph.microsoft.com/v1.0";
+
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self::with_base_url(Self::DEFAULT_BASE_URL, access_token)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>, access_token: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            token: BearerToken::new(access_token.into()),
+        }
+    }
+
+    pub fn set_access_token(&self, access_token: impl Into<String>) {
+        self.token.set(access_token.into());
+    }
+
+    /// 解析 `calendarView` 响应；请求时要求以 UTC 返回时间。
+    pub fn parse_events(body: &Value) -> Vec<CalendarEvent> {
+        let Some(items) = body["value"].as_array() else {
+            return Vec::new();
+        };
+        items
+            .iter()
+            .filter(|item| item["isCancelled"].as_bool() != Some(true))
+            .filter_map(|item| {
+                let time =
+                    |field: &str| item[field]["dateTime"].as_str().and_then(parse_rfc3339_ms);
+                Some(CalendarEvent {
+                    title: non_empty(&item["subject"]).unwrap_or_default(),
+                    starts_at_ms: time("start")?,
+                    ends_at_ms: time("end")?,
+                    attendees: item["attendees"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|attendee| CalendarAttendee {
+                            name: non_empty(&attendee["emailAddress"]["name"]),
+                            email: non_empty(&attendee["emailAddress"]["address"]),
+                        })
+                        .collect(),
+                    location: non_empty(&item["location"]["displayName"]),
+                    provider: "graph".into(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl CalendarProvider for GraphCalendarProvider {
+    fn name(&self) -> &'static str {
+        "graph"
+    }
+
+    fn events_between(&self, from_ms: i64, to_ms: i64) -> Result<Vec<CalendarEvent>> {
+        let request = ureq::get(&format!("{}/me/calendarView", self.base_url))
+            .query("startDateTime", &format_timestamp(from_ms))
+            .query("endDateTime", &format_timestamp(to_ms))
+            .query(
+                "$select",
+                "subject,start,end,attendees,location,isCancelled",
+            )
+            .set("Prefer", "outlook.timezone=\"UTC\"");
+        Ok(Self::parse_events(&get_json(
+            request,
+            &self.token,
+            "graph",
+        )?))
+    }
+}
+
+/// Google Calendar 事件列表（`/calendars/{id}/events`）。
+#[derive(Debug)]
+pub struct GoogleCalendarProvider {
+    base_url: String,
+    calendar_id: String,
+    token: BearerToken,
+}
+
+impl GoogleCalendarProvider {
+    pub const DEFAULT_BASE_URL: &'static str = "https://www.googleapis.com/calendar/v3";
+
+    /// 读取用户主日历。
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self::with_calendar(Self::DEFAULT_BASE_URL, "primary", access_token)
+    }
+
+    pub fn with_calendar(
+        base_url: impl Into<String>,
+        calendar_id: impl Into<String>,
+        access_token: impl Into<String>,
+    ) -> Self {
+        Self {
+            base_url: base_url.into(),
+            calendar_id: calendar_id.into(),
+            token: BearerToken::new(access_token.into()),
+        }
+    }
+
+    pub fn set_access_token(&self, access_token: impl Into<String>) {
+        self.token.set(access_token.into());
+    }
+
+    /// 解析事件列表响应；会议室等资源不计入参会人。
+    pub fn parse_events(body: &Value) -> Vec<CalendarEvent> {
+        let Some(items) = body["items"].as_array() else {
+            return Vec::new();
+        };
+        items
+            .iter()
+            .filter(|item| item["status"].as_str() != Some("cancelled"))
+            .filter_map(|item| {
+                let time = |field: &str| {
+                    item[field]["dateTime"]
+                        .as_str()
+                        .or_else(|| item[field]["date"].as_str())
+                        .and_then(parse_rfc3339_ms)
+                };
+                Some(CalendarEvent {
+                    title: non_empty(&item["summary"]).unwrap_or_default(),
+                    starts_at_ms: time("start")?,
+                    ends_at_ms: time("end")?,
+                    attendees: item["attendees"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter(|attendee| attendee["resource"].as_bool() != Some(true))
+                        .map(|attendee| CalendarAttendee {
+                            name: non_empty(&attendee["displayName"]),
+                            email: non_empty(&attendee["email"]),
+                        })
+                        .collect(),
+                    location: non_empty(&item["location"]),
+                    provider: "google".into(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl CalendarProvider for GoogleCalendarProvider {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn events_between(&self, from_ms: i64, to_ms: i64) -> Result<Vec<CalendarEvent>> {
+        let request = ureq::get(&format!(
+            "{}/calendars/{}/events",
+            self.base_url, self.calendar_id
+        ))
+        .query("timeMin", &format_timestamp(from_ms))
+        .query("timeMax", &format_timestamp(to_ms))
+        .query("singleEvents", "true")
+        .query("orderBy", "startTime");
+        Ok(Self::parse_events(&get_json(
+            request,
+            &self.token,
+            "google",
+        )?))
+    }
+}
+
+impl SessionManager {
+    /// 设置日历数据源；为空时不再查找会议。
+    pub fn set_calendar_provider(&self, provider: Option<Arc<dyn CalendarProvider>>) {
+        *self
+            .calendar
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = provider;
+    }
+
+    /// 会话开始时在后台查找当前会议，发布时取出结果，不拖慢开始录音。
+    pub(crate) fn begin_calendar_lookup(&self, session_id: &str, at_ms: i64) {
+        let Some(provider) = self
+            .calendar
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+        else {
+            return;
+        };
+        let lookup = tokio::task::spawn_blocking(move || match provider.current_event(at_ms) {
+            Ok(event) => event,
+            Err(err) => {
+                warn!(target: "session_calendar", %err, provider = provider.name(), "calendar lookup failed");
+                None
+            }
+        });
+        self.calendar_lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(session_id.to_string(), lookup);
+    }
+
+    /// 把会话开始时匹配到的会议写入元数据与标签；查找未完成时最多等待
+    /// 一个请求超时。
+    pub(crate) async fn apply_calendar_event(&self, snapshot: &mut SessionSnapshot) {
+        let Some(lookup) = self
+            .calendar_lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&snapshot.session_id)
+        else {
+            return;
+        };
+        let Ok(Ok(Some(event))) = tokio::time::timeout(LOOKUP_TIMEOUT, lookup).await else {
+            return;
+        };
+        info!(
+            target: "session_calendar",
+            session_id = %snapshot.session_id,
+            provider = %event.provider,
+            "tagged session with calendar event"
+        );
+        event.annotate(&mut snapshot.metadata);
+        for tag in event.tags() {
+            if !snapshot.tags.contains(&tag) {
+                snapshot.tags.push(tag);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_792_227_600_000; // 2026-10-17T09:00:00Z
+
+    #[test]
+    fn parses_ics_events_with_folded_lines_and_attendees() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Weekly sync\\, platform\r\n\
+            DTSTART:20261017T090000Z\r\n\
+            DTEND:20261017T093000Z\r\n\
+            ATTENDEE;CN=\"Alice Chen\";ROLE=REQ-PARTICIPANT:mailto:alice@example.com\r\n\
+            ATTENDEE;CN=Bob:mailto:bob@exam\r\n ple.com\r\n\
+            LOCATION:Room 4\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Offsite\r\n\
+            DTSTART;VALUE=DATE:20261017\r\n\
+            DTEND;VALUE=DATE:20261018\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let events = parse_ics(ics);
+        assert_eq!(events.len(), 2);
+        let sync = &events[0];
+        assert_eq!(sync.title, "Weekly sync, platform");
+        assert_eq!(sync.starts_at_ms, START);
+        assert_eq!(sync.ends_at_ms, START + 30 * 60 * 1_000);
+        assert_eq!(sync.location.as_deref(), Some("Room 4"));
+        assert_eq!(sync.attendees[0].name.as_deref(), Some("Alice Chen"));
+        assert_eq!(sync.attendees[1].email.as_deref(), Some("bob@example.com"));
+        assert_eq!(
+            sync.tags(),
+            vec![
+                "meeting:Weekly sync, platform",
+                "attendee:alice@example.com",
+                "attendee:bob@example.com"
+            ]
+        );
+
+        // 全天事件与具体会议重叠时取具体会议，会前几分钟开始也能匹配。
+        let picked = pick_current(events.clone(), START - 60_000).unwrap();
+        assert_eq!(picked.title, "Weekly sync, platform");
+        assert_eq!(
+            pick_current(events, START + 60 * 60 * 1_000).unwrap().title,
+            "Offsite"
+        );
+    }
+
+    #[test]
+    fn parses_graph_and_google_responses() {
+        let graph = json!({ "value": [{
+            "subject": "Design review",
+            "start": { "dateTime": "2026-10-17T09:00:00.0000000", "timeZone": "UTC" },
+            "end": { "dateTime": "2026-10-17T10:00:00.0000000", "timeZone": "UTC" },
+            "attendees": [{ "emailAddress": { "name": "Alice", "address": "alice@example.com" } }],
+            "location": { "displayName": "" }
+        }, {
+            "subject": "Cancelled",
+            "isCancelled": true,
+            "start": { "dateTime": "2026-10-17T09:00:00" },
+            "end": { "dateTime": "2026-10-17T10:00:00" }
+        }]});
+        let events = GraphCalendarProvider::parse_events(&graph);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].starts_at_ms, START);
+        assert_eq!(events[0].location, None);
+        assert_eq!(
+            events[0].attendees[0].email.as_deref(),
+            Some("alice@example.com")
+        );
+
+        let google = json!({ "items": [{
+            "summary": "Standup",
+            "start": { "dateTime": "2026-10-17T11:00:00+02:00" },
+            "end": { "dateTime": "2026-10-17T11:15:00.500+02:00" },
+            "attendees": [
+                { "email": "bob@example.com", "displayName": "Bob" },
+                { "email": "room@resource.example.com", "resource": true }
+            ]
+        }]});
+        let events = GoogleCalendarProvider::parse_events(&google);
+        assert_eq!(events[0].starts_at_ms, START);
+        assert_eq!(events[0].ends_at_ms, START + 15 * 60 * 1_000 + 500);
+        assert_eq!(events[0].attendees.len(), 1);
+        assert_eq!(events[0].provider, "google");
+
+        assert_eq!(
+            parse_rfc3339_ms(&format_timestamp(START + 42)),
+            Some(START + 42)
+        );
+        assert_eq!(parse_rfc3339_ms("2026-10-17"), Some(START - 9 * 3_600_000));
+    }
+}
@@ -0,0 +1,345 @@
+//! 实时字幕广播：把润色后的句子按字幕格式切行，推送给桌面悬浮层或本地 SSE 端点，
+//! 方便演讲者在会议中展示字幕。
+
+use std::env;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+use tracing::{info, warn};
+
+use crate::session::shutdown::CancellationToken;
+
+pub const CAPTIONS_ADDR_ENV: &str = "FLOWWISPER_CAPTIONS_ADDR";
+
+/// SSE 空闲时发送注释行的间隔，用于及时发现断开的连接。
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptionConfig {
+    /// 每行最多字符数；中文等无空格文本按字符切分。
+    pub max_line_chars: usize,
+    /// 每屏最多行数，超出的内容拆成后续字幕帧。
+    pub max_lines: usize,
+    /// 每屏字幕的展示时长。
+    pub display_duration: Duration,
+}
+
+impl Default for CaptionConfig {
+    fn default() -> Self {
+        Self {
+            max_line_chars: 42,
+            max_lines: 2,
+            display_duration: Duration::from_secs(4),
+        }
+    }
+}
+
+/// 一屏字幕。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptionFrame {
+    pub sentence_id: u64,
+    /// 同一句拆出的第几屏，从 0 开始。
+    pub index: usize,
+    pub lines: Vec<String>,
+    pub display_ms: u64,
+}
+
+/// 按行宽切分文本：优先在空格处换行，超长单词或无空格文本按字符硬切。
+pub fn wrap_caption(text: &str, max_line_chars: usize) -> Vec<String> {
+    let max = max_line_chars.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut len = 0;
+    for word in text.split_whitespace() {
+        let word_len = word.chars().count();
+        if len > 0 && len + 1 + word_len <= max {
+            line.push(' ');
+            line.push_str(word);
+            len += 1 + word_len;
+            continue;
+        }
+        if len > 0 {
+            lines.push(std::mem::take(&mut line));
+            len = 0;
+        }
+        for ch in word.chars() {
+            if len == max {
+                lines.push(std::mem::take(&mut line));
+                len = 0;
+            }
+            line.push(ch);
+            len += 1;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 字幕广播器；未启用时 `publish` 不做任何事。
+#[derive(Clone)]
+pub struct CaptionBroadcaster {
+    config: Arc<RwLock<Option<CaptionConfig>>>,
+    tx: broadcast::Sender<CaptionFrame>,
+    shutdown: CancellationToken,
+}
+
+impl Default for CaptionBroadcaster {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(64);
+        Self {
+            config: Arc::new(RwLock::new(None)),
+            tx,
+            shutdown: CancellationToken::new(),
+        }
+    }
+}
+
+impl CaptionBroadcaster {
+    /// 令牌取消后 SSE 端点停止监听并断开所有连接。
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub fn config(&self) -> Option<CaptionConfig> {
+        self.config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// `None` 关闭字幕广播。
+    pub fn set_config(&self, config: Option<CaptionConfig>) {
+        *self
+            .config
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CaptionFrame> {
+        self.tx.subscribe()
+    }
+
+    /// 把一句润色稿切成字幕帧并广播，返回帧数。
+    pub fn publish(&self, sentence_id: u64, text: &str) -> usize {
+        let Some(config) = self.config() else {
+            return 0;
+        };
+        let lines = wrap_caption(text, config.max_line_chars);
+        let frames: Vec<CaptionFrame> = lines
+            .chunks(config.max_lines.max(1))
+            .enumerate()
+            .map(|(index, lines)| CaptionFrame {
+                sentence_id,
+                index,
+                lines: lines.to_vec(),
+                display_ms: config.display_duration.as_millis() as u64,
+            })
+            .collect();
+        let count = frames.len();
+        for frame in frames {
+            // 没有订阅者时发送失败属正常情况。
+            let _ = self.tx.send(frame);
+        }
+        count
+    }
+
+    /// 监听 `addr` 并以 SSE 提供 `GET /captions`，直到任务被终止或令牌取消。返回实际绑定地址。
+    pub async fn serve(&self, addr: SocketAddr) -> Result<(SocketAddr, JoinHandle<()>)> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|err| anyhow!("failed to bind caption listener on {addr}: {err}"))?;
+        let local_addr = listener.local_addr().unwrap_or(addr);
+        info!(target: "session::captions", addr = %local_addr, "caption endpoint listening");
+
+        let tx = self.tx.clone();
+        let shutdown = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            while let Some(accepted) = shutdown.run_until_cancelled(listener.accept()).await {
+                match accepted {
+                    Ok((stream, _)) => {
+                        let frames = tx.subscribe();
+                        let shutdown = shutdown.clone();
+                        tokio::spawn(async move {
+                            let connection = handle_connection(stream, frames);
+                            if let Some(Err(err)) = shutdown.run_until_cancelled(connection).await {
+                                warn!(target: "session::captions", %err, "caption stream closed");
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        warn!(target: "session::captions", %err, "caption accept failed");
+                    }
+                }
+            }
+        });
+        Ok((local_addr, handle))
+    }
+}
+
+pub fn configured_addr() -> Option<SocketAddr> {
+    let value = env::var(CAPTIONS_ADDR_ENV).ok()?;
+    match value.trim().parse() {
+        Ok(addr) => Some(addr),
+        Err(err) => {
+            warn!(
+                target: "session::captions",
+                %err,
+                value = %value,
+                "ignoring invalid caption listen address"
+            );
+            None
+        }
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    mut frames: broadcast::Receiver<CaptionFrame>,
+) -> Result<()> {
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut parts = request.split_whitespace();
+    if !matches!(
+        (parts.next(), parts.next()),
+        (Some("GET"), Some("/captions"))
+    ) {
+        stream
+            .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .await?;
+        stream.shutdown().await?;
+        return Ok(());
+    }
+
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\nAccess-Control-Allow-Origin: *\r\n\r\n",
+        )
+        .await?;
+    let mut keep_alive = interval(KEEP_ALIVE);
+    keep_alive.tick().await;
+    loop {
+        let chunk = tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => format!("event: caption\ndata: {}\n\n", serde_json::to_string(&frame)?),
+                // 落后的客户端直接跳到最新字幕。
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            _ = keep_alive.tick() => ": keep-alive\n\n".to_string(),
+        };
+        stream.write_all(chunk.as_bytes()).await?;
+    }
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    #[test]
+    fn wraps_words_and_splits_unspaced_text() {
+        assert_eq!(
+            wrap_caption("see you at the meeting tomorrow", 12),
+            vec!["see you at", "the meeting", "tomorrow"]
+        );
+        assert_eq!(
+            wrap_caption("我们明天下午两点开会", 4),
+            vec!["我们明天", "下午两点", "开会"]
+        );
+
+        let captions = CaptionBroadcaster::default();
+        let mut rx = captions.subscribe();
+        assert_eq!(captions.publish(1, "ignored while disabled"), 0);
+        captions.set_config(Some(CaptionConfig {
+            max_line_chars: 12,
+            max_lines: 2,
+            display_duration: Duration::from_secs(3),
+        }));
+        assert_eq!(captions.publish(7, "see you at the meeting tomorrow"), 2);
+        let first = rx.try_recv().unwrap();
+        assert_eq!(first.lines, vec!["see you at", "the meeting"]);
+        assert_eq!(first.display_ms, 3_000);
+        let second = rx.try_recv().unwrap();
+        assert_eq!((second.sentence_id, second.index), (7, 1));
+        assert_eq!(second.lines, vec!["tomorrow"]);
+    }
+
+    #[tokio::test]
+    async fn streams_captions_over_sse() {
+        let captions = CaptionBroadcaster::default();
+        captions.set_config(Some(CaptionConfig::default()));
+        let (addr, handle) = captions
+            .serve("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /captions HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert!(line.starts_with("HTTP/1.1 200 OK"));
+        while line != "\r\n" {
+            line.clear();
+            reader.read_line(&mut line).await.unwrap();
+        }
+
+        captions.publish(3, "Hello everyone.");
+        line.clear();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "event: caption\n");
+        line.clear();
+        reader.read_line(&mut line).await.unwrap();
+        handle.abort();
+        assert!(line.contains("\"lines\":[\"Hello everyone.\"]"), "{line}");
+        assert!(line.contains("\"sentenceId\":3"));
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_listener_and_open_streams() {
+        let shutdown = CancellationToken::new();
+        let captions = CaptionBroadcaster::default().with_shutdown(shutdown.clone());
+        let (addr, handle) = captions
+            .serve("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /captions HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert!(line.starts_with("HTTP/1.1 200 OK"));
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("listener exits on shutdown")
+            .unwrap();
+        let mut rest = String::new();
+        let closed = tokio::time::timeout(Duration::from_secs(2), reader.read_to_string(&mut rest))
+            .await
+            .expect("open stream closes on shutdown");
+        assert!(closed.is_ok());
+    }
+}
@@ -0,0 +1,320 @@
+//! 录音触发模式：决定何时把 PCM 帧转发给转写会话，以及何时自动开始、结束录音。
+//! 桌面端热键层只上报按键状态，由这里统一解释。
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hotkey::HotkeyGesture;
+
+/// 音频管线输出 PCM 帧的采样率。
+const FRAME_SAMPLE_RATE_HZ: f64 = 16_000.0;
+
+/// 管线输出的一帧 PCM 对应的音频时长。
+pub(crate) fn frame_duration(frame: &[f32]) -> Duration {
+    Duration::from_secs_f64(frame.len() as f64 / FRAME_SAMPLE_RATE_HZ)
+}
+
+/// 录音触发模式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureMode {
+    /// 按住热键时录音，松开即结束。
+    HoldToTalk,
+    /// 按一次开始、再按一次结束。
+    #[default]
+    Toggle,
+    /// 热键只负责开启或关闭监听，监听期间检测到语音自动开始、静音一段时间后自动结束。
+    VoiceActivated,
+}
+
+impl CaptureMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaptureMode::HoldToTalk => "holdToTalk",
+            CaptureMode::Toggle => "toggle",
+            CaptureMode::VoiceActivated => "voiceActivated",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "holdtotalk" | "hold_to_talk" | "hold" | "ptt" => Some(CaptureMode::HoldToTalk),
+            "toggle" => Some(CaptureMode::Toggle),
+            "voiceactivated" | "voice_activated" | "vad" => Some(CaptureMode::VoiceActivated),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceActivationConfig {
+    /// 帧 RMS 不低于该值视为语音。
+    pub speech_rms: f32,
+    /// 连续语音达到该时长才开始录音，过滤短促噪声。
+    pub min_speech: Duration,
+    /// 录音中连续静音达到该时长后结束。
+    pub hangover: Duration,
+}
+
+impl Default for VoiceActivationConfig {
+    fn default() -> Self {
+        Self {
+            speech_rms: 5e-3,
+            min_speech: Duration::from_millis(120),
+            hangover: Duration::from_millis(1_500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureTransition {
+    Started,
+    Stopped,
+}
+
+/// 触发录音状态变化的来源。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureTrigger {
+    Hotkey,
+    Voice,
+    SilenceTimeout,
+    MaxDuration,
+    ModeChange,
+    /// 双击热键：丢弃当前草稿，随后立即开始新会话。
+    Discard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureEvent {
+    pub mode: CaptureMode,
+    pub transition: CaptureTransition,
+    pub trigger: CaptureTrigger,
+}
+
+/// 录音状态机。
+#[derive(Debug)]
+pub struct CaptureController {
+    mode: CaptureMode,
+    vad: VoiceActivationConfig,
+    capturing: bool,
+    /// 语音激活模式下是否处于监听状态。
+    listening: bool,
+    speech_run: Duration,
+    silence_run: Duration,
+}
+
+impl CaptureController {
+    pub fn new(mode: CaptureMode, vad: VoiceActivationConfig) -> Self {
+        Self {
+            mode,
+            vad,
+            capturing: false,
+            listening: false,
+            speech_run: Duration::ZERO,
+            silence_run: Duration::ZERO,
+        }
+    }
+
+    pub fn mode(&self) -> CaptureMode {
+        self.mode
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening
+    }
+
+    fn transition(&mut self, capturing: bool, trigger: CaptureTrigger) -> Option<CaptureEvent> {
+        if self.capturing == capturing {
+            return None;
+        }
+        self.capturing = capturing;
+        self.speech_run = Duration::ZERO;
+        self.silence_run = Duration::ZERO;
+        Some(CaptureEvent {
+            mode: self.mode,
+            transition: if capturing {
+                CaptureTransition::Started
+            } else {
+                CaptureTransition::Stopped
+            },
+            trigger,
+        })
+    }
+
+    /// 切换模式；录音中切换会先结束当前录音。
+    pub fn set_mode(&mut self, mode: CaptureMode) -> Option<CaptureEvent> {
+        if mode == self.mode {
+            return None;
+        }
+        let stopped = self.transition(false, CaptureTrigger::ModeChange);
+        self.mode = mode;
+        self.listening = false;
+        stopped
+    }
+
+    /// 上报热键按下或松开。
+    pub fn hotkey(&mut self, pressed: bool) -> Option<CaptureEvent> {
+        match (self.mode, pressed) {
+            (CaptureMode::HoldToTalk, pressed) => self.transition(pressed, CaptureTrigger::Hotkey),
+            (CaptureMode::Toggle, true) => self.transition(!self.capturing, CaptureTrigger::Hotkey),
+            (CaptureMode::VoiceActivated, true) => {
+                self.listening = !self.listening;
+                self.speech_run = Duration::ZERO;
+                if self.listening {
+                    None
+                } else {
+                    self.transition(false, CaptureTrigger::Hotkey)
+                }
+            }
+            (_, false) => None,
+        }
+    }
+
+    /// 上报同一绑定上识别出的手势：单击切换、长按按住说话、双击丢弃草稿并重新开始。
+    /// 双击可能产生一次停止与一次开始，按顺序返回。
+    pub fn gesture(&mut self, gesture: HotkeyGesture) -> Vec<CaptureEvent> {
+        let mut events = Vec::new();
+        match gesture {
+            HotkeyGesture::Tap => {
+                events.extend(self.transition(!self.capturing, CaptureTrigger::Hotkey));
+            }
+            HotkeyGesture::HoldStarted => {
+                events.extend(self.transition(true, CaptureTrigger::Hotkey));
+            }
+            HotkeyGesture::HoldEnded { .. } => {
+                events.extend(self.transition(false, CaptureTrigger::Hotkey));
+            }
+            HotkeyGesture::DoubleTap => {
+                events.extend(self.transition(false, CaptureTrigger::Discard));
+                events.extend(self.transition(true, CaptureTrigger::Hotkey));
+            }
+        }
+        events
+    }
+
+    /// 送入一帧的能量；语音激活模式据此自动开始或结束录音。
+    pub fn observe_frame(&mut self, rms: f32, duration: Duration) -> Option<CaptureEvent> {
+        if self.mode != CaptureMode::VoiceActivated || !self.listening {
+            return None;
+        }
+        let speech = rms >= self.vad.speech_rms;
+        if speech {
+            self.speech_run += duration;
+            self.silence_run = Duration::ZERO;
+        } else {
+            self.silence_run += duration;
+            self.speech_run = Duration::ZERO;
+        }
+        if !self.capturing && self.speech_run >= self.vad.min_speech {
+            self.transition(true, CaptureTrigger::Voice)
+        } else if self.capturing && self.silence_run >= self.vad.hangover {
+            self.transition(false, CaptureTrigger::Voice)
+        } else {
+            None
+        }
+    }
+
+    /// 送入一帧管线输出的 PCM。
+    pub fn observe_pcm(&mut self, frame: &[f32]) -> Option<CaptureEvent> {
+        if frame.is_empty() {
+            return None;
+        }
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        self.observe_frame(rms, frame_duration(frame))
+    }
+
+    /// 静音倒计时结束等外部原因结束录音；语音激活模式仍保持监听。
+    pub fn stop(&mut self, trigger: CaptureTrigger) -> Option<CaptureEvent> {
+        self.transition(false, trigger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hold_and_toggle_follow_hotkey() {
+        let mut hold = CaptureController::new(CaptureMode::HoldToTalk, Default::default());
+        assert_eq!(
+            hold.hotkey(true).map(|event| event.transition),
+            Some(CaptureTransition::Started)
+        );
+        assert!(hold.hotkey(true).is_none());
+        assert_eq!(
+            hold.hotkey(false).map(|event| event.transition),
+            Some(CaptureTransition::Stopped)
+        );
+
+        let mut toggle = CaptureController::new(CaptureMode::Toggle, Default::default());
+        assert!(toggle.hotkey(true).is_some());
+        assert!(toggle.hotkey(false).is_none());
+        assert!(toggle.is_capturing());
+        let stopped = toggle
+            .set_mode(CaptureMode::HoldToTalk)
+            .expect("mode change");
+        assert_eq!(stopped.trigger, CaptureTrigger::ModeChange);
+        assert!(!toggle.is_capturing());
+    }
+
+    #[test]
+    fn voice_activation_starts_and_stops_on_speech() {
+        let frame = Duration::from_millis(100);
+        let mut vad = CaptureController::new(CaptureMode::VoiceActivated, Default::default());
+        // 未开启监听时忽略语音。
+        assert!(vad.observe_frame(0.1, frame).is_none());
+        assert!(vad.hotkey(true).is_none());
+        assert!(vad.is_listening());
+
+        assert!(vad.observe_frame(0.1, frame).is_none());
+        let started = vad.observe_frame(0.1, frame).expect("speech long enough");
+        assert_eq!(started.trigger, CaptureTrigger::Voice);
+        for _ in 0..14 {
+            assert!(vad.observe_frame(0.0, frame).is_none());
+        }
+        let stopped = vad.observe_frame(0.0, frame).expect("hangover elapsed");
+        assert_eq!(stopped.transition, CaptureTransition::Stopped);
+        assert!(vad.is_listening());
+
+        vad.observe_frame(0.1, frame);
+        vad.observe_frame(0.1, frame);
+        let disarmed = vad.hotkey(true).expect("stop on disarm");
+        assert_eq!(disarmed.trigger, CaptureTrigger::Hotkey);
+        assert!(!vad.is_listening());
+    }
+
+    #[test]
+    fn gestures_toggle_hold_and_restart() {
+        let mut capture = CaptureController::new(CaptureMode::Toggle, Default::default());
+        let started = capture.gesture(HotkeyGesture::Tap);
+        assert_eq!(started.len(), 1);
+        assert!(capture.is_capturing());
+
+        let restarted = capture.gesture(HotkeyGesture::DoubleTap);
+        assert_eq!(
+            restarted
+                .iter()
+                .map(|event| (event.transition, event.trigger))
+                .collect::<Vec<_>>(),
+            vec![
+                (CaptureTransition::Stopped, CaptureTrigger::Discard),
+                (CaptureTransition::Started, CaptureTrigger::Hotkey),
+            ]
+        );
+        assert!(capture.is_capturing());
+
+        assert_eq!(capture.gesture(HotkeyGesture::Tap).len(), 1);
+        assert!(!capture.is_capturing());
+        assert_eq!(capture.gesture(HotkeyGesture::HoldStarted).len(), 1);
+        assert!(capture.is_capturing());
+        assert_eq!(
+            capture.gesture(HotkeyGesture::HoldEnded { held_ms: 800 })[0].transition,
+            CaptureTransition::Stopped
+        );
+    }
+}
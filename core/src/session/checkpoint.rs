@@ -0,0 +1,197 @@
+//! 会话检查点：进行中会话定期写入已识别的句子，进程被强制结束后下次启动时恢复为草稿。
+
+use std::time::Duration as StdDuration;
+
+use tokio::time::Interval;
+use tracing::warn;
+
+use crate::orchestrator::NoticeLevel;
+use crate::persistence::{DraftSaveRequest, PersistenceHandle};
+use crate::session::recovery::RecoverySnapshot;
+use crate::session::SessionManager;
+use crate::telemetry::events::{record_session_draft_failed, record_session_draft_saved};
+
+/// 进行中会话写入检查点的默认间隔秒数；进程被强制结束时最多丢失这段时间的转写。
+pub(crate) const DEFAULT_CHECKPOINT_SECS: u64 = 5;
+
+/// 由检查点恢复的草稿所带的标签。
+const CHECKPOINT_DRAFT_TAG: &str = "recovered";
+
+impl SessionManager {
+    /// 把上次未正常结束的会话检查点恢复为草稿；已写入历史的会话只清理检查点。
+    pub(super) async fn restore_checkpoints(&self) {
+        let checkpoints = match self.persistence.list_checkpoints().await {
+            Ok(checkpoints) => checkpoints,
+            Err(err) => {
+                warn!(target: "session_manager", %err, "failed to read session checkpoints");
+                return;
+            }
+        };
+        let mut restored = None;
+        for snapshot in checkpoints {
+            let session_id = snapshot.session_id.clone();
+            let finished = matches!(
+                self.persistence.load_session(session_id.clone()).await,
+                Ok(Some(_))
+            );
+            let content = snapshot.polished_transcript();
+            if !finished && !content.trim().is_empty() {
+                let request = DraftSaveRequest {
+                    draft_id: format!("{session_id}-checkpoint"),
+                    session_id: session_id.clone(),
+                    content,
+                    title: None,
+                    tags: Some(vec![CHECKPOINT_DRAFT_TAG.to_string()]),
+                };
+                match self.persistence.save_draft(request).await {
+                    Ok(record) => {
+                        record_session_draft_saved(&session_id, &record.draft_id, &record.tags);
+                        restored = Some(snapshot);
+                    }
+                    // 保留检查点，下次启动再试。
+                    Err(err) => {
+                        record_session_draft_failed(&session_id, err.to_string());
+                        continue;
+                    }
+                }
+            }
+            if let Err(err) = self.persistence.remove_checkpoint(session_id).await {
+                warn!(target: "session_manager", %err, "failed to remove session checkpoint");
+            }
+        }
+
+        let Some(snapshot) = restored else {
+            return;
+        };
+        warn!(
+            target: "session_manager",
+            session_id = %snapshot.session_id,
+            frame_cursor = snapshot.frame_cursor,
+            "restored interrupted session from checkpoint"
+        );
+        let mut recovered = self.recovered_session.lock().await;
+        if recovered.is_none() {
+            self.emit_notice(
+                NoticeLevel::Warn,
+                "检测到上次会话未正常结束，已将转写内容保存为草稿。",
+            );
+            *recovered = Some(snapshot);
+        }
+    }
+
+    pub fn checkpoint_interval(&self) -> Option<StdDuration> {
+        *self
+            .checkpoint_interval
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 设置进行中会话写入检查点的间隔，`None` 表示不写入；对之后开始的会话生效。
+    pub fn set_checkpoint_interval(&self, interval: Option<StdDuration>) {
+        *self
+            .checkpoint_interval
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = interval;
+    }
+}
+
+/// 等待下一次写入检查点；未启用检查点时永不就绪。
+pub(super) async fn next_checkpoint(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// 将进行中会话已识别的句子与帧位置写入持久层，进程异常退出后可据此恢复。
+pub(super) async fn save_checkpoint(
+    persistence: &PersistenceHandle,
+    snapshot: Option<RecoverySnapshot>,
+) {
+    let Some(snapshot) = snapshot.filter(|snapshot| !snapshot.sentences.is_empty()) else {
+        return;
+    };
+    let session_id = snapshot.session_id.clone();
+    if let Err(err) = persistence.save_checkpoint(snapshot).await {
+        warn!(
+            target: "session_manager",
+            %err,
+            session_id = %session_id,
+            "failed to write session checkpoint"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator, RealtimeSessionConfig};
+    use crate::session::tests::ProgrammedSpeechEngine;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn checkpoints_partial_transcript_and_restores_it_as_draft() {
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(vec![Ok(
+                "notes before the crash.".to_string(),
+            )])),
+        );
+        let manager = SessionManager::with_orchestrator(orchestrator);
+        manager.set_checkpoint_interval(Some(StdDuration::from_millis(20)));
+        manager.set_active_session_id("session-checkpoint").await;
+
+        let config = RealtimeSessionConfig {
+            enable_polisher: false,
+            ..RealtimeSessionConfig::default()
+        };
+        let (_handle, _client_rx) = manager.start_realtime_transcription(config).await;
+        manager
+            .audio_pipeline()
+            .push_pcm_frame(vec![0.25_f32; 1_600])
+            .await
+            .expect("push pcm frame");
+
+        let persistence = manager.persistence_handle();
+        let checkpoint = timeout(Duration::from_secs(2), async {
+            loop {
+                let checkpoints = persistence.list_checkpoints().await.unwrap();
+                if let Some(checkpoint) = checkpoints
+                    .into_iter()
+                    .find(|checkpoint| checkpoint.session_id == "session-checkpoint")
+                {
+                    break checkpoint;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("checkpoint written");
+        assert_eq!(checkpoint.raw_transcript(), "notes before the crash.");
+        assert_eq!(checkpoint.frame_cursor, 1);
+
+        // 模拟进程被强制结束后重新启动：未完成的会话以草稿恢复。
+        manager.crash_guard.clear();
+        manager.restore_checkpoints().await;
+        let drafts = persistence.list_drafts(20).await.unwrap();
+        let draft = drafts
+            .iter()
+            .find(|draft| draft.draft_id == "session-checkpoint-checkpoint")
+            .expect("checkpoint restored as draft");
+        assert_eq!(draft.content, "notes before the crash.");
+        assert_eq!(draft.tags, vec![CHECKPOINT_DRAFT_TAG.to_string()]);
+        assert!(manager.recover_last_session().await.is_some());
+        assert!(!persistence
+            .list_checkpoints()
+            .await
+            .unwrap()
+            .iter()
+            .any(|checkpoint| checkpoint.session_id == "session-checkpoint"));
+    }
+}
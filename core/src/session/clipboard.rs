@@ -103,7 +103,7 @@ impl ClipboardManager {
     }
 
     pub fn with_system() -> Self {
-        Self::new(Arc::new(SystemClipboard::default()))
+        Self::new(Arc::new(SystemClipboard))
     }
 
     pub async fn backup(&self, timeout: Duration) -> Result<ClipboardSnapshot, ClipboardError> {
@@ -299,6 +299,7 @@ mod tests {
             *self.read_error.lock().await = Some(error);
         }
 
+        #[allow(dead_code)]
         async fn inject_write_error(&self, error: ClipboardError) {
             *self.write_error.lock().await = Some(error);
         }
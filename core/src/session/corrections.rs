@@ -0,0 +1,451 @@
+//! 个人纠错语料：用户标记准确性时提交的修正稿与原稿逐词对齐，提取（原文, 修正）短语对
+//! 累计到 `corrections` 表；反复出现且结论一致的修正会在之后的转写发布前自动应用。
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::orchestrator::language::{script_of, Script};
+use crate::orchestrator::stabilizer::tokenize;
+use crate::session::publisher::FocusWindowContext;
+use crate::session::replacement::{ReplacementRule, ReplacementRules};
+use crate::session::SessionManager;
+
+/// 同一修正至少出现的次数，达到后才会自动应用。
+pub const MIN_AUTO_APPLY_OCCURRENCES: u32 = 3;
+/// 同一原文的所有修正中，该修正所占的最低比例。
+pub const MIN_AUTO_APPLY_CONFIDENCE: f64 = 0.8;
+/// 单侧超过该词数的改动视为改写而非纠错，不计入语料。
+const MAX_PHRASE_TOKENS: usize = 4;
+/// 比对矩阵的上限，超长文本只比较首尾以外的差异区间仍过大时放弃提取。
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+/// 语料中的一条修正及其累计次数。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionPair {
+    pub original: String,
+    pub corrected: String,
+    pub occurrences: u32,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+}
+
+/// 分词结果及各词在原文中的字节区间。
+struct Tokens<'a> {
+    text: &'a str,
+    spans: Vec<(usize, usize)>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        // tokenize 的结果首尾相接覆盖全文，累加长度即可得到偏移。
+        let mut offset = 0;
+        let spans = tokenize(text)
+            .into_iter()
+            .map(|token| {
+                let span = (offset, offset + token.len());
+                offset = span.1;
+                span
+            })
+            .collect();
+        Self { text, spans }
+    }
+
+    fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    fn word(&self, index: usize) -> &'a str {
+        let (start, end) = self.spans[index];
+        self.text[start..end].trim()
+    }
+
+    fn phrase(&self, from: usize, to: usize) -> &'a str {
+        self.text[self.spans[from].0..self.spans[to - 1].1].trim()
+    }
+}
+
+/// 逐词比对原稿与修正稿，返回被替换的短语对；纯插入、纯删除与标点改动不计入。
+pub fn extract_corrections(original: &str, corrected: &str) -> Vec<(String, String)> {
+    let before = Tokens::new(original);
+    let after = Tokens::new(corrected);
+    let (n, m) = (before.len(), after.len());
+
+    let mut prefix = 0;
+    while prefix < n.min(m) && before.word(prefix) == after.word(prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < (n - prefix).min(m - prefix)
+        && before.word(n - 1 - suffix) == after.word(m - 1 - suffix)
+    {
+        suffix += 1;
+    }
+    let (rows, cols) = (n - prefix - suffix, m - prefix - suffix);
+    if rows == 0 || cols == 0 || (rows + 1) * (cols + 1) > MAX_ALIGNMENT_CELLS {
+        return Vec::new();
+    }
+
+    // 最长公共子序列，lcs[i][j] 为两侧从 i、j 起的后缀的匹配长度。
+    let mut lcs = vec![vec![0u32; cols + 1]; rows + 1];
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            lcs[i][j] = if before.word(prefix + i) == after.word(prefix + j) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let mut push = |from_i: usize, i: usize, from_j: usize, j: usize| {
+        if from_i == i || from_j == j {
+            return;
+        }
+        if i - from_i > MAX_PHRASE_TOKENS || j - from_j > MAX_PHRASE_TOKENS {
+            return;
+        }
+        let (mut from_i, mut from_j) = (prefix + from_i, prefix + from_j);
+        // 单个汉字或假名脱离上下文没有意义，带上前面相同的一个字。
+        if from_i > 0 && from_j > 0 && is_ideographic(before.phrase(from_i, prefix + i)) {
+            from_i -= 1;
+            from_j -= 1;
+        }
+        let original = before.phrase(from_i, prefix + i);
+        let corrected = after.phrase(from_j, prefix + j);
+        let wordy = |text: &str| text.chars().any(char::is_alphanumeric);
+        if original != corrected && wordy(original) && wordy(corrected) {
+            pairs.push((original.to_string(), corrected.to_string()));
+        }
+    };
+    let (mut i, mut j) = (0, 0);
+    let (mut gap_i, mut gap_j) = (0, 0);
+    while i < rows && j < cols {
+        if before.word(prefix + i) == after.word(prefix + j) {
+            push(gap_i, i, gap_j, j);
+            i += 1;
+            j += 1;
+            (gap_i, gap_j) = (i, j);
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    push(gap_i, rows, gap_j, cols);
+    pairs
+}
+
+fn is_ideograph(c: char) -> bool {
+    matches!(script_of(c), Script::Han | Script::Kana)
+}
+
+fn is_ideographic(phrase: &str) -> bool {
+    let mut chars = phrase.chars();
+    chars.next().is_some_and(is_ideograph) && chars.next().is_none()
+}
+
+/// 由语料编译出的自动纠错规则，复用替换规则的按词边界、忽略大小写匹配。
+#[derive(Default)]
+pub struct Corrector {
+    rules: ReplacementRules,
+}
+
+impl Corrector {
+    /// 只采用高置信的修正：次数达到阈值，且在同一原文（忽略大小写）的全部修正中占绝大多数。
+    pub fn from_corpus(corpus: &[CorrectionPair]) -> Self {
+        let mut totals: HashMap<String, u32> = HashMap::new();
+        for pair in corpus {
+            *totals.entry(pair.original.to_lowercase()).or_default() += pair.occurrences;
+        }
+        let mut confident: Vec<&CorrectionPair> = corpus
+            .iter()
+            .filter(|pair| pair.occurrences >= MIN_AUTO_APPLY_OCCURRENCES)
+            .filter(|pair| {
+                let total = totals[&pair.original.to_lowercase()];
+                f64::from(pair.occurrences) / f64::from(total) >= MIN_AUTO_APPLY_CONFIDENCE
+            })
+            .collect();
+        // 长短语优先，避免短修正先行改写其中的片段。
+        confident.sort_by_key(|pair| std::cmp::Reverse(pair.original.chars().count()));
+        let rules = confident
+            .into_iter()
+            .enumerate()
+            .map(|(index, pair)| {
+                // 汉字之间没有词边界，按原文直接匹配。
+                let ideographic = pair.original.chars().any(is_ideograph);
+                ReplacementRule {
+                    rule_id: format!("correction-{index}"),
+                    pattern: if ideographic {
+                        regex::escape(&pair.original)
+                    } else {
+                        pair.original.clone()
+                    },
+                    replacement: if ideographic {
+                        pair.corrected.replace('$', "$$")
+                    } else {
+                        pair.corrected.clone()
+                    },
+                    is_regex: ideographic,
+                    app_identifier: None,
+                    enabled: true,
+                    created_at_ms: pair.first_seen_ms,
+                    updated_at_ms: pair.last_seen_ms,
+                }
+            })
+            .collect();
+        Self {
+            rules: ReplacementRules::compile(rules),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.rules.apply(text, &FocusWindowContext::default())
+    }
+}
+
+/// 以 JSON Lines 导出语料，每行一条修正；返回写入的条数。
+pub fn write_corpus(path: &Path, corpus: &[CorrectionPair]) -> Result<usize> {
+    let file = File::create(path)
+        .with_context(|| format!("failed to create correction corpus {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    for pair in corpus {
+        serde_json::to_writer(&mut writer, pair)?;
+        writer.write_all(b"\n")?;
+    }
+    writer
+        .flush()
+        .context("failed to write correction corpus")?;
+    Ok(corpus.len())
+}
+
+impl SessionManager {
+    /// 个人纠错语料，出现次数多的在前。
+    pub async fn correction_corpus(&self) -> Result<Vec<CorrectionPair>> {
+        self.persistence
+            .list_corrections()
+            .await
+            .map_err(|err| anyhow!("failed to load correction corpus: {err}"))
+    }
+
+    /// 将纠错语料导出为 JSON Lines 文件，返回条数。
+    pub async fn export_correction_corpus(&self, path: PathBuf) -> Result<usize> {
+        let corpus = self.correction_corpus().await?;
+        tokio::task::spawn_blocking(move || write_corpus(&path, &corpus))
+            .await
+            .map_err(|err| anyhow!("blocking corpus export task failed: {err}"))?
+    }
+
+    /// 删除一条修正，使其不再自动应用。
+    pub async fn remove_correction(&self, original: String, corrected: String) -> Result<bool> {
+        let removed = self
+            .persistence
+            .remove_correction(original, corrected)
+            .await
+            .map_err(|err| anyhow!("failed to remove correction: {err}"))?;
+        self.refresh_corrector().await?;
+        Ok(removed)
+    }
+
+    pub(super) async fn refresh_corrector(&self) -> Result<()> {
+        let corrector = Corrector::from_corpus(&self.persistence.list_corrections().await?);
+        *self
+            .corrector
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(corrector);
+        Ok(())
+    }
+
+    pub(super) fn apply_corrections(&self, text: &str) -> String {
+        let corrector = self
+            .corrector
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if corrector.is_empty() {
+            return text.to_string();
+        }
+        corrector.apply(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator};
+    use crate::session::clipboard::ClipboardManager;
+    use crate::session::history::{AccuracyFlag, AccuracyUpdate};
+    use crate::session::publisher::{
+        FallbackStrategy, InsertionMethod, PublishOutcome, PublishRequest, PublishStrategy,
+        PublisherStatus,
+    };
+    use crate::session::tests::{
+        make_snapshot, ProgrammedSpeechEngine, RecordingClipboard, StubPublisher,
+    };
+
+    fn pair(original: &str, corrected: &str, occurrences: u32) -> CorrectionPair {
+        CorrectionPair {
+            original: original.into(),
+            corrected: corrected.into(),
+            occurrences,
+            first_seen_ms: 0,
+            last_seen_ms: 0,
+        }
+    }
+
+    #[test]
+    fn extracts_replaced_phrases_only() {
+        assert_eq!(
+            extract_corrections(
+                "Push the fix to get hub, then ping cuber netties folks.",
+                "Push the fix to GitHub, then ping Kubernetes folks today."
+            ),
+            vec![
+                ("get hub".to_string(), "GitHub".to_string()),
+                ("cuber netties".to_string(), "Kubernetes".to_string()),
+            ]
+        );
+        // 标点改动与大段改写都不算纠错。
+        assert!(extract_corrections("ok, ship it", "ok. ship it").is_empty());
+        assert!(extract_corrections(
+            "one two three four five six",
+            "seven eight nine ten eleven twelve"
+        )
+        .is_empty());
+        assert_eq!(
+            extract_corrections("发布到飞数", "发布到飞书"),
+            vec![("飞数".to_string(), "飞书".to_string())]
+        );
+    }
+
+    #[test]
+    fn applies_only_confident_recurring_corrections() {
+        let corrector = Corrector::from_corpus(&[
+            pair("get hub", "GitHub", 4),
+            pair("Get Hub", "GitHub", 1),
+            pair("flow whisper", "Flowwisper", 3),
+            pair("flow whisper", "FlowWhisper", 2),
+            pair("jura", "Jira", 2),
+            pair("飞数", "飞书", 3),
+        ]);
+        assert!(!corrector.is_empty());
+        assert_eq!(
+            corrector.apply("Open get hub, flow whisper and jura."),
+            "Open GitHub, flow whisper and jura."
+        );
+        assert_eq!(corrector.apply("发到飞数群里"), "发到飞书群里");
+        assert!(Corrector::from_corpus(&[]).is_empty());
+    }
+
+    #[test]
+    fn writes_corpus_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corpus.jsonl");
+        let count = write_corpus(&path, &[pair("get hub", "GitHub", 2)]).unwrap();
+        assert_eq!(count, 1);
+        let line = std::fs::read_to_string(&path).unwrap();
+        let parsed: CorrectionPair = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(parsed, pair("get hub", "GitHub", 2));
+    }
+
+    #[tokio::test]
+    async fn accuracy_corrections_build_corpus_and_auto_apply() {
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+        );
+        let manager = SessionManager::with_components(
+            orchestrator,
+            Arc::new(StubPublisher::new(PublishOutcome {
+                status: PublisherStatus::Completed,
+                strategy: PublishStrategy::DirectInsert,
+                attempts: 1,
+                fallback: None,
+                failure: None,
+                undo_token: None,
+            })),
+            ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+        );
+        let request = |transcript: &str| PublishRequest {
+            transcript: transcript.into(),
+            focus: FocusWindowContext::default(),
+            fallback: FallbackStrategy::ClipboardCopy,
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        for round in 0..MIN_AUTO_APPLY_OCCURRENCES {
+            let session_id = format!("session-correction-{round}");
+            let text = "Ship the zorblex widget today.";
+            manager
+                .persistence
+                .persist_session(make_snapshot(&session_id, text, text))
+                .await
+                .expect("snapshot persisted");
+            manager
+                .update_history_accuracy(AccuracyUpdate {
+                    session_id: session_id.clone(),
+                    flag: AccuracyFlag::InaccuratePolished,
+                    remarks: None,
+                    corrected_transcript: Some("Ship the Zorblax widget today.".into()),
+                })
+                .await
+                .expect("accuracy updated");
+            let entry = manager
+                .load_history_entry(&session_id)
+                .await
+                .unwrap()
+                .expect("entry exists");
+            assert_eq!(entry.polished_transcript, "Ship the Zorblax widget today.");
+        }
+
+        let corpus = manager.correction_corpus().await.expect("corpus loads");
+        let pair = corpus
+            .iter()
+            .find(|pair| pair.original == "zorblex")
+            .expect("correction recorded");
+        assert_eq!(pair.corrected, "Zorblax");
+        assert!(pair.occurrences >= MIN_AUTO_APPLY_OCCURRENCES);
+
+        let dir = tempfile::tempdir().unwrap();
+        let exported = manager
+            .export_correction_corpus(dir.path().join("corpus.jsonl"))
+            .await
+            .expect("corpus exported");
+        assert_eq!(exported, corpus.len());
+
+        manager
+            .publish_transcript(
+                make_snapshot("session-correction-auto", "zorblex", "Demo the zorblex."),
+                request("Demo the zorblex."),
+            )
+            .await
+            .expect("publish should succeed");
+        let entry = manager
+            .load_history_entry("session-correction-auto")
+            .await
+            .unwrap()
+            .expect("entry exists");
+        assert_eq!(entry.polished_transcript, "Demo the Zorblax.");
+
+        assert!(manager
+            .remove_correction("zorblex".into(), "Zorblax".into())
+            .await
+            .unwrap());
+        assert_eq!(manager.apply_corrections("zorblex"), "zorblex");
+    }
+}
@@ -0,0 +1,485 @@
+//! 诊断包导出：把飞行记录、近期遥测日志、脱敏配置、设备与校准信息和数据库完整性检查
+//! 打包为单个 zip，供用户附在支持请求里。导出前必须取得用户的明确同意。
+
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::flight_recorder::{FlightRecorder, FLIGHT_DUMP_EXTENSION};
+use crate::config::FlowwisperConfig;
+use crate::persistence::backup::archive;
+use crate::persistence::sqlite::SqlitePersistence;
+use crate::telemetry;
+use crate::telemetry::sealed::{decrypt_log, TelemetryLogKey};
+
+/// 默认打包最近两天的遥测日志。
+pub const DEFAULT_LOG_MAX_AGE: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+/// 遥测日志的总大小上限，超出后不再加入更早的文件。
+const MAX_LOG_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum DiagnosticsError {
+    #[error("exporting diagnostics requires the user's explicit consent")]
+    ConsentRequired,
+    #[error("no diagnostic sections were selected")]
+    NothingSelected,
+    #[error("failed to write diagnostic bundle: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to write diagnostic bundle: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// 诊断包可包含的内容，界面应逐项向用户说明并由用户勾选。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSection {
+    FlightRecorder,
+    TelemetryLogs,
+    Config,
+    Device,
+    Database,
+}
+
+impl DiagnosticSection {
+    pub const ALL: [DiagnosticSection; 5] = [
+        DiagnosticSection::FlightRecorder,
+        DiagnosticSection::TelemetryLogs,
+        DiagnosticSection::Config,
+        DiagnosticSection::Device,
+        DiagnosticSection::Database,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticSection::FlightRecorder => "flight_recorder",
+            DiagnosticSection::TelemetryLogs => "telemetry_logs",
+            DiagnosticSection::Config => "config",
+            DiagnosticSection::Device => "device",
+            DiagnosticSection::Database => "database",
+        }
+    }
+
+    /// 征求同意时展示给用户的说明。
+    pub fn description(&self) -> &'static str {
+        match self {
+            DiagnosticSection::FlightRecorder => {
+                "最近几分钟的内部事件：音频帧长度、识别延迟与字数、提示和状态变化，不含转写正文，需设置诊断包口令"
+            }
+            DiagnosticSection::TelemetryLogs => "最近两天的运行日志，需设置诊断包口令",
+            DiagnosticSection::Config => "应用配置，API Key、口令等敏感值已替换为占位符",
+            DiagnosticSection::Device => "操作系统、处理器与加速器信息，以及引擎配置和延迟校准结果",
+            DiagnosticSection::Database => "历史数据库的完整性检查结果，不含历史内容",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsRequest {
+    /// 用户已阅读各项说明并确认导出；为 `false` 时拒绝导出。
+    pub consent: bool,
+    /// 用户同意包含的内容，至少一项。
+    pub sections: Vec<DiagnosticSection>,
+    /// 诊断包的输出目录。
+    pub output_dir: PathBuf,
+    /// 飞行记录与遥测日志以该口令加密后放入诊断包，口令至少 8 字节；未设置时跳过这两项。
+    pub passphrase: Option<String>,
+}
+
+/// 诊断包的数据来源；缺失的来源在清单中注明，不影响其余内容。
+pub struct DiagnosticSources {
+    pub flight_recorder: Option<Arc<FlightRecorder>>,
+    pub config: Option<FlowwisperConfig>,
+    pub device: Option<Value>,
+    pub database: Option<Arc<SqlitePersistence>>,
+    pub log_max_age: Duration,
+    /// 遥测日志目录；未设置时使用当前配置档的日志目录。
+    pub log_dir: Option<PathBuf>,
+    /// 遥测日志的解密密钥；未设置时使用当前配置档的日志密钥。
+    pub log_key: Option<TelemetryLogKey>,
+}
+
+impl Default for DiagnosticSources {
+    fn default() -> Self {
+        Self {
+            flight_recorder: None,
+            config: None,
+            device: None,
+            database: None,
+            log_max_age: DEFAULT_LOG_MAX_AGE,
+            log_dir: None,
+            log_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsBundle {
+    pub path: PathBuf,
+    /// 实际写入的内容。
+    pub sections: Vec<DiagnosticSection>,
+    pub size_bytes: u64,
+    /// 跳过或部分失败的内容说明，同样写入清单。
+    pub notes: Vec<String>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// 基础设备信息；宿主可在返回值上补充引擎与校准状态。
+pub fn device_report() -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "hardware": crate::orchestrator::HardwareProfile::probe(),
+    })
+}
+
+/// 生成诊断包。会阻塞读取日志与数据库，异步调用方应放在 `spawn_blocking` 中执行。
+pub fn export_bundle(
+    request: &DiagnosticsRequest,
+    sources: &DiagnosticSources,
+) -> Result<DiagnosticsBundle, DiagnosticsError> {
+    if !request.consent {
+        return Err(DiagnosticsError::ConsentRequired);
+    }
+    if request.sections.is_empty() {
+        return Err(DiagnosticsError::NothingSelected);
+    }
+
+    let created_at_ms = now_ms();
+    fs::create_dir_all(&request.output_dir)?;
+    let path = request
+        .output_dir
+        .join(format!("flowwisper-diagnostics-{created_at_ms}.zip"));
+    let mut zip = ZipWriter::new(File::create(&path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut included = Vec::new();
+    let mut notes = Vec::new();
+
+    for section in DiagnosticSection::ALL {
+        if !request.sections.contains(&section) {
+            continue;
+        }
+        let written = match section {
+            DiagnosticSection::FlightRecorder => {
+                write_flight_recorder(&mut zip, options, request, sources, &mut notes)?
+            }
+            DiagnosticSection::TelemetryLogs => {
+                write_logs(&mut zip, options, request, sources, &mut notes)?
+            }
+            DiagnosticSection::Config => match &sources.config {
+                Some(config) => {
+                    let redacted =
+                        toml::to_string_pretty(&config.redacted()).map_err(anyhow::Error::from)?;
+                    zip.start_file("config.toml", options)?;
+                    zip.write_all(redacted.as_bytes())?;
+                    true
+                }
+                None => false,
+            },
+            DiagnosticSection::Device => match &sources.device {
+                Some(device) => {
+                    zip.start_file("device.json", options)?;
+                    zip.write_all(
+                        &serde_json::to_vec_pretty(device).map_err(anyhow::Error::from)?,
+                    )?;
+                    true
+                }
+                None => false,
+            },
+            DiagnosticSection::Database => match &sources.database {
+                Some(database) => {
+                    let report = match database.integrity_check() {
+                        Ok(results) => json!({
+                            "ok": results.len() == 1 && results[0] == "ok",
+                            "integrityCheck": results,
+                        }),
+                        Err(err) => json!({ "ok": false, "error": format!("{err:#}") }),
+                    };
+                    zip.start_file("database.json", options)?;
+                    zip.write_all(
+                        &serde_json::to_vec_pretty(&report).map_err(anyhow::Error::from)?,
+                    )?;
+                    true
+                }
+                None => false,
+            },
+        };
+        if written {
+            included.push(section);
+        } else if !notes.iter().any(|note| note.starts_with(section.as_str())) {
+            notes.push(format!("{}: not available", section.as_str()));
+        }
+    }
+
+    let manifest = json!({
+        "createdAtMs": created_at_ms,
+        "version": env!("CARGO_PKG_VERSION"),
+        "consented": true,
+        "sections": included,
+        "notes": notes,
+    });
+    zip.start_file("manifest.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest).map_err(anyhow::Error::from)?)?;
+    zip.finish()?;
+
+    Ok(DiagnosticsBundle {
+        size_bytes: fs::metadata(&path)?.len(),
+        path,
+        sections: included,
+        notes,
+    })
+}
+
+fn write_flight_recorder(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    request: &DiagnosticsRequest,
+    sources: &DiagnosticSources,
+    notes: &mut Vec<String>,
+) -> Result<bool, DiagnosticsError> {
+    let Some(recorder) = &sources.flight_recorder else {
+        return Ok(false);
+    };
+    let Some(passphrase) = &request.passphrase else {
+        notes.push("flight_recorder: skipped, a bundle passphrase is required".into());
+        return Ok(false);
+    };
+    match recorder.dump_encrypted(passphrase) {
+        Ok(dump) => {
+            zip.start_file(format!("flight-recorder.{FLIGHT_DUMP_EXTENSION}"), options)?;
+            zip.write_all(&dump)?;
+            Ok(true)
+        }
+        Err(err) => {
+            notes.push(format!("flight_recorder: {err:#}"));
+            Ok(false)
+        }
+    }
+}
+
+/// 解开诊断包中以口令加密的日志（`logs/*.fwdiag`）。
+pub fn open_bundled_log(passphrase: &str, sealed: &[u8]) -> anyhow::Result<Vec<u8>> {
+    archive::open(passphrase, sealed)
+}
+
+/// 日志落盘时逐行加密，打包前用日志密钥解密，再以诊断包口令整体加密。
+fn write_logs(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    request: &DiagnosticsRequest,
+    sources: &DiagnosticSources,
+    notes: &mut Vec<String>,
+) -> Result<bool, DiagnosticsError> {
+    let Some(passphrase) = &request.passphrase else {
+        notes.push("telemetry_logs: skipped, a bundle passphrase is required".into());
+        return Ok(false);
+    };
+    let files = match &sources.log_dir {
+        Some(dir) => telemetry::recent_log_files_in(dir, sources.log_max_age),
+        None => telemetry::recent_log_files(sources.log_max_age),
+    };
+    let files = match files {
+        Ok(files) => files,
+        Err(err) => {
+            notes.push(format!("telemetry_logs: {err}"));
+            return Ok(false);
+        }
+    };
+    if files.is_empty() {
+        return Ok(false);
+    }
+    let key = match sources.log_key.clone() {
+        Some(key) => key,
+        None => match TelemetryLogKey::for_active_profile() {
+            Ok(key) => key,
+            Err(err) => {
+                notes.push(format!("telemetry_logs: {err:#}"));
+                return Ok(false);
+            }
+        },
+    };
+    let mut total = 0_u64;
+    let mut written = false;
+    for file in files {
+        let Some(name) = file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+        let mut plaintext = Vec::new();
+        let decrypted = File::open(&file)
+            .map_err(anyhow::Error::from)
+            .and_then(|log| decrypt_log(&key, BufReader::new(log), &mut plaintext));
+        if let Err(err) = decrypted {
+            notes.push(format!("telemetry_logs: failed to read {name}: {err:#}"));
+            continue;
+        }
+        if total + plaintext.len() as u64 > MAX_LOG_BYTES {
+            notes.push("telemetry_logs: older logs omitted to stay within the size limit".into());
+            break;
+        }
+        total += plaintext.len() as u64;
+        let sealed = match archive::seal(passphrase, plaintext) {
+            Ok(sealed) => sealed,
+            Err(err) => {
+                notes.push(format!("telemetry_logs: {err:#}"));
+                return Ok(written);
+            }
+        };
+        zip.start_file(format!("logs/{name}.{FLIGHT_DUMP_EXTENSION}"), options)?;
+        zip.write_all(&sealed)?;
+        written = true;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::flight_recorder::{open_flight_dump, FlightRecord};
+    use crate::telemetry::sealed::SealedLineWriter;
+    use std::io::Read;
+
+    #[test]
+    fn requires_consent_and_writes_redacted_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Arc::new(FlightRecorder::default());
+        recorder.record(
+            "session-1",
+            FlightRecord::Notice {
+                level: "Warn".into(),
+                message: "slow".into(),
+            },
+        );
+        let mut config = FlowwisperConfig::default();
+        config.polisher.api_key = Some("sk-live-secret".into());
+        let sources = DiagnosticSources {
+            flight_recorder: Some(recorder),
+            config: Some(config),
+            device: Some(device_report()),
+            ..DiagnosticSources::default()
+        };
+        let mut request = DiagnosticsRequest {
+            consent: false,
+            sections: vec![
+                DiagnosticSection::FlightRecorder,
+                DiagnosticSection::Config,
+                DiagnosticSection::Device,
+                DiagnosticSection::Database,
+            ],
+            output_dir: dir.path().to_path_buf(),
+            passphrase: None,
+        };
+        assert!(matches!(
+            export_bundle(&request, &sources),
+            Err(DiagnosticsError::ConsentRequired)
+        ));
+
+        request.consent = true;
+        let bundle = export_bundle(&request, &sources).unwrap();
+        assert_eq!(
+            bundle.sections,
+            vec![DiagnosticSection::Config, DiagnosticSection::Device]
+        );
+        assert_eq!(
+            bundle.notes,
+            vec![
+                "flight_recorder: skipped, a bundle passphrase is required".to_string(),
+                "database: not available".to_string(),
+            ]
+        );
+
+        let mut archive = zip::ZipArchive::new(File::open(&bundle.path).unwrap()).unwrap();
+        let mut config = String::new();
+        archive
+            .by_name("config.toml")
+            .unwrap()
+            .read_to_string(&mut config)
+            .unwrap();
+        assert!(config.contains("<redacted>") && !config.contains("sk-live-secret"));
+        assert!(archive.by_name("flight-recorder.json").is_err());
+        assert!(archive.by_name("manifest.json").is_ok());
+
+        request.passphrase = Some("support-ticket-42".into());
+        let bundle = export_bundle(&request, &sources).unwrap();
+        assert_eq!(bundle.sections.len(), 3);
+        let mut archive = zip::ZipArchive::new(File::open(&bundle.path).unwrap()).unwrap();
+        let mut dump = Vec::new();
+        archive
+            .by_name(&format!("flight-recorder.{FLIGHT_DUMP_EXTENSION}"))
+            .unwrap()
+            .read_to_end(&mut dump)
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&dump).contains("slow"));
+        let flight = open_flight_dump("support-ticket-42", &dump).unwrap();
+        assert!(serde_json::to_string(&flight).unwrap().contains("slow"));
+    }
+
+    #[test]
+    fn bundles_sealed_logs_readable_with_the_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_dir = dir.path().join("logs");
+        fs::create_dir_all(&log_dir).unwrap();
+        let key = TelemetryLogKey::derive(&[9u8; 32]).unwrap();
+        let mut writer = SealedLineWriter::new(
+            File::create(log_dir.join("dual-view.json.2026-10-17")).unwrap(),
+            key.clone(),
+        );
+        writer
+            .write_all(b"{\"event\":\"latency\",\"ms\":42}\n")
+            .unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let sources = DiagnosticSources {
+            log_dir: Some(log_dir),
+            log_key: Some(key),
+            ..DiagnosticSources::default()
+        };
+        let mut request = DiagnosticsRequest {
+            consent: true,
+            sections: vec![DiagnosticSection::TelemetryLogs],
+            output_dir: dir.path().join("out"),
+            passphrase: None,
+        };
+        let bundle = export_bundle(&request, &sources).unwrap();
+        assert!(bundle.sections.is_empty());
+        assert_eq!(
+            bundle.notes,
+            vec!["telemetry_logs: skipped, a bundle passphrase is required".to_string()]
+        );
+
+        request.passphrase = Some("support-ticket-42".into());
+        let bundle = export_bundle(&request, &sources).unwrap();
+        assert_eq!(bundle.sections, vec![DiagnosticSection::TelemetryLogs]);
+        let mut archive_file = zip::ZipArchive::new(File::open(&bundle.path).unwrap()).unwrap();
+        let mut sealed = Vec::new();
+        archive_file
+            .by_name(&format!(
+                "logs/dual-view.json.2026-10-17.{FLIGHT_DUMP_EXTENSION}"
+            ))
+            .unwrap()
+            .read_to_end(&mut sealed)
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("latency"));
+        let plaintext = open_bundled_log("support-ticket-42", &sealed).unwrap();
+        assert_eq!(plaintext, b"{\"event\":\"latency\",\"ms\":42}\n");
+    }
+}
@@ -0,0 +1,221 @@
+//! 会话更新的双通道分发：Warn/Error 提示与语音指令、选句等控制类更新走保证送达的优先通道，
+//! 转写与增量等高频更新走有界的尽力通道，消费方跟不上时丢弃最旧的一条。
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::{mpsc, Notify};
+use tracing::warn;
+
+use crate::orchestrator::{NoticeLevel, SessionNotice, TranscriptionUpdate, UpdatePayload};
+use crate::session::shutdown::CancellationToken;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+    /// 不丢弃，先于尽力通道投递。
+    Priority,
+    /// 队列满时丢弃最旧的更新。
+    BestEffort,
+}
+
+pub fn lane_of(update: &TranscriptionUpdate) -> Lane {
+    match &update.payload {
+        UpdatePayload::Notice(SessionNotice {
+            level: NoticeLevel::Warn | NoticeLevel::Error,
+            ..
+        })
+        | UpdatePayload::Command(_)
+        | UpdatePayload::Selection(_) => Lane::Priority,
+        _ => Lane::BestEffort,
+    }
+}
+
+#[derive(Default)]
+struct Lanes {
+    priority: VecDeque<TranscriptionUpdate>,
+    best_effort: VecDeque<TranscriptionUpdate>,
+    closed: bool,
+}
+
+/// 单个消费方的分发器；`dispatch` 不会阻塞，由后台任务按优先级写入消费方的通道。
+/// 分发器被丢弃后，已排队的更新投递完毕即关闭通道；`shutdown` 取消时投递任务立即退出。
+pub struct UpdateDispatcher {
+    lanes: Arc<StdMutex<Lanes>>,
+    notify: Arc<Notify>,
+    capacity: usize,
+    tx: mpsc::Sender<TranscriptionUpdate>,
+}
+
+impl UpdateDispatcher {
+    /// `capacity` 同时限定消费方通道与尽力通道的长度。
+    pub fn channel(
+        capacity: usize,
+        shutdown: &CancellationToken,
+    ) -> (Self, mpsc::Receiver<TranscriptionUpdate>) {
+        let capacity = capacity.max(1);
+        let (tx, rx) = mpsc::channel(capacity);
+        let lanes = Arc::new(StdMutex::new(Lanes::default()));
+        let notify = Arc::new(Notify::new());
+        let delivery = deliver(Arc::clone(&lanes), Arc::clone(&notify), tx.clone());
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown.run_until_cancelled(delivery).await;
+        });
+        (
+            Self {
+                lanes,
+                notify,
+                capacity,
+                tx,
+            },
+            rx,
+        )
+    }
+
+    /// 按通道排队；消费方已关闭时返回 `false`。
+    pub fn dispatch(&self, update: TranscriptionUpdate) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        {
+            let mut lanes = self
+                .lanes
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            match lane_of(&update) {
+                Lane::Priority => lanes.priority.push_back(update),
+                Lane::BestEffort => {
+                    if lanes.best_effort.len() >= self.capacity {
+                        lanes.best_effort.pop_front();
+                        warn!(
+                            target: "session_manager",
+                            "dropping oldest session update due to slow consumer"
+                        );
+                    }
+                    lanes.best_effort.push_back(update);
+                }
+            }
+        }
+        self.notify.notify_one();
+        true
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl Drop for UpdateDispatcher {
+    fn drop(&mut self) {
+        self.lanes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .closed = true;
+        self.notify.notify_one();
+    }
+}
+
+async fn deliver(
+    lanes: Arc<StdMutex<Lanes>>,
+    notify: Arc<Notify>,
+    tx: mpsc::Sender<TranscriptionUpdate>,
+) {
+    loop {
+        let (next, closed) = {
+            let mut lanes = lanes
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let next = match lanes.priority.pop_front() {
+                Some(update) => Some(update),
+                None => lanes.best_effort.pop_front(),
+            };
+            (next, lanes.closed)
+        };
+        match next {
+            Some(update) => {
+                if tx.send(update).await.is_err() {
+                    return;
+                }
+            }
+            None if closed => return,
+            None => notify.notified().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{TranscriptPayload, TranscriptSource};
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn transcript(sentence_id: u64) -> TranscriptionUpdate {
+        TranscriptionUpdate {
+            payload: UpdatePayload::Transcript(TranscriptPayload {
+                sentence_id,
+                text: format!("sentence {sentence_id}"),
+                source: TranscriptSource::Local,
+                is_primary: true,
+                within_sla: true,
+                segments: Vec::new(),
+                translation: None,
+                audio_source: Default::default(),
+            }),
+            latency: Duration::ZERO,
+            frame_index: sentence_id as usize,
+            is_first: false,
+        }
+    }
+
+    fn notice(level: NoticeLevel) -> TranscriptionUpdate {
+        TranscriptionUpdate {
+            payload: UpdatePayload::Notice(SessionNotice {
+                level,
+                message: "engine failed".into(),
+            }),
+            latency: Duration::ZERO,
+            frame_index: 0,
+            is_first: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_critical_notices_when_consumer_lags() {
+        let (dispatcher, mut rx) = UpdateDispatcher::channel(2, &CancellationToken::new());
+        for sentence_id in 0..6 {
+            assert!(dispatcher.dispatch(transcript(sentence_id)));
+        }
+        assert!(dispatcher.dispatch(notice(NoticeLevel::Error)));
+        assert!(dispatcher.dispatch(notice(NoticeLevel::Info)));
+        drop(dispatcher);
+
+        let mut received = Vec::new();
+        while let Some(update) = timeout(Duration::from_secs(1), rx.recv()).await.unwrap() {
+            received.push(update);
+        }
+        let errors = received
+            .iter()
+            .filter(|update| lane_of(update) == Lane::Priority)
+            .count();
+        assert_eq!(errors, 1);
+        // 尽力通道只保留最新的更新，且保持原有顺序。
+        let sentences: Vec<u64> = received
+            .iter()
+            .filter_map(|update| match &update.payload {
+                UpdatePayload::Transcript(payload) => Some(payload.sentence_id),
+                _ => None,
+            })
+            .collect();
+        assert!(sentences.len() < 6);
+        assert!(sentences.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(sentences.last(), Some(&5));
+    }
+
+    #[tokio::test]
+    async fn reports_closed_consumer() {
+        let (dispatcher, rx) = UpdateDispatcher::channel(1, &CancellationToken::new());
+        drop(rx);
+        assert!(!dispatcher.dispatch(notice(NoticeLevel::Warn)));
+    }
+}
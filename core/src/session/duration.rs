@@ -0,0 +1,245 @@
+//! 会话时长上限：录音接近上限时提醒，达到上限后自动停止并保存已识别的部分结果。
+
+use std::time::{Duration as StdDuration, SystemTime};
+
+use serde_json::json;
+use tracing::warn;
+
+use crate::persistence::{DraftSaveRequest, PersistenceHandle};
+use crate::session::recovery::RecoverySnapshot;
+use crate::session::{system_time_to_ms, SessionManager};
+use crate::telemetry::events::{
+    record_session_draft_failed, record_session_draft_saved, record_session_max_duration_autostop,
+    EVENT_MAX_DURATION_AUTOSTOP,
+};
+
+/// 单次会话的默认最长录音时长，防止遗忘停止的录音耗尽内存或云端额度。
+pub(crate) const DEFAULT_MAX_SESSION_SECS: u64 = 10 * 60;
+
+/// 录音时长达到上限的该比例时发出提醒。
+const DURATION_WARNING_RATIO: f64 = 0.8;
+
+/// 录音时长即将达到上限。
+#[derive(Debug, Clone)]
+pub struct SessionDurationWarning {
+    pub elapsed_ms: u64,
+    pub limit_ms: u64,
+}
+
+/// 累计单次会话已转发的录音时长，依次给出提醒与停止信号。
+pub(super) struct DurationGuard {
+    limit: Option<StdDuration>,
+    recorded: StdDuration,
+    warned: bool,
+}
+
+impl DurationGuard {
+    pub(super) fn new(limit: Option<StdDuration>) -> Self {
+        Self {
+            limit,
+            recorded: StdDuration::ZERO,
+            warned: false,
+        }
+    }
+
+    pub(super) fn record(&mut self, duration: StdDuration) {
+        self.recorded += duration;
+    }
+
+    /// 首次越过提醒比例时返回提醒，之后不再重复。
+    pub(super) fn take_warning(&mut self) -> Option<SessionDurationWarning> {
+        let limit = self.limit?;
+        if self.warned || self.recorded < limit.mul_f64(DURATION_WARNING_RATIO) {
+            return None;
+        }
+        self.warned = true;
+        Some(SessionDurationWarning {
+            elapsed_ms: self.recorded.as_millis() as u64,
+            limit_ms: limit.as_millis() as u64,
+        })
+    }
+
+    /// 已达到上限时返回该上限。
+    pub(super) fn reached(&self) -> Option<StdDuration> {
+        self.limit.filter(|limit| self.recorded >= *limit)
+    }
+}
+
+impl SessionManager {
+    pub fn max_session_duration(&self) -> Option<StdDuration> {
+        *self
+            .max_session_duration
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 设置单次会话的最长录音时长，`None` 表示不限制；对之后开始的会话生效。
+    pub fn set_max_session_duration(&self, limit: Option<StdDuration>) {
+        *self
+            .max_session_duration
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = limit;
+    }
+}
+
+/// 会话因超长被自动停止：上报遥测，并把已识别的部分结果保存为草稿。
+pub(super) async fn persist_max_duration_stop(
+    persistence: &PersistenceHandle,
+    partial: Option<RecoverySnapshot>,
+    limit: StdDuration,
+) {
+    let session_id = partial
+        .as_ref()
+        .map(|snapshot| snapshot.session_id.clone())
+        .unwrap_or_else(|| "unassigned".to_string());
+    let timestamp = SystemTime::now();
+    record_session_max_duration_autostop(&session_id, limit, timestamp);
+
+    let queue_payload = json!({
+        "sessionId": session_id,
+        "timestampMs": system_time_to_ms(timestamp),
+        "reason": "maxDuration",
+        "limitMs": limit.as_millis() as u64,
+    });
+    if let Err(err) = persistence
+        .enqueue_telemetry(
+            session_id.clone(),
+            EVENT_MAX_DURATION_AUTOSTOP.to_string(),
+            queue_payload,
+        )
+        .await
+    {
+        warn!(
+            target: "session_manager",
+            %err,
+            "failed to queue max duration autostop telemetry",
+        );
+    }
+
+    let Some(snapshot) = partial else {
+        return;
+    };
+    let content = snapshot.polished_transcript();
+    if content.trim().is_empty() {
+        return;
+    }
+    let request = DraftSaveRequest {
+        draft_id: format!("{session_id}-max-duration"),
+        session_id: session_id.clone(),
+        content,
+        title: None,
+        tags: Some(vec!["maxDuration".to_string()]),
+    };
+    match persistence.save_draft(request).await {
+        Ok(record) => record_session_draft_saved(&session_id, &record.draft_id, &record.tags),
+        Err(err) => record_session_draft_failed(&session_id, err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator, RealtimeSessionConfig};
+    use crate::session::tests::ProgrammedSpeechEngine;
+    use crate::session::{AutoStopReason, SessionAutoStop, SessionEvent};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[test]
+    fn duration_guard_warns_once_then_reports_limit() {
+        let mut guard = DurationGuard::new(Some(StdDuration::from_millis(500)));
+        guard.record(StdDuration::from_millis(300));
+        assert!(guard.take_warning().is_none());
+        guard.record(StdDuration::from_millis(100));
+        let warning = guard.take_warning().expect("warning at 80%");
+        assert_eq!((warning.elapsed_ms, warning.limit_ms), (400, 500));
+        assert!(guard.take_warning().is_none());
+        assert!(guard.reached().is_none());
+        guard.record(StdDuration::from_millis(100));
+        assert_eq!(guard.reached(), Some(StdDuration::from_millis(500)));
+
+        let mut unlimited = DurationGuard::new(None);
+        unlimited.record(StdDuration::from_secs(3_600));
+        assert!(unlimited.take_warning().is_none());
+        assert!(unlimited.reached().is_none());
+    }
+
+    #[tokio::test]
+    async fn stops_session_at_max_duration_and_saves_partial_transcript() {
+        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(
+            "runaway recording.".to_string()
+        )]));
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            local_engine,
+        );
+        let manager = SessionManager::with_orchestrator(orchestrator);
+        manager.run().await.expect("bootstrap should succeed");
+        manager.set_max_session_duration(Some(StdDuration::from_millis(500)));
+        manager.set_active_session_id("session-max-duration").await;
+        let mut events = manager.subscribe_events();
+
+        let config = RealtimeSessionConfig {
+            enable_polisher: false,
+            ..RealtimeSessionConfig::default()
+        };
+        let (_handle, mut client_rx) = manager.start_realtime_transcription(config).await;
+        let audio = manager.audio_pipeline();
+        audio
+            .push_pcm_frame(vec![0.25_f32; 1_600])
+            .await
+            .expect("push pcm frame");
+        timeout(Duration::from_millis(600), client_rx.recv())
+            .await
+            .expect("client channel timed out")
+            .expect("client channel closed");
+        for _ in 0..5 {
+            audio
+                .push_pcm_frame(vec![0.25_f32; 1_600])
+                .await
+                .expect("push pcm frame");
+        }
+
+        let warning = timeout(Duration::from_millis(600), events.recv())
+            .await
+            .expect("warning timed out")
+            .expect("event channel closed");
+        match warning {
+            SessionEvent::DurationWarning(payload) => {
+                assert_eq!(payload.limit_ms, 500);
+                assert_eq!(payload.elapsed_ms, 400);
+            }
+            other => panic!("expected duration warning, got {other:?}"),
+        }
+        let stop = timeout(Duration::from_millis(600), events.recv())
+            .await
+            .expect("auto-stop timed out")
+            .expect("event channel closed");
+        assert!(matches!(
+            stop,
+            SessionEvent::AutoStop(SessionAutoStop {
+                reason: AutoStopReason::MaxDuration
+            })
+        ));
+
+        let persistence = manager.persistence_handle();
+        let draft = timeout(Duration::from_secs(2), async {
+            loop {
+                let drafts = persistence.list_drafts(10).await.expect("list drafts");
+                if let Some(draft) = drafts
+                    .into_iter()
+                    .find(|draft| draft.session_id == "session-max-duration")
+                {
+                    break draft;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("partial transcript saved");
+        assert_eq!(draft.content, "runaway recording.");
+    }
+}
@@ -0,0 +1,299 @@
+//! 飞行记录器：在环形缓冲中保留最近一段时间的内部事件，出问题时导出为加密转储附在问题报告里。
+//!
+//! 只记录元数据：音频帧只记长度与是否为语音，转写只记来源、延迟与字数，不保存正文。
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::capture::CaptureEvent;
+use super::lifecycle::{SessionLifecyclePayload, SessionLifecycleUpdate};
+use crate::orchestrator::{TranscriptionUpdate, UpdatePayload};
+use crate::persistence::backup::archive;
+
+/// 默认保留最近 5 分钟的事件。
+pub const DEFAULT_FLIGHT_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// 事件条数上限；16 kHz、100 ms 一帧时 5 分钟约 3000 帧，留足余量给其他事件。
+pub const DEFAULT_FLIGHT_CAPACITY: usize = 20_000;
+/// 转储文件扩展名；加密格式与历史备份相同，使用口令解密。
+pub const FLIGHT_DUMP_EXTENSION: &str = "fwdiag";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FlightRecord {
+    Frame {
+        samples: usize,
+        duration_ms: u64,
+        speech: bool,
+    },
+    EngineUpdate {
+        payload: String,
+        frame_index: usize,
+        latency_ms: u64,
+        source: Option<String>,
+        chars: usize,
+    },
+    Notice {
+        level: String,
+        message: String,
+    },
+    Lifecycle {
+        phase: String,
+        detail: Option<String>,
+    },
+    Capture {
+        transition: String,
+        trigger: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlightEvent {
+    pub at_ms: i64,
+    pub session_id: Option<String>,
+    #[serde(flatten)]
+    pub record: FlightRecord,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlightRecording {
+    pub generated_at_ms: i64,
+    pub window_secs: u64,
+    /// 因超出时间窗口或条数上限被丢弃的事件数。
+    pub dropped: u64,
+    pub events: Vec<FlightEvent>,
+}
+
+#[derive(Debug, Default)]
+struct Ring {
+    events: VecDeque<FlightEvent>,
+    dropped: u64,
+}
+
+pub struct FlightRecorder {
+    window: Duration,
+    capacity: usize,
+    ring: Mutex<Ring>,
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        Self::new(DEFAULT_FLIGHT_WINDOW, DEFAULT_FLIGHT_CAPACITY)
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn session_label(session_id: &str) -> Option<String> {
+    (!session_id.is_empty()).then(|| session_id.to_string())
+}
+
+impl FlightRecorder {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            window,
+            capacity: capacity.max(1),
+            ring: Mutex::new(Ring::default()),
+        }
+    }
+
+    pub fn record(&self, session_id: &str, record: FlightRecord) {
+        self.record_at(now_ms(), session_id, record);
+    }
+
+    fn record_at(&self, at_ms: i64, session_id: &str, record: FlightRecord) {
+        let mut ring = self
+            .ring
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        ring.events.push_back(FlightEvent {
+            at_ms,
+            session_id: session_label(session_id),
+            record,
+        });
+        let oldest = at_ms - self.window.as_millis() as i64;
+        while ring.events.len() > self.capacity
+            || ring
+                .events
+                .front()
+                .is_some_and(|event| event.at_ms < oldest)
+        {
+            ring.events.pop_front();
+            ring.dropped += 1;
+        }
+    }
+
+    pub fn record_frame(&self, session_id: &str, frame: &[f32], duration: Duration, speech: bool) {
+        self.record(
+            session_id,
+            FlightRecord::Frame {
+                samples: frame.len(),
+                duration_ms: duration.as_millis() as u64,
+                speech,
+            },
+        );
+    }
+
+    /// 记录编排器下发的更新；提示按 `Notice` 记录，其余只保留类型、来源、延迟与字数。
+    pub fn record_update(&self, session_id: &str, update: &TranscriptionUpdate) {
+        let (payload, source, chars) = match &update.payload {
+            UpdatePayload::Notice(notice) => {
+                self.record(
+                    session_id,
+                    FlightRecord::Notice {
+                        level: format!("{:?}", notice.level),
+                        message: notice.message.clone(),
+                    },
+                );
+                return;
+            }
+            UpdatePayload::Transcript(transcript) => (
+                "transcript",
+                Some(transcript.source.as_str().to_string()),
+                transcript.text.chars().count(),
+            ),
+            UpdatePayload::Selection(_) => ("selection", None, 0),
+            UpdatePayload::Command(command) => ("command", None, command.utterance.chars().count()),
+            UpdatePayload::PolishDelta(delta) => ("polish_delta", None, delta.text.chars().count()),
+            UpdatePayload::LanguageChanged(changed) => {
+                ("language_changed", Some(changed.language.clone()), 0)
+            }
+            UpdatePayload::TranscriptDelta(_) => ("transcript_delta", None, 0),
+            UpdatePayload::QualityFlag(flag) => ("quality_flag", None, flag.text.chars().count()),
+            UpdatePayload::Arbitration(_) => ("arbitration", None, 0),
+        };
+        self.record(
+            session_id,
+            FlightRecord::EngineUpdate {
+                payload: payload.to_string(),
+                frame_index: update.frame_index,
+                latency_ms: update.latency.as_millis() as u64,
+                source,
+                chars,
+            },
+        );
+    }
+
+    pub fn record_lifecycle(&self, update: &SessionLifecycleUpdate) {
+        let detail = match &update.payload {
+            SessionLifecyclePayload::None => None,
+            SessionLifecyclePayload::Publishing(publishing) => Some(format!(
+                "attempt={} strategy={:?}",
+                publishing.attempt, publishing.strategy
+            )),
+            SessionLifecyclePayload::Completed(completed) => {
+                Some(format!("{:?}", completed.outcome))
+            }
+            SessionLifecyclePayload::Failed(failure) => Some(failure.error.clone()),
+            SessionLifecyclePayload::Warmup(status) => {
+                Some(format!("local={:?} cloud={:?}", status.local, status.cloud))
+            }
+        };
+        self.record(
+            &update.session_id,
+            FlightRecord::Lifecycle {
+                phase: format!("{:?}", update.phase),
+                detail,
+            },
+        );
+    }
+
+    pub fn record_capture(&self, event: &CaptureEvent) {
+        self.record(
+            "",
+            FlightRecord::Capture {
+                transition: format!("{:?}", event.transition),
+                trigger: format!("{:?}", event.trigger),
+            },
+        );
+    }
+
+    pub fn snapshot(&self) -> FlightRecording {
+        let ring = self
+            .ring
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        FlightRecording {
+            generated_at_ms: now_ms(),
+            window_secs: self.window.as_secs(),
+            dropped: ring.dropped,
+            events: ring.events.iter().cloned().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .events
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 以口令加密当前记录，口令至少 8 字节。
+    pub fn dump_encrypted(&self, passphrase: &str) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(&self.snapshot())?;
+        archive::seal(passphrase, json)
+    }
+}
+
+/// 解密 [`FlightRecorder::dump_encrypted`] 生成的转储。
+pub fn open_flight_dump(passphrase: &str, dump: &[u8]) -> Result<FlightRecording> {
+    let json = archive::open(passphrase, dump)?;
+    serde_json::from_slice(&json).context("flight recorder dump is not valid JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_recent_window_and_round_trips_encrypted_dumps() {
+        let recorder = FlightRecorder::new(Duration::from_secs(60), 3);
+        let frame = |at_ms| {
+            recorder.record_at(
+                at_ms,
+                "session-1",
+                FlightRecord::Frame {
+                    samples: 1_600,
+                    duration_ms: 100,
+                    speech: true,
+                },
+            )
+        };
+        frame(0);
+        frame(30_000);
+        frame(70_000);
+        assert_eq!(recorder.len(), 2);
+        frame(71_000);
+        recorder.record_at(
+            72_000,
+            "",
+            FlightRecord::Lifecycle {
+                phase: "Idle".into(),
+                detail: None,
+            },
+        );
+        let recording = recorder.snapshot();
+        assert_eq!(recording.events.len(), 3);
+        assert_eq!(recording.dropped, 2);
+        assert_eq!(recording.events[0].at_ms, 70_000);
+        assert_eq!(recording.events[2].session_id, None);
+
+        let dump = recorder.dump_encrypted("correct horse").unwrap();
+        assert!(open_flight_dump("wrong passphrase", &dump).is_err());
+        let opened = open_flight_dump("correct horse", &dump).unwrap();
+        assert_eq!(opened.events, recording.events);
+    }
+}
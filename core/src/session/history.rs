@@ -3,6 +3,22 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::cmp::min;
+use std::time::Duration;
+
+use crate::orchestrator::{LanguageSegment, MeetingNotes, QualityFlag};
+use crate::session::calendar::CALENDAR_METADATA_KEY;
+
+pub mod actions;
+pub mod export;
+pub mod import;
+
+pub use actions::{
+    ActionPlugin, ActionRegistry, CopyAction, OpenUrlAction, RunOutcome, WebhookAction,
+};
+pub use export::{
+    ExportFields, ExportFormat, ExportRequest, ExportSelection, ExportService, ExportSummary,
+};
+pub use import::{HistoryArchive, ImportSource, ImportSummary};
 
 /// History retention in hours. Sessions older than this window will be purged.
 pub const HISTORY_RETENTION_HOURS: i64 = 48;
@@ -14,19 +30,15 @@ pub const HISTORY_PREVIEW_LIMIT: usize = 120;
 /// Accuracy flag captured from user feedback flows.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
+#[derive(Default)]
 pub enum AccuracyFlag {
     Accurate,
     InaccurateRaw,
     InaccuratePolished,
+    #[default]
     Unknown,
 }
 
-impl Default for AccuracyFlag {
-    fn default() -> Self {
-        AccuracyFlag::Unknown
-    }
-}
-
 impl AccuracyFlag {
     /// Returns the canonical string value persisted in the database.
     pub fn as_str(&self) -> &'static str {
@@ -49,6 +61,7 @@ impl AccuracyFlag {
 }
 
 /// Post actions triggered from history detail (copy, reinsert, export, etc.).
+/// Kinds backed by an [`ActionPlugin`] are executed when recorded.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum HistoryActionKind {
@@ -57,6 +70,10 @@ pub enum HistoryActionKind {
     Export,
     SaveDraft,
     ClipboardBackup,
+    OpenUrl,
+    Webhook,
+    /// Third-party plugin named by `detail.plugin`.
+    Plugin,
 }
 
 impl HistoryActionKind {
@@ -67,6 +84,9 @@ impl HistoryActionKind {
             HistoryActionKind::Export => "export",
             HistoryActionKind::SaveDraft => "save_draft",
             HistoryActionKind::ClipboardBackup => "clipboard_backup",
+            HistoryActionKind::OpenUrl => "open_url",
+            HistoryActionKind::Webhook => "webhook",
+            HistoryActionKind::Plugin => "plugin",
         }
     }
 }
@@ -95,6 +115,34 @@ impl HistoryPostAction {
     }
 }
 
+/// Sessions with less voiced audio than this report no words-per-minute figure,
+/// since a short burst gives a meaningless rate.
+pub const MIN_SPEED_SAMPLE_MS: i64 = 2_000;
+
+/// Dictation pace of one session: final word count over the time spent speaking.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DictationSpeed {
+    /// Voiced audio as judged by the VAD, excluding pauses.
+    pub speech_duration_ms: i64,
+    pub words: usize,
+    #[serde(default)]
+    pub words_per_minute: Option<f64>,
+}
+
+impl DictationSpeed {
+    pub fn new(words: usize, speech: Duration) -> Self {
+        let speech_duration_ms = speech.as_millis().min(i64::MAX as u128) as i64;
+        let words_per_minute = (speech_duration_ms >= MIN_SPEED_SAMPLE_MS)
+            .then(|| words as f64 * 60_000.0 / speech_duration_ms as f64);
+        Self {
+            speech_duration_ms,
+            words,
+            words_per_minute,
+        }
+    }
+}
+
 /// Snapshot of a completed session ready for persistence.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -116,6 +164,26 @@ pub struct SessionSnapshot {
     pub metadata: serde_json::Value,
     #[serde(default)]
     pub post_actions: Vec<HistoryPostAction>,
+    /// Per-language spans of the polished transcript; derived on insert when empty.
+    #[serde(default)]
+    pub language_segments: Vec<LanguageSegment>,
+    /// Translation of the polished transcript, stored alongside the original.
+    #[serde(default)]
+    pub translated_transcript: Option<String>,
+    #[serde(default)]
+    pub translation_locale: Option<String>,
+    /// Sentences the recognizer was unsure about.
+    #[serde(default)]
+    pub quality_flags: Vec<QualityFlag>,
+    /// Dictation pace measured from voiced audio; absent when no audio was observed.
+    #[serde(default)]
+    pub speed: Option<DictationSpeed>,
+    /// Topics, summary and action items of a meeting-mode session.
+    #[serde(default)]
+    pub meeting: Option<MeetingNotes>,
+    /// Free-form labels such as the calendar meeting and its attendees.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl SessionSnapshot {
@@ -137,7 +205,7 @@ impl SessionSnapshot {
         if text.len() > HISTORY_PREVIEW_LIMIT {
             let preview_end = min(text.len(), HISTORY_PREVIEW_LIMIT);
             text.truncate(preview_end);
-            text.push_str("…");
+            text.push('…');
         }
 
         text
@@ -158,6 +226,12 @@ pub struct HistoryQuery {
     pub limit: usize,
     #[serde(default)]
     pub offset: usize,
+    /// Restrict results to pinned entries.
+    #[serde(default)]
+    pub pinned_only: bool,
+    /// Restrict results to entries carrying this tag (case-insensitive).
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 impl HistoryQuery {
@@ -193,6 +267,26 @@ pub struct HistoryEntry {
     pub post_actions: Vec<HistoryPostAction>,
     #[serde(default)]
     pub metadata: serde_json::Value,
+    /// Pinned entries are kept past the retention window.
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub language_segments: Vec<LanguageSegment>,
+    #[serde(default)]
+    pub translated_transcript: Option<String>,
+    #[serde(default)]
+    pub translation_locale: Option<String>,
+    #[serde(default)]
+    pub quality_flags: Vec<QualityFlag>,
+    #[serde(default)]
+    pub speed: Option<DictationSpeed>,
+    #[serde(default)]
+    pub meeting: Option<MeetingNotes>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Populated only for keyword searches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_hit: Option<HistorySearchHit>,
 }
 
 impl HistoryEntry {
@@ -210,7 +304,13 @@ impl HistoryEntry {
             polished_transcript,
             metadata,
             post_actions,
-            ..
+            language_segments,
+            translated_transcript,
+            translation_locale,
+            quality_flags,
+            speed,
+            meeting,
+            tags,
         } = snapshot;
         let duration_ms = (completed_at_ms - started_at_ms).max(0);
         Self {
@@ -229,8 +329,76 @@ impl HistoryEntry {
             confidence_score,
             raw_transcript,
             polished_transcript,
+            pinned: false,
+            language_segments,
+            translated_transcript,
+            translation_locale,
+            quality_flags,
+            speed,
+            meeting,
+            tags,
+            search_hit: None,
         }
     }
+
+    /// Title of the calendar meeting the session was recorded in, if one was found.
+    pub fn meeting_title(&self) -> Option<&str> {
+        self.metadata[CALENDAR_METADATA_KEY]["title"]
+            .as_str()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+    }
+
+    /// Accuracy update pre-populated with the sentences flagged as low confidence,
+    /// used to seed the accuracy-marking dialog. Existing marks take precedence.
+    pub fn accuracy_draft(&self) -> AccuracyUpdate {
+        let suspects = self
+            .quality_flags
+            .iter()
+            .map(|flag| flag.text.as_str())
+            .collect::<Vec<_>>();
+        let flag = match &self.accuracy_flag {
+            AccuracyFlag::Unknown if !suspects.is_empty() => AccuracyFlag::InaccurateRaw,
+            flag => flag.clone(),
+        };
+        let remarks = self
+            .accuracy_remarks
+            .clone()
+            .or_else(|| (!suspects.is_empty()).then(|| suspects.join("\n")));
+        AccuracyUpdate {
+            session_id: self.session_id.clone(),
+            flag,
+            remarks,
+            corrected_transcript: None,
+        }
+    }
+}
+
+/// Transcript column a keyword search matched in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryMatchField {
+    Raw,
+    Polished,
+}
+
+/// Highlighted span within a search snippet, as char offsets (`end` exclusive).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Ranked full-text match attached to a history entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchHit {
+    pub field: HistoryMatchField,
+    pub snippet: String,
+    pub highlights: Vec<HighlightRange>,
+    /// BM25 score reported by FTS5; lower is more relevant.
+    pub rank: f64,
 }
 
 /// Paginated result returned to UI/IPC clients.
@@ -249,4 +417,8 @@ pub struct AccuracyUpdate {
     pub flag: AccuracyFlag,
     #[serde(default)]
     pub remarks: Option<String>,
+    /// The polished transcript as edited by the user; replaces the stored text
+    /// and feeds the personal correction corpus.
+    #[serde(default)]
+    pub corrected_transcript: Option<String>,
 }
@@ -0,0 +1,557 @@
+//! Pluggable post actions executed from history entries.
+//!
+//! Each [`HistoryPostAction`] resolves to a plugin id: built-in kinds use
+//! [`HistoryActionKind::as_str`], while [`HistoryActionKind::Plugin`] names the
+//! plugin in `detail.plugin`. Kinds without a registered plugin (reinsert,
+//! export, ...) are recorded as-is because the host already performed them.
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{HistoryActionKind, HistoryEntry, HistoryPostAction};
+use crate::persistence::audit::{EgressChannel, EgressRecorder};
+use crate::session::clipboard::ClipboardManager;
+
+const COPY_TIMEOUT: Duration = Duration::from_millis(500);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An action that can be run against a stored session.
+#[async_trait]
+pub trait ActionPlugin: Send + Sync {
+    /// Stable identifier used to look the plugin up from an action.
+    fn id(&self) -> &str;
+
+    /// Runs the action with the caller-supplied parameters and returns a
+    /// result that is persisted alongside them.
+    async fn execute(&self, entry: &HistoryEntry, params: &Value) -> Result<Value>;
+}
+
+/// Registered action plugins, shared between clones.
+#[derive(Clone, Default)]
+pub struct ActionRegistry {
+    plugins: Arc<RwLock<HashMap<String, Arc<dyn ActionPlugin>>>>,
+}
+
+impl std::fmt::Debug for ActionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionRegistry")
+            .field("plugins", &self.ids())
+            .finish()
+    }
+}
+
+impl ActionRegistry {
+    /// Registry preloaded with the copy, open URL and webhook built-ins. Webhook
+    /// posts are recorded through `egress`.
+    pub fn with_builtins(clipboard: ClipboardManager, egress: EgressRecorder) -> Self {
+        let registry = Self::default();
+        registry.register(Arc::new(CopyAction::new(clipboard)));
+        registry.register(Arc::new(OpenUrlAction::default()));
+        registry.register(Arc::new(WebhookAction::new(egress)));
+        registry
+    }
+
+    /// Adds or replaces the plugin with the same id.
+    pub fn register(&self, plugin: Arc<dyn ActionPlugin>) {
+        let mut plugins = self
+            .plugins
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        plugins.insert(plugin.id().to_string(), plugin);
+    }
+
+    pub fn unregister(&self, id: &str) -> bool {
+        let mut plugins = self
+            .plugins
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        plugins.remove(id).is_some()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn ActionPlugin>> {
+        let plugins = self
+            .plugins
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        plugins.get(id).cloned()
+    }
+
+    /// Registered plugin ids in sorted order.
+    pub fn ids(&self) -> Vec<String> {
+        let plugins = self
+            .plugins
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut ids: Vec<String> = plugins.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Executes the plugin behind `action`, if any, and rewrites its detail to
+    /// `{plugin, params, status, result | error}`. Failures are captured in the
+    /// returned action so they can be persisted before being surfaced.
+    pub async fn run(&self, entry: &HistoryEntry, mut action: HistoryPostAction) -> RunOutcome {
+        let id = plugin_id(&action);
+        let Some(plugin) = id.as_deref().and_then(|id| self.get(id)) else {
+            if action.kind == HistoryActionKind::Plugin {
+                let message = format!("no action plugin registered for {id:?}");
+                return RunOutcome {
+                    action,
+                    error: Some(message),
+                };
+            }
+            return RunOutcome {
+                action,
+                error: None,
+            };
+        };
+
+        let params = action.detail.clone();
+        let (detail, error) = match plugin.execute(entry, &params).await {
+            Ok(result) => (
+                json!({
+                    "plugin": plugin.id(),
+                    "params": params,
+                    "status": "succeeded",
+                    "result": result,
+                }),
+                None,
+            ),
+            Err(err) => (
+                json!({
+                    "plugin": plugin.id(),
+                    "params": params,
+                    "status": "failed",
+                    "error": err.to_string(),
+                }),
+                Some(err.to_string()),
+            ),
+        };
+        action.detail = detail;
+        RunOutcome { action, error }
+    }
+}
+
+/// Result of [`ActionRegistry::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOutcome {
+    pub action: HistoryPostAction,
+    pub error: Option<String>,
+}
+
+fn plugin_id(action: &HistoryPostAction) -> Option<String> {
+    match action.kind {
+        HistoryActionKind::Plugin => action
+            .detail
+            .get("plugin")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        ref kind => Some(kind.as_str().to_string()),
+    }
+}
+
+/// Picks the transcript named by `params.field` (`polished` by default).
+fn transcript_field<'a>(entry: &'a HistoryEntry, params: &Value) -> Result<&'a str> {
+    match params.get("field").and_then(Value::as_str) {
+        None | Some("polished") if !entry.polished_transcript.trim().is_empty() => {
+            Ok(&entry.polished_transcript)
+        }
+        None | Some("polished") | Some("raw") => Ok(&entry.raw_transcript),
+        Some("translated") => entry
+            .translated_transcript
+            .as_deref()
+            .ok_or_else(|| anyhow!("session has no translated transcript")),
+        Some(other) => Err(anyhow!("unknown transcript field {other}")),
+    }
+}
+
+/// Replaces `{{placeholder}}` tokens with session fields. Unknown tokens are
+/// left untouched so payloads can carry literal braces.
+pub fn render_template(template: &str, entry: &HistoryEntry) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        match template_field(&after[..end], entry) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+fn template_field(name: &str, entry: &HistoryEntry) -> Option<String> {
+    let value = match name.trim() {
+        "sessionId" => entry.session_id.clone(),
+        "transcript" => entry.polished_transcript.clone(),
+        "rawTranscript" => entry.raw_transcript.clone(),
+        "translatedTranscript" => entry.translated_transcript.clone().unwrap_or_default(),
+        "appIdentifier" => entry.app_identifier.clone().unwrap_or_default(),
+        "locale" => entry.locale.clone().unwrap_or_default(),
+        "completedAtMs" => entry.completed_at_ms.to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Applies [`render_template`] to every string inside a JSON payload, so
+/// substituted text is always escaped correctly.
+pub fn render_payload(payload: &Value, entry: &HistoryEntry) -> Value {
+    match payload {
+        Value::String(text) => Value::String(render_template(text, entry)),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_payload(item, entry))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render_payload(value, entry)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Copies a transcript of the entry to the clipboard.
+pub struct CopyAction {
+    clipboard: ClipboardManager,
+}
+
+impl CopyAction {
+    pub fn new(clipboard: ClipboardManager) -> Self {
+        Self { clipboard }
+    }
+}
+
+#[async_trait]
+impl ActionPlugin for CopyAction {
+    fn id(&self) -> &str {
+        HistoryActionKind::Copy.as_str()
+    }
+
+    async fn execute(&self, entry: &HistoryEntry, params: &Value) -> Result<Value> {
+        let text = transcript_field(entry, params)?;
+        self.clipboard
+            .write_with_backup(text, COPY_TIMEOUT)
+            .await
+            .map_err(|err| anyhow!("{err}"))?
+            .commit();
+        Ok(json!({ "copiedChars": text.chars().count() }))
+    }
+}
+
+type UrlOpener = dyn Fn(&str) -> Result<()> + Send + Sync;
+
+/// Opens `params.url` after substituting URL-encoded session fields, e.g.
+/// `notion://new?content={{transcript}}`.
+pub struct OpenUrlAction {
+    opener: Arc<UrlOpener>,
+}
+
+impl Default for OpenUrlAction {
+    fn default() -> Self {
+        Self::with_opener(Arc::new(open_with_system))
+    }
+}
+
+impl OpenUrlAction {
+    pub fn with_opener(opener: Arc<UrlOpener>) -> Self {
+        Self { opener }
+    }
+
+    pub fn render_url(template: &str, entry: &HistoryEntry) -> Result<String> {
+        let encoded = HistoryEntry {
+            session_id: percent_encode(&entry.session_id),
+            polished_transcript: percent_encode(&entry.polished_transcript),
+            raw_transcript: percent_encode(&entry.raw_transcript),
+            translated_transcript: entry.translated_transcript.as_deref().map(percent_encode),
+            app_identifier: entry.app_identifier.as_deref().map(percent_encode),
+            locale: entry.locale.as_deref().map(percent_encode),
+            ..entry.clone()
+        };
+        let url = render_template(template, &encoded);
+        let scheme = url
+            .split_once(':')
+            .map(|(scheme, _)| scheme.to_ascii_lowercase())
+            .filter(|scheme| {
+                !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || "+-.".contains(ch))
+            })
+            .ok_or_else(|| anyhow!("action url must include a scheme"))?;
+        if matches!(scheme.as_str(), "file" | "javascript" | "data") {
+            return Err(anyhow!(
+                "refusing to open {scheme}: urls from history actions"
+            ));
+        }
+        Ok(url)
+    }
+}
+
+fn open_with_system(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| anyhow!("failed to open url: {err}"))
+}
+
+#[async_trait]
+impl ActionPlugin for OpenUrlAction {
+    fn id(&self) -> &str {
+        HistoryActionKind::OpenUrl.as_str()
+    }
+
+    async fn execute(&self, entry: &HistoryEntry, params: &Value) -> Result<Value> {
+        let template = params
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("open_url action requires a url"))?;
+        let url = Self::render_url(template, entry)?;
+        (self.opener)(&url)?;
+        Ok(json!({ "url": url }))
+    }
+}
+
+/// POSTs a templated JSON payload to `params.url`. Without `params.payload`
+/// the session id and polished transcript are sent.
+#[derive(Default)]
+pub struct WebhookAction {
+    egress: EgressRecorder,
+}
+
+impl WebhookAction {
+    pub fn new(egress: EgressRecorder) -> Self {
+        Self { egress }
+    }
+
+    pub fn payload(entry: &HistoryEntry, params: &Value) -> Value {
+        let template = params.get("payload").cloned().unwrap_or_else(|| {
+            json!({
+                "sessionId": "{{sessionId}}",
+                "transcript": "{{transcript}}",
+            })
+        });
+        render_payload(&template, entry)
+    }
+
+    fn post_blocking(url: &str, headers: &[(String, String)], body: &Value) -> Result<u16> {
+        let mut request = ureq::post(url)
+            .timeout(WEBHOOK_TIMEOUT)
+            .set("Content-Type", "application/json");
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        let response = request
+            .send_string(&body.to_string())
+            .map_err(|err| anyhow!("webhook request failed: {err}"))?;
+        Ok(response.status())
+    }
+}
+
+#[async_trait]
+impl ActionPlugin for WebhookAction {
+    fn id(&self) -> &str {
+        HistoryActionKind::Webhook.as_str()
+    }
+
+    async fn execute(&self, entry: &HistoryEntry, params: &Value) -> Result<Value> {
+        let url = params
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("webhook action requires a url"))?
+            .to_string();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(anyhow!("webhook url must use http or https"));
+        }
+        let headers: Vec<(String, String)> = params
+            .get("headers")
+            .and_then(Value::as_object)
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        value
+                            .as_str()
+                            .map(|value| (name.clone(), render_template(value, entry)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let body = Self::payload(entry, params);
+        self.egress.for_session(&entry.session_id).record(
+            EgressChannel::Webhook,
+            &url,
+            body.to_string().as_bytes(),
+        );
+        let status =
+            tokio::task::spawn_blocking(move || Self::post_blocking(&url, &headers, &body))
+                .await
+                .map_err(|err| anyhow!("blocking webhook task failed: {err}"))??;
+        Ok(json!({ "httpStatus": status }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn entry() -> HistoryEntry {
+        HistoryEntry {
+            session_id: "s-1".into(),
+            started_at_ms: 0,
+            completed_at_ms: 1_000,
+            duration_ms: 1_000,
+            locale: Some("en-US".into()),
+            app_identifier: Some("com.apple.mail".into()),
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: "raw words".into(),
+            polished_transcript: "Ship it & \"done\"".into(),
+            preview: String::new(),
+            accuracy_flag: Default::default(),
+            accuracy_remarks: None,
+            post_actions: Vec::new(),
+            metadata: Value::Null,
+            pinned: false,
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+            search_hit: None,
+        }
+    }
+
+    fn action(kind: HistoryActionKind, detail: Value) -> HistoryPostAction {
+        HistoryPostAction {
+            kind,
+            timestamp_ms: 5,
+            detail,
+        }
+    }
+
+    #[test]
+    fn renders_templates_for_payloads_and_urls() {
+        let payload = WebhookAction::payload(
+            &entry(),
+            &json!({ "payload": { "title": "{{appIdentifier}}", "body": ["{{transcript}}", 3] } }),
+        );
+        assert_eq!(
+            payload,
+            json!({ "title": "com.apple.mail", "body": ["Ship it & \"done\"", 3] })
+        );
+        assert_eq!(
+            WebhookAction::payload(&entry(), &Value::Null)["sessionId"],
+            "s-1"
+        );
+        let mut nested = entry();
+        nested.polished_transcript = "{{rawTranscript}}".into();
+        assert_eq!(
+            render_template("{{transcript}} {{unknown}} {{", &nested),
+            "{{rawTranscript}} {{unknown}} {{"
+        );
+
+        let url = OpenUrlAction::render_url("https://example.com/new?q={{transcript}}", &entry())
+            .unwrap();
+        assert_eq!(
+            url,
+            "https://example.com/new?q=Ship%20it%20%26%20%22done%22"
+        );
+        assert!(OpenUrlAction::render_url("file:///etc/passwd", &entry()).is_err());
+        assert!(OpenUrlAction::render_url("no scheme here", &entry()).is_err());
+    }
+
+    #[tokio::test]
+    async fn runs_registered_plugins_and_records_results() {
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let sink = opened.clone();
+        let registry = ActionRegistry::default();
+        registry.register(Arc::new(OpenUrlAction::with_opener(Arc::new(
+            move |url: &str| {
+                sink.lock().unwrap().push(url.to_string());
+                Ok(())
+            },
+        ))));
+
+        let outcome = registry
+            .run(
+                &entry(),
+                action(
+                    HistoryActionKind::OpenUrl,
+                    json!({ "url": "obsidian://new?content={{sessionId}}" }),
+                ),
+            )
+            .await;
+        assert_eq!(outcome.error, None);
+        assert_eq!(outcome.action.detail["status"], "succeeded");
+        assert_eq!(
+            outcome.action.detail["result"]["url"],
+            "obsidian://new?content=s-1"
+        );
+        assert_eq!(opened.lock().unwrap().len(), 1);
+
+        let failed = registry
+            .run(&entry(), action(HistoryActionKind::OpenUrl, json!({})))
+            .await;
+        assert_eq!(failed.action.detail["status"], "failed");
+        assert!(failed.error.is_some());
+
+        let recorded = registry
+            .run(&entry(), action(HistoryActionKind::Reinsert, json!({})))
+            .await;
+        assert_eq!(recorded.action.detail, json!({}));
+        assert_eq!(recorded.error, None);
+
+        let unknown = registry
+            .run(
+                &entry(),
+                action(HistoryActionKind::Plugin, json!({ "plugin": "jira" })),
+            )
+            .await;
+        assert!(unknown.error.is_some());
+        assert!(registry.unregister("open_url"));
+        assert!(registry.ids().is_empty());
+    }
+}
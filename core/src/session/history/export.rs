@@ -0,0 +1,552 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value as JsonValue};
+
+use super::import::HistoryArchive;
+use super::HistoryEntry;
+
+/// File format produced by [`ExportService`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Json,
+    Csv,
+    /// Lossless archive that can be imported on another machine.
+    Archive,
+    /// Markdown meeting minutes: summary, action items and the transcript by
+    /// topic with speaker labels.
+    MeetingNotes,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "markdown",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Archive => "archive",
+            ExportFormat::MeetingNotes => "meeting_notes",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Archive => "fwh",
+            ExportFormat::MeetingNotes => "md",
+        }
+    }
+}
+
+/// Columns included in an export. The session id is always written; archives
+/// ignore the selection and keep every field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportFields {
+    pub raw_transcript: bool,
+    pub polished_transcript: bool,
+    pub timestamps: bool,
+    pub app_context: bool,
+}
+
+impl Default for ExportFields {
+    fn default() -> Self {
+        Self {
+            raw_transcript: false,
+            polished_transcript: true,
+            timestamps: true,
+            app_context: true,
+        }
+    }
+}
+
+/// Which history entries to export.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum ExportSelection {
+    /// Explicitly selected sessions.
+    Sessions { session_ids: Vec<String> },
+    /// Sessions completed within `[from_ms, to_ms)`.
+    DateRange { from_ms: i64, to_ms: i64 },
+}
+
+/// Export request accepted by `SessionManager::export_history` and the desktop shell.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRequest {
+    pub selection: ExportSelection,
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub fields: ExportFields,
+    pub destination: PathBuf,
+    /// Passphrase sealing an [`ExportFormat::Archive`]; required for archives and
+    /// ignored by the readable formats.
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+impl std::fmt::Debug for ExportRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExportRequest")
+            .field("selection", &self.selection)
+            .field("format", &self.format)
+            .field("fields", &self.fields)
+            .field("destination", &self.destination)
+            .field(
+                "passphrase",
+                &self.passphrase.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// Outcome of a completed export.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    pub entries: usize,
+}
+
+/// Renders history entries into Markdown, JSON or CSV documents, or seals them
+/// into an encrypted archive.
+#[derive(Clone, Default)]
+pub struct ExportService {
+    format: ExportFormat,
+    fields: ExportFields,
+    passphrase: Option<String>,
+}
+
+impl ExportService {
+    pub fn new(format: ExportFormat, fields: ExportFields) -> Self {
+        Self {
+            format,
+            fields,
+            passphrase: None,
+        }
+    }
+
+    /// Passphrase used to seal [`ExportFormat::Archive`] output.
+    pub fn with_passphrase(mut self, passphrase: Option<String>) -> Self {
+        self.passphrase = passphrase;
+        self
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    pub fn fields(&self) -> ExportFields {
+        self.fields
+    }
+
+    /// Render a readable document. Archives are binary; use [`ExportService::write`].
+    pub fn render(&self, entries: &[HistoryEntry]) -> Result<String> {
+        match self.format {
+            ExportFormat::Markdown => Ok(self.render_markdown(entries)),
+            ExportFormat::Json => self.render_json(entries),
+            ExportFormat::Csv => Ok(self.render_csv(entries)),
+            ExportFormat::Archive => bail!("history archives are encrypted and cannot be rendered"),
+            ExportFormat::MeetingNotes => Ok(self.render_meeting_notes(entries)),
+        }
+    }
+
+    /// Render `entries` and write them to `destination`, creating parent
+    /// directories as needed.
+    pub fn write(&self, entries: &[HistoryEntry], destination: &Path) -> Result<ExportSummary> {
+        if destination.as_os_str().is_empty() {
+            bail!("export destination must not be empty");
+        }
+        let document = match self.format {
+            ExportFormat::Archive => self.seal_archive(entries)?,
+            _ => self.render(entries)?.into_bytes(),
+        };
+        if let Some(parent) = destination
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .map_err(|err| anyhow!("failed to create export directory: {err}"))?;
+        }
+        fs::write(destination, document)
+            .map_err(|err| anyhow!("failed to write history export: {err}"))?;
+        Ok(ExportSummary {
+            path: destination.to_path_buf(),
+            format: self.format,
+            entries: entries.len(),
+        })
+    }
+
+    fn render_markdown(&self, entries: &[HistoryEntry]) -> String {
+        let mut output = String::from("# Flowwisper history export\n");
+        for entry in entries {
+            output.push_str(&format!("\n## {}\n\n", entry.session_id));
+            if self.fields.timestamps {
+                output.push_str(&format!(
+                    "- Started: {}\n- Completed: {}\n- Duration: {} ms\n",
+                    format_timestamp(entry.started_at_ms),
+                    format_timestamp(entry.completed_at_ms),
+                    entry.duration_ms
+                ));
+            }
+            if self.fields.app_context {
+                if let Some(app) = &entry.app_identifier {
+                    match &entry.app_version {
+                        Some(version) => output.push_str(&format!("- App: {app} ({version})\n")),
+                        None => output.push_str(&format!("- App: {app}\n")),
+                    }
+                }
+                if let Some(locale) = &entry.locale {
+                    output.push_str(&format!("- Locale: {locale}\n"));
+                }
+            }
+            if self.fields.polished_transcript {
+                output.push_str(&format!(
+                    "\n### Polished\n\n{}\n",
+                    entry.polished_transcript.trim()
+                ));
+            }
+            if self.fields.raw_transcript {
+                output.push_str(&format!("\n### Raw\n\n{}\n", entry.raw_transcript.trim()));
+            }
+        }
+        output
+    }
+
+    /// Entries recorded outside meeting mode fall back to their polished
+    /// transcript so a mixed selection still exports completely.
+    fn render_meeting_notes(&self, entries: &[HistoryEntry]) -> String {
+        let mut output = String::from("# Flowwisper meeting notes\n");
+        for entry in entries {
+            let heading = entry.meeting_title().unwrap_or(&entry.session_id);
+            output.push_str(&format!("\n## {heading}\n"));
+            if self.fields.timestamps {
+                output.push_str(&format!(
+                    "\n- Started: {}\n- Duration: {} ms\n",
+                    format_timestamp(entry.started_at_ms),
+                    entry.duration_ms
+                ));
+            }
+            let Some(meeting) = &entry.meeting else {
+                output.push_str(&format!(
+                    "\n### Transcript\n\n{}\n",
+                    entry.polished_transcript.trim()
+                ));
+                continue;
+            };
+            if !meeting.summary.trim().is_empty() {
+                output.push_str(&format!("\n### Summary\n\n{}\n", meeting.summary.trim()));
+            }
+            if !meeting.action_items.is_empty() {
+                output.push_str("\n### Action items\n\n");
+                for item in &meeting.action_items {
+                    match &item.owner {
+                        Some(owner) => output.push_str(&format!("- [ ] {} ({owner})\n", item.task)),
+                        None => output.push_str(&format!("- [ ] {}\n", item.task)),
+                    }
+                }
+            }
+            for topic in &meeting.topics {
+                output.push_str(&format!(
+                    "\n### {} ({}–{})\n\n",
+                    topic.title,
+                    format_offset(topic.start_ms),
+                    format_offset(topic.end_ms)
+                ));
+                for utterance in &topic.utterances {
+                    output.push_str(&format!(
+                        "- **{}**: {}\n",
+                        utterance.speaker.speaker_label(),
+                        utterance.text
+                    ));
+                }
+            }
+        }
+        output
+    }
+
+    fn render_json(&self, entries: &[HistoryEntry]) -> Result<String> {
+        let rows: Vec<JsonValue> = entries
+            .iter()
+            .map(|entry| {
+                let mut object = Map::new();
+                for (column, value) in self.columns(entry) {
+                    object.insert(column.to_string(), value);
+                }
+                JsonValue::Object(object)
+            })
+            .collect();
+        serde_json::to_string_pretty(&rows)
+            .map_err(|err| anyhow!("failed to encode history export: {err}"))
+    }
+
+    fn seal_archive(&self, entries: &[HistoryEntry]) -> Result<Vec<u8>> {
+        let Some(passphrase) = self.passphrase.as_deref() else {
+            bail!("history archives require a passphrase");
+        };
+        let exported_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+        HistoryArchive::new(exported_at_ms, entries.to_vec()).seal(passphrase)
+    }
+
+    fn render_csv(&self, entries: &[HistoryEntry]) -> String {
+        let header: Vec<&str> = self.column_names();
+        let mut output = header.join(",");
+        output.push_str("\r\n");
+        for entry in entries {
+            let row: Vec<String> = self
+                .columns(entry)
+                .into_iter()
+                .map(|(_, value)| match value {
+                    JsonValue::Null => String::new(),
+                    JsonValue::String(text) => csv_field(&text),
+                    other => csv_field(&other.to_string()),
+                })
+                .collect();
+            output.push_str(&row.join(","));
+            output.push_str("\r\n");
+        }
+        output
+    }
+
+    fn column_names(&self) -> Vec<&'static str> {
+        let mut names = vec!["sessionId"];
+        if self.fields.timestamps {
+            names.extend(["startedAt", "completedAt", "durationMs"]);
+        }
+        if self.fields.app_context {
+            names.extend(["appIdentifier", "appVersion", "locale"]);
+        }
+        if self.fields.polished_transcript {
+            names.push("polishedTranscript");
+        }
+        if self.fields.raw_transcript {
+            names.push("rawTranscript");
+        }
+        names
+    }
+
+    fn columns(&self, entry: &HistoryEntry) -> Vec<(&'static str, JsonValue)> {
+        let mut values = vec![json!(entry.session_id)];
+        if self.fields.timestamps {
+            values.push(json!(format_timestamp(entry.started_at_ms)));
+            values.push(json!(format_timestamp(entry.completed_at_ms)));
+            values.push(json!(entry.duration_ms));
+        }
+        if self.fields.app_context {
+            values.push(json!(entry.app_identifier));
+            values.push(json!(entry.app_version));
+            values.push(json!(entry.locale));
+        }
+        if self.fields.polished_transcript {
+            values.push(json!(entry.polished_transcript));
+        }
+        if self.fields.raw_transcript {
+            values.push(json!(entry.raw_transcript));
+        }
+        self.column_names().into_iter().zip(values).collect()
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Format an offset into a recording as `mm:ss`.
+fn format_offset(offset_ms: u64) -> String {
+    let seconds = offset_ms / 1_000;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Format epoch milliseconds as an RFC 3339 UTC timestamp.
+pub(crate) fn format_timestamp(epoch_ms: i64) -> String {
+    let seconds = epoch_ms.div_euclid(1_000);
+    let millis = epoch_ms.rem_euclid(1_000);
+    let days = seconds.div_euclid(86_400);
+    let secs_of_day = seconds.rem_euclid(86_400);
+
+    // Civil-from-days conversion (proleptic Gregorian calendar).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{millis:03}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioSource;
+    use crate::orchestrator::{ActionItem, MeetingNotes, MeetingTopic, MeetingUtterance};
+    use crate::session::history::AccuracyFlag;
+
+    fn entry(session_id: &str, polished: &str) -> HistoryEntry {
+        HistoryEntry {
+            session_id: session_id.into(),
+            started_at_ms: 1_700_000_000_000,
+            completed_at_ms: 1_700_000_004_500,
+            duration_ms: 4_500,
+            locale: Some("en-US".into()),
+            app_identifier: Some("com.example.notes".into()),
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: "um hello, world".into(),
+            polished_transcript: polished.into(),
+            preview: polished.into(),
+            accuracy_flag: AccuracyFlag::Unknown,
+            accuracy_remarks: None,
+            post_actions: Vec::new(),
+            metadata: JsonValue::Null,
+            pinned: false,
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: None,
+            tags: Vec::new(),
+            search_hit: None,
+        }
+    }
+
+    #[test]
+    fn formats_epoch_millis_as_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp(1_700_000_004_500),
+            "2023-11-14T22:13:24.500Z"
+        );
+    }
+
+    #[test]
+    fn csv_escapes_and_respects_field_selection() {
+        let fields = ExportFields {
+            raw_transcript: true,
+            polished_transcript: true,
+            timestamps: false,
+            app_context: false,
+        };
+        let service = ExportService::new(ExportFormat::Csv, fields);
+        let csv = service
+            .render(&[entry("s-1", "Hello, \"world\".")])
+            .unwrap();
+        assert_eq!(
+            csv,
+            "sessionId,polishedTranscript,rawTranscript\r\n\
+             s-1,\"Hello, \"\"world\"\".\",\"um hello, world\"\r\n"
+        );
+    }
+
+    #[test]
+    fn json_and_markdown_include_requested_context() {
+        let service = ExportService::new(ExportFormat::Json, ExportFields::default());
+        let json: JsonValue =
+            serde_json::from_str(&service.render(&[entry("s-1", "Hello.")]).unwrap()).unwrap();
+        assert_eq!(json[0]["sessionId"], "s-1");
+        assert_eq!(json[0]["appIdentifier"], "com.example.notes");
+        assert_eq!(json[0]["completedAt"], "2023-11-14T22:13:24.500Z");
+        assert!(json[0].get("rawTranscript").is_none());
+
+        let service = ExportService::new(ExportFormat::Markdown, ExportFields::default());
+        let markdown = service.render(&[entry("s-1", "Hello.")]).unwrap();
+        assert!(markdown.contains("## s-1"));
+        assert!(markdown.contains("- App: com.example.notes"));
+        assert!(markdown.contains("### Polished\n\nHello."));
+        assert!(!markdown.contains("### Raw"));
+    }
+
+    #[test]
+    fn meeting_notes_list_summary_actions_and_topics() {
+        let mut meeting = entry("m-1", "me: Ship Friday.");
+        meeting.meeting = Some(MeetingNotes {
+            topics: vec![MeetingTopic {
+                title: "release, friday".into(),
+                start_ms: 5_000,
+                end_ms: 65_000,
+                utterances: vec![
+                    MeetingUtterance {
+                        speaker: AudioSource::SystemLoopback,
+                        offset_ms: 5_000,
+                        text: "Can we release Friday?".into(),
+                    },
+                    MeetingUtterance {
+                        speaker: AudioSource::Microphone,
+                        offset_ms: 65_000,
+                        text: "Yes, I'll tag the release.".into(),
+                    },
+                ],
+            }],
+            summary: "Release moves to Friday.".into(),
+            action_items: vec![ActionItem {
+                owner: Some("me".into()),
+                task: "Tag the release".into(),
+            }],
+        });
+        let service = ExportService::new(ExportFormat::MeetingNotes, ExportFields::default());
+        let notes = service
+            .render(&[meeting, entry("s-2", "Plain dictation.")])
+            .unwrap();
+
+        assert!(notes.contains("### Summary\n\nRelease moves to Friday."));
+        assert!(notes.contains("- [ ] Tag the release (me)"));
+        assert!(notes.contains("### release, friday (00:05–01:05)"));
+        assert!(notes.contains("- **others**: Can we release Friday?"));
+        assert!(notes.contains("## s-2\n"));
+        assert!(notes.contains("### Transcript\n\nPlain dictation."));
+        assert_eq!(ExportFormat::MeetingNotes.extension(), "md");
+    }
+
+    #[test]
+    fn writes_export_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exports").join("history.json");
+        let service = ExportService::new(ExportFormat::Json, ExportFields::default());
+        let summary = service
+            .write(&[entry("s-1", "Hello."), entry("s-2", "Bye.")], &path)
+            .unwrap();
+        assert_eq!(summary.entries, 2);
+        assert!(fs::read_to_string(&path).unwrap().contains("s-2"));
+    }
+
+    #[test]
+    fn archives_are_sealed_with_the_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.fwh");
+        let service = ExportService::new(ExportFormat::Archive, ExportFields::default());
+        assert!(service.write(&[entry("s-1", "Hello.")], &path).is_err());
+
+        let service = service.with_passphrase(Some("correct horse".into()));
+        service.write(&[entry("s-1", "Hello.")], &path).unwrap();
+        let sealed = fs::read(&path).unwrap();
+        assert!(!sealed.windows(6).any(|window| window == b"Hello."));
+        let archive = HistoryArchive::read(&path, "correct horse").unwrap();
+        assert_eq!(archive.entries[0].session_id, "s-1");
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{HistoryEntry, HistoryPostAction};
+use crate::persistence::backup::archive::Envelope;
+
+/// Identifier written into every full-fidelity history archive.
+pub const HISTORY_ARCHIVE_KIND: &str = "flowwisper-history";
+pub const HISTORY_ARCHIVE_VERSION: u32 = 1;
+
+/// Archives leave the SQLCipher database, so they are sealed with a
+/// passphrase-derived key in the same container format as backups.
+const ARCHIVE_ENVELOPE: Envelope = Envelope::new(b"FWHST\x01", "history");
+
+/// Lossless history dump produced by `ExportFormat::Archive` and accepted by
+/// the import path. Unlike the other export formats it always carries every
+/// field, including post actions and metadata.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryArchive {
+    pub kind: String,
+    pub version: u32,
+    pub exported_at_ms: i64,
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistoryArchive {
+    pub fn new(exported_at_ms: i64, entries: Vec<HistoryEntry>) -> Self {
+        Self {
+            kind: HISTORY_ARCHIVE_KIND.to_string(),
+            version: HISTORY_ARCHIVE_VERSION,
+            exported_at_ms,
+            entries,
+        }
+    }
+
+    /// Encode the archive and encrypt it with a key derived from `passphrase`.
+    pub fn seal(&self, passphrase: &str) -> Result<Vec<u8>> {
+        let plaintext = serde_json::to_vec(self)
+            .map_err(|err| anyhow!("failed to encode history archive: {err}"))?;
+        ARCHIVE_ENVELOPE.seal(passphrase, plaintext)
+    }
+
+    /// Decrypt and validate archive bytes produced by [`HistoryArchive::seal`].
+    /// A wrong passphrase or any modification to the file is rejected.
+    pub fn open(sealed: &[u8], passphrase: &str) -> Result<Self> {
+        let plaintext = ARCHIVE_ENVELOPE.open(passphrase, sealed)?;
+        let archive: HistoryArchive = serde_json::from_slice(&plaintext)
+            .map_err(|err| anyhow!("failed to parse history archive: {err}"))?;
+        if archive.kind != HISTORY_ARCHIVE_KIND {
+            bail!("not a Flowwisper history archive: {}", archive.kind);
+        }
+        if archive.version != HISTORY_ARCHIVE_VERSION {
+            bail!("unsupported history archive version: {}", archive.version);
+        }
+        Ok(archive)
+    }
+
+    /// Read and validate an archive file written by the export service.
+    pub fn read(path: &Path, passphrase: &str) -> Result<Self> {
+        let sealed =
+            fs::read(path).map_err(|err| anyhow!("failed to read history archive: {err}"))?;
+        Self::open(&sealed, passphrase)
+    }
+}
+
+/// Where imported history comes from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum ImportSource {
+    /// A history archive produced by the export service, with the passphrase it
+    /// was sealed with.
+    Archive { path: PathBuf, passphrase: String },
+    /// Another machine's `history.db`, opened read-only with its SQLCipher key.
+    Database {
+        path: PathBuf,
+        #[serde(default)]
+        key: Option<String>,
+    },
+}
+
+/// Counts reported after merging imported history into the local store.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    /// Sessions that did not exist locally.
+    pub inserted: usize,
+    /// Existing sessions that gained post actions from the import.
+    pub merged: usize,
+    /// Existing sessions the import had nothing new for.
+    pub unchanged: usize,
+    /// Entries that failed validation and were skipped.
+    pub rejected: usize,
+}
+
+/// Check that an imported entry is internally consistent.
+pub fn validate_entry(entry: &HistoryEntry) -> Result<()> {
+    if entry.session_id.trim().is_empty() {
+        bail!("history entry is missing a session id");
+    }
+    if entry.completed_at_ms < entry.started_at_ms {
+        bail!(
+            "history entry {} completes before it starts",
+            entry.session_id
+        );
+    }
+    if entry.raw_transcript.is_empty() && entry.polished_transcript.is_empty() {
+        bail!("history entry {} has no transcript", entry.session_id);
+    }
+    Ok(())
+}
+
+/// Union two post-action lists, dropping duplicates and ordering by time.
+pub fn merge_post_actions(
+    local: &[HistoryPostAction],
+    incoming: &[HistoryPostAction],
+) -> Vec<HistoryPostAction> {
+    let mut merged = local.to_vec();
+    for action in incoming {
+        let duplicate = merged.iter().any(|existing| {
+            existing.kind == action.kind && existing.timestamp_ms == action.timestamp_ms
+        });
+        if !duplicate {
+            merged.push(action.clone());
+        }
+    }
+    merged.sort_by_key(|action| action.timestamp_ms);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::history::HistoryActionKind;
+
+    fn action(kind: HistoryActionKind, timestamp_ms: i64) -> HistoryPostAction {
+        HistoryPostAction {
+            kind,
+            timestamp_ms,
+            detail: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn merges_post_actions_without_duplicates() {
+        let local = vec![
+            action(HistoryActionKind::Copy, 10),
+            action(HistoryActionKind::Export, 30),
+        ];
+        let incoming = vec![
+            action(HistoryActionKind::Copy, 10),
+            action(HistoryActionKind::Reinsert, 20),
+        ];
+        let merged = merge_post_actions(&local, &incoming);
+        let timestamps: Vec<_> = merged.iter().map(|action| action.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn rejects_foreign_or_future_archives() {
+        let seal = |json: &str| ARCHIVE_ENVELOPE.seal("correct horse", json.into()).unwrap();
+
+        let foreign = seal(r#"{"kind":"other","version":1,"exportedAtMs":0,"entries":[]}"#);
+        assert!(HistoryArchive::open(&foreign, "correct horse").is_err());
+
+        let future =
+            seal(r#"{"kind":"flowwisper-history","version":9,"exportedAtMs":0,"entries":[]}"#);
+        assert!(HistoryArchive::open(&future, "correct horse").is_err());
+
+        let plain = serde_json::to_vec(&HistoryArchive::new(5, Vec::new())).unwrap();
+        assert!(HistoryArchive::open(&plain, "correct horse").is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_or_wrong_key_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.fwh");
+        let sealed = HistoryArchive::new(5, Vec::new())
+            .seal("correct horse")
+            .unwrap();
+        fs::write(&path, &sealed).unwrap();
+        assert_eq!(
+            HistoryArchive::read(&path, "correct horse")
+                .unwrap()
+                .exported_at_ms,
+            5
+        );
+        assert!(HistoryArchive::read(&path, "wrong horse!").is_err());
+
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(HistoryArchive::open(&tampered, "correct horse").is_err());
+
+        let mut header = sealed;
+        header[8] ^= 1;
+        assert!(HistoryArchive::open(&header, "correct horse").is_err());
+    }
+}
@@ -3,6 +3,7 @@
 use std::time::SystemTime;
 
 use super::publisher::{FallbackStrategy, PublishOutcome, PublishStrategy, PublisherStatus};
+use crate::orchestrator::EngineWarmupStatus;
 
 /// 会话状态机的阶段划分。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,18 +18,15 @@ pub enum SessionLifecyclePhase {
 }
 
 /// 生命周期事件的附加信息。
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum SessionLifecyclePayload {
+    #[default]
     None,
     Publishing(PublishingPayload),
     Completed(CompletionPayload),
     Failed(FailurePayload),
-}
-
-impl Default for SessionLifecyclePayload {
-    fn default() -> Self {
-        SessionLifecyclePayload::None
-    }
+    /// 引擎预热状态变化，不属于任何会话。
+    Warmup(EngineWarmupStatus),
 }
 
 /// 发布阶段的状态快照。
@@ -103,6 +101,16 @@ impl SessionLifecycleUpdate {
         }
     }
 
+    /// 会话之外的引擎预热进度，会话 ID 为空、阶段为 Idle。
+    pub fn warmup(status: EngineWarmupStatus) -> Self {
+        Self {
+            session_id: String::new(),
+            phase: SessionLifecyclePhase::Idle,
+            issued_at: status.updated_at,
+            payload: SessionLifecyclePayload::Warmup(status),
+        }
+    }
+
     /// 声明发布失败。
     pub fn failed<S: Into<String>>(
         session_id: S,
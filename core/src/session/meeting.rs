@@ -0,0 +1,174 @@
+//! 会议会话：同时转写麦克风与系统回放并实时下发带说话方的更新；结束时切分话题、
+//! 生成摘要与待办，作为结构化字段写入历史记录。
+
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::history::{HistoryEntry, SessionSnapshot};
+use super::{system_time_to_ms, SessionManager};
+use crate::audio::{AudioMixer, AudioSource};
+use crate::orchestrator::{
+    build_meeting_notes, ExtractiveSummarizer, LlmMeetingSummarizer, LlmPolisherConfig,
+    MeetingSummarizer, MeetingTranscriptBuilder, MeetingUtterance, MixedSessionHandle,
+    RealtimeSessionConfig, TopicSegmenter, TranscriptionUpdate,
+};
+use crate::persistence::audit::EgressRecorder;
+
+/// 历史记录元数据中标注会话类型的字段及会议会话的取值。
+pub const SESSION_TYPE_METADATA_KEY: &str = "sessionType";
+pub const MEETING_SESSION_TYPE: &str = "meeting";
+
+/// 进行中的会议；交给 [`SessionManager::finish_meeting`] 结束并生成纪要。
+pub struct MeetingSession {
+    session_id: String,
+    started_at_ms: i64,
+    sources: Vec<AudioSource>,
+    handle: MixedSessionHandle,
+    feeder: JoinHandle<()>,
+    collector: JoinHandle<()>,
+    transcript: Arc<StdMutex<MeetingTranscriptBuilder>>,
+}
+
+impl MeetingSession {
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn handle(&self) -> &MixedSessionHandle {
+        &self.handle
+    }
+
+    /// 目前已整理出的发言，按时间排序。
+    pub fn utterances(&self) -> Vec<MeetingUtterance> {
+        self.transcript
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .utterances()
+    }
+}
+
+/// 配置了润色大模型时用其生成摘要，否则使用抽取式摘要。
+pub(crate) fn default_summarizer(
+    polisher: Option<LlmPolisherConfig>,
+    egress: &EgressRecorder,
+) -> Arc<dyn MeetingSummarizer> {
+    match polisher {
+        Some(config) => Arc::new(LlmMeetingSummarizer::new(config).with_egress(egress.clone())),
+        None => Arc::new(ExtractiveSummarizer),
+    }
+}
+
+impl SessionManager {
+    pub fn set_meeting_summarizer(&self, summarizer: Arc<dyn MeetingSummarizer>) {
+        *self
+            .meeting_summarizer
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = summarizer;
+    }
+
+    fn meeting_summarizer(&self) -> Arc<dyn MeetingSummarizer> {
+        self.meeting_summarizer
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 以 `mixer` 中的全部来源开始会议：各来源独立识别，更新交织后经返回的通道下发。
+    pub fn start_meeting(
+        &self,
+        session_id: &str,
+        mixer: &AudioMixer,
+        config: RealtimeSessionConfig,
+    ) -> (MeetingSession, mpsc::Receiver<TranscriptionUpdate>) {
+        let sources = mixer.sources();
+        let capacity = config.buffer_capacity;
+        let (handle, mut updates) = self.orchestrator.start_mixed_session(config, &sources);
+        let feeder = handle.spawn_feeder(mixer.subscribe(capacity));
+
+        let transcript = Arc::new(StdMutex::new(MeetingTranscriptBuilder::new()));
+        let (client_tx, client_rx) = mpsc::channel(capacity.max(1));
+        let recorded = Arc::clone(&transcript);
+        let collector = self.spawn_background(async move {
+            while let Some(update) = updates.recv().await {
+                recorded
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .record(&update);
+                // 界面不再订阅时仍继续整理纪要。
+                let _ = client_tx.try_send(update);
+            }
+        });
+
+        let started_at_ms = system_time_to_ms(SystemTime::now()) as i64;
+        self.begin_calendar_lookup(session_id, started_at_ms);
+        let meeting = MeetingSession {
+            session_id: session_id.to_string(),
+            started_at_ms,
+            sources,
+            handle,
+            feeder,
+            collector,
+            transcript,
+        };
+        (meeting, client_rx)
+    }
+
+    /// 结束会议：停止识别，切分话题并生成摘要与待办，写入历史记录后返回该条目。
+    pub async fn finish_meeting(&self, meeting: MeetingSession) -> Result<HistoryEntry> {
+        let MeetingSession {
+            session_id,
+            started_at_ms,
+            sources,
+            handle,
+            feeder,
+            collector,
+            transcript,
+        } = meeting;
+        feeder.abort();
+        // 丢弃句柄后各路会话结束，更新通道随之关闭。
+        drop(handle);
+        let _ = collector.await;
+
+        let utterances = transcript
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .utterances();
+        let summarizer = self.meeting_summarizer();
+        let notes =
+            build_meeting_notes(&utterances, &TopicSegmenter::default(), summarizer.as_ref()).await;
+        let text = notes.transcript();
+        let mut snapshot = SessionSnapshot {
+            session_id: session_id.clone(),
+            started_at_ms,
+            completed_at_ms: system_time_to_ms(SystemTime::now()) as i64,
+            locale: None,
+            app_identifier: None,
+            app_version: None,
+            confidence_score: None,
+            raw_transcript: text.clone(),
+            polished_transcript: text,
+            metadata: json!({
+                SESSION_TYPE_METADATA_KEY: MEETING_SESSION_TYPE,
+                "audioSources": sources.iter().map(AudioSource::as_str).collect::<Vec<_>>(),
+            }),
+            post_actions: Vec::new(),
+            language_segments: Vec::new(),
+            translated_transcript: None,
+            translation_locale: None,
+            quality_flags: Vec::new(),
+            speed: None,
+            meeting: Some(notes),
+            tags: Vec::new(),
+        };
+        self.apply_calendar_event(&mut snapshot).await;
+        self.persist_transcript(snapshot).await?;
+        self.load_history_entry(&session_id)
+            .await?
+            .ok_or_else(|| anyhow!("meeting {session_id} missing after persistence"))
+    }
+}
@@ -1,63 +1,156 @@
 //! 会话管理状态机脚手架。
 
+pub mod amend;
+pub mod analytics;
+pub mod app_profile;
+pub mod calendar;
+pub mod captions;
+pub mod capture;
+mod checkpoint;
 pub mod clipboard;
+pub mod corrections;
+pub mod diagnostics;
+pub mod dispatch;
+mod duration;
+pub mod flight_recorder;
 pub mod history;
 pub mod lifecycle;
+pub mod meeting;
+pub mod preset;
+mod publish;
 pub mod publisher;
-
-use crate::audio::AudioPipeline;
+mod realtime;
+pub mod recovery;
+pub mod replacement;
+pub mod retry_queue;
+pub mod scripting;
+pub mod self_check;
+pub mod shutdown;
+mod silence;
+mod undo;
+pub mod webhooks;
+pub mod workspace;
+
+use crate::audio::{
+    AudioPipeline, AudioSource, NoiseKind, RecordedAudio, SessionRecorder, SilencePolicy,
+    SpillConfig,
+};
+use crate::config::{
+    ConfigSection, ConfigService, PolicyEngine, PolicyError, DEFAULT_WATCH_INTERVAL,
+};
+use crate::hotkey::{
+    spawn_gestures, spawn_hold_to_talk, GestureConfig, HoldToTalkEvent, HotkeyAction,
+    HotkeyBackend, HotkeyCombination, HotkeyError, HotkeyGesture, HotkeyListener,
+};
+use crate::models::ModelManager;
 use crate::orchestrator::{
-    EngineConfig, EngineOrchestrator, NoticeLevel, RealtimeSessionConfig, RealtimeSessionHandle,
-    SessionNotice, TranscriptionUpdate, UpdatePayload,
+    resolve_profile, BudgetReport, CloudBudget, EngineOrchestrator, EngineTuning,
+    EngineWarmupStatus, HardwareProfile, LatencyCalibrator, LlmProvider, MeetingSummarizer,
+    NetworkMonitor, NoticeLevel, PolishProfile, PolishProfileBinding, SessionNotice,
+    SlaCalibration, TranscriptCommand, TranscriptionUpdate, UpdatePayload, Vocabulary,
+    VocabularyTerm, CLOUD_KEEPALIVE_INTERVAL,
 };
-use crate::persistence::sqlite::{EnvKeyResolver, SqliteConfig, SqlitePath, SqlitePersistence};
+use crate::persistence::audit::{
+    EgressLog, EgressQuery, EgressRecord, EgressRecorder, EgressVerification,
+};
+use crate::persistence::backup::{self, BackupInfo, BackupReport, BackupService};
+use crate::persistence::sqlite::{
+    RekeyStage, SecretStoreKeyResolver, SqliteConfig, SqlitePath, SqlitePersistence,
+};
+use crate::persistence::sync::SyncEngine;
 use crate::persistence::{
     DraftRecord, DraftSaveRequest, NoticeSaveRequest, PersistenceActor, PersistenceCommand,
     PersistenceHandle,
 };
+use crate::plugins::PluginHost;
+use crate::power::{PerformanceGovernor, PerformanceLevel};
+use crate::secrets::{self, SecretStore};
+use crate::session::amend::{SentenceEdit, TranscriptAmendment};
+use crate::session::analytics::{count_words, UsageRange, UsageStats};
+use crate::session::app_profile::{resolve_app_profile, AppProfile};
+use crate::session::calendar::{CalendarEvent, CalendarProvider};
+use crate::session::captions::{CaptionBroadcaster, CaptionConfig, CaptionFrame};
+use crate::session::capture::{
+    CaptureController, CaptureEvent, CaptureMode, CaptureTransition, CaptureTrigger,
+    VoiceActivationConfig,
+};
+use crate::session::checkpoint::save_checkpoint;
 use crate::session::clipboard::{ClipboardFallback, ClipboardManager};
+use crate::session::corrections::Corrector;
+use crate::session::diagnostics::{
+    device_report, export_bundle, DiagnosticSources, DiagnosticsBundle, DiagnosticsError,
+    DiagnosticsRequest,
+};
+use crate::session::dispatch::UpdateDispatcher;
+use crate::session::flight_recorder::{FlightRecorder, FLIGHT_DUMP_EXTENSION};
 use crate::session::history::{
-    AccuracyUpdate, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery, SessionSnapshot,
+    AccuracyUpdate, ActionPlugin, ActionRegistry, DictationSpeed, ExportRequest, ExportSelection,
+    ExportService, ExportSummary, HistoryEntry, HistoryPage, HistoryPostAction, HistoryQuery,
+    ImportSource, ImportSummary, SessionSnapshot,
 };
-use crate::session::lifecycle::{SessionLifecyclePhase, SessionLifecycleUpdate};
+use crate::session::lifecycle::SessionLifecycleUpdate;
+use crate::session::preset::SessionPreset;
 use crate::session::publisher::{
-    FallbackStrategy, PublishOutcome, PublishRequest, PublishStrategy, Publisher, PublisherFailure,
-    PublisherFailureCode, PublisherStatus, SessionPublisher,
+    FieldRole, FocusWindowContext, PasswordFieldPolicy, Publisher, SessionPublisher,
 };
+use crate::session::recovery::{CrashGuard, RecoverySnapshot};
+use crate::session::replacement::{ReplacementRule, ReplacementRules};
+use crate::session::retry_queue::PublishRetrier;
+use crate::session::scripting::{AutomationScript, ScriptHost};
+use crate::session::self_check::{
+    run_self_check, CaptureBackendProbe, SelfCheckPlatform, SelfCheckReport, SelfCheckTargets,
+};
+use crate::session::shutdown::CancellationToken;
+use crate::session::undo::PendingUndo;
+use crate::session::webhooks::{WebhookConfig, WebhookDispatcher};
 use crate::telemetry::events::{
-    record_session_draft_failed, record_session_draft_saved, record_session_noise_warning,
-    record_session_publish_attempt, record_session_publish_degradation,
-    record_session_publish_failure, record_session_publish_outcome,
-    record_session_silence_autostop, record_session_silence_countdown, EVENT_NOISE_WARNING,
-    EVENT_SILENCE_AUTOSTOP, EVENT_SILENCE_COUNTDOWN,
+    record_engine_tuning, record_session_draft_failed, record_session_draft_saved,
+    record_session_transcript_amended, record_sla_calibration,
 };
-use anyhow::{anyhow, Context, Result};
-use dirs::data_dir;
+use crate::telemetry::metrics::{self};
+use crate::telemetry::uploader::TelemetryUploader;
+use anyhow::{anyhow, Result};
 use serde_json::json;
-use std::env;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock};
 use std::time::{Duration as StdDuration, SystemTime, UNIX_EPOCH};
 use tokio::sync::{
     broadcast::{self, error::RecvError},
-    mpsc, Mutex,
+    mpsc, watch, Mutex,
 };
-use tokio::time::{interval, timeout, Duration};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, timeout_at, Duration};
 use tracing::{error, info, warn};
 
-const CLIPBOARD_FALLBACK_TIMEOUT_MS: u64 = 200;
+pub(crate) use crate::session::checkpoint::DEFAULT_CHECKPOINT_SECS;
+pub use crate::session::duration::SessionDurationWarning;
+pub(crate) use crate::session::duration::DEFAULT_MAX_SESSION_SECS;
+
 const NOTICE_ACTION_COPY: &str = "copy";
 const NOTICE_RESULT_SUCCESS: &str = "success";
 const NOTICE_RESULT_FAILURE: &str = "failure";
 const HISTORY_CLEANUP_INTERVAL_SECS: u64 = 30 * 60;
+const BUDGET_PERSIST_INTERVAL: StdDuration = StdDuration::from_secs(30);
+/// 电源与温度的采样间隔；插拔电源后最迟在一个间隔内调整档位。
+const POWER_POLL_INTERVAL: StdDuration = StdDuration::from_secs(30);
+/// 按下热键前保留的音频时长，避免丢失第一个音节。
+pub(crate) const DEFAULT_PREROLL_MS: u64 = 1_500;
+/// 历史记录元数据中标注音频来源的字段。
+pub const AUDIO_SOURCE_METADATA_KEY: &str = "audioSource";
+/// 退出时等待进行中会话收尾的默认期限。
+pub const SHUTDOWN_DEADLINE: StdDuration = StdDuration::from_secs(3);
 
 #[derive(Debug, Clone)]
 pub enum SessionEvent {
     NoiseWarning(SessionNoiseWarning),
     SilenceCountdown(SessionSilenceCountdown),
     AutoStop(SessionAutoStop),
+    DurationWarning(SessionDurationWarning),
+    EchoDetected(SessionEchoDetected),
+    SpeedSummary(SessionSpeedSummary),
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +159,7 @@ pub struct SessionNoiseWarning {
     pub threshold_db: f32,
     pub level_db: f32,
     pub persistence_ms: u32,
+    pub kind: NoiseKind,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -87,6 +181,24 @@ pub struct SessionSilenceCountdown {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AutoStopReason {
     SilenceTimeout,
+    MaxDuration,
+}
+
+/// 麦克风录入了系统正在播放的声音。
+#[derive(Debug, Clone)]
+pub struct SessionEchoDetected {
+    pub correlation: f32,
+    pub delay_ms: u32,
+    pub persistence_ms: u32,
+    /// 提示用户改用耳机。
+    pub hint: &'static str,
+}
+
+/// 会话结束时的听写语速。
+#[derive(Debug, Clone)]
+pub struct SessionSpeedSummary {
+    pub session_id: String,
+    pub speed: DictationSpeed,
 }
 
 #[derive(Debug, Clone)]
@@ -106,22 +218,34 @@ struct SilenceCountdownSnapshot {
     remaining_ms: u32,
 }
 
-fn resolve_persistence_config() -> Result<SqliteConfig> {
-    let base_dir = match env::var("FLOWWISPER_DATA_DIR").map(PathBuf::from) {
-        Ok(path) => path,
-        Err(_) => data_dir()
-            .map(|dir| dir.join("Flowwisper"))
-            .ok_or_else(|| anyhow!("failed to resolve persistence data directory"))?,
-    };
+/// 当前配置档的数据目录，历史库、录音、恢复快照与插件数据都位于其下。
+fn resolve_data_dir() -> Result<PathBuf> {
+    workspace::active_data_dir()
+}
 
-    fs::create_dir_all(&base_dir).context("failed to create data directory")?;
-    let db_path = base_dir.join("history.db");
+/// 当前配置档的密钥库。
+fn open_secret_store() -> Result<Arc<dyn SecretStore>> {
+    Ok(secrets::default_store(
+        &resolve_data_dir()?,
+        &workspace::active_profile()?,
+    )?)
+}
+
+fn resolve_persistence_config() -> Result<SqliteConfig> {
+    let data_dir = resolve_data_dir()?;
+    let db_path = data_dir.join("history.db");
+    if backup::apply_staged_restore(&db_path)? {
+        info!(target: "session_manager", "restored history database from backup");
+    }
 
     Ok(SqliteConfig {
         path: SqlitePath::File(db_path),
         pool_size: 8,
         busy_timeout: StdDuration::from_millis(250),
-        key_resolver: Arc::new(EnvKeyResolver::default()),
+        key_resolver: Arc::new(SecretStoreKeyResolver::for_profile(
+            open_secret_store()?,
+            &workspace::active_profile()?,
+        )),
     })
 }
 
@@ -144,6 +268,8 @@ pub struct SessionManager {
     orchestrator: EngineOrchestrator,
     persistence: PersistenceHandle,
     update_tx: broadcast::Sender<TranscriptionUpdate>,
+    /// 按优先级分通道接收更新的订阅者。
+    lane_subscribers: Arc<StdMutex<Vec<UpdateDispatcher>>>,
     lifecycle_tx: broadcast::Sender<SessionLifecycleUpdate>,
     event_tx: broadcast::Sender<SessionEvent>,
     publisher: Arc<dyn SessionPublisher>,
@@ -154,20 +280,107 @@ pub struct SessionManager {
     auto_stop_triggered: Arc<AtomicBool>,
     silence_countdown_snapshot: Arc<Mutex<Option<SilenceCountdownSnapshot>>>,
     active_session_id: Arc<Mutex<Option<String>>>,
+    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    /// 最近一段时间的内部事件，供导出诊断转储。
+    flight_recorder: Arc<FlightRecorder>,
+    telemetry_uploader: TelemetryUploader,
+    /// 外发审计写入端，默认关闭，由组织策略或宿主开启。
+    egress: EgressRecorder,
+    egress_log: Arc<EgressLog>,
+    /// 当前组织策略要求外发审计，宿主不能关闭。
+    egress_audit_required: AtomicBool,
+    models: ModelManager,
+    history_sync: Option<SyncEngine>,
+    history_backup: Option<BackupService>,
+    crash_guard: CrashGuard,
+    recovered_session: Arc<Mutex<Option<RecoverySnapshot>>>,
+    vocabulary: Arc<StdRwLock<Option<Arc<Vocabulary>>>>,
+    replacement_rules: Arc<StdRwLock<Arc<ReplacementRules>>>,
+    /// 由个人纠错语料编译的自动纠错，先于替换规则应用。
+    corrector: Arc<StdRwLock<Arc<Corrector>>>,
+    polish_profiles: Arc<StdRwLock<Vec<PolishProfileBinding>>>,
+    app_profiles: Arc<StdRwLock<Vec<AppProfile>>>,
+    /// 当前生效的听写预设，作用于之后开始的会话与发布。
+    active_preset: Arc<StdRwLock<Option<SessionPreset>>>,
+    /// 录音触发模式；未设置时由宿主自行控制录音，帧全部转发。
+    capture: Arc<StdMutex<Option<CaptureController>>>,
+    capture_tx: broadcast::Sender<CaptureEvent>,
+    max_session_duration: Arc<StdRwLock<Option<StdDuration>>>,
+    checkpoint_interval: Arc<StdRwLock<Option<StdDuration>>>,
+    /// 组织策略要求的历史保留上限，短于内置 48 小时时提前清理。
+    history_retention_cap: Arc<StdRwLock<Option<StdDuration>>>,
+    /// 各会话发布前提交的手动修改，发布时取出。
+    amendments: Arc<StdMutex<HashMap<String, TranscriptAmendment>>>,
+    /// 各会话累计的有声时长（按 VAD 判定），发布时取出计算语速。
+    speech_time: Arc<StdMutex<HashMap<String, StdDuration>>>,
+    /// 各会话开始转写时的音频来源，发布时写入历史元数据。
+    audio_sources: Arc<StdMutex<HashMap<String, AudioSource>>>,
+    meeting_summarizer: Arc<StdRwLock<Arc<dyn MeetingSummarizer>>>,
+    calendar: Arc<StdRwLock<Option<Arc<dyn CalendarProvider>>>>,
+    /// 各会话开始时发起的会议查找，发布时取出结果。
+    calendar_lookups: Arc<StdMutex<HashMap<String, JoinHandle<Option<CalendarEvent>>>>>,
+    captions: CaptionBroadcaster,
+    pending_undo: Arc<Mutex<HashMap<String, PendingUndo>>>,
+    /// 最近开始的实时会话的命令入口，供快捷键重新润色使用。
+    transcript_commands: Arc<StdMutex<Option<mpsc::Sender<TranscriptCommand>>>>,
+    publish_retry: PublishRetrier,
+    /// 宿主上报的前台应用，驱动重试队列的焦点触发。
+    focus_tx: watch::Sender<FocusWindowContext>,
+    publish_retry_started: AtomicBool,
+    password_field_policy: Arc<StdRwLock<PasswordFieldPolicy>>,
+    history_actions: ActionRegistry,
+    webhooks: WebhookDispatcher,
+    webhooks_started: AtomicBool,
+    scripts: ScriptHost,
+    plugins: PluginHost,
+    config: ConfigService,
+    config_started: AtomicBool,
+    config_watch: StdMutex<Option<JoinHandle<()>>>,
+    /// 后台任务共用的取消令牌，`shutdown` 时触发。
+    shutdown: CancellationToken,
+    /// 进行中实时会话的转发任务，退出时在期限内等待其收尾。
+    realtime_tasks: Arc<StdMutex<Vec<JoinHandle<()>>>>,
 }
 
 impl SessionManager {
     pub fn new() -> Result<Self> {
         let audio = AudioPipeline::new();
-        let orchestrator = EngineOrchestrator::new(EngineConfig {
-            prefer_cloud: false,
-        })?;
-        Ok(Self::from_parts(
+        let config = ConfigService::load_or_default(ConfigService::default_path());
+        let settings = config.current();
+        audio.set_preroll_window(settings.preroll_window());
+        audio.configure_agc(settings.agc_config());
+        audio.enable_spill(SpillConfig {
+            dir: resolve_data_dir()?.join("spill"),
+            ..SpillConfig::default()
+        });
+        let secrets = open_secret_store()?;
+        info!(target: "session_manager", backend = secrets.backend(), "secret store ready");
+        let hardware = HardwareProfile::probe();
+        let tuning = settings.engine_tuning(&hardware);
+        record_engine_tuning(&hardware, &tuning);
+        let governor = Arc::new(PerformanceGovernor::system(settings.governor_config()));
+        let network = settings
+            .network_monitor_config()
+            .map(|config| Arc::new(NetworkMonitor::new(config)));
+        let mut orchestrator = EngineOrchestrator::tuned(settings.engine_config(), tuning)?
+            .with_secret_store(secrets)
+            .with_governor(governor)
+            .with_latency_calibrator(Arc::new(LatencyCalibrator::default()));
+        if let Some(monitor) = &network {
+            orchestrator = orchestrator.with_network_monitor(Arc::clone(monitor));
+        }
+        let manager = Self::from_parts(
             audio,
             orchestrator,
             Arc::new(Publisher::default()),
             ClipboardManager::with_system(),
-        ))
+            config,
+        );
+        if let Some(monitor) = network {
+            info!(target: "session_manager", "hybrid engine routing enabled");
+            monitor.spawn(&manager.shutdown);
+        }
+        Ok(manager)
     }
 
     pub fn with_orchestrator(orchestrator: EngineOrchestrator) -> Self {
@@ -177,6 +390,7 @@ impl SessionManager {
             orchestrator,
             Arc::new(Publisher::default()),
             ClipboardManager::with_system(),
+            ConfigService::load_or_default(ConfigService::default_path()),
         )
     }
 
@@ -190,6 +404,7 @@ impl SessionManager {
             orchestrator,
             publisher,
             ClipboardManager::with_system(),
+            ConfigService::load_or_default(ConfigService::default_path()),
         )
     }
 
@@ -198,26 +413,98 @@ impl SessionManager {
         orchestrator: EngineOrchestrator,
         publisher: Arc<dyn SessionPublisher>,
         clipboard: ClipboardManager,
+        config: ConfigService,
     ) -> Self {
-        let config = resolve_persistence_config().expect("persistence config should resolve");
-        let persistence =
-            spawn_persistence_runtime(config).expect("persistence runtime should spawn");
+        let settings = config.current();
+        let persistence = spawn_persistence_runtime(
+            resolve_persistence_config().expect("persistence config should resolve"),
+        )
+        .expect("persistence runtime should spawn");
+        persistence.set_redactor(settings.redactor().map(Arc::new));
+        orchestrator.budget().set_caps(settings.budget_caps());
         let (update_tx, _) = broadcast::channel(64);
         let (lifecycle_tx, _) = broadcast::channel(32);
         let (event_tx, _) = broadcast::channel(32);
+        let (capture_tx, _) = broadcast::channel(32);
         let silence_countdown_active = Arc::new(AtomicBool::new(false));
         let auto_stop_triggered = Arc::new(AtomicBool::new(false));
         let silence_countdown_snapshot = Arc::new(Mutex::new(None));
         let active_session_id = Arc::new(Mutex::new(None));
+        let data_dir = resolve_data_dir().expect("data directory should resolve");
+        // 审计链密钥与数据库分开保存；未注入密钥库的编排器退回到数据目录下的默认密钥库。
+        let secrets = orchestrator
+            .secret_store()
+            .map(Ok)
+            .unwrap_or_else(open_secret_store)
+            .expect("secret store should open");
+        let egress_log = Arc::new(EgressLog::new(persistence.sqlite(), secrets));
+        let egress = EgressRecorder::spawn(Arc::clone(&egress_log));
+        let models = ModelManager::new(
+            settings
+                .engine
+                .model_dir
+                .clone()
+                .or_else(ModelManager::default_dir)
+                .expect("model directory should resolve"),
+        );
+        let telemetry_uploader =
+            TelemetryUploader::new(persistence.sqlite(), settings.telemetry_upload_config())
+                .with_egress(egress.clone());
+        let history_sync = settings.sync_config().and_then(|config| {
+            SyncEngine::from_config(persistence.sqlite(), &config, &egress)
+                .map_err(|err| {
+                    warn!(target: "session_manager", %err, "history sync disabled");
+                })
+                .ok()
+        });
+        let history_backup = settings
+            .backup_config()
+            .map(|config| BackupService::from_config(persistence.sqlite(), &config, &egress));
+        let publish_retry =
+            PublishRetrier::new(publisher.clone(), persistence.clone(), lifecycle_tx.clone());
+        let (focus_tx, _) = watch::channel(FocusWindowContext::default());
+        // 恢复文件含转写原文，沿用数据库密钥加密；数据库未加密时只依靠检查点恢复。
+        let recovery_dir = data_dir.join("recovery");
+        let crash_guard = match persistence.sqlite().key_material() {
+            Ok(Some(key)) => CrashGuard::new(&recovery_dir).with_key_material(&key),
+            Ok(None) => Ok(CrashGuard::new(&recovery_dir)),
+            Err(err) => Err(err),
+        }
+        .unwrap_or_else(|err| {
+            warn!(target: "session_manager", %err, "crash recovery file disabled");
+            CrashGuard::new(&recovery_dir)
+        });
+        let plugins = PluginHost::new(data_dir.join("plugin-data"));
+
+        let polisher_config = settings.polisher_config().map(|mut config| {
+            orchestrator.resolve_llm_config(&mut config);
+            config
+        });
+        let meeting_summarizer = meeting::default_summarizer(polisher_config, &egress);
+        let shutdown = CancellationToken::new();
 
         let manager = Self {
             audio,
             orchestrator,
             persistence,
             update_tx,
+            lane_subscribers: Arc::new(StdMutex::new(Vec::new())),
             lifecycle_tx,
             event_tx,
             publisher,
+            history_actions: ActionRegistry::with_builtins(clipboard.clone(), egress.clone()),
+            webhooks: WebhookDispatcher::default()
+                .with_egress(egress.clone())
+                .with_shutdown(shutdown.clone()),
+            webhooks_started: AtomicBool::new(false),
+            scripts: ScriptHost::default(),
+            plugins,
+            config,
+            config_started: AtomicBool::new(false),
+            config_watch: StdMutex::new(None),
+            captions: CaptionBroadcaster::default().with_shutdown(shutdown.clone()),
+            shutdown,
+            realtime_tasks: Arc::new(StdMutex::new(Vec::new())),
             clipboard,
             clipboard_fallback: Arc::new(Mutex::new(None)),
             history_cleanup_started: AtomicBool::new(false),
@@ -225,6 +512,40 @@ impl SessionManager {
             auto_stop_triggered,
             silence_countdown_snapshot,
             active_session_id,
+            recorder: Arc::new(Mutex::new(None)),
+            flight_recorder: Arc::new(FlightRecorder::default()),
+            telemetry_uploader,
+            egress,
+            egress_log,
+            egress_audit_required: AtomicBool::new(false),
+            models,
+            history_sync,
+            history_backup,
+            crash_guard,
+            recovered_session: Arc::new(Mutex::new(None)),
+            vocabulary: Arc::new(StdRwLock::new(None)),
+            replacement_rules: Arc::new(StdRwLock::new(Arc::new(ReplacementRules::default()))),
+            corrector: Arc::new(StdRwLock::new(Arc::new(Corrector::default()))),
+            polish_profiles: Arc::new(StdRwLock::new(Vec::new())),
+            app_profiles: Arc::new(StdRwLock::new(Vec::new())),
+            active_preset: Arc::new(StdRwLock::new(None)),
+            capture: Arc::new(StdMutex::new(None)),
+            capture_tx,
+            max_session_duration: Arc::new(StdRwLock::new(settings.max_session_duration())),
+            checkpoint_interval: Arc::new(StdRwLock::new(settings.checkpoint_interval())),
+            history_retention_cap: Arc::new(StdRwLock::new(None)),
+            amendments: Arc::new(StdMutex::new(HashMap::new())),
+            speech_time: Arc::new(StdMutex::new(HashMap::new())),
+            audio_sources: Arc::new(StdMutex::new(HashMap::new())),
+            meeting_summarizer: Arc::new(StdRwLock::new(meeting_summarizer)),
+            calendar: Arc::new(StdRwLock::new(None)),
+            calendar_lookups: Arc::new(StdMutex::new(HashMap::new())),
+            pending_undo: Arc::new(Mutex::new(HashMap::new())),
+            transcript_commands: Arc::new(StdMutex::new(None)),
+            publish_retry,
+            focus_tx,
+            publish_retry_started: AtomicBool::new(false),
+            password_field_policy: Arc::new(StdRwLock::new(PasswordFieldPolicy::default())),
         };
 
         manager.spawn_noise_listener();
@@ -239,1818 +560,1613 @@ impl SessionManager {
         clipboard: ClipboardManager,
     ) -> Self {
         let audio = AudioPipeline::new();
-        Self::from_parts(audio, orchestrator, publisher, clipboard)
+        Self::from_parts(
+            audio,
+            orchestrator,
+            publisher,
+            clipboard,
+            ConfigService::from_config(Default::default()),
+        )
     }
 
     pub async fn run(&self) -> Result<()> {
         info!(target: "session_manager", "running bootstrap tasks");
         self.audio.start().await?;
-        self.orchestrator.warmup().await?;
+        self.spawn_flight_recorder();
+        self.spawn_engine_warmup();
+        self.spawn_power_governor();
         self.schedule_history_cleanup();
+        self.spawn_publish_retry_worker();
+        self.spawn_webhook_dispatcher();
+        self.spawn_config_watcher();
+        self.detect_orphaned_session().await;
+        if self.config.current().session.record_audio {
+            match resolve_data_dir() {
+                Ok(data_dir) => self.enable_configured_recording(&data_dir).await,
+                Err(err) => warn!(target: "session_manager", %err, "session recording disabled"),
+            }
+        }
+        if let Err(err) = self.refresh_vocabulary().await {
+            warn!(target: "session_manager", %err, "failed to load custom vocabulary");
+        }
+        if let Err(err) = self.refresh_replacement_rules().await {
+            warn!(target: "session_manager", %err, "failed to load replacement rules");
+        }
+        if let Err(err) = self.refresh_corrector().await {
+            warn!(target: "session_manager", %err, "failed to load correction corpus");
+        }
+        if let Err(err) = self.refresh_polish_profiles().await {
+            warn!(target: "session_manager", %err, "failed to load polish profiles");
+        }
+        if let Err(err) = self.refresh_app_profiles().await {
+            warn!(target: "session_manager", %err, "failed to load app profiles");
+        }
+        if let Err(err) = self.refresh_silence_policy().await {
+            warn!(target: "session_manager", %err, "failed to load silence policy");
+        }
+        if let Err(err) = self.refresh_noise_baselines().await {
+            warn!(target: "session_manager", %err, "failed to load noise baselines");
+        }
+        if let Err(err) = self.restore_cloud_budget().await {
+            warn!(target: "session_manager", %err, "failed to load cloud budget usage");
+        }
+        self.spawn_budget_persister();
+        if let Err(err) = self.restore_latency_calibration().await {
+            warn!(target: "session_manager", %err, "failed to load latency calibration");
+        }
+        self.spawn_latency_persister();
+        self.telemetry_uploader.spawn();
+        if let Some(sync) = &self.history_sync {
+            sync.spawn();
+        }
+        if let Some(backup) = &self.history_backup {
+            backup.spawn();
+        }
+        if let Some(addr) = captions::configured_addr() {
+            if self.captions.config().is_none() {
+                self.captions.set_config(Some(CaptionConfig::default()));
+            }
+            if let Err(err) = self.captions.serve(addr).await {
+                warn!(target: "session_manager", %err, "caption endpoint unavailable");
+            }
+        }
+        if let Some(addr) = metrics::configured_addr() {
+            if let Err(err) = metrics::serve(addr).await {
+                warn!(target: "session_manager", %err, "metrics endpoint unavailable");
+            }
+        }
         Ok(())
     }
 
-    pub fn audio_pipeline(&self) -> AudioPipeline {
-        self.audio.clone()
-    }
+    /// 后台预热引擎，不阻塞启动：状态变化以生命周期事件广播，按下热键时补热未就绪的引擎，
+    /// 并定期保活云端连接。
+    fn spawn_engine_warmup(&self) {
+        let mut status_rx = self.orchestrator.subscribe_warmup();
+        let lifecycle_tx = self.lifecycle_tx.clone();
+        self.spawn_background(async move {
+            while status_rx.changed().await.is_ok() {
+                let status = status_rx.borrow_and_update().clone();
+                let _ = lifecycle_tx.send(SessionLifecycleUpdate::warmup(status));
+            }
+        });
 
-    pub fn subscribe_updates(&self) -> broadcast::Receiver<TranscriptionUpdate> {
-        self.update_tx.subscribe()
+        let orchestrator = self.orchestrator.clone();
+        let mut capture_rx = self.capture_tx.subscribe();
+        self.spawn_background(async move {
+            if let Err(err) = orchestrator.warmup().await {
+                warn!(target: "session_manager", %err, "engine warmup failed");
+            }
+            let mut keepalive = tokio::time::interval(CLOUD_KEEPALIVE_INTERVAL);
+            keepalive.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            keepalive.tick().await;
+            loop {
+                tokio::select! {
+                    _ = keepalive.tick() => {
+                        orchestrator.keep_cloud_warm(CLOUD_KEEPALIVE_INTERVAL).await;
+                    }
+                    event = capture_rx.recv() => match event {
+                        Ok(event) if event.transition == CaptureTransition::Started => {
+                            if let Err(err) = orchestrator.ensure_warm().await {
+                                warn!(target: "session_manager", %err, "engine warmup failed");
+                            }
+                        }
+                        Ok(_) | Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
     }
 
-    pub fn subscribe_lifecycle(&self) -> broadcast::Receiver<SessionLifecycleUpdate> {
-        self.lifecycle_tx.subscribe()
+    /// 把生命周期与录音状态变化写入飞行记录器；会话内的帧与更新由转发任务直接记录。
+    fn spawn_flight_recorder(&self) {
+        let recorder = Arc::clone(&self.flight_recorder);
+        let mut lifecycle_rx = self.lifecycle_tx.subscribe();
+        let mut capture_rx = self.capture_tx.subscribe();
+        self.spawn_background(async move {
+            loop {
+                tokio::select! {
+                    update = lifecycle_rx.recv() => match update {
+                        Ok(update) => recorder.record_lifecycle(&update),
+                        Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => break,
+                    },
+                    event = capture_rx.recv() => match event {
+                        Ok(event) => recorder.record_capture(&event),
+                        Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
     }
 
-    pub fn subscribe_events(&self) -> broadcast::Receiver<SessionEvent> {
-        self.event_tx.subscribe()
+    pub fn flight_recorder(&self) -> Arc<FlightRecorder> {
+        Arc::clone(&self.flight_recorder)
     }
 
-    pub async fn set_active_session_id<S: Into<String>>(&self, session_id: S) {
-        let mut guard = self.active_session_id.lock().await;
-        *guard = Some(session_id.into());
+    /// 导出诊断包；`request.consent` 为假时直接拒绝，不读取任何数据。
+    pub async fn export_diagnostics(
+        &self,
+        request: DiagnosticsRequest,
+    ) -> Result<DiagnosticsBundle, DiagnosticsError> {
+        if !request.consent {
+            return Err(DiagnosticsError::ConsentRequired);
+        }
+        let mut device = device_report();
+        device["engineTuning"] = json!(self.engine_tuning());
+        device["warmup"] = json!(self.engine_warmup_status());
+        device["slaCalibration"] = json!(self.sla_calibration());
+        device["performanceLevel"] = json!(self.performance_level());
+        device["powerState"] = json!(self
+            .orchestrator
+            .governor()
+            .map(|governor| governor.power_state()));
+        let sources = DiagnosticSources {
+            flight_recorder: Some(Arc::clone(&self.flight_recorder)),
+            config: Some(self.config.current().as_ref().clone()),
+            device: Some(device),
+            database: Some(self.persistence.sqlite()),
+            ..DiagnosticSources::default()
+        };
+        let bundle = tokio::task::spawn_blocking(move || export_bundle(&request, &sources))
+            .await
+            .map_err(|err| anyhow!("diagnostics export task failed: {err}"))??;
+        info!(
+            target: "session_manager",
+            path = %bundle.path.display(),
+            size_bytes = bundle.size_bytes,
+            "diagnostic bundle exported"
+        );
+        Ok(bundle)
     }
 
-    pub async fn clear_active_session_id(&self) {
-        let mut guard = self.active_session_id.lock().await;
-        *guard = None;
+    /// 把飞行记录器的当前内容以口令加密写入 `dir`，返回转储文件路径。
+    pub async fn dump_flight_recorder(&self, dir: &Path, passphrase: &str) -> Result<PathBuf> {
+        let dump = self.flight_recorder.dump_encrypted(passphrase)?;
+        let path = dir.join(format!(
+            "flight-{}.{FLIGHT_DUMP_EXTENSION}",
+            system_time_to_ms(SystemTime::now())
+        ));
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&path, dump).await?;
+        info!(target: "session_manager", path = %path.display(), "flight recorder dumped");
+        Ok(path)
     }
 
-    fn spawn_noise_listener(&self) {
-        let mut noise_rx = self.audio.subscribe_noise_events();
-        let event_tx = self.event_tx.clone();
-        let audio = self.audio.clone();
-        let persistence = self.persistence.clone();
-        let countdown_active = Arc::clone(&self.silence_countdown_active);
-        let auto_stop_triggered = Arc::clone(&self.auto_stop_triggered);
-        let snapshot = Arc::clone(&self.silence_countdown_snapshot);
-        let active_session_id = Arc::clone(&self.active_session_id);
-
-        tokio::spawn(async move {
+    /// 定期采样电源与温度，档位变化时调整本地引擎并通知用户；未接入调节器时不启动。
+    fn spawn_power_governor(&self) {
+        if self.orchestrator.governor().is_none() {
+            return;
+        }
+        let orchestrator = self.orchestrator.clone();
+        let update_tx = self.update_tx.clone();
+        let flight_recorder = Arc::clone(&self.flight_recorder);
+        self.spawn_background(async move {
+            let mut ticker = tokio::time::interval(POWER_POLL_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
             loop {
-                match noise_rx.recv().await {
-                    Ok(crate::audio::NoiseEvent::NoiseWarning(payload)) => {
-                        let event = SessionEvent::NoiseWarning(SessionNoiseWarning {
-                            baseline_db: payload.baseline_db,
-                            threshold_db: payload.threshold_db,
-                            level_db: payload.window_db,
-                            persistence_ms: payload.persistence_ms,
-                        });
-
-                        let timestamp = SystemTime::now();
-                        let session_id = {
-                            active_session_id
-                                .lock()
-                                .await
-                                .clone()
-                                .unwrap_or_else(|| "unassigned".to_string())
-                        };
-
-                        record_session_noise_warning(
-                            &session_id,
-                            payload.baseline_db,
-                            payload.threshold_db,
-                            payload.window_db,
-                            payload.persistence_ms,
-                            false,
-                            timestamp,
-                        );
-
-                        if let Err(err) = event_tx.send(event) {
-                            warn!(
-                                target: "session_manager",
-                                %err,
-                                "failed to broadcast noise warning event",
-                            );
-                        }
-
-                        let occurred_at_ms = system_time_to_ms(timestamp);
-                        let queue_payload = json!({
-                            "sessionId": session_id,
-                            "occurredAtMs": occurred_at_ms,
-                            "baselineDb": payload.baseline_db,
-                            "thresholdDb": payload.threshold_db,
-                            "levelDb": payload.window_db,
-                            "persistenceMs": payload.persistence_ms,
-                            "strongNoiseMode": false,
-                        });
-
-                        if let Err(err) = persistence
-                            .enqueue_telemetry(
-                                session_id,
-                                EVENT_NOISE_WARNING.to_string(),
-                                queue_payload,
-                            )
-                            .await
-                        {
-                            warn!(
-                                target: "session_manager",
-                                %err,
-                                "failed to queue noise warning telemetry",
-                            );
-                        }
-                    }
-                    Ok(crate::audio::NoiseEvent::SilenceCountdown(payload)) => {
-                        let state = match payload.status {
-                            crate::audio::SilenceCountdownStatus::Started => {
-                                SilenceCountdownState::Started
-                            }
-                            crate::audio::SilenceCountdownStatus::Tick => {
-                                SilenceCountdownState::Tick
-                            }
-                            crate::audio::SilenceCountdownStatus::Canceled => {
-                                SilenceCountdownState::Canceled
-                            }
-                            crate::audio::SilenceCountdownStatus::Completed => {
-                                SilenceCountdownState::Completed
-                            }
-                        };
-
-                        let mut snapshot_guard = snapshot.lock().await;
-                        match state {
-                            SilenceCountdownState::Canceled => {
-                                *snapshot_guard = None;
-                            }
-                            _ => {
-                                *snapshot_guard = Some(SilenceCountdownSnapshot {
-                                    total_ms: payload.total_ms,
-                                    remaining_ms: payload.remaining_ms,
-                                });
-                            }
-                        }
-                        drop(snapshot_guard);
-
-                        let cancel_reason = if matches!(state, SilenceCountdownState::Canceled) {
-                            Some(SilenceCancellationReason::SpeechDetected)
-                        } else {
-                            None
-                        };
-
-                        let countdown_event =
-                            SessionEvent::SilenceCountdown(SessionSilenceCountdown {
-                                total_ms: payload.total_ms,
-                                remaining_ms: payload.remaining_ms,
-                                state,
-                                cancel_reason,
-                            });
-
-                        if let Err(err) = event_tx.send(countdown_event) {
-                            warn!(
-                                target: "session_manager",
-                                %err,
-                                "failed to broadcast silence countdown event",
-                            );
-                        }
-
-                        if !matches!(state, SilenceCountdownState::Tick) {
-                            let timestamp = SystemTime::now();
-                            let session_id = {
-                                active_session_id
-                                    .lock()
-                                    .await
-                                    .clone()
-                                    .unwrap_or_else(|| "unassigned".to_string())
-                            };
-                            let cancel_reason_value = cancel_reason.map(|reason| match reason {
-                                SilenceCancellationReason::SpeechDetected => "speechDetected",
-                                SilenceCancellationReason::ManualStop => "manualStop",
-                            });
-
-                            record_session_silence_countdown(
-                                &session_id,
-                                countdown_state_label(state),
-                                payload.total_ms,
-                                payload.remaining_ms,
-                                cancel_reason_value,
-                                timestamp,
-                            );
-
-                            let timestamp_ms = system_time_to_ms(timestamp);
-                            let queue_payload = json!({
-                                "sessionId": session_id,
-                                "timestampMs": timestamp_ms,
-                                "state": countdown_state_label(state),
-                                "totalMs": payload.total_ms,
-                                "remainingMs": payload.remaining_ms,
-                                "cancelReason": cancel_reason_value,
-                            });
-
-                            if let Err(err) = persistence
-                                .enqueue_telemetry(
-                                    queue_payload["sessionId"]
-                                        .as_str()
-                                        .unwrap_or("unassigned")
-                                        .to_string(),
-                                    EVENT_SILENCE_COUNTDOWN.to_string(),
-                                    queue_payload,
-                                )
-                                .await
-                            {
-                                warn!(
-                                    target: "session_manager",
-                                    %err,
-                                    "failed to queue silence countdown telemetry",
-                                );
-                            }
-                        }
-
-                        match state {
-                            SilenceCountdownState::Started => {
-                                countdown_active.store(true, Ordering::SeqCst);
-                                auto_stop_triggered.store(false, Ordering::SeqCst);
-                            }
-                            SilenceCountdownState::Tick => {
-                                countdown_active.store(true, Ordering::SeqCst);
-                            }
-                            SilenceCountdownState::Canceled => {
-                                countdown_active.store(false, Ordering::SeqCst);
-                                auto_stop_triggered.store(false, Ordering::SeqCst);
-                            }
-                            SilenceCountdownState::Completed => {
-                                countdown_active.store(false, Ordering::SeqCst);
-                                let already_triggered =
-                                    auto_stop_triggered.swap(true, Ordering::SeqCst);
-                                if !already_triggered {
-                                    {
-                                        let mut guard = snapshot.lock().await;
-                                        *guard = None;
-                                    }
-
-                                    let auto_stop_event = SessionEvent::AutoStop(SessionAutoStop {
-                                        reason: AutoStopReason::SilenceTimeout,
-                                    });
-
-                                    if let Err(err) = event_tx.send(auto_stop_event) {
-                                        warn!(
-                                            target: "session_manager",
-                                            %err,
-                                            "failed to broadcast auto-stop event",
-                                        );
-                                    }
-
-                                    audio.reset_session();
-                                    info!(
-                                        target: "session_manager",
-                                        "silence countdown completed; auto-stop triggered",
-                                    );
-
-                                    let timestamp = SystemTime::now();
-                                    let session_id = {
-                                        active_session_id
-                                            .lock()
-                                            .await
-                                            .clone()
-                                            .unwrap_or_else(|| "unassigned".to_string())
-                                    };
-
-                                    record_session_silence_autostop(
-                                        &session_id,
-                                        payload.total_ms,
-                                        timestamp,
-                                    );
-
-                                    let timestamp_ms = system_time_to_ms(timestamp);
-                                    let queue_payload = json!({
-                                        "sessionId": session_id,
-                                        "timestampMs": timestamp_ms,
-                                        "reason": "silenceTimeout",
-                                        "countdownMs": payload.total_ms,
-                                    });
-
-                                    if let Err(err) = persistence
-                                        .enqueue_telemetry(
-                                            queue_payload["sessionId"]
-                                                .as_str()
-                                                .unwrap_or("unassigned")
-                                                .to_string(),
-                                            EVENT_SILENCE_AUTOSTOP.to_string(),
-                                            queue_payload,
-                                        )
-                                        .await
-                                    {
-                                        warn!(
-                                            target: "session_manager",
-                                            %err,
-                                            "failed to queue silence autostop telemetry",
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Ok(crate::audio::NoiseEvent::BaselineEstablished { .. }) => {
-                        countdown_active.store(false, Ordering::SeqCst);
-                        auto_stop_triggered.store(false, Ordering::SeqCst);
-                        let mut guard = snapshot.lock().await;
-                        *guard = None;
-                    }
-                    Err(RecvError::Lagged(skipped)) => {
-                        warn!(
-                            target: "session_manager",
-                            skipped,
-                            "noise event listener lagged",
-                        );
-                    }
-                    Err(RecvError::Closed) => break,
+                ticker.tick().await;
+                let Some(decision) = orchestrator.govern() else {
+                    continue;
+                };
+                let level = if decision.level == PerformanceLevel::Minimal {
+                    NoticeLevel::Warn
+                } else {
+                    NoticeLevel::Info
+                };
+                let mut message = decision.reason;
+                if decision.prefer_cloud {
+                    message.push_str("，新会话将优先使用云端识别");
                 }
+                let update = TranscriptionUpdate {
+                    payload: UpdatePayload::Notice(SessionNotice { level, message }),
+                    latency: Duration::from_millis(0),
+                    frame_index: 0,
+                    is_first: false,
+                };
+                flight_recorder.record_update("", &update);
+                let _ = update_tx.send(update);
             }
         });
     }
 
-    pub async fn cancel_silence_countdown_due_to_manual_stop(&self) {
-        let was_active = self.silence_countdown_active.swap(false, Ordering::SeqCst);
-        self.auto_stop_triggered.store(false, Ordering::SeqCst);
+    /// 电源调节器当前的档位；未接入调节器时为全速。
+    pub fn performance_level(&self) -> PerformanceLevel {
+        self.orchestrator
+            .governor()
+            .map(|governor| governor.level())
+            .unwrap_or_default()
+    }
 
-        if !was_active {
-            return;
-        }
+    /// 启动时为本地引擎选定的硬件配置。
+    pub fn engine_tuning(&self) -> Option<EngineTuning> {
+        self.orchestrator.tuning().cloned()
+    }
 
-        let snapshot = {
-            let mut guard = self.silence_countdown_snapshot.lock().await;
-            guard.take().unwrap_or(SilenceCountdownSnapshot {
-                total_ms: 5_000,
-                remaining_ms: 5_000,
-            })
-        };
+    /// 引擎当前的预热状态，界面可据此在开口前显示"引擎就绪"。
+    pub fn engine_warmup_status(&self) -> EngineWarmupStatus {
+        self.orchestrator.warmup_status()
+    }
 
-        let event = SessionEvent::SilenceCountdown(SessionSilenceCountdown {
-            total_ms: snapshot.total_ms,
-            remaining_ms: snapshot.remaining_ms,
-            state: SilenceCountdownState::Canceled,
-            cancel_reason: Some(SilenceCancellationReason::ManualStop),
-        });
+    /// 崩溃守护；进程入口应调用 `install_panic_hook` 以便 panic 时保存进行中的会话。
+    pub fn crash_guard(&self) -> CrashGuard {
+        self.crash_guard.clone()
+    }
 
-        if let Err(err) = self.event_tx.send(event) {
-            warn!(
-                target: "session_manager",
-                %err,
-                "failed to broadcast manual silence cancellation",
-            );
-        }
+    /// 返回启动时检测到的、上次异常退出遗留的会话快照。
+    pub async fn recover_last_session(&self) -> Option<RecoverySnapshot> {
+        self.recovered_session.lock().await.clone()
+    }
 
-        let timestamp = SystemTime::now();
-        let session_id = {
-            self.active_session_id
-                .lock()
-                .await
-                .clone()
-                .unwrap_or_else(|| "unassigned".to_string())
-        };
+    async fn detect_orphaned_session(&self) {
+        match self.crash_guard.take_orphaned() {
+            Ok(Some(snapshot)) => {
+                warn!(
+                    target: "session_manager",
+                    session_id = %snapshot.session_id,
+                    reason = snapshot.reason.as_deref().unwrap_or("unknown"),
+                    "recovered session from previous crash"
+                );
+                self.emit_notice(
+                    NoticeLevel::Warn,
+                    "检测到上次会话异常中断，可恢复未保存的转写内容。",
+                );
+                *self.recovered_session.lock().await = Some(snapshot);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!(target: "session_manager", %err, "failed to read crash recovery snapshot");
+            }
+        }
+        self.restore_checkpoints().await;
+    }
 
-        record_session_silence_countdown(
-            &session_id,
-            countdown_state_label(SilenceCountdownState::Canceled),
-            snapshot.total_ms,
-            snapshot.remaining_ms,
-            Some("manualStop"),
-            timestamp,
-        );
+    /// 切换遥测离线模式；离线时暂停上传并限制本地队列增长。
+    pub fn set_telemetry_offline(&self, offline: bool) {
+        self.telemetry_uploader.set_offline(offline);
+    }
 
-        let queue_payload = json!({
-            "sessionId": session_id,
-            "timestampMs": system_time_to_ms(timestamp),
-            "state": "canceled",
-            "totalMs": snapshot.total_ms,
-            "remainingMs": snapshot.remaining_ms,
-            "cancelReason": "manualStop",
-        });
+    pub fn telemetry_uploader(&self) -> TelemetryUploader {
+        self.telemetry_uploader.clone()
+    }
 
-        if let Err(err) = self
-            .persistence
-            .enqueue_telemetry(
-                queue_payload["sessionId"]
-                    .as_str()
-                    .unwrap_or("unassigned")
-                    .to_string(),
-                EVENT_SILENCE_COUNTDOWN.to_string(),
-                queue_payload,
-            )
-            .await
-        {
-            warn!(
-                target: "session_manager",
-                %err,
-                "failed to queue manual silence cancel telemetry",
-            );
+    /// 应用组织策略：按策略关闭云端与遥测、收紧历史保留，并拒绝与策略冲突的当前配置；
+    /// 之后热加载的配置同样按策略校验。
+    pub async fn apply_tenant_policy(&self, engine: &PolicyEngine) -> Result<(), PolicyError> {
+        let Some(policy) = engine.policy() else {
+            return Ok(());
+        };
+        let guard = self.orchestrator.offline_guard();
+        guard.set_tenant_forbids_cloud(!policy.allows_cloud());
+        guard.set_tenant_blocked_features(policy.region_blocked_features(&self.config.current()));
+        self.telemetry_uploader
+            .set_opted_out(policy.telemetry_opt_out);
+        self.egress_audit_required
+            .store(policy.audit_egress, Ordering::SeqCst);
+        if policy.audit_egress {
+            self.egress.set_enabled(true);
+        }
+        let cap = policy.history_retention_cap();
+        *self
+            .history_retention_cap
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = cap;
+        if cap.is_some() {
+            if let Err(err) = cleanup_history(&self.persistence, &self.history_retention_cap).await
+            {
+                warn!(target: "session_manager", %err, "policy history cleanup failed");
+            }
         }
+        self.config.enforce_policy(Some(policy));
+        engine.check_config(&self.config.current())
     }
 
-    async fn persist_transcript(&self, snapshot: SessionSnapshot) -> Result<()> {
-        self.persistence
-            .persist_session(snapshot)
-            .await
-            .map_err(|err| anyhow!("failed to persist transcript: {err}"))
+    /// 把服务商的 API Key 写入密钥库，`None` 时删除；之后开始的会话生效。
+    pub fn set_provider_api_key(&self, provider: LlmProvider, key: Option<&str>) -> Result<()> {
+        let name = provider
+            .secret_name()
+            .ok_or_else(|| anyhow!("{} does not use an api key", provider.as_str()))?;
+        let store = self
+            .orchestrator
+            .secret_store()
+            .ok_or_else(|| anyhow!("no secret store configured"))?;
+        match key {
+            Some(key) => store.set(name, key)?,
+            None => {
+                store.delete(name)?;
+            }
+        }
+        Ok(())
     }
 
-    fn emit_lifecycle(&self, update: SessionLifecycleUpdate) {
-        if let Err(err) = self.lifecycle_tx.send(update) {
-            warn!(
-                target: "session_manager",
-                %err,
-                "failed to broadcast lifecycle update"
-            );
+    /// 开启或关闭外发审计；组织策略要求审计时拒绝关闭。
+    pub fn set_egress_audit(&self, enabled: bool) -> Result<(), PolicyError> {
+        if !enabled && self.egress_audit_required.load(Ordering::SeqCst) {
+            return Err(PolicyError::Violation(vec![
+                "策略要求外发审计，不能关闭".to_string()
+            ]));
         }
+        self.egress.set_enabled(enabled);
+        Ok(())
     }
 
-    pub async fn publish_transcript(
-        &self,
-        snapshot: SessionSnapshot,
-        request: PublishRequest,
-    ) -> Result<PublishOutcome> {
-        let session_id = snapshot.session_id.clone();
-
-        let focus_context = request.focus.clone();
-        let fallback_strategy = request.fallback.clone();
-        let transcript = request.transcript.clone();
-
-        let fallback = fallback_option(&fallback_strategy);
-        self.emit_lifecycle(SessionLifecycleUpdate::publishing(
-            &session_id,
-            1,
-            PublishStrategy::DirectInsert,
-            fallback.clone(),
-        ));
-
-        record_session_publish_attempt(
-            &session_id,
-            focus_context.app_identifier.as_deref(),
-            focus_context.window_title.as_deref(),
-            fallback_strategy.as_str(),
-        );
-
-        match self.publisher.publish(request).await {
-            Ok(mut outcome) => {
-                if outcome.status == PublisherStatus::Failed
-                    && matches!(fallback_strategy, FallbackStrategy::ClipboardCopy)
-                {
-                    outcome = self
-                        .attempt_clipboard_fallback(
-                            &session_id,
-                            &transcript,
-                            &fallback_strategy,
-                            outcome,
-                        )
-                        .await;
-                }
-
-                let phase = outcome.status.as_phase();
-                match phase {
-                    SessionLifecyclePhase::Completed => {
-                        self.emit_lifecycle(SessionLifecycleUpdate::completed(
-                            &session_id,
-                            outcome.clone(),
-                        ));
-                    }
-                    SessionLifecyclePhase::Failed => {
-                        let (message, code) = outcome
-                            .failure
-                            .as_ref()
-                            .map(|failure| {
-                                (
-                                    failure.message.clone(),
-                                    Some(failure.code.as_str().to_string()),
-                                )
-                            })
-                            .unwrap_or_else(|| ("publisher reported failure".to_string(), None));
-
-                        self.emit_lifecycle(SessionLifecycleUpdate::failed(
-                            &session_id,
-                            outcome.attempts.max(1),
-                            message.clone(),
-                            code.clone(),
-                            outcome.fallback.clone(),
-                        ));
-
-                        record_session_publish_failure(
-                            &session_id,
-                            message,
-                            outcome.attempts.max(1),
-                            outcome.fallback.as_ref().map(FallbackStrategy::as_str),
-                        );
-                    }
-                    SessionLifecyclePhase::Publishing => {
-                        self.emit_lifecycle(SessionLifecycleUpdate::new(
-                            &session_id,
-                            SessionLifecyclePhase::Publishing,
-                        ));
-                    }
-                    other => {
-                        self.emit_lifecycle(SessionLifecycleUpdate::new(&session_id, other));
-                    }
-                }
+    pub fn egress_audit_enabled(&self) -> bool {
+        self.egress.is_enabled()
+    }
 
-                record_session_publish_outcome(
-                    &session_id,
-                    outcome.status.as_str(),
-                    outcome.strategy.as_str(),
-                    outcome.attempts,
-                    outcome.fallback.as_ref().map(FallbackStrategy::as_str),
-                );
+    /// 本地模型管理器，供引导页列出、下载与选择模型；切换的模型在下次启动时加载。
+    pub fn models(&self) -> ModelManager {
+        self.models.clone()
+    }
 
-                if matches!(
-                    outcome.status,
-                    PublisherStatus::Completed | PublisherStatus::Deferred
-                ) {
-                    if let Err(err) = self.persist_transcript(snapshot.clone()).await {
-                        self.handle_persistence_failure(&snapshot, err).await;
-                    }
-                }
+    /// 按条件查询外发审计记录，最新的在前。
+    pub async fn egress_log(&self, query: EgressQuery) -> Result<Vec<EgressRecord>> {
+        let log = Arc::clone(&self.egress_log);
+        tokio::task::spawn_blocking(move || log.query(&query))
+            .await
+            .map_err(|err| anyhow!("egress audit query task failed: {err}"))?
+    }
 
-                Ok(outcome)
-            }
-            Err(err) => {
-                self.emit_lifecycle(SessionLifecycleUpdate::failed(
-                    &session_id,
-                    1,
-                    err.to_string(),
-                    None,
-                    fallback.clone(),
-                ));
-                record_session_publish_failure(
-                    &session_id,
-                    err.to_string(),
-                    1,
-                    fallback.as_ref().map(FallbackStrategy::as_str),
-                );
-                Err(anyhow::anyhow!(err))
-            }
-        }
+    /// 校验审计链：任一记录被改动、删除或链尾被截断都会报告首个断点。
+    pub async fn verify_egress_log(&self) -> Result<EgressVerification> {
+        let log = Arc::clone(&self.egress_log);
+        tokio::task::spawn_blocking(move || log.verify())
+            .await
+            .map_err(|err| anyhow!("egress audit verification task failed: {err}"))?
     }
 
-    pub async fn save_transcript_draft(&self, request: DraftSaveRequest) -> Result<DraftRecord> {
-        let session_id = request.session_id.clone();
-        match self.persistence.save_draft(request).await {
-            Ok(record) => {
-                record_session_draft_saved(&session_id, &record.draft_id, &record.tags);
-                Ok(record)
-            }
-            Err(err) => {
-                record_session_draft_failed(&session_id, err.to_string());
-                Err(err)
-            }
-        }
+    /// 当前的自定义词表，新会话未显式指定词表时使用。
+    pub fn vocabulary(&self) -> Option<Arc<Vocabulary>> {
+        self.vocabulary
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
     }
 
-    pub async fn search_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+    pub async fn add_vocabulary_term(&self, term: VocabularyTerm) -> Result<()> {
         self.persistence
-            .search_history(query)
+            .upsert_vocabulary_term(term)
             .await
-            .map_err(|err| anyhow!("history search failed: {err}"))
+            .map_err(|err| anyhow!("failed to save vocabulary term: {err}"))?;
+        self.refresh_vocabulary().await
     }
 
-    pub async fn load_history_entry(&self, session_id: &str) -> Result<Option<HistoryEntry>> {
-        self.persistence
-            .load_session(session_id.to_string())
+    pub async fn remove_vocabulary_term(&self, term: String) -> Result<bool> {
+        let removed = self
+            .persistence
+            .remove_vocabulary_term(term)
             .await
-            .map_err(|err| anyhow!("history load failed: {err}"))
+            .map_err(|err| anyhow!("failed to remove vocabulary term: {err}"))?;
+        self.refresh_vocabulary().await?;
+        Ok(removed)
     }
 
-    pub async fn update_history_accuracy(&self, update: AccuracyUpdate) -> Result<()> {
+    async fn refresh_vocabulary(&self) -> Result<()> {
+        let vocabulary = self.persistence.load_vocabulary().await?;
+        *self
+            .vocabulary
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            (!vocabulary.is_empty()).then(|| Arc::new(vocabulary));
+        Ok(())
+    }
+
+    pub async fn replacement_rules(&self) -> Result<Vec<ReplacementRule>> {
         self.persistence
-            .update_accuracy(update)
+            .list_replacement_rules()
             .await
-            .map_err(|err| anyhow!("failed to update history accuracy: {err}"))
+            .map_err(|err| anyhow!("failed to load replacement rules: {err}"))
     }
 
-    pub async fn record_history_action(
-        &self,
-        session_id: String,
-        action: HistoryPostAction,
-    ) -> Result<Vec<HistoryPostAction>> {
+    pub async fn save_replacement_rule(&self, rule: ReplacementRule) -> Result<()> {
         self.persistence
-            .append_post_action(session_id, action)
+            .upsert_replacement_rule(rule)
             .await
-            .map_err(|err| anyhow!("failed to append history action: {err}"))
+            .map_err(|err| anyhow!("failed to save replacement rule: {err}"))?;
+        self.refresh_replacement_rules().await
     }
 
-    async fn attempt_clipboard_fallback(
-        &self,
-        session_id: &str,
-        transcript: &str,
-        fallback_strategy: &FallbackStrategy,
-        mut outcome: PublishOutcome,
-    ) -> PublishOutcome {
-        match self
-            .clipboard
-            .write_with_backup(
-                transcript,
-                Duration::from_millis(CLIPBOARD_FALLBACK_TIMEOUT_MS),
-            )
+    pub async fn remove_replacement_rule(&self, rule_id: String) -> Result<bool> {
+        let removed = self
+            .persistence
+            .remove_replacement_rule(rule_id)
             .await
-        {
-            Ok(fallback_handle) => {
-                info!(
-                    target: "session_manager",
-                    session_id,
-                    "clipboard fallback executed"
-                );
-
-                {
-                    let mut guard = self.clipboard_fallback.lock().await;
-                    *guard = Some(fallback_handle);
-                }
-
-                if let Some(failure) = &outcome.failure {
-                    record_session_publish_failure(
-                        session_id,
-                        failure.message.clone(),
-                        outcome.attempts.max(1),
-                        Some(fallback_strategy.as_str()),
-                    );
-                }
-
-                let message =
-                    "自动降级：已将润色稿复制到剪贴板，请切换至目标窗口粘贴（原内容已备份）。"
-                        .to_string();
-                self.emit_notice(NoticeLevel::Warn, message.clone());
-                self.persist_notice_entry(
-                    session_id,
-                    NOTICE_ACTION_COPY,
-                    NOTICE_RESULT_SUCCESS,
-                    NoticeLevel::Warn,
-                    message,
-                    None,
-                )
-                .await;
-                record_session_publish_degradation(
-                    session_id,
-                    fallback_strategy.as_str(),
-                    NOTICE_RESULT_SUCCESS,
-                );
-
-                outcome.status = PublisherStatus::Deferred;
-                outcome.strategy = PublishStrategy::ClipboardFallback;
-                outcome.fallback = Some(fallback_strategy.clone());
-                outcome
-            }
-            Err(err) => {
-                warn!(
-                    target: "session_manager",
-                    %err,
-                    "clipboard fallback failed"
-                );
-
-                let fallback_error = format!("剪贴板复制失败: {err}");
-                match outcome.failure.as_mut() {
-                    Some(failure) => {
-                        failure.message = format!("{}; {fallback_error}", failure.message);
-                    }
-                    None => {
-                        outcome.failure = Some(PublisherFailure::new(
-                            PublisherFailureCode::Unknown,
-                            fallback_error.clone(),
-                        ));
-                    }
-                }
+            .map_err(|err| anyhow!("failed to remove replacement rule: {err}"))?;
+        self.refresh_replacement_rules().await?;
+        Ok(removed)
+    }
 
-                let message =
-                    format!("自动降级失败：无法复制润色稿到剪贴板，请手动复制。错误: {err}");
-                self.emit_notice(NoticeLevel::Error, message.clone());
-                self.persist_notice_entry(
-                    session_id,
-                    NOTICE_ACTION_COPY,
-                    NOTICE_RESULT_FAILURE,
-                    NoticeLevel::Error,
-                    message,
-                    None,
-                )
-                .await;
-                record_session_publish_degradation(
-                    session_id,
-                    fallback_strategy.as_str(),
-                    NOTICE_RESULT_FAILURE,
-                );
+    async fn refresh_replacement_rules(&self) -> Result<()> {
+        let rules = ReplacementRules::compile(self.persistence.list_replacement_rules().await?);
+        *self
+            .replacement_rules
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(rules);
+        Ok(())
+    }
 
-                outcome
-            }
+    /// 润色之后、发布之前的最后一个文本阶段：按焦点应用替换规则。
+    fn apply_replacement_rules(&self, text: &str, focus: &FocusWindowContext) -> String {
+        let rules = self
+            .replacement_rules
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if rules.is_empty() {
+            return text.to_string();
         }
+        rules.apply(text, focus)
     }
 
-    fn emit_notice<S: Into<String>>(&self, level: NoticeLevel, message: S) {
-        let update = TranscriptionUpdate {
-            payload: UpdatePayload::Notice(SessionNotice {
-                level,
-                message: message.into(),
-            }),
-            latency: Duration::from_millis(0),
-            frame_index: 0,
-            is_first: false,
-        };
-
-        if let Err(err) = self.update_tx.send(update) {
-            warn!(
-                target: "session_manager",
-                %err,
-                "failed to broadcast clipboard notice"
-            );
-        }
+    pub fn polish_profiles(&self) -> Vec<PolishProfileBinding> {
+        self.polish_profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
     }
 
-    fn schedule_history_cleanup(&self) {
-        if self.history_cleanup_started.swap(true, Ordering::SeqCst) {
-            return;
+    /// 目标应用的润色风格：应用偏好优先，其次为应用绑定的预设，最后为全局默认。
+    pub fn polish_profile_for(&self, focus: &FocusWindowContext) -> Option<PolishProfile> {
+        if let Some(profile) = self
+            .app_profile_for(focus)
+            .and_then(|profile| profile.polish_profile)
+        {
+            return Some(profile);
         }
-        let persistence = self.persistence.clone();
-        tokio::spawn(async move {
-            let mut ticker = interval(Duration::from_secs(HISTORY_CLEANUP_INTERVAL_SECS));
-            loop {
-                ticker.tick().await;
-                let now_ms = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map(|duration| duration.as_millis() as i64)
-                    .unwrap_or(0);
-
-                if let Err(err) = persistence.cleanup_expired(now_ms).await {
-                    warn!(
-                        target: "session_manager",
-                        %err,
-                        "scheduled history cleanup failed"
-                    );
-                }
-            }
-        });
+        let bindings = self
+            .polish_profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        resolve_profile(&bindings, focus.app_identifier.as_deref())
     }
 
-    async fn persist_notice_entry(
+    pub async fn set_polish_profile(
         &self,
-        session_id: &str,
-        action: &str,
-        result: &str,
-        level: NoticeLevel,
-        message: String,
-        undo_token: Option<String>,
-    ) {
-        let request = NoticeSaveRequest {
-            notice_id: make_notice_id(session_id),
-            session_id: session_id.to_string(),
-            action: action.to_string(),
-            result: result.to_string(),
-            level: notice_level_value(level).to_string(),
-            message,
-            undo_token,
-        };
-
-        if let Err(err) = self.persistence.save_notice(request).await {
-            warn!(
-                target: "session_manager",
-                %err,
-                "failed to persist publish notice"
-            );
-        }
+        app_identifier: Option<String>,
+        profile: PolishProfile,
+    ) -> Result<()> {
+        self.persistence
+            .set_polish_profile(app_identifier, profile)
+            .await
+            .map_err(|err| anyhow!("failed to save polish profile: {err}"))?;
+        self.refresh_polish_profiles().await
     }
 
-    async fn handle_persistence_failure(&self, snapshot: &SessionSnapshot, error: anyhow::Error) {
-        warn!(
-            target: "session_manager",
-            session_id = %snapshot.session_id,
-            %error,
-            "failed to persist session history"
-        );
-
-        let mut notice_message = format!("历史记录保存失败：{}。", error);
+    pub async fn clear_polish_profile(&self, app_identifier: Option<String>) -> Result<bool> {
+        let removed = self
+            .persistence
+            .clear_polish_profile(app_identifier)
+            .await
+            .map_err(|err| anyhow!("failed to clear polish profile: {err}"))?;
+        self.refresh_polish_profiles().await?;
+        Ok(removed)
+    }
 
-        let clipboard_result = self
-            .clipboard
-            .write_with_backup(
-                &snapshot.polished_transcript,
-                Duration::from_millis(CLIPBOARD_FALLBACK_TIMEOUT_MS),
-            )
-            .await;
+    async fn refresh_polish_profiles(&self) -> Result<()> {
+        let bindings = self.persistence.list_polish_profiles().await?;
+        *self
+            .polish_profiles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = bindings;
+        Ok(())
+    }
 
-        match clipboard_result {
-            Ok(fallback_handle) => {
-                {
-                    let mut guard = self.clipboard_fallback.lock().await;
-                    *guard = Some(fallback_handle);
-                }
-                notice_message.push_str("已将润色稿复制到剪贴板作为备份。");
-            }
-            Err(copy_err) => {
-                notice_message.push_str("且无法复制到剪贴板，请手动保存文本。");
-                warn!(
-                    target: "session_manager",
-                    session_id = %snapshot.session_id,
-                    %copy_err,
-                    "clipboard backup for persistence failure failed"
-                );
-            }
-        }
+    pub fn app_profiles(&self) -> Vec<AppProfile> {
+        self.app_profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
 
-        self.emit_notice(NoticeLevel::Error, notice_message.clone());
-        self.persist_notice_entry(
-            &snapshot.session_id,
-            NOTICE_ACTION_COPY,
-            NOTICE_RESULT_FAILURE,
-            NoticeLevel::Error,
-            notice_message,
-            None,
-        )
-        .await;
+    pub fn app_profile_for(&self, focus: &FocusWindowContext) -> Option<AppProfile> {
+        let profiles = self
+            .app_profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        resolve_app_profile(&profiles, focus).cloned()
+    }
 
-        let payload = json!({
-            "session_id": snapshot.session_id,
-            "error": error.to_string(),
-        });
+    pub async fn save_app_profile(&self, profile: AppProfile) -> Result<()> {
+        self.persistence
+            .upsert_app_profile(profile)
+            .await
+            .map_err(|err| anyhow!("failed to save app profile: {err}"))?;
+        self.refresh_app_profiles().await
+    }
 
-        let _ = self
+    /// 删除应用偏好；`field_role` 为空时删除应用级偏好。
+    pub async fn remove_app_profile(
+        &self,
+        app_identifier: String,
+        field_role: Option<FieldRole>,
+    ) -> Result<bool> {
+        let removed = self
             .persistence
-            .enqueue_telemetry(
-                snapshot.session_id.clone(),
-                "history_persist_failure".into(),
-                payload,
-            )
-            .await;
+            .remove_app_profile(app_identifier, field_role)
+            .await
+            .map_err(|err| anyhow!("failed to remove app profile: {err}"))?;
+        self.refresh_app_profiles().await?;
+        Ok(removed)
     }
 
-    pub fn start_realtime_transcription(
-        &self,
-        config: RealtimeSessionConfig,
-    ) -> (RealtimeSessionHandle, mpsc::Receiver<TranscriptionUpdate>) {
-        let (handle, mut rx) = self.orchestrator.start_realtime_session(config.clone());
-        let frame_tx = handle.frame_sender();
-        let mut pcm_rx = self
-            .audio
-            .subscribe_lossless_pcm_frames(config.buffer_capacity);
-        let audio = self.audio.clone();
-        let updates_bus = self.update_tx.clone();
-        let (client_tx, client_rx) = mpsc::channel(config.buffer_capacity);
+    async fn refresh_app_profiles(&self) -> Result<()> {
+        let profiles = self.persistence.list_app_profiles().await?;
+        *self
+            .app_profiles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = profiles;
+        Ok(())
+    }
 
-        tokio::spawn(async move {
-            while let Some(frame) = pcm_rx.recv().await {
-                if frame_tx.send(frame).await.is_err() {
-                    break;
-                }
-            }
+    /// 已配置的多设备历史同步引擎。
+    pub fn history_sync(&self) -> Option<SyncEngine> {
+        self.history_sync.clone()
+    }
 
-            if let Err(err) = audio.flush_pending().await {
-                warn!(
-                    target: "session_manager",
-                    %err,
-                    "failed to flush pending pcm frames",
-                );
-            }
+    fn require_backup(&self) -> Result<BackupService> {
+        self.history_backup
+            .clone()
+            .ok_or_else(|| anyhow!("history backup is not configured"))
+    }
 
-            loop {
-                match timeout(Duration::from_millis(100), pcm_rx.recv()).await {
-                    Ok(Some(frame)) => {
-                        if frame_tx.send(frame).await.is_err() {
-                            break;
-                        }
-                    }
-                    Ok(None) | Err(_) => break,
-                }
-            }
-        });
+    /// 立即备份一次历史库。
+    pub async fn backup_history_now(&self) -> Result<BackupReport> {
+        let backup = self.require_backup()?;
+        tokio::task::spawn_blocking(move || {
+            backup.backup_once(system_time_to_ms(SystemTime::now()) as i64)
+        })
+        .await
+        .map_err(|err| anyhow!("blocking backup task failed: {err}"))?
+    }
 
-        tokio::spawn(async move {
-            while let Some(update) = rx.recv().await {
-                let guarantee_delivery = matches!(
-                    update.payload,
-                    UpdatePayload::Notice(SessionNotice {
-                        level: NoticeLevel::Warn | NoticeLevel::Error,
-                        ..
-                    })
-                );
+    pub async fn list_history_backups(&self) -> Result<Vec<BackupInfo>> {
+        let backup = self.require_backup()?;
+        tokio::task::spawn_blocking(move || backup.list_backups())
+            .await
+            .map_err(|err| anyhow!("blocking backup listing task failed: {err}"))?
+    }
 
-                if let Err(err) = updates_bus.send(update.clone()) {
-                    warn!(
-                        target: "session_manager",
-                        %err,
-                        "failed to broadcast session update"
-                    );
-                }
+    /// 下载并校验备份，下次启动时替换当前历史库；返回备份中的会话数。
+    pub async fn restore_history_backup(&self, name: &str) -> Result<usize> {
+        let backup = self.require_backup()?;
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || backup.stage_restore(&name))
+            .await
+            .map_err(|err| anyhow!("blocking restore task failed: {err}"))?
+    }
 
-                match client_tx.try_send(update.clone()) {
-                    Ok(()) => {}
-                    Err(mpsc::error::TrySendError::Full(update)) => {
-                        if guarantee_delivery {
-                            if client_tx.send(update).await.is_err() {
-                                break;
-                            }
-                        } else {
-                            warn!(
-                                target: "session_manager",
-                                "dropping realtime session update due to slow consumer"
-                            );
-                        }
-                    }
-                    Err(mpsc::error::TrySendError::Closed(_)) => break,
-                }
-            }
+    /// 以新密钥重新加密历史库；新密钥先写入密钥库，进度经 `progress` 汇报。
+    pub async fn rotate_history_key(
+        &self,
+        new_key: String,
+        progress: Option<mpsc::UnboundedSender<RekeyStage>>,
+    ) -> Result<()> {
+        self.persistence.rotate_key(new_key, progress).await?;
+        // 恢复文件密钥由库密钥派生，换密钥后同步更新，否则崩溃时写下的文件重启后无法解密。
+        if let Some(material) = self.persistence.sqlite().key_material()? {
+            self.crash_guard.rekey(&material)?;
+        }
+        Ok(())
+    }
+
+    /// 运行一键自检。未提供平台探针时用音频管线配置的采集后端检查设备与采集，
+    /// 两者都没有时权限、设备与采集项记为跳过。
+    pub async fn self_check(
+        &self,
+        platform: Option<Arc<dyn SelfCheckPlatform>>,
+    ) -> SelfCheckReport {
+        let platform = platform.or_else(|| {
+            self.audio.capture_backend().map(|(backend, config)| {
+                Arc::new(CaptureBackendProbe::new(backend, config.device_id))
+                    as Arc<dyn SelfCheckPlatform>
+            })
         });
+        run_self_check(SelfCheckTargets {
+            platform,
+            orchestrator: Some(&self.orchestrator),
+            database: Some(self.persistence.sqlite()),
+        })
+        .await
+    }
 
-        (handle, client_rx)
+    pub fn audio_pipeline(&self) -> AudioPipeline {
+        self.audio.clone()
     }
 
-    #[cfg(test)]
-    pub fn persistence_handle(&self) -> PersistenceHandle {
-        self.persistence.clone()
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<TranscriptionUpdate> {
+        self.update_tx.subscribe()
     }
-}
 
-fn fallback_option(strategy: &FallbackStrategy) -> Option<FallbackStrategy> {
-    match strategy {
-        FallbackStrategy::None => None,
-        other => Some(other.clone()),
+    /// 与 `subscribe_updates` 相同的更新流，但 Warn/Error 提示与控制类更新保证送达，
+    /// 只有转写等尽力更新会在消费过慢时被丢弃。
+    pub fn subscribe_update_lanes(&self, capacity: usize) -> mpsc::Receiver<TranscriptionUpdate> {
+        let (dispatcher, rx) = UpdateDispatcher::channel(capacity, &self.shutdown);
+        self.lane_subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(dispatcher);
+        rx
     }
-}
 
-fn countdown_state_label(state: SilenceCountdownState) -> &'static str {
-    match state {
-        SilenceCountdownState::Started => "started",
-        SilenceCountdownState::Tick => "tick",
-        SilenceCountdownState::Canceled => "canceled",
-        SilenceCountdownState::Completed => "completed",
+    pub fn subscribe_lifecycle(&self) -> broadcast::Receiver<SessionLifecycleUpdate> {
+        self.lifecycle_tx.subscribe()
     }
-}
 
-fn system_time_to_ms(timestamp: SystemTime) -> u128 {
-    timestamp
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_millis())
-        .unwrap_or(0)
-}
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SessionEvent> {
+        self.event_tx.subscribe()
+    }
 
-fn make_notice_id(session_id: &str) -> String {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_millis())
-        .unwrap_or(0);
-    format!("{session_id}-notice-{timestamp}")
-}
+    pub fn subscribe_capture(&self) -> broadcast::Receiver<CaptureEvent> {
+        self.capture_tx.subscribe()
+    }
 
-fn notice_level_value(level: NoticeLevel) -> &'static str {
-    match level {
-        NoticeLevel::Info => "info",
-        NoticeLevel::Warn => "warn",
-        NoticeLevel::Error => "error",
+    /// 字幕广播器，可用于启动本地 SSE 端点。
+    pub fn captions(&self) -> CaptionBroadcaster {
+        self.captions.clone()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::orchestrator::{
-        EngineConfig, EngineOrchestrator, NoticeLevel, SpeechEngine, TranscriptSource,
-        UpdatePayload,
-    };
-    use crate::session::clipboard::{ClipboardAccess, ClipboardError, ClipboardManager};
-    use crate::session::lifecycle::SessionLifecyclePayload;
-    use crate::session::publisher::FocusWindowContext;
-    use crate::session::publisher::PublisherError;
-    use anyhow::anyhow;
-    use async_trait::async_trait;
-    use serde_json::json;
-    use std::collections::VecDeque;
-    use std::sync::{Arc, Mutex};
-    use tokio::sync::{broadcast::error::RecvError, Mutex as AsyncMutex};
-    use tokio::time::{timeout, Duration};
-
-    struct ProgrammedSpeechEngine {
-        responses: Mutex<VecDeque<anyhow::Result<String>>>,
-    }
-
-    impl ProgrammedSpeechEngine {
-        fn new(responses: Vec<anyhow::Result<String>>) -> Self {
-            Self {
-                responses: Mutex::new(responses.into()),
-            }
-        }
+    /// 开启实时字幕：此后每句润色稿都会按 `config` 切行后广播。
+    pub fn enable_captions(&self, config: CaptionConfig) {
+        self.captions.set_config(Some(config));
     }
 
-    #[async_trait]
-    impl SpeechEngine for ProgrammedSpeechEngine {
-        async fn transcribe(&self, _frame: &[f32]) -> anyhow::Result<String> {
-            match self
-                .responses
-                .lock()
-                .expect("responses lock poisoned")
-                .pop_front()
-            {
-                Some(result) => result,
-                None => Ok(String::new()),
-            }
-        }
+    pub fn disable_captions(&self) {
+        self.captions.set_config(None);
     }
 
-    #[derive(Clone)]
-    struct StubPublisher {
-        outcome: PublishOutcome,
+    /// 供桌面端字幕悬浮层订阅。
+    pub fn subscribe_captions(&self) -> broadcast::Receiver<CaptionFrame> {
+        self.captions.subscribe()
     }
 
-    impl StubPublisher {
-        fn new(outcome: PublishOutcome) -> Self {
-            Self { outcome }
-        }
+    pub fn capture_mode(&self) -> Option<CaptureMode> {
+        self.capture
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_ref()
+            .map(CaptureController::mode)
     }
 
-    #[async_trait]
-    impl SessionPublisher for StubPublisher {
-        async fn publish(
-            &self,
-            _request: PublishRequest,
-        ) -> Result<PublishOutcome, PublisherError> {
-            Ok(self.outcome.clone())
-        }
+    /// 由核心接管录音触发：此后仅在录音状态下向转写会话转发 PCM 帧。
+    pub fn set_capture_mode(&self, mode: CaptureMode) {
+        self.configure_capture(mode, VoiceActivationConfig::default());
     }
 
-    fn make_snapshot(session_id: &str, raw: &str, polished: &str) -> SessionSnapshot {
-        SessionSnapshot {
-            session_id: session_id.into(),
-            started_at_ms: 0,
-            completed_at_ms: 0,
-            locale: None,
-            app_identifier: Some("com.example.app".into()),
-            app_version: None,
-            confidence_score: None,
-            raw_transcript: raw.into(),
-            polished_transcript: polished.into(),
-            metadata: json!({}),
-            post_actions: vec![],
+    pub fn configure_capture(&self, mode: CaptureMode, voice: VoiceActivationConfig) {
+        let event = {
+            let mut guard = self
+                .capture
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let event = guard
+                .as_mut()
+                .and_then(|controller| controller.set_mode(mode));
+            *guard = Some(CaptureController::new(mode, voice));
+            event
+        };
+        if let Some(event) = event {
+            apply_capture_event(&self.audio, &self.capture_tx, event);
         }
     }
 
-    #[derive(Clone, Default)]
-    struct RecordingClipboard {
-        state: Arc<AsyncMutex<Option<String>>>,
-        write_error: Arc<AsyncMutex<Option<ClipboardError>>>,
+    /// 桌面端热键层上报按键状态，由当前录音模式决定是否开始或结束录音。
+    pub fn report_hotkey(&self, pressed: bool) -> Option<CaptureEvent> {
+        report_capture_hotkey(&self.audio, &self.capture, &self.capture_tx, pressed)
     }
 
-    impl RecordingClipboard {
-        async fn contents(&self) -> Option<String> {
-            self.state.lock().await.clone()
-        }
-
-        async fn set_write_error(&self, error: ClipboardError) {
-            *self.write_error.lock().await = Some(error);
-        }
+    /// 由 core 直接监听全局热键（CLI 等没有桌面热键层的外壳使用），
+    /// 绑定组合的按下与抬起等同于 [`Self::report_hotkey`]。释放返回的句柄即停止监听。
+    pub fn listen_hotkey(
+        &self,
+        backend: &dyn HotkeyBackend,
+        binding: HotkeyCombination,
+    ) -> Result<HotkeyListener, HotkeyError> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let listener = spawn_hold_to_talk(backend, binding, tx)?;
+        let audio = self.audio.clone();
+        let capture = Arc::clone(&self.capture);
+        let capture_tx = self.capture_tx.clone();
+        std::thread::spawn(move || {
+            for event in rx {
+                let pressed = matches!(event, HoldToTalkEvent::Pressed { .. });
+                report_capture_hotkey(&audio, &capture, &capture_tx, pressed);
+            }
+        });
+        Ok(listener)
     }
 
-    #[async_trait]
-    impl ClipboardAccess for RecordingClipboard {
-        async fn read_text(&self, _timeout: Duration) -> Result<Option<String>, ClipboardError> {
-            Ok(self.state.lock().await.clone())
-        }
+    /// 上报同一绑定上识别出的手势。双击产生的停止事件以 [`CaptureTrigger::Discard`]
+    /// 标记，订阅方据此丢弃未发布的草稿而不是走发布流程。
+    pub fn report_gesture(&self, gesture: HotkeyGesture) -> Vec<CaptureEvent> {
+        report_capture_gesture(&self.audio, &self.capture, &self.capture_tx, gesture)
+    }
 
-        async fn write_text(
-            &self,
-            contents: &str,
-            _timeout: Duration,
-        ) -> Result<(), ClipboardError> {
-            if let Some(err) = self.write_error.lock().await.clone() {
-                return Err(err);
+    /// 与 [`Self::listen_hotkey`] 相同，但按 `config` 识别单击、双击与长按，
+    /// 经 [`Self::report_gesture`] 驱动录音。
+    pub fn listen_hotkey_gestures(
+        &self,
+        backend: &dyn HotkeyBackend,
+        binding: HotkeyCombination,
+        config: GestureConfig,
+    ) -> Result<HotkeyListener, HotkeyError> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let listener = spawn_gestures(backend, binding, config, tx)?;
+        let audio = self.audio.clone();
+        let capture = Arc::clone(&self.capture);
+        let capture_tx = self.capture_tx.clone();
+        std::thread::spawn(move || {
+            for gesture in rx {
+                report_capture_gesture(&audio, &capture, &capture_tx, gesture);
             }
+        });
+        Ok(listener)
+    }
 
-            *self.state.lock().await = Some(contents.to_string());
-            Ok(())
-        }
+    /// 结束当前录音并丢弃草稿，停止事件以 [`CaptureTrigger::Discard`] 标记。
+    pub fn cancel_capture(&self) -> Option<CaptureEvent> {
+        let event = self
+            .capture
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_mut()?
+            .stop(CaptureTrigger::Discard)?;
+        apply_capture_event(&self.audio, &self.capture_tx, event);
+        Some(event)
+    }
 
-        async fn clear(&self, _timeout: Duration) -> Result<(), ClipboardError> {
-            *self.state.lock().await = None;
-            Ok(())
+    /// 执行命名快捷键对应的操作。
+    pub async fn dispatch_action(&self, action: HotkeyAction) -> Result<()> {
+        info!(
+            target: "session_manager",
+            action = action.as_str(),
+            "hotkey action dispatched"
+        );
+        match action {
+            HotkeyAction::CancelSession => {
+                if self.cancel_capture().is_none() {
+                    return Err(anyhow!("no capture in progress"));
+                }
+                Ok(())
+            }
+            HotkeyAction::UndoPublish => self.undo_last_publish().await,
+            HotkeyAction::RepolishLast => self.repolish_last_sentence().await,
         }
     }
 
-    #[tokio::test]
-    async fn noise_warning_events_forwarded_to_session_channel() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager
-            .run()
+    /// 重新润色最近开始的实时会话的最后一句，结果经该会话的更新通道下发。
+    pub async fn repolish_last_sentence(&self) -> Result<()> {
+        let commands = self
+            .transcript_commands
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+            .ok_or_else(|| anyhow!("no realtime session to repolish"))?;
+        commands
+            .send(TranscriptCommand::RepolishLast)
             .await
-            .expect("session manager bootstrap should succeed");
+            .map_err(|_| anyhow!("realtime session has ended"))
+    }
 
-        manager.set_active_session_id("session-manual-stop").await;
+    pub fn password_field_policy(&self) -> PasswordFieldPolicy {
+        *self
+            .password_field_policy
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 
-        manager
-            .set_active_session_id("session-noise-telemetry")
-            .await;
+    pub fn set_password_field_policy(&self, policy: PasswordFieldPolicy) {
+        *self
+            .password_field_policy
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+    }
+
+    pub fn silence_policy(&self) -> SilencePolicy {
+        self.audio.silence_policy()
+    }
 
-        let audio = manager.audio_pipeline();
-        let mut events_rx = manager.subscribe_events();
+    /// 设置静音自动停止策略并按用户保存，立即作用于进行中的倒计时。
+    pub async fn set_silence_policy(&self, policy: SilencePolicy) -> Result<()> {
+        policy.validate()?;
+        self.persistence
+            .store_silence_policy(policy)
+            .await
+            .map_err(|err| anyhow!("failed to save silence policy: {err}"))?;
+        self.audio.set_silence_policy(policy);
+        Ok(())
+    }
 
-        audio.begin_preroll(Some(-32.0));
-        audio.begin_recording();
+    async fn refresh_noise_baselines(&self) -> Result<()> {
+        let baselines = self.persistence.list_noise_baselines().await?;
+        self.audio
+            .set_noise_baselines(baselines.into_iter().collect());
+        Ok(())
+    }
 
-        let loud_frame = vec![0.8_f32; 1_600];
-        for _ in 0..3 {
-            audio
-                .push_pcm_frame(loud_frame.clone())
-                .await
-                .expect("push loud frame");
+    async fn refresh_silence_policy(&self) -> Result<()> {
+        if let Some(policy) = self.persistence.load_silence_policy().await? {
+            self.audio.set_silence_policy(policy);
         }
+        Ok(())
+    }
+
+    async fn restore_cloud_budget(&self) -> Result<()> {
+        if let Some(usage) = self.persistence.load_cloud_budget_usage().await? {
+            self.orchestrator.budget().restore(usage);
+        }
+        Ok(())
+    }
 
-        let warning = timeout(Duration::from_millis(800), async {
+    /// 定期保存云端用量，重启后当期的累计不会清零。
+    fn spawn_budget_persister(&self) {
+        let budget = self.orchestrator.budget();
+        let persistence = self.persistence.clone();
+        self.spawn_background(async move {
+            let mut ticker = tokio::time::interval(BUDGET_PERSIST_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
             loop {
-                match events_rx.recv().await {
-                    Ok(SessionEvent::NoiseWarning(payload)) => break payload,
-                    Ok(_) => continue,
-                    Err(RecvError::Lagged(_)) => continue,
-                    Err(err) => panic!("session event channel closed: {err:?}"),
-                }
+                ticker.tick().await;
+                save_cloud_budget(&persistence, &budget).await;
             }
-        })
-        .await
-        .expect("noise warning timed out");
+        });
+    }
 
-        assert!((warning.threshold_db - warning.baseline_db - 15.0).abs() < 0.5);
-        assert!(warning.level_db >= warning.threshold_db);
-        assert!(warning.persistence_ms >= 300);
+    async fn restore_latency_calibration(&self) -> Result<()> {
+        let Some(calibrator) = self.orchestrator.latency_calibrator() else {
+            return Ok(());
+        };
+        if let Some(samples) = self.persistence.load_latency_samples().await? {
+            calibrator.restore(samples);
+        }
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn silence_countdown_completion_triggers_auto_stop_once() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager
-            .run()
-            .await
-            .expect("session manager bootstrap should succeed");
+    /// 每次会话产生新样本后保存，期限变化时上报遥测。
+    fn spawn_latency_persister(&self) {
+        let Some(calibrator) = self.orchestrator.latency_calibrator() else {
+            return;
+        };
+        let persistence = self.persistence.clone();
+        let mut calibration_rx = calibrator.subscribe();
+        self.spawn_background(async move {
+            let mut reported = calibration_rx.borrow().first_update_deadline;
+            while calibration_rx.changed().await.is_ok() {
+                let calibration = *calibration_rx.borrow_and_update();
+                if calibration.calibrated && calibration.first_update_deadline != reported {
+                    reported = calibration.first_update_deadline;
+                    record_sla_calibration(&calibration);
+                }
+                if let Err(err) = persistence
+                    .store_latency_samples(calibrator.samples())
+                    .await
+                {
+                    warn!(target: "session_manager", %err, "failed to save latency calibration");
+                }
+            }
+        });
+    }
 
-        manager
-            .set_active_session_id("session-silence-autostop")
-            .await;
+    /// 按本机实测延迟校准的会话期限；未接入校准器时为 `None`。
+    pub fn sla_calibration(&self) -> Option<SlaCalibration> {
+        self.orchestrator
+            .latency_calibrator()
+            .map(|calibrator| calibrator.calibration())
+    }
 
-        let audio = manager.audio_pipeline();
-        let mut events_rx = manager.subscribe_events();
+    /// 当前日、月的云端用量与上限。
+    pub fn cloud_budget(&self) -> BudgetReport {
+        self.orchestrator.budget().report()
+    }
 
-        audio.begin_preroll(Some(-28.0));
-        audio.begin_recording();
+    pub fn is_capturing(&self) -> bool {
+        self.capture
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_ref()
+            .is_none_or(CaptureController::is_capturing)
+    }
 
-        let quiet_frame = vec![0.001_f32; 1_600];
-        for _ in 0..50 {
-            audio
-                .push_pcm_frame(quiet_frame.clone())
-                .await
-                .expect("push quiet frame");
+    pub async fn set_active_session_id<S: Into<String>>(&self, session_id: S) {
+        let session_id = session_id.into();
+        let recorder = self.recorder.lock().await.clone();
+        if let Some(recorder) = recorder {
+            if let Err(err) = recorder.start(&self.audio, &session_id).await {
+                warn!(target: "session_manager", %err, session_id = %session_id, "failed to start session recording");
+            }
         }
+        self.crash_guard.begin(&session_id);
+        self.begin_calendar_lookup(&session_id, system_time_to_ms(SystemTime::now()) as i64);
+        let mut guard = self.active_session_id.lock().await;
+        *guard = Some(session_id);
+    }
 
-        let mut countdown_completed = false;
-        let mut auto_stop_count = 0;
-
-        for _ in 0..128 {
-            let event = timeout(Duration::from_millis(1_000), events_rx.recv())
+    pub async fn clear_active_session_id(&self) {
+        self.finish_recording().await;
+        let finished = self.crash_guard.in_flight();
+        self.crash_guard.clear();
+        if let Some(snapshot) = finished {
+            if let Err(err) = self
+                .persistence
+                .remove_checkpoint(snapshot.session_id)
                 .await
-                .expect("waiting for session event timed out");
-
-            match event {
-                Ok(SessionEvent::SilenceCountdown(payload))
-                    if payload.state == SilenceCountdownState::Completed =>
-                {
-                    countdown_completed = true;
-                }
-                Ok(SessionEvent::AutoStop(_)) => {
-                    auto_stop_count += 1;
-                    break;
-                }
-                Ok(_) => {}
-                Err(RecvError::Lagged(_)) => continue,
-                Err(err) => panic!("session event channel closed: {err:?}"),
+            {
+                warn!(target: "session_manager", %err, "failed to remove session checkpoint");
             }
         }
+        let mut guard = self.active_session_id.lock().await;
+        *guard = None;
+    }
 
-        assert!(countdown_completed, "expected countdown completion event");
-        assert_eq!(auto_stop_count, 1, "auto-stop should trigger exactly once");
+    /// 启用会话录音：此后每个活动会话的原始 PCM 会加密写入以会话 ID 命名的归档。
+    pub async fn enable_recording(&self, recorder: SessionRecorder) {
+        self.finish_recording().await;
+        self.persistence.attach_recordings(Some(recorder.clone()));
+        *self.recorder.lock().await = Some(recorder);
+    }
 
-        for _ in 0..5 {
-            audio
-                .push_pcm_frame(quiet_frame.clone())
-                .await
-                .expect("push quiet frame after auto-stop");
+    /// 按 `session.record_audio` 启用录音：归档写入 `data_dir/recordings`，密钥取自密钥库，
+    /// 桌面壳回放时用同一把密钥解密。
+    async fn enable_configured_recording(&self, data_dir: &Path) {
+        let Some(store) = self.orchestrator.secret_store() else {
+            warn!(target: "session_manager", "session recording needs a secret store");
+            return;
+        };
+        match SessionRecorder::open(data_dir, store.as_ref()) {
+            Ok(recorder) => self.enable_recording(recorder).await,
+            Err(err) => warn!(target: "session_manager", %err, "session recording disabled"),
         }
+    }
 
-        if let Ok(event) = timeout(Duration::from_millis(250), events_rx.recv()).await {
-            if let Ok(event) = event {
-                assert!(
-                    !matches!(event, SessionEvent::AutoStop(_)),
-                    "unexpected extra auto-stop event",
-                );
+    pub async fn disable_recording(&self) {
+        self.finish_recording().await;
+        self.persistence.attach_recordings(None);
+        *self.recorder.lock().await = None;
+    }
+
+    async fn finish_recording(&self) {
+        let recorder = self.recorder.lock().await.clone();
+        if let Some(recorder) = recorder {
+            if let Err(err) = recorder.finish().await {
+                warn!(target: "session_manager", %err, "failed to finalise session recording");
             }
         }
     }
 
-    #[tokio::test]
-    async fn manual_stop_cancels_active_silence_countdown() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager
-            .run()
-            .await
-            .expect("session manager bootstrap should succeed");
+    /// 转写结束、发布之前提交用户对句子的修改，返回改后的全文；发布时以该文本为准。
+    /// 可多次提交，同一句以最后一次为准。
+    pub fn amend_transcript(&self, session_id: &str, edits: Vec<SentenceEdit>) -> Result<String> {
+        let snapshot = self
+            .crash_guard
+            .in_flight()
+            .filter(|snapshot| snapshot.session_id == session_id)
+            .ok_or_else(|| anyhow!("session {session_id} has no transcript awaiting publish"))?;
+        let mut amendments = self
+            .amendments
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let amendment = TranscriptAmendment::apply(
+            &snapshot,
+            amendments.get(session_id),
+            &edits,
+            system_time_to_ms(SystemTime::now()) as i64,
+        )?;
+        record_session_transcript_amended(session_id, amendment.edited_sentences());
+        let text = amendment.text.clone();
+        amendments.insert(session_id.to_string(), amendment);
+        Ok(text)
+    }
 
-        manager.set_active_session_id("session-manual-stop").await;
+    fn take_amendment(&self, session_id: &str) -> Option<TranscriptAmendment> {
+        self.amendments
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(session_id)
+    }
 
-        let audio = manager.audio_pipeline();
-        let mut events_rx = manager.subscribe_events();
+    fn take_audio_source(&self, session_id: &str) -> Option<AudioSource> {
+        self.audio_sources
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(session_id)
+    }
 
-        audio.begin_preroll(Some(-30.0));
-        audio.begin_recording();
+    /// 取出会话累计的有声时长，结合最终文本字数计算语速并广播摘要。
+    fn take_dictation_speed(&self, snapshot: &SessionSnapshot) -> Option<DictationSpeed> {
+        let speech = self
+            .speech_time
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&snapshot.session_id)?;
+        let transcript = if snapshot.polished_transcript.trim().is_empty() {
+            &snapshot.raw_transcript
+        } else {
+            &snapshot.polished_transcript
+        };
+        let speed = DictationSpeed::new(count_words(transcript), speech);
+        let _ = self
+            .event_tx
+            .send(SessionEvent::SpeedSummary(SessionSpeedSummary {
+                session_id: snapshot.session_id.clone(),
+                speed,
+            }));
+        Some(speed)
+    }
 
-        let quiet_frame = vec![0.001_f32; 1_600];
-        audio
-            .push_pcm_frame(quiet_frame.clone())
+    async fn persist_transcript(&self, snapshot: SessionSnapshot) -> Result<()> {
+        self.persistence
+            .persist_session(snapshot)
             .await
-            .expect("push quiet frame");
-
-        timeout(Duration::from_millis(800), async {
-            loop {
-                match events_rx.recv().await {
-                    Ok(SessionEvent::SilenceCountdown(payload))
-                        if payload.state == SilenceCountdownState::Started =>
-                    {
-                        break;
-                    }
-                    Ok(_) => continue,
-                    Err(RecvError::Lagged(_)) => continue,
-                    Err(err) => panic!("session event channel closed: {err:?}"),
-                }
-            }
-        })
-        .await
-        .expect("countdown start not observed");
-
-        manager.cancel_silence_countdown_due_to_manual_stop().await;
+            .map_err(|err| anyhow!("failed to persist transcript: {err}"))
+    }
 
-        let canceled = timeout(Duration::from_millis(800), async {
-            loop {
-                match events_rx.recv().await {
-                    Ok(SessionEvent::SilenceCountdown(payload))
-                        if payload.state == SilenceCountdownState::Canceled =>
-                    {
-                        break payload;
-                    }
-                    Ok(_) => continue,
-                    Err(RecvError::Lagged(_)) => continue,
-                    Err(err) => panic!("session event channel closed: {err:?}"),
-                }
-            }
-        })
-        .await
-        .expect("manual cancellation event missing");
+    fn emit_lifecycle(&self, update: SessionLifecycleUpdate) {
+        if let Err(err) = self.lifecycle_tx.send(update) {
+            warn!(
+                target: "session_manager",
+                %err,
+                "failed to broadcast lifecycle update"
+            );
+        }
+    }
 
-        assert_eq!(
-            canceled.cancel_reason,
-            Some(SilenceCancellationReason::ManualStop),
-            "expected manual stop cancel reason",
+    /// Webhook 只在 `run` 中启动一次，配置可随时替换。
+    fn spawn_webhook_dispatcher(&self) {
+        if self.webhooks_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let active_session_id = Arc::clone(&self.active_session_id);
+        self.webhooks.spawn(
+            self.lifecycle_tx.subscribe(),
+            self.event_tx.subscribe(),
+            move || {
+                active_session_id
+                    .try_lock()
+                    .ok()
+                    .and_then(|active| active.clone())
+            },
         );
+    }
 
-        audio.reset_session();
-        audio.begin_preroll(Some(-30.0));
-        audio.begin_recording();
+    /// 全局配置服务，供宿主读取各子系统配置或订阅变更。
+    pub fn config(&self) -> &ConfigService {
+        &self.config
+    }
 
-        for _ in 0..10 {
-            audio
-                .push_pcm_frame(quiet_frame.clone())
-                .await
-                .expect("push quiet frame after restart");
-        }
+    /// 本进程使用的配置档，数据均存放在其目录下。
+    pub fn active_profile(&self) -> Result<String> {
+        workspace::active_profile()
+    }
 
-        timeout(Duration::from_millis(1_200), async {
+    /// 监听配置文件；会话段的变更立即生效，其余段落在重启后生效。
+    fn spawn_config_watcher(&self) {
+        if self.config_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let mut changes = self.config.subscribe();
+        let watcher = self.config.watch(DEFAULT_WATCH_INTERVAL);
+        *self
+            .config_watch
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(watcher);
+        let audio = self.audio.clone();
+        let max_session_duration = Arc::clone(&self.max_session_duration);
+        let checkpoint_interval = Arc::clone(&self.checkpoint_interval);
+        let persistence = self.persistence.clone();
+        let budget = self.orchestrator.budget();
+        let governor = self.orchestrator.governor();
+        self.spawn_background(async move {
             loop {
-                match events_rx.recv().await {
-                    Ok(SessionEvent::SilenceCountdown(payload))
-                        if payload.state == SilenceCountdownState::Started =>
-                    {
-                        break;
-                    }
-                    Ok(_) => continue,
+                let change = match changes.recv().await {
+                    Ok(change) => change,
                     Err(RecvError::Lagged(_)) => continue,
-                    Err(err) => panic!("session event channel closed: {err:?}"),
+                    Err(RecvError::Closed) => break,
+                };
+                for section in &change.changed {
+                    if !section.is_live() {
+                        info!(
+                            target: "session_manager",
+                            ?section,
+                            "configuration change takes effect after restart"
+                        );
+                    }
+                }
+                if change.changed.contains(&ConfigSection::Session) {
+                    audio.set_preroll_window(change.config.preroll_window());
+                    audio.configure_agc(change.config.agc_config());
+                    *max_session_duration
+                        .write()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+                        change.config.max_session_duration();
+                    *checkpoint_interval
+                        .write()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+                        change.config.checkpoint_interval();
+                }
+                if change.changed.contains(&ConfigSection::Redaction) {
+                    persistence.set_redactor(change.config.redactor().map(Arc::new));
+                }
+                if change.changed.contains(&ConfigSection::Budget) {
+                    budget.set_caps(change.config.budget_caps());
+                }
+                if change.changed.contains(&ConfigSection::Power) {
+                    if let Some(governor) = &governor {
+                        governor.set_config(change.config.governor_config());
+                    }
                 }
             }
-        })
-        .await
-        .expect("countdown did not restart after manual cancel");
+        });
     }
 
-    #[tokio::test]
-    async fn noise_warning_is_persisted_to_telemetry_queue() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager
-            .run()
-            .await
-            .expect("session manager bootstrap should succeed");
+    /// 注册自动化脚本，同名脚本会被替换；未启用 `scripting` 特性时返回错误。
+    pub fn register_script(&self, script: AutomationScript) -> Result<()> {
+        self.scripts.register(script)
+    }
 
-        manager
-            .set_active_session_id("session-telemetry-noise")
-            .await;
+    pub fn remove_script(&self, name: &str) -> bool {
+        self.scripts.remove(name)
+    }
 
-        let audio = manager.audio_pipeline();
-        audio.begin_preroll(Some(-36.0));
-        audio.begin_recording();
+    pub fn automation_scripts(&self) -> Vec<AutomationScript> {
+        self.scripts.scripts()
+    }
 
-        let loud_frame = vec![0.9_f32; 1_600];
-        for _ in 0..3 {
-            audio
-                .push_pcm_frame(loud_frame.clone())
-                .await
-                .expect("push loud frame");
-        }
+    /// 加载数据目录下 `plugins` 中的插件，并把提供后续动作的插件注册到历史动作。
+    /// 自定义润色器与发布器需由宿主通过 `plugins()` 取出后组装编排器与会话。
+    pub fn load_plugins(&self) -> Result<Vec<String>> {
+        let loaded = self
+            .plugins
+            .load_dir(&resolve_data_dir()?.join("plugins"))?;
+        self.plugins.register_actions(&self.history_actions);
+        Ok(loaded)
+    }
 
-        let mut rx = manager.subscribe_events();
-        timeout(Duration::from_millis(800), async {
-            loop {
-                match rx.recv().await {
-                    Ok(SessionEvent::NoiseWarning(_)) => break,
-                    Ok(_) => continue,
-                    Err(RecvError::Lagged(_)) => continue,
-                    Err(err) => panic!("session event channel closed: {err:?}"),
-                }
-            }
-        })
-        .await
-        .expect("noise warning timed out");
-
-        let persistence = manager.persistence_handle();
-        let conn = persistence
-            .sqlite()
-            .connection()
-            .expect("persistence connection");
-        let (event_type, payload): (String, String) = conn
-            .query_row(
-                "SELECT event_type, payload FROM telemetry_queue ORDER BY id DESC LIMIT 1",
-                [],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .expect("telemetry row");
-
-        assert_eq!(event_type, EVENT_NOISE_WARNING);
-        let payload_json: serde_json::Value =
-            serde_json::from_str(&payload).expect("noise telemetry payload");
-        assert_eq!(payload_json["sessionId"], "session-telemetry-noise");
-        assert!(payload_json["thresholdDb"].as_f64().is_some());
-        assert!(payload_json["occurredAtMs"].as_u64().is_some());
-    }
-
-    #[tokio::test]
-    async fn silence_completion_records_auto_stop_telemetry() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager
-            .run()
-            .await
-            .expect("session manager bootstrap should succeed");
+    pub fn plugins(&self) -> &PluginHost {
+        &self.plugins
+    }
 
-        manager
-            .set_active_session_id("session-telemetry-silence")
-            .await;
+    /// 停止全部插件进程，宿主退出前调用。
+    pub async fn shutdown_plugins(&self) {
+        self.plugins.shutdown().await;
+    }
 
-        let audio = manager.audio_pipeline();
-        audio.begin_preroll(Some(-28.0));
-        audio.begin_recording();
+    pub fn webhook_config(&self) -> WebhookConfig {
+        self.webhooks.config()
+    }
 
-        let quiet_frame = vec![0.0005_f32; 1_600];
-        for _ in 0..60 {
-            audio
-                .push_pcm_frame(quiet_frame.clone())
-                .await
-                .expect("push quiet frame");
-        }
+    pub fn set_webhook_config(&self, config: WebhookConfig) -> Result<()> {
+        self.webhooks.set_config(config)
+    }
 
-        let mut rx = manager.subscribe_events();
-        timeout(Duration::from_secs(3), async {
-            loop {
-                match rx.recv().await {
-                    Ok(SessionEvent::AutoStop(_)) => break,
-                    Ok(_) => continue,
-                    Err(RecvError::Lagged(_)) => continue,
-                    Err(err) => panic!("session event channel closed: {err:?}"),
-                }
+    pub async fn save_transcript_draft(&self, request: DraftSaveRequest) -> Result<DraftRecord> {
+        let session_id = request.session_id.clone();
+        match self.persistence.save_draft(request).await {
+            Ok(record) => {
+                record_session_draft_saved(&session_id, &record.draft_id, &record.tags);
+                Ok(record)
             }
-        })
-        .await
-        .expect("auto-stop timed out");
-
-        let persistence = manager.persistence_handle();
-        let conn = persistence
-            .sqlite()
-            .connection()
-            .expect("persistence connection");
-        let (event_type, payload): (String, String) = conn
-            .query_row(
-                "SELECT event_type, payload FROM telemetry_queue ORDER BY id DESC LIMIT 1",
-                [],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .expect("telemetry row");
-
-        assert_eq!(event_type, EVENT_SILENCE_AUTOSTOP);
-        let payload_json: serde_json::Value =
-            serde_json::from_str(&payload).expect("auto-stop telemetry payload");
-        assert_eq!(payload_json["sessionId"], "session-telemetry-silence");
-        assert_eq!(payload_json["reason"], "silenceTimeout");
-        assert!(payload_json["countdownMs"].as_u64().is_some());
-    }
-
-    #[tokio::test]
-    async fn routes_audio_frames_and_broadcasts_updates() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("local.".to_string())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager.run().await.expect("bootstrap should succeed");
-
-        let mut broadcast_rx = manager.subscribe_updates();
-        let mut config = RealtimeSessionConfig::default();
-        config.enable_polisher = false;
-        let (handle, mut client_rx) = manager.start_realtime_transcription(config);
-
-        // Keep the handle alive for the duration of the test.
-        let _guard = handle;
+            Err(err) => {
+                record_session_draft_failed(&session_id, err.to_string());
+                Err(err)
+            }
+        }
+    }
 
-        let audio = manager.audio_pipeline();
-        audio
-            .push_pcm_frame(vec![0.25_f32; 1_600])
+    pub async fn search_history(&self, query: HistoryQuery) -> Result<HistoryPage> {
+        self.persistence
+            .search_history(query)
             .await
-            .expect("push pcm frame");
+            .map_err(|err| anyhow!("history search failed: {err}"))
+    }
 
-        let update = timeout(Duration::from_millis(600), client_rx.recv())
+    pub async fn load_history_entry(&self, session_id: &str) -> Result<Option<HistoryEntry>> {
+        self.persistence
+            .load_session(session_id.to_string())
             .await
-            .expect("client channel timed out")
-            .expect("client channel closed");
-
-        let transcript = match &update.payload {
-            UpdatePayload::Transcript(payload) => payload,
-            _ => panic!("expected transcript payload"),
-        };
-
-        assert_eq!(update.frame_index, 1);
-        assert_eq!(transcript.source, TranscriptSource::Local);
-        assert!(transcript.is_primary);
-        assert_eq!(transcript.text, "local.");
+            .map_err(|err| anyhow!("history load failed: {err}"))
+    }
 
-        let broadcast_update = timeout(Duration::from_millis(600), broadcast_rx.recv())
+    pub async fn load_history_audio(&self, session_id: &str) -> Result<Option<RecordedAudio>> {
+        self.persistence
+            .load_session_audio(session_id.to_string())
             .await
-            .expect("broadcast channel timed out")
-            .expect("broadcast channel closed");
+            .map_err(|err| anyhow!("history audio load failed: {err}"))
+    }
 
-        assert_eq!(broadcast_update.frame_index, update.frame_index);
-        match broadcast_update.payload {
-            UpdatePayload::Transcript(_) => {}
-            _ => panic!("expected broadcast transcript payload"),
-        }
+    /// 汇总区间内的使用统计，供仪表盘展示。
+    pub async fn usage_stats(&self, range: UsageRange) -> Result<UsageStats> {
+        range.validate()?;
+        let entries = self
+            .persistence
+            .export_sessions(ExportSelection::DateRange {
+                from_ms: range.from_ms,
+                to_ms: range.to_ms,
+            })
+            .await
+            .map_err(|err| anyhow!("usage statistics load failed: {err}"))?;
+        let mut stats = analytics::compute_usage(range, &entries);
+        stats.cloud_budget = Some(self.cloud_budget());
+        Ok(stats)
     }
 
-    #[tokio::test]
-    async fn delivers_warn_notice_to_slow_clients() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![
-            Ok("local-fast.".to_string()),
-            Err(anyhow!("local failure")),
-        ]));
-        let cloud_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("cloud.".to_string())]));
-        let orchestrator = EngineOrchestrator::with_engines(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-            Some(cloud_engine),
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-        manager.run().await.expect("bootstrap should succeed");
-
-        let mut broadcast_rx = manager.subscribe_updates();
-        let mut config = RealtimeSessionConfig::default();
-        config.buffer_capacity = 1;
-        config.enable_polisher = false;
-        let (handle, mut client_rx) = manager.start_realtime_transcription(config);
-        let _guard = handle;
-
-        let audio = manager.audio_pipeline();
-        audio
-            .push_pcm_frame(vec![0.5_f32; 1_600])
+    /// 将选中的历史记录导出为 Markdown/JSON/CSV 文件。
+    pub async fn export_history(&self, request: ExportRequest) -> Result<ExportSummary> {
+        let ExportRequest {
+            selection,
+            format,
+            fields,
+            destination,
+            passphrase,
+        } = request;
+        let entries = self
+            .persistence
+            .export_sessions(selection)
             .await
-            .expect("push first frame");
-        audio
-            .push_pcm_frame(vec![0.5_f32; 1_600])
+            .map_err(|err| anyhow!("history export load failed: {err}"))?;
+        let service = ExportService::new(format, fields).with_passphrase(passphrase);
+        let summary = tokio::task::spawn_blocking(move || service.write(&entries, &destination))
             .await
-            .expect("push second frame");
+            .map_err(|err| anyhow!("history export task failed: {err}"))??;
+        info!(
+            target: "session_manager",
+            format = format.as_str(),
+            entries = summary.entries,
+            "history exported"
+        );
+        Ok(summary)
+    }
 
-        // Drain the first transcript after both frames are queued so the WARN notice
-        // arrives while the client queue is full.
-        let first_update = timeout(Duration::from_millis(600), client_rx.recv())
+    /// 从导出归档或其他设备的数据库恢复历史记录。
+    pub async fn import_history(&self, source: ImportSource) -> Result<ImportSummary> {
+        let summary = self
+            .persistence
+            .import_history(source)
             .await
-            .expect("first client update timed out")
-            .expect("client channel closed");
-        match &first_update.payload {
-            UpdatePayload::Transcript(payload) => {
-                assert_eq!(payload.text, "local-fast.");
-                assert_eq!(payload.source, TranscriptSource::Local);
-            }
-            _ => panic!("expected first transcript"),
-        }
+            .map_err(|err| anyhow!("history import failed: {err}"))?;
+        info!(
+            target: "session_manager",
+            inserted = summary.inserted,
+            merged = summary.merged,
+            unchanged = summary.unchanged,
+            rejected = summary.rejected,
+            "history imported"
+        );
+        Ok(summary)
+    }
 
-        let warn_update = timeout(Duration::from_millis(800), client_rx.recv())
-            .await
-            .expect("warn update timed out")
-            .expect("warn update missing");
-        match &warn_update.payload {
-            UpdatePayload::Notice(notice) => {
-                assert_eq!(notice.level, NoticeLevel::Error);
-                assert!(notice.message.contains("切换云端"));
+    /// 保存准确性标记；附带修正稿时，与原润色稿比对出的修正计入个人纠错语料。
+    pub async fn update_history_accuracy(&self, update: AccuracyUpdate) -> Result<()> {
+        let pairs = match update.corrected_transcript.as_deref() {
+            Some(corrected) => {
+                let entry = self
+                    .persistence
+                    .load_session(update.session_id.clone())
+                    .await
+                    .map_err(|err| anyhow!("failed to load history entry: {err}"))?
+                    .ok_or_else(|| anyhow!("history entry {} not found", update.session_id))?;
+                let original = if entry.polished_transcript.trim().is_empty() {
+                    &entry.raw_transcript
+                } else {
+                    &entry.polished_transcript
+                };
+                corrections::extract_corrections(original, corrected)
             }
-            _ => panic!("expected warn/error notice"),
+            None => Vec::new(),
+        };
+        self.persistence
+            .update_accuracy(update)
+            .await
+            .map_err(|err| anyhow!("failed to update history accuracy: {err}"))?;
+        if pairs.is_empty() {
+            return Ok(());
         }
-
-        // Broadcast channel should also see the WARN/Error notice.
-        let broadcast_notice = timeout(Duration::from_millis(800), async {
-            loop {
-                match broadcast_rx.recv().await {
-                    Ok(update) => match update.payload {
-                        UpdatePayload::Notice(notice) => break notice,
-                        _ => continue,
-                    },
-                    Err(err) => panic!("broadcast closed: {err}"),
-                }
-            }
-        })
-        .await
-        .expect("broadcast timed out");
-        assert!(matches!(
-            broadcast_notice.level,
-            NoticeLevel::Warn | NoticeLevel::Error
-        ));
+        self.persistence
+            .record_corrections(pairs)
+            .await
+            .map_err(|err| anyhow!("failed to record corrections: {err}"))?;
+        self.refresh_corrector().await
     }
 
-    #[tokio::test]
-    async fn publishes_transcript_and_emits_lifecycle_updates() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("local.".into())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-
-        let mut lifecycle_rx = manager.subscribe_lifecycle();
-        let snapshot = make_snapshot("session-1", "raw", "polished");
-        let request = PublishRequest {
-            transcript: "polished".into(),
-            focus: FocusWindowContext::from_app_identifier("com.example.app"),
-            fallback: FallbackStrategy::ClipboardCopy,
-        };
-
-        let outcome = manager
-            .publish_transcript(snapshot, request)
+    pub async fn set_history_pinned(&self, session_id: String, pinned: bool) -> Result<()> {
+        self.persistence
+            .set_pinned(session_id, pinned)
             .await
-            .expect("publish should succeed");
-
-        assert_eq!(outcome.status, PublisherStatus::Completed);
-        assert_eq!(outcome.strategy, PublishStrategy::DirectInsert);
+            .map_err(|err| anyhow!("failed to update history pin: {err}"))
+    }
 
-        let publishing_update = lifecycle_rx
-            .recv()
+    /// 执行动作对应的插件（若有）并记录结果；插件失败时仍写入历史后再返回错误。
+    pub async fn record_history_action(
+        &self,
+        session_id: String,
+        action: HistoryPostAction,
+    ) -> Result<Vec<HistoryPostAction>> {
+        let entry = self
+            .persistence
+            .load_session(session_id.clone())
             .await
-            .expect("publishing update missing");
-        assert_eq!(publishing_update.phase, SessionLifecyclePhase::Publishing);
-
-        let completion_update = lifecycle_rx
-            .recv()
+            .map_err(|err| anyhow!("failed to load history entry: {err}"))?
+            .ok_or_else(|| anyhow!("history entry {session_id} not found"))?;
+        let outcome = self.history_actions.run(&entry, action).await;
+        let actions = self
+            .persistence
+            .append_post_action(session_id, outcome.action)
             .await
-            .expect("completion update missing");
-        assert_eq!(completion_update.phase, SessionLifecyclePhase::Completed);
-        match completion_update.payload {
-            SessionLifecyclePayload::Completed(payload) => {
-                assert_eq!(payload.outcome.status, PublisherStatus::Completed);
-            }
-            other => panic!("unexpected payload: {other:?}"),
+            .map_err(|err| anyhow!("failed to append history action: {err}"))?;
+        match outcome.error {
+            Some(error) => Err(anyhow!("history action failed: {error}")),
+            None => Ok(actions),
         }
     }
 
-    #[tokio::test]
-    async fn surfaces_publisher_errors_and_emits_failure_update() {
-        let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("local.".into())]));
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            local_engine,
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-
-        let mut lifecycle_rx = manager.subscribe_lifecycle();
-        let snapshot = make_snapshot("session-2", "raw", "");
-        let request = PublishRequest {
-            transcript: "   ".into(),
-            focus: FocusWindowContext::default(),
-            fallback: FallbackStrategy::NotifyOnly,
-        };
+    /// 注册或替换历史动作插件，例如发送到 Notion 或创建 Jira 工单。
+    pub fn register_history_action(&self, plugin: Arc<dyn ActionPlugin>) {
+        self.history_actions.register(plugin);
+    }
 
-        let result = manager.publish_transcript(snapshot, request).await;
-        assert!(result.is_err());
+    pub fn history_actions(&self) -> &ActionRegistry {
+        &self.history_actions
+    }
 
-        let publishing_update = lifecycle_rx
-            .recv()
-            .await
-            .expect("publishing update missing");
-        assert_eq!(publishing_update.phase, SessionLifecyclePhase::Publishing);
-
-        let failure_update = lifecycle_rx.recv().await.expect("failure update missing");
-        assert_eq!(failure_update.phase, SessionLifecyclePhase::Failed);
-        match failure_update.payload {
-            SessionLifecyclePayload::Failed(payload) => {
-                assert_eq!(payload.attempts, 1);
-                assert_eq!(payload.fallback, Some(FallbackStrategy::NotifyOnly));
-            }
-            other => panic!("unexpected payload: {other:?}"),
+    fn emit_notice<S: Into<String>>(&self, level: NoticeLevel, message: S) {
+        let update = TranscriptionUpdate {
+            payload: UpdatePayload::Notice(SessionNotice {
+                level,
+                message: message.into(),
+            }),
+            latency: Duration::from_millis(0),
+            frame_index: 0,
+            is_first: false,
+        };
+        self.flight_recorder.record_update("", &update);
+
+        if let Err(err) = self.update_tx.send(update) {
+            warn!(
+                target: "session_manager",
+                %err,
+                "failed to broadcast clipboard notice"
+            );
         }
     }
 
-    #[tokio::test]
-    async fn clipboard_fallback_copies_and_notifies() {
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
-        );
-
-        let failure = PublisherFailure::new(PublisherFailureCode::Timeout, "operation timed out");
-        let outcome = PublishOutcome {
-            status: PublisherStatus::Failed,
-            strategy: PublishStrategy::DirectInsert,
-            attempts: 2,
-            fallback: None,
-            failure: Some(failure.clone()),
-        };
+    fn schedule_history_cleanup(&self) {
+        if self.history_cleanup_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let persistence = self.persistence.clone();
+        let retention_cap = Arc::clone(&self.history_retention_cap);
+        self.spawn_background(async move {
+            let mut ticker = interval(Duration::from_secs(HISTORY_CLEANUP_INTERVAL_SECS));
+            loop {
+                ticker.tick().await;
+                if let Err(err) = cleanup_history(&persistence, &retention_cap).await {
+                    warn!(
+                        target: "session_manager",
+                        %err,
+                        "scheduled history cleanup failed"
+                    );
+                }
+            }
+        });
+    }
 
-        let publisher = Arc::new(StubPublisher::new(outcome));
-        let clipboard_access = RecordingClipboard::default();
-        let clipboard = ClipboardManager::new(Arc::new(clipboard_access.clone()));
-        let manager = SessionManager::with_components(orchestrator, publisher, clipboard);
-
-        let mut updates_rx = manager.subscribe_updates();
-        let snapshot = make_snapshot("session-fallback", "raw", "polished");
-        let request = PublishRequest {
-            transcript: "polished".into(),
-            focus: FocusWindowContext::default(),
-            fallback: FallbackStrategy::ClipboardCopy,
+    async fn persist_notice_entry(
+        &self,
+        session_id: &str,
+        action: &str,
+        result: &str,
+        level: NoticeLevel,
+        message: String,
+        undo_token: Option<String>,
+    ) {
+        let request = NoticeSaveRequest {
+            notice_id: make_notice_id(session_id),
+            session_id: session_id.to_string(),
+            action: action.to_string(),
+            result: result.to_string(),
+            level: notice_level_value(level).to_string(),
+            message,
+            undo_token,
         };
 
-        let outcome = manager
-            .publish_transcript(snapshot, request)
-            .await
-            .expect("publish should succeed");
-
-        assert_eq!(outcome.status, PublisherStatus::Deferred);
-        assert_eq!(outcome.strategy, PublishStrategy::ClipboardFallback);
-        assert_eq!(outcome.fallback, Some(FallbackStrategy::ClipboardCopy));
-        match outcome.failure {
-            Some(ref failure) => assert_eq!(failure.code, PublisherFailureCode::Timeout),
-            None => panic!("expected failure context"),
+        if let Err(err) = self.persistence.save_notice(request).await {
+            warn!(
+                target: "session_manager",
+                %err,
+                "failed to persist publish notice"
+            );
         }
+    }
+
+    /// 优雅退出，等待进行中会话收尾的期限为 [`SHUTDOWN_DEADLINE`]。
+    pub async fn shutdown(&self) -> Result<()> {
+        self.shutdown_within(SHUTDOWN_DEADLINE).await
+    }
 
-        let clipboard_contents = clipboard_access.contents().await;
-        assert_eq!(clipboard_contents.as_deref(), Some("polished"));
+    /// 停止采集并在 `deadline` 内等待进行中的会话转发完剩余音频与结果，超时的任务被中止；
+    /// 随后写入最后的检查点、取消后台任务、停止插件，待持久化队列落盘后才返回。
+    pub async fn shutdown_within(&self, deadline: StdDuration) -> Result<()> {
+        info!(target: "session_manager", "shutting down session manager");
+        if let Err(err) = self.audio.stop().await {
+            warn!(target: "session_manager", %err, "failed to stop audio pipeline");
+        }
 
-        let notice = updates_rx.recv().await.expect("notice missing");
-        match notice.payload {
-            UpdatePayload::Notice(SessionNotice { level, message }) => {
-                assert_eq!(level, NoticeLevel::Warn);
-                assert!(message.contains("已将润色稿复制到剪贴板"));
+        let tasks = std::mem::take(
+            &mut *self
+                .realtime_tasks
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        let drain_until = tokio::time::Instant::now() + deadline;
+        for mut task in tasks {
+            if timeout_at(drain_until, &mut task).await.is_err() {
+                warn!(
+                    target: "session_manager",
+                    deadline_ms = deadline.as_millis() as u64,
+                    "realtime session did not drain before shutdown deadline; aborting"
+                );
+                task.abort();
             }
-            _ => panic!("expected clipboard fallback notice"),
         }
+        save_checkpoint(&self.persistence, self.crash_guard.in_flight()).await;
+        save_cloud_budget(&self.persistence, &self.orchestrator.budget()).await;
+        self.finish_recording().await;
+
+        self.shutdown.cancel();
+        if let Some(watcher) = self
+            .config_watch
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+        {
+            watcher.abort();
+        }
+        self.plugins.shutdown().await;
+        self.persistence.flush().await
+    }
 
-        let notices = manager
-            .persistence_handle()
-            .list_notices(10)
-            .await
-            .expect("persisted notices available");
-        assert!(notices.iter().any(
-            |entry| entry.action == NOTICE_ACTION_COPY && entry.result == NOTICE_RESULT_SUCCESS
-        ));
+    /// 启动随 `shutdown` 取消的后台任务。
+    fn spawn_background<F>(&self, task: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            shutdown.run_until_cancelled(task).await;
+        })
     }
 
-    #[tokio::test]
-    async fn clipboard_fallback_failure_updates_outcome() {
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
-        );
+    #[cfg(test)]
+    pub fn persistence_handle(&self) -> PersistenceHandle {
+        self.persistence.clone()
+    }
+}
 
-        let failure = PublisherFailure::new(PublisherFailureCode::Timeout, "operation timed out");
-        let outcome = PublishOutcome {
-            status: PublisherStatus::Failed,
-            strategy: PublishStrategy::DirectInsert,
-            attempts: 1,
-            fallback: None,
-            failure: Some(failure),
-        };
+/// 清理过期历史；组织策略设有保留上限时，超过上限的条目即使已置顶也会删除。
+async fn cleanup_history(
+    persistence: &PersistenceHandle,
+    cap: &StdRwLock<Option<StdDuration>>,
+) -> Result<usize> {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+    let cap = *cap.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match cap {
+        Some(cap) => {
+            persistence
+                .cleanup_with_retention_cap(now_ms, cap.as_millis() as i64)
+                .await
+        }
+        None => persistence.cleanup_expired(now_ms).await,
+    }
+}
 
-        let publisher = Arc::new(StubPublisher::new(outcome));
-        let clipboard_access = RecordingClipboard::default();
-        clipboard_access
-            .set_write_error(ClipboardError::write("permission denied"))
-            .await;
-        let clipboard = ClipboardManager::new(Arc::new(clipboard_access.clone()));
-        let manager = SessionManager::with_components(orchestrator, publisher, clipboard);
-
-        let mut updates_rx = manager.subscribe_updates();
-        let snapshot = make_snapshot("session-fallback-fail", "raw", "polished");
-        let request = PublishRequest {
-            transcript: "polished".into(),
-            focus: FocusWindowContext::default(),
-            fallback: FallbackStrategy::ClipboardCopy,
-        };
+/// 仅在用量有变化时写入。
+async fn save_cloud_budget(persistence: &PersistenceHandle, budget: &CloudBudget) {
+    let Some(usage) = budget.take_dirty() else {
+        return;
+    };
+    if let Err(err) = persistence.store_cloud_budget_usage(usage).await {
+        warn!(target: "session_manager", %err, "failed to save cloud budget usage");
+    }
+}
 
-        let outcome = manager
-            .publish_transcript(snapshot, request)
-            .await
-            .expect("publish should surface fallback failure");
-
-        assert_eq!(outcome.status, PublisherStatus::Failed);
-        let failure = outcome.failure.expect("failure details missing");
-        assert!(failure.message.contains("operation timed out"));
-        assert!(failure.message.contains("剪贴板复制失败"));
-
-        let notice = updates_rx.recv().await.expect("failure notice missing");
-        match notice.payload {
-            UpdatePayload::Notice(SessionNotice { level, message }) => {
-                assert_eq!(level, NoticeLevel::Error);
-                assert!(message.contains("自动降级失败"));
-                assert!(message.contains("手动复制"));
-            }
-            _ => panic!("expected clipboard failure notice"),
-        }
+fn report_capture_hotkey(
+    audio: &AudioPipeline,
+    capture: &StdMutex<Option<CaptureController>>,
+    capture_tx: &broadcast::Sender<CaptureEvent>,
+    pressed: bool,
+) -> Option<CaptureEvent> {
+    let event = capture
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .as_mut()?
+        .hotkey(pressed)?;
+    apply_capture_event(audio, capture_tx, event);
+    Some(event)
+}
 
-        let notices = manager
-            .persistence_handle()
-            .list_notices(10)
-            .await
-            .expect("persisted notices available");
-        assert!(notices.iter().any(
-            |entry| entry.action == NOTICE_ACTION_COPY && entry.result == NOTICE_RESULT_FAILURE
-        ));
+fn report_capture_gesture(
+    audio: &AudioPipeline,
+    capture: &StdMutex<Option<CaptureController>>,
+    capture_tx: &broadcast::Sender<CaptureEvent>,
+    gesture: HotkeyGesture,
+) -> Vec<CaptureEvent> {
+    let events = match capture
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .as_mut()
+    {
+        Some(controller) => controller.gesture(gesture),
+        None => return Vec::new(),
+    };
+    for event in &events {
+        apply_capture_event(audio, capture_tx, *event);
     }
+    events
+}
 
-    #[tokio::test]
-    async fn saves_transcript_draft_and_records_history() {
-        let orchestrator = EngineOrchestrator::with_engine(
-            EngineConfig {
-                prefer_cloud: false,
-            },
-            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
-        );
-        let manager = SessionManager::with_orchestrator(orchestrator);
-
-        let request = DraftSaveRequest {
-            draft_id: "draft-001".into(),
-            session_id: "session-save".into(),
-            content: "Draft body".into(),
-            title: None,
-            tags: None,
-        };
+fn apply_capture_event(
+    audio: &AudioPipeline,
+    capture_tx: &broadcast::Sender<CaptureEvent>,
+    event: CaptureEvent,
+) {
+    match event.transition {
+        CaptureTransition::Started => audio.begin_recording(),
+        CaptureTransition::Stopped => audio.reset_session(),
+    }
+    info!(
+        target: "session_manager",
+        mode = event.mode.as_str(),
+        transition = ?event.transition,
+        trigger = ?event.trigger,
+        "capture state changed"
+    );
+    let _ = capture_tx.send(event);
+}
 
-        let record = manager
-            .save_transcript_draft(request)
-            .await
-            .expect("draft save should succeed");
-        assert_eq!(record.title, "Polished transcript");
-        assert_eq!(record.tags, vec!["transcript".to_string()]);
+fn system_time_to_ms(timestamp: SystemTime) -> u128 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0)
+}
 
-        let drafts = manager
-            .persistence_handle()
-            .list_drafts(5)
-            .await
-            .expect("draft history available");
-        assert!(drafts.iter().any(|draft| draft.draft_id == "draft-001"));
+fn make_notice_id(session_id: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    format!("{session_id}-notice-{timestamp}")
+}
+
+fn notice_level_value(level: NoticeLevel) -> &'static str {
+    match level {
+        NoticeLevel::Info => "info",
+        NoticeLevel::Warn => "warn",
+        NoticeLevel::Error => "error",
     }
 }
+
+#[cfg(test)]
+mod tests;
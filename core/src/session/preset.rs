@@ -0,0 +1,320 @@
+//! 听写预设：把引擎、润色风格、语言、目标应用与回退策略保存为一组，
+//! 便于在“会议纪要”“代码注释”等工作流之间一键切换。
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::orchestrator::{
+    PolishProfile, RealtimeSessionConfig, RealtimeSessionHandle, TranscriptionUpdate,
+};
+use crate::session::publisher::{FallbackStrategy, FocusWindowContext, PublishRequest};
+use crate::session::SessionManager;
+
+/// 预设指定的识别引擎。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineChoice {
+    Local,
+    Cloud,
+}
+
+impl EngineChoice {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EngineChoice::Local => "local",
+            EngineChoice::Cloud => "cloud",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "local" => Some(EngineChoice::Local),
+            "cloud" => Some(EngineChoice::Cloud),
+            _ => None,
+        }
+    }
+}
+
+/// 为空的字段沿用应用偏好或全局默认。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPreset {
+    pub name: String,
+    #[serde(default)]
+    pub engine: Option<EngineChoice>,
+    #[serde(default)]
+    pub polish_profile: Option<PolishProfile>,
+    /// BCP 47 语言标签，用于原始稿的标点恢复。
+    #[serde(default)]
+    pub language: Option<String>,
+    /// 会话的目标应用，决定应用偏好、词表子集与输出格式。
+    #[serde(default)]
+    pub target_app: Option<String>,
+    #[serde(default)]
+    pub fallback: Option<FallbackStrategy>,
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+impl SessionPreset {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into().trim().to_string(),
+            engine: None,
+            polish_profile: None,
+            language: None,
+            target_app: None,
+            fallback: None,
+            updated_at_ms: 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("session preset name must not be empty"));
+        }
+        if self
+            .language
+            .as_deref()
+            .is_some_and(|language| language.trim().is_empty())
+        {
+            return Err(anyhow!("session preset language must not be blank"));
+        }
+        Ok(())
+    }
+
+    /// 把预设写入实时会话配置；已显式设置的润色风格不会被覆盖。
+    pub fn apply_to_config(&self, config: &mut RealtimeSessionConfig) {
+        if let Some(engine) = self.engine {
+            config.prefer_cloud = Some(engine == EngineChoice::Cloud);
+        }
+        if config.polish_profile.is_none() {
+            config.polish_profile = self.polish_profile;
+        }
+        if let Some(language) = &self.language {
+            config.punctuation_language = Some(language.clone());
+        }
+    }
+
+    /// 预设的目标应用对应的焦点；未指定时为空焦点。
+    pub fn focus(&self) -> FocusWindowContext {
+        self.target_app
+            .as_deref()
+            .map(FocusWindowContext::from_app_identifier)
+            .unwrap_or_default()
+    }
+
+    /// 预设的回退策略优先于应用偏好。
+    pub fn apply_to_request(&self, request: &mut PublishRequest) {
+        if let Some(fallback) = &self.fallback {
+            request.fallback = fallback.clone();
+        }
+    }
+}
+
+impl SessionManager {
+    pub async fn session_presets(&self) -> Result<Vec<SessionPreset>> {
+        self.persistence.list_session_presets().await
+    }
+
+    /// 保存预设，同名（忽略大小写）预设会被覆盖；若覆盖的是当前预设则同步更新。
+    pub async fn save_session_preset(&self, preset: SessionPreset) -> Result<()> {
+        self.persistence
+            .upsert_session_preset(preset.clone())
+            .await
+            .map_err(|err| anyhow!("failed to save session preset: {err}"))?;
+        let mut active = self
+            .active_preset
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if active
+            .as_ref()
+            .is_some_and(|current| current.name.eq_ignore_ascii_case(preset.name.trim()))
+        {
+            *active = Some(SessionPreset {
+                name: preset.name.trim().to_string(),
+                ..preset
+            });
+        }
+        Ok(())
+    }
+
+    pub async fn remove_session_preset(&self, name: &str) -> Result<bool> {
+        let removed = self
+            .persistence
+            .remove_session_preset(name.to_string())
+            .await
+            .map_err(|err| anyhow!("failed to remove session preset: {err}"))?;
+        let mut active = self
+            .active_preset
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if active
+            .as_ref()
+            .is_some_and(|current| current.name.eq_ignore_ascii_case(name.trim()))
+        {
+            *active = None;
+        }
+        Ok(removed)
+    }
+
+    /// 切换到指定预设；之后开始的会话使用其引擎、润色风格与语言，发布使用其回退策略。
+    pub async fn apply_preset(&self, name: &str) -> Result<SessionPreset> {
+        let preset = self
+            .session_presets()
+            .await?
+            .into_iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| anyhow!("session preset {name} not found"))?;
+        *self
+            .active_preset
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(preset.clone());
+        Ok(preset)
+    }
+
+    pub fn active_preset(&self) -> Option<SessionPreset> {
+        self.active_preset
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn clear_active_preset(&self) {
+        *self
+            .active_preset
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+
+    /// 切换到指定预设并以其目标应用开始转写。
+    pub async fn start_with_preset(
+        &self,
+        name: &str,
+    ) -> Result<(RealtimeSessionHandle, mpsc::Receiver<TranscriptionUpdate>)> {
+        let preset = self.apply_preset(name).await?;
+        Ok(self
+            .start_realtime_transcription_for(RealtimeSessionConfig::default(), &preset.focus())
+            .await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator};
+    use crate::session::clipboard::ClipboardManager;
+    use crate::session::publisher::{
+        InsertionMethod, PublishOutcome, PublishStrategy, PublisherFailure, PublisherFailureCode,
+        PublisherStatus,
+    };
+    use crate::session::tests::{
+        make_snapshot, ProgrammedSpeechEngine, RecordingClipboard, StubPublisher,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn applies_preset_to_session_config() {
+        let mut preset = SessionPreset::new("  code comments ");
+        preset.engine = Some(EngineChoice::Local);
+        preset.polish_profile = Some(PolishProfile::CodeComment);
+        preset.language = Some("en-US".into());
+        preset.target_app = Some("com.microsoft.VSCode".into());
+        assert_eq!(preset.name, "code comments");
+        preset.validate().unwrap();
+
+        let mut config = RealtimeSessionConfig::default();
+        preset.apply_to_config(&mut config);
+        assert_eq!(config.prefer_cloud, Some(false));
+        assert_eq!(config.polish_profile, Some(PolishProfile::CodeComment));
+        assert_eq!(config.punctuation_language.as_deref(), Some("en-US"));
+        assert_eq!(
+            preset.focus().app_identifier.as_deref(),
+            Some("com.microsoft.VSCode")
+        );
+
+        let mut explicit = RealtimeSessionConfig {
+            polish_profile: Some(PolishProfile::Casual),
+            ..RealtimeSessionConfig::default()
+        };
+        preset.apply_to_config(&mut explicit);
+        assert_eq!(explicit.polish_profile, Some(PolishProfile::Casual));
+        assert!(SessionPreset::new(" ").validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn session_presets_apply_to_sessions_and_publishing() {
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+        );
+        let clipboard_access = RecordingClipboard::default();
+        let manager = SessionManager::with_components(
+            orchestrator,
+            Arc::new(StubPublisher::new(PublishOutcome {
+                status: PublisherStatus::Failed,
+                strategy: PublishStrategy::DirectInsert,
+                attempts: 1,
+                fallback: None,
+                failure: Some(PublisherFailure::new(
+                    PublisherFailureCode::Timeout,
+                    "operation timed out",
+                )),
+                undo_token: None,
+            })),
+            ClipboardManager::new(Arc::new(clipboard_access.clone())),
+        );
+
+        let mut preset = SessionPreset::new("Test preset: meeting notes");
+        preset.polish_profile = Some(PolishProfile::BulletNotes);
+        preset.target_app = Some("com.example.Presets".into());
+        preset.fallback = Some(FallbackStrategy::NotifyOnly);
+        manager
+            .save_session_preset(preset)
+            .await
+            .expect("preset saved");
+        assert!(manager
+            .session_presets()
+            .await
+            .expect("presets listed")
+            .iter()
+            .any(|preset| preset.name == "Test preset: meeting notes"));
+        assert!(manager.apply_preset("missing preset").await.is_err());
+
+        let (handle, _updates) = manager
+            .start_with_preset("test preset: MEETING NOTES")
+            .await
+            .expect("session started with preset");
+        assert_eq!(
+            handle.config().polish_profile,
+            Some(PolishProfile::BulletNotes)
+        );
+        drop(handle);
+
+        let outcome = manager
+            .publish_transcript(
+                make_snapshot("session-preset", "raw", "polished"),
+                PublishRequest {
+                    transcript: "polished".into(),
+                    focus: FocusWindowContext::from_app_identifier("com.example.Presets"),
+                    fallback: FallbackStrategy::ClipboardCopy,
+                    insertion: InsertionMethod::default(),
+                    strategy: None,
+                    html: None,
+                },
+            )
+            .await
+            .expect("publish should return outcome");
+        assert_eq!(outcome.status, PublisherStatus::Failed);
+        assert!(clipboard_access.contents().await.is_none());
+
+        assert!(manager
+            .remove_session_preset("Test preset: meeting notes")
+            .await
+            .expect("preset removed"));
+        assert!(manager.active_preset().is_none());
+    }
+}
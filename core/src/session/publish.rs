@@ -0,0 +1,524 @@
+//! 发布转写稿：按焦点窗口选择插入方式，失败时降级到剪贴板，并记录发布结果与撤销令牌。
+
+use std::time::Duration as StdDuration;
+
+use serde_json::json;
+use tokio::time::{timeout, Duration};
+use tracing::{info, warn};
+
+use crate::audio::AudioSource;
+use crate::error::{FlowwisperError, FlowwisperResult};
+use crate::orchestrator::NoticeLevel;
+use crate::session::history::SessionSnapshot;
+use crate::session::lifecycle::{SessionLifecyclePhase, SessionLifecycleUpdate};
+use crate::session::publisher::{
+    FallbackStrategy, FieldRole, FocusWindowContext, InsertionMethod, OutputFormat,
+    PasswordFieldPolicy, PublishOutcome, PublishRequest, PublishStrategy, PublisherFailure,
+    PublisherFailureCode, PublisherStatus,
+};
+use crate::session::scripting::HookPoint;
+use crate::session::undo::{make_undo_token, UndoAction};
+use crate::session::{
+    SessionManager, AUDIO_SOURCE_METADATA_KEY, NOTICE_ACTION_COPY, NOTICE_RESULT_FAILURE,
+    NOTICE_RESULT_SUCCESS,
+};
+use crate::telemetry::events::{
+    record_session_publish_attempt, record_session_publish_degradation,
+    record_session_publish_failure, record_session_publish_outcome,
+};
+use crate::telemetry::metrics::metrics;
+
+const CLIPBOARD_FALLBACK_TIMEOUT_MS: u64 = 200;
+
+impl SessionManager {
+    pub async fn publish_transcript(
+        &self,
+        mut snapshot: SessionSnapshot,
+        mut request: PublishRequest,
+    ) -> FlowwisperResult<PublishOutcome> {
+        let session_id = snapshot.session_id.clone();
+        if request.focus.field_role.is_none() {
+            request.focus.field_role = self.publisher.inspect_field_role(&request.focus).await;
+        }
+        if request.focus.field_role == Some(FieldRole::PasswordField) {
+            if let Some(outcome) = self.guard_password_field(&session_id).await {
+                return Ok(outcome);
+            }
+        }
+        let profile = self.app_profile_for(&request.focus);
+        if let Some(profile) = &profile {
+            profile.apply_to_request(&mut request);
+        }
+        if let Some(preset) = self.active_preset() {
+            preset.apply_to_request(&mut request);
+        }
+        let amendment = self.take_amendment(&session_id);
+        let hand_edited = amendment.is_some();
+        if let Some(amendment) = amendment {
+            // 用户逐句确认过的文本不再自动纠错，替换规则照常展开。
+            request.transcript = amendment.text.clone();
+            snapshot.polished_transcript = amendment.text.clone();
+            amendment.annotate(&mut snapshot.metadata);
+        } else {
+            request.transcript = self.apply_corrections(&request.transcript);
+            snapshot.polished_transcript = self.apply_corrections(&snapshot.polished_transcript);
+        }
+        request.transcript = self.apply_replacement_rules(&request.transcript, &request.focus);
+        snapshot.polished_transcript =
+            self.apply_replacement_rules(&snapshot.polished_transcript, &request.focus);
+        if snapshot.speed.is_none() {
+            snapshot.speed = self.take_dictation_speed(&snapshot);
+        }
+        if let Some(source) = self.take_audio_source(&session_id) {
+            annotate_audio_source(&mut snapshot.metadata, source);
+        }
+        self.apply_calendar_event(&mut snapshot).await;
+        request.transcript = self.scripts.run(
+            HookPoint::PrePublish,
+            &request.transcript,
+            &request.focus,
+            None,
+        );
+        let output_format = profile
+            .and_then(|profile| profile.output_format)
+            .or_else(|| OutputFormat::for_focus(&request.focus));
+        if let Some(format) = output_format {
+            request.apply_format(format);
+        }
+
+        let focus_context = request.focus.clone();
+        let fallback_strategy = request.fallback.clone();
+        let transcript = request.transcript.clone();
+
+        let fallback = fallback_option(&fallback_strategy);
+        let retry_request = request.clone();
+        let undo_token = make_undo_token(&session_id);
+        let mut focus_failure = None;
+        if let Some(PublishStrategy::WaitForFocus {
+            app_identifier,
+            timeout: limit,
+        }) = request.strategy.clone()
+        {
+            self.emit_lifecycle(SessionLifecycleUpdate::publishing(
+                &session_id,
+                1,
+                PublishStrategy::WaitForFocus {
+                    app_identifier: app_identifier.clone(),
+                    timeout: limit,
+                },
+                fallback.clone(),
+            ));
+            match self.wait_for_focus(&app_identifier, limit).await {
+                Some(focus) => request.focus = focus,
+                None => {
+                    focus_failure = Some(PublishOutcome::failed(
+                        1,
+                        PublishStrategy::WaitForFocus {
+                            app_identifier: app_identifier.clone(),
+                            timeout: limit,
+                        },
+                        None,
+                        PublisherFailure::new(
+                            PublisherFailureCode::FocusLost,
+                            format!(
+                                "{app_identifier} did not become frontmost within {}ms",
+                                limit.as_millis()
+                            ),
+                        ),
+                    ));
+                }
+            }
+        }
+        let strategy = if request.insertion == InsertionMethod::SimulatedTyping {
+            PublishStrategy::SimulatedTyping
+        } else {
+            PublishStrategy::DirectInsert
+        };
+        self.emit_lifecycle(SessionLifecycleUpdate::publishing(
+            &session_id,
+            1,
+            strategy,
+            fallback.clone(),
+        ));
+
+        record_session_publish_attempt(
+            &session_id,
+            focus_context.app_identifier.as_deref(),
+            focus_context.window_title.as_deref(),
+            fallback_strategy.as_str(),
+        );
+
+        let published = match focus_failure {
+            Some(outcome) => Ok(outcome),
+            None => self.publisher.publish(request).await,
+        };
+        match published {
+            Ok(mut outcome) => {
+                if outcome.status == PublisherStatus::Failed
+                    && matches!(fallback_strategy, FallbackStrategy::ClipboardCopy)
+                {
+                    outcome = self
+                        .attempt_clipboard_fallback(
+                            &session_id,
+                            &transcript,
+                            &fallback_strategy,
+                            &undo_token,
+                            outcome,
+                        )
+                        .await;
+                }
+
+                let undo_action = match (outcome.status, &outcome.strategy) {
+                    (PublisherStatus::Completed, _) => Some(UndoAction::RemoveInserted),
+                    (PublisherStatus::Deferred, PublishStrategy::ClipboardFallback) => {
+                        Some(UndoAction::RestoreClipboard)
+                    }
+                    _ => None,
+                };
+                if let Some(action) = undo_action {
+                    self.register_undo(&undo_token, &session_id, &transcript, action)
+                        .await;
+                    outcome.undo_token = Some(undo_token.clone());
+                }
+
+                let phase = outcome.status.as_phase();
+                match phase {
+                    SessionLifecyclePhase::Completed => {
+                        self.emit_lifecycle(SessionLifecycleUpdate::completed(
+                            &session_id,
+                            outcome.clone(),
+                        ));
+                    }
+                    SessionLifecyclePhase::Failed => {
+                        let (message, code) = outcome
+                            .failure
+                            .as_ref()
+                            .map(|failure| {
+                                (
+                                    failure.message.clone(),
+                                    Some(failure.code.as_str().to_string()),
+                                )
+                            })
+                            .unwrap_or_else(|| ("publisher reported failure".to_string(), None));
+
+                        self.emit_lifecycle(SessionLifecycleUpdate::failed(
+                            &session_id,
+                            outcome.attempts.max(1),
+                            message.clone(),
+                            code.clone(),
+                            outcome.fallback.clone(),
+                        ));
+
+                        let error = match &outcome.failure {
+                            Some(failure) => FlowwisperError::from(failure),
+                            None => FlowwisperError::Publish(message),
+                        };
+                        record_session_publish_failure(
+                            &session_id,
+                            &error,
+                            outcome.attempts.max(1),
+                            outcome.fallback.as_ref().map(FallbackStrategy::as_str),
+                        );
+                    }
+                    SessionLifecyclePhase::Publishing => {
+                        self.emit_lifecycle(SessionLifecycleUpdate::new(
+                            &session_id,
+                            SessionLifecyclePhase::Publishing,
+                        ));
+                    }
+                    other => {
+                        self.emit_lifecycle(SessionLifecycleUpdate::new(&session_id, other));
+                    }
+                }
+
+                record_session_publish_outcome(
+                    &session_id,
+                    outcome.status.as_str(),
+                    outcome.strategy.as_str(),
+                    outcome.attempts,
+                    outcome.fallback.as_ref().map(FallbackStrategy::as_str),
+                    hand_edited,
+                );
+                self.scripts.run(
+                    HookPoint::PostPublish,
+                    &transcript,
+                    &focus_context,
+                    Some(&outcome),
+                );
+
+                if matches!(
+                    outcome.status,
+                    PublisherStatus::Completed | PublisherStatus::Deferred
+                ) {
+                    if let Err(err) = self.persist_transcript(snapshot.clone()).await {
+                        self.handle_persistence_failure(&snapshot, err).await;
+                    }
+                } else {
+                    self.queue_publish_retry(snapshot, &retry_request, &outcome)
+                        .await;
+                }
+
+                Ok(outcome)
+            }
+            Err(err) => {
+                self.emit_lifecycle(SessionLifecycleUpdate::failed(
+                    &session_id,
+                    1,
+                    err.to_string(),
+                    None,
+                    fallback.clone(),
+                ));
+                let error = FlowwisperError::from(err);
+                record_session_publish_failure(
+                    &session_id,
+                    &error,
+                    1,
+                    fallback.as_ref().map(FallbackStrategy::as_str),
+                );
+                Err(error)
+            }
+        }
+    }
+
+    /// 焦点位于密码框时按策略拦截或提示；拦截时不做剪贴板降级，也不进入重试队列。
+    async fn guard_password_field(&self, session_id: &str) -> Option<PublishOutcome> {
+        match self.password_field_policy() {
+            PasswordFieldPolicy::Warn => {
+                self.emit_notice(
+                    NoticeLevel::Warn,
+                    "焦点位于密码框，润色稿仍将插入，请确认目标输入框。",
+                );
+                None
+            }
+            PasswordFieldPolicy::Block => {
+                let message = "焦点位于密码框，已阻止插入润色稿。";
+                self.emit_notice(NoticeLevel::Warn, message);
+                self.emit_lifecycle(SessionLifecycleUpdate::failed(
+                    session_id,
+                    1,
+                    message,
+                    Some(PublisherFailureCode::PasswordField.as_str().to_string()),
+                    None,
+                ));
+                record_session_publish_failure(
+                    session_id,
+                    &FlowwisperError::Publish(message.to_string()),
+                    1,
+                    None,
+                );
+                Some(PublishOutcome::failed(
+                    1,
+                    PublishStrategy::DirectInsert,
+                    None,
+                    PublisherFailure::new(
+                        PublisherFailureCode::PasswordField,
+                        "refusing to insert into a password field",
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// 等待宿主上报目标应用成为前台；当前焦点已匹配时立即返回。
+    async fn wait_for_focus(
+        &self,
+        app_identifier: &str,
+        limit: StdDuration,
+    ) -> Option<FocusWindowContext> {
+        let mut focus_rx = self.focus_tx.subscribe();
+        let frontmost = focus_rx.wait_for(|focus| {
+            focus
+                .app_identifier
+                .as_deref()
+                .is_some_and(|app| app.eq_ignore_ascii_case(app_identifier))
+        });
+        let focus = match timeout(limit, frontmost).await {
+            Ok(Ok(focus)) => Some(focus.clone()),
+            _ => None,
+        };
+        focus
+    }
+
+    async fn attempt_clipboard_fallback(
+        &self,
+        session_id: &str,
+        transcript: &str,
+        fallback_strategy: &FallbackStrategy,
+        undo_token: &str,
+        mut outcome: PublishOutcome,
+    ) -> PublishOutcome {
+        match self
+            .clipboard
+            .write_with_backup(
+                transcript,
+                Duration::from_millis(CLIPBOARD_FALLBACK_TIMEOUT_MS),
+            )
+            .await
+        {
+            Ok(fallback_handle) => {
+                info!(
+                    target: "session_manager",
+                    session_id,
+                    "clipboard fallback executed"
+                );
+                metrics().clipboard_fallbacks.inc();
+
+                {
+                    let mut guard = self.clipboard_fallback.lock().await;
+                    *guard = Some(fallback_handle);
+                }
+
+                if let Some(failure) = &outcome.failure {
+                    record_session_publish_failure(
+                        session_id,
+                        &FlowwisperError::from(failure),
+                        outcome.attempts.max(1),
+                        Some(fallback_strategy.as_str()),
+                    );
+                }
+
+                let message =
+                    "自动降级：已将润色稿复制到剪贴板，请切换至目标窗口粘贴（原内容已备份）。"
+                        .to_string();
+                self.emit_notice(NoticeLevel::Warn, message.clone());
+                self.persist_notice_entry(
+                    session_id,
+                    NOTICE_ACTION_COPY,
+                    NOTICE_RESULT_SUCCESS,
+                    NoticeLevel::Warn,
+                    message,
+                    Some(undo_token.to_string()),
+                )
+                .await;
+                record_session_publish_degradation(
+                    session_id,
+                    fallback_strategy.as_str(),
+                    NOTICE_RESULT_SUCCESS,
+                );
+
+                outcome.status = PublisherStatus::Deferred;
+                outcome.strategy = PublishStrategy::ClipboardFallback;
+                outcome.fallback = Some(fallback_strategy.clone());
+                outcome
+            }
+            Err(err) => {
+                warn!(
+                    target: "session_manager",
+                    %err,
+                    "clipboard fallback failed"
+                );
+
+                let fallback_error = format!("剪贴板复制失败: {err}");
+                match outcome.failure.as_mut() {
+                    Some(failure) => {
+                        failure.message = format!("{}; {fallback_error}", failure.message);
+                    }
+                    None => {
+                        outcome.failure = Some(PublisherFailure::new(
+                            PublisherFailureCode::Unknown,
+                            fallback_error.clone(),
+                        ));
+                    }
+                }
+
+                let message =
+                    format!("自动降级失败：无法复制润色稿到剪贴板，请手动复制。错误: {err}");
+                self.emit_notice(NoticeLevel::Error, message.clone());
+                self.persist_notice_entry(
+                    session_id,
+                    NOTICE_ACTION_COPY,
+                    NOTICE_RESULT_FAILURE,
+                    NoticeLevel::Error,
+                    message,
+                    None,
+                )
+                .await;
+                record_session_publish_degradation(
+                    session_id,
+                    fallback_strategy.as_str(),
+                    NOTICE_RESULT_FAILURE,
+                );
+
+                outcome
+            }
+        }
+    }
+
+    async fn handle_persistence_failure(&self, snapshot: &SessionSnapshot, error: anyhow::Error) {
+        warn!(
+            target: "session_manager",
+            session_id = %snapshot.session_id,
+            %error,
+            "failed to persist session history"
+        );
+
+        let mut notice_message = format!("历史记录保存失败：{}。", error);
+
+        let clipboard_result = self
+            .clipboard
+            .write_with_backup(
+                &snapshot.polished_transcript,
+                Duration::from_millis(CLIPBOARD_FALLBACK_TIMEOUT_MS),
+            )
+            .await;
+
+        match clipboard_result {
+            Ok(fallback_handle) => {
+                {
+                    let mut guard = self.clipboard_fallback.lock().await;
+                    *guard = Some(fallback_handle);
+                }
+                notice_message.push_str("已将润色稿复制到剪贴板作为备份。");
+            }
+            Err(copy_err) => {
+                notice_message.push_str("且无法复制到剪贴板，请手动保存文本。");
+                warn!(
+                    target: "session_manager",
+                    session_id = %snapshot.session_id,
+                    %copy_err,
+                    "clipboard backup for persistence failure failed"
+                );
+            }
+        }
+
+        self.emit_notice(NoticeLevel::Error, notice_message.clone());
+        self.persist_notice_entry(
+            &snapshot.session_id,
+            NOTICE_ACTION_COPY,
+            NOTICE_RESULT_FAILURE,
+            NoticeLevel::Error,
+            notice_message,
+            None,
+        )
+        .await;
+
+        let payload = json!({
+            "session_id": snapshot.session_id,
+            "error": error.to_string(),
+        });
+
+        let _ = self
+            .persistence
+            .enqueue_telemetry(
+                snapshot.session_id.clone(),
+                "history_persist_failure".into(),
+                payload,
+            )
+            .await;
+    }
+}
+
+fn fallback_option(strategy: &FallbackStrategy) -> Option<FallbackStrategy> {
+    match strategy {
+        FallbackStrategy::None => None,
+        other => Some(other.clone()),
+    }
+}
+
+/// 在保留既有字段的前提下写入音频来源；元数据不是对象时改为对象。
+fn annotate_audio_source(metadata: &mut serde_json::Value, source: AudioSource) {
+    if !metadata.is_object() {
+        *metadata = json!({});
+    }
+    metadata[AUDIO_SOURCE_METADATA_KEY] = json!(source.as_str());
+}
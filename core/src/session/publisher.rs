@@ -7,8 +7,17 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+mod automation;
+mod format;
+mod ime;
+mod typing;
+pub use format::{render as render_transcript, FormattedTranscript, OutputFormat};
+pub use ime::{ImeCompositionState, ImeState};
+pub use typing::{TypingConfig, TypingPublisher, TypingQuirk};
+
 /// 描述当前焦点窗口的上下文信息，用于辅助决策插入策略。
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct FocusWindowContext {
@@ -18,6 +27,8 @@ pub struct FocusWindowContext {
     pub window_title: Option<String>,
     /// 补充上下文，例如编辑模式、输入法提示等。
     pub metadata: Option<String>,
+    /// 焦点控件的类型；宿主未提供时由可访问性 API 探测补全。
+    pub field_role: Option<FieldRole>,
 }
 
 impl FocusWindowContext {
@@ -30,23 +41,96 @@ impl FocusWindowContext {
     }
 }
 
+/// 焦点控件的类型，用于拦截密码框插入并让应用偏好区分不同输入框。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldRole {
+    /// 普通单行或多行文本框。
+    TextField,
+    SearchBox,
+    CodeEditor,
+    Terminal,
+    PasswordField,
+}
+
+impl FieldRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldRole::TextField => "text_field",
+            FieldRole::SearchBox => "search_box",
+            FieldRole::CodeEditor => "code_editor",
+            FieldRole::Terminal => "terminal",
+            FieldRole::PasswordField => "password_field",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text_field" => Some(FieldRole::TextField),
+            "search_box" => Some(FieldRole::SearchBox),
+            "code_editor" => Some(FieldRole::CodeEditor),
+            "terminal" => Some(FieldRole::Terminal),
+            "password_field" => Some(FieldRole::PasswordField),
+            _ => None,
+        }
+    }
+
+    /// 可访问性 API 只能识别出普通文本框时，按常见终端与编辑器的应用标识细化。
+    pub fn refine_for_app(role: Option<Self>, app_identifier: Option<&str>) -> Option<Self> {
+        if !matches!(role, None | Some(FieldRole::TextField)) {
+            return role;
+        }
+        let app = app_identifier.unwrap_or_default().to_ascii_lowercase();
+        const TERMINALS: [&str; 7] = [
+            "com.apple.terminal",
+            "com.googlecode.iterm2",
+            "dev.warp.warp-stable",
+            "windowsterminal.exe",
+            "cmd.exe",
+            "powershell.exe",
+            "alacritty",
+        ];
+        const EDITORS: [&str; 6] = [
+            "com.microsoft.vscode",
+            "code.exe",
+            "com.apple.dt.xcode",
+            "com.jetbrains.",
+            "com.sublimetext.",
+            "dev.zed.zed",
+        ];
+        if TERMINALS.iter().any(|known| app.starts_with(known)) {
+            Some(FieldRole::Terminal)
+        } else if EDITORS.iter().any(|known| app.starts_with(known)) {
+            Some(FieldRole::CodeEditor)
+        } else {
+            role
+        }
+    }
+}
+
+/// 焦点位于密码框时的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasswordFieldPolicy {
+    /// 拒绝插入，避免把口述内容写进密码框。
+    #[default]
+    Block,
+    /// 仍然插入，但提示用户。
+    Warn,
+}
+
 /// 插入失败后允许的回退策略。
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FallbackStrategy {
     /// 不允许自动降级，由上层交互决定后续动作。
     None,
     /// 将润色稿写入剪贴板，提示用户粘贴或撤销。
+    #[default]
     ClipboardCopy,
     /// 仅通知用户保留原稿，常用于敏感或不可写的窗口。
     NotifyOnly,
 }
 
-impl Default for FallbackStrategy {
-    fn default() -> Self {
-        Self::ClipboardCopy
-    }
-}
-
 impl FallbackStrategy {
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -55,6 +139,55 @@ impl FallbackStrategy {
             FallbackStrategy::NotifyOnly => "notify_only",
         }
     }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(FallbackStrategy::None),
+            "clipboard_copy" => Some(FallbackStrategy::ClipboardCopy),
+            "notify_only" => Some(FallbackStrategy::NotifyOnly),
+            _ => None,
+        }
+    }
+}
+
+/// 直接插入时使用的自动化通道。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertionMethod {
+    /// 优先剪贴板粘贴，失败后尝试模拟键入。
+    #[default]
+    Auto,
+    /// 仅使用剪贴板粘贴，适用于会丢弃模拟按键的应用。
+    ClipboardPaste,
+    /// 仅模拟键入，适用于禁止粘贴或会污染剪贴板历史的应用。
+    Keystrokes,
+    /// 按配置速率逐段模拟键入，适用于会丢弃一次性大段按键的应用。
+    SimulatedTyping,
+    /// 仅通过可访问性 API 写入光标处，适用于粘贴与按键均被拦截的输入框。
+    Accessibility,
+}
+
+impl InsertionMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InsertionMethod::Auto => "auto",
+            InsertionMethod::ClipboardPaste => "clipboard_paste",
+            InsertionMethod::Keystrokes => "keystrokes",
+            InsertionMethod::SimulatedTyping => "simulated_typing",
+            InsertionMethod::Accessibility => "accessibility",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(InsertionMethod::Auto),
+            "clipboard_paste" => Some(InsertionMethod::ClipboardPaste),
+            "keystrokes" => Some(InsertionMethod::Keystrokes),
+            "simulated_typing" => Some(InsertionMethod::SimulatedTyping),
+            "accessibility" => Some(InsertionMethod::Accessibility),
+            _ => None,
+        }
+    }
 }
 
 /// 执行插入时的配置项。
@@ -66,6 +199,8 @@ pub struct PublisherConfig {
     pub fallback_timeout: Duration,
     /// 允许的最大重试次数（不含首次尝试）。
     pub max_retry: u8,
+    /// 模拟键入通道的节奏与按应用特殊处理。
+    pub typing: TypingConfig,
 }
 
 impl Default for PublisherConfig {
@@ -74,6 +209,7 @@ impl Default for PublisherConfig {
             direct_insert_timeout: Duration::from_millis(400),
             fallback_timeout: Duration::from_millis(200),
             max_retry: 1,
+            typing: TypingConfig::default(),
         }
     }
 }
@@ -87,9 +223,22 @@ pub struct PublishRequest {
     pub focus: FocusWindowContext,
     /// 失败后的回退策略。
     pub fallback: FallbackStrategy,
+    /// 直接插入使用的通道。
+    pub insertion: InsertionMethod,
+    /// 插入前的等待策略；目前仅 `WaitForFocus` 生效，由 `SessionManager` 根据宿主上报的焦点执行。
+    pub strategy: Option<PublishStrategy>,
+    /// 富文本目标的 HTML 版本，粘贴时与 `transcript` 一同写入剪贴板。
+    pub html: Option<String>,
 }
 
 impl PublishRequest {
+    /// 按目标格式改写待插入文本。
+    pub fn apply_format(&mut self, format: OutputFormat) {
+        let formatted = render_transcript(&self.transcript, format);
+        self.transcript = formatted.text;
+        self.html = formatted.html;
+    }
+
     /// 确保文本内容经过裁剪，避免因空白导致误判。
     pub fn validate(&self) -> Result<(), PublisherError> {
         if self.transcript.trim().is_empty() {
@@ -112,7 +261,7 @@ pub enum PublisherStatus {
 }
 
 /// 实际采用的执行策略。
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PublishStrategy {
     /// 直接向焦点窗口插入文本。
     DirectInsert,
@@ -120,6 +269,13 @@ pub enum PublishStrategy {
     ClipboardFallback,
     /// 仅发出通知或记录草稿，不做插入。
     NotifyOnly,
+    /// 按节奏逐段模拟键入。
+    SimulatedTyping,
+    /// 等待目标应用成为前台后再插入，超时视为焦点丢失。
+    WaitForFocus {
+        app_identifier: String,
+        timeout: Duration,
+    },
 }
 
 /// 插入失败时的标准化错误码。
@@ -130,6 +286,9 @@ pub enum PublisherFailureCode {
     FocusLost,
     ChannelUnavailable,
     AutomationRejected,
+    ImeCompositionActive,
+    /// 焦点位于密码框，按策略拒绝插入。
+    PasswordField,
     Unknown,
 }
 
@@ -141,6 +300,8 @@ impl PublisherFailureCode {
             PublisherFailureCode::FocusLost => "focus_lost",
             PublisherFailureCode::ChannelUnavailable => "channel_unavailable",
             PublisherFailureCode::AutomationRejected => "automation_rejected",
+            PublisherFailureCode::ImeCompositionActive => "ime_composition_active",
+            PublisherFailureCode::PasswordField => "password_field",
             PublisherFailureCode::Unknown => "unknown",
         }
     }
@@ -202,6 +363,8 @@ pub struct PublishOutcome {
     pub fallback: Option<FallbackStrategy>,
     /// 若插入失败，附带失败详情供 UI 展示。
     pub failure: Option<PublisherFailure>,
+    /// 插入或降级成功后由会话分配，可在撤销窗口内传给 `SessionManager::undo_publish`。
+    pub undo_token: Option<String>,
 }
 
 impl PublishOutcome {
@@ -216,6 +379,7 @@ impl PublishOutcome {
             attempts,
             fallback: None,
             failure: None,
+            undo_token: None,
         }
     }
 
@@ -226,6 +390,7 @@ impl PublishOutcome {
             attempts: 0,
             fallback,
             failure: None,
+            undo_token: None,
         }
     }
 
@@ -241,6 +406,7 @@ impl PublishOutcome {
             attempts,
             fallback,
             failure: Some(failure),
+            undo_token: None,
         }
     }
 }
@@ -251,6 +417,8 @@ impl PublishStrategy {
             PublishStrategy::DirectInsert => "direct_insert",
             PublishStrategy::ClipboardFallback => "clipboard_fallback",
             PublishStrategy::NotifyOnly => "notify_only",
+            PublishStrategy::SimulatedTyping => "simulated_typing",
+            PublishStrategy::WaitForFocus { .. } => "wait_for_focus",
         }
     }
 }
@@ -271,6 +439,8 @@ pub struct FocusCapabilities {
     pub is_writable: bool,
     pub supports_clipboard_paste: bool,
     pub supports_keystroke_injection: bool,
+    /// 焦点控件可通过 AXUIElement / UI Automation 在光标处写入文本。
+    pub supports_accessibility_insert: bool,
     pub reason: Option<String>,
 }
 
@@ -280,6 +450,7 @@ impl FocusCapabilities {
             is_writable: true,
             supports_clipboard_paste: true,
             supports_keystroke_injection: false,
+            supports_accessibility_insert: false,
             reason: None,
         }
     }
@@ -289,6 +460,7 @@ impl FocusCapabilities {
             is_writable: true,
             supports_clipboard_paste: false,
             supports_keystroke_injection: true,
+            supports_accessibility_insert: false,
             reason: None,
         }
     }
@@ -298,6 +470,17 @@ impl FocusCapabilities {
             is_writable: true,
             supports_clipboard_paste: true,
             supports_keystroke_injection: true,
+            supports_accessibility_insert: false,
+            reason: None,
+        }
+    }
+
+    pub fn writable_with_accessibility() -> Self {
+        Self {
+            is_writable: true,
+            supports_clipboard_paste: false,
+            supports_keystroke_injection: false,
+            supports_accessibility_insert: true,
             reason: None,
         }
     }
@@ -307,6 +490,7 @@ impl FocusCapabilities {
             is_writable: false,
             supports_clipboard_paste: false,
             supports_keystroke_injection: false,
+            supports_accessibility_insert: false,
             reason: Some(reason.into()),
         }
     }
@@ -363,6 +547,73 @@ pub trait FocusAutomation: Send + Sync {
         contents: &str,
         timeout: Duration,
     ) -> Result<(), AutomationError>;
+
+    /// 以 HTML 与纯文本两种格式写入剪贴板后粘贴；默认只粘贴纯文本。
+    async fn paste_html_via_clipboard(
+        &self,
+        _html: &str,
+        plain: &str,
+        timeout: Duration,
+    ) -> Result<(), AutomationError> {
+        self.paste_via_clipboard(plain, timeout).await
+    }
+
+    /// 通过可访问性 API 在光标处写入文本，默认不支持。
+    async fn insert_via_accessibility(
+        &self,
+        _contents: &str,
+        _timeout: Duration,
+    ) -> Result<(), AutomationError> {
+        Err(AutomationError::channel_unavailable(
+            "accessibility insertion unsupported",
+        ))
+    }
+
+    /// 删除光标前刚插入的文本，用于撤销；默认不支持。
+    async fn remove_inserted(
+        &self,
+        _contents: &str,
+        _timeout: Duration,
+    ) -> Result<(), AutomationError> {
+        Err(AutomationError::channel_unavailable(
+            "removing inserted text unsupported",
+        ))
+    }
+
+    /// 检测焦点应用的输入法组合态，默认视为未启用输入法。
+    async fn inspect_ime(
+        &self,
+        _context: &FocusWindowContext,
+        _timeout: Duration,
+    ) -> Result<ImeState, AutomationError> {
+        Ok(ImeState::inactive())
+    }
+
+    /// 提交焦点应用中未完成的组合串。
+    async fn commit_composition(&self, _timeout: Duration) -> Result<(), AutomationError> {
+        Err(AutomationError::channel_unavailable(
+            "ime composition commit unsupported",
+        ))
+    }
+
+    /// 探测焦点控件类型，默认无法识别。
+    async fn inspect_field_role(
+        &self,
+        _context: &FocusWindowContext,
+        _timeout: Duration,
+    ) -> Result<Option<FieldRole>, AutomationError> {
+        Ok(None)
+    }
+}
+
+/// 输入法检测后允许的插入方式。
+enum ImeReadiness {
+    /// 无组合串干扰，可按原流程插入。
+    Ready,
+    /// 组合态不可确认，仅允许剪贴板粘贴。
+    PasteOnly,
+    /// 组合串无法提交，交由回退策略处理。
+    Blocked(PublisherFailure),
 }
 
 /// 发布器负责协调插入与降级的执行。
@@ -408,6 +659,11 @@ impl Publisher {
     /// 执行插入流程。
     pub async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
         request.validate()?;
+        if request.insertion == InsertionMethod::SimulatedTyping {
+            return TypingPublisher::new(self.config.clone(), self.automation.clone())
+                .publish(request)
+                .await;
+        }
 
         let max_attempts = self.config.max_retry.saturating_add(1);
         let mut attempts: u8 = 0;
@@ -446,8 +702,23 @@ impl Publisher {
                 ));
             }
 
-            if !capabilities.supports_clipboard_paste && !capabilities.supports_keystroke_injection
-            {
+            let allow_paste = capabilities.supports_clipboard_paste
+                && matches!(
+                    request.insertion,
+                    InsertionMethod::Auto | InsertionMethod::ClipboardPaste
+                );
+            let mut allow_keystrokes = capabilities.supports_keystroke_injection
+                && matches!(
+                    request.insertion,
+                    InsertionMethod::Auto | InsertionMethod::Keystrokes
+                );
+            let allow_accessibility = capabilities.supports_accessibility_insert
+                && matches!(
+                    request.insertion,
+                    InsertionMethod::Auto | InsertionMethod::Accessibility
+                );
+
+            if !allow_paste && !allow_keystrokes && !allow_accessibility {
                 let reason = capabilities
                     .reason
                     .unwrap_or_else(|| "no automation channel available".to_string());
@@ -461,12 +732,67 @@ impl Publisher {
                 ));
             }
 
+            match self.prepare_ime(&request.focus).await {
+                ImeReadiness::Ready => {}
+                ImeReadiness::PasteOnly => {
+                    if !allow_paste && !allow_accessibility {
+                        let failure = PublisherFailure::new(
+                            PublisherFailureCode::ImeCompositionActive,
+                            "input method composition may interleave with keystrokes",
+                        );
+                        return Ok(PublishOutcome::failed(
+                            attempts,
+                            PublishStrategy::DirectInsert,
+                            None,
+                            failure,
+                        ));
+                    }
+                    allow_keystrokes = false;
+                }
+                // 任何插入通道都会与组合串交错，不再重试；由会话按请求的回退策略复制到剪贴板。
+                ImeReadiness::Blocked(failure) => {
+                    return Ok(PublishOutcome::failed(
+                        attempts,
+                        PublishStrategy::DirectInsert,
+                        None,
+                        failure,
+                    ));
+                }
+            }
+
             let mut channel_failure: Option<PublisherFailure> = None;
 
-            if capabilities.supports_clipboard_paste {
+            if allow_paste {
+                let timeout = self.config.direct_insert_timeout;
+                let pasted = match &request.html {
+                    Some(html) => {
+                        self.automation
+                            .paste_html_via_clipboard(html, &request.transcript, timeout)
+                            .await
+                    }
+                    None => {
+                        self.automation
+                            .paste_via_clipboard(&request.transcript, timeout)
+                            .await
+                    }
+                };
+                match pasted {
+                    Ok(()) => {
+                        return Ok(PublishOutcome::completed_with_attempts(
+                            PublishStrategy::DirectInsert,
+                            attempts,
+                        ));
+                    }
+                    Err(error) => {
+                        channel_failure = Some(PublisherFailure::from_automation_error(error));
+                    }
+                }
+            }
+
+            if allow_keystrokes {
                 match self
                     .automation
-                    .paste_via_clipboard(&request.transcript, self.config.direct_insert_timeout)
+                    .simulate_keystrokes(&request.transcript, self.config.direct_insert_timeout)
                     .await
                 {
                     Ok(()) => {
@@ -481,10 +807,13 @@ impl Publisher {
                 }
             }
 
-            if capabilities.supports_keystroke_injection {
+            if allow_accessibility {
                 match self
                     .automation
-                    .simulate_keystrokes(&request.transcript, self.config.direct_insert_timeout)
+                    .insert_via_accessibility(
+                        &request.transcript,
+                        self.config.direct_insert_timeout,
+                    )
                     .await
                 {
                     Ok(()) => {
@@ -518,17 +847,55 @@ impl Publisher {
             failure,
         ))
     }
+
+    /// 在插入前处理输入法组合串：能提交则提交，否则限制为剪贴板通道或直接放弃。
+    async fn prepare_ime(&self, focus: &FocusWindowContext) -> ImeReadiness {
+        let timeout = self.config.direct_insert_timeout;
+        let state = self
+            .automation
+            .inspect_ime(focus, timeout)
+            .await
+            .unwrap_or_else(|_| ImeState::unknown());
+
+        if !state.may_interleave() {
+            return ImeReadiness::Ready;
+        }
+
+        match self.automation.commit_composition(timeout).await {
+            Ok(()) => ImeReadiness::Ready,
+            Err(error) if state.composition == ImeCompositionState::Composing => {
+                ImeReadiness::Blocked(PublisherFailure::with_error(
+                    PublisherFailureCode::ImeCompositionActive,
+                    "input method composition is pending and could not be committed",
+                    error,
+                ))
+            }
+            Err(_) => ImeReadiness::PasteOnly,
+        }
+    }
 }
 
 impl Default for Publisher {
     fn default() -> Self {
-        Self::with_automation(Arc::new(SystemFocusAutomation::default()))
+        Self::with_automation(Arc::new(SystemFocusAutomation))
     }
 }
 
 #[async_trait]
 pub trait SessionPublisher: Send + Sync {
     async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError>;
+
+    /// 撤销刚插入的 `transcript`，默认不支持。
+    async fn undo(&self, _transcript: &str) -> Result<(), PublisherError> {
+        Err(PublisherError::UndoFailed(
+            AutomationError::channel_unavailable("publisher does not support undo"),
+        ))
+    }
+
+    /// 探测焦点控件类型，供会话补全 `FocusWindowContext::field_role`；默认无法识别。
+    async fn inspect_field_role(&self, _focus: &FocusWindowContext) -> Option<FieldRole> {
+        None
+    }
 }
 
 #[async_trait]
@@ -536,6 +903,22 @@ impl SessionPublisher for Publisher {
     async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
         Publisher::publish(self, request).await
     }
+
+    async fn undo(&self, transcript: &str) -> Result<(), PublisherError> {
+        self.automation
+            .remove_inserted(transcript, self.config.direct_insert_timeout)
+            .await
+            .map_err(PublisherError::UndoFailed)
+    }
+
+    async fn inspect_field_role(&self, focus: &FocusWindowContext) -> Option<FieldRole> {
+        let role = self
+            .automation
+            .inspect_field_role(focus, self.config.direct_insert_timeout)
+            .await
+            .unwrap_or_default();
+        FieldRole::refine_for_app(role, focus.app_identifier.as_deref())
+    }
 }
 
 #[derive(Default)]
@@ -546,10 +929,16 @@ impl FocusAutomation for SystemFocusAutomation {
     async fn inspect_focus(
         &self,
         _context: &FocusWindowContext,
-        _timeout: Duration,
+        timeout: Duration,
     ) -> Result<FocusCapabilities, AutomationError> {
-        // TODO(task 2.1+): 实现真实的跨平台可写性检测，当前默认允许插入。
-        Ok(FocusCapabilities::writable_with_all_channels())
+        // TODO(task 2.1+): 粘贴与键入通道的可写性检测尚未实现，当前默认允许；
+        // 可访问性通道按焦点控件实际探测。
+        let mut capabilities = FocusCapabilities::writable_with_all_channels();
+        capabilities.supports_accessibility_insert =
+            run_blocking(timeout, automation::probe_caret_insert)
+                .await
+                .unwrap_or(false);
+        Ok(capabilities)
     }
 
     async fn paste_via_clipboard(
@@ -569,6 +958,57 @@ impl FocusAutomation for SystemFocusAutomation {
         // TODO(task 2.1+): 调用系统键入模拟。
         Ok(())
     }
+
+    async fn insert_via_accessibility(
+        &self,
+        contents: &str,
+        timeout: Duration,
+    ) -> Result<(), AutomationError> {
+        let contents = contents.to_string();
+        run_blocking(timeout, move || automation::insert_at_caret(&contents)).await
+    }
+
+    async fn remove_inserted(
+        &self,
+        contents: &str,
+        timeout: Duration,
+    ) -> Result<(), AutomationError> {
+        let contents = contents.to_string();
+        run_blocking(timeout, move || automation::delete_before_caret(&contents)).await
+    }
+
+    async fn inspect_ime(
+        &self,
+        _context: &FocusWindowContext,
+        _timeout: Duration,
+    ) -> Result<ImeState, AutomationError> {
+        Ok(ime::probe_system_ime())
+    }
+
+    async fn commit_composition(&self, _timeout: Duration) -> Result<(), AutomationError> {
+        ime::commit_system_composition().map_err(AutomationError::channel_unavailable)
+    }
+
+    async fn inspect_field_role(
+        &self,
+        _context: &FocusWindowContext,
+        timeout: Duration,
+    ) -> Result<Option<FieldRole>, AutomationError> {
+        run_blocking(timeout, automation::focused_field_role).await
+    }
+}
+
+/// 在阻塞线程池执行平台可访问性调用，超时后放弃等待。
+async fn run_blocking<T, F>(timeout: Duration, call: F) -> Result<T, AutomationError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AutomationError> + Send + 'static,
+{
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(call)).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(err)) => Err(AutomationError::other(err.to_string())),
+        Err(_) => Err(AutomationError::Timeout),
+    }
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -583,11 +1023,17 @@ pub enum PublisherError {
     FocusInspectionFailed(AutomationError),
     #[error("insertion failed: {0}")]
     InsertionFailed(AutomationError),
+    #[error("undo failed: {0}")]
+    UndoFailed(AutomationError),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator};
+    use crate::session::clipboard::ClipboardManager;
+    use crate::session::tests::{make_snapshot, ProgrammedSpeechEngine, RecordingClipboard};
+    use crate::session::SessionManager;
     use std::sync::Arc;
     use tokio::sync::Mutex;
 
@@ -598,6 +1044,7 @@ mod tests {
         keystroke_calls: Arc<Mutex<Vec<String>>>,
         paste_result: Arc<Mutex<Result<(), AutomationError>>>,
         keystroke_result: Arc<Mutex<Result<(), AutomationError>>>,
+        accessibility_calls: Arc<Mutex<Vec<String>>>,
     }
 
     impl MockAutomation {
@@ -608,6 +1055,7 @@ mod tests {
                 keystroke_calls: Arc::new(Mutex::new(Vec::new())),
                 paste_result: Arc::new(Mutex::new(Ok(()))),
                 keystroke_result: Arc::new(Mutex::new(Ok(()))),
+                accessibility_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
@@ -618,6 +1066,7 @@ mod tests {
                 keystroke_calls: Arc::new(Mutex::new(Vec::new())),
                 paste_result: Arc::new(Mutex::new(Ok(()))),
                 keystroke_result: Arc::new(Mutex::new(Ok(()))),
+                accessibility_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
@@ -667,6 +1116,18 @@ mod tests {
             self.keystroke_calls.lock().await.push(contents.to_string());
             self.keystroke_result.lock().await.clone()
         }
+
+        async fn insert_via_accessibility(
+            &self,
+            contents: &str,
+            _timeout: Duration,
+        ) -> Result<(), AutomationError> {
+            self.accessibility_calls
+                .lock()
+                .await
+                .push(contents.to_string());
+            Ok(())
+        }
     }
 
     #[derive(Clone)]
@@ -723,6 +1184,90 @@ mod tests {
         }
     }
 
+    /// 模拟 CJK 输入法：组合串未提交时，键入的字符会被输入法吞入组合缓冲区。
+    #[derive(Clone)]
+    struct ImeAutomation {
+        capabilities: FocusCapabilities,
+        ime: ImeState,
+        commit_supported: bool,
+        composition: Arc<Mutex<String>>,
+        document: Arc<Mutex<String>>,
+        paste_calls: Arc<Mutex<Vec<String>>>,
+        keystroke_calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ImeAutomation {
+        fn new(capabilities: FocusCapabilities, ime: ImeState, commit_supported: bool) -> Self {
+            Self {
+                capabilities,
+                ime,
+                commit_supported,
+                composition: Arc::new(Mutex::new("ni'hao".to_string())),
+                document: Arc::new(Mutex::new(String::new())),
+                paste_calls: Arc::new(Mutex::new(Vec::new())),
+                keystroke_calls: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        async fn document(&self) -> String {
+            self.document.lock().await.clone()
+        }
+    }
+
+    #[async_trait]
+    impl FocusAutomation for ImeAutomation {
+        async fn inspect_focus(
+            &self,
+            _context: &FocusWindowContext,
+            _timeout: Duration,
+        ) -> Result<FocusCapabilities, AutomationError> {
+            Ok(self.capabilities.clone())
+        }
+
+        async fn paste_via_clipboard(
+            &self,
+            contents: &str,
+            _timeout: Duration,
+        ) -> Result<(), AutomationError> {
+            self.paste_calls.lock().await.push(contents.to_string());
+            self.document.lock().await.push_str(contents);
+            Ok(())
+        }
+
+        async fn simulate_keystrokes(
+            &self,
+            contents: &str,
+            _timeout: Duration,
+        ) -> Result<(), AutomationError> {
+            self.keystroke_calls.lock().await.push(contents.to_string());
+            let mut composition = self.composition.lock().await;
+            if composition.is_empty() {
+                self.document.lock().await.push_str(contents);
+            } else {
+                composition.push_str(contents);
+            }
+            Ok(())
+        }
+
+        async fn inspect_ime(
+            &self,
+            _context: &FocusWindowContext,
+            _timeout: Duration,
+        ) -> Result<ImeState, AutomationError> {
+            Ok(self.ime.clone())
+        }
+
+        async fn commit_composition(&self, _timeout: Duration) -> Result<(), AutomationError> {
+            if !self.commit_supported {
+                return Err(AutomationError::channel_unavailable("commit unsupported"));
+            }
+            let mut composition = self.composition.lock().await;
+            self.document.lock().await.push_str("你好");
+            composition.clear();
+            Ok(())
+        }
+    }
+
     #[tokio::test]
     async fn rejects_empty_transcript() {
         let automation =
@@ -732,6 +1277,9 @@ mod tests {
             transcript: "   ".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let result = publisher.publish(request).await;
@@ -750,6 +1298,9 @@ mod tests {
             transcript: "润色稿内容".to_string(),
             focus: context.clone(),
             fallback: fallback.clone(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         request.focus.window_title = Some("Editor".into());
@@ -781,6 +1332,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request.clone()).await.unwrap();
@@ -793,6 +1347,45 @@ mod tests {
         assert!(outcome.failure.is_none());
     }
 
+    #[tokio::test]
+    async fn honours_requested_insertion_method() {
+        let automation =
+            MockAutomation::with_capabilities(FocusCapabilities::writable_with_all_channels());
+        let publisher = Publisher::with_automation(Arc::new(automation.clone()));
+        let request = PublishRequest {
+            transcript: "Hello".to_string(),
+            focus: FocusWindowContext::default(),
+            fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::Keystrokes,
+            strategy: None,
+            html: None,
+        };
+
+        let outcome = publisher.publish(request.clone()).await.unwrap();
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert!(automation.paste_calls().await.is_empty());
+        assert_eq!(
+            automation.keystroke_calls().await,
+            vec!["Hello".to_string()]
+        );
+
+        let paste_only =
+            MockAutomation::with_capabilities(FocusCapabilities::writable_with_keystroke());
+        let outcome = Publisher::with_automation(Arc::new(paste_only.clone()))
+            .publish(PublishRequest {
+                insertion: InsertionMethod::ClipboardPaste,
+                ..request
+            })
+            .await
+            .unwrap();
+        assert_eq!(outcome.status, PublisherStatus::Failed);
+        assert_eq!(
+            outcome.failure.map(|failure| failure.code),
+            Some(PublisherFailureCode::ChannelUnavailable)
+        );
+        assert!(paste_only.keystroke_calls().await.is_empty());
+    }
+
     #[tokio::test]
     async fn errors_when_focus_is_read_only() {
         let automation =
@@ -802,6 +1395,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -821,6 +1417,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -840,6 +1439,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -861,6 +1463,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -881,6 +1486,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -894,13 +1502,18 @@ mod tests {
     #[tokio::test]
     async fn fails_after_exhausting_retries() {
         let automation = FlakyAutomation::new(5);
-        let mut config = PublisherConfig::default();
-        config.max_retry = 1;
+        let config = PublisherConfig {
+            max_retry: 1,
+            ..PublisherConfig::default()
+        };
         let publisher = Publisher::new(config, Arc::new(automation.clone()));
         let request = PublishRequest {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -919,6 +1532,7 @@ mod tests {
             is_writable: true,
             supports_clipboard_paste: false,
             supports_keystroke_injection: false,
+            supports_accessibility_insert: false,
             reason: Some("no channel".into()),
         });
         let publisher = Publisher::with_automation(Arc::new(automation));
@@ -926,6 +1540,9 @@ mod tests {
             transcript: "Hello".to_string(),
             focus: FocusWindowContext::default(),
             fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
         };
 
         let outcome = publisher.publish(request).await.unwrap();
@@ -1014,4 +1631,214 @@ mod tests {
             PublishStrategy::NotifyOnly
         ));
     }
+
+    #[tokio::test]
+    async fn commits_pending_composition_before_keystrokes() {
+        let automation = ImeAutomation::new(
+            FocusCapabilities::writable_with_keystroke(),
+            ImeState::composing(),
+            true,
+        );
+        let publisher = Publisher::with_automation(Arc::new(automation.clone()));
+        let request = PublishRequest {
+            transcript: "世界".to_string(),
+            focus: FocusWindowContext::default(),
+            fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        let outcome = publisher.publish(request).await.unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert_eq!(automation.document().await, "你好世界");
+        assert!(automation.composition.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fails_without_typing_when_composition_cannot_be_committed() {
+        let automation = ImeAutomation::new(
+            FocusCapabilities::writable_with_all_channels(),
+            ImeState::composing(),
+            false,
+        );
+        let publisher = Publisher::with_automation(Arc::new(automation.clone()));
+        let request = PublishRequest {
+            transcript: "世界".to_string(),
+            focus: FocusWindowContext::default(),
+            fallback: FallbackStrategy::ClipboardCopy,
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        let outcome = publisher.publish(request).await.unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Failed);
+        let failure = outcome.failure.expect("failure details");
+        assert_eq!(failure.code, PublisherFailureCode::ImeCompositionActive);
+        assert!(automation.keystroke_calls.lock().await.is_empty());
+        assert!(automation.paste_calls.lock().await.is_empty());
+        assert_eq!(&*automation.composition.lock().await, "ni'hao");
+        assert!(automation.document().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn blocked_composition_falls_back_to_clipboard_copy() {
+        let automation = ImeAutomation::new(
+            FocusCapabilities::writable_with_all_channels(),
+            ImeState::composing(),
+            false,
+        );
+        let clipboard_access = RecordingClipboard::default();
+        *clipboard_access.state.lock().await = Some("original".into());
+        let manager = SessionManager::with_components(
+            EngineOrchestrator::with_engine(
+                EngineConfig {
+                    prefer_cloud: false,
+                },
+                Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+            ),
+            Arc::new(Publisher::with_automation(Arc::new(automation.clone()))),
+            ClipboardManager::new(Arc::new(clipboard_access.clone())),
+        );
+
+        let outcome = manager
+            .publish_transcript(
+                make_snapshot("session-ime-blocked", "raw", "世界"),
+                PublishRequest {
+                    transcript: "世界".to_string(),
+                    focus: FocusWindowContext::default(),
+                    fallback: FallbackStrategy::ClipboardCopy,
+                    insertion: InsertionMethod::default(),
+                    strategy: None,
+                    html: None,
+                },
+            )
+            .await
+            .expect("publish should degrade");
+
+        assert_eq!(outcome.status, PublisherStatus::Deferred);
+        assert_eq!(outcome.strategy, PublishStrategy::ClipboardFallback);
+        assert_eq!(outcome.fallback, Some(FallbackStrategy::ClipboardCopy));
+        assert_eq!(
+            outcome.failure.map(|failure| failure.code),
+            Some(PublisherFailureCode::ImeCompositionActive)
+        );
+        assert_eq!(clipboard_access.contents().await.as_deref(), Some("世界"));
+        assert!(automation.keystroke_calls.lock().await.is_empty());
+        assert!(automation.paste_calls.lock().await.is_empty());
+        assert_eq!(&*automation.composition.lock().await, "ni'hao");
+        assert!(manager
+            .pending_publish_retries()
+            .await
+            .expect("retry queue readable")
+            .iter()
+            .all(|entry| entry.session_id != "session-ime-blocked"));
+    }
+
+    #[tokio::test]
+    async fn unknown_composition_restricts_insertion_to_clipboard_paste() {
+        let automation = ImeAutomation::new(
+            FocusCapabilities::writable_with_all_channels(),
+            ImeState::unknown(),
+            false,
+        );
+        let publisher = Publisher::with_automation(Arc::new(automation.clone()));
+        let request = PublishRequest {
+            transcript: "世界".to_string(),
+            focus: FocusWindowContext::default(),
+            fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        let outcome = publisher.publish(request).await.unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert_eq!(
+            *automation.paste_calls.lock().await,
+            vec!["世界".to_string()]
+        );
+        assert!(automation.keystroke_calls.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_composition_without_clipboard_is_rejected() {
+        let automation = ImeAutomation::new(
+            FocusCapabilities::writable_with_keystroke(),
+            ImeState::unknown(),
+            false,
+        );
+        let publisher = Publisher::with_automation(Arc::new(automation.clone()));
+        let request = PublishRequest {
+            transcript: "世界".to_string(),
+            focus: FocusWindowContext::default(),
+            fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        let outcome = publisher.publish(request).await.unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Failed);
+        assert_eq!(
+            outcome.failure.map(|failure| failure.code),
+            Some(PublisherFailureCode::ImeCompositionActive)
+        );
+        assert!(automation.keystroke_calls.lock().await.is_empty());
+        assert_eq!(&*automation.composition.lock().await, "ni'hao");
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_accessibility_when_paste_and_keystrokes_fail() {
+        let mut capabilities = FocusCapabilities::writable_with_all_channels();
+        capabilities.supports_accessibility_insert = true;
+        let automation = MockAutomation::with_capabilities(capabilities);
+        automation
+            .set_paste_error(AutomationError::other("paste blocked"))
+            .await;
+        automation
+            .set_keystroke_error(AutomationError::other("keystrokes dropped"))
+            .await;
+        let config = PublisherConfig {
+            max_retry: 0,
+            ..PublisherConfig::default()
+        };
+        let publisher = Publisher::new(config, Arc::new(automation.clone()));
+        let request = PublishRequest {
+            transcript: "安全字段".to_string(),
+            focus: FocusWindowContext::from_app_identifier("com.example.bank"),
+            fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        let outcome = publisher.publish(request.clone()).await.unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert_eq!(automation.paste_calls().await.len(), 1);
+        assert_eq!(automation.keystroke_calls().await.len(), 1);
+        assert_eq!(
+            *automation.accessibility_calls.lock().await,
+            vec![request.transcript.clone()]
+        );
+
+        let accessibility_only =
+            MockAutomation::with_capabilities(FocusCapabilities::writable_with_accessibility());
+        let publisher = Publisher::with_automation(Arc::new(accessibility_only.clone()));
+        let outcome = publisher
+            .publish(PublishRequest {
+                insertion: InsertionMethod::Accessibility,
+                ..request
+            })
+            .await
+            .unwrap();
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert!(accessibility_only.paste_calls().await.is_empty());
+    }
 }
@@ -0,0 +1,811 @@
+//! 可访问性 API 插入通道：macOS 通过 AXUIElement 写入焦点控件的选中文本，
+//! Windows 通过 UI Automation 的 TextPattern 定位光标、ValuePattern 写回内容。
+//! 用于粘贴与模拟键入均被拒绝的输入框。同一套接口也用于识别焦点控件类型。
+
+use super::{AutomationError, FieldRole};
+
+/// 焦点控件是否支持在光标处直接写入文本。
+pub(crate) fn probe_caret_insert() -> Result<bool, AutomationError> {
+    platform::probe()
+}
+
+/// 在焦点控件的光标处插入文本，若有选中内容则替换之。
+pub(crate) fn insert_at_caret(text: &str) -> Result<(), AutomationError> {
+    platform::insert(text)
+}
+
+/// 删除光标前刚插入的 `text`，用于撤销插入。
+pub(crate) fn delete_before_caret(text: &str) -> Result<(), AutomationError> {
+    platform::delete_before_caret(text)
+}
+
+/// 识别焦点控件类型，无法判断时返回 `None`。
+pub(crate) fn focused_field_role() -> Result<Option<FieldRole>, AutomationError> {
+    Ok(classify_field(&platform::field_hints()?))
+}
+
+/// 平台可访问性 API 报告的焦点控件特征。
+#[derive(Debug, Default)]
+pub(crate) struct FieldHints {
+    /// macOS 的 AXRole，或 Windows 控件类型映射出的名称。
+    pub role: String,
+    pub subrole: String,
+    /// 类名、自动化标识等辅助判断的文本。
+    pub identifier: String,
+    pub is_password: bool,
+}
+
+pub(crate) fn classify_field(hints: &FieldHints) -> Option<FieldRole> {
+    let identifier = hints.identifier.to_ascii_lowercase();
+    if hints.is_password || hints.subrole == "AXSecureTextField" {
+        return Some(FieldRole::PasswordField);
+    }
+    if hints.subrole == "AXSearchField" || identifier.contains("search") {
+        return Some(FieldRole::SearchBox);
+    }
+    if ["terminal", "console", "termcontrol"]
+        .iter()
+        .any(|hint| identifier.contains(hint))
+    {
+        return Some(FieldRole::Terminal);
+    }
+    if ["monaco", "codemirror", "scintilla", "code-editor"]
+        .iter()
+        .any(|hint| identifier.contains(hint))
+    {
+        return Some(FieldRole::CodeEditor);
+    }
+    match hints.role.as_str() {
+        "AXTextField" | "AXTextArea" | "AXComboBox" | "edit" | "document" | "combobox" => {
+            Some(FieldRole::TextField)
+        }
+        _ => None,
+    }
+}
+
+/// 以 UTF-16 下标把 `text` 拼入 `value` 的 `[start, start + replaced)` 区间。
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn splice_utf16(value: &[u16], start: usize, replaced: usize, text: &str) -> Vec<u16> {
+    let start = start.min(value.len());
+    let end = start.saturating_add(replaced).min(value.len());
+    let mut spliced = Vec::with_capacity(value.len() + text.len());
+    spliced.extend_from_slice(&value[..start]);
+    spliced.extend(text.encode_utf16());
+    spliced.extend_from_slice(&value[end..]);
+    spliced
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{AutomationError, FieldHints};
+    use std::ffi::{c_char, c_void};
+    use std::ptr;
+
+    type CFTypeRef = *const c_void;
+    type CFStringRef = *const c_void;
+    type CFIndex = isize;
+    type AXUIElementRef = *const c_void;
+    type AXError = i32;
+
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    const AX_ERROR_SUCCESS: AXError = 0;
+    const AX_ERROR_NO_VALUE: AXError = -25212;
+    const AX_ERROR_API_DISABLED: AXError = -25211;
+    const AX_ERROR_ATTRIBUTE_UNSUPPORTED: AXError = -25205;
+    const AX_FOCUSED_UI_ELEMENT: &str = "AXFocusedUIElement";
+    const AX_SELECTED_TEXT: &str = "AXSelectedText";
+    const AX_SELECTED_TEXT_RANGE: &str = "AXSelectedTextRange";
+    const AX_VALUE_CF_RANGE_TYPE: u32 = 4;
+    const AX_ROLE: &str = "AXRole";
+    const AX_SUBROLE: &str = "AXSubrole";
+    const AX_IDENTIFIER: &str = "AXIdentifier";
+
+    #[repr(C)]
+    struct CFRange {
+        location: CFIndex,
+        length: CFIndex,
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> u8;
+        fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+        fn AXUIElementCopyAttributeValue(
+            element: AXUIElementRef,
+            attribute: CFStringRef,
+            value: *mut CFTypeRef,
+        ) -> AXError;
+        fn AXUIElementIsAttributeSettable(
+            element: AXUIElementRef,
+            attribute: CFStringRef,
+            settable: *mut u8,
+        ) -> AXError;
+        fn AXUIElementSetAttributeValue(
+            element: AXUIElementRef,
+            attribute: CFStringRef,
+            value: CFTypeRef,
+        ) -> AXError;
+        fn AXValueCreate(value_type: u32, value: *const c_void) -> CFTypeRef;
+        fn AXValueGetValue(value: CFTypeRef, value_type: u32, out: *mut c_void) -> u8;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithBytes(
+            allocator: *const c_void,
+            bytes: *const u8,
+            length: CFIndex,
+            encoding: u32,
+            is_external: u8,
+        ) -> CFStringRef;
+        fn CFRelease(value: CFTypeRef);
+        fn CFGetTypeID(value: CFTypeRef) -> usize;
+        fn CFStringGetTypeID() -> usize;
+        fn CFStringGetLength(string: CFStringRef) -> CFIndex;
+        fn CFStringGetCString(
+            string: CFStringRef,
+            buffer: *mut c_char,
+            size: CFIndex,
+            encoding: u32,
+        ) -> u8;
+    }
+
+    /// 按 Create/Copy 规则持有的 CF 对象，离开作用域时释放。
+    struct Owned(CFTypeRef);
+
+    impl Drop for Owned {
+        fn drop(&mut self) {
+            // SAFETY: 仅包装非空且由本进程持有引用计数的对象。
+            unsafe { CFRelease(self.0) }
+        }
+    }
+
+    fn cf_string(value: &str) -> Result<Owned, AutomationError> {
+        // SAFETY: 字节切片在调用期间有效，CF 会复制内容。
+        let string = unsafe {
+            CFStringCreateWithBytes(
+                ptr::null(),
+                value.as_ptr(),
+                value.len() as CFIndex,
+                CF_STRING_ENCODING_UTF8,
+                0,
+            )
+        };
+        if string.is_null() {
+            return Err(AutomationError::other("failed to create CFString"));
+        }
+        Ok(Owned(string))
+    }
+
+    fn check(code: AXError, action: &str) -> Result<(), AutomationError> {
+        match code {
+            AX_ERROR_SUCCESS => Ok(()),
+            AX_ERROR_API_DISABLED => Err(AutomationError::PermissionDenied),
+            AX_ERROR_NO_VALUE => Err(AutomationError::focus_not_found()),
+            code => Err(AutomationError::other(format!(
+                "AXUIElement {action} failed ({code})"
+            ))),
+        }
+    }
+
+    fn focused_element() -> Result<Owned, AutomationError> {
+        // SAFETY: AX 调用只读取输出指针，返回的对象均按 Copy/Create 规则由 Owned 释放。
+        unsafe {
+            if AXIsProcessTrusted() == 0 {
+                return Err(AutomationError::PermissionDenied);
+            }
+            let system = AXUIElementCreateSystemWide();
+            if system.is_null() {
+                return Err(AutomationError::channel_unavailable(
+                    "accessibility system element unavailable",
+                ));
+            }
+            let system = Owned(system);
+            let attribute = cf_string(AX_FOCUSED_UI_ELEMENT)?;
+            let mut focused: CFTypeRef = ptr::null();
+            check(
+                AXUIElementCopyAttributeValue(system.0, attribute.0, &mut focused),
+                "focus lookup",
+            )?;
+            if focused.is_null() {
+                return Err(AutomationError::focus_not_found());
+            }
+            Ok(Owned(focused))
+        }
+    }
+
+    /// 读取字符串类型的属性；属性不存在或不是字符串时返回 `None`。
+    fn string_attribute(element: &Owned, name: &str) -> Result<Option<String>, AutomationError> {
+        let attribute = cf_string(name)?;
+        // SAFETY: 读取到的对象按 Copy 规则由 Owned 释放，缓冲区按 UTF-8 最坏长度分配。
+        unsafe {
+            let mut value: CFTypeRef = ptr::null();
+            match AXUIElementCopyAttributeValue(element.0, attribute.0, &mut value) {
+                AX_ERROR_NO_VALUE | AX_ERROR_ATTRIBUTE_UNSUPPORTED => return Ok(None),
+                code => check(code, "attribute read")?,
+            }
+            if value.is_null() {
+                return Ok(None);
+            }
+            let value = Owned(value);
+            if CFGetTypeID(value.0) != CFStringGetTypeID() {
+                return Ok(None);
+            }
+            let capacity = CFStringGetLength(value.0) * 4 + 1;
+            let mut buffer = vec![0u8; capacity as usize];
+            if CFStringGetCString(
+                value.0,
+                buffer.as_mut_ptr() as *mut c_char,
+                capacity,
+                CF_STRING_ENCODING_UTF8,
+            ) == 0
+            {
+                return Ok(None);
+            }
+            let end = buffer.iter().position(|&byte| byte == 0).unwrap_or(0);
+            Ok(Some(String::from_utf8_lossy(&buffer[..end]).into_owned()))
+        }
+    }
+
+    pub(super) fn field_hints() -> Result<FieldHints, AutomationError> {
+        let focused = focused_element()?;
+        Ok(FieldHints {
+            role: string_attribute(&focused, AX_ROLE)?.unwrap_or_default(),
+            subrole: string_attribute(&focused, AX_SUBROLE)?.unwrap_or_default(),
+            identifier: string_attribute(&focused, AX_IDENTIFIER)?.unwrap_or_default(),
+            // 密码框以 AXSecureTextField 子角色表示。
+            is_password: false,
+        })
+    }
+
+    pub(super) fn probe() -> Result<bool, AutomationError> {
+        let focused = focused_element()?;
+        let attribute = cf_string(AX_SELECTED_TEXT)?;
+        let mut settable = 0u8;
+        // SAFETY: focused 与 attribute 在调用期间有效。
+        let code = unsafe { AXUIElementIsAttributeSettable(focused.0, attribute.0, &mut settable) };
+        check(code, "capability probe")?;
+        Ok(settable != 0)
+    }
+
+    pub(super) fn insert(text: &str) -> Result<(), AutomationError> {
+        let focused = focused_element()?;
+        let attribute = cf_string(AX_SELECTED_TEXT)?;
+        let value = cf_string(text)?;
+        // SAFETY: 写入选中文本会替换选区；无选区时即插入到光标处。
+        let code = unsafe { AXUIElementSetAttributeValue(focused.0, attribute.0, value.0) };
+        check(code, "insert")
+    }
+
+    pub(super) fn delete_before_caret(text: &str) -> Result<(), AutomationError> {
+        let len = text.encode_utf16().count() as CFIndex;
+        let focused = focused_element()?;
+        let attribute = cf_string(AX_SELECTED_TEXT_RANGE)?;
+        // SAFETY: 选区以 AXValue(CFRange) 表示，读取后按 Copy 规则释放；新建的 AXValue 同理。
+        unsafe {
+            let mut value: CFTypeRef = ptr::null();
+            check(
+                AXUIElementCopyAttributeValue(focused.0, attribute.0, &mut value),
+                "selection lookup",
+            )?;
+            if value.is_null() {
+                return Err(AutomationError::focus_not_found());
+            }
+            let value = Owned(value);
+            let mut caret = CFRange {
+                location: 0,
+                length: 0,
+            };
+            let read = AXValueGetValue(
+                value.0,
+                AX_VALUE_CF_RANGE_TYPE,
+                &mut caret as *mut CFRange as *mut c_void,
+            );
+            if read == 0 || caret.location < len {
+                return Err(AutomationError::other(
+                    "inserted text is no longer before the caret",
+                ));
+            }
+            let inserted = CFRange {
+                location: caret.location - len,
+                length: len,
+            };
+            let range = AXValueCreate(
+                AX_VALUE_CF_RANGE_TYPE,
+                &inserted as *const CFRange as *const c_void,
+            );
+            if range.is_null() {
+                return Err(AutomationError::other("failed to create AXValue"));
+            }
+            let range = Owned(range);
+            check(
+                AXUIElementSetAttributeValue(focused.0, attribute.0, range.0),
+                "select",
+            )?;
+        }
+        insert("")
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{splice_utf16, AutomationError, FieldHints};
+    use std::ffi::c_void;
+    use std::ptr;
+
+    type HResult = i32;
+    type Bstr = *mut u16;
+
+    #[repr(C)]
+    struct Guid(u32, u16, u16, [u8; 8]);
+
+    const CLSID_CUI_AUTOMATION: Guid = Guid(
+        0xff48_dba4,
+        0x60ef,
+        0x4201,
+        [0xaa, 0x87, 0x54, 0x10, 0x3e, 0xef, 0x59, 0x4e],
+    );
+    const IID_IUI_AUTOMATION: Guid = Guid(
+        0x30cb_e57d,
+        0xd9d0,
+        0x452a,
+        [0xab, 0x13, 0x7a, 0xc5, 0xac, 0x48, 0x25, 0xee],
+    );
+    const IID_TEXT_PATTERN: Guid = Guid(
+        0x32eb_a289,
+        0x3583,
+        0x42c9,
+        [0x9c, 0x59, 0x3b, 0x6d, 0x9a, 0x1e, 0x9b, 0x6a],
+    );
+    const IID_VALUE_PATTERN: Guid = Guid(
+        0xa94c_d8b1,
+        0x0844,
+        0x4cd6,
+        [0x9d, 0x2d, 0x64, 0x05, 0x37, 0xab, 0x39, 0xe9],
+    );
+    const UIA_VALUE_PATTERN_ID: i32 = 10002;
+    const UIA_TEXT_PATTERN_ID: i32 = 10014;
+    const COINIT_APARTMENTTHREADED: u32 = 0x2;
+    const CLSCTX_INPROC_SERVER: u32 = 0x1;
+    const ENDPOINT_START: i32 = 0;
+    const ENDPOINT_END: i32 = 1;
+    const E_ACCESSDENIED: HResult = 0x8007_0005_u32 as HResult;
+
+    // COM 虚表下标（含 IUnknown 的三个方法）。
+    const RELEASE: usize = 2;
+    const AUTOMATION_GET_FOCUSED_ELEMENT: usize = 8;
+    const ELEMENT_GET_CURRENT_PATTERN_AS: usize = 14;
+    const ELEMENT_GET_CURRENT_CONTROL_TYPE: usize = 21;
+    const ELEMENT_GET_CURRENT_AUTOMATION_ID: usize = 29;
+    const ELEMENT_GET_CURRENT_CLASS_NAME: usize = 30;
+    const ELEMENT_GET_CURRENT_IS_PASSWORD: usize = 35;
+    const UIA_COMBO_BOX_CONTROL_TYPE_ID: i32 = 50003;
+    const UIA_EDIT_CONTROL_TYPE_ID: i32 = 50004;
+    const UIA_DOCUMENT_CONTROL_TYPE_ID: i32 = 50030;
+    const TEXT_PATTERN_GET_SELECTION: usize = 5;
+    const TEXT_PATTERN_GET_DOCUMENT_RANGE: usize = 7;
+    const RANGE_ARRAY_GET_LENGTH: usize = 3;
+    const RANGE_ARRAY_GET_ELEMENT: usize = 4;
+    const RANGE_MOVE_ENDPOINT_BY_RANGE: usize = 15;
+    const RANGE_GET_TEXT: usize = 12;
+    const VALUE_PATTERN_SET_VALUE: usize = 3;
+    const VALUE_PATTERN_GET_CURRENT_VALUE: usize = 4;
+    const VALUE_PATTERN_GET_IS_READ_ONLY: usize = 5;
+
+    #[link(name = "ole32")]
+    extern "system" {
+        fn CoInitializeEx(reserved: *mut c_void, flags: u32) -> HResult;
+        fn CoUninitialize();
+        fn CoCreateInstance(
+            clsid: *const Guid,
+            outer: *mut c_void,
+            context: u32,
+            iid: *const Guid,
+            out: *mut *mut c_void,
+        ) -> HResult;
+    }
+
+    #[link(name = "oleaut32")]
+    extern "system" {
+        fn SysAllocStringLen(value: *const u16, len: u32) -> Bstr;
+        fn SysStringLen(value: Bstr) -> u32;
+        fn SysFreeString(value: Bstr);
+    }
+
+    /// 持有一个 COM 接口指针，离开作用域时 Release。
+    struct Com(*mut c_void);
+
+    impl Com {
+        /// 取虚表第 `index` 项并按 `F` 解释。
+        ///
+        /// SAFETY: 调用方保证 `F` 与该接口虚表项的签名一致。
+        unsafe fn method<F: Copy>(&self, index: usize) -> F {
+            let vtable = *(self.0 as *const *const usize);
+            std::mem::transmute_copy(&*vtable.add(index))
+        }
+    }
+
+    impl Drop for Com {
+        fn drop(&mut self) {
+            // SAFETY: 指针来自成功的 COM 调用，Release 为 IUnknown 第三项。
+            unsafe {
+                let release: unsafe extern "system" fn(*mut c_void) -> u32 = self.method(RELEASE);
+                release(self.0);
+            }
+        }
+    }
+
+    /// 调用期间持有的 BSTR。
+    struct OwnedBstr(Bstr);
+
+    impl OwnedBstr {
+        fn from_utf16(value: &[u16]) -> Self {
+            // SAFETY: 按长度复制切片内容。
+            Self(unsafe { SysAllocStringLen(value.as_ptr(), value.len() as u32) })
+        }
+
+        fn to_utf16(&self) -> Vec<u16> {
+            if self.0.is_null() {
+                return Vec::new();
+            }
+            // SAFETY: BSTR 前缀记录了字符数。
+            unsafe { std::slice::from_raw_parts(self.0, SysStringLen(self.0) as usize).to_vec() }
+        }
+    }
+
+    impl Drop for OwnedBstr {
+        fn drop(&mut self) {
+            // SAFETY: SysFreeString 接受空指针。
+            unsafe { SysFreeString(self.0) }
+        }
+    }
+
+    fn check(hr: HResult, action: &str) -> Result<(), AutomationError> {
+        match hr {
+            hr if hr >= 0 => Ok(()),
+            E_ACCESSDENIED => Err(AutomationError::PermissionDenied),
+            hr => Err(AutomationError::other(format!(
+                "UI Automation {action} failed (0x{:08x})",
+                hr as u32
+            ))),
+        }
+    }
+
+    fn out_interface(ptr: *mut c_void, what: &str) -> Result<Com, AutomationError> {
+        if ptr.is_null() {
+            return Err(AutomationError::channel_unavailable(format!(
+                "focused element does not support {what}"
+            )));
+        }
+        Ok(Com(ptr))
+    }
+
+    /// 当前线程的 COM 初始化；仅在本次成功初始化时反初始化。
+    struct Apartment(bool);
+
+    impl Apartment {
+        fn enter() -> Self {
+            // SAFETY: 单线程套间初始化；已初始化时返回 S_FALSE 或 RPC_E_CHANGED_MODE。
+            let hr = unsafe { CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED) };
+            Self(hr >= 0)
+        }
+    }
+
+    impl Drop for Apartment {
+        fn drop(&mut self) {
+            if self.0 {
+                // SAFETY: 与成功的 CoInitializeEx 配对。
+                unsafe { CoUninitialize() }
+            }
+        }
+    }
+
+    /// 焦点元素的文本与值模式。
+    struct FocusedText {
+        text: Com,
+        value: Com,
+    }
+
+    fn focused_element() -> Result<Com, AutomationError> {
+        type GetFocused = unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HResult;
+
+        // SAFETY: 所有接口指针由 Com 持有，虚表签名与 UIAutomationClient.h 一致。
+        unsafe {
+            let mut automation = ptr::null_mut();
+            check(
+                CoCreateInstance(
+                    &CLSID_CUI_AUTOMATION,
+                    ptr::null_mut(),
+                    CLSCTX_INPROC_SERVER,
+                    &IID_IUI_AUTOMATION,
+                    &mut automation,
+                ),
+                "initialisation",
+            )?;
+            let automation = out_interface(automation, "UI Automation")?;
+
+            let mut element = ptr::null_mut();
+            let get_focused: GetFocused = automation.method(AUTOMATION_GET_FOCUSED_ELEMENT);
+            check(get_focused(automation.0, &mut element), "focus lookup")?;
+            let element = Com(element);
+            if element.0.is_null() {
+                return Err(AutomationError::focus_not_found());
+            }
+            Ok(element)
+        }
+    }
+
+    fn focused_text() -> Result<FocusedText, AutomationError> {
+        type GetPatternAs =
+            unsafe extern "system" fn(*mut c_void, i32, *const Guid, *mut *mut c_void) -> HResult;
+
+        let element = focused_element()?;
+        // SAFETY: 模式接口由 Com 持有，虚表签名与 UIAutomationClient.h 一致。
+        unsafe {
+            let get_pattern: GetPatternAs = element.method(ELEMENT_GET_CURRENT_PATTERN_AS);
+            let mut text = ptr::null_mut();
+            check(
+                get_pattern(element.0, UIA_TEXT_PATTERN_ID, &IID_TEXT_PATTERN, &mut text),
+                "TextPattern lookup",
+            )?;
+            let text = out_interface(text, "TextPattern")?;
+            let mut value = ptr::null_mut();
+            check(
+                get_pattern(
+                    element.0,
+                    UIA_VALUE_PATTERN_ID,
+                    &IID_VALUE_PATTERN,
+                    &mut value,
+                ),
+                "ValuePattern lookup",
+            )?;
+            let value = out_interface(value, "ValuePattern")?;
+            Ok(FocusedText { text, value })
+        }
+    }
+
+    impl FocusedText {
+        fn is_read_only(&self) -> Result<bool, AutomationError> {
+            type GetReadOnly = unsafe extern "system" fn(*mut c_void, *mut i32) -> HResult;
+            let mut read_only = 0;
+            // SAFETY: IUIAutomationValuePattern::get_CurrentIsReadOnly。
+            unsafe {
+                let get: GetReadOnly = self.value.method(VALUE_PATTERN_GET_IS_READ_ONLY);
+                check(get(self.value.0, &mut read_only), "read-only probe")?;
+            }
+            Ok(read_only != 0)
+        }
+
+        fn current_value(&self) -> Result<Vec<u16>, AutomationError> {
+            type GetValue = unsafe extern "system" fn(*mut c_void, *mut Bstr) -> HResult;
+            let mut value = ptr::null_mut();
+            // SAFETY: IUIAutomationValuePattern::get_CurrentValue，BSTR 交由 OwnedBstr 释放。
+            unsafe {
+                let get: GetValue = self.value.method(VALUE_PATTERN_GET_CURRENT_VALUE);
+                check(get(self.value.0, &mut value), "value read")?;
+            }
+            Ok(OwnedBstr(value).to_utf16())
+        }
+
+        fn set_value(&self, value: &[u16]) -> Result<(), AutomationError> {
+            type SetValue = unsafe extern "system" fn(*mut c_void, Bstr) -> HResult;
+            let value = OwnedBstr::from_utf16(value);
+            // SAFETY: IUIAutomationValuePattern::SetValue。
+            unsafe {
+                let set: SetValue = self.value.method(VALUE_PATTERN_SET_VALUE);
+                check(set(self.value.0, value.0), "value write")
+            }
+        }
+
+        /// 选区起点相对文档开头的 UTF-16 偏移，以及选区长度。
+        fn selection(&self) -> Result<(usize, usize), AutomationError> {
+            type GetRange = unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HResult;
+            type GetLength = unsafe extern "system" fn(*mut c_void, *mut i32) -> HResult;
+            type GetElement =
+                unsafe extern "system" fn(*mut c_void, i32, *mut *mut c_void) -> HResult;
+            type MoveEndpoint =
+                unsafe extern "system" fn(*mut c_void, i32, *mut c_void, i32) -> HResult;
+
+            // SAFETY: 虚表签名与 IUIAutomationTextPattern / TextRangeArray / TextRange 一致。
+            unsafe {
+                let get_selection: GetRange = self.text.method(TEXT_PATTERN_GET_SELECTION);
+                let mut ranges = ptr::null_mut();
+                check(get_selection(self.text.0, &mut ranges), "selection lookup")?;
+                let ranges = out_interface(ranges, "text selection")?;
+                let get_length: GetLength = ranges.method(RANGE_ARRAY_GET_LENGTH);
+                let mut length = 0;
+                check(get_length(ranges.0, &mut length), "selection lookup")?;
+                if length < 1 {
+                    return Err(AutomationError::channel_unavailable(
+                        "focused element has no caret",
+                    ));
+                }
+                let get_element: GetElement = ranges.method(RANGE_ARRAY_GET_ELEMENT);
+                let mut selection = ptr::null_mut();
+                check(get_element(ranges.0, 0, &mut selection), "selection lookup")?;
+                let selection = out_interface(selection, "text selection")?;
+
+                let get_document: GetRange = self.text.method(TEXT_PATTERN_GET_DOCUMENT_RANGE);
+                let mut prefix = ptr::null_mut();
+                check(get_document(self.text.0, &mut prefix), "document lookup")?;
+                let prefix = out_interface(prefix, "document range")?;
+                let move_endpoint: MoveEndpoint = prefix.method(RANGE_MOVE_ENDPOINT_BY_RANGE);
+                check(
+                    move_endpoint(prefix.0, ENDPOINT_END, selection.0, ENDPOINT_START),
+                    "caret lookup",
+                )?;
+
+                Ok((range_text(&prefix)?.len(), range_text(&selection)?.len()))
+            }
+        }
+    }
+
+    fn range_text(range: &Com) -> Result<Vec<u16>, AutomationError> {
+        type GetText = unsafe extern "system" fn(*mut c_void, i32, *mut Bstr) -> HResult;
+        let mut text = ptr::null_mut();
+        // SAFETY: IUIAutomationTextRange::GetText，-1 表示不截断。
+        unsafe {
+            let get: GetText = range.method(RANGE_GET_TEXT);
+            check(get(range.0, -1, &mut text), "text read")?;
+        }
+        Ok(OwnedBstr(text).to_utf16())
+    }
+
+    fn element_string(element: &Com, index: usize) -> Result<String, AutomationError> {
+        type GetString = unsafe extern "system" fn(*mut c_void, *mut Bstr) -> HResult;
+        let mut value = ptr::null_mut();
+        // SAFETY: IUIAutomationElement 的 BSTR 属性读取，BSTR 交由 OwnedBstr 释放。
+        unsafe {
+            let get: GetString = element.method(index);
+            check(get(element.0, &mut value), "property read")?;
+        }
+        Ok(String::from_utf16_lossy(&OwnedBstr(value).to_utf16()))
+    }
+
+    pub(super) fn field_hints() -> Result<FieldHints, AutomationError> {
+        type GetInt = unsafe extern "system" fn(*mut c_void, *mut i32) -> HResult;
+
+        let _apartment = Apartment::enter();
+        let element = focused_element()?;
+        let mut control_type = 0;
+        let mut is_password = 0;
+        // SAFETY: get_CurrentControlType 与 get_CurrentIsPassword 均写出一个 32 位整数。
+        unsafe {
+            let get: GetInt = element.method(ELEMENT_GET_CURRENT_CONTROL_TYPE);
+            check(get(element.0, &mut control_type), "control type read")?;
+            let get: GetInt = element.method(ELEMENT_GET_CURRENT_IS_PASSWORD);
+            check(get(element.0, &mut is_password), "password probe")?;
+        }
+        let role = match control_type {
+            UIA_EDIT_CONTROL_TYPE_ID => "edit",
+            UIA_DOCUMENT_CONTROL_TYPE_ID => "document",
+            UIA_COMBO_BOX_CONTROL_TYPE_ID => "combobox",
+            _ => "",
+        };
+        Ok(FieldHints {
+            role: role.to_string(),
+            subrole: String::new(),
+            identifier: format!(
+                "{} {}",
+                element_string(&element, ELEMENT_GET_CURRENT_CLASS_NAME)?,
+                element_string(&element, ELEMENT_GET_CURRENT_AUTOMATION_ID)?
+            ),
+            is_password: is_password != 0,
+        })
+    }
+
+    pub(super) fn probe() -> Result<bool, AutomationError> {
+        let _apartment = Apartment::enter();
+        match focused_text() {
+            Ok(focused) => Ok(!focused.is_read_only()?),
+            Err(AutomationError::ChannelUnavailable { .. }) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub(super) fn insert(text: &str) -> Result<(), AutomationError> {
+        let _apartment = Apartment::enter();
+        let focused = focused_text()?;
+        if focused.is_read_only()? {
+            return Err(AutomationError::channel_unavailable(
+                "focused element is read-only",
+            ));
+        }
+        let (caret, selected) = focused.selection()?;
+        let value = focused.current_value()?;
+        focused.set_value(&splice_utf16(&value, caret, selected, text))
+    }
+
+    pub(super) fn delete_before_caret(text: &str) -> Result<(), AutomationError> {
+        let _apartment = Apartment::enter();
+        let focused = focused_text()?;
+        let (caret, _) = focused.selection()?;
+        let value = focused.current_value()?;
+        let inserted: Vec<u16> = text.encode_utf16().collect();
+        let start = caret.checked_sub(inserted.len());
+        if start.and_then(|start| value.get(start..caret)) != Some(inserted.as_slice()) {
+            return Err(AutomationError::other(
+                "inserted text is no longer before the caret",
+            ));
+        }
+        focused.set_value(&splice_utf16(
+            &value,
+            caret - inserted.len(),
+            inserted.len(),
+            "",
+        ))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::{AutomationError, FieldHints};
+
+    pub(super) fn field_hints() -> Result<FieldHints, AutomationError> {
+        Ok(FieldHints::default())
+    }
+
+    pub(super) fn probe() -> Result<bool, AutomationError> {
+        Ok(false)
+    }
+
+    pub(super) fn insert(_text: &str) -> Result<(), AutomationError> {
+        Err(AutomationError::channel_unavailable(
+            "accessibility insertion is not supported on this platform",
+        ))
+    }
+
+    pub(super) fn delete_before_caret(_text: &str) -> Result<(), AutomationError> {
+        Err(AutomationError::channel_unavailable(
+            "accessibility deletion is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splices_at_utf16_offsets() {
+        let value: Vec<u16> = "你好 world".encode_utf16().collect();
+        let spliced = splice_utf16(&value, 3, 5, "👋 there");
+        assert_eq!(String::from_utf16(&spliced).unwrap(), "你好 👋 there");
+        let appended = splice_utf16(&value, 99, 4, "!");
+        assert_eq!(String::from_utf16(&appended).unwrap(), "你好 world!");
+    }
+
+    #[test]
+    fn classifies_focused_field_hints() {
+        let hints = |role: &str, subrole: &str, identifier: &str| FieldHints {
+            role: role.into(),
+            subrole: subrole.into(),
+            identifier: identifier.into(),
+            is_password: false,
+        };
+        assert_eq!(
+            classify_field(&hints("AXTextField", "AXSecureTextField", "")),
+            Some(FieldRole::PasswordField)
+        );
+        assert_eq!(
+            classify_field(&FieldHints {
+                is_password: true,
+                ..hints("edit", "", "TextBox")
+            }),
+            Some(FieldRole::PasswordField)
+        );
+        assert_eq!(
+            classify_field(&hints("edit", "", "Edit SearchBox")),
+            Some(FieldRole::SearchBox)
+        );
+        assert_eq!(
+            classify_field(&hints("document", "", "TermControl")),
+            Some(FieldRole::Terminal)
+        );
+        assert_eq!(
+            classify_field(&hints("AXTextArea", "", "")),
+            Some(FieldRole::TextField)
+        );
+        assert_eq!(classify_field(&hints("AXButton", "", "")), None);
+    }
+}
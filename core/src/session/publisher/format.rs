@@ -0,0 +1,321 @@
+//! 输出格式化：按目标应用把润色稿转换为合适的标记——编辑器用 Markdown，
+//! 终端与搜索框用单行纯文本，富文本编辑器额外在剪贴板放入 HTML。
+
+use serde::{Deserialize, Serialize};
+
+use super::{FieldRole, FocusWindowContext};
+
+/// 润色稿插入时使用的标记格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// 去除标记并合并为单行，避免换行在终端中提前执行命令。
+    PlainText,
+    Markdown,
+    /// 剪贴板同时携带 HTML 与保留段落的纯文本。
+    Html,
+}
+
+impl OutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::PlainText => "plain_text",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Html => "html",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "plain_text" => Some(OutputFormat::PlainText),
+            "markdown" => Some(OutputFormat::Markdown),
+            "html" => Some(OutputFormat::Html),
+            _ => None,
+        }
+    }
+
+    /// 应用偏好未指定格式时按焦点推断；无法判断的目标保持原文。
+    pub fn for_focus(focus: &FocusWindowContext) -> Option<Self> {
+        let role = FieldRole::refine_for_app(focus.field_role, focus.app_identifier.as_deref());
+        match role {
+            Some(FieldRole::Terminal | FieldRole::SearchBox) => {
+                return Some(OutputFormat::PlainText)
+            }
+            Some(FieldRole::CodeEditor) => return Some(OutputFormat::Markdown),
+            _ => {}
+        }
+        let app = focus
+            .app_identifier
+            .as_deref()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        const MARKDOWN_APPS: [&str; 5] = [
+            "md.obsidian",
+            "obsidian.exe",
+            "abnerworks.typora",
+            "typora.exe",
+            "notion.id",
+        ];
+        const RICH_TEXT_APPS: [&str; 6] = [
+            "com.microsoft.word",
+            "winword.exe",
+            "com.apple.iwork.pages",
+            "com.apple.mail",
+            "com.microsoft.outlook",
+            "outlook.exe",
+        ];
+        if MARKDOWN_APPS.iter().any(|known| app.starts_with(known)) {
+            Some(OutputFormat::Markdown)
+        } else if RICH_TEXT_APPS.iter().any(|known| app.starts_with(known)) {
+            Some(OutputFormat::Html)
+        } else {
+            None
+        }
+    }
+}
+
+/// 格式化结果：`text` 用于键入与粘贴，`html` 仅在富文本格式下提供。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedTranscript {
+    pub text: String,
+    pub html: Option<String>,
+}
+
+/// 按格式转换润色稿；识别段落、标题、有序与无序列表以及 `**粗体**`、`` `代码` ``。
+pub fn render(transcript: &str, format: OutputFormat) -> FormattedTranscript {
+    let blocks = parse_blocks(transcript);
+    match format {
+        OutputFormat::PlainText => FormattedTranscript {
+            text: blocks
+                .iter()
+                .flat_map(Block::lines)
+                .map(|line| strip_inline(&line))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            html: None,
+        },
+        OutputFormat::Markdown => FormattedTranscript {
+            text: join_blocks(&blocks, |line| line.to_string()),
+            html: None,
+        },
+        OutputFormat::Html => FormattedTranscript {
+            text: join_blocks(&blocks, strip_inline),
+            html: Some(blocks.iter().map(Block::to_html).collect()),
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(Vec<String>),
+    Bullets(Vec<String>),
+    Numbered(Vec<String>),
+}
+
+impl Block {
+    /// Markdown 形式的各行，列表项已统一为 `- ` 与 `1. `。
+    fn lines(&self) -> Vec<String> {
+        match self {
+            Block::Heading(level, text) => vec![format!("{} {text}", "#".repeat(*level))],
+            Block::Paragraph(lines) => lines.clone(),
+            Block::Bullets(items) => items.iter().map(|item| format!("- {item}")).collect(),
+            Block::Numbered(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| format!("{}. {item}", index + 1))
+                .collect(),
+        }
+    }
+
+    fn to_html(&self) -> String {
+        let items = |items: &[String]| {
+            items
+                .iter()
+                .map(|item| format!("<li>{}</li>", inline_html(item)))
+                .collect::<String>()
+        };
+        match self {
+            Block::Heading(level, text) => format!("<h{level}>{}</h{level}>", inline_html(text)),
+            Block::Paragraph(lines) => format!(
+                "<p>{}</p>",
+                lines
+                    .iter()
+                    .map(|line| inline_html(line))
+                    .collect::<Vec<_>>()
+                    .join("<br>")
+            ),
+            Block::Bullets(list) => format!("<ul>{}</ul>", items(list)),
+            Block::Numbered(list) => format!("<ol>{}</ol>", items(list)),
+        }
+    }
+}
+
+fn parse_blocks(transcript: &str) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut open = false;
+    for raw in transcript.lines() {
+        let line = raw.trim();
+        if line.is_empty() {
+            open = false;
+            continue;
+        }
+        if let Some((level, text)) = heading(line) {
+            blocks.push(Block::Heading(level, text.to_string()));
+            open = false;
+            continue;
+        }
+        let last = if open { blocks.last_mut() } else { None };
+        match (list_item(line), last) {
+            (Some((false, item)), Some(Block::Bullets(items)))
+            | (Some((true, item)), Some(Block::Numbered(items))) => items.push(item.to_string()),
+            (Some((false, item)), _) => blocks.push(Block::Bullets(vec![item.to_string()])),
+            (Some((true, item)), _) => blocks.push(Block::Numbered(vec![item.to_string()])),
+            (None, Some(Block::Paragraph(lines))) => lines.push(line.to_string()),
+            (None, _) => blocks.push(Block::Paragraph(vec![line.to_string()])),
+        }
+        open = true;
+    }
+    blocks
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|ch| *ch == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    line[level..]
+        .strip_prefix(' ')
+        .map(|text| (level, text.trim()))
+}
+
+/// 识别列表项，返回 `(是否有序, 内容)`；支持口述常见的 `•`、`·` 与 `1、`。
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    for marker in ["- ", "* ", "• ", "· "] {
+        if let Some(item) = line.strip_prefix(marker) {
+            return Some((false, item.trim()));
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let rest = &line[digits..];
+    rest.strip_prefix(". ")
+        .or_else(|| rest.strip_prefix('、'))
+        .or_else(|| rest.strip_prefix(") "))
+        .map(|item| (true, item.trim()))
+}
+
+fn join_blocks(blocks: &[Block], map_line: impl Fn(&str) -> String) -> String {
+    blocks
+        .iter()
+        .map(|block| {
+            block
+                .lines()
+                .iter()
+                .map(|line| map_line(line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn strip_inline(line: &str) -> String {
+    let line = heading(line).map_or(line, |(_, text)| text);
+    line.replace("**", "").replace('`', "")
+}
+
+fn inline_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut bold = false;
+    let mut code = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' if !code && chars.peek() == Some(&'*') => {
+                chars.next();
+                html.push_str(if bold { "</strong>" } else { "<strong>" });
+                bold = !bold;
+            }
+            '`' => {
+                html.push_str(if code { "</code>" } else { "<code>" });
+                code = !code;
+            }
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(ch),
+        }
+    }
+    if code {
+        html.push_str("</code>");
+    }
+    if bold {
+        html.push_str("</strong>");
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICTATED: &str = "# Release notes\nShip the **new** parser.\nIt handles `a<b`.\n\n• first\n• second\n\n1、draft\n2、review";
+
+    #[test]
+    fn renders_markdown_plain_text_and_html() {
+        let markdown = render(DICTATED, OutputFormat::Markdown);
+        assert_eq!(
+            markdown.text,
+            "# Release notes\n\nShip the **new** parser.\nIt handles `a<b`.\n\n- first\n- second\n\n1. draft\n2. review"
+        );
+        assert_eq!(markdown.html, None);
+
+        let plain = render(DICTATED, OutputFormat::PlainText);
+        assert_eq!(
+            plain.text,
+            "Release notes Ship the new parser. It handles a<b. - first - second 1. draft 2. review"
+        );
+
+        let rich = render(DICTATED, OutputFormat::Html);
+        assert_eq!(
+            rich.html.as_deref(),
+            Some(
+                "<h1>Release notes</h1><p>Ship the <strong>new</strong> parser.<br>It handles <code>a&lt;b</code>.</p><ul><li>first</li><li>second</li></ul><ol><li>draft</li><li>review</li></ol>"
+            )
+        );
+        assert!(rich
+            .text
+            .starts_with("Release notes\n\nShip the new parser."));
+    }
+
+    #[test]
+    fn infers_format_from_focus() {
+        let terminal = FocusWindowContext::from_app_identifier("com.googlecode.iterm2");
+        assert_eq!(
+            OutputFormat::for_focus(&terminal),
+            Some(OutputFormat::PlainText)
+        );
+        let obsidian = FocusWindowContext::from_app_identifier("md.obsidian");
+        assert_eq!(
+            OutputFormat::for_focus(&obsidian),
+            Some(OutputFormat::Markdown)
+        );
+        let mut mail = FocusWindowContext::from_app_identifier("com.apple.mail");
+        assert_eq!(OutputFormat::for_focus(&mail), Some(OutputFormat::Html));
+        mail.field_role = Some(FieldRole::SearchBox);
+        assert_eq!(
+            OutputFormat::for_focus(&mail),
+            Some(OutputFormat::PlainText)
+        );
+        assert_eq!(
+            OutputFormat::for_focus(&FocusWindowContext::from_app_identifier("com.example")),
+            None
+        );
+        assert_eq!(OutputFormat::parse("html"), Some(OutputFormat::Html));
+    }
+}
@@ -0,0 +1,323 @@
+//! 输入法（IME）组合态检测，用于避免直接插入与 CJK 输入法组合串交错。
+
+/// 焦点应用当前的输入法组合状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImeCompositionState {
+    /// 未启用输入法或处于直接输入模式。
+    #[default]
+    Inactive,
+    /// 输入法已打开，但没有待提交的组合串。
+    Idle,
+    /// 存在待提交的组合串，直接插入会与其交错。
+    Composing,
+    /// 输入法已打开，但平台无法观测组合串。
+    Unknown,
+}
+
+impl ImeCompositionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImeCompositionState::Inactive => "inactive",
+            ImeCompositionState::Idle => "idle",
+            ImeCompositionState::Composing => "composing",
+            ImeCompositionState::Unknown => "unknown",
+        }
+    }
+}
+
+/// 焦点应用的输入法快照。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImeState {
+    pub composition: ImeCompositionState,
+    /// 平台输入源标识，例如 macOS 的 input source id 或 Windows 的 HKL。
+    pub input_source: Option<String>,
+}
+
+impl ImeState {
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+
+    pub fn composing() -> Self {
+        Self {
+            composition: ImeCompositionState::Composing,
+            input_source: None,
+        }
+    }
+
+    pub fn unknown() -> Self {
+        Self {
+            composition: ImeCompositionState::Unknown,
+            input_source: None,
+        }
+    }
+
+    pub fn with_input_source<S: Into<String>>(mut self, source: S) -> Self {
+        self.input_source = Some(source.into());
+        self
+    }
+
+    /// 当前状态下直接插入是否可能与组合串交错。
+    pub fn may_interleave(&self) -> bool {
+        matches!(
+            self.composition,
+            ImeCompositionState::Composing | ImeCompositionState::Unknown
+        )
+    }
+}
+
+/// 已知的 CJK 输入法输入源前缀（macOS）。
+const CJK_INPUT_SOURCE_PREFIXES: &[&str] = &[
+    "com.apple.inputmethod.SCIM",
+    "com.apple.inputmethod.TCIM",
+    "com.apple.inputmethod.TYIM",
+    "com.apple.inputmethod.Kotoeri",
+    "com.apple.inputmethod.Korean",
+    "com.apple.inputmethod.ChineseHandwriting",
+    "com.sogou.inputmethod",
+    "com.baidu.inputmethod",
+    "com.tencent.inputmethod",
+    "com.google.inputmethod.Japanese",
+    "im.rime.inputmethod",
+];
+
+/// Windows 键盘布局中对应 CJK 语言的 LANGID。
+const CJK_LANG_IDS: &[u16] = &[0x0404, 0x0804, 0x0c04, 0x1004, 0x0411, 0x0412];
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn is_cjk_input_source(source: &str) -> bool {
+    CJK_INPUT_SOURCE_PREFIXES
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn is_cjk_lang_id(lang_id: u16) -> bool {
+    CJK_LANG_IDS.contains(&lang_id)
+}
+
+/// 读取系统焦点应用的输入法状态。
+pub(crate) fn probe_system_ime() -> ImeState {
+    platform::probe()
+}
+
+/// 请求系统提交焦点应用的组合串。
+pub(crate) fn commit_system_composition() -> Result<(), String> {
+    platform::commit()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{is_cjk_input_source, ImeCompositionState, ImeState};
+    use std::ffi::{c_char, c_void, CStr};
+
+    type CFTypeRef = *const c_void;
+    type CFStringRef = *const c_void;
+    type CFIndex = isize;
+
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        static kTISPropertyInputSourceID: CFStringRef;
+        fn TISCopyCurrentKeyboardInputSource() -> CFTypeRef;
+        fn TISGetInputSourceProperty(source: CFTypeRef, key: CFStringRef) -> *const c_void;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringGetCString(
+            string: CFStringRef,
+            buffer: *mut c_char,
+            size: CFIndex,
+            encoding: u32,
+        ) -> u8;
+        fn CFRelease(value: CFTypeRef);
+    }
+
+    fn current_input_source() -> Option<String> {
+        // SAFETY: TIS 返回的 source 由 Copy 规则持有，读取属性后释放；属性本身为借用引用。
+        unsafe {
+            let source = TISCopyCurrentKeyboardInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let id = TISGetInputSourceProperty(source, kTISPropertyInputSourceID);
+            let mut buffer = [0 as c_char; 256];
+            let ok = !id.is_null()
+                && CFStringGetCString(
+                    id,
+                    buffer.as_mut_ptr(),
+                    buffer.len() as CFIndex,
+                    CF_STRING_ENCODING_UTF8,
+                ) != 0;
+            CFRelease(source);
+            if ok {
+                Some(
+                    CStr::from_ptr(buffer.as_ptr())
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            } else {
+                None
+            }
+        }
+    }
+
+    pub(super) fn probe() -> ImeState {
+        match current_input_source() {
+            // macOS 不对外暴露其他进程的 marked text，只能保守地视为未知。
+            Some(source) if is_cjk_input_source(&source) => ImeState {
+                composition: ImeCompositionState::Unknown,
+                input_source: Some(source),
+            },
+            Some(source) => ImeState::inactive().with_input_source(source),
+            None => ImeState::inactive(),
+        }
+    }
+
+    pub(super) fn commit() -> Result<(), String> {
+        Err("macOS 不支持跨进程提交输入法组合串".to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{is_cjk_lang_id, ImeCompositionState, ImeState};
+    use std::ffi::c_void;
+    use std::ptr;
+
+    type Hwnd = *mut c_void;
+    type Himc = *mut c_void;
+
+    const WM_IME_CONTROL: u32 = 0x0283;
+    const IMC_GETOPENSTATUS: usize = 0x0005;
+    const GCS_COMPSTR: u32 = 0x0008;
+    const NI_COMPOSITIONSTR: u32 = 0x0015;
+    const CPS_COMPLETE: u32 = 0x0001;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> Hwnd;
+        fn GetWindowThreadProcessId(hwnd: Hwnd, process_id: *mut u32) -> u32;
+        fn GetKeyboardLayout(thread_id: u32) -> isize;
+        fn SendMessageW(hwnd: Hwnd, msg: u32, wparam: usize, lparam: isize) -> isize;
+    }
+
+    #[link(name = "imm32")]
+    extern "system" {
+        fn ImmGetDefaultIMEWnd(hwnd: Hwnd) -> Hwnd;
+        fn ImmGetContext(hwnd: Hwnd) -> Himc;
+        fn ImmReleaseContext(hwnd: Hwnd, himc: Himc) -> i32;
+        fn ImmGetCompositionStringW(himc: Himc, index: u32, buffer: *mut c_void, len: u32) -> i32;
+        fn ImmNotifyIME(himc: Himc, action: u32, index: u32, value: u32) -> i32;
+    }
+
+    pub(super) fn probe() -> ImeState {
+        // SAFETY: 仅调用只读查询 API，所有句柄均在使用前判空并及时释放。
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_null() {
+                return ImeState::inactive();
+            }
+
+            let thread = GetWindowThreadProcessId(hwnd, ptr::null_mut());
+            let layout = GetKeyboardLayout(thread);
+            let lang_id = (layout as usize & 0xffff) as u16;
+            let input_source = format!("hkl:{:08x}", layout as usize);
+            if !is_cjk_lang_id(lang_id) {
+                return ImeState::inactive().with_input_source(input_source);
+            }
+
+            let ime_wnd = ImmGetDefaultIMEWnd(hwnd);
+            let open = !ime_wnd.is_null()
+                && SendMessageW(ime_wnd, WM_IME_CONTROL, IMC_GETOPENSTATUS, 0) != 0;
+            if !open {
+                return ImeState::inactive().with_input_source(input_source);
+            }
+
+            // 跨进程时 ImmGetContext 返回空句柄，此时无法读取组合串。
+            let himc = ImmGetContext(hwnd);
+            if himc.is_null() {
+                return ImeState {
+                    composition: ImeCompositionState::Unknown,
+                    input_source: Some(input_source),
+                };
+            }
+            let pending = ImmGetCompositionStringW(himc, GCS_COMPSTR, ptr::null_mut(), 0);
+            ImmReleaseContext(hwnd, himc);
+
+            let composition = if pending > 0 {
+                ImeCompositionState::Composing
+            } else {
+                ImeCompositionState::Idle
+            };
+            ImeState {
+                composition,
+                input_source: Some(input_source),
+            }
+        }
+    }
+
+    pub(super) fn commit() -> Result<(), String> {
+        // SAFETY: 句柄在使用前判空，输入上下文在通知后立即释放。
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_null() {
+                return Err("未找到焦点窗口".to_string());
+            }
+            let himc = ImmGetContext(hwnd);
+            if himc.is_null() {
+                return Err("无法获取焦点窗口的输入法上下文".to_string());
+            }
+            let committed = ImmNotifyIME(himc, NI_COMPOSITIONSTR, CPS_COMPLETE, 0) != 0;
+            ImmReleaseContext(hwnd, himc);
+            if committed {
+                Ok(())
+            } else {
+                Err("输入法拒绝提交组合串".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::ImeState;
+
+    pub(super) fn probe() -> ImeState {
+        ImeState::inactive()
+    }
+
+    pub(super) fn commit() -> Result<(), String> {
+        Err("当前平台不支持提交输入法组合串".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_cjk_input_sources() {
+        assert!(is_cjk_input_source("com.apple.inputmethod.SCIM.ITABC"));
+        assert!(is_cjk_input_source("com.sogou.inputmethod.sogou.pinyin"));
+        assert!(!is_cjk_input_source("com.apple.keylayout.US"));
+
+        assert!(is_cjk_lang_id(0x0804));
+        assert!(is_cjk_lang_id(0x0411));
+        assert!(!is_cjk_lang_id(0x0409));
+    }
+
+    #[test]
+    fn only_composing_or_unknown_states_may_interleave() {
+        assert!(ImeState::composing().may_interleave());
+        assert!(ImeState::unknown().may_interleave());
+        assert!(!ImeState::inactive().may_interleave());
+        let idle = ImeState {
+            composition: ImeCompositionState::Idle,
+            input_source: None,
+        };
+        assert!(!idle.may_interleave());
+    }
+}
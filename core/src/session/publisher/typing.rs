@@ -0,0 +1,293 @@
+//! 模拟键入发布：按可配置的字符速率逐段发送合成按键，适用于拒绝程序化粘贴的应用。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{
+    FocusAutomation, FocusWindowContext, PublishOutcome, PublishRequest, PublishStrategy,
+    PublisherConfig, PublisherError, PublisherFailure, PublisherFailureCode, SessionPublisher,
+};
+
+/// 每段按键覆盖的时长；速率越高每段字符越多，以减少自动化调用次数。
+const TYPING_TICK: Duration = Duration::from_millis(50);
+
+/// 模拟键入的默认节奏与按应用的特殊处理。
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypingConfig {
+    pub chars_per_second: f32,
+    pub quirks: Vec<TypingQuirk>,
+}
+
+impl Default for TypingConfig {
+    fn default() -> Self {
+        Self {
+            chars_per_second: 40.0,
+            quirks: Vec::new(),
+        }
+    }
+}
+
+impl TypingConfig {
+    pub fn quirk_for(&self, focus: &FocusWindowContext) -> Option<&TypingQuirk> {
+        let app = focus.app_identifier.as_deref()?;
+        self.quirks
+            .iter()
+            .find(|quirk| quirk.app_identifier.eq_ignore_ascii_case(app))
+    }
+}
+
+/// 单个应用的键入特殊处理。
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypingQuirk {
+    pub app_identifier: String,
+    /// 覆盖默认速率，适用于会丢失快速按键的应用。
+    pub chars_per_second: Option<f32>,
+    /// 输入法安全模式：逐字发送，并在每个字符前提交残留的组合串。
+    pub ime_safe: bool,
+}
+
+impl TypingQuirk {
+    pub fn new(app_identifier: impl Into<String>) -> Self {
+        Self {
+            app_identifier: app_identifier.into(),
+            chars_per_second: None,
+            ime_safe: false,
+        }
+    }
+}
+
+/// 以模拟键入方式插入文本。键入中途失败不会重试，避免重复输入已发送的内容。
+pub struct TypingPublisher {
+    config: PublisherConfig,
+    automation: Arc<dyn FocusAutomation>,
+}
+
+impl TypingPublisher {
+    pub fn new(config: PublisherConfig, automation: Arc<dyn FocusAutomation>) -> Self {
+        Self { config, automation }
+    }
+
+    pub async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
+        request.validate()?;
+        let timeout = self.config.direct_insert_timeout;
+
+        let capabilities = match self.automation.inspect_focus(&request.focus, timeout).await {
+            Ok(capabilities) => capabilities,
+            Err(error) => return Ok(failed(PublisherFailure::from_automation_error(error))),
+        };
+        if !capabilities.is_writable {
+            let reason = capabilities
+                .reason
+                .unwrap_or_else(|| "focus target rejected automation".to_string());
+            return Ok(failed(PublisherFailure::new(
+                PublisherFailureCode::AutomationRejected,
+                reason,
+            )));
+        }
+        if !capabilities.supports_keystroke_injection {
+            return Ok(failed(PublisherFailure::new(
+                PublisherFailureCode::ChannelUnavailable,
+                "focus target does not accept synthetic keystrokes",
+            )));
+        }
+
+        let typing = &self.config.typing;
+        let quirk = typing.quirk_for(&request.focus);
+        let ime_safe = quirk.is_some_and(|quirk| quirk.ime_safe);
+        let chars_per_second = quirk
+            .and_then(|quirk| quirk.chars_per_second)
+            .unwrap_or(typing.chars_per_second)
+            .max(1.0);
+        let chunk_chars = if ime_safe {
+            1
+        } else {
+            ((chars_per_second * TYPING_TICK.as_secs_f32()).round() as usize).max(1)
+        };
+
+        let chars: Vec<char> = request.transcript.chars().collect();
+        let mut chunks = chars.chunks(chunk_chars).peekable();
+        while let Some(chunk) = chunks.next() {
+            if ime_safe {
+                if let Some(failure) = self.commit_pending_composition(&request.focus).await {
+                    return Ok(failed(failure));
+                }
+            }
+            let text: String = chunk.iter().collect();
+            if let Err(error) = self.automation.simulate_keystrokes(&text, timeout).await {
+                return Ok(failed(PublisherFailure::from_automation_error(error)));
+            }
+            if chunks.peek().is_some() {
+                tokio::time::sleep(Duration::from_secs_f32(
+                    chunk.len() as f32 / chars_per_second,
+                ))
+                .await;
+            }
+        }
+
+        Ok(PublishOutcome::completed_with_attempts(
+            PublishStrategy::SimulatedTyping,
+            1,
+        ))
+    }
+
+    async fn commit_pending_composition(
+        &self,
+        focus: &FocusWindowContext,
+    ) -> Option<PublisherFailure> {
+        let timeout = self.config.direct_insert_timeout;
+        let state = self.automation.inspect_ime(focus, timeout).await.ok()?;
+        if !state.may_interleave() {
+            return None;
+        }
+        let error = self.automation.commit_composition(timeout).await.err()?;
+        Some(PublisherFailure::with_error(
+            PublisherFailureCode::ImeCompositionActive,
+            "input method composition could not be committed before typing",
+            error,
+        ))
+    }
+}
+
+fn failed(failure: PublisherFailure) -> PublishOutcome {
+    PublishOutcome::failed(1, PublishStrategy::SimulatedTyping, None, failure)
+}
+
+#[async_trait]
+impl SessionPublisher for TypingPublisher {
+    async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
+        TypingPublisher::publish(self, request).await
+    }
+
+    async fn undo(&self, transcript: &str) -> Result<(), PublisherError> {
+        self.automation
+            .remove_inserted(transcript, self.config.direct_insert_timeout)
+            .await
+            .map_err(PublisherError::UndoFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::publisher::{
+        AutomationError, FallbackStrategy, FocusCapabilities, ImeState, InsertionMethod, Publisher,
+        PublisherStatus,
+    };
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    #[derive(Default)]
+    struct RecordingAutomation {
+        keystrokes: Mutex<Vec<String>>,
+        composing: Mutex<bool>,
+        commits: Mutex<u32>,
+    }
+
+    #[async_trait]
+    impl FocusAutomation for RecordingAutomation {
+        async fn inspect_focus(
+            &self,
+            _context: &FocusWindowContext,
+            _timeout: Duration,
+        ) -> Result<FocusCapabilities, AutomationError> {
+            Ok(FocusCapabilities::writable_with_keystroke())
+        }
+
+        async fn paste_via_clipboard(
+            &self,
+            _contents: &str,
+            _timeout: Duration,
+        ) -> Result<(), AutomationError> {
+            Err(AutomationError::channel_unavailable("paste rejected"))
+        }
+
+        async fn simulate_keystrokes(
+            &self,
+            contents: &str,
+            _timeout: Duration,
+        ) -> Result<(), AutomationError> {
+            self.keystrokes.lock().unwrap().push(contents.to_string());
+            // 输入法会把键入的字符重新拉起组合态。
+            *self.composing.lock().unwrap() = true;
+            Ok(())
+        }
+
+        async fn inspect_ime(
+            &self,
+            _context: &FocusWindowContext,
+            _timeout: Duration,
+        ) -> Result<ImeState, AutomationError> {
+            Ok(if *self.composing.lock().unwrap() {
+                ImeState::composing()
+            } else {
+                ImeState::inactive()
+            })
+        }
+
+        async fn commit_composition(&self, _timeout: Duration) -> Result<(), AutomationError> {
+            *self.composing.lock().unwrap() = false;
+            *self.commits.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    fn request(transcript: &str, app: &str) -> PublishRequest {
+        PublishRequest {
+            transcript: transcript.to_string(),
+            focus: FocusWindowContext::from_app_identifier(app),
+            fallback: FallbackStrategy::default(),
+            insertion: InsertionMethod::SimulatedTyping,
+            strategy: None,
+            html: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn types_in_paced_chunks_through_the_publisher() {
+        let automation = Arc::new(RecordingAutomation::default());
+        let mut config = PublisherConfig::default();
+        config.typing.chars_per_second = 200.0;
+        let publisher = Publisher::new(config, automation.clone());
+
+        let started = Instant::now();
+        let outcome = publisher
+            .publish(request("abcdefghijklmnopqrstuvwxy", "com.example.terminal"))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert_eq!(outcome.strategy, PublishStrategy::SimulatedTyping);
+        assert_eq!(
+            *automation.keystrokes.lock().unwrap(),
+            vec!["abcdefghij", "klmnopqrst", "uvwxy"]
+        );
+        assert!(started.elapsed() >= Duration::from_millis(90));
+        assert_eq!(*automation.commits.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn ime_safe_quirk_types_one_character_at_a_time() {
+        let automation = Arc::new(RecordingAutomation::default());
+        let mut config = PublisherConfig::default();
+        config.typing.quirks.push(TypingQuirk {
+            chars_per_second: Some(500.0),
+            ime_safe: true,
+            ..TypingQuirk::new("com.tencent.xinwechat")
+        });
+        let publisher = TypingPublisher::new(config, automation.clone());
+
+        let outcome = publisher
+            .publish(request("你好ok", "com.tencent.xinWeChat"))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.status, PublisherStatus::Completed);
+        assert_eq!(
+            *automation.keystrokes.lock().unwrap(),
+            vec!["你", "好", "o", "k"]
+        );
+        assert_eq!(*automation.commits.lock().unwrap(), 3);
+    }
+}
@@ -0,0 +1,304 @@
+//! 实时转写：把采集到的音频转发给引擎，并把识别更新分发给界面、字幕与检查点。
+
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::time::{interval, timeout, Duration, MissedTickBehavior};
+use tracing::{info_span, warn, Instrument};
+
+use crate::audio::is_speech;
+use crate::orchestrator::{
+    RealtimeSessionConfig, RealtimeSessionHandle, TranscriptSource, TranscriptionUpdate,
+    UpdatePayload,
+};
+use crate::session::capture::{frame_duration, CaptureController, CaptureEvent, CaptureTrigger};
+use crate::session::checkpoint::{next_checkpoint, save_checkpoint};
+use crate::session::dispatch::UpdateDispatcher;
+use crate::session::duration::{persist_max_duration_stop, DurationGuard};
+use crate::session::publisher::FocusWindowContext;
+use crate::session::scripting::HookPoint;
+use crate::session::{
+    apply_capture_event, AutoStopReason, SessionAutoStop, SessionEvent, SessionManager,
+};
+
+impl SessionManager {
+    pub async fn start_realtime_transcription(
+        &self,
+        config: RealtimeSessionConfig,
+    ) -> (RealtimeSessionHandle, mpsc::Receiver<TranscriptionUpdate>) {
+        self.start_realtime_transcription_for(config, &FocusWindowContext::default())
+            .await
+    }
+
+    /// 以目标应用开始转写：未显式指定词表与润色风格时依次使用当前预设与该应用的偏好；
+    /// 未指定目标应用时使用预设的目标应用。`config.session_id` 为空时取当前活跃会话。
+    pub async fn start_realtime_transcription_for(
+        &self,
+        mut config: RealtimeSessionConfig,
+        focus: &FocusWindowContext,
+    ) -> (RealtimeSessionHandle, mpsc::Receiver<TranscriptionUpdate>) {
+        let preset_focus;
+        let mut focus = focus;
+        if let Some(preset) = self.active_preset() {
+            preset.apply_to_config(&mut config);
+            if focus.app_identifier.is_none() && preset.target_app.is_some() {
+                preset_focus = preset.focus();
+                focus = &preset_focus;
+            }
+        }
+        if config.vocabulary.is_none() {
+            config.vocabulary = self.vocabulary();
+            if let (Some(vocabulary), Some(profile)) =
+                (config.vocabulary.as_ref(), self.app_profile_for(focus))
+            {
+                config.vocabulary = Some(Arc::new(profile.vocabulary_subset(vocabulary)));
+            }
+        }
+        if config.polish_profile.is_none() {
+            config.polish_profile = self.polish_profile_for(focus);
+        }
+        if config.redaction.is_none() {
+            config.redaction = self.persistence.redactor();
+        }
+        if config.pre_polish.is_none() && self.scripts.has_hook(HookPoint::PrePolish) {
+            config.pre_polish = Some(self.scripts.pre_polish_hook(focus));
+        }
+        // 帧长由采集管线决定，会话按管线当前的帧窗口校验与调度。
+        (config.min_frame_duration, config.max_frame_duration) = self.audio.frame_window();
+        config.audio_source = self.audio.audio_source();
+        if config.session_id.is_none() {
+            config.session_id = self.active_session_id.lock().await.clone();
+        }
+        let session_id = config.session_id.clone().unwrap_or_else(|| {
+            warn!(
+                target: "session_manager",
+                "realtime transcription started without an active session id"
+            );
+            String::new()
+        });
+        if !config.egress.is_attached() {
+            config.egress = self.egress.for_session(&session_id);
+        }
+        self.audio_sources
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(session_id.clone(), config.audio_source);
+        let span = info_span!(target: "session_manager", "realtime_transcription", %session_id);
+        let (handle, mut rx) =
+            span.in_scope(|| self.orchestrator.start_realtime_session(config.clone()));
+        let frame_tx = handle.frame_sender();
+        let frame_window = handle.frame_window();
+        let mut frame_window_rx = self.audio.subscribe_frame_window();
+        let session_closed = handle.frame_sender();
+        *self
+            .transcript_commands
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(handle.command_sender());
+        self.spawn_background(async move {
+            loop {
+                tokio::select! {
+                    _ = session_closed.closed() => break,
+                    changed = frame_window_rx.recv() => match changed {
+                        Ok(changed) => frame_window.set(changed.min_frame, changed.max_frame),
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+        let mut pcm_rx = self
+            .audio
+            .subscribe_lossless_pcm_frames(config.buffer_capacity);
+        let audio = self.audio.clone();
+        let capture = Arc::clone(&self.capture);
+        let capture_tx = self.capture_tx.clone();
+        let max_duration = self.max_session_duration();
+        let event_tx = self.event_tx.clone();
+        let persistence = self.persistence.clone();
+        let partial_results = self.crash_guard.clone();
+        let updates_bus = self.update_tx.clone();
+        let flight_recorder = Arc::clone(&self.flight_recorder);
+        let frame_recorder = Arc::clone(&self.flight_recorder);
+        let update_session = session_id.clone();
+        let crash_guard = self.crash_guard.clone();
+        let checkpoints = self.persistence.clone();
+        let mut checkpoint_ticker = self.checkpoint_interval().map(|period| {
+            let mut ticker = interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker
+        });
+        let captions = self.captions.clone();
+        let speech_time = Arc::clone(&self.speech_time);
+        let speech_session = session_id.clone();
+        speech_time
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&speech_session);
+        let (client, client_rx) = UpdateDispatcher::channel(config.buffer_capacity, &self.shutdown);
+        let lane_subscribers = Arc::clone(&self.lane_subscribers);
+
+        let forwarder = tokio::spawn(
+            async move {
+                let mut forwarding = false;
+                let mut duration_guard = DurationGuard::new(max_duration);
+                let mut limit_reached = None;
+                'frames: while let Some(frame) = pcm_rx.recv().await {
+                    let (forward, event) = gate_capture_frame(&capture, &frame);
+                    if let Some(event) = event {
+                        apply_capture_event(&audio, &capture_tx, event);
+                    }
+                    let frames = match forward {
+                        Some(false) => {
+                            forwarding = false;
+                            continue;
+                        }
+                        // 录音刚开始：先补发按键前的预录音频。
+                        Some(true) if !forwarding => {
+                            forwarding = true;
+                            audio.take_preroll(frame)
+                        }
+                        _ => vec![frame],
+                    };
+                    for frame in frames {
+                        let duration = frame_duration(&frame);
+                        let speech = is_speech(&frame);
+                        duration_guard.record(duration);
+                        frame_recorder.record_frame(&speech_session, &frame, duration, speech);
+                        if !speech_session.is_empty() && speech {
+                            *speech_time
+                                .lock()
+                                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                .entry(speech_session.clone())
+                                .or_default() += duration;
+                        }
+                        if frame_tx.send(frame).await.is_err() {
+                            break 'frames;
+                        }
+                    }
+                    if let Some(warning) = duration_guard.take_warning() {
+                        let _ = event_tx.send(SessionEvent::DurationWarning(warning));
+                    }
+                    if let Some(limit) = duration_guard.reached() {
+                        limit_reached = Some(limit);
+                        break;
+                    }
+                }
+
+                if let Some(limit) = limit_reached {
+                    let _ = event_tx.send(SessionEvent::AutoStop(SessionAutoStop {
+                        reason: AutoStopReason::MaxDuration,
+                    }));
+                    let stopped = capture
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .as_mut()
+                        .and_then(|controller| controller.stop(CaptureTrigger::MaxDuration));
+                    if let Some(event) = stopped {
+                        let _ = capture_tx.send(event);
+                    }
+                    audio.reset_session();
+                    warn!(
+                        target: "session_manager",
+                        limit_ms = limit.as_millis() as u64,
+                        "session reached maximum duration; auto-stop triggered",
+                    );
+                    persist_max_duration_stop(&persistence, partial_results.in_flight(), limit)
+                        .await;
+                    return;
+                }
+
+                if let Err(err) = audio.flush_pending().await {
+                    warn!(
+                        target: "session_manager",
+                        %err,
+                        "failed to flush pending pcm frames",
+                    );
+                }
+
+                while let Ok(Some(frame)) = timeout(Duration::from_millis(100), pcm_rx.recv()).await
+                {
+                    if frame_tx.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            .instrument(span.clone()),
+        );
+
+        let forwarding_updates = tokio::spawn(
+            async move {
+                let mut dirty = false;
+                loop {
+                    let update = tokio::select! {
+                        update = rx.recv() => match update {
+                            Some(update) => update,
+                            None => break,
+                        },
+                        _ = next_checkpoint(&mut checkpoint_ticker) => {
+                            if std::mem::take(&mut dirty) {
+                                save_checkpoint(&checkpoints, crash_guard.in_flight()).await;
+                            }
+                            continue;
+                        }
+                    };
+                    crash_guard.record_frame(update.frame_index);
+                    flight_recorder.record_update(&update_session, &update);
+                    if let UpdatePayload::Transcript(transcript) = &update.payload {
+                        dirty = true;
+                        crash_guard.record_transcript(
+                            transcript.sentence_id,
+                            &transcript.text,
+                            matches!(transcript.source, TranscriptSource::Polished),
+                        );
+                        if matches!(transcript.source, TranscriptSource::Polished) {
+                            captions.publish(transcript.sentence_id, &transcript.text);
+                        }
+                    }
+                    if let Err(err) = updates_bus.send(update.clone()) {
+                        warn!(
+                            target: "session_manager",
+                            %err,
+                            "failed to broadcast session update"
+                        );
+                    }
+
+                    lane_subscribers
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .retain(|subscriber| subscriber.dispatch(update.clone()));
+                    if !client.dispatch(update) {
+                        break;
+                    }
+                }
+            }
+            .instrument(span),
+        );
+        let mut tasks = self
+            .realtime_tasks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        tasks.retain(|task| !task.is_finished());
+        tasks.extend([forwarder, forwarding_updates]);
+        drop(tasks);
+
+        (handle, client_rx)
+    }
+}
+
+/// 语音激活模式据帧能量切换录音状态；返回该帧是否应转发给转写会话，未接管录音时为 `None`。
+fn gate_capture_frame(
+    capture: &StdMutex<Option<CaptureController>>,
+    frame: &[f32],
+) -> (Option<bool>, Option<CaptureEvent>) {
+    let mut guard = capture
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match guard.as_mut() {
+        None => (None, None),
+        Some(controller) => {
+            let event = controller.observe_pcm(frame);
+            (Some(controller.is_capturing()), event)
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! 崩溃守护：panic 时落盘进行中的会话快照，下次启动时用于恢复。
+//! 进程被强制结束时来不及写入，此时依靠会话中定期写入持久层的检查点。
+//!
+//! 恢复文件含转写原文，以数据库密钥派生的密钥封装（与录音缓存相同的信封格式）；
+//! 数据库未加密时不落盘，只依靠检查点恢复。
+
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use ring::digest;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+use crate::telemetry::flush_tracing;
+
+const RECOVERY_FILE: &str = "last_session.sealed";
+const RECOVERY_AAD: &[u8] = b"flowwisper.recovery.v1";
+
+static HOOK_GUARD: OnceLock<CrashGuard> = OnceLock::new();
+
+/// 单句转写的恢复数据。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredSentence {
+    pub sentence_id: u64,
+    pub raw: String,
+    #[serde(default)]
+    pub polished: Option<String>,
+}
+
+/// 进行中会话的快照，崩溃时写入恢复文件。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverySnapshot {
+    pub session_id: String,
+    pub started_at_ms: i64,
+    #[serde(default)]
+    pub captured_at_ms: Option<i64>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub sentences: Vec<RecoveredSentence>,
+    /// 已转写到的最后一帧。
+    #[serde(default)]
+    pub frame_cursor: usize,
+}
+
+impl RecoverySnapshot {
+    fn new(session_id: String) -> Self {
+        Self {
+            session_id,
+            started_at_ms: now_ms(),
+            captured_at_ms: None,
+            reason: None,
+            sentences: Vec::new(),
+            frame_cursor: 0,
+        }
+    }
+
+    pub fn raw_transcript(&self) -> String {
+        self.sentences
+            .iter()
+            .map(|sentence| sentence.raw.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// 润色文本，缺失润色的句子回退为原始文本。
+    pub fn polished_transcript(&self) -> String {
+        self.sentences
+            .iter()
+            .map(|sentence| sentence.polished.as_deref().unwrap_or(&sentence.raw))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn record(&mut self, sentence_id: u64, text: &str, polished: bool) {
+        let index = match self
+            .sentences
+            .iter()
+            .position(|sentence| sentence.sentence_id == sentence_id)
+        {
+            Some(index) => index,
+            None => {
+                self.sentences.push(RecoveredSentence {
+                    sentence_id,
+                    raw: String::new(),
+                    polished: None,
+                });
+                self.sentences.len() - 1
+            }
+        };
+        let sentence = &mut self.sentences[index];
+        if polished {
+            sentence.polished = Some(text.to_string());
+        } else {
+            sentence.raw = text.to_string();
+        }
+    }
+}
+
+/// 跟踪进行中的会话，并在 panic 时将其写入恢复文件。
+#[derive(Clone)]
+pub struct CrashGuard {
+    path: PathBuf,
+    /// 与 panic hook 中的副本共享，历史库换密钥后可原地替换。
+    keys: Arc<RwLock<Option<AudioCacheKeys>>>,
+    in_flight: Arc<Mutex<Option<RecoverySnapshot>>>,
+}
+
+impl CrashGuard {
+    /// `dir` 为恢复文件所在目录。未调用 [`CrashGuard::with_key_material`] 时不写恢复文件。
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: dir.as_ref().join(RECOVERY_FILE),
+            keys: Arc::new(RwLock::new(None)),
+            in_flight: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 以 SQLCipher 密钥派生恢复文件的加密密钥。密钥长度不定，先做 SHA-256 再派生。
+    pub fn with_key_material(self, material: &str) -> Result<Self> {
+        self.rekey(material)?;
+        Ok(self)
+    }
+
+    /// 历史库换密钥后重新派生恢复文件密钥，所有克隆（含 panic hook 持有的）同时生效。
+    pub fn rekey(&self, material: &str) -> Result<()> {
+        let mut input = RECOVERY_AAD.to_vec();
+        input.extend_from_slice(material.as_bytes());
+        let master = digest::digest(&digest::SHA256, &input);
+        let keys = AudioCacheKeys::derive(master.as_ref())?;
+        *self
+            .keys
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(keys);
+        Ok(())
+    }
+
+    pub fn recovery_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn begin(&self, session_id: &str) {
+        *self.lock() = Some(RecoverySnapshot::new(session_id.to_string()));
+    }
+
+    pub fn record_transcript(&self, sentence_id: u64, text: &str, polished: bool) {
+        if let Some(snapshot) = self.lock().as_mut() {
+            snapshot.record(sentence_id, text, polished);
+        }
+    }
+
+    pub fn record_frame(&self, frame_index: usize) {
+        if let Some(snapshot) = self.lock().as_mut() {
+            snapshot.frame_cursor = snapshot.frame_cursor.max(frame_index);
+        }
+    }
+
+    pub fn clear(&self) {
+        *self.lock() = None;
+    }
+
+    pub fn in_flight(&self) -> Option<RecoverySnapshot> {
+        self.lock().clone()
+    }
+
+    /// 将进行中的会话加密写入恢复文件；没有进行中的会话或未配置密钥时返回 `false`。
+    pub fn write_recovery(&self, reason: &str) -> Result<bool> {
+        let keys = match self.keys.try_read() {
+            Ok(guard) => guard.clone(),
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().clone(),
+            Err(TryLockError::WouldBlock) => return Ok(false),
+        };
+        let Some(keys) = keys else {
+            return Ok(false);
+        };
+        let snapshot = match self.in_flight.try_lock() {
+            Ok(guard) => guard.clone(),
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().clone(),
+            // 持锁线程正在 panic，放弃写入以免死锁。
+            Err(TryLockError::WouldBlock) => return Ok(false),
+        };
+        let Some(mut snapshot) = snapshot else {
+            return Ok(false);
+        };
+        snapshot.captured_at_ms = Some(now_ms());
+        snapshot.reason = Some(reason.to_string());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| anyhow!("failed to create recovery directory: {err}"))?;
+        }
+        let encoded = serde_json::to_vec(&snapshot)
+            .map_err(|err| anyhow!("failed to encode recovery snapshot: {err}"))?;
+        let sealed = serde_json::to_vec(&seal_payload(&keys, RECOVERY_AAD, &encoded)?)
+            .map_err(|err| anyhow!("failed to encode recovery envelope: {err}"))?;
+        fs::write(&self.path, sealed)
+            .map_err(|err| anyhow!("failed to write recovery snapshot: {err}"))?;
+        Ok(true)
+    }
+
+    /// 读取并删除上次崩溃留下的恢复文件。
+    pub fn take_orphaned(&self) -> Result<Option<RecoverySnapshot>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read(&self.path)
+            .map_err(|err| anyhow!("failed to read recovery snapshot: {err}"))?;
+        fs::remove_file(&self.path)
+            .map_err(|err| anyhow!("failed to remove recovery snapshot: {err}"))?;
+        let keys = self
+            .keys
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let Some(keys) = keys else {
+            return Err(anyhow!(
+                "recovery snapshot is sealed but no key is configured"
+            ));
+        };
+        let envelope: SealedEnvelope = serde_json::from_slice(&contents)
+            .map_err(|err| anyhow!("failed to parse recovery envelope: {err}"))?;
+        let decoded = open_payload(&keys, RECOVERY_AAD, &envelope)?;
+        let snapshot = serde_json::from_slice(&decoded)
+            .map_err(|err| anyhow!("failed to parse recovery snapshot: {err}"))?;
+        Ok(Some(snapshot))
+    }
+
+    /// 安装进程级 panic hook：写入恢复文件、刷新遥测，再交给原有 hook。
+    /// 仅第一次调用生效。
+    pub fn install_panic_hook(&self) {
+        if HOOK_GUARD.set(self.clone()).is_err() {
+            return;
+        }
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Some(guard) = HOOK_GUARD.get() {
+                if let Err(err) = guard.write_recovery(&info.to_string()) {
+                    eprintln!("failed to write crash recovery snapshot: {err}");
+                }
+            }
+            flush_tracing();
+            previous(info);
+        }));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<RecoverySnapshot>> {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sealed_guard(dir: &Path, key: &str) -> CrashGuard {
+        CrashGuard::new(dir).with_key_material(key).unwrap()
+    }
+
+    #[test]
+    fn writes_and_recovers_in_flight_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = sealed_guard(&dir.path().join("recovery"), "db-secret");
+        assert!(!guard.write_recovery("idle").unwrap());
+
+        guard.begin("session-crash");
+        guard.record_transcript(1, "hello world", false);
+        guard.record_transcript(2, "second line", false);
+        guard.record_transcript(1, "Hello, world.", true);
+        guard.record_frame(12);
+        guard.record_frame(7);
+        assert!(guard.write_recovery("panicked at 'boom'").unwrap());
+
+        let recovered = guard.take_orphaned().unwrap().expect("snapshot written");
+        assert_eq!(recovered.session_id, "session-crash");
+        assert_eq!(recovered.reason.as_deref(), Some("panicked at 'boom'"));
+        assert_eq!(recovered.raw_transcript(), "hello world second line");
+        assert_eq!(recovered.polished_transcript(), "Hello, world. second line");
+        assert_eq!(recovered.frame_cursor, 12);
+        assert!(guard.take_orphaned().unwrap().is_none());
+    }
+
+    #[test]
+    fn clear_discards_in_flight_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = sealed_guard(dir.path(), "db-secret");
+        guard.begin("session-done");
+        guard.clear();
+        assert!(guard.in_flight().is_none());
+        assert!(!guard.write_recovery("late panic").unwrap());
+    }
+
+    #[test]
+    fn recovery_file_is_sealed_with_the_database_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = sealed_guard(dir.path(), "db-secret");
+        guard.begin("session-secret");
+        guard.record_transcript(1, "confidential launch plan", false);
+        assert!(guard.write_recovery("panic").unwrap());
+        let on_disk = fs::read(guard.recovery_path()).unwrap();
+        assert!(!String::from_utf8_lossy(&on_disk).contains("confidential"));
+
+        let other = sealed_guard(dir.path(), "other-secret");
+        assert!(other.take_orphaned().is_err());
+        assert!(!guard.recovery_path().exists());
+
+        let unkeyed = CrashGuard::new(dir.path());
+        unkeyed.begin("session-plain");
+        unkeyed.record_transcript(1, "confidential launch plan", false);
+        assert!(!unkeyed.write_recovery("panic").unwrap());
+        assert!(!unkeyed.recovery_path().exists());
+    }
+
+    #[test]
+    fn rekey_applies_to_every_clone() {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = sealed_guard(dir.path(), "old-secret");
+        let hook_copy = guard.clone();
+        guard.rekey("new-secret").unwrap();
+
+        hook_copy.begin("session-after-rotation");
+        hook_copy.record_transcript(1, "written after rotation", false);
+        assert!(hook_copy.write_recovery("panic").unwrap());
+
+        let restarted = sealed_guard(dir.path(), "new-secret");
+        let recovered = restarted
+            .take_orphaned()
+            .unwrap()
+            .expect("snapshot written");
+        assert_eq!(recovered.raw_transcript(), "written after rotation");
+    }
+}
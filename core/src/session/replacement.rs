@@ -0,0 +1,200 @@
+//! 文本替换 / 片段展开规则：在润色稿发布前按焦点应用替换。
+
+use anyhow::{anyhow, Result};
+use regex::{NoExpand, Regex};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::session::publisher::FocusWindowContext;
+
+/// 用户配置的单条替换规则。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacementRule {
+    pub rule_id: String,
+    /// 字面量（忽略大小写、按词边界匹配）或正则表达式。
+    pub pattern: String,
+    /// 正则规则中可使用 `$1` 等捕获组引用。
+    pub replacement: String,
+    #[serde(default)]
+    pub is_regex: bool,
+    /// 仅在该应用获得焦点时生效；为空时对所有应用生效。
+    #[serde(default)]
+    pub app_identifier: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ReplacementRule {
+    fn compile(&self) -> Result<Regex> {
+        if self.pattern.trim().is_empty() {
+            return Err(anyhow!("replacement pattern must not be empty"));
+        }
+        let source = if self.is_regex {
+            self.pattern.clone()
+        } else {
+            let literal = self.pattern.trim();
+            let starts_word = literal.chars().next().is_some_and(is_word_char);
+            let ends_word = literal.chars().last().is_some_and(is_word_char);
+            format!(
+                "(?i){}{}{}",
+                if starts_word { r"\b" } else { "" },
+                regex::escape(literal),
+                if ends_word { r"\b" } else { "" },
+            )
+        };
+        Regex::new(&source)
+            .map_err(|err| anyhow!("invalid replacement pattern {:?}: {err}", self.pattern))
+    }
+
+    /// 校验规则能否编译。
+    pub fn validate(&self) -> Result<()> {
+        self.compile().map(|_| ())
+    }
+
+    fn applies_to(&self, focus: &FocusWindowContext) -> bool {
+        match (&self.app_identifier, &focus.app_identifier) {
+            (None, _) => true,
+            (Some(scope), Some(app)) => scope.eq_ignore_ascii_case(app),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+struct CompiledRule {
+    rule: ReplacementRule,
+    regex: Regex,
+}
+
+/// 编译后的规则集合。应用专属规则先于全局规则执行，同组内按创建顺序执行。
+#[derive(Default)]
+pub struct ReplacementRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl ReplacementRules {
+    /// 跳过已停用的规则；无法编译的规则记录告警后忽略，避免单条坏规则阻断发布。
+    pub fn compile(rules: Vec<ReplacementRule>) -> Self {
+        let mut compiled: Vec<CompiledRule> = rules
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match rule.compile() {
+                Ok(regex) => Some(CompiledRule { rule, regex }),
+                Err(err) => {
+                    warn!(
+                        target: "session_manager",
+                        rule_id = %rule.rule_id,
+                        %err,
+                        "skipping invalid replacement rule"
+                    );
+                    None
+                }
+            })
+            .collect();
+        compiled.sort_by_key(|compiled| compiled.rule.app_identifier.is_none());
+        Self { rules: compiled }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn apply(&self, text: &str, focus: &FocusWindowContext) -> String {
+        let mut output = text.to_string();
+        for compiled in &self.rules {
+            if !compiled.rule.applies_to(focus) {
+                continue;
+            }
+            let replaced = if compiled.rule.is_regex {
+                compiled
+                    .regex
+                    .replace_all(&output, compiled.rule.replacement.as_str())
+            } else {
+                compiled
+                    .regex
+                    .replace_all(&output, NoExpand(&compiled.rule.replacement))
+            };
+            output = replaced.into_owned();
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(rule_id: &str, pattern: &str, replacement: &str) -> ReplacementRule {
+        ReplacementRule {
+            rule_id: rule_id.into(),
+            pattern: pattern.into(),
+            replacement: replacement.into(),
+            is_regex: false,
+            app_identifier: None,
+            enabled: true,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn expands_literals_and_regex_rules() {
+        let mut ticket = rule("ticket", r"ticket (\d+)", "FW-$1");
+        ticket.is_regex = true;
+        let rules = ReplacementRules::compile(vec![
+            rule("email", "my email", "me@example.com"),
+            ticket,
+            rule("price", "$5", "five dollars"),
+        ]);
+
+        let focus = FocusWindowContext::default();
+        assert_eq!(
+            rules.apply("Send My Email about ticket 42 for $5.", &focus),
+            "Send me@example.com about FW-42 for five dollars."
+        );
+        // 字面量按词边界匹配。
+        assert_eq!(rules.apply("my emails", &focus), "my emails");
+    }
+
+    #[test]
+    fn scopes_rules_to_focused_app_and_skips_invalid() {
+        let mut slack = rule("slack-sig", "sign off", "cheers :wave:");
+        slack.app_identifier = Some("com.tinyspeck.slackmacgap".into());
+        let mut disabled = rule("disabled", "sign off", "unused");
+        disabled.enabled = false;
+        let mut broken = rule("broken", "(", "x");
+        broken.is_regex = true;
+        assert!(broken.validate().is_err());
+
+        let rules = ReplacementRules::compile(vec![
+            rule("global-sig", "sign off", "Best regards"),
+            slack,
+            disabled,
+            broken,
+        ]);
+
+        assert_eq!(
+            rules.apply(
+                "sign off",
+                &FocusWindowContext::from_app_identifier("com.tinyspeck.slackmacgap")
+            ),
+            "cheers :wave:"
+        );
+        assert_eq!(
+            rules.apply("sign off", &FocusWindowContext::default()),
+            "Best regards"
+        );
+    }
+}
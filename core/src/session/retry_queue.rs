@@ -0,0 +1,426 @@
+//! 发布重试队列：直接插入与剪贴板降级均失败时，润色稿落盘保存，
+//! 待目标应用重新获得焦点或用户手动触发时再次插入。
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Mutex};
+use tracing::{info, warn};
+
+use crate::orchestrator::NoticeLevel;
+use crate::persistence::PersistenceHandle;
+use crate::session::history::SessionSnapshot;
+use crate::session::lifecycle::SessionLifecycleUpdate;
+use crate::session::publisher::{
+    FallbackStrategy, FocusWindowContext, InsertionMethod, PublishOutcome, PublishRequest,
+    PublishStrategy, PublisherFailure, PublisherFailureCode, PublisherStatus, SessionPublisher,
+};
+use crate::session::SessionManager;
+
+/// 焦点触发的自动重试次数上限；超过后只能由用户手动重试或丢弃。
+pub const MAX_AUTO_RETRY_ATTEMPTS: u32 = 5;
+
+/// 一条等待重新插入的润色稿。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishRetryEntry {
+    pub retry_id: String,
+    pub session_id: String,
+    pub transcript: String,
+    #[serde(default)]
+    pub app_identifier: Option<String>,
+    #[serde(default)]
+    pub window_title: Option<String>,
+    #[serde(default)]
+    pub insertion: InsertionMethod,
+    /// 入队后已执行的重试次数，不含最初的发布。
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// 重试成功后写入历史记录的会话快照。
+    pub snapshot: SessionSnapshot,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+impl PublishRetryEntry {
+    pub fn new(
+        snapshot: SessionSnapshot,
+        request: &PublishRequest,
+        last_error: Option<String>,
+    ) -> Self {
+        let now_ms = now_ms();
+        Self {
+            retry_id: format!("{}-retry-{now_ms}", snapshot.session_id),
+            session_id: snapshot.session_id.clone(),
+            transcript: request.transcript.clone(),
+            app_identifier: request.focus.app_identifier.clone(),
+            window_title: request.focus.window_title.clone(),
+            insertion: request.insertion,
+            attempts: 0,
+            last_error,
+            snapshot,
+            created_at_ms: now_ms,
+            updated_at_ms: now_ms,
+        }
+    }
+
+    /// 仅按应用标识匹配；未记录目标应用的条目不会被自动触发。
+    pub fn matches(&self, focus: &FocusWindowContext) -> bool {
+        match (&self.app_identifier, &focus.app_identifier) {
+            (Some(target), Some(app)) => target.eq_ignore_ascii_case(app),
+            _ => false,
+        }
+    }
+
+    /// 重试不再走剪贴板降级，避免反复覆盖用户的剪贴板；富文本目标只粘贴格式化后的纯文本。
+    pub fn request(&self) -> PublishRequest {
+        PublishRequest {
+            transcript: self.transcript.clone(),
+            focus: FocusWindowContext {
+                app_identifier: self.app_identifier.clone(),
+                window_title: self.window_title.clone(),
+                ..FocusWindowContext::default()
+            },
+            fallback: FallbackStrategy::None,
+            insertion: self.insertion,
+            strategy: None,
+            html: None,
+        }
+    }
+}
+
+/// 执行队列中的重试；同一时刻只处理一条，防止焦点触发与手动重试重复插入。
+#[derive(Clone)]
+pub struct PublishRetrier {
+    publisher: Arc<dyn SessionPublisher>,
+    persistence: PersistenceHandle,
+    lifecycle_tx: broadcast::Sender<SessionLifecycleUpdate>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl PublishRetrier {
+    pub fn new(
+        publisher: Arc<dyn SessionPublisher>,
+        persistence: PersistenceHandle,
+        lifecycle_tx: broadcast::Sender<SessionLifecycleUpdate>,
+    ) -> Self {
+        Self {
+            publisher,
+            persistence,
+            lifecycle_tx,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub async fn enqueue(&self, entry: PublishRetryEntry) -> Result<()> {
+        info!(
+            target: "session_manager",
+            retry_id = %entry.retry_id,
+            session_id = %entry.session_id,
+            "queued stranded transcript for retry"
+        );
+        self.persistence.save_publish_retry(entry).await
+    }
+
+    pub async fn pending(&self) -> Result<Vec<PublishRetryEntry>> {
+        self.persistence.list_publish_retries().await
+    }
+
+    pub async fn discard(&self, retry_id: &str) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+        self.persistence
+            .remove_publish_retry(retry_id.to_string())
+            .await
+    }
+
+    /// 重试所有以 `focus` 所在应用为目标且未超过自动重试上限的条目。
+    pub async fn retry_for_focus(&self, focus: &FocusWindowContext) -> Result<usize> {
+        let mut completed = 0;
+        for entry in self.pending().await? {
+            if !entry.matches(focus) || entry.attempts >= MAX_AUTO_RETRY_ATTEMPTS {
+                continue;
+            }
+            if let Some(outcome) = self.retry(&entry.retry_id).await? {
+                if outcome.status != PublisherStatus::Failed {
+                    completed += 1;
+                }
+            }
+        }
+        Ok(completed)
+    }
+
+    /// 立即重试一条记录；条目已被处理或丢弃时返回 `None`。
+    pub async fn retry(&self, retry_id: &str) -> Result<Option<PublishOutcome>> {
+        let _guard = self.lock.lock().await;
+        let Some(mut entry) = self
+            .pending()
+            .await?
+            .into_iter()
+            .find(|entry| entry.retry_id == retry_id)
+        else {
+            return Ok(None);
+        };
+
+        entry.attempts += 1;
+        let attempt = u8::try_from(entry.attempts.saturating_add(1)).unwrap_or(u8::MAX);
+        let strategy = if entry.insertion == InsertionMethod::SimulatedTyping {
+            PublishStrategy::SimulatedTyping
+        } else {
+            PublishStrategy::DirectInsert
+        };
+        self.emit(SessionLifecycleUpdate::publishing(
+            &entry.session_id,
+            attempt,
+            strategy.clone(),
+            None,
+        ));
+
+        let outcome = match self.publisher.publish(entry.request()).await {
+            Ok(outcome) => outcome,
+            Err(err) => PublishOutcome::failed(
+                1,
+                strategy,
+                None,
+                PublisherFailure::new(PublisherFailureCode::Unknown, err.to_string()),
+            ),
+        };
+
+        if outcome.status == PublisherStatus::Failed {
+            let message = outcome
+                .failure
+                .as_ref()
+                .map(|failure| failure.message.clone())
+                .unwrap_or_else(|| "publisher reported failure".to_string());
+            let code = outcome
+                .failure
+                .as_ref()
+                .map(|failure| failure.code.as_str().to_string());
+            entry.last_error = Some(message.clone());
+            entry.updated_at_ms = now_ms();
+            self.emit(SessionLifecycleUpdate::failed(
+                &entry.session_id,
+                attempt,
+                message,
+                code,
+                None,
+            ));
+            self.persistence.save_publish_retry(entry).await?;
+            return Ok(Some(outcome));
+        }
+
+        self.persistence
+            .remove_publish_retry(entry.retry_id.clone())
+            .await?;
+        self.emit(SessionLifecycleUpdate::completed(
+            &entry.session_id,
+            outcome.clone(),
+        ));
+        if let Err(err) = self.persistence.persist_session(entry.snapshot).await {
+            warn!(
+                target: "session_manager",
+                %err,
+                session_id = %entry.session_id,
+                "failed to persist retried transcript"
+            );
+        }
+        Ok(Some(outcome))
+    }
+
+    /// 供手动重试使用，未知编号返回错误。
+    pub async fn retry_now(&self, retry_id: &str) -> Result<PublishOutcome> {
+        self.retry(retry_id)
+            .await?
+            .ok_or_else(|| anyhow!("unknown or already completed publish retry {retry_id}"))
+    }
+
+    fn emit(&self, update: SessionLifecycleUpdate) {
+        // 没有订阅者时发送失败属正常情况。
+        let _ = self.lifecycle_tx.send(update);
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+impl SessionManager {
+    /// 桌面端焦点监听上报前台应用；仅在应用切换时通知重试队列，窗口标题变化不会重复触发。
+    pub fn report_focus(&self, focus: FocusWindowContext) {
+        self.focus_tx.send_if_modified(|current| {
+            let changed = match (&current.app_identifier, &focus.app_identifier) {
+                (Some(previous), Some(next)) => !previous.eq_ignore_ascii_case(next),
+                (previous, next) => previous != next,
+            };
+            *current = focus;
+            changed
+        });
+    }
+
+    /// 直接插入与降级都失败时保存润色稿，等待焦点触发或手动重试。
+    pub(super) async fn queue_publish_retry(
+        &self,
+        snapshot: SessionSnapshot,
+        request: &PublishRequest,
+        outcome: &PublishOutcome,
+    ) {
+        let last_error = outcome
+            .failure
+            .as_ref()
+            .map(|failure| failure.message.clone());
+        let entry = PublishRetryEntry::new(snapshot, request, last_error);
+        match self.publish_retry.enqueue(entry).await {
+            Ok(()) => self.emit_notice(
+                NoticeLevel::Warn,
+                "润色稿未能插入，已保存到重试队列，目标应用重新获得焦点时将自动重试。",
+            ),
+            Err(err) => warn!(
+                target: "session_manager",
+                %err,
+                "failed to queue publish retry"
+            ),
+        }
+    }
+
+    pub async fn pending_publish_retries(&self) -> Result<Vec<PublishRetryEntry>> {
+        self.publish_retry.pending().await
+    }
+
+    /// 立即重试一条滞留的润色稿，不受自动重试次数上限限制。
+    pub async fn retry_publish_now(&self, retry_id: &str) -> Result<PublishOutcome> {
+        self.publish_retry.retry_now(retry_id).await
+    }
+
+    pub async fn discard_publish_retry(&self, retry_id: &str) -> Result<bool> {
+        self.publish_retry.discard(retry_id).await
+    }
+
+    pub(super) fn spawn_publish_retry_worker(&self) {
+        if self.publish_retry_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let retrier = self.publish_retry.clone();
+        let mut focus_rx = self.focus_tx.subscribe();
+        self.spawn_background(async move {
+            while focus_rx.changed().await.is_ok() {
+                let focus = focus_rx.borrow_and_update().clone();
+                if let Err(err) = retrier.retry_for_focus(&focus).await {
+                    warn!(
+                        target: "session_manager",
+                        %err,
+                        "publish retry on focus change failed"
+                    );
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator};
+    use crate::session::clipboard::{ClipboardError, ClipboardManager};
+    use crate::session::lifecycle::{SessionLifecyclePayload, SessionLifecyclePhase};
+    use crate::session::tests::{
+        make_snapshot, ProgrammedSpeechEngine, RecordingClipboard, ToggledPublisher,
+    };
+    use std::time::Duration;
+    use tokio::time::timeout;
+    #[tokio::test]
+    async fn stranded_publish_is_queued_and_retried_on_refocus() {
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+        );
+        let publisher = ToggledPublisher::default();
+        let clipboard_access = RecordingClipboard::default();
+        clipboard_access
+            .set_write_error(ClipboardError::write("permission denied"))
+            .await;
+        let manager = SessionManager::with_components(
+            orchestrator,
+            Arc::new(publisher.clone()),
+            ClipboardManager::new(Arc::new(clipboard_access)),
+        );
+        let target = FocusWindowContext::from_app_identifier("com.example.RetryTarget");
+
+        let outcome = manager
+            .publish_transcript(
+                make_snapshot("session-retry-queue", "raw", "polished"),
+                PublishRequest {
+                    transcript: "polished".into(),
+                    focus: target.clone(),
+                    fallback: FallbackStrategy::ClipboardCopy,
+                    insertion: InsertionMethod::default(),
+                    strategy: None,
+                    html: None,
+                },
+            )
+            .await
+            .expect("publish should surface failure");
+        assert_eq!(outcome.status, PublisherStatus::Failed);
+
+        async fn pending(manager: &SessionManager) -> Vec<PublishRetryEntry> {
+            manager
+                .pending_publish_retries()
+                .await
+                .expect("retry queue readable")
+                .into_iter()
+                .filter(|entry| entry.session_id == "session-retry-queue")
+                .collect()
+        }
+        let queued = pending(&manager).await;
+        assert_eq!(queued.len(), 1);
+        let retry_id = queued[0].retry_id.clone();
+        assert_eq!(queued[0].transcript, "polished");
+
+        let retried = manager
+            .retry_publish_now(&retry_id)
+            .await
+            .expect("manual retry runs");
+        assert_eq!(retried.status, PublisherStatus::Failed);
+        let queued = pending(&manager).await;
+        assert_eq!(queued[0].attempts, 1);
+        assert_eq!(queued[0].last_error.as_deref(), Some("focus target lost"));
+
+        let mut lifecycle = manager.subscribe_lifecycle();
+        publisher.ready.store(true, Ordering::SeqCst);
+        manager.spawn_publish_retry_worker();
+        manager.report_focus(FocusWindowContext::from_app_identifier("com.example.Other"));
+        manager.report_focus(FocusWindowContext::from_app_identifier(
+            "com.example.retrytarget",
+        ));
+
+        let mut phases = Vec::new();
+        while phases.last() != Some(&SessionLifecyclePhase::Completed) {
+            let update = timeout(Duration::from_secs(2), lifecycle.recv())
+                .await
+                .expect("retry lifecycle update")
+                .expect("lifecycle channel open");
+            if let SessionLifecyclePayload::Publishing(payload) = &update.payload {
+                assert_eq!(payload.attempt, 3);
+            }
+            phases.push(update.phase);
+        }
+        assert_eq!(
+            phases,
+            vec![
+                SessionLifecyclePhase::Publishing,
+                SessionLifecyclePhase::Completed
+            ]
+        );
+        assert_eq!(*publisher.published.lock().unwrap(), vec!["polished"]);
+        assert!(pending(&manager).await.is_empty());
+        assert!(manager.retry_publish_now(&retry_id).await.is_err());
+    }
+}
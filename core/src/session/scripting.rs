@@ -0,0 +1,323 @@
+//! 自动化脚本：在润色前、发布前与发布后执行用户注册的 Rhai 脚本，无需重新编译即可自定义改写。
+//!
+//! 脚本可读取 `text`、`app`、`window_title`、`field_role`，发布后钩子另有 `status`。
+//! 脚本返回字符串或修改 `text` 即改写文本；执行出错时记录告警并保留原文。
+//! 需要启用 `scripting` 特性，否则注册脚本会返回错误。
+
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::orchestrator::PrePolishHook;
+use crate::session::publisher::{FocusWindowContext, PublishOutcome};
+
+/// 单个脚本允许执行的最大操作数，防止死循环卡住会话。
+#[cfg(feature = "scripting")]
+const MAX_SCRIPT_OPERATIONS: u64 = 100_000;
+#[cfg(feature = "scripting")]
+const MAX_SCRIPT_STRING_SIZE: usize = 64 * 1024;
+
+/// 脚本挂载的时机。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPoint {
+    /// 每句原始稿进入润色器之前。
+    PrePolish,
+    /// 润色稿插入目标应用之前。
+    PrePublish,
+    /// 发布结束后，返回值被忽略。
+    PostPublish,
+}
+
+/// 用户注册的脚本。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationScript {
+    pub name: String,
+    pub hook: HookPoint,
+    pub source: String,
+    /// 仅在这些应用中执行；为空时对所有应用生效。
+    #[serde(default)]
+    pub apps: Vec<String>,
+}
+
+impl AutomationScript {
+    pub fn new(name: impl Into<String>, hook: HookPoint, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            hook,
+            source: source.into(),
+            apps: Vec::new(),
+        }
+    }
+
+    fn applies_to(&self, focus: &FocusWindowContext) -> bool {
+        self.apps.is_empty()
+            || focus.app_identifier.as_deref().is_some_and(|app| {
+                self.apps
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(app))
+            })
+    }
+}
+
+struct CompiledScript {
+    script: AutomationScript,
+    #[cfg(feature = "scripting")]
+    ast: rhai::AST,
+}
+
+/// 已注册脚本的集合，克隆之间共享。
+#[derive(Clone)]
+pub struct ScriptHost {
+    scripts: Arc<RwLock<Vec<CompiledScript>>>,
+    #[cfg(feature = "scripting")]
+    engine: Arc<rhai::Engine>,
+}
+
+impl std::fmt::Debug for ScriptHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptHost")
+            .field("scripts", &self.scripts().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self {
+            scripts: Arc::new(RwLock::new(Vec::new())),
+            #[cfg(feature = "scripting")]
+            engine: Arc::new(Self::build_engine()),
+        }
+    }
+}
+
+impl ScriptHost {
+    #[cfg(feature = "scripting")]
+    fn build_engine() -> rhai::Engine {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        engine.set_max_string_size(MAX_SCRIPT_STRING_SIZE);
+        engine.set_max_call_levels(32);
+        engine.on_print(|message| {
+            tracing::info!(target: "session_scripts", "{message}");
+        });
+        engine.on_debug(|message, _, position| {
+            tracing::debug!(target: "session_scripts", %position, "{message}");
+        });
+        engine
+    }
+
+    /// 编译并注册脚本，同名脚本会被替换。
+    pub fn register(&self, script: AutomationScript) -> Result<()> {
+        if script.name.trim().is_empty() {
+            return Err(anyhow!("automation script name must not be empty"));
+        }
+        let compiled = self.compile(script)?;
+        let mut scripts = self
+            .scripts
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        scripts.retain(|existing| existing.script.name != compiled.script.name);
+        scripts.push(compiled);
+        Ok(())
+    }
+
+    #[cfg(feature = "scripting")]
+    fn compile(&self, script: AutomationScript) -> Result<CompiledScript> {
+        let ast = self
+            .engine
+            .compile(&script.source)
+            .map_err(|err| anyhow!("failed to compile script {}: {err}", script.name))?;
+        Ok(CompiledScript { script, ast })
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn compile(&self, script: AutomationScript) -> Result<CompiledScript> {
+        let _ = script;
+        Err(anyhow!(
+            "automation scripts require the `scripting` feature"
+        ))
+    }
+
+    pub fn remove(&self, name: &str) -> bool {
+        let mut scripts = self
+            .scripts
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let before = scripts.len();
+        scripts.retain(|existing| existing.script.name != name);
+        scripts.len() != before
+    }
+
+    pub fn scripts(&self) -> Vec<AutomationScript> {
+        self.scripts
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .map(|compiled| compiled.script.clone())
+            .collect()
+    }
+
+    pub fn has_hook(&self, hook: HookPoint) -> bool {
+        self.scripts
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .any(|compiled| compiled.script.hook == hook)
+    }
+
+    /// 按注册顺序依次执行挂在 `hook` 上的脚本，前一个脚本的输出作为下一个的输入。
+    pub fn run(
+        &self,
+        hook: HookPoint,
+        text: &str,
+        focus: &FocusWindowContext,
+        outcome: Option<&PublishOutcome>,
+    ) -> String {
+        let scripts = self
+            .scripts
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut text = text.to_string();
+        for compiled in scripts
+            .iter()
+            .filter(|compiled| compiled.script.hook == hook && compiled.script.applies_to(focus))
+        {
+            match self.eval(compiled, &text, focus, outcome) {
+                Ok(Some(rewritten)) => text = rewritten,
+                Ok(None) => {}
+                Err(err) => warn!(
+                    target: "session_scripts",
+                    %err,
+                    script = %compiled.script.name,
+                    "automation script failed, keeping text unchanged"
+                ),
+            }
+        }
+        text
+    }
+
+    #[cfg(feature = "scripting")]
+    fn eval(
+        &self,
+        compiled: &CompiledScript,
+        text: &str,
+        focus: &FocusWindowContext,
+        outcome: Option<&PublishOutcome>,
+    ) -> Result<Option<String>> {
+        let mut scope = rhai::Scope::new();
+        scope.push("text", text.to_string());
+        scope.push_constant("app", focus.app_identifier.clone().unwrap_or_default());
+        scope.push_constant(
+            "window_title",
+            focus.window_title.clone().unwrap_or_default(),
+        );
+        scope.push_constant(
+            "field_role",
+            focus
+                .field_role
+                .map(|role| role.as_str().to_string())
+                .unwrap_or_default(),
+        );
+        if let Some(outcome) = outcome {
+            scope.push_constant("status", outcome.status.as_str().to_string());
+        }
+        let result: rhai::Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &compiled.ast)
+            .map_err(|err| anyhow!("{err}"))?;
+        if result.is_string() {
+            return Ok(result.into_string().ok());
+        }
+        Ok(scope
+            .get_value::<String>("text")
+            .filter(|rewritten| rewritten != text))
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn eval(
+        &self,
+        _compiled: &CompiledScript,
+        _text: &str,
+        _focus: &FocusWindowContext,
+        _outcome: Option<&PublishOutcome>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// 绑定焦点的润色前钩子，供实时会话使用。
+    pub fn pre_polish_hook(&self, focus: &FocusWindowContext) -> Arc<dyn PrePolishHook> {
+        Arc::new(ScriptedPrePolish {
+            host: self.clone(),
+            focus: focus.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ScriptedPrePolish {
+    host: ScriptHost,
+    focus: FocusWindowContext,
+}
+
+impl PrePolishHook for ScriptedPrePolish {
+    fn apply(&self, sentence: &str) -> String {
+        self.host
+            .run(HookPoint::PrePolish, sentence, &self.focus, None)
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_scripts_in_order_and_isolates_failures() {
+        let host = ScriptHost::default();
+        host.register(AutomationScript::new(
+            "shout",
+            HookPoint::PrePublish,
+            "text.to_upper()",
+        ))
+        .unwrap();
+        let mut signature = AutomationScript::new(
+            "signature",
+            HookPoint::PrePublish,
+            r#"text += " -- sent from " + app;"#,
+        );
+        signature.apps = vec!["com.apple.mail".into()];
+        host.register(signature).unwrap();
+        host.register(AutomationScript::new(
+            "broken",
+            HookPoint::PrePublish,
+            "loop { }",
+        ))
+        .unwrap();
+        assert!(host
+            .register(AutomationScript::new("bad", HookPoint::PrePolish, "let ="))
+            .is_err());
+
+        let mail = FocusWindowContext::from_app_identifier("com.apple.mail");
+        assert_eq!(
+            host.run(HookPoint::PrePublish, "hi", &mail, None),
+            "HI -- sent from com.apple.mail"
+        );
+        let other = FocusWindowContext::from_app_identifier("com.example");
+        assert_eq!(host.run(HookPoint::PrePublish, "hi", &other, None), "HI");
+        assert_eq!(host.run(HookPoint::PrePolish, "hi", &other, None), "hi");
+
+        host.register(AutomationScript::new(
+            "strip",
+            HookPoint::PrePolish,
+            r#"text.replace("um ", ""); text"#,
+        ))
+        .unwrap();
+        assert_eq!(host.pre_polish_hook(&other).apply("um ship it"), "ship it");
+        assert!(host.remove("shout"));
+        assert_eq!(host.scripts().len(), 3);
+    }
+}
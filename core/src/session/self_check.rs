@@ -0,0 +1,570 @@
+//! 一键自检：权限、设备、采集、引擎预热、数据库读写与遥测写入。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::time::timeout;
+
+use crate::audio::{record_microphone, CaptureBackend};
+use crate::orchestrator::EngineOrchestrator;
+use crate::persistence::sqlite::SqlitePersistence;
+use crate::telemetry;
+use crate::telemetry::events::record_self_check;
+
+const CAPTURE_DURATION: Duration = Duration::from_secs(1);
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+/// 采集样本至少需要覆盖期望时长的比例。
+const MIN_CAPTURE_COVERAGE: f32 = 0.9;
+/// 低于该 RMS 视为麦克风无信号（约 -80 dBFS）。
+const MIN_CAPTURE_RMS: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfCheckStep {
+    Permissions,
+    Devices,
+    Capture,
+    EngineWarmup,
+    Database,
+    Telemetry,
+}
+
+impl SelfCheckStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelfCheckStep::Permissions => "permissions",
+            SelfCheckStep::Devices => "devices",
+            SelfCheckStep::Capture => "capture",
+            SelfCheckStep::EngineWarmup => "engine_warmup",
+            SelfCheckStep::Database => "database",
+            SelfCheckStep::Telemetry => "telemetry",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfCheckStatus {
+    Pass,
+    Fail,
+    /// 当前运行环境无法执行该项（例如无界面的 CLI 没有权限探针），`detail` 说明原因。
+    Skipped,
+}
+
+/// 整体结论：有跳过项时不算通过，避免把“没检查”当成“没问题”。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfCheckOutcome {
+    Passed,
+    /// 没有失败项，但至少一项被跳过。
+    Incomplete,
+    Failed,
+}
+
+/// 平台探针无法在当前环境执行某项时返回的错误，对应项记为跳过而非失败。
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct SelfCheckUnsupported(pub String);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelfCheckItem {
+    pub step: SelfCheckStep,
+    pub status: SelfCheckStatus,
+    pub detail: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelfCheckReport {
+    pub generated_at_ms: u128,
+    /// 没有失败项；跳过的项见 `outcome`。
+    pub passed: bool,
+    pub outcome: SelfCheckOutcome,
+    pub items: Vec<SelfCheckItem>,
+}
+
+impl SelfCheckReport {
+    fn new(items: Vec<SelfCheckItem>) -> Self {
+        let mut report = Self {
+            generated_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis())
+                .unwrap_or(0),
+            passed: true,
+            outcome: SelfCheckOutcome::Passed,
+            items,
+        };
+        report.refresh_outcome();
+        report
+    }
+
+    /// 宿主在自检之外发现的失败（例如引擎初始化失败）记到对应项上。
+    pub fn mark_failed(&mut self, step: SelfCheckStep, detail: String) {
+        if let Some(item) = self.items.iter_mut().find(|item| item.step == step) {
+            item.status = SelfCheckStatus::Fail;
+            item.detail = Some(detail);
+            self.refresh_outcome();
+        }
+    }
+
+    fn refresh_outcome(&mut self) {
+        let status = |wanted| self.items.iter().any(|item| item.status == wanted);
+        self.outcome = if status(SelfCheckStatus::Fail) {
+            SelfCheckOutcome::Failed
+        } else if status(SelfCheckStatus::Skipped) {
+            SelfCheckOutcome::Incomplete
+        } else {
+            SelfCheckOutcome::Passed
+        };
+        self.passed = self.outcome != SelfCheckOutcome::Failed;
+    }
+
+    pub fn item(&self, step: SelfCheckStep) -> Option<&SelfCheckItem> {
+        self.items.iter().find(|item| item.step == step)
+    }
+
+    pub fn failed_steps(&self) -> Vec<SelfCheckStep> {
+        self.items
+            .iter()
+            .filter(|item| item.status == SelfCheckStatus::Fail)
+            .map(|item| item.step)
+            .collect()
+    }
+
+    pub fn skipped_steps(&self) -> Vec<SelfCheckStep> {
+        self.items
+            .iter()
+            .filter(|item| item.status == SelfCheckStatus::Skipped)
+            .map(|item| item.step)
+            .collect()
+    }
+}
+
+/// 采集探针返回的 PCM 样本。
+#[derive(Debug, Clone)]
+pub struct CaptureSample {
+    pub samples: Vec<f32>,
+    pub sample_rate_hz: u32,
+}
+
+/// 由宿主（桌面端）提供的平台探针，均为阻塞调用，由自检在后台线程执行。
+pub trait SelfCheckPlatform: Send + Sync {
+    /// 返回权限检查摘要；任一必需权限缺失时返回错误。
+    fn check_permissions(&self) -> Result<String>;
+    /// 返回可用输入设备名称列表。
+    fn enumerate_devices(&self) -> Result<Vec<String>>;
+    /// 从默认输入设备采集指定时长的音频。
+    fn capture(&self, duration: Duration) -> Result<CaptureSample>;
+}
+
+/// 直接从采集后端取样的探针，供没有桌面壳的 CLI 使用。权限只能由桌面壳检查，记为跳过。
+pub struct CaptureBackendProbe {
+    backend: Arc<dyn CaptureBackend>,
+    device_id: Option<String>,
+}
+
+impl CaptureBackendProbe {
+    pub fn new(backend: Arc<dyn CaptureBackend>, device_id: Option<String>) -> Self {
+        Self { backend, device_id }
+    }
+}
+
+impl SelfCheckPlatform for CaptureBackendProbe {
+    fn check_permissions(&self) -> Result<String> {
+        Err(SelfCheckUnsupported("permission probes require the desktop shell".into()).into())
+    }
+
+    fn enumerate_devices(&self) -> Result<Vec<String>> {
+        Ok(self
+            .backend
+            .input_devices()?
+            .into_iter()
+            .map(|device| device.label)
+            .collect())
+    }
+
+    fn capture(&self, duration: Duration) -> Result<CaptureSample> {
+        let (samples, sample_rate_hz) =
+            record_microphone(self.backend.as_ref(), self.device_id.as_deref(), duration)?;
+        Ok(CaptureSample {
+            samples,
+            sample_rate_hz,
+        })
+    }
+}
+
+/// 自检需要覆盖的组件，缺失的组件对应项记为跳过。
+#[derive(Default)]
+pub struct SelfCheckTargets<'a> {
+    pub platform: Option<Arc<dyn SelfCheckPlatform>>,
+    pub orchestrator: Option<&'a EngineOrchestrator>,
+    pub database: Option<Arc<SqlitePersistence>>,
+}
+
+/// 顺序执行所有自检项并生成报告，单项失败不会中断后续检查。
+pub async fn run_self_check(targets: SelfCheckTargets<'_>) -> SelfCheckReport {
+    let mut items = Vec::with_capacity(6);
+
+    let platform = targets.platform.clone();
+    items.push(
+        blocking_step(SelfCheckStep::Permissions, platform.clone(), |platform| {
+            platform.check_permissions()
+        })
+        .await,
+    );
+    items.push(
+        blocking_step(SelfCheckStep::Devices, platform.clone(), |platform| {
+            let devices = platform.enumerate_devices()?;
+            if devices.is_empty() {
+                return Err(anyhow!("no audio input device found"));
+            }
+            Ok(format!(
+                "{} input device(s): {}",
+                devices.len(),
+                devices.join(", ")
+            ))
+        })
+        .await,
+    );
+    items.push(
+        blocking_step(SelfCheckStep::Capture, platform, |platform| {
+            let capture = platform.capture(CAPTURE_DURATION)?;
+            assess_capture(&capture, CAPTURE_DURATION)
+        })
+        .await,
+    );
+
+    items.push(match targets.orchestrator {
+        Some(orchestrator) => {
+            run_step(SelfCheckStep::EngineWarmup, async {
+                orchestrator.warmup().await?;
+                Ok("engine warmup completed".to_string())
+            })
+            .await
+        }
+        None => skipped(SelfCheckStep::EngineWarmup, "no speech engine configured"),
+    });
+
+    items.push(
+        blocking_step(SelfCheckStep::Database, targets.database, |sqlite| {
+            let latency = sqlite.probe()?;
+            Ok(format!("read/write probe ok in {}ms", latency.as_millis()))
+        })
+        .await,
+    );
+
+    items.push(
+        run_step(SelfCheckStep::Telemetry, async {
+            let path = tokio::task::spawn_blocking(telemetry::probe_write)
+                .await
+                .map_err(|err| anyhow!("telemetry probe task failed: {err}"))??;
+            Ok(format!("wrote probe to {}", path.display()))
+        })
+        .await,
+    );
+
+    let report = SelfCheckReport::new(items);
+    let names = |steps: Vec<SelfCheckStep>| -> Vec<&'static str> {
+        steps.iter().map(SelfCheckStep::as_str).collect()
+    };
+    record_self_check(
+        report.passed,
+        &names(report.failed_steps()),
+        &names(report.skipped_steps()),
+    );
+
+    report
+}
+
+fn assess_capture(capture: &CaptureSample, expected: Duration) -> Result<String> {
+    let expected_samples = expected.as_secs_f32() * capture.sample_rate_hz as f32;
+    let coverage = if expected_samples > 0.0 {
+        capture.samples.len() as f32 / expected_samples
+    } else {
+        0.0
+    };
+    if coverage < MIN_CAPTURE_COVERAGE {
+        return Err(anyhow!(
+            "captured only {} samples, expected about {:.0}",
+            capture.samples.len(),
+            expected_samples
+        ));
+    }
+
+    let energy: f32 = capture.samples.iter().map(|sample| sample * sample).sum();
+    let rms = (energy / capture.samples.len() as f32).sqrt();
+    if rms < MIN_CAPTURE_RMS {
+        return Err(anyhow!(
+            "microphone is silent; check the mute switch and input volume"
+        ));
+    }
+
+    let rms_db = 20.0 * rms.log10();
+    Ok(format!(
+        "captured {} samples @ {}Hz, rms {:.1} dBFS",
+        capture.samples.len(),
+        capture.sample_rate_hz,
+        rms_db
+    ))
+}
+
+async fn blocking_step<T, F>(step: SelfCheckStep, target: Option<Arc<T>>, job: F) -> SelfCheckItem
+where
+    T: ?Sized + Send + Sync + 'static,
+    F: FnOnce(&T) -> Result<String> + Send + 'static,
+{
+    let Some(target) = target else {
+        let reason = match step {
+            SelfCheckStep::Database => "no database configured",
+            _ => "no capture backend or platform probe configured",
+        };
+        return skipped(step, reason);
+    };
+
+    run_step(step, async move {
+        tokio::task::spawn_blocking(move || job(target.as_ref()))
+            .await
+            .map_err(|err| anyhow!("self-check task failed: {err}"))?
+    })
+    .await
+}
+
+async fn run_step<F>(step: SelfCheckStep, future: F) -> SelfCheckItem
+where
+    F: std::future::Future<Output = Result<String>>,
+{
+    let started = Instant::now();
+    let (status, detail) = match timeout(STEP_TIMEOUT, future).await {
+        Ok(Ok(detail)) => (SelfCheckStatus::Pass, Some(detail)),
+        Ok(Err(err)) if err.is::<SelfCheckUnsupported>() => {
+            (SelfCheckStatus::Skipped, Some(err.to_string()))
+        }
+        Ok(Err(err)) => (SelfCheckStatus::Fail, Some(err.to_string())),
+        Err(_) => (
+            SelfCheckStatus::Fail,
+            Some(format!("timed out after {}s", STEP_TIMEOUT.as_secs())),
+        ),
+    };
+
+    SelfCheckItem {
+        step,
+        status,
+        detail,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn skipped(step: SelfCheckStep, reason: &str) -> SelfCheckItem {
+    SelfCheckItem {
+        step,
+        status: SelfCheckStatus::Skipped,
+        detail: Some(reason.to_string()),
+        elapsed_ms: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{
+        AudioDevice, CaptureHandle, CaptureSink, CaptureTarget, PcmFormat, SampleEncoding,
+    };
+    use crate::orchestrator::{EngineConfig, SpeechEngine};
+    use crate::persistence::sqlite::SqliteConfig;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use std::env;
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    struct SilentEngine;
+
+    #[async_trait]
+    impl SpeechEngine for SilentEngine {
+        async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
+            Ok(String::new())
+        }
+    }
+
+    struct FakePlatform {
+        permissions_granted: bool,
+        amplitude: f32,
+    }
+
+    impl SelfCheckPlatform for FakePlatform {
+        fn check_permissions(&self) -> Result<String> {
+            if self.permissions_granted {
+                Ok("microphone, accessibility".into())
+            } else {
+                Err(anyhow!("accessibility permission missing"))
+            }
+        }
+
+        fn enumerate_devices(&self) -> Result<Vec<String>> {
+            Ok(vec!["Built-in Microphone".into()])
+        }
+
+        fn capture(&self, duration: Duration) -> Result<CaptureSample> {
+            let len = (duration.as_secs_f32() * 16_000.0) as usize;
+            Ok(CaptureSample {
+                samples: vec![self.amplitude; len],
+                sample_rate_hz: 16_000,
+            })
+        }
+    }
+
+    fn use_temp_telemetry_dir() {
+        static DIR: OnceLock<PathBuf> = OnceLock::new();
+        DIR.get_or_init(|| {
+            let dir = tempfile::tempdir().expect("temp dir").keep();
+            env::set_var("FLOWWISPER_TELEMETRY_DIR", &dir);
+            dir
+        });
+    }
+
+    #[tokio::test]
+    async fn passes_when_all_components_are_healthy() {
+        use_temp_telemetry_dir();
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(SilentEngine),
+        );
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+
+        let report = run_self_check(SelfCheckTargets {
+            platform: Some(Arc::new(FakePlatform {
+                permissions_granted: true,
+                amplitude: 0.1,
+            })),
+            orchestrator: Some(&orchestrator),
+            database: Some(sqlite),
+        })
+        .await;
+
+        assert!(report.passed, "unexpected failures: {:?}", report.items);
+        assert_eq!(report.outcome, SelfCheckOutcome::Passed);
+        assert_eq!(report.items.len(), 6);
+        assert!(report
+            .items
+            .iter()
+            .all(|item| item.status == SelfCheckStatus::Pass));
+    }
+
+    #[tokio::test]
+    async fn reports_failures_without_aborting_remaining_steps() {
+        use_temp_telemetry_dir();
+        let report = run_self_check(SelfCheckTargets {
+            platform: Some(Arc::new(FakePlatform {
+                permissions_granted: false,
+                amplitude: 0.0,
+            })),
+            orchestrator: None,
+            database: None,
+        })
+        .await;
+
+        assert!(!report.passed);
+        assert_eq!(report.outcome, SelfCheckOutcome::Failed);
+        assert_eq!(
+            report.failed_steps(),
+            vec![SelfCheckStep::Permissions, SelfCheckStep::Capture]
+        );
+        assert_eq!(
+            report.item(SelfCheckStep::Devices).map(|item| item.status),
+            Some(SelfCheckStatus::Pass)
+        );
+        assert_eq!(
+            report.item(SelfCheckStep::Database).map(|item| item.status),
+            Some(SelfCheckStatus::Skipped)
+        );
+        assert_eq!(
+            report
+                .item(SelfCheckStep::Telemetry)
+                .map(|item| item.status),
+            Some(SelfCheckStatus::Pass)
+        );
+    }
+
+    #[test]
+    fn capture_assessment_rejects_short_recordings() {
+        let capture = CaptureSample {
+            samples: vec![0.1; 4_000],
+            sample_rate_hz: 16_000,
+        };
+        assert!(assess_capture(&capture, Duration::from_secs(1)).is_err());
+    }
+
+    struct ToneBackend;
+
+    impl CaptureBackend for ToneBackend {
+        fn input_devices(&self) -> Result<Vec<AudioDevice>> {
+            Ok(vec![AudioDevice {
+                id: "builtin".into(),
+                label: "Built-in Microphone".into(),
+                is_default: true,
+            }])
+        }
+
+        fn supported_formats(&self, _: &CaptureTarget) -> Result<Vec<PcmFormat>> {
+            Ok(vec![PcmFormat::new(SampleEncoding::I16, 2, 16_000)])
+        }
+
+        fn open(
+            &self,
+            _: &CaptureTarget,
+            format: PcmFormat,
+            sink: CaptureSink,
+        ) -> Result<CaptureHandle> {
+            // 每 100ms 一块立体声 PCM，共 1.2 秒。
+            let chunk: Vec<u8> = std::iter::repeat_n(3_000_i16, 1_600 * format.channels as usize)
+                .flat_map(i16::to_le_bytes)
+                .collect();
+            for _ in 0..12 {
+                sink.push(Bytes::from(chunk.clone()));
+            }
+            Ok(CaptureHandle::new(move || drop(sink)))
+        }
+    }
+
+    #[tokio::test]
+    async fn capture_backend_probe_skips_permissions_and_samples_the_microphone() {
+        use_temp_telemetry_dir();
+        let report = run_self_check(SelfCheckTargets {
+            platform: Some(Arc::new(CaptureBackendProbe::new(
+                Arc::new(ToneBackend),
+                None,
+            ))),
+            orchestrator: None,
+            database: None,
+        })
+        .await;
+
+        assert!(report.passed, "unexpected failures: {:?}", report.items);
+        assert_eq!(report.outcome, SelfCheckOutcome::Incomplete);
+        let permissions = report.item(SelfCheckStep::Permissions).unwrap();
+        assert_eq!(permissions.status, SelfCheckStatus::Skipped);
+        assert!(permissions.detail.is_some());
+        assert_eq!(
+            report.item(SelfCheckStep::Capture).map(|item| item.status),
+            Some(SelfCheckStatus::Pass)
+        );
+        assert_eq!(
+            report.skipped_steps(),
+            vec![
+                SelfCheckStep::Permissions,
+                SelfCheckStep::EngineWarmup,
+                SelfCheckStep::Database
+            ]
+        );
+
+        let mut report = report;
+        report.mark_failed(SelfCheckStep::Database, "locked".into());
+        assert_eq!(report.outcome, SelfCheckOutcome::Failed);
+        assert!(!report.passed);
+    }
+}
@@ -0,0 +1,79 @@
+//! 优雅退出：后台任务监听同一个取消令牌，`SessionManager::shutdown` 触发后各自收尾退出。
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// 可克隆的取消令牌，任一克隆调用 `cancel` 后所有等待者立即返回。
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        Self { tx: Arc::new(tx) }
+    }
+
+    pub fn cancel(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// 取消后就绪；已取消时立即返回。
+    pub async fn cancelled(&self) {
+        let mut rx = self.tx.subscribe();
+        // 发送端由令牌自身持有，等待期间不会关闭。
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+
+    /// 运行 `future` 直至完成或令牌被取消；取消时丢弃 `future` 并返回 `None`。
+    pub async fn run_until_cancelled<F: Future>(&self, future: F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => None,
+            output = future => Some(output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn wakes_every_waiter_on_cancel() {
+        let token = CancellationToken::new();
+        let waiter = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        assert!(!token.is_cancelled());
+        token.clone().cancel();
+        tokio::time::timeout(Duration::from_millis(200), waiter)
+            .await
+            .expect("waiter should wake")
+            .unwrap();
+        assert!(token.is_cancelled());
+        // 取消后再等待立即返回。
+        token.cancelled().await;
+        assert_eq!(
+            token
+                .run_until_cancelled(std::future::pending::<()>())
+                .await,
+            None
+        );
+    }
+}
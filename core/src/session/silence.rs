@@ -0,0 +1,475 @@
+//! 静音倒计时：噪声监听发现持续静音时倒计时并自动停止，手动停止时取消倒计时并记录遥测。
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::session::capture::CaptureTrigger;
+use crate::session::{
+    system_time_to_ms, AutoStopReason, SessionAutoStop, SessionEchoDetected, SessionEvent,
+    SessionManager, SessionNoiseWarning, SessionSilenceCountdown, SilenceCancellationReason,
+    SilenceCountdownSnapshot, SilenceCountdownState,
+};
+use crate::telemetry::events::{
+    record_session_echo_detected, record_session_noise_warning, record_session_silence_autostop,
+    record_session_silence_countdown, EVENT_ECHO_DETECTED, EVENT_NOISE_WARNING,
+    EVENT_SILENCE_AUTOSTOP, EVENT_SILENCE_COUNTDOWN,
+};
+
+impl SessionManager {
+    pub(super) fn spawn_noise_listener(&self) {
+        let mut noise_rx = self.audio.subscribe_noise_events();
+        let event_tx = self.event_tx.clone();
+        let audio = self.audio.clone();
+        let persistence = self.persistence.clone();
+        let countdown_active = Arc::clone(&self.silence_countdown_active);
+        let auto_stop_triggered = Arc::clone(&self.auto_stop_triggered);
+        let snapshot = Arc::clone(&self.silence_countdown_snapshot);
+        let active_session_id = Arc::clone(&self.active_session_id);
+        let capture = Arc::clone(&self.capture);
+        let capture_tx = self.capture_tx.clone();
+
+        self.spawn_background(async move {
+            loop {
+                match noise_rx.recv().await {
+                    Ok(crate::audio::NoiseEvent::NoiseWarning(payload)) => {
+                        let event = SessionEvent::NoiseWarning(SessionNoiseWarning {
+                            baseline_db: payload.baseline_db,
+                            threshold_db: payload.threshold_db,
+                            level_db: payload.window_db,
+                            persistence_ms: payload.persistence_ms,
+                            kind: payload.kind,
+                        });
+
+                        let timestamp = SystemTime::now();
+                        let session_id = {
+                            active_session_id
+                                .lock()
+                                .await
+                                .clone()
+                                .unwrap_or_else(|| "unassigned".to_string())
+                        };
+
+                        record_session_noise_warning(
+                            &session_id,
+                            payload.baseline_db,
+                            payload.threshold_db,
+                            payload.window_db,
+                            payload.persistence_ms,
+                            payload.kind.as_str(),
+                            false,
+                            timestamp,
+                        );
+
+                        if let Err(err) = event_tx.send(event) {
+                            warn!(
+                                target: "session_manager",
+                                %err,
+                                "failed to broadcast noise warning event",
+                            );
+                        }
+
+                        let occurred_at_ms = system_time_to_ms(timestamp);
+                        let queue_payload = json!({
+                            "sessionId": session_id,
+                            "occurredAtMs": occurred_at_ms,
+                            "baselineDb": payload.baseline_db,
+                            "thresholdDb": payload.threshold_db,
+                            "levelDb": payload.window_db,
+                            "persistenceMs": payload.persistence_ms,
+                            "noiseKind": payload.kind.as_str(),
+                            "strongNoiseMode": false,
+                        });
+
+                        if let Err(err) = persistence
+                            .enqueue_telemetry(
+                                session_id,
+                                EVENT_NOISE_WARNING.to_string(),
+                                queue_payload,
+                            )
+                            .await
+                        {
+                            warn!(
+                                target: "session_manager",
+                                %err,
+                                "failed to queue noise warning telemetry",
+                            );
+                        }
+                    }
+                    Ok(crate::audio::NoiseEvent::SilenceCountdown(payload)) => {
+                        let state = match payload.status {
+                            crate::audio::SilenceCountdownStatus::Started => {
+                                SilenceCountdownState::Started
+                            }
+                            crate::audio::SilenceCountdownStatus::Tick => {
+                                SilenceCountdownState::Tick
+                            }
+                            crate::audio::SilenceCountdownStatus::Canceled => {
+                                SilenceCountdownState::Canceled
+                            }
+                            crate::audio::SilenceCountdownStatus::Completed => {
+                                SilenceCountdownState::Completed
+                            }
+                        };
+
+                        let mut snapshot_guard = snapshot.lock().await;
+                        match state {
+                            SilenceCountdownState::Canceled => {
+                                *snapshot_guard = None;
+                            }
+                            _ => {
+                                *snapshot_guard = Some(SilenceCountdownSnapshot {
+                                    total_ms: payload.total_ms,
+                                    remaining_ms: payload.remaining_ms,
+                                });
+                            }
+                        }
+                        drop(snapshot_guard);
+
+                        let cancel_reason = if matches!(state, SilenceCountdownState::Canceled) {
+                            Some(SilenceCancellationReason::SpeechDetected)
+                        } else {
+                            None
+                        };
+
+                        let countdown_event =
+                            SessionEvent::SilenceCountdown(SessionSilenceCountdown {
+                                total_ms: payload.total_ms,
+                                remaining_ms: payload.remaining_ms,
+                                state,
+                                cancel_reason,
+                            });
+
+                        if let Err(err) = event_tx.send(countdown_event) {
+                            warn!(
+                                target: "session_manager",
+                                %err,
+                                "failed to broadcast silence countdown event",
+                            );
+                        }
+
+                        if !matches!(state, SilenceCountdownState::Tick) {
+                            let timestamp = SystemTime::now();
+                            let session_id = {
+                                active_session_id
+                                    .lock()
+                                    .await
+                                    .clone()
+                                    .unwrap_or_else(|| "unassigned".to_string())
+                            };
+                            let cancel_reason_value = cancel_reason.map(|reason| match reason {
+                                SilenceCancellationReason::SpeechDetected => "speechDetected",
+                                SilenceCancellationReason::ManualStop => "manualStop",
+                            });
+
+                            record_session_silence_countdown(
+                                &session_id,
+                                countdown_state_label(state),
+                                payload.total_ms,
+                                payload.remaining_ms,
+                                cancel_reason_value,
+                                timestamp,
+                            );
+
+                            let timestamp_ms = system_time_to_ms(timestamp);
+                            let queue_payload = json!({
+                                "sessionId": session_id,
+                                "timestampMs": timestamp_ms,
+                                "state": countdown_state_label(state),
+                                "totalMs": payload.total_ms,
+                                "remainingMs": payload.remaining_ms,
+                                "cancelReason": cancel_reason_value,
+                            });
+
+                            if let Err(err) = persistence
+                                .enqueue_telemetry(
+                                    queue_payload["sessionId"]
+                                        .as_str()
+                                        .unwrap_or("unassigned")
+                                        .to_string(),
+                                    EVENT_SILENCE_COUNTDOWN.to_string(),
+                                    queue_payload,
+                                )
+                                .await
+                            {
+                                warn!(
+                                    target: "session_manager",
+                                    %err,
+                                    "failed to queue silence countdown telemetry",
+                                );
+                            }
+                        }
+
+                        match state {
+                            SilenceCountdownState::Started => {
+                                countdown_active.store(true, Ordering::SeqCst);
+                                auto_stop_triggered.store(false, Ordering::SeqCst);
+                            }
+                            SilenceCountdownState::Tick => {
+                                countdown_active.store(true, Ordering::SeqCst);
+                            }
+                            SilenceCountdownState::Canceled => {
+                                countdown_active.store(false, Ordering::SeqCst);
+                                auto_stop_triggered.store(false, Ordering::SeqCst);
+                            }
+                            SilenceCountdownState::Completed => {
+                                countdown_active.store(false, Ordering::SeqCst);
+                                let already_triggered =
+                                    auto_stop_triggered.swap(true, Ordering::SeqCst);
+                                if !already_triggered {
+                                    {
+                                        let mut guard = snapshot.lock().await;
+                                        *guard = None;
+                                    }
+
+                                    let auto_stop_event = SessionEvent::AutoStop(SessionAutoStop {
+                                        reason: AutoStopReason::SilenceTimeout,
+                                    });
+
+                                    if let Err(err) = event_tx.send(auto_stop_event) {
+                                        warn!(
+                                            target: "session_manager",
+                                            %err,
+                                            "failed to broadcast auto-stop event",
+                                        );
+                                    }
+
+                                    audio.reset_session();
+                                    let stopped = capture
+                                        .lock()
+                                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                        .as_mut()
+                                        .and_then(|controller| {
+                                            controller.stop(CaptureTrigger::SilenceTimeout)
+                                        });
+                                    if let Some(event) = stopped {
+                                        let _ = capture_tx.send(event);
+                                    }
+                                    info!(
+                                        target: "session_manager",
+                                        "silence countdown completed; auto-stop triggered",
+                                    );
+
+                                    let timestamp = SystemTime::now();
+                                    let session_id = {
+                                        active_session_id
+                                            .lock()
+                                            .await
+                                            .clone()
+                                            .unwrap_or_else(|| "unassigned".to_string())
+                                    };
+
+                                    record_session_silence_autostop(
+                                        &session_id,
+                                        payload.total_ms,
+                                        timestamp,
+                                    );
+
+                                    let timestamp_ms = system_time_to_ms(timestamp);
+                                    let queue_payload = json!({
+                                        "sessionId": session_id,
+                                        "timestampMs": timestamp_ms,
+                                        "reason": "silenceTimeout",
+                                        "countdownMs": payload.total_ms,
+                                    });
+
+                                    if let Err(err) = persistence
+                                        .enqueue_telemetry(
+                                            queue_payload["sessionId"]
+                                                .as_str()
+                                                .unwrap_or("unassigned")
+                                                .to_string(),
+                                            EVENT_SILENCE_AUTOSTOP.to_string(),
+                                            queue_payload,
+                                        )
+                                        .await
+                                    {
+                                        warn!(
+                                            target: "session_manager",
+                                            %err,
+                                            "failed to queue silence autostop telemetry",
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Ok(crate::audio::NoiseEvent::EchoDetected(payload)) => {
+                        let event = SessionEvent::EchoDetected(SessionEchoDetected {
+                            correlation: payload.correlation,
+                            delay_ms: payload.delay_ms,
+                            persistence_ms: payload.persistence_ms,
+                            hint: payload.hint(),
+                        });
+
+                        let timestamp = SystemTime::now();
+                        let session_id = {
+                            active_session_id
+                                .lock()
+                                .await
+                                .clone()
+                                .unwrap_or_else(|| "unassigned".to_string())
+                        };
+
+                        record_session_echo_detected(
+                            &session_id,
+                            payload.correlation,
+                            payload.delay_ms,
+                            payload.persistence_ms,
+                            timestamp,
+                        );
+
+                        if let Err(err) = event_tx.send(event) {
+                            warn!(
+                                target: "session_manager",
+                                %err,
+                                "failed to broadcast echo detected event",
+                            );
+                        }
+
+                        let queue_payload = json!({
+                            "sessionId": session_id,
+                            "occurredAtMs": system_time_to_ms(timestamp),
+                            "correlation": payload.correlation,
+                            "delayMs": payload.delay_ms,
+                            "persistenceMs": payload.persistence_ms,
+                        });
+
+                        if let Err(err) = persistence
+                            .enqueue_telemetry(
+                                session_id,
+                                EVENT_ECHO_DETECTED.to_string(),
+                                queue_payload,
+                            )
+                            .await
+                        {
+                            warn!(
+                                target: "session_manager",
+                                %err,
+                                "failed to queue echo detected telemetry",
+                            );
+                        }
+                    }
+                    Ok(crate::audio::NoiseEvent::BaselineEstablished { level_db }) => {
+                        countdown_active.store(false, Ordering::SeqCst);
+                        auto_stop_triggered.store(false, Ordering::SeqCst);
+                        let mut guard = snapshot.lock().await;
+                        *guard = None;
+                        drop(guard);
+                        // 按设备保存，下次预录时热启动。
+                        if let Some(device_id) = audio.active_device() {
+                            if let Err(err) =
+                                persistence.store_noise_baseline(device_id, level_db).await
+                            {
+                                warn!(
+                                    target: "session_manager",
+                                    %err,
+                                    "failed to persist noise baseline",
+                                );
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            target: "session_manager",
+                            skipped,
+                            "noise event listener lagged",
+                        );
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    pub async fn cancel_silence_countdown_due_to_manual_stop(&self) {
+        let was_active = self.silence_countdown_active.swap(false, Ordering::SeqCst);
+        self.auto_stop_triggered.store(false, Ordering::SeqCst);
+
+        if !was_active {
+            return;
+        }
+
+        let snapshot = {
+            let mut guard = self.silence_countdown_snapshot.lock().await;
+            guard.take().unwrap_or(SilenceCountdownSnapshot {
+                total_ms: 5_000,
+                remaining_ms: 5_000,
+            })
+        };
+
+        let event = SessionEvent::SilenceCountdown(SessionSilenceCountdown {
+            total_ms: snapshot.total_ms,
+            remaining_ms: snapshot.remaining_ms,
+            state: SilenceCountdownState::Canceled,
+            cancel_reason: Some(SilenceCancellationReason::ManualStop),
+        });
+
+        if let Err(err) = self.event_tx.send(event) {
+            warn!(
+                target: "session_manager",
+                %err,
+                "failed to broadcast manual silence cancellation",
+            );
+        }
+
+        let timestamp = SystemTime::now();
+        let session_id = {
+            self.active_session_id
+                .lock()
+                .await
+                .clone()
+                .unwrap_or_else(|| "unassigned".to_string())
+        };
+
+        record_session_silence_countdown(
+            &session_id,
+            countdown_state_label(SilenceCountdownState::Canceled),
+            snapshot.total_ms,
+            snapshot.remaining_ms,
+            Some("manualStop"),
+            timestamp,
+        );
+
+        let queue_payload = json!({
+            "sessionId": session_id,
+            "timestampMs": system_time_to_ms(timestamp),
+            "state": "canceled",
+            "totalMs": snapshot.total_ms,
+            "remainingMs": snapshot.remaining_ms,
+            "cancelReason": "manualStop",
+        });
+
+        if let Err(err) = self
+            .persistence
+            .enqueue_telemetry(
+                queue_payload["sessionId"]
+                    .as_str()
+                    .unwrap_or("unassigned")
+                    .to_string(),
+                EVENT_SILENCE_COUNTDOWN.to_string(),
+                queue_payload,
+            )
+            .await
+        {
+            warn!(
+                target: "session_manager",
+                %err,
+                "failed to queue manual silence cancel telemetry",
+            );
+        }
+    }
+}
+
+fn countdown_state_label(state: SilenceCountdownState) -> &'static str {
+    match state {
+        SilenceCountdownState::Started => "started",
+        SilenceCountdownState::Tick => "tick",
+        SilenceCountdownState::Canceled => "canceled",
+        SilenceCountdownState::Completed => "completed",
+    }
+}
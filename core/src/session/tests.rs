@@ -0,0 +1,2359 @@
+use super::*;
+use crate::audio::AudioMixer;
+use crate::orchestrator::{
+    EngineConfig, EngineOrchestrator, ExtractiveSummarizer, NoticeLevel, RealtimeSessionConfig,
+    SpeechEngine, TranscriptSource, UpdatePayload,
+};
+use crate::session::clipboard::{ClipboardAccess, ClipboardError, ClipboardManager};
+use crate::session::history::HistoryActionKind;
+use crate::session::lifecycle::{SessionLifecyclePayload, SessionLifecyclePhase};
+use crate::session::publisher::{
+    FallbackStrategy, FocusWindowContext, InsertionMethod, OutputFormat, PublishOutcome,
+    PublishRequest, PublishStrategy, PublisherError, PublisherFailure, PublisherFailureCode,
+    PublisherStatus,
+};
+use crate::telemetry::events::{EVENT_NOISE_WARNING, EVENT_SILENCE_AUTOSTOP};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast::error::RecvError, Mutex as AsyncMutex};
+use tokio::time::{timeout, Duration};
+
+pub(super) struct ProgrammedSpeechEngine {
+    responses: Mutex<VecDeque<anyhow::Result<String>>>,
+}
+
+impl ProgrammedSpeechEngine {
+    pub(super) fn new(responses: Vec<anyhow::Result<String>>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechEngine for ProgrammedSpeechEngine {
+    async fn transcribe(&self, _frame: &[f32]) -> anyhow::Result<String> {
+        match self
+            .responses
+            .lock()
+            .expect("responses lock poisoned")
+            .pop_front()
+        {
+            Some(result) => result,
+            None => Ok(String::new()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct StubPublisher {
+    outcome: PublishOutcome,
+    pub(super) undone: Arc<StdMutex<Vec<String>>>,
+}
+
+impl StubPublisher {
+    pub(super) fn new(outcome: PublishOutcome) -> Self {
+        Self {
+            outcome,
+            undone: Arc::new(StdMutex::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl SessionPublisher for StubPublisher {
+    async fn publish(&self, _request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
+        Ok(self.outcome.clone())
+    }
+
+    async fn undo(&self, transcript: &str) -> Result<(), PublisherError> {
+        self.undone.lock().unwrap().push(transcript.to_string());
+        Ok(())
+    }
+}
+
+pub(super) fn make_snapshot(session_id: &str, raw: &str, polished: &str) -> SessionSnapshot {
+    SessionSnapshot {
+        session_id: session_id.into(),
+        started_at_ms: 0,
+        completed_at_ms: 0,
+        locale: None,
+        app_identifier: Some("com.example.app".into()),
+        app_version: None,
+        confidence_score: None,
+        raw_transcript: raw.into(),
+        polished_transcript: polished.into(),
+        metadata: json!({}),
+        post_actions: vec![],
+        language_segments: vec![],
+        translated_transcript: None,
+        translation_locale: None,
+        quality_flags: Vec::new(),
+        speed: None,
+        meeting: None,
+        tags: Vec::new(),
+    }
+}
+
+#[derive(Clone, Default)]
+pub(super) struct RecordingClipboard {
+    pub(super) state: Arc<AsyncMutex<Option<String>>>,
+    write_error: Arc<AsyncMutex<Option<ClipboardError>>>,
+}
+
+impl RecordingClipboard {
+    pub(super) async fn contents(&self) -> Option<String> {
+        self.state.lock().await.clone()
+    }
+
+    pub(super) async fn set_write_error(&self, error: ClipboardError) {
+        *self.write_error.lock().await = Some(error);
+    }
+}
+
+#[async_trait]
+impl ClipboardAccess for RecordingClipboard {
+    async fn read_text(&self, _timeout: Duration) -> Result<Option<String>, ClipboardError> {
+        Ok(self.state.lock().await.clone())
+    }
+
+    async fn write_text(&self, contents: &str, _timeout: Duration) -> Result<(), ClipboardError> {
+        if let Some(err) = self.write_error.lock().await.clone() {
+            return Err(err);
+        }
+
+        *self.state.lock().await = Some(contents.to_string());
+        Ok(())
+    }
+
+    async fn clear(&self, _timeout: Duration) -> Result<(), ClipboardError> {
+        *self.state.lock().await = None;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn noise_warning_events_forwarded_to_session_channel() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager
+        .run()
+        .await
+        .expect("session manager bootstrap should succeed");
+
+    manager.set_active_session_id("session-manual-stop").await;
+
+    manager
+        .set_active_session_id("session-noise-telemetry")
+        .await;
+
+    let audio = manager.audio_pipeline();
+    let mut events_rx = manager.subscribe_events();
+
+    audio.begin_preroll(Some(-32.0));
+    audio.begin_recording();
+
+    let loud_frame = vec![0.8_f32; 1_600];
+    for _ in 0..3 {
+        audio
+            .push_pcm_frame(loud_frame.clone())
+            .await
+            .expect("push loud frame");
+    }
+
+    let warning = timeout(Duration::from_millis(800), async {
+        loop {
+            match events_rx.recv().await {
+                Ok(SessionEvent::NoiseWarning(payload)) => break payload,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(err) => panic!("session event channel closed: {err:?}"),
+            }
+        }
+    })
+    .await
+    .expect("noise warning timed out");
+
+    assert!((warning.threshold_db - warning.baseline_db - 15.0).abs() < 0.5);
+    assert!(warning.level_db >= warning.threshold_db);
+    assert!(warning.persistence_ms >= 300);
+}
+
+#[tokio::test]
+async fn silence_countdown_completion_triggers_auto_stop_once() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager
+        .run()
+        .await
+        .expect("session manager bootstrap should succeed");
+
+    manager
+        .set_active_session_id("session-silence-autostop")
+        .await;
+
+    let audio = manager.audio_pipeline();
+    let mut events_rx = manager.subscribe_events();
+
+    audio.begin_preroll(Some(-28.0));
+    audio.begin_recording();
+
+    let quiet_frame = vec![0.001_f32; 1_600];
+    for _ in 0..50 {
+        audio
+            .push_pcm_frame(quiet_frame.clone())
+            .await
+            .expect("push quiet frame");
+    }
+
+    let mut countdown_completed = false;
+    let mut auto_stop_count = 0;
+
+    for _ in 0..128 {
+        let event = timeout(Duration::from_millis(1_000), events_rx.recv())
+            .await
+            .expect("waiting for session event timed out");
+
+        match event {
+            Ok(SessionEvent::SilenceCountdown(payload))
+                if payload.state == SilenceCountdownState::Completed =>
+            {
+                countdown_completed = true;
+            }
+            Ok(SessionEvent::AutoStop(_)) => {
+                auto_stop_count += 1;
+                break;
+            }
+            Ok(_) => {}
+            Err(RecvError::Lagged(_)) => continue,
+            Err(err) => panic!("session event channel closed: {err:?}"),
+        }
+    }
+
+    assert!(countdown_completed, "expected countdown completion event");
+    assert_eq!(auto_stop_count, 1, "auto-stop should trigger exactly once");
+
+    for _ in 0..5 {
+        audio
+            .push_pcm_frame(quiet_frame.clone())
+            .await
+            .expect("push quiet frame after auto-stop");
+    }
+
+    if let Ok(Ok(event)) = timeout(Duration::from_millis(250), events_rx.recv()).await {
+        assert!(
+            !matches!(event, SessionEvent::AutoStop(_)),
+            "unexpected extra auto-stop event",
+        );
+    }
+}
+
+#[tokio::test]
+async fn manual_stop_cancels_active_silence_countdown() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager
+        .run()
+        .await
+        .expect("session manager bootstrap should succeed");
+
+    manager.set_active_session_id("session-manual-stop").await;
+
+    let audio = manager.audio_pipeline();
+    let mut events_rx = manager.subscribe_events();
+
+    audio.begin_preroll(Some(-30.0));
+    audio.begin_recording();
+
+    let quiet_frame = vec![0.001_f32; 1_600];
+    audio
+        .push_pcm_frame(quiet_frame.clone())
+        .await
+        .expect("push quiet frame");
+
+    timeout(Duration::from_millis(800), async {
+        loop {
+            match events_rx.recv().await {
+                Ok(SessionEvent::SilenceCountdown(payload))
+                    if payload.state == SilenceCountdownState::Started =>
+                {
+                    break;
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(err) => panic!("session event channel closed: {err:?}"),
+            }
+        }
+    })
+    .await
+    .expect("countdown start not observed");
+
+    manager.cancel_silence_countdown_due_to_manual_stop().await;
+
+    let canceled = timeout(Duration::from_millis(800), async {
+        loop {
+            match events_rx.recv().await {
+                Ok(SessionEvent::SilenceCountdown(payload))
+                    if payload.state == SilenceCountdownState::Canceled =>
+                {
+                    break payload;
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(err) => panic!("session event channel closed: {err:?}"),
+            }
+        }
+    })
+    .await
+    .expect("manual cancellation event missing");
+
+    assert_eq!(
+        canceled.cancel_reason,
+        Some(SilenceCancellationReason::ManualStop),
+        "expected manual stop cancel reason",
+    );
+
+    audio.reset_session();
+    audio.begin_preroll(Some(-30.0));
+    audio.begin_recording();
+
+    for _ in 0..10 {
+        audio
+            .push_pcm_frame(quiet_frame.clone())
+            .await
+            .expect("push quiet frame after restart");
+    }
+
+    timeout(Duration::from_millis(1_200), async {
+        loop {
+            match events_rx.recv().await {
+                Ok(SessionEvent::SilenceCountdown(payload))
+                    if payload.state == SilenceCountdownState::Started =>
+                {
+                    break;
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(err) => panic!("session event channel closed: {err:?}"),
+            }
+        }
+    })
+    .await
+    .expect("countdown did not restart after manual cancel");
+}
+
+#[tokio::test]
+async fn noise_warning_is_persisted_to_telemetry_queue() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager
+        .run()
+        .await
+        .expect("session manager bootstrap should succeed");
+
+    manager
+        .set_active_session_id("session-telemetry-noise")
+        .await;
+
+    let audio = manager.audio_pipeline();
+    audio.begin_preroll(Some(-36.0));
+    audio.begin_recording();
+
+    let loud_frame = vec![0.9_f32; 1_600];
+    for _ in 0..3 {
+        audio
+            .push_pcm_frame(loud_frame.clone())
+            .await
+            .expect("push loud frame");
+    }
+
+    let mut rx = manager.subscribe_events();
+    timeout(Duration::from_millis(800), async {
+        loop {
+            match rx.recv().await {
+                Ok(SessionEvent::NoiseWarning(_)) => break,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(err) => panic!("session event channel closed: {err:?}"),
+            }
+        }
+    })
+    .await
+    .expect("noise warning timed out");
+
+    let persistence = manager.persistence_handle();
+    let conn = persistence
+        .sqlite()
+        .connection()
+        .expect("persistence connection");
+    let (event_type, payload): (String, String) = conn
+        .query_row(
+            "SELECT event_type, payload FROM telemetry_queue ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("telemetry row");
+
+    assert_eq!(event_type, EVENT_NOISE_WARNING);
+    let payload_json: serde_json::Value =
+        serde_json::from_str(&payload).expect("noise telemetry payload");
+    assert_eq!(payload_json["sessionId"], "session-telemetry-noise");
+    assert!(payload_json["thresholdDb"].as_f64().is_some());
+    assert!(payload_json["occurredAtMs"].as_u64().is_some());
+    assert!(payload_json["noiseKind"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn silence_completion_records_auto_stop_telemetry() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(String::new())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager
+        .run()
+        .await
+        .expect("session manager bootstrap should succeed");
+
+    manager
+        .set_active_session_id("session-telemetry-silence")
+        .await;
+
+    let audio = manager.audio_pipeline();
+    audio.begin_preroll(Some(-28.0));
+    audio.begin_recording();
+
+    let quiet_frame = vec![0.0005_f32; 1_600];
+    for _ in 0..60 {
+        audio
+            .push_pcm_frame(quiet_frame.clone())
+            .await
+            .expect("push quiet frame");
+    }
+
+    let mut rx = manager.subscribe_events();
+    timeout(Duration::from_secs(3), async {
+        loop {
+            match rx.recv().await {
+                Ok(SessionEvent::AutoStop(_)) => break,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(err) => panic!("session event channel closed: {err:?}"),
+            }
+        }
+    })
+    .await
+    .expect("auto-stop timed out");
+
+    let persistence = manager.persistence_handle();
+    let conn = persistence
+        .sqlite()
+        .connection()
+        .expect("persistence connection");
+    let (event_type, payload): (String, String) = conn
+        .query_row(
+            "SELECT event_type, payload FROM telemetry_queue ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("telemetry row");
+
+    assert_eq!(event_type, EVENT_SILENCE_AUTOSTOP);
+    let payload_json: serde_json::Value =
+        serde_json::from_str(&payload).expect("auto-stop telemetry payload");
+    assert_eq!(payload_json["sessionId"], "session-telemetry-silence");
+    assert_eq!(payload_json["reason"], "silenceTimeout");
+    assert!(payload_json["countdownMs"].as_u64().is_some());
+}
+
+/// 在内存中记录新建的 span 及其父级，用于校验链路上下文。
+#[derive(Clone, Default)]
+struct SpanCapture(Arc<Mutex<Vec<CapturedSpan>>>);
+
+#[derive(Debug, Clone)]
+struct CapturedSpan {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: HashMap<&'static str, String>,
+}
+
+impl tracing::field::Visit for CapturedSpan {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.fields.insert(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.fields.insert(field.name(), format!("{value:?}"));
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for SpanCapture
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let mut span = CapturedSpan {
+            name: attrs.metadata().name(),
+            parent: ctx
+                .span(id)
+                .and_then(|span| span.parent())
+                .map(|parent| parent.name()),
+            fields: HashMap::new(),
+        };
+        attrs.record(&mut span);
+        self.0.lock().unwrap().push(span);
+    }
+}
+
+#[tokio::test]
+async fn realtime_spans_carry_the_session_id() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let capture = SpanCapture::default();
+    let _subscriber =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.set_active_session_id("session-traced").await;
+    let (_active, _active_rx) = manager
+        .start_realtime_transcription(RealtimeSessionConfig::default())
+        .await;
+    let (_explicit, _explicit_rx) = manager
+        .start_realtime_transcription(RealtimeSessionConfig {
+            session_id: Some("session-explicit".into()),
+            ..RealtimeSessionConfig::default()
+        })
+        .await;
+
+    let spans = capture.0.lock().unwrap().clone();
+    let named = |name: &str| -> Vec<CapturedSpan> {
+        spans
+            .iter()
+            .filter(|span| span.name == name)
+            .cloned()
+            .collect()
+    };
+    let sessions = named("realtime_transcription");
+    let engines = named("realtime_session");
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(engines.len(), 2);
+    for (span, expected) in sessions
+        .iter()
+        .chain(&engines)
+        .zip(["session-traced", "session-explicit"].iter().cycle())
+    {
+        assert_eq!(
+            span.fields.get("session_id").map(String::as_str),
+            Some(*expected)
+        );
+    }
+    assert!(engines
+        .iter()
+        .all(|span| span.parent == Some("realtime_transcription")));
+}
+
+#[tokio::test]
+async fn routes_audio_frames_and_broadcasts_updates() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("local.".to_string())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.run().await.expect("bootstrap should succeed");
+
+    let mut broadcast_rx = manager.subscribe_updates();
+    let config = RealtimeSessionConfig {
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    };
+    let (handle, mut client_rx) = manager.start_realtime_transcription(config).await;
+
+    // Keep the handle alive for the duration of the test.
+    let _guard = handle;
+
+    let audio = manager.audio_pipeline();
+    audio
+        .push_pcm_frame(vec![0.25_f32; 1_600])
+        .await
+        .expect("push pcm frame");
+
+    let update = timeout(Duration::from_millis(600), client_rx.recv())
+        .await
+        .expect("client channel timed out")
+        .expect("client channel closed");
+
+    let transcript = match &update.payload {
+        UpdatePayload::Transcript(payload) => payload,
+        _ => panic!("expected transcript payload"),
+    };
+
+    assert_eq!(update.frame_index, 1);
+    assert_eq!(transcript.source, TranscriptSource::Local);
+    assert!(transcript.is_primary);
+    assert_eq!(transcript.text, "local.");
+
+    let broadcast_update = timeout(Duration::from_millis(600), broadcast_rx.recv())
+        .await
+        .expect("broadcast channel timed out")
+        .expect("broadcast channel closed");
+
+    assert_eq!(broadcast_update.frame_index, update.frame_index);
+    match broadcast_update.payload {
+        UpdatePayload::Transcript(_) => {}
+        _ => panic!("expected broadcast transcript payload"),
+    }
+}
+
+#[tokio::test]
+async fn broadcasts_polished_sentences_as_captions() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok(
+        "uh see you at the meeting tomorrow.".to_string(),
+    )]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.run().await.expect("bootstrap should succeed");
+    manager.enable_captions(CaptionConfig {
+        max_line_chars: 20,
+        ..CaptionConfig::default()
+    });
+    let mut captions_rx = manager.subscribe_captions();
+
+    let (_handle, _client_rx) = manager
+        .start_realtime_transcription(RealtimeSessionConfig::default())
+        .await;
+    manager
+        .audio_pipeline()
+        .push_pcm_frame(vec![0.25_f32; 1_600])
+        .await
+        .expect("push pcm frame");
+
+    let caption = timeout(Duration::from_secs(2), captions_rx.recv())
+        .await
+        .expect("caption timed out")
+        .expect("caption channel closed");
+    assert_eq!(caption.lines, vec!["See you at the", "meeting tomorrow."]);
+}
+
+#[tokio::test]
+async fn hold_to_talk_forwards_frames_only_while_key_is_held() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("held.".to_string())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.run().await.expect("bootstrap should succeed");
+    manager.set_capture_mode(CaptureMode::HoldToTalk);
+    let mut capture_rx = manager.subscribe_capture();
+
+    let config = RealtimeSessionConfig {
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    };
+    let (_handle, mut client_rx) = manager.start_realtime_transcription(config).await;
+    let audio = manager.audio_pipeline();
+
+    audio
+        .push_pcm_frame(vec![0.25_f32; 1_600])
+        .await
+        .expect("push pcm frame");
+    assert!(timeout(Duration::from_millis(200), client_rx.recv())
+        .await
+        .is_err());
+
+    let started = manager.report_hotkey(true).expect("press starts capture");
+    assert_eq!(started.transition, CaptureTransition::Started);
+    assert_eq!(capture_rx.recv().await.expect("capture event"), started);
+    audio
+        .push_pcm_frame(vec![0.25_f32; 1_600])
+        .await
+        .expect("push pcm frame");
+    let update = timeout(Duration::from_millis(600), client_rx.recv())
+        .await
+        .expect("client channel timed out")
+        .expect("client channel closed");
+    assert_eq!(update.frame_index, 1);
+
+    let stopped = manager.report_hotkey(false).expect("release stops capture");
+    assert_eq!(stopped.trigger, CaptureTrigger::Hotkey);
+    assert!(!manager.is_capturing());
+}
+
+#[tokio::test]
+async fn prepends_preroll_when_capture_starts() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![
+        Ok("first syllable".to_string()),
+        Ok("after press.".to_string()),
+    ]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.run().await.expect("bootstrap should succeed");
+    manager.set_capture_mode(CaptureMode::Toggle);
+    let audio = manager.audio_pipeline();
+    audio.set_preroll_window(StdDuration::from_millis(150));
+
+    let config = RealtimeSessionConfig {
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    };
+    let (_handle, mut client_rx) = manager.start_realtime_transcription(config).await;
+
+    // 超出预录窗口的旧帧被丢弃，只保留按键前最近的一帧。
+    for _ in 0..2 {
+        audio
+            .push_pcm_frame(vec![0.25_f32; 1_600])
+            .await
+            .expect("push pcm frame");
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    manager.report_hotkey(true).expect("press starts capture");
+    audio
+        .push_pcm_frame(vec![0.25_f32; 1_600])
+        .await
+        .expect("push pcm frame");
+
+    let update = timeout(Duration::from_millis(600), client_rx.recv())
+        .await
+        .expect("client channel timed out")
+        .expect("client channel closed");
+    let UpdatePayload::Transcript(transcript) = update.payload else {
+        panic!("expected transcript payload");
+    };
+    assert_eq!(update.frame_index, 2);
+    assert_eq!(transcript.text, "first syllable after press.");
+}
+
+#[tokio::test]
+async fn records_dictation_speed_from_voiced_frames() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let manager = SessionManager::with_components(
+        orchestrator,
+        Arc::new(StubPublisher::new(PublishOutcome {
+            status: PublisherStatus::Completed,
+            strategy: PublishStrategy::DirectInsert,
+            attempts: 1,
+            fallback: None,
+            failure: None,
+            undo_token: None,
+        })),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+    manager.set_active_session_id("session-speed").await;
+    let mut events = manager.subscribe_events();
+
+    let config = RealtimeSessionConfig {
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    };
+    let (_handle, _client_rx) = manager.start_realtime_transcription(config).await;
+    let audio = manager.audio_pipeline();
+    for index in 0..25 {
+        // Twenty 100ms frames of speech interleaved with five silent ones.
+        let level = if index % 5 == 4 { 0.0 } else { 0.25 };
+        audio
+            .push_pcm_frame(vec![level; 1_600])
+            .await
+            .expect("push pcm frame");
+    }
+    let speech_time = Arc::clone(&manager.speech_time);
+    timeout(Duration::from_secs(2), async {
+        loop {
+            let speech = speech_time
+                .lock()
+                .unwrap()
+                .get("session-speed")
+                .copied()
+                .unwrap_or_default();
+            if speech >= StdDuration::from_secs(2) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("speech accumulated");
+
+    let request = PublishRequest {
+        transcript: "one two three four five six".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+    manager
+        .publish_transcript(
+            make_snapshot("session-speed", "raw", "one two three four five six"),
+            request,
+        )
+        .await
+        .expect("publish should succeed");
+
+    let summary = timeout(Duration::from_millis(500), async {
+        loop {
+            if let SessionEvent::SpeedSummary(summary) =
+                events.recv().await.expect("event channel open")
+            {
+                break summary;
+            }
+        }
+    })
+    .await
+    .expect("speed summary emitted");
+    assert_eq!(summary.session_id, "session-speed");
+    assert_eq!(summary.speed.words, 6);
+    assert_eq!(summary.speed.speech_duration_ms, 2_000);
+    assert_eq!(summary.speed.words_per_minute, Some(180.0));
+
+    let entry = manager
+        .persistence
+        .load_session("session-speed".into())
+        .await
+        .unwrap()
+        .expect("entry exists");
+    assert_eq!(entry.speed, Some(summary.speed));
+}
+
+#[tokio::test]
+async fn amended_transcript_is_published_and_recorded() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let manager = SessionManager::with_components(
+        orchestrator,
+        Arc::new(StubPublisher::new(PublishOutcome {
+            status: PublisherStatus::Completed,
+            strategy: PublishStrategy::DirectInsert,
+            attempts: 1,
+            fallback: None,
+            failure: None,
+            undo_token: None,
+        })),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+    assert!(manager
+        .amend_transcript("session-amend", Vec::new())
+        .is_err());
+
+    manager.set_active_session_id("session-amend").await;
+    manager
+        .crash_guard
+        .record_transcript(1, "Meet at noon.", true);
+    manager
+        .crash_guard
+        .record_transcript(2, "Bring the deck.", true);
+    let edit = |sentence_id, text: &str| SentenceEdit {
+        sentence_id,
+        text: text.into(),
+    };
+    assert!(manager
+        .amend_transcript("session-other", vec![edit(1, "x")])
+        .is_err());
+    let amended = manager
+        .amend_transcript("session-amend", vec![edit(1, "Meet at one.")])
+        .expect("edit accepted");
+    assert_eq!(amended, "Meet at one. Bring the deck.");
+
+    let request = PublishRequest {
+        transcript: "Meet at noon. Bring the deck.".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+    manager
+        .publish_transcript(
+            make_snapshot(
+                "session-amend",
+                "meet at noon bring the deck",
+                "Meet at noon.",
+            ),
+            request,
+        )
+        .await
+        .expect("publish should succeed");
+    manager.clear_active_session_id().await;
+
+    let entry = manager
+        .load_history_entry("session-amend")
+        .await
+        .unwrap()
+        .expect("entry exists");
+    assert_eq!(entry.polished_transcript, "Meet at one. Bring the deck.");
+    let edits = &entry.metadata[amend::AMENDMENT_METADATA_KEY]["edits"];
+    assert_eq!(edits[0]["original"], "Meet at noon.");
+    assert_eq!(edits[0]["amended"], "Meet at one.");
+    assert!(manager.take_amendment("session-amend").is_none());
+}
+
+#[tokio::test]
+async fn shutdown_drains_sessions_and_checkpoints_before_returning() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(vec![Ok(
+            "words before exit.".to_string()
+        )])),
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.set_checkpoint_interval(None);
+    manager.set_active_session_id("session-shutdown").await;
+
+    let config = RealtimeSessionConfig {
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    };
+    let (_handle, mut client_rx) = manager.start_realtime_transcription(config).await;
+    manager
+        .audio_pipeline()
+        .push_pcm_frame(vec![0.25_f32; 1_600])
+        .await
+        .expect("push pcm frame");
+    timeout(Duration::from_secs(2), async {
+        while let Some(update) = client_rx.recv().await {
+            if matches!(update.payload, UpdatePayload::Transcript(_)) {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("transcript delivered");
+
+    // 宿主仍持有会话句柄，转发任务在期限到达后被中止。
+    timeout(
+        Duration::from_secs(2),
+        manager.shutdown_within(StdDuration::from_millis(100)),
+    )
+    .await
+    .expect("shutdown resolves within the deadline")
+    .expect("shutdown succeeds");
+
+    assert!(manager.audio_pipeline().is_stopped());
+    assert!(manager.shutdown.is_cancelled());
+    assert!(manager.realtime_tasks.lock().unwrap().is_empty());
+    let checkpoints = manager
+        .persistence_handle()
+        .list_checkpoints()
+        .await
+        .unwrap();
+    let checkpoint = checkpoints
+        .iter()
+        .find(|checkpoint| checkpoint.session_id == "session-shutdown")
+        .expect("final checkpoint written on shutdown");
+    assert_eq!(checkpoint.raw_transcript(), "words before exit.");
+    assert!(timeout(Duration::from_secs(1), client_rx.recv())
+        .await
+        .expect("update channel closes")
+        .is_none());
+}
+
+#[tokio::test]
+async fn history_records_loopback_audio_source() {
+    let manager = SessionManager::with_orchestrator(EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    ));
+    manager
+        .audio_pipeline()
+        .set_audio_source(AudioSource::SystemLoopback);
+    manager.set_active_session_id("session-loopback").await;
+    let (_handle, _client_rx) = manager
+        .start_realtime_transcription(RealtimeSessionConfig::default())
+        .await;
+
+    let request = PublishRequest {
+        transcript: "Quarterly numbers look good.".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+    let mut snapshot = make_snapshot(
+        "session-loopback",
+        "quarterly numbers look good",
+        "Quarterly numbers look good.",
+    );
+    snapshot.metadata = json!({ "origin": "desktop" });
+    manager
+        .publish_transcript(snapshot, request)
+        .await
+        .expect("publish should succeed");
+    manager.clear_active_session_id().await;
+
+    let entry = manager
+        .load_history_entry("session-loopback")
+        .await
+        .unwrap()
+        .expect("entry exists");
+    assert_eq!(entry.metadata[AUDIO_SOURCE_METADATA_KEY], "system_loopback");
+    assert_eq!(entry.metadata["origin"], "desktop");
+    assert!(manager.take_audio_source("session-loopback").is_none());
+}
+
+#[tokio::test]
+async fn history_is_tagged_with_the_current_calendar_meeting() {
+    struct FixedCalendar;
+
+    impl CalendarProvider for FixedCalendar {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn events_between(&self, from_ms: i64, to_ms: i64) -> Result<Vec<CalendarEvent>> {
+            Ok(vec![CalendarEvent {
+                title: "Roadmap review".into(),
+                starts_at_ms: from_ms,
+                ends_at_ms: to_ms,
+                attendees: vec![calendar::CalendarAttendee {
+                    name: Some("Alice".into()),
+                    email: Some("Alice@Example.com".into()),
+                }],
+                location: None,
+                provider: "fixed".into(),
+            }])
+        }
+    }
+
+    let manager = SessionManager::with_orchestrator(EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    ));
+    manager.set_calendar_provider(Some(Arc::new(FixedCalendar)));
+    manager.set_active_session_id("session-calendar").await;
+
+    let request = PublishRequest {
+        transcript: "Ship the beta next week.".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+    let snapshot = make_snapshot(
+        "session-calendar",
+        "ship the beta next week",
+        "Ship the beta next week.",
+    );
+    manager
+        .publish_transcript(snapshot, request)
+        .await
+        .expect("publish should succeed");
+    manager.clear_active_session_id().await;
+
+    let entry = manager
+        .load_history_entry("session-calendar")
+        .await
+        .unwrap()
+        .expect("entry exists");
+    assert_eq!(entry.meeting_title(), Some("Roadmap review"));
+    assert_eq!(
+        entry.metadata[calendar::CALENDAR_METADATA_KEY]["attendees"][0]["name"],
+        "Alice"
+    );
+    assert_eq!(
+        entry.tags,
+        vec!["meeting:Roadmap review", "attendee:alice@example.com"]
+    );
+
+    let page = manager
+        .search_history(HistoryQuery {
+            tag: Some("Meeting:roadmap REVIEW".into()),
+            limit: 10,
+            ..HistoryQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.entries.len(), 1);
+    let page = manager
+        .search_history(HistoryQuery {
+            tag: Some("meeting:standup".into()),
+            limit: 10,
+            ..HistoryQuery::default()
+        })
+        .await
+        .unwrap();
+    assert!(page.entries.is_empty());
+}
+
+#[tokio::test]
+async fn meeting_stores_labeled_topics_and_summary_in_history() {
+    /// The far end of the call is louder than the local microphone.
+    struct LoudnessEngine;
+
+    #[async_trait]
+    impl SpeechEngine for LoudnessEngine {
+        async fn transcribe(&self, frame: &[f32]) -> anyhow::Result<String> {
+            let peak = frame.iter().fold(0.0_f32, |peak, sample| peak.max(*sample));
+            Ok(if peak > 0.3 {
+                "We need to finalize the launch checklist.".into()
+            } else {
+                "I'll own the launch checklist.".into()
+            })
+        }
+    }
+
+    let manager = SessionManager::with_orchestrator(EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(LoudnessEngine),
+    ));
+    manager.set_meeting_summarizer(Arc::new(ExtractiveSummarizer));
+    let mixer = AudioMixer::new();
+    let microphone = AudioPipeline::new();
+    let loopback = AudioPipeline::new();
+    loopback.set_audio_source(AudioSource::SystemLoopback);
+    mixer.add_source(microphone.clone()).unwrap();
+    mixer.add_source(loopback.clone()).unwrap();
+
+    let (meeting, mut updates) = manager.start_meeting(
+        "session-meeting",
+        &mixer,
+        RealtimeSessionConfig {
+            enable_polisher: false,
+            ..RealtimeSessionConfig::default()
+        },
+    );
+    for _ in 0..4 {
+        microphone.push_pcm_frame(vec![0.2; 1_600]).await.unwrap();
+        loopback.push_pcm_frame(vec![0.5; 1_600]).await.unwrap();
+    }
+    microphone.flush_pending().await.unwrap();
+    loopback.flush_pending().await.unwrap();
+    timeout(Duration::from_secs(5), async {
+        while meeting.utterances().len() < 2 {
+            updates.recv().await.expect("meeting updates open");
+        }
+    })
+    .await
+    .expect("both speakers transcribed");
+
+    let entry = manager.finish_meeting(meeting).await.unwrap();
+    assert_eq!(
+        entry.metadata[meeting::SESSION_TYPE_METADATA_KEY],
+        meeting::MEETING_SESSION_TYPE
+    );
+    assert_eq!(
+        entry.metadata["audioSources"],
+        json!(["microphone", "system_loopback"])
+    );
+    assert!(entry
+        .polished_transcript
+        .contains("me: I'll own the launch checklist."));
+    let notes = entry.meeting.expect("meeting notes stored");
+    assert_eq!(notes.topics.len(), 1);
+    assert!(notes.topics[0].title.contains("launch"));
+    assert!(notes.summary.contains("launch checklist"));
+    let owners: Vec<_> = notes
+        .action_items
+        .iter()
+        .filter_map(|item| item.owner.as_deref())
+        .collect();
+    assert!(owners.contains(&"me") && owners.contains(&"others"));
+}
+
+#[tokio::test]
+async fn session_follows_pipeline_frame_window() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(Vec::new()));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    let audio = manager.audio_pipeline();
+    audio.set_frame_window(StdDuration::from_millis(100));
+
+    let (handle, _client_rx) = manager
+        .start_realtime_transcription(RealtimeSessionConfig::default())
+        .await;
+    assert_eq!(handle.frame_window().cadence(), Duration::from_millis(100));
+
+    audio.set_frame_window(StdDuration::from_millis(200));
+    timeout(Duration::from_millis(500), async {
+        while handle.frame_window().cadence() != Duration::from_millis(200) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("session cadence follows the pipeline");
+}
+
+#[tokio::test]
+async fn delivers_warn_notice_to_slow_clients() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![
+        Ok("local-fast.".to_string()),
+        Err(anyhow!("local failure")),
+    ]));
+    let cloud_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("cloud.".to_string())]));
+    let orchestrator = EngineOrchestrator::with_engines(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+        Some(cloud_engine),
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.run().await.expect("bootstrap should succeed");
+
+    let mut broadcast_rx = manager.subscribe_updates();
+    let config = RealtimeSessionConfig {
+        buffer_capacity: 1,
+        enable_polisher: false,
+        ..RealtimeSessionConfig::default()
+    };
+    let (handle, mut client_rx) = manager.start_realtime_transcription(config).await;
+    let _guard = handle;
+
+    let audio = manager.audio_pipeline();
+    audio
+        .push_pcm_frame(vec![0.5_f32; 1_600])
+        .await
+        .expect("push first frame");
+    audio
+        .push_pcm_frame(vec![0.5_f32; 1_600])
+        .await
+        .expect("push second frame");
+
+    // Drain the first transcript after both frames are queued so the WARN notice
+    // arrives while the client queue is full.
+    let first_update = timeout(Duration::from_millis(600), client_rx.recv())
+        .await
+        .expect("first client update timed out")
+        .expect("client channel closed");
+    match &first_update.payload {
+        UpdatePayload::Transcript(payload) => {
+            assert_eq!(payload.text, "local-fast.");
+            assert_eq!(payload.source, TranscriptSource::Local);
+        }
+        _ => panic!("expected first transcript"),
+    }
+
+    // 尽力通道中的转写可能先于提示送达，但提示本身不会被丢弃。
+    let warn_notice = timeout(Duration::from_millis(800), async {
+        loop {
+            let update = client_rx.recv().await.expect("warn update missing");
+            if let UpdatePayload::Notice(notice) = update.payload {
+                break notice;
+            }
+        }
+    })
+    .await
+    .expect("warn update timed out");
+    assert_eq!(warn_notice.level, NoticeLevel::Error);
+    assert!(warn_notice.message.contains("切换云端"));
+
+    // Broadcast channel should also see the WARN/Error notice.
+    let broadcast_notice = timeout(Duration::from_millis(800), async {
+        loop {
+            match broadcast_rx.recv().await {
+                Ok(update) => match update.payload {
+                    UpdatePayload::Notice(notice) => break notice,
+                    _ => continue,
+                },
+                Err(err) => panic!("broadcast closed: {err}"),
+            }
+        }
+    })
+    .await
+    .expect("broadcast timed out");
+    assert!(matches!(
+        broadcast_notice.level,
+        NoticeLevel::Warn | NoticeLevel::Error
+    ));
+}
+
+#[tokio::test]
+async fn publishes_transcript_and_emits_lifecycle_updates() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("local.".into())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+
+    let mut lifecycle_rx = manager.subscribe_lifecycle();
+    let snapshot = make_snapshot("session-1", "raw", "polished");
+    let request = PublishRequest {
+        transcript: "polished".into(),
+        focus: FocusWindowContext::from_app_identifier("com.example.app"),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+
+    let outcome = manager
+        .publish_transcript(snapshot, request)
+        .await
+        .expect("publish should succeed");
+
+    assert_eq!(outcome.status, PublisherStatus::Completed);
+    assert_eq!(outcome.strategy, PublishStrategy::DirectInsert);
+
+    let publishing_update = lifecycle_rx
+        .recv()
+        .await
+        .expect("publishing update missing");
+    assert_eq!(publishing_update.phase, SessionLifecyclePhase::Publishing);
+
+    let completion_update = lifecycle_rx
+        .recv()
+        .await
+        .expect("completion update missing");
+    assert_eq!(completion_update.phase, SessionLifecyclePhase::Completed);
+    match completion_update.payload {
+        SessionLifecyclePayload::Completed(payload) => {
+            assert_eq!(payload.outcome.status, PublisherStatus::Completed);
+        }
+        other => panic!("unexpected payload: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn surfaces_publisher_errors_and_emits_failure_update() {
+    let local_engine = Arc::new(ProgrammedSpeechEngine::new(vec![Ok("local.".into())]));
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        local_engine,
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+
+    let mut lifecycle_rx = manager.subscribe_lifecycle();
+    let snapshot = make_snapshot("session-2", "raw", "");
+    let request = PublishRequest {
+        transcript: "   ".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::NotifyOnly,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+
+    let result = manager.publish_transcript(snapshot, request).await;
+    assert!(result.is_err());
+
+    let publishing_update = lifecycle_rx
+        .recv()
+        .await
+        .expect("publishing update missing");
+    assert_eq!(publishing_update.phase, SessionLifecyclePhase::Publishing);
+
+    let failure_update = lifecycle_rx.recv().await.expect("failure update missing");
+    assert_eq!(failure_update.phase, SessionLifecyclePhase::Failed);
+    match failure_update.payload {
+        SessionLifecyclePayload::Failed(payload) => {
+            assert_eq!(payload.attempts, 1);
+            assert_eq!(payload.fallback, Some(FallbackStrategy::NotifyOnly));
+        }
+        other => panic!("unexpected payload: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn clipboard_fallback_copies_and_notifies() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+
+    let failure = PublisherFailure::new(PublisherFailureCode::Timeout, "operation timed out");
+    let outcome = PublishOutcome {
+        status: PublisherStatus::Failed,
+        strategy: PublishStrategy::DirectInsert,
+        attempts: 2,
+        fallback: None,
+        failure: Some(failure.clone()),
+        undo_token: None,
+    };
+
+    let publisher = Arc::new(StubPublisher::new(outcome));
+    let clipboard_access = RecordingClipboard::default();
+    let clipboard = ClipboardManager::new(Arc::new(clipboard_access.clone()));
+    let manager = SessionManager::with_components(orchestrator, publisher, clipboard);
+
+    let mut updates_rx = manager.subscribe_updates();
+    let snapshot = make_snapshot("session-fallback", "raw", "polished");
+    let request = PublishRequest {
+        transcript: "polished".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+
+    let outcome = manager
+        .publish_transcript(snapshot, request)
+        .await
+        .expect("publish should succeed");
+
+    assert_eq!(outcome.status, PublisherStatus::Deferred);
+    assert_eq!(outcome.strategy, PublishStrategy::ClipboardFallback);
+    assert_eq!(outcome.fallback, Some(FallbackStrategy::ClipboardCopy));
+    match outcome.failure {
+        Some(ref failure) => assert_eq!(failure.code, PublisherFailureCode::Timeout),
+        None => panic!("expected failure context"),
+    }
+
+    let clipboard_contents = clipboard_access.contents().await;
+    assert_eq!(clipboard_contents.as_deref(), Some("polished"));
+
+    let notice = updates_rx.recv().await.expect("notice missing");
+    match notice.payload {
+        UpdatePayload::Notice(SessionNotice { level, message }) => {
+            assert_eq!(level, NoticeLevel::Warn);
+            assert!(message.contains("已将润色稿复制到剪贴板"));
+        }
+        _ => panic!("expected clipboard fallback notice"),
+    }
+
+    let notices = manager
+        .persistence_handle()
+        .list_notices(10)
+        .await
+        .expect("persisted notices available");
+    assert!(notices
+        .iter()
+        .any(|entry| entry.action == NOTICE_ACTION_COPY && entry.result == NOTICE_RESULT_SUCCESS));
+}
+
+#[tokio::test]
+async fn hotkey_actions_dispatch_to_session_apis() {
+    let inserted = StubPublisher::new(PublishOutcome::completed());
+    let manager = SessionManager::with_components(
+        EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+        ),
+        Arc::new(inserted.clone()),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+
+    assert!(manager
+        .dispatch_action(HotkeyAction::CancelSession)
+        .await
+        .is_err());
+    manager.set_capture_mode(CaptureMode::Toggle);
+    let mut capture_rx = manager.subscribe_capture();
+    manager.report_hotkey(true).expect("press starts capture");
+    manager
+        .dispatch_action(HotkeyAction::CancelSession)
+        .await
+        .expect("cancel capture");
+    capture_rx.recv().await.expect("started event");
+    let cancelled = capture_rx.recv().await.expect("stopped event");
+    assert_eq!(cancelled.trigger, CaptureTrigger::Discard);
+    assert!(!manager.is_capturing());
+
+    assert!(manager
+        .dispatch_action(HotkeyAction::UndoPublish)
+        .await
+        .is_err());
+    manager
+        .publish_transcript(
+            make_snapshot("session-hotkey-undo", "raw", "polished"),
+            PublishRequest {
+                transcript: "polished".into(),
+                focus: FocusWindowContext::default(),
+                fallback: FallbackStrategy::None,
+                insertion: InsertionMethod::default(),
+                strategy: None,
+                html: None,
+            },
+        )
+        .await
+        .expect("publish should succeed");
+    manager
+        .dispatch_action(HotkeyAction::UndoPublish)
+        .await
+        .expect("undo last publish");
+    assert_eq!(*inserted.undone.lock().unwrap(), vec!["polished"]);
+
+    assert!(manager
+        .dispatch_action(HotkeyAction::RepolishLast)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn polish_profiles_resolve_per_target_app() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let manager = SessionManager::with_components(
+        orchestrator,
+        Arc::new(StubPublisher::new(PublishOutcome {
+            status: PublisherStatus::Completed,
+            strategy: PublishStrategy::DirectInsert,
+            attempts: 1,
+            fallback: None,
+            failure: None,
+            undo_token: None,
+        })),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+
+    let vscode = FocusWindowContext::from_app_identifier("com.microsoft.VSCode");
+    assert_eq!(manager.polish_profile_for(&vscode), None);
+    manager
+        .set_polish_profile(None, PolishProfile::Casual)
+        .await
+        .expect("default profile saved");
+    manager
+        .set_polish_profile(
+            Some("com.microsoft.VSCode".into()),
+            PolishProfile::CodeComment,
+        )
+        .await
+        .expect("app profile saved");
+
+    assert_eq!(
+        manager.polish_profile_for(&vscode),
+        Some(PolishProfile::CodeComment)
+    );
+    assert_eq!(
+        manager.polish_profile_for(&FocusWindowContext::default()),
+        Some(PolishProfile::Casual)
+    );
+    assert!(manager
+        .clear_polish_profile(Some("com.microsoft.VSCode".into()))
+        .await
+        .expect("profile cleared"));
+    assert_eq!(
+        manager.polish_profile_for(&vscode),
+        Some(PolishProfile::Casual)
+    );
+    assert_eq!(manager.polish_profiles().len(), 1);
+}
+
+#[tokio::test]
+async fn app_profile_overrides_publish_preferences() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let clipboard_access = RecordingClipboard::default();
+    let manager = SessionManager::with_components(
+        orchestrator,
+        Arc::new(StubPublisher::new(PublishOutcome {
+            status: PublisherStatus::Failed,
+            strategy: PublishStrategy::DirectInsert,
+            attempts: 1,
+            fallback: None,
+            failure: Some(PublisherFailure::new(
+                PublisherFailureCode::Timeout,
+                "operation timed out",
+            )),
+            undo_token: None,
+        })),
+        ClipboardManager::new(Arc::new(clipboard_access.clone())),
+    );
+
+    let mut profile = AppProfile::new("com.agilebits.onepassword");
+    profile.fallback = Some(FallbackStrategy::NotifyOnly);
+    profile.polish_profile = Some(PolishProfile::Formal);
+    manager
+        .save_app_profile(profile)
+        .await
+        .expect("app profile saved");
+    manager
+        .set_polish_profile(None, PolishProfile::Casual)
+        .await
+        .expect("default profile saved");
+
+    let focus = FocusWindowContext::from_app_identifier("com.agilebits.onepassword");
+    assert_eq!(
+        manager.polish_profile_for(&focus),
+        Some(PolishProfile::Formal)
+    );
+
+    let outcome = manager
+        .publish_transcript(
+            make_snapshot("session-app-profile", "raw", "polished"),
+            PublishRequest {
+                transcript: "polished".into(),
+                focus,
+                fallback: FallbackStrategy::ClipboardCopy,
+                insertion: InsertionMethod::default(),
+                strategy: None,
+                html: None,
+            },
+        )
+        .await
+        .expect("publish should return outcome");
+    assert_eq!(outcome.status, PublisherStatus::Failed);
+    assert!(clipboard_access.contents().await.is_none());
+
+    assert!(manager
+        .remove_app_profile("com.agilebits.onepassword".into(), None)
+        .await
+        .expect("app profile removed"));
+    assert!(manager.app_profiles().is_empty());
+}
+
+#[tokio::test]
+async fn replacement_rules_expand_before_publishing() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let outcome = PublishOutcome {
+        status: PublisherStatus::Failed,
+        strategy: PublishStrategy::DirectInsert,
+        attempts: 1,
+        fallback: None,
+        failure: Some(PublisherFailure::new(
+            PublisherFailureCode::Timeout,
+            "operation timed out",
+        )),
+        undo_token: None,
+    };
+    let clipboard_access = RecordingClipboard::default();
+    let manager = SessionManager::with_components(
+        orchestrator,
+        Arc::new(StubPublisher::new(outcome)),
+        ClipboardManager::new(Arc::new(clipboard_access.clone())),
+    );
+
+    let mail_only = ReplacementRule {
+        rule_id: "mail-sig".into(),
+        pattern: "sign off".into(),
+        replacement: "Best regards".into(),
+        is_regex: false,
+        app_identifier: Some("com.apple.mail".into()),
+        enabled: true,
+        created_at_ms: 0,
+        updated_at_ms: 0,
+    };
+    manager
+        .save_replacement_rule(ReplacementRule {
+            rule_id: "email".into(),
+            pattern: "my email".into(),
+            replacement: "me@example.com".into(),
+            app_identifier: None,
+            ..mail_only.clone()
+        })
+        .await
+        .expect("rule saved");
+    manager
+        .save_replacement_rule(mail_only)
+        .await
+        .expect("rule saved");
+    assert!(manager
+        .save_replacement_rule(ReplacementRule {
+            rule_id: "broken".into(),
+            pattern: "(".into(),
+            replacement: String::new(),
+            is_regex: true,
+            app_identifier: None,
+            enabled: true,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+        })
+        .await
+        .is_err());
+    assert_eq!(manager.replacement_rules().await.unwrap().len(), 2);
+
+    let request = PublishRequest {
+        transcript: "reach me at my email, sign off".into(),
+        focus: FocusWindowContext::from_app_identifier("com.example.app"),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+    manager
+        .publish_transcript(make_snapshot("session-rules", "raw", "polished"), request)
+        .await
+        .expect("publish should succeed");
+    assert_eq!(
+        clipboard_access.contents().await.as_deref(),
+        Some("reach me at me@example.com, sign off")
+    );
+
+    assert!(manager
+        .remove_replacement_rule("email".into())
+        .await
+        .unwrap());
+    assert_eq!(manager.replacement_rules().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn clipboard_fallback_failure_updates_outcome() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+
+    let failure = PublisherFailure::new(PublisherFailureCode::Timeout, "operation timed out");
+    let outcome = PublishOutcome {
+        status: PublisherStatus::Failed,
+        strategy: PublishStrategy::DirectInsert,
+        attempts: 1,
+        fallback: None,
+        failure: Some(failure),
+        undo_token: None,
+    };
+
+    let publisher = Arc::new(StubPublisher::new(outcome));
+    let clipboard_access = RecordingClipboard::default();
+    clipboard_access
+        .set_write_error(ClipboardError::write("permission denied"))
+        .await;
+    let clipboard = ClipboardManager::new(Arc::new(clipboard_access.clone()));
+    let manager = SessionManager::with_components(orchestrator, publisher, clipboard);
+
+    let mut updates_rx = manager.subscribe_updates();
+    let snapshot = make_snapshot("session-fallback-fail", "raw", "polished");
+    let request = PublishRequest {
+        transcript: "polished".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::default(),
+        strategy: None,
+        html: None,
+    };
+
+    let outcome = manager
+        .publish_transcript(snapshot, request)
+        .await
+        .expect("publish should surface fallback failure");
+
+    assert_eq!(outcome.status, PublisherStatus::Failed);
+    let failure = outcome.failure.expect("failure details missing");
+    assert!(failure.message.contains("operation timed out"));
+    assert!(failure.message.contains("剪贴板复制失败"));
+
+    let notice = updates_rx.recv().await.expect("failure notice missing");
+    match notice.payload {
+        UpdatePayload::Notice(SessionNotice { level, message }) => {
+            assert_eq!(level, NoticeLevel::Error);
+            assert!(message.contains("自动降级失败"));
+            assert!(message.contains("手动复制"));
+        }
+        _ => panic!("expected clipboard failure notice"),
+    }
+
+    let notices = manager
+        .persistence_handle()
+        .list_notices(10)
+        .await
+        .expect("persisted notices available");
+    assert!(notices
+        .iter()
+        .any(|entry| entry.action == NOTICE_ACTION_COPY && entry.result == NOTICE_RESULT_FAILURE));
+}
+
+#[tokio::test]
+async fn configured_recording_is_readable_by_a_fresh_recorder() {
+    let dir = tempfile::tempdir().expect("data dir");
+    let store: Arc<dyn secrets::SecretStore> = Arc::new(
+        secrets::EncryptedFileStore::open(&dir.path().join("secrets")).expect("secret store"),
+    );
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    )
+    .with_secret_store(Arc::clone(&store));
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    manager.enable_configured_recording(dir.path()).await;
+
+    manager.set_active_session_id("session-configured").await;
+    let audio = manager.audio_pipeline();
+    for _ in 0..3 {
+        audio
+            .push_pcm_frame(vec![0.5_f32; 1_600])
+            .await
+            .expect("push pcm frame");
+    }
+    manager.clear_active_session_id().await;
+
+    // 桌面壳另行打开录音目录，只依赖密钥库中的同一把密钥。
+    let desktop = SessionRecorder::open(dir.path(), store.as_ref()).expect("open recorder");
+    let recorded = desktop.load("session-configured").expect("load recording");
+    assert_eq!(recorded.samples.len(), 4_800);
+    assert!(desktop
+        .archive_path("session-configured")
+        .starts_with(dir.path().join(crate::audio::RECORDINGS_DIR)));
+    assert!(desktop.archive_path("session-configured").exists());
+}
+
+#[tokio::test]
+async fn records_active_session_audio_and_plays_it_back() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+    let dir = tempfile::tempdir().expect("recording dir");
+    let keys = crate::audio::AudioCacheKeys::derive(&[3u8; 32]).expect("derive keys");
+    let recorder = SessionRecorder::new(dir.path(), keys);
+    manager.enable_recording(recorder.clone()).await;
+
+    let audio = manager.audio_pipeline();
+    audio.enable_agc(crate::audio::AgcConfig::default());
+    manager.set_active_session_id("session-recorded").await;
+    for _ in 0..5 {
+        audio
+            .push_pcm_frame(vec![0.25_f32; 1_600])
+            .await
+            .expect("push pcm frame");
+    }
+    manager.clear_active_session_id().await;
+
+    let recorded = manager
+        .load_history_audio("session-recorded")
+        .await
+        .expect("load recording")
+        .expect("recording exists");
+    assert_eq!(recorded.samples.len(), 8_000);
+    // 录音取自增益之前：0.25 的输入应原样保存，而 AGC 会把它压向 0.2。
+    assert!(recorded
+        .samples
+        .iter()
+        .all(|sample| (8_191..=8_192).contains(sample)));
+    assert!(recorder.active_session().await.is_none());
+    assert!(manager
+        .load_history_audio("session-missing")
+        .await
+        .expect("missing recording lookup")
+        .is_none());
+
+    manager.disable_recording().await;
+    assert!(manager
+        .load_history_audio("session-recorded")
+        .await
+        .expect("lookup without recorder")
+        .is_none());
+}
+
+#[tokio::test]
+async fn saves_transcript_draft_and_records_history() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let manager = SessionManager::with_orchestrator(orchestrator);
+
+    let request = DraftSaveRequest {
+        draft_id: "draft-001".into(),
+        session_id: "session-save".into(),
+        content: "Draft body".into(),
+        title: None,
+        tags: None,
+    };
+
+    let record = manager
+        .save_transcript_draft(request)
+        .await
+        .expect("draft save should succeed");
+    assert_eq!(record.title, "Polished transcript");
+    assert_eq!(record.tags, vec!["transcript".to_string()]);
+
+    let drafts = manager
+        .persistence_handle()
+        .list_drafts(5)
+        .await
+        .expect("draft history available");
+    assert!(drafts.iter().any(|draft| draft.draft_id == "draft-001"));
+}
+
+#[derive(Clone, Default)]
+pub(super) struct ToggledPublisher {
+    pub(super) ready: Arc<AtomicBool>,
+    pub(super) published: Arc<StdMutex<Vec<String>>>,
+    requests: Arc<StdMutex<Vec<PublishRequest>>>,
+    field_role: Option<FieldRole>,
+}
+
+#[async_trait]
+impl SessionPublisher for ToggledPublisher {
+    async fn publish(&self, request: PublishRequest) -> Result<PublishOutcome, PublisherError> {
+        if !self.ready.load(Ordering::SeqCst) {
+            return Ok(PublishOutcome::failed(
+                1,
+                PublishStrategy::DirectInsert,
+                None,
+                PublisherFailure::new(PublisherFailureCode::FocusLost, "focus target lost"),
+            ));
+        }
+        self.published
+            .lock()
+            .unwrap()
+            .push(request.transcript.clone());
+        self.requests.lock().unwrap().push(request);
+        Ok(PublishOutcome::completed())
+    }
+
+    async fn inspect_field_role(&self, _focus: &FocusWindowContext) -> Option<FieldRole> {
+        self.field_role
+    }
+}
+
+#[tokio::test]
+async fn wait_for_focus_defers_insert_until_target_is_frontmost() {
+    let orchestrator = EngineOrchestrator::with_engine(
+        EngineConfig {
+            prefer_cloud: false,
+        },
+        Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+    );
+    let publisher = ToggledPublisher::default();
+    publisher.ready.store(true, Ordering::SeqCst);
+    let manager = SessionManager::with_components(
+        orchestrator,
+        Arc::new(publisher.clone()),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+    let request = |limit| PublishRequest {
+        transcript: "polished".into(),
+        focus: FocusWindowContext::default(),
+        fallback: FallbackStrategy::None,
+        insertion: InsertionMethod::default(),
+        strategy: Some(PublishStrategy::WaitForFocus {
+            app_identifier: "com.example.Loading".into(),
+            timeout: limit,
+        }),
+        html: None,
+    };
+
+    let mut lifecycle = manager.subscribe_lifecycle();
+    let (outcome, ()) = tokio::join!(
+        manager.publish_transcript(
+            make_snapshot("session-wait-focus", "raw", "polished"),
+            request(Duration::from_secs(2)),
+        ),
+        async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(publisher.published.lock().unwrap().is_empty());
+            manager.report_focus(FocusWindowContext::from_app_identifier(
+                "com.example.loading",
+            ));
+        }
+    );
+    let outcome = outcome.expect("publish should succeed");
+    assert_eq!(outcome.status, PublisherStatus::Completed);
+    assert_eq!(*publisher.published.lock().unwrap(), vec!["polished"]);
+    let first = lifecycle.recv().await.expect("publishing update");
+    match first.payload {
+        SessionLifecyclePayload::Publishing(payload) => {
+            assert_eq!(payload.strategy.as_str(), "wait_for_focus")
+        }
+        other => panic!("expected publishing payload, got {other:?}"),
+    }
+
+    manager.report_focus(FocusWindowContext::from_app_identifier("com.example.Other"));
+    let outcome = manager
+        .publish_transcript(
+            make_snapshot("session-wait-focus-timeout", "raw", "polished"),
+            request(Duration::from_millis(50)),
+        )
+        .await
+        .expect("publish should surface timeout");
+    assert_eq!(outcome.status, PublisherStatus::Failed);
+    let failure = outcome.failure.expect("failure details");
+    assert_eq!(failure.code, PublisherFailureCode::FocusLost);
+    assert_eq!(publisher.published.lock().unwrap().len(), 1);
+    for entry in manager.pending_publish_retries().await.unwrap() {
+        if entry.session_id == "session-wait-focus-timeout" {
+            manager
+                .discard_publish_retry(&entry.retry_id)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn password_fields_are_blocked_and_field_roles_select_profiles() {
+    let engine = || {
+        EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+        )
+    };
+    let request = || PublishRequest {
+        transcript: "polished".into(),
+        focus: FocusWindowContext::from_app_identifier("com.example.FieldRoles"),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::Auto,
+        strategy: None,
+        html: None,
+    };
+
+    let publisher = ToggledPublisher {
+        field_role: Some(FieldRole::PasswordField),
+        ..ToggledPublisher::default()
+    };
+    publisher.ready.store(true, Ordering::SeqCst);
+    let clipboard_access = RecordingClipboard::default();
+    let manager = SessionManager::with_components(
+        engine(),
+        Arc::new(publisher.clone()),
+        ClipboardManager::new(Arc::new(clipboard_access.clone())),
+    );
+    let outcome = manager
+        .publish_transcript(
+            make_snapshot("session-password-block", "raw", "polished"),
+            request(),
+        )
+        .await
+        .expect("blocked publish still returns an outcome");
+    assert_eq!(outcome.status, PublisherStatus::Failed);
+    assert_eq!(
+        outcome.failure.map(|failure| failure.code),
+        Some(PublisherFailureCode::PasswordField)
+    );
+    assert!(publisher.published.lock().unwrap().is_empty());
+    assert_eq!(clipboard_access.contents().await, None);
+
+    manager.set_password_field_policy(PasswordFieldPolicy::Warn);
+    let outcome = manager
+        .publish_transcript(
+            make_snapshot("session-password-warn", "raw", "polished"),
+            request(),
+        )
+        .await
+        .expect("warned publish succeeds");
+    assert_eq!(outcome.status, PublisherStatus::Completed);
+
+    let publisher = ToggledPublisher {
+        field_role: Some(FieldRole::SearchBox),
+        ..ToggledPublisher::default()
+    };
+    publisher.ready.store(true, Ordering::SeqCst);
+    let manager = SessionManager::with_components(
+        engine(),
+        Arc::new(publisher.clone()),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+    let mut search = AppProfile::new("com.example.FieldRoles");
+    search.field_role = Some(FieldRole::SearchBox);
+    search.insertion = Some(InsertionMethod::Keystrokes);
+    manager
+        .save_app_profile(search)
+        .await
+        .expect("profile saved");
+    manager
+        .publish_transcript(
+            make_snapshot("session-search-field", "raw", "polished"),
+            request(),
+        )
+        .await
+        .expect("publish succeeds");
+    let requests = publisher.requests.lock().unwrap().clone();
+    assert_eq!(requests[0].focus.field_role, Some(FieldRole::SearchBox));
+    assert_eq!(requests[0].insertion, InsertionMethod::Keystrokes);
+    manager
+        .remove_app_profile("com.example.FieldRoles".into(), Some(FieldRole::SearchBox))
+        .await
+        .expect("profile removed");
+}
+
+#[tokio::test]
+async fn transcripts_are_formatted_for_the_target_app() {
+    let publisher = ToggledPublisher::default();
+    publisher.ready.store(true, Ordering::SeqCst);
+    let manager = SessionManager::with_components(
+        EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+        ),
+        Arc::new(publisher.clone()),
+        ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+    );
+    let request = |app: &str| PublishRequest {
+        transcript: "Deploy **today**\n- build\n- ship".into(),
+        focus: FocusWindowContext::from_app_identifier(app),
+        fallback: FallbackStrategy::ClipboardCopy,
+        insertion: InsertionMethod::Auto,
+        strategy: None,
+        html: None,
+    };
+
+    let mut profile = AppProfile::new("com.example.Formatting");
+    profile.output_format = Some(OutputFormat::Html);
+    manager
+        .save_app_profile(profile)
+        .await
+        .expect("profile saved");
+    for (session_id, app) in [
+        ("session-format-terminal", "com.apple.Terminal"),
+        ("session-format-rich", "com.example.Formatting"),
+    ] {
+        manager
+            .publish_transcript(make_snapshot(session_id, "raw", "polished"), request(app))
+            .await
+            .expect("publish succeeds");
+    }
+
+    let requests = publisher.requests.lock().unwrap().clone();
+    assert_eq!(requests[0].transcript, "Deploy today - build - ship");
+    assert_eq!(requests[0].html, None);
+    assert_eq!(requests[1].transcript, "Deploy today\n\n- build\n- ship");
+    assert_eq!(
+        requests[1].html.as_deref(),
+        Some("<p>Deploy <strong>today</strong></p><ul><li>build</li><li>ship</li></ul>")
+    );
+    manager
+        .remove_app_profile("com.example.Formatting".into(), None)
+        .await
+        .expect("profile removed");
+}
+
+#[tokio::test]
+async fn tenant_policy_locks_egress_audit_and_blocks_undeclared_regions() {
+    use crate::config::{SignedTenantPolicy, TenantPolicy};
+    use crate::orchestrator::{CloudBlock, CloudFeature};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("generate key");
+    let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("parse key");
+    let raw = serde_json::to_vec(&TenantPolicy {
+        tenant_id: "acme".into(),
+        audit_egress: true,
+        cloud_regions: Some(vec!["eu-central-1".into()]),
+        ..TenantPolicy::default()
+    })
+    .expect("serialize policy");
+    let engine = PolicyEngine::new(keypair.public_key().as_ref());
+    engine
+        .load(SignedTenantPolicy {
+            policy: BASE64.encode(&raw),
+            signature: BASE64.encode(keypair.sign(&raw).as_ref()),
+        })
+        .expect("load policy");
+
+    let manager = SessionManager::new().expect("manager initialises");
+    manager
+        .set_egress_audit(false)
+        .expect("no policy applied yet");
+    manager
+        .apply_tenant_policy(&engine)
+        .await
+        .expect("default config satisfies the policy");
+
+    assert!(manager.egress_audit_enabled());
+    assert!(matches!(
+        manager.set_egress_audit(false),
+        Err(PolicyError::Violation(_))
+    ));
+    assert!(manager.egress_audit_enabled());
+    manager.set_egress_audit(true).expect("enabling is allowed");
+
+    // 默认配置未声明云端识别所在区域。
+    assert_eq!(
+        manager
+            .orchestrator
+            .offline_guard()
+            .feature_block(CloudFeature::Transcription),
+        Some(CloudBlock::TenantPolicy)
+    );
+}
+
+#[tokio::test]
+async fn history_actions_run_registered_plugins() {
+    struct TicketAction;
+
+    #[async_trait]
+    impl ActionPlugin for TicketAction {
+        fn id(&self) -> &str {
+            "jira"
+        }
+
+        async fn execute(
+            &self,
+            entry: &HistoryEntry,
+            params: &serde_json::Value,
+        ) -> Result<serde_json::Value> {
+            Ok(json!({
+                "project": params["project"],
+                "summary": entry.polished_transcript,
+            }))
+        }
+    }
+
+    let manager = SessionManager::new().expect("manager initialises");
+    manager.register_history_action(Arc::new(TicketAction));
+    manager
+        .persistence
+        .persist_session(make_snapshot("session-history-plugin", "raw", "file a bug"))
+        .await
+        .expect("snapshot persisted");
+
+    let actions = manager
+        .record_history_action(
+            "session-history-plugin".into(),
+            HistoryPostAction {
+                kind: HistoryActionKind::Plugin,
+                timestamp_ms: 10,
+                detail: json!({ "plugin": "jira", "project": "FW" }),
+            },
+        )
+        .await
+        .expect("plugin action recorded");
+    let detail = &actions.last().expect("action stored").detail;
+    assert_eq!(detail["status"], "succeeded");
+    assert_eq!(detail["result"]["summary"], "file a bug");
+    assert_eq!(detail["result"]["project"], "FW");
+
+    let failed = manager
+        .record_history_action(
+            "session-history-plugin".into(),
+            HistoryPostAction {
+                kind: HistoryActionKind::Webhook,
+                timestamp_ms: 20,
+                detail: json!({ "url": "ftp://example.com" }),
+            },
+        )
+        .await;
+    assert!(failed.is_err());
+    let entry = manager
+        .persistence
+        .load_session("session-history-plugin".into())
+        .await
+        .unwrap()
+        .expect("entry exists");
+    assert_eq!(entry.post_actions.len(), 2);
+    assert_eq!(entry.post_actions[1].detail["status"], "failed");
+}
+
+#[tokio::test]
+async fn recovery_snapshot_survives_history_key_rotation() {
+    use crate::persistence::sqlite::{SecretStoreKeyResolver, SqlitePath};
+    use crate::secrets::EncryptedFileStore;
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = EncryptedFileStore::open(&dir.path().join("secrets")).unwrap();
+    let config = SqliteConfig {
+        path: SqlitePath::File(dir.path().join("history.db")),
+        key_resolver: Arc::new(SecretStoreKeyResolver::new(Arc::new(store))),
+        ..SqliteConfig::memory()
+    };
+    config.key_resolver.store_key("old-secret").unwrap();
+
+    let mut manager = SessionManager::new().expect("manager initialises");
+    manager.persistence = spawn_persistence_runtime(config).unwrap();
+    manager.crash_guard = CrashGuard::new(dir.path().join("recovery"))
+        .with_key_material("old-secret")
+        .unwrap();
+
+    manager
+        .rotate_history_key("new-secret".into(), None)
+        .await
+        .expect("rotation succeeds");
+    let guard = manager.crash_guard();
+    guard.begin("session-rotated");
+    guard.record_transcript(1, "after rotation", false);
+    assert!(guard.write_recovery("panic").unwrap());
+
+    let restarted = CrashGuard::new(dir.path().join("recovery"))
+        .with_key_material("new-secret")
+        .unwrap();
+    let recovered = restarted
+        .take_orphaned()
+        .unwrap()
+        .expect("snapshot readable with the rotated key");
+    assert_eq!(recovered.raw_transcript(), "after rotation");
+}
@@ -0,0 +1,228 @@
+//! 发布撤销：直接插入或剪贴板降级后的一段时间内，可凭令牌撤回本次发布。
+
+use std::time::{Duration as StdDuration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+
+use crate::orchestrator::NoticeLevel;
+use crate::session::{SessionManager, NOTICE_RESULT_FAILURE, NOTICE_RESULT_SUCCESS};
+
+const NOTICE_ACTION_UNDO: &str = "undo";
+/// 插入或降级后允许撤销的时长。
+const UNDO_WINDOW_SECS: u64 = 30;
+
+/// 撤销一次发布时需要执行的动作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UndoAction {
+    /// 从焦点控件删除已插入的文本。
+    RemoveInserted,
+    /// 恢复降级前备份的剪贴板内容。
+    RestoreClipboard,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct PendingUndo {
+    session_id: String,
+    transcript: String,
+    action: UndoAction,
+    expires_at: Instant,
+}
+
+impl SessionManager {
+    /// 在撤销窗口内撤回一次发布：直接插入的文本从焦点控件删除，剪贴板降级则恢复原剪贴板。
+    /// 令牌只能使用一次。
+    pub async fn undo_publish(&self, token: &str) -> Result<()> {
+        let pending = self
+            .pending_undo
+            .lock()
+            .await
+            .remove(token)
+            .ok_or_else(|| anyhow!("unknown or already used undo token"))?;
+        if Instant::now() > pending.expires_at {
+            return Err(anyhow!("undo window of {UNDO_WINDOW_SECS}s has expired"));
+        }
+
+        let result = match pending.action {
+            UndoAction::RemoveInserted => self
+                .publisher
+                .undo(&pending.transcript)
+                .await
+                .map_err(|err| anyhow!(err)),
+            UndoAction::RestoreClipboard => match self.clipboard_fallback.lock().await.take() {
+                Some(handle) => handle.restore_once().await.map_err(|err| anyhow!(err)),
+                None => Err(anyhow!("clipboard backup is no longer available")),
+            },
+        };
+
+        let (level, outcome, message) = match (&result, pending.action) {
+            (Ok(()), UndoAction::RemoveInserted) => (
+                NoticeLevel::Info,
+                NOTICE_RESULT_SUCCESS,
+                "已撤销插入的润色稿。".to_string(),
+            ),
+            (Ok(()), UndoAction::RestoreClipboard) => (
+                NoticeLevel::Info,
+                NOTICE_RESULT_SUCCESS,
+                "已恢复原剪贴板内容。".to_string(),
+            ),
+            (Err(err), _) => (
+                NoticeLevel::Warn,
+                NOTICE_RESULT_FAILURE,
+                format!("撤销失败：{err}"),
+            ),
+        };
+        self.emit_notice(level, message.clone());
+        self.persist_notice_entry(
+            &pending.session_id,
+            NOTICE_ACTION_UNDO,
+            outcome,
+            level,
+            message,
+            Some(token.to_string()),
+        )
+        .await;
+        result
+    }
+
+    /// 撤回撤销窗口内最近一次发布。
+    pub async fn undo_last_publish(&self) -> Result<()> {
+        let now = Instant::now();
+        let token = self
+            .pending_undo
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, entry)| entry.expires_at >= now)
+            .max_by_key(|(_, entry)| entry.expires_at)
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| anyhow!("nothing to undo"))?;
+        self.undo_publish(&token).await
+    }
+
+    pub(super) async fn register_undo(
+        &self,
+        token: &str,
+        session_id: &str,
+        transcript: &str,
+        action: UndoAction,
+    ) {
+        let now = Instant::now();
+        let mut pending = self.pending_undo.lock().await;
+        pending.retain(|_, entry| entry.expires_at >= now);
+        pending.insert(
+            token.to_string(),
+            PendingUndo {
+                session_id: session_id.to_string(),
+                transcript: transcript.to_string(),
+                action,
+                expires_at: now + StdDuration::from_secs(UNDO_WINDOW_SECS),
+            },
+        );
+    }
+}
+
+pub(super) fn make_undo_token(session_id: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    format!("{session_id}-undo-{timestamp}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{EngineConfig, EngineOrchestrator};
+    use crate::session::clipboard::ClipboardManager;
+    use crate::session::publisher::{
+        FallbackStrategy, FocusWindowContext, InsertionMethod, PublishOutcome, PublishRequest,
+        PublishStrategy, PublisherFailure, PublisherFailureCode,
+    };
+    use crate::session::tests::{
+        make_snapshot, ProgrammedSpeechEngine, RecordingClipboard, StubPublisher,
+    };
+    use crate::session::NOTICE_ACTION_COPY;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn undo_publish_removes_insert_and_restores_clipboard() {
+        let engine = || {
+            EngineOrchestrator::with_engine(
+                EngineConfig {
+                    prefer_cloud: false,
+                },
+                Arc::new(ProgrammedSpeechEngine::new(Vec::new())),
+            )
+        };
+        let request = |fallback| PublishRequest {
+            transcript: "polished".into(),
+            focus: FocusWindowContext::default(),
+            fallback,
+            insertion: InsertionMethod::default(),
+            strategy: None,
+            html: None,
+        };
+
+        let inserted = StubPublisher::new(PublishOutcome::completed());
+        let manager = SessionManager::with_components(
+            engine(),
+            Arc::new(inserted.clone()),
+            ClipboardManager::new(Arc::new(RecordingClipboard::default())),
+        );
+        let outcome = manager
+            .publish_transcript(
+                make_snapshot("session-undo", "raw", "polished"),
+                request(FallbackStrategy::None),
+            )
+            .await
+            .expect("publish should succeed");
+        let token = outcome.undo_token.expect("undo token assigned");
+        manager.undo_publish(&token).await.expect("undo insert");
+        assert_eq!(*inserted.undone.lock().unwrap(), vec!["polished"]);
+        assert!(manager.undo_publish(&token).await.is_err());
+
+        let clipboard_access = RecordingClipboard::default();
+        *clipboard_access.state.lock().await = Some("original".into());
+        let manager = SessionManager::with_components(
+            engine(),
+            Arc::new(StubPublisher::new(PublishOutcome::failed(
+                1,
+                PublishStrategy::DirectInsert,
+                None,
+                PublisherFailure::new(PublisherFailureCode::Timeout, "operation timed out"),
+            ))),
+            ClipboardManager::new(Arc::new(clipboard_access.clone())),
+        );
+        let outcome = manager
+            .publish_transcript(
+                make_snapshot("session-undo-copy", "raw", "polished"),
+                request(FallbackStrategy::ClipboardCopy),
+            )
+            .await
+            .expect("publish should succeed");
+        assert_eq!(outcome.strategy, PublishStrategy::ClipboardFallback);
+        assert_eq!(
+            clipboard_access.contents().await.as_deref(),
+            Some("polished")
+        );
+        let token = outcome.undo_token.expect("undo token assigned");
+        manager.undo_publish(&token).await.expect("undo clipboard");
+        assert_eq!(
+            clipboard_access.contents().await.as_deref(),
+            Some("original")
+        );
+
+        let notices = manager
+            .persistence_handle()
+            .list_notices(10)
+            .await
+            .expect("persisted notices available");
+        assert!(notices
+            .iter()
+            .any(|entry| entry.action == NOTICE_ACTION_COPY
+                && entry.undo_token.as_deref() == Some(token.as_str())));
+        assert!(notices.iter().any(
+            |entry| entry.action == NOTICE_ACTION_UNDO && entry.result == NOTICE_RESULT_SUCCESS
+        ));
+    }
+}
@@ -0,0 +1,530 @@
+//! 会话事件 Webhook：把会话完成、发布失败与噪声告警以 JSON POST 到用户配置的地址，
+//! 使用 HMAC-SHA256 签名并在失败时指数退避重试。
+
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+use crate::persistence::audit::{EgressChannel, EgressRecorder};
+use crate::session::lifecycle::{
+    SessionLifecyclePayload, SessionLifecyclePhase, SessionLifecycleUpdate,
+};
+use crate::session::shutdown::CancellationToken;
+use crate::session::SessionEvent;
+use crate::telemetry::uploader::UploadBackoff;
+
+/// 签名请求头，值为 `sha256=<hex>`，签名内容为 `{timestamp}.{body}`。
+pub const SIGNATURE_HEADER: &str = "X-Flowwisper-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Flowwisper-Timestamp";
+pub const EVENT_HEADER: &str = "X-Flowwisper-Event";
+
+/// 可订阅的事件类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    SessionCompleted,
+    PublishFailed,
+    NoiseWarning,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::SessionCompleted => "session_completed",
+            WebhookEvent::PublishFailed => "publish_failed",
+            WebhookEvent::NoiseWarning => "noise_warning",
+        }
+    }
+}
+
+/// 单个 Webhook 地址及其订阅的事件。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEndpoint {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// 签名密钥；为空时不附带签名头。
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+impl WebhookEndpoint {
+    pub fn validate(&self) -> Result<()> {
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(anyhow!("webhook url must use http or https: {}", self.url));
+        }
+        if self.events.is_empty() {
+            return Err(anyhow!("webhook {} subscribes to no events", self.url));
+        }
+        Ok(())
+    }
+
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+/// Webhook 投递配置。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// 每次投递的最大尝试次数（含首次）。
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// 负责实际发送请求，便于测试替换。
+pub trait WebhookTransport: Send + Sync {
+    fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: &str,
+        timeout: Duration,
+    ) -> Result<()>;
+}
+
+/// 基于 `ureq` 的 HTTP 传输。
+#[derive(Debug, Default)]
+pub struct HttpWebhookTransport;
+
+impl WebhookTransport for HttpWebhookTransport {
+    fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        let mut request = ureq::post(url)
+            .timeout(timeout)
+            .set("Content-Type", "application/json");
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        request
+            .send_string(body)
+            .map_err(|err| anyhow!("webhook delivery to {url} failed: {err}"))?;
+        Ok(())
+    }
+}
+
+/// 计算 `{timestamp}.{body}` 的 HMAC-SHA256 签名。
+pub fn sign(secret: &str, timestamp_ms: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, format!("{timestamp_ms}.{body}").as_bytes());
+    let hex: String = tag
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+/// 按配置向订阅了事件的地址投递，每个地址独立重试。
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    config: Arc<RwLock<WebhookConfig>>,
+    transport: Arc<dyn WebhookTransport>,
+    egress: EgressRecorder,
+    shutdown: CancellationToken,
+}
+
+impl std::fmt::Debug for WebhookDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookDispatcher")
+            .field("config", &self.config())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for WebhookDispatcher {
+    fn default() -> Self {
+        Self::with_transport(Arc::new(HttpWebhookTransport))
+    }
+}
+
+impl WebhookDispatcher {
+    pub fn with_transport(transport: Arc<dyn WebhookTransport>) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(WebhookConfig::default())),
+            transport,
+            egress: EgressRecorder::default(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// 每次投递尝试都写入外发审计。
+    pub fn with_egress(mut self, egress: EgressRecorder) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    /// 令牌取消后监听与进行中的投递随之结束。
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub fn config(&self) -> WebhookConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn set_config(&self, config: WebhookConfig) -> Result<()> {
+        for endpoint in &config.endpoints {
+            endpoint.validate()?;
+        }
+        *self
+            .config
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+        Ok(())
+    }
+
+    /// 构造事件负载并为每个订阅地址启动投递任务，返回启动的任务数。
+    pub fn dispatch(&self, event: WebhookEvent, session_id: Option<&str>, data: Value) -> usize {
+        let config = self.config();
+        let body = json!({
+            "event": event.as_str(),
+            "sessionId": session_id,
+            "timestampMs": now_ms(),
+            "data": data,
+        })
+        .to_string();
+        let mut spawned = 0;
+        for endpoint in config
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.subscribes_to(event))
+        {
+            let dispatcher = self.clone();
+            let endpoint = endpoint.clone();
+            let body = body.clone();
+            let config = config.clone();
+            let shutdown = self.shutdown.clone();
+            tokio::spawn(async move {
+                let delivery = dispatcher.deliver(&endpoint, event, &body, &config);
+                if let Some(Err(err)) = shutdown.run_until_cancelled(delivery).await {
+                    warn!(
+                        target: "session_webhooks",
+                        %err,
+                        url = %endpoint.url,
+                        event = event.as_str(),
+                        "webhook delivery abandoned"
+                    );
+                }
+            });
+            spawned += 1;
+        }
+        spawned
+    }
+
+    /// 投递一次事件，失败后按退避重试直至成功或达到上限；返回实际尝试次数。
+    pub async fn deliver(
+        &self,
+        endpoint: &WebhookEndpoint,
+        event: WebhookEvent,
+        body: &str,
+        config: &WebhookConfig,
+    ) -> Result<u32> {
+        let mut backoff = UploadBackoff::new(config.initial_backoff, config.max_backoff);
+        let max_attempts = config.max_attempts.max(1);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let timestamp_ms = now_ms();
+            let mut headers = vec![
+                (EVENT_HEADER.to_string(), event.as_str().to_string()),
+                (TIMESTAMP_HEADER.to_string(), timestamp_ms.to_string()),
+            ];
+            if let Some(secret) = &endpoint.secret {
+                headers.push((
+                    SIGNATURE_HEADER.to_string(),
+                    sign(secret, timestamp_ms, body),
+                ));
+            }
+            self.egress
+                .record(EgressChannel::Webhook, &endpoint.url, body.as_bytes());
+            let transport = self.transport.clone();
+            let url = endpoint.url.clone();
+            let payload = body.to_string();
+            let timeout = config.request_timeout;
+            let result = tokio::task::spawn_blocking(move || {
+                transport.post(&url, &headers, &payload, timeout)
+            })
+            .await
+            .map_err(|err| anyhow!("blocking webhook task failed: {err}"))?;
+            match result {
+                Ok(()) => {
+                    info!(
+                        target: "session_webhooks",
+                        url = %endpoint.url,
+                        event = event.as_str(),
+                        attempt,
+                        "webhook delivered"
+                    );
+                    return Ok(attempt);
+                }
+                Err(err) if attempt >= max_attempts => return Err(err),
+                Err(err) => {
+                    let delay = backoff.next_delay();
+                    warn!(
+                        target: "session_webhooks",
+                        %err,
+                        url = %endpoint.url,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        "webhook delivery failed, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// 监听生命周期与会话事件并转发给订阅的地址。
+    pub fn spawn(
+        &self,
+        mut lifecycle_rx: broadcast::Receiver<SessionLifecycleUpdate>,
+        mut event_rx: broadcast::Receiver<SessionEvent>,
+        active_session: impl Fn() -> Option<String> + Send + 'static,
+    ) {
+        let dispatcher = self.clone();
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            while let Some(received) = shutdown.run_until_cancelled(lifecycle_rx.recv()).await {
+                match received {
+                    Ok(update) => {
+                        if let Some((event, data)) = lifecycle_event(&update) {
+                            dispatcher.dispatch(event, Some(&update.session_id), data);
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(target: "session_webhooks", skipped, "webhook listener lagged");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let dispatcher = self.clone();
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            while let Some(received) = shutdown.run_until_cancelled(event_rx.recv()).await {
+                match received {
+                    Ok(SessionEvent::NoiseWarning(warning)) => {
+                        let data = json!({
+                            "baselineDb": warning.baseline_db,
+                            "thresholdDb": warning.threshold_db,
+                            "levelDb": warning.level_db,
+                            "persistenceMs": warning.persistence_ms,
+                            "kind": warning.kind.as_str(),
+                        });
+                        let session_id = active_session();
+                        dispatcher.dispatch(
+                            WebhookEvent::NoiseWarning,
+                            session_id.as_deref(),
+                            data,
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(target: "session_webhooks", skipped, "webhook listener lagged");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+fn lifecycle_event(update: &SessionLifecycleUpdate) -> Option<(WebhookEvent, Value)> {
+    match (&update.phase, &update.payload) {
+        (SessionLifecyclePhase::Completed, SessionLifecyclePayload::Completed(payload)) => {
+            let outcome = &payload.outcome;
+            Some((
+                WebhookEvent::SessionCompleted,
+                json!({
+                    "status": outcome.status.as_str(),
+                    "strategy": outcome.strategy.as_str(),
+                    "attempts": outcome.attempts,
+                    "fallback": outcome.fallback.as_ref().map(|fallback| fallback.as_str()),
+                }),
+            ))
+        }
+        (SessionLifecyclePhase::Failed, SessionLifecyclePayload::Failed(payload)) => Some((
+            WebhookEvent::PublishFailed,
+            json!({
+                "attempts": payload.attempts,
+                "error": payload.error,
+                "code": payload.code,
+                "fallback": payload.fallback.as_ref().map(|fallback| fallback.as_str()),
+            }),
+        )),
+        _ => None,
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::publisher::PublishOutcome;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    type Delivery = (String, Vec<(String, String)>, String);
+
+    #[derive(Default)]
+    struct FlakyTransport {
+        failures: AtomicU32,
+        delivered: Mutex<Vec<Delivery>>,
+    }
+
+    impl WebhookTransport for FlakyTransport {
+        fn post(
+            &self,
+            url: &str,
+            headers: &[(String, String)],
+            body: &str,
+            _timeout: Duration,
+        ) -> Result<()> {
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                    left.checked_sub(1)
+                })
+                .is_ok()
+            {
+                return Err(anyhow!("connection refused"));
+            }
+            self.delivered.lock().unwrap().push((
+                url.to_string(),
+                headers.to_vec(),
+                body.to_string(),
+            ));
+            Ok(())
+        }
+    }
+
+    fn config(events: Vec<WebhookEvent>) -> WebhookConfig {
+        WebhookConfig {
+            endpoints: vec![WebhookEndpoint {
+                url: "https://hooks.example.com/flowwisper".into(),
+                events,
+                secret: Some("s3cret".into()),
+            }],
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+            ..WebhookConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn signs_and_retries_deliveries() {
+        let transport = Arc::new(FlakyTransport::default());
+        transport.failures.store(2, Ordering::SeqCst);
+        let dispatcher = WebhookDispatcher::with_transport(transport.clone());
+        let config = config(vec![WebhookEvent::PublishFailed]);
+        let endpoint = config.endpoints[0].clone();
+
+        let attempts = dispatcher
+            .deliver(&endpoint, WebhookEvent::PublishFailed, "{}", &config)
+            .await
+            .expect("delivered after retries");
+        assert_eq!(attempts, 3);
+        let delivered = transport.delivered.lock().unwrap().clone();
+        let headers = &delivered[0].1;
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .expect("header present")
+        };
+        let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        assert_eq!(header(SIGNATURE_HEADER), sign("s3cret", timestamp, "{}"));
+        assert_eq!(header(EVENT_HEADER), "publish_failed");
+
+        transport.failures.store(10, Ordering::SeqCst);
+        assert!(dispatcher
+            .deliver(&endpoint, WebhookEvent::PublishFailed, "{}", &config)
+            .await
+            .is_err());
+        assert_eq!(transport.failures.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn forwards_subscribed_lifecycle_events() {
+        let transport = Arc::new(FlakyTransport::default());
+        let dispatcher = WebhookDispatcher::with_transport(transport.clone());
+        dispatcher
+            .set_config(config(vec![WebhookEvent::SessionCompleted]))
+            .unwrap();
+        assert!(dispatcher
+            .set_config(WebhookConfig {
+                endpoints: vec![WebhookEndpoint {
+                    url: "ftp://example.com".into(),
+                    events: vec![WebhookEvent::NoiseWarning],
+                    secret: None,
+                }],
+                ..WebhookConfig::default()
+            })
+            .is_err());
+
+        let (lifecycle_tx, lifecycle_rx) = broadcast::channel(8);
+        let (_event_tx, event_rx) = broadcast::channel(8);
+        dispatcher.spawn(lifecycle_rx, event_rx, || None);
+        lifecycle_tx
+            .send(SessionLifecycleUpdate::failed("s-1", 1, "boom", None, None))
+            .unwrap();
+        lifecycle_tx
+            .send(SessionLifecycleUpdate::completed(
+                "s-1",
+                PublishOutcome::completed(),
+            ))
+            .unwrap();
+
+        for _ in 0..50 {
+            if !transport.delivered.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let delivered = transport.delivered.lock().unwrap().clone();
+        assert_eq!(delivered.len(), 1);
+        let body: Value = serde_json::from_str(&delivered[0].2).unwrap();
+        assert_eq!(body["event"], "session_completed");
+        assert_eq!(body["sessionId"], "s-1");
+        assert_eq!(body["data"]["status"], "completed");
+    }
+}
@@ -0,0 +1,248 @@
+//! 多用户工作区：历史库、遥测队列与日志、录音、模型、插件数据以及平台密钥库条目
+//! 按所选配置档（profile）隔离，便于共用电脑或区分“工作”“个人”听写数据。
+//!
+//! 默认配置档直接使用数据根目录，兼容升级前的数据；其余配置档位于 `<根目录>/profiles/<名称>`。
+//! 配置档在进程启动时确定，切换后需重启生效。
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PROFILE: &str = "default";
+/// 设置后覆盖已保存的配置档选择，仅对本次启动生效。
+pub const PROFILE_ENV: &str = "FLOWWISPER_PROFILE";
+pub const DATA_DIR_ENV: &str = "FLOWWISPER_DATA_DIR";
+
+const PROFILES_DIR: &str = "profiles";
+const SELECTION_FILE: &str = "workspace.json";
+const MAX_PROFILE_NAME_LEN: usize = 64;
+
+/// 本进程启动时确定的数据目录。
+static ACTIVE_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceProfile {
+    pub name: String,
+    pub dir: PathBuf,
+    /// 下次启动将使用的配置档。
+    pub selected: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Selection {
+    profile: Option<String>,
+}
+
+/// 数据根目录下的配置档集合。
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    root: PathBuf,
+}
+
+impl Workspace {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 数据根目录：`FLOWWISPER_DATA_DIR`，否则为系统数据目录下的 `Flowwisper`。
+    pub fn from_env() -> Result<Self> {
+        let root = match env::var(DATA_DIR_ENV).map(PathBuf::from) {
+            Ok(path) => path,
+            Err(_) => dirs::data_dir()
+                .map(|dir| dir.join("Flowwisper"))
+                .ok_or_else(|| anyhow!("failed to resolve persistence data directory"))?,
+        };
+        Ok(Self::new(root))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 已创建的配置档，默认配置档总在首位。
+    pub fn list_profiles(&self) -> Result<Vec<WorkspaceProfile>> {
+        let selected = self.selected_profile()?;
+        let mut names = Vec::new();
+        let profiles_dir = self.root.join(PROFILES_DIR);
+        if profiles_dir.is_dir() {
+            for entry in fs::read_dir(&profiles_dir).context("failed to list profiles")? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.file_type()?.is_dir() && validate_name(&name).is_ok() {
+                    names.push(name);
+                }
+            }
+        }
+        names.sort_by_key(|name| name.to_lowercase());
+        Ok(std::iter::once(DEFAULT_PROFILE.to_string())
+            .chain(names)
+            .map(|name| WorkspaceProfile {
+                dir: self.profile_dir(&name),
+                selected: name == selected,
+                name,
+            })
+            .collect())
+    }
+
+    pub fn create_profile(&self, name: &str) -> Result<WorkspaceProfile> {
+        let name = name.trim();
+        validate_name(name)?;
+        if self
+            .list_profiles()?
+            .iter()
+            .any(|profile| profile.name.eq_ignore_ascii_case(name))
+        {
+            return Err(anyhow!("profile {name} already exists"));
+        }
+        let dir = self.profile_dir(name);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create profile directory {}", dir.display()))?;
+        Ok(WorkspaceProfile {
+            name: name.to_string(),
+            dir,
+            selected: false,
+        })
+    }
+
+    /// 保存下次启动使用的配置档；配置档须已存在。
+    pub fn switch_profile(&self, name: &str) -> Result<WorkspaceProfile> {
+        let mut profile = self
+            .list_profiles()?
+            .into_iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| anyhow!("profile {name} does not exist"))?;
+        fs::create_dir_all(&self.root).context("failed to create data directory")?;
+        let selection = Selection {
+            profile: (profile.name != DEFAULT_PROFILE).then(|| profile.name.clone()),
+        };
+        fs::write(
+            self.root.join(SELECTION_FILE),
+            serde_json::to_vec_pretty(&selection)?,
+        )
+        .context("failed to save profile selection")?;
+        profile.selected = true;
+        Ok(profile)
+    }
+
+    /// 已保存的配置档选择；选择文件缺失或损坏时为默认配置档。
+    pub fn selected_profile(&self) -> Result<String> {
+        let selection = match fs::read(self.root.join(SELECTION_FILE)) {
+            Ok(raw) => serde_json::from_slice::<Selection>(&raw).unwrap_or_default(),
+            Err(_) => Selection::default(),
+        };
+        Ok(selection
+            .profile
+            .filter(|name| validate_name(name).is_ok())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+    }
+
+    /// 本次启动使用的配置档：`FLOWWISPER_PROFILE` 优先，其次为已保存的选择。
+    pub fn startup_profile(&self) -> Result<String> {
+        match env::var(PROFILE_ENV) {
+            Ok(name) if !name.trim().is_empty() => {
+                let name = name.trim().to_string();
+                validate_name(&name)?;
+                Ok(name)
+            }
+            _ => self.selected_profile(),
+        }
+    }
+
+    pub fn profile_dir(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            self.root.clone()
+        } else {
+            self.root.join(PROFILES_DIR).join(name)
+        }
+    }
+
+    /// 指定配置档的数据目录，不存在时创建。
+    pub fn data_dir_for(&self, name: &str) -> Result<PathBuf> {
+        validate_name(name)?;
+        let dir = self.profile_dir(name);
+        fs::create_dir_all(&dir).context("failed to create data directory")?;
+        Ok(dir)
+    }
+}
+
+/// 本进程的数据目录；首次调用时按启动配置档解析，之后保持不变。
+pub fn active_data_dir() -> Result<PathBuf> {
+    if let Some(dir) = ACTIVE_DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
+    let workspace = Workspace::from_env()?;
+    let dir = workspace.data_dir_for(&workspace.startup_profile()?)?;
+    Ok(ACTIVE_DATA_DIR.get_or_init(|| dir).clone())
+}
+
+/// 本进程使用的配置档名称。
+pub fn active_profile() -> Result<String> {
+    let dir = active_data_dir()?;
+    let workspace = Workspace::from_env()?;
+    if dir == workspace.root() {
+        return Ok(DEFAULT_PROFILE.to_string());
+    }
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("active data directory has no profile name"))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME_LEN
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '.' | ' '));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("invalid profile name {name:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_lists_and_switches_profiles() {
+        let root = tempfile::tempdir().unwrap();
+        let workspace = Workspace::new(root.path());
+
+        let profiles = workspace.list_profiles().unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, DEFAULT_PROFILE);
+        assert_eq!(profiles[0].dir, root.path());
+        assert!(profiles[0].selected);
+
+        let work = workspace.create_profile(" work ").unwrap();
+        assert_eq!(work.dir, root.path().join("profiles").join("work"));
+        assert!(work.dir.is_dir());
+        assert!(workspace.create_profile("WORK").is_err());
+        assert!(workspace.create_profile("../escape").is_err());
+        assert!(workspace.create_profile(DEFAULT_PROFILE).is_err());
+        assert!(workspace.switch_profile("personal").is_err());
+
+        assert!(workspace.switch_profile("Work").unwrap().selected);
+        assert_eq!(workspace.selected_profile().unwrap(), "work");
+        let names: Vec<_> = workspace
+            .list_profiles()
+            .unwrap()
+            .into_iter()
+            .filter(|profile| profile.selected)
+            .map(|profile| profile.name)
+            .collect();
+        assert_eq!(names, vec!["work"]);
+
+        workspace.switch_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(workspace.selected_profile().unwrap(), DEFAULT_PROFILE);
+        fs::write(root.path().join(SELECTION_FILE), "not json").unwrap();
+        assert_eq!(workspace.selected_profile().unwrap(), DEFAULT_PROFILE);
+    }
+}
@@ -3,9 +3,20 @@ use serde::Serialize;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{info, warn};
 
+use crate::error::FlowwisperError;
+use crate::orchestrator::hardware::{EngineTuning, HardwareProfile};
+use crate::orchestrator::sla::SlaCalibration;
+use crate::telemetry::metrics::metrics;
+
 pub(crate) const TARGET: &str = "telemetry::dual_view";
 pub(crate) const EVENT_LATENCY: &str = "dual_view_latency";
 pub(crate) const EVENT_REVERT: &str = "dual_view_revert";
+pub(crate) const EVENT_ARBITRATION: &str = "dual_view_arbitration";
+
+pub(crate) const ENGINE_TARGET: &str = "telemetry::engine";
+pub(crate) const EVENT_ROUTE_SWITCH: &str = "engine_route_switch";
+pub(crate) const EVENT_ENGINE_TUNING: &str = "engine_tuning";
+pub(crate) const EVENT_SLA_CALIBRATION: &str = "sla_calibration";
 
 pub(crate) const SESSION_TARGET: &str = "telemetry::session";
 pub(crate) const EVENT_PUBLISH_ATTEMPT: &str = "session_publish_attempt";
@@ -15,14 +26,24 @@ pub(crate) const EVENT_PUBLISH_DEGRADATION: &str = "session_publish_degradation"
 pub(crate) const EVENT_DRAFT_SAVE_SUCCESS: &str = "session_draft_save_success";
 pub(crate) const EVENT_DRAFT_SAVE_FAILURE: &str = "session_draft_save_failure";
 pub(crate) const EVENT_PUBLISH_UNDO: &str = "session_publish_undo";
+pub(crate) const EVENT_TRANSCRIPT_AMENDED: &str = "session_transcript_amended";
 pub(crate) const EVENT_HISTORY_PERSISTED: &str = "session_history_persisted";
 pub(crate) const EVENT_HISTORY_PERSIST_FAILURE: &str = "session_history_persist_failure";
 pub(crate) const EVENT_HISTORY_ACCURACY: &str = "session_history_accuracy";
 pub(crate) const EVENT_HISTORY_ACTION: &str = "session_history_action";
 pub(crate) const EVENT_HISTORY_CLEANUP: &str = "session_history_cleanup";
+pub(crate) const EVENT_HISTORY_SYNC: &str = "session_history_sync";
+pub(crate) const EVENT_HISTORY_BACKUP: &str = "session_history_backup";
+pub(crate) const EVENT_HISTORY_KEY_ROTATION: &str = "session_history_key_rotation";
 pub(crate) const EVENT_NOISE_WARNING: &str = "session_noise_warning";
 pub(crate) const EVENT_SILENCE_COUNTDOWN: &str = "session_silence_countdown";
 pub(crate) const EVENT_SILENCE_AUTOSTOP: &str = "session_silence_autostop";
+pub(crate) const EVENT_MAX_DURATION_AUTOSTOP: &str = "session_max_duration_autostop";
+pub(crate) const EVENT_ECHO_DETECTED: &str = "session_echo_detected";
+pub(crate) const EVENT_SELF_CHECK: &str = "session_self_check";
+
+pub(crate) const UPLOAD_TARGET: &str = "telemetry::upload";
+pub(crate) const EVENT_TELEMETRY_UPLOAD: &str = "telemetry_upload";
 
 #[derive(Debug, Serialize)]
 pub struct DualViewLatencyEvent {
@@ -40,6 +61,27 @@ pub struct DualViewSelectionLog {
     pub variant: &'static str,
 }
 
+#[derive(Debug, Serialize)]
+pub struct DualViewArbitrationEvent {
+    pub frame_index: usize,
+    pub winner: &'static str,
+    pub reason: &'static str,
+    pub agreement: f32,
+    pub local_score: f32,
+    pub cloud_score: f32,
+    pub local_latency_ms: u64,
+    pub cloud_latency_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EngineRouteSwitchEvent {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub score: f32,
+    pub rtt_ms: Option<u64>,
+    pub packet_loss: f32,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DualViewRevertEvent {
     pub requested: Vec<DualViewSelectionLog>,
@@ -61,11 +103,14 @@ pub struct SessionPublishOutcomeEvent<'a> {
     pub strategy: &'a str,
     pub attempts: u8,
     pub fallback: Option<&'a str>,
+    /// 发布的文本是否经用户手动修改。
+    pub hand_edited: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SessionPublishFailureEvent<'a> {
     pub session_id: &'a str,
+    pub error_code: &'a str,
     pub error: &'a str,
     pub attempts: u8,
     pub fallback: Option<&'a str>,
@@ -106,6 +151,7 @@ pub struct SessionNoiseWarningEvent<'a> {
     pub threshold_db: f32,
     pub level_db: f32,
     pub persistence_ms: u32,
+    pub noise_kind: &'a str,
     pub strong_noise_mode: bool,
 }
 
@@ -128,6 +174,23 @@ pub struct SessionSilenceAutoStopEvent<'a> {
     pub countdown_ms: u32,
 }
 
+#[derive(Debug, Serialize)]
+pub struct SessionMaxDurationAutoStopEvent<'a> {
+    pub session_id: &'a str,
+    pub timestamp_ms: u128,
+    pub reason: &'a str,
+    pub limit_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionEchoDetectedEvent<'a> {
+    pub session_id: &'a str,
+    pub occurred_at_ms: u128,
+    pub correlation: f32,
+    pub delay_ms: u32,
+    pub persistence_ms: u32,
+}
+
 pub fn record_dual_view_latency(
     sentence_id: u64,
     variant: &'static str,
@@ -166,6 +229,82 @@ pub fn record_dual_view_latency(
     }
 }
 
+pub fn record_dual_view_arbitration(event: DualViewArbitrationEvent) {
+    match serde_json::to_string(&event) {
+        Ok(payload) => info!(
+            target: TARGET,
+            event = EVENT_ARBITRATION,
+            frame_index = event.frame_index,
+            winner = event.winner,
+            reason = event.reason,
+            payload = %payload
+        ),
+        Err(err) => warn!(
+            target: TARGET,
+            event = EVENT_ARBITRATION,
+            %err,
+            "failed to encode dual view arbitration event"
+        ),
+    }
+}
+
+pub fn record_engine_route_switch(event: EngineRouteSwitchEvent) {
+    match serde_json::to_string(&event) {
+        Ok(payload) => info!(
+            target: ENGINE_TARGET,
+            event = EVENT_ROUTE_SWITCH,
+            from = event.from,
+            to = event.to,
+            score = event.score,
+            payload = %payload
+        ),
+        Err(err) => warn!(
+            target: ENGINE_TARGET,
+            event = EVENT_ROUTE_SWITCH,
+            %err,
+            "failed to encode engine route switch event"
+        ),
+    }
+}
+
+/// 启动时的硬件探测结果与本地引擎的配置决策。
+pub fn record_engine_tuning(hardware: &HardwareProfile, tuning: &EngineTuning) {
+    let accelerators = hardware
+        .accelerators
+        .iter()
+        .map(|accelerator| accelerator.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    info!(
+        target: ENGINE_TARGET,
+        event = EVENT_ENGINE_TUNING,
+        os = hardware.os,
+        arch = hardware.arch,
+        logical_cores = hardware.logical_cores,
+        simd = hardware.simd.as_str(),
+        accelerators = %accelerators,
+        accelerator = tuning.accelerator.map(|accelerator| accelerator.as_str()).unwrap_or("cpu"),
+        threads = tuning.threads,
+        quantization = tuning.quantization.as_str(),
+        gpu_layers = tuning.gpu_layers,
+        overridden = tuning.overridden,
+        "local engine configured"
+    );
+}
+
+/// 按本机实测延迟重新校准的实时会话期限。
+pub fn record_sla_calibration(calibration: &SlaCalibration) {
+    info!(
+        target: ENGINE_TARGET,
+        event = EVENT_SLA_CALIBRATION,
+        first_update_deadline_ms = calibration.first_update_deadline.as_millis() as u64,
+        cadence_slack = calibration.cadence_slack,
+        samples = calibration.samples,
+        p90_ms = calibration.p90_ms,
+        "latency sla calibrated"
+    );
+}
+
 pub fn record_dual_view_revert(
     requested: Vec<DualViewSelectionLog>,
     applied: Vec<DualViewSelectionLog>,
@@ -229,6 +368,7 @@ pub fn record_session_publish_outcome(
     strategy: &str,
     attempts: u8,
     fallback: Option<&str>,
+    hand_edited: bool,
 ) {
     let event = SessionPublishOutcomeEvent {
         session_id,
@@ -236,6 +376,7 @@ pub fn record_session_publish_outcome(
         strategy,
         attempts,
         fallback,
+        hand_edited,
     };
 
     match serde_json::to_string(&event) {
@@ -247,6 +388,7 @@ pub fn record_session_publish_outcome(
             strategy,
             attempts,
             fallback,
+            hand_edited,
             payload = %payload
         ),
         Err(err) => warn!(
@@ -260,13 +402,16 @@ pub fn record_session_publish_outcome(
 
 pub fn record_session_publish_failure(
     session_id: &str,
-    error: String,
+    error: &FlowwisperError,
     attempts: u8,
     fallback: Option<&str>,
 ) {
+    metrics().publish_failures.inc();
+    let error_code = error.code().as_str();
     let event = SessionPublishFailureEvent {
         session_id,
-        error: &error,
+        error_code,
+        error: error.message(),
         attempts,
         fallback,
     };
@@ -278,7 +423,8 @@ pub fn record_session_publish_failure(
             session_id,
             attempts,
             fallback,
-            error = %error,
+            error_code,
+            error = %error.message(),
             payload = %payload
         ),
         Err(err) => warn!(
@@ -441,12 +587,125 @@ pub fn record_session_history_cleanup(count: usize, duration: Duration) {
     );
 }
 
+pub fn record_session_history_key_rotation(stage: &str, error: Option<&Error>) {
+    match error {
+        None => info!(
+            target: SESSION_TARGET,
+            event = EVENT_HISTORY_KEY_ROTATION,
+            stage,
+            "history database key rotation progressed"
+        ),
+        Some(error) => warn!(
+            target: SESSION_TARGET,
+            event = EVENT_HISTORY_KEY_ROTATION,
+            stage,
+            error = %error,
+            "history database key rotation failed"
+        ),
+    }
+}
+
+pub fn record_session_history_sync(
+    pushed: usize,
+    pulled: usize,
+    applied: usize,
+    conflicts: usize,
+    skipped: usize,
+    error: Option<&Error>,
+) {
+    match error {
+        None => info!(
+            target: SESSION_TARGET,
+            event = EVENT_HISTORY_SYNC,
+            pushed,
+            pulled,
+            applied,
+            conflicts,
+            skipped,
+            "history sync round completed"
+        ),
+        Some(error) => warn!(
+            target: SESSION_TARGET,
+            event = EVENT_HISTORY_SYNC,
+            error = %error,
+            "history sync round failed"
+        ),
+    }
+}
+
+pub fn record_session_history_backup(
+    bytes: usize,
+    sessions: usize,
+    pruned: usize,
+    error: Option<&Error>,
+) {
+    match error {
+        None => info!(
+            target: SESSION_TARGET,
+            event = EVENT_HISTORY_BACKUP,
+            bytes,
+            sessions,
+            pruned,
+            "history backup completed"
+        ),
+        Some(error) => warn!(
+            target: SESSION_TARGET,
+            event = EVENT_HISTORY_BACKUP,
+            error = %error,
+            "history backup failed"
+        ),
+    }
+}
+
+pub fn record_session_transcript_amended(session_id: &str, edited_sentences: usize) {
+    info!(
+        target: SESSION_TARGET,
+        event = EVENT_TRANSCRIPT_AMENDED,
+        session_id,
+        edited_sentences,
+        "transcript amended before publish"
+    );
+}
+
+pub fn record_self_check(passed: bool, failed_steps: &[&str], skipped_steps: &[&str]) {
+    let failed = failed_steps.join(",");
+    let skipped = skipped_steps.join(",");
+    info!(
+        target: SESSION_TARGET,
+        event = EVENT_SELF_CHECK,
+        passed,
+        failed_steps = %failed,
+        skipped_steps = %skipped,
+        "self-check completed"
+    );
+}
+
+pub fn record_telemetry_upload(delivered: usize, error: Option<&Error>) {
+    match error {
+        None => info!(
+            target: UPLOAD_TARGET,
+            event = EVENT_TELEMETRY_UPLOAD,
+            delivered,
+            "telemetry batch uploaded"
+        ),
+        Some(error) => warn!(
+            target: UPLOAD_TARGET,
+            event = EVENT_TELEMETRY_UPLOAD,
+            delivered,
+            error = %error,
+            "telemetry upload failed"
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn record_session_noise_warning(
     session_id: &str,
     baseline_db: f32,
     threshold_db: f32,
     level_db: f32,
     persistence_ms: u32,
+    noise_kind: &str,
     strong_noise_mode: bool,
     occurred_at: SystemTime,
 ) {
@@ -457,6 +716,7 @@ pub fn record_session_noise_warning(
         threshold_db,
         level_db,
         persistence_ms,
+        noise_kind,
         strong_noise_mode,
     };
 
@@ -469,6 +729,7 @@ pub fn record_session_noise_warning(
             threshold_db,
             level_db,
             persistence_ms,
+            noise_kind,
             strong_noise_mode,
             payload = %payload
         ),
@@ -543,6 +804,70 @@ pub fn record_session_silence_autostop(session_id: &str, countdown_ms: u32, time
     }
 }
 
+pub fn record_session_max_duration_autostop(
+    session_id: &str,
+    limit: Duration,
+    timestamp: SystemTime,
+) {
+    let limit_ms = duration_to_ms(limit);
+    let event = SessionMaxDurationAutoStopEvent {
+        session_id,
+        timestamp_ms: system_time_to_ms(timestamp),
+        reason: "maxDuration",
+        limit_ms,
+    };
+
+    match serde_json::to_string(&event) {
+        Ok(payload) => info!(
+            target: SESSION_TARGET,
+            event = EVENT_MAX_DURATION_AUTOSTOP,
+            session_id,
+            limit_ms,
+            payload = %payload
+        ),
+        Err(err) => warn!(
+            target: SESSION_TARGET,
+            event = EVENT_MAX_DURATION_AUTOSTOP,
+            %err,
+            "failed to encode session max duration autostop telemetry"
+        ),
+    }
+}
+
+pub fn record_session_echo_detected(
+    session_id: &str,
+    correlation: f32,
+    delay_ms: u32,
+    persistence_ms: u32,
+    occurred_at: SystemTime,
+) {
+    let event = SessionEchoDetectedEvent {
+        session_id,
+        occurred_at_ms: system_time_to_ms(occurred_at),
+        correlation,
+        delay_ms,
+        persistence_ms,
+    };
+
+    match serde_json::to_string(&event) {
+        Ok(payload) => info!(
+            target: SESSION_TARGET,
+            event = EVENT_ECHO_DETECTED,
+            session_id,
+            correlation,
+            delay_ms,
+            persistence_ms,
+            payload = %payload
+        ),
+        Err(err) => warn!(
+            target: SESSION_TARGET,
+            event = EVENT_ECHO_DETECTED,
+            %err,
+            "failed to encode session echo detected telemetry"
+        ),
+    }
+}
+
 fn duration_to_ms(duration: Duration) -> u64 {
     duration.as_millis().min(u64::MAX as u128) as u64
 }
@@ -577,6 +902,7 @@ mod tests {
             -17.0,
             -12.0,
             320,
+            "keyboardClatter",
             false,
             SystemTime::UNIX_EPOCH + Duration::from_millis(42),
         );
@@ -602,4 +928,15 @@ mod tests {
             SystemTime::UNIX_EPOCH + Duration::from_millis(126),
         );
     }
+
+    #[test]
+    fn echo_detected_event_serializes() {
+        record_session_echo_detected(
+            "session-test",
+            0.92,
+            80,
+            400,
+            SystemTime::UNIX_EPOCH + Duration::from_millis(168),
+        );
+    }
 }
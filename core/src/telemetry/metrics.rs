@@ -0,0 +1,342 @@
+//! 轻量指标门面：进程内计数器/直方图，并可选通过 `/metrics` 暴露 Prometheus 文本格式。
+
+use std::env;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+pub const METRICS_ADDR_ENV: &str = "FLOWWISPER_METRICS_ADDR";
+
+/// Latency buckets in seconds, tuned around the 400 ms first-update SLA.
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.2, 0.3, 0.4, 0.6, 0.8, 1.0, 2.0, 5.0];
+
+/// Monotonic counter.
+#[derive(Debug)]
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    fn render(&self, output: &mut String) {
+        let _ = writeln!(output, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(output, "# TYPE {} counter", self.name);
+        let _ = writeln!(output, "{} {}", self.name, self.get());
+    }
+}
+
+/// Point-in-time value that can go up and down.
+#[derive(Debug)]
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Gauge {
+    const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set(&self, value: u64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    fn render(&self, output: &mut String) {
+        let _ = writeln!(output, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(output, "# TYPE {} gauge", self.name);
+        let _ = writeln!(output, "{} {}", self.name, self.get());
+    }
+}
+
+/// Cumulative histogram with fixed bucket bounds.
+#[derive(Debug)]
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    bounds: &'static [f64],
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    /// Sum of observations in microseconds, kept integral for atomic updates.
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn new(name: &'static str, help: &'static str, bounds: &'static [f64]) -> Self {
+        Self {
+            name,
+            help,
+            bounds,
+            buckets: bounds.iter().map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bound, bucket) in self.bounds.iter().zip(&self.buckets) {
+            if seconds <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn render(&self, output: &mut String) {
+        let _ = writeln!(output, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(output, "# TYPE {} histogram", self.name);
+        for (bound, bucket) in self.bounds.iter().zip(&self.buckets) {
+            let _ = writeln!(
+                output,
+                "{}_bucket{{le=\"{bound}\"}} {}",
+                self.name,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let count = self.count();
+        let _ = writeln!(output, "{}_bucket{{le=\"+Inf\"}} {count}", self.name);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(output, "{}_sum {sum}", self.name);
+        let _ = writeln!(output, "{}_count {count}", self.name);
+    }
+}
+
+/// Process-wide metrics registry.
+#[derive(Debug)]
+pub struct Metrics {
+    pub frames_processed: Counter,
+    pub pcm_queue_depth: Gauge,
+    pub pcm_frames_coalesced: Counter,
+    pub pcm_frames_dropped: Counter,
+    pub first_update_latency: Histogram,
+    pub partial_stabilization_latency: Histogram,
+    pub publish_failures: Counter,
+    pub clipboard_fallbacks: Counter,
+    pub strong_noise_activations: Counter,
+    pub strong_noise_warnings: Counter,
+    pub keystroke_suppressed_samples: Counter,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            frames_processed: Counter::new(
+                "flowwisper_frames_processed_total",
+                "PCM frames emitted by the audio pipeline.",
+            ),
+            pcm_queue_depth: Gauge::new(
+                "flowwisper_pcm_queue_depth",
+                "Frames queued for the most backlogged PCM subscriber.",
+            ),
+            pcm_frames_coalesced: Counter::new(
+                "flowwisper_pcm_frames_coalesced_total",
+                "PCM frames merged into a queued frame because a subscriber lagged.",
+            ),
+            pcm_frames_dropped: Counter::new(
+                "flowwisper_pcm_frames_dropped_total",
+                "PCM frames dropped because a lossy subscriber lagged.",
+            ),
+            first_update_latency: Histogram::new(
+                "flowwisper_first_update_latency_seconds",
+                "Time from session start to the first transcript update.",
+                LATENCY_BUCKETS,
+            ),
+            partial_stabilization_latency: Histogram::new(
+                "flowwisper_partial_stabilization_seconds",
+                "Time for a partial transcript token to stop changing.",
+                LATENCY_BUCKETS,
+            ),
+            publish_failures: Counter::new(
+                "flowwisper_publish_failures_total",
+                "Transcript publish attempts that failed.",
+            ),
+            clipboard_fallbacks: Counter::new(
+                "flowwisper_clipboard_fallbacks_total",
+                "Publishes degraded to the clipboard fallback.",
+            ),
+            strong_noise_activations: Counter::new(
+                "flowwisper_strong_noise_activations_total",
+                "Times the noise detector switched to the strong-noise profile.",
+            ),
+            strong_noise_warnings: Counter::new(
+                "flowwisper_strong_noise_warnings_total",
+                "Noise warnings raised under the strong-noise profile.",
+            ),
+            keystroke_suppressed_samples: Counter::new(
+                "flowwisper_keystroke_suppressed_samples_total",
+                "Samples attenuated or gated around reported keystrokes.",
+            ),
+        }
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        self.frames_processed.render(&mut output);
+        self.pcm_queue_depth.render(&mut output);
+        self.pcm_frames_coalesced.render(&mut output);
+        self.pcm_frames_dropped.render(&mut output);
+        self.first_update_latency.render(&mut output);
+        self.partial_stabilization_latency.render(&mut output);
+        self.publish_failures.render(&mut output);
+        self.clipboard_fallbacks.render(&mut output);
+        self.strong_noise_activations.render(&mut output);
+        self.strong_noise_warnings.render(&mut output);
+        self.keystroke_suppressed_samples.render(&mut output);
+        output
+    }
+}
+
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// 读取 `FLOWWISPER_METRICS_ADDR`；未配置时不启动监听。
+pub fn configured_addr() -> Option<SocketAddr> {
+    let value = env::var(METRICS_ADDR_ENV).ok()?;
+    match value.trim().parse() {
+        Ok(addr) => Some(addr),
+        Err(err) => {
+            warn!(
+                target: "telemetry::metrics",
+                %err,
+                value = %value,
+                "ignoring invalid metrics listen address"
+            );
+            None
+        }
+    }
+}
+
+/// Bind `addr` and serve `GET /metrics` until the task is aborted. Returns
+/// the bound address, which differs from `addr` when port 0 is requested.
+pub async fn serve(addr: SocketAddr) -> Result<(SocketAddr, JoinHandle<()>)> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|err| anyhow!("failed to bind metrics listener on {addr}: {err}"))?;
+    let local_addr = listener.local_addr().unwrap_or(addr);
+    info!(target: "telemetry::metrics", addr = %local_addr, "metrics endpoint listening");
+
+    let handle = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(stream).await {
+                            warn!(target: "telemetry::metrics", %err, "metrics request failed");
+                        }
+                    });
+                }
+                Err(err) => {
+                    warn!(target: "telemetry::metrics", %err, "metrics accept failed");
+                }
+            }
+        }
+    });
+    Ok((local_addr, handle))
+}
+
+async fn handle_connection(mut stream: TcpStream) -> Result<()> {
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut parts = request.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = metrics().render();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_renders_cumulative_buckets() {
+        let histogram = Histogram::new("test_latency_seconds", "test", &[0.1, 0.5]);
+        histogram.observe(Duration::from_millis(50));
+        histogram.observe(Duration::from_millis(300));
+        histogram.observe(Duration::from_secs(2));
+
+        let mut output = String::new();
+        histogram.render(&mut output);
+        assert!(output.contains("test_latency_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(output.contains("test_latency_seconds_bucket{le=\"0.5\"} 2\n"));
+        assert!(output.contains("test_latency_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(output.contains("test_latency_seconds_sum 2.35\n"));
+        assert!(output.contains("test_latency_seconds_count 3\n"));
+    }
+
+    #[tokio::test]
+    async fn serves_prometheus_text_over_http() {
+        metrics().publish_failures.inc();
+        let (addr, handle) = serve("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        handle.abort();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE flowwisper_publish_failures_total counter"));
+        assert!(response.contains("flowwisper_first_update_latency_seconds_count"));
+    }
+}
@@ -1,6 +1,11 @@
 //! 观测性初始化脚手架。
 
 pub mod events;
+pub mod metrics;
+#[cfg(feature = "otel")]
+mod otel;
+pub mod sealed;
+pub mod uploader;
 
 use std::env;
 use std::fs;
@@ -10,8 +15,12 @@ use std::sync::OnceLock;
 use std::time::{Duration, SystemTime};
 
 use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
-use tracing_subscriber::{fmt, layer::SubscriberExt, EnvFilter, Registry};
+use tracing_subscriber::{fmt, layer::SubscriberExt, EnvFilter, Layer, Registry};
 
+use crate::session::workspace;
+use sealed::{SealedLineWriter, TelemetryLogKey};
+
+/// 相对当前配置档数据目录的日志目录。
 const LOG_DIR: &str = "logs/telemetry";
 const LOG_DIR_ENV: &str = "FLOWWISPER_TELEMETRY_DIR";
 const TELEMETRY_PREFIX: &str = "dual-view.json";
@@ -34,6 +43,7 @@ pub fn init_tracing() {
                     .with_target(true)
                     .with_writer(writer);
                 let subscriber = Registry::default()
+                    .with(otel_layer())
                     .with(env_filter.clone())
                     .with(fmt::layer().with_target(false))
                     .with(file_layer);
@@ -44,6 +54,7 @@ pub fn init_tracing() {
             Err(err) => {
                 eprintln!("failed to initialize telemetry file logging: {err}");
                 let subscriber = Registry::default()
+                    .with(otel_layer())
                     .with(env_filter)
                     .with(fmt::layer().with_target(false));
 
@@ -54,32 +65,134 @@ pub fn init_tracing() {
     });
 }
 
+/// OTLP 导出层，仅在启用 `otel` 特性且配置了端点时生效。
+fn otel_layer() -> Option<Box<dyn Layer<Registry> + Send + Sync>> {
+    #[cfg(feature = "otel")]
+    {
+        otel::layer()
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        None
+    }
+}
+
 fn build_file_writer() -> io::Result<(NonBlocking, WorkerGuard)> {
-    let log_dir = telemetry_dir();
+    let log_dir = telemetry_dir()?;
     fs::create_dir_all(&log_dir)?;
 
     if let Err(err) = prune_old_logs(&log_dir, RETENTION_DAYS) {
         eprintln!("failed to prune telemetry logs: {err}");
     }
 
+    // 取不到密钥时不退回明文，整体放弃文件日志。
+    let key = TelemetryLogKey::for_active_profile().map_err(io::Error::other)?;
     let appender = tracing_appender::rolling::daily(log_dir, TELEMETRY_PREFIX);
-    Ok(tracing_appender::non_blocking(appender))
+    Ok(tracing_appender::non_blocking(SealedLineWriter::new(
+        appender, key,
+    )))
+}
+
+/// `FLOWWISPER_TELEMETRY_DIR` 优先，否则位于当前配置档的数据目录下。
+fn telemetry_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = env::var(LOG_DIR_ENV).ok().filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    workspace::active_data_dir()
+        .map(|dir| dir.join(LOG_DIR))
+        .map_err(io::Error::other)
+}
+
+/// 在遥测目录写入并删除一个探针文件，确认日志目录可写。
+pub fn probe_write() -> io::Result<PathBuf> {
+    let log_dir = telemetry_dir()?;
+    fs::create_dir_all(&log_dir)?;
+    let probe = log_dir.join(format!(".{TELEMETRY_PREFIX}.probe"));
+    fs::write(&probe, b"flowwisper telemetry probe")?;
+    fs::remove_file(&probe)?;
+    Ok(log_dir)
+}
+
+/// 最近 `max_age` 内修改过的遥测日志，按修改时间从新到旧排列；目录不存在时返回空。
+pub fn recent_log_files(max_age: Duration) -> io::Result<Vec<PathBuf>> {
+    recent_log_files_in(&telemetry_dir()?, max_age)
 }
 
-fn telemetry_dir() -> PathBuf {
-    env::var(LOG_DIR_ENV)
-        .ok()
-        .filter(|path| !path.is_empty())
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from(LOG_DIR))
+/// 同 [`recent_log_files`]，但从 `log_dir` 读取。
+pub fn recent_log_files_in(log_dir: &Path, max_age: Duration) -> io::Result<Vec<PathBuf>> {
+    if !log_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let threshold = SystemTime::now()
+        .checked_sub(max_age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(log_dir)?.flatten() {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(TELEMETRY_PREFIX)
+        {
+            continue;
+        }
+        let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) else {
+            continue;
+        };
+        if modified >= threshold {
+            files.push((modified, entry.path()));
+        }
+    }
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(files.into_iter().map(|(_, path)| path).collect())
 }
 
 pub fn flush_tracing() {
+    #[cfg(feature = "otel")]
+    otel::flush();
     if TELEMETRY_GUARD.get().is_some() {
         std::thread::sleep(Duration::from_millis(50));
     }
 }
 
+fn prune_old_logs(log_dir: &Path, retention_days: u64) -> io::Result<()> {
+    let retention = Duration::from_secs(retention_days.saturating_mul(24 * 60 * 60));
+    let threshold = SystemTime::now()
+        .checked_sub(retention)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    for entry in fs::read_dir(log_dir)? {
+        let entry = entry?;
+        let file_name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+
+        if !file_name.starts_with(TELEMETRY_PREFIX) {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let modified = match metadata.modified() {
+            Ok(modified) => modified,
+            Err(_) => continue,
+        };
+
+        if modified < threshold {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::events::{
@@ -93,6 +206,10 @@ mod tests {
     fn telemetry_logs_are_json_enveloped() {
         let temp_dir = tempfile::tempdir().expect("temp telemetry dir");
         env::set_var(LOG_DIR_ENV, temp_dir.path());
+        env::set_var(
+            sealed::TELEMETRY_KEY_ENV,
+            "telemetry-test-key-0123456789abcdef",
+        );
 
         // Try to initialize tracing, but don't fail if it's already initialized
         let _ = std::panic::catch_unwind(|| {
@@ -137,7 +254,15 @@ mod tests {
             std::thread::sleep(Duration::from_millis(50));
         };
 
-        let contents = fs::read_to_string(&log_path).expect("log contents readable");
+        let key = TelemetryLogKey::from_env()
+            .expect("valid key")
+            .expect("key set");
+        let sealed = fs::read(&log_path).expect("log contents readable");
+        let mut plaintext = Vec::new();
+        let decrypted =
+            sealed::decrypt_log(&key, sealed.as_slice(), &mut plaintext).expect("decrypt log");
+        assert!(decrypted > 0, "telemetry log lines must be sealed");
+        let contents = String::from_utf8(plaintext).expect("utf8 log");
 
         let mut saw_latency = false;
         let mut saw_revert = false;
@@ -162,13 +287,13 @@ mod tests {
                 .unwrap_or_default();
 
             match event {
-                EVENT_LATENCY => {
+                EVENT_LATENCY
                     // Only validate events with our specific test data
                     if fields.get("sentence_id").and_then(|v| v.as_u64()) == Some(7)
                         && fields.get("variant").and_then(|v| v.as_str()) == Some("polished")
                         && fields.get("source").and_then(|v| v.as_str()) == Some("local")
                         && fields.get("latency_ms").and_then(|v| v.as_u64()) == Some(1800)
-                    {
+                    => {
                         assert_eq!(
                             fields.get("is_primary").and_then(|v| v.as_bool()),
                             Some(true)
@@ -187,7 +312,6 @@ mod tests {
                         assert_eq!(payload_json["sentence_id"], 7);
                         saw_latency = true;
                     }
-                }
                 EVENT_REVERT => {
                     let payload = fields
                         .get("payload")
@@ -237,42 +361,3 @@ mod tests {
         assert!(saw_revert, "missing revert telemetry record with test data");
     }
 }
-
-fn prune_old_logs(log_dir: &Path, retention_days: u64) -> io::Result<()> {
-    let retention = Duration::from_secs(retention_days.saturating_mul(24 * 60 * 60));
-    let threshold = SystemTime::now()
-        .checked_sub(retention)
-        .unwrap_or(SystemTime::UNIX_EPOCH);
-
-    for entry in fs::read_dir(log_dir)? {
-        let entry = entry?;
-        let file_name = match entry.file_name().into_string() {
-            Ok(name) => name,
-            Err(_) => continue,
-        };
-
-        if !file_name.starts_with(TELEMETRY_PREFIX) {
-            continue;
-        }
-
-        let metadata = match entry.metadata() {
-            Ok(metadata) => metadata,
-            Err(_) => continue,
-        };
-
-        if !metadata.is_file() {
-            continue;
-        }
-
-        let modified = match metadata.modified() {
-            Ok(modified) => modified,
-            Err(_) => continue,
-        };
-
-        if modified < threshold {
-            let _ = fs::remove_file(entry.path());
-        }
-    }
-
-    Ok(())
-}
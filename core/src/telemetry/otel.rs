@@ -0,0 +1,61 @@
+//! 可选的 OTLP 导出器（`otel` 特性），将 tracing span 与双视图延迟事件发送到 Jaeger/Tempo。
+
+use std::env;
+use std::sync::OnceLock;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::{Layer, Registry};
+
+const OTLP_ENDPOINT_ENV: &str = "FLOWWISPER_OTLP_ENDPOINT";
+const STANDARD_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const SERVICE_NAME: &str = "flowwisper-core";
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Endpoint configured through `FLOWWISPER_OTLP_ENDPOINT`, falling back to the
+/// standard `OTEL_EXPORTER_OTLP_ENDPOINT`.
+fn configured_endpoint() -> Option<String> {
+    [OTLP_ENDPOINT_ENV, STANDARD_ENDPOINT_ENV]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Build the OpenTelemetry layer when an OTLP endpoint is configured.
+pub(crate) fn layer() -> Option<Box<dyn Layer<Registry> + Send + Sync>> {
+    let endpoint = configured_endpoint()?;
+    let exporter = match SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint.as_str())
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(err) => {
+            eprintln!("failed to initialize OTLP exporter for {endpoint}: {err}");
+            return None;
+        }
+    };
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+    let tracer = provider.tracer(SERVICE_NAME);
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    let _ = PROVIDER.set(provider);
+
+    Some(tracing_opentelemetry::layer().with_tracer(tracer).boxed())
+}
+
+/// Export any spans still buffered in the batch processor.
+pub(crate) fn flush() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(err) = provider.force_flush() {
+            eprintln!("failed to flush OTLP exporter: {err}");
+        }
+    }
+}
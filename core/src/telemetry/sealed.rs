@@ -0,0 +1,198 @@
+//! 遥测日志的静态加密：每行 JSON 单独封装为 AES-256-GCM 信封，支持排查时离线解密。
+//!
+//! 日志默认加密，密钥首次使用时生成并保存在当前配置档的密钥库中。
+
+use std::env;
+use std::io::{self, BufRead, Write};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::audio::{open_payload, seal_payload, AudioCacheKeys, SealedEnvelope};
+use crate::secrets::{self, SecretStore};
+use crate::session::workspace;
+
+/// 遥测日志密钥，至少 32 字节；设置后覆盖密钥库中的日志密钥。
+pub const TELEMETRY_KEY_ENV: &str = "FLOWWISPER_TELEMETRY_KEY";
+/// 密钥库中保存日志密钥的条目。
+pub const TELEMETRY_KEY_SECRET: &str = "telemetry.log_key";
+const LINE_AAD: &[u8] = b"flowwisper.telemetry.log.v1";
+
+/// 遥测日志加密密钥。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryLogKey(AudioCacheKeys);
+
+impl TelemetryLogKey {
+    pub fn derive(material: &[u8]) -> Result<Self> {
+        AudioCacheKeys::derive(material)
+            .map(Self)
+            .context("invalid telemetry log key")
+    }
+
+    /// 由 [`TELEMETRY_KEY_ENV`] 派生；未设置时返回 `None`。
+    pub fn from_env() -> Result<Option<Self>> {
+        match env::var(TELEMETRY_KEY_ENV) {
+            Ok(value) if !value.is_empty() => Self::derive(value.as_bytes()).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// [`TELEMETRY_KEY_ENV`] 优先，否则取 `store` 中的日志密钥，首次使用时生成并保存。
+    pub fn resolve(store: &dyn SecretStore) -> Result<Self> {
+        match Self::from_env()? {
+            Some(key) => Ok(key),
+            None => Self::from_store(store),
+        }
+    }
+
+    /// `store` 中的日志密钥，首次使用时生成并保存。
+    pub fn from_store(store: &dyn SecretStore) -> Result<Self> {
+        let material = match store.get(TELEMETRY_KEY_SECRET)? {
+            Some(encoded) => BASE64
+                .decode(encoded.trim())
+                .context("stored telemetry log key is corrupted")?,
+            None => {
+                let mut bytes = [0u8; 32];
+                SystemRandom::new()
+                    .fill(&mut bytes)
+                    .map_err(|_| anyhow!("failed to generate telemetry log key"))?;
+                store.set(TELEMETRY_KEY_SECRET, &BASE64.encode(bytes))?;
+                bytes.to_vec()
+            }
+        };
+        Self::derive(&material)
+    }
+
+    /// 当前配置档的日志密钥。
+    pub fn for_active_profile() -> Result<Self> {
+        let store = secrets::default_store(
+            &workspace::active_data_dir()?,
+            &workspace::active_profile()?,
+        )?;
+        Self::resolve(store.as_ref())
+    }
+
+    fn seal_line(&self, line: &[u8]) -> Result<Vec<u8>> {
+        let envelope = seal_payload(&self.0, LINE_AAD, line)?;
+        let mut sealed = serde_json::to_vec(&envelope)?;
+        sealed.push(b'\n');
+        Ok(sealed)
+    }
+
+    fn open_line(&self, envelope: &SealedEnvelope) -> Result<Vec<u8>> {
+        open_payload(&self.0, LINE_AAD, envelope)
+    }
+}
+
+/// 按行加密的写入器：缓存到换行符再整行封装，保证每行可以独立解密。
+pub struct SealedLineWriter<W> {
+    inner: W,
+    key: TelemetryLogKey,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> SealedLineWriter<W> {
+    pub fn new(inner: W, key: TelemetryLogKey) -> Self {
+        Self {
+            inner,
+            key,
+            pending: Vec::new(),
+        }
+    }
+
+    fn seal_pending_lines(&mut self) -> io::Result<()> {
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let sealed = self.key.seal_line(&line[..end]).map_err(io::Error::other)?;
+            self.inner.write_all(&sealed)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for SealedLineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        self.seal_pending_lines()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 把加密日志逐行解密写入 `output`；启用加密前写入的明文行原样保留。返回解密的行数。
+pub fn decrypt_log(
+    key: &TelemetryLogKey,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<usize> {
+    let mut decrypted = 0;
+    for (index, line) in input.lines().enumerate() {
+        let line = line.context("failed to read telemetry log")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<SealedEnvelope>(&line) {
+            Ok(envelope) => {
+                let plaintext = key.open_line(&envelope).with_context(|| {
+                    format!("failed to decrypt telemetry log line {}", index + 1)
+                })?;
+                output.write_all(&plaintext)?;
+                decrypted += 1;
+            }
+            Err(_) => output.write_all(line.as_bytes())?,
+        }
+        output.write_all(b"\n")?;
+    }
+    output.flush()?;
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::EncryptedFileStore;
+
+    #[test]
+    fn generates_and_keeps_a_key_in_the_secret_store() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store = EncryptedFileStore::open(dir.path()).expect("store");
+
+        let key = TelemetryLogKey::from_store(&store).expect("generated key");
+        assert!(store.get(TELEMETRY_KEY_SECRET).expect("read").is_some());
+        assert_eq!(
+            TelemetryLogKey::from_store(&store).expect("stored key"),
+            key
+        );
+    }
+
+    #[test]
+    fn sealed_lines_round_trip_and_reject_other_keys() {
+        let key = TelemetryLogKey::derive(&[5u8; 32]).expect("key");
+        let mut writer = SealedLineWriter::new(Vec::new(), key.clone());
+        writer
+            .write_all(b"{\"event\":\"one\"}\n{\"event\":")
+            .unwrap();
+        writer.write_all(b"\"two\"}\n").unwrap();
+        let sealed = writer.inner;
+        let text = String::from_utf8(sealed.clone()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(!text.contains("event"));
+
+        let mut input = b"{\"event\":\"plain\"}\n".to_vec();
+        input.extend_from_slice(&sealed);
+        let mut output = Vec::new();
+        assert_eq!(decrypt_log(&key, input.as_slice(), &mut output).unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"event\":\"plain\"}\n{\"event\":\"one\"}\n{\"event\":\"two\"}\n"
+        );
+
+        let other = TelemetryLogKey::derive(&[6u8; 32]).expect("key");
+        assert!(decrypt_log(&other, sealed.as_slice(), io::sink()).is_err());
+        assert!(TelemetryLogKey::derive(b"too short").is_err());
+    }
+}
@@ -0,0 +1,433 @@
+//! 遥测上传任务：从 SQLite 队列分批读取事件，通过 HTTPS 上报并标记已送达。
+
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::persistence::audit::{EgressChannel, EgressRecorder};
+use crate::persistence::sqlite::{QueuedTelemetry, SqlitePersistence, MAX_TELEMETRY_QUEUE};
+use crate::telemetry::events::record_telemetry_upload;
+
+pub const TELEMETRY_ENDPOINT_ENV: &str = "FLOWWISPER_TELEMETRY_ENDPOINT";
+
+const DEFAULT_BATCH_SIZE: usize = 50;
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_INITIAL_BACKOFF_SECS: u64 = 5;
+const DEFAULT_MAX_BACKOFF_SECS: u64 = 15 * 60;
+const DEFAULT_OFFLINE_QUEUE_CAP: i64 = 100;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Upload schedule and limits for [`TelemetryUploader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryUploadConfig {
+    /// HTTPS collector URL; `None` keeps the uploader in offline mode.
+    pub endpoint: Option<String>,
+    pub batch_size: usize,
+    pub interval: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Undelivered rows kept while offline; older rows are dropped first.
+    pub offline_queue_cap: i64,
+    pub request_timeout: Duration,
+}
+
+impl Default for TelemetryUploadConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            interval: Duration::from_secs(DEFAULT_INTERVAL_SECS),
+            initial_backoff: Duration::from_secs(DEFAULT_INITIAL_BACKOFF_SECS),
+            max_backoff: Duration::from_secs(DEFAULT_MAX_BACKOFF_SECS),
+            offline_queue_cap: DEFAULT_OFFLINE_QUEUE_CAP,
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl TelemetryUploadConfig {
+    /// 从 `FLOWWISPER_TELEMETRY_ENDPOINT` 读取上报地址，仅接受 HTTPS。
+    pub fn from_env() -> Self {
+        let endpoint = env::var(TELEMETRY_ENDPOINT_ENV)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .and_then(|value| {
+                if value.starts_with("https://") {
+                    Some(value)
+                } else {
+                    warn!(
+                        target: "telemetry_uploader",
+                        endpoint = %value,
+                        "ignoring non-HTTPS telemetry endpoint"
+                    );
+                    None
+                }
+            });
+        Self {
+            endpoint,
+            ..Self::default()
+        }
+    }
+}
+
+/// Request body posted to the collector.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryBatch {
+    pub sent_at_ms: i64,
+    pub events: Vec<QueuedTelemetry>,
+}
+
+/// Delivers one batch to the collector.
+pub trait TelemetryTransport: Send + Sync {
+    fn send(&self, endpoint: &str, batch: &TelemetryBatch) -> Result<()>;
+}
+
+/// JSON-over-HTTPS transport backed by `ureq`.
+pub struct HttpsTransport {
+    timeout: Duration,
+}
+
+impl HttpsTransport {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl TelemetryTransport for HttpsTransport {
+    fn send(&self, endpoint: &str, batch: &TelemetryBatch) -> Result<()> {
+        let body = serde_json::to_string(batch)
+            .map_err(|err| anyhow!("failed to encode telemetry batch: {err}"))?;
+        ureq::post(endpoint)
+            .timeout(self.timeout)
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+            .map_err(|err| anyhow!("telemetry upload failed: {err}"))?;
+        Ok(())
+    }
+}
+
+/// Exponential backoff between failed upload attempts.
+#[derive(Debug, Clone)]
+pub struct UploadBackoff {
+    initial: Duration,
+    max: Duration,
+    current: Option<Duration>,
+}
+
+impl UploadBackoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+            current: None,
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = match self.current {
+            Some(previous) => previous.saturating_mul(2).min(self.max),
+            None => self.initial,
+        };
+        self.current = Some(delay);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+/// Drains `telemetry_queue` in batches and posts them to the configured endpoint.
+#[derive(Clone)]
+pub struct TelemetryUploader {
+    sqlite: Arc<SqlitePersistence>,
+    config: TelemetryUploadConfig,
+    transport: Arc<dyn TelemetryTransport>,
+    offline: Arc<AtomicBool>,
+    /// 组织策略要求不上报遥测，优先于离线开关。
+    opted_out: Arc<AtomicBool>,
+    started: Arc<AtomicBool>,
+    egress: EgressRecorder,
+}
+
+impl TelemetryUploader {
+    pub fn new(sqlite: Arc<SqlitePersistence>, config: TelemetryUploadConfig) -> Self {
+        let transport = Arc::new(HttpsTransport::new(config.request_timeout));
+        Self::with_transport(sqlite, config, transport)
+    }
+
+    pub fn with_transport(
+        sqlite: Arc<SqlitePersistence>,
+        config: TelemetryUploadConfig,
+        transport: Arc<dyn TelemetryTransport>,
+    ) -> Self {
+        let uploader = Self {
+            sqlite,
+            config,
+            transport,
+            offline: Arc::new(AtomicBool::new(false)),
+            opted_out: Arc::new(AtomicBool::new(false)),
+            started: Arc::new(AtomicBool::new(false)),
+            egress: EgressRecorder::default(),
+        };
+        uploader.sync_queue_cap();
+        uploader
+    }
+
+    /// 每个上传批次都写入外发审计。
+    pub fn with_egress(mut self, egress: EgressRecorder) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    pub fn config(&self) -> &TelemetryUploadConfig {
+        &self.config
+    }
+
+    /// 离线模式下不上传，入队时即把未送达队列限制在 `offline_queue_cap`。
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+        self.sync_queue_cap();
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::SeqCst) || self.is_opted_out() || self.config.endpoint.is_none()
+    }
+
+    /// 退出遥测后不再上传，已排队的事件在下次处理时全部丢弃。
+    pub fn set_opted_out(&self, opted_out: bool) {
+        self.opted_out.store(opted_out, Ordering::SeqCst);
+        self.sync_queue_cap();
+    }
+
+    pub fn is_opted_out(&self) -> bool {
+        self.opted_out.load(Ordering::SeqCst)
+    }
+
+    /// 当前状态下队列允许保留的未送达条数。
+    fn queue_cap(&self) -> i64 {
+        if self.is_opted_out() {
+            0
+        } else if self.is_offline() {
+            self.config.offline_queue_cap
+        } else {
+            MAX_TELEMETRY_QUEUE
+        }
+    }
+
+    /// 把上限同步到入队路径，离线期间不依赖 `drain_once` 也不会超限。
+    fn sync_queue_cap(&self) {
+        self.sqlite.set_telemetry_queue_cap(self.queue_cap());
+    }
+
+    /// Upload every pending batch once. Blocking; returns the number of rows
+    /// delivered and removed from the queue.
+    pub fn drain_once(&self) -> Result<usize> {
+        let Some(endpoint) = self
+            .config
+            .endpoint
+            .as_deref()
+            .filter(|_| !self.is_offline())
+        else {
+            self.sqlite.cap_pending_telemetry(self.queue_cap())?;
+            return Ok(0);
+        };
+
+        let mut delivered = 0;
+        loop {
+            let events = self
+                .sqlite
+                .pending_telemetry(self.config.batch_size.max(1))?;
+            if events.is_empty() {
+                break;
+            }
+            let ids: Vec<i64> = events.iter().map(|event| event.id).collect();
+            let batch = TelemetryBatch {
+                sent_at_ms: now_ms(),
+                events,
+            };
+            if self.egress.is_enabled() {
+                let payload = serde_json::to_vec(&batch)?;
+                self.egress
+                    .record(EgressChannel::TelemetryUpload, endpoint, &payload);
+            }
+            self.transport.send(endpoint, &batch)?;
+            delivered += self.sqlite.remove_delivered_telemetry(&ids)?;
+        }
+        Ok(delivered)
+    }
+
+    /// Start the background upload loop; later calls are no-ops.
+    pub fn spawn(&self) -> Option<JoinHandle<()>> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let uploader = self.clone();
+        Some(tokio::spawn(async move {
+            let mut backoff =
+                UploadBackoff::new(uploader.config.initial_backoff, uploader.config.max_backoff);
+            loop {
+                let worker = uploader.clone();
+                let result = tokio::task::spawn_blocking(move || worker.drain_once())
+                    .await
+                    .map_err(|err| anyhow!("telemetry upload task failed: {err}"))
+                    .and_then(|result| result);
+                let delay = match result {
+                    Ok(delivered) => {
+                        backoff.reset();
+                        if delivered > 0 {
+                            record_telemetry_upload(delivered, None);
+                        }
+                        uploader.config.interval
+                    }
+                    Err(err) => {
+                        record_telemetry_upload(0, Some(&err));
+                        backoff.next_delay()
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        }))
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sqlite::SqliteConfig;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        failures_left: Mutex<usize>,
+        batches: Mutex<Vec<usize>>,
+    }
+
+    impl TelemetryTransport for RecordingTransport {
+        fn send(&self, _endpoint: &str, batch: &TelemetryBatch) -> Result<()> {
+            let mut failures = self.failures_left.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(anyhow!("collector unavailable"));
+            }
+            self.batches.lock().unwrap().push(batch.events.len());
+            Ok(())
+        }
+    }
+
+    fn queue(sqlite: &SqlitePersistence, count: usize) {
+        for idx in 0..count {
+            sqlite
+                .enqueue_telemetry("session", "test_event", json!({ "idx": idx }))
+                .unwrap();
+        }
+    }
+
+    fn config() -> TelemetryUploadConfig {
+        TelemetryUploadConfig {
+            endpoint: Some("https://collector.example/v1/events".into()),
+            batch_size: 4,
+            offline_queue_cap: 3,
+            ..TelemetryUploadConfig::default()
+        }
+    }
+
+    #[test]
+    fn uploads_in_batches_and_retries_undelivered_rows() {
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        queue(&sqlite, 10);
+        let transport = Arc::new(RecordingTransport {
+            failures_left: Mutex::new(1),
+            ..RecordingTransport::default()
+        });
+        let uploader =
+            TelemetryUploader::with_transport(sqlite.clone(), config(), transport.clone());
+
+        assert!(uploader.drain_once().is_err());
+        assert_eq!(sqlite.pending_telemetry(100).unwrap().len(), 10);
+
+        assert_eq!(uploader.drain_once().unwrap(), 10);
+        assert_eq!(*transport.batches.lock().unwrap(), vec![4, 4, 2]);
+        assert!(sqlite.pending_telemetry(100).unwrap().is_empty());
+        assert_eq!(stored_rows(&sqlite), 0);
+    }
+
+    fn stored_rows(sqlite: &SqlitePersistence) -> i64 {
+        sqlite
+            .connection()
+            .unwrap()
+            .query_row("SELECT count(*) FROM telemetry_queue", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn offline_cap_applies_on_enqueue() {
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        let transport = Arc::new(RecordingTransport::default());
+        let uploader = TelemetryUploader::with_transport(sqlite.clone(), config(), transport);
+        uploader.set_offline(true);
+
+        queue(&sqlite, 8);
+        let pending = sqlite.pending_telemetry(100).unwrap();
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending[0].payload["idx"], 5);
+
+        // 退出遥测后新事件不再入队。
+        uploader.set_opted_out(true);
+        queue(&sqlite, 2);
+        assert_eq!(stored_rows(&sqlite), 3);
+
+        uploader.set_opted_out(false);
+        uploader.set_offline(false);
+        queue(&sqlite, 5);
+        assert_eq!(stored_rows(&sqlite), 8);
+    }
+
+    #[test]
+    fn offline_mode_caps_pending_queue() {
+        let sqlite = Arc::new(SqlitePersistence::bootstrap(SqliteConfig::memory()).unwrap());
+        queue(&sqlite, 8);
+        let transport = Arc::new(RecordingTransport::default());
+        let uploader =
+            TelemetryUploader::with_transport(sqlite.clone(), config(), transport.clone());
+        uploader.set_offline(true);
+
+        assert_eq!(uploader.drain_once().unwrap(), 0);
+        let pending = sqlite.pending_telemetry(100).unwrap();
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending[0].payload["idx"], 5);
+        assert!(transport.batches.lock().unwrap().is_empty());
+
+        // 退出遥测时即使恢复在线也不上传，并清空队列。
+        uploader.set_offline(false);
+        uploader.set_opted_out(true);
+        assert_eq!(uploader.drain_once().unwrap(), 0);
+        assert!(sqlite.pending_telemetry(100).unwrap().is_empty());
+        assert!(transport.batches.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut backoff = UploadBackoff::new(Duration::from_secs(5), Duration::from_secs(30));
+        let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![5, 10, 20, 30, 30]);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Simulated clock backed by tokio's paused time.
+///
+/// Once paused, `sleep`, `timeout` and [`tokio::time::Instant`] only move when
+/// the clock is advanced explicitly or the runtime has nothing left to do but
+/// wait on a timer, in which case it jumps straight to the next deadline.
+/// Requires a current-thread runtime.
+#[derive(Debug, Clone, Copy)]
+pub struct SimClock {
+    origin: Instant,
+}
+
+impl SimClock {
+    pub fn pause() -> Self {
+        tokio::time::pause();
+        Self {
+            origin: Instant::now(),
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Simulated time since the clock was paused.
+    pub fn elapsed(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    /// Move the clock forward, firing every timer that falls due on the way.
+    pub async fn advance(&self, by: Duration) {
+        tokio::time::advance(by).await;
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tokio::time::sleep;
+
+use crate::orchestrator::SpeechEngine;
+
+/// One scripted engine call: wait `latency`, then return `result`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptedStep {
+    pub latency: Duration,
+    pub result: std::result::Result<String, String>,
+}
+
+impl ScriptedStep {
+    pub fn ok(text: impl Into<String>, latency: Duration) -> Self {
+        Self {
+            latency,
+            result: Ok(text.into()),
+        }
+    }
+
+    pub fn err(message: impl Into<String>, latency: Duration) -> Self {
+        Self {
+            latency,
+            result: Err(message.into()),
+        }
+    }
+}
+
+/// Speech engine that replays a script of latencies and results. Latencies are
+/// tokio sleeps, so under a [`SimClock`](super::SimClock) they cost no real time.
+/// Calls past the end of the script return an empty transcript immediately.
+#[derive(Debug, Default)]
+pub struct ScriptedEngine {
+    steps: Mutex<VecDeque<ScriptedStep>>,
+    calls: AtomicUsize,
+}
+
+impl ScriptedEngine {
+    pub fn new(steps: Vec<ScriptedStep>) -> Self {
+        Self {
+            steps: Mutex::new(steps.into()),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of transcription calls made so far.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.steps
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+}
+
+#[async_trait]
+impl SpeechEngine for ScriptedEngine {
+    async fn transcribe(&self, _frame: &[f32]) -> Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let step = self
+            .steps
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop_front();
+        let Some(step) = step else {
+            return Ok(String::new());
+        };
+        sleep(step.latency).await;
+        step.result.map_err(|message| anyhow!(message))
+    }
+}
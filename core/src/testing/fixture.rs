@@ -0,0 +1,119 @@
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::time::{sleep_until, Instant};
+
+use crate::audio::{decode_audio_file, decode_wav, AudioPipeline, DownmixPolicy, SAMPLE_RATE_HZ};
+use crate::orchestrator::RealtimeSessionHandle;
+
+const DEFAULT_FRAME: Duration = Duration::from_millis(100);
+
+/// Scripted PCM input: engine-rate mono samples split into fixed-length frames
+/// and fed at a configurable real-time factor.
+#[derive(Debug, Clone)]
+pub struct PcmFixture {
+    samples: Vec<f32>,
+    frame_duration: Duration,
+}
+
+impl PcmFixture {
+    /// `samples` must already be mono at the engine rate.
+    pub fn from_samples(samples: Vec<f32>) -> Self {
+        Self {
+            samples,
+            frame_duration: DEFAULT_FRAME,
+        }
+    }
+
+    /// A constant-amplitude signal, handy for driving the VAD without a file.
+    pub fn tone(amplitude: f32, duration: Duration) -> Self {
+        let len = (duration.as_secs_f64() * SAMPLE_RATE_HZ as f64).round() as usize;
+        Self::from_samples(vec![amplitude; len])
+    }
+
+    /// Load a WAV file, folding it to mono and resampling to the engine rate.
+    pub fn load(path: &Path) -> Result<Self> {
+        let audio = decode_audio_file(path)?;
+        Ok(Self::from_samples(
+            audio.to_engine_mono(DownmixPolicy::default())?,
+        ))
+    }
+
+    pub fn from_wav_bytes(bytes: &[u8]) -> Result<Self> {
+        let audio = decode_wav(bytes)?;
+        Ok(Self::from_samples(
+            audio.to_engine_mono(DownmixPolicy::default())?,
+        ))
+    }
+
+    pub fn with_frame_duration(mut self, frame_duration: Duration) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / SAMPLE_RATE_HZ as f64)
+    }
+
+    /// The fixture cut into frames; the last frame may be shorter.
+    pub fn frames(&self) -> Vec<Vec<f32>> {
+        let frame_len =
+            ((self.frame_duration.as_secs_f64() * SAMPLE_RATE_HZ as f64).round() as usize).max(1);
+        self.samples
+            .chunks(frame_len)
+            .map(<[f32]>::to_vec)
+            .collect()
+    }
+
+    /// Feed every frame to `sink`, pacing them so the audio plays at
+    /// `realtime_factor` times real time (2.0 plays twice as fast). A factor of
+    /// zero or less feeds all frames back to back. Each frame is due at the
+    /// fixture's start plus the audio preceding it, so pacing does not drift.
+    /// Returns the number of frames fed.
+    pub async fn play<F, Fut>(&self, realtime_factor: f64, mut sink: F) -> Result<usize>
+    where
+        F: FnMut(Vec<f32>) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let started = Instant::now();
+        let mut played = Duration::ZERO;
+        let mut count = 0;
+        for frame in self.frames() {
+            if realtime_factor > 0.0 {
+                let due = started + played.div_f64(realtime_factor);
+                if due > Instant::now() {
+                    sleep_until(due).await;
+                }
+            }
+            played += Duration::from_secs_f64(frame.len() as f64 / SAMPLE_RATE_HZ as f64);
+            sink(frame).await?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub async fn play_into_pipeline(
+        &self,
+        pipeline: &AudioPipeline,
+        realtime_factor: f64,
+    ) -> Result<usize> {
+        self.play(realtime_factor, |frame| pipeline.push_pcm_frame(frame))
+            .await
+    }
+
+    pub async fn play_into_session(
+        &self,
+        session: &RealtimeSessionHandle,
+        realtime_factor: f64,
+    ) -> Result<usize> {
+        self.play(realtime_factor, |frame| async move {
+            session
+                .push_frame(frame)
+                .await
+                .map_err(|_| anyhow!("realtime session closed"))
+        })
+        .await
+    }
+}
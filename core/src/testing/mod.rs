@@ -0,0 +1,143 @@
+//! 实时管线的确定性仿真：暂停 tokio 时钟的模拟时钟、按倍速回放的 PCM 夹具与按脚本返回
+//! 结果和延迟的引擎，使 SLA 监测、节奏降级与故障切换的测试不依赖真实等待。
+//! 单元测试中直接可用；下游 crate 需启用 `testing` 特性。
+
+mod clock;
+mod engine;
+mod fixture;
+
+pub use clock::SimClock;
+pub use engine::{ScriptedEngine, ScriptedStep};
+pub use fixture::PcmFixture;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::RecordedAudio;
+    use crate::orchestrator::{
+        EngineConfig, EngineOrchestrator, NoticeLevel, RealtimeSessionConfig, TranscriptSource,
+        TranscriptionUpdate, UpdatePayload,
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+
+    async fn next_update(rx: &mut mpsc::Receiver<TranscriptionUpdate>) -> TranscriptionUpdate {
+        // 模拟时间下的超时，不会真实等待。
+        timeout(Duration::from_secs(30), rx.recv())
+            .await
+            .expect("update timed out")
+            .expect("session closed")
+    }
+
+    #[tokio::test]
+    async fn replays_wav_fixture_at_realtime_factor() {
+        let clock = SimClock::pause();
+        let wav = RecordedAudio {
+            session_id: "fixture".into(),
+            sample_rate_hz: 16_000,
+            samples: vec![1_000; 4_000],
+        }
+        .to_wav_bytes();
+        let fixture = PcmFixture::from_wav_bytes(&wav).unwrap();
+        assert_eq!(fixture.duration(), Duration::from_millis(250));
+        let lengths: Vec<usize> = fixture.frames().iter().map(Vec::len).collect();
+        assert_eq!(lengths, [1_600, 1_600, 800]);
+
+        let mut due = Vec::new();
+        let played = fixture
+            .play(2.0, |_| {
+                due.push(clock.elapsed());
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(played, 3);
+        // 定时器以毫秒为刻度，到期时间最多向后取整 1ms。
+        for (actual, expected) in due.iter().zip([0, 50, 100]) {
+            let expected = Duration::from_millis(expected);
+            assert!(*actual >= expected && *actual <= expected + Duration::from_millis(1));
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_engine_trips_first_update_deadline() {
+        let clock = SimClock::pause();
+        let engine = Arc::new(ScriptedEngine::new(vec![ScriptedStep::ok(
+            "hello there.",
+            Duration::from_secs(2),
+        )]));
+        let orchestrator = EngineOrchestrator::with_engine(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            engine.clone(),
+        );
+        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
+            enable_polisher: false,
+            first_update_deadline: Duration::from_millis(400),
+            ..RealtimeSessionConfig::default()
+        });
+        PcmFixture::tone(0.4, Duration::from_millis(100))
+            .play_into_session(&session, 1.0)
+            .await
+            .unwrap();
+
+        let notice = next_update(&mut rx).await;
+        match &notice.payload {
+            UpdatePayload::Notice(notice) => assert_eq!(notice.level, NoticeLevel::Warn),
+            other => panic!("expected deadline notice, got {other:?}"),
+        }
+        // 监测按 25ms 轮询，模拟时钟下延迟落在确定的区间内。
+        assert!(notice.latency >= Duration::from_millis(400));
+        assert!(notice.latency <= Duration::from_millis(450));
+
+        let transcript = loop {
+            if let UpdatePayload::Transcript(payload) = next_update(&mut rx).await.payload {
+                break payload;
+            }
+        };
+        assert_eq!(transcript.text, "hello there.");
+        assert!(clock.elapsed() >= Duration::from_secs(2));
+        assert_eq!(engine.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn local_failure_fails_over_to_cloud() {
+        let clock = SimClock::pause();
+        let local = Arc::new(ScriptedEngine::new(vec![ScriptedStep::err(
+            "decoder crashed",
+            Duration::from_millis(50),
+        )]));
+        let cloud = Arc::new(ScriptedEngine::new(vec![ScriptedStep::ok(
+            "from the cloud.",
+            Duration::from_millis(300),
+        )]));
+        let orchestrator = EngineOrchestrator::with_engines(
+            EngineConfig {
+                prefer_cloud: false,
+            },
+            local.clone(),
+            Some(cloud.clone()),
+        );
+        let (session, mut rx) = orchestrator.start_realtime_session(RealtimeSessionConfig {
+            enable_polisher: false,
+            ..RealtimeSessionConfig::default()
+        });
+        PcmFixture::tone(0.4, Duration::from_millis(100))
+            .play_into_session(&session, 1.0)
+            .await
+            .unwrap();
+
+        let transcript = loop {
+            if let UpdatePayload::Transcript(payload) = next_update(&mut rx).await.payload {
+                break payload;
+            }
+        };
+        assert_eq!(transcript.source, TranscriptSource::Cloud);
+        assert_eq!(transcript.text, "from the cloud.");
+        assert_eq!((local.calls(), cloud.calls()), (1, 1));
+        assert!(clock.elapsed() >= Duration::from_millis(300));
+    }
+}